@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use polymarket_hft_agent::markets::ConditionId;
+use polymarket_hft_agent::polymarket::contracts::{derive_asset_ids, derive_asset_ids_batch};
+use polymarket_hft_agent::polymarket::{DerivedAssetCache, PositionCollateral};
+
+/// Market storm sizes: conditions derived per burst
+const BURSTS: [usize; 3] = [1, 64, 512];
+
+fn condition_ids(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("0x{:064x}", i + 1)).collect()
+}
+
+fn benchmark_derivation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("asset_derivation");
+
+    for burst in BURSTS {
+        let ids = condition_ids(burst);
+        let refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        group.throughput(Throughput::Elements(burst as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("one_at_a_time", burst),
+            &refs,
+            |b, refs| {
+                b.iter(|| {
+                    for id in refs {
+                        black_box(derive_asset_ids(id).unwrap());
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("batch", burst), &refs, |b, refs| {
+            b.iter(|| black_box(derive_asset_ids_batch(refs).unwrap()))
+        });
+
+        // Repeats of conditions already derived (replayed events, restarts)
+        let cache = DerivedAssetCache::new(PositionCollateral::UsdcE).unwrap();
+        let parsed: Vec<ConditionId> = ids
+            .iter()
+            .map(|id| ConditionId::parse(id).unwrap())
+            .collect();
+        cache.derive_all(&parsed);
+        group.bench_with_input(BenchmarkId::new("cached", burst), &parsed, |b, parsed| {
+            b.iter(|| {
+                for id in parsed {
+                    black_box(cache.get_or_derive(id));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_derivation);
+criterion_main!(benches);
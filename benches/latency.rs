@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use polymarket_hft_agent::config::ArbitrageConfig;
+use polymarket_hft_agent::config::{ArbitrageConfig, SizingMode};
 use polymarket_hft_agent::polymarket::{MarketData, OrderBook, OrderLevel};
 use polymarket_hft_agent::strategies::arbitrage::ArbitrageStrategy;
 use polymarket_hft_agent::strategies::position_sizing::PositionSizer;
@@ -31,46 +31,36 @@ fn create_mock_orderbook(depth: usize) -> OrderBook {
 }
 
 fn benchmark_arbitrage_strategy(c: &mut Criterion) {
-    let config = ArbitrageConfig {
-        min_edge_bps: 200,
-        max_position_size_usd: 10.0,
-        use_dynamic_sizing: false,
-        kelly_fraction: 0.25,
-        min_position_pct: 0.01,
-        max_position_pct: 0.10,
-    };
-    let strategy = ArbitrageStrategy::new(config);
+    let config = ArbitrageConfig::default()
+        .enabled(true)
+        .min_edge_bps(200)
+        .max_position_size_usd(10.0)
+        .sizing_mode(SizingMode::Fixed)
+        .kelly_fraction(0.25)
+        .min_position_pct(0.01)
+        .max_position_pct(0.10);
+    let strategy = ArbitrageStrategy::new(config, 1000.0);
 
     // Case 1: No Opportunity
     let market_no_arb = MarketData {
-        id: "market_no".to_string(),
+        id: "market_no".into(),
         question: "No Arb Market".to_string(),
-        end_date: None,
         volume: 10000.0,
         liquidity: 5000.0,
         yes_price: 0.55,
         no_price: 0.55,
-        description: None,
-        order_book_imbalance: 0.0,
-        best_bid: 0.0,
-        best_ask: 0.0,
-        asset_ids: vec![],
+        ..Default::default()
     };
 
     // Case 2: Profitable Opportunity (0.4 + 0.4 = 0.8 < 1.0)
     let market_arb = MarketData {
-        id: "market_arb".to_string(),
+        id: "market_arb".into(),
         question: "Arb Market".to_string(),
-        end_date: None,
         volume: 10000.0,
         liquidity: 5000.0,
         yes_price: 0.40,
         no_price: 0.40,
-        description: None,
-        order_book_imbalance: 0.0,
-        best_bid: 0.0,
-        best_ask: 0.0,
-        asset_ids: vec![],
+        ..Default::default()
     };
 
     let mut group = c.benchmark_group("arbitrage_strategy");
@@ -91,15 +81,15 @@ fn benchmark_arbitrage_strategy(c: &mut Criterion) {
 }
 
 fn benchmark_orderbook_analysis(c: &mut Criterion) {
-    let config = ArbitrageConfig {
-        min_edge_bps: 200,
-        max_position_size_usd: 10.0,
-        use_dynamic_sizing: false,
-        kelly_fraction: 0.25,
-        min_position_pct: 0.01,
-        max_position_pct: 0.10,
-    };
-    let strategy = ArbitrageStrategy::new(config);
+    let config = ArbitrageConfig::default()
+        .enabled(true)
+        .min_edge_bps(200)
+        .max_position_size_usd(10.0)
+        .sizing_mode(SizingMode::Fixed)
+        .kelly_fraction(0.25)
+        .min_position_pct(0.01)
+        .max_position_pct(0.10);
+    let strategy = ArbitrageStrategy::new(config, 1000.0);
 
     let mut group = c.benchmark_group("orderbook_analysis");
 
@@ -159,40 +149,35 @@ fn benchmark_position_sizing(c: &mut Criterion) {
 
 fn benchmark_full_pipeline(c: &mut Criterion) {
     // Test with dynamic sizing enabled
-    let config_dynamic = ArbitrageConfig {
-        min_edge_bps: 200,
-        max_position_size_usd: 10.0,
-        use_dynamic_sizing: true,
-        kelly_fraction: 0.25,
-        min_position_pct: 0.01,
-        max_position_pct: 0.10,
-    };
-    let strategy_dynamic = ArbitrageStrategy::new(config_dynamic);
+    let config_dynamic = ArbitrageConfig::default()
+        .enabled(true)
+        .min_edge_bps(200)
+        .max_position_size_usd(10.0)
+        .sizing_mode(SizingMode::Kelly)
+        .kelly_fraction(0.25)
+        .min_position_pct(0.01)
+        .max_position_pct(0.10);
+    let strategy_dynamic = ArbitrageStrategy::new(config_dynamic, 1000.0);
 
     // Test with dynamic sizing disabled
-    let config_fixed = ArbitrageConfig {
-        min_edge_bps: 200,
-        max_position_size_usd: 10.0,
-        use_dynamic_sizing: false,
-        kelly_fraction: 0.25,
-        min_position_pct: 0.01,
-        max_position_pct: 0.10,
-    };
-    let strategy_fixed = ArbitrageStrategy::new(config_fixed);
+    let config_fixed = ArbitrageConfig::default()
+        .enabled(true)
+        .min_edge_bps(200)
+        .max_position_size_usd(10.0)
+        .sizing_mode(SizingMode::Fixed)
+        .kelly_fraction(0.25)
+        .min_position_pct(0.01)
+        .max_position_pct(0.10);
+    let strategy_fixed = ArbitrageStrategy::new(config_fixed, 1000.0);
 
     let market_arb = MarketData {
-        id: "market_arb".to_string(),
+        id: "market_arb".into(),
         question: "Arb Market".to_string(),
-        end_date: None,
         volume: 10000.0,
         liquidity: 5000.0,
         yes_price: 0.40,
         no_price: 0.40,
-        description: None,
-        order_book_imbalance: 0.0,
-        best_bid: 0.0,
-        best_ask: 0.0,
-        asset_ids: vec![],
+        ..Default::default()
     };
 
     let mut group = c.benchmark_group("full_pipeline");
@@ -0,0 +1,199 @@
+//! End-to-end tick -> decision latency through the Sniper's book update path:
+//! asset lookup, registry / suspension / latency state updates, then the arbitrage decision
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use polymarket_hft_agent::alloc_profile;
+use polymarket_hft_agent::analytics::PnLTracker;
+use polymarket_hft_agent::clock;
+use polymarket_hft_agent::config::Config;
+use polymarket_hft_agent::polymarket::ws::{OrderbookUpdate, PriceLevel};
+use polymarket_hft_agent::polymarket::MarketData;
+use polymarket_hft_agent::sniper::Sniper;
+use polymarket_hft_agent::strategies::arbitrage::TradeAction;
+
+// `cargo bench --bench tick_to_decision --features alloc-profiling` adds per-region
+// allocation counts for the percentile run
+#[cfg(feature = "alloc-profiling")]
+#[global_allocator]
+static GLOBAL: alloc_profile::CountingAlloc<std::alloc::System> =
+    alloc_profile::CountingAlloc(std::alloc::System);
+
+const MARKETS: usize = 500;
+
+/// Simulation-mode Sniper with `MARKETS` registered markets and no network connections
+fn sniper(runtime: &tokio::runtime::Runtime) -> Sniper {
+    let mut config = Config::from_env().expect("config");
+    config.agent.simulation_mode = true;
+    config.agent.data_dir = std::env::temp_dir()
+        .join(format!("tick-bench-{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .into_owned();
+    config.agent.decision_log_mode = "off".to_string();
+    config.polygon_ws_rpc = None;
+    config.ctf_contract_address = None;
+    config.flashbots.enabled = false;
+    // Never write into the operator's database
+    config.storage.backend = "none".to_string();
+    config.arbitrage.enabled = true;
+    config.arbitrage.min_edge_bps = 20;
+
+    let pnl_tracker = Arc::new(Mutex::new(PnLTracker::new(1000.0)));
+    let sniper = runtime
+        .block_on(Sniper::new(config, pnl_tracker))
+        .expect("sniper");
+
+    for i in 0..MARKETS {
+        let (no_id, yes_id) = asset_ids(i);
+        sniper.registry().insert(MarketData {
+            id: market_id(i).into(),
+            question: format!("Will benchmark market {} resolve YES?", i),
+            volume: 100_000.0,
+            liquidity: 50_000.0,
+            yes_price: 0.50,
+            no_price: 0.52,
+            volume_24h: 10_000.0,
+            asset_ids: vec![no_id.clone(), yes_id.clone()],
+            ..Default::default()
+        });
+        sniper.registry().map_assets(&market_id(i), &no_id, &yes_id);
+    }
+    sniper
+}
+
+fn market_id(i: usize) -> String {
+    format!("0xbench{:060}", i)
+}
+
+fn asset_ids(i: usize) -> (String, String) {
+    (format!("{}0", 10_000 + i), format!("{}1", 10_000 + i))
+}
+
+/// Two-sided book update for one outcome of market `i`
+fn update(i: usize, yes: bool, best_ask: f64) -> OrderbookUpdate {
+    let (no_id, yes_id) = asset_ids(i);
+    let level = |price: f64| PriceLevel {
+        price: format!("{:.2}", price),
+        size: "250".to_string(),
+    };
+    OrderbookUpdate {
+        asset_id: if yes { yes_id } else { no_id },
+        bids: vec![level(best_ask - 0.01), level(best_ask - 0.02)],
+        asks: vec![level(best_ask), level(best_ask + 0.01)],
+        timestamp: "0".to_string(),
+        hash: String::new(),
+        seq: 0,
+        received_ns: clock::now_ns(),
+        change: None,
+    }
+}
+
+/// Feed updates and decide on every market they touched
+fn tick(sniper: &mut Sniper, updates: Vec<OrderbookUpdate>) -> usize {
+    let dirty = sniper.apply_book_updates(updates);
+    dirty
+        .iter()
+        .filter(|(id, _)| !matches!(sniper.arbitrage_signal(id), TradeAction::None))
+        .count()
+}
+
+fn benchmark_tick_to_decision(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("runtime");
+    let mut sniper = sniper(&runtime);
+    let mut group = c.benchmark_group("tick_to_decision");
+
+    // Ask stays at 0.50 + 0.52: no arbitrage, the common case
+    group.bench_function("single_update_no_signal", |b| {
+        let mut i = 0;
+        b.iter_batched(
+            || {
+                i = (i + 1) % MARKETS;
+                update(i, true, 0.50)
+            },
+            |update| black_box(tick(&mut sniper, vec![update])),
+            BatchSize::SmallInput,
+        )
+    });
+
+    // YES ask drops to 0.40: 0.40 + 0.52 clears fees and the threshold
+    group.bench_function("single_update_signal", |b| {
+        let mut i = 0;
+        b.iter_batched(
+            || {
+                i = (i + 1) % MARKETS;
+                update(i, true, 0.40)
+            },
+            |update| black_box(tick(&mut sniper, vec![update])),
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Coalesced burst: both books of 25 markets
+    group.bench_function("burst_50_updates", |b| {
+        let mut offset = 0;
+        b.iter_batched(
+            || {
+                offset = (offset + 25) % MARKETS;
+                (0..25)
+                    .flat_map(|k| {
+                        let i = (offset + k) % MARKETS;
+                        [update(i, true, 0.50), update(i, false, 0.52)]
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |updates| black_box(tick(&mut sniper, updates)),
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Unknown asset: lookup miss only
+    group.bench_function("unmapped_asset", |b| {
+        b.iter_batched(
+            || {
+                let mut u = update(0, true, 0.50);
+                u.asset_id = "999999".to_string();
+                u
+            },
+            |update| black_box(tick(&mut sniper, vec![update])),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+
+    alloc_profile::reset();
+    report_percentiles(&mut sniper);
+    alloc_profile::print_report(10);
+}
+
+/// Tail latency of single ticks (criterion reports means; p99 is what misses fills)
+fn report_percentiles(sniper: &mut Sniper) {
+    const SAMPLES: usize = 100_000;
+    let mut samples: Vec<Duration> = Vec::with_capacity(SAMPLES);
+    for n in 0..SAMPLES {
+        let update = update(
+            n % MARKETS,
+            n % 2 == 0,
+            if n % 10 == 0 { 0.40 } else { 0.50 },
+        );
+        let start = Instant::now();
+        black_box(tick(sniper, vec![update]));
+        samples.push(start.elapsed());
+    }
+    samples.sort();
+    let at = |p: f64| samples[((p / 100.0) * (SAMPLES - 1) as f64) as usize];
+    println!(
+        "tick_to_decision single-update latency over {} ticks: p50 {:?} | p90 {:?} | p99 {:?} | p99.9 {:?} | max {:?}",
+        SAMPLES,
+        at(50.0),
+        at(90.0),
+        at(99.0),
+        at(99.9),
+        samples[SAMPLES - 1]
+    );
+}
+
+criterion_group!(benches, benchmark_tick_to_decision);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use polymarket_hft_agent::pricefeed::BinanceClient;
+use polymarket_hft_agent::markets::QuestionSpec;
 
 fn main() {
     let test_cases = vec![
@@ -21,7 +21,9 @@ fn main() {
     let mut failed = 0;
 
     for (question, expected) in test_cases {
-        let result = BinanceClient::symbol_from_question(question);
+        let result = QuestionSpec::parse(question)
+            .underlying
+            .map(|u| u.binance_symbol);
 
         let status = if result == expected {
             passed += 1;
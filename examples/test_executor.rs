@@ -1,7 +1,9 @@
 use polymarket_hft_agent::config::Config;
 use polymarket_hft_agent::execution::Executor;
+use polymarket_hft_agent::markets::MarketRegistry;
 use polymarket_hft_agent::polymarket::{MarketInterface, PolymarketClient};
 use polymarket_hft_agent::strategies::risk::RiskManager;
+use polymarket_hft_agent::{Price, UsdAmount};
 use std::sync::Arc;
 
 #[tokio::main]
@@ -116,7 +118,12 @@ async fn main() -> anyhow::Result<()> {
     );
 
     // 5. Initialize Executor
-    let executor = Executor::new(Box::new(client), None);
+    let executor = Executor::new(
+        Arc::new(client),
+        None,
+        config.execution_health.clone(),
+        MarketRegistry::new(),
+    );
     let mut risk_manager = RiskManager::new(config.risk.clone());
 
     // 6. Place a test order ($2.0 - ensures we are well above $1.0 min)
@@ -130,7 +137,15 @@ async fn main() -> anyhow::Result<()> {
     );
 
     match executor
-        .execute_snipe(market, "YES", price, size_usd, &trade_id, &mut risk_manager)
+        .execute_snipe(
+            market,
+            "YES",
+            Price::new(price),
+            UsdAmount::new(size_usd),
+            &trade_id,
+            &mut risk_manager,
+            None,
+        )
         .await
     {
         Ok(order_id) => {
@@ -3,6 +3,7 @@ use dotenvy::dotenv;
 use polymarket_client_sdk::clob::types::OrderType;
 use polymarket_hft_agent::config::Config;
 use polymarket_hft_agent::polymarket::{MarketInterface, PolymarketClient};
+use polymarket_hft_agent::{Price, UsdAmount};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -51,8 +52,8 @@ async fn main() -> Result<()> {
                     .place_order(
                         &market.id,
                         "YES",
-                        5.0,  // $5 USD (minimum order size)
-                        0.50, // at $0.50 price
+                        UsdAmount::new(5.0), // $5 USD (minimum order size)
+                        Price::new(0.50),    // at $0.50 price
                         OrderType::GTC,
                     )
                     .await
@@ -0,0 +1,115 @@
+//! Embedding facade: build the trading engine from a `Config`, give it your own strategies
+//! (and optionally your own venue client), follow what it does through its events, run it
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+use crate::analytics::PnLTracker;
+use crate::api::ApiState;
+use crate::config::Config;
+use crate::events::AgentEvent;
+use crate::exit_report::{ExitReason, ShutdownHandle};
+use crate::polymarket::MarketInterface;
+use crate::sniper::{Sniper, SniperBuilder};
+use crate::strategies::Strategy;
+
+/// Configures an `Agent`
+pub struct AgentBuilder {
+    sniper: SniperBuilder,
+    strategies: Vec<Arc<dyn Strategy>>,
+}
+
+impl AgentBuilder {
+    pub fn new(config: Config) -> Self {
+        Self {
+            sniper: SniperBuilder::new(config),
+            strategies: Vec::new(),
+        }
+    }
+
+    /// Share a PnL tracker with the host (default: a fresh one on $1000)
+    pub fn pnl_tracker(mut self, pnl_tracker: Arc<Mutex<PnLTracker>>) -> Self {
+        self.sniper = self.sniper.pnl_tracker(pnl_tracker);
+        self
+    }
+
+    /// Read markets and place orders through `market_interface` instead of the Polymarket
+    /// client (or simulator, with `SIMULATION_MODE`) the config picks
+    pub fn market_interface(
+        mut self,
+        market_interface: Arc<dyn MarketInterface + Send + Sync>,
+    ) -> Self {
+        self.sniper = self.sniper.market_interface(market_interface);
+        self
+    }
+
+    /// Run `strategy` on every evaluated market, after the built-in ones (added in order)
+    pub fn strategy(mut self, strategy: impl Strategy + 'static) -> Self {
+        self.strategies.push(Arc::new(strategy));
+        self
+    }
+
+    /// Supply or leave out the engine's own components (WS feed, redemption, Flashbots)
+    pub fn configure(mut self, f: impl FnOnce(SniperBuilder) -> SniperBuilder) -> Self {
+        self.sniper = f(self.sniper);
+        self
+    }
+
+    /// Connect to the venue, restore state from `DATA_DIR` and set up the strategies
+    pub async fn build(self) -> Result<Agent> {
+        let mut sniper = self.sniper.build().await?;
+        for strategy in self.strategies {
+            sniper.add_strategy(strategy);
+        }
+        Ok(Agent { sniper })
+    }
+}
+
+/// The trading engine the binary runs, for use as a library
+pub struct Agent {
+    sniper: Sniper,
+}
+
+impl Agent {
+    pub fn builder(config: Config) -> AgentBuilder {
+        AgentBuilder::new(config)
+    }
+
+    /// Signals, entries and closes from now on. Subscribe before `run`
+    pub fn events(&self) -> broadcast::Receiver<AgentEvent> {
+        self.sniper.subscribe_events()
+    }
+
+    pub fn pnl_tracker(&self) -> Arc<Mutex<PnLTracker>> {
+        self.sniper.pnl_tracker()
+    }
+
+    /// Stops `run` as SIGTERM does: take one before spawning the agent to stop it from the host
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.sniper.shutdown_handle()
+    }
+
+    /// State for `api::serve`, to expose the HTTP API alongside the host's own
+    pub fn api_state(&self) -> ApiState {
+        self.sniper.api_state()
+    }
+
+    /// The engine itself, for what the facade doesn't cover. Its API may change between
+    /// releases
+    pub fn sniper_mut(&mut self) -> &mut Sniper {
+        &mut self.sniper
+    }
+
+    /// Trade until SIGINT / SIGTERM, a `shutdown_handle` stop or a risk halt, then save state,
+    /// write the exit report and say why it stopped (`ExitReason::kind.code()` is the exit
+    /// status the binary uses)
+    pub async fn run(mut self) -> Result<ExitReason> {
+        let outcome = self.sniper.run().await;
+        let reason = match &outcome {
+            Ok(reason) => reason.clone(),
+            Err(e) => ExitReason::crash(format!("{:#}", e)),
+        };
+        self.sniper.write_exit_report(&reason).await;
+        outcome
+    }
+}
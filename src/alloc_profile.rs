@@ -0,0 +1,214 @@
+//! Allocation accounting per code region (`alloc-profiling` feature)
+//!
+//! Hot-path code marks itself with `let _alloc = alloc_profile::region("name");`. With the
+//! feature on, a binary that installs `CountingAlloc` as its global allocator attributes
+//! every allocation to the innermost region active on the thread; `report()` lists the top
+//! allocating regions. Without the feature `region` is a no-op and nothing is counted.
+use serde::{Deserialize, Serialize};
+
+/// Allocation totals of one region
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionStats {
+    pub name: String,
+    /// Times the region was entered
+    pub calls: u64,
+    pub allocations: u64,
+    pub bytes: u64,
+    pub allocations_per_call: f64,
+}
+
+#[cfg(feature = "alloc-profiling")]
+mod imp {
+    use std::alloc::{GlobalAlloc, Layout};
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+
+    use super::RegionStats;
+
+    /// Region slots; slot 0 collects allocations outside any region
+    const MAX_REGIONS: usize = 64;
+
+    struct Counters {
+        calls: AtomicU64,
+        allocations: AtomicU64,
+        bytes: AtomicU64,
+    }
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: Counters = Counters {
+        calls: AtomicU64::new(0),
+        allocations: AtomicU64::new(0),
+        bytes: AtomicU64::new(0),
+    };
+
+    static COUNTERS: [Counters; MAX_REGIONS] = [ZERO; MAX_REGIONS];
+    /// Slot -> region name (index 0 is "(unattributed)")
+    static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    thread_local! {
+        // Const-initialized and drop-free: safe to read from inside the allocator
+        static CURRENT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Global allocator wrapper counting allocations into the current region
+    pub struct CountingAlloc<A>(pub A);
+
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count(layout.size());
+            self.0.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            count(layout.size());
+            self.0.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count(new_size);
+            self.0.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.0.dealloc(ptr, layout)
+        }
+    }
+
+    fn count(bytes: usize) {
+        let slot = CURRENT.try_with(Cell::get).unwrap_or(0);
+        COUNTERS[slot].allocations.fetch_add(1, Ordering::Relaxed);
+        COUNTERS[slot]
+            .bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn slot(name: &'static str) -> usize {
+        let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+        if names.is_empty() {
+            names.push("(unattributed)");
+        }
+        if let Some(slot) = names.iter().position(|n| *n == name) {
+            return slot;
+        }
+        if names.len() == MAX_REGIONS {
+            // Out of slots: count as unattributed rather than fail
+            return 0;
+        }
+        names.push(name);
+        names.len() - 1
+    }
+
+    /// Restores the enclosing region on drop
+    pub struct RegionGuard {
+        previous: usize,
+    }
+
+    impl Drop for RegionGuard {
+        fn drop(&mut self) {
+            let _ = CURRENT.try_with(|c| c.set(self.previous));
+        }
+    }
+
+    pub fn region(name: &'static str) -> RegionGuard {
+        let slot = slot(name);
+        COUNTERS[slot].calls.fetch_add(1, Ordering::Relaxed);
+        RegionGuard {
+            previous: CURRENT.try_with(|c| c.replace(slot)).unwrap_or(0),
+        }
+    }
+
+    pub fn report() -> Vec<RegionStats> {
+        let names: Vec<&'static str> = NAMES.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut stats: Vec<RegionStats> = names
+            .iter()
+            .enumerate()
+            .map(|(slot, name)| {
+                let calls = COUNTERS[slot].calls.load(Ordering::Relaxed);
+                let allocations = COUNTERS[slot].allocations.load(Ordering::Relaxed);
+                RegionStats {
+                    name: name.to_string(),
+                    calls,
+                    allocations,
+                    bytes: COUNTERS[slot].bytes.load(Ordering::Relaxed),
+                    allocations_per_call: if calls > 0 {
+                        allocations as f64 / calls as f64
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| b.allocations.cmp(&a.allocations));
+        stats
+    }
+
+    pub fn reset() {
+        for counters in &COUNTERS {
+            counters.calls.store(0, Ordering::Relaxed);
+            counters.allocations.store(0, Ordering::Relaxed);
+            counters.bytes.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(not(feature = "alloc-profiling"))]
+mod imp {
+    use super::RegionStats;
+
+    pub struct RegionGuard;
+
+    #[inline(always)]
+    pub fn region(_name: &'static str) -> RegionGuard {
+        RegionGuard
+    }
+
+    pub fn report() -> Vec<RegionStats> {
+        Vec::new()
+    }
+
+    pub fn reset() {}
+}
+
+#[cfg(feature = "alloc-profiling")]
+pub use imp::CountingAlloc;
+pub use imp::{region, report, reset, RegionGuard};
+
+/// Print the `top` allocating regions (nothing without the feature)
+pub fn print_report(top: usize) {
+    let stats = report();
+    if stats.is_empty() {
+        return;
+    }
+    println!("\n🧮 Allocations by region (top {})", top);
+    println!(
+        "   {:<40} {:>12} {:>14} {:>14} {:>10}",
+        "region", "calls", "allocations", "bytes", "allocs/call"
+    );
+    for s in stats.iter().take(top) {
+        println!(
+            "   {:<40} {:>12} {:>14} {:>14} {:>10.2}",
+            s.name, s.calls, s.allocations, s.bytes, s.allocations_per_call
+        );
+    }
+}
+
+#[cfg(all(test, feature = "alloc-profiling"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_call_counts() {
+        // The test binary uses the system allocator, so only call counts are observable
+        {
+            let _outer = region("test::outer");
+            let _inner = region("test::inner");
+        }
+        let _outer = region("test::outer");
+
+        let stats = report();
+        let calls = |name: &str| stats.iter().find(|s| s.name == name).map(|s| s.calls);
+        assert_eq!(calls("test::outer"), Some(2));
+        assert_eq!(calls("test::inner"), Some(1));
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+use super::pnl::{Position, Trade};
+
+/// Key of positions and trades opened before sessions recorded a config hash
+const UNKNOWN_CONFIG: &str = "unknown";
+
+/// Strategy that opened a position, from its trade id (`<strategy>_<market>_<ts>`)
+/// Hedges (`<origin>-hedge`), arbitrage legs and partial closes keep their origin's prefix
+pub fn strategy_of(trade_id: &str) -> &str {
+    trade_id.split('_').next().unwrap_or(trade_id)
+}
+
+/// Trade id an exit plan id (`close_<id>`, `sell_<id>`, `liq_<id>_<rung>`) works on
+pub fn origin_trade_id(plan_id: &str) -> &str {
+    let mut id = plan_id;
+    while let Some(rest) = ["close_", "sell_", "liq_"]
+        .iter()
+        .find_map(|prefix| id.strip_prefix(prefix))
+    {
+        id = rest;
+    }
+    id
+}
+
+/// Trade id prefix of a strategy given by name (`arbitrage`, `expiration`, `predictive`,
+/// `dislocation`) or already by prefix
+pub fn strategy_key(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.as_str() {
+        "arbitrage" => "arb".to_string(),
+        "expiration" => "snipe".to_string(),
+        "predictive" => "pred".to_string(),
+        "dislocation" => "disloc".to_string(),
+        _ => name,
+    }
+}
+
+/// Dimension PnL is broken down by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Dimension {
+    /// Trade id prefix (arb, pred, snipe, disloc, ...)
+    #[default]
+    Strategy,
+    /// Hash of the session config the entry was made under
+    Config,
+}
+
+impl Dimension {
+    pub fn key_of(self, id: &str, config_hash: Option<&str>) -> String {
+        match self {
+            Dimension::Strategy => strategy_of(id).to_string(),
+            Dimension::Config => config_hash.unwrap_or(UNKNOWN_CONFIG).to_string(),
+        }
+    }
+}
+
+/// Closed trades and open positions of one strategy / config (or all of them)
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct AttributionRow {
+    pub key: String,
+    pub num_trades: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub realized_pnl: f64,
+    /// Sum of closed sizes (USD)
+    pub volume: f64,
+    pub open_positions: usize,
+    pub unrealized_pnl: f64,
+}
+
+impl AttributionRow {
+    fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn add_trade(&mut self, trade: &Trade) {
+        let pnl = trade.realized_pnl.unwrap_or(0.0);
+        self.num_trades += 1;
+        self.wins += usize::from(pnl > 0.0);
+        self.realized_pnl += pnl;
+        self.volume += trade.size;
+    }
+
+    fn add_position(&mut self, position: &Position) {
+        self.open_positions += 1;
+        self.unrealized_pnl += position.unrealized_pnl();
+    }
+
+    fn finish(mut self) -> Self {
+        if self.num_trades > 0 {
+            self.win_rate = self.wins as f64 / self.num_trades as f64;
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AttributionReport {
+    pub by: Dimension,
+    /// Every row added up
+    pub total: AttributionRow,
+    /// Best total PnL first
+    pub rows: Vec<AttributionRow>,
+}
+
+/// Realized and unrealized PnL per strategy or config
+pub fn breakdown<'a>(
+    by: Dimension,
+    trades: impl IntoIterator<Item = &'a Trade>,
+    positions: impl IntoIterator<Item = &'a Position>,
+) -> AttributionReport {
+    let mut total = AttributionRow::new("total");
+    let mut rows: BTreeMap<String, AttributionRow> = BTreeMap::new();
+    for trade in trades {
+        let key = by.key_of(&trade.id, trade.config_hash.as_deref());
+        total.add_trade(trade);
+        rows.entry(key.clone())
+            .or_insert_with(|| AttributionRow::new(&key))
+            .add_trade(trade);
+    }
+    for position in positions {
+        let key = by.key_of(&position.id, position.config_hash.as_deref());
+        total.add_position(position);
+        rows.entry(key.clone())
+            .or_insert_with(|| AttributionRow::new(&key))
+            .add_position(position);
+    }
+    let mut rows: Vec<AttributionRow> = rows.into_values().map(AttributionRow::finish).collect();
+    rows.sort_by(|a, b| {
+        (b.realized_pnl + b.unrealized_pnl).total_cmp(&(a.realized_pnl + a.unrealized_pnl))
+    });
+    AttributionReport {
+        by,
+        total: total.finish(),
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn trade(id: &str, config_hash: Option<&str>, pnl: f64) -> Trade {
+        Trade {
+            id: id.to_string(),
+            market_id: "m1".to_string(),
+            market_question: "m1?".to_string(),
+            side: "YES".to_string(),
+            size: 10.0,
+            entry_price: 0.5,
+            exit_price: Some(0.5 + pnl / 10.0),
+            entry_time: Utc::now(),
+            exit_time: Some(Utc::now()),
+            realized_pnl: Some(pnl),
+            config_hash: config_hash.map(str::to_string),
+            note: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_exit_plans_keep_their_origin_strategy() {
+        assert_eq!(origin_trade_id("pred_m1_1"), "pred_m1_1");
+        assert_eq!(origin_trade_id("close_pred_m1_1"), "pred_m1_1");
+        assert_eq!(strategy_of(origin_trade_id("liq_arb_m1_1_2")), "arb");
+        assert_eq!(
+            strategy_of(origin_trade_id("sell_snipe_m1_1-hedge")),
+            "snipe"
+        );
+    }
+
+    #[test]
+    fn test_breakdown_by_strategy_and_config() {
+        let trades = [
+            trade("arb_m1_1_YES", Some("a"), 2.0),
+            trade("arb_m1_1_NO", Some("a"), -1.0),
+            // Hedges and partial closes are charged to the origin's strategy
+            trade("pred_m2_1-hedge", Some("b"), -0.5),
+            trade("pred_m2_1-1", None, 3.0),
+        ];
+        let position = Position {
+            id: "snipe_m3_1".to_string(),
+            market_id: "m3".to_string(),
+            market_question: "m3?".to_string(),
+            side: "NO".to_string(),
+            size: 10.0,
+            entry_price: 0.9,
+            current_price: 0.8,
+            entry_time: Utc::now(),
+            config_hash: Some("b".to_string()),
+            group_id: None,
+        };
+
+        let report = breakdown(Dimension::Strategy, &trades, [&position]);
+        let keys: Vec<&str> = report.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["pred", "arb", "snipe"]);
+        assert_eq!(report.rows[0].num_trades, 2);
+        assert!((report.rows[0].win_rate - 0.5).abs() < 1e-9);
+        assert_eq!(report.rows[2].open_positions, 1);
+        assert!((report.rows[2].unrealized_pnl + 1.0).abs() < 1e-9);
+        assert_eq!(report.total.num_trades, 4);
+        assert!((report.total.realized_pnl - 3.5).abs() < 1e-9);
+
+        let report = breakdown(Dimension::Config, &trades, [&position]);
+        let keys: Vec<&str> = report.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["unknown", "a", "b"]);
+    }
+}
@@ -0,0 +1,357 @@
+//! Cost basis of positions the bot didn't open
+//!
+//! Positions found in the wallet at startup (bought by hand, or by another instance) come
+//! without an entry price. It is rebuilt from the wallet's own fills on the data-api, replayed
+//! at average cost. Tokens that arrived without a fill (transfers, splits) are priced at the
+//! mean of the token's recent price history, and failing that at the current mark, so their
+//! PnL starts from zero rather than from a made-up price.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::markets::normalize_market_id;
+
+const DATA_API_URL: &str = "https://data-api.polymarket.com";
+
+/// Fills requested per data-api page
+const FILL_PAGE_SIZE: usize = 500;
+
+/// Pages of fills fetched per market before giving up on older ones
+const MAX_FILL_PAGES: usize = 10;
+
+/// Positions requested per data-api page
+const POSITION_PAGE_SIZE: usize = 500;
+
+/// Pages of positions fetched before giving up on the rest of the wallet
+const MAX_POSITION_PAGES: usize = 20;
+
+/// Minutes between price history points of the estimate
+const HISTORY_FIDELITY_MINS: u64 = 60;
+
+/// Where an imported position's entry price came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostBasisSource {
+    /// Average cost of the wallet's fills
+    Fills,
+    /// Mean of the token's recent price history
+    PriceHistory,
+    /// Current price: nothing better known
+    Mark,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostBasis {
+    pub entry_price: f64,
+    pub source: CostBasisSource,
+}
+
+/// A token balance of the wallet, from the data-api `/positions` listing
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletPosition {
+    pub condition_id: String,
+    /// Token id
+    pub asset: String,
+    /// Shares held
+    pub size: f64,
+    /// Outcome name ("Yes" / "No")
+    #[serde(default)]
+    pub outcome: String,
+    #[serde(default)]
+    pub cur_price: f64,
+    #[serde(default)]
+    pub title: String,
+}
+
+impl WalletPosition {
+    /// "YES" / "NO", None for outcomes of other names
+    pub fn side(&self) -> Option<&'static str> {
+        match self.outcome.to_lowercase().as_str() {
+            "yes" => Some("YES"),
+            "no" => Some("NO"),
+            _ => None,
+        }
+    }
+}
+
+/// One of the wallet's fills, from the data-api `/trades` listing
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fill {
+    /// Unix seconds
+    pub timestamp: u64,
+    /// "BUY" / "SELL"
+    pub side: String,
+    /// Token id
+    pub asset: String,
+    /// Shares
+    pub size: f64,
+    pub price: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceHistory {
+    history: Vec<PricePoint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PricePoint {
+    p: f64,
+}
+
+/// Average cost of the shares still held after replaying `fills` of one token oldest first:
+/// buys add at their price, sells take shares out at the running average. None when the
+/// replay ends with nothing held (the position came from elsewhere)
+pub fn average_cost(fills: &[Fill]) -> Option<f64> {
+    let mut fills: Vec<&Fill> = fills.iter().collect();
+    fills.sort_by_key(|f| f.timestamp);
+    let (mut shares, mut cost) = (0.0_f64, 0.0_f64);
+    for fill in fills {
+        if fill.side.eq_ignore_ascii_case("BUY") {
+            shares += fill.size;
+            cost += fill.size * fill.price;
+        } else if shares > 0.0 {
+            let sold = fill.size.min(shares);
+            cost -= sold * cost / shares;
+            shares -= sold;
+        }
+    }
+    (shares > 1e-9).then(|| cost / shares)
+}
+
+/// `positions` without repeats of a token id (a page boundary shifting while paging), first
+/// listing kept
+fn unique_assets(positions: Vec<WalletPosition>) -> Vec<WalletPosition> {
+    let mut seen = HashSet::new();
+    positions
+        .into_iter()
+        .filter(|p| seen.insert(p.asset.clone()))
+        .collect()
+}
+
+/// Mean of a price series, None when empty
+fn mean_price(prices: &[f64]) -> Option<f64> {
+    let usable: Vec<f64> = prices
+        .iter()
+        .copied()
+        .filter(|p| *p > 0.0 && *p < 1.0)
+        .collect();
+    (!usable.is_empty()).then(|| usable.iter().sum::<f64>() / usable.len() as f64)
+}
+
+/// Fetches a wallet's positions and rebuilds their cost basis. Public endpoints, no API key
+pub struct CostBasisBackfill {
+    http: reqwest::Client,
+    clob_url: String,
+    /// Price history window of the estimate
+    history_days: u64,
+}
+
+impl CostBasisBackfill {
+    pub fn new(clob_url: &str, history_days: u64) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()?;
+        Ok(Self {
+            http,
+            clob_url: clob_url.trim_end_matches('/').to_string(),
+            history_days: history_days.max(1),
+        })
+    }
+
+    /// Token balances of `user`, one per token id
+    pub async fn positions(&self, user: &str) -> Result<Vec<WalletPosition>> {
+        let mut positions = Vec::new();
+        for page in 0..MAX_POSITION_PAGES {
+            let batch: Vec<WalletPosition> = self
+                .http
+                .get(format!("{}/positions", DATA_API_URL))
+                .query(&[
+                    ("user", user.to_string()),
+                    ("sizeThreshold", "0".to_string()),
+                    ("limit", POSITION_PAGE_SIZE.to_string()),
+                    ("offset", (page * POSITION_PAGE_SIZE).to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let done = batch.len() < POSITION_PAGE_SIZE;
+            positions.extend(batch);
+            if done {
+                return Ok(unique_assets(positions));
+            }
+        }
+        warn!(
+            "⚠️ Wallet lists over {} positions, importing the first ones",
+            MAX_POSITION_PAGES * POSITION_PAGE_SIZE
+        );
+        Ok(unique_assets(positions))
+    }
+
+    /// Fills of `user` in the token of `position`, newest first
+    async fn fills(&self, user: &str, position: &WalletPosition) -> Result<Vec<Fill>> {
+        let mut fills = Vec::new();
+        for page in 0..MAX_FILL_PAGES {
+            let batch: Vec<Fill> = self
+                .http
+                .get(format!("{}/trades", DATA_API_URL))
+                .query(&[
+                    ("user", user.to_string()),
+                    (
+                        "market",
+                        normalize_market_id(&position.condition_id).into_owned(),
+                    ),
+                    ("limit", FILL_PAGE_SIZE.to_string()),
+                    ("offset", (page * FILL_PAGE_SIZE).to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let done = batch.len() < FILL_PAGE_SIZE;
+            fills.extend(batch.into_iter().filter(|f| f.asset == position.asset));
+            if done {
+                break;
+            }
+        }
+        Ok(fills)
+    }
+
+    /// Latest fills of `user` in a market at or after `since` (unix seconds), all tokens
+    pub async fn fills_since(
+        &self,
+        user: &str,
+        condition_id: &str,
+        since: u64,
+    ) -> Result<Vec<Fill>> {
+        let fills: Vec<Fill> = self
+            .http
+            .get(format!("{}/trades", DATA_API_URL))
+            .query(&[
+                ("user", user.to_string()),
+                ("market", normalize_market_id(condition_id).into_owned()),
+                ("limit", FILL_PAGE_SIZE.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(fills.into_iter().filter(|f| f.timestamp >= since).collect())
+    }
+
+    /// Mean price of the token over the history window
+    async fn history_mean(&self, asset_id: &str) -> Result<Option<f64>> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let start = now.saturating_sub(self.history_days * 86_400);
+        let history: PriceHistory = self
+            .http
+            .get(format!("{}/prices-history", self.clob_url))
+            .query(&[
+                ("market", asset_id.to_string()),
+                ("startTs", start.to_string()),
+                ("fidelity", HISTORY_FIDELITY_MINS.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let prices: Vec<f64> = history.history.iter().map(|p| p.p).collect();
+        Ok(mean_price(&prices))
+    }
+
+    /// Entry price of a position `user` holds: its fills, else its price history, else its mark
+    pub async fn cost_basis(&self, user: &str, position: &WalletPosition) -> CostBasis {
+        match self.fills(user, position).await {
+            Ok(fills) => {
+                if let Some(entry_price) = average_cost(&fills) {
+                    return CostBasis {
+                        entry_price,
+                        source: CostBasisSource::Fills,
+                    };
+                }
+            }
+            Err(e) => debug!("No fills for {}: {:#}", position.asset, e),
+        }
+        match self.history_mean(&position.asset).await {
+            Ok(Some(entry_price)) => {
+                return CostBasis {
+                    entry_price,
+                    source: CostBasisSource::PriceHistory,
+                }
+            }
+            Ok(None) => {}
+            Err(e) => debug!("No price history for {}: {:#}", position.asset, e),
+        }
+        CostBasis {
+            entry_price: position.cur_price,
+            source: CostBasisSource::Mark,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(timestamp: u64, side: &str, size: f64, price: f64) -> Fill {
+        Fill {
+            timestamp,
+            side: side.to_string(),
+            asset: "a1".to_string(),
+            size,
+            price,
+        }
+    }
+
+    #[test]
+    fn test_average_cost_replays_fills_in_order() {
+        // Listed newest first, as the data-api returns them
+        let fills = [
+            fill(30, "BUY", 50.0, 0.70),
+            fill(20, "SELL", 50.0, 0.80),
+            fill(10, "BUY", 100.0, 0.40),
+        ];
+        // 50 left at 0.40 after the sale, plus 50 at 0.70
+        assert!((average_cost(&fills).unwrap() - 0.55).abs() < 1e-9);
+
+        // Sold out: the balance came from somewhere the fills don't show
+        assert_eq!(
+            average_cost(&[fill(1, "BUY", 10.0, 0.5), fill(2, "SELL", 10.0, 0.6)]),
+            None
+        );
+        assert_eq!(average_cost(&[]), None);
+    }
+
+    #[test]
+    fn test_positions_are_unique_per_asset() {
+        let position = |asset: &str, outcome: &str| WalletPosition {
+            condition_id: "0xc1".to_string(),
+            asset: asset.to_string(),
+            size: 10.0,
+            outcome: outcome.to_string(),
+            cur_price: 0.5,
+            title: String::new(),
+        };
+        let held = unique_assets(vec![
+            position("yes", "Yes"),
+            position("no", "No"),
+            position("yes", "Yes"),
+        ]);
+        // Both outcomes of the market stay, the repeat goes
+        let assets: Vec<&str> = held.iter().map(|p| p.asset.as_str()).collect();
+        assert_eq!(assets, vec!["yes", "no"]);
+    }
+
+    #[test]
+    fn test_mean_price_skips_unusable_points() {
+        assert_eq!(mean_price(&[]), None);
+        assert!((mean_price(&[0.4, 0.6, 0.0, 1.0]).unwrap() - 0.5).abs() < 1e-9);
+    }
+}
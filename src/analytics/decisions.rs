@@ -0,0 +1,354 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use tracing::{error, info, warn};
+
+use crate::config::{
+    ArbitrageConfig, DislocationConfig, ExpirationConfig, PredictiveConfig, RiskConfig,
+};
+use crate::polymarket::MarketData;
+use crate::pricefeed::BinanceClient;
+use crate::strategies::arbitrage::{ArbitrageInputs, ArbitrageStrategy, TradeAction};
+use crate::strategies::dislocation::DislocationStrategy;
+use crate::strategies::expiration::ExpirationStrategy;
+use crate::strategies::predictive::PredictiveStrategy;
+
+pub const DECISION_LOG_FILE: &str = "decisions.jsonl";
+
+/// Strategy configuration in effect when a decision was made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyConfigSnapshot {
+    pub arbitrage: ArbitrageConfig,
+    pub expiration: ExpirationConfig,
+    pub predictive: PredictiveConfig,
+    /// Missing in records written before the dislocation strategy existed
+    #[serde(default)]
+    pub dislocation: Option<DislocationConfig>,
+}
+
+/// Everything a strategy looked at, plus what it decided
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub strategy: String, // "predictive" | "expiration" | "arbitrage" | "dislocation"
+    pub market: MarketData,
+    pub binance_price: Option<f64>,
+    /// Effective arbitrage edge threshold (edge tuner, toxicity, fee-free markets) when it
+    /// differed from the configured one
+    #[serde(default)]
+    pub min_edge_bps: Option<i32>,
+    /// Fee, size multiplier and ask ladders of an arbitrage decision. None on other strategies
+    /// and in records written before they were kept (replayed at the usual fee, unscaled and
+    /// without the depth check)
+    #[serde(default)]
+    pub arbitrage: Option<ArbitrageInputs>,
+    pub config: StrategyConfigSnapshot,
+    /// Session config hash (see `sessions.jsonl`)
+    #[serde(default)]
+    pub config_hash: Option<String>,
+    /// Socket read of the book update behind the evaluation -> decision, in ns (None when no
+    /// book update triggered it: new markets, timers)
+    #[serde(default)]
+    pub receive_to_decision_ns: Option<u64>,
+    pub action: TradeAction,
+}
+
+/// Which decisions get written to the log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionLogMode {
+    Off,
+    /// Only evaluations that produced a signal
+    Signals,
+    /// Every evaluation, including skips (large files)
+    All,
+}
+
+impl DecisionLogMode {
+    pub fn parse(mode: &str) -> Self {
+        match mode.to_lowercase().as_str() {
+            "all" => DecisionLogMode::All,
+            "signals" => DecisionLogMode::Signals,
+            _ => DecisionLogMode::Off,
+        }
+    }
+}
+
+/// Append-only JSONL decision log
+/// Records are serialized on a dedicated writer thread to keep file I/O off the hot path
+pub struct DecisionLog {
+    mode: DecisionLogMode,
+    config: Arc<StrategyConfigSnapshot>,
+    config_hash: Option<String>,
+    tx: mpsc::Sender<DecisionRecord>,
+}
+
+impl DecisionLog {
+    pub fn new(
+        data_dir: &str,
+        mode: DecisionLogMode,
+        config: StrategyConfigSnapshot,
+        config_hash: Option<String>,
+    ) -> Result<Self> {
+        fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create data dir {}", data_dir))?;
+        let path = Path::new(data_dir).join(DECISION_LOG_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open decision log {:?}", path))?;
+
+        let (tx, rx) = mpsc::channel::<DecisionRecord>();
+        std::thread::Builder::new()
+            .name("decision-log".to_string())
+            .spawn(move || {
+                let mut writer = BufWriter::new(file);
+                while let Ok(record) = rx.recv() {
+                    write_record(&mut writer, &record);
+                    // Drain the rest of the burst, then flush once
+                    while let Ok(record) = rx.try_recv() {
+                        write_record(&mut writer, &record);
+                    }
+                    let _ = writer.flush();
+                }
+            })?;
+
+        info!("📼 Decision log enabled ({:?}): {:?}", mode, path);
+
+        Ok(Self {
+            mode,
+            config: Arc::new(config),
+            config_hash,
+            tx,
+        })
+    }
+
+    /// Record one strategy evaluation. Returns the record id if it was logged
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &self,
+        strategy: &str,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        binance_price: Option<f64>,
+        min_edge_bps: Option<i32>,
+        arbitrage: Option<ArbitrageInputs>,
+        receive_to_decision_ns: Option<u64>,
+        action: &TradeAction,
+    ) -> Option<String> {
+        let is_signal = !matches!(action, TradeAction::None);
+        match self.mode {
+            DecisionLogMode::Off => return None,
+            DecisionLogMode::Signals if !is_signal => return None,
+            _ => {}
+        }
+
+        let id = format!("{}-{}", now.timestamp_millis(), uuid::Uuid::new_v4().simple());
+        let record = DecisionRecord {
+            id: id.clone(),
+            timestamp: now,
+            strategy: strategy.to_string(),
+            market: market.clone(),
+            binance_price,
+            min_edge_bps,
+            arbitrage,
+            config: (*self.config).clone(),
+            config_hash: self.config_hash.clone(),
+            receive_to_decision_ns,
+            action: action.clone(),
+        };
+
+        if self.tx.send(record).is_err() {
+            warn!("⚠️ Decision log writer stopped, dropping record {}", id);
+            return None;
+        }
+        Some(id)
+    }
+}
+
+fn write_record(writer: &mut BufWriter<File>, record: &DecisionRecord) {
+    let res = serde_json::to_writer(&mut *writer, record)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| writer.write_all(b"\n").map_err(anyhow::Error::from));
+    if let Err(e) = res {
+        error!("❌ Failed to write decision record {}: {}", record.id, e);
+    }
+}
+
+/// Path of the decision log inside a data dir
+pub fn decision_log_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(DECISION_LOG_FILE)
+}
+
+/// Find a record by id (or unique id prefix) in a decision log
+pub fn find_decision(path: &Path, id: &str) -> Result<DecisionRecord> {
+    let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.contains(id) {
+            continue;
+        }
+        let record: DecisionRecord = match serde_json::from_str(&line) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        if record.id.starts_with(id) {
+            return Ok(record);
+        }
+    }
+    anyhow::bail!("Decision {} not found in {:?}", id, path)
+}
+
+/// Re-run the recorded strategy on the recorded inputs
+/// The Binance price and clock are taken from the record, so no network access is needed
+pub fn replay_decision(record: &DecisionRecord) -> Result<TradeAction> {
+    // Records written before the end time was cached on the market don't carry it
+    let market = record.market.clone().with_end_time();
+    let action = match record.strategy.as_str() {
+        "arbitrage" => {
+            let min_edge_bps = record
+                .min_edge_bps
+                .unwrap_or(record.config.arbitrage.min_edge_bps);
+            let capital = record
+                .arbitrage
+                .as_ref()
+                .and_then(|inputs| inputs.sizing_capital)
+                .unwrap_or_else(|| RiskConfig::default().capital_usd);
+            let strategy = ArbitrageStrategy::new(record.config.arbitrage.clone(), capital);
+            match &record.arbitrage {
+                Some(inputs) => strategy.check_opportunity_on_book(
+                    &market,
+                    min_edge_bps,
+                    inputs.fee_per_leg_bps,
+                    inputs.size_multiplier,
+                    inputs.book(),
+                ),
+                None => strategy.check_opportunity_with_edge(&market, min_edge_bps),
+            }
+        }
+        "expiration" => ExpirationStrategy::new(record.config.expiration.clone())
+            .check_opportunity_at(&market, record.timestamp),
+        "predictive" => {
+            let Some(binance_price) = record.binance_price else {
+                // No price was fetched, so the strategy bailed out during pre-filtering
+                return Ok(TradeAction::None);
+            };
+            PredictiveStrategy::new(
+                record.config.predictive.clone(),
+                Arc::new(BinanceClient::new()),
+            )
+            .evaluate(&market, record.timestamp, binance_price)
+        }
+        "dislocation" => {
+            let Some(config) = record.config.dislocation.clone() else {
+                anyhow::bail!("Record {} has no dislocation config", record.id);
+            };
+            // Strike markets were priced off Binance, everything else off the complementary side
+            let fair_yes = record.binance_price.and_then(|price| {
+                PredictiveStrategy::new(
+                    record.config.predictive.clone(),
+                    Arc::new(BinanceClient::new()),
+                )
+                .fair_yes_probability(
+                    &market,
+                    record.timestamp,
+                    price,
+                    config.annual_volatility,
+                )
+            });
+            DislocationStrategy::new(config).check_opportunity(&market, fair_yes)
+        }
+        other => anyhow::bail!("Unknown strategy in record: {}", other),
+    };
+    Ok(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, SizingMode};
+
+    fn record(arbitrage: Option<ArbitrageInputs>) -> DecisionRecord {
+        let mut config = Config::default();
+        config.arbitrage.enabled = true;
+        config.arbitrage.sizing_mode = SizingMode::Fixed;
+        config.arbitrage.min_depth_usd = 50.0;
+        DecisionRecord {
+            id: "d1".to_string(),
+            timestamp: Utc::now(),
+            strategy: "arbitrage".to_string(),
+            market: MarketData {
+                id: "m1".into(),
+                question: "Will it happen?".to_string(),
+                yes_price: 0.45,
+                no_price: 0.50,
+                asset_ids: vec!["t-no".to_string(), "t-yes".to_string()],
+                ..Default::default()
+            },
+            binance_price: None,
+            min_edge_bps: None,
+            arbitrage,
+            config: StrategyConfigSnapshot {
+                arbitrage: config.arbitrage,
+                expiration: config.expiration,
+                predictive: config.predictive,
+                dislocation: Some(config.dislocation),
+            },
+            config_hash: None,
+            receive_to_decision_ns: None,
+            action: TradeAction::None,
+        }
+    }
+
+    fn round_trip(record: &DecisionRecord) -> DecisionRecord {
+        serde_json::from_str(&serde_json::to_string(record).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_replay_uses_the_recorded_fee_and_book() {
+        let inputs = |asks: Vec<(f64, f64)>| ArbitrageInputs {
+            fee_per_leg_bps: 0,
+            size_multiplier: 1.5,
+            yes_asks: Some(asks.clone()),
+            no_asks: Some(asks),
+            sizing_capital: Some(1000.0),
+        };
+
+        // $4.50 within the edge on each side: the depth guard drops it
+        let thin = round_trip(&record(Some(inputs(vec![(0.45, 10.0)]))));
+        assert_eq!(thin.arbitrage, Some(inputs(vec![(0.45, 10.0)])));
+        assert!(matches!(replay_decision(&thin).unwrap(), TradeAction::None));
+
+        // Fee-free and deep: traded at 1.5x the size
+        let deep = round_trip(&record(Some(inputs(vec![(0.45, 1000.0), (0.50, 1000.0)]))));
+        match replay_decision(&deep).unwrap() {
+            TradeAction::BuyBoth {
+                size_usd,
+                expected_profit_bps,
+                ..
+            } => {
+                assert_eq!(expected_profit_bps, 500);
+                assert!((size_usd.get() - 15.0).abs() < 1e-9);
+            }
+            other => panic!("expected an arbitrage, got {:?}", other),
+        }
+
+        // Records written before the inputs were kept replay at the usual fee, unscaled
+        let mut old = serde_json::to_value(record(None)).unwrap();
+        old.as_object_mut().unwrap().remove("arbitrage");
+        let old: DecisionRecord = serde_json::from_value(old).unwrap();
+        match replay_decision(&old).unwrap() {
+            TradeAction::BuyBoth {
+                size_usd,
+                expected_profit_bps,
+                ..
+            } => assert_eq!((size_usd.get(), expected_profit_bps), (10.0, 420)),
+            other => panic!("expected an arbitrage, got {:?}", other),
+        }
+    }
+}
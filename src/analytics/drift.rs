@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use utoipa::ToSchema;
+
+pub const DRIFT_FILE: &str = "drift.json";
+
+/// Live orders kept (oldest evicted first)
+const MAX_ORDERS: usize = 1000;
+
+/// Most recent orders included in the report
+const RECENT_ORDERS: usize = 20;
+
+/// One live order and its paper twin: the same order sent to the paper trading engine at the
+/// same time, both checked for their fill the same way
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ShadowOrder {
+    /// Trade id of the position the order opened (arbitrage legs: `<plan id>_<side>`)
+    pub trade_id: String,
+    pub market_id: String,
+    pub side: String,
+    pub limit_price: f64,
+    pub size_usd: f64,
+    pub placed_at: DateTime<Utc>,
+    /// Share of the live order matched (0-1). None until checked
+    pub filled_fraction: Option<f64>,
+    /// How far the book ran past the limit (bps) when the live order didn't fill
+    pub slippage_bps: Option<f64>,
+    /// Share of the paper twin matched (0-1). None until checked
+    #[serde(default)]
+    pub paper_filled_fraction: Option<f64>,
+    #[serde(default)]
+    pub paper_slippage_bps: Option<f64>,
+}
+
+impl ShadowOrder {
+    /// Both sides checked (the gaps compare these)
+    fn is_checked(&self) -> bool {
+        self.filled_fraction.is_some() && self.paper_filled_fraction.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct DriftReport {
+    pub orders: usize,
+    /// Orders whose live fill and paper twin were both checked (the gaps below are over these)
+    pub checked: usize,
+    pub paper_fill_rate: f64,
+    pub live_fill_rate: f64,
+    pub fill_rate_gap: f64,
+    /// Mean distance the book ran past the limit
+    pub paper_slippage_bps: f64,
+    pub live_slippage_bps: f64,
+    pub slippage_gap_bps: f64,
+    /// Checked orders whose position has closed trades in the journal
+    pub settled: usize,
+    /// Journal PnL of the settled orders (booked in full at the limit) scaled to the share of
+    /// each paper twin that filled
+    pub paper_pnl: f64,
+    /// The same scaled to the share of each order that actually filled
+    pub live_pnl: f64,
+    pub pnl_gap: f64,
+    /// Share of paper PnL live trading kept (live / paper): how much to discount paper
+    /// results by. None until paper PnL is positive
+    pub live_to_paper_ratio: Option<f64>,
+    pub recent: Vec<ShadowOrder>,
+}
+
+/// Shadows every live order with its paper twin, run in parallel, and measures how far live
+/// results drift from what paper trading reports for the same signals. Persisted in
+/// `data_dir/drift.json`
+pub struct DriftTracker {
+    path: Option<PathBuf>,
+    orders: VecDeque<ShadowOrder>,
+}
+
+impl DriftTracker {
+    /// Load recorded orders from `data_dir` (empty if none were recorded yet)
+    pub fn load(data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(DRIFT_FILE);
+        let orders = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                VecDeque::new()
+            }),
+            Err(_) => VecDeque::new(),
+        };
+        Self {
+            path: Some(path),
+            orders,
+        }
+    }
+
+    /// No-op for in-memory trackers
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.orders)?)?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to persist drift tracker: {}", e);
+        }
+    }
+
+    pub fn record_order(
+        &mut self,
+        trade_id: &str,
+        market_id: &str,
+        side: &str,
+        limit_price: f64,
+        size_usd: f64,
+        now: DateTime<Utc>,
+    ) {
+        if self.orders.len() >= MAX_ORDERS {
+            self.orders.pop_front();
+        }
+        self.orders.push_back(ShadowOrder {
+            trade_id: trade_id.to_string(),
+            market_id: market_id.to_string(),
+            side: side.to_string(),
+            limit_price,
+            size_usd,
+            placed_at: now,
+            filled_fraction: None,
+            slippage_bps: None,
+            paper_filled_fraction: None,
+            paper_slippage_bps: None,
+        });
+        self.persist();
+    }
+
+    /// How the live order filled
+    pub fn record_fill(&mut self, trade_id: &str, filled_fraction: f64, slippage_bps: f64) {
+        let Some(order) = self.orders.iter_mut().find(|o| o.trade_id == trade_id) else {
+            return;
+        };
+        order.filled_fraction = Some(filled_fraction.clamp(0.0, 1.0));
+        order.slippage_bps = Some(slippage_bps.max(0.0));
+        self.persist();
+    }
+
+    /// How its paper twin filled
+    pub fn record_paper_fill(&mut self, trade_id: &str, filled_fraction: f64, slippage_bps: f64) {
+        let Some(order) = self.orders.iter_mut().find(|o| o.trade_id == trade_id) else {
+            return;
+        };
+        order.paper_filled_fraction = Some(filled_fraction.clamp(0.0, 1.0));
+        order.paper_slippage_bps = Some(slippage_bps.max(0.0));
+        self.persist();
+    }
+
+    /// `realized_pnl_of` gives the journal PnL of a trade id's closes (None while still open)
+    pub fn report(&self, realized_pnl_of: impl Fn(&str) -> Option<f64>) -> DriftReport {
+        let checked: Vec<&ShadowOrder> = self.orders.iter().filter(|o| o.is_checked()).collect();
+        let mut report = DriftReport {
+            orders: self.orders.len(),
+            checked: checked.len(),
+            recent: self
+                .orders
+                .iter()
+                .rev()
+                .take(RECENT_ORDERS)
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        if checked.is_empty() {
+            return report;
+        }
+
+        let n = checked.len() as f64;
+        let mean = |of: fn(&ShadowOrder) -> Option<f64>| {
+            checked.iter().filter_map(|o| of(o)).sum::<f64>() / n
+        };
+        report.paper_fill_rate = mean(|o| o.paper_filled_fraction);
+        report.live_fill_rate = mean(|o| o.filled_fraction);
+        report.fill_rate_gap = report.paper_fill_rate - report.live_fill_rate;
+        report.paper_slippage_bps = mean(|o| o.paper_slippage_bps);
+        report.live_slippage_bps = mean(|o| o.slippage_bps);
+        report.slippage_gap_bps = report.live_slippage_bps - report.paper_slippage_bps;
+
+        for order in &checked {
+            let Some(pnl) = realized_pnl_of(&order.trade_id) else {
+                continue;
+            };
+            report.settled += 1;
+            report.paper_pnl += pnl * order.paper_filled_fraction.unwrap_or(0.0);
+            report.live_pnl += pnl * order.filled_fraction.unwrap_or(0.0);
+        }
+        report.pnl_gap = report.paper_pnl - report.live_pnl;
+        report.live_to_paper_ratio =
+            (report.paper_pnl > 0.0).then(|| report.live_pnl / report.paper_pnl);
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> DriftTracker {
+        DriftTracker {
+            path: None,
+            orders: VecDeque::new(),
+        }
+    }
+
+    #[test]
+    fn test_gaps_between_paper_and_live() {
+        let mut drift = tracker();
+        let now = Utc::now();
+        drift.record_order("arb_m1_1_YES", "m1", "YES", 0.4, 10.0, now);
+        drift.record_order("arb_m1_1_NO", "m1", "NO", 0.5, 10.0, now);
+        drift.record_order("snipe_m2_1", "m2", "YES", 0.9, 20.0, now);
+        drift.record_order("snipe_m3_1", "m3", "NO", 0.9, 20.0, now);
+        assert_eq!(drift.report(|_| None).checked, 0);
+
+        drift.record_fill("arb_m1_1_YES", 1.0, 0.0);
+        drift.record_fill("arb_m1_1_NO", 0.5, 0.0);
+        // The book ran 60 bps away before the snipe could fill
+        drift.record_fill("snipe_m2_1", 0.0, 60.0);
+        // Not compared until the paper twin is checked too
+        assert_eq!(drift.report(|_| None).checked, 0);
+        for trade_id in ["arb_m1_1_YES", "arb_m1_1_NO", "snipe_m2_1"] {
+            drift.record_paper_fill(trade_id, 1.0, 0.0);
+        }
+        drift.record_fill("snipe_m3_1", 1.0, 0.0);
+
+        let report = drift.report(|id| match id {
+            "arb_m1_1_YES" => Some(2.0),
+            "arb_m1_1_NO" => Some(1.0),
+            "snipe_m2_1" => Some(4.0),
+            _ => None,
+        });
+        assert_eq!((report.orders, report.checked, report.settled), (4, 3, 3));
+        assert!((report.live_fill_rate - 0.5).abs() < 1e-9);
+        assert!((report.fill_rate_gap - 0.5).abs() < 1e-9);
+        assert!((report.slippage_gap_bps - 20.0).abs() < 1e-9);
+        assert!((report.paper_pnl - 7.0).abs() < 1e-9);
+        assert!((report.live_pnl - 2.5).abs() < 1e-9);
+        assert!((report.live_to_paper_ratio.unwrap() - 2.5 / 7.0).abs() < 1e-9);
+        assert_eq!(report.recent[0].trade_id, "snipe_m3_1");
+    }
+}
@@ -0,0 +1,400 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::execution::ExecutionReport;
+use crate::telemetry::Percentiles;
+
+pub const EDGE_REALIZATION_FILE: &str = "edge_realization.json";
+
+/// Settled trades included in the report
+const RECENT_TRADES: usize = 20;
+
+/// One arbitrage leg as submitted, plus how much of it filled
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LegFill {
+    pub side: String,
+    pub order_id: Option<String>,
+    pub limit_price: f64,
+    pub size_usd: f64,
+    /// Share of the leg matched (0-1). None until checked
+    pub filled_fraction: Option<f64>,
+}
+
+impl LegFill {
+    /// Still worth asking the exchange about
+    pub fn needs_check(&self) -> bool {
+        self.order_id.is_some() && self.filled_fraction.is_none_or(|f| f < 1.0)
+    }
+
+    fn filled_usd(&self) -> f64 {
+        self.size_usd * self.filled_fraction.unwrap_or(0.0)
+    }
+
+    fn filled_shares(&self) -> f64 {
+        if self.limit_price > 0.0 {
+            self.filled_usd() / self.limit_price
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A placed leg to ask the exchange about
+#[derive(Debug, Clone)]
+pub struct FillCheck {
+    pub trade_id: String,
+    pub market_id: String,
+    pub side: String,
+    pub order_id: String,
+}
+
+/// Expected edge at signal time vs what the trade actually made
+/// Both are bps of the planned pair payout ($1 per YES+NO pair), so they compare directly:
+/// a trade that fills both legs in full at its limits and settles at $1 per pair realizes
+/// its expected edge, anything less is execution shortfall
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EdgeRealization {
+    pub trade_id: String,
+    pub market_id: String,
+    pub signal_at: DateTime<Utc>,
+    /// Net edge after fees the strategy saw
+    pub expected_profit_bps: i32,
+    /// Fee per filled share of each leg, in bps of the $1 payout (the strategy's convention)
+    pub fee_per_leg_bps: i32,
+    pub legs: Vec<LegFill>,
+    pub settled_at: Option<DateTime<Utc>>,
+    pub realized_pnl_usd: Option<f64>,
+    pub realized_bps: Option<f64>,
+}
+
+impl EdgeRealization {
+    pub fn from_report(
+        market_id: &str,
+        signal_at: DateTime<Utc>,
+        expected_profit_bps: i32,
+        fee_per_leg_bps: i32,
+        report: &ExecutionReport,
+    ) -> Self {
+        Self {
+            trade_id: report.plan_id.clone(),
+            market_id: market_id.to_string(),
+            signal_at,
+            expected_profit_bps,
+            fee_per_leg_bps,
+            legs: report
+                .legs
+                .iter()
+                .map(|leg| {
+                    let placed = leg.is_placed();
+                    LegFill {
+                        side: leg.side.clone(),
+                        order_id: leg.order_id.clone().map(String::from).filter(|_| placed),
+                        limit_price: leg.price,
+                        size_usd: leg.size_usd,
+                        // Failed or unwound legs never fill
+                        filled_fraction: (!placed).then_some(0.0),
+                    }
+                })
+                .collect(),
+            settled_at: None,
+            realized_pnl_usd: None,
+            realized_bps: None,
+        }
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.settled_at.is_some()
+    }
+
+    pub fn fully_filled(&self) -> bool {
+        self.legs
+            .iter()
+            .all(|l| l.filled_fraction.is_some_and(|f| f >= 1.0))
+    }
+
+    /// $1 pair payouts the plan was sized for
+    pub fn planned_pairs(&self) -> f64 {
+        let cost: f64 = self.legs.iter().map(|l| l.limit_price).sum();
+        let size: f64 = self.legs.iter().map(|l| l.size_usd).sum();
+        if cost > 0.0 {
+            size / cost
+        } else {
+            0.0
+        }
+    }
+
+    /// Book the outcome. `yes_value` / `no_value` are what one share of each side paid out
+    /// (1/0 on resolution, 1 per merged pair, or the price it was sold at)
+    pub fn settle(&mut self, yes_value: f64, no_value: f64, now: DateTime<Utc>) {
+        let cost: f64 = self.legs.iter().map(|l| l.filled_usd()).sum();
+        let shares: f64 = self.legs.iter().map(|l| l.filled_shares()).sum();
+        let fees = shares * self.fee_per_leg_bps as f64 / 10000.0;
+        let payout: f64 = self
+            .legs
+            .iter()
+            .map(|l| {
+                let value = if l.side == "YES" { yes_value } else { no_value };
+                l.filled_shares() * value
+            })
+            .sum();
+
+        let pnl = payout - cost - fees;
+        let pairs = self.planned_pairs();
+        self.realized_pnl_usd = Some(pnl);
+        self.realized_bps = Some(if pairs > 0.0 {
+            pnl / pairs * 10000.0
+        } else {
+            0.0
+        });
+        self.settled_at = Some(now);
+    }
+
+    /// Expected minus realized edge (positive = the trade made less than the signal promised)
+    pub fn shortfall_bps(&self) -> Option<f64> {
+        self.realized_bps
+            .map(|realized| self.expected_profit_bps as f64 - realized)
+    }
+
+    /// Expected minus realized PnL in USD, once settled
+    pub fn shortfall_usd(&self) -> Option<f64> {
+        let expected = self.expected_profit_bps as f64 / 10000.0 * self.planned_pairs();
+        self.realized_pnl_usd.map(|realized| expected - realized)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct EdgeDecayReport {
+    pub open: usize,
+    pub settled: usize,
+    /// Settled trades where every leg filled in full
+    pub fully_filled_rate: f64,
+    pub mean_expected_bps: f64,
+    pub mean_realized_bps: f64,
+    pub mean_shortfall_bps: f64,
+    pub p50_shortfall_bps: f64,
+    pub p90_shortfall_bps: f64,
+    /// Share of the expected edge that was kept on average
+    pub capture_ratio: f64,
+    pub recent: Vec<EdgeRealization>,
+}
+
+/// Expected vs realized edge for every executed arbitrage, persisted in
+/// `data_dir/edge_realization.json` so trades settle across restarts
+pub struct EdgeRealizationTracker {
+    path: PathBuf,
+    trades: Vec<EdgeRealization>,
+}
+
+impl EdgeRealizationTracker {
+    /// Load recorded trades from `data_dir` (empty if none were recorded yet)
+    pub fn load(data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(EDGE_REALIZATION_FILE);
+        let trades = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self { path, trades }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.trades)?)?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("Failed to write {:?}", self.path))?;
+        Ok(())
+    }
+
+    pub fn open(&mut self, trade: EdgeRealization) {
+        self.trades.push(trade);
+        self.persist();
+    }
+
+    /// Legs of open trades whose fill isn't final yet
+    pub fn pending_fill_checks(&self) -> Vec<FillCheck> {
+        self.trades
+            .iter()
+            .filter(|t| !t.is_settled())
+            .flat_map(|t| {
+                t.legs.iter().filter(|l| l.needs_check()).filter_map(|l| {
+                    l.order_id.clone().map(|order_id| FillCheck {
+                        trade_id: t.trade_id.clone(),
+                        market_id: t.market_id.clone(),
+                        side: l.side.clone(),
+                        order_id,
+                    })
+                })
+            })
+            .collect()
+    }
+
+    pub fn record_fill(&mut self, trade_id: &str, side: &str, filled_fraction: f64) {
+        let leg = self
+            .trades
+            .iter_mut()
+            .filter(|t| t.trade_id == trade_id)
+            .flat_map(|t| t.legs.iter_mut())
+            .find(|l| l.side == side);
+        if let Some(leg) = leg {
+            leg.filled_fraction = Some(filled_fraction.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Settle every open trade in a market. Returns how many were settled
+    pub fn settle_market(
+        &mut self,
+        market_id: &str,
+        yes_value: f64,
+        no_value: f64,
+        now: DateTime<Utc>,
+    ) -> usize {
+        let mut settled = 0;
+        for trade in self
+            .trades
+            .iter_mut()
+            .filter(|t| t.market_id == market_id && !t.is_settled())
+        {
+            trade.settle(yes_value, no_value, now);
+            info!(
+                "📐 Edge realized for {}: expected {} bps, realized {:.1} bps (shortfall {:.1} bps)",
+                trade.trade_id,
+                trade.expected_profit_bps,
+                trade.realized_bps.unwrap_or(0.0),
+                trade.shortfall_bps().unwrap_or(0.0)
+            );
+            settled += 1;
+        }
+        if settled > 0 {
+            self.persist();
+        }
+        settled
+    }
+
+    pub fn settled(&self) -> impl Iterator<Item = &EdgeRealization> {
+        self.trades.iter().filter(|t| t.is_settled())
+    }
+
+    /// Whether a journal trade is a leg of a recorded arbitrage (`<plan id>_<side>`)
+    pub fn covers(&self, trade_id: &str) -> bool {
+        self.trades.iter().any(|t| {
+            trade_id
+                .strip_prefix(t.trade_id.as_str())
+                .is_some_and(|rest| rest.starts_with('_'))
+        })
+    }
+
+    /// Total shortfall (USD) of a market's settled trades
+    pub fn market_shortfall_usd(&self, market_id: &str) -> f64 {
+        self.trades
+            .iter()
+            .filter(|t| t.market_id == market_id)
+            .filter_map(|t| t.shortfall_usd())
+            .sum()
+    }
+
+    pub fn report(&self) -> EdgeDecayReport {
+        let settled: Vec<&EdgeRealization> =
+            self.trades.iter().filter(|t| t.is_settled()).collect();
+        if settled.is_empty() {
+            return EdgeDecayReport {
+                open: self.trades.len(),
+                ..Default::default()
+            };
+        }
+
+        let n = settled.len() as f64;
+        let mean_expected_bps = settled
+            .iter()
+            .map(|t| t.expected_profit_bps as f64)
+            .sum::<f64>()
+            / n;
+        let mean_realized_bps = settled
+            .iter()
+            .map(|t| t.realized_bps.unwrap_or(0.0))
+            .sum::<f64>()
+            / n;
+
+        let shortfalls =
+            Percentiles::from_samples(settled.iter().filter_map(|t| t.shortfall_bps()).collect());
+
+        EdgeDecayReport {
+            open: self.trades.len() - settled.len(),
+            settled: settled.len(),
+            fully_filled_rate: settled.iter().filter(|t| t.fully_filled()).count() as f64 / n,
+            mean_expected_bps,
+            mean_realized_bps,
+            mean_shortfall_bps: mean_expected_bps - mean_realized_bps,
+            p50_shortfall_bps: shortfalls.p50,
+            p90_shortfall_bps: shortfalls.p90,
+            capture_ratio: if mean_expected_bps > 0.0 {
+                mean_realized_bps / mean_expected_bps
+            } else {
+                0.0
+            },
+            recent: settled
+                .iter()
+                .rev()
+                .take(RECENT_TRADES)
+                .map(|t| (*t).clone())
+                .collect(),
+        }
+    }
+
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to persist edge realization: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(yes_fill: f64, no_fill: f64) -> EdgeRealization {
+        let leg = |side: &str, price: f64, fill: f64| LegFill {
+            side: side.to_string(),
+            order_id: Some(format!("o-{}", side)),
+            limit_price: price,
+            // Equal share counts on both legs
+            size_usd: 100.0 * price,
+            filled_fraction: Some(fill),
+        };
+        EdgeRealization {
+            trade_id: "arb_m1".to_string(),
+            market_id: "m1".to_string(),
+            signal_at: Utc::now(),
+            // 1 - 0.97 = 300 bps, less 2 x 40 bps fees
+            expected_profit_bps: 220,
+            fee_per_leg_bps: 40,
+            legs: vec![leg("YES", 0.47, yes_fill), leg("NO", 0.50, no_fill)],
+            settled_at: None,
+            realized_pnl_usd: None,
+            realized_bps: None,
+        }
+    }
+
+    #[test]
+    fn test_full_fill_realizes_expected_edge() {
+        let mut full = trade(1.0, 1.0);
+        full.settle(1.0, 0.0, Utc::now());
+        assert!(full.fully_filled());
+        assert!((full.realized_bps.unwrap() - 220.0).abs() < 1e-6);
+        assert!(full.shortfall_bps().unwrap().abs() < 1e-6);
+
+        // Only YES filled and it lost: the whole leg is gone
+        let mut legged = trade(1.0, 0.0);
+        legged.settle(0.0, 1.0, Utc::now());
+        assert!(legged.realized_bps.unwrap() < -4000.0);
+        assert!(legged.shortfall_bps().unwrap() > 4000.0);
+    }
+}
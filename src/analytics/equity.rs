@@ -0,0 +1,168 @@
+//! Retention of the equity curve
+//!
+//! A portfolio snapshot is taken every 10 seconds for as long as the agent runs. The last
+//! `SNAPSHOT_FULL_RESOLUTION_HOURS` are kept as taken, the `SNAPSHOT_MINUTE_BARS_DAYS` before
+//! that as 1-minute bars and anything older as hourly bars, in memory and in the database. A
+//! bar is the last snapshot of its period, the value a resampled curve carries forward, so the
+//! curve the risk metrics are computed on doesn't move when its resampling period is a
+//! multiple of the bar's (dips inside a bar are what's lost).
+use chrono::{DateTime, Duration, Utc};
+
+use super::pnl::PortfolioSnapshot;
+use crate::config::PerformanceConfig;
+
+const MINUTE_SECS: i64 = 60;
+const HOUR_SECS: i64 = 3600;
+
+/// How long each resolution of the equity curve is kept
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapshotRetention {
+    /// Snapshots younger than this are kept as taken
+    pub full: Duration,
+    /// Then 1-minute bars for this long; hourly bars beyond
+    pub minute_bars: Duration,
+}
+
+impl SnapshotRetention {
+    pub fn from_config(config: &PerformanceConfig) -> Self {
+        Self {
+            full: Duration::hours(config.snapshot_full_hours as i64),
+            minute_bars: Duration::days(config.snapshot_minute_days as i64),
+        }
+    }
+
+    /// Bar a snapshot taken `at` belongs to, as (period, start) seconds. None while it is
+    /// kept at full resolution
+    fn bar(&self, at: DateTime<Utc>, now: DateTime<Utc>) -> Option<(i64, i64)> {
+        let age = now - at;
+        if age < self.full {
+            return None;
+        }
+        let period = if age < self.full + self.minute_bars {
+            MINUTE_SECS
+        } else {
+            HOUR_SECS
+        };
+        Some((period, at.timestamp().div_euclid(period) * period))
+    }
+}
+
+/// Which of `timestamps` (oldest first) survive compaction: those at full resolution, and the
+/// last of each bar. Compacting a compacted series keeps it as is
+pub fn retained(
+    timestamps: &[DateTime<Utc>],
+    now: DateTime<Utc>,
+    retention: &SnapshotRetention,
+) -> Vec<bool> {
+    let bars: Vec<Option<(i64, i64)>> = timestamps
+        .iter()
+        .map(|at| retention.bar(*at, now))
+        .collect();
+    bars.iter()
+        .enumerate()
+        .map(|(i, bar)| bar.is_none() || bars.get(i + 1) != Some(bar))
+        .collect()
+}
+
+/// Downsample `snapshots` (oldest first) in place. Returns how many were dropped
+pub fn compact(
+    snapshots: &mut Vec<PortfolioSnapshot>,
+    now: DateTime<Utc>,
+    retention: &SnapshotRetention,
+) -> usize {
+    // Everything at full resolution stays: only the older prefix needs looking at
+    let cutoff = now - retention.full;
+    let older = snapshots.partition_point(|s| s.timestamp < cutoff);
+    let timestamps: Vec<DateTime<Utc>> = snapshots[..older].iter().map(|s| s.timestamp).collect();
+    let keep = retained(&timestamps, now, retention);
+    let before = snapshots.len();
+    let mut index = 0;
+    snapshots.retain(|_| {
+        let kept = keep.get(index).copied().unwrap_or(true);
+        index += 1;
+        kept
+    });
+    before - snapshots.len()
+}
+
+/// The snapshots taken since `since` (all of them for None), without copying the rest
+pub fn since(
+    snapshots: &[PortfolioSnapshot],
+    since: Option<DateTime<Utc>>,
+) -> &[PortfolioSnapshot] {
+    match since {
+        Some(since) => &snapshots[snapshots.partition_point(|s| s.timestamp < since)..],
+        None => snapshots,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn snapshot(at: DateTime<Utc>, value: f64) -> PortfolioSnapshot {
+        PortfolioSnapshot {
+            timestamp: at,
+            total_value: value,
+            cash: value,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_compaction_keeps_the_last_snapshot_of_each_bar() {
+        let retention = SnapshotRetention {
+            full: Duration::hours(24),
+            minute_bars: Duration::days(7),
+        };
+        let now = Utc.with_ymd_and_hms(2026, 3, 20, 12, 0, 0).unwrap();
+        // Every 10s over the last 9 days
+        let start = now - Duration::days(9);
+        let mut snapshots: Vec<PortfolioSnapshot> = (0..9 * 24 * 360)
+            .map(|i| snapshot(start + Duration::seconds(10 * i), i as f64))
+            .collect();
+        let total = snapshots.len();
+        let recent = |snapshots: &[PortfolioSnapshot]| {
+            snapshots
+                .iter()
+                .filter(|s| now - s.timestamp < Duration::hours(24))
+                .count()
+        };
+        let full_resolution = recent(&snapshots);
+
+        let removed = compact(&mut snapshots, now, &retention);
+        assert_eq!(snapshots.len(), total - removed);
+        assert_eq!(recent(&snapshots), full_resolution);
+        // Snapshots aged [from, to)
+        let count = |from: Duration, to: Duration| {
+            snapshots
+                .iter()
+                .filter(|s| now - s.timestamp >= from && now - s.timestamp < to)
+                .count()
+        };
+        // One per minute, then one per hour
+        let minutes = count(Duration::hours(25), Duration::hours(191));
+        assert_eq!(minutes, 166 * 60);
+        let hours = count(
+            Duration::hours(193),
+            Duration::days(9) + Duration::seconds(1),
+        );
+        assert_eq!(hours, 23);
+
+        // A bar holds its period's closing value
+        let hourly = snapshots.first().unwrap();
+        assert_eq!(hourly.timestamp, start + Duration::seconds(3590));
+        assert_eq!(hourly.total_value, 359.0);
+        assert!(snapshots
+            .windows(2)
+            .all(|w| w[0].timestamp < w[1].timestamp));
+
+        // Idempotent, and the window lookup only slices
+        assert_eq!(compact(&mut snapshots, now, &retention), 0);
+        let recent = since(&snapshots, Some(now - Duration::hours(1)));
+        assert_eq!(recent.len(), 360);
+        assert_eq!(since(&snapshots, None).len(), snapshots.len());
+    }
+}
@@ -0,0 +1,272 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+use crate::telemetry::Percentiles;
+
+pub const EXECUTION_DRIFT_FILE: &str = "execution_drift.jsonl";
+
+/// Upper bounds (ms) of the signal -> post latency buckets; the last bucket is open-ended
+const BUCKET_BOUNDS_MS: [f64; 6] = [5.0, 10.0, 25.0, 50.0, 100.0, 250.0];
+
+/// Samples kept in memory for the report (oldest evicted first)
+const MAX_SAMPLES: usize = 10_000;
+
+/// Most recent samples included in the report
+const RECENT_SAMPLES: usize = 20;
+
+/// Best ask of the traded side when the signal fired and when its order was posted
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DriftSample {
+    pub trade_id: String,
+    pub market_id: String,
+    pub side: String,
+    pub signal_at: DateTime<Utc>,
+    pub signal_ask: f64,
+    /// Best ask on the CLOB WS as the order went out (before it could take any of the book)
+    pub post_ask: f64,
+    /// Signal -> order post, monotonic clock
+    pub latency_us: u64,
+}
+
+impl DriftSample {
+    /// How far the ask moved against us, in bps of the signal ask (positive = edge lost)
+    pub fn drift_bps(&self) -> f64 {
+        if self.signal_ask > 0.0 {
+            (self.post_ask - self.signal_ask) / self.signal_ask * 10000.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn latency_ms(&self) -> f64 {
+        self.latency_us as f64 / 1000.0
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct LatencyBucket {
+    /// Latency range, e.g. "10-25ms" or ">250ms"
+    pub label: String,
+    pub samples: usize,
+    pub mean_latency_ms: f64,
+    pub mean_drift_bps: f64,
+    pub p90_drift_bps: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionDriftReport {
+    pub samples: usize,
+    pub mean_latency_ms: f64,
+    pub mean_drift_bps: f64,
+    /// Least-squares slope of drift over latency: edge lost per millisecond of signal -> post
+    /// latency. None until the samples span more than one latency
+    pub drift_bps_per_ms: Option<f64>,
+    /// Non-empty buckets, fastest first
+    pub buckets: Vec<LatencyBucket>,
+    pub recent: Vec<DriftSample>,
+}
+
+/// Measures how much of the ask a signal saw is gone by the time its order is posted, per
+/// latency bucket. Samples are appended to `data_dir/execution_drift.jsonl`
+pub struct ExecutionDriftTracker {
+    samples: VecDeque<DriftSample>,
+    path: Option<PathBuf>,
+}
+
+impl ExecutionDriftTracker {
+    /// Samples are appended to `data_dir/execution_drift.jsonl` when a data dir is given
+    pub fn new(data_dir: Option<&str>) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            path: data_dir.map(execution_drift_path),
+        }
+    }
+
+    /// In-memory tracker holding the samples another instance logged to `data_dir`
+    /// (observer mode; nothing is written back)
+    pub fn load(data_dir: &str) -> Self {
+        let mut tracker = Self::new(None);
+        let raw = fs::read_to_string(execution_drift_path(data_dir)).unwrap_or_default();
+        for sample in raw
+            .lines()
+            .filter_map(|line| serde_json::from_str::<DriftSample>(line).ok())
+        {
+            tracker.push(sample);
+        }
+        tracker
+    }
+
+    pub fn record(&mut self, sample: DriftSample) {
+        debug!(
+            "⏱️ Signal -> post drift for {} {}: {:.4} -> {:.4} ({:+.1} bps) in {:.1}ms",
+            sample.trade_id,
+            sample.side,
+            sample.signal_ask,
+            sample.post_ask,
+            sample.drift_bps(),
+            sample.latency_ms()
+        );
+        if let Some(path) = &self.path {
+            if let Err(e) = append_sample(path, &sample) {
+                warn!("⚠️ Failed to persist execution drift: {}", e);
+            }
+        }
+        self.push(sample);
+    }
+
+    /// Largest drift recorded for a trade, or for any leg of it (`<trade id>_<side>`)
+    pub fn worst_drift_bps(&self, trade_id: &str) -> Option<f64> {
+        self.samples
+            .iter()
+            .filter(|s| {
+                s.trade_id
+                    .strip_prefix(trade_id)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+            })
+            .map(DriftSample::drift_bps)
+            .reduce(f64::max)
+    }
+
+    /// Signal -> post latencies of the samples held, oldest first
+    pub fn latencies_ms(&self) -> Vec<f64> {
+        self.samples.iter().map(DriftSample::latency_ms).collect()
+    }
+
+    pub fn report(&self) -> ExecutionDriftReport {
+        if self.samples.is_empty() {
+            return ExecutionDriftReport::default();
+        }
+        let n = self.samples.len() as f64;
+        let mean_latency_ms = self.samples.iter().map(|s| s.latency_ms()).sum::<f64>() / n;
+        let mean_drift_bps = self.samples.iter().map(|s| s.drift_bps()).sum::<f64>() / n;
+
+        let covariance: f64 = self
+            .samples
+            .iter()
+            .map(|s| (s.latency_ms() - mean_latency_ms) * (s.drift_bps() - mean_drift_bps))
+            .sum();
+        let variance: f64 = self
+            .samples
+            .iter()
+            .map(|s| (s.latency_ms() - mean_latency_ms).powi(2))
+            .sum();
+
+        let mut buckets: Vec<Vec<&DriftSample>> = vec![Vec::new(); BUCKET_BOUNDS_MS.len() + 1];
+        for sample in &self.samples {
+            let index = BUCKET_BOUNDS_MS
+                .iter()
+                .position(|bound| sample.latency_ms() < *bound)
+                .unwrap_or(BUCKET_BOUNDS_MS.len());
+            buckets[index].push(sample);
+        }
+
+        ExecutionDriftReport {
+            samples: self.samples.len(),
+            mean_latency_ms,
+            mean_drift_bps,
+            drift_bps_per_ms: (variance > 0.0).then(|| covariance / variance),
+            buckets: buckets
+                .iter()
+                .enumerate()
+                .filter(|(_, samples)| !samples.is_empty())
+                .map(|(index, samples)| bucket(index, samples))
+                .collect(),
+            recent: self
+                .samples
+                .iter()
+                .rev()
+                .take(RECENT_SAMPLES)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn push(&mut self, sample: DriftSample) {
+        if self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+fn bucket(index: usize, samples: &[&DriftSample]) -> LatencyBucket {
+    let label = match index {
+        0 => format!("<{}ms", BUCKET_BOUNDS_MS[0]),
+        i if i == BUCKET_BOUNDS_MS.len() => format!(">{}ms", BUCKET_BOUNDS_MS[i - 1]),
+        i => format!("{}-{}ms", BUCKET_BOUNDS_MS[i - 1], BUCKET_BOUNDS_MS[i]),
+    };
+    let n = samples.len() as f64;
+    let drifts: Vec<f64> = samples.iter().map(|s| s.drift_bps()).collect();
+
+    LatencyBucket {
+        label,
+        samples: samples.len(),
+        mean_latency_ms: samples.iter().map(|s| s.latency_ms()).sum::<f64>() / n,
+        mean_drift_bps: drifts.iter().sum::<f64>() / n,
+        p90_drift_bps: Percentiles::from_samples(drifts).p90,
+    }
+}
+
+fn append_sample(path: &Path, sample: &DriftSample) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(sample)?)?;
+    Ok(())
+}
+
+/// Path of the execution drift log inside a data dir
+pub fn execution_drift_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(EXECUTION_DRIFT_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latency_us: u64, post_ask: f64) -> DriftSample {
+        DriftSample {
+            trade_id: "snipe_m1".to_string(),
+            market_id: "m1".to_string(),
+            side: "YES".to_string(),
+            signal_at: Utc::now(),
+            signal_ask: 0.50,
+            post_ask,
+            latency_us,
+        }
+    }
+
+    #[test]
+    fn test_drift_by_latency_bucket() {
+        let dir = std::env::temp_dir().join(format!("execution-drift-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let mut tracker = ExecutionDriftTracker::new(Some(data_dir));
+        assert_eq!(tracker.report().samples, 0);
+
+        // 2ms: unchanged, 20ms: +1 tick (200 bps), 40ms: +2 ticks
+        tracker.record(sample(2_000, 0.50));
+        tracker.record(sample(20_000, 0.51));
+        tracker.record(sample(40_000, 0.52));
+
+        let report = tracker.report();
+        assert_eq!(report.samples, 3);
+        assert!((report.mean_drift_bps - 200.0).abs() < 1e-6);
+        assert!((report.drift_bps_per_ms.unwrap() - 10.5).abs() < 0.1);
+        let labels: Vec<&str> = report.buckets.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["<5ms", "10-25ms", "25-50ms"]);
+        assert!((report.buckets[1].mean_drift_bps - 200.0).abs() < 1e-6);
+
+        // The observer reads the same samples back
+        let loaded = ExecutionDriftTracker::load(data_dir);
+        assert_eq!(loaded.report().samples, 3);
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,282 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::telemetry::Percentiles;
+
+pub const INDEXING_LATENCY_FILE: &str = "indexing_latency.jsonl";
+
+/// Markets kept in memory for the distributions (oldest evicted first)
+const MAX_TRACKED_MARKETS: usize = 1000;
+
+/// Most recent markets included in the report
+const RECENT_MARKETS: usize = 20;
+
+/// Lifecycle timestamps of one on-chain-detected market
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarketIndexing {
+    pub market_id: String,
+    /// ConditionPreparation seen on-chain
+    pub detected_at: DateTime<Utc>,
+    /// Gamma first returned the market's metadata
+    pub gamma_available_at: Option<DateTime<Utc>>,
+    /// First CLOB book snapshot for any of its assets
+    pub first_book_at: Option<DateTime<Utc>>,
+    /// First book with a resting ask (something to buy)
+    pub first_quote_at: Option<DateTime<Utc>>,
+}
+
+impl MarketIndexing {
+    /// On-chain detection -> Gamma availability
+    pub fn gamma_lag_ms(&self) -> Option<i64> {
+        self.gamma_available_at
+            .map(|t| (t - self.detected_at).num_milliseconds())
+    }
+
+    /// First book snapshot -> first tradeable quote
+    pub fn book_to_quote_ms(&self) -> Option<i64> {
+        match (self.first_book_at, self.first_quote_at) {
+            (Some(book), Some(quote)) => Some((quote - book).num_milliseconds()),
+            _ => None,
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.gamma_available_at.is_some() && self.first_quote_at.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct LatencyDistribution {
+    pub count: usize,
+    pub min_ms: i64,
+    pub mean_ms: f64,
+    pub p50_ms: i64,
+    pub p90_ms: i64,
+    pub p99_ms: i64,
+    pub max_ms: i64,
+}
+
+impl LatencyDistribution {
+    pub fn from_samples(samples: Vec<i64>) -> Self {
+        let (Some(&min_ms), Some(&max_ms)) = (samples.iter().min(), samples.iter().max()) else {
+            return Self::default();
+        };
+        let mean_ms = samples.iter().sum::<i64>() as f64 / samples.len() as f64;
+        // Whole milliseconds: exact as f64, and the percentiles are samples
+        let pct = Percentiles::from_samples(samples.iter().map(|&ms| ms as f64).collect());
+
+        Self {
+            count: samples.len(),
+            min_ms,
+            mean_ms,
+            p50_ms: pct.p50 as i64,
+            p90_ms: pct.p90 as i64,
+            p99_ms: pct.p99 as i64,
+            max_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IndexingLatencyReport {
+    pub markets_tracked: usize,
+    /// Markets detected on-chain that Gamma hasn't returned yet
+    pub awaiting_gamma: usize,
+    pub gamma_lag: LatencyDistribution,
+    pub book_to_quote: LatencyDistribution,
+    pub recent: Vec<MarketIndexing>,
+}
+
+/// Measures how long new markets take to become usable after on-chain creation
+/// Feeds the tuning of the Gamma retry loop
+pub struct IndexingLatencyTracker {
+    markets: HashMap<String, MarketIndexing>,
+    order: VecDeque<String>,
+    path: Option<PathBuf>,
+}
+
+impl IndexingLatencyTracker {
+    /// Completed records are appended to `data_dir/indexing_latency.jsonl` when a data dir is given
+    pub fn new(data_dir: Option<&str>) -> Self {
+        let path = data_dir.map(indexing_latency_path);
+        if let Some(dir) = data_dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warn!("⚠️ Failed to create data dir {}: {}", dir, e);
+            }
+        }
+
+        Self {
+            markets: HashMap::new(),
+            order: VecDeque::new(),
+            path,
+        }
+    }
+
+    /// In-memory tracker holding the completed records another instance logged to `data_dir`
+    /// (observer mode; nothing is written back)
+    pub fn load_completed(data_dir: &str) -> Self {
+        let mut tracker = Self::new(None);
+        let raw = fs::read_to_string(indexing_latency_path(data_dir)).unwrap_or_default();
+        for entry in raw
+            .lines()
+            .filter_map(|line| serde_json::from_str::<MarketIndexing>(line).ok())
+        {
+            if tracker.order.len() >= MAX_TRACKED_MARKETS {
+                if let Some(oldest) = tracker.order.pop_front() {
+                    tracker.markets.remove(&oldest);
+                }
+            }
+            if !tracker.markets.contains_key(&entry.market_id) {
+                tracker.order.push_back(entry.market_id.clone());
+            }
+            tracker.markets.insert(entry.market_id.clone(), entry);
+        }
+        tracker
+    }
+
+    pub fn mark_detected(&mut self, market_id: &str) {
+        if self.markets.contains_key(market_id) {
+            return;
+        }
+        if self.order.len() >= MAX_TRACKED_MARKETS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.markets.remove(&oldest);
+            }
+        }
+
+        self.order.push_back(market_id.to_string());
+        self.markets.insert(
+            market_id.to_string(),
+            MarketIndexing {
+                market_id: market_id.to_string(),
+                detected_at: Utc::now(),
+                gamma_available_at: None,
+                first_book_at: None,
+                first_quote_at: None,
+            },
+        );
+    }
+
+    /// Gamma returned metadata. Ignored for markets not detected on-chain
+    pub fn mark_gamma_available(&mut self, market_id: &str) {
+        let Some(entry) = self.markets.get_mut(market_id) else {
+            return;
+        };
+        if entry.gamma_available_at.is_some() {
+            return;
+        }
+        entry.gamma_available_at = Some(Utc::now());
+        info!(
+            "⏱️ Gamma indexed {} {}ms after on-chain creation",
+            market_id,
+            entry.gamma_lag_ms().unwrap_or(0)
+        );
+        self.persist_if_complete(market_id);
+    }
+
+    /// A book snapshot arrived; `has_quote` when it carries at least one ask
+    pub fn mark_book(&mut self, market_id: &str, has_quote: bool) {
+        let Some(entry) = self.markets.get_mut(market_id) else {
+            return;
+        };
+        if entry.first_quote_at.is_some() {
+            return;
+        }
+
+        let now = Utc::now();
+        entry.first_book_at.get_or_insert(now);
+        if has_quote {
+            entry.first_quote_at = Some(now);
+            self.persist_if_complete(market_id);
+        }
+    }
+
+    pub fn report(&self) -> IndexingLatencyReport {
+        let entries: Vec<&MarketIndexing> = self
+            .order
+            .iter()
+            .filter_map(|id| self.markets.get(id))
+            .collect();
+
+        IndexingLatencyReport {
+            markets_tracked: entries.len(),
+            awaiting_gamma: entries
+                .iter()
+                .filter(|e| e.gamma_available_at.is_none())
+                .count(),
+            gamma_lag: LatencyDistribution::from_samples(
+                entries.iter().filter_map(|e| e.gamma_lag_ms()).collect(),
+            ),
+            book_to_quote: LatencyDistribution::from_samples(
+                entries.iter().filter_map(|e| e.book_to_quote_ms()).collect(),
+            ),
+            recent: entries
+                .iter()
+                .rev()
+                .take(RECENT_MARKETS)
+                .map(|e| (*e).clone())
+                .collect(),
+        }
+    }
+
+    fn persist_if_complete(&self, market_id: &str) {
+        let (Some(path), Some(entry)) = (&self.path, self.markets.get(market_id)) else {
+            return;
+        };
+        if !entry.is_complete() {
+            return;
+        }
+        if let Err(e) = append_record(path, entry) {
+            warn!("⚠️ Failed to persist indexing latency for {}: {}", market_id, e);
+        }
+    }
+}
+
+fn append_record(path: &Path, entry: &MarketIndexing) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Path of the indexing latency log inside a data dir
+pub fn indexing_latency_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(INDEXING_LATENCY_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_percentiles() {
+        let dist = LatencyDistribution::from_samples((1..=100).collect());
+        assert_eq!(dist.count, 100);
+        assert_eq!(dist.p50_ms, 50);
+        assert_eq!(dist.p90_ms, 90);
+        assert_eq!(dist.p99_ms, 99);
+        assert_eq!(dist.max_ms, 100);
+    }
+
+    #[test]
+    fn test_only_detected_markets_are_tracked() {
+        let mut tracker = IndexingLatencyTracker::new(None);
+        tracker.mark_gamma_available("unknown");
+        tracker.mark_detected("m1");
+        tracker.mark_book("m1", false);
+        tracker.mark_book("m1", true);
+        tracker.mark_gamma_available("m1");
+
+        let report = tracker.report();
+        assert_eq!(report.markets_tracked, 1);
+        assert_eq!(report.awaiting_gamma, 0);
+        assert_eq!(report.gamma_lag.count, 1);
+        assert_eq!(report.book_to_quote.count, 1);
+    }
+}
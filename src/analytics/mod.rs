@@ -1,3 +1,36 @@
+pub mod attribution;
+pub mod cost_basis;
+pub mod decisions;
+pub mod drift;
+pub mod equity;
+pub mod edge_realization;
+pub mod execution_drift;
+pub mod indexing_latency;
+pub mod opportunities;
+pub mod performance;
 pub mod pnl;
+pub mod pnl_buckets;
+pub mod postmortem;
+pub mod query;
+pub mod resolutions;
+pub mod session;
+pub mod trade_context;
+pub mod trade_journal;
 
+pub use attribution::{AttributionReport, Dimension};
+pub use decisions::{DecisionLog, DecisionLogMode, DecisionRecord};
+pub use drift::{DriftReport, DriftTracker};
+pub use equity::SnapshotRetention;
+pub use edge_realization::{EdgeRealization, EdgeRealizationTracker};
+pub use execution_drift::{DriftSample, ExecutionDriftTracker};
+pub use indexing_latency::IndexingLatencyTracker;
+pub use opportunities::{OpportunityBook, OpportunityRecord};
+pub use performance::{Methodology, PerformanceStats};
 pub use pnl::PnLTracker;
+pub use pnl_buckets::{PnlBucket, PnlCalendar};
+pub use postmortem::{Cause, PostmortemLog};
+pub use query::{ListQuery, SortKey, SortOrder};
+pub use resolutions::{MarketResolution, ResolutionRecorder};
+pub use session::SessionRecord;
+pub use trade_context::{trade_context, TradeContext, MAX_PADDING_SECS};
+pub use trade_journal::{CategoryPnL, TradeAnnotation, TradeJournal};
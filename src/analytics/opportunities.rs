@@ -0,0 +1,343 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+pub const OPPORTUNITIES_FILE: &str = "opportunities.jsonl";
+pub const OPPORTUNITY_SUMMARIES_FILE: &str = "opportunity_summaries.jsonl";
+
+/// Edges are bucketed per bps inside this range (clamped outside it)
+const HISTOGRAM_MIN_BPS: i32 = -1000;
+const HISTOGRAM_MAX_BPS: i32 = 1000;
+
+/// Daily summaries kept in memory for the API
+const MAX_DAILY_SUMMARIES: usize = 30;
+
+/// Threshold offsets (bps, relative to min_edge) reported in the pass counts
+const THRESHOLD_OFFSETS_BPS: [i32; 7] = [-100, -50, -25, 0, 25, 50, 100];
+
+/// One evaluated arbitrage edge, whether or not it cleared the threshold
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OpportunityRecord {
+    pub timestamp: DateTime<Utc>,
+    pub market_id: String,
+    /// Edge of buying both sides after fees
+    pub net_spread_bps: i32,
+    /// Threshold in effect for this market
+    pub min_edge_bps: i32,
+    pub liquidity: f64,
+}
+
+impl OpportunityRecord {
+    pub fn is_signal(&self) -> bool {
+        self.net_spread_bps > self.min_edge_bps
+    }
+}
+
+/// How many evaluations a given min_edge would have let through
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ThresholdCount {
+    pub min_edge_bps: i32,
+    pub signals: u64,
+}
+
+/// Distribution of evaluated edges over one UTC day
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EdgeSummary {
+    pub date: NaiveDate,
+    pub evaluations: u64,
+    pub signals: u64,
+    /// Base threshold at the end of the day
+    pub min_edge_bps: i32,
+    pub mean_bps: f64,
+    pub p50_bps: i32,
+    pub p90_bps: i32,
+    pub p99_bps: i32,
+    pub max_bps: i32,
+    /// Signals at thresholds around the current one
+    pub would_pass: Vec<ThresholdCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OpportunityReport {
+    pub today: Option<EdgeSummary>,
+    /// Completed days, most recent first
+    pub history: Vec<EdgeSummary>,
+    /// Most recent evaluations, newest first
+    pub recent: Vec<OpportunityRecord>,
+}
+
+/// Per-bps edge counts for one day
+struct EdgeHistogram {
+    counts: Vec<u64>,
+    total: u64,
+    sum_bps: f64,
+    max_bps: i32,
+}
+
+impl EdgeHistogram {
+    fn new() -> Self {
+        Self {
+            counts: vec![0; (HISTOGRAM_MAX_BPS - HISTOGRAM_MIN_BPS + 1) as usize],
+            total: 0,
+            sum_bps: 0.0,
+            max_bps: i32::MIN,
+        }
+    }
+
+    fn add(&mut self, bps: i32) {
+        let idx = (bps.clamp(HISTOGRAM_MIN_BPS, HISTOGRAM_MAX_BPS) - HISTOGRAM_MIN_BPS) as usize;
+        self.counts[idx] += 1;
+        self.total += 1;
+        self.sum_bps += bps as f64;
+        self.max_bps = self.max_bps.max(bps);
+    }
+
+    /// Nearest-rank percentile
+    fn percentile(&self, p: f64) -> i32 {
+        let rank = ((p / 100.0) * self.total as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (idx, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return HISTOGRAM_MIN_BPS + idx as i32;
+            }
+        }
+        HISTOGRAM_MAX_BPS
+    }
+
+    /// Evaluations with an edge strictly above `min_edge_bps`
+    fn count_above(&self, min_edge_bps: i32) -> u64 {
+        let start = (min_edge_bps + 1 - HISTOGRAM_MIN_BPS).max(0) as usize;
+        self.counts.iter().skip(start).sum()
+    }
+}
+
+/// Every evaluated arbitrage edge, kept in memory for the API and appended to
+/// `data_dir/opportunities.jsonl`, with a daily summary of the edge distribution
+/// Shows whether min_edge_bps is starving the strategy or letting noise through
+pub struct OpportunityBook {
+    capacity: usize,
+    recent: VecDeque<OpportunityRecord>,
+    pending: Vec<OpportunityRecord>,
+    day: Option<NaiveDate>,
+    histogram: EdgeHistogram,
+    signals: u64,
+    min_edge_bps: i32,
+    history: VecDeque<EdgeSummary>,
+    pending_summaries: Vec<EdgeSummary>,
+    data_dir: Option<PathBuf>,
+}
+
+impl OpportunityBook {
+    /// Records are persisted on `flush` when a data dir is given
+    pub fn new(capacity: usize, data_dir: Option<&str>) -> Self {
+        if let Some(dir) = data_dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warn!("⚠️ Failed to create data dir {}: {}", dir, e);
+            }
+        }
+
+        Self {
+            capacity: capacity.max(1),
+            recent: VecDeque::new(),
+            pending: Vec::new(),
+            day: None,
+            histogram: EdgeHistogram::new(),
+            signals: 0,
+            min_edge_bps: 0,
+            history: VecDeque::new(),
+            pending_summaries: Vec::new(),
+            data_dir: data_dir.map(PathBuf::from),
+        }
+    }
+
+    pub fn record(&mut self, record: OpportunityRecord) {
+        let date = record.timestamp.date_naive();
+        if self.day.is_some_and(|day| day != date) {
+            self.close_day();
+        }
+        self.day = Some(date);
+
+        self.histogram.add(record.net_spread_bps);
+        if record.is_signal() {
+            self.signals += 1;
+        }
+        self.min_edge_bps = record.min_edge_bps;
+
+        if self.recent.len() >= self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(record.clone());
+        if self.data_dir.is_some() {
+            self.pending.push(record);
+        }
+    }
+
+    /// Summary of the current day so far
+    pub fn today(&self) -> Option<EdgeSummary> {
+        let date = self.day?;
+        if self.histogram.total == 0 {
+            return None;
+        }
+
+        Some(EdgeSummary {
+            date,
+            evaluations: self.histogram.total,
+            signals: self.signals,
+            min_edge_bps: self.min_edge_bps,
+            mean_bps: self.histogram.sum_bps / self.histogram.total as f64,
+            p50_bps: self.histogram.percentile(50.0),
+            p90_bps: self.histogram.percentile(90.0),
+            p99_bps: self.histogram.percentile(99.0),
+            max_bps: self.histogram.max_bps,
+            would_pass: THRESHOLD_OFFSETS_BPS
+                .iter()
+                .map(|offset| {
+                    let min_edge_bps = self.min_edge_bps + offset;
+                    ThresholdCount {
+                        min_edge_bps,
+                        signals: self.histogram.count_above(min_edge_bps),
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    /// `limit` most recent records, optionally only those with at least `min_bps` of edge
+    pub fn report(&self, limit: usize, min_bps: Option<i32>) -> OpportunityReport {
+        OpportunityReport {
+            today: self.today(),
+            history: self.history.iter().rev().cloned().collect(),
+            recent: self
+                .recent
+                .iter()
+                .rev()
+                .filter(|r| min_bps.is_none_or(|min| r.net_spread_bps >= min))
+                .take(limit)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Append buffered records (and finished daily summaries) to the data dir
+    pub fn flush(&mut self) -> Result<()> {
+        let Some(dir) = &self.data_dir else {
+            return Ok(());
+        };
+
+        if !self.pending.is_empty() {
+            append_all(&dir.join(OPPORTUNITIES_FILE), &self.pending)?;
+            self.pending.clear();
+        }
+        if !self.pending_summaries.is_empty() {
+            append_all(
+                &dir.join(OPPORTUNITY_SUMMARIES_FILE),
+                &self.pending_summaries,
+            )?;
+            self.pending_summaries.clear();
+        }
+        Ok(())
+    }
+
+    fn close_day(&mut self) {
+        if let Some(summary) = self.today() {
+            info!(
+                "📊 Edge distribution {}: {} evaluations, {} signals at {} bps | p50 {} p90 {} p99 {} max {} bps",
+                summary.date,
+                summary.evaluations,
+                summary.signals,
+                summary.min_edge_bps,
+                summary.p50_bps,
+                summary.p90_bps,
+                summary.p99_bps,
+                summary.max_bps
+            );
+            if self.history.len() >= MAX_DAILY_SUMMARIES {
+                self.history.pop_front();
+            }
+            self.history.push_back(summary.clone());
+            if self.data_dir.is_some() {
+                self.pending_summaries.push(summary);
+            }
+        }
+
+        self.histogram = EdgeHistogram::new();
+        self.signals = 0;
+    }
+}
+
+fn append_all<T: Serialize>(path: &Path, records: &[T]) -> Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = BufWriter::new(file);
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Path of the opportunity log inside a data dir
+pub fn opportunities_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(OPPORTUNITIES_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn record(timestamp: DateTime<Utc>, net_spread_bps: i32) -> OpportunityRecord {
+        OpportunityRecord {
+            timestamp,
+            market_id: "m1".to_string(),
+            net_spread_bps,
+            min_edge_bps: 50,
+            liquidity: 1000.0,
+        }
+    }
+
+    #[test]
+    fn test_daily_distribution_rolls_over() {
+        let mut book = OpportunityBook::new(3, None);
+        let day = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for bps in [-200, -100, 0, 40, 60] {
+            book.record(record(day, bps));
+        }
+
+        let today = book.today().unwrap();
+        assert_eq!(today.evaluations, 5);
+        assert_eq!(today.signals, 1);
+        assert_eq!(today.p50_bps, 0);
+        assert_eq!(today.max_bps, 60);
+        let at = |min: i32| {
+            today
+                .would_pass
+                .iter()
+                .find(|t| t.min_edge_bps == min)
+                .unwrap()
+                .signals
+        };
+        assert_eq!(at(25), 2);
+        assert_eq!(at(-50), 3);
+
+        // Near misses are kept, capped at capacity
+        let report = book.report(10, Some(0));
+        assert_eq!(report.recent.len(), 3);
+        assert_eq!(report.recent[0].net_spread_bps, 60);
+
+        book.record(record(day + Duration::days(1), 10));
+        let report = book.report(10, None);
+        assert_eq!(report.history.len(), 1);
+        assert_eq!(report.history[0].evaluations, 5);
+        assert_eq!(report.today.unwrap().evaluations, 1);
+    }
+}
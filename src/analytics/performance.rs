@@ -0,0 +1,307 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::pnl::{PortfolioSnapshot, Trade};
+use crate::config::PerformanceConfig;
+
+const SECS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0;
+
+const METHODOLOGY: &str = "The equity curve (portfolio value snapshots) is resampled to a fixed \
+period, carrying the last value forward, so the sampling rate and gaps don't change the result. \
+Sharpe = (mean period return - risk-free rate per period) / sample std dev, times \
+sqrt(periods per year). Sortino uses the downside deviation below the risk-free rate instead. \
+Annualized return = mean period return * periods per year; Calmar = annualized return / max \
+drawdown. trade_sharpe is the mean / std dev of per-trade returns (PnL over size), not annualized. \
+Ratios are 0 with fewer than 2 returns.";
+
+/// How the ratios are computed, returned alongside them
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Methodology {
+    pub resample_secs: u64,
+    pub periods_per_year: f64,
+    /// Annual
+    pub risk_free_rate: f64,
+    pub description: String,
+}
+
+impl Methodology {
+    pub fn new(config: &PerformanceConfig) -> Self {
+        Self {
+            resample_secs: config.resample_secs.max(1),
+            periods_per_year: SECS_PER_YEAR / config.resample_secs.max(1) as f64,
+            risk_free_rate: config.risk_free_rate,
+            description: METHODOLOGY.to_string(),
+        }
+    }
+}
+
+/// Return and risk metrics of the portfolio over one period (see `Methodology`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct PerformanceStats {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// Portfolio value change over the period (fraction)
+    pub total_return: f64,
+    /// Resampled returns the ratios are computed from
+    pub periods: usize,
+    pub annualized_return: f64,
+    pub annualized_volatility: f64,
+    pub sharpe_ratio: f64,
+    /// Excess return over downside deviation only
+    pub sortino_ratio: f64,
+    /// Annualized return over max drawdown
+    pub calmar_ratio: f64,
+    pub max_drawdown: f64,
+    pub num_trades: usize,
+    pub win_rate: f64,
+    /// Gross profit over gross loss. None without losing trades
+    pub profit_factor: Option<f64>,
+    pub avg_win: f64,
+    /// Average losing trade (negative)
+    pub avg_loss: f64,
+    pub trade_sharpe: f64,
+}
+
+impl PerformanceStats {
+    /// Metrics from `since` on (everything when None)
+    ///
+    /// `snapshots` must be in time order. A rolling window opens at the last snapshot before
+    /// `since`; the full history opens at `initial_value`.
+    pub fn compute(
+        snapshots: &[PortfolioSnapshot],
+        trades: &[Trade],
+        since: Option<DateTime<Utc>>,
+        initial_value: f64,
+        config: &PerformanceConfig,
+    ) -> Self {
+        let first = match since {
+            Some(since) => snapshots
+                .partition_point(|s| s.timestamp < since)
+                .saturating_sub(1),
+            None => 0,
+        };
+        let series = &snapshots[first..];
+        let trades: Vec<&Trade> = trades
+            .iter()
+            .filter(|t| since.is_none_or(|since| t.exit_time.is_some_and(|at| at >= since)))
+            .collect();
+
+        let mut stats = Self {
+            start: series.first().map(|s| s.timestamp),
+            end: series.last().map(|s| s.timestamp),
+            ..Self::default()
+        };
+        stats.trade_stats(&trades);
+
+        let opening = match (since, series.first()) {
+            (None, _) => initial_value,
+            (Some(_), Some(s)) => s.total_value,
+            (Some(_), None) => return stats,
+        };
+        if let Some(last) = series.last() {
+            if opening > 0.0 {
+                stats.total_return = last.total_value / opening - 1.0;
+            }
+        }
+
+        // Drawdown from the running peak (the opening value counts as the first peak)
+        let mut peak = opening;
+        for snapshot in series {
+            peak = peak.max(snapshot.total_value);
+            if peak > 0.0 {
+                stats.max_drawdown = stats.max_drawdown.max((peak - snapshot.total_value) / peak);
+            }
+        }
+
+        let methodology = Methodology::new(config);
+        let returns = resampled_returns(
+            series,
+            Duration::seconds(methodology.resample_secs.min(i64::MAX as u64) as i64),
+        );
+        stats.periods = returns.len();
+        if returns.len() < 2 {
+            return stats;
+        }
+
+        let periods_per_year = methodology.periods_per_year;
+        let risk_free = methodology.risk_free_rate / periods_per_year;
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let std_dev = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        let downside_deviation = (returns
+            .iter()
+            .map(|r| (r - risk_free).min(0.0).powi(2))
+            .sum::<f64>()
+            / n)
+            .sqrt();
+
+        stats.annualized_return = mean * periods_per_year;
+        stats.annualized_volatility = std_dev * periods_per_year.sqrt();
+        stats.sharpe_ratio = ratio(mean - risk_free, std_dev) * periods_per_year.sqrt();
+        stats.sortino_ratio = ratio(mean - risk_free, downside_deviation) * periods_per_year.sqrt();
+        stats.calmar_ratio = ratio(stats.annualized_return, stats.max_drawdown);
+        stats
+    }
+
+    fn trade_stats(&mut self, trades: &[&Trade]) {
+        let pnls: Vec<f64> = trades.iter().filter_map(|t| t.realized_pnl).collect();
+        let wins: Vec<f64> = pnls.iter().copied().filter(|p| *p > 0.0).collect();
+        let losses: Vec<f64> = pnls.iter().copied().filter(|p| *p < 0.0).collect();
+        let gross_profit: f64 = wins.iter().sum();
+        let gross_loss: f64 = -losses.iter().sum::<f64>();
+
+        self.num_trades = trades.len();
+        if !trades.is_empty() {
+            self.win_rate = wins.len() as f64 / trades.len() as f64;
+        }
+        if !wins.is_empty() {
+            self.avg_win = gross_profit / wins.len() as f64;
+        }
+        if !losses.is_empty() {
+            self.avg_loss = -gross_loss / losses.len() as f64;
+            self.profit_factor = Some(gross_profit / gross_loss);
+        }
+
+        let returns: Vec<f64> = trades
+            .iter()
+            .filter(|t| t.size > 0.0)
+            .filter_map(|t| t.realized_pnl.map(|pnl| pnl / t.size))
+            .collect();
+        if returns.len() >= 2 {
+            let n = returns.len() as f64;
+            let mean = returns.iter().sum::<f64>() / n;
+            let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            self.trade_sharpe = ratio(mean, variance.sqrt());
+        }
+    }
+}
+
+/// Returns between fixed-period points of the equity curve (last value carried forward)
+fn resampled_returns(series: &[PortfolioSnapshot], period: Duration) -> Vec<f64> {
+    let (Some(first), Some(last)) = (series.first(), series.last()) else {
+        return Vec::new();
+    };
+    let mut values = Vec::new();
+    let mut at = first.timestamp;
+    let mut i = 0;
+    while at <= last.timestamp {
+        while i + 1 < series.len() && series[i + 1].timestamp <= at {
+            i += 1;
+        }
+        values.push(series[i].total_value);
+        at += period;
+    }
+    values
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect()
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(at: DateTime<Utc>, value: f64) -> PortfolioSnapshot {
+        PortfolioSnapshot {
+            timestamp: at,
+            total_value: value,
+            cash: value,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    fn trade(at: DateTime<Utc>, pnl: f64) -> Trade {
+        Trade {
+            id: format!("t{}", pnl),
+            market_id: "m1".to_string(),
+            market_question: "Q?".to_string(),
+            side: "YES".to_string(),
+            size: 10.0,
+            entry_price: 0.5,
+            exit_price: None,
+            entry_time: at,
+            exit_time: Some(at),
+            realized_pnl: Some(pnl),
+            config_hash: None,
+            note: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn hourly(risk_free_rate: f64) -> PerformanceConfig {
+        PerformanceConfig {
+            resample_secs: 3600,
+            risk_free_rate,
+            snapshot_full_hours: 24,
+            snapshot_minute_days: 7,
+        }
+    }
+
+    #[test]
+    fn test_resampled_ratios_and_windows() {
+        let t0 = Utc::now() - Duration::days(3);
+        // A 2-day gap between the 2nd and 3rd snapshot
+        let snapshots = vec![
+            snapshot(t0, 100.0),
+            snapshot(t0 + Duration::hours(1), 110.0),
+            snapshot(t0 + Duration::hours(49), 99.0),
+            snapshot(t0 + Duration::hours(50), 104.0),
+            snapshot(t0 + Duration::hours(51), 108.0),
+        ];
+        let trades = vec![
+            trade(t0, 6.0),
+            trade(t0 + Duration::hours(50), -2.0),
+            trade(t0 + Duration::hours(51), 4.0),
+        ];
+
+        let all = PerformanceStats::compute(&snapshots, &trades, None, 100.0, &hourly(0.0));
+        assert_eq!(all.periods, 51); // The gap counts as flat hours
+        assert!((all.total_return - 0.08).abs() < 1e-9);
+        assert!((all.max_drawdown - 0.1).abs() < 1e-9);
+        assert_eq!(all.num_trades, 3);
+        assert_eq!(all.profit_factor, Some(5.0));
+        assert!((all.avg_win - 5.0).abs() < 1e-9);
+        assert!((all.avg_loss + 2.0).abs() < 1e-9);
+        assert!(all.trade_sharpe > 0.0);
+        assert!(all.sortino_ratio > all.sharpe_ratio);
+        assert!((all.calmar_ratio - all.annualized_return / 0.1).abs() < 1e-9);
+
+        // A risk-free rate lowers Sharpe; denser sampling of the same curve doesn't change it
+        let with_rf = PerformanceStats::compute(&snapshots, &trades, None, 100.0, &hourly(0.04));
+        assert!(with_rf.sharpe_ratio < all.sharpe_ratio);
+        let mut dense = snapshots.clone();
+        dense.insert(
+            2,
+            snapshot(t0 + Duration::hours(1) + Duration::seconds(10), 110.0),
+        );
+        let dense = PerformanceStats::compute(&dense, &trades, None, 100.0, &hourly(0.0));
+        assert!((dense.sharpe_ratio - all.sharpe_ratio).abs() < 1e-9);
+
+        // Last 2h opens at the 99.0 snapshot: no drawdown, only the later trades
+        let recent = PerformanceStats::compute(
+            &snapshots,
+            &trades,
+            Some(t0 + Duration::hours(49) + Duration::minutes(30)),
+            100.0,
+            &hourly(0.0),
+        );
+        assert_eq!(recent.start, Some(t0 + Duration::hours(49)));
+        assert!((recent.total_return - (108.0 / 99.0 - 1.0)).abs() < 1e-9);
+        assert_eq!(recent.max_drawdown, 0.0);
+        assert_eq!(recent.num_trades, 2);
+        assert_eq!(recent.periods, 2);
+        assert_eq!(recent.sortino_ratio, 0.0); // No period below the risk-free rate
+        assert!(recent.sharpe_ratio > 0.0);
+    }
+}
@@ -1,31 +1,55 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::attribution::{AttributionReport, Dimension};
+use super::equity::{self, SnapshotRetention};
+use super::performance::{Methodology, PerformanceStats};
+use super::pnl_buckets::PnlCalendar;
+use super::query::{time_key, ListQuery, SortKey};
+use super::trade_journal::{TradeAnnotation, TradeJournal};
+use crate::config::PerformanceConfig;
+use crate::ids::{Price, UsdAmount};
+use crate::polymarket::QuoteQuality;
+use crate::storage::{StorageOp, StorageWriter};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Position {
     pub id: String,
     pub market_id: String,
     pub market_question: String,
     pub side: String, // "YES" or "NO"
+    /// Entry notional in USD
     pub size: f64,
     pub entry_price: f64,
     pub current_price: f64,
     pub entry_time: DateTime<Utc>,
+    /// Hash of the session config the position was opened under
+    #[serde(default)]
+    pub config_hash: Option<String>,
+    /// Entry the position is one leg of (the arbitrage plan id for YES + NO entries)
+    #[serde(default)]
+    pub group_id: Option<String>,
 }
 
 impl Position {
-    pub fn unrealized_pnl(&self) -> f64 {
-        let price_change = if self.side == "YES" {
-            self.current_price - self.entry_price
+    /// Shares held (`size` is the entry notional)
+    pub fn shares(&self) -> f64 {
+        if self.entry_price > 0.0 {
+            self.size / self.entry_price
         } else {
-            self.entry_price - self.current_price
-        };
-        self.size * price_change
+            0.0
+        }
+    }
+
+    /// Entry and mark are both prices of the held outcome's token, whichever side it is
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.shares() * (self.current_price - self.entry_price)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Trade {
     pub id: String,
     pub market_id: String,
@@ -37,9 +61,19 @@ pub struct Trade {
     pub entry_time: DateTime<Utc>,
     pub exit_time: Option<DateTime<Utc>>,
     pub realized_pnl: Option<f64>,
+    #[serde(default)]
+    pub config_hash: Option<String>,
+    /// Operator review notes and tags (PATCH /api/trades/{id})
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Minimum time between two compactions of the equity curve
+const COMPACT_EVERY_MINS: i64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PortfolioSnapshot {
     pub timestamp: DateTime<Utc>,
     pub total_value: f64,
@@ -48,7 +82,7 @@ pub struct PortfolioSnapshot {
     pub realized_pnl: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PnLStats {
     pub total_pnl: f64,
     pub unrealized_pnl: f64,
@@ -58,7 +92,17 @@ pub struct PnLStats {
     pub num_trades: usize,
     pub win_rate: f64,
     pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub calmar_ratio: f64,
     pub max_drawdown: f64,
+    pub profit_factor: Option<f64>,
+    pub avg_win: f64,
+    pub avg_loss: f64,
+    /// The same metrics over the session and rolling windows
+    pub all_time: PerformanceStats,
+    pub last_24h: PerformanceStats,
+    pub last_7d: PerformanceStats,
+    pub methodology: Methodology,
 }
 
 pub struct PnLTracker {
@@ -67,6 +111,17 @@ pub struct PnLTracker {
     pub snapshots: Vec<PortfolioSnapshot>,
     pub initial_capital: f64,
     pub cash: f64,
+    /// Stamped on positions opened through this tracker
+    pub config_hash: Option<String>,
+    /// Hourly / daily aggregates of trades and snapshots
+    pub calendar: PnlCalendar,
+    /// Closed trades across sessions, with operator notes and tags
+    pub journal: TradeJournal,
+    /// Trades and snapshots are mirrored here when a storage backend is configured
+    pub storage: Option<StorageWriter>,
+    /// Resampling period and risk-free rate of the risk metrics, and snapshot retention
+    pub performance: PerformanceConfig,
+    last_compaction: Option<DateTime<Utc>>,
 }
 
 impl PnLTracker {
@@ -77,53 +132,349 @@ impl PnLTracker {
             snapshots: Vec::new(),
             initial_capital,
             cash: initial_capital,
+            config_hash: None,
+            calendar: PnlCalendar::default(),
+            journal: TradeJournal::default(),
+            storage: None,
+            performance: PerformanceConfig {
+                resample_secs: 3600,
+                risk_free_rate: 0.0,
+                snapshot_full_hours: 24,
+                snapshot_minute_days: 7,
+            },
+            last_compaction: None,
         }
     }
 
-    pub fn add_position(&mut self, position: Position) {
+    /// Keep the hourly / daily buckets in `data_dir` (in memory only otherwise)
+    pub fn load_calendar(&mut self, data_dir: &str) {
+        self.calendar = PnlCalendar::load(data_dir);
+    }
+
+    /// Keep the trade journal in `data_dir` (in memory only otherwise)
+    pub fn load_journal(&mut self, data_dir: &str) {
+        self.journal = TradeJournal::load(data_dir);
+    }
+
+    pub fn set_config_hash(&mut self, config_hash: String) {
+        self.config_hash = Some(config_hash);
+    }
+
+    pub fn set_performance_config(&mut self, performance: PerformanceConfig) {
+        self.performance = performance;
+    }
+
+    pub fn set_storage(&mut self, storage: StorageWriter) {
+        self.storage = Some(storage);
+    }
+
+    /// Note / tag edit on a journaled trade (mirrored to storage). None if no trade has this id
+    pub fn annotate_trade(&mut self, id: &str, annotation: TradeAnnotation) -> Option<Trade> {
+        let trade = self.journal.annotate(id, annotation)?;
+        if let Some(storage) = &self.storage {
+            storage.send(StorageOp::Trade(trade.clone()));
+        }
+        Some(trade)
+    }
+
+    pub fn add_position(&mut self, mut position: Position) {
+        if position.config_hash.is_none() {
+            position.config_hash = self.config_hash.clone();
+        }
         // Deduct cash for position
         self.cash -= position.size;
         self.positions.insert(position.id.clone(), position);
     }
 
+    /// A side without a usable quote keeps its last mark instead of dropping to 0
     pub fn update_market_price(&mut self, market_id: &str, yes_price: f64, no_price: f64) {
         for position in self.positions.values_mut() {
             if position.market_id == market_id {
-                position.current_price = if position.side == "YES" {
+                let price = if position.side == "YES" {
                     yes_price
                 } else {
                     no_price
                 };
+                if QuoteQuality::is_usable(price) {
+                    position.current_price = price;
+                }
             }
         }
     }
 
+    /// Add `size` USD bought at `price` to an open position; the entry price becomes the
+    /// share-weighted average cost, as in the risk manager
+    pub fn increase_position(&mut self, position_id: &str, size: UsdAmount, price: Price) -> bool {
+        let (size, price) = (size.get(), price.get());
+        let Some(position) = self.positions.get_mut(position_id) else {
+            return false;
+        };
+        if size <= 0.0 || price <= 0.0 {
+            return true;
+        }
+
+        let shares = position.shares() + size / price;
+        position.size += size;
+        position.entry_price = position.size / shares;
+        self.cash -= size;
+        true
+    }
+
+    /// Close `size` USD of entry notional at `exit_price`. The rest keeps its average cost
+    /// Returns the realized PnL of the closed quantity (recorded as its own trade)
+    pub fn reduce_position(
+        &mut self,
+        position_id: &str,
+        size: UsdAmount,
+        exit_price: Price,
+    ) -> Option<f64> {
+        let (size, exit_price) = (size.get(), exit_price.get());
+        let position = self.positions.get_mut(position_id)?;
+        let closed = size.clamp(0.0, position.size);
+
+        let realized_pnl = if position.entry_price > 0.0 {
+            closed * (exit_price / position.entry_price - 1.0)
+        } else {
+            0.0
+        };
+
+        // Return cash + PnL for the closed quantity
+        self.cash += closed + realized_pnl;
+        position.size -= closed;
+
+        // Record trade (later partial closes of the same position get their own id)
+        let closes = self.journal.closes_of(&position.id);
+        let trade = Trade {
+            id: if closes == 0 {
+                position.id.clone()
+            } else {
+                format!("{}-{}", position.id, closes)
+            },
+            market_id: position.market_id.clone(),
+            market_question: position.market_question.clone(),
+            side: position.side.clone(),
+            size: closed,
+            entry_price: position.entry_price,
+            exit_price: Some(exit_price),
+            entry_time: position.entry_time,
+            exit_time: Some(Utc::now()),
+            realized_pnl: Some(realized_pnl),
+            config_hash: position.config_hash.clone(),
+            note: None,
+            tags: Vec::new(),
+        };
+        self.calendar.record_trade(&trade);
+        self.journal.record(trade.clone());
+        if let Some(storage) = &self.storage {
+            storage.send(StorageOp::Trade(trade.clone()));
+        }
+        self.trades.push(trade);
+
+        // Dust left after rounding counts as closed
+        if position.size < 1e-9 {
+            self.positions.remove(position_id);
+        }
+
+        Some(realized_pnl)
+    }
+
     pub fn close_position(&mut self, position_id: &str) -> Option<f64> {
-        if let Some(position) = self.positions.remove(position_id) {
-            let realized_pnl = position.unrealized_pnl();
-
-            // Return cash + PnL
-            self.cash += position.size + realized_pnl;
-
-            // Record trade
-            let trade = Trade {
-                id: position.id.clone(),
-                market_id: position.market_id.clone(),
-                market_question: position.market_question.clone(),
-                side: position.side.clone(),
-                size: position.size,
-                entry_price: position.entry_price,
-                exit_price: Some(position.current_price),
-                entry_time: position.entry_time,
-                exit_time: Some(Utc::now()),
-                realized_pnl: Some(realized_pnl),
-            };
-            self.trades.push(trade);
-
-            Some(realized_pnl)
+        let (size, price) = self
+            .positions
+            .get(position_id)
+            .map(|p| (p.size, p.current_price))?;
+        self.reduce_position(position_id, UsdAmount::new(size), Price::new(price))
+    }
+
+    /// Open the legs of one entry (YES + NO arbitrage) as positions linked by `group_id`
+    pub fn add_group(&mut self, group_id: &str, legs: Vec<Position>) {
+        for mut leg in legs {
+            leg.group_id = Some(group_id.to_string());
+            self.add_position(leg);
+        }
+    }
+
+    /// Open legs of a group
+    pub fn group_legs(&self, group_id: &str) -> Vec<&Position> {
+        self.positions
+            .values()
+            .filter(|p| p.group_id.as_deref() == Some(group_id))
+            .collect()
+    }
+
+    /// Close `fraction` of every leg of a group at its mark. Returns the realized PnL
+    pub fn reduce_group(&mut self, group_id: &str, fraction: f64) -> Option<f64> {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let legs: Vec<(String, f64, f64)> = self
+            .group_legs(group_id)
+            .iter()
+            .map(|p| (p.id.clone(), p.size * fraction, p.current_price))
+            .collect();
+        if legs.is_empty() {
+            return None;
+        }
+        Some(
+            legs.into_iter()
+                .filter_map(|(id, size, price)| {
+                    self.reduce_position(&id, UsdAmount::new(size), Price::new(price))
+                })
+                .sum(),
+        )
+    }
+
+    pub fn close_group(&mut self, group_id: &str) -> Option<f64> {
+        self.reduce_group(group_id, 1.0)
+    }
+
+    /// Merge the matched YES + NO quantity of a group back into collateral. Each pair pays 1,
+    /// split between the legs in proportion to their marks. Returns the realized PnL
+    pub fn merge_group(&mut self, group_id: &str) -> Option<f64> {
+        let legs = self.group_legs(group_id);
+        let yes = legs.iter().find(|p| p.side == "YES")?;
+        let no = legs.iter().find(|p| p.side == "NO")?;
+        let pairs = yes.shares().min(no.shares());
+        let (yes_closed, no_closed) = (pairs * yes.entry_price, pairs * no.entry_price);
+        let marks = yes.current_price + no.current_price;
+        let yes_exit = if marks > 0.0 {
+            yes.current_price / marks
         } else {
-            None
+            0.5
+        };
+        let (yes_id, no_id) = (yes.id.clone(), no.id.clone());
+
+        let yes_pnl =
+            self.reduce_position(&yes_id, UsdAmount::new(yes_closed), Price::new(yes_exit))?;
+        let no_pnl = self.reduce_position(
+            &no_id,
+            UsdAmount::new(no_closed),
+            Price::new(1.0 - yes_exit),
+        )?;
+        Some(yes_pnl + no_pnl)
+    }
+
+    /// Convert `sets` NO tokens of each leg of a group (one per question of a neg-risk market)
+    /// through the adapter, never more than a leg holds. Each set pays `payout_per_set`, split
+    /// between the legs in proportion to their marks. Returns the realized PnL
+    pub fn convert_group(&mut self, group_id: &str, sets: f64, payout_per_set: f64) -> Option<f64> {
+        let legs: Vec<(String, f64, f64)> = self
+            .group_legs(group_id)
+            .iter()
+            .filter(|p| p.side == "NO")
+            .map(|p| {
+                let closed = p.size.min(sets.max(0.0) * p.entry_price);
+                (p.id.clone(), closed, p.current_price)
+            })
+            .collect();
+        if legs.len() < 2 {
+            return None;
         }
+        let marks: f64 = legs.iter().map(|(_, _, mark)| mark).sum();
+        let count = legs.len() as f64;
+        Some(
+            legs.into_iter()
+                .filter_map(|(id, size, mark)| {
+                    let exit = if marks > 0.0 {
+                        payout_per_set * mark / marks
+                    } else {
+                        payout_per_set / count
+                    };
+                    self.reduce_position(&id, UsdAmount::new(size), Price::new(exit))
+                })
+                .sum(),
+        )
+    }
+
+    /// Settle a position of a resolved market: the winning outcome pays 1, the other 0
+    pub fn settle_position(&mut self, position_id: &str, winning_side: &str) -> Option<f64> {
+        let (size, side) = self
+            .positions
+            .get(position_id)
+            .map(|p| (p.size, p.side.clone()))?;
+        let payout = if side == winning_side { 1.0 } else { 0.0 };
+        self.reduce_position(position_id, UsdAmount::new(size), Price::new(payout))
+    }
+
+    /// Settle every open position (every leg) of a resolved market. Returns the realized PnL
+    pub fn resolve_market(&mut self, market_id: &str, winning_side: &str) -> f64 {
+        let ids: Vec<String> = self
+            .positions
+            .values()
+            .filter(|p| p.market_id == market_id)
+            .map(|p| p.id.clone())
+            .collect();
+        ids.iter()
+            .filter_map(|id| self.settle_position(id, winning_side))
+            .sum()
+    }
+
+    /// Settle a redeemed market at what the redemption actually paid out, spread evenly over
+    /// the shares held (for when the winner couldn't be read, e.g. a split payout)
+    pub fn redeem_market(&mut self, market_id: &str, payout_usd: f64) -> f64 {
+        let legs: Vec<(String, f64, f64)> = self
+            .positions
+            .values()
+            .filter(|p| p.market_id == market_id)
+            .map(|p| (p.id.clone(), p.size, p.shares()))
+            .collect();
+        let shares: f64 = legs.iter().map(|(_, _, shares)| shares).sum();
+        if shares <= 0.0 {
+            return 0.0;
+        }
+        let price = (payout_usd / shares).clamp(0.0, 1.0);
+        legs.iter()
+            .filter_map(|(id, size, _)| {
+                self.reduce_position(id, UsdAmount::new(*size), Price::new(price))
+            })
+            .sum()
+    }
+
+    /// YES and NO shares held in a market
+    pub fn market_shares(&self, market_id: &str) -> (f64, f64) {
+        self.positions
+            .values()
+            .filter(|p| p.market_id == market_id)
+            .fold((0.0, 0.0), |(yes, no), p| match p.side.as_str() {
+                "YES" => (yes + p.shares(), no),
+                _ => (yes, no + p.shares()),
+            })
+    }
+
+    /// Open positions matching `query`, sorted and paged, with the number that matched
+    /// Open positions matching the filters of `query` (paging ignored)
+    pub fn filtered_positions<'a>(
+        &'a self,
+        query: &'a ListQuery,
+    ) -> impl Iterator<Item = &'a Position> {
+        self.positions.values().filter(|p| {
+            query.matches(&p.market_id, p.entry_time, &[])
+                && query.matches_origin(&p.id, p.config_hash.as_deref())
+        })
+    }
+
+    pub fn query_positions(&self, query: &ListQuery) -> (usize, Vec<Position>) {
+        let matching = self.filtered_positions(query).collect();
+        query.page(matching, |p, key| match key {
+            SortKey::Time => time_key(p.entry_time),
+            SortKey::Pnl => p.unrealized_pnl(),
+            SortKey::Size => p.size,
+        })
+    }
+
+    /// Journal trades and open positions matching `query`, per strategy or config
+    pub fn attribution(&self, by: Dimension, query: &ListQuery) -> AttributionReport {
+        self.journal
+            .attribution(by, query, self.filtered_positions(query))
+    }
+
+    /// Realized PnL over the trade journal per strategy (trade id prefix)
+    pub fn realized_pnl_by_strategy(&self) -> BTreeMap<String, f64> {
+        let query = ListQuery::default();
+        self.journal
+            .attribution(Dimension::Strategy, &query, std::iter::empty())
+            .rows
+            .into_iter()
+            .map(|row| (row.key, row.realized_pnl))
+            .collect()
     }
 
     pub fn calculate_unrealized_pnl(&self) -> f64 {
@@ -155,7 +506,35 @@ impl PnLTracker {
             unrealized_pnl: self.calculate_unrealized_pnl(),
             realized_pnl: self.calculate_realized_pnl(),
         };
+        self.calendar.record_snapshot(&snapshot);
+        if let Some(storage) = &self.storage {
+            storage.send(StorageOp::Snapshot(snapshot.clone()));
+        }
         self.snapshots.push(snapshot);
+
+        let now = Utc::now();
+        let due = match self.last_compaction {
+            Some(last) => now - last >= Duration::minutes(COMPACT_EVERY_MINS),
+            None => true,
+        };
+        if due {
+            self.compact_snapshots(now);
+        }
+    }
+
+    /// Downsample the equity curve here and in the database (see `analytics::equity`)
+    pub fn compact_snapshots(&mut self, now: DateTime<Utc>) {
+        let retention = SnapshotRetention::from_config(&self.performance);
+        equity::compact(&mut self.snapshots, now, &retention);
+        if let Some(storage) = &self.storage {
+            storage.send(StorageOp::CompactSnapshots { now, retention });
+        }
+        self.last_compaction = Some(now);
+    }
+
+    /// The (downsampled) equity curve since `since`, oldest first
+    pub fn equity_curve(&self, since: Option<DateTime<Utc>>) -> &[PortfolioSnapshot] {
+        equity::since(&self.snapshots, since)
     }
 
     pub fn get_stats(&self) -> PnLStats {
@@ -164,52 +543,24 @@ impl PnLTracker {
         let realized_pnl = self.calculate_realized_pnl();
         let portfolio_value = self.portfolio_value();
 
-        // Win rate
-        let winning_trades = self
-            .trades
-            .iter()
-            .filter(|t| t.realized_pnl.unwrap_or(0.0) > 0.0)
-            .count();
-        let win_rate = if self.trades.is_empty() {
-            0.0
-        } else {
-            winning_trades as f64 / self.trades.len() as f64
-        };
-
-        // Sharpe ratio (simplified)
-        let returns: Vec<f64> = self
-            .snapshots
-            .windows(2)
-            .map(|w| (w[1].total_value - w[0].total_value) / w[0].total_value)
-            .collect();
-
-        let sharpe_ratio = if returns.len() > 1 {
-            let mean = returns.iter().sum::<f64>() / returns.len() as f64;
-            let variance =
-                returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
-            let std_dev = variance.sqrt();
-            if std_dev > 0.0 {
-                mean / std_dev * (252.0_f64).sqrt() // Annualized
-            } else {
-                0.0
-            }
-        } else {
-            0.0
+        let all_time = PerformanceStats::compute(
+            &self.snapshots,
+            &self.trades,
+            None,
+            self.initial_capital,
+            &self.performance,
+        );
+        let now = Utc::now();
+        let window = |duration: Duration| {
+            PerformanceStats::compute(
+                &self.snapshots,
+                &self.trades,
+                Some(now - duration),
+                self.initial_capital,
+                &self.performance,
+            )
         };
 
-        // Max drawdown
-        let mut peak = self.initial_capital;
-        let mut max_dd = 0.0;
-        for snapshot in &self.snapshots {
-            if snapshot.total_value > peak {
-                peak = snapshot.total_value;
-            }
-            let dd = (peak - snapshot.total_value) / peak;
-            if dd > max_dd {
-                max_dd = dd;
-            }
-        }
-
         PnLStats {
             total_pnl,
             unrealized_pnl,
@@ -217,9 +568,132 @@ impl PnLTracker {
             portfolio_value,
             num_positions: self.positions.len(),
             num_trades: self.trades.len(),
-            win_rate,
-            sharpe_ratio,
-            max_drawdown: max_dd,
+            win_rate: all_time.win_rate,
+            sharpe_ratio: all_time.sharpe_ratio,
+            sortino_ratio: all_time.sortino_ratio,
+            calmar_ratio: all_time.calmar_ratio,
+            max_drawdown: all_time.max_drawdown,
+            profit_factor: all_time.profit_factor,
+            avg_win: all_time.avg_win,
+            avg_loss: all_time.avg_loss,
+            last_24h: window(Duration::hours(24)),
+            last_7d: window(Duration::days(7)),
+            all_time,
+            methodology: Methodology::new(&self.performance),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(size: f64, entry_price: f64) -> Position {
+        Position {
+            id: "p1".to_string(),
+            market_id: "m1".to_string(),
+            market_question: "Will it happen?".to_string(),
+            side: "YES".to_string(),
+            size,
+            entry_price,
+            current_price: entry_price,
+            entry_time: Utc::now(),
+            config_hash: None,
+            group_id: None,
+        }
+    }
+
+    #[test]
+    fn test_partial_close_keeps_average_cost() {
+        let mut tracker = PnLTracker::new(100.0);
+        tracker.set_config_hash("abc123".to_string());
+        // $10 at 0.40 (25 shares) + $10 at 0.60 (16.67 shares): average cost 20 / 41.67
+        tracker.add_position(position(10.0, 0.40));
+        assert!(tracker.increase_position("p1", UsdAmount::new(10.0), Price::new(0.60)));
+        assert!((tracker.positions["p1"].size - 20.0).abs() < 1e-9);
+        assert!((tracker.positions["p1"].entry_price - 0.48).abs() < 1e-9);
+
+        // Sell a quarter of the notional (10.42 shares) at 0.60: 5 * (0.60 / 0.48 - 1)
+        let realized = tracker
+            .reduce_position("p1", UsdAmount::new(5.0), Price::new(0.60))
+            .unwrap();
+        assert!((realized - 1.25).abs() < 1e-9);
+        assert!((tracker.positions["p1"].size - 15.0).abs() < 1e-9);
+        assert!((tracker.positions["p1"].entry_price - 0.48).abs() < 1e-9);
+
+        // Closing more than is left only closes what's there: 15 * (0.45 / 0.48 - 1)
+        let realized = tracker
+            .reduce_position("p1", UsdAmount::new(100.0), Price::new(0.45))
+            .unwrap();
+        assert!((realized + 0.9375).abs() < 1e-9);
+        assert!(tracker.positions.is_empty());
+        assert_eq!(tracker.trades.len(), 2);
+        assert_eq!(tracker.trades[1].config_hash.as_deref(), Some("abc123"));
+        assert!((tracker.calculate_realized_pnl() - 0.3125).abs() < 1e-9);
+        assert!((tracker.cash - 100.3125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_arbitrage_legs_mark_merge_and_resolve() {
+        let leg = |side: &str, size: f64, price: f64| Position {
+            id: format!("arb_m1_{}", side),
+            side: side.to_string(),
+            ..position(size, price)
+        };
+        let mut tracker = PnLTracker::new(100.0);
+        // 10 YES shares and 6 NO shares
+        tracker.add_group("arb_m1", vec![leg("YES", 4.5, 0.45), leg("NO", 3.0, 0.50)]);
+        assert_eq!(tracker.group_legs("arb_m1").len(), 2);
+        let (yes, no) = tracker.market_shares("m1");
+        assert!((yes - 10.0).abs() < 1e-9 && (no - 6.0).abs() < 1e-9);
+
+        // Each leg is marked on its own side
+        tracker.update_market_price("m1", 0.60, 0.38);
+        assert!((tracker.positions["arb_m1_YES"].current_price - 0.60).abs() < 1e-9);
+        assert!((tracker.positions["arb_m1_NO"].current_price - 0.38).abs() < 1e-9);
+        assert!((tracker.calculate_unrealized_pnl() - (1.5 - 0.72)).abs() < 1e-9);
+
+        // Merging the 6 matched pairs locks in 1 - 0.45 - 0.50 per pair
+        let merged = tracker.merge_group("arb_m1").unwrap();
+        assert!((merged - 6.0 * 0.05).abs() < 1e-9);
+        assert!(!tracker.positions.contains_key("arb_m1_NO"));
+        assert!((tracker.positions["arb_m1_YES"].shares() - 4.0).abs() < 1e-9);
+
+        // YES wins: the unmatched YES quantity pays 1
+        let settled = tracker.resolve_market("m1", "YES");
+        assert!((settled - 4.0 * 0.55).abs() < 1e-9);
+        assert!(tracker.positions.is_empty());
+        assert_eq!(tracker.trades.len(), 3);
+
+        // A 50/50 payout booked from the redeemed amount
+        tracker.add_group("arb_m2", vec![leg("YES", 4.5, 0.45)]);
+        let redeemed = tracker.redeem_market("m1", 5.0);
+        assert!((redeemed - 10.0 * 0.05).abs() < 1e-9);
+        assert!(tracker.positions.is_empty());
+    }
+
+    #[test]
+    fn test_conversion_books_only_the_converted_sets() {
+        let leg = |market: &str, size: f64, price: f64| Position {
+            id: format!("nr_{}_NO", market),
+            market_id: market.to_string(),
+            side: "NO".to_string(),
+            ..position(size, price)
+        };
+        let mut tracker = PnLTracker::new(100.0);
+        // 10 NOs of q1 and q2, 12 of q3 (a leg filled more than the basket)
+        tracker.add_group(
+            "nr",
+            vec![
+                leg("q1", 5.0, 0.50),
+                leg("q2", 5.0, 0.50),
+                leg("q3", 6.0, 0.50),
+            ],
+        );
+        // 10 sets pay 2 each: 20 for 15 put in
+        let pnl = tracker.convert_group("nr", 10.0, 2.0).unwrap();
+        assert!((pnl - 5.0).abs() < 1e-9);
+        assert!((tracker.positions["nr_q3_NO"].shares() - 2.0).abs() < 1e-9);
+        assert!(!tracker.positions.contains_key("nr_q1_NO"));
+    }
+}
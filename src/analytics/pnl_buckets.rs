@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::pnl::{PortfolioSnapshot, Trade};
+
+pub const PNL_BUCKETS_FILE: &str = "pnl_buckets.json";
+
+/// Buckets kept per resolution (30 days of hours, a year of days)
+const MAX_HOURLY_BUCKETS: usize = 24 * 30;
+const MAX_DAILY_BUCKETS: usize = 366;
+
+/// Realized PnL and trade outcomes over one hour or one UTC day
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PnlBucket {
+    pub start: DateTime<Utc>,
+    pub realized_pnl: f64,
+    pub trades: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub win_rate: f64,
+    /// Portfolio value and unrealized PnL at the last snapshot in the bucket
+    pub closing_value: Option<f64>,
+    pub unrealized_pnl: Option<f64>,
+}
+
+impl PnlBucket {
+    fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            realized_pnl: 0.0,
+            trades: 0,
+            wins: 0,
+            losses: 0,
+            win_rate: 0.0,
+            closing_value: None,
+            unrealized_pnl: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Resolution {
+    Hourly,
+    Daily,
+}
+
+impl Resolution {
+    fn floor(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let step = match self {
+            Resolution::Hourly => Duration::hours(1),
+            Resolution::Daily => Duration::days(1),
+        };
+        at.duration_trunc(step).unwrap_or(at)
+    }
+
+    fn capacity(self) -> usize {
+        match self {
+            Resolution::Hourly => MAX_HOURLY_BUCKETS,
+            Resolution::Daily => MAX_DAILY_BUCKETS,
+        }
+    }
+}
+
+/// Chronological buckets of one resolution
+#[derive(Debug, Clone, Default)]
+struct BucketSeries {
+    buckets: Vec<PnlBucket>,
+}
+
+impl BucketSeries {
+    /// Bucket containing `at`, created if missing (late trades land in their own bucket)
+    fn bucket(&mut self, resolution: Resolution, at: DateTime<Utc>) -> Option<&mut PnlBucket> {
+        let start = resolution.floor(at);
+        let idx = match self.buckets.binary_search_by_key(&start, |b| b.start) {
+            Ok(idx) => idx,
+            // Older than everything retained
+            Err(0) if self.buckets.len() >= resolution.capacity() => return None,
+            Err(idx) => {
+                self.buckets.insert(idx, PnlBucket::new(start));
+                if self.buckets.len() > resolution.capacity() {
+                    self.buckets.remove(0);
+                    idx - 1
+                } else {
+                    idx
+                }
+            }
+        };
+        Some(&mut self.buckets[idx])
+    }
+
+    fn last(&self, n: usize) -> Vec<PnlBucket> {
+        let skip = self.buckets.len().saturating_sub(n);
+        self.buckets[skip..].to_vec()
+    }
+}
+
+/// Realized PnL, trade count and win rate per hour and per UTC day, for the dashboard's
+/// calendar heatmap. Persisted to `data_dir/pnl_buckets.json` when loaded from a data dir
+#[derive(Debug, Default)]
+pub struct PnlCalendar {
+    hourly: BucketSeries,
+    daily: BucketSeries,
+    path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PnlCalendarFile {
+    hourly: Vec<PnlBucket>,
+    daily: Vec<PnlBucket>,
+}
+
+impl PnlCalendar {
+    pub fn load(data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(PNL_BUCKETS_FILE);
+        let file = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str::<PnlCalendarFile>(&raw)
+                .map(Some)
+                .unwrap_or_else(|e| {
+                    warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                    None
+                }),
+            Err(_) => None,
+        };
+        let (hourly, daily) = file.map(|f| (f.hourly, f.daily)).unwrap_or_default();
+
+        Self {
+            hourly: BucketSeries { buckets: hourly },
+            daily: BucketSeries { buckets: daily },
+            path: Some(path),
+        }
+    }
+
+    /// No-op for in-memory calendars
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = PnlCalendarFile {
+            hourly: self.hourly.buckets.clone(),
+            daily: self.daily.buckets.clone(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&file)?)?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    /// Count a closed (or partially closed) trade in the buckets of its exit time
+    pub fn record_trade(&mut self, trade: &Trade) {
+        let (Some(pnl), Some(at)) = (trade.realized_pnl, trade.exit_time) else {
+            return;
+        };
+        for (series, resolution) in [
+            (&mut self.hourly, Resolution::Hourly),
+            (&mut self.daily, Resolution::Daily),
+        ] {
+            let Some(bucket) = series.bucket(resolution, at) else {
+                continue;
+            };
+            bucket.realized_pnl += pnl;
+            bucket.trades += 1;
+            if pnl > 0.0 {
+                bucket.wins += 1;
+            } else if pnl < 0.0 {
+                bucket.losses += 1;
+            }
+            bucket.win_rate = bucket.wins as f64 / bucket.trades as f64;
+        }
+    }
+
+    pub fn record_snapshot(&mut self, snapshot: &PortfolioSnapshot) {
+        for (series, resolution) in [
+            (&mut self.hourly, Resolution::Hourly),
+            (&mut self.daily, Resolution::Daily),
+        ] {
+            let Some(bucket) = series.bucket(resolution, snapshot.timestamp) else {
+                continue;
+            };
+            bucket.closing_value = Some(snapshot.total_value);
+            bucket.unrealized_pnl = Some(snapshot.unrealized_pnl);
+        }
+    }
+
+    /// Most recent `hours` hourly buckets, oldest first (hours without activity are absent)
+    pub fn hourly(&self, hours: usize) -> Vec<PnlBucket> {
+        self.hourly.last(hours)
+    }
+
+    /// Most recent `days` daily buckets, oldest first (days without activity are absent)
+    pub fn daily(&self, days: usize) -> Vec<PnlBucket> {
+        self.daily.last(days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(exit: &str, pnl: f64) -> Trade {
+        let at = DateTime::parse_from_rfc3339(exit)
+            .unwrap()
+            .with_timezone(&Utc);
+        Trade {
+            id: "t".to_string(),
+            market_id: "m1".to_string(),
+            market_question: "Will it happen?".to_string(),
+            side: "YES".to_string(),
+            size: 10.0,
+            entry_price: 0.5,
+            exit_price: Some(0.6),
+            entry_time: at,
+            exit_time: Some(at),
+            realized_pnl: Some(pnl),
+            config_hash: None,
+            note: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_trades_bucket_by_hour_and_day() {
+        let mut calendar = PnlCalendar::default();
+        calendar.record_trade(&trade("2024-03-01T10:15:00Z", 2.0));
+        calendar.record_trade(&trade("2024-03-01T10:45:00Z", -1.0));
+        calendar.record_trade(&trade("2024-03-02T01:00:00Z", 3.0));
+        // Late arrival for an earlier hour
+        calendar.record_trade(&trade("2024-03-01T08:30:00Z", 1.0));
+
+        let hourly = calendar.hourly(24);
+        assert_eq!(hourly.len(), 3);
+        assert_eq!(hourly[0].start.to_rfc3339(), "2024-03-01T08:00:00+00:00");
+        assert_eq!(hourly[1].trades, 2);
+        assert_eq!(hourly[1].win_rate, 0.5);
+
+        let daily = calendar.daily(7);
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].realized_pnl, 2.0);
+        assert_eq!(daily[0].trades, 3);
+        assert_eq!(daily[1].realized_pnl, 3.0);
+
+        let dir = std::env::temp_dir().join(format!("pnl-buckets-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let mut persisted = PnlCalendar::load(data_dir);
+        persisted.record_trade(&trade("2024-03-01T10:15:00Z", 2.0));
+        persisted.save().unwrap();
+        assert_eq!(PnlCalendar::load(data_dir).daily(1)[0].trades, 1);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,591 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use super::edge_realization::{EdgeRealization, EdgeRealizationTracker};
+use super::execution_drift::ExecutionDriftTracker;
+use super::pnl::Trade;
+
+pub const POSTMORTEM_FILE: &str = "postmortems.json";
+
+/// Postmortems kept (oldest evicted first)
+const MAX_POSTMORTEMS: usize = 1000;
+
+/// Most recent postmortems included in the report
+const RECENT_POSTMORTEMS: usize = 20;
+
+/// Trade ids remembered as reviewed (oldest forgotten first); only the losses closed around the
+/// last sweep are looked at, so this only has to cover those
+const MAX_REVIEWED: usize = 10_000;
+
+/// How far before the last sweep a sweep looks back, for losses recorded late
+const REVIEW_OVERLAP_SECS: i64 = 300;
+
+/// Signal -> post ask drift (bps) past which a losing directional entry is blamed on its quote
+const STALE_DRIFT_BPS: f64 = 100.0;
+
+/// Fill gap between the legs of an arbitrage past which the pair counts as single-legged
+const UNHEDGED_FILL_GAP: f64 = 0.05;
+
+/// Why a trade lost or failed, by the subsystem to look at
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+    /// The book moved between the signal and the order post (feed / latency)
+    StaleQuote,
+    /// The CLOB refused the order with a 429 (submission pacing)
+    RateLimit,
+    /// One arbitrage leg filled without the other (execution plan / unwinds)
+    SingleLeg,
+    /// The market resolved against the position (signal)
+    AdverseResolution,
+    /// A fully hedged pair lost money: fees ate more than the strategy priced in
+    FeeMiscalculation,
+    /// Nothing recorded points at a cause
+    Unclassified,
+}
+
+/// One losing or failed trade and what it was put down to
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Postmortem {
+    /// Trade id of the position (arbitrage: the plan id, covering both legs)
+    pub trade_id: String,
+    pub market_id: String,
+    pub at: DateTime<Utc>,
+    pub cause: Cause,
+    /// Realized loss (USD, positive). None for orders that failed outright
+    pub loss_usd: Option<f64>,
+    /// The evidence, e.g. the drift or the leg fills
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CauseSummary {
+    pub cause: Cause,
+    pub count: usize,
+    /// Orders that failed outright among them
+    pub failures: usize,
+    pub loss_usd: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct PostmortemReport {
+    pub total: usize,
+    pub loss_usd: f64,
+    /// Largest loss first: the subsystem to improve next
+    pub causes: Vec<CauseSummary>,
+    pub recent: Vec<Postmortem>,
+}
+
+/// Cause of a failed order call, from its error
+pub fn classify_failure(error: &str) -> Cause {
+    let error = error.to_lowercase();
+    if error.contains("429") || error.contains("rate limit") || error.contains("too many requests")
+    {
+        Cause::RateLimit
+    } else if error.contains("stale") {
+        Cause::StaleQuote
+    } else {
+        Cause::Unclassified
+    }
+}
+
+/// Cause of a settled arbitrage that lost money, given the worst ask drift of its legs
+pub fn classify_arbitrage(edge: &EdgeRealization, drift_bps: Option<f64>) -> (Cause, String) {
+    let fills: Vec<f64> = edge
+        .legs
+        .iter()
+        .map(|l| l.filled_fraction.unwrap_or(0.0))
+        .collect();
+    let gap = fills.iter().copied().fold(0.0, f64::max) - fills.iter().copied().fold(1.0, f64::min);
+    if edge.legs.len() < 2 || gap > UNHEDGED_FILL_GAP {
+        let legs: Vec<String> = edge
+            .legs
+            .iter()
+            .zip(&fills)
+            .map(|(l, f)| format!("{} {:.0}%", l.side, f * 100.0))
+            .collect();
+        return (Cause::SingleLeg, format!("legs filled {}", legs.join(", ")));
+    }
+    if let Some(drift) = drift_bps.filter(|d| *d >= edge.expected_profit_bps as f64) {
+        return (
+            Cause::StaleQuote,
+            format!(
+                "asks drifted {:.1} bps against an expected edge of {} bps",
+                drift, edge.expected_profit_bps
+            ),
+        );
+    }
+    (
+        Cause::FeeMiscalculation,
+        format!(
+            "hedged pair realized {:.1} bps vs {} bps expected net of {} bps fees per leg",
+            edge.realized_bps.unwrap_or(0.0),
+            edge.expected_profit_bps,
+            edge.fee_per_leg_bps
+        ),
+    )
+}
+
+/// Cause of a directional trade that closed at a loss, given the ask drift of its entry
+pub fn classify_directional(trade: &Trade, drift_bps: Option<f64>) -> (Cause, String) {
+    if let Some(drift) = drift_bps.filter(|d| *d >= STALE_DRIFT_BPS) {
+        return (
+            Cause::StaleQuote,
+            format!("ask drifted {:.1} bps between signal and post", drift),
+        );
+    }
+    // Settlement books a loser at 0
+    if trade.exit_price.is_some_and(|p| p <= 0.0) {
+        return (
+            Cause::AdverseResolution,
+            format!("{} resolved against the position", trade.side),
+        );
+    }
+    (
+        Cause::Unclassified,
+        format!(
+            "exited at {:.4} after entering at {:.4}",
+            trade.exit_price.unwrap_or(0.0),
+            trade.entry_price
+        ),
+    )
+}
+
+/// What `postmortems.json` holds
+#[derive(Default, Serialize, Deserialize)]
+struct Stored {
+    postmortems: VecDeque<Postmortem>,
+    /// Trade ids already classified, oldest first
+    reviewed: VecDeque<String>,
+    reviewed_through: Option<DateTime<Utc>>,
+}
+
+/// `postmortems.json` as first written: the postmortems alone
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredFile {
+    Current(Stored),
+    Legacy(VecDeque<Postmortem>),
+}
+
+/// The losses a sweep looks at, gathered from the journal, the arbitrage tracker and the drift
+/// tracker one lock at a time
+pub struct LossSweep {
+    since: Option<DateTime<Utc>>,
+    arbitrages: Vec<(EdgeRealization, Option<f64>)>,
+    trades: Vec<(Trade, Option<f64>)>,
+}
+
+impl LossSweep {
+    /// Closed directional trades that lost money since the last sweep
+    pub fn collect_trades(&mut self, trades: &[Trade]) {
+        let since = self.since;
+        self.trades.extend(
+            trades
+                .iter()
+                .rev()
+                .filter(|t| t.realized_pnl.is_some_and(|pnl| pnl < 0.0))
+                .filter(|t| since.is_none_or(|since| t.exit_time.is_none_or(|at| at >= since)))
+                .map(|t| (t.clone(), None)),
+        );
+    }
+
+    /// Settled arbitrages that lost money since the last sweep. Their legs are judged as a
+    /// pair, so collected after the trades to drop them from those
+    pub fn collect_arbitrages(&mut self, edges: &EdgeRealizationTracker) {
+        let since = self.since;
+        self.arbitrages.extend(
+            edges
+                .settled()
+                .filter(|e| e.realized_pnl_usd.is_some_and(|pnl| pnl < 0.0))
+                .filter(|e| since.is_none_or(|since| e.settled_at.is_none_or(|at| at >= since)))
+                .map(|e| (e.clone(), None)),
+        );
+        self.trades.retain(|(t, _)| !edges.covers(&t.id));
+    }
+
+    /// The worst ask drift of each loss's entry
+    pub fn attach_drift(&mut self, drift: &ExecutionDriftTracker) {
+        for (edge, drift_bps) in &mut self.arbitrages {
+            *drift_bps = drift.worst_drift_bps(&edge.trade_id);
+        }
+        for (trade, drift_bps) in &mut self.trades {
+            *drift_bps = drift.worst_drift_bps(&trade.id);
+        }
+    }
+}
+
+/// Classifies every losing or failed trade from what was recorded around it (execution drift,
+/// leg fills, settlement) and tallies the causes. Persisted in `data_dir/postmortems.json`
+#[derive(Default)]
+pub struct PostmortemLog {
+    path: Option<PathBuf>,
+    postmortems: VecDeque<Postmortem>,
+    /// Trades already classified (bounded, oldest first), so a sweep never reports one twice
+    reviewed: VecDeque<String>,
+    seen: HashSet<String>,
+    /// When the last sweep ran: the next only looks at losses from shortly before
+    reviewed_through: Option<DateTime<Utc>>,
+}
+
+impl PostmortemLog {
+    /// In-memory log (nothing persisted)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load recorded postmortems from `data_dir` (empty if none were recorded yet)
+    pub fn load(data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(POSTMORTEM_FILE);
+        let stored = match fs::read_to_string(&path) {
+            Ok(raw) => match serde_json::from_str(&raw) {
+                Ok(StoredFile::Current(stored)) => stored,
+                Ok(StoredFile::Legacy(postmortems)) => Stored {
+                    reviewed: postmortems.iter().map(|p| p.trade_id.clone()).collect(),
+                    postmortems,
+                    reviewed_through: None,
+                },
+                Err(e) => {
+                    warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                    Stored::default()
+                }
+            },
+            Err(_) => Stored::default(),
+        };
+        Self {
+            path: Some(path),
+            postmortems: stored.postmortems,
+            seen: stored.reviewed.iter().cloned().collect(),
+            reviewed: stored.reviewed,
+            reviewed_through: stored.reviewed_through,
+        }
+    }
+
+    /// No-op for in-memory logs
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let stored = Stored {
+            postmortems: self.postmortems.clone(),
+            reviewed: self.reviewed.clone(),
+            reviewed_through: self.reviewed_through,
+        };
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&stored)?)?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to persist postmortems: {}", e);
+        }
+    }
+
+    fn push(&mut self, postmortem: Postmortem) {
+        info!(
+            "🩺 Postmortem {}: {:?} ({})",
+            postmortem.trade_id, postmortem.cause, postmortem.detail
+        );
+        if self.postmortems.len() >= MAX_POSTMORTEMS {
+            self.postmortems.pop_front();
+        }
+        self.mark_reviewed(&postmortem.trade_id);
+        self.postmortems.push_back(postmortem);
+    }
+
+    fn mark_reviewed(&mut self, trade_id: &str) {
+        if !self.seen.insert(trade_id.to_string()) {
+            return;
+        }
+        if self.reviewed.len() >= MAX_REVIEWED {
+            if let Some(oldest) = self.reviewed.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.reviewed.push_back(trade_id.to_string());
+    }
+
+    /// A sweep of the losses since the last one, to fill and hand to `review`
+    pub fn sweep(&self) -> LossSweep {
+        LossSweep {
+            since: self
+                .reviewed_through
+                .map(|at| at - Duration::seconds(REVIEW_OVERLAP_SECS)),
+            arbitrages: Vec::new(),
+            trades: Vec::new(),
+        }
+    }
+
+    /// An order call that failed outright
+    pub fn record_failure(
+        &mut self,
+        trade_id: &str,
+        market_id: &str,
+        error: &str,
+        now: DateTime<Utc>,
+    ) {
+        self.push(Postmortem {
+            trade_id: trade_id.to_string(),
+            market_id: market_id.to_string(),
+            at: now,
+            cause: classify_failure(error),
+            loss_usd: None,
+            detail: error.to_string(),
+        });
+        self.persist();
+    }
+
+    /// Classify the losses of `sweep` not looked at yet: settled arbitrages that lost money,
+    /// and closed directional trades that did. Returns how many were added
+    pub fn review(&mut self, sweep: LossSweep, now: DateTime<Utc>) -> usize {
+        let mut added = Vec::new();
+        for (edge, drift_bps) in &sweep.arbitrages {
+            let Some(pnl) = edge.realized_pnl_usd.filter(|pnl| *pnl < 0.0) else {
+                continue;
+            };
+            if self.seen.contains(&edge.trade_id) {
+                continue;
+            }
+            let (cause, detail) = classify_arbitrage(edge, *drift_bps);
+            added.push(Postmortem {
+                trade_id: edge.trade_id.clone(),
+                market_id: edge.market_id.clone(),
+                at: edge.settled_at.unwrap_or(now),
+                cause,
+                loss_usd: Some(-pnl),
+                detail,
+            });
+        }
+        for (trade, drift_bps) in &sweep.trades {
+            let Some(pnl) = trade.realized_pnl.filter(|pnl| *pnl < 0.0) else {
+                continue;
+            };
+            if self.seen.contains(&trade.id) {
+                continue;
+            }
+            let (cause, detail) = classify_directional(trade, *drift_bps);
+            added.push(Postmortem {
+                trade_id: trade.id.clone(),
+                market_id: trade.market_id.clone(),
+                at: trade.exit_time.unwrap_or(now),
+                cause,
+                loss_usd: Some(-pnl),
+                detail,
+            });
+        }
+
+        let count = added.len();
+        for postmortem in added {
+            self.push(postmortem);
+        }
+        // Only persisted with a new postmortem: a sweep with none has nothing to remember
+        self.reviewed_through = Some(now);
+        if count > 0 {
+            self.persist();
+        }
+        count
+    }
+
+    pub fn report(&self) -> PostmortemReport {
+        let mut by_cause: BTreeMap<Cause, CauseSummary> = BTreeMap::new();
+        for postmortem in &self.postmortems {
+            let summary = by_cause
+                .entry(postmortem.cause)
+                .or_insert_with(|| CauseSummary {
+                    cause: postmortem.cause,
+                    count: 0,
+                    failures: 0,
+                    loss_usd: 0.0,
+                });
+            summary.count += 1;
+            summary.failures += postmortem.loss_usd.is_none() as usize;
+            summary.loss_usd += postmortem.loss_usd.unwrap_or(0.0);
+        }
+        let mut causes: Vec<CauseSummary> = by_cause.into_values().collect();
+        causes.sort_by(|a, b| {
+            b.loss_usd
+                .total_cmp(&a.loss_usd)
+                .then(b.count.cmp(&a.count))
+        });
+
+        PostmortemReport {
+            total: self.postmortems.len(),
+            loss_usd: causes.iter().map(|c| c.loss_usd).sum(),
+            causes,
+            recent: self
+                .postmortems
+                .iter()
+                .rev()
+                .take(RECENT_POSTMORTEMS)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::edge_realization::LegFill;
+    use crate::analytics::execution_drift::DriftSample;
+
+    fn edge(yes_fill: f64, no_fill: f64) -> EdgeRealization {
+        let leg = |side: &str, fill: f64| LegFill {
+            side: side.to_string(),
+            order_id: Some(format!("order-{}", side)),
+            limit_price: 0.48,
+            size_usd: 48.0,
+            filled_fraction: Some(fill),
+        };
+        EdgeRealization {
+            trade_id: "arb_m1_1".to_string(),
+            market_id: "m1".to_string(),
+            signal_at: Utc::now(),
+            expected_profit_bps: 200,
+            fee_per_leg_bps: 100,
+            legs: vec![leg("YES", yes_fill), leg("NO", no_fill)],
+            settled_at: None,
+            realized_pnl_usd: None,
+            realized_bps: None,
+        }
+    }
+
+    fn trade(id: &str, exit_price: f64) -> Trade {
+        Trade {
+            id: id.to_string(),
+            market_id: "m2".to_string(),
+            market_question: "Will it?".to_string(),
+            side: "YES".to_string(),
+            size: 10.0,
+            entry_price: 0.9,
+            exit_price: Some(exit_price),
+            entry_time: Utc::now(),
+            exit_time: Some(Utc::now()),
+            realized_pnl: Some(10.0 * (exit_price - 0.9)),
+            config_hash: None,
+            note: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_losses_and_failures_are_classified_and_tallied() {
+        assert_eq!(
+            classify_failure("post order: HTTP 429 Too Many Requests"),
+            Cause::RateLimit
+        );
+        assert_eq!(
+            classify_failure("insufficient balance"),
+            Cause::Unclassified
+        );
+
+        // Uneven fills first, then drift past the edge, then the fees
+        assert_eq!(
+            classify_arbitrage(&edge(1.0, 0.0), None).0,
+            Cause::SingleLeg
+        );
+        assert_eq!(
+            classify_arbitrage(&edge(1.0, 1.0), Some(250.0)).0,
+            Cause::StaleQuote
+        );
+        assert_eq!(
+            classify_arbitrage(&edge(1.0, 1.0), Some(50.0)).0,
+            Cause::FeeMiscalculation
+        );
+        assert_eq!(
+            classify_directional(&trade("snipe_m2_1", 0.0), None).0,
+            Cause::AdverseResolution
+        );
+        assert_eq!(
+            classify_directional(&trade("snipe_m2_1", 0.5), Some(150.0)).0,
+            Cause::StaleQuote
+        );
+
+        let now = Utc::now();
+        let dir = std::env::temp_dir().join(format!("postmortem-{}", uuid::Uuid::new_v4()));
+        let mut edges = EdgeRealizationTracker::load(dir.to_str().unwrap());
+        edges.open(edge(1.0, 0.0));
+        edges.settle_market("m1", 0.0, 1.0, now);
+        let mut drift = ExecutionDriftTracker::new(None);
+        drift.record(DriftSample {
+            trade_id: "pred_m2_1".to_string(),
+            market_id: "m2".to_string(),
+            side: "YES".to_string(),
+            signal_at: now,
+            signal_ask: 0.9,
+            post_ask: 0.92,
+            latency_us: 40_000,
+        });
+        let trades = vec![
+            trade("arb_m1_1_YES", 0.0),
+            trade("pred_m2_1", 0.5),
+            trade("snipe_m2_2", 0.0),
+            trade("snipe_m2_3", 1.0),
+        ];
+
+        let sweep = |log: &PostmortemLog| {
+            let mut sweep = log.sweep();
+            sweep.collect_trades(&trades);
+            sweep.collect_arbitrages(&edges);
+            sweep.attach_drift(&drift);
+            sweep
+        };
+        let mut log = PostmortemLog::new();
+        log.record_failure("arb_m3_1", "m3", "HTTP 429 Too Many Requests", now);
+        assert_eq!(log.review(sweep(&log), now), 3);
+        // Reviewed trades aren't reported again
+        assert_eq!(log.review(sweep(&log), now), 0);
+
+        let report = log.report();
+        assert_eq!(report.total, 4);
+        let cause = |c: Cause| report.causes.iter().find(|s| s.cause == c).unwrap();
+        assert_eq!(cause(Cause::SingleLeg).count, 1);
+        assert_eq!(cause(Cause::StaleQuote).count, 1);
+        assert_eq!(cause(Cause::AdverseResolution).count, 1);
+        assert_eq!(cause(Cause::RateLimit).failures, 1);
+        assert_eq!(cause(Cause::RateLimit).loss_usd, 0.0);
+        // Costliest cause first
+        assert_eq!(report.causes[0].cause, Cause::SingleLeg);
+        assert!(report
+            .causes
+            .windows(2)
+            .all(|w| w[0].loss_usd >= w[1].loss_usd));
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_sweeps_skip_losses_reviewed_before_a_restart() {
+        let now = Utc::now();
+        let dir = std::env::temp_dir().join(format!("postmortem-{}", uuid::Uuid::new_v4()));
+        let mut old = trade("snipe_m2_1", 0.0);
+        old.exit_time = Some(now - Duration::hours(1));
+        let trades = vec![old, trade("snipe_m2_2", 0.0)];
+
+        let mut log = PostmortemLog::load(dir.to_str().unwrap());
+        let mut sweep = log.sweep();
+        sweep.collect_trades(&trades[..1]);
+        assert_eq!(log.review(sweep, now - Duration::minutes(30)), 1);
+
+        // Reloaded: the old loss is past the last sweep, only the new one is looked at
+        let mut log = PostmortemLog::load(dir.to_str().unwrap());
+        let mut sweep = log.sweep();
+        sweep.collect_trades(&trades);
+        assert_eq!(sweep.trades.len(), 1);
+        assert_eq!(log.review(sweep, now), 1);
+        assert_eq!(log.report().total, 2);
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use super::attribution::strategy_of;
+
+/// Page size when none is asked for
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+/// Largest page served in one response
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Exit time for trades, entry time for open positions
+    #[default]
+    Time,
+    /// Realized PnL for trades, unrealized for open positions
+    Pnl,
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Filters, sort and page of a trade / position listing (GET /api/trades, /api/positions)
+/// The PnL breakdowns take the same filters and ignore the paging
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Page size (default 100, at most 1000)
+    pub limit: Option<usize>,
+    /// Matching items skipped before the page
+    pub offset: Option<usize>,
+    pub market_id: Option<String>,
+    /// Only items carrying this tag (trades only)
+    pub tag: Option<String>,
+    /// Time range (RFC 3339), inclusive
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Only items opened by this strategy (trade id prefix, e.g. `arb`)
+    pub strategy: Option<String>,
+    /// Only items opened under this session config
+    pub config_hash: Option<String>,
+    pub sort: Option<SortKey>,
+    pub order: Option<SortOrder>,
+}
+
+impl ListQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset.unwrap_or(0)
+    }
+
+    pub fn matches(&self, market_id: &str, at: DateTime<Utc>, tags: &[String]) -> bool {
+        self.market_id.as_deref().is_none_or(|m| m == market_id)
+            && self
+                .tag
+                .as_deref()
+                .is_none_or(|tag| tags.iter().any(|t| t == tag))
+            && self.from.is_none_or(|from| at >= from)
+            && self.to.is_none_or(|to| at <= to)
+    }
+
+    /// Strategy / config filters, on an item's trade id and config hash
+    pub fn matches_origin(&self, id: &str, config_hash: Option<&str>) -> bool {
+        self.strategy
+            .as_deref()
+            .is_none_or(|s| s == strategy_of(id))
+            && self
+                .config_hash
+                .as_deref()
+                .is_none_or(|hash| config_hash == Some(hash))
+    }
+
+    /// Sort matching items (`key` gives an item's value for a sort key) and cut the page
+    /// Returns the number of matching items along with the page
+    pub fn page<T: Clone>(
+        &self,
+        mut items: Vec<&T>,
+        key: impl Fn(&T, SortKey) -> f64,
+    ) -> (usize, Vec<T>) {
+        let sort = self.sort.unwrap_or_default();
+        // Stable: equal keys keep their recorded order, reversed along with the rest
+        items.sort_by(|a, b| key(a, sort).total_cmp(&key(b, sort)));
+        if self.order.unwrap_or_default() == SortOrder::Desc {
+            items.reverse();
+        }
+        let total = items.len();
+        let page = items
+            .into_iter()
+            .skip(self.offset())
+            .take(self.limit())
+            .cloned()
+            .collect();
+        (total, page)
+    }
+}
+
+/// Sort value of a timestamp
+pub fn time_key(at: DateTime<Utc>) -> f64 {
+    at.timestamp_millis() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_filters_sorts_and_pages() {
+        let t0 = Utc::now();
+        // (market, minutes after t0, pnl)
+        let items = [
+            ("m1", 0, 1.0),
+            ("m2", 1, -2.0),
+            ("m1", 2, 3.0),
+            ("m1", 3, 0.5),
+        ];
+        let select = |query: &ListQuery| {
+            let matching = items
+                .iter()
+                .filter(|(m, min, _)| query.matches(m, t0 + Duration::minutes(*min), &[]))
+                .collect();
+            query.page(matching, |(_, min, pnl), key| match key {
+                SortKey::Pnl => *pnl,
+                _ => *min as f64,
+            })
+        };
+
+        // Newest first by default
+        let (total, page) = select(&ListQuery {
+            market_id: Some("m1".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(total, 3);
+        assert_eq!(page.iter().map(|i| i.1).collect::<Vec<_>>(), vec![3, 2]);
+
+        let (total, page) = select(&ListQuery {
+            from: Some(t0 + Duration::minutes(1)),
+            sort: Some(SortKey::Pnl),
+            order: Some(SortOrder::Asc),
+            offset: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(total, 3);
+        assert_eq!(page.iter().map(|i| i.2).collect::<Vec<_>>(), vec![0.5, 3.0]);
+
+        assert_eq!(
+            ListQuery {
+                limit: Some(10_000),
+                ..Default::default()
+            }
+            .limit(),
+            MAX_PAGE_SIZE
+        );
+        assert!(!ListQuery {
+            tag: Some("late fill".to_string()),
+            ..Default::default()
+        }
+        .matches("m1", t0, &[]));
+        let arb = ListQuery {
+            strategy: Some("arb".to_string()),
+            ..Default::default()
+        };
+        assert!(arb.matches_origin("arb_m1_1_YES", None));
+        assert!(!arb.matches_origin("pred_m1_1-hedge", None));
+    }
+}
@@ -0,0 +1,288 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::polymarket::MarketData;
+
+/// Ended markets still unresolved after this long are given up on
+const MAX_PENDING_DAYS: i64 = 7;
+
+/// Quoted outcome prices of a resolved market: the winner at 1, the loser at 0
+const RESOLVED_PRICE_EPSILON: f64 = 0.005;
+
+/// YES / NO asks of a market at one point of its last hours
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSample {
+    pub at: DateTime<Utc>,
+    pub yes_price: f64,
+    pub no_price: f64,
+}
+
+impl PriceSample {
+    fn price(&self, side: &str) -> f64 {
+        if side == "YES" {
+            self.yes_price
+        } else {
+            self.no_price
+        }
+    }
+}
+
+/// Final outcome of a market alongside its price trajectory before the end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketResolution {
+    pub market_id: String,
+    pub question: String,
+    pub end_date: DateTime<Utc>,
+    /// YES or NO
+    pub winner: String,
+    pub resolved_at: DateTime<Utc>,
+    /// Oldest first, up to the end date
+    pub trajectory: Vec<PriceSample>,
+}
+
+impl MarketResolution {
+    /// Last sample at least `before` ahead of the end date
+    pub fn sample_before_end(&self, before: Duration) -> Option<&PriceSample> {
+        let cutoff = self.end_date - before;
+        self.trajectory.iter().rev().find(|s| s.at <= cutoff)
+    }
+}
+
+struct TrackedMarket {
+    question: String,
+    end_date: DateTime<Utc>,
+    samples: Vec<PriceSample>,
+}
+
+/// Samples the prices of markets in their last hours and turns them into `MarketResolution`s
+/// once the outcome is known, building the dataset the expiration strategy is calibrated on
+pub struct ResolutionRecorder {
+    window: Duration,
+    sample_interval: Duration,
+    tracked: HashMap<String, TrackedMarket>,
+}
+
+impl ResolutionRecorder {
+    pub fn new(window_hours: u64, sample_secs: u64) -> Self {
+        Self {
+            window: Duration::hours(window_hours.min(i64::MAX as u64 / 3600) as i64),
+            sample_interval: Duration::seconds(sample_secs.max(1).min(i64::MAX as u64) as i64),
+            tracked: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracked.is_empty()
+    }
+
+    /// Sample markets inside the window before their end (at most once per sample interval)
+    pub fn observe(&mut self, markets: &[MarketData], now: DateTime<Utc>) {
+        for market in markets {
+            let Some(end) = market.end_time else {
+                continue;
+            };
+            if end <= now || end - now > self.window {
+                continue;
+            }
+            // Not quoted on both sides yet (or evicted from the WS)
+            if market.yes_price <= 0.0 || market.no_price <= 0.0 {
+                continue;
+            }
+            let tracked = self
+                .tracked
+                .entry(market.id.to_string())
+                .or_insert_with(|| TrackedMarket {
+                    question: market.question.clone(),
+                    end_date: end,
+                    samples: Vec::new(),
+                });
+            if tracked
+                .samples
+                .last()
+                .is_some_and(|last| now - last.at < self.sample_interval)
+            {
+                continue;
+            }
+            tracked.samples.push(PriceSample {
+                at: now,
+                yes_price: market.yes_price,
+                no_price: market.no_price,
+            });
+        }
+        let give_up = now - Duration::days(MAX_PENDING_DAYS);
+        self.tracked.retain(|_, t| t.end_date > give_up);
+    }
+
+    /// Ended markets waiting for their outcome
+    pub fn pending(&self, now: DateTime<Utc>) -> Vec<String> {
+        self.tracked
+            .iter()
+            .filter(|(_, t)| t.end_date <= now)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Record the outcome of a tracked market. None if it wasn't tracked
+    pub fn resolve(
+        &mut self,
+        market_id: &str,
+        winner: &str,
+        now: DateTime<Utc>,
+    ) -> Option<MarketResolution> {
+        let tracked = self.tracked.remove(market_id)?;
+        Some(MarketResolution {
+            market_id: market_id.to_string(),
+            question: tracked.question,
+            end_date: tracked.end_date,
+            winner: winner.to_string(),
+            resolved_at: now,
+            trajectory: tracked.samples,
+        })
+    }
+}
+
+/// Winner of a market from its quoted outcome prices, once they have settled at 1 / 0
+pub fn winner_from_prices(yes_price: f64, no_price: f64) -> Option<&'static str> {
+    let settled = |winner: f64, loser: f64| {
+        winner >= 1.0 - RESOLVED_PRICE_EPSILON && loser <= RESOLVED_PRICE_EPSILON
+    };
+    if settled(yes_price, no_price) {
+        Some("YES")
+    } else if settled(no_price, yes_price) {
+        Some("NO")
+    } else {
+        None
+    }
+}
+
+/// Outcomes priced in `[price_from, price_to)` some time before the end, and how many won
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationBucket {
+    pub price_from: f64,
+    pub price_to: f64,
+    pub outcomes: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub avg_price: f64,
+}
+
+impl CalibrationBucket {
+    /// Win rate minus the price paid: the edge of buying every outcome of the bucket
+    pub fn edge(&self) -> f64 {
+        self.win_rate - self.avg_price
+    }
+}
+
+/// Win rate of outcomes by their price `before` the end date. Both sides of every market count,
+/// so the 0.95 bucket holds favourites and the 0.05 bucket their complements
+pub fn calibration(
+    resolutions: &[MarketResolution],
+    before: Duration,
+    bucket_width: f64,
+) -> Vec<CalibrationBucket> {
+    let width = bucket_width.clamp(0.001, 1.0);
+    let count = (1.0 / width).ceil() as usize;
+    let mut buckets: Vec<CalibrationBucket> = (0..count)
+        .map(|i| CalibrationBucket {
+            price_from: i as f64 * width,
+            price_to: ((i + 1) as f64 * width).min(1.0),
+            outcomes: 0,
+            wins: 0,
+            win_rate: 0.0,
+            avg_price: 0.0,
+        })
+        .collect();
+
+    for resolution in resolutions {
+        let Some(sample) = resolution.sample_before_end(before) else {
+            continue;
+        };
+        for side in ["YES", "NO"] {
+            let price = sample.price(side);
+            if !(0.0..=1.0).contains(&price) {
+                continue;
+            }
+            // Nudged so prices on a boundary (0.95 / 0.05) land in the bucket they start
+            let index = (price / width + 1e-9) as usize;
+            let bucket = &mut buckets[index.min(count - 1)];
+            bucket.outcomes += 1;
+            bucket.wins += usize::from(resolution.winner == side);
+            bucket.avg_price += price;
+        }
+    }
+
+    buckets.retain(|b| b.outcomes > 0);
+    for bucket in &mut buckets {
+        bucket.win_rate = bucket.wins as f64 / bucket.outcomes as f64;
+        bucket.avg_price /= bucket.outcomes as f64;
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(id: &str, end: DateTime<Utc>, yes_price: f64) -> MarketData {
+        MarketData {
+            id: id.into(),
+            question: format!("{}?", id),
+            end_date: Some(end.to_rfc3339()),
+            yes_price,
+            no_price: 1.0 - yes_price,
+            ..Default::default()
+        }
+        .with_end_time()
+    }
+
+    #[test]
+    fn test_records_trajectories_and_calibrates() {
+        let start = Utc::now();
+        let end = start + Duration::hours(1);
+        let mut recorder = ResolutionRecorder::new(2, 60);
+
+        // Favourites at 0.95 an hour out: three win, one loses
+        for minute in [0, 1, 30] {
+            let now = start + Duration::minutes(minute);
+            let markets: Vec<MarketData> = (0..4)
+                .map(|i| market(&format!("m{}", i), end, 0.95))
+                .chain([market("later", end + Duration::hours(5), 0.5)])
+                .collect();
+            recorder.observe(&markets, now);
+            // Within the sample interval: ignored
+            recorder.observe(&markets, now + Duration::seconds(10));
+        }
+        assert_eq!(recorder.len(), 4);
+        assert!(recorder.pending(start).is_empty());
+
+        let after = end + Duration::minutes(10);
+        let mut pending = recorder.pending(after);
+        pending.sort();
+        let resolutions: Vec<MarketResolution> = pending
+            .iter()
+            .map(|id| {
+                let winner = if id == "m3" { "NO" } else { "YES" };
+                recorder.resolve(id, winner, after).unwrap()
+            })
+            .collect();
+        assert!(recorder.is_empty());
+        assert_eq!(resolutions[0].trajectory.len(), 3);
+
+        let buckets = calibration(&resolutions, Duration::minutes(45), 0.05);
+        assert_eq!(buckets.len(), 2);
+        let favourites = &buckets[1];
+        assert!((favourites.price_from - 0.95).abs() < 1e-9);
+        assert_eq!((favourites.outcomes, favourites.wins), (4, 3));
+        assert!((favourites.edge() - (0.75 - 0.95)).abs() < 1e-9);
+        assert_eq!(buckets[0].wins, 1);
+
+        assert_eq!(winner_from_prices(0.999, 0.001), Some("YES"));
+        assert_eq!(winner_from_prices(0.0, 1.0), Some("NO"));
+        assert_eq!(winner_from_prices(0.97, 0.03), None);
+    }
+}
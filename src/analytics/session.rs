@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+use crate::config::Config;
+
+pub const SESSIONS_FILE: &str = "sessions.jsonl";
+
+/// Configuration a run was started with. Trades and positions carry `config_hash`,
+/// so results can be grouped by the parameters that produced them
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    pub config_hash: String,
+    /// Full config with credentials and endpoints redacted
+    pub config: serde_json::Value,
+}
+
+impl SessionRecord {
+    pub fn new(config: &Config) -> Result<Self> {
+        let started_at = Utc::now();
+        let snapshot = serde_json::to_value(config.redacted())?;
+        Ok(Self {
+            session_id: format!(
+                "{}-{}",
+                started_at.timestamp_millis(),
+                uuid::Uuid::new_v4().simple()
+            ),
+            started_at,
+            config_hash: config_hash(&snapshot),
+            config: snapshot,
+        })
+    }
+
+    /// Append to `data_dir/sessions.jsonl`
+    pub fn save(&self, data_dir: &str) -> Result<()> {
+        fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create data dir {}", data_dir))?;
+        let path = sessions_path(data_dir);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {:?}", path))?;
+        serde_json::to_writer(&mut file, self)?;
+        file.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Short, stable hash of a redacted config snapshot
+/// Struct fields serialize in declaration order, so equal configs hash equally
+pub fn config_hash(snapshot: &serde_json::Value) -> String {
+    let digest = Sha256::digest(snapshot.to_string().as_bytes());
+    hex::encode(&digest[..8])
+}
+
+/// Path of the session log inside a data dir
+pub fn sessions_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(SESSIONS_FILE)
+}
+
+/// All recorded sessions, oldest first (empty if none were recorded yet)
+pub fn load_sessions(data_dir: &str) -> Result<Vec<SessionRecord>> {
+    let path = sessions_path(data_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = File::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut sessions = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(record) = serde_json::from_str(&line?) {
+            sessions.push(record);
+        }
+    }
+    Ok(sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_tracks_parameters_not_secrets() {
+        let mut config = Config::default();
+        let base = SessionRecord::new(&config).unwrap();
+
+        config.polymarket.secret = "changed".to_string();
+        config.polygon_private_key = Some("0xdeadbeef".to_string());
+        let same = SessionRecord::new(&config).unwrap();
+        assert_eq!(base.config_hash, same.config_hash);
+        assert_ne!(base.session_id, same.session_id);
+        assert!(!same.config.to_string().contains("0xdeadbeef"));
+
+        config.arbitrage.min_edge_bps += 1;
+        let changed = SessionRecord::new(&config).unwrap();
+        assert_ne!(base.config_hash, changed.config_hash);
+
+        let dir = std::env::temp_dir().join(format!("sessions-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        base.save(data_dir).unwrap();
+        changed.save(data_dir).unwrap();
+        let sessions = load_sessions(data_dir).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[1].config_hash, changed.config_hash);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
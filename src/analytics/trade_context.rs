@@ -0,0 +1,316 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use utoipa::ToSchema;
+
+use super::decisions::decision_log_path;
+use super::pnl::Trade;
+use crate::ml::dataset::market_records;
+use crate::ml::FEATURE_NAMES;
+use crate::polymarket::MarketData;
+
+/// Longest padding a replay reads around a trade
+pub const MAX_PADDING_SECS: i64 = 86_400;
+
+/// Bytes of decision log below which the seek for the replay's start stops halving
+const SEEK_WINDOW_BYTES: u64 = 64 * 1024;
+
+/// Records are written in decision order, give or take the logger's queue: how far past the
+/// replay's end the read goes on before it stops
+const ORDER_SLACK_SECS: i64 = 60;
+
+/// Book of the market as a strategy saw it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BookPoint {
+    pub at: DateTime<Utc>,
+    pub yes_price: f64,
+    pub no_price: f64,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub order_book_imbalance: f64,
+}
+
+/// A recorded strategy decision with its signal inputs
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DecisionPoint {
+    pub id: String,
+    pub at: DateTime<Utc>,
+    pub strategy: String,
+    pub binance_price: Option<f64>,
+    pub min_edge_bps: Option<i32>,
+    /// Socket read of the triggering book update -> decision, ns
+    pub receive_to_decision_ns: Option<u64>,
+    /// The `TradeAction` as recorded
+    #[schema(value_type = Object)]
+    pub action: serde_json::Value,
+}
+
+/// Model feature vector sampled for the market (`FEATURE_RECORD_SECS`)
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FeaturePoint {
+    pub at: DateTime<Utc>,
+    pub yes_mid: f64,
+    pub features: BTreeMap<String, f32>,
+}
+
+/// What was recorded for a trade's market around its entry and exit, oldest first: the data
+/// behind the dashboard's trade replay. Books and decisions come from the decision log
+/// (`DECISION_LOG=signals` only has the evaluations that signalled, `all` every book evaluated),
+/// features from the feature recorder
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TradeContext {
+    pub trade: Trade,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub books: Vec<BookPoint>,
+    pub decisions: Vec<DecisionPoint>,
+    pub features: Vec<FeaturePoint>,
+}
+
+/// When a decision log line was recorded
+#[derive(Deserialize)]
+struct Stamp {
+    timestamp: DateTime<Utc>,
+}
+
+/// The parts of a `DecisionRecord` the replay needs (skips parsing the config snapshot)
+#[derive(Deserialize)]
+struct DecisionLine {
+    id: String,
+    timestamp: DateTime<Utc>,
+    strategy: String,
+    market: MarketData,
+    binance_price: Option<f64>,
+    #[serde(default)]
+    min_edge_bps: Option<i32>,
+    #[serde(default)]
+    receive_to_decision_ns: Option<u64>,
+    action: serde_json::Value,
+}
+
+/// Records of `trade`'s market from `padding` (at most `MAX_PADDING_SECS`) before its entry to
+/// `padding` after its exit (now while it is open). The decision log is read from the first
+/// record of the span on, up to its end
+pub fn trade_context(data_dir: &str, trade: &Trade, padding: Duration) -> Result<TradeContext> {
+    let padding = padding.clamp(Duration::zero(), Duration::seconds(MAX_PADDING_SECS));
+    let from = trade.entry_time - padding;
+    let to = trade.exit_time.unwrap_or_else(Utc::now) + padding;
+    let read_until = to + Duration::seconds(ORDER_SLACK_SECS);
+    let mut context = TradeContext {
+        trade: trade.clone(),
+        from,
+        to,
+        books: Vec::new(),
+        decisions: Vec::new(),
+        features: Vec::new(),
+    };
+
+    let path = decision_log_path(data_dir);
+    if let Ok(mut file) = File::open(&path) {
+        let start =
+            seek_to(&mut file, from).with_context(|| format!("Failed to read {:?}", path))?;
+        let mut lines = BufReader::new(file);
+        lines.seek(SeekFrom::Start(start))?;
+        if start > 0 {
+            // Mid-line: the first full line comes after the next newline
+            lines.read_until(b'\n', &mut Vec::new())?;
+        }
+        for line in lines.lines() {
+            let line = line.with_context(|| format!("Failed to read {:?}", path))?;
+            // Cheap pre-filter: most lines are other markets
+            if !line.contains(&trade.market_id) {
+                if serde_json::from_str::<Stamp>(&line).is_ok_and(|s| s.timestamp > read_until) {
+                    break;
+                }
+                continue;
+            }
+            let Ok(record) = serde_json::from_str::<DecisionLine>(&line) else {
+                continue;
+            };
+            if record.timestamp > read_until {
+                break;
+            }
+            if record.market.id != trade.market_id
+                || record.timestamp < from
+                || record.timestamp > to
+            {
+                continue;
+            }
+            let market = &record.market;
+            context.books.push(BookPoint {
+                at: record.timestamp,
+                yes_price: market.yes_price,
+                no_price: market.no_price,
+                best_bid: market.best_bid,
+                best_ask: market.best_ask,
+                order_book_imbalance: market.order_book_imbalance,
+            });
+            context.decisions.push(DecisionPoint {
+                id: record.id,
+                at: record.timestamp,
+                strategy: record.strategy,
+                binance_price: record.binance_price,
+                min_edge_bps: record.min_edge_bps,
+                receive_to_decision_ns: record.receive_to_decision_ns,
+                action: record.action,
+            });
+        }
+    }
+
+    context.features = market_records(data_dir, &trade.market_id, from, to)?
+        .into_iter()
+        .map(|r| FeaturePoint {
+            at: r.at,
+            yes_mid: r.yes_mid,
+            features: FEATURE_NAMES
+                .iter()
+                .map(|name| name.to_string())
+                .zip(r.features)
+                .collect(),
+        })
+        .collect();
+
+    context.books.sort_by_key(|b| b.at);
+    context.decisions.sort_by_key(|d| d.at);
+    context.features.sort_by_key(|f| f.at);
+    Ok(context)
+}
+
+/// Offset in the decision log at or before the first record at `from`: halves the file on the
+/// timestamps of the lines it lands in until the window is small
+fn seek_to(file: &mut File, from: DateTime<Utc>) -> Result<u64> {
+    let (mut low, mut high) = (0, file.metadata()?.len());
+    while high - low > SEEK_WINDOW_BYTES {
+        let mid = low + (high - low) / 2;
+        file.seek(SeekFrom::Start(mid))?;
+        let mut reader = BufReader::new(&mut *file);
+        // Skip the partial line, then read the first whole one
+        reader.read_until(b'\n', &mut Vec::new())?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        match serde_json::from_str::<Stamp>(&line) {
+            Ok(stamp) if stamp.timestamp < from => low = mid,
+            Ok(_) => high = mid,
+            // No whole line after `mid`, or an unreadable one: read on from `low`
+            Err(_) => break,
+        }
+    }
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn decision(id: &str, market_id: &str, at: DateTime<Utc>, yes_price: f64) -> String {
+        serde_json::json!({
+            "id": id,
+            "timestamp": at,
+            "strategy": "predictive",
+            "market": {
+                "id": market_id,
+                "question": "BTC up?",
+                "end_date": null,
+                "volume": 0.0,
+                "liquidity": 0.0,
+                "yes_price": yes_price,
+                "no_price": 1.0 - yes_price,
+                "volume_24h": 0.0,
+                "description": null,
+                "order_book_imbalance": 0.2,
+                "best_bid": yes_price - 0.01,
+                "best_ask": yes_price + 0.01,
+                "asset_ids": [],
+                "category": null
+            },
+            "binance_price": 65000.0,
+            "config": {},
+            "action": {"Buy": {"side": "YES"}}
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_context_around_the_trade() {
+        let dir = std::env::temp_dir().join(format!("trade-context-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let data_dir = dir.to_str().unwrap();
+
+        let entry = "2026-10-15T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let exit = entry + Duration::minutes(10);
+        let lines = [
+            decision("early", "0xbtc", entry - Duration::minutes(30), 0.40),
+            decision("entry", "0xbtc", entry - Duration::seconds(2), 0.55),
+            decision("other", "0xeth", entry, 0.50),
+            decision("exit", "0xbtc", exit, 0.70),
+        ];
+        fs::write(decision_log_path(data_dir), lines.join("\n") + "\n").unwrap();
+
+        let trade = Trade {
+            id: "pos-1".to_string(),
+            market_id: "0xbtc".to_string(),
+            market_question: "BTC up?".to_string(),
+            side: "YES".to_string(),
+            size: 20.0,
+            entry_price: 0.56,
+            exit_price: Some(0.69),
+            entry_time: entry,
+            exit_time: Some(exit),
+            realized_pnl: Some(2.6),
+            config_hash: None,
+            note: None,
+            tags: vec![],
+        };
+        let context = trade_context(data_dir, &trade, Duration::minutes(5)).unwrap();
+        assert_eq!(context.from, entry - Duration::minutes(5));
+        assert_eq!(context.to, exit + Duration::minutes(5));
+        let ids: Vec<&str> = context.decisions.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["entry", "exit"]);
+        assert_eq!(context.books.len(), 2);
+        assert!((context.books[1].yes_price - 0.70).abs() < 1e-9);
+        assert_eq!(context.decisions[0].binance_price, Some(65000.0));
+        assert!(context.features.is_empty());
+
+        // A huge padding is capped instead of overflowing
+        let context = trade_context(data_dir, &trade, Duration::seconds(i64::MAX / 1000)).unwrap();
+        assert_eq!(context.from, entry - Duration::seconds(MAX_PADDING_SECS));
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_seek_lands_before_the_span() {
+        let dir = std::env::temp_dir().join(format!("trade-seek-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let data_dir = dir.to_str().unwrap();
+
+        let start = "2026-10-15T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let lines: Vec<String> = (0..2000)
+            .map(|i| {
+                decision(
+                    &format!("d{}", i),
+                    "0xeth",
+                    start + Duration::minutes(i),
+                    0.5,
+                )
+            })
+            .collect();
+        let path = decision_log_path(data_dir);
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let mut file = File::open(&path).unwrap();
+        let from = start + Duration::minutes(1500);
+        let offset = seek_to(&mut file, from).unwrap();
+        assert!(offset > 0);
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(offset)).unwrap();
+        reader.read_until(b'\n', &mut Vec::new()).unwrap();
+        let first: Stamp = serde_json::from_str(&reader.lines().next().unwrap().unwrap()).unwrap();
+        assert!(first.timestamp <= from);
+        assert!(from - first.timestamp < Duration::minutes(200));
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,336 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::attribution::{self, AttributionReport, Dimension};
+use super::pnl::{Position, Trade};
+use super::query::{time_key, ListQuery, SortKey};
+use crate::markets::MarketCategory;
+
+pub const TRADE_JOURNAL_FILE: &str = "trades.json";
+
+/// Operator edit of a trade: fields left out are kept
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct TradeAnnotation {
+    /// Replaces the note (empty string clears it)
+    pub note: Option<String>,
+    /// Replaces the tag set
+    pub tags: Option<Vec<String>>,
+}
+
+/// Realized PnL attributed to one market category
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CategoryPnL {
+    pub category: MarketCategory,
+    pub num_trades: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub realized_pnl: f64,
+    /// Sum of closed sizes (USD)
+    pub volume: f64,
+}
+
+/// Every closed trade across sessions, with operator notes and tags for post-session review
+/// Persisted to `data_dir/trades.json` when loaded from a data dir: changes are taken as a
+/// `JournalSnapshot` under the tracker lock and written outside it
+#[derive(Debug, Default)]
+pub struct TradeJournal {
+    trades: Vec<Trade>,
+    path: Option<PathBuf>,
+    /// Bumped on every change
+    version: u64,
+    saved_version: u64,
+    /// Version last written to disk, shared with the snapshots so writes are serialized and
+    /// an older snapshot never overwrites a newer one
+    written: Arc<Mutex<u64>>,
+}
+
+/// The journal as of one change, to write without holding the tracker lock
+#[derive(Debug)]
+pub struct JournalSnapshot {
+    trades: Vec<Trade>,
+    path: PathBuf,
+    version: u64,
+    written: Arc<Mutex<u64>>,
+}
+
+impl JournalSnapshot {
+    /// Blocking; skipped if a newer snapshot was already written
+    pub fn save(&self) -> Result<()> {
+        let mut written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+        if *written >= self.version {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.trades)?)?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("Failed to write {:?}", self.path))?;
+        *written = self.version;
+        Ok(())
+    }
+}
+
+impl TradeJournal {
+    pub fn load(data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(TRADE_JOURNAL_FILE);
+        let trades = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            trades,
+            path: Some(path),
+            ..Self::default()
+        }
+    }
+
+    /// Changes since the last call, to `save` outside the lock. None if there are none or
+    /// the journal is in memory only
+    pub fn take_unsaved(&mut self) -> Option<JournalSnapshot> {
+        if self.version == self.saved_version {
+            return None;
+        }
+        self.saved_version = self.version;
+        Some(JournalSnapshot {
+            trades: self.trades.clone(),
+            path: self.path.clone()?,
+            version: self.version,
+            written: self.written.clone(),
+        })
+    }
+
+    pub fn record(&mut self, trade: Trade) {
+        self.trades.push(trade);
+        self.version += 1;
+    }
+
+    /// Closes already recorded for a position (partial closes get `<position id>-<n>` ids)
+    pub fn closes_of(&self, position_id: &str) -> usize {
+        let prefix = format!("{}-", position_id);
+        self.trades
+            .iter()
+            .filter(|t| t.id == position_id || t.id.starts_with(&prefix))
+            .count()
+    }
+
+    /// Realized PnL of a position's closes so far. None if it has none
+    pub fn realized_pnl_of(&self, position_id: &str) -> Option<f64> {
+        let prefix = format!("{}-", position_id);
+        self.trades
+            .iter()
+            .filter(|t| t.id == position_id || t.id.starts_with(&prefix))
+            .map(|t| t.realized_pnl.unwrap_or(0.0))
+            .reduce(|a, b| a + b)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Trade> {
+        self.trades.iter().find(|t| t.id == id)
+    }
+
+    /// Most recent `limit` trades, newest first, optionally only those carrying `tag`
+    pub fn recent(&self, limit: usize, tag: Option<&str>) -> Vec<Trade> {
+        self.trades
+            .iter()
+            .rev()
+            .filter(|t| tag.is_none_or(|tag| t.tags.iter().any(|t| t == tag)))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Trades matching the filters of `query` (paging ignored)
+    pub fn filtered<'a>(&'a self, query: &'a ListQuery) -> impl Iterator<Item = &'a Trade> {
+        self.trades.iter().filter(|t| {
+            query.matches(&t.market_id, t.exit_time.unwrap_or(t.entry_time), &t.tags)
+                && query.matches_origin(&t.id, t.config_hash.as_deref())
+        })
+    }
+
+    /// Trades matching `query`, sorted and paged, with the number that matched
+    pub fn query(&self, query: &ListQuery) -> (usize, Vec<Trade>) {
+        let matching = self.filtered(query).collect();
+        query.page(matching, |t, key| match key {
+            SortKey::Time => time_key(t.exit_time.unwrap_or(t.entry_time)),
+            SortKey::Pnl => t.realized_pnl.unwrap_or(0.0),
+            SortKey::Size => t.size,
+        })
+    }
+
+    /// Apply a note / tag edit. None if no trade has this id
+    pub fn annotate(&mut self, id: &str, annotation: TradeAnnotation) -> Option<Trade> {
+        let trade = self.trades.iter_mut().find(|t| t.id == id)?;
+        if let Some(note) = annotation.note {
+            let note = note.trim();
+            trade.note = (!note.is_empty()).then(|| note.to_string());
+        }
+        if let Some(tags) = annotation.tags {
+            let mut tags: Vec<String> = tags
+                .iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect();
+            tags.sort();
+            tags.dedup();
+            trade.tags = tags;
+        }
+        let trade = trade.clone();
+        self.version += 1;
+        Some(trade)
+    }
+
+    /// Realized PnL per category of the trades matching `query`, best first (`category_of`
+    /// places each trade's market)
+    pub fn by_category(
+        &self,
+        query: &ListQuery,
+        category_of: impl Fn(&Trade) -> MarketCategory,
+    ) -> Vec<CategoryPnL> {
+        let mut totals: BTreeMap<MarketCategory, CategoryPnL> = BTreeMap::new();
+        for trade in self.filtered(query) {
+            let category = category_of(trade);
+            let entry = totals.entry(category).or_insert(CategoryPnL {
+                category,
+                num_trades: 0,
+                wins: 0,
+                win_rate: 0.0,
+                realized_pnl: 0.0,
+                volume: 0.0,
+            });
+            let pnl = trade.realized_pnl.unwrap_or(0.0);
+            entry.num_trades += 1;
+            entry.wins += usize::from(pnl > 0.0);
+            entry.realized_pnl += pnl;
+            entry.volume += trade.size;
+        }
+        let mut categories: Vec<CategoryPnL> = totals
+            .into_values()
+            .map(|mut c| {
+                c.win_rate = c.wins as f64 / c.num_trades as f64;
+                c
+            })
+            .collect();
+        categories.sort_by(|a, b| b.realized_pnl.total_cmp(&a.realized_pnl));
+        categories
+    }
+
+    /// PnL per strategy or config of the trades matching `query`, with `positions` still open
+    pub fn attribution<'a>(
+        &'a self,
+        by: Dimension,
+        query: &'a ListQuery,
+        positions: impl IntoIterator<Item = &'a Position>,
+    ) -> AttributionReport {
+        attribution::breakdown(by, self.filtered(query), positions)
+    }
+
+    /// CSV export (one row per trade, tags `;`-separated)
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "id,market_id,market_question,side,size,entry_price,exit_price,entry_time,exit_time,realized_pnl,config_hash,note,tags\n",
+        );
+        let opt = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        for t in &self.trades {
+            let row = [
+                t.id.clone(),
+                t.market_id.clone(),
+                t.market_question.clone(),
+                t.side.clone(),
+                t.size.to_string(),
+                t.entry_price.to_string(),
+                opt(t.exit_price),
+                t.entry_time.to_rfc3339(),
+                t.exit_time.map(|at| at.to_rfc3339()).unwrap_or_default(),
+                opt(t.realized_pnl),
+                t.config_hash.clone().unwrap_or_default(),
+                t.note.clone().unwrap_or_default(),
+                t.tags.join(";"),
+            ];
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn trade(id: &str, pnl: f64) -> Trade {
+        Trade {
+            id: id.to_string(),
+            market_id: "m1".to_string(),
+            market_question: "Will it happen, or not?".to_string(),
+            side: "YES".to_string(),
+            size: 10.0,
+            entry_price: 0.5,
+            exit_price: Some(0.6),
+            entry_time: Utc::now(),
+            exit_time: Some(Utc::now()),
+            realized_pnl: Some(pnl),
+            config_hash: None,
+            note: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_annotate_persists_and_exports() {
+        let dir = std::env::temp_dir().join(format!("trade-journal-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let mut journal = TradeJournal::load(data_dir);
+        journal.record(trade("p1", 1.0));
+        let first = journal.take_unsaved().unwrap();
+        journal.record(trade("p1-1", -0.5));
+        assert_eq!(journal.closes_of("p1"), 2);
+
+        let annotated = journal
+            .annotate(
+                "p1-1",
+                TradeAnnotation {
+                    note: Some(" filled \"late\" ".to_string()),
+                    tags: Some(vec!["late fill".into(), "".into(), "late fill".into()]),
+                },
+            )
+            .unwrap();
+        assert_eq!(annotated.note.as_deref(), Some("filled \"late\""));
+        assert_eq!(annotated.tags, vec!["late fill".to_string()]);
+        assert!(journal
+            .annotate("missing", TradeAnnotation::default())
+            .is_none());
+        journal.take_unsaved().unwrap().save().unwrap();
+        assert!(journal.take_unsaved().is_none());
+        // A snapshot taken before the last write is stale
+        first.save().unwrap();
+
+        // Survives a restart
+        let journal = TradeJournal::load(data_dir);
+        assert_eq!(journal.recent(10, Some("late fill")).len(), 1);
+        assert_eq!(journal.get("p1-1").unwrap().tags.len(), 1);
+
+        let csv = journal.to_csv();
+        assert!(csv.contains("\"Will it happen, or not?\""));
+        assert!(csv.contains("\"filled \"\"late\"\"\",late fill"));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,280 @@
+//! API roles and audit log
+//!
+//! With `API_READ_TOKENS` / `API_ADMIN_TOKENS` set, every API route needs
+//! `Authorization: Bearer <token>`. Read tokens (dashboards) may only GET; control calls - any
+//! other method, e.g. trade annotations - need an admin token. Every control call, allowed or
+//! not, is appended to `data_dir/api_audit.jsonl`. Without tokens reads are open and control
+//! calls are refused.
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use super::ApiState;
+use crate::config::AgentConfig;
+
+pub const AUDIT_FILE: &str = "api_audit.jsonl";
+
+/// What a token may do. Admin includes read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Read,
+    Admin,
+}
+
+impl Role {
+    /// Role a request with `method` needs: reads for GET / HEAD, admin for everything else
+    pub fn required(method: &Method) -> Self {
+        if method == Method::GET || method == Method::HEAD {
+            Role::Read
+        } else {
+            Role::Admin
+        }
+    }
+}
+
+/// One control call, as written to the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    /// None = no or unknown token
+    pub role: Option<Role>,
+    /// Short hash of the presented token, never the token itself
+    pub token: Option<String>,
+    pub status: u16,
+}
+
+/// Configured tokens and where control calls are audited
+#[derive(Debug, Default)]
+pub struct ApiAuth {
+    tokens: Vec<(String, Role)>,
+    /// None = audit to the log only (observer mode: the data dir is another instance's)
+    audit_path: Option<PathBuf>,
+}
+
+impl ApiAuth {
+    pub fn new(config: &AgentConfig, audit_dir: Option<&str>) -> Self {
+        let mut tokens = HashMap::new();
+        for token in &config.api_read_tokens {
+            tokens.insert(token.clone(), Role::Read);
+        }
+        // A token listed in both is an admin token
+        for token in &config.api_admin_tokens {
+            tokens.insert(token.clone(), Role::Admin);
+        }
+        Self {
+            tokens: tokens.into_iter().collect(),
+            audit_path: audit_dir.map(|dir| Path::new(dir).join(AUDIT_FILE)),
+        }
+    }
+
+    /// False when no tokens are configured: reads are open, control calls refused
+    pub fn enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// Role of the bearer token in `headers`, if it is a configured one. The token is
+    /// compared against every configured one in constant time, so response times don't
+    /// reveal how close a guess was
+    pub fn role(&self, headers: &HeaderMap) -> Option<Role> {
+        let presented = bearer_token(headers)?;
+        self.tokens.iter().fold(None, |role, (token, token_role)| {
+            if constant_time_eq(presented, token) {
+                Some(*token_role)
+            } else {
+                role
+            }
+        })
+    }
+
+    /// Err with 401 (no / unknown token) or 403 (read token on a control call, or any
+    /// control call while no tokens are configured)
+    pub fn check(&self, method: &Method, headers: &HeaderMap) -> Result<(), StatusCode> {
+        if !self.enabled() {
+            return match Role::required(method) {
+                Role::Read => Ok(()),
+                Role::Admin => Err(StatusCode::FORBIDDEN),
+            };
+        }
+        match self.role(headers) {
+            None => Err(StatusCode::UNAUTHORIZED),
+            Some(role) if role < Role::required(method) => Err(StatusCode::FORBIDDEN),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Log a control call and append it to the audit file
+    pub fn audit(&self, entry: &AuditEntry) {
+        if entry.status < 400 {
+            info!(
+                "🔐 API control call: {} {} by {:?} token {} -> {}",
+                entry.method,
+                entry.path,
+                entry.role,
+                entry.token.as_deref().unwrap_or("-"),
+                entry.status
+            );
+        } else {
+            warn!(
+                "🔐 API control call refused: {} {} by {:?} token {} -> {}",
+                entry.method,
+                entry.path,
+                entry.role,
+                entry.token.as_deref().unwrap_or("-"),
+                entry.status
+            );
+        }
+        let Some(path) = &self.audit_path else {
+            return;
+        };
+        let written = (|| -> anyhow::Result<()> {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            serde_json::to_writer(&mut file, entry)?;
+            file.write_all(b"\n")?;
+            Ok(())
+        })();
+        if let Err(e) = written {
+            warn!("⚠️  Failed to write API audit log {:?}: {}", path, e);
+        }
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Equality that takes the same time wherever the inputs differ: both are hashed to a fixed
+/// length, then every byte is compared
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (Sha256::digest(a.as_bytes()), Sha256::digest(b.as_bytes()));
+    a.iter()
+        .zip(b.iter())
+        .fold(0, |diff, (x, y)| diff | (x ^ y))
+        == 0
+}
+
+/// Short, stable id of a token for the audit log
+fn fingerprint(token: &str) -> String {
+    hex::encode(&Sha256::digest(token.as_bytes())[..4])
+}
+
+/// Middleware on every API route: enforces roles and audits control calls
+pub async fn authorize(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let auth = state.auth.clone();
+    let method = request.method().clone();
+    let checked = auth.check(&method, request.headers());
+    if Role::required(&method) == Role::Read {
+        return match checked {
+            Ok(()) => next.run(request).await,
+            Err(status) => status.into_response(),
+        };
+    }
+
+    let path = request.uri().path().to_string();
+    let role = auth.role(request.headers());
+    let token = bearer_token(request.headers()).map(fingerprint);
+    let response = match checked {
+        Ok(()) => next.run(request).await,
+        Err(status) => status.into_response(),
+    };
+    auth.audit(&AuditEntry {
+        timestamp: Utc::now(),
+        method: method.to_string(),
+        path,
+        role,
+        token,
+        status: response.status().as_u16(),
+    });
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_roles_and_audit() {
+        let dir = std::env::temp_dir().join(format!("api-auth-{}", uuid::Uuid::new_v4()));
+        let auth = ApiAuth {
+            tokens: vec![
+                ("dash".to_string(), Role::Read),
+                ("ops".to_string(), Role::Admin),
+            ],
+            audit_path: Some(dir.join(AUDIT_FILE)),
+        };
+
+        assert_eq!(auth.check(&Method::GET, &headers("dash")), Ok(()));
+        assert_eq!(auth.check(&Method::GET, &headers("ops")), Ok(()));
+        assert_eq!(
+            auth.check(&Method::PATCH, &headers("dash")),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(auth.check(&Method::PATCH, &headers("ops")), Ok(()));
+        assert_eq!(
+            auth.check(&Method::GET, &headers("guess")),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        // A prefix of a token is no token
+        assert_eq!(
+            auth.check(&Method::GET, &headers("op")),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            auth.check(&Method::GET, &HeaderMap::new()),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        // No tokens configured: reads open, control calls refused
+        assert_eq!(
+            ApiAuth::default().check(&Method::GET, &HeaderMap::new()),
+            Ok(())
+        );
+        assert_eq!(
+            ApiAuth::default().check(&Method::PATCH, &HeaderMap::new()),
+            Err(StatusCode::FORBIDDEN)
+        );
+
+        auth.audit(&AuditEntry {
+            timestamp: Utc::now(),
+            method: "PATCH".to_string(),
+            path: "/api/trades/t1".to_string(),
+            role: Some(Role::Read),
+            token: Some(fingerprint("dash")),
+            status: 403,
+        });
+        let log = fs::read_to_string(dir.join(AUDIT_FILE)).unwrap();
+        let entry: AuditEntry = serde_json::from_str(log.lines().next().unwrap()).unwrap();
+        assert_eq!(entry.status, 403);
+        assert_eq!(entry.token.unwrap().len(), 8);
+        assert!(!log.contains("dash"));
+        fs::remove_dir_all(dir).ok();
+    }
+}
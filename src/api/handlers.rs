@@ -0,0 +1,1415 @@
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderName, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use utoipa::{IntoParams, ToSchema};
+
+use super::summary::{self, DashboardSummary};
+use super::ApiState;
+use crate::analytics::drift::DriftReport;
+use crate::analytics::edge_realization::EdgeDecayReport;
+use crate::analytics::execution_drift::ExecutionDriftReport;
+use crate::analytics::indexing_latency::IndexingLatencyReport;
+use crate::analytics::opportunities::OpportunityReport;
+use crate::analytics::pnl::{PnLStats, PortfolioSnapshot, Position, Trade};
+use crate::analytics::postmortem::PostmortemReport;
+use crate::analytics::{
+    self, AttributionReport, CategoryPnL, Dimension, ListQuery, PnlBucket, SessionRecord,
+    TradeAnnotation, TradeContext,
+};
+use crate::execution::{AnomalySnapshot, CollateralView, MissedWindowReport, SettlementStatus};
+use crate::failover::{FenceRequest, Heartbeat};
+use crate::llm_budget::LlmCostReport;
+use crate::logging::{self, LogLevelsView};
+use crate::markets::{
+    AssetRef, DormantMarket, ExpirationEntry, FeatureFrame, LifecycleSummary, MarketCategory,
+    MarketFee, MarketLifecycle, MarketState, MarketToxicity, NewSeries, Pin, PinKey, PinRequest,
+    SubscriptionStats, WhaleStats,
+};
+use crate::polymarket::breaker::BreakerSnapshot;
+use crate::polymarket::{BandwidthStats, MarketData, SequenceStats};
+use crate::pricefeed::FeedStatus;
+use crate::profiles::ProfileStatus;
+use crate::scheduling::TaskStats;
+use crate::strategies::hedging::HedgeSnapshot;
+
+/// GET /api/indexing-latency
+#[utoipa::path(
+    get,
+    path = "/api/indexing-latency",
+    tag = "analytics",
+    responses(
+        (status = 200, body = IndexingLatencyReport),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn indexing_latency(
+    State(state): State<ApiState>,
+) -> Result<Json<IndexingLatencyReport>, StatusCode> {
+    let tracker = state
+        .indexing_latency
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.report()))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MarketStatesQuery {
+    /// Only list markets in this state, e.g. `?state=Tradable`
+    pub state: Option<MarketState>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MarketStatesResponse {
+    pub summary: LifecycleSummary,
+    pub markets: Vec<MarketLifecycle>,
+}
+
+/// GET /api/markets/states[?state=...]
+#[utoipa::path(
+    get,
+    path = "/api/markets/states",
+    tag = "markets",
+    params(MarketStatesQuery),
+    responses(
+        (status = 200, body = MarketStatesResponse),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn market_states(
+    State(state): State<ApiState>,
+    Query(query): Query<MarketStatesQuery>,
+) -> Result<Json<MarketStatesResponse>, StatusCode> {
+    let lifecycle = state
+        .lifecycle
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(MarketStatesResponse {
+        summary: lifecycle.summary(),
+        markets: query
+            .state
+            .map(|s| lifecycle.in_state(s))
+            .unwrap_or_default(),
+    }))
+}
+
+/// GET /api/markets/{id}/state
+#[utoipa::path(
+    get,
+    path = "/api/markets/{id}/state",
+    tag = "markets",
+    params(("id" = String, Path, description = "Market (condition) id")),
+    responses(
+        (status = 200, body = MarketLifecycle),
+        (status = 404, description = "Market not tracked"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn market_state(
+    State(state): State<ApiState>,
+    Path(market_id): Path<String>,
+) -> Result<Json<MarketLifecycle>, StatusCode> {
+    let lifecycle = state
+        .lifecycle
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    lifecycle
+        .get(&market_id)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MarketsQuery {
+    /// Only list markets in this category, e.g. `?category=sports`
+    pub category: Option<String>,
+}
+
+/// GET /api/markets[?category=...]
+#[utoipa::path(
+    get,
+    path = "/api/markets",
+    tag = "markets",
+    params(MarketsQuery),
+    responses(
+        (status = 200, body = Vec<MarketData>),
+        (status = 400, description = "Unknown category")
+    )
+)]
+pub async fn markets(
+    State(state): State<ApiState>,
+    Query(query): Query<MarketsQuery>,
+) -> Result<Json<Vec<MarketData>>, StatusCode> {
+    let category = match query.category.as_deref() {
+        Some(name) => Some(MarketCategory::parse(name).ok_or(StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+    Ok(Json(
+        state
+            .registry
+            .markets()
+            .into_iter()
+            .filter(|m| category.is_none_or(|c| MarketCategory::of(m) == c))
+            .collect(),
+    ))
+}
+
+/// GET /api/markets/{id}
+#[utoipa::path(
+    get,
+    path = "/api/markets/{id}",
+    tag = "markets",
+    params(("id" = String, Path, description = "Market (condition) id")),
+    responses(
+        (status = 200, body = MarketData),
+        (status = 404, description = "Market not in the registry")
+    )
+)]
+pub async fn market(
+    State(state): State<ApiState>,
+    Path(market_id): Path<String>,
+) -> Result<Json<MarketData>, StatusCode> {
+    state
+        .registry
+        .get(&market_id)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LookupQuery {
+    /// Condition id, market slug or polymarket.com URL
+    pub market: String,
+}
+
+/// GET /api/markets/lookup?market=...
+#[utoipa::path(
+    get,
+    path = "/api/markets/lookup",
+    tag = "markets",
+    params(LookupQuery),
+    responses(
+        (status = 200, description = "The market with its condition id and token ids", body = MarketData),
+        (status = 400, description = "Not a condition id, slug or market URL"),
+        (status = 404, description = "No such market (or Gamma unreachable)"),
+        (status = 503, description = "No market client (observer mode)")
+    )
+)]
+pub async fn lookup_market(
+    State(state): State<ApiState>,
+    Query(query): Query<LookupQuery>,
+) -> Result<Json<MarketData>, StatusCode> {
+    let lookup = state
+        .market_lookup
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let key = PinKey::parse(&query.market).ok_or(StatusCode::BAD_REQUEST)?;
+    key.fetch(lookup.as_ref())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::NOT_FOUND)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExpirationsQuery {
+    /// Soonest markets to return (default 100)
+    pub limit: Option<usize>,
+}
+
+/// GET /api/expirations
+#[utoipa::path(
+    get,
+    path = "/api/expirations",
+    tag = "markets",
+    params(ExpirationsQuery),
+    responses(
+        (status = 200, body = Vec<ExpirationEntry>),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn expirations(
+    State(state): State<ApiState>,
+    Query(query): Query<ExpirationsQuery>,
+) -> Result<Json<Vec<ExpirationEntry>>, StatusCode> {
+    let markets = state.registry.markets();
+    let watchlist = state
+        .watchlist
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(watchlist.calendar(
+        &markets,
+        Utc::now(),
+        query.limit.unwrap_or(100),
+    )))
+}
+
+/// GET /api/watchlist
+#[utoipa::path(
+    get,
+    path = "/api/watchlist",
+    tag = "markets",
+    responses(
+        (status = 200, body = Vec<Pin>),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn pinned_markets(State(state): State<ApiState>) -> Result<Json<Vec<Pin>>, StatusCode> {
+    let pins = state
+        .pins
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(pins.pins()))
+}
+
+/// POST /api/watchlist
+#[utoipa::path(
+    post,
+    path = "/api/watchlist",
+    tag = "markets",
+    request_body = PinRequest,
+    responses(
+        (status = 200, description = "Pinned (or pin updated); tracked from the next poll", body = Pin),
+        (status = 400, description = "Not a condition id, slug or market URL"),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Read-only observer or non-admin token"),
+        (status = 404, description = "No such market (or Gamma unreachable)"),
+        (status = 500, description = "State lock poisoned or pins not persisted")
+    )
+)]
+pub async fn pin_market(
+    State(state): State<ApiState>,
+    Json(request): Json<PinRequest>,
+) -> Result<Json<Pin>, StatusCode> {
+    if state.read_only {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let key = PinKey::parse(&request.market).ok_or(StatusCode::BAD_REQUEST)?;
+    // Resolved up front so a mistyped slug is refused instead of polled forever
+    let market = match &state.market_lookup {
+        Some(lookup) => Some(
+            key.fetch(lookup.as_ref())
+                .await
+                .map_err(|_| StatusCode::NOT_FOUND)?,
+        ),
+        None => None,
+    };
+    let mut pins = state
+        .pins
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut pin = pins.pin(request).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if let Some(market) = market {
+        pins.resolve(&pin.key, &market);
+        pin.market_id = Some(market.id.into_inner());
+        pin.question = Some(market.question);
+    }
+    Ok(Json(pin))
+}
+
+/// DELETE /api/watchlist/{key}
+#[utoipa::path(
+    delete,
+    path = "/api/watchlist/{key}",
+    tag = "markets",
+    params(("key" = String, Path, description = "Pinned slug or condition id")),
+    responses(
+        (status = 200, body = Pin),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Read-only observer or non-admin token"),
+        (status = 404, description = "Not pinned"),
+        (status = 500, description = "State lock poisoned or pins not persisted")
+    )
+)]
+pub async fn unpin_market(
+    State(state): State<ApiState>,
+    Path(key): Path<String>,
+) -> Result<Json<Pin>, StatusCode> {
+    if state.read_only {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let mut pins = state
+        .pins
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    match pins.unpin(&key) {
+        Ok(Some(pin)) => Ok(Json(pin)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// GET /api/assets/{asset_id}
+#[utoipa::path(
+    get,
+    path = "/api/assets/{asset_id}",
+    tag = "markets",
+    params(("asset_id" = String, Path, description = "Outcome token id")),
+    responses(
+        (status = 200, body = AssetRef),
+        (status = 404, description = "Asset not in the registry")
+    )
+)]
+pub async fn asset(
+    State(state): State<ApiState>,
+    Path(asset_id): Path<String>,
+) -> Result<Json<AssetRef>, StatusCode> {
+    state
+        .registry
+        .asset(&asset_id)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// GET /api/features/{asset_id}
+#[utoipa::path(
+    get,
+    path = "/api/features/{asset_id}",
+    tag = "markets",
+    params(("asset_id" = String, Path, description = "Outcome token id")),
+    responses(
+        (status = 200, body = FeatureFrame),
+        (status = 404, description = "Disabled in this run, or no book seen for the asset"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn features(
+    State(state): State<ApiState>,
+    Path(asset_id): Path<String>,
+) -> Result<Json<FeatureFrame>, StatusCode> {
+    let features = state.features.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let features = features
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    features
+        .frame(&asset_id, Utc::now().timestamp_millis() as u64)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// GET /api/session
+#[utoipa::path(
+    get,
+    path = "/api/session",
+    tag = "session",
+    responses((status = 200, body = SessionRecord))
+)]
+pub async fn session(State(state): State<ApiState>) -> Json<SessionRecord> {
+    Json((*state.session).clone())
+}
+
+/// GET /api/summary
+#[utoipa::path(
+    get,
+    path = "/api/summary",
+    tag = "session",
+    responses(
+        (status = 200, body = DashboardSummary),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn summary(State(state): State<ApiState>) -> Result<Json<DashboardSummary>, StatusCode> {
+    let now = Utc::now();
+    let summary = state
+        .summary
+        .get_or_build(now, || summary::build(&state, now))?;
+    Ok(Json(summary))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OpportunitiesQuery {
+    /// Most recent evaluations to return (default 100)
+    pub limit: Option<usize>,
+    /// Only evaluations with at least this much net edge, e.g. `?min_bps=-50`
+    pub min_bps: Option<i32>,
+}
+
+/// GET /api/opportunities[?limit=100&min_bps=-50]
+#[utoipa::path(
+    get,
+    path = "/api/opportunities",
+    tag = "analytics",
+    params(OpportunitiesQuery),
+    responses(
+        (status = 200, body = OpportunityReport),
+        (status = 404, description = "Disabled in this run"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn opportunities(
+    State(state): State<ApiState>,
+    Query(query): Query<OpportunitiesQuery>,
+) -> Result<Json<OpportunityReport>, StatusCode> {
+    let book = state.opportunities.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let book = book.lock().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(book.report(query.limit.unwrap_or(100), query.min_bps)))
+}
+
+/// GET /api/edge-realization
+#[utoipa::path(
+    get,
+    path = "/api/edge-realization",
+    tag = "analytics",
+    responses(
+        (status = 200, body = EdgeDecayReport),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn edge_realization(
+    State(state): State<ApiState>,
+) -> Result<Json<EdgeDecayReport>, StatusCode> {
+    let tracker = state
+        .edge_realization
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.report()))
+}
+
+/// GET /api/execution-drift
+#[utoipa::path(
+    get,
+    path = "/api/execution-drift",
+    tag = "analytics",
+    responses(
+        (status = 200, body = ExecutionDriftReport),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn execution_drift(
+    State(state): State<ApiState>,
+) -> Result<Json<ExecutionDriftReport>, StatusCode> {
+    let tracker = state
+        .execution_drift
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.report()))
+}
+
+/// GET /api/missed-windows
+#[utoipa::path(
+    get,
+    path = "/api/missed-windows",
+    tag = "analytics",
+    responses(
+        (status = 200, body = MissedWindowReport),
+        (status = 404, description = "Not tracked in observer mode"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn missed_windows(
+    State(state): State<ApiState>,
+) -> Result<Json<MissedWindowReport>, StatusCode> {
+    let missed_windows = state.missed_windows.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let missed_windows = missed_windows
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(missed_windows.report()))
+}
+
+/// GET /api/drift
+#[utoipa::path(
+    get,
+    path = "/api/drift",
+    tag = "analytics",
+    responses(
+        (status = 200, body = DriftReport),
+        (status = 404, description = "Not trading live"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn drift(State(state): State<ApiState>) -> Result<Json<DriftReport>, StatusCode> {
+    let drift = state.drift.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let drift = drift
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(drift.report(|id| tracker.journal.realized_pnl_of(id))))
+}
+
+/// GET /api/postmortems
+#[utoipa::path(
+    get,
+    path = "/api/postmortems",
+    tag = "analytics",
+    responses(
+        (status = 200, body = PostmortemReport),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn postmortems(
+    State(state): State<ApiState>,
+) -> Result<Json<PostmortemReport>, StatusCode> {
+    let log = state
+        .postmortems
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(log.report()))
+}
+
+/// GET /api/breakers
+#[utoipa::path(
+    get,
+    path = "/api/breakers",
+    tag = "risk",
+    responses((status = 200, body = Vec<BreakerSnapshot>))
+)]
+pub async fn breakers(State(state): State<ApiState>) -> Json<Vec<BreakerSnapshot>> {
+    Json(state.breakers.snapshot())
+}
+
+/// GET /api/price-feed
+#[utoipa::path(
+    get,
+    path = "/api/price-feed",
+    tag = "risk",
+    responses(
+        (status = 200, body = Vec<FeedStatus>),
+        (status = 404, description = "Observer mode")
+    )
+)]
+pub async fn price_feed(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<FeedStatus>>, StatusCode> {
+    let feed = state.price_feed.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(feed.feed_status()))
+}
+
+/// GET /api/scheduler
+#[utoipa::path(
+    get,
+    path = "/api/scheduler",
+    tag = "session",
+    responses(
+        (status = 200, body = Vec<TaskStats>),
+        (status = 404, description = "Observer mode")
+    )
+)]
+pub async fn scheduler(State(state): State<ApiState>) -> Result<Json<Vec<TaskStats>>, StatusCode> {
+    let scheduler = state.scheduler.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(scheduler.snapshot()))
+}
+
+/// GET /api/llm/costs
+#[utoipa::path(
+    get,
+    path = "/api/llm/costs",
+    tag = "analytics",
+    responses(
+        (status = 200, body = LlmCostReport),
+        (status = 404, description = "LLM classifier off, or observer mode")
+    )
+)]
+pub async fn llm_costs(State(state): State<ApiState>) -> Result<Json<LlmCostReport>, StatusCode> {
+    let budget = state.llm_costs.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let budget = budget
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(budget.report(Utc::now())))
+}
+
+/// GET /api/anomalies
+#[utoipa::path(
+    get,
+    path = "/api/anomalies",
+    tag = "risk",
+    responses(
+        (status = 200, body = AnomalySnapshot),
+        (status = 404, description = "Disabled in this run")
+    )
+)]
+pub async fn anomalies(State(state): State<ApiState>) -> Result<Json<AnomalySnapshot>, StatusCode> {
+    let guard = state.anomaly_guard.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(guard.snapshot()))
+}
+
+/// GET /api/whales
+#[utoipa::path(
+    get,
+    path = "/api/whales",
+    tag = "markets",
+    responses(
+        (status = 200, body = Vec<WhaleStats>),
+        (status = 404, description = "Disabled in this run"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn whales(State(state): State<ApiState>) -> Result<Json<Vec<WhaleStats>>, StatusCode> {
+    let whales = state.whales.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let whales = whales
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(whales.all(Utc::now())))
+}
+
+/// GET /api/zombies
+#[utoipa::path(
+    get,
+    path = "/api/zombies",
+    tag = "markets",
+    responses(
+        (status = 200, body = Vec<DormantMarket>),
+        (status = 404, description = "Disabled in this run"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn zombies(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<DormantMarket>>, StatusCode> {
+    let zombies = state.zombies.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let zombies = zombies
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(zombies.dormant()))
+}
+
+/// GET /api/series
+#[utoipa::path(
+    get,
+    path = "/api/series",
+    tag = "markets",
+    responses(
+        (status = 200, description = "Event series first seen this run, most recent first", body = Vec<NewSeries>),
+        (status = 404, description = "Disabled in this run"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn series(State(state): State<ApiState>) -> Result<Json<Vec<NewSeries>>, StatusCode> {
+    let series = state.series.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let series = series
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(series.recent()))
+}
+
+/// GET /api/fees
+#[utoipa::path(
+    get,
+    path = "/api/fees",
+    tag = "markets",
+    responses(
+        (status = 200, description = "Fee-free markets first", body = Vec<MarketFee>),
+        (status = 404, description = "Not tracked in observer mode")
+    )
+)]
+pub async fn fees(State(state): State<ApiState>) -> Result<Json<Vec<MarketFee>>, StatusCode> {
+    let fee_rates = state.fee_rates.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(fee_rates.all()))
+}
+
+/// GET /api/hedges
+#[utoipa::path(
+    get,
+    path = "/api/hedges",
+    tag = "risk",
+    responses(
+        (status = 200, body = HedgeSnapshot),
+        (status = 404, description = "Disabled in this run"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn hedges(State(state): State<ApiState>) -> Result<Json<HedgeSnapshot>, StatusCode> {
+    let hedger = state.hedger.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let hedger = hedger
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(hedger.snapshot()))
+}
+
+/// GET /api/profiles
+#[utoipa::path(
+    get,
+    path = "/api/profiles",
+    tag = "risk",
+    responses(
+        (status = 200, body = ProfileStatus),
+        (status = 404, description = "No parameter profiles configured"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn profiles(State(state): State<ApiState>) -> Result<Json<ProfileStatus>, StatusCode> {
+    let profiles = state.profiles.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let profiles = profiles
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(profiles.status()))
+}
+
+/// GET /api/settlement
+#[utoipa::path(
+    get,
+    path = "/api/settlement",
+    tag = "risk",
+    responses(
+        (status = 200, body = SettlementStatus),
+        (status = 404, description = "Settlement watch off (or not trading live)"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn settlement(
+    State(state): State<ApiState>,
+) -> Result<Json<SettlementStatus>, StatusCode> {
+    let settlement = state.settlement.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let settlement = settlement
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(settlement.status()))
+}
+
+/// GET /api/collateral
+#[utoipa::path(
+    get,
+    path = "/api/collateral",
+    tag = "risk",
+    responses(
+        (status = 200, body = CollateralView),
+        (status = 404, description = "Observer mode (the margin view lives in memory)"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn collateral(State(state): State<ApiState>) -> Result<Json<CollateralView>, StatusCode> {
+    let margin = state.margin.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let margin = margin
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(margin.view()))
+}
+
+/// GET /api/toxicity
+#[utoipa::path(
+    get,
+    path = "/api/toxicity",
+    tag = "risk",
+    responses(
+        (status = 200, body = Vec<MarketToxicity>),
+        (status = 404, description = "Toxicity tracking off (or not trading live)"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn toxicity(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<MarketToxicity>>, StatusCode> {
+    let toxicity = state.toxicity.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let toxicity = toxicity
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(toxicity.snapshot()))
+}
+
+/// GET /api/subscriptions
+#[utoipa::path(
+    get,
+    path = "/api/subscriptions",
+    tag = "markets",
+    responses(
+        (status = 200, body = SubscriptionStats),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn subscriptions(
+    State(state): State<ApiState>,
+) -> Result<Json<SubscriptionStats>, StatusCode> {
+    let subscriptions = state
+        .subscriptions
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(subscriptions.stats()))
+}
+
+/// GET /api/ws/bandwidth
+#[utoipa::path(
+    get,
+    path = "/api/ws/bandwidth",
+    tag = "session",
+    responses(
+        (status = 200, body = BandwidthStats),
+        (status = 404, description = "Disabled in this run")
+    )
+)]
+pub async fn ws_bandwidth(
+    State(state): State<ApiState>,
+) -> Result<Json<BandwidthStats>, StatusCode> {
+    let meter = state.ws_bandwidth.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(meter.stats()))
+}
+
+/// GET /api/ws/sequencing
+#[utoipa::path(
+    get,
+    path = "/api/ws/sequencing",
+    tag = "session",
+    responses(
+        (status = 200, body = SequenceStats),
+        (status = 404, description = "Disabled in this run")
+    )
+)]
+pub async fn ws_sequencing(
+    State(state): State<ApiState>,
+) -> Result<Json<SequenceStats>, StatusCode> {
+    let sequencer = state.ws_sequencing.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(sequencer.stats()))
+}
+
+/// What a resync / resubscribe request queued
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WsResyncAck {
+    /// Assets whose book snapshot was re-requested
+    pub assets: usize,
+}
+
+/// POST /api/ws/resync
+#[utoipa::path(
+    post,
+    path = "/api/ws/resync",
+    tag = "session",
+    responses(
+        (status = 202, description = "Reconnect queued; every subscription is replayed", body = WsResyncAck),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Read-only observer or non-admin token"),
+        (status = 404, description = "CLOB WS off in this run"),
+        (status = 500, description = "State lock poisoned or socket task gone")
+    )
+)]
+pub async fn ws_resync(
+    State(state): State<ApiState>,
+) -> Result<(StatusCode, Json<WsResyncAck>), StatusCode> {
+    if state.read_only {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let control = state.ws_control.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let assets = state
+        .subscriptions
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .len();
+    if !control.resync() {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    Ok((StatusCode::ACCEPTED, Json(WsResyncAck { assets })))
+}
+
+/// POST /api/ws/resubscribe/{asset_id}
+#[utoipa::path(
+    post,
+    path = "/api/ws/resubscribe/{asset_id}",
+    tag = "session",
+    params(("asset_id" = String, Path, description = "Token id")),
+    responses(
+        (status = 202, description = "Book snapshot re-requested", body = WsResyncAck),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Read-only observer or non-admin token"),
+        (status = 404, description = "CLOB WS off, or asset not subscribed"),
+        (status = 500, description = "State lock poisoned or socket task gone")
+    )
+)]
+pub async fn ws_resubscribe(
+    State(state): State<ApiState>,
+    Path(asset_id): Path<String>,
+) -> Result<(StatusCode, Json<WsResyncAck>), StatusCode> {
+    if state.read_only {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let control = state.ws_control.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let subscribed = state
+        .subscriptions
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .contains(&asset_id);
+    if !subscribed {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if !control.resubscribe(vec![asset_id]) {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    Ok((StatusCode::ACCEPTED, Json(WsResyncAck { assets: 1 })))
+}
+
+/// GET /api/log-levels
+#[utoipa::path(
+    get,
+    path = "/api/log-levels",
+    tag = "session",
+    responses(
+        (status = 200, description = "Startup filter, overrides and the filter in effect", body = LogLevelsView),
+        (status = 404, description = "Runtime log control not installed"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn log_levels(State(state): State<ApiState>) -> Result<Json<LogLevelsView>, StatusCode> {
+    let levels = state.log_levels.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    levels
+        .view()
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LogLevelRequest {
+    /// `target=level` (e.g. `polymarket_hft_agent::polymarket::ws=trace`) or a bare level
+    pub directive: String,
+    /// Seconds before the override lapses (omit to keep it until reset)
+    pub ttl_secs: Option<u64>,
+}
+
+/// POST /api/log-levels
+#[utoipa::path(
+    post,
+    path = "/api/log-levels",
+    tag = "session",
+    request_body = LogLevelRequest,
+    responses(
+        (status = 200, body = LogLevelsView),
+        (status = 400, description = "Invalid directive"),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Non-admin token"),
+        (status = 404, description = "Runtime log control not installed")
+    )
+)]
+pub async fn set_log_level(
+    State(state): State<ApiState>,
+    Json(request): Json<LogLevelRequest>,
+) -> Result<Json<LogLevelsView>, StatusCode> {
+    let levels = state.log_levels.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let ttl = request
+        .ttl_secs
+        .map(|secs| Duration::seconds(secs.min(logging::MAX_TTL_SECS) as i64));
+    levels
+        .set(&request.directive, ttl)
+        .map(Json)
+        .map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+/// DELETE /api/log-levels
+#[utoipa::path(
+    delete,
+    path = "/api/log-levels",
+    tag = "session",
+    responses(
+        (status = 200, description = "Back to the startup filter", body = LogLevelsView),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Non-admin token"),
+        (status = 404, description = "Runtime log control not installed"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn reset_log_levels(
+    State(state): State<ApiState>,
+) -> Result<Json<LogLevelsView>, StatusCode> {
+    let levels = state.log_levels.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    levels
+        .reset()
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /api/failover
+#[utoipa::path(
+    get,
+    path = "/api/failover",
+    tag = "failover",
+    responses(
+        (status = 200, body = Heartbeat),
+        (status = 404, description = "Failover off")
+    )
+)]
+pub async fn failover(State(state): State<ApiState>) -> Result<Json<Heartbeat>, StatusCode> {
+    let failover = state.failover.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(failover.heartbeat()))
+}
+
+/// POST /api/failover/fence
+#[utoipa::path(
+    post,
+    path = "/api/failover/fence",
+    tag = "failover",
+    request_body = FenceRequest,
+    responses(
+        (status = 200, description = "Heartbeat after the fence", body = Heartbeat),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Non-admin token"),
+        (status = 404, description = "Failover off")
+    )
+)]
+pub async fn fence(
+    State(state): State<ApiState>,
+    Json(request): Json<FenceRequest>,
+) -> Result<Json<Heartbeat>, StatusCode> {
+    let failover = state.failover.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(failover.fence(request.epoch)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PnlDailyQuery {
+    /// Most recent days to return (default 90)
+    pub days: Option<usize>,
+}
+
+/// GET /api/pnl/daily
+#[utoipa::path(
+    get,
+    path = "/api/pnl/daily",
+    tag = "pnl",
+    params(PnlDailyQuery),
+    responses(
+        (status = 200, body = Vec<PnlBucket>),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn pnl_daily(
+    State(state): State<ApiState>,
+    Query(query): Query<PnlDailyQuery>,
+) -> Result<Json<Vec<PnlBucket>>, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.calendar.daily(query.days.unwrap_or(90))))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PnlHourlyQuery {
+    /// Most recent hours to return (default 48)
+    pub hours: Option<usize>,
+}
+
+/// GET /api/pnl/hourly
+#[utoipa::path(
+    get,
+    path = "/api/pnl/hourly",
+    tag = "pnl",
+    params(PnlHourlyQuery),
+    responses(
+        (status = 200, body = Vec<PnlBucket>),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn pnl_hourly(
+    State(state): State<ApiState>,
+    Query(query): Query<PnlHourlyQuery>,
+) -> Result<Json<Vec<PnlBucket>>, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.calendar.hourly(query.hours.unwrap_or(48))))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PnlEquityQuery {
+    /// Most recent hours to return (default 168; 0 = everything kept)
+    pub hours: Option<i64>,
+}
+
+/// GET /api/pnl/equity
+#[utoipa::path(
+    get,
+    path = "/api/pnl/equity",
+    tag = "pnl",
+    params(PnlEquityQuery),
+    responses(
+        (status = 200, body = Vec<PortfolioSnapshot>),
+        (status = 400, description = "hours out of range"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn pnl_equity(
+    State(state): State<ApiState>,
+    Query(query): Query<PnlEquityQuery>,
+) -> Result<Json<Vec<PortfolioSnapshot>>, StatusCode> {
+    let since = match query.hours.unwrap_or(168) {
+        hours if hours > 0 => Some(
+            Duration::try_hours(hours)
+                .and_then(|window| Utc::now().checked_sub_signed(window))
+                .ok_or(StatusCode::BAD_REQUEST)?,
+        ),
+        _ => None,
+    };
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.equity_curve(since).to_vec()))
+}
+
+/// GET /api/pnl/stats
+#[utoipa::path(
+    get,
+    path = "/api/pnl/stats",
+    tag = "pnl",
+    responses((status = 200, body = PnLStats), (status = 500, description = "State lock poisoned"))
+)]
+pub async fn pnl_stats(State(state): State<ApiState>) -> Result<Json<PnLStats>, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.get_stats()))
+}
+
+/// GET /api/pnl/paper
+#[utoipa::path(
+    get,
+    path = "/api/pnl/paper",
+    tag = "pnl",
+    responses(
+        (status = 200, body = PnLStats),
+        (status = 404, description = "No paper-only strategies in this run"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn pnl_paper(State(state): State<ApiState>) -> Result<Json<PnLStats>, StatusCode> {
+    let book = state.paper_pnl.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let tracker = book.lock().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(tracker.get_stats()))
+}
+
+/// GET /api/pnl/categories
+#[utoipa::path(
+    get,
+    path = "/api/pnl/categories",
+    tag = "pnl",
+    params(ListQuery),
+    responses(
+        (status = 200, body = Vec<CategoryPnL>),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn pnl_categories(
+    State(state): State<ApiState>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<Vec<CategoryPnL>>, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Markets no longer in the registry fall back to the rules on the recorded question
+    Ok(Json(tracker.journal.by_category(
+        &query,
+        |trade| match state.registry.get(&trade.market_id) {
+            Some(market) => MarketCategory::of(&market),
+            None => MarketCategory::classify(&trade.market_question),
+        },
+    )))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AttributionQuery {
+    /// Break PnL down by `strategy` (default) or `config`
+    pub by: Option<Dimension>,
+}
+
+/// GET /api/pnl/attribution[?by=strategy|config]
+#[utoipa::path(
+    get,
+    path = "/api/pnl/attribution",
+    tag = "pnl",
+    params(AttributionQuery, ListQuery),
+    responses(
+        (status = 200, body = AttributionReport),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn pnl_attribution(
+    State(state): State<ApiState>,
+    Query(attribution): Query<AttributionQuery>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<AttributionReport>, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(
+        tracker.attribution(attribution.by.unwrap_or_default(), &query),
+    ))
+}
+
+/// Matching items before paging, for listings that return one page
+const TOTAL_COUNT: HeaderName = HeaderName::from_static("x-total-count");
+
+/// GET /api/trades
+#[utoipa::path(
+    get,
+    path = "/api/trades",
+    tag = "trades",
+    params(ListQuery),
+    responses(
+        (
+            status = 200,
+            body = Vec<Trade>,
+            headers(("x-total-count" = usize, description = "Matching trades before paging"))
+        ),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn trades(
+    State(state): State<ApiState>,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (total, trades) = tracker.journal.query(&query);
+    Ok(([(TOTAL_COUNT, total.to_string())], Json(trades)))
+}
+
+/// GET /api/positions
+#[utoipa::path(
+    get,
+    path = "/api/positions",
+    tag = "trades",
+    params(ListQuery),
+    responses(
+        (
+            status = 200,
+            body = Vec<Position>,
+            headers(("x-total-count" = usize, description = "Matching positions before paging"))
+        ),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn positions(
+    State(state): State<ApiState>,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (total, positions) = tracker.query_positions(&query);
+    Ok(([(TOTAL_COUNT, total.to_string())], Json(positions)))
+}
+
+/// GET /api/trades/export
+#[utoipa::path(
+    get,
+    path = "/api/trades/export",
+    tag = "trades",
+    responses(
+        (status = 200, body = String, content_type = "text/csv"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn trades_export(State(state): State<ApiState>) -> Result<impl IntoResponse, StatusCode> {
+    let tracker = state
+        .pnl_tracker
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"trades.csv\"",
+            ),
+        ],
+        tracker.journal.to_csv(),
+    ))
+}
+
+/// PATCH /api/trades/{id}
+#[utoipa::path(
+    patch,
+    path = "/api/trades/{id}",
+    tag = "trades",
+    params(("id" = String, Path, description = "Trade id")),
+    request_body = TradeAnnotation,
+    responses(
+        (status = 200, body = Trade),
+        (status = 401, description = "Missing or unknown API token"),
+        (status = 403, description = "Read-only observer or non-admin token"),
+        (status = 404, description = "Trade not found"),
+        (status = 500, description = "State lock poisoned")
+    )
+)]
+pub async fn annotate_trade(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    Json(annotation): Json<TradeAnnotation>,
+) -> Result<Json<Trade>, StatusCode> {
+    if state.read_only {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let (trade, unsaved) = {
+        let mut tracker = state
+            .pnl_tracker
+            .lock()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let trade = tracker
+            .annotate_trade(&id, annotation)
+            .ok_or(StatusCode::NOT_FOUND)?;
+        (trade, tracker.journal.take_unsaved())
+    };
+    if let Some(unsaved) = unsaved {
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = unsaved.save() {
+                warn!("⚠️ Failed to persist trade journal: {}", e);
+            }
+        });
+    }
+    Ok(Json(trade))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TradeContextQuery {
+    /// Seconds of records before the entry and after the exit (default 300, at most a day)
+    pub padding_secs: Option<i64>,
+}
+
+/// GET /api/trades/{id}/context
+#[utoipa::path(
+    get,
+    path = "/api/trades/{id}/context",
+    tag = "trades",
+    params(("id" = String, Path, description = "Trade id"), TradeContextQuery),
+    responses(
+        (status = 200, description = "Books, decisions and features recorded around the trade", body = TradeContext),
+        (status = 404, description = "Trade not found"),
+        (status = 500, description = "State lock poisoned or records unreadable")
+    )
+)]
+pub async fn trade_context(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    Query(query): Query<TradeContextQuery>,
+) -> Result<Json<TradeContext>, StatusCode> {
+    let trade = {
+        let tracker = state
+            .pnl_tracker
+            .lock()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        tracker
+            .journal
+            .get(&id)
+            .or_else(|| tracker.trades.iter().find(|t| t.id == id))
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)?
+    };
+    let padding = chrono::Duration::seconds(
+        query
+            .padding_secs
+            .unwrap_or(300)
+            .clamp(0, analytics::MAX_PADDING_SECS),
+    );
+    // The decision log can be large
+    tokio::task::spawn_blocking(move || analytics::trade_context(&state.data_dir, &trade, padding))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
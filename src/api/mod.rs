@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use axum::middleware;
+use axum::routing::{delete, get, patch, post};
+use axum::Router;
+use std::sync::{Arc, Mutex};
+use tracing::info;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::analytics::{
+    DriftTracker, EdgeRealizationTracker, ExecutionDriftTracker, IndexingLatencyTracker,
+    OpportunityBook, PnLTracker, PostmortemLog, SessionRecord,
+};
+use crate::execution::{AnomalyGuard, MarginBook, MissedWindows, SettlementReconciler};
+use crate::failover::Failover;
+use crate::llm_budget::LlmBudget;
+use crate::logging::LogLevels;
+use crate::markets::{
+    ExpirationWatchlist, FeatureTracker, FeeRates, LifecycleTracker, MarketRegistry, PinnedMarkets,
+    SeriesWatcher, SubscriptionSet, ToxicityTracker, WhaleTracker, ZombieTracker,
+};
+use crate::polymarket::{
+    BandwidthMeter, BookSequencer, CircuitBreakers, MarketInterface, WsControl,
+};
+use crate::pricefeed::BinanceClient;
+use crate::profiles::ProfileSchedule;
+use crate::scheduling::SchedulerStats;
+use crate::strategies::hedging::Hedger;
+
+pub mod auth;
+mod docs;
+mod handlers;
+pub mod summary;
+
+pub use auth::ApiAuth;
+pub use docs::ApiDoc;
+pub use summary::SummaryCache;
+
+/// Shared handles the API reads from (owned by the Sniper)
+#[derive(Clone)]
+pub struct ApiState {
+    pub indexing_latency: Arc<Mutex<IndexingLatencyTracker>>,
+    pub lifecycle: Arc<Mutex<LifecycleTracker>>,
+    pub registry: MarketRegistry,
+    pub session: Arc<SessionRecord>,
+    /// None when the opportunity book is disabled
+    pub opportunities: Option<Arc<Mutex<OpportunityBook>>>,
+    pub edge_realization: Arc<Mutex<EdgeRealizationTracker>>,
+    pub execution_drift: Arc<Mutex<ExecutionDriftTracker>>,
+    pub postmortems: Arc<Mutex<PostmortemLog>>,
+    /// None unless trading live with the settlement watch on (and in observer mode)
+    pub settlement: Option<Arc<Mutex<SettlementReconciler>>>,
+    /// Locked vs free collateral; None in observer mode (it lives in memory)
+    pub margin: Option<Arc<Mutex<MarginBook>>>,
+    pub breakers: Arc<CircuitBreakers>,
+    pub subscriptions: Arc<Mutex<SubscriptionSet>>,
+    pub pnl_tracker: Arc<Mutex<PnLTracker>>,
+    /// Book of the paper-only strategies of a live run; None when there are none
+    pub paper_pnl: Option<Arc<Mutex<PnLTracker>>>,
+    pub watchlist: Arc<Mutex<ExpirationWatchlist>>,
+    /// Operator-pinned markets (the observer serves the trading instance's, read-only)
+    pub pins: Arc<Mutex<PinnedMarkets>>,
+    /// Resolves slugs / URLs to markets; None in observer mode (it doesn't call the exchange)
+    pub market_lookup: Option<Arc<dyn MarketInterface + Send + Sync>>,
+    /// None unless trading live with the anomaly guard on
+    pub anomaly_guard: Option<Arc<AnomalyGuard>>,
+    /// None unless the activity feed is on
+    pub whales: Option<Arc<Mutex<WhaleTracker>>>,
+    /// Resolved-but-open markets; None when detection is off, and in observer mode
+    pub zombies: Option<Arc<Mutex<ZombieTracker>>>,
+    /// New event series on Gamma; None when the watch is off, and in observer mode
+    pub series: Option<Arc<Mutex<SeriesWatcher>>>,
+    /// Fee rates checked on the CLOB; None in observer mode
+    pub fee_rates: Option<FeeRates>,
+    /// Signals dropped over their latency budget; None in observer mode
+    pub missed_windows: Option<Arc<Mutex<MissedWindows>>>,
+    /// None unless hedging is on
+    pub hedger: Option<Arc<Mutex<Hedger>>>,
+    /// None unless trading live with toxicity tracking on (and in observer mode)
+    pub toxicity: Option<Arc<Mutex<ToxicityTracker>>>,
+    /// None without a parameter profiles file (and in observer mode)
+    pub profiles: Option<Arc<Mutex<ProfileSchedule>>>,
+    /// None unless trading live (the observer reads the live instance's)
+    pub drift: Option<Arc<Mutex<DriftTracker>>>,
+    /// None when the CLOB WS is off (simulation mode)
+    pub ws_bandwidth: Option<Arc<BandwidthMeter>>,
+    /// None when the CLOB WS is off (simulation mode)
+    pub ws_sequencing: Option<Arc<BookSequencer>>,
+    /// Forces resyncs of the market channel; None when the CLOB WS is off (and in observer mode)
+    pub ws_control: Option<WsControl>,
+    /// Runtime log filter; None when the process didn't install it (embedders, tests)
+    pub log_levels: Option<LogLevels>,
+    /// External price feed and its outlier filter; None in observer mode
+    pub price_feed: Option<Arc<BinanceClient>>,
+    /// Call limits and cost ledger of the LLM classifier; None when it's off, and in observer mode
+    pub llm_costs: Option<Arc<Mutex<LlmBudget>>>,
+    /// Runs and timing of the run loop's maintenance tasks; None in observer mode
+    pub scheduler: Option<SchedulerStats>,
+    /// None when order flow features are off, and in observer mode (they live in memory)
+    pub features: Option<Arc<Mutex<FeatureTracker>>>,
+    /// None unless failover is on (and in observer mode)
+    pub failover: Option<Arc<Failover>>,
+    /// Recorded decisions and features are read from here (trade replays)
+    pub data_dir: String,
+    /// Observer mode: the data dir belongs to another instance, edits are refused
+    pub read_only: bool,
+    /// Read / admin tokens and the control call audit log
+    pub auth: Arc<ApiAuth>,
+    /// Last /api/summary, served until API_SUMMARY_CACHE_SECS old
+    pub summary: SummaryCache,
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/api/indexing-latency", get(handlers::indexing_latency))
+        .route("/api/markets", get(handlers::markets))
+        .route("/api/markets/states", get(handlers::market_states))
+        .route("/api/markets/lookup", get(handlers::lookup_market))
+        .route("/api/markets/{id}", get(handlers::market))
+        .route("/api/markets/{id}/state", get(handlers::market_state))
+        .route("/api/expirations", get(handlers::expirations))
+        .route(
+            "/api/watchlist",
+            get(handlers::pinned_markets).post(handlers::pin_market),
+        )
+        .route("/api/watchlist/{key}", delete(handlers::unpin_market))
+        .route("/api/assets/{asset_id}", get(handlers::asset))
+        .route("/api/features/{asset_id}", get(handlers::features))
+        .route("/api/session", get(handlers::session))
+        .route("/api/summary", get(handlers::summary))
+        .route("/api/opportunities", get(handlers::opportunities))
+        .route("/api/edge-realization", get(handlers::edge_realization))
+        .route("/api/execution-drift", get(handlers::execution_drift))
+        .route("/api/drift", get(handlers::drift))
+        .route("/api/postmortems", get(handlers::postmortems))
+        .route("/api/breakers", get(handlers::breakers))
+        .route("/api/price-feed", get(handlers::price_feed))
+        .route("/api/llm/costs", get(handlers::llm_costs))
+        .route("/api/scheduler", get(handlers::scheduler))
+        .route("/api/anomalies", get(handlers::anomalies))
+        .route("/api/whales", get(handlers::whales))
+        .route("/api/zombies", get(handlers::zombies))
+        .route("/api/series", get(handlers::series))
+        .route("/api/fees", get(handlers::fees))
+        .route("/api/missed-windows", get(handlers::missed_windows))
+        .route("/api/hedges", get(handlers::hedges))
+        .route("/api/profiles", get(handlers::profiles))
+        .route("/api/settlement", get(handlers::settlement))
+        .route("/api/collateral", get(handlers::collateral))
+        .route("/api/toxicity", get(handlers::toxicity))
+        .route("/api/subscriptions", get(handlers::subscriptions))
+        .route("/api/ws/bandwidth", get(handlers::ws_bandwidth))
+        .route("/api/ws/sequencing", get(handlers::ws_sequencing))
+        .route("/api/ws/resync", post(handlers::ws_resync))
+        .route(
+            "/api/ws/resubscribe/{asset_id}",
+            post(handlers::ws_resubscribe),
+        )
+        .route(
+            "/api/log-levels",
+            get(handlers::log_levels)
+                .post(handlers::set_log_level)
+                .delete(handlers::reset_log_levels),
+        )
+        .route("/api/failover", get(handlers::failover))
+        .route("/api/failover/fence", post(handlers::fence))
+        .route("/api/pnl/daily", get(handlers::pnl_daily))
+        .route("/api/pnl/hourly", get(handlers::pnl_hourly))
+        .route("/api/pnl/equity", get(handlers::pnl_equity))
+        .route("/api/pnl/stats", get(handlers::pnl_stats))
+        .route("/api/pnl/paper", get(handlers::pnl_paper))
+        .route("/api/pnl/categories", get(handlers::pnl_categories))
+        .route("/api/pnl/attribution", get(handlers::pnl_attribution))
+        .route("/api/trades", get(handlers::trades))
+        .route("/api/positions", get(handlers::positions))
+        .route("/api/trades/export", get(handlers::trades_export))
+        .route("/api/trades/{id}", patch(handlers::annotate_trade))
+        .route("/api/trades/{id}/context", get(handlers::trade_context))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::authorize))
+        .with_state(state)
+        .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi()))
+}
+
+/// Serve the API until the process exits
+pub async fn serve(state: ApiState, bind: &str, port: u16) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind((bind, port))
+        .await
+        .with_context(|| format!("Failed to bind API to {}:{}", bind, port))?;
+    info!("🌐 API listening on http://{}:{}", bind, port);
+
+    axum::serve(listener, router(state))
+        .await
+        .context("API server stopped")
+}
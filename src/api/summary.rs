@@ -0,0 +1,192 @@
+//! Dashboard summary widgets
+//!
+//! The dashboard polls a handful of numbers every few seconds. Deriving them from the full
+//! statistics (every trade, every snapshot) on each poll is wasted work, so `/api/summary`
+//! builds them from the aggregates already kept - the hourly PnL buckets, the open positions,
+//! the journal's latest trade, the WS meters - and serves the same copy for
+//! `API_SUMMARY_CACHE_SECS`.
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+use super::ApiState;
+
+/// The latest closed trade
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LastTrade {
+    pub id: String,
+    pub market_question: String,
+    pub side: String,
+    pub realized_pnl: Option<f64>,
+    pub exit_time: Option<DateTime<Utc>>,
+}
+
+/// Market data connection at a glance
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WsHealth {
+    /// None when the CLOB WS is off (simulation mode, observer)
+    pub connected: Option<bool>,
+    pub connected_since: Option<DateTime<Utc>>,
+    /// Over the last completed one-second window
+    pub messages_per_sec: f64,
+    pub subscribed_assets: usize,
+    /// Book updates dropped as out of order or duplicated
+    pub dropped_updates: u64,
+}
+
+/// The numbers the dashboard shows
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DashboardSummary {
+    pub computed_at: DateTime<Utc>,
+    pub uptime_secs: i64,
+    /// Realized over the hourly buckets of the last 24 hours
+    pub pnl_24h: f64,
+    pub trades_24h: u32,
+    pub unrealized_pnl: f64,
+    pub open_positions: usize,
+    /// Cost of the open positions: their entry notional in USD, not their share counts
+    pub open_exposure_usd: f64,
+    pub last_trade: Option<LastTrade>,
+    pub ws: WsHealth,
+}
+
+/// The last summary built, shared by the API's handlers
+#[derive(Clone)]
+pub struct SummaryCache {
+    ttl: Duration,
+    cached: Arc<Mutex<Option<DashboardSummary>>>,
+}
+
+impl SummaryCache {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            ttl: Duration::seconds(ttl_secs as i64),
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The cached summary, rebuilt with `build` once it is `ttl` old
+    pub fn get_or_build<E>(
+        &self,
+        now: DateTime<Utc>,
+        build: impl FnOnce() -> Result<DashboardSummary, E>,
+    ) -> Result<DashboardSummary, E> {
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(summary) = cached.as_ref().filter(|s| now - s.computed_at < self.ttl) {
+            return Ok(summary.clone());
+        }
+        let summary = build()?;
+        *cached = Some(summary.clone());
+        Ok(summary)
+    }
+}
+
+/// Build the summary from the state's aggregates
+pub fn build(state: &ApiState, now: DateTime<Utc>) -> Result<DashboardSummary, StatusCode> {
+    let (pnl_24h, trades_24h, unrealized_pnl, open_positions, open_exposure_usd, last_trade) = {
+        let tracker = state
+            .pnl_tracker
+            .lock()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let day = tracker
+            .calendar
+            .hourly(24)
+            .into_iter()
+            .filter(|bucket| now - bucket.start < Duration::hours(24));
+        let (pnl_24h, trades_24h) = day.fold((0.0, 0), |(pnl, trades), bucket| {
+            (pnl + bucket.realized_pnl, trades + bucket.trades)
+        });
+        let last_trade = tracker
+            .journal
+            .recent(1, None)
+            .pop()
+            .map(|trade| LastTrade {
+                id: trade.id,
+                market_question: trade.market_question,
+                side: trade.side,
+                realized_pnl: trade.realized_pnl,
+                exit_time: trade.exit_time,
+            });
+        (
+            pnl_24h,
+            trades_24h,
+            tracker.calculate_unrealized_pnl(),
+            tracker.positions.len(),
+            tracker.positions.values().map(|p| p.size).sum(),
+            last_trade,
+        )
+    };
+
+    let bandwidth = state.ws_bandwidth.as_ref().map(|meter| meter.stats());
+    let current = bandwidth.as_ref().and_then(|stats| stats.current.as_ref());
+    let ws = WsHealth {
+        connected: bandwidth.as_ref().map(|stats| stats.current.is_some()),
+        connected_since: current.map(|connection| connection.connected_at),
+        messages_per_sec: current.map_or(0.0, |connection| connection.messages_per_sec),
+        subscribed_assets: state
+            .subscriptions
+            .lock()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .stats()
+            .subscribed,
+        dropped_updates: state.ws_sequencing.as_ref().map_or(0, |sequencer| {
+            let stats = sequencer.stats();
+            stats.reordered + stats.duplicates
+        }),
+    };
+
+    Ok(DashboardSummary {
+        computed_at: now,
+        uptime_secs: (now - state.session.started_at).num_seconds().max(0),
+        pnl_24h,
+        trades_24h,
+        unrealized_pnl,
+        open_positions,
+        open_exposure_usd,
+        last_trade,
+        ws,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(computed_at: DateTime<Utc>) -> DashboardSummary {
+        DashboardSummary {
+            computed_at,
+            uptime_secs: 0,
+            pnl_24h: 0.0,
+            trades_24h: 0,
+            unrealized_pnl: 0.0,
+            open_positions: 0,
+            open_exposure_usd: 0.0,
+            last_trade: None,
+            ws: WsHealth {
+                connected: None,
+                connected_since: None,
+                messages_per_sec: 0.0,
+                subscribed_assets: 0,
+                dropped_updates: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_serves_the_cached_summary_until_it_expires() {
+        let cache = SummaryCache::new(5);
+        let start = Utc::now();
+        let built_at = |at| move || Ok::<_, ()>(summary(at));
+
+        let first = cache.get_or_build(start, built_at(start)).unwrap();
+        assert_eq!(first.computed_at, start);
+        let soon = start + Duration::seconds(3);
+        let cached = cache.get_or_build(soon, built_at(soon)).unwrap();
+        assert_eq!(cached.computed_at, start);
+        let later = start + Duration::seconds(5);
+        let rebuilt = cache.get_or_build(later, built_at(later)).unwrap();
+        assert_eq!(rebuilt.computed_at, later);
+    }
+}
@@ -0,0 +1,360 @@
+//! Soak test: synthetic market launch storms against a simulation-mode Sniper
+//!
+//! Bursts of new-market events and a sustained book update firehose are pushed through the
+//! same channels the CTF listener and CLOB WS feed, while memory (RSS), update queue depth
+//! and drops are reported. Exits non-zero when drops exceed `SOAK_MAX_DROP_PCT`.
+//!
+//! `cargo run --release --bin soak` (tune with the `SOAK_*` variables, see README)
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time::interval;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use polymarket_hft_agent::alloc_profile;
+use polymarket_hft_agent::analytics::PnLTracker;
+use polymarket_hft_agent::api::ApiState;
+use polymarket_hft_agent::clock;
+use polymarket_hft_agent::config::Config;
+use polymarket_hft_agent::markets::ConditionId;
+use polymarket_hft_agent::monitor::rss_mb;
+use polymarket_hft_agent::polymarket::contracts::derive_asset_ids_batch;
+use polymarket_hft_agent::polymarket::ws::{OrderbookUpdate, PriceLevel};
+use polymarket_hft_agent::polymarket::{update_queue, DropPolicy, UpdateSender};
+use polymarket_hft_agent::sniper::Sniper;
+
+#[cfg(not(feature = "alloc-profiling"))]
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+// Same allocator, with per-region accounting
+#[cfg(feature = "alloc-profiling")]
+#[global_allocator]
+static GLOBAL: alloc_profile::CountingAlloc<mimalloc::MiMalloc> =
+    alloc_profile::CountingAlloc(mimalloc::MiMalloc);
+
+/// Feed generator ticks (updates are spread evenly across ticks)
+const FEED_TICK: Duration = Duration::from_millis(10);
+/// Share of updates aimed at the latest burst (fresh launches are where the flow is)
+const HOT_SHARE: f64 = 0.8;
+
+#[derive(Debug, Clone)]
+struct SoakSettings {
+    duration_secs: u64,
+    burst_markets: usize,
+    burst_interval_secs: u64,
+    updates_per_sec: u64,
+    report_interval_secs: u64,
+    max_drop_pct: f64,
+}
+
+impl SoakSettings {
+    fn from_env() -> Self {
+        Self {
+            duration_secs: env::var("SOAK_DURATION_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            burst_markets: env::var("SOAK_BURST_MARKETS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            burst_interval_secs: env::var("SOAK_BURST_INTERVAL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            updates_per_sec: env::var("SOAK_UPDATES_PER_SEC")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            report_interval_secs: env::var("SOAK_REPORT_INTERVAL_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            max_drop_pct: env::var("SOAK_MAX_DROP_PCT")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0),
+        }
+    }
+}
+
+/// What the generator has pushed so far
+#[derive(Default)]
+struct FeedStats {
+    markets_launched: AtomicU64,
+    updates_sent: AtomicU64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Agent logs at warn by default: a storm at info level mostly measures the terminal
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let settings = SoakSettings::from_env();
+    let data_dir = env::temp_dir().join(format!("soak-{}", uuid::Uuid::new_v4()));
+
+    let mut config = Config::from_env()?;
+    config.agent.simulation_mode = true;
+    config.agent.data_dir = data_dir.to_string_lossy().into_owned();
+    config.agent.decision_log_mode = "off".to_string();
+    config.polygon_ws_rpc = None;
+    config.ctf_contract_address = None;
+    config.flashbots.enabled = false;
+    // SQLite in the temp data dir, never the operator's database
+    config.storage.backend = "sqlite".to_string();
+    config.storage.url = None;
+    let queue_capacity = config.agent.ws_queue_capacity;
+    let queue_policy = DropPolicy::parse(&config.agent.ws_queue_policy);
+
+    println!("🌪️  Soak test: {:?}", settings);
+    println!(
+        "📬 Update queue: capacity {} | policy {:?}",
+        queue_capacity, queue_policy
+    );
+
+    let pnl_tracker = Arc::new(Mutex::new(PnLTracker::new(1000.0)));
+    let (market_tx, market_rx) = mpsc::unbounded_channel();
+    let (update_tx, update_rx) = update_queue(queue_capacity, queue_policy);
+    let mut sniper = Sniper::builder(config)
+        .pnl_tracker(pnl_tracker)
+        .new_market_feed(market_rx)
+        .book_feed(update_rx)
+        .build()
+        .await?;
+    let state = sniper.api_state();
+
+    let feed = Arc::new(FeedStats::default());
+    let queue = update_tx.clone();
+    tokio::spawn(generate(
+        settings.clone(),
+        market_tx,
+        update_tx,
+        feed.clone(),
+    ));
+
+    // The Sniper runs until the monitor's deadline (it only returns on error)
+    let samples = tokio::select! {
+        result = sniper.run() => {
+            result?;
+            Vec::new()
+        }
+        samples = monitor(&settings, &state, &queue, &feed) => samples,
+    };
+
+    let dropped_pct = summarize(&queue, &feed, &samples);
+    alloc_profile::print_report(20);
+    let _ = std::fs::remove_dir_all(&data_dir);
+
+    if dropped_pct > settings.max_drop_pct {
+        anyhow::bail!(
+            "Dropped {:.2}% of book updates (limit {:.2}%)",
+            dropped_pct,
+            settings.max_drop_pct
+        );
+    }
+    println!("✅ Soak test passed");
+    Ok(())
+}
+
+/// Launch a burst of markets every `burst_interval_secs` and stream book updates for every
+/// launched market at `updates_per_sec`
+async fn generate(
+    settings: SoakSettings,
+    new_markets: mpsc::UnboundedSender<ConditionId>,
+    updates: UpdateSender,
+    feed: Arc<FeedStats>,
+) {
+    let mut rng = StdRng::from_os_rng();
+    let mut assets: Vec<String> = Vec::new();
+    let mut hot_from = 0;
+
+    let mut burst = interval(Duration::from_secs(settings.burst_interval_secs.max(1)));
+    let mut tick = interval(FEED_TICK);
+    let per_tick = settings.updates_per_sec as f64 * FEED_TICK.as_secs_f64();
+    let mut owed = 0.0;
+
+    loop {
+        tokio::select! {
+            _ = burst.tick() => {
+                hot_from = assets.len();
+                let condition_ids: Vec<ConditionId> = (0..settings.burst_markets)
+                    .filter_map(|_| {
+                        ConditionId::parse(&format!(
+                            "{}{}",
+                            uuid::Uuid::new_v4().simple(),
+                            uuid::Uuid::new_v4().simple()
+                        ))
+                        .ok()
+                    })
+                    .collect();
+                let ids: Vec<&str> = condition_ids.iter().map(ConditionId::as_str).collect();
+                let Ok(derived) = derive_asset_ids_batch(&ids) else {
+                    return;
+                };
+                for (condition_id, derived) in condition_ids.iter().zip(derived) {
+                    let Ok((yes_id, no_id)) = derived else {
+                        continue;
+                    };
+                    if new_markets.send(condition_id.clone()).is_err() {
+                        return;
+                    }
+                    assets.push(yes_id);
+                    assets.push(no_id);
+                    feed.markets_launched.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            _ = tick.tick() => {
+                if assets.is_empty() {
+                    continue;
+                }
+                owed += per_tick;
+                while owed >= 1.0 {
+                    owed -= 1.0;
+                    let from = if hot_from < assets.len() && rng.random_bool(HOT_SHARE) {
+                        hot_from
+                    } else {
+                        0
+                    };
+                    let asset_id = assets[rng.random_range(from..assets.len())].clone();
+                    let update = book(&mut rng, asset_id);
+                    if updates.send(update).await.is_err() {
+                        return;
+                    }
+                    feed.updates_sent.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+/// Three levels a side around a random mid
+fn book(rng: &mut StdRng, asset_id: String) -> OrderbookUpdate {
+    let mid: f64 = rng.random_range(0.05..0.95);
+    let mut level = |price: f64| PriceLevel {
+        price: format!("{:.2}", price.clamp(0.01, 0.99)),
+        size: format!("{:.0}", rng.random_range(10.0..2_000.0)),
+    };
+    OrderbookUpdate {
+        asset_id,
+        bids: (1..=3).map(|i| level(mid - 0.01 * i as f64)).collect(),
+        asks: (1..=3).map(|i| level(mid + 0.01 * i as f64)).collect(),
+        timestamp: chrono::Utc::now().timestamp_millis().to_string(),
+        hash: String::new(),
+        seq: 0,
+        received_ns: clock::now_ns(),
+        change: None,
+    }
+}
+
+/// Print a status line every report interval until the soak duration is up
+/// Returns the (elapsed secs, RSS MB) samples
+async fn monitor(
+    settings: &SoakSettings,
+    state: &ApiState,
+    queue: &UpdateSender,
+    feed: &FeedStats,
+) -> Vec<(f64, f64)> {
+    let start = Instant::now();
+    let duration = Duration::from_secs(settings.duration_secs);
+    let mut report = interval(Duration::from_secs(settings.report_interval_secs.max(1)));
+    let baseline = rss_mb();
+    let mut samples = Vec::new();
+
+    loop {
+        report.tick().await;
+        let elapsed = start.elapsed();
+        let rss = rss_mb();
+        if let Some(rss) = rss {
+            samples.push((elapsed.as_secs_f64(), rss));
+        }
+
+        let stats = queue.stats();
+        let retries = state
+            .lifecycle
+            .lock()
+            .map(|l| l.summary().pending_metadata_retries)
+            .unwrap_or_default();
+        println!(
+            "⏱️  {:>5}s | markets {} (launched {}) | metadata retries {} | queue {}/{} (peak {}) | sent {} | dropped {} | coalesced {} | RSS {}",
+            elapsed.as_secs(),
+            state.registry.len(),
+            feed.markets_launched.load(Ordering::Relaxed),
+            retries,
+            stats.depth,
+            stats.capacity,
+            stats.high_water_mark,
+            feed.updates_sent.load(Ordering::Relaxed),
+            stats.dropped,
+            stats.coalesced,
+            match (rss, baseline) {
+                (Some(rss), Some(base)) => format!("{:.1} MB ({:+.1})", rss, rss - base),
+                _ => "n/a".to_string(),
+            }
+        );
+
+        if elapsed >= duration {
+            return samples;
+        }
+    }
+}
+
+/// Final report; returns the share of updates dropped (percent)
+fn summarize(queue: &UpdateSender, feed: &FeedStats, samples: &[(f64, f64)]) -> f64 {
+    let stats = queue.stats();
+    let dropped_pct = if stats.enqueued > 0 {
+        stats.dropped as f64 / stats.enqueued as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    println!("\n📊 Soak summary");
+    println!(
+        "   • Markets launched: {}",
+        feed.markets_launched.load(Ordering::Relaxed)
+    );
+    println!(
+        "   • Book updates: {} sent | {} dropped ({:.2}%) | {} coalesced | {} superseded",
+        stats.enqueued, stats.dropped, dropped_pct, stats.coalesced, stats.superseded
+    );
+    println!(
+        "   • Queue peak: {}/{}{}",
+        stats.high_water_mark,
+        stats.capacity,
+        if stats.high_water_mark >= stats.capacity {
+            " ⚠️ hit capacity"
+        } else {
+            ""
+        }
+    );
+
+    if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+        let peak = samples.iter().map(|(_, rss)| *rss).fold(0.0, f64::max);
+        println!(
+            "   • RSS: {:.1} MB -> {:.1} MB (peak {:.1} MB)",
+            first.1, last.1, peak
+        );
+        // Second half only: warm-up allocations aren't a leak
+        let mid = samples[samples.len() / 2];
+        if last.0 > mid.0 {
+            println!(
+                "   • RSS growth over the second half: {:+.2} MB/min",
+                (last.1 - mid.1) / (last.0 - mid.0) * 60.0
+            );
+        }
+    } else {
+        println!("   • RSS: n/a (needs /proc)");
+    }
+
+    dropped_pct
+}
@@ -0,0 +1,767 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::analytics::decisions::{decision_log_path, find_decision, replay_decision};
+use crate::analytics::resolutions::calibration;
+use crate::config::Config;
+#[cfg(feature = "trading")]
+use crate::execution::remote;
+use crate::execution::{FundingQueue, FundingStatus};
+use crate::markets::lookup_market;
+use crate::ml::dataset::{label, load_records, write_parquet};
+use crate::observer;
+#[cfg(feature = "trading")]
+use crate::onboarding;
+use crate::polymarket::{MarketInterface, PolymarketClient};
+use crate::simulation::{latency, read_tape, write_tape, HistoryDownloader, LatencyDistribution};
+use crate::storage::{self, ResolutionStore};
+use crate::strategies::arbitrage::ArbitrageStrategy;
+use crate::strategies::edge_tuning::load_adjustments;
+use crate::strategies::experiment::{load_report, ArmSummary};
+use crate::strategies::expiration::ExpirationStrategy;
+use crate::strategies::Strategy;
+use crate::venues::{self, KalshiClient, Venue};
+
+/// One-shot subcommands handled instead of starting the bot
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Re-run a recorded strategy decision: `replay-decision <id>`
+    ReplayDecision { id: String },
+    /// Print the latest A/B experiment comparison: `experiment-report`
+    ExperimentReport,
+    /// Show learned per-category min_edge adjustments: `edge-adjustments`
+    EdgeAdjustments,
+    /// Interactive setup that writes a starter config: `init [path]` (default .env)
+    Init { output: String },
+    /// List or act on wallet funding requests: `funding [list|approve <id>|reject <id>]`
+    Funding { action: FundingAction },
+    /// Serve the API read-only from another instance's DATA_DIR: `observe`
+    Observe,
+    /// Hold the wallet key and serve order entry to a detector over EXECUTOR_SOCKET: `executor`
+    Executor,
+    /// Build a replay tape from Polymarket's public price / trade history:
+    /// `download-history <output.csv> <market_id>... [--days N] [--fidelity MINS]`
+    DownloadHistory {
+        output: String,
+        market_ids: Vec<String>,
+        /// None = all history
+        days: Option<u64>,
+        fidelity_mins: u64,
+    },
+    /// Replay a tape once per decision -> order latency and compare each strategy's PnL:
+    /// `latency-sweep <tape.csv> [--latencies MS,...]`
+    LatencySweep {
+        tape: String,
+        latencies_ms: Vec<u64>,
+    },
+    /// How often outcomes priced at p some time before the end actually won, from the resolution
+    /// dataset: `resolution-calibration [--minutes-before N] [--bucket WIDTH] [--days N]`
+    ResolutionCalibration {
+        minutes_before: i64,
+        bucket_width: f64,
+        /// None = whole dataset
+        days: Option<i64>,
+    },
+    /// Compare the quotes of the KALSHI_PAIRS markets on Polymarket and Kalshi: `compare-venues`
+    CompareVenues,
+    /// Resolve a condition id, market slug or polymarket.com URL to the market's condition id
+    /// and token ids: `lookup <market>`
+    Lookup { market: String },
+    /// Join the recorded feature vectors with forward returns and outcomes into a Parquet
+    /// training set: `export-training <output.parquet> [--horizons SECS,...] [--days N]`
+    ExportTraining {
+        output: String,
+        horizons_secs: Vec<i64>,
+        /// None = everything recorded
+        days: Option<i64>,
+    },
+    /// Show or change the log filter of the instance serving the API on API_PORT:
+    /// `log-level [show|reset|<target=level> [--for SECS]]`
+    LogLevel { action: LogLevelAction },
+}
+
+#[derive(Debug, Clone)]
+pub enum LogLevelAction {
+    Show,
+    Reset,
+    Set {
+        directive: String,
+        /// None = until reset
+        ttl_secs: Option<u64>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum FundingAction {
+    List,
+    Approve(String),
+    Reject(String),
+}
+
+impl Command {
+    /// Parse CLI args (without the binary name). Returns None when no subcommand was given
+    pub fn parse(args: &[String]) -> Result<Option<Self>> {
+        let Some(name) = args.first() else {
+            return Ok(None);
+        };
+
+        match name.as_str() {
+            "replay-decision" => {
+                let id = args
+                    .get(1)
+                    .ok_or_else(|| anyhow::anyhow!("Usage: replay-decision <id>"))?;
+                Ok(Some(Command::ReplayDecision { id: id.clone() }))
+            }
+            "experiment-report" => Ok(Some(Command::ExperimentReport)),
+            "edge-adjustments" => Ok(Some(Command::EdgeAdjustments)),
+            "init" => Ok(Some(Command::Init {
+                output: args.get(1).cloned().unwrap_or_else(|| ".env".to_string()),
+            })),
+            "funding" => {
+                let usage = || anyhow::anyhow!("Usage: funding [list|approve <id>|reject <id>]");
+                let id = || args.get(2).cloned().ok_or_else(usage);
+                let action = match args.get(1).map(|s| s.as_str()) {
+                    None | Some("list") => FundingAction::List,
+                    Some("approve") => FundingAction::Approve(id()?),
+                    Some("reject") => FundingAction::Reject(id()?),
+                    Some(_) => return Err(usage()),
+                };
+                Ok(Some(Command::Funding { action }))
+            }
+            "observe" => Ok(Some(Command::Observe)),
+            "executor" => Ok(Some(Command::Executor)),
+            "download-history" => {
+                let usage = || {
+                    anyhow::anyhow!(
+                        "Usage: download-history <output.csv> <market_id>... [--days N] [--fidelity MINS]"
+                    )
+                };
+                let output = args.get(1).cloned().ok_or_else(usage)?;
+                let mut market_ids = Vec::new();
+                let mut days = None;
+                let mut fidelity_mins = 1;
+                let mut rest = args[2..].iter();
+                while let Some(arg) = rest.next() {
+                    match arg.as_str() {
+                        "--days" => {
+                            days = Some(rest.next().ok_or_else(usage)?.parse()?);
+                        }
+                        "--fidelity" => {
+                            fidelity_mins = rest.next().ok_or_else(usage)?.parse()?;
+                        }
+                        id => market_ids.push(id.to_string()),
+                    }
+                }
+                if market_ids.is_empty() {
+                    return Err(usage());
+                }
+                Ok(Some(Command::DownloadHistory {
+                    output,
+                    market_ids,
+                    days,
+                    fidelity_mins,
+                }))
+            }
+            "latency-sweep" => {
+                let usage =
+                    || anyhow::anyhow!("Usage: latency-sweep <tape.csv> [--latencies MS,...]");
+                let tape = args.get(1).cloned().ok_or_else(usage)?;
+                let mut latencies_ms = vec![0, 50, 100, 250, 500, 1000];
+                let mut rest = args[2..].iter();
+                while let Some(arg) = rest.next() {
+                    let value = rest.next().ok_or_else(usage)?;
+                    match arg.as_str() {
+                        "--latencies" => {
+                            latencies_ms = value
+                                .split(',')
+                                .map(|ms| ms.trim().parse())
+                                .collect::<Result<_, _>>()?;
+                        }
+                        _ => return Err(usage()),
+                    }
+                }
+                if latencies_ms.is_empty() {
+                    return Err(usage());
+                }
+                Ok(Some(Command::LatencySweep { tape, latencies_ms }))
+            }
+            "resolution-calibration" => {
+                let usage = || {
+                    anyhow::anyhow!(
+                        "Usage: resolution-calibration [--minutes-before N] [--bucket WIDTH] [--days N]"
+                    )
+                };
+                let mut minutes_before = 60;
+                let mut bucket_width = 0.05;
+                let mut days = None;
+                let mut rest = args[1..].iter();
+                while let Some(arg) = rest.next() {
+                    let value = rest.next().ok_or_else(usage)?;
+                    match arg.as_str() {
+                        "--minutes-before" => minutes_before = value.parse()?,
+                        "--bucket" => bucket_width = value.parse()?,
+                        "--days" => days = Some(value.parse()?),
+                        _ => return Err(usage()),
+                    }
+                }
+                Ok(Some(Command::ResolutionCalibration {
+                    minutes_before,
+                    bucket_width,
+                    days,
+                }))
+            }
+            "compare-venues" => Ok(Some(Command::CompareVenues)),
+            "lookup" => {
+                let market = args
+                    .get(1)
+                    .ok_or_else(|| anyhow::anyhow!("Usage: lookup <condition id|slug|URL>"))?;
+                Ok(Some(Command::Lookup {
+                    market: market.clone(),
+                }))
+            }
+            "export-training" => {
+                let usage = || {
+                    anyhow::anyhow!(
+                        "Usage: export-training <output.parquet> [--horizons SECS,...] [--days N]"
+                    )
+                };
+                let output = args.get(1).cloned().ok_or_else(usage)?;
+                let mut horizons_secs = vec![10, 60, 300];
+                let mut days = None;
+                let mut rest = args[2..].iter();
+                while let Some(arg) = rest.next() {
+                    let value = rest.next().ok_or_else(usage)?;
+                    match arg.as_str() {
+                        "--horizons" => {
+                            horizons_secs = value
+                                .split(',')
+                                .map(|h| h.trim().parse())
+                                .collect::<Result<_, _>>()?;
+                        }
+                        "--days" => days = Some(value.parse()?),
+                        _ => return Err(usage()),
+                    }
+                }
+                if horizons_secs.iter().any(|h| *h <= 0) {
+                    return Err(usage());
+                }
+                Ok(Some(Command::ExportTraining {
+                    output,
+                    horizons_secs,
+                    days,
+                }))
+            }
+            "log-level" => {
+                let usage =
+                    || anyhow::anyhow!("Usage: log-level [show|reset|<target=level> [--for SECS]]");
+                let action = match args.get(1).map(|s| s.as_str()) {
+                    None | Some("show") => LogLevelAction::Show,
+                    Some("reset") => LogLevelAction::Reset,
+                    Some(directive) => {
+                        let ttl_secs = match (args.get(2).map(|s| s.as_str()), args.get(3)) {
+                            (None, _) => None,
+                            (Some("--for"), Some(secs)) => Some(secs.parse()?),
+                            _ => return Err(usage()),
+                        };
+                        LogLevelAction::Set {
+                            directive: directive.to_string(),
+                            ttl_secs,
+                        }
+                    }
+                };
+                Ok(Some(Command::LogLevel { action }))
+            }
+            other if other.starts_with('-') => Ok(None),
+            other => anyhow::bail!("Unknown command: {}", other),
+        }
+    }
+}
+
+/// Execute a subcommand
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::ReplayDecision { id } => {
+            let config = Config::from_env()?;
+            run_replay_decision(&config, &id)
+        }
+        Command::ExperimentReport => {
+            let config = Config::from_env()?;
+            run_experiment_report(&config)
+        }
+        Command::EdgeAdjustments => {
+            let config = Config::from_env()?;
+            run_edge_adjustments(&config)
+        }
+        // No Config::from_env here: init is what creates the config
+        #[cfg(feature = "trading")]
+        Command::Init { output } => onboarding::run_wizard(&output).await,
+        Command::Funding { action } => {
+            let config = Config::from_env()?;
+            run_funding(&config, action)
+        }
+        Command::Observe => {
+            let config = Config::from_env()?;
+            observer::run(config).await
+        }
+        #[cfg(feature = "trading")]
+        Command::Executor => {
+            let config = Config::from_env()?;
+            remote::run_executor(config).await
+        }
+        // Both take the wallet key
+        #[cfg(not(feature = "trading"))]
+        Command::Init { .. } | Command::Executor => {
+            anyhow::bail!("Not available in the read-only build (rebuild with --features trading)")
+        }
+        Command::DownloadHistory {
+            output,
+            market_ids,
+            days,
+            fidelity_mins,
+        } => {
+            let config = Config::from_env()?;
+            run_download_history(&config, &output, &market_ids, days, fidelity_mins).await
+        }
+        Command::LatencySweep { tape, latencies_ms } => {
+            let config = Config::from_env()?;
+            run_latency_sweep(&config, &tape, &latencies_ms).await
+        }
+        Command::ResolutionCalibration {
+            minutes_before,
+            bucket_width,
+            days,
+        } => {
+            let config = Config::from_env()?;
+            run_resolution_calibration(&config, minutes_before, bucket_width, days).await
+        }
+        Command::CompareVenues => {
+            let config = Config::from_env()?;
+            run_compare_venues(&config).await
+        }
+        Command::Lookup { market } => {
+            let config = Config::from_env()?;
+            run_lookup(&config, &market).await
+        }
+        Command::ExportTraining {
+            output,
+            horizons_secs,
+            days,
+        } => {
+            let config = Config::from_env()?;
+            run_export_training(&config, &output, &horizons_secs, days).await
+        }
+        Command::LogLevel { action } => {
+            let config = Config::from_env()?;
+            run_log_level(&config, action).await
+        }
+    }
+}
+
+/// Goes through the running instance's API (admin token from API_ADMIN_TOKENS, if any)
+async fn run_log_level(config: &Config, action: LogLevelAction) -> Result<()> {
+    let url = format!("http://127.0.0.1:{}/api/log-levels", config.agent.api_port);
+    let http = reqwest::Client::new();
+    let request = match action {
+        LogLevelAction::Show => http.get(&url),
+        LogLevelAction::Reset => http.delete(&url),
+        LogLevelAction::Set {
+            directive,
+            ttl_secs,
+        } => http.post(&url).json(&serde_json::json!({
+            "directive": directive,
+            "ttl_secs": ttl_secs,
+        })),
+    };
+    let request = match config.agent.api_admin_tokens.first() {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    };
+    let response = request
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("No agent API on {}: {}", url, e))?;
+    if !response.status().is_success() {
+        anyhow::bail!("{} answered {}", url, response.status());
+    }
+    let view: serde_json::Value = response.json().await?;
+    println!(
+        "🔧 Startup filter: {}",
+        view["base"].as_str().unwrap_or_default()
+    );
+    for o in view["overrides"].as_array().into_iter().flatten() {
+        println!(
+            "   override {} (until {})",
+            o["directive"].as_str().unwrap_or_default(),
+            o["expires_at"].as_str().unwrap_or("reset")
+        );
+    }
+    println!(
+        "   In effect: {}",
+        view["effective"].as_str().unwrap_or_default()
+    );
+    Ok(())
+}
+
+async fn run_export_training(
+    config: &Config,
+    output: &str,
+    horizons_secs: &[i64],
+    days: Option<i64>,
+) -> Result<()> {
+    let since = days
+        .map(|d| chrono::Utc::now() - chrono::Duration::days(d))
+        .unwrap_or_default();
+    let records = load_records(&config.agent.data_dir, since)?;
+    if records.is_empty() {
+        println!(
+            "No feature records in {}/features (set FEATURE_RECORD_SECS on the trading instance)",
+            config.agent.data_dir
+        );
+        return Ok(());
+    }
+    // Outcomes come from the resolution dataset; without storage only forward returns are labelled
+    let resolutions = match storage::open(&config.storage, &config.agent.data_dir).await? {
+        Some(storage) => storage.resolutions(since).await?,
+        None => Vec::new(),
+    };
+    let horizons: Vec<chrono::Duration> = horizons_secs
+        .iter()
+        .map(|secs| chrono::Duration::seconds(*secs))
+        .collect();
+    let rows = label(records, &resolutions, &horizons);
+    write_parquet(output, &rows, &horizons)?;
+
+    let resolved = rows.iter().filter(|r| r.resolved_yes.is_some()).count();
+    println!(
+        "🧮 Wrote {} training rows to {} ({} with a resolved outcome)",
+        rows.len(),
+        output,
+        resolved
+    );
+    for (i, secs) in horizons_secs.iter().enumerate() {
+        let labelled = rows
+            .iter()
+            .filter(|r| r.forward_returns[i].is_some())
+            .count();
+        println!("   fwd_return_{}s: {} labelled", secs, labelled);
+    }
+    Ok(())
+}
+
+async fn run_lookup(config: &Config, input: &str) -> Result<()> {
+    let polymarket = PolymarketClient::new(&config.polymarket, true, None)?;
+    let market = lookup_market(&polymarket, input).await?;
+
+    println!("🔎 {}", market.question);
+    println!("   Condition: {}", market.id);
+    for (outcome, asset_id) in ["YES", "NO"].iter().zip(&market.asset_ids) {
+        println!("   {} token: {}", outcome, asset_id);
+    }
+    println!(
+        "   Prices:    YES {:.4} | NO {:.4}",
+        market.yes_price, market.no_price
+    );
+    println!("   End date:  {:?}", market.end_date);
+    Ok(())
+}
+
+async fn run_compare_venues(config: &Config) -> Result<()> {
+    let pairs = venues::load_pairs(&config.venues)?;
+    if pairs.is_empty() {
+        println!("No venue pairs configured (VENUE_PAIRS_FILE or KALSHI_PAIRS)");
+        return Ok(());
+    }
+    // Read-only: no key, so the Polymarket client can't trade either
+    let polymarket = PolymarketClient::new(&config.polymarket, true, None)?;
+    let kalshi = KalshiClient::new(&config.venues.kalshi_url)?;
+
+    println!(
+        "🔀 Polymarket vs Kalshi ({} pairs, before fees)",
+        pairs.len()
+    );
+    for pair in &pairs {
+        let (condition_id, ticker) = (&pair.polymarket_id, &pair.kalshi_ticker);
+        let quotes = tokio::try_join!(
+            polymarket.get_market_details(condition_id),
+            kalshi.get_market_details(ticker)
+        );
+        let (left, right) = match quotes {
+            Ok((left, right)) => (left, pair.align(right)),
+            Err(e) => {
+                println!("   {} / {}: {:#}", condition_id, ticker, e);
+                continue;
+            }
+        };
+        match venues::compare(Venue::Polymarket, &left, Venue::Kalshi, &right) {
+            Some(gap) => println!(
+                "   {} / {} | YES gap {:+.3} | set {:.3} (YES on {}) | edge {:+.0} bps | {}",
+                condition_id,
+                ticker,
+                gap.yes_gap,
+                gap.cross_cost,
+                gap.buy_yes_on.name(),
+                gap.edge_bps,
+                left.question
+            ),
+            None => println!(
+                "   {} / {}: not quoted on both venues",
+                condition_id, ticker
+            ),
+        }
+    }
+    Ok(())
+}
+
+async fn run_resolution_calibration(
+    config: &Config,
+    minutes_before: i64,
+    bucket_width: f64,
+    days: Option<i64>,
+) -> Result<()> {
+    let storage = storage::open(&config.storage, &config.agent.data_dir)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("The resolution dataset needs STORAGE_BACKEND"))?;
+    let since = days
+        .map(|d| chrono::Utc::now() - chrono::Duration::days(d))
+        .unwrap_or_default();
+    let resolutions = storage.resolutions(since).await?;
+    let before = chrono::Duration::minutes(minutes_before);
+    let buckets = calibration(&resolutions, before, bucket_width);
+
+    println!(
+        "🎯 Outcome calibration {} min before the end ({} resolved markets)",
+        minutes_before,
+        resolutions.len()
+    );
+    for bucket in &buckets {
+        println!(
+            "   {:.2}-{:.2} | outcomes {:>5} | won {:>5} ({:>5.1}%) | avg price {:.3} | edge {:+.3}",
+            bucket.price_from,
+            bucket.price_to,
+            bucket.outcomes,
+            bucket.wins,
+            bucket.win_rate * 100.0,
+            bucket.avg_price,
+            bucket.edge()
+        );
+    }
+    if buckets.is_empty() {
+        println!("   No market has a price sample that far before its end yet");
+    }
+
+    Ok(())
+}
+
+async fn run_download_history(
+    config: &Config,
+    output: &str,
+    market_ids: &[String],
+    days: Option<u64>,
+    fidelity_mins: u64,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let start = days.map(|d| now.saturating_sub(d * 86_400));
+    let downloader = HistoryDownloader::new(&config.polymarket.host, fidelity_mins)?;
+    let ticks = downloader.download(market_ids, start, None).await?;
+    write_tape(output, &ticks)?;
+    println!(
+        "📼 Wrote {} ticks for {} markets to {}",
+        ticks.len(),
+        market_ids.len(),
+        output
+    );
+    Ok(())
+}
+
+async fn run_latency_sweep(config: &Config, tape: &str, latencies_ms: &[u64]) -> Result<()> {
+    let ticks = read_tape(tape)?;
+    let mut market_ids: Vec<&str> = ticks.iter().map(|t| t.market_id.as_str()).collect();
+    market_ids.sort_unstable();
+    market_ids.dedup();
+
+    // Read-only: the tape only has prices, the rest of each market comes from Gamma
+    let polymarket = PolymarketClient::new(&config.polymarket, true, None)?;
+    let mut markets = Vec::new();
+    for market_id in &market_ids {
+        markets.push(polymarket.get_market_details(market_id).await?);
+    }
+    // Only the strategies that decide on quotes alone can run off a tape
+    let strategies: Vec<Arc<dyn Strategy>> = vec![
+        Arc::new(ArbitrageStrategy::new(
+            config.arbitrage.clone(),
+            config.risk.capital_usd,
+        )),
+        Arc::new(ExpirationStrategy::new(config.expiration.clone())),
+    ];
+    let order_to_fill =
+        LatencyDistribution::parse(&config.sim_latency.order_to_fill, &config.agent.data_dir)?;
+
+    let points = latency::sweep(
+        &markets,
+        &ticks,
+        &strategies,
+        latencies_ms,
+        &order_to_fill,
+        config.sim_latency.seed,
+    )
+    .await?;
+    println!(
+        "🐢 Latency sweep over {} ticks of {} markets (order -> fill {})",
+        ticks.len(),
+        markets.len(),
+        order_to_fill
+    );
+    for point in &points {
+        println!(
+            "   {:>6}ms | {:<10} | orders {:>4} | fills {:>4} | PnL ${:>+10.2}",
+            point.latency_ms, point.strategy, point.orders, point.fills, point.pnl_usd
+        );
+    }
+    Ok(())
+}
+
+fn run_funding(config: &Config, action: FundingAction) -> Result<()> {
+    let queue = FundingQueue::new(&config.agent.data_dir);
+
+    let (id, status) = match action {
+        FundingAction::List => {
+            let requests = queue.load()?;
+            println!("🪫 Funding requests ({})", requests.len());
+            for request in requests {
+                println!(
+                    "   {} | {:<9} | {:?} ${:.2} -> {} | balance ${:.2} | {}{}",
+                    request.id,
+                    format!("{:?}", request.status),
+                    request.source,
+                    request.amount_usd,
+                    request.destination,
+                    request.balance_usd,
+                    request.updated_at.to_rfc3339(),
+                    request
+                        .tx_hash
+                        .map(|tx| format!(" | tx {}", tx))
+                        .or(request.error.map(|e| format!(" | error {}", e)))
+                        .unwrap_or_default()
+                );
+            }
+            return Ok(());
+        }
+        FundingAction::Approve(id) => (id, FundingStatus::Approved),
+        FundingAction::Reject(id) => (id, FundingStatus::Rejected),
+    };
+
+    let request = queue.set_status(&id, status)?;
+    println!(
+        "✅ Funding {} is now {:?} (${:.2} via {:?})",
+        request.id, request.status, request.amount_usd, request.source
+    );
+    Ok(())
+}
+
+fn run_edge_adjustments(config: &Config) -> Result<()> {
+    let adjustments = load_adjustments(&config.agent.data_dir)?;
+    let base = config.arbitrage.min_edge_bps;
+
+    println!("📐 Learned min_edge adjustments (base {} bps)", base);
+    let mut categories: Vec<_> = adjustments.iter().collect();
+    categories.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.adjustment_bps));
+    for (category, stats) in categories {
+        println!(
+            "   {:<10} +{:>4} bps -> {:>5} bps | fills {:>4}/{:<4} ({:.0}%) | slippage {:.1} bps | updated {}",
+            format!("{:?}", category),
+            stats.adjustment_bps,
+            base + stats.adjustment_bps,
+            stats.fills,
+            stats.attempts,
+            stats.fill_rate() * 100.0,
+            stats.ewma_slippage_bps,
+            stats
+                .updated_at
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_else(|| "-".to_string())
+        );
+    }
+
+    Ok(())
+}
+
+fn run_experiment_report(config: &Config) -> Result<()> {
+    let report = load_report(&config.agent.data_dir)?;
+
+    println!("🧪 Experiment on {} strategy", report.strategy);
+    println!("   Generated: {}", report.generated_at);
+    println!("   Treatment: {}", report.overrides);
+    println!();
+    print_arm(&report.control);
+    print_arm(&report.treatment);
+    println!();
+    println!(
+        "   Mean return diff (treatment - control): {:+.4}",
+        report.mean_diff
+    );
+    println!("   Welch t = {:.3}, p = {:.4}", report.t_stat, report.p_value);
+    if report.p_value < 0.05 {
+        println!("✅ Difference is significant at 5%");
+    } else {
+        println!("⚖️ No significant difference yet");
+    }
+
+    Ok(())
+}
+
+fn print_arm(arm: &ArmSummary) {
+    println!(
+        "   {:<10} signals {:>5} (live {:>5}) | positions {:>4} | PnL ${:>8.2} | mean {:+.4} ± {:.4} | win {:.1}%",
+        format!("{:?}", arm.arm),
+        arm.signals,
+        arm.live_signals,
+        arm.positions,
+        arm.total_pnl,
+        arm.mean_return,
+        arm.std_return,
+        arm.win_rate * 100.0
+    );
+}
+
+fn run_replay_decision(config: &Config, id: &str) -> Result<()> {
+    let path = decision_log_path(&config.agent.data_dir);
+    let record = find_decision(&path, id)?;
+
+    println!("📼 Decision {}", record.id);
+    println!("   Strategy:  {}", record.strategy);
+    println!("   Time:      {}", record.timestamp);
+    println!("   Market:    {} ({})", record.market.question, record.market.id);
+    println!(
+        "   Prices:    YES {:.4} | NO {:.4} | bid {:.4} ask {:.4}",
+        record.market.yes_price,
+        record.market.no_price,
+        record.market.best_bid,
+        record.market.best_ask
+    );
+    println!("   End date:  {:?}", record.market.end_date);
+    if let Some(price) = record.binance_price {
+        println!("   Binance:   {:.4}", price);
+    }
+    if let Some(ns) = record.receive_to_decision_ns {
+        println!("   Latency:   {}µs receive → decision", ns / 1_000);
+    }
+    println!("   Config:    {}", serde_json::to_string(&record.config)?);
+    if let Some(hash) = &record.config_hash {
+        println!("   Session:   config {}", hash);
+    }
+    println!();
+    println!("   Recorded:  {:?}", record.action);
+
+    let replayed = replay_decision(&record)?;
+    println!("   Replayed:  {:?}", replayed);
+
+    let recorded_json = serde_json::to_value(&record.action)?;
+    let replayed_json = serde_json::to_value(&replayed)?;
+    if recorded_json == replayed_json {
+        println!("✅ Replay matches the recorded decision");
+    } else {
+        println!("⚠️ Replay differs from the recorded decision (code changed since recording?)");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+//! Monotonic nanosecond timestamps for latency measurement
+//!
+//! Wall-clock time is too coarse for tick-to-decision latency and can step under NTP. Hot-path
+//! timestamps are nanoseconds on the monotonic clock since a process-wide anchor: only
+//! differences between two readings mean anything, and they never go backwards.
+use std::sync::OnceLock;
+use std::time::Instant;
+
+static ANCHOR: OnceLock<Instant> = OnceLock::new();
+
+/// Monotonic nanoseconds since the process anchor. Never 0, which marks unstamped values
+#[inline]
+pub fn now_ns() -> u64 {
+    let anchor = ANCHOR.get_or_init(Instant::now);
+    (anchor.elapsed().as_nanos() as u64).max(1)
+}
+
+/// Nanoseconds since an earlier `now_ns` reading
+#[inline]
+pub fn elapsed_ns(since_ns: u64) -> u64 {
+    now_ns().saturating_sub(since_ns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monotonic_readings() {
+        let first = now_ns();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = now_ns();
+        assert!(first > 0);
+        assert!(second - first >= 2_000_000);
+        assert!(elapsed_ns(first) >= second - first);
+        assert_eq!(elapsed_ns(u64::MAX), 0);
+    }
+}
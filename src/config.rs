@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
+use std::sync::OnceLock;
+
+use crate::analytics::attribution::strategy_key;
+use crate::markets::MarketCategory;
+use crate::polymarket::PositionCollateral;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct Config {
     pub polymarket: PolymarketConfig,
     pub arbitrage: ArbitrageConfig,
@@ -14,38 +21,159 @@ pub struct Config {
     pub ctf_contract_address: Option<String>,
     pub expiration: ExpirationConfig,
     pub predictive: PredictiveConfig,
+    pub dislocation: DislocationConfig,
+    pub experiment: ExperimentConfig,
+    pub edge_tuning: EdgeTuningConfig,
+    pub execution_health: ExecutionHealthConfig,
+    pub execution_queue: ExecutionQueueConfig,
+    pub obfuscation: ObfuscationConfig,
+    pub anomaly: AnomalyConfig,
+    pub whales: WhaleConfig,
+    pub toxicity: ToxicityConfig,
+    pub liquidation: LiquidationConfig,
+    pub hedging: HedgeConfig,
+    pub arb_exit: ArbExitConfig,
+    pub collateral: CollateralConfig,
+    pub gas: GasConfig,
+    pub funding: FundingConfig,
+    pub suspension: SuspensionConfig,
+    pub synthetic: SyntheticConfig,
+    pub storage: StorageConfig,
+    pub performance: PerformanceConfig,
+    pub classifier: ClassifierConfig,
+    pub venues: VenueConfig,
+    pub cross_venue: CrossVenueConfig,
+    pub features: FeatureConfig,
+    pub chaos: ChaosConfig,
+    pub sim_latency: SimLatencyConfig,
+    pub self_monitor: SelfMonitorConfig,
+    pub daily_report: DailyReportConfig,
+    pub failover: FailoverConfig,
+    pub settlement: SettlementConfig,
+    pub position_import: PositionImportConfig,
+    pub order_wal: OrderWalConfig,
+    pub telemetry: TelemetryConfig,
+    pub capital_wait: CapitalWaitConfig,
+    pub price_feed: PriceFeedConfig,
+    pub llm_budget: LlmBudgetConfig,
+    pub market_orders: MarketOrderConfig,
+    pub neg_risk: NegRiskConfig,
+    pub order_role: OrderRoleConfig,
+    pub zombies: ZombieConfig,
+    pub chain_events: ChainEventsConfig,
+    pub zero_fee: ZeroFeeConfig,
+    pub latency_budget: LatencyBudgetConfig,
+    pub metadata_cache: MetadataCacheConfig,
+    pub series_watch: SeriesWatchConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct PolymarketConfig {
     pub api_key: String,
     pub secret: String,
     pub passphrase: String,
     pub host: String,
     pub proxy_address: Option<String>,
+    /// Re-authentication attempts after the CLOB rejects our credentials
+    pub auth_max_retries: u32,
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Collateral of conditions whose collateral isn't learned from the chain or Gamma
+    /// (None = USDC.e). Only for deployments settling in another token
+    pub collateral_token: Option<String>,
+}
+
+impl PolymarketConfig {
+    /// Collateral assumed for conditions nothing else is known about
+    pub fn default_collateral(&self) -> PositionCollateral {
+        self.collateral_token
+            .as_deref()
+            .map(PositionCollateral::from_address)
+            .unwrap_or_default()
+    }
+}
+
+/// Breakers around the Gamma, CLOB REST and RPC upstreams
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    /// Consecutive failures that open a breaker
+    pub failure_threshold: u32,
+    /// Time an open breaker waits before letting a probe through
+    pub cooldown_secs: u64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ArbitrageConfig {
     pub enabled: bool,
     pub min_edge_bps: i32,
     pub max_position_size_usd: f64,
     // Dynamic position sizing
-    pub use_dynamic_sizing: bool,
+    #[serde(default)]
+    pub sizing_mode: SizingMode,
     pub kelly_fraction: f64,
     pub min_position_pct: f64,
     pub max_position_pct: f64,
+    /// Share of portfolio value kept out of compounding sizing
+    #[serde(default)]
+    pub capital_reserve_fraction: f64,
+    /// Notional each side's book must hold within the edge before an entry (0 = off)
+    #[serde(default)]
+    pub min_depth_usd: f64,
+}
+
+/// How arbitrage entries are sized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizingMode {
+    /// Every entry is `max_position_size_usd`
+    #[default]
+    Fixed,
+    /// Kelly fraction of a constant capital
+    Kelly,
+    /// Kelly fraction of the live portfolio value (minus the reserve), so size grows and
+    /// shrinks with the account
+    Compounding,
+}
+
+impl SizingMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_lowercase().as_str() {
+            "fixed" => Some(SizingMode::Fixed),
+            "kelly" => Some(SizingMode::Kelly),
+            "compounding" => Some(SizingMode::Compounding),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ExpirationConfig {
     pub enabled: bool,
     pub max_time_remaining_sec: u64,
     pub min_price: f64,
     pub target_price: f64,
+    /// Markets this close to their end join the pre-expiry watchlist
+    pub watch_window_sec: u64,
+    /// How often watchlisted markets are re-evaluated
+    pub watch_poll_interval_ms: u64,
+    /// Buy the opposite outcome with each snipe, when it's cheap enough, to cap the loss
+    pub hedge_enabled: bool,
+    /// Opposite outcomes asked above this aren't bought
+    pub hedge_max_price: f64,
+    /// Opposite shares bought per share sniped (1 = the loss is fully covered)
+    pub hedge_ratio: f64,
+    /// Chance the sniped side wins, what the hedged snipe's expected value is taken at
+    pub win_prob: f64,
+    /// Expected value the hedged snipe must keep, bps of its total cost
+    pub hedge_min_ev_bps: f64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct PredictiveConfig {
     pub enabled: bool,
     pub min_confidence: f64,
@@ -53,17 +181,105 @@ pub struct PredictiveConfig {
     pub binance_signal_threshold_pct: f64,
     pub final_window_sec: u64,
     pub max_entry_price: f64,
+    /// Normalized order flow imbalance the bought side must show over the shortest feature
+    /// window (0 = no order flow confirmation)
+    #[serde(default)]
+    pub min_ofi: f64,
+    /// Model whose probability of the bought outcome must reach `min_confidence` (needs the
+    /// `onnx` feature)
+    #[serde(default)]
+    pub model_path: Option<String>,
+}
+
+/// Single-leg entries when one side trades below its fair value
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct DislocationConfig {
+    pub enabled: bool,
+    /// Required gap between fair value and ask, in bps of price
+    pub min_dislocation_bps: i32,
+    pub max_entry_price: f64,
+    /// Annualized volatility used to turn a Binance price into a strike probability
+    pub annual_volatility: f64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct AgentConfig {
     pub paper_trading: bool,
     pub simulation_mode: bool,
     pub market_poll_interval_secs: u64,
+    /// Fast new-market discovery poll in milliseconds (0 = disabled)
+    pub new_market_poll_interval_ms: u64,
     pub scan_existing_on_startup: bool,
+    /// Directory for persisted state (decision log, etc.)
+    pub data_dir: String,
+    /// Where the exit report is written (default `data_dir/exit_report.json`)
+    pub exit_report_path: Option<String>,
+    /// Strategy decision recording: "off", "signals" or "all"
+    pub decision_log_mode: String,
+    /// Max queued CLOB book updates between the WS reader and the agent
+    pub ws_queue_capacity: usize,
+    /// Overflow policy for the book update queue: "coalesce", "drop_oldest" or "block"
+    pub ws_queue_policy: String,
+    /// Book levels kept per side when parsing CLOB WS snapshots (0 = full book). The arbitrage
+    /// depth guard reads the full books over REST when a cut ladder is too thin
+    pub ws_book_depth: usize,
+    /// Minimum ms between book updates forwarded for assets only held to mark open positions
+    /// (0 = every update for every asset)
+    pub ws_marking_interval_ms: u64,
+    /// Marking-only assets go back to full rate this many seconds before their market ends
+    pub ws_full_rate_before_expiry_secs: u64,
+    /// How often markets pinned through the watchlist API are re-fetched and re-evaluated
+    pub pinned_poll_interval_ms: u64,
+    /// YES price move that raises an alert for pins with `notify`
+    pub pin_notify_move: f64,
+    /// Entries are held while the WS has said nothing (keep-alives included) for this long, or
+    /// has dropped / skipped updates of the books they price off (0 = off)
+    pub quote_max_feed_silence_ms: u64,
+    /// Re-read stale books over REST and go ahead if they still support the entry, instead of
+    /// refusing it
+    pub quote_reverify: bool,
+    /// HTTP API port (0 = disabled)
+    pub api_port: u16,
+    /// Address the API listens on: loopback unless a dashboard or failover peer on another
+    /// host needs it
+    pub api_bind: String,
+    /// Bearer tokens allowed to read the API (dashboards). With none here and in
+    /// `api_admin_tokens` the API is open
+    pub api_read_tokens: Vec<String>,
+    /// Bearer tokens allowed to read and to make control calls (audited)
+    pub api_admin_tokens: Vec<String>,
+    /// How long /api/summary serves the same numbers before recomputing them
+    pub api_summary_cache_secs: u64,
+    /// Evaluated arbitrage edges kept in memory for /api/opportunities (0 = disabled)
+    pub opportunity_book_capacity: usize,
+    /// CLOB WS asset subscriptions before the least recently active are evicted (0 = unlimited)
+    pub max_subscribed_assets: usize,
+    /// How often the `observe` command re-reads the live instance's data dir
+    pub observer_refresh_secs: u64,
+    /// Hours before their end that markets' prices are recorded for the resolution dataset
+    /// (0 = disabled; needs storage)
+    pub resolution_window_hours: u64,
+    /// Seconds between price samples of a market in the resolution dataset
+    pub resolution_sample_secs: u64,
+    /// Unix socket of the `executor` process: orders go there instead of being signed here
+    /// (two-process deployment, see `execution::remote`)
+    pub executor_socket: Option<String>,
+    /// Seconds between full state snapshots for `--restore` (0 = only on shutdown)
+    pub state_snapshot_interval_secs: u64,
+    /// Resume from the last state snapshot instead of cold-starting (`--restore`)
+    pub restore_snapshot: bool,
+    /// Startup policy rules (`policy::PolicyRule`) allowed to be broken, with a warning
+    pub policy_overrides: Vec<String>,
+    /// JSON file of scheduled parameter profiles (see `profiles::ParameterProfile`)
+    pub profiles_file: Option<String>,
+    /// Strategies (trade id prefixes) kept in paper while the rest of a live run trades for real
+    pub paper_strategies: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct RiskConfig {
     pub max_position_size_pct: f64,
     pub max_portfolio_exposure_pct: f64,
@@ -71,203 +287,2253 @@ pub struct RiskConfig {
     pub use_dynamic_sl: bool,
     pub min_hold_time_secs: u64,
     pub auto_sell_threshold: f64,
+    /// Bankroll (USD) the position, exposure and bucket limits are fractions of, and the PnL
+    /// tracker starts from
+    pub capital_usd: f64,
+    /// Max open notional per market category (categories not listed are only capped globally)
+    pub category_exposure_limits: BTreeMap<MarketCategory, f64>,
+    /// Share of capital (%) each strategy trades with, by trade id prefix. Unlisted strategies
+    /// share what's left. Empty = one pool for all
+    pub strategy_capital_pct: BTreeMap<String, f64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct MarketFilters {
     pub min_market_volume: f64,
     pub min_liquidity: f64,
     pub min_24h_volume: f64,
+    /// Only trade these categories (empty = all)
+    pub categories: Vec<MarketCategory>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct FlashbotsConfig {
     pub enabled: bool,
     pub relay_url: String,
     pub signing_key: Option<String>,
     pub max_retries: u32,
+    /// Paper runs: probability that a bundle passing simulation misses its block
+    pub paper_miss_rate: f64,
+}
+
+/// A/B experiment: a treatment parameterization evaluated next to the production strategy
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ExperimentConfig {
+    pub enabled: bool,
+    /// Strategy under test: "arbitrage", "expiration" or "predictive"
+    pub strategy: String,
+    /// Treatment overrides on top of the strategy config, e.g. "min_edge_bps=40,kelly_fraction=0.5"
+    pub overrides: String,
+    /// Share of markets (0-100) where the treatment arm trades and control runs shadow-only
+    pub treatment_traffic_pct: f64,
+}
+
+/// Per-category min_edge adjustment learned from fill quality
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct EdgeTuningConfig {
+    pub enabled: bool,
+    /// Cap on the extra edge required on top of min_edge_bps
+    pub max_adjust_bps: i32,
+    /// Extra edge required at a 0% fill rate (scaled linearly by the miss rate)
+    pub fill_penalty_bps: i32,
+    /// Orders observed in a category before any adjustment kicks in
+    pub min_samples: u64,
+}
+
+/// Executor health thresholds used to throttle or pause signal generation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ExecutionHealthConfig {
+    /// Rolling window of order submissions considered (seconds)
+    pub window_secs: u64,
+    /// Submissions needed in the window before health is judged
+    pub min_samples: usize,
+    /// Error rate (0-1) at which signals are throttled
+    pub degraded_error_rate: f64,
+    /// Error rate (0-1) at which signals are paused
+    pub pause_error_rate: f64,
+    /// Average submission latency at which signals are throttled
+    pub max_latency_ms: u64,
+    /// How long a pause lasts before probing again
+    pub pause_secs: u64,
+    /// Minimum spacing between evaluated signals while degraded
+    pub degraded_min_interval_ms: u64,
+}
+
+/// Limits on concurrent order submissions
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ExecutionQueueConfig {
+    /// Order submissions allowed in flight at once (0 = unlimited)
+    pub max_in_flight: usize,
+    /// Submissions allowed to wait for a slot; beyond that the least urgent is shed
+    pub max_queued: usize,
+    /// How long a submission waits for a slot before it is dropped
+    pub max_wait_ms: u64,
+}
+
+/// Randomization of entries so counterparties watching the books can't fingerprint them
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ObfuscationConfig {
+    /// Entries are shrunk by a random share of up to this percent (0 = off). Never grown, so
+    /// they stay within what the risk checks approved
+    pub size_jitter_pct: f64,
+    /// Random delay of up to this before an entry is submitted (ms, 0 = off). Exits and
+    /// hedges are never delayed
+    pub timing_jitter_ms: u64,
+    /// Sign Polymarket orders with a random salt instead of the SDK's time-based one
+    pub randomize_order_ids: bool,
+}
+
+/// Execution anomalies that switch a live run to paper trading
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+    /// Rolling window anomalies are counted over (seconds)
+    pub window_secs: u64,
+    /// An order the book moved this far past (bps) counts as a slippage anomaly
+    pub max_slippage_bps: f64,
+    /// Slippage anomalies in the window before trading goes paper
+    pub max_slippage_events: usize,
+    /// Arbitrage plans left with one leg in the window before trading goes paper
+    pub max_single_leg_failures: usize,
+    /// Realized arbitrage PnL below expectation (USD, summed over the window) before trading
+    /// goes paper
+    pub max_pnl_shortfall_usd: f64,
+    /// Stop the process (risk halt exit code) instead of going paper once tripped
+    pub halt: bool,
+}
+
+/// Large trades from the public activity feed, used to confirm or veto snipes
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct WhaleConfig {
+    pub enabled: bool,
+    /// Trades below this notional (USD) are ignored
+    pub min_notional_usd: f64,
+    /// Rolling window the per-market statistics cover (seconds)
+    pub window_secs: u64,
+    /// Confidence added to a snipe when whale flow fully agrees with it (subtracted when it
+    /// fully opposes it), scaled by the flow's bias
+    pub confidence_weight: f64,
+}
+
+/// Per-market detection of our resting orders being picked off or jumped (see
+/// `markets::ToxicityTracker`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ToxicityConfig {
+    pub enabled: bool,
+    /// How long after a post a pulled quote or a print at our price counts against the market
+    pub window_ms: u64,
+    /// Score (EWMA of missed orders that were pulled or jumped, 0-1) from which a market is toxic
+    pub threshold: f64,
+    /// Orders judged before a market's score is trusted
+    pub min_samples: u32,
+    /// Edge added to the arbitrage minimum on toxic markets, whose legs also go fill-or-kill
+    pub extra_edge_bps: i32,
+    /// Also watch the mempool (POLYGON_WS_RPC) for others' exchange settlements racing ours.
+    /// Fetches every pending transaction, which costs RPC credits
+    pub mempool: bool,
+}
+
+/// Complementary-outcome hedges against directional (predictive / expiration) exposure
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct HedgeConfig {
+    pub enabled: bool,
+    /// Unhedged directional notional (USD) above which positions get hedged
+    pub max_directional_exposure_usd: f64,
+    /// Share of a position's shares covered by its hedge
+    pub hedge_ratio: f64,
+    /// Complementary outcomes priced above this are too expensive to hedge with
+    pub max_hedge_price: f64,
+    /// market_id -> market resolving the same way, used when the market's own complement
+    /// can't be bought (both directions are listed)
+    pub correlated_markets: BTreeMap<String, String>,
+}
+
+/// Early exits of YES + NO arbitrage pairs (see `strategies::arb_exit`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ArbExitConfig {
+    pub enabled: bool,
+    /// How often open pairs are re-valued
+    pub check_interval_secs: u64,
+    /// Gas used by one `mergePositions` transaction
+    pub merge_gas_units: u64,
+    /// Extra value per pair a one-leg sale must show over merging or holding
+    pub min_leg_edge: f64,
+}
+
+/// Stop-loss exits worked through a ladder of limit orders instead of one marketable order
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct LiquidationConfig {
+    pub enabled: bool,
+    /// Time from the stop-loss trigger until the ladder crosses the spread
+    pub deadline_secs: u64,
+    /// Rungs between the passive start price and the ask
+    pub steps: usize,
+    /// First rung's discount to the exit-side ask
+    pub start_discount_bps: i32,
+    /// How often a resting rung is checked for fills
+    pub step_interval_secs: u64,
+    /// Be out this long before the market ends, whatever the deadline
+    pub end_buffer_secs: u64,
+}
+
+/// Keeps the exchange collateral (USDC.e) funded from native USDC
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct CollateralConfig {
+    /// Swap the other USDC variant in when the required one runs low
+    pub auto_swap: bool,
+    /// Trading balance below which a swap is triggered
+    pub swap_floor_usd: f64,
+    /// Largest single swap
+    pub max_swap_usd: f64,
+    pub max_slippage_bps: u32,
+    /// Uniswap V3 pool fee tier (100 = 0.01%)
+    pub pool_fee: u32,
+}
+
+/// Polygon gas cost weighed against the value of on-chain actions (see `execution::gas`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct GasConfig {
+    /// Skip redemptions that don't pay for their gas and batch small ones (false = redeem
+    /// every resolved position at once, losers included)
+    pub aware_redemption: bool,
+    /// Gas used by one `redeemPositions` transaction
+    pub redeem_gas_units: u64,
+    /// Used when the RPC can't be asked for the current gas price
+    pub fallback_gas_price_gwei: f64,
+    /// USD price of POL, the gas token
+    pub native_token_usd: f64,
+    /// A redemption must pay at least this many times its gas cost
+    pub min_value_multiple: f64,
+    /// Redemptions worth less than this wait until the waiting ones add up to it
+    pub batch_min_value_usd: f64,
+    /// Longest a resolved position waits: then it's redeemed if it pays more than its gas, else
+    /// written off
+    pub redeem_max_wait_secs: u64,
+    /// Redemption transactions sent back to back on consecutive nonces before awaiting receipts
+    pub redeem_max_in_flight: usize,
+    /// How long to wait for a redemption receipt before retrying it next round
+    pub redeem_receipt_timeout_secs: u64,
+}
+
+/// Tops the Polygon trading wallet up from mainnet or a CEX when it runs low
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct FundingConfig {
+    pub enabled: bool,
+    /// "mainnet" (PoS bridge deposit) or "cex" (operator withdrawal)
+    pub source: String,
+    /// Execute mainnet deposits without waiting for `funding approve`
+    pub auto_confirm: bool,
+    /// Trading balance that triggers a request
+    pub floor_usd: f64,
+    /// Balance a request tops up to
+    pub target_usd: f64,
+    pub check_interval_secs: u64,
+    /// Ethereum mainnet HTTP RPC (mainnet source only)
+    pub mainnet_rpc: Option<String>,
+}
+
+/// Keeps strategies off markets whose books are suspended or one-sided (in-play sports)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct SuspensionConfig {
+    pub enabled: bool,
+    /// Only guard markets classified as sports
+    pub sports_only: bool,
+    /// Quotes are ignored for this long after a suspension lifts (repricing)
+    pub resume_grace_ms: u64,
+}
+
+/// What strategies may do on synthetic markets: conditions seen on-chain whose token ids were
+/// derived locally, traded before Gamma has their metadata (so no filter can apply)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct SyntheticConfig {
+    /// Strategies evaluated on them (decision-log names; empty = none)
+    pub strategies: Vec<String>,
+    /// Cap on each entry's size
+    pub max_size_usd: f64,
+    /// Their first book has to arrive within this long of the on-chain event, or they aren't
+    /// traded until metadata loads
+    pub first_book_ms: u64,
+    /// Abandoned (unsubscribed, metadata retries stopped) if Gamma has nothing after this long
+    pub metadata_timeout_secs: u64,
+}
+
+/// Database behind trades, positions, snapshots, the market registry and seen markets
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct StorageConfig {
+    /// sqlite, postgres (needs the `postgres` feature) or none
+    pub backend: String,
+    /// Connection URL; SQLite defaults to `data_dir/agent.db`
+    pub url: Option<String>,
+    /// Writes queued for the database before portfolio snapshots are dropped
+    pub queue_capacity: usize,
+}
+
+/// How PnL risk metrics are computed (GET /api/pnl/stats)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct PerformanceConfig {
+    /// The equity curve is resampled to this period; returns are measured between the points
+    pub resample_secs: u64,
+    /// Annual risk-free rate subtracted in Sharpe / Sortino (0.04 = 4%)
+    pub risk_free_rate: f64,
+    /// Portfolio snapshots younger than this are kept as taken
+    pub snapshot_full_hours: u64,
+    /// Then as 1-minute bars for this many days; hourly bars beyond
+    pub snapshot_minute_days: u64,
+}
+
+/// Market category classification: keyword rules, plus an optional LLM for what they miss
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ClassifierConfig {
+    /// OpenAI-compatible chat completions URL (unset = rules only)
+    pub llm_url: Option<String>,
+    pub llm_api_key: Option<String>,
+    pub llm_model: String,
+}
+
+/// Venues other than Polymarket, quoted for cross-venue price comparison
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct VenueConfig {
+    /// Kalshi public trade API base URL
+    pub kalshi_url: String,
+    /// Polymarket condition id -> Kalshi ticker of the same question
+    pub kalshi_pairs: BTreeMap<String, String>,
+    /// JSON mapping file of equivalent contracts (see `venues::VenuePair`), merged with the pairs
+    pub pairs_file: Option<String>,
+    /// Kalshi API key; without it (and the private key) Kalshi is read-only
+    pub kalshi_key_id: Option<String>,
+    /// PEM RSA key the Kalshi API key signs requests with
+    pub kalshi_private_key_path: Option<String>,
+}
+
+/// Complete sets bought across venues: YES on one, NO on the other
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct CrossVenueConfig {
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+    /// Required edge after both venues' fees and withdrawal frictions, in bps of the set cost
+    pub min_net_edge_bps: i32,
+    /// Set cost targeted per entry
+    pub size_usd: f64,
+    /// Cost of moving the capital back between venues, in bps of the set cost
+    pub withdrawal_bps: f64,
+    /// Fixed cost per entry of moving capital (bank / bridge fees)
+    pub withdrawal_usd: f64,
+    /// Max open cross-venue notional on Polymarket
+    pub max_polymarket_usd: f64,
+    /// Max open cross-venue notional on Kalshi
+    pub max_kalshi_usd: f64,
+}
+
+/// Order flow features computed per asset from the L2 stream (see `markets::FeatureTracker`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct FeatureConfig {
+    pub enabled: bool,
+    /// Rolling windows the features are summed over
+    pub windows_ms: Vec<u64>,
+    /// Feature vectors of predictive candidates are sampled to `data_dir/features/` at most
+    /// this often per market, for `export-training` (0 = off)
+    pub record_secs: u64,
+}
+
+/// Random network faults injected in simulation / paper runs (`--features chaos` builds only)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ChaosConfig {
+    pub enabled: bool,
+    /// RNG seed, to replay a run's faults (0 = random)
+    pub seed: u64,
+    /// Chance that a CLOB WS message drops the connection instead
+    pub ws_disconnect_prob: f64,
+    /// Chances that an HTTP call times out / is rejected with a 429
+    pub http_timeout_prob: f64,
+    pub http_429_prob: f64,
+    /// Chance that a balance RPC call errors
+    pub rpc_error_prob: f64,
+    /// How long an injected timeout hangs before failing
+    pub timeout_ms: u64,
+}
+
+/// Network latency of orders in simulation mode (see `simulation::LatencyModel`). Specs are
+/// `50` / `fixed:50`, `uniform:20-80`, `normal:50,15` (ms), or `measured`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct SimLatencyConfig {
+    /// Decision -> order delay (signing, submission); `measured` resamples the signal -> post
+    /// latencies the live agent logged to `execution_drift.jsonl`
+    pub decision_to_order: String,
+    /// Order -> fill delay (exchange matching)
+    pub order_to_fill: String,
+    /// RNG seed, to replay a run's delays (0 = random)
+    pub seed: u64,
+}
+
+/// Periodic self report of memory, tasks, maps and channels (see `monitor::SelfMonitor`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct SelfMonitorConfig {
+    /// Minutes between reports (0 = off)
+    pub interval_mins: u64,
+    /// Alert thresholds (0 = no limit)
+    pub max_rss_mb: f64,
+    pub max_tasks: usize,
+    /// Applies to registry markets and markets seen by the lifecycle tracker
+    pub max_markets: usize,
+    pub max_assets: usize,
+    /// Applies to each of the WS update queue, metadata retry and new-market channels
+    pub max_channel_depth: usize,
+}
+
+/// Daily PnL / health summary sent by webhook and / or email (see `report::DailyReporter`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct DailyReportConfig {
+    /// UTC hour the report goes out
+    pub hour_utc: u32,
+    /// Slack / Discord / generic JSON webhook
+    pub webhook_url: Option<String>,
+    /// SMTP server (STARTTLS); email is off without it
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    /// Sender address (defaults to the SMTP username)
+    pub email_from: Option<String>,
+    pub email_to: Vec<String>,
+}
+
+/// Hot-standby failover between two instances (see `failover::Failover`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct FailoverConfig {
+    /// "primary", "standby" or "off"
+    pub role: String,
+    /// Base URL of the peer instance's API, e.g. http://10.0.2.4:3002
+    pub peer_url: Option<String>,
+    /// Admin token of the peer's API (heartbeats and fencing)
+    pub peer_token: Option<String>,
+    pub heartbeat_ms: u64,
+    /// Timeout of one heartbeat or fence request to the peer, kept below `heartbeat_ms` so a
+    /// slow peer doesn't swallow the next round
+    pub peer_timeout_ms: u64,
+    /// Directory both instances can read (shared volume): the primary mirrors its state
+    /// snapshot there and a promoted standby resumes from it
+    pub snapshot_dir: Option<String>,
+    /// A primary stops opening positions after this long without reaching its standby
+    /// (0 = never, at the risk of double trading during a partition)
+    pub lease_secs: u64,
+    /// A standby takes over after the primary has been silent this long. Keep it well above
+    /// `lease_secs`
+    pub promote_after_secs: u64,
+}
+
+/// On-chain confirmation of CLOB fills (see `execution::SettlementReconciler`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct SettlementConfig {
+    /// Watch the funder wallet's ERC-1155 transfers (needs POLYGON_WS_RPC and the CTF address)
+    pub enabled: bool,
+    /// A fill whose tokens haven't fully moved this long after it is a settlement mismatch
+    pub timeout_secs: u64,
+    /// Share of the expected amount that may be missing before it counts as short (rounding)
+    pub tolerance_pct: f64,
+}
+
+/// Positions found in the wallet at startup that the bot doesn't know of (see
+/// `analytics::cost_basis`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct PositionImportConfig {
+    pub enabled: bool,
+    /// Balances below this many shares are dust and stay out
+    pub min_shares: f64,
+    /// Price history averaged for tokens the wallet holds without a fill
+    pub history_days: u64,
+}
+
+/// Write-ahead log of live orders, reconciled against the exchange at startup (see
+/// `execution::wal`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct OrderWalConfig {
+    pub enabled: bool,
+}
+
+/// Opt-in push of anonymized, aggregate performance metrics to a collector of the user's own
+/// (see `telemetry`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct TelemetryConfig {
+    /// Collector the metrics are POSTed to; telemetry is off without one
+    pub endpoint: Option<String>,
+    /// Sent as a bearer token when set
+    pub token: Option<String>,
+    pub interval_secs: u64,
+}
+
+/// High-edge entries held while free collateral is short but redemptions are about to free some
+/// (see `execution::capital_wait`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct CapitalWaitConfig {
+    pub enabled: bool,
+    /// Only signals with at least this edge wait
+    pub min_edge_bps: f64,
+    /// Releases expected within this many seconds count
+    pub horizon_secs: u64,
+    /// A waiting signal is dropped after this many seconds
+    pub max_wait_secs: u64,
+    /// Signals waiting at once (the lowest edge makes room)
+    pub max_queued: usize,
+}
+
+/// Sanity checks on the external (Binance) price feed (see `pricefeed::FeedFilter`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct PriceFeedConfig {
+    /// A print further than this from the rolling median is rejected (0 = off)
+    pub max_deviation_bps: f64,
+    /// Rolling window of the median, and of the faults counted towards a hold
+    pub window_secs: u64,
+    /// Prints the window needs before the median is trusted
+    pub min_samples: usize,
+    /// Second source each print is checked against: "coinbase" (None = Binance alone)
+    pub confirm_source: Option<String>,
+    /// Largest gap allowed between the two sources
+    pub max_cross_deviation_bps: f64,
+    /// Faults within the window that put strategies on the feed on hold (0 = never)
+    pub hold_after_faults: usize,
+    /// How long a hold lasts after the last fault
+    pub hold_secs: u64,
+}
+
+/// Spending limits of LLM calls (see `llm_budget`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct LlmBudgetConfig {
+    /// Calls allowed per hour (token bucket; 0 = none)
+    pub requests_per_hour: u32,
+    /// Calls are refused once the day's (UTC) spend reaches this
+    pub max_usd_per_day: f64,
+    /// How long an answer is reused for the same prompt
+    pub cache_ttl_secs: u64,
+    /// Prices used when the provider doesn't report a call's cost
+    pub usd_per_1k_prompt_tokens: f64,
+    pub usd_per_1k_completion_tokens: f64,
+}
+
+/// Market orders emulated with marketable limit orders (see `execution::marketable`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct MarketOrderConfig {
+    /// Snipes take the book at submission instead of posting at the signal's price
+    pub snipes: bool,
+    /// Added to the price the book needs to fill the order
+    pub slippage_bps: f64,
+    /// Snipes never pay more than the signal's price plus this
+    pub max_slippage_bps: f64,
+}
+
+/// Arbitrage on neg-risk questions checked against the adapter's paths (see `strategies::neg_risk`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct NegRiskConfig {
+    /// Cost the pair (and the conversion) from the live asks before executing
+    pub enabled: bool,
+    /// Convert a NO basket instead of buying the pair when it earns more
+    pub convert: bool,
+    /// Fee the adapter keeps on conversions (the market's fee bips)
+    pub adapter_fee_bps: u32,
+    /// Gas of a `convertPositions` transaction, plus per question converted
+    pub convert_gas_units: u64,
+    pub convert_gas_units_per_question: u64,
+    /// Edge a path must keep after CLOB fees and gas
+    pub min_edge_bps: f64,
+}
+
+/// Per-leg choice between posting inside the spread and crossing it (see `execution::role`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct OrderRoleConfig {
+    /// Off: every leg crosses the spread
+    pub enabled: bool,
+    /// Fees of a fill that rested (maker) and of one that crossed (taker)
+    pub maker_fee_bps: f64,
+    pub taker_fee_bps: f64,
+    /// Edge (before fees) at which a leg takes rather than waits
+    pub take_edge_bps: f64,
+    /// Markets ending sooner than this are taken
+    pub urgent_secs: u64,
+    /// Touch changes over the shortest feature window above which the book is moving
+    pub max_book_changes: usize,
+    /// Price grid a maker leg improves the bid by
+    pub tick: f64,
+    /// A maker leg still unfilled this long after it posted is cancelled and its pair's
+    /// taker leg unwound
+    pub maker_cancel_secs: u64,
+}
+
+/// Resolved-but-open markets moved off the strategies (see `markets::zombies`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ZombieConfig {
+    pub enabled: bool,
+    /// An ask at or above this (or at or below 1 - this) is an extreme quote
+    pub extreme_price: f64,
+    /// How long the quotes must stay extreme
+    pub stable_secs: u64,
+    /// How long past its end date a market must be, without a proposed resolution
+    pub end_grace_secs: u64,
+    /// How often the registry is scanned
+    pub check_interval_secs: u64,
+}
+
+/// Reorg and duplicate handling of the CTF market-creation events
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ChainEventsConfig {
+    /// Blocks on top of an event's block before it is acted on (0 = act on delivery)
+    pub confirmations: u64,
+    /// How long a condition already acted on is remembered, so a repeat delivery is dropped
+    pub dedup_window_secs: u64,
+}
+
+/// Fee-free (promotional) markets, detected from the CLOB fee rates
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ZeroFeeConfig {
+    /// Check the markets' fee rates; fee-free ones are costed at 0 and get their own minimum
+    /// edge and size
+    pub enabled: bool,
+    /// Net arbitrage edge required on a fee-free market, in place of MIN_EDGE_BPS
+    pub min_edge_bps: i32,
+    /// Arbitrage size on a fee-free market, as a multiple of the usual one
+    pub size_multiplier: f64,
+    /// How often a market's fee rate is checked again
+    pub refresh_secs: u64,
+}
+
+/// Time allowed between a signal and its order posting (0 = no budget)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct LatencyBudgetConfig {
+    pub enabled: bool,
+    /// Expiration snipes
+    pub snipe_ms: u64,
+    /// YES + NO arbitrage entries
+    pub arbitrage_ms: u64,
+    /// Every other signal (predictive, dislocation, custom strategies)
+    pub default_ms: u64,
+}
+
+/// Gamma market metadata kept on disk (see `polymarket::metadata_cache`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct MetadataCacheConfig {
+    pub enabled: bool,
+    /// Metadata fetched within this long is used instead of asking Gamma again
+    pub ttl_secs: u64,
+    /// Entries not refreshed within this long are dropped
+    pub max_age_secs: u64,
+}
+
+/// Watch for new event series on Gamma (see `markets::series`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct SeriesWatchConfig {
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+    /// Lowercase keywords; a new series whose title or slug contains one gets its open markets
+    /// pinned. Empty: alerts only
+    pub auto_pin_keywords: Vec<String>,
+    /// Most markets pinned per new series
+    pub auto_pin_max_markets: usize,
+    /// Auto pins log their price moves as alerts
+    pub auto_pin_notify: bool,
 }
 
 impl Config {
     pub fn from_env() -> anyhow::Result<Self> {
         dotenvy::dotenv().ok();
+        Self::from_vars(|name| env::var(name))
+    }
 
+    /// Build from `var`, looked up like `std::env::var`
+    fn from_vars(var: impl Fn(&str) -> Result<String, env::VarError>) -> anyhow::Result<Self> {
         let polymarket = PolymarketConfig {
             // SDK auto-generates credentials from private key - these are optional
-            api_key: env::var("POLYMARKET_API_KEY").unwrap_or_default(),
-            secret: env::var("POLYMARKET_SECRET").unwrap_or_default(),
-            passphrase: env::var("POLYMARKET_PASSPHRASE").unwrap_or_default(),
-            host: env::var("POLYMARKET_HOST")
+            api_key: var("POLYMARKET_API_KEY").unwrap_or_default(),
+            secret: var("POLYMARKET_SECRET").unwrap_or_default(),
+            passphrase: var("POLYMARKET_PASSPHRASE").unwrap_or_default(),
+            host: var("POLYMARKET_HOST")
                 .unwrap_or_else(|_| "https://clob.polymarket.com".to_string()),
-            proxy_address: env::var("POLYMARKET_PROXY_ADDRESS").ok(),
+            proxy_address: var("POLYMARKET_PROXY_ADDRESS").ok(),
+            auth_max_retries: var("POLYMARKET_AUTH_MAX_RETRIES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            circuit_breaker: CircuitBreakerConfig {
+                enabled: var("CIRCUIT_BREAKER_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap_or(true),
+                failure_threshold: var("CIRCUIT_BREAKER_FAILURES")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
+                cooldown_secs: var("CIRCUIT_BREAKER_COOLDOWN_SECS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
+            },
+            collateral_token: var("COLLATERAL_TOKEN")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
         };
 
         let arbitrage = ArbitrageConfig {
-            enabled: env::var("ARBITRAGE_ENABLED")
+            enabled: var("ARBITRAGE_ENABLED")
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
                 .unwrap_or(false),
-            min_edge_bps: env::var("MIN_EDGE_BPS")
+            min_edge_bps: var("MIN_EDGE_BPS")
                 .unwrap_or_else(|_| "20".to_string())
                 .parse()
                 .unwrap_or(20),
-            max_position_size_usd: env::var("MAX_POSITION_SIZE_USD")
+            max_position_size_usd: var("MAX_POSITION_SIZE_USD")
                 .unwrap_or_else(|_| "10.0".to_string())
                 .parse()
                 .unwrap_or(10.0),
-            use_dynamic_sizing: env::var("USE_DYNAMIC_SIZING")
-                .unwrap_or_else(|_| "true".to_string())
-                .parse()
-                .unwrap_or(true),
-            kelly_fraction: env::var("KELLY_FRACTION")
+            // USE_DYNAMIC_SIZING picks kelly or fixed when SIZING_MODE isn't set
+            sizing_mode: var("SIZING_MODE")
+                .ok()
+                .and_then(|mode| SizingMode::parse(&mode))
+                .unwrap_or_else(|| {
+                    let dynamic = var("USE_DYNAMIC_SIZING")
+                        .unwrap_or_else(|_| "true".to_string())
+                        .parse()
+                        .unwrap_or(true);
+                    if dynamic {
+                        SizingMode::Kelly
+                    } else {
+                        SizingMode::Fixed
+                    }
+                }),
+            kelly_fraction: var("KELLY_FRACTION")
                 .unwrap_or_else(|_| "0.25".to_string())
                 .parse()
                 .unwrap_or(0.25),
-            min_position_pct: env::var("MIN_POSITION_PCT")
+            min_position_pct: var("MIN_POSITION_PCT")
                 .unwrap_or_else(|_| "0.01".to_string())
                 .parse()
                 .unwrap_or(0.01),
-            max_position_pct: env::var("MAX_POSITION_PCT")
+            max_position_pct: var("MAX_POSITION_PCT")
                 .unwrap_or_else(|_| "0.10".to_string())
                 .parse()
                 .unwrap_or(0.10),
+            capital_reserve_fraction: var("CAPITAL_RESERVE_FRACTION")
+                .unwrap_or_else(|_| "0.1".to_string())
+                .parse()
+                .unwrap_or(0.1),
+            min_depth_usd: var("ARBITRAGE_MIN_DEPTH_USD")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50.0),
         };
 
         let expiration = ExpirationConfig {
-            enabled: env::var("EXPIRATION_SNIPING_ENABLED")
+            enabled: var("EXPIRATION_SNIPING_ENABLED")
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
                 .unwrap_or(false),
-            max_time_remaining_sec: env::var("EXPIRATION_MAX_TIME_SEC")
+            max_time_remaining_sec: var("EXPIRATION_MAX_TIME_SEC")
                 .unwrap_or_else(|_| "60".to_string())
                 .parse()
                 .unwrap_or(60),
-            min_price: env::var("EXPIRATION_MIN_PRICE")
+            min_price: var("EXPIRATION_MIN_PRICE")
                 .unwrap_or_else(|_| "0.92".to_string())
                 .parse()
                 .unwrap_or(0.92),
-            target_price: env::var("EXPIRATION_TARGET_PRICE")
+            target_price: var("EXPIRATION_TARGET_PRICE")
                 .unwrap_or_else(|_| "0.99".to_string())
                 .parse()
                 .unwrap_or(0.99),
+            watch_window_sec: var("EXPIRATION_WATCH_WINDOW_SEC")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+            watch_poll_interval_ms: var("EXPIRATION_WATCH_POLL_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+            hedge_enabled: var("EXPIRATION_HEDGE_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            hedge_max_price: var("EXPIRATION_HEDGE_MAX_PRICE")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02),
+            hedge_ratio: var("EXPIRATION_HEDGE_RATIO")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0),
+            win_prob: var("EXPIRATION_WIN_PROB")
+                .unwrap_or_else(|_| "0.98".to_string())
+                .parse()
+                .unwrap_or(0.98),
+            hedge_min_ev_bps: var("EXPIRATION_HEDGE_MIN_EV_BPS")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100.0),
+        };
+
+        let dislocation = DislocationConfig {
+            enabled: var("DISLOCATION_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            min_dislocation_bps: var("DISLOCATION_MIN_BPS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+            max_entry_price: var("DISLOCATION_MAX_ENTRY_PRICE")
+                .unwrap_or_else(|_| "0.90".to_string())
+                .parse()
+                .unwrap_or(0.90),
+            annual_volatility: var("DISLOCATION_ANNUAL_VOL")
+                .unwrap_or_else(|_| "0.60".to_string())
+                .parse()
+                .unwrap_or(0.60),
         };
 
         let agent = AgentConfig {
-            paper_trading: env::var("PAPER_TRADING")
+            paper_trading: var("PAPER_TRADING")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
                 .unwrap_or(true),
-            simulation_mode: env::var("SIMULATION_MODE")
+            simulation_mode: var("SIMULATION_MODE")
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
                 .unwrap_or(false),
-            market_poll_interval_secs: env::var("MARKET_POLL_INTERVAL_SECS")
+            market_poll_interval_secs: var("MARKET_POLL_INTERVAL_SECS")
                 .unwrap_or_else(|_| "5".to_string())
                 .parse()
                 .unwrap_or(5),
-            scan_existing_on_startup: env::var("SCAN_EXISTING_ON_STARTUP")
+            new_market_poll_interval_ms: var("NEW_MARKET_POLL_INTERVAL_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            scan_existing_on_startup: var("SCAN_EXISTING_ON_STARTUP")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            data_dir: var("DATA_DIR").unwrap_or_else(|_| "data".to_string()),
+            exit_report_path: var("EXIT_REPORT_PATH").ok().filter(|path| !path.is_empty()),
+            decision_log_mode: var("DECISION_LOG_MODE").unwrap_or_else(|_| "signals".to_string()),
+            ws_queue_capacity: var("WS_QUEUE_CAPACITY")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            ws_queue_policy: var("WS_QUEUE_POLICY").unwrap_or_else(|_| "coalesce".to_string()),
+            ws_book_depth: var("WS_BOOK_DEPTH")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            ws_marking_interval_ms: var("WS_MARKING_INTERVAL_MS")
+                .unwrap_or_else(|_| "250".to_string())
+                .parse()
+                .unwrap_or(250),
+            ws_full_rate_before_expiry_secs: var("WS_FULL_RATE_BEFORE_EXPIRY_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            pinned_poll_interval_ms: var("PINNED_POLL_INTERVAL_MS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+            pin_notify_move: var("PIN_NOTIFY_MOVE")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05),
+            quote_max_feed_silence_ms: var("QUOTE_MAX_FEED_SILENCE_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            quote_reverify: var("QUOTE_REVERIFY")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
                 .unwrap_or(true),
+            api_port: var("API_PORT")
+                .unwrap_or_else(|_| "3002".to_string())
+                .parse()
+                .unwrap_or(3002),
+            api_bind: var("API_BIND").unwrap_or_else(|_| "127.0.0.1".to_string()),
+            api_read_tokens: var("API_READ_TOKENS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty())
+                .collect(),
+            api_admin_tokens: var("API_ADMIN_TOKENS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty())
+                .collect(),
+            api_summary_cache_secs: var("API_SUMMARY_CACHE_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            opportunity_book_capacity: var("OPPORTUNITY_BOOK_CAPACITY")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            max_subscribed_assets: var("MAX_SUBSCRIBED_ASSETS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+            observer_refresh_secs: var("OBSERVER_REFRESH_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            resolution_window_hours: var("RESOLUTION_WINDOW_HOURS")
+                .unwrap_or_else(|_| "6".to_string())
+                .parse()
+                .unwrap_or(6),
+            resolution_sample_secs: var("RESOLUTION_SAMPLE_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            executor_socket: var("EXECUTOR_SOCKET").ok().filter(|s| !s.is_empty()),
+            state_snapshot_interval_secs: var("STATE_SNAPSHOT_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            restore_snapshot: var("RESTORE_SNAPSHOT")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            policy_overrides: var("POLICY_OVERRIDES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|rule| rule.trim().to_string())
+                .filter(|rule| !rule.is_empty())
+                .collect(),
+            profiles_file: var("PARAMETER_PROFILES_FILE")
+                .ok()
+                .filter(|path| !path.is_empty()),
+            // e.g. "predictive,dislocation"
+            paper_strategies: var("PAPER_STRATEGIES")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|strategy| !strategy.trim().is_empty())
+                .map(strategy_key)
+                .collect(),
         };
 
         let risk = RiskConfig {
-            max_position_size_pct: env::var("MAX_POSITION_SIZE_PCT")
+            max_position_size_pct: var("MAX_POSITION_SIZE_PCT")
                 .unwrap_or_else(|_| "5.0".to_string())
                 .parse()
                 .unwrap_or(5.0),
-            max_portfolio_exposure_pct: env::var("MAX_PORTFOLIO_EXPOSURE_PCT")
+            max_portfolio_exposure_pct: var("MAX_PORTFOLIO_EXPOSURE_PCT")
                 .unwrap_or_else(|_| "50.0".to_string())
                 .parse()
                 .unwrap_or(50.0),
-            stop_loss_pct: env::var("STOP_LOSS_PCT")
+            stop_loss_pct: var("STOP_LOSS_PCT")
                 .unwrap_or_else(|_| "10.0".to_string())
                 .parse()
                 .unwrap_or(10.0),
-            use_dynamic_sl: env::var("USE_DYNAMIC_SL")
+            use_dynamic_sl: var("USE_DYNAMIC_SL")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
                 .unwrap_or(true),
-            min_hold_time_secs: env::var("MIN_HOLD_TIME_SECS")
+            min_hold_time_secs: var("MIN_HOLD_TIME_SECS")
                 .unwrap_or_else(|_| "60".to_string())
                 .parse()
                 .unwrap_or(60),
-            auto_sell_threshold: env::var("AUTO_SELL_THRESHOLD")
+            auto_sell_threshold: var("AUTO_SELL_THRESHOLD")
                 .unwrap_or_else(|_| "0.99".to_string())
                 .parse()
                 .unwrap_or(0.99),
+            capital_usd: var("CAPITAL_USD")
+                .unwrap_or_else(|_| "1000.0".to_string())
+                .parse()
+                .unwrap_or(1000.0),
+            // e.g. "sports:200,politics:500"
+            category_exposure_limits: var("CATEGORY_MAX_EXPOSURE_USD")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pair| {
+                    let (category, limit) = pair.split_once(':')?;
+                    Some((MarketCategory::parse(category)?, limit.trim().parse().ok()?))
+                })
+                .collect(),
+            // e.g. "arbitrage:60,expiration:30,predictive:10"
+            strategy_capital_pct: var("STRATEGY_CAPITAL_PCT")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pair| {
+                    let (strategy, pct) = pair.split_once(':')?;
+                    Some((strategy_key(strategy), pct.trim().parse().ok()?))
+                })
+                .collect(),
         };
 
         let market_filters = MarketFilters {
-            min_market_volume: env::var("MIN_MARKET_VOLUME")
+            min_market_volume: var("MIN_MARKET_VOLUME")
                 .unwrap_or_else(|_| "1000.0".to_string())
                 .parse()
                 .unwrap_or(1000.0),
-            min_liquidity: env::var("MIN_LIQUIDITY")
+            min_liquidity: var("MIN_LIQUIDITY")
                 .unwrap_or_else(|_| "500.0".to_string())
                 .parse()
                 .unwrap_or(500.0),
-            min_24h_volume: env::var("MIN_24H_VOLUME")
+            min_24h_volume: var("MIN_24H_VOLUME")
                 .unwrap_or_else(|_| "0.0".to_string())
                 .parse()
                 .unwrap_or(0.0),
+            categories: var("MARKET_CATEGORIES")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(MarketCategory::parse)
+                .collect(),
         };
 
-        let polygon_ws_rpc = env::var("POLYGON_WS_RPC").ok();
-        let polygon_private_key = env::var("POLYGON_PRIVATE_KEY").ok();
-        let ctf_contract_address = env::var("CTF_CONTRACT_ADDRESS").ok();
+        let polygon_ws_rpc = var("POLYGON_WS_RPC").ok();
+        let polygon_private_key = var("POLYGON_PRIVATE_KEY").ok();
+        // The read-only build never holds the key, even when the env file it was given has it
+        #[cfg(not(feature = "trading"))]
+        let polygon_private_key = polygon_private_key.and_then(|_| {
+            tracing::warn!("⚠️ POLYGON_PRIVATE_KEY ignored: read-only build, nothing signs");
+            None::<String>
+        });
+        let ctf_contract_address = var("CTF_CONTRACT_ADDRESS").ok();
 
         let flashbots = FlashbotsConfig {
-            enabled: env::var("USE_FLASHBOTS")
+            enabled: var("USE_FLASHBOTS")
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
                 .unwrap_or(false),
-            relay_url: env::var("FLASHBOTS_RELAY_URL")
+            relay_url: var("FLASHBOTS_RELAY_URL")
                 .unwrap_or_else(|_| "https://relay.flashbots.net".to_string()),
-            signing_key: env::var("FLASHBOTS_SIGNING_KEY").ok(),
-            max_retries: env::var("MAX_BUNDLE_RETRIES")
+            signing_key: var("FLASHBOTS_SIGNING_KEY")
+                .ok()
+                .filter(|_| cfg!(feature = "trading")),
+            max_retries: var("MAX_BUNDLE_RETRIES")
                 .unwrap_or_else(|_| "3".to_string())
                 .parse()
                 .unwrap_or(3),
+            paper_miss_rate: var("PAPER_BUNDLE_MISS_RATE")
+                .unwrap_or_else(|_| "0.0".to_string())
+                .parse()
+                .unwrap_or(0.0),
         };
 
-        Ok(Config {
-            polymarket,
-            arbitrage,
-            agent,
-            risk,
-            market_filters,
-            flashbots,
-            polygon_ws_rpc,
-            polygon_private_key,
-            ctf_contract_address,
-            expiration,
-            predictive: PredictiveConfig {
-                enabled: env::var("PREDICTIVE_SNIPING_ENABLED")
-                    .unwrap_or_else(|_| "true".to_string())
-                    .parse()
-                    .unwrap_or(true),
-                min_confidence: env::var("PREDICTIVE_MIN_CONFIDENCE")
+        let experiment = ExperimentConfig {
+            enabled: var("EXPERIMENT_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            strategy: var("EXPERIMENT_STRATEGY").unwrap_or_else(|_| "arbitrage".to_string()),
+            overrides: var("EXPERIMENT_OVERRIDES").unwrap_or_default(),
+            treatment_traffic_pct: var("EXPERIMENT_TREATMENT_TRAFFIC_PCT")
+                .unwrap_or_else(|_| "0.0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+        };
+
+        let edge_tuning = EdgeTuningConfig {
+            enabled: var("DYNAMIC_MIN_EDGE_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            max_adjust_bps: var("DYNAMIC_MIN_EDGE_MAX_ADJUST_BPS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200),
+            fill_penalty_bps: var("DYNAMIC_MIN_EDGE_FILL_PENALTY_BPS")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            min_samples: var("DYNAMIC_MIN_EDGE_MIN_SAMPLES")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+        };
+
+        let execution_health = ExecutionHealthConfig {
+            window_secs: var("EXEC_HEALTH_WINDOW_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            min_samples: var("EXEC_HEALTH_MIN_SAMPLES")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            degraded_error_rate: var("EXEC_HEALTH_DEGRADED_ERROR_RATE")
+                .unwrap_or_else(|_| "0.2".to_string())
+                .parse()
+                .unwrap_or(0.2),
+            pause_error_rate: var("EXEC_HEALTH_PAUSE_ERROR_RATE")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .unwrap_or(0.5),
+            max_latency_ms: var("EXEC_HEALTH_MAX_LATENCY_MS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+            pause_secs: var("EXEC_HEALTH_PAUSE_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            degraded_min_interval_ms: var("EXEC_HEALTH_DEGRADED_INTERVAL_MS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+        };
+
+        let execution_queue = ExecutionQueueConfig {
+            max_in_flight: var("EXEC_QUEUE_MAX_IN_FLIGHT")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .unwrap_or(4),
+            max_queued: var("EXEC_QUEUE_MAX_QUEUED")
+                .unwrap_or_else(|_| "32".to_string())
+                .parse()
+                .unwrap_or(32),
+            max_wait_ms: var("EXEC_QUEUE_MAX_WAIT_MS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+        };
+
+        let obfuscation = ObfuscationConfig {
+            size_jitter_pct: var("OBFUSCATION_SIZE_JITTER_PCT")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse::<f64>()
+                .unwrap_or(0.0)
+                .clamp(0.0, 50.0),
+            timing_jitter_ms: var("OBFUSCATION_TIMING_JITTER_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            randomize_order_ids: var("OBFUSCATION_RANDOM_ORDER_IDS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+        };
+
+        let anomaly = AnomalyConfig {
+            enabled: var("ANOMALY_GUARD_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            window_secs: var("ANOMALY_WINDOW_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            max_slippage_bps: var("ANOMALY_MAX_SLIPPAGE_BPS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300.0),
+            max_slippage_events: var("ANOMALY_MAX_SLIPPAGE_EVENTS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            max_single_leg_failures: var("ANOMALY_MAX_SINGLE_LEG_FAILURES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            max_pnl_shortfall_usd: var("ANOMALY_MAX_PNL_SHORTFALL_USD")
+                .unwrap_or_else(|_| "50.0".to_string())
+                .parse()
+                .unwrap_or(50.0),
+            halt: var("ANOMALY_HALT")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+        };
+
+        let whales = WhaleConfig {
+            enabled: var("WHALE_FEED_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            min_notional_usd: var("WHALE_MIN_NOTIONAL_USD")
+                .unwrap_or_else(|_| "1000.0".to_string())
+                .parse()
+                .unwrap_or(1000.0),
+            window_secs: var("WHALE_WINDOW_SECS")
+                .unwrap_or_else(|_| "900".to_string())
+                .parse()
+                .unwrap_or(900),
+            confidence_weight: var("WHALE_CONFIDENCE_WEIGHT")
+                .unwrap_or_else(|_| "0.2".to_string())
+                .parse()
+                .unwrap_or(0.2),
+        };
+
+        let toxicity = ToxicityConfig {
+            enabled: var("TOXICITY_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            window_ms: var("TOXICITY_WINDOW_MS")
+                .unwrap_or_else(|_| "1500".to_string())
+                .parse()
+                .unwrap_or(1500),
+            threshold: var("TOXICITY_THRESHOLD")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .unwrap_or(0.5),
+            min_samples: var("TOXICITY_MIN_SAMPLES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            extra_edge_bps: var("TOXICITY_EXTRA_EDGE_BPS")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50),
+            mempool: var("TOXICITY_MEMPOOL")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+        };
+
+        let liquidation = LiquidationConfig {
+            enabled: var("LIQUIDATION_LADDER_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            deadline_secs: var("LIQUIDATION_DEADLINE_SECS")
+                .unwrap_or_else(|_| "120".to_string())
+                .parse()
+                .unwrap_or(120),
+            steps: var("LIQUIDATION_STEPS")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .unwrap_or(4),
+            start_discount_bps: var("LIQUIDATION_START_DISCOUNT_BPS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            step_interval_secs: var("LIQUIDATION_STEP_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            end_buffer_secs: var("LIQUIDATION_END_BUFFER_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+        };
+
+        let hedging = HedgeConfig {
+            enabled: var("HEDGING_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            max_directional_exposure_usd: var("HEDGE_MAX_DIRECTIONAL_EXPOSURE_USD")
+                .unwrap_or_else(|_| "200.0".to_string())
+                .parse()
+                .unwrap_or(200.0),
+            hedge_ratio: var("HEDGE_RATIO")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .unwrap_or(0.5),
+            max_hedge_price: var("HEDGE_MAX_PRICE")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .unwrap_or(0.5),
+            // e.g. "<market_a>:<market_b>,<market_c>:<market_d>"
+            correlated_markets: var("HEDGE_CORRELATED_MARKETS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pair| {
+                    let (a, b) = pair.split_once(':')?;
+                    let (a, b) = (a.trim(), b.trim());
+                    (!a.is_empty() && !b.is_empty()).then(|| (a.to_string(), b.to_string()))
+                })
+                .flat_map(|(a, b)| [(a.clone(), b.clone()), (b, a)])
+                .collect(),
+        };
+
+        let arb_exit = ArbExitConfig {
+            enabled: var("ARB_EXIT_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            check_interval_secs: var("ARB_EXIT_CHECK_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            merge_gas_units: var("ARB_MERGE_GAS_UNITS")
+                .unwrap_or_else(|_| "150000".to_string())
+                .parse()
+                .unwrap_or(150_000),
+            min_leg_edge: var("ARB_EXIT_MIN_LEG_EDGE")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02),
+        };
+
+        let collateral = CollateralConfig {
+            auto_swap: var("COLLATERAL_AUTO_SWAP")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            swap_floor_usd: var("COLLATERAL_SWAP_FLOOR_USD")
+                .unwrap_or_else(|_| "10.0".to_string())
+                .parse()
+                .unwrap_or(10.0),
+            max_swap_usd: var("COLLATERAL_MAX_SWAP_USD")
+                .unwrap_or_else(|_| "100.0".to_string())
+                .parse()
+                .unwrap_or(100.0),
+            max_slippage_bps: var("COLLATERAL_MAX_SLIPPAGE_BPS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            pool_fee: var("COLLATERAL_POOL_FEE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+        };
+
+        let gas = GasConfig {
+            aware_redemption: var("GAS_AWARE_REDEMPTION")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            redeem_gas_units: var("REDEEM_GAS_UNITS")
+                .unwrap_or_else(|_| "200000".to_string())
+                .parse()
+                .unwrap_or(200_000),
+            fallback_gas_price_gwei: var("GAS_PRICE_GWEI")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50.0),
+            native_token_usd: var("POL_PRICE_USD")
+                .unwrap_or_else(|_| "0.25".to_string())
+                .parse()
+                .unwrap_or(0.25),
+            min_value_multiple: var("GAS_MIN_VALUE_MULTIPLE")
+                .unwrap_or_else(|_| "3.0".to_string())
+                .parse()
+                .unwrap_or(3.0),
+            batch_min_value_usd: var("REDEEM_BATCH_MIN_USD")
+                .unwrap_or_else(|_| "5.0".to_string())
+                .parse()
+                .unwrap_or(5.0),
+            redeem_max_wait_secs: var("REDEEM_MAX_WAIT_SECS")
+                .unwrap_or_else(|_| "604800".to_string())
+                .parse()
+                .unwrap_or(604_800),
+            redeem_max_in_flight: var("REDEEM_MAX_IN_FLIGHT")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            redeem_receipt_timeout_secs: var("REDEEM_RECEIPT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "120".to_string())
+                .parse()
+                .unwrap_or(120),
+        };
+
+        let funding = FundingConfig {
+            enabled: var("FUNDING_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            source: var("FUNDING_SOURCE").unwrap_or_else(|_| "mainnet".to_string()),
+            auto_confirm: var("FUNDING_AUTO_CONFIRM")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            floor_usd: var("FUNDING_FLOOR_USD")
+                .unwrap_or_else(|_| "20.0".to_string())
+                .parse()
+                .unwrap_or(20.0),
+            target_usd: var("FUNDING_TARGET_USD")
+                .unwrap_or_else(|_| "100.0".to_string())
+                .parse()
+                .unwrap_or(100.0),
+            check_interval_secs: var("FUNDING_CHECK_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            mainnet_rpc: var("ETH_MAINNET_RPC").ok(),
+        };
+
+        let suspension = SuspensionConfig {
+            enabled: var("SUSPENSION_GUARD_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            sports_only: var("SUSPENSION_SPORTS_ONLY")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            resume_grace_ms: var("SUSPENSION_RESUME_GRACE_MS")
+                .unwrap_or_else(|_| "3000".to_string())
+                .parse()
+                .unwrap_or(3000),
+        };
+
+        let synthetic = SyntheticConfig {
+            strategies: var("SYNTHETIC_STRATEGIES")
+                .unwrap_or_else(|_| "arbitrage".to_string())
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            max_size_usd: var("SYNTHETIC_MAX_SIZE_USD")
+                .unwrap_or_else(|_| "25.0".to_string())
+                .parse()
+                .unwrap_or(25.0),
+            first_book_ms: var("SYNTHETIC_FIRST_BOOK_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            metadata_timeout_secs: var("SYNTHETIC_METADATA_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+        };
+
+        let storage = StorageConfig {
+            backend: var("STORAGE_BACKEND").unwrap_or_else(|_| "sqlite".to_string()),
+            url: var("STORAGE_URL").ok().filter(|url| !url.is_empty()),
+            queue_capacity: var("STORAGE_QUEUE_CAPACITY")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+        };
+
+        let performance = PerformanceConfig {
+            resample_secs: var("PERF_RESAMPLE_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            risk_free_rate: var("RISK_FREE_RATE")
+                .unwrap_or_else(|_| "0.0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            snapshot_full_hours: var("SNAPSHOT_FULL_RESOLUTION_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .unwrap_or(24),
+            snapshot_minute_days: var("SNAPSHOT_MINUTE_BARS_DAYS")
+                .unwrap_or_else(|_| "7".to_string())
+                .parse()
+                .unwrap_or(7),
+        };
+
+        let classifier = ClassifierConfig {
+            llm_url: var("CATEGORY_LLM_URL").ok().filter(|url| !url.is_empty()),
+            llm_api_key: var("CATEGORY_LLM_API_KEY")
+                .ok()
+                .filter(|key| !key.is_empty()),
+            llm_model: var("CATEGORY_LLM_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+        };
+
+        let venues = VenueConfig {
+            kalshi_url: var("KALSHI_API_URL")
+                .unwrap_or_else(|_| "https://api.elections.kalshi.com/trade-api/v2".to_string()),
+            // e.g. "0xabc...:KXFEDDECISION-25DEC-H0,0xdef...:KXBTCD-25DEC3117-T99999.99"
+            kalshi_pairs: var("KALSHI_PAIRS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pair| {
+                    let (condition_id, ticker) = pair.split_once(':')?;
+                    Some((condition_id.trim().to_string(), ticker.trim().to_string()))
+                })
+                .collect(),
+            pairs_file: var("VENUE_PAIRS_FILE").ok().filter(|path| !path.is_empty()),
+            kalshi_key_id: var("KALSHI_API_KEY_ID").ok().filter(|key| !key.is_empty()),
+            kalshi_private_key_path: var("KALSHI_PRIVATE_KEY_PATH")
+                .ok()
+                .filter(|path| !path.is_empty()),
+        };
+
+        let cross_venue = CrossVenueConfig {
+            enabled: var("CROSS_VENUE_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            poll_interval_secs: var("CROSS_VENUE_POLL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            min_net_edge_bps: var("CROSS_VENUE_MIN_EDGE_BPS")
+                .unwrap_or_else(|_| "150".to_string())
+                .parse()
+                .unwrap_or(150),
+            size_usd: var("CROSS_VENUE_SIZE_USD")
+                .unwrap_or_else(|_| "50.0".to_string())
+                .parse()
+                .unwrap_or(50.0),
+            withdrawal_bps: var("CROSS_VENUE_WITHDRAWAL_BPS")
+                .unwrap_or_else(|_| "25.0".to_string())
+                .parse()
+                .unwrap_or(25.0),
+            withdrawal_usd: var("CROSS_VENUE_WITHDRAWAL_USD")
+                .unwrap_or_else(|_| "0.0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            max_polymarket_usd: var("CROSS_VENUE_MAX_POLYMARKET_USD")
+                .unwrap_or_else(|_| "500.0".to_string())
+                .parse()
+                .unwrap_or(500.0),
+            max_kalshi_usd: var("CROSS_VENUE_MAX_KALSHI_USD")
+                .unwrap_or_else(|_| "500.0".to_string())
+                .parse()
+                .unwrap_or(500.0),
+        };
+
+        let features = FeatureConfig {
+            enabled: var("FEATURES_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            windows_ms: var("FEATURE_WINDOWS_MS")
+                .unwrap_or_else(|_| "1000,10000,60000".to_string())
+                .split(',')
+                .filter_map(|w| w.trim().parse().ok())
+                .collect(),
+            record_secs: var("FEATURE_RECORD_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+        };
+
+        let chaos = ChaosConfig {
+            enabled: var("CHAOS_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            seed: var("CHAOS_SEED")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            ws_disconnect_prob: var("CHAOS_WS_DISCONNECT_PROB")
+                .unwrap_or_else(|_| "0.001".to_string())
+                .parse()
+                .unwrap_or(0.001),
+            http_timeout_prob: var("CHAOS_HTTP_TIMEOUT_PROB")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02),
+            http_429_prob: var("CHAOS_HTTP_429_PROB")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02),
+            rpc_error_prob: var("CHAOS_RPC_ERROR_PROB")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05),
+            timeout_ms: var("CHAOS_TIMEOUT_MS")
+                .unwrap_or_else(|_| "4000".to_string())
+                .parse()
+                .unwrap_or(4000),
+        };
+
+        let sim_latency = SimLatencyConfig {
+            decision_to_order: var("SIM_DECISION_LATENCY").unwrap_or_else(|_| "0".to_string()),
+            order_to_fill: var("SIM_FILL_LATENCY").unwrap_or_else(|_| "0".to_string()),
+            seed: var("SIM_LATENCY_SEED")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+        };
+
+        let failover = FailoverConfig {
+            role: var("FAILOVER_ROLE").unwrap_or_else(|_| "off".to_string()),
+            peer_url: var("FAILOVER_PEER_URL").ok().filter(|url| !url.is_empty()),
+            peer_token: var("FAILOVER_PEER_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            heartbeat_ms: var("FAILOVER_HEARTBEAT_MS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            peer_timeout_ms: var("FAILOVER_PEER_TIMEOUT_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+            snapshot_dir: var("FAILOVER_SNAPSHOT_DIR")
+                .ok()
+                .filter(|dir| !dir.is_empty()),
+            lease_secs: var("FAILOVER_LEASE_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            promote_after_secs: var("FAILOVER_PROMOTE_AFTER_SECS")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15),
+        };
+
+        let settlement = SettlementConfig {
+            enabled: var("SETTLEMENT_WATCH_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            timeout_secs: var("SETTLEMENT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            tolerance_pct: var("SETTLEMENT_TOLERANCE_PCT")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02),
+        };
+
+        let daily_report = DailyReportConfig {
+            hour_utc: var("DAILY_REPORT_HOUR_UTC")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            webhook_url: var("DAILY_REPORT_WEBHOOK_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            smtp_host: var("SMTP_HOST").ok().filter(|host| !host.is_empty()),
+            smtp_port: var("SMTP_PORT")
+                .unwrap_or_else(|_| "587".to_string())
+                .parse()
+                .unwrap_or(587),
+            smtp_username: var("SMTP_USERNAME")
+                .ok()
+                .filter(|username| !username.is_empty()),
+            smtp_password: var("SMTP_PASSWORD")
+                .ok()
+                .filter(|password| !password.is_empty()),
+            email_from: var("DAILY_REPORT_EMAIL_FROM")
+                .ok()
+                .filter(|from| !from.is_empty()),
+            email_to: var("DAILY_REPORT_EMAIL_TO")
+                .unwrap_or_default()
+                .split(',')
+                .map(|to| to.trim().to_string())
+                .filter(|to| !to.is_empty())
+                .collect(),
+        };
+
+        let self_monitor = SelfMonitorConfig {
+            interval_mins: var("SELF_MONITOR_INTERVAL_MINS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            max_rss_mb: var("SELF_MONITOR_MAX_RSS_MB")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            max_tasks: var("SELF_MONITOR_MAX_TASKS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            max_markets: var("SELF_MONITOR_MAX_MARKETS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            max_assets: var("SELF_MONITOR_MAX_ASSETS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            max_channel_depth: var("SELF_MONITOR_MAX_CHANNEL_DEPTH")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+        };
+
+        let position_import = PositionImportConfig {
+            enabled: var("POSITION_IMPORT_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            min_shares: var("POSITION_IMPORT_MIN_SHARES")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0),
+            history_days: var("POSITION_IMPORT_HISTORY_DAYS")
+                .unwrap_or_else(|_| "7".to_string())
+                .parse()
+                .unwrap_or(7),
+        };
+
+        let order_wal = OrderWalConfig {
+            enabled: var("ORDER_WAL_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+        };
+
+        let telemetry = TelemetryConfig {
+            endpoint: var("TELEMETRY_ENDPOINT").ok().filter(|url| !url.is_empty()),
+            token: var("TELEMETRY_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            interval_secs: var("TELEMETRY_INTERVAL_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+        };
+
+        let capital_wait = CapitalWaitConfig {
+            enabled: var("CAPITAL_WAIT_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            min_edge_bps: var("CAPITAL_WAIT_MIN_EDGE_BPS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300.0),
+            horizon_secs: var("CAPITAL_WAIT_HORIZON_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+            max_wait_secs: var("CAPITAL_WAIT_MAX_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            max_queued: var("CAPITAL_WAIT_MAX_QUEUED")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+        };
+
+        let price_feed = PriceFeedConfig {
+            max_deviation_bps: var("PRICE_FEED_MAX_DEVIATION_BPS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200.0),
+            window_secs: var("PRICE_FEED_WINDOW_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            min_samples: var("PRICE_FEED_MIN_SAMPLES")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            confirm_source: var("PRICE_FEED_CONFIRM_SOURCE")
+                .ok()
+                .map(|source| source.trim().to_lowercase())
+                .filter(|source| !source.is_empty()),
+            max_cross_deviation_bps: var("PRICE_FEED_MAX_CROSS_DEVIATION_BPS")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50.0),
+            hold_after_faults: var("PRICE_FEED_HOLD_FAULTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            hold_secs: var("PRICE_FEED_HOLD_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+        };
+
+        let llm_budget = LlmBudgetConfig {
+            requests_per_hour: var("LLM_REQUESTS_PER_HOUR")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            max_usd_per_day: var("LLM_MAX_USD_PER_DAY")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0),
+            cache_ttl_secs: var("LLM_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()
+                .unwrap_or(86_400),
+            usd_per_1k_prompt_tokens: var("LLM_USD_PER_1K_PROMPT_TOKENS")
+                .unwrap_or_else(|_| "0.00015".to_string())
+                .parse()
+                .unwrap_or(0.00015),
+            usd_per_1k_completion_tokens: var("LLM_USD_PER_1K_COMPLETION_TOKENS")
+                .unwrap_or_else(|_| "0.0006".to_string())
+                .parse()
+                .unwrap_or(0.0006),
+        };
+
+        let market_orders = MarketOrderConfig {
+            snipes: var("MARKET_ORDER_SNIPES")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            slippage_bps: var("MARKET_ORDER_SLIPPAGE_BPS")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50.0),
+            max_slippage_bps: var("MARKET_ORDER_MAX_SLIPPAGE_BPS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200.0),
+        };
+
+        let neg_risk = NegRiskConfig {
+            enabled: var("NEG_RISK_CHECK")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            convert: var("NEG_RISK_CONVERT")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            adapter_fee_bps: var("NEG_RISK_ADAPTER_FEE_BPS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            convert_gas_units: var("NEG_RISK_CONVERT_GAS_UNITS")
+                .unwrap_or_else(|_| "150000".to_string())
+                .parse()
+                .unwrap_or(150_000),
+            convert_gas_units_per_question: var("NEG_RISK_CONVERT_GAS_PER_QUESTION")
+                .unwrap_or_else(|_| "60000".to_string())
+                .parse()
+                .unwrap_or(60_000),
+            min_edge_bps: var("NEG_RISK_MIN_EDGE_BPS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10.0),
+        };
+
+        let order_role = OrderRoleConfig {
+            enabled: var("ORDER_ROLE_SELECTION")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            maker_fee_bps: var("MAKER_FEE_BPS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            taker_fee_bps: var("TAKER_FEE_BPS")
+                .unwrap_or_else(|_| "40".to_string())
+                .parse()
+                .unwrap_or(40.0),
+            take_edge_bps: var("ORDER_ROLE_TAKE_EDGE_BPS")
+                .unwrap_or_else(|_| "150".to_string())
+                .parse()
+                .unwrap_or(150.0),
+            urgent_secs: var("ORDER_ROLE_URGENT_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            max_book_changes: var("ORDER_ROLE_MAX_BOOK_CHANGES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            tick: var("ORDER_ROLE_TICK")
+                .unwrap_or_else(|_| "0.01".to_string())
+                .parse()
+                .unwrap_or(0.01),
+            maker_cancel_secs: var("ORDER_ROLE_MAKER_CANCEL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+        };
+
+        let zombies = ZombieConfig {
+            enabled: var("ZOMBIE_DETECTION")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            extreme_price: var("ZOMBIE_EXTREME_PRICE")
+                .unwrap_or_else(|_| "0.995".to_string())
+                .parse()
+                .unwrap_or(0.995),
+            stable_secs: var("ZOMBIE_STABLE_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+            end_grace_secs: var("ZOMBIE_END_GRACE_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            check_interval_secs: var("ZOMBIE_CHECK_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+        };
+
+        let chain_events = ChainEventsConfig {
+            confirmations: var("CHAIN_CONFIRMATIONS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            dedup_window_secs: var("CHAIN_DEDUP_WINDOW_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+        };
+
+        let zero_fee = ZeroFeeConfig {
+            enabled: var("ZERO_FEE_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            min_edge_bps: var("ZERO_FEE_MIN_EDGE_BPS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            size_multiplier: var("ZERO_FEE_SIZE_MULTIPLIER")
+                .unwrap_or_else(|_| "1.5".to_string())
+                .parse()
+                .unwrap_or(1.5),
+            refresh_secs: var("FEE_RATE_REFRESH_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+        };
+
+        let latency_budget = LatencyBudgetConfig {
+            enabled: var("LATENCY_BUDGET_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            snipe_ms: var("LATENCY_BUDGET_SNIPE_MS")
+                .unwrap_or_else(|_| "150".to_string())
+                .parse()
+                .unwrap_or(150),
+            arbitrage_ms: var("LATENCY_BUDGET_ARBITRAGE_MS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            default_ms: var("LATENCY_BUDGET_DEFAULT_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+        };
+
+        let metadata_cache = MetadataCacheConfig {
+            enabled: var("METADATA_CACHE_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            ttl_secs: var("METADATA_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "21600".to_string())
+                .parse()
+                .unwrap_or(21_600),
+            max_age_secs: var("METADATA_CACHE_MAX_AGE_SECS")
+                .unwrap_or_else(|_| "604800".to_string())
+                .parse()
+                .unwrap_or(604_800),
+        };
+
+        let series_watch = SeriesWatchConfig {
+            enabled: var("SERIES_WATCH_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            poll_interval_secs: var("SERIES_WATCH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            auto_pin_keywords: var("SERIES_AUTO_PIN_KEYWORDS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|keyword| keyword.trim().to_lowercase())
+                .filter(|keyword| !keyword.is_empty())
+                .collect(),
+            auto_pin_max_markets: var("SERIES_AUTO_PIN_MAX_MARKETS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            auto_pin_notify: var("SERIES_AUTO_PIN_NOTIFY")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+        };
+
+        Ok(Config {
+            polymarket,
+            arbitrage,
+            agent,
+            risk,
+            market_filters,
+            flashbots,
+            polygon_ws_rpc,
+            polygon_private_key,
+            ctf_contract_address,
+            expiration,
+            dislocation,
+            experiment,
+            edge_tuning,
+            execution_health,
+            execution_queue,
+            obfuscation,
+            anomaly,
+            whales,
+            toxicity,
+            liquidation,
+            hedging,
+            arb_exit,
+            collateral,
+            gas,
+            funding,
+            suspension,
+            synthetic,
+            storage,
+            performance,
+            classifier,
+            venues,
+            cross_venue,
+            features,
+            chaos,
+            sim_latency,
+            self_monitor,
+            daily_report,
+            failover,
+            settlement,
+            position_import,
+            order_wal,
+            telemetry,
+            capital_wait,
+            price_feed,
+            llm_budget,
+            market_orders,
+            neg_risk,
+            order_role,
+            zombies,
+            chain_events,
+            zero_fee,
+            latency_budget,
+            metadata_cache,
+            series_watch,
+            predictive: PredictiveConfig {
+                enabled: var("PREDICTIVE_SNIPING_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap_or(true),
+                min_confidence: var("PREDICTIVE_MIN_CONFIDENCE")
                     .unwrap_or_else(|_| "0.50".to_string())
                     .parse()
                     .unwrap_or(0.50),
-                max_uncertainty: env::var("PREDICTIVE_MAX_UNCERTAINTY")
+                max_uncertainty: var("PREDICTIVE_MAX_UNCERTAINTY")
                     .unwrap_or_else(|_| "0.10".to_string())
                     .parse()
                     .unwrap_or(0.10),
-                binance_signal_threshold_pct: env::var("BINANCE_SIGNAL_THRESHOLD_PCT")
+                binance_signal_threshold_pct: var("BINANCE_SIGNAL_THRESHOLD_PCT")
                     .unwrap_or_else(|_| "0.5".to_string())
                     .parse()
                     .unwrap_or(0.5),
-                final_window_sec: env::var("PREDICTIVE_FINAL_WINDOW_SEC")
+                final_window_sec: var("PREDICTIVE_FINAL_WINDOW_SEC")
                     .unwrap_or_else(|_| "180".to_string())
                     .parse()
                     .unwrap_or(180),
-                max_entry_price: env::var("PREDICTIVE_MAX_ENTRY_PRICE")
+                max_entry_price: var("PREDICTIVE_MAX_ENTRY_PRICE")
                     .unwrap_or_else(|_| "0.97".to_string())
                     .parse()
                     .unwrap_or(0.97),
+                min_ofi: var("PREDICTIVE_MIN_OFI")
+                    .unwrap_or_else(|_| "0.0".to_string())
+                    .parse()
+                    .unwrap_or(0.0),
+                model_path: var("PREDICTIVE_MODEL_PATH")
+                    .ok()
+                    .filter(|path| !path.is_empty()),
             },
         })
     }
+
+    /// Copy with credentials and RPC endpoints (which often embed API keys) blanked out
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.polymarket.api_key.clear();
+        config.polymarket.secret.clear();
+        config.polymarket.passphrase.clear();
+        config.polygon_private_key = None;
+        config.polygon_ws_rpc = None;
+        config.flashbots.signing_key = None;
+        config.funding.mainnet_rpc = None;
+        config.storage.url = None;
+        config.classifier.llm_api_key = None;
+        config.venues.kalshi_key_id = None;
+        config.agent.api_read_tokens.clear();
+        config.agent.api_admin_tokens.clear();
+        config.failover.peer_token = None;
+        // Webhook URLs embed their secret
+        config.daily_report.webhook_url = None;
+        config.daily_report.smtp_password = None;
+        config.telemetry.endpoint = None;
+        config.telemetry.token = None;
+        config
+    }
+}
+
+/// Every setting at the default `from_env` uses for an unset variable (so no credentials)
+impl Default for Config {
+    fn default() -> Self {
+        Self::from_vars(|_| Err(env::VarError::NotPresent))
+            .expect("defaults don't depend on the environment")
+    }
 }
+
+/// `Config::default()`, built once for the sections' `Default`
+fn default_config() -> &'static Config {
+    static DEFAULT: OnceLock<Config> = OnceLock::new();
+    DEFAULT.get_or_init(Config::default)
+}
+
+/// `Default` of a section: its part of `Config::default()`, so the defaults live in one place
+macro_rules! default_from_config {
+    ($($ty:ty => $($field:ident).+;)*) => {
+        $(
+            impl Default for $ty {
+                fn default() -> Self {
+                    default_config().$($field).+.clone()
+                }
+            }
+        )*
+    };
+}
+
+default_from_config! {
+    PolymarketConfig => polymarket;
+    CircuitBreakerConfig => polymarket.circuit_breaker;
+    ArbitrageConfig => arbitrage;
+    AgentConfig => agent;
+    RiskConfig => risk;
+    MarketFilters => market_filters;
+    FlashbotsConfig => flashbots;
+    ExpirationConfig => expiration;
+    PredictiveConfig => predictive;
+    DislocationConfig => dislocation;
+    ExperimentConfig => experiment;
+    EdgeTuningConfig => edge_tuning;
+    ExecutionHealthConfig => execution_health;
+    ExecutionQueueConfig => execution_queue;
+    ObfuscationConfig => obfuscation;
+    AnomalyConfig => anomaly;
+    WhaleConfig => whales;
+    ToxicityConfig => toxicity;
+    LiquidationConfig => liquidation;
+    HedgeConfig => hedging;
+    ArbExitConfig => arb_exit;
+    CollateralConfig => collateral;
+    GasConfig => gas;
+    FundingConfig => funding;
+    SuspensionConfig => suspension;
+    SyntheticConfig => synthetic;
+    StorageConfig => storage;
+    PerformanceConfig => performance;
+    ClassifierConfig => classifier;
+    VenueConfig => venues;
+    CrossVenueConfig => cross_venue;
+    FeatureConfig => features;
+    ChaosConfig => chaos;
+    SimLatencyConfig => sim_latency;
+    SelfMonitorConfig => self_monitor;
+    DailyReportConfig => daily_report;
+    FailoverConfig => failover;
+    SettlementConfig => settlement;
+    PositionImportConfig => position_import;
+    OrderWalConfig => order_wal;
+    TelemetryConfig => telemetry;
+    CapitalWaitConfig => capital_wait;
+    PriceFeedConfig => price_feed;
+    LlmBudgetConfig => llm_budget;
+    MarketOrderConfig => market_orders;
+    NegRiskConfig => neg_risk;
+    OrderRoleConfig => order_role;
+    ZombieConfig => zombies;
+    ChainEventsConfig => chain_events;
+    ZeroFeeConfig => zero_fee;
+    LatencyBudgetConfig => latency_budget;
+    MetadataCacheConfig => metadata_cache;
+    SeriesWatchConfig => series_watch;
+}
+
+/// Chained setters named after the fields, for the sections handed to strategies and the
+/// risk manager directly: `ArbitrageConfig::default().enabled(true).min_edge_bps(150)`
+macro_rules! setters {
+    ($ty:ty { $($field:ident: $field_ty:ty,)* }) => {
+        impl $ty {
+            $(
+                pub fn $field(mut self, $field: $field_ty) -> Self {
+                    self.$field = $field;
+                    self
+                }
+            )*
+        }
+    };
+}
+
+setters!(ArbitrageConfig {
+    enabled: bool,
+    min_edge_bps: i32,
+    max_position_size_usd: f64,
+    sizing_mode: SizingMode,
+    kelly_fraction: f64,
+    min_position_pct: f64,
+    max_position_pct: f64,
+    capital_reserve_fraction: f64,
+    min_depth_usd: f64,
+});
+
+setters!(ExpirationConfig {
+    enabled: bool,
+    max_time_remaining_sec: u64,
+    min_price: f64,
+    target_price: f64,
+    watch_window_sec: u64,
+    watch_poll_interval_ms: u64,
+    hedge_enabled: bool,
+    hedge_max_price: f64,
+    hedge_ratio: f64,
+    win_prob: f64,
+    hedge_min_ev_bps: f64,
+});
+
+setters!(PredictiveConfig {
+    enabled: bool,
+    min_confidence: f64,
+    max_uncertainty: f64,
+    binance_signal_threshold_pct: f64,
+    final_window_sec: u64,
+    max_entry_price: f64,
+    min_ofi: f64,
+    model_path: Option<String>,
+});
+
+setters!(DislocationConfig {
+    enabled: bool,
+    min_dislocation_bps: i32,
+    max_entry_price: f64,
+    annual_volatility: f64,
+});
+
+setters!(RiskConfig {
+    max_position_size_pct: f64,
+    max_portfolio_exposure_pct: f64,
+    stop_loss_pct: f64,
+    use_dynamic_sl: bool,
+    min_hold_time_secs: u64,
+    auto_sell_threshold: f64,
+    capital_usd: f64,
+    category_exposure_limits: BTreeMap<MarketCategory, f64>,
+    strategy_capital_pct: BTreeMap<String, f64>,
+});
@@ -0,0 +1,98 @@
+//! Events the agent publishes as it trades, for projects embedding it (`Agent::events`)
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::strategies::arbitrage::TradeAction;
+
+/// Events buffered per subscriber; a subscriber further behind misses the oldest
+/// (`RecvError::Lagged`) rather than holding up the decision loop
+pub const EVENT_BUFFER: usize = 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentEvent {
+    /// A strategy signalled on a market (before sizing and risk checks)
+    Signal {
+        strategy: String,
+        market_id: String,
+        action: TradeAction,
+    },
+    /// An entry order was accepted
+    OrderPlaced {
+        trade_id: String,
+        market_id: String,
+        side: String,
+        price: f64,
+        size_usd: f64,
+    },
+    /// The last open position in a market was closed
+    PositionClosed { market_id: String },
+    /// A signal ran over its latency budget and was dropped before submission
+    MissedWindow {
+        trade_id: String,
+        market_id: String,
+        strategy: String,
+        budget_ms: u64,
+        elapsed_ms: f64,
+    },
+    /// Gamma listed an event series the agent hadn't seen
+    NewSeries {
+        series_id: String,
+        slug: String,
+        title: String,
+        recurrence: Option<String>,
+        /// Auto-pin keyword it matched: its markets are pinned next (see `GET /api/series`)
+        matched_keyword: Option<String>,
+    },
+}
+
+/// Fan-out of agent events (cheap to clone, all clones publish to the same subscribers)
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<AgentEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUFFER);
+        Self { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AgentEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Publish to the current subscribers; `event` is only built when there are some
+    pub fn publish(&self, event: impl FnOnce() -> AgentEvent) {
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(event());
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_reach_subscribers() {
+        let bus = EventBus::new();
+        // Nobody listening: nothing built
+        bus.publish(|| unreachable!());
+
+        let mut rx = bus.subscribe();
+        bus.clone().publish(|| AgentEvent::PositionClosed {
+            market_id: "0xabc".to_string(),
+        });
+        match rx.try_recv().unwrap() {
+            AgentEvent::PositionClosed { market_id } => assert_eq!(market_id, "0xabc"),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use crate::config::AnomalyConfig;
+
+/// Recent anomalies kept for the API, tripped or not
+const RECENT_ANOMALIES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum AnomalyKind {
+    /// The book moved far past an order's limit
+    FillSlippage,
+    /// An arbitrage plan ended up with only one leg
+    SingleLeg,
+    /// Settled arbitrage PnL below what the strategy expected
+    PnlShortfall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub market_id: String,
+    pub at: DateTime<Utc>,
+    /// Slippage in bps, or shortfall in USD (1 for single legs)
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AnomalySnapshot {
+    pub tripped: bool,
+    pub tripped_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+    pub recent: Vec<Anomaly>,
+}
+
+struct AnomalyState {
+    recent: VecDeque<Anomaly>,
+    tripped_at: Option<DateTime<Utc>>,
+    reason: Option<String>,
+    /// Trip not yet picked up by the Sniper
+    pending: bool,
+}
+
+/// Dead man's switch for live trading: counts execution anomalies over a rolling window and
+/// trips once any kind crosses its limit. The Sniper then downgrades the run to paper trading
+/// for good (a restart is needed to trade live again)
+pub struct AnomalyGuard {
+    config: AnomalyConfig,
+    state: Mutex<AnomalyState>,
+}
+
+impl AnomalyGuard {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(AnomalyState {
+                recent: VecDeque::new(),
+                tripped_at: None,
+                reason: None,
+                pending: false,
+            }),
+        }
+    }
+
+    /// How far the book moved past an order's limit (from the fill check)
+    pub fn record_slippage(&self, market_id: &str, slippage_bps: f64) {
+        if slippage_bps > self.config.max_slippage_bps {
+            self.record(
+                AnomalyKind::FillSlippage,
+                market_id,
+                slippage_bps,
+                Utc::now(),
+            );
+        }
+    }
+
+    pub fn record_single_leg(&self, market_id: &str) {
+        self.record(AnomalyKind::SingleLeg, market_id, 1.0, Utc::now());
+    }
+
+    /// Expected minus realized PnL of settled trades (USD, positive = worse than expected)
+    pub fn record_pnl_shortfall(&self, market_id: &str, shortfall_usd: f64) {
+        if shortfall_usd > 0.0 {
+            self.record(
+                AnomalyKind::PnlShortfall,
+                market_id,
+                shortfall_usd,
+                Utc::now(),
+            );
+        }
+    }
+
+    /// Reason of a trip not acted on yet (returned once)
+    pub fn take_trip(&self) -> Option<String> {
+        let mut state = self.state.lock().ok()?;
+        if !state.pending {
+            return None;
+        }
+        state.pending = false;
+        state.reason.clone()
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.state
+            .lock()
+            .map(|s| s.tripped_at.is_some())
+            .unwrap_or(false)
+    }
+
+    pub fn snapshot(&self) -> AnomalySnapshot {
+        let state = self.state.lock().unwrap();
+        AnomalySnapshot {
+            tripped: state.tripped_at.is_some(),
+            tripped_at: state.tripped_at,
+            reason: state.reason.clone(),
+            recent: state.recent.iter().cloned().collect(),
+        }
+    }
+
+    fn record(&self, kind: AnomalyKind, market_id: &str, value: f64, now: DateTime<Utc>) {
+        if !self.config.enabled {
+            return;
+        }
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        warn!(
+            "⚠️ Execution anomaly on {}: {:?} ({:.2})",
+            market_id, kind, value
+        );
+        state.recent.push_back(Anomaly {
+            kind,
+            market_id: market_id.to_string(),
+            at: now,
+            value,
+        });
+        while state.recent.len() > RECENT_ANOMALIES {
+            state.recent.pop_front();
+        }
+        if state.tripped_at.is_some() {
+            return;
+        }
+
+        let since = now - Duration::seconds(self.config.window_secs.min(i64::MAX as u64) as i64);
+        let in_window: Vec<&Anomaly> = state
+            .recent
+            .iter()
+            .filter(|a| a.kind == kind && a.at >= since)
+            .collect();
+        let reason = match kind {
+            AnomalyKind::FillSlippage => {
+                (in_window.len() >= self.config.max_slippage_events).then(|| {
+                    format!(
+                        "{} orders missed by more than {} bps",
+                        in_window.len(),
+                        self.config.max_slippage_bps
+                    )
+                })
+            }
+            AnomalyKind::SingleLeg => (in_window.len() >= self.config.max_single_leg_failures)
+                .then(|| format!("{} arbitrage plans left with a single leg", in_window.len())),
+            AnomalyKind::PnlShortfall => {
+                let shortfall: f64 = in_window.iter().map(|a| a.value).sum();
+                (shortfall >= self.config.max_pnl_shortfall_usd)
+                    .then(|| format!("arbitrage PnL ${:.2} below expectation", shortfall))
+            }
+        };
+
+        if let Some(reason) = reason {
+            error!(
+                "🚨 ANOMALY GUARD TRIPPED: {} within {}s - switching to paper trading",
+                reason, self.config.window_secs
+            );
+            state.tripped_at = Some(now);
+            state.reason = Some(reason);
+            state.pending = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AnomalyConfig {
+        AnomalyConfig {
+            enabled: true,
+            window_secs: 600,
+            max_slippage_bps: 300.0,
+            max_slippage_events: 3,
+            max_single_leg_failures: 2,
+            max_pnl_shortfall_usd: 10.0,
+            halt: false,
+        }
+    }
+
+    #[test]
+    fn test_trips_once_within_window() {
+        let guard = AnomalyGuard::new(config());
+        let t0 = Utc::now();
+
+        // Small slippage isn't an anomaly; old single legs fall out of the window
+        guard.record_slippage("m1", 50.0);
+        guard.record(AnomalyKind::SingleLeg, "m1", 1.0, t0 - Duration::hours(1));
+        guard.record(AnomalyKind::SingleLeg, "m2", 1.0, t0);
+        guard.record(AnomalyKind::PnlShortfall, "m3", 6.0, t0);
+        assert!(!guard.is_tripped());
+        assert_eq!(guard.snapshot().recent.len(), 3);
+
+        guard.record(AnomalyKind::PnlShortfall, "m4", 5.0, t0);
+        assert!(guard.is_tripped());
+        let reason = guard.take_trip().unwrap();
+        assert!(reason.contains("$11.00"), "{}", reason);
+        assert!(guard.take_trip().is_none());
+
+        // Stays tripped, later anomalies are only recorded
+        guard.record(AnomalyKind::SingleLeg, "m5", 1.0, t0);
+        assert!(guard.take_trip().is_none());
+        assert_eq!(guard.snapshot().reason, Some(reason));
+    }
+}
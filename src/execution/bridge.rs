@@ -0,0 +1,464 @@
+//! Executes wallet top-ups: the mainnet bridge deposit signs with the wallet key, so this
+//! module is only in the trading build (the request queue is in `funding`)
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use ethers::prelude::*;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::config::{Config, FundingConfig};
+use crate::execution::funding::{FundingQueue, FundingRequest, FundingSource, FundingStatus};
+
+/// USDC on Ethereum mainnet (bridged to Polygon as USDC.e, the exchange collateral)
+const MAINNET_USDC_ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+/// Polygon PoS bridge RootChainManager (proxy)
+const ROOT_CHAIN_MANAGER_ADDRESS: &str = "0xA0c68C638235ee32657e8f720a23ceC1bFc77C77";
+/// ERC20Predicate that pulls the deposited tokens
+const ERC20_PREDICATE_ADDRESS: &str = "0x40ec5B33f54e0E8A33A975908C5BA1c14e5BbbDf";
+/// Executions of one request before it is marked Failed
+const MAX_FUNDING_ATTEMPTS: u32 = 4;
+/// Wait after the first failed attempt, doubled on each further one
+const RETRY_BACKOFF_SECS: i64 = 120;
+
+abigen!(
+    RootChainManager,
+    r#"[
+        function depositFor(address user, address rootToken, bytes calldata depositData) external
+    ]"#
+);
+
+abigen!(
+    MainnetERC20,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function allowance(address owner, address spender) external view returns (uint256)
+        function approve(address spender, uint256 amount) external returns (bool)
+    ]"#
+);
+
+/// Deposits mainnet USDC into the PoS bridge for a Polygon address
+pub struct MainnetBridge {
+    client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+}
+
+impl MainnetBridge {
+    pub fn new(rpc_url: &str, private_key: &str) -> Result<Self> {
+        let provider = Provider::<Http>::try_from(rpc_url)?;
+        let wallet = LocalWallet::from_str(private_key)?.with_chain_id(1u64); // Ethereum Mainnet ID
+        Ok(Self {
+            client: Arc::new(SignerMiddleware::new(provider, wallet)),
+        })
+    }
+
+    /// Bridge `amount_usd` of mainnet USDC to `recipient` on Polygon. Returns the deposit tx
+    /// hash; `on_signed` gets it before the broadcast so a restart can look the deposit up
+    pub async fn deposit_usdc(
+        &self,
+        recipient: &str,
+        amount_usd: f64,
+        on_signed: impl FnOnce(&str) -> Result<()>,
+    ) -> Result<String> {
+        let usdc = MainnetERC20::new(
+            Address::from_str(MAINNET_USDC_ADDRESS)?,
+            self.client.clone(),
+        );
+        let predicate = Address::from_str(ERC20_PREDICATE_ADDRESS)?;
+        let amount = U256::from((amount_usd * 1_000_000.0) as u128);
+
+        let available = usdc.balance_of(self.client.address()).call().await?;
+        if available < amount {
+            anyhow::bail!(
+                "Mainnet USDC balance ${:.2} is below the requested ${:.2}",
+                available.as_u128() as f64 / 1_000_000.0,
+                amount_usd
+            );
+        }
+
+        let allowance = usdc
+            .allowance(self.client.address(), predicate)
+            .call()
+            .await?;
+        if allowance < amount {
+            let pending = usdc.approve(predicate, amount).send().await?;
+            info!("✅ Bridge approval sent: {:?}", pending.tx_hash());
+            pending.await?;
+        }
+
+        let manager = RootChainManager::new(
+            Address::from_str(ROOT_CHAIN_MANAGER_ADDRESS)?,
+            self.client.clone(),
+        );
+        let deposit_data = ethers::abi::encode(&[ethers::abi::Token::Uint(amount)]);
+        let call = manager.deposit_for(
+            Address::from_str(recipient)?,
+            Address::from_str(MAINNET_USDC_ADDRESS)?,
+            deposit_data.into(),
+        );
+        let mut tx = call.tx.clone();
+        self.client.fill_transaction(&mut tx, None).await?;
+        let signature = self.client.signer().sign_transaction(&tx).await?;
+        let raw = tx.rlp_signed(&signature);
+        let tx_hash = format!("{:?}", H256::from(ethers::utils::keccak256(&raw)));
+        on_signed(&tx_hash)?;
+        self.client.provider().send_raw_transaction(raw).await?;
+
+        info!("✅ Bridge deposit sent! Hash: {}", tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// The node knows the transaction (mined or still in its mempool)
+    pub async fn is_known(&self, tx_hash: &str) -> Result<bool> {
+        let hash = H256::from_str(tx_hash)?;
+        Ok(self.client.get_transaction(hash).await?.is_some())
+    }
+}
+
+/// Keeps the Polygon trading wallet funded: raises a request when the balance drops below
+/// the floor and executes it once approved (immediately in auto mode)
+pub struct FundingManager {
+    config: FundingConfig,
+    source: FundingSource,
+    destination: String,
+    queue: FundingQueue,
+    bridge: Option<MainnetBridge>,
+    /// A `process` round is running (it waits for mainnet confirmations)
+    processing: AtomicBool,
+}
+
+impl FundingManager {
+    /// The helper `config` enables, for live trading only. It funds the wallet that trades: the
+    /// Safe when a proxy is configured, else the signer
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if !config.funding.enabled || config.agent.paper_trading || config.agent.simulation_mode {
+            return None;
+        }
+        let destination = config.polymarket.proxy_address.clone().or_else(|| {
+            config
+                .polygon_private_key
+                .as_deref()
+                .and_then(|pk| LocalWallet::from_str(pk).ok())
+                .map(|w| format!("{:?}", w.address()))
+        });
+        let Some(destination) = destination else {
+            error!("❌ Funding helper needs a proxy address or POLYGON_PRIVATE_KEY");
+            return None;
+        };
+        match Self::new(
+            config.funding.clone(),
+            &config.agent.data_dir,
+            destination,
+            config.polygon_private_key.as_deref(),
+        ) {
+            Ok(manager) => {
+                info!("✅ Funding helper enabled ({})", config.funding.source);
+                Some(manager)
+            }
+            Err(e) => {
+                error!("❌ Failed to init funding helper: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn new(
+        config: FundingConfig,
+        data_dir: &str,
+        destination: String,
+        private_key: Option<&str>,
+    ) -> Result<Self> {
+        let source = FundingSource::parse(&config.source)
+            .ok_or_else(|| anyhow::anyhow!("Unknown FUNDING_SOURCE: {}", config.source))?;
+        let bridge = match (source, &config.mainnet_rpc, private_key) {
+            (FundingSource::Mainnet, Some(rpc), Some(pk)) => Some(MainnetBridge::new(rpc, pk)?),
+            (FundingSource::Mainnet, _, _) => {
+                anyhow::bail!("Mainnet funding needs ETH_MAINNET_RPC and POLYGON_PRIVATE_KEY")
+            }
+            (FundingSource::Cex, _, _) => None,
+        };
+
+        Ok(Self {
+            source,
+            destination,
+            queue: FundingQueue::new(data_dir),
+            bridge,
+            config,
+            processing: AtomicBool::new(false),
+        })
+    }
+
+    /// Raise a request if the balance is below the floor and nothing is already in flight
+    pub fn check(&self, balance_usd: f64) -> Result<Option<FundingRequest>> {
+        if balance_usd >= self.config.floor_usd {
+            return Ok(None);
+        }
+        self.queue
+            .update(|requests| Ok(self.raise(requests, balance_usd)))
+    }
+
+    fn raise(
+        &self,
+        requests: &mut Vec<FundingRequest>,
+        balance_usd: f64,
+    ) -> Option<FundingRequest> {
+        if requests.iter().any(|r| r.status.is_open()) {
+            return None;
+        }
+
+        let now = Utc::now();
+        // Only mainnet deposits can run unattended; CEX withdrawals always need the operator
+        let status = if self.config.auto_confirm && self.source == FundingSource::Mainnet {
+            FundingStatus::Approved
+        } else {
+            FundingStatus::Pending
+        };
+        let request = FundingRequest {
+            id: format!("fund-{}", now.timestamp_millis()),
+            created_at: now,
+            updated_at: now,
+            source: self.source,
+            amount_usd: (self.config.target_usd - balance_usd).max(0.0),
+            balance_usd,
+            destination: self.destination.clone(),
+            status,
+            tx_hash: None,
+            error: None,
+            attempts: 0,
+            retry_after: None,
+        };
+
+        warn!(
+            "🪫 Trading balance ${:.2} below floor ${:.2}: funding request {} for ${:.2} ({:?}, {:?})",
+            balance_usd,
+            self.config.floor_usd,
+            request.id,
+            request.amount_usd,
+            request.source,
+            request.status
+        );
+        if request.status == FundingStatus::Pending {
+            match request.source {
+                FundingSource::Mainnet => warn!(
+                    "🚨 ALERT: approve with `funding approve {}` to bridge from mainnet",
+                    request.id
+                ),
+                FundingSource::Cex => warn!(
+                    "🚨 ALERT: withdraw ${:.2} USDC.e (Polygon) to {}, then run `funding approve {}`",
+                    request.amount_usd, request.destination, request.id
+                ),
+            }
+        }
+
+        requests.push(request.clone());
+        Some(request)
+    }
+
+    /// Execute approved requests that are due. One round at a time: each request is marked
+    /// Sending before anything is signed, and its tx hash is saved before the broadcast
+    pub async fn process(&self) -> Result<()> {
+        if self.processing.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let result = self.process_due().await;
+        self.processing.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn process_due(&self) -> Result<()> {
+        self.recover_interrupted().await?;
+
+        let now = Utc::now();
+        let due = self.queue.update(|requests| {
+            let mut due = Vec::new();
+            for request in requests.iter_mut().filter(|r| {
+                r.status == FundingStatus::Approved && r.retry_after.is_none_or(|at| at <= now)
+            }) {
+                request.status = FundingStatus::Sending;
+                request.attempts += 1;
+                request.updated_at = now;
+                due.push(request.clone());
+            }
+            Ok(due)
+        })?;
+
+        for request in due {
+            let outcome = match (request.source, &self.bridge) {
+                (FundingSource::Mainnet, Some(bridge)) => bridge
+                    .deposit_usdc(&request.destination, request.amount_usd, |tx_hash| {
+                        self.set(&request.id, |r| r.tx_hash = Some(tx_hash.to_string()))
+                    })
+                    .await
+                    .map(Some),
+                (FundingSource::Mainnet, None) => {
+                    Err(anyhow::anyhow!("No mainnet bridge configured"))
+                }
+                // The operator already did the withdrawal when approving
+                (FundingSource::Cex, _) => {
+                    info!("🏦 Funding {} acknowledged (CEX withdrawal)", request.id);
+                    Ok(None)
+                }
+            };
+            match outcome {
+                Ok(tx) => {
+                    if let Some(tx) = &tx {
+                        info!("🌉 Funding {} bridged: {}", request.id, tx);
+                    }
+                    self.set(&request.id, |r| {
+                        r.status = FundingStatus::Submitted;
+                        r.error = None;
+                    })?;
+                }
+                Err(e) => self.fail(&request.id, request.attempts, &e.to_string())?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Requests left Sending by a stopped round: a deposit the node knows went out; one it
+    /// doesn't (or that was never signed) is safe to execute again
+    async fn recover_interrupted(&self) -> Result<()> {
+        let interrupted: Vec<FundingRequest> = self
+            .queue
+            .load()?
+            .into_iter()
+            .filter(|r| r.status == FundingStatus::Sending)
+            .collect();
+        for request in interrupted {
+            let sent = match (&request.tx_hash, &self.bridge) {
+                (Some(tx_hash), Some(bridge)) => bridge.is_known(tx_hash).await?,
+                _ => false,
+            };
+            if sent {
+                info!("🌉 Funding {} was sent before the restart", request.id);
+                self.set(&request.id, |r| r.status = FundingStatus::Submitted)?;
+            } else {
+                warn!(
+                    "⚠️ Funding {} was interrupted before its deposit went out",
+                    request.id
+                );
+                self.set(&request.id, |r| {
+                    r.status = FundingStatus::Approved;
+                    r.tx_hash = None;
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Back to Approved after a backoff, or Failed once the attempts are used up
+    fn fail(&self, id: &str, attempts: u32, error: &str) -> Result<()> {
+        let exhausted = attempts >= MAX_FUNDING_ATTEMPTS;
+        if exhausted {
+            error!(
+                "❌ Funding {} failed after {} attempts: {}",
+                id, attempts, error
+            );
+        } else {
+            warn!("⚠️ Funding {} attempt {} failed: {}", id, attempts, error);
+        }
+        let backoff = Duration::seconds(RETRY_BACKOFF_SECS << attempts.saturating_sub(1).min(10));
+        self.set(id, |r| {
+            r.error = Some(error.to_string());
+            r.tx_hash = None;
+            if exhausted {
+                r.status = FundingStatus::Failed;
+                r.retry_after = None;
+            } else {
+                r.status = FundingStatus::Approved;
+                r.retry_after = Some(Utc::now() + backoff);
+            }
+        })
+    }
+
+    /// Update one request under the queue lock
+    fn set(&self, id: &str, apply: impl FnOnce(&mut FundingRequest)) -> Result<()> {
+        self.queue.update(|requests| {
+            if let Some(request) = requests.iter_mut().find(|r| r.id == id) {
+                apply(request);
+                request.updated_at = Utc::now();
+            }
+            Ok(())
+        })
+    }
+
+    /// Once the balance is back above the floor, in-flight requests are done
+    pub fn settle(&self, balance_usd: f64) -> Result<()> {
+        if balance_usd < self.config.floor_usd {
+            return Ok(());
+        }
+        self.queue.update(|requests| {
+            for request in requests
+                .iter_mut()
+                .filter(|r| r.status == FundingStatus::Submitted)
+            {
+                info!(
+                    "✅ Funding {} arrived (balance ${:.2})",
+                    request.id, balance_usd
+                );
+                request.status = FundingStatus::Completed;
+                request.updated_at = Utc::now();
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(data_dir: &str, auto_confirm: bool) -> FundingManager {
+        FundingManager::new(
+            FundingConfig {
+                enabled: true,
+                source: "cex".to_string(),
+                auto_confirm,
+                floor_usd: 20.0,
+                target_usd: 100.0,
+                check_interval_secs: 60,
+                mainnet_rpc: None,
+            },
+            data_dir,
+            "0xabc".to_string(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_lifecycle() {
+        let dir = std::env::temp_dir().join(format!("funding-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        // CEX withdrawals are never auto-confirmed
+        let funding = manager(data_dir, true);
+
+        assert!(funding.check(50.0).unwrap().is_none());
+        let request = funding.check(5.0).unwrap().unwrap();
+        assert_eq!(request.status, FundingStatus::Pending);
+        assert_eq!(request.amount_usd, 95.0);
+        // Only one request in flight
+        assert!(funding.check(5.0).unwrap().is_none());
+
+        let queue = FundingQueue::new(data_dir);
+        queue
+            .set_status(&request.id, FundingStatus::Approved)
+            .unwrap();
+        assert!(queue
+            .set_status(&request.id, FundingStatus::Rejected)
+            .is_err());
+
+        funding.settle(100.0).unwrap();
+        assert_eq!(queue.load().unwrap()[0].status, FundingStatus::Approved);
+
+        funding.process().await.unwrap();
+        let processed = &queue.load().unwrap()[0];
+        assert_eq!(processed.status, FundingStatus::Submitted);
+        assert_eq!(processed.attempts, 1);
+        funding.settle(100.0).unwrap();
+        assert_eq!(queue.load().unwrap()[0].status, FundingStatus::Completed);
+        // The lock is released after each update
+        assert!(!dir.join("funding_requests.json.lock").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
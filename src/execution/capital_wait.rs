@@ -0,0 +1,275 @@
+//! Entries waiting for capital about to be released
+//!
+//! With free collateral short, a signal is skipped - even when resolved positions are being
+//! redeemed and would fund it. When the redemptions expected to land within
+//! `CAPITAL_WAIT_HORIZON_SECS` cover what's missing, a signal with enough edge waits here
+//! instead, and is placed as soon as the proceeds land, at its own prices or better, unless it
+//! has waited `CAPITAL_WAIT_MAX_SECS`. Markets merely ending don't count: their payout only
+//! comes back once they resolve and are redeemed.
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::CapitalWaitConfig;
+
+/// Order a waiting signal places
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeferredAction {
+    Snipe {
+        side: String,
+        price: f64,
+        size_usd: f64,
+        confidence: f64,
+    },
+    Arbitrage {
+        yes_price: f64,
+        no_price: f64,
+        size_usd: f64,
+        expected_profit_bps: i32,
+    },
+}
+
+impl DeferredAction {
+    /// Snipes: return on the price if the side wins; arbitrage: the expected profit
+    pub fn edge_bps(&self) -> f64 {
+        match self {
+            DeferredAction::Snipe { price, .. } if *price > 0.0 => (1.0 - price) / price * 10_000.0,
+            DeferredAction::Snipe { .. } => 0.0,
+            DeferredAction::Arbitrage {
+                expected_profit_bps,
+                ..
+            } => f64::from(*expected_profit_bps),
+        }
+    }
+
+    pub fn size_usd(&self) -> f64 {
+        match self {
+            DeferredAction::Snipe { size_usd, .. } | DeferredAction::Arbitrage { size_usd, .. } => {
+                *size_usd
+            }
+        }
+    }
+}
+
+/// A signal held until collateral frees up
+#[derive(Debug, Clone)]
+pub struct DeferredSignal {
+    pub market_id: String,
+    pub trade_prefix: String,
+    pub action: DeferredAction,
+    /// When the signal first waited; kept when it is deferred again
+    pub queued_at: DateTime<Utc>,
+}
+
+/// Collateral expected back: a redemption under way
+#[derive(Debug, Clone)]
+pub struct CapitalRelease {
+    pub market_id: String,
+    pub payout_usd: f64,
+    pub expected_at: DateTime<Utc>,
+}
+
+pub struct CapitalWait {
+    config: CapitalWaitConfig,
+    queue: Vec<DeferredSignal>,
+}
+
+impl CapitalWait {
+    pub fn new(config: CapitalWaitConfig) -> Self {
+        Self {
+            config,
+            queue: Vec::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn horizon(&self) -> Duration {
+        Duration::seconds(self.config.horizon_secs as i64)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Payout of the releases expected within the horizon
+    pub fn expected_release_usd(&self, releases: &[CapitalRelease], now: DateTime<Utc>) -> f64 {
+        let until = now + self.horizon();
+        releases
+            .iter()
+            .filter(|r| r.expected_at <= until)
+            .map(|r| r.payout_usd.max(0.0))
+            .sum()
+    }
+
+    /// Hold `signal`, short `shortfall_usd` of collateral, if its edge is high enough and the
+    /// releases expected within the horizon cover the shortfall. A newer signal on the same
+    /// market replaces the waiting one (keeping its wait); with the queue full, the lowest edge
+    /// makes room
+    pub fn defer(
+        &mut self,
+        mut signal: DeferredSignal,
+        shortfall_usd: f64,
+        releases: &[CapitalRelease],
+        now: DateTime<Utc>,
+    ) -> bool {
+        if !self.config.enabled || self.config.max_queued == 0 {
+            return false;
+        }
+        let edge_bps = signal.action.edge_bps();
+        if edge_bps < self.config.min_edge_bps
+            || self.expected_release_usd(releases, now) < shortfall_usd
+        {
+            return false;
+        }
+
+        if let Some(i) = self
+            .queue
+            .iter()
+            .position(|s| s.market_id == signal.market_id)
+        {
+            let replaced = self.queue.remove(i);
+            signal.queued_at = signal.queued_at.min(replaced.queued_at);
+        }
+        if self.queue.len() >= self.config.max_queued {
+            let Some((lowest, lowest_edge)) = self
+                .queue
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (i, s.action.edge_bps()))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                return false;
+            };
+            if lowest_edge >= edge_bps {
+                return false;
+            }
+            self.queue.remove(lowest);
+        }
+        self.queue.push(signal);
+        true
+    }
+
+    /// Drop the signals that waited too long; returns them
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<DeferredSignal> {
+        let max_wait = Duration::seconds(self.config.max_wait_secs as i64);
+        let (expired, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.queue)
+            .into_iter()
+            .partition(|s| now - s.queued_at > max_wait);
+        self.queue = waiting;
+        expired
+    }
+
+    /// Signals `free_usd` can fund now, best edge first; the rest keep waiting
+    pub fn release(&mut self, free_usd: f64) -> Vec<DeferredSignal> {
+        self.queue
+            .sort_by(|a, b| b.action.edge_bps().total_cmp(&a.action.edge_bps()));
+        let mut left = free_usd;
+        let mut ready = Vec::new();
+        let mut waiting = Vec::new();
+        for signal in std::mem::take(&mut self.queue) {
+            let size_usd = signal.action.size_usd();
+            if size_usd <= left {
+                left -= size_usd;
+                ready.push(signal);
+            } else {
+                waiting.push(signal);
+            }
+        }
+        self.queue = waiting;
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CapitalWaitConfig {
+        CapitalWaitConfig {
+            enabled: true,
+            min_edge_bps: 300.0,
+            horizon_secs: 600,
+            max_wait_secs: 300,
+            max_queued: 2,
+        }
+    }
+
+    fn snipe(market_id: &str, price: f64, size_usd: f64, now: DateTime<Utc>) -> DeferredSignal {
+        DeferredSignal {
+            market_id: market_id.to_string(),
+            trade_prefix: "snipe".to_string(),
+            action: DeferredAction::Snipe {
+                side: "YES".to_string(),
+                price,
+                size_usd,
+                confidence: 0.9,
+            },
+            queued_at: now,
+        }
+    }
+
+    fn release(payout_usd: f64, expected_at: DateTime<Utc>) -> CapitalRelease {
+        CapitalRelease {
+            market_id: "held".to_string(),
+            payout_usd,
+            expected_at,
+        }
+    }
+
+    #[test]
+    fn test_waits_only_for_covering_releases_and_enough_edge() {
+        let now = Utc::now();
+        let mut wait = CapitalWait::new(config());
+        let soon = [release(40.0, now + Duration::minutes(5))];
+        let late = [release(40.0, now + Duration::hours(1))];
+
+        // 0.99 is a 101 bps edge
+        assert!(!wait.defer(snipe("m1", 0.99, 20.0, now), 20.0, &soon, now));
+        assert!(!wait.defer(snipe("m1", 0.90, 20.0, now), 20.0, &late, now));
+        assert!(!wait.defer(snipe("m1", 0.90, 50.0, now), 50.0, &soon, now));
+        assert!(wait.defer(snipe("m1", 0.90, 20.0, now), 20.0, &soon, now));
+        assert_eq!(wait.len(), 1);
+    }
+
+    #[test]
+    fn test_full_queue_keeps_the_best_edges() {
+        let now = Utc::now();
+        let mut wait = CapitalWait::new(config());
+        let soon = [release(100.0, now)];
+        assert!(wait.defer(snipe("m1", 0.90, 10.0, now), 10.0, &soon, now));
+        assert!(wait.defer(snipe("m2", 0.80, 10.0, now), 10.0, &soon, now));
+        // Below both waiting edges
+        assert!(!wait.defer(snipe("m3", 0.95, 10.0, now), 10.0, &soon, now));
+        assert!(wait.defer(snipe("m4", 0.50, 10.0, now), 10.0, &soon, now));
+
+        // $15 funds the best only
+        let ready = wait.release(15.0);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].market_id, "m4");
+        assert_eq!(wait.len(), 1);
+
+        let expired = wait.expire(now + Duration::minutes(10));
+        assert_eq!(expired[0].market_id, "m2");
+        assert!(wait.is_empty());
+    }
+
+    #[test]
+    fn test_deferring_again_keeps_the_wait() {
+        let now = Utc::now();
+        let later = now + Duration::minutes(4);
+        let mut wait = CapitalWait::new(config());
+        let soon = [release(100.0, later)];
+        assert!(wait.defer(snipe("m1", 0.90, 10.0, now), 10.0, &soon, now));
+        assert!(wait.defer(snipe("m1", 0.85, 10.0, later), 10.0, &soon, later));
+        assert_eq!(wait.len(), 1);
+
+        // 6 minutes since first queued, past the 5 minute max
+        let expired = wait.expire(now + Duration::minutes(6));
+        assert_eq!(expired.len(), 1);
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::Result;
+use ethers::abi::{encode, Token};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use crate::config::{CollateralConfig, Config};
+use crate::polymarket::{Collateral, CollateralBalances};
+
+abigen!(
+    ERC20,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function allowance(address owner, address spender) external view returns (uint256)
+        function approve(address spender, uint256 amount) external returns (bool)
+    ]"#
+);
+
+/// Uniswap V3 SwapRouter on Polygon
+const SWAP_ROUTER_ADDRESS: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
+
+/// exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))
+const EXACT_INPUT_SINGLE_SELECTOR: [u8; 4] = [0x41, 0x4b, 0xf3, 0x89];
+
+/// Wait for a swap to settle before considering another one
+const SWAP_COOLDOWN: Duration = Duration::from_secs(120);
+
+/// Swaps the signer's other USDC variant into the collateral the exchange needs
+/// The output goes to the trading wallet (the Safe when a proxy is configured)
+pub struct CollateralSwapper {
+    client: Arc<SignerMiddleware<Provider<Ws>, LocalWallet>>,
+    config: CollateralConfig,
+    recipient: Address,
+    last_swap: Option<Instant>,
+}
+
+impl CollateralSwapper {
+    /// The swapper `COLLATERAL_AUTO_SWAP` enables; swaps move real funds, so live trading with
+    /// an RPC and key only
+    pub async fn connect(config: &Config) -> Option<Self> {
+        let live = !config.agent.paper_trading && !config.agent.simulation_mode;
+        let (Some(rpc), Some(pk)) = (&config.polygon_ws_rpc, &config.polygon_private_key) else {
+            return None;
+        };
+        if !config.collateral.auto_swap || !live {
+            return None;
+        }
+        match Self::new(
+            rpc,
+            pk,
+            config.polymarket.proxy_address.as_deref(),
+            config.collateral.clone(),
+        )
+        .await
+        {
+            Ok(swapper) => {
+                info!("✅ Collateral auto-swap enabled");
+                Some(swapper)
+            }
+            Err(e) => {
+                error!("❌ Failed to init CollateralSwapper: {}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn new(
+        rpc_url: &str,
+        private_key: &str,
+        trading_wallet: Option<&str>,
+        config: CollateralConfig,
+    ) -> Result<Self> {
+        let provider = Provider::<Ws>::connect(rpc_url).await?;
+        let wallet = LocalWallet::from_str(private_key)?.with_chain_id(137u64); // Polygon Mainnet ID
+        let recipient = match trading_wallet {
+            Some(addr) => Address::from_str(addr)?,
+            None => wallet.address(),
+        };
+
+        Ok(Self {
+            client: Arc::new(SignerMiddleware::new(provider, wallet)),
+            config,
+            recipient,
+            last_swap: None,
+        })
+    }
+
+    /// Signer balance of a USDC variant, in dollars
+    pub async fn balance(&self, collateral: Collateral) -> Result<f64> {
+        let token = ERC20::new(
+            Address::from_str(collateral.address())?,
+            self.client.clone(),
+        );
+        let raw = token.balance_of(self.client.address()).call().await?;
+        Ok(raw.as_u128() as f64 / 1_000_000.0)
+    }
+
+    /// Swap into `required` if the trading wallet is below the floor. Returns the tx hash
+    pub async fn top_up(
+        &mut self,
+        required: Collateral,
+        trading_balances: &CollateralBalances,
+    ) -> Result<Option<String>> {
+        if !trading_balances.is_short(required, self.config.swap_floor_usd) {
+            return Ok(None);
+        }
+        if self.last_swap.is_some_and(|t| t.elapsed() < SWAP_COOLDOWN) {
+            return Ok(None);
+        }
+
+        let source = required.other();
+        let amount = self.balance(source).await?.min(self.config.max_swap_usd);
+        if amount < 1.0 {
+            warn!(
+                "⚠️ {} below ${:.2} and no {} to swap from",
+                required.symbol(),
+                self.config.swap_floor_usd,
+                source.symbol()
+            );
+            return Ok(None);
+        }
+
+        let tx = self.swap(source, required, amount).await?;
+        self.last_swap = Some(Instant::now());
+        Ok(Some(tx))
+    }
+
+    /// Swap `amount_usd` of one USDC variant into the other through the Uniswap V3 pool
+    pub async fn swap(&self, from: Collateral, to: Collateral, amount_usd: f64) -> Result<String> {
+        let router = Address::from_str(SWAP_ROUTER_ADDRESS)?;
+        let token_in = Address::from_str(from.address())?;
+        let token_out = Address::from_str(to.address())?;
+        let amount_in = U256::from((amount_usd * 1_000_000.0) as u128);
+        // Both sides are dollar stablecoins: the minimum out is the input less the slippage budget
+        let min_out = U256::from(
+            (amount_usd * (1.0 - self.config.max_slippage_bps as f64 / 10000.0) * 1_000_000.0)
+                as u128,
+        );
+
+        info!(
+            "🔁 Swapping ${:.2} {} -> {} (min out ${:.2})",
+            amount_usd,
+            from.symbol(),
+            to.symbol(),
+            min_out.as_u128() as f64 / 1_000_000.0
+        );
+
+        // 1. Router allowance
+        let token = ERC20::new(token_in, self.client.clone());
+        let allowance = token
+            .allowance(self.client.address(), router)
+            .call()
+            .await?;
+        if allowance < amount_in {
+            let approve = token.approve(router, U256::MAX);
+            let pending = approve.send().await?;
+            info!("✅ Router approval sent: {:?}", pending.tx_hash());
+            pending.await?;
+        }
+
+        // 2. exactInputSingle
+        let deadline = U256::from(chrono::Utc::now().timestamp() as u64 + 300);
+        let params = Token::Tuple(vec![
+            Token::Address(token_in),
+            Token::Address(token_out),
+            Token::Uint(U256::from(self.config.pool_fee)),
+            Token::Address(self.recipient),
+            Token::Uint(deadline),
+            Token::Uint(amount_in),
+            Token::Uint(min_out),
+            Token::Uint(U256::zero()),
+        ]);
+        let mut data = EXACT_INPUT_SINGLE_SELECTOR.to_vec();
+        data.extend(encode(&[params]));
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(router)
+            .data(Bytes::from(data))
+            .into();
+        let pending = self.client.send_transaction(tx, None).await?;
+        let tx_hash = pending.tx_hash();
+
+        info!("✅ Swap transaction sent! Hash: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+}
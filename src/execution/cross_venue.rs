@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::clob::types::OrderType;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::polymarket::MarketInterface;
+use crate::strategies::cross_venue::CrossVenueSignal;
+use crate::venues::Venue;
+
+/// Order id of each leg of a cross-venue set (None = not placed)
+#[derive(Debug, Clone, Default)]
+pub struct CrossVenueFill {
+    pub polymarket_order: Option<String>,
+    pub kalshi_order: Option<String>,
+    /// The opposite Kalshi outcome bought when the Polymarket leg failed, and its price
+    pub kalshi_unwind: Option<(String, f64)>,
+}
+
+impl CrossVenueFill {
+    pub fn is_complete(&self) -> bool {
+        self.polymarket_order.is_some() && self.kalshi_order.is_some()
+    }
+}
+
+/// Places the two legs of a cross-venue set, one on each venue's client. Both legs are
+/// fill-or-kill: Kalshi (the thinner book) goes first. When the Polymarket leg then fails, the
+/// filled Kalshi leg (which can't be cancelled any more) is flattened by buying the opposite
+/// Kalshi outcome at its ask, like any close; only when that fails too is it left one-sided
+pub struct CrossVenueExecutor {
+    polymarket: Arc<dyn MarketInterface + Send + Sync>,
+    kalshi: Arc<dyn MarketInterface + Send + Sync>,
+}
+
+impl CrossVenueExecutor {
+    pub fn new(
+        polymarket: Arc<dyn MarketInterface + Send + Sync>,
+        kalshi: Arc<dyn MarketInterface + Send + Sync>,
+    ) -> Self {
+        Self { polymarket, kalshi }
+    }
+
+    pub fn kalshi(&self) -> &Arc<dyn MarketInterface + Send + Sync> {
+        &self.kalshi
+    }
+
+    /// Errors only when nothing was placed
+    pub async fn execute(&self, signal: &CrossVenueSignal) -> Result<CrossVenueFill> {
+        let pair = &signal.pair;
+        let (side, price, size_usd) = signal.leg(Venue::Kalshi);
+        let kalshi_side = pair.kalshi_side(side);
+        // An inverted pair buys the opposite Kalshi outcome, at the same price
+        let kalshi_order = self
+            .kalshi
+            .place_order(
+                &MarketId::from(&pair.kalshi_ticker),
+                kalshi_side,
+                size_usd,
+                price,
+                OrderType::FOK,
+            )
+            .await
+            .with_context(|| format!("Kalshi {} leg on {}", kalshi_side, pair.kalshi_ticker))?;
+        info!(
+            "✅ Kalshi {} leg placed: {} ({})",
+            kalshi_side, kalshi_order, pair.kalshi_ticker
+        );
+
+        let (side, price, size_usd) = signal.leg(Venue::Polymarket);
+        let polymarket_order = match self
+            .polymarket
+            .place_order(
+                &MarketId::from(&pair.polymarket_id),
+                side,
+                size_usd,
+                price,
+                OrderType::FOK,
+            )
+            .await
+        {
+            Ok(order_id) => {
+                info!("✅ Polymarket {} leg placed: {}", side, order_id);
+                Some(order_id)
+            }
+            Err(e) => {
+                error!(
+                    "❌ Polymarket {} leg failed, unwinding Kalshi leg {}: {}",
+                    side, kalshi_order, e
+                );
+                None
+            }
+        };
+        let kalshi_unwind = if polymarket_order.is_none() {
+            match self.unwind_kalshi(signal, kalshi_side).await {
+                Ok(unwind) => Some(unwind),
+                Err(e) => {
+                    error!(
+                        "❌ Kalshi leg {} not unwound, one-sided until resolution: {:#}",
+                        kalshi_order, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(CrossVenueFill {
+            polymarket_order,
+            kalshi_order: Some(kalshi_order),
+            kalshi_unwind,
+        })
+    }
+
+    /// Buy as many of the other Kalshi outcome as the leg bought of `kalshi_side`: the two pay
+    /// 1 per set at resolution whatever the result. Returns the order id and its price
+    async fn unwind_kalshi(
+        &self,
+        signal: &CrossVenueSignal,
+        kalshi_side: &str,
+    ) -> Result<(String, f64)> {
+        let ticker = &signal.pair.kalshi_ticker;
+        let opposite = if kalshi_side == "YES" { "NO" } else { "YES" };
+        let market = self.kalshi.get_market_details(ticker).await?;
+        let price = market
+            .ask(opposite)
+            .with_context(|| format!("No Kalshi {} ask on {}", opposite, ticker))?;
+        let order_id = self
+            .kalshi
+            .place_order(
+                &MarketId::from(ticker),
+                opposite,
+                UsdAmount::new(signal.contracts * price),
+                Price::new(price),
+                OrderType::FOK,
+            )
+            .await
+            .with_context(|| format!("Kalshi {} unwind on {}", opposite, ticker))?;
+        warn!(
+            "↩️ Kalshi leg on {} flattened: {} {} @ {:.3} ({})",
+            ticker, signal.contracts, opposite, price, order_id
+        );
+        Ok((order_id, price))
+    }
+}
@@ -1,28 +1,171 @@
-use anyhow::Result;
-use tracing::{info, warn};
-
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
+
+use crate::analytics::attribution::{origin_trade_id, strategy_of};
+use crate::clock;
+use crate::config::ExecutionHealthConfig;
+use crate::execution::anomaly::AnomalyGuard;
 use crate::execution::flashbots::FlashbotsClient;
-use crate::polymarket::{MarketData, MarketInterface};
+use crate::execution::health::ExecutorHealth;
+use crate::execution::latency_budget::LatencyBudget;
+use crate::execution::liquidation::LiquidationLadder;
+use crate::execution::marketable::marketable_price;
+use crate::execution::paper_relay::{BundleOutcome, PaperRelay};
+use crate::execution::plan::{
+    Atomicity, ExecutionPlan, ExecutionReport, ExecutionRoute, Leg, LegResult,
+};
+use crate::execution::submission::SubmissionQueue;
+use crate::failover::Failover;
+use crate::ids::{OrderId, Price, UsdAmount};
+use crate::markets::MarketRegistry;
+use crate::polymarket::{MarketData, MarketInterface, OrderRequest, OrderStatus};
+use crate::scheduling::Priority;
 use crate::strategies::risk::RiskManager;
 use crate::strategies::types::TradingDecision;
 use polymarket_client_sdk::clob::types::OrderType;
 
+/// Status of an order on `interface` and the share of it matched (0-1, 0 when unknown)
+pub async fn order_fill(
+    interface: &(dyn MarketInterface + Send + Sync),
+    order_id: &str,
+    market_id: &str,
+) -> (OrderStatus, f64) {
+    let status = interface
+        .get_order_status(order_id)
+        .await
+        .unwrap_or(OrderStatus::Unknown);
+    let filled = match status {
+        OrderStatus::Matched => 1.0,
+        OrderStatus::PartiallyFilled => interface
+            .get_open_orders(Some(market_id))
+            .await
+            .ok()
+            .and_then(|orders| orders.into_iter().find(|o| o.order_id == order_id))
+            .filter(|o| o.original_size > 0.0)
+            .map(|o| o.size_matched / o.original_size)
+            .unwrap_or(0.0),
+        _ => 0.0,
+    };
+    (status, filled)
+}
+
 pub struct Executor {
-    market_interface: Box<dyn MarketInterface>,
+    market_interface: Arc<dyn MarketInterface + Send + Sync>,
     flashbots_client: Option<FlashbotsClient>,
+    paper_relay: Option<Arc<PaperRelay>>,
+    health: Arc<ExecutorHealth>,
+    submissions: Arc<SubmissionQueue>,
+    registry: MarketRegistry,
+    anomaly_guard: Option<Arc<AnomalyGuard>>,
+    // Paper client for the strategies kept out of a live run
+    paper_interface: Option<Arc<dyn MarketInterface + Send + Sync>>,
+    paper_strategies: Vec<String>,
+    // Added to the book's price of marketable legs (MARKET_ORDER_SLIPPAGE_BPS)
+    market_order_slippage_bps: f64,
+    // Hot-standby fence: nothing is submitted while the peer may be trading
+    failover: Option<Arc<Failover>>,
 }
 
 impl Executor {
     pub fn new(
-        market_interface: Box<dyn MarketInterface>,
+        market_interface: Arc<dyn MarketInterface + Send + Sync>,
         flashbots_client: Option<FlashbotsClient>,
+        health_config: ExecutionHealthConfig,
+        registry: MarketRegistry,
     ) -> Self {
         Self {
             market_interface,
             flashbots_client,
+            paper_relay: None,
+            health: Arc::new(ExecutorHealth::new(health_config)),
+            submissions: Arc::new(SubmissionQueue::unlimited()),
+            registry,
+            anomaly_guard: None,
+            paper_interface: None,
+            paper_strategies: Vec::new(),
+            market_order_slippage_bps: 0.0,
+            failover: None,
+        }
+    }
+
+    /// Slippage budget of marketable legs on top of the price the book needs to fill them
+    pub fn set_market_order_slippage(&mut self, slippage_bps: f64) {
+        self.market_order_slippage_bps = slippage_bps.max(0.0);
+    }
+
+    /// Send the orders of `strategies` (trade id prefixes) to `interface`, a paper client,
+    /// while every other strategy keeps trading through the live one
+    pub fn set_paper_strategies(
+        &mut self,
+        interface: Arc<dyn MarketInterface + Send + Sync>,
+        strategies: Vec<String>,
+    ) {
+        self.paper_interface = Some(interface);
+        self.paper_strategies = strategies;
+    }
+
+    /// Whether `trade_id` (or a plan exiting it) belongs to a paper-only strategy
+    pub fn is_paper(&self, trade_id: &str) -> bool {
+        self.paper_interface.is_some()
+            && self
+                .paper_strategies
+                .iter()
+                .any(|s| s == strategy_of(origin_trade_id(trade_id)))
+    }
+
+    /// Client a plan's orders go through
+    fn interface_for(&self, plan_id: &str) -> &Arc<dyn MarketInterface + Send + Sync> {
+        match &self.paper_interface {
+            Some(paper) if self.is_paper(plan_id) => paper,
+            _ => &self.market_interface,
+        }
+    }
+
+    /// Client that placed `order_id`
+    fn interface_for_order(&self, order_id: &str) -> &Arc<dyn MarketInterface + Send + Sync> {
+        match &self.paper_interface {
+            Some(paper) if order_id.starts_with("paper-order-") => paper,
+            _ => &self.market_interface,
         }
     }
 
+    /// Report multi-leg plans that end up with a single leg to the anomaly guard
+    pub fn set_anomaly_guard(&mut self, guard: Arc<AnomalyGuard>) {
+        self.anomaly_guard = Some(guard);
+    }
+
+    /// Take the bundle route in paper runs through a relay that only simulates
+    pub fn set_paper_relay(&mut self, relay: Arc<PaperRelay>) {
+        self.paper_relay = Some(relay);
+    }
+
+    /// Refuse every plan while failover fences this instance
+    pub fn set_failover(&mut self, failover: Arc<Failover>) {
+        self.failover = Some(failover);
+    }
+
+    /// Limit concurrent order submissions (unlimited otherwise)
+    pub fn set_submission_queue(&mut self, queue: Arc<SubmissionQueue>) {
+        self.submissions = queue;
+    }
+
+    pub fn submission_queue(&self) -> Arc<SubmissionQueue> {
+        self.submissions.clone()
+    }
+
+    /// Stop sending real orders for the rest of the run
+    pub fn downgrade_to_paper(&self) {
+        self.market_interface.downgrade_to_paper();
+    }
+
+    /// Rolling submission health, consumed by the Sniper to back off
+    pub fn health(&self) -> Arc<ExecutorHealth> {
+        self.health.clone()
+    }
+
     /// Execute a trading decision
     pub async fn execute_trade(
         &self,
@@ -45,134 +188,493 @@ impl Executor {
             market.no_price
         };
 
-        // Calculate actual position size in dollars
-        let capital = self.market_interface.get_balance().await.unwrap_or(1000.0);
+        // Calculate actual position size in dollars; no trade while the balance is unknown
+        let capital = self
+            .market_interface
+            .get_balance()
+            .await
+            .context("Balance unavailable to size the trade")?;
         let position_size_usd = capital * decision.position_size_pct;
 
-        // Place order (regular submission - Flashbots is used for atomic bundles only)
-        let order_id = self
-            .market_interface
-            .place_order(
-                &market.id,
+        // Single leg, regular submission (Flashbots is used for atomic bundles only)
+        let plan = ExecutionPlan::new(
+            trade_id,
+            vec![Leg::new(
+                market,
                 &decision.side,
-                position_size_usd,
-                price,
+                Price::new(price),
+                UsdAmount::new(position_size_usd),
                 OrderType::GTC,
-            )
-            .await?;
+            )],
+            Atomicity::BestEffort,
+        );
+        let report = self.execute_plan(&plan, risk_manager).await?;
 
         info!(
             "✅ Order placed: {} {} @ ${:.4} (Size: ${:.2})",
             decision.side, market.question, price, position_size_usd
         );
 
-        // Register position with risk manager
-        risk_manager.add_position(
-            market.id.clone(),
-            trade_id.to_string(),
-            decision.side.clone(),
-            position_size_usd,
-            price,
-        );
-
-        Ok(order_id)
+        Ok(Self::first_order_id(&report))
     }
 
-    /// Execute atomic arbitrage bundle (YES + NO together)
-    /// This is the key method for MEV protection
-    pub async fn execute_arbitrage_bundle(
+    /// Execute a plan and register placed legs with the risk manager
+    /// Errors only when nothing was placed; partial / unwound outcomes are in the report
+    pub async fn execute_plan(
         &self,
-        market: &MarketData,
-        yes_price: f64,
-        no_price: f64,
-        size_usd: f64,
-        trade_id: &str,
+        plan: &ExecutionPlan,
         risk_manager: &mut RiskManager,
-    ) -> Result<String> {
-        // Check if Flashbots is enabled
-        if let Some(_flashbots) = &self.flashbots_client {
-            info!("⚡ Executing ATOMIC arbitrage bundle via Flashbots");
-
-            // TODO: Build actual transactions for YES and NO orders
-            // This requires:
-            // 1. Creating TypedTransaction for each order
-            // 2. Setting proper gas limits and prices
-            // 3. Signing with wallet
-
-            // For now, we'll use the regular interface and log that Flashbots would be used
-            warn!("⚠️ Flashbots bundle creation not yet implemented - using regular execution");
-            warn!("⚠️ TODO: Build TypedTransaction from Polymarket order data");
-
-            // Fallback to regular execution
-            self.execute_regular_arbitrage(
-                market,
-                yes_price,
-                no_price,
-                size_usd,
-                trade_id,
-                risk_manager,
-            )
-            .await
+    ) -> Result<ExecutionReport> {
+        let report = self.submit_plan(plan).await?;
+
+        let mut opened = false;
+        for (leg, result) in plan.legs.iter().zip(&report.legs) {
+            if result.is_placed() {
+                opened = true;
+                // Marketable legs at the asks they took, others at the price submitted
+                risk_manager.add_position(
+                    leg.market_id.to_string(),
+                    plan.leg_trade_id(leg),
+                    leg.side.clone(),
+                    leg.size_usd,
+                    Price::new(result.price),
+                );
+            }
+        }
+
+        // Persist right away: a crash must not lose the id -> market mapping of a fresh position
+        if opened {
+            let registry = self.registry.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = registry.save() {
+                    warn!("⚠️ Failed to persist market registry after fill: {}", e);
+                }
+            });
+        }
+
+        if !opened {
+            anyhow::bail!("Plan {} not executed: {}", plan.id, report.summary());
+        }
+        if !report.is_complete() {
+            warn!(
+                "⚠️ Plan {} partially executed via {:?}: {}",
+                plan.id,
+                report.route,
+                report.summary()
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Pick a submission route for a plan
+    fn choose_route(&self, plan: &ExecutionPlan) -> ExecutionRoute {
+        match plan.atomicity {
+            Atomicity::AllOrNothing if plan.legs.len() > 1 => {
+                if self.flashbots_client.is_some() || self.paper_relay.is_some() {
+                    ExecutionRoute::Bundle
+                } else {
+                    ExecutionRoute::SequentialWithUnwind
+                }
+            }
+            _ => ExecutionRoute::Batch,
+        }
+    }
+
+    /// Submit a plan's orders without touching risk state
+    async fn submit_plan(&self, plan: &ExecutionPlan) -> Result<ExecutionReport> {
+        if plan.legs.is_empty() {
+            anyhow::bail!("Plan {} has no legs", plan.id);
+        }
+        if let Some(failover) = &self.failover {
+            failover.ensure_trading()?;
+        }
+
+        let route = self.choose_route(plan);
+        let priced;
+        let plan = if plan.legs.iter().any(|l| l.max_price.is_some()) {
+            match self.price_marketable(plan).await {
+                Ok(plan) => {
+                    priced = plan;
+                    &priced
+                }
+                Err(e) => {
+                    warn!("🎯 Plan {} not submitted: {}", plan.id, e);
+                    return Ok(ExecutionReport {
+                        plan_id: plan.id.clone(),
+                        route,
+                        legs: Self::unsubmitted(plan, &e),
+                    });
+                }
+            }
         } else {
-            // No Flashbots - regular execution
-            self.execute_regular_arbitrage(
-                market,
-                yes_price,
-                no_price,
-                size_usd,
-                trade_id,
-                risk_manager,
+            plan
+        };
+        // Past its budget the signal is stale: nothing is submitted
+        if let Some(budget) = &plan.latency_budget {
+            budget.check(&plan.id, clock::now_ns())?;
+        }
+        // One slot for the whole plan, its unwind included: a second leg must not queue behind
+        // other plans while the first one is exposed
+        let _permit = match self.submissions.acquire(plan.priority).await {
+            Ok(permit) => permit,
+            Err(e) => {
+                warn!("🚦 Plan {} not submitted: {}", plan.id, e);
+                return Ok(ExecutionReport {
+                    plan_id: plan.id.clone(),
+                    route,
+                    legs: Self::unsubmitted(plan, &e.to_string()),
+                });
+            }
+        };
+        // The wait for the slot counts too
+        if let Some(budget) = &plan.latency_budget {
+            budget.check(&plan.id, clock::now_ns())?;
+        }
+        info!(
+            "📋 Executing plan {} ({} legs, ${:.2}, {:?} via {:?})",
+            plan.id,
+            plan.legs.len(),
+            plan.total_size_usd(),
+            plan.atomicity,
+            route
+        );
+
+        let mut legs = match route {
+            ExecutionRoute::Bundle if self.paper_relay.is_some() => {
+                self.submit_paper_bundle(plan).await
+            }
+            ExecutionRoute::Bundle => {
+                info!("⚡ Executing ATOMIC bundle via Flashbots");
+
+                // TODO: Build actual transactions for each leg
+                // This requires:
+                // 1. Creating TypedTransaction for each order
+                // 2. Setting proper gas limits and prices
+                // 3. Signing with wallet
+                warn!("⚠️ Flashbots bundle creation not yet implemented - using sequential execution");
+                warn!("⚠️ TODO: Build TypedTransaction from Polymarket order data");
+                self.submit_sequential(plan).await
+            }
+            ExecutionRoute::SequentialWithUnwind => self.submit_sequential(plan).await,
+            ExecutionRoute::Batch => self.submit_batch(plan).await,
+        };
+
+        if plan.atomicity == Atomicity::AllOrNothing && legs.iter().any(|l| l.order_id.is_none()) {
+            self.unwind(plan, &mut legs).await;
+        }
+        // A marketable leg fills at the asks it takes, not at its limit: no status round trip
+        // needed, a placed FOK order filled whole
+        for (leg, result) in plan.legs.iter().zip(legs.iter_mut()) {
+            if let (Some(fill_price), true) = (leg.fill_price, result.is_placed()) {
+                result.price = fill_price.get();
+            }
+        }
+
+        // Some legs placed, some not: one-sided exposure (or an unwind) instead of the plan
+        if legs.iter().any(|l| l.order_id.is_some()) && legs.iter().any(|l| l.order_id.is_none()) {
+            if let Some(guard) = &self.anomaly_guard {
+                guard.record_single_leg(&plan.legs[0].market_id);
+            }
+        }
+
+        Ok(ExecutionReport {
+            plan_id: plan.id.clone(),
+            route,
+            legs,
+        })
+    }
+
+    /// Price the marketable legs of `plan` off the live asks of their tokens
+    async fn price_marketable(&self, plan: &ExecutionPlan) -> Result<ExecutionPlan, String> {
+        let mut priced = plan.clone();
+        for leg in &mut priced.legs {
+            let Some(max_price) = leg.max_price else {
+                continue;
+            };
+            let asset_id = leg
+                .asset_id
+                .as_deref()
+                .ok_or_else(|| format!("no token id for the {} leg", leg.side))?;
+            let book = self
+                .interface_for(&plan.id)
+                .get_orderbook(asset_id)
+                .await
+                .map_err(|e| format!("{} book unavailable: {}", leg.side, e))?;
+            let price = marketable_price(
+                book.ask_levels(),
+                leg.size_usd.get(),
+                self.market_order_slippage_bps,
+                max_price.get(),
             )
-            .await
+            .map_err(|e| format!("{} leg: {}", leg.side, e))?;
+            info!(
+                "🎯 Marketable {} ${:.2}: best ask {:.4}, sweeps to {:.4} (avg {:.4}), limit {:.2} (cap {:.4})",
+                leg.side,
+                leg.size_usd,
+                price.best_ask,
+                price.sweep,
+                price.avg_price,
+                price.limit,
+                max_price
+            );
+            leg.price = Price::new(price.limit);
+            leg.fill_price = Some(Price::new(price.avg_price.min(price.limit)));
+            leg.order_type = OrderType::FOK;
+        }
+        Ok(priced)
+    }
+
+    /// All legs in one multi-order submission
+    async fn submit_batch(&self, plan: &ExecutionPlan) -> Vec<LegResult> {
+        let requests: Vec<OrderRequest> = plan
+            .legs
+            .iter()
+            .map(|leg| OrderRequest {
+                market_id: leg.market_id.clone(),
+                side: leg.side.clone(),
+                size_usd: leg.size_usd,
+                price: leg.price,
+                order_type: leg.order_type.clone(),
+            })
+            .collect();
+
+        let paper = self.is_paper(&plan.id);
+        let started = Instant::now();
+        let results = self.interface_for(&plan.id).place_orders(&requests).await;
+        // Batched legs share the call latency; paper fills say nothing about the exchange
+        let latency = started.elapsed();
+        if !paper {
+            for result in &results {
+                self.health.record(result.is_ok(), latency);
+            }
+        }
+
+        plan.legs
+            .iter()
+            .zip(results)
+            .map(|(leg, result)| Self::leg_result(leg, result))
+            .collect()
+    }
+
+    /// One leg at a time; stops at the first failure when the plan is all-or-nothing
+    async fn submit_sequential(&self, plan: &ExecutionPlan) -> Vec<LegResult> {
+        let mut results = Vec::with_capacity(plan.legs.len());
+        let mut failed = false;
+        let paper = self.is_paper(&plan.id);
+
+        for leg in &plan.legs {
+            if failed {
+                results.push(LegResult {
+                    side: leg.side.clone(),
+                    price: leg.price.get(),
+                    size_usd: leg.size_usd.get(),
+                    order_id: None,
+                    error: None,
+                    unwound: false,
+                });
+                continue;
+            }
+
+            let started = Instant::now();
+            let result = self
+                .interface_for(&plan.id)
+                .place_order(
+                    &leg.market_id,
+                    &leg.side,
+                    leg.size_usd,
+                    leg.price,
+                    leg.order_type.clone(),
+                )
+                .await;
+            if !paper {
+                self.health.record(result.is_ok(), started.elapsed());
+            }
+            let result = Self::leg_result(leg, result);
+            if let Some(order_id) = &result.order_id {
+                info!("✅ {} order placed: {}", leg.side, order_id);
+            } else {
+                failed = plan.atomicity == Atomicity::AllOrNothing;
+            }
+            results.push(result);
+        }
+
+        results
+    }
+
+    /// Bundle route of a paper run: the legs are placed only when the simulated bundle lands,
+    /// all of them or none
+    async fn submit_paper_bundle(&self, plan: &ExecutionPlan) -> Vec<LegResult> {
+        let outcome = match &self.paper_relay {
+            Some(relay) => relay.run(plan),
+            None => BundleOutcome::Rejected("no relay".to_string()),
+        };
+        let error = match outcome {
+            BundleOutcome::Included => return self.submit_sequential(plan).await,
+            BundleOutcome::Rejected(reason) => format!("bundle rejected: {}", reason),
+            BundleOutcome::NotIncluded => "bundle not included".to_string(),
+        };
+        Self::unsubmitted(plan, &error)
+    }
+
+    /// Every leg of a plan failed with `error` before reaching the exchange
+    fn unsubmitted(plan: &ExecutionPlan, error: &str) -> Vec<LegResult> {
+        plan.legs
+            .iter()
+            .map(|leg| LegResult {
+                side: leg.side.clone(),
+                price: leg.price.get(),
+                size_usd: leg.size_usd.get(),
+                order_id: None,
+                error: Some(error.to_string()),
+                unwound: false,
+            })
+            .collect()
+    }
+
+    /// Take back the legs that did get placed so an all-or-nothing plan leaves no one-sided
+    /// exposure: cancel what rests, then sell back what already filled (as the same number of
+    /// the other outcome at its ask)
+    async fn unwind(&self, plan: &ExecutionPlan, legs: &mut [LegResult]) {
+        for (planned, leg) in plan.legs.iter().zip(legs.iter_mut()) {
+            let Some(order_id) = leg.order_id.clone() else {
+                continue;
+            };
+            let interface = self.interface_for_order(&order_id);
+            // A matched order can't be cancelled: the fill is what's left to flatten
+            if let Err(e) = interface.cancel_order(&order_id).await {
+                debug!("Cancel of {} leg {} failed: {}", leg.side, order_id, e);
+            }
+            let filled = self.filled_fraction(&order_id, &planned.market_id).await;
+            if filled <= 0.0 {
+                info!("↩️ Unwound {} leg {}", leg.side, order_id);
+                leg.unwound = true;
+                continue;
+            }
+
+            let shares = filled * leg.size_usd / leg.price.max(0.01);
+            let opposite_side = if leg.side == "YES" { "NO" } else { "YES" };
+            let cached_ask = self
+                .registry
+                .get(&planned.market_id)
+                .and_then(|market| market.ask(opposite_side));
+            let mut result = Err(anyhow::anyhow!("no {} ask", opposite_side));
+            let mut price = 0.0;
+            // The cached ask first, then the live book's: a stale ask must not leave it naked
+            for attempt in 0..2 {
+                let ask = match (attempt, cached_ask) {
+                    (0, Some(ask)) => ask,
+                    (0, None) => continue,
+                    _ => match self
+                        .live_ask(interface, &planned.market_id, opposite_side)
+                        .await
+                    {
+                        Some(ask) => ask,
+                        None => break,
+                    },
+                };
+                price = ask;
+                let started = Instant::now();
+                result = interface
+                    .place_order(
+                        &planned.market_id,
+                        opposite_side,
+                        UsdAmount::new(shares * price),
+                        Price::new(price),
+                        OrderType::FOK,
+                    )
+                    .await;
+                if !self.is_paper(&plan.id) {
+                    self.health.record(result.is_ok(), started.elapsed());
+                }
+                if result.is_ok() {
+                    break;
+                }
+            }
+            match result {
+                Ok(flatten_id) => {
+                    info!(
+                        "↩️ Unwound {} leg {}: {:.2} filled shares sold back @ {:.4} ({})",
+                        leg.side,
+                        order_id,
+                        shares,
+                        1.0 - price,
+                        flatten_id
+                    );
+                    leg.unwound = true;
+                }
+                Err(e) => error!(
+                    "❌ Failed to flatten {} leg {} (position remains): {}",
+                    leg.side, order_id, e
+                ),
+            }
         }
     }
 
-    /// Regular (non-atomic) arbitrage execution
-    async fn execute_regular_arbitrage(
+    /// Best ask of an outcome on its live book, None when it can't be read
+    async fn live_ask(
         &self,
-        market: &MarketData,
-        yes_price: f64,
-        no_price: f64,
-        size_usd: f64,
-        trade_id: &str,
-        risk_manager: &mut RiskManager,
-    ) -> Result<String> {
-        info!("🔄 Executing regular arbitrage (non-atomic)");
+        interface: &Arc<dyn MarketInterface + Send + Sync>,
+        market_id: &str,
+        side: &str,
+    ) -> Option<f64> {
+        let market = self.registry.get(market_id)?;
+        // asset_ids follow the [NO, YES] convention
+        let asset_id = match side {
+            "YES" => market.asset_ids.get(1),
+            _ => market.asset_ids.first(),
+        }?;
+        interface.get_orderbook(asset_id).await.ok()?.best_ask()
+    }
 
-        // Execute YES order
-        let yes_order_id = self
-            .market_interface
-            .place_order(&market.id, "YES", size_usd / 2.0, yes_price, OrderType::GTC)
-            .await?;
+    fn first_order_id(report: &ExecutionReport) -> String {
+        report
+            .placed()
+            .next()
+            .map(|(_, order_id, _)| order_id.to_string())
+            .unwrap_or_default()
+    }
 
-        info!("✅ YES order placed: {}", yes_order_id);
+    fn leg_result(leg: &Leg, result: Result<String>) -> LegResult {
+        let (order_id, error) = match result {
+            Ok(id) => (Some(OrderId::from(id)), None),
+            Err(e) => {
+                error!("❌ {} leg failed: {}", leg.side, e);
+                (None, Some(e.to_string()))
+            }
+        };
+        LegResult {
+            side: leg.side.clone(),
+            price: leg.price.get(),
+            size_usd: leg.size_usd.get(),
+            order_id,
+            error,
+            unwound: false,
+        }
+    }
 
-        // Execute NO order
-        let no_order_id = self
-            .market_interface
-            .place_order(&market.id, "NO", size_usd / 2.0, no_price, OrderType::GTC)
-            .await?;
-
-        info!("✅ NO order placed: {}", no_order_id);
-
-        // Register positions
-        risk_manager.add_position(
-            market.id.clone(),
-            format!("{}_YES", trade_id),
-            "YES".to_string(),
-            size_usd / 2.0,
-            yes_price,
-        );
+    /// Buy an outcome against an existing position (or unwind such a hedge). The caller
+    /// registers it with the risk manager as a hedge, apart from the position it covers
+    pub async fn execute_hedge(
+        &self,
+        market: &MarketData,
+        side: &str,
+        price: Price,
+        size_usd: UsdAmount,
+        trade_id: &str,
+    ) -> Result<String> {
+        let plan = ExecutionPlan::snipe(trade_id, market, side, price, size_usd)
+            .with_priority(Priority::Protection);
+        let report = self.submit_plan(&plan).await?;
+        if !report.is_complete() {
+            anyhow::bail!("Hedge order failed: {}", report.summary());
+        }
 
-        risk_manager.add_position(
-            market.id.clone(),
-            format!("{}_NO", trade_id),
-            "NO".to_string(),
-            size_usd / 2.0,
-            no_price,
+        info!(
+            "🛡️ Hedge order placed: {} {} @ ${:.4} (Size: ${:.2})",
+            side, market.question, price, size_usd
         );
-
-        Ok(format!("YES:{},NO:{}", yes_order_id, no_order_id))
+        Ok(Self::first_order_id(&report))
     }
 
     /// Close a position
@@ -200,16 +702,22 @@ impl Executor {
 
         // Place closing order (opposite side)
         let opposite_side = if side == "YES" { "NO" } else { "YES" };
-        let _order_id = self
-            .market_interface
-            .place_order(
-                &market.id,
+        let plan = ExecutionPlan::new(
+            format!("close_{}", position.trade_id),
+            vec![Leg::new(
+                market,
                 opposite_side,
-                position.size_usd,
-                price,
+                Price::new(price),
+                UsdAmount::new(position.size_usd),
                 OrderType::FOK,
-            )
-            .await?;
+            )],
+            Atomicity::BestEffort,
+        )
+        .with_priority(Priority::Protection);
+        let report = self.submit_plan(&plan).await?;
+        if !report.is_complete() {
+            anyhow::bail!("Closing order failed: {}", report.summary());
+        }
 
         // Remove position from risk manager
         risk_manager.remove_position(&market.id);
@@ -218,41 +726,215 @@ impl Executor {
         Ok(())
     }
 
-    /// Execute a "Snipe" trade (Single sided, explicit USD size)
+    /// Sell `shares` of one leg of an arbitrage pair into its bid `price`. The legs share the
+    /// market's single risk position, so the caller books the sale. Returns the order id
+    pub async fn sell_leg(
+        &self,
+        market: &MarketData,
+        side: &str,
+        price: Price,
+        shares: f64,
+        trade_id: &str,
+    ) -> Result<String> {
+        let price = price.get();
+        info!(
+            "🔴 Selling {:.2} {} shares of {} @ {:.4}",
+            shares, side, market.question, price
+        );
+
+        // Same order as a close: as many of the opposite side, whose ask is 1 minus our bid
+        let opposite_side = if side == "YES" { "NO" } else { "YES" };
+        let opposite_price = (1.0 - price).clamp(0.0, 1.0);
+        let plan = ExecutionPlan::new(
+            format!("sell_{}", trade_id),
+            vec![Leg::new(
+                market,
+                opposite_side,
+                Price::new(opposite_price),
+                UsdAmount::new(shares * opposite_price),
+                OrderType::FOK,
+            )],
+            Atomicity::BestEffort,
+        )
+        .with_priority(Priority::Protection);
+        let report = self.submit_plan(&plan).await?;
+        report
+            .legs
+            .first()
+            .and_then(|l| l.order_id.clone())
+            .map(String::from)
+            .ok_or_else(|| anyhow::anyhow!("Sell order failed: {}", report.summary()))
+    }
+
+    /// Advance a liquidation ladder: book what the resting order filled, then re-price the
+    /// remainder on the current rung. Returns true once the position is fully worked out
+    pub async fn step_liquidation(
+        &self,
+        ladder: &mut LiquidationLadder,
+        market: &MarketData,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let ask = ladder.exit_ask(market);
+        if ask <= 0.0 {
+            anyhow::bail!("No exit price for {}", market.question);
+        }
+        let rung = ladder.rung_at(now, ask);
+        ladder.last_step = Some(now);
+
+        // 1. Fills of the resting order; keep it if it's still on the current rung
+        if let Some(resting) = ladder.resting.clone() {
+            let filled = self.filled_fraction(&resting.order_id, &market.id).await;
+            if filled >= 1.0 {
+                ladder.record_fill(1.0);
+            } else if resting.rung == rung.index {
+                return Ok(false);
+            } else {
+                if let Err(e) = self
+                    .interface_for_order(&resting.order_id)
+                    .cancel_order(&resting.order_id)
+                    .await
+                {
+                    warn!(
+                        "⚠️ Failed to cancel ladder order {}: {}",
+                        resting.order_id, e
+                    );
+                    return Ok(false);
+                }
+                ladder.record_fill(filled);
+            }
+        }
+        if ladder.is_done() {
+            return Ok(true);
+        }
+
+        // 2. Next rung: rest passively, cross the spread once the deadline is reached
+        let order_type = if rung.is_final {
+            OrderType::FOK
+        } else {
+            OrderType::GTC
+        };
+        let plan = ExecutionPlan::new(
+            format!("liq_{}_{}", ladder.trade_id, rung.index),
+            vec![Leg::new(
+                market,
+                ladder.exit_side(),
+                Price::new(rung.price),
+                // The shares still open, bought on the exit side at the rung's price
+                UsdAmount::new(ladder.remaining_shares * rung.price),
+                order_type,
+            )],
+            Atomicity::BestEffort,
+        )
+        .with_priority(Priority::Protection);
+        let report = self.submit_plan(&plan).await?;
+
+        match report
+            .legs
+            .first()
+            .and_then(|l| l.order_id.clone().map(String::from))
+        {
+            Some(order_id) => {
+                info!(
+                    "🪜 Ladder rung {}{} for {}: {} {:.2} shares @ {:.2}",
+                    rung.index,
+                    if rung.is_final { " (final)" } else { "" },
+                    market.question,
+                    ladder.exit_side(),
+                    ladder.remaining_shares,
+                    rung.price
+                );
+                ladder.resting = Some(ladder.order_at(order_id, rung.index, rung.price));
+                // Fill-or-kill accepted means filled
+                if rung.is_final {
+                    ladder.record_fill(1.0);
+                }
+            }
+            None => warn!(
+                "⚠️ Ladder rung {} for {} not placed: {}",
+                rung.index,
+                market.question,
+                report.summary()
+            ),
+        }
+
+        Ok(ladder.is_done())
+    }
+
+    /// Cancel a resting order
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        self.interface_for_order(order_id)
+            .cancel_order(order_id)
+            .await
+    }
+
+    /// Share of an order that has matched (0-1), 0 when unknown
+    pub async fn filled_fraction(&self, order_id: &str, market_id: &str) -> f64 {
+        order_fill(
+            self.interface_for_order(order_id).as_ref(),
+            order_id,
+            market_id,
+        )
+        .await
+        .1
+    }
+
+    /// Client that placed `order_id`, to read it off the caller's task
+    pub fn client_for_order(&self, order_id: &str) -> Arc<dyn MarketInterface + Send + Sync> {
+        self.interface_for_order(order_id).clone()
+    }
+
+    /// Buy an outcome now at the book's price, never above `max_price`. Returns the order id and
+    /// the price it filled at
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_market_order(
+        &self,
+        market: &MarketData,
+        side: &str,
+        max_price: Price,
+        size_usd: UsdAmount,
+        trade_id: &str,
+        risk_manager: &mut RiskManager,
+        latency_budget: Option<LatencyBudget>,
+    ) -> Result<(String, f64)> {
+        let plan = ExecutionPlan::market_order(trade_id, market, side, max_price, size_usd)
+            .with_latency_budget(latency_budget);
+        let report = self.execute_plan(&plan, risk_manager).await?;
+        let price = report.legs.first().map_or(max_price.get(), |l| l.price);
+
+        info!(
+            "✅ Market order placed: {} {} @ ${:.4} (cap ${:.4}, Size: ${:.2})",
+            side, market.question, price, max_price, size_usd
+        );
+        Ok((Self::first_order_id(&report), price))
+    }
+
+    /// Execute a "Snipe" trade (Single sided, explicit USD size), not submitted once over
+    /// `latency_budget`
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute_snipe(
         &self,
         market: &MarketData,
         side: &str,
-        price: f64,
-        size_usd: f64,
+        price: Price,
+        size_usd: UsdAmount,
         trade_id: &str,
         risk_manager: &mut RiskManager,
+        latency_budget: Option<LatencyBudget>,
     ) -> Result<String> {
         info!(
             "🎯 Executing SNIPE for market: {} ({})",
             market.question, side
         );
 
-        // Place order
-        let order_id = self
-            .market_interface
-            .place_order(&market.id, side, size_usd, price, OrderType::FOK)
-            .await?;
+        let plan = ExecutionPlan::snipe(trade_id, market, side, price, size_usd)
+            .with_latency_budget(latency_budget);
+        let report = self.execute_plan(&plan, risk_manager).await?;
 
         info!(
             "✅ Snipe Order placed: {} {} @ ${:.4} (Size: ${:.2})",
             side, market.question, price, size_usd
         );
 
-        // Register position with risk manager
-        risk_manager.add_position(
-            market.id.clone(),
-            trade_id.to_string(),
-            side.to_string(),
-            size_usd,
-            price,
-        );
-
-        Ok(order_id)
+        Ok(Self::first_order_id(&report))
     }
 }
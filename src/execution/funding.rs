@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const FUNDING_REQUESTS_FILE: &str = "funding_requests.json";
+/// How long a writer waits for the other process to release the request file
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+/// A lock older than this was left by a process that died mid-write
+const STALE_LOCK: Duration = Duration::from_secs(30);
+
+/// Where top-ups come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FundingSource {
+    /// Signer's USDC on Ethereum mainnet, bridged over the PoS bridge
+    Mainnet,
+    /// Exchange withdrawal done by the operator (no API access: always manual)
+    Cex,
+}
+
+impl FundingSource {
+    pub fn parse(source: &str) -> Option<Self> {
+        match source.trim().to_lowercase().as_str() {
+            "mainnet" | "ethereum" => Some(FundingSource::Mainnet),
+            "cex" => Some(FundingSource::Cex),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FundingStatus {
+    /// Waiting for the operator (`funding approve <id>`)
+    Pending,
+    /// Cleared to execute
+    Approved,
+    Rejected,
+    /// Being executed; left behind if the bot stopped mid-deposit (checked on the next round)
+    Sending,
+    /// Bridge deposit sent, or CEX withdrawal acknowledged
+    Submitted,
+    /// Balance back above the floor
+    Completed,
+    Failed,
+}
+
+impl FundingStatus {
+    /// Still blocks new requests from being raised
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            FundingStatus::Pending
+                | FundingStatus::Approved
+                | FundingStatus::Sending
+                | FundingStatus::Submitted
+        )
+    }
+}
+
+/// One top-up of the Polygon trading wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRequest {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub source: FundingSource,
+    pub amount_usd: f64,
+    /// Trading balance when the request was raised
+    pub balance_usd: f64,
+    /// Polygon wallet to fund
+    pub destination: String,
+    pub status: FundingStatus,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+    /// Executions tried so far
+    #[serde(default)]
+    pub attempts: u32,
+    /// Backoff after a failed attempt
+    #[serde(default)]
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+/// Funding requests persisted in `data_dir/funding_requests.json`
+/// Shared between the running bot and the `funding` CLI (the operator approves there)
+pub struct FundingQueue {
+    path: PathBuf,
+}
+
+impl FundingQueue {
+    pub fn new(data_dir: &str) -> Self {
+        Self {
+            path: funding_requests_path(data_dir),
+        }
+    }
+
+    pub fn load(&self) -> Result<Vec<FundingRequest>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {:?}", self.path))?;
+        serde_json::from_str(&raw).context("Invalid funding requests file")
+    }
+
+    /// Read-modify-write under the file lock; the CLI and the bot both update this file
+    pub fn update<T>(
+        &self,
+        apply: impl FnOnce(&mut Vec<FundingRequest>) -> Result<T>,
+    ) -> Result<T> {
+        let _lock = self.lock()?;
+        let mut requests = self.load()?;
+        let result = apply(&mut requests)?;
+        self.save(&requests)?;
+        Ok(result)
+    }
+
+    /// Exclusive `funding_requests.json.lock`, removed on drop
+    fn lock(&self) -> Result<QueueLock> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let path = self.path.with_extension("json.lock");
+        let started = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(QueueLock { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .is_ok_and(|at| at.elapsed().unwrap_or_default() > STALE_LOCK);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                    } else if started.elapsed() > LOCK_TIMEOUT {
+                        anyhow::bail!("{:?} is held by another process", path);
+                    } else {
+                        std::thread::sleep(Duration::from_millis(20));
+                    }
+                }
+                Err(e) => return Err(e).with_context(|| format!("Failed to lock {:?}", path)),
+            }
+        }
+    }
+
+    fn save(&self, requests: &[FundingRequest]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(requests)?)
+            .with_context(|| format!("Failed to write {:?}", tmp))?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("Failed to replace {:?}", self.path))
+    }
+
+    /// Operator decision on a pending request
+    pub fn set_status(&self, id: &str, status: FundingStatus) -> Result<FundingRequest> {
+        self.update(|requests| {
+            let request = requests
+                .iter_mut()
+                .find(|r| r.id == id)
+                .ok_or_else(|| anyhow::anyhow!("Funding request {} not found", id))?;
+            if request.status != FundingStatus::Pending {
+                anyhow::bail!(
+                    "Funding request {} is {:?}, not Pending",
+                    id,
+                    request.status
+                );
+            }
+            request.status = status;
+            request.updated_at = Utc::now();
+            Ok(request.clone())
+        })
+    }
+}
+
+struct QueueLock {
+    path: PathBuf,
+}
+
+impl Drop for QueueLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Path of the funding request file inside a data dir
+pub fn funding_requests_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(FUNDING_REQUESTS_FILE)
+}
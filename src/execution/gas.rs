@@ -0,0 +1,230 @@
+//! Gas-aware on-chain actions
+//!
+//! A redemption costs the same Polygon gas whatever it collects, so small positions can cost
+//! more to redeem than they pay. Resolved positions are sorted into redemptions worth their
+//! gas, small ones that wait until enough of them add up to `REDEEM_BATCH_MIN_USD`, and losing
+//! ones that hold nothing to collect and are released without a transaction. Nothing waits
+//! longer than `REDEEM_MAX_WAIT_SECS`: then a position is redeemed if it pays more than its gas,
+//! else written off.
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::GasConfig;
+use crate::strategies::risk::Position;
+
+/// Cost of one transaction at a gas price
+#[derive(Debug, Clone, Copy)]
+pub struct GasCost {
+    pub units: u64,
+    pub price_gwei: f64,
+    /// USD per POL
+    pub native_token_usd: f64,
+}
+
+impl GasCost {
+    pub fn usd(&self) -> f64 {
+        self.units as f64 * self.price_gwei * 1e-9 * self.native_token_usd
+    }
+}
+
+/// A resolved position and what redeeming it pays out
+#[derive(Debug, Clone)]
+pub struct RedemptionCandidate {
+    pub market_id: String,
+    /// None when the condition's payouts couldn't be read
+    pub payout_usd: Option<f64>,
+    pub resolved_at: DateTime<Utc>,
+}
+
+impl RedemptionCandidate {
+    /// The position's shares at the condition's payout per share, (YES, NO): 1 / 0 for a
+    /// winner, fractions for a split resolution
+    pub fn new(
+        position: &Position,
+        payout_per_share: Option<(f64, f64)>,
+        resolved_at: DateTime<Utc>,
+    ) -> Self {
+        let shares = if position.entry_price > 0.0 {
+            position.size_usd / position.entry_price
+        } else {
+            0.0
+        };
+        let payout_usd = payout_per_share.map(|(yes, no)| {
+            if position.side.eq_ignore_ascii_case("YES") {
+                shares * yes
+            } else {
+                shares * no
+            }
+        });
+        Self {
+            market_id: position.market_id.clone(),
+            payout_usd,
+            resolved_at,
+        }
+    }
+
+    /// What the margin view expects back: nothing while the payout is unknown
+    pub fn expected_usd(&self) -> f64 {
+        self.payout_usd.unwrap_or(0.0)
+    }
+}
+
+/// What to do with this round's resolved positions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RedemptionPlan {
+    /// Redeem on-chain now
+    pub redeem: Vec<String>,
+    /// Losing positions: nothing to collect, close them without a transaction
+    pub release: Vec<String>,
+    /// Kept for a later round: small ones until the batch fills, not worth the gas yet, or
+    /// whose payout couldn't be read
+    pub waiting: Vec<String>,
+    /// Waited `REDEEM_MAX_WAIT_SECS` and still not worth the gas: closed as a loss, the tokens
+    /// left in the wallet
+    pub written_off: Vec<String>,
+}
+
+/// Sort resolved positions by whether they are worth their redemption gas
+pub fn plan_redemptions(
+    candidates: &[RedemptionCandidate],
+    gas: GasCost,
+    config: &GasConfig,
+    now: DateTime<Utc>,
+) -> RedemptionPlan {
+    let mut plan = RedemptionPlan::default();
+    if !config.aware_redemption {
+        plan.redeem = candidates.iter().map(|c| c.market_id.clone()).collect();
+        return plan;
+    }
+
+    let min_payout = gas.usd() * config.min_value_multiple;
+    let max_wait = i64::try_from(config.redeem_max_wait_secs)
+        .ok()
+        .and_then(Duration::try_seconds)
+        .unwrap_or(Duration::MAX);
+    let mut small: Vec<(&RedemptionCandidate, f64)> = Vec::new();
+    for candidate in candidates {
+        let overdue = now - candidate.resolved_at >= max_wait;
+        let market_id = candidate.market_id.clone();
+        match candidate.payout_usd {
+            Some(payout) if payout <= 0.0 => plan.release.push(market_id),
+            // Redeeming finds out what it pays
+            None if overdue => plan.redeem.push(market_id),
+            None => plan.waiting.push(market_id),
+            Some(payout) if overdue && payout > gas.usd() => plan.redeem.push(market_id),
+            Some(_) if overdue => plan.written_off.push(market_id),
+            Some(payout) if payout < min_payout => plan.waiting.push(market_id),
+            Some(payout) if payout < config.batch_min_value_usd => small.push((candidate, payout)),
+            Some(_) => plan.redeem.push(market_id),
+        }
+    }
+
+    let batch_value: f64 = small.iter().map(|(_, payout)| payout).sum();
+    let batch = small.into_iter().map(|(c, _)| c.market_id.clone());
+    if batch_value >= config.batch_min_value_usd {
+        plan.redeem.extend(batch);
+    } else {
+        plan.waiting.extend(batch);
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(aware_redemption: bool) -> GasConfig {
+        GasConfig {
+            aware_redemption,
+            redeem_gas_units: 200_000,
+            fallback_gas_price_gwei: 50.0,
+            native_token_usd: 0.5,
+            min_value_multiple: 3.0,
+            batch_min_value_usd: 5.0,
+            redeem_max_wait_secs: 86_400,
+            redeem_max_in_flight: 20,
+            redeem_receipt_timeout_secs: 120,
+        }
+    }
+
+    fn candidate(market_id: &str, payout_usd: f64) -> RedemptionCandidate {
+        RedemptionCandidate {
+            market_id: market_id.to_string(),
+            payout_usd: Some(payout_usd),
+            resolved_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_redemptions_weighed_against_gas() {
+        // 200k gas at 100 gwei, POL at $0.50: $0.01 per redemption
+        let gas = GasCost {
+            units: 200_000,
+            price_gwei: 100.0,
+            native_token_usd: 0.5,
+        };
+        assert!((gas.usd() - 0.01).abs() < 1e-12);
+
+        let candidates = vec![
+            candidate("big", 40.0),
+            candidate("lost", 0.0),
+            candidate("dust", 0.02),
+            candidate("small", 2.0),
+        ];
+        let now = Utc::now();
+        let plan = plan_redemptions(&candidates, gas, &config(true), now);
+        assert_eq!(plan.redeem, vec!["big"]);
+        assert_eq!(plan.release, vec!["lost"]);
+        assert_eq!(plan.waiting, vec!["dust", "small"]);
+
+        // Enough small ones to fill the batch go out together
+        let candidates = vec![candidate("a", 2.0), candidate("b", 3.5)];
+        let plan = plan_redemptions(&candidates, gas, &config(true), now);
+        assert_eq!(plan.redeem, vec!["a", "b"]);
+
+        let plan = plan_redemptions(&[candidate("lost", 0.0)], gas, &config(false), now);
+        assert_eq!(plan.redeem, vec!["lost"]);
+    }
+
+    #[test]
+    fn test_waiting_redemptions_age_out() {
+        let gas = GasCost {
+            units: 200_000,
+            price_gwei: 100.0,
+            native_token_usd: 0.5,
+        };
+        let unknown = RedemptionCandidate {
+            payout_usd: None,
+            ..candidate("unknown", 0.0)
+        };
+        let candidates = vec![candidate("dust", 0.005), candidate("small", 0.02), unknown];
+        let plan = plan_redemptions(&candidates, gas, &config(true), Utc::now());
+        assert_eq!(plan.waiting, vec!["dust", "small", "unknown"]);
+
+        // A day on: what pays more than its gas goes out, the rest is written off
+        let later = Utc::now() + Duration::days(1);
+        let plan = plan_redemptions(&candidates, gas, &config(true), later);
+        assert_eq!(plan.redeem, vec!["small", "unknown"]);
+        assert_eq!(plan.written_off, vec!["dust"]);
+        assert!(plan.waiting.is_empty());
+    }
+
+    #[test]
+    fn test_candidate_valued_at_the_payouts() {
+        let position = Position {
+            market_id: "m1".to_string(),
+            trade_id: "t1".to_string(),
+            side: "YES".to_string(),
+            size_usd: 50.0,
+            entry_price: 0.5,
+            timestamp: 0,
+            realized_pnl: 0.0,
+            config_hash: None,
+        };
+        let value = |payouts| RedemptionCandidate::new(&position, payouts, Utc::now()).payout_usd;
+        assert_eq!(value(Some((1.0, 0.0))), Some(100.0));
+        assert_eq!(value(Some((0.0, 1.0))), Some(0.0));
+        assert_eq!(value(Some((0.3, 0.7))), Some(30.0));
+        // Unreadable payouts aren't guessed
+        assert_eq!(value(None), None);
+    }
+}
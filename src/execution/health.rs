@@ -0,0 +1,273 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::config::ExecutionHealthConfig;
+
+/// Executor condition as seen by the strategies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthStatus {
+    Healthy,
+    /// Elevated errors or latency: signals are throttled
+    Degraded,
+    /// Error storm (rate limit, auth failure...): signal generation paused
+    Paused,
+}
+
+/// Rolling view of recent order submissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthSnapshot {
+    pub status: HealthStatus,
+    pub samples: usize,
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+    /// Seconds until a pause ends (0 when not paused)
+    pub paused_for_secs: u64,
+}
+
+struct Sample {
+    at: Instant,
+    ok: bool,
+    latency: Duration,
+}
+
+struct HealthState {
+    samples: VecDeque<Sample>,
+    status: HealthStatus,
+    paused_until: Option<Instant>,
+    last_admitted: Option<Instant>,
+    /// When the current degraded spell began
+    degraded_since: Option<Instant>,
+}
+
+/// Tracks order submission outcomes and tells the Sniper when to back off
+pub struct ExecutorHealth {
+    config: ExecutionHealthConfig,
+    state: Mutex<HealthState>,
+}
+
+impl ExecutorHealth {
+    pub fn new(config: ExecutionHealthConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(HealthState {
+                samples: VecDeque::new(),
+                status: HealthStatus::Healthy,
+                paused_until: None,
+                last_admitted: None,
+                degraded_since: None,
+            }),
+        }
+    }
+
+    /// Record one order submission
+    pub fn record(&self, ok: bool, latency: Duration) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state.samples.push_back(Sample {
+            at: now,
+            ok,
+            latency,
+        });
+        self.evaluate(&mut state, now);
+    }
+
+    /// Should a new signal be generated right now?
+    /// Healthy: always. Degraded: at most one per `degraded_min_interval_ms`. Paused: never
+    pub fn admit(&self) -> bool {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        self.evaluate(&mut state, now);
+
+        match state.status {
+            HealthStatus::Healthy => true,
+            HealthStatus::Paused => false,
+            HealthStatus::Degraded => {
+                let interval = Duration::from_millis(self.config.degraded_min_interval_ms);
+                let due = state
+                    .last_admitted
+                    .map(|t| now.duration_since(t) >= interval)
+                    .unwrap_or(true);
+                if due {
+                    state.last_admitted = Some(now);
+                }
+                due
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> HealthSnapshot {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        self.evaluate(&mut state, now);
+        let (error_rate, avg_latency_ms) = Self::window_stats(&state.samples);
+
+        HealthSnapshot {
+            status: state.status,
+            samples: state.samples.len(),
+            error_rate,
+            avg_latency_ms,
+            paused_for_secs: state
+                .paused_until
+                .map(|t| t.saturating_duration_since(now).as_secs())
+                .unwrap_or(0),
+        }
+    }
+
+    fn window_stats(samples: &VecDeque<Sample>) -> (f64, f64) {
+        if samples.is_empty() {
+            return (0.0, 0.0);
+        }
+        let n = samples.len() as f64;
+        let errors = samples.iter().filter(|s| !s.ok).count() as f64;
+        let latency_ms = samples
+            .iter()
+            .map(|s| s.latency.as_secs_f64() * 1000.0)
+            .sum::<f64>();
+        (errors / n, latency_ms / n)
+    }
+
+    fn evaluate(&self, state: &mut HealthState, now: Instant) {
+        let window = Duration::from_secs(self.config.window_secs);
+        while state
+            .samples
+            .front()
+            .map(|s| now.duration_since(s.at) > window)
+            .unwrap_or(false)
+        {
+            state.samples.pop_front();
+        }
+
+        let previous = state.status;
+
+        if let Some(until) = state.paused_until {
+            if now < until {
+                return;
+            }
+            // Pause over: probe carefully until fresh samples say otherwise
+            state.paused_until = None;
+            state.status = HealthStatus::Degraded;
+        }
+
+        let (error_rate, avg_latency_ms) = Self::window_stats(&state.samples);
+        if state.samples.len() >= self.config.min_samples {
+            state.status = if error_rate >= self.config.pause_error_rate {
+                state.paused_until = Some(now + Duration::from_secs(self.config.pause_secs));
+                // Start the next window clean so old failures don't re-trigger the pause
+                state.samples.clear();
+                HealthStatus::Paused
+            } else if error_rate >= self.config.degraded_error_rate
+                || avg_latency_ms >= self.config.max_latency_ms as f64
+            {
+                HealthStatus::Degraded
+            } else {
+                HealthStatus::Healthy
+            };
+        } else if state.status == HealthStatus::Degraded
+            && state
+                .degraded_since
+                .is_some_and(|since| now.duration_since(since) >= window)
+            && state
+                .samples
+                .iter()
+                .all(|s| s.ok && s.latency < Duration::from_millis(self.config.max_latency_ms))
+        {
+            // Throttled signals may never fill a window again: a whole window degraded without
+            // a failed or slow submission is enough to recover
+            state.status = HealthStatus::Healthy;
+        }
+
+        if state.status != HealthStatus::Degraded {
+            state.degraded_since = None;
+        } else if previous != HealthStatus::Degraded {
+            state.degraded_since = Some(now);
+        }
+
+        if state.status != previous {
+            match state.status {
+                HealthStatus::Paused => warn!(
+                    "⏸️ Executor unhealthy (error rate {:.0}%) - pausing signals for {}s",
+                    error_rate * 100.0,
+                    self.config.pause_secs
+                ),
+                HealthStatus::Degraded => warn!(
+                    "🐢 Executor degraded (error rate {:.0}%, avg latency {:.0}ms) - throttling signals",
+                    error_rate * 100.0,
+                    avg_latency_ms
+                ),
+                HealthStatus::Healthy => info!("💚 Executor healthy again - signals resumed"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ExecutionHealthConfig {
+        ExecutionHealthConfig {
+            window_secs: 60,
+            min_samples: 4,
+            degraded_error_rate: 0.2,
+            pause_error_rate: 0.5,
+            max_latency_ms: 2000,
+            pause_secs: 30,
+            degraded_min_interval_ms: 60_000,
+        }
+    }
+
+    #[test]
+    fn test_error_storm_pauses_signals() {
+        let health = ExecutorHealth::new(config());
+        for _ in 0..4 {
+            health.record(false, Duration::from_millis(50));
+        }
+
+        assert!(!health.admit());
+        let snapshot = health.snapshot();
+        assert_eq!(snapshot.status, HealthStatus::Paused);
+        assert!(snapshot.paused_for_secs > 0);
+    }
+
+    #[test]
+    fn test_degraded_throttles_signals() {
+        let health = ExecutorHealth::new(config());
+        health.record(false, Duration::from_millis(50));
+        for _ in 0..3 {
+            health.record(true, Duration::from_millis(50));
+        }
+
+        assert_eq!(health.snapshot().status, HealthStatus::Degraded);
+        assert!(health.admit());
+        assert!(!health.admit());
+    }
+
+    #[test]
+    fn test_degraded_recovers_after_a_clean_window() {
+        let health = ExecutorHealth::new(config());
+        health.record(false, Duration::from_millis(50));
+        for _ in 0..3 {
+            health.record(true, Duration::from_millis(50));
+        }
+        let mut state = health.state.lock().unwrap();
+        assert_eq!(state.status, HealthStatus::Degraded);
+
+        // Too few submissions to judge, and one of them failed: still degraded
+        let later = Instant::now() + Duration::from_secs(61);
+        state.samples.push_back(Sample {
+            at: later,
+            ok: false,
+            latency: Duration::from_millis(50),
+        });
+        health.evaluate(&mut state, later);
+        assert_eq!(state.status, HealthStatus::Degraded);
+
+        // A window later the failure is gone
+        health.evaluate(&mut state, later + Duration::from_secs(61));
+        assert_eq!(state.status, HealthStatus::Healthy);
+        assert!(state.degraded_since.is_none());
+    }
+}
@@ -0,0 +1,185 @@
+//! Latency budgets of signals
+//!
+//! A snipe posted 400 ms after its signal is a different trade from the one signalled: the ask
+//! it was priced off may be gone, or taken by someone faster. Each signal gets a budget by kind
+//! (`LATENCY_BUDGET_SNIPE_MS`, `LATENCY_BUDGET_ARBITRAGE_MS`, `LATENCY_BUDGET_DEFAULT_MS`),
+//! counted on the monotonic clock from the socket read of the book update the signal came from
+//! (from the moment the sniper takes it up when no update triggered it). The job queue wait,
+//! freshness checks, balance refreshes, sizing and marketable pricing all count against it. The
+//! executor checks it right before the plan goes to the submission queue and again once a slot
+//! is granted: a plan over budget is never submitted, and the sniper records a missed window
+//! instead of a failed execution.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use utoipa::ToSchema;
+
+use crate::config::LatencyBudgetConfig;
+
+/// Missed windows kept for the API
+const RECENT_MISSED: usize = 100;
+
+/// Time a signal has to reach the exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyBudget {
+    /// `clock` time of the signal's book update (or of its take-up)
+    pub signal_ns: u64,
+    pub budget_ms: u64,
+}
+
+impl LatencyBudget {
+    /// Budget of a single-sided signal: expiration snipes (`snipe`) get their own, the other
+    /// strategies the default. None when off or 0
+    pub fn for_snipe(
+        config: &LatencyBudgetConfig,
+        trade_prefix: &str,
+        signal_ns: u64,
+    ) -> Option<Self> {
+        let budget_ms = match trade_prefix {
+            "snipe" => config.snipe_ms,
+            _ => config.default_ms,
+        };
+        Self::new(config, budget_ms, signal_ns)
+    }
+
+    /// Budget of a YES + NO arbitrage entry. None when off or 0
+    pub fn for_arbitrage(config: &LatencyBudgetConfig, signal_ns: u64) -> Option<Self> {
+        Self::new(config, config.arbitrage_ms, signal_ns)
+    }
+
+    fn new(config: &LatencyBudgetConfig, budget_ms: u64, signal_ns: u64) -> Option<Self> {
+        (config.enabled && budget_ms > 0).then_some(Self {
+            signal_ns,
+            budget_ms,
+        })
+    }
+
+    /// Err once `now_ns` is past the budget
+    pub fn check(&self, plan_id: &str, now_ns: u64) -> Result<(), MissedWindow> {
+        let elapsed_ns = now_ns.saturating_sub(self.signal_ns);
+        if elapsed_ns <= self.budget_ms * 1_000_000 {
+            return Ok(());
+        }
+        Err(MissedWindow {
+            plan_id: plan_id.to_string(),
+            budget_ms: self.budget_ms,
+            elapsed_ms: elapsed_ns as f64 / 1_000_000.0,
+        })
+    }
+}
+
+/// Returned instead of submitting a plan that ran over its latency budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissedWindow {
+    pub plan_id: String,
+    pub budget_ms: u64,
+    pub elapsed_ms: f64,
+}
+
+impl std::fmt::Display for MissedWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "plan {} missed its window: {:.1} ms since the signal, budget {} ms",
+            self.plan_id, self.elapsed_ms, self.budget_ms
+        )
+    }
+}
+
+impl std::error::Error for MissedWindow {}
+
+/// The missed window behind this error, if it's one (nothing was submitted)
+pub fn missed_window(e: &anyhow::Error) -> Option<&MissedWindow> {
+    e.downcast_ref::<MissedWindow>()
+}
+
+/// A signal dropped for running over its budget
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MissedWindowEntry {
+    pub at: DateTime<Utc>,
+    pub trade_id: String,
+    pub market_id: String,
+    pub strategy: String,
+    pub budget_ms: u64,
+    pub elapsed_ms: f64,
+}
+
+/// Missed windows of the run
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MissedWindowReport {
+    pub total: u64,
+    pub by_strategy: BTreeMap<String, u64>,
+    /// Most recent first
+    pub recent: Vec<MissedWindowEntry>,
+}
+
+/// Counts of the signals dropped over budget, with the most recent ones
+#[derive(Debug, Default)]
+pub struct MissedWindows {
+    total: u64,
+    by_strategy: BTreeMap<String, u64>,
+    recent: VecDeque<MissedWindowEntry>,
+}
+
+impl MissedWindows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: MissedWindowEntry) {
+        self.total += 1;
+        *self.by_strategy.entry(entry.strategy.clone()).or_default() += 1;
+        if self.recent.len() == RECENT_MISSED {
+            self.recent.pop_back();
+        }
+        self.recent.push_front(entry);
+    }
+
+    pub fn report(&self) -> MissedWindowReport {
+        MissedWindowReport {
+            total: self.total,
+            by_strategy: self.by_strategy.clone(),
+            recent: self.recent.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plans_over_budget_are_missed_windows() {
+        let config = LatencyBudgetConfig {
+            enabled: true,
+            snipe_ms: 150,
+            arbitrage_ms: 300,
+            default_ms: 0,
+        };
+        assert!(LatencyBudget::for_snipe(&config, "pred", 1).is_none());
+        let budget = LatencyBudget::for_snipe(&config, "snipe", 1_000_000).unwrap();
+        assert!(budget.check("p1", 151_000_000).is_ok());
+        let missed = budget.check("p1", 151_500_000).unwrap_err();
+        assert_eq!(missed.budget_ms, 150);
+        assert!((missed.elapsed_ms - 150.5).abs() < 1e-9);
+
+        let e = anyhow::Error::from(missed);
+        assert!(missed_window(&e).is_some());
+        assert!(missed_window(&anyhow::anyhow!("rejected")).is_none());
+
+        let mut log = MissedWindows::new();
+        for trade_id in ["snipe_m1_1", "snipe_m1_2"] {
+            log.record(MissedWindowEntry {
+                at: Utc::now(),
+                trade_id: trade_id.to_string(),
+                market_id: "m1".to_string(),
+                strategy: "snipe".to_string(),
+                budget_ms: 150,
+                elapsed_ms: 180.0,
+            });
+        }
+        let report = log.report();
+        assert_eq!((report.total, report.by_strategy["snipe"]), (2, 2));
+        assert_eq!(report.recent[0].trade_id, "snipe_m1_2");
+    }
+}
@@ -0,0 +1,273 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::LiquidationConfig;
+use crate::polymarket::MarketData;
+
+/// One rung of the exit ladder: where to rest the closing order and how
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rung {
+    pub index: usize,
+    pub price: f64,
+    /// Last rung: cross the spread (fill-or-kill) instead of resting
+    pub is_final: bool,
+}
+
+/// Closing order currently working on the book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestingOrder {
+    pub order_id: String,
+    pub rung: usize,
+    /// Limit price of the exit-side order
+    pub price: f64,
+    /// Exit-side shares bought, one per position share it closes
+    #[serde(default)]
+    pub shares: f64,
+    pub size_usd: f64,
+}
+
+/// Part of the position worked out, priced on the position's own side
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LadderFill {
+    /// Entry notional of the shares closed, as the risk manager sizes positions
+    pub size_usd: f64,
+    pub exit_price: f64,
+}
+
+/// Works a losing position out with limit orders that get more aggressive towards a deadline
+/// Closing follows the Executor convention (buy the opposite outcome), so the ladder prices
+/// the opposite side: it starts below its ask and walks up to the ask by the deadline. A share
+/// of the opposite outcome offsets a share of ours, so the ladder counts shares: each rung buys
+/// the shares still open at the rung's price, whatever the two sides' prices are
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationLadder {
+    pub market_id: String,
+    pub trade_id: String,
+    /// Side of the position being exited
+    pub side: String,
+    /// Entry notional of the position
+    pub size_usd: f64,
+    /// Entry price of the position (0 on ladders saved before shares were counted)
+    #[serde(default)]
+    pub entry_price: f64,
+    /// Position shares still to be worked out
+    #[serde(default)]
+    pub remaining_shares: f64,
+    pub started_at: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub resting: Option<RestingOrder>,
+    /// Fills not yet booked against the position
+    pub fills: Vec<LadderFill>,
+    pub last_step: Option<DateTime<Utc>>,
+    steps: usize,
+    start_discount_bps: i32,
+    step_interval_secs: u64,
+}
+
+impl LiquidationLadder {
+    /// Start a ladder now on a position of `size_usd` bought at `entry_price`. The deadline is
+    /// the configured exit window, or the market's end (minus the configured buffer) if that
+    /// comes first
+    pub fn start(
+        config: &LiquidationConfig,
+        market: &MarketData,
+        trade_id: &str,
+        side: &str,
+        size_usd: f64,
+        entry_price: f64,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut deadline = now + Duration::seconds(config.deadline_secs as i64);
+        if let Some(end) = market.end_time {
+            let market_deadline = end - Duration::seconds(config.end_buffer_secs as i64);
+            deadline = deadline.min(market_deadline.max(now));
+        }
+
+        Self {
+            market_id: market.id.to_string(),
+            trade_id: trade_id.to_string(),
+            side: side.to_string(),
+            size_usd,
+            entry_price,
+            remaining_shares: if entry_price > 0.0 {
+                size_usd / entry_price
+            } else {
+                0.0
+            },
+            started_at: now,
+            deadline,
+            resting: None,
+            fills: Vec::new(),
+            last_step: None,
+            steps: config.steps.max(1),
+            start_discount_bps: config.start_discount_bps,
+            step_interval_secs: config.step_interval_secs,
+        }
+    }
+
+    /// Side the closing orders buy
+    pub fn exit_side(&self) -> &'static str {
+        if self.side == "YES" {
+            "NO"
+        } else {
+            "YES"
+        }
+    }
+
+    /// Current ask of the exit side
+    pub fn exit_ask(&self, market: &MarketData) -> f64 {
+        if self.side == "YES" {
+            market.no_price
+        } else {
+            market.yes_price
+        }
+    }
+
+    /// Rung for the current time: evenly spaced from the passive start price up to the ask
+    pub fn rung_at(&self, now: DateTime<Utc>, ask: f64) -> Rung {
+        let total_ms = (self.deadline - self.started_at).num_milliseconds().max(1) as f64;
+        let elapsed_ms = (now - self.started_at).num_milliseconds().max(0) as f64;
+        let progress = (elapsed_ms / total_ms).min(1.0);
+
+        let index = ((progress * self.steps as f64) as usize).min(self.steps);
+        let is_final = index >= self.steps;
+
+        let passive = ask * (1.0 - self.start_discount_bps as f64 / 10000.0);
+        let price = passive + (ask - passive) * index as f64 / self.steps as f64;
+
+        Rung {
+            index,
+            price: (price * 100.0).round() / 100.0,
+            is_final,
+        }
+    }
+
+    /// Time to check fills / re-price: the rung moved or the poll interval elapsed
+    pub fn is_due(&self, now: DateTime<Utc>, ask: f64) -> bool {
+        if self.resting.as_ref().map(|r| r.rung) != Some(self.rung_at(now, ask).index) {
+            return true;
+        }
+        match self.last_step {
+            Some(last) => (now - last).num_seconds() >= self.step_interval_secs as i64,
+            None => true,
+        }
+    }
+
+    /// Entry notional still to be worked out
+    pub fn remaining_usd(&self) -> f64 {
+        self.remaining_shares * self.entry_price
+    }
+
+    /// The order a rung at `price` places: every share still open, bought on the exit side
+    pub fn order_at(&self, order_id: String, rung: usize, price: f64) -> RestingOrder {
+        RestingOrder {
+            order_id,
+            rung,
+            price,
+            shares: self.remaining_shares,
+            size_usd: self.remaining_shares * price,
+        }
+    }
+
+    /// Book a fill (fraction of the resting order's size, 0-1) and drop the order
+    pub fn record_fill(&mut self, fraction: f64) {
+        if let Some(order) = self.resting.take() {
+            let filled = (order.shares * fraction.clamp(0.0, 1.0)).min(self.remaining_shares);
+            if filled > 0.0 {
+                self.remaining_shares -= filled;
+                // Buying the opposite outcome at p exits ours at 1 - p
+                self.fills.push(LadderFill {
+                    size_usd: filled * self.entry_price,
+                    exit_price: 1.0 - order.price,
+                });
+            }
+        }
+    }
+
+    /// Fills since the last call, for partial-close booking
+    pub fn take_fills(&mut self) -> Vec<LadderFill> {
+        std::mem::take(&mut self.fills)
+    }
+
+    /// Nothing meaningful left to sell
+    pub fn is_done(&self) -> bool {
+        self.remaining_usd() < 0.01
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LiquidationConfig {
+        LiquidationConfig {
+            enabled: true,
+            deadline_secs: 100,
+            steps: 4,
+            start_discount_bps: 1000,
+            step_interval_secs: 5,
+            end_buffer_secs: 30,
+        }
+    }
+
+    fn market(end_date: Option<String>) -> MarketData {
+        MarketData {
+            id: "m1".into(),
+            question: "Will it happen?".to_string(),
+            end_date,
+            yes_price: 0.3,
+            no_price: 0.7,
+            ..Default::default()
+        }
+        .with_end_time()
+    }
+
+    #[test]
+    fn test_rungs_get_more_aggressive() {
+        let now = Utc::now();
+        let ladder =
+            LiquidationLadder::start(&config(), &market(None), "t1", "YES", 10.0, 0.5, now);
+        assert_eq!(ladder.exit_side(), "NO");
+
+        let first = ladder.rung_at(now, 0.70);
+        let middle = ladder.rung_at(now + Duration::seconds(50), 0.70);
+        let last = ladder.rung_at(now + Duration::seconds(100), 0.70);
+
+        assert_eq!(first.index, 0);
+        assert!((first.price - 0.63).abs() < 1e-9);
+        assert!(middle.price > first.price && middle.price < 0.70);
+        assert!(last.is_final);
+        assert!((last.price - 0.70).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_deadline_capped_by_market_end() {
+        let now = Utc::now();
+        let end = (now + Duration::seconds(60)).to_rfc3339();
+        // $10 of NO at 0.50: 20 shares
+        let mut ladder =
+            LiquidationLadder::start(&config(), &market(Some(end)), "t1", "NO", 10.0, 0.5, now);
+        assert!(ladder.deadline <= now + Duration::seconds(30));
+
+        ladder.resting = Some(ladder.order_at("o1".to_string(), 0, 0.60));
+        assert!((ladder.resting.as_ref().unwrap().size_usd - 12.0).abs() < 1e-9);
+        ladder.record_fill(0.5);
+        assert!(!ladder.is_done());
+        assert!(ladder.resting.is_none());
+        assert!((ladder.remaining_shares - 10.0).abs() < 1e-9);
+
+        // The next rung buys the 10 shares left, not $5 worth
+        ladder.resting = Some(ladder.order_at("o2".to_string(), 1, 0.65));
+        assert!((ladder.resting.as_ref().unwrap().size_usd - 6.5).abs() < 1e-9);
+        ladder.record_fill(1.0);
+        assert!(ladder.is_done());
+
+        let fills = ladder.take_fills();
+        assert_eq!(fills.len(), 2);
+        assert!((fills[0].exit_price - 0.40).abs() < 1e-9);
+        // Booked at entry notional: half the position each
+        assert!((fills[0].size_usd - 5.0).abs() < 1e-9);
+        assert!((fills[1].size_usd - 5.0).abs() < 1e-9);
+        assert!(ladder.take_fills().is_empty());
+    }
+}
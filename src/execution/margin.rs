@@ -0,0 +1,251 @@
+//! Portfolio margin view
+//!
+//! The wallet balance alone overstates what can be spent: buy orders resting on the CLOB are
+//! backed by it until they fill or are cancelled. Collateral is split into what resting orders
+//! lock, what open positions hold at cost, what resolved positions will pay once redeemed, and
+//! what is actually free for new entries.
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::polymarket::OpenOrder;
+use crate::strategies::risk::Position;
+
+/// Where the trading collateral is
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct CollateralView {
+    /// Exchange collateral in the trading wallet, less what was spent since the last refresh
+    pub wallet_usd: f64,
+    /// Backing our resting buy orders
+    pub resting_orders_usd: f64,
+    pub resting_orders: usize,
+    /// Cost of open positions not yet resolved
+    pub open_positions_usd: f64,
+    /// What resolved positions pay out once redeemed
+    pub pending_redemption_usd: f64,
+    /// Wallet less resting orders: what new entries are sized against
+    pub free_usd: f64,
+    /// Last wallet and open order refresh (None before the first)
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A resolved position waiting for its redemption
+#[derive(Debug, Clone)]
+struct PendingRedemption {
+    payout_usd: f64,
+    /// When the redemption sent for it should have landed (None while it waits for gas or a
+    /// batch)
+    due_at: Option<DateTime<Utc>>,
+    /// When it was first due; kept across retries to tell a stuck redemption
+    first_due: Option<DateTime<Utc>>,
+}
+
+/// Locked vs free collateral, refreshed with the trading balance
+#[derive(Debug, Default)]
+pub struct MarginBook {
+    wallet_usd: f64,
+    /// Notional of each resting buy order, by order id
+    resting: HashMap<String, f64>,
+    open_positions_usd: f64,
+    /// Expected payout of each resolved position waiting for its redemption, by market id
+    pending_redemption: HashMap<String, PendingRedemption>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl MarginBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wallet balance as just read from the exchange
+    pub fn set_wallet(&mut self, balance_usd: f64, now: DateTime<Utc>) {
+        self.wallet_usd = balance_usd;
+        self.updated_at = Some(now);
+    }
+
+    /// Our resting orders as just listed by the exchange; only buys lock collateral
+    pub fn set_resting_orders(&mut self, orders: &[OpenOrder]) {
+        self.resting = orders
+            .iter()
+            .filter(|o| o.side.eq_ignore_ascii_case("BUY"))
+            .map(|o| (o.order_id.clone(), o.price * o.remaining_size()))
+            .filter(|(_, notional)| *notional > 0.0)
+            .collect();
+    }
+
+    /// Open positions at cost. Those waiting for redemption count as pending instead, and
+    /// pending payouts of positions closed since are dropped
+    pub fn set_positions(&mut self, positions: &[Position]) {
+        self.pending_redemption
+            .retain(|market_id, _| positions.iter().any(|p| p.market_id == *market_id));
+        self.open_positions_usd = positions
+            .iter()
+            .filter(|p| !self.pending_redemption.contains_key(&p.market_id))
+            .map(|p| p.size_usd)
+            .sum();
+    }
+
+    /// A resolved position waits for its redemption, `due_at` once one is sent (None while it
+    /// waits for gas or a batch)
+    pub fn set_pending_redemption(
+        &mut self,
+        market_id: &str,
+        payout_usd: f64,
+        due_at: Option<DateTime<Utc>>,
+    ) {
+        let first_due = self
+            .pending_redemption
+            .get(market_id)
+            .and_then(|p| p.first_due)
+            .or(due_at);
+        self.pending_redemption.insert(
+            market_id.to_string(),
+            PendingRedemption {
+                payout_usd: payout_usd.max(0.0),
+                due_at,
+                first_due,
+            },
+        );
+    }
+
+    /// Expected payout and landing time of the redemptions under way. Those not sent yet are
+    /// left out, and so are the stuck ones: still pending `give_up` after first due
+    pub fn expected_redemptions(
+        &self,
+        now: DateTime<Utc>,
+        give_up: Duration,
+    ) -> impl Iterator<Item = (&str, f64, DateTime<Utc>)> {
+        self.pending_redemption
+            .iter()
+            .filter(move |(_, p)| p.first_due.is_some_and(|first| now - first <= give_up))
+            .filter_map(|(market_id, p)| Some((market_id.as_str(), p.payout_usd, p.due_at?)))
+    }
+
+    /// A resolved position was redeemed or released
+    pub fn clear_pending_redemption(&mut self, market_id: &str) {
+        self.pending_redemption.remove(market_id);
+    }
+
+    /// The wallet was read at least once
+    pub fn is_refreshed(&self) -> bool {
+        self.updated_at.is_some()
+    }
+
+    /// Collateral an order just took, until the next refresh sees it leave the wallet or rest
+    pub fn spend(&mut self, size_usd: f64) {
+        self.wallet_usd = (self.wallet_usd - size_usd.max(0.0)).max(0.0);
+    }
+
+    pub fn resting_orders_usd(&self) -> f64 {
+        self.resting.values().sum()
+    }
+
+    /// Wallet less what resting orders lock
+    pub fn free_usd(&self) -> f64 {
+        (self.wallet_usd - self.resting_orders_usd()).max(0.0)
+    }
+
+    pub fn view(&self) -> CollateralView {
+        CollateralView {
+            wallet_usd: self.wallet_usd,
+            resting_orders_usd: self.resting_orders_usd(),
+            resting_orders: self.resting.len(),
+            open_positions_usd: self.open_positions_usd,
+            pending_redemption_usd: self.pending_redemption.values().map(|p| p.payout_usd).sum(),
+            free_usd: self.free_usd(),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::OrderStatus;
+
+    fn order(order_id: &str, side: &str, price: f64, size: f64, matched: f64) -> OpenOrder {
+        OpenOrder {
+            order_id: order_id.to_string(),
+            market_id: "m1".to_string(),
+            asset_id: "a1".to_string(),
+            side: side.to_string(),
+            price,
+            original_size: size,
+            size_matched: matched,
+            status: OrderStatus::Live,
+            created_at: 0,
+        }
+    }
+
+    fn position(market_id: &str, size_usd: f64) -> Position {
+        Position {
+            market_id: market_id.to_string(),
+            trade_id: format!("arb_{}_1", market_id),
+            side: "YES".to_string(),
+            size_usd,
+            entry_price: 0.5,
+            timestamp: 0,
+            realized_pnl: 0.0,
+            config_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_resting_orders_lock_collateral() {
+        let mut margin = MarginBook::new();
+        margin.set_wallet(100.0, Utc::now());
+        // 40 of 100 shares left at 0.50 lock $20; sells lock tokens, not collateral
+        margin.set_resting_orders(&[
+            order("o1", "BUY", 0.50, 100.0, 60.0),
+            order("o2", "SELL", 0.90, 50.0, 0.0),
+        ]);
+        assert!((margin.free_usd() - 80.0).abs() < 1e-9);
+
+        margin.spend(30.0);
+        let view = margin.view();
+        assert_eq!(view.resting_orders, 1);
+        assert!((view.wallet_usd - 70.0).abs() < 1e-9);
+        assert!((view.free_usd - 50.0).abs() < 1e-9);
+
+        // More locked than held: nothing free, never negative
+        margin.set_resting_orders(&[order("o3", "BUY", 0.80, 200.0, 0.0)]);
+        assert_eq!(margin.free_usd(), 0.0);
+    }
+
+    #[test]
+    fn test_pending_redemptions_leave_open_positions() {
+        let mut margin = MarginBook::new();
+        margin.set_pending_redemption("m1", 40.0, None);
+        margin.set_positions(&[position("m1", 20.0), position("m2", 15.0)]);
+        let view = margin.view();
+        assert!((view.open_positions_usd - 15.0).abs() < 1e-9);
+        assert!((view.pending_redemption_usd - 40.0).abs() < 1e-9);
+
+        // Closed without going through the redemption path: its payout is gone too
+        margin.set_positions(&[position("m2", 15.0)]);
+        assert_eq!(margin.view().pending_redemption_usd, 0.0);
+
+        margin.set_pending_redemption("m2", 30.0, None);
+        margin.clear_pending_redemption("m2");
+        margin.set_positions(&[position("m2", 15.0)]);
+        assert!((margin.view().open_positions_usd - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_expected_redemptions_skip_unsent_and_stuck() {
+        let now = Utc::now();
+        let give_up = Duration::minutes(10);
+        let mut margin = MarginBook::new();
+        margin.set_pending_redemption("waiting", 10.0, None);
+        margin.set_pending_redemption("sent", 20.0, Some(now + Duration::minutes(2)));
+        let expected: Vec<_> = margin.expected_redemptions(now, give_up).collect();
+        assert_eq!(expected, vec![("sent", 20.0, now + Duration::minutes(2))]);
+
+        // Retried past the give-up: no longer counted on, though still pending
+        let later = now + Duration::minutes(15);
+        margin.set_pending_redemption("sent", 20.0, Some(later + Duration::minutes(2)));
+        assert_eq!(margin.expected_redemptions(later, give_up).count(), 0);
+        assert!((margin.view().pending_redemption_usd - 30.0).abs() < 1e-9);
+    }
+}
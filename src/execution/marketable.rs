@@ -0,0 +1,181 @@
+//! Market orders emulated with marketable limit orders
+//!
+//! The CLOB has no true market order: a "market" buy is a limit order priced through the book.
+//! A marketable leg carries the worst price its strategy accepts, and is priced at submission
+//! from the live asks: the level that completes its size plus `MARKET_ORDER_SLIPPAGE_BPS`, never
+//! above that cap - "fill me now, but never worse than X". It goes out fill-or-kill, so it either
+//! fills at once or not at all.
+use std::fmt;
+
+use crate::polymarket::OrderLevel;
+
+/// Highest price an outcome can be bought at
+const MAX_PRICE: f64 = 0.99;
+
+/// Limit price of a marketable order and the book it was priced off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketablePrice {
+    /// Price the order is posted at
+    pub limit: f64,
+    pub best_ask: f64,
+    /// Worst level the size reaches
+    pub sweep: f64,
+    /// Average price of the size across the levels it takes
+    pub avg_price: f64,
+}
+
+/// Why a marketable order isn't sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarketableError {
+    NoAsks,
+    /// The whole book holds less than the order
+    Thin {
+        available_usd: f64,
+        size_usd: f64,
+    },
+    /// Filling the size takes a price above the cap
+    AboveCap {
+        needed: f64,
+        max_price: f64,
+    },
+}
+
+impl fmt::Display for MarketableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketableError::NoAsks => write!(f, "no asks to take"),
+            MarketableError::Thin {
+                available_usd,
+                size_usd,
+            } => write!(
+                f,
+                "book holds ${:.2} of the ${:.2} to fill",
+                available_usd, size_usd
+            ),
+            MarketableError::AboveCap { needed, max_price } => write!(
+                f,
+                "filling takes {:.4}, above the {:.4} cap",
+                needed, max_price
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MarketableError {}
+
+fn cents_up(price: f64) -> f64 {
+    (price * 100.0 - 1e-9).ceil() / 100.0
+}
+
+fn cents_down(price: f64) -> f64 {
+    (price * 100.0 + 1e-9).floor() / 100.0
+}
+
+/// Limit price that buys `size_usd` now off `asks` (best first): the level completing the size
+/// plus `slippage_bps`, on the cent grid, at most `max_price`
+pub fn marketable_price(
+    asks: &[OrderLevel],
+    size_usd: f64,
+    slippage_bps: f64,
+    max_price: f64,
+) -> Result<MarketablePrice, MarketableError> {
+    let best_ask = asks
+        .first()
+        .filter(|l| l.price > 0.0)
+        .ok_or(MarketableError::NoAsks)?
+        .price;
+
+    let mut cost_usd = 0.0;
+    let mut shares = 0.0;
+    let mut sweep = None;
+    for level in asks.iter().filter(|l| l.price > 0.0 && l.size > 0.0) {
+        let take_usd = (size_usd - cost_usd).min(level.price * level.size);
+        cost_usd += take_usd;
+        shares += take_usd / level.price;
+        if cost_usd >= size_usd - 1e-9 {
+            sweep = Some(level.price);
+            break;
+        }
+    }
+    let Some(sweep) = sweep else {
+        return Err(MarketableError::Thin {
+            available_usd: cost_usd,
+            size_usd,
+        });
+    };
+
+    let max_price = max_price.min(MAX_PRICE);
+    let needed = cents_up(sweep);
+    if needed > max_price + 1e-9 {
+        return Err(MarketableError::AboveCap { needed, max_price });
+    }
+    let limit = cents_up(sweep * (1.0 + slippage_bps / 10_000.0))
+        .min(cents_down(max_price))
+        .max(needed);
+    Ok(MarketablePrice {
+        limit,
+        best_ask,
+        sweep,
+        avg_price: if shares > 0.0 {
+            cost_usd / shares
+        } else {
+            sweep
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asks() -> Vec<OrderLevel> {
+        vec![
+            OrderLevel {
+                price: 0.50,
+                size: 100.0,
+            },
+            OrderLevel {
+                price: 0.52,
+                size: 100.0,
+            },
+            OrderLevel {
+                price: 0.60,
+                size: 1000.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_prices_through_the_levels_the_size_takes() {
+        // $50 fits the first level: 1% of slippage rounds up to the next cent
+        let price = marketable_price(&asks(), 50.0, 100.0, 0.90).unwrap();
+        assert_eq!(
+            (price.best_ask, price.sweep, price.limit),
+            (0.50, 0.50, 0.51)
+        );
+
+        // $80 reaches the second level; the cap trims the slippage
+        let price = marketable_price(&asks(), 80.0, 500.0, 0.53).unwrap();
+        assert_eq!((price.sweep, price.limit), (0.52, 0.53));
+        assert!(price.avg_price > 0.50 && price.avg_price < 0.52);
+    }
+
+    #[test]
+    fn test_refuses_thin_books_and_prices_above_the_cap() {
+        assert_eq!(
+            marketable_price(&[], 10.0, 50.0, 0.9),
+            Err(MarketableError::NoAsks)
+        );
+        assert!(matches!(
+            marketable_price(&asks(), 10_000.0, 50.0, 0.9),
+            Err(MarketableError::Thin { .. })
+        ));
+        assert_eq!(
+            marketable_price(&asks(), 200.0, 50.0, 0.55),
+            Err(MarketableError::AboveCap {
+                needed: 0.60,
+                max_price: 0.55
+            })
+        );
+    }
+}
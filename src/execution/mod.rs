@@ -1,9 +1,78 @@
+//! Order execution and the wallet's on-chain operations
+//!
+//! Everything that signs (orders, redemptions, bridge deposits, Flashbots bundles) is behind
+//! the `trading` feature; the rest is shared with the read-only build.
+pub mod anomaly;
+#[cfg(feature = "trading")]
+pub mod bridge;
+pub mod capital_wait;
+#[cfg(feature = "trading")]
+pub mod collateral;
 pub mod cpu_affinity;
+#[cfg(feature = "trading")]
+pub mod cross_venue;
+#[cfg(feature = "trading")]
 pub mod executor;
+#[cfg(feature = "trading")]
 pub mod flashbots;
+pub mod funding;
+pub mod gas;
+pub mod health;
+pub mod latency_budget;
+pub mod liquidation;
+pub mod margin;
+pub mod marketable;
+pub mod obfuscation;
+#[cfg(feature = "trading")]
+pub mod paper_relay;
+pub mod plan;
+#[cfg(feature = "trading")]
 pub mod redemption;
+#[cfg(feature = "trading")]
+pub mod remote;
+pub mod role;
+pub mod settlement;
+#[cfg(feature = "trading")]
+pub mod submission;
+#[cfg(feature = "trading")]
+pub mod wal;
 
+pub use anomaly::{Anomaly, AnomalyGuard, AnomalyKind, AnomalySnapshot};
+#[cfg(feature = "trading")]
+pub use bridge::{FundingManager, MainnetBridge};
+pub use capital_wait::{CapitalRelease, CapitalWait, DeferredAction, DeferredSignal};
+#[cfg(feature = "trading")]
+pub use collateral::CollateralSwapper;
 pub use cpu_affinity::CpuPinner;
-pub use executor::Executor;
+#[cfg(feature = "trading")]
+pub use cross_venue::{CrossVenueExecutor, CrossVenueFill};
+#[cfg(feature = "trading")]
+pub use executor::{order_fill, Executor};
+#[cfg(feature = "trading")]
 pub use flashbots::FlashbotsClient;
-pub use redemption::RedemptionManager;
+pub use funding::{FundingQueue, FundingRequest, FundingStatus};
+pub use gas::{GasCost, RedemptionCandidate, RedemptionPlan};
+pub use health::{ExecutorHealth, HealthSnapshot, HealthStatus};
+pub use latency_budget::{
+    missed_window, LatencyBudget, MissedWindow, MissedWindowEntry, MissedWindowReport,
+    MissedWindows,
+};
+pub use liquidation::{LadderFill, LiquidationLadder, RestingOrder, Rung};
+pub use margin::{CollateralView, MarginBook};
+pub use marketable::{marketable_price, MarketableError, MarketablePrice};
+pub use obfuscation::Obfuscator;
+#[cfg(feature = "trading")]
+pub use paper_relay::{BundleOutcome, PaperRelay, RelayStats};
+pub use plan::{Atomicity, ExecutionPlan, ExecutionReport, ExecutionRoute, Leg, LegResult};
+#[cfg(feature = "trading")]
+pub use redemption::{RedemptionManager, RedemptionResult, TxOutcome};
+#[cfg(feature = "trading")]
+pub use remote::RemoteExecutor;
+pub use role::{choose_role, LegBook, LiquidityRole, RestingMakerLeg, RoleChoice, TakenLeg};
+pub use settlement::{
+    ExpectedSettlement, MismatchKind, SettlementMismatch, SettlementReconciler, SettlementStatus,
+};
+#[cfg(feature = "trading")]
+pub use submission::{QueueStats, SubmissionPermit, SubmissionQueue};
+#[cfg(feature = "trading")]
+pub use wal::{OrderIntent, OrderWal, Reconciliation, WalInterface, WalOutcome};
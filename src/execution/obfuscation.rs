@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use crate::config::ObfuscationConfig;
+
+/// Largest salt handed out: the CLOB reads it as a JSON number, so it stays a safe integer
+const MAX_SALT: u64 = (1 << 53) - 1;
+
+/// Randomizes the size and timing of entries (see `ObfuscationConfig`). Sizes only ever
+/// shrink, so a jittered entry passes every check the original did
+#[derive(Debug, Clone)]
+pub struct Obfuscator {
+    config: ObfuscationConfig,
+}
+
+impl Obfuscator {
+    pub fn new(config: ObfuscationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Entry size after jitter
+    pub fn size(&self, size_usd: f64) -> f64 {
+        self.size_with(size_usd, rand::random::<f64>())
+    }
+
+    /// `size_usd` shrunk by `sample` (0-1) of the configured jitter
+    fn size_with(&self, size_usd: f64, sample: f64) -> f64 {
+        if self.config.size_jitter_pct <= 0.0 {
+            return size_usd;
+        }
+        let shrink = self.config.size_jitter_pct / 100.0 * sample.clamp(0.0, 1.0);
+        size_usd * (1.0 - shrink)
+    }
+
+    /// Delay before submitting an entry, None when timing jitter is off
+    pub fn delay(&self) -> Option<Duration> {
+        self.delay_with(rand::random::<f64>())
+    }
+
+    fn delay_with(&self, sample: f64) -> Option<Duration> {
+        if self.config.timing_jitter_ms == 0 {
+            return None;
+        }
+        let ms = (self.config.timing_jitter_ms as f64 * sample.clamp(0.0, 1.0)).round();
+        Some(Duration::from_millis(ms as u64))
+    }
+}
+
+/// Random order salt: unlike a time-based one it says nothing about when or by whom the
+/// order was built
+pub fn random_salt() -> u64 {
+    rand::random::<u64>() & MAX_SALT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obfuscator(size_jitter_pct: f64, timing_jitter_ms: u64) -> Obfuscator {
+        Obfuscator::new(ObfuscationConfig {
+            size_jitter_pct,
+            timing_jitter_ms,
+            randomize_order_ids: true,
+        })
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let off = obfuscator(0.0, 0);
+        assert_eq!(off.size(25.0), 25.0);
+        assert_eq!(off.delay(), None);
+
+        let on = obfuscator(10.0, 200);
+        assert_eq!(on.size_with(25.0, 0.0), 25.0);
+        assert!((on.size_with(25.0, 1.0) - 22.5).abs() < 1e-9);
+        assert!((on.size_with(25.0, 0.5) - 23.75).abs() < 1e-9);
+        for _ in 0..1000 {
+            let size = on.size(25.0);
+            assert!(size > 22.5 - 1e-9 && size <= 25.0, "{}", size);
+            assert!(on.delay().unwrap() <= Duration::from_millis(200));
+        }
+        assert_eq!(on.delay_with(0.5), Some(Duration::from_millis(100)));
+
+        assert!((0..100).all(|_| random_salt() <= MAX_SALT));
+    }
+}
@@ -0,0 +1,269 @@
+//! Paper Flashbots relay
+//!
+//! A paper run with `USE_FLASHBOTS=true` has no relay to talk to, so all-or-nothing plans never
+//! took the bundle route. The paper relay builds the bundle a plan would send, simulates it and
+//! reports whether it landed, without broadcasting anything; only a bundle that lands has its
+//! legs placed (all of them) on the paper interface.
+use anyhow::Result;
+use ethers::types::U256;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::execution::flashbots::SimulationResult;
+use crate::execution::plan::ExecutionPlan;
+
+/// Gas of one order-fill transaction in a bundle
+pub const BUNDLE_TX_GAS: u64 = 150_000;
+
+/// One leg of a plan as a bundle transaction
+#[derive(Debug, Clone)]
+pub struct BundleTx {
+    pub market_id: String,
+    /// CLOB token id the transaction trades
+    pub asset_id: String,
+    pub side: String,
+    pub price: f64,
+    pub size_usd: f64,
+}
+
+/// What a plan would have sent to the relay
+#[derive(Debug, Clone)]
+pub struct PaperBundle {
+    /// sha256 of the plan id and its transactions
+    pub hash: String,
+    pub plan_id: String,
+    pub txs: Vec<BundleTx>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BundleOutcome {
+    /// Landed: every leg executes
+    Included,
+    /// Couldn't be built or failed simulation: nothing executes
+    Rejected(String),
+    /// Valid but missed its block: nothing executes
+    NotIncluded,
+}
+
+/// Bundle outcomes of the run so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelayStats {
+    pub built: u64,
+    pub rejected: u64,
+    pub included: u64,
+    pub not_included: u64,
+}
+
+/// Stands in for the Flashbots relay in paper runs
+pub struct PaperRelay {
+    /// Probability that a valid bundle misses its block
+    miss_rate: f64,
+    stats: Mutex<RelayStats>,
+}
+
+impl PaperRelay {
+    pub fn new(miss_rate: f64) -> Self {
+        Self {
+            miss_rate: miss_rate.clamp(0.0, 1.0),
+            stats: Mutex::new(RelayStats::default()),
+        }
+    }
+
+    /// One transaction per leg; a leg without a token id can't be turned into a transaction
+    pub fn build(&self, plan: &ExecutionPlan) -> Result<PaperBundle> {
+        if plan.legs.is_empty() {
+            anyhow::bail!("Cannot build an empty bundle");
+        }
+        let mut txs = Vec::with_capacity(plan.legs.len());
+        for leg in &plan.legs {
+            let Some(asset_id) = leg.asset_id.clone() else {
+                anyhow::bail!("{} leg of {} has no token id", leg.side, leg.market_id);
+            };
+            txs.push(BundleTx {
+                market_id: leg.market_id.to_string(),
+                asset_id: asset_id.into(),
+                side: leg.side.clone(),
+                price: leg.price.get(),
+                size_usd: leg.size_usd.get(),
+            });
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(plan.id.as_bytes());
+        for tx in &txs {
+            hasher.update(format!(
+                "{}:{}:{}:{}",
+                tx.asset_id, tx.side, tx.price, tx.size_usd
+            ));
+        }
+        Ok(PaperBundle {
+            hash: hex::encode(hasher.finalize()),
+            plan_id: plan.id.clone(),
+            txs,
+        })
+    }
+
+    /// Revert on what would revert on-chain: an unfillable price or size, or outcomes of one
+    /// market costing $1 or more together (the bundle's profit check)
+    pub fn simulate(&self, bundle: &PaperBundle) -> SimulationResult {
+        let mut error = bundle
+            .txs
+            .iter()
+            .find(|tx| tx.price <= 0.0 || tx.price >= 1.0 || tx.size_usd <= 0.0)
+            .map(|tx| {
+                format!(
+                    "{} leg unfillable at {:.4} x ${:.2}",
+                    tx.side, tx.price, tx.size_usd
+                )
+            });
+        if error.is_none() {
+            for tx in &bundle.txs {
+                let cost: f64 = bundle
+                    .txs
+                    .iter()
+                    .filter(|other| other.market_id == tx.market_id)
+                    .map(|other| other.price)
+                    .sum();
+                if cost >= 1.0 && bundle.txs.len() > 1 {
+                    error = Some(format!("{} outcomes cost {:.4} >= 1", tx.market_id, cost));
+                    break;
+                }
+            }
+        }
+
+        SimulationResult {
+            success: error.is_none(),
+            gas_used: U256::from(BUNDLE_TX_GAS * bundle.txs.len() as u64),
+            effective_gas_price: U256::zero(),
+            error,
+        }
+    }
+
+    /// Build, simulate and "submit" a plan's bundle, counting and logging the outcome
+    pub fn run(&self, plan: &ExecutionPlan) -> BundleOutcome {
+        let outcome = match self.build(plan) {
+            Err(e) => BundleOutcome::Rejected(e.to_string()),
+            Ok(bundle) => {
+                let simulation = self.simulate(&bundle);
+                if !simulation.success {
+                    BundleOutcome::Rejected(simulation.error.unwrap_or_default())
+                } else if rand::random::<f64>() < self.miss_rate {
+                    BundleOutcome::NotIncluded
+                } else {
+                    info!(
+                        "📦 Paper bundle {} for {}: {} txs, {} gas, included",
+                        &bundle.hash[..12],
+                        plan.id,
+                        bundle.txs.len(),
+                        simulation.gas_used
+                    );
+                    BundleOutcome::Included
+                }
+            }
+        };
+
+        let stats = match self.stats.lock() {
+            Ok(mut stats) => {
+                stats.built += 1;
+                match &outcome {
+                    BundleOutcome::Included => stats.included += 1,
+                    BundleOutcome::Rejected(_) => stats.rejected += 1,
+                    BundleOutcome::NotIncluded => stats.not_included += 1,
+                }
+                *stats
+            }
+            Err(_) => RelayStats::default(),
+        };
+        match &outcome {
+            BundleOutcome::Included => {}
+            BundleOutcome::Rejected(reason) => {
+                warn!("⚠️ Paper bundle for {} rejected: {}", plan.id, reason)
+            }
+            BundleOutcome::NotIncluded => warn!("⚠️ Paper bundle for {} missed its block", plan.id),
+        }
+        info!(
+            "📦 Paper relay: {}/{} bundles landed ({} rejected, {} missed)",
+            stats.included, stats.built, stats.rejected, stats.not_included
+        );
+        outcome
+    }
+
+    pub fn stats(&self) -> RelayStats {
+        self.stats.lock().map(|s| *s).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::plan::{Atomicity, Leg};
+    use crate::ids::{AssetId, Price, UsdAmount};
+    use polymarket_client_sdk::clob::types::OrderType;
+
+    fn leg(side: &str, asset_id: Option<&str>, price: f64) -> Leg {
+        Leg {
+            market_id: "m1".into(),
+            asset_id: asset_id.map(AssetId::from),
+            side: side.to_string(),
+            price: Price::new(price),
+            size_usd: UsdAmount::new(10.0),
+            order_type: OrderType::GTC,
+            max_price: None,
+            fill_price: None,
+        }
+    }
+
+    fn plan(yes_price: f64, no_price: f64) -> ExecutionPlan {
+        ExecutionPlan::new(
+            "arb_m1",
+            vec![
+                leg("YES", Some("t-yes"), yes_price),
+                leg("NO", Some("t-no"), no_price),
+            ],
+            Atomicity::AllOrNothing,
+        )
+    }
+
+    #[test]
+    fn test_paper_bundle_outcomes() {
+        let relay = PaperRelay::new(0.0);
+        let bundle = relay.build(&plan(0.45, 0.50)).unwrap();
+        assert_eq!(bundle.txs.len(), 2);
+        assert_eq!(bundle.hash.len(), 64);
+        let simulation = relay.simulate(&bundle);
+        assert!(simulation.success);
+        assert_eq!(simulation.gas_used, U256::from(2 * BUNDLE_TX_GAS));
+        assert_eq!(relay.run(&plan(0.45, 0.50)), BundleOutcome::Included);
+
+        // Paying $1 or more for both outcomes reverts
+        assert!(matches!(
+            relay.run(&plan(0.55, 0.50)),
+            BundleOutcome::Rejected(_)
+        ));
+
+        // No token id: the transaction can't be built
+        let unbuildable = ExecutionPlan::new(
+            "snipe_m1",
+            vec![leg("YES", None, 0.45)],
+            Atomicity::AllOrNothing,
+        );
+        assert!(matches!(
+            relay.run(&unbuildable),
+            BundleOutcome::Rejected(_)
+        ));
+
+        let missing = PaperRelay::new(1.0);
+        assert_eq!(missing.run(&plan(0.45, 0.50)), BundleOutcome::NotIncluded);
+
+        assert_eq!(
+            relay.stats(),
+            RelayStats {
+                built: 3,
+                rejected: 2,
+                included: 1,
+                not_included: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use polymarket_client_sdk::clob::types::OrderType;
+use serde::{Deserialize, Serialize};
+
+use crate::execution::latency_budget::LatencyBudget;
+use crate::ids::{AssetId, MarketId, OrderId, Price, UsdAmount};
+use crate::polymarket::MarketData;
+use crate::scheduling::Priority;
+
+/// How strictly the legs of a plan must execute together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Atomicity {
+    /// Every leg stands on its own (partial execution is acceptable)
+    BestEffort,
+    /// Either all legs execute or the placed ones are unwound
+    AllOrNothing,
+}
+
+/// How the Executor actually submitted a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionRoute {
+    /// All legs submitted together to the CLOB
+    Batch,
+    /// Legs one at a time, unwinding placed legs if a later one fails
+    SequentialWithUnwind,
+    /// Private on-chain bundle (Flashbots)
+    Bundle,
+}
+
+/// One order in a plan
+#[derive(Debug, Clone)]
+pub struct Leg {
+    pub market_id: MarketId,
+    /// CLOB token id, when known ([NO, YES] convention on MarketData)
+    pub asset_id: Option<AssetId>,
+    pub side: String, // "YES" or "NO"
+    pub price: Price,
+    pub size_usd: UsdAmount,
+    pub order_type: OrderType,
+    /// Marketable: priced off the live asks at submission, never above this (see `marketable`)
+    pub max_price: Option<Price>,
+    /// Marketable, once priced: the average of the asks the size takes, what a fill is booked at
+    pub fill_price: Option<Price>,
+}
+
+impl Leg {
+    pub fn new(
+        market: &MarketData,
+        side: &str,
+        price: Price,
+        size_usd: UsdAmount,
+        order_type: OrderType,
+    ) -> Self {
+        let asset_id = match side {
+            "YES" => market.asset_ids.get(1),
+            _ => market.asset_ids.first(),
+        }
+        .map(AssetId::from);
+
+        Self {
+            market_id: market.id.clone(),
+            asset_id,
+            side: side.to_string(),
+            price,
+            size_usd,
+            order_type,
+            max_price: None,
+            fill_price: None,
+        }
+    }
+
+    /// Fill now at whatever the book asks, but never above `max_price`
+    pub fn marketable(mut self, max_price: Price) -> Self {
+        self.max_price = Some(max_price);
+        self.order_type = OrderType::FOK;
+        self
+    }
+}
+
+/// N legs plus their atomicity requirement, built from a strategy signal and consumed by the Executor
+#[derive(Debug, Clone)]
+pub struct ExecutionPlan {
+    /// Trade id used for risk/PnL registration
+    pub id: String,
+    pub legs: Vec<Leg>,
+    pub atomicity: Atomicity,
+    /// Place in the order submission queue (snipes go first, then exits)
+    pub priority: Priority,
+    /// Not submitted once over it (see `latency_budget`)
+    pub latency_budget: Option<LatencyBudget>,
+}
+
+impl ExecutionPlan {
+    pub fn new(id: impl Into<String>, legs: Vec<Leg>, atomicity: Atomicity) -> Self {
+        Self {
+            id: id.into(),
+            legs,
+            atomicity,
+            priority: Priority::Routine,
+            latency_budget: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_latency_budget(mut self, budget: Option<LatencyBudget>) -> Self {
+        self.latency_budget = budget;
+        self
+    }
+
+    /// Every leg fill-or-kill, so nothing rests on the book to be picked off
+    pub fn fill_or_kill(mut self) -> Self {
+        for leg in &mut self.legs {
+            leg.order_type = OrderType::FOK;
+        }
+        self
+    }
+
+    /// Single-sided snipe, fill-or-kill
+    pub fn snipe(
+        id: impl Into<String>,
+        market: &MarketData,
+        side: &str,
+        price: Price,
+        size_usd: UsdAmount,
+    ) -> Self {
+        Self::new(
+            id,
+            vec![Leg::new(market, side, price, size_usd, OrderType::FOK)],
+            Atomicity::BestEffort,
+        )
+        .with_priority(Priority::NewMarket)
+    }
+
+    /// Single-sided buy at the book's price, never above `max_price`
+    pub fn market_order(
+        id: impl Into<String>,
+        market: &MarketData,
+        side: &str,
+        max_price: Price,
+        size_usd: UsdAmount,
+    ) -> Self {
+        Self::new(
+            id,
+            vec![Leg::new(market, side, max_price, size_usd, OrderType::FOK).marketable(max_price)],
+            Atomicity::BestEffort,
+        )
+        .with_priority(Priority::NewMarket)
+    }
+
+    /// YES + NO arbitrage, notional split evenly, both legs required
+    pub fn arbitrage(
+        id: impl Into<String>,
+        market: &MarketData,
+        yes_price: Price,
+        no_price: Price,
+        size_usd: UsdAmount,
+    ) -> Self {
+        let half = UsdAmount::new(size_usd.get() / 2.0);
+        Self::new(
+            id,
+            vec![
+                Leg::new(market, "YES", yes_price, half, OrderType::GTC),
+                Leg::new(market, "NO", no_price, half, OrderType::GTC),
+            ],
+            Atomicity::AllOrNothing,
+        )
+    }
+
+    /// The same number of NO shares of each question of a neg-risk market, bought to be
+    /// converted. `questions` are the markets with their NO asks; every leg is required
+    pub fn neg_risk_basket(
+        id: impl Into<String>,
+        questions: &[(MarketData, f64)],
+        sets: f64,
+    ) -> Self {
+        Self::new(
+            id,
+            questions
+                .iter()
+                .map(|(market, no_ask)| {
+                    Leg::new(
+                        market,
+                        "NO",
+                        Price::new(*no_ask),
+                        UsdAmount::new(sets * no_ask),
+                        OrderType::FOK,
+                    )
+                })
+                .collect(),
+            Atomicity::AllOrNothing,
+        )
+    }
+
+    pub fn total_size_usd(&self) -> f64 {
+        self.legs.iter().map(|l| l.size_usd.get()).sum()
+    }
+
+    /// Trade id for a leg: the plan id for single-leg plans, "<id>_<SIDE>" otherwise, and
+    /// "<id>_<market>_<SIDE>" where several legs buy the same side (of different markets)
+    pub fn leg_trade_id(&self, leg: &Leg) -> String {
+        if self.legs.len() == 1 {
+            self.id.clone()
+        } else if self.legs.iter().filter(|l| l.side == leg.side).count() > 1 {
+            format!("{}_{}_{}", self.id, leg.market_id, leg.side)
+        } else {
+            format!("{}_{}", self.id, leg.side)
+        }
+    }
+}
+
+/// Outcome of one leg
+#[derive(Debug, Clone)]
+pub struct LegResult {
+    pub side: String,
+    pub price: f64,
+    pub size_usd: f64,
+    pub order_id: Option<OrderId>,
+    pub error: Option<String>,
+    /// Order was cancelled, and any fill sold back, as part of an unwind
+    pub unwound: bool,
+}
+
+impl LegResult {
+    pub fn is_placed(&self) -> bool {
+        self.order_id.is_some() && !self.unwound
+    }
+}
+
+/// What happened to a plan
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    pub plan_id: String,
+    pub route: ExecutionRoute,
+    pub legs: Vec<LegResult>,
+}
+
+impl ExecutionReport {
+    pub fn is_complete(&self) -> bool {
+        self.legs.iter().all(|l| l.is_placed())
+    }
+
+    /// Placed legs as (side, order_id, price)
+    pub fn placed(&self) -> impl Iterator<Item = (&str, &str, f64)> {
+        self.legs.iter().filter(|l| l.is_placed()).filter_map(|l| {
+            l.order_id
+                .as_deref()
+                .map(|id| (l.side.as_str(), id, l.price))
+        })
+    }
+
+    /// Compact "SIDE:order_id" summary for logs
+    pub fn summary(&self) -> String {
+        self.legs
+            .iter()
+            .map(|l| match (&l.order_id, &l.error) {
+                (Some(id), _) if l.unwound => format!("{}:{} (unwound)", l.side, id),
+                (Some(id), _) => format!("{}:{}", l.side, id),
+                (None, Some(e)) => format!("{}:failed ({})", l.side, e),
+                (None, None) => format!("{}:skipped", l.side),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
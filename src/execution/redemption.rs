@@ -1,28 +1,125 @@
 use anyhow::Result;
 use ethers::prelude::*;
 use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
-use std::sync::Arc;
-use tracing::info;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::execution::remote::RemoteExecutor;
+use crate::failover::Failover;
+use crate::polymarket::collateral::{PositionCollateral, NEG_RISK_ADAPTER_ADDRESS};
+use crate::polymarket::contracts::AssetIdDeriver;
 
 // Partial ABI for Conditional Tokens Framework (CTF)
 abigen!(
     CTF,
     r#"[
+        function balanceOf(address owner, uint256 id) external view returns (uint256)
         function payoutDenominator(bytes32 conditionId) external view returns (uint256)
         function payoutNumerators(bytes32 conditionId, uint256 index) external view returns (uint256)
         function redeemPositions(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] calldata indexSets) external
+        function mergePositions(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] calldata partition, uint256 amount) external
+        event PayoutRedemption(address indexed redeemer, address indexed collateralToken, bytes32 indexed parentCollectionId, bytes32 conditionId, uint256[] indexSets, uint256 payout)
+    ]"#
+);
+
+// Partial ABI for Polymarket's NegRiskAdapter, which holds the wrapped collateral of
+// multi-outcome conditions and unwraps it on the way out
+abigen!(
+    NegRiskAdapter,
+    r#"[
+        function redeemPositions(bytes32 conditionId, uint256[] calldata amounts) external
+        function mergePositions(bytes32 conditionId, uint256 amount) external
+        function convertPositions(bytes32 marketId, uint256 indexSet, uint256 amount) external
     ]"#
 );
 
+// The Polymarket proxy wallet (a Gnosis Safe our key owns) and Safe's MultiSendCallOnly, which
+// the Safe delegatecalls to make several calls in one transaction
+abigen!(
+    GnosisSafe,
+    r#"[
+        function execTransaction(address to, uint256 value, bytes data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address refundReceiver, bytes signatures) external payable returns (bool)
+    ]"#
+);
+abigen!(
+    MultiSend,
+    r#"[
+        function multiSend(bytes transactions) external payable
+    ]"#
+);
+
+/// Safe's MultiSendCallOnly v1.3.0 (same address on every chain)
+const MULTI_SEND_CALL_ONLY_ADDRESS: &str = "0x40A2aCCbd92BCA938b02010E17A5b8929b49130D";
+
 const CTF_ADDRESS: &str = "0x4d97dcd97ec945f40cf65f87097ace5ea0476045";
-const COLLATERAL_TOKEN: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"; // USDC.e on Polygon
+
+/// USDC.e (and the adapter's wrapped USDC.e) has 6 decimals; other collaterals are assumed to
+/// be USDC variants with the same
+const COLLATERAL_DECIMALS: f64 = 1e6;
+
+type SignerClient = SignerMiddleware<Provider<Ws>, LocalWallet>;
+
+/// How one condition of a batch went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedemptionResult {
+    pub condition_id: String,
+    /// None when the transaction was never sent
+    pub tx_hash: Option<String>,
+    /// Collateral paid out (USD), from the PayoutRedemption event. Some only on success
+    pub payout_usd: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl RedemptionResult {
+    pub fn succeeded(&self) -> bool {
+        self.payout_usd.is_some()
+    }
+
+    fn failed(condition_id: &str, tx_hash: Option<String>, error: impl ToString) -> Self {
+        Self {
+            condition_id: condition_id.to_string(),
+            tx_hash,
+            payout_usd: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Where a transaction sent earlier stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxOutcome {
+    /// Known to the node, not mined yet
+    Pending,
+    Succeeded,
+    Reverted,
+    /// Unknown to the node: never broadcast or evicted, safe to send again
+    Dropped,
+}
+
+/// What signs the transactions: the key held here, or the `executor` process holding it
+/// (two-process deployment, see `execution::remote`)
+enum Signer {
+    Local(Arc<SignerClient>),
+    Remote(Arc<RemoteExecutor>),
+}
 
 pub struct RedemptionManager {
     contract: CTF<Provider<Ws>>, // Using WebSocket provider
-    #[allow(dead_code)]
-    wallet: Option<LocalWallet>, // Wallet for signing transactions (optional for read-only checks)
-    client: Arc<SignerMiddleware<Provider<Ws>, LocalWallet>>,
+    /// Reads: resolutions, receipts, gas price
+    provider: Arc<Provider<Ws>>,
+    signer: Signer,
+    // The Safe holding the outcome tokens (POLYMARKET_PROXY_ADDRESS); None = the signer holds them
+    safe: Option<Address>,
+    // Hot-standby fence: no transaction while the peer may be sending its own
+    failover: Option<Arc<Failover>>,
+    // Redemptions whose receipt timed out, by condition: looked up again before the condition
+    // is sent, so one that landed late isn't redeemed twice (the second would pay nothing)
+    unconfirmed: Mutex<HashMap<String, TxHash>>,
 }
 
 impl RedemptionManager {
@@ -32,22 +129,89 @@ impl RedemptionManager {
         let wallet = LocalWallet::from_str(private_key)?.with_chain_id(137u64); // Polygon Mainnet ID
 
         // Use clone for client (SignerMiddleware takes the provider instance)
-        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet.clone()));
+        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+        Self::with_signer(provider, Signer::Local(client))
+    }
 
-        // Parse address
-        let address = Address::from_str(CTF_ADDRESS)?;
+    /// Read on-chain here, and have the `executor` process send the transactions
+    pub async fn remote(rpc_url: &str, executor: Arc<RemoteExecutor>) -> Result<Self> {
+        let provider = Provider::<Ws>::connect(rpc_url).await?;
+        Self::with_signer(provider, Signer::Remote(executor))
+    }
 
-        // Wrap provider in Arc for CTF contract (read-only instance)
-        // CTF::new expects Into<Arc<M>>
-        let contract = CTF::new(address, Arc::new(provider));
+    /// From the config's RPC and key, redeeming from the proxy wallet when one is configured;
+    /// None without them (or when the RPC is unreachable)
+    pub async fn connect(config: &Config) -> Option<Self> {
+        let (Some(rpc), Some(pk)) = (&config.polygon_ws_rpc, &config.polygon_private_key) else {
+            return None;
+        };
+        let manager = async {
+            let manager = Self::new(rpc, pk).await?;
+            match &config.polymarket.proxy_address {
+                Some(proxy) => manager.with_safe(proxy),
+                None => Ok(manager),
+            }
+        };
+        match manager.await {
+            Ok(rm) => {
+                info!("✅ RedemptionManager initialized");
+                Some(rm)
+            }
+            Err(e) => {
+                error!("❌ Failed to init RedemptionManager: {}", e);
+                None
+            }
+        }
+    }
 
+    fn with_signer(provider: Provider<Ws>, signer: Signer) -> Result<Self> {
+        let provider = Arc::new(provider);
+        // CTF contract for reads (CTF::new expects Into<Arc<M>>)
+        let contract = CTF::new(Address::from_str(CTF_ADDRESS)?, provider.clone());
         Ok(Self {
             contract,
-            wallet: Some(wallet),
-            client,
+            provider,
+            signer,
+            safe: None,
+            failover: None,
+            unconfirmed: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Redeem the tokens `safe` holds (a Safe the key owns) instead of the signer's
+    pub fn with_safe(mut self, safe: &str) -> Result<Self> {
+        self.safe = Some(Address::from_str(safe)?);
+        Ok(self)
+    }
+
+    /// The wallet holding the outcome tokens
+    fn holder(&self) -> Result<Address> {
+        match self.safe {
+            Some(safe) => Ok(safe),
+            None => Ok(self.client()?.address()),
+        }
+    }
+
+    /// The local signer; only reached after the remote case has been forwarded
+    fn client(&self) -> Result<&Arc<SignerClient>> {
+        match &self.signer {
+            Signer::Local(client) => Ok(client),
+            Signer::Remote(_) => anyhow::bail!("Transactions are signed by the executor process"),
+        }
+    }
+
+    /// Refuse redemptions, merges and conversions while failover fences this instance
+    pub fn set_failover(&mut self, failover: Arc<Failover>) {
+        self.failover = Some(failover);
+    }
+
+    fn ensure_unfenced(&self) -> Result<()> {
+        match &self.failover {
+            Some(failover) => failover.ensure_trading(),
+            None => Ok(()),
+        }
+    }
+
     /// Check if a condition is resolved using payoutDenominator
     pub async fn is_condition_resolved(&self, condition_id_hex: &str) -> Result<bool> {
         let condition_id = self.parse_bytes32(condition_id_hex)?;
@@ -62,29 +226,102 @@ impl RedemptionManager {
         Ok(denominator > U256::zero())
     }
 
-    /// Redeem positions for a resolved condition
-    /// For binary markets: indexSets = [1, 2] usually (Outcome A and Outcome B)
-    pub async fn redeem_positions(&self, condition_id_hex: &str) -> Result<String> {
-        info!("💰 Attempting to redeem positions for {}", condition_id_hex);
+    /// Winning side of a resolved binary condition (index 0 = NO, index 1 = YES)
+    /// None while unresolved, or when the payout is split between both outcomes
+    pub async fn winning_side(&self, condition_id_hex: &str) -> Result<Option<&'static str>> {
+        let condition_id = self.parse_bytes32(condition_id_hex)?;
+        let no = self
+            .contract
+            .payout_numerators(condition_id, U256::zero())
+            .call()
+            .await?;
+        let yes = self
+            .contract
+            .payout_numerators(condition_id, U256::one())
+            .call()
+            .await?;
+
+        Ok(match (yes.is_zero(), no.is_zero()) {
+            (false, true) => Some("YES"),
+            (true, false) => Some("NO"),
+            _ => None,
+        })
+    }
 
+    /// What one share of each side collects on a resolved binary condition, (YES, NO): 1 and 0
+    /// for a winner, fractions summing to 1 for a split resolution
+    pub async fn payout_per_share(&self, condition_id_hex: &str) -> Result<(f64, f64)> {
         let condition_id = self.parse_bytes32(condition_id_hex)?;
-        let parent_collection_id = [0u8; 32]; // Always 0x0 for direct questions
-        let collateral_token = Address::from_str(COLLATERAL_TOKEN)?;
+        let denominator = self
+            .contract
+            .payout_denominator(condition_id)
+            .call()
+            .await?;
+        if denominator.is_zero() {
+            anyhow::bail!("Condition {} is not resolved", condition_id_hex);
+        }
+        // The denominator is the numerators' sum: bounding it bounds them
+        if denominator > U256::from(u64::MAX) {
+            anyhow::bail!("Payout denominator of {} out of range", condition_id_hex);
+        }
+        let mut per_share = [0.0; 2];
+        for (index, share) in per_share.iter_mut().enumerate() {
+            let numerator = self
+                .contract
+                .payout_numerators(condition_id, U256::from(index))
+                .call()
+                .await?;
+            *share = numerator.as_u128() as f64 / denominator.as_u128() as f64;
+        }
+        // Index 0 = NO, index 1 = YES
+        Ok((per_share[1], per_share[0]))
+    }
 
-        // Index sets for binary market (1 and 2)
-        // 1 = 0b01 (Outcome 0), 2 = 0b10 (Outcome 1)
-        let index_sets = vec![U256::from(1), U256::from(2)];
+    /// Look a sent transaction up by hash: its receipt if mined, else whether the node still
+    /// has it
+    pub async fn transaction_outcome(&self, tx_hash: &str) -> Result<TxOutcome> {
+        let hash = H256::from_str(tx_hash)?;
+        if let Some(receipt) = self.provider.get_transaction_receipt(hash).await? {
+            return Ok(if receipt.status == Some(U64::one()) {
+                TxOutcome::Succeeded
+            } else {
+                TxOutcome::Reverted
+            });
+        }
+        Ok(match self.provider.get_transaction(hash).await? {
+            Some(_) => TxOutcome::Pending,
+            None => TxOutcome::Dropped,
+        })
+    }
 
-        // We need to use the client with signer to send tx
-        let address = Address::from_str(CTF_ADDRESS)?;
-        let contract_with_signer = CTF::new(address, self.client.clone());
+    /// Current Polygon gas price in gwei
+    pub async fn gas_price_gwei(&self) -> Result<f64> {
+        let wei = self.provider.get_gas_price().await?;
+        Ok(wei.as_u128() as f64 / 1e9)
+    }
 
-        let tx = contract_with_signer.redeem_positions(
-            collateral_token,
-            parent_collection_id,
-            condition_id,
-            index_sets,
-        );
+    /// Redeem positions for a resolved condition backed by `collateral`
+    /// For binary markets: indexSets = [1, 2] usually (Outcome A and Outcome B)
+    pub async fn redeem_positions(
+        &self,
+        condition_id_hex: &str,
+        collateral: &PositionCollateral,
+    ) -> Result<String> {
+        self.ensure_unfenced()?;
+        if let Signer::Remote(executor) = &self.signer {
+            return executor
+                .redeem_positions(condition_id_hex, collateral)
+                .await;
+        }
+        info!("💰 Attempting to redeem positions for {}", condition_id_hex);
+        if let Some(safe) = self.safe {
+            let condition = [(condition_id_hex.to_string(), collateral.clone())];
+            let tx_hash = self.send_through_safe(safe, &condition).await?;
+            info!("✅ Redeem transaction sent! Hash: {:?}", tx_hash);
+            return Ok(format!("{:?}", tx_hash));
+        }
+
+        let tx = self.redeem_call(condition_id_hex, collateral).await?;
 
         // Send transaction
         let pending_tx = tx.send().await?;
@@ -98,6 +335,423 @@ impl RedemptionManager {
         Ok(format!("{:?}", tx_hash))
     }
 
+    /// Merge `pairs` YES + NO pairs of an unresolved condition back into collateral (1 each)
+    pub async fn merge_positions(
+        &self,
+        condition_id_hex: &str,
+        pairs: f64,
+        collateral: &PositionCollateral,
+    ) -> Result<String> {
+        self.ensure_unfenced()?;
+        if let Signer::Remote(executor) = &self.signer {
+            return executor
+                .merge_positions(condition_id_hex, pairs, collateral)
+                .await;
+        }
+        info!("🔗 Merging {:.2} pairs of {}", pairs, condition_id_hex);
+
+        let condition_id = self.parse_bytes32(condition_id_hex)?;
+        // Outcome tokens have the collateral's decimals
+        let amount = U256::from((pairs * COLLATERAL_DECIMALS).floor() as u64);
+
+        let tx = if collateral.is_neg_risk() {
+            // The adapter merges on the wrapped collateral and pays out USDC.e
+            let adapter = NegRiskAdapter::new(
+                Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?,
+                self.client()?.clone(),
+            );
+            adapter.merge_positions(condition_id, amount)
+        } else {
+            let contract_with_signer =
+                CTF::new(Address::from_str(CTF_ADDRESS)?, self.client()?.clone());
+            contract_with_signer.merge_positions(
+                Address::from_str(collateral.address())?,
+                [0u8; 32],
+                condition_id,
+                vec![U256::from(1), U256::from(2)],
+                amount,
+            )
+        };
+        let pending_tx = tx.send().await?;
+        let tx_hash = pending_tx.tx_hash();
+
+        info!("✅ Merge transaction sent! Hash: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+
+    /// Convert `sets` NO tokens of each question in `index_set` (bit i for question i) of an
+    /// adapter market: pays one collateral per set for every question but one, plus a YES of
+    /// each question left out
+    pub async fn convert_positions(
+        &self,
+        neg_risk_market_id: &str,
+        index_set: u128,
+        sets: f64,
+    ) -> Result<String> {
+        self.ensure_unfenced()?;
+        if let Signer::Remote(executor) = &self.signer {
+            return executor
+                .convert_positions(neg_risk_market_id, index_set, sets)
+                .await;
+        }
+        info!(
+            "🔄 Converting {:.2} NO sets (questions {:#b}) of {}",
+            sets, index_set, neg_risk_market_id
+        );
+
+        let market_id = self.parse_bytes32(neg_risk_market_id)?;
+        let amount = U256::from((sets * COLLATERAL_DECIMALS).floor() as u64);
+        let adapter = NegRiskAdapter::new(
+            Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?,
+            self.client()?.clone(),
+        );
+        let tx = adapter.convert_positions(market_id, U256::from(index_set), amount);
+        let pending_tx = tx.send().await?;
+        let tx_hash = pending_tx.tx_hash();
+
+        info!("✅ Convert transaction sent! Hash: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+
+    /// Redeem many resolved conditions in few round trips. A redemption has to come from the
+    /// wallet holding the tokens (a multicall contract would redeem its own), so up to
+    /// `max_in_flight` transactions are sent back to back on consecutive nonces and their
+    /// receipts awaited together. A send failure stops the window (later nonces would be
+    /// stuck behind the gap); unsent and failed conditions are left for the next round. A
+    /// condition whose earlier redemption timed out isn't sent again until that one is known
+    /// to have failed
+    pub async fn redeem_batch(
+        &self,
+        condition_ids: &[(String, PositionCollateral)],
+        max_in_flight: usize,
+        receipt_timeout: Duration,
+    ) -> Vec<RedemptionResult> {
+        let mut results = Vec::with_capacity(condition_ids.len());
+        if let Err(e) = self.ensure_unfenced() {
+            return condition_ids
+                .iter()
+                .map(|(condition_id, _)| RedemptionResult::failed(condition_id, None, &e))
+                .collect();
+        }
+        if let Signer::Remote(executor) = &self.signer {
+            return match executor
+                .redeem_batch(condition_ids, max_in_flight, receipt_timeout)
+                .await
+            {
+                Ok(results) => results,
+                Err(e) => condition_ids
+                    .iter()
+                    .map(|(condition_id, _)| RedemptionResult::failed(condition_id, None, &e))
+                    .collect(),
+            };
+        }
+        let mut to_send = Vec::with_capacity(condition_ids.len());
+        for (condition_id, collateral) in condition_ids {
+            match self.recheck(condition_id).await {
+                Some(result) => results.push(result),
+                None => to_send.push((condition_id.clone(), collateral.clone())),
+            }
+        }
+        let rechecked = results.len();
+        for window in to_send.chunks(max_in_flight.max(1)) {
+            if let Some(safe) = self.safe {
+                // The whole window in one transaction, each condition read from its receipt
+                let sent = self.send_through_safe(safe, window).await;
+                for (condition_id, _) in window {
+                    results.push(match &sent {
+                        Ok(tx_hash) => self.confirm(condition_id, *tx_hash, receipt_timeout).await,
+                        Err(e) => RedemptionResult::failed(condition_id, None, e),
+                    });
+                }
+                if sent.is_err() {
+                    break;
+                }
+                continue;
+            }
+            let sent = self.send_window(window).await;
+            let stopped = sent.len() < window.len();
+            for ((condition_id, _), sent) in window.iter().zip(sent) {
+                results.push(match sent {
+                    Ok(tx_hash) => self.confirm(condition_id, tx_hash, receipt_timeout).await,
+                    Err(e) => RedemptionResult::failed(condition_id, None, e),
+                });
+            }
+            if stopped {
+                break;
+            }
+        }
+        for (condition_id, _) in &to_send[results.len() - rechecked..] {
+            results.push(RedemptionResult::failed(
+                condition_id,
+                None,
+                "not sent: an earlier transaction of the batch failed",
+            ));
+        }
+        results
+    }
+
+    /// Redeem `conditions` in one transaction from `safe`: the key, its owner, has it
+    /// delegatecall MultiSendCallOnly with each condition's redeem call
+    async fn send_through_safe(
+        &self,
+        safe: Address,
+        conditions: &[(String, PositionCollateral)],
+    ) -> Result<TxHash> {
+        let client = self.client()?;
+        // MultiSend packing per call: operation (0 = call), target, value, data length, data
+        let mut calls = Vec::new();
+        for (condition_id, collateral) in conditions {
+            let call = self.redeem_call(condition_id, collateral).await?;
+            let target = *call
+                .tx
+                .to_addr()
+                .ok_or_else(|| anyhow::anyhow!("Redeem call of {} has no target", condition_id))?;
+            let data = call.calldata().unwrap_or_default();
+            let mut word = [0u8; 32];
+            calls.push(0u8);
+            calls.extend_from_slice(target.as_bytes());
+            calls.extend_from_slice(&word);
+            U256::from(data.len()).to_big_endian(&mut word);
+            calls.extend_from_slice(&word);
+            calls.extend_from_slice(&data);
+        }
+        let multi_send_address = Address::from_str(MULTI_SEND_CALL_ONLY_ADDRESS)?;
+        let batch = MultiSend::new(multi_send_address, client.clone())
+            .multi_send(calls.into())
+            .calldata()
+            .ok_or_else(|| anyhow::anyhow!("Failed to encode the MultiSend batch"))?;
+        // Pre-validated signature: the owner sending the transaction approves it (r = owner,
+        // s = 0, v = 1)
+        let mut signature = vec![0u8; 65];
+        signature[12..32].copy_from_slice(client.address().as_bytes());
+        signature[64] = 1;
+        let call = GnosisSafe::new(safe, client.clone()).exec_transaction(
+            multi_send_address,
+            U256::zero(),
+            batch,
+            1, // delegatecall
+            U256::zero(),
+            U256::zero(),
+            U256::zero(),
+            Address::zero(),
+            Address::zero(),
+            signature.into(),
+        );
+        let pending = call.send().await?;
+        let tx_hash = pending.tx_hash();
+        info!(
+            "💰 Redeem of {} conditions sent through the Safe: {:?}",
+            conditions.len(),
+            tx_hash
+        );
+        Ok(tx_hash)
+    }
+
+    /// Send one window on consecutive nonces; stops at the first failure (included as an Err)
+    async fn send_window(
+        &self,
+        condition_ids: &[(String, PositionCollateral)],
+    ) -> Vec<Result<TxHash>> {
+        let mut sent = Vec::with_capacity(condition_ids.len());
+        let client = match self.client() {
+            Ok(client) => client,
+            Err(e) => {
+                sent.push(Err(e));
+                return sent;
+            }
+        };
+        let mut nonce = match client
+            .get_transaction_count(client.address(), Some(BlockNumber::Pending.into()))
+            .await
+        {
+            Ok(nonce) => nonce,
+            Err(e) => {
+                sent.push(Err(anyhow::anyhow!("Failed to read nonce: {}", e)));
+                return sent;
+            }
+        };
+
+        for (condition_id, collateral) in condition_ids {
+            let sent_one = async {
+                let call = self
+                    .redeem_call(condition_id, collateral)
+                    .await?
+                    .nonce(nonce);
+                let pending = call.send().await?;
+                Ok::<TxHash, anyhow::Error>(pending.tx_hash())
+            }
+            .await;
+            let failed = sent_one.is_err();
+            if let Ok(tx_hash) = &sent_one {
+                info!(
+                    "💰 Redeem {} sent (nonce {}): {:?}",
+                    condition_id, nonce, tx_hash
+                );
+                nonce += U256::one();
+            }
+            sent.push(sent_one);
+            if failed {
+                break;
+            }
+        }
+        sent
+    }
+
+    /// Where the timed-out redemption of `condition_id` stands, if there is one: its result
+    /// once mined, a failure while pending (or unknown), None when it can be sent again. A
+    /// dropped one is sent again on the pending nonce, which replaces it should it come back
+    async fn recheck(&self, condition_id: &str) -> Option<RedemptionResult> {
+        let tx_hash = self.unconfirmed.lock().ok()?.get(condition_id).copied()?;
+        let hash = format!("{:?}", tx_hash);
+        let outcome = match self.transaction_outcome(&hash).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                return Some(RedemptionResult::failed(
+                    condition_id,
+                    Some(hash),
+                    format!("earlier redemption unknown: {}", e),
+                ))
+            }
+        };
+        match outcome {
+            TxOutcome::Succeeded => match self.provider.get_transaction_receipt(tx_hash).await {
+                Ok(Some(receipt)) => {
+                    info!("✅ Earlier redemption of {} landed: {}", condition_id, hash);
+                    self.forget(condition_id);
+                    Some(Self::paid_out(condition_id, hash, &receipt))
+                }
+                _ => Some(RedemptionResult::failed(
+                    condition_id,
+                    Some(hash),
+                    "earlier redemption's receipt unavailable",
+                )),
+            },
+            TxOutcome::Pending => Some(RedemptionResult::failed(
+                condition_id,
+                Some(hash),
+                "earlier redemption still pending",
+            )),
+            TxOutcome::Reverted | TxOutcome::Dropped => {
+                self.forget(condition_id);
+                None
+            }
+        }
+    }
+
+    fn forget(&self, condition_id: &str) {
+        if let Ok(mut unconfirmed) = self.unconfirmed.lock() {
+            unconfirmed.remove(condition_id);
+        }
+    }
+
+    /// Wait for a redemption's receipt and read what it paid out
+    async fn confirm(
+        &self,
+        condition_id: &str,
+        tx_hash: TxHash,
+        timeout: Duration,
+    ) -> RedemptionResult {
+        let hash = format!("{:?}", tx_hash);
+        let pending = PendingTransaction::new(tx_hash, self.provider.as_ref());
+        let receipt = match tokio::time::timeout(timeout, pending).await {
+            Ok(Ok(Some(receipt))) => receipt,
+            Ok(Ok(None)) => return RedemptionResult::failed(condition_id, Some(hash), "dropped"),
+            Ok(Err(e)) => return RedemptionResult::failed(condition_id, Some(hash), e),
+            Err(_) => {
+                // May still land: checked before the condition is sent again
+                if let Ok(mut unconfirmed) = self.unconfirmed.lock() {
+                    unconfirmed.insert(condition_id.to_string(), tx_hash);
+                }
+                return RedemptionResult::failed(condition_id, Some(hash), "receipt timed out");
+            }
+        };
+        Self::paid_out(condition_id, hash, &receipt)
+    }
+
+    /// What a mined redemption paid out for `condition_id`, from its PayoutRedemption events
+    /// (a Safe batch has one per condition)
+    fn paid_out(
+        condition_id: &str,
+        hash: String,
+        receipt: &TransactionReceipt,
+    ) -> RedemptionResult {
+        if receipt.status != Some(U64::one()) {
+            warn!("⚠️ Redeem {} reverted: {}", condition_id, hash);
+            return RedemptionResult::failed(condition_id, Some(hash), "reverted");
+        }
+
+        let payout: U256 = receipt
+            .logs
+            .iter()
+            .filter_map(|log| {
+                PayoutRedemptionFilter::decode_log(&ethers::abi::RawLog {
+                    topics: log.topics.clone(),
+                    data: log.data.to_vec(),
+                })
+                .ok()
+            })
+            .filter(|event| {
+                hex::encode(event.condition_id)
+                    .eq_ignore_ascii_case(condition_id.trim_start_matches("0x"))
+            })
+            .map(|event| event.payout)
+            .fold(U256::zero(), |total, payout| total + payout);
+        RedemptionResult {
+            condition_id: condition_id.to_string(),
+            tx_hash: Some(hash),
+            payout_usd: Some(payout.as_u128() as f64 / COLLATERAL_DECIMALS),
+            error: None,
+        }
+    }
+
+    /// The redeem transaction of one condition: on the CTF with the condition's collateral, or
+    /// through the NegRiskAdapter for wrapped collateral. The adapter takes the amount of each
+    /// outcome to redeem rather than index sets, so the wallet's balances are read first
+    async fn redeem_call(
+        &self,
+        condition_id_hex: &str,
+        collateral: &PositionCollateral,
+    ) -> Result<ContractCall<SignerClient, ()>> {
+        let condition_id = self.parse_bytes32(condition_id_hex)?;
+        if collateral.is_neg_risk() {
+            let amounts = self.position_balances(condition_id, collateral).await?;
+            let adapter = NegRiskAdapter::new(
+                Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?,
+                self.client()?.clone(),
+            );
+            return Ok(adapter.redeem_positions(condition_id, amounts));
+        }
+
+        // Index sets for binary market (1 and 2)
+        // 1 = 0b01 (Outcome 0), 2 = 0b10 (Outcome 1)
+        let index_sets = vec![U256::from(1), U256::from(2)];
+        let contract_with_signer =
+            CTF::new(Address::from_str(CTF_ADDRESS)?, self.client()?.clone());
+        Ok(contract_with_signer.redeem_positions(
+            Address::from_str(collateral.address())?,
+            [0u8; 32], // Always 0x0 for direct questions
+            condition_id,
+            index_sets,
+        ))
+    }
+
+    /// The wallet's balance of each outcome token of a condition (index set 1, then 2)
+    async fn position_balances(
+        &self,
+        condition_id: [u8; 32],
+        collateral: &PositionCollateral,
+    ) -> Result<Vec<U256>> {
+        let (yes, no) = AssetIdDeriver::for_collateral(collateral)?.derive(H256(condition_id));
+        let owner = self.holder()?;
+        let mut balances = Vec::with_capacity(2);
+        for position_id in [no, yes] {
+            let position_id = U256::from_dec_str(&position_id)
+                .map_err(|e| anyhow::anyhow!("Invalid position id {}: {:?}", position_id, e))?;
+            balances.push(self.contract.balance_of(owner, position_id).call().await?);
+        }
+        Ok(balances)
+    }
+
     fn parse_bytes32(&self, hex_str: &str) -> Result<[u8; 32]> {
         let clean = hex_str.trim_start_matches("0x");
         let bytes = hex::decode(clean)?;
@@ -0,0 +1,1204 @@
+//! Two-process deployment: a detector and an executor
+//!
+//! The detector is the usual agent, run without `POLYGON_PRIVATE_KEY`: it handles the WS, Gamma
+//! and chain feeds and the strategies. With `EXECUTOR_SOCKET` set, its order entry goes through
+//! a `RemoteExecutor` to the `executor` process, the only one holding the key, and so do the
+//! other transactions the key signs: redemptions, merges, conversions, collateral swaps and
+//! funding top-ups. The two talk newline-delimited JSON over a Unix socket (one request per
+//! line, answered by one response with the same id). Calls share the connection without
+//! waiting on each other: the executor answers each as it completes. Either side can be
+//! restarted on its own: the detector reconnects on its next call.
+//!
+//! The executor doesn't take the detector's word for the size of an order: it holds every one
+//! to `MAX_POSITION_SIZE_PCT` of its wallet and the notional placed in the last hour to
+//! `MAX_PORTFOLIO_EXPOSURE_PCT`. Requests that send something carry the detector's failover
+//! epoch; once a newer epoch has been seen, older ones are refused, so a fenced primary
+//! sharing the executor can't trade through it.
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use polymarket_client_sdk::clob::types::OrderType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+use crate::config::{Config, RiskConfig};
+use crate::execution::bridge::FundingManager;
+use crate::execution::collateral::CollateralSwapper;
+use crate::execution::redemption::{RedemptionManager, RedemptionResult};
+use crate::failover::Failover;
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::polymarket::{
+    Collateral, CollateralBalances, EventSeries, MarketData, MarketInterface, OpenOrder, OrderBook,
+    OrderRequest, OrderStatus, PolymarketClient, PositionCollateral,
+};
+
+/// Longest the detector waits for the executor to answer a query or an order
+const CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest the detector waits for a transaction the executor signs (RPC round trips, swaps)
+const CUSTODY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Window of the executor's placed-notional cap
+const NOTIONAL_WINDOW: Duration = Duration::from_secs(3600);
+
+/// How long the executor sizes its caps on a wallet balance before reading it again
+const CAP_BALANCE_TTL: Duration = Duration::from_secs(60);
+
+/// Answers the executor may have queued for the detector on one connection
+const RESPONSE_QUEUE: usize = 64;
+
+/// Calls the detector makes to the executor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ExecRequest {
+    Ping,
+    PlaceOrder {
+        order: WireOrder,
+    },
+    PlaceOrders {
+        orders: Vec<WireOrder>,
+    },
+    CancelOrder {
+        order_id: String,
+    },
+    GetOpenOrders {
+        market_id: Option<String>,
+    },
+    GetOrderStatus {
+        order_id: String,
+    },
+    GetBalance,
+    GetCollateralBalances,
+    DowngradeToPaper,
+    RedeemPositions {
+        condition_id: String,
+        collateral: PositionCollateral,
+    },
+    RedeemBatch {
+        conditions: Vec<(String, PositionCollateral)>,
+        max_in_flight: usize,
+        receipt_timeout_secs: u64,
+    },
+    MergePositions {
+        condition_id: String,
+        pairs: f64,
+        collateral: PositionCollateral,
+    },
+    ConvertPositions {
+        neg_risk_market_id: String,
+        /// Decimal: a u128 doesn't survive the flattened envelope
+        index_set: String,
+        sets: f64,
+    },
+    TopUpCollateral {
+        required: Collateral,
+        balances: CollateralBalances,
+    },
+    Fund {
+        balance_usd: f64,
+    },
+}
+
+impl ExecRequest {
+    /// Sends orders or transactions: fenced by the failover epoch, and never sent twice when
+    /// the connection dropped mid-call (the first attempt may have gone through)
+    fn is_write(&self) -> bool {
+        matches!(
+            self,
+            ExecRequest::PlaceOrder { .. }
+                | ExecRequest::PlaceOrders { .. }
+                | ExecRequest::RedeemPositions { .. }
+                | ExecRequest::RedeemBatch { .. }
+                | ExecRequest::MergePositions { .. }
+                | ExecRequest::ConvertPositions { .. }
+                | ExecRequest::TopUpCollateral { .. }
+                | ExecRequest::Fund { .. }
+        )
+    }
+
+    /// How long the detector waits for the answer
+    fn timeout(&self) -> Duration {
+        match self {
+            ExecRequest::RedeemBatch {
+                conditions,
+                max_in_flight,
+                receipt_timeout_secs,
+            } => {
+                // Windows go one after the other, each waiting for its receipts
+                let windows = conditions.len().div_ceil((*max_in_flight).max(1)).max(1);
+                CUSTODY_TIMEOUT + Duration::from_secs(*receipt_timeout_secs) * windows as u32
+            }
+            ExecRequest::RedeemPositions { .. }
+            | ExecRequest::MergePositions { .. }
+            | ExecRequest::ConvertPositions { .. }
+            | ExecRequest::TopUpCollateral { .. } => CUSTODY_TIMEOUT,
+            _ => CALL_TIMEOUT,
+        }
+    }
+}
+
+/// `OrderRequest` on the wire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WireOrder {
+    pub market_id: MarketId,
+    pub side: String,
+    pub size_usd: UsdAmount,
+    pub price: Price,
+    /// "GTC" or "FOK"
+    pub order_type: String,
+}
+
+impl WireOrder {
+    pub fn from_request(order: &OrderRequest) -> Self {
+        Self {
+            market_id: order.market_id.clone(),
+            side: order.side.clone(),
+            size_usd: order.size_usd,
+            price: order.price,
+            order_type: format!("{:?}", order.order_type),
+        }
+    }
+
+    pub fn to_request(&self) -> Result<OrderRequest> {
+        let order_type = match self.order_type.as_str() {
+            "GTC" => OrderType::GTC,
+            "FOK" => OrderType::FOK,
+            other => anyhow::bail!("Unsupported order type {}", other),
+        };
+        Ok(OrderRequest {
+            market_id: self.market_id.clone(),
+            side: self.side.clone(),
+            size_usd: self.size_usd,
+            price: self.price,
+            order_type,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope<T> {
+    id: u64,
+    /// The detector's failover epoch, on writes (None without failover)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    epoch: Option<u64>,
+    #[serde(flatten)]
+    request: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExecResponse {
+    id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// The transactions the executor's key signs besides orders; None where not configured
+#[derive(Default)]
+pub struct Custody {
+    pub redemption: Option<RedemptionManager>,
+    /// One swap at a time (the swapper keeps its cooldown)
+    pub swapper: Option<tokio::sync::Mutex<CollateralSwapper>>,
+    pub funding: Option<Arc<FundingManager>>,
+}
+
+/// Per-order and hourly notional limits the executor holds orders to, sized on its own wallet
+/// whatever the detector believes. Buys only: sells close what was bought
+pub struct OrderCaps {
+    max_order_pct: f64,
+    max_hourly_pct: f64,
+    state: Mutex<CapState>,
+}
+
+#[derive(Default)]
+struct CapState {
+    /// Last wallet balance and when it was read
+    balance: Option<(f64, Instant)>,
+    /// Notional placed in the window, oldest first
+    placed: VecDeque<(Instant, f64)>,
+}
+
+impl OrderCaps {
+    pub fn new(risk: &RiskConfig) -> Self {
+        Self {
+            max_order_pct: risk.max_position_size_pct,
+            max_hourly_pct: risk.max_portfolio_exposure_pct,
+            state: Mutex::new(CapState::default()),
+        }
+    }
+
+    fn balance(&self, now: Instant) -> Option<f64> {
+        let state = self.state.lock().ok()?;
+        state
+            .balance
+            .filter(|(_, at)| now.duration_since(*at) < CAP_BALANCE_TTL)
+            .map(|(balance, _)| balance)
+    }
+
+    fn set_balance(&self, balance: f64, now: Instant) {
+        if let Ok(mut state) = self.state.lock() {
+            state.balance = Some((balance, now));
+        }
+    }
+
+    /// Count `size_usd` against the window, or refuse the order
+    fn reserve(&self, size_usd: f64, balance: f64, now: Instant) -> Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("Executor caps unavailable"))?;
+        while state
+            .placed
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= NOTIONAL_WINDOW)
+        {
+            state.placed.pop_front();
+        }
+        let max_order = balance * self.max_order_pct / 100.0;
+        if size_usd > max_order + 1e-9 {
+            anyhow::bail!(
+                "Order ${:.2} over the executor's cap of ${:.2} ({}% of ${:.2})",
+                size_usd,
+                max_order,
+                self.max_order_pct,
+                balance
+            );
+        }
+        let placed: f64 = state.placed.iter().map(|(_, usd)| usd).sum();
+        let max_hourly = balance * self.max_hourly_pct / 100.0;
+        if placed + size_usd > max_hourly + 1e-9 {
+            anyhow::bail!(
+                "Order ${:.2} would take the last hour's notional to ${:.2}, over the executor's cap of ${:.2} ({}% of ${:.2})",
+                size_usd,
+                placed + size_usd,
+                max_hourly,
+                self.max_hourly_pct,
+                balance
+            );
+        }
+        state.placed.push_back((now, size_usd));
+        Ok(())
+    }
+
+    /// Give back a reservation whose order wasn't placed
+    fn release(&self, size_usd: f64, at: Instant) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(index) = state
+                .placed
+                .iter()
+                .position(|&(placed_at, usd)| placed_at == at && usd == size_usd)
+            {
+                state.placed.remove(index);
+            }
+        }
+    }
+}
+
+/// Executor side: the keyed client, the other transactions its key signs, and the limits it
+/// enforces on what the detector sends
+pub struct ExecutorService {
+    interface: Arc<dyn MarketInterface + Send + Sync>,
+    custody: Custody,
+    caps: Option<OrderCaps>,
+    /// Highest detector epoch seen on a write
+    epoch: AtomicU64,
+}
+
+impl ExecutorService {
+    pub fn new(interface: Arc<dyn MarketInterface + Send + Sync>) -> Self {
+        Self {
+            interface,
+            custody: Custody::default(),
+            caps: None,
+            epoch: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_custody(mut self, custody: Custody) -> Self {
+        self.custody = custody;
+        self
+    }
+
+    pub fn with_caps(mut self, caps: OrderCaps) -> Self {
+        self.caps = Some(caps);
+        self
+    }
+
+    async fn answer(&self, envelope: Envelope<ExecRequest>) -> ExecResponse {
+        let id = envelope.id;
+        let result = if envelope.request.is_write() {
+            match self.check_epoch(envelope.epoch) {
+                Ok(()) => self.dispatch(envelope.request).await,
+                Err(e) => Err(e),
+            }
+        } else {
+            self.dispatch(envelope.request).await
+        };
+        match result {
+            Ok(result) => ExecResponse {
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err(e) => ExecResponse {
+                id,
+                result: None,
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    }
+
+    /// Refuse writes from an epoch older than one already seen: that detector was fenced by
+    /// its standby
+    fn check_epoch(&self, epoch: Option<u64>) -> Result<()> {
+        let Some(epoch) = epoch else {
+            return Ok(());
+        };
+        let seen = self.epoch.fetch_max(epoch, Ordering::SeqCst);
+        if epoch < seen {
+            warn!(
+                "🚫 Refused a request from epoch {} (epoch {} took over)",
+                epoch, seen
+            );
+            anyhow::bail!("Fenced: epoch {} took over from {}", seen, epoch);
+        }
+        Ok(())
+    }
+
+    /// Count a buy against the caps. Returns when it was counted, to release it if the order
+    /// isn't placed
+    async fn reserve(&self, order: &OrderRequest) -> Result<Option<Instant>> {
+        let Some(caps) = &self.caps else {
+            return Ok(None);
+        };
+        if !matches!(order.side.to_uppercase().as_str(), "BUY" | "YES") {
+            return Ok(None);
+        }
+        let now = Instant::now();
+        let balance = match caps.balance(now) {
+            Some(balance) => balance,
+            None => {
+                // No balance, no order: the caps would mean nothing
+                let balance = self
+                    .interface
+                    .get_balance()
+                    .await
+                    .context("Executor caps need the wallet balance")?;
+                caps.set_balance(balance, now);
+                balance
+            }
+        };
+        caps.reserve(order.size_usd.get(), balance, now)?;
+        Ok(Some(now))
+    }
+
+    fn release(&self, order: &OrderRequest, reserved: Option<Instant>) {
+        if let (Some(caps), Some(at)) = (&self.caps, reserved) {
+            caps.release(order.size_usd.get(), at);
+        }
+    }
+
+    fn redemption(&self) -> Result<&RedemptionManager> {
+        self.custody
+            .redemption
+            .as_ref()
+            .context("The executor has no RPC for redemptions (set POLYGON_WS_RPC)")
+    }
+
+    async fn dispatch(&self, request: ExecRequest) -> Result<Value> {
+        let interface = self.interface.as_ref();
+        Ok(match request {
+            ExecRequest::Ping => Value::Null,
+            ExecRequest::PlaceOrder { order } => {
+                let order = order.to_request()?;
+                info!(
+                    "📨 Order from detector: {} {} ${:.2} @ {:.4}",
+                    order.market_id, order.side, order.size_usd, order.price
+                );
+                let reserved = self.reserve(&order).await?;
+                let placed = interface
+                    .place_order(
+                        &order.market_id,
+                        &order.side,
+                        order.size_usd,
+                        order.price,
+                        order.order_type,
+                    )
+                    .await;
+                if placed.is_err() {
+                    self.release(&order, reserved);
+                }
+                Value::String(placed?)
+            }
+            ExecRequest::PlaceOrders { orders } => {
+                let orders = orders
+                    .iter()
+                    .map(WireOrder::to_request)
+                    .collect::<Result<Vec<_>>>()?;
+                info!("📨 {} orders from detector", orders.len());
+                // Each order passes the caps or fails on its own
+                let mut results: Vec<Result<String, String>> = Vec::with_capacity(orders.len());
+                let mut allowed = Vec::with_capacity(orders.len());
+                for order in &orders {
+                    match self.reserve(order).await {
+                        Ok(reserved) => {
+                            allowed.push((order.clone(), reserved));
+                            results.push(Ok(String::new()));
+                        }
+                        Err(e) => results.push(Err(format!("{:#}", e))),
+                    }
+                }
+                let requests: Vec<OrderRequest> =
+                    allowed.iter().map(|(order, _)| order.clone()).collect();
+                let mut placed = interface
+                    .place_orders(&requests)
+                    .await
+                    .into_iter()
+                    .zip(&allowed);
+                for result in results.iter_mut().filter(|r| r.is_ok()) {
+                    *result = match placed.next() {
+                        Some((Ok(order_id), _)) => Ok(order_id),
+                        Some((Err(e), (order, reserved))) => {
+                            self.release(order, *reserved);
+                            Err(format!("{:#}", e))
+                        }
+                        None => Err("Order not placed".to_string()),
+                    };
+                }
+                serde_json::to_value(results)?
+            }
+            ExecRequest::CancelOrder { order_id } => {
+                interface.cancel_order(&order_id).await?;
+                Value::Null
+            }
+            ExecRequest::GetOpenOrders { market_id } => {
+                serde_json::to_value(interface.get_open_orders(market_id.as_deref()).await?)?
+            }
+            ExecRequest::GetOrderStatus { order_id } => {
+                serde_json::to_value(interface.get_order_status(&order_id).await?)?
+            }
+            ExecRequest::GetBalance => serde_json::to_value(interface.get_balance().await?)?,
+            ExecRequest::GetCollateralBalances => {
+                serde_json::to_value(interface.get_collateral_balances().await?)?
+            }
+            ExecRequest::DowngradeToPaper => {
+                warn!("🚨 Detector downgraded execution to paper trading");
+                interface.downgrade_to_paper();
+                Value::Null
+            }
+            ExecRequest::RedeemPositions {
+                condition_id,
+                collateral,
+            } => Value::String(
+                self.redemption()?
+                    .redeem_positions(&condition_id, &collateral)
+                    .await?,
+            ),
+            ExecRequest::RedeemBatch {
+                conditions,
+                max_in_flight,
+                receipt_timeout_secs,
+            } => serde_json::to_value(
+                self.redemption()?
+                    .redeem_batch(
+                        &conditions,
+                        max_in_flight,
+                        Duration::from_secs(receipt_timeout_secs),
+                    )
+                    .await,
+            )?,
+            ExecRequest::MergePositions {
+                condition_id,
+                pairs,
+                collateral,
+            } => Value::String(
+                self.redemption()?
+                    .merge_positions(&condition_id, pairs, &collateral)
+                    .await?,
+            ),
+            ExecRequest::ConvertPositions {
+                neg_risk_market_id,
+                index_set,
+                sets,
+            } => {
+                let index_set: u128 = index_set
+                    .parse()
+                    .with_context(|| format!("Invalid index set {}", index_set))?;
+                Value::String(
+                    self.redemption()?
+                        .convert_positions(&neg_risk_market_id, index_set, sets)
+                        .await?,
+                )
+            }
+            ExecRequest::TopUpCollateral { required, balances } => {
+                let swapper = self
+                    .custody
+                    .swapper
+                    .as_ref()
+                    .context("Collateral auto-swap is off on the executor")?;
+                // A swap in progress is already covering the shortfall
+                match swapper.try_lock() {
+                    Ok(mut swapper) => {
+                        serde_json::to_value(swapper.top_up(required, &balances).await?)?
+                    }
+                    Err(_) => Value::Null,
+                }
+            }
+            ExecRequest::Fund { balance_usd } => {
+                let funding = self
+                    .custody
+                    .funding
+                    .clone()
+                    .context("The funding helper is off on the executor")?;
+                if let Err(e) = funding.settle(balance_usd) {
+                    warn!("⚠️ Funding settle failed: {}", e);
+                }
+                funding.check(balance_usd)?;
+                // Approvals and deposits wait for mainnet: answer now (one round at a time)
+                tokio::spawn(async move {
+                    if let Err(e) = funding.process().await {
+                        warn!("⚠️ Funding processing failed: {}", e);
+                    }
+                });
+                Value::Null
+            }
+        })
+    }
+}
+
+/// Executor side: answer detector calls on `socket_path` with `service`
+/// A stale socket file from a previous run is replaced; the new one is owner-only
+pub async fn serve(socket_path: &str, service: Arc<ExecutorService>) -> Result<()> {
+    let path = Path::new(socket_path);
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("Failed to remove {}", socket_path))?;
+    }
+    // Created owner-only: no window for another user to connect before the chmod below
+    // SAFETY: umask only swaps the process file mode mask
+    let umask = unsafe { libc::umask(0o177) };
+    let bound = UnixListener::bind(path);
+    // SAFETY: as above, restoring the mask it returned
+    unsafe { libc::umask(umask) };
+    let listener =
+        bound.with_context(|| format!("Failed to bind executor socket {}", socket_path))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    info!("🔐 Executor listening on {}", socket_path);
+
+    // Connections end with the server
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                info!("🔌 Detector connected");
+                let service = service.clone();
+                connections.spawn(async move {
+                    match handle_connection(stream, service).await {
+                        Ok(()) => info!("🔌 Detector disconnected"),
+                        Err(e) => warn!("⚠️ Detector connection failed: {:#}", e),
+                    }
+                });
+            }
+            Some(_) = connections.join_next() => {}
+        }
+    }
+}
+
+/// Each request runs on its own task and is answered when it completes, so a slow one
+/// (a redemption waiting for receipts) doesn't hold up the orders behind it
+async fn handle_connection(stream: UnixStream, service: Arc<ExecutorService>) -> Result<()> {
+    let (read, mut write) = stream.into_split();
+    let (responses, mut outgoing) = mpsc::channel::<ExecResponse>(RESPONSE_QUEUE);
+    let writer = tokio::spawn(async move {
+        while let Some(response) = outgoing.recv().await {
+            let mut out = serde_json::to_vec(&response)?;
+            out.push(b'\n');
+            write.write_all(&out).await?;
+        }
+        anyhow::Ok(())
+    });
+
+    let mut lines = BufReader::new(read).lines();
+    while let Some(line) = lines.next_line().await? {
+        let (service, responses) = (service.clone(), responses.clone());
+        // Detached: a detector going away doesn't cut a transaction short
+        tokio::spawn(async move {
+            let response = match serde_json::from_str::<Envelope<ExecRequest>>(&line) {
+                Ok(envelope) => service.answer(envelope).await,
+                Err(e) => ExecResponse {
+                    id: 0,
+                    result: None,
+                    error: Some(format!("Malformed request: {}", e)),
+                },
+            };
+            let _ = responses.send(response).await;
+        });
+    }
+    // The writer ends once the calls in flight have answered
+    drop(responses);
+    writer.await?
+}
+
+/// The `executor` command: serve order entry with the keyed Polymarket client, along with
+/// the redemptions, swaps and funding the detector forwards
+pub async fn run_executor(config: Config) -> Result<()> {
+    let socket_path = config
+        .agent
+        .executor_socket
+        .clone()
+        .context("Set EXECUTOR_SOCKET to the socket path the detector connects to")?;
+    if config.polygon_private_key.is_none() && !config.agent.paper_trading {
+        anyhow::bail!("The executor signs orders: set POLYGON_PRIVATE_KEY (or PAPER_TRADING=true)");
+    }
+    let client = PolymarketClient::new(
+        &config.polymarket,
+        config.agent.paper_trading,
+        config.polygon_private_key.clone(),
+    )?
+    .with_random_salts(config.obfuscation.randomize_order_ids);
+    let custody = Custody {
+        redemption: RedemptionManager::connect(&config).await,
+        swapper: CollateralSwapper::connect(&config)
+            .await
+            .map(tokio::sync::Mutex::new),
+        funding: FundingManager::from_config(&config).map(Arc::new),
+    };
+    let service = ExecutorService::new(Arc::new(client))
+        .with_custody(custody)
+        .with_caps(OrderCaps::new(&config.risk));
+    serve(&socket_path, Arc::new(service)).await
+}
+
+/// One connection to the executor, shared by concurrent calls: answers are matched to their
+/// callers by id
+struct Connection {
+    write: tokio::sync::Mutex<OwnedWriteHalf>,
+    waiting: Arc<Mutex<HashMap<u64, oneshot::Sender<ExecResponse>>>>,
+    closed: Arc<AtomicBool>,
+    reader: tokio::task::JoinHandle<()>,
+}
+
+impl Connection {
+    async fn open(socket_path: &str) -> Result<Self> {
+        let stream = UnixStream::connect(socket_path)
+            .await
+            .with_context(|| format!("Executor not reachable on {}", socket_path))?;
+        let (read, write) = stream.into_split();
+        let waiting = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let reader = tokio::spawn(read_responses(read, waiting.clone(), closed.clone()));
+        Ok(Self {
+            write: tokio::sync::Mutex::new(write),
+            waiting,
+            closed,
+            reader,
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut waiting) = self.waiting.lock() {
+            waiting.remove(&id);
+        }
+    }
+
+    async fn round_trip(&self, id: u64, line: &[u8], timeout: Duration) -> Result<ExecResponse> {
+        let (answer, answered) = oneshot::channel();
+        if let Ok(mut waiting) = self.waiting.lock() {
+            waiting.insert(id, answer);
+        }
+        if self.is_closed() {
+            self.forget(id);
+            anyhow::bail!("Executor closed the connection");
+        }
+        if let Err(e) = self.write.lock().await.write_all(line).await {
+            self.closed.store(true, Ordering::SeqCst);
+            self.forget(id);
+            return Err(e.into());
+        }
+        match tokio::time::timeout(timeout, answered).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(anyhow!("Executor closed the connection")),
+            Err(_) => {
+                self.forget(id);
+                Err(anyhow!("Executor did not answer within {:?}", timeout))
+            }
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Hand each answer to its caller; at EOF, fail the calls still waiting
+async fn read_responses(
+    read: OwnedReadHalf,
+    waiting: Arc<Mutex<HashMap<u64, oneshot::Sender<ExecResponse>>>>,
+    closed: Arc<AtomicBool>,
+) {
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response: ExecResponse = match serde_json::from_str(&line) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("⚠️ Malformed answer from the executor: {}", e);
+                continue;
+            }
+        };
+        let caller = waiting
+            .lock()
+            .ok()
+            .and_then(|mut waiting| waiting.remove(&response.id));
+        if let Some(caller) = caller {
+            let _ = caller.send(response);
+        }
+    }
+    closed.store(true, Ordering::SeqCst);
+    if let Ok(mut waiting) = waiting.lock() {
+        waiting.clear();
+    }
+}
+
+fn encode(id: u64, epoch: Option<u64>, request: &ExecRequest) -> Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(&Envelope { id, epoch, request })?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Detector side: market data from the local (keyless) interface; order entry, balances and
+/// the transactions the key signs from the executor process
+pub struct RemoteExecutor {
+    socket_path: String,
+    market_data: Arc<dyn MarketInterface + Send + Sync>,
+    // Held only to open a connection; calls run concurrently on it
+    connection: tokio::sync::Mutex<Option<Arc<Connection>>>,
+    next_id: AtomicU64,
+    // Writes carry the failover epoch and stop while fenced
+    failover: OnceLock<Arc<Failover>>,
+}
+
+impl RemoteExecutor {
+    pub fn new(socket_path: &str, market_data: Arc<dyn MarketInterface + Send + Sync>) -> Self {
+        Self {
+            socket_path: socket_path.to_string(),
+            market_data,
+            connection: tokio::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+            failover: OnceLock::new(),
+        }
+    }
+
+    pub fn socket_path(&self) -> &str {
+        &self.socket_path
+    }
+
+    /// Stamp writes with this instance's epoch, and refuse them while it's fenced
+    pub fn set_failover(&self, failover: Arc<Failover>) {
+        let _ = self.failover.set(failover);
+    }
+
+    /// Check the executor answers
+    pub async fn ping(&self) -> Result<()> {
+        self.call(ExecRequest::Ping).await.map(|_| ())
+    }
+
+    /// `RedemptionManager::redeem_positions` on the executor
+    pub async fn redeem_positions(
+        &self,
+        condition_id: &str,
+        collateral: &PositionCollateral,
+    ) -> Result<String> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::RedeemPositions {
+                condition_id: condition_id.to_string(),
+                collateral: collateral.clone(),
+            })
+            .await?,
+        )?)
+    }
+
+    /// `RedemptionManager::redeem_batch` on the executor
+    pub async fn redeem_batch(
+        &self,
+        conditions: &[(String, PositionCollateral)],
+        max_in_flight: usize,
+        receipt_timeout: Duration,
+    ) -> Result<Vec<RedemptionResult>> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::RedeemBatch {
+                conditions: conditions.to_vec(),
+                max_in_flight,
+                receipt_timeout_secs: receipt_timeout.as_secs().max(1),
+            })
+            .await?,
+        )?)
+    }
+
+    /// `RedemptionManager::merge_positions` on the executor
+    pub async fn merge_positions(
+        &self,
+        condition_id: &str,
+        pairs: f64,
+        collateral: &PositionCollateral,
+    ) -> Result<String> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::MergePositions {
+                condition_id: condition_id.to_string(),
+                pairs,
+                collateral: collateral.clone(),
+            })
+            .await?,
+        )?)
+    }
+
+    /// `RedemptionManager::convert_positions` on the executor
+    pub async fn convert_positions(
+        &self,
+        neg_risk_market_id: &str,
+        index_set: u128,
+        sets: f64,
+    ) -> Result<String> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::ConvertPositions {
+                neg_risk_market_id: neg_risk_market_id.to_string(),
+                index_set: index_set.to_string(),
+                sets,
+            })
+            .await?,
+        )?)
+    }
+
+    /// `CollateralSwapper::top_up` on the executor: None when no swap was needed (or one is
+    /// already running)
+    pub async fn top_up_collateral(
+        &self,
+        required: Collateral,
+        balances: CollateralBalances,
+    ) -> Result<Option<String>> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::TopUpCollateral { required, balances })
+                .await?,
+        )?)
+    }
+
+    /// Settle, raise and execute the executor's funding requests against `balance_usd`
+    pub async fn fund(&self, balance_usd: f64) -> Result<()> {
+        self.call(ExecRequest::Fund { balance_usd }).await?;
+        Ok(())
+    }
+
+    /// The open connection, or a new one when there is none or it closed
+    async fn connection(&self) -> Result<Arc<Connection>> {
+        let mut connection = self.connection.lock().await;
+        if let Some(open) = connection.as_ref().filter(|c| !c.is_closed()) {
+            return Ok(open.clone());
+        }
+        let open = Arc::new(Connection::open(&self.socket_path).await?);
+        *connection = Some(open.clone());
+        Ok(open)
+    }
+
+    async fn call(&self, request: ExecRequest) -> Result<Value> {
+        let epoch = match self.failover.get() {
+            Some(failover) if request.is_write() => {
+                failover.ensure_trading()?;
+                Some(failover.heartbeat().epoch)
+            }
+            _ => None,
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let line = encode(id, epoch, &request)?;
+        let attempts = if request.is_write() { 1 } else { 2 };
+        let mut last_error = None;
+        for _ in 0..attempts {
+            let connection = self.connection().await?;
+            match connection.round_trip(id, &line, request.timeout()).await {
+                Ok(ExecResponse {
+                    error: Some(error), ..
+                }) => return Err(anyhow!("Executor: {}", error)),
+                Ok(response) => return Ok(response.result.unwrap_or(Value::Null)),
+                // The executor restarted: start over on a fresh connection
+                Err(e) if connection.is_closed() => {
+                    warn!("⚠️ Executor connection lost: {:#}", e);
+                    last_error = Some(e);
+                }
+                // Slow, not gone: the connection stays up for the other calls
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow!("Executor call failed")))
+    }
+}
+
+#[async_trait]
+impl MarketInterface for RemoteExecutor {
+    async fn get_active_markets(&self) -> Result<Vec<MarketData>> {
+        self.market_data.get_active_markets().await
+    }
+
+    async fn get_market_details(&self, market_id: &str) -> Result<MarketData> {
+        self.market_data.get_market_details(market_id).await
+    }
+
+    async fn get_balance(&self) -> Result<f64> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::GetBalance).await?,
+        )?)
+    }
+
+    async fn get_collateral_balances(&self) -> Result<CollateralBalances> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::GetCollateralBalances).await?,
+        )?)
+    }
+
+    async fn place_order(
+        &self,
+        market_id: &MarketId,
+        side: &str,
+        size: UsdAmount,
+        price: Price,
+        order_type: OrderType,
+    ) -> Result<String> {
+        let order = WireOrder::from_request(&OrderRequest {
+            market_id: market_id.clone(),
+            side: side.to_string(),
+            size_usd: size,
+            price,
+            order_type,
+        });
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::PlaceOrder { order }).await?,
+        )?)
+    }
+
+    async fn place_orders(&self, orders: &[OrderRequest]) -> Vec<Result<String>> {
+        let request = ExecRequest::PlaceOrders {
+            orders: orders.iter().map(WireOrder::from_request).collect(),
+        };
+        let results = self.call(request).await.and_then(|value| {
+            serde_json::from_value::<Vec<Result<String, String>>>(value).map_err(Into::into)
+        });
+        match results {
+            Ok(results) => results
+                .into_iter()
+                .map(|result| result.map_err(|e| anyhow!(e)))
+                .collect(),
+            Err(e) => orders.iter().map(|_| Err(anyhow!("{:#}", e))).collect(),
+        }
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        self.call(ExecRequest::CancelOrder {
+            order_id: order_id.to_string(),
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn get_open_orders(&self, market_id: Option<&str>) -> Result<Vec<OpenOrder>> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::GetOpenOrders {
+                market_id: market_id.map(str::to_string),
+            })
+            .await?,
+        )?)
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        Ok(serde_json::from_value(
+            self.call(ExecRequest::GetOrderStatus {
+                order_id: order_id.to_string(),
+            })
+            .await?,
+        )?)
+    }
+
+    async fn get_orderbook(&self, asset_id: &str) -> Result<OrderBook> {
+        self.market_data.get_orderbook(asset_id).await
+    }
+
+    fn downgrade_to_paper(&self) {
+        // Sync hook: sent on its own connection
+        let socket_path = self.socket_path.clone();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        tokio::spawn(async move {
+            let sent = async {
+                let line = encode(id, None, &ExecRequest::DowngradeToPaper)?;
+                Connection::open(&socket_path)
+                    .await?
+                    .round_trip(id, &line, CALL_TIMEOUT)
+                    .await
+            };
+            if let Err(e) = sent.await {
+                warn!(
+                    "⚠️ Failed to downgrade the executor to paper trading: {:#}",
+                    e
+                );
+            }
+        });
+    }
+
+    async fn get_market_metadata(&self, market_id: &str) -> Result<MarketData> {
+        self.market_data.get_market_metadata(market_id).await
+    }
+
+    async fn get_market_by_slug(&self, slug: &str) -> Result<MarketData> {
+        self.market_data.get_market_by_slug(slug).await
+    }
+
+    async fn get_fee_rate_bps(&self, asset_id: &str) -> Result<Option<u32>> {
+        self.market_data.get_fee_rate_bps(asset_id).await
+    }
+
+    async fn get_event_series(&self) -> Result<Vec<EventSeries>> {
+        self.market_data.get_event_series().await
+    }
+
+    async fn get_series_markets(&self, series_id: &str) -> Result<Vec<MarketData>> {
+        self.market_data.get_series_markets(series_id).await
+    }
+
+    async fn poll_new_markets(&self) -> Result<Vec<MarketData>> {
+        self.market_data.poll_new_markets().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::MarketSimulator;
+
+    #[tokio::test]
+    async fn test_calls_survive_executor_restart() {
+        let socket = std::env::temp_dir().join(format!("executor-{}.sock", uuid::Uuid::new_v4()));
+        let socket = socket.to_str().unwrap().to_string();
+        let simulator: Arc<dyn MarketInterface + Send + Sync> = Arc::new(MarketSimulator::new());
+
+        let path = socket.clone();
+        let executor = Arc::new(ExecutorService::new(simulator.clone()));
+        let service = executor.clone();
+        let server = tokio::spawn(async move { serve(&path, service).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let remote = RemoteExecutor::new(&socket, simulator.clone());
+        assert_eq!(remote.get_balance().await.unwrap(), 10_000.0);
+        assert!(remote.get_open_orders(None).await.unwrap().is_empty());
+        assert_eq!(
+            remote.get_order_status("missing").await.unwrap(),
+            OrderStatus::Unknown
+        );
+        // Executor errors come back as errors on a connection that stays usable
+        let err = remote.cancel_order("missing").await.unwrap_err();
+        assert!(err.to_string().contains("not open"));
+
+        // Restart the executor: the next call reconnects
+        server.abort();
+        let _ = server.await;
+        let path = socket.clone();
+        let server = tokio::spawn(async move { serve(&path, executor).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(remote.get_balance().await.unwrap(), 10_000.0);
+
+        server.abort();
+        let _ = fs::remove_file(&socket);
+    }
+
+    fn simulator() -> Arc<MarketSimulator> {
+        let simulator = Arc::new(MarketSimulator::new());
+        simulator.load_markets(vec![MarketData {
+            id: MarketId::from("m1"),
+            yes_price: 0.5,
+            no_price: 0.5,
+            ..Default::default()
+        }]);
+        simulator
+    }
+
+    fn buy(size_usd: f64) -> ExecRequest {
+        ExecRequest::PlaceOrder {
+            order: WireOrder {
+                market_id: MarketId::from("m1"),
+                side: "YES".to_string(),
+                size_usd: UsdAmount::new(size_usd),
+                price: Price::new(0.5),
+                order_type: "GTC".to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_executor_caps_orders_on_its_own_balance() {
+        let risk = RiskConfig {
+            max_position_size_pct: 5.0,
+            max_portfolio_exposure_pct: 12.0,
+            ..RiskConfig::default()
+        };
+        // $10,000 wallet: $500 an order, $1,200 an hour
+        let service = ExecutorService::new(simulator()).with_caps(OrderCaps::new(&risk));
+        let err = service.dispatch(buy(600.0)).await.unwrap_err();
+        assert!(err.to_string().contains("cap"));
+        assert!(service.dispatch(buy(500.0)).await.is_ok());
+        assert!(service.dispatch(buy(500.0)).await.is_ok());
+        assert!(service.dispatch(buy(500.0)).await.is_err());
+        assert!(service.dispatch(buy(200.0)).await.is_ok());
+
+        // A refused order in a batch fails alone
+        let service = ExecutorService::new(simulator()).with_caps(OrderCaps::new(&risk));
+        let ExecRequest::PlaceOrder { order } = buy(400.0) else {
+            unreachable!()
+        };
+        let orders = vec![
+            order.clone(),
+            WireOrder {
+                size_usd: UsdAmount::new(900.0),
+                ..order.clone()
+            },
+            order,
+        ];
+        let results: Vec<Result<String, String>> = serde_json::from_value(
+            service
+                .dispatch(ExecRequest::PlaceOrders { orders })
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(results[1].as_ref().unwrap_err().contains("cap"));
+    }
+
+    #[tokio::test]
+    async fn test_executor_fences_older_epochs() {
+        let service = ExecutorService::new(simulator());
+        let request = |epoch, request| Envelope {
+            id: 1,
+            epoch: Some(epoch),
+            request,
+        };
+        assert!(service.answer(request(2, buy(10.0))).await.error.is_none());
+        // The old primary can still read, but not trade
+        let fenced = service.answer(request(1, buy(10.0))).await;
+        assert!(fenced.error.unwrap().contains("Fenced"));
+        let read = service.answer(request(1, ExecRequest::GetBalance)).await;
+        assert!(read.error.is_none());
+        assert!(service.answer(request(3, buy(10.0))).await.error.is_none());
+    }
+}
@@ -0,0 +1,212 @@
+//! Maker or taker, per leg
+//!
+//! Crossing the spread fills now and pays the taker fee on the ask. Posting a tick above the
+//! best bid pays the maker fee on a better price, but may never fill. A leg takes when waiting
+//! is what it can't afford: the market is about to end, the edge is big enough that someone
+//! else will take it first, or the book is moving. Otherwise it posts.
+//!
+//! A pair posts at most one leg. The posted leg is booked as it fills; once its deadline passes
+//! it is cancelled, and the part of the taker leg it left uncovered is sold back. The entry's
+//! expected edge stays priced as if every leg took: the improvement may never fill.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::OrderRoleConfig;
+use crate::ids::OrderId;
+
+/// Which side of the spread a leg trades on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LiquidityRole {
+    /// Rests inside the spread, a tick above the best bid
+    Maker,
+    /// Crosses the spread at the ask
+    Taker,
+}
+
+/// A leg's book at the signal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LegBook {
+    pub best_bid: Option<f64>,
+    pub best_ask: f64,
+    /// Changes of the touch over the feature tracker's shortest window, None without it
+    pub recent_changes: Option<usize>,
+}
+
+/// Role of a leg, the price it goes out at and the fee that price pays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoleChoice {
+    pub role: LiquidityRole,
+    pub price: f64,
+    pub fee_bps: f64,
+    /// Why the leg got its role
+    pub reason: &'static str,
+}
+
+/// The taker leg of a pair whose other leg rests
+#[derive(Debug, Clone, PartialEq)]
+pub struct TakenLeg {
+    pub leg_id: String,
+    pub side: String,
+    pub price: f64,
+    pub size_usd: f64,
+}
+
+/// The posted leg of an arbitrage pair, until it fills or its deadline passes
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestingMakerLeg {
+    pub group_id: String,
+    pub leg_id: String,
+    pub market_id: String,
+    pub side: String,
+    pub order_id: OrderId,
+    pub price: f64,
+    pub size_usd: f64,
+    pub cancel_at: DateTime<Utc>,
+    /// The pair's other leg, None when it wasn't placed
+    pub taker: Option<TakenLeg>,
+}
+
+impl RestingMakerLeg {
+    /// Part of the taker leg the maker leg left uncovered once `filled` (0-1) of it matched
+    pub fn uncovered_usd(&self, filled: f64) -> f64 {
+        self.taker
+            .as_ref()
+            .map_or(0.0, |taker| taker.size_usd * (1.0 - filled.clamp(0.0, 1.0)))
+    }
+}
+
+/// Cross the spread at the ask
+pub fn taker(config: &OrderRoleConfig, book: &LegBook, reason: &'static str) -> RoleChoice {
+    RoleChoice {
+        role: LiquidityRole::Taker,
+        price: book.best_ask,
+        fee_bps: config.taker_fee_bps,
+        reason,
+    }
+}
+
+/// Fee a fill in `role` pays
+pub fn fee_bps(config: &OrderRoleConfig, role: LiquidityRole) -> f64 {
+    match role {
+        LiquidityRole::Maker => config.maker_fee_bps,
+        LiquidityRole::Taker => config.taker_fee_bps,
+    }
+}
+
+/// Role of a leg of an entry with `edge_bps` (before fees) in a market ending in
+/// `secs_to_expiry`. Always the taker with role selection off
+pub fn choose_role(
+    config: &OrderRoleConfig,
+    book: &LegBook,
+    edge_bps: f64,
+    secs_to_expiry: Option<i64>,
+) -> RoleChoice {
+    let take = |reason| taker(config, book, reason);
+    if !config.enabled {
+        return take("role selection off");
+    }
+    if secs_to_expiry.is_some_and(|secs| secs < config.urgent_secs as i64) {
+        return take("market ending");
+    }
+    if edge_bps >= config.take_edge_bps {
+        return take("edge too big to wait for");
+    }
+    let Some(changes) = book.recent_changes else {
+        return take("no book history");
+    };
+    if changes > config.max_book_changes {
+        return take("book moving");
+    }
+    let tick = config.tick.max(0.0001);
+    let Some(price) = book
+        .best_bid
+        .map(|bid| ((bid + tick) / tick).round() * tick)
+        .filter(|price| *price < book.best_ask - 1e-9)
+    else {
+        return take("no room inside the spread");
+    };
+    RoleChoice {
+        role: LiquidityRole::Maker,
+        price,
+        fee_bps: config.maker_fee_bps,
+        reason: "stable book, edge to wait for",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> OrderRoleConfig {
+        OrderRoleConfig {
+            enabled: true,
+            maker_fee_bps: 0.0,
+            taker_fee_bps: 40.0,
+            take_edge_bps: 150.0,
+            urgent_secs: 3600,
+            max_book_changes: 3,
+            tick: 0.01,
+            maker_cancel_secs: 30,
+        }
+    }
+
+    fn book(best_bid: f64, recent_changes: usize) -> LegBook {
+        LegBook {
+            best_bid: Some(best_bid),
+            best_ask: 0.50,
+            recent_changes: Some(recent_changes),
+        }
+    }
+
+    #[test]
+    fn test_posts_inside_the_spread_of_a_stable_book() {
+        let choice = choose_role(&config(), &book(0.47, 1), 80.0, Some(86_400));
+        assert_eq!(choice.role, LiquidityRole::Maker);
+        assert!((choice.price - 0.48).abs() < 1e-9);
+        assert_eq!(choice.fee_bps, 0.0);
+    }
+
+    #[test]
+    fn test_takes_when_waiting_costs_more() {
+        let cases = [
+            (book(0.47, 1), 80.0, Some(600), "market ending"),
+            (book(0.47, 1), 200.0, None, "edge too big to wait for"),
+            (book(0.47, 9), 80.0, None, "book moving"),
+            (book(0.49, 0), 80.0, None, "no room inside the spread"),
+        ];
+        for (book, edge_bps, expiry, reason) in cases {
+            let choice = choose_role(&config(), &book, edge_bps, expiry);
+            assert_eq!(
+                (choice.role, choice.price, choice.reason),
+                (LiquidityRole::Taker, 0.50, reason)
+            );
+            assert_eq!(choice.fee_bps, 40.0);
+        }
+    }
+
+    #[test]
+    fn test_unfilled_maker_leg_uncovers_its_taker_leg() {
+        let leg = RestingMakerLeg {
+            group_id: "arb_m1".to_string(),
+            leg_id: "arb_m1_YES".to_string(),
+            market_id: "m1".to_string(),
+            side: "YES".to_string(),
+            order_id: OrderId::new("o1"),
+            price: 0.48,
+            size_usd: 10.0,
+            cancel_at: Utc::now(),
+            taker: Some(TakenLeg {
+                leg_id: "arb_m1_NO".to_string(),
+                side: "NO".to_string(),
+                price: 0.50,
+                size_usd: 10.0,
+            }),
+        };
+        assert!((leg.uncovered_usd(0.25) - 7.5).abs() < 1e-9);
+        assert_eq!(leg.uncovered_usd(1.0), 0.0);
+        assert_eq!(
+            RestingMakerLeg { taker: None, ..leg }.uncovered_usd(0.0),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use utoipa::ToSchema;
+
+use crate::config::SettlementConfig;
+use crate::polymarket::{MarketData, TokenTransfer, TransferDirection};
+
+/// Mismatches kept for the status report (oldest evicted first)
+const MAX_MISMATCHES: usize = 100;
+
+/// A fill (or merge) whose outcome tokens should move through the funder wallet
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExpectedSettlement {
+    pub trade_id: String,
+    pub market_id: String,
+    pub asset_id: String,
+    pub direction: TransferDirection,
+    pub shares: f64,
+    /// Shares seen moving on-chain so far
+    pub settled_shares: f64,
+    pub expected_at: DateTime<Utc>,
+}
+
+impl ExpectedSettlement {
+    fn remaining(&self) -> f64 {
+        (self.shares - self.settled_shares).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// No tokens moved at all
+    Missing,
+    /// Fewer tokens moved than the fill was for
+    Short,
+}
+
+/// An expected settlement still (partly) unseen after the timeout
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SettlementMismatch {
+    pub kind: MismatchKind,
+    pub expected: ExpectedSettlement,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SettlementStatus {
+    /// Wallet whose transfers are watched
+    pub wallet: String,
+    pub pending: Vec<ExpectedSettlement>,
+    pub confirmed: u64,
+    pub mismatches: u64,
+    /// Transfers no fill claimed before the timeout (redemptions, manual moves, better fills)
+    pub unmatched_transfers: u64,
+    pub recent_mismatches: Vec<SettlementMismatch>,
+}
+
+/// Reconciles the agent's fills against the ERC-1155 transfers of the funder wallet: each
+/// fill expects its shares in (exits buy the opposite side, so they expect tokens in too),
+/// each merge expects both legs out. Transfers may land before the fill check records its
+/// expectation, so unclaimed transfers are held until the timeout
+pub struct SettlementReconciler {
+    wallet: String,
+    timeout: Duration,
+    tolerance_pct: f64,
+    pending: Vec<ExpectedSettlement>,
+    /// Transfers (and the part of them left over) not yet matched to a fill
+    unclaimed: VecDeque<(TokenTransfer, DateTime<Utc>)>,
+    confirmed: u64,
+    mismatches: u64,
+    unmatched_transfers: u64,
+    recent_mismatches: VecDeque<SettlementMismatch>,
+}
+
+impl SettlementReconciler {
+    pub fn new(config: &SettlementConfig, wallet: String) -> Self {
+        Self {
+            wallet,
+            timeout: Duration::seconds(config.timeout_secs as i64),
+            tolerance_pct: config.tolerance_pct,
+            pending: Vec::new(),
+            unclaimed: VecDeque::new(),
+            confirmed: 0,
+            mismatches: 0,
+            unmatched_transfers: 0,
+            recent_mismatches: VecDeque::new(),
+        }
+    }
+
+    /// Expect the shares a filled `side` order of `size_usd` at `price` buys
+    pub fn expect_fill(
+        &mut self,
+        market: &MarketData,
+        trade_id: &str,
+        side: &str,
+        size_usd: f64,
+        price: f64,
+        now: DateTime<Utc>,
+    ) {
+        if price > 0.0 {
+            self.expect_shares(market, trade_id, side, size_usd / price, now);
+        }
+    }
+
+    /// Expect `shares` of `side` in (exits, whose shares are the position's recorded fill)
+    pub fn expect_shares(
+        &mut self,
+        market: &MarketData,
+        trade_id: &str,
+        side: &str,
+        shares: f64,
+        now: DateTime<Utc>,
+    ) {
+        // asset_ids follow the [NO, YES] convention
+        let asset_id = match side {
+            "YES" => market.asset_ids.get(1),
+            _ => market.asset_ids.first(),
+        };
+        if let Some(asset_id) = asset_id.filter(|_| shares > 0.0) {
+            self.expect(
+                trade_id,
+                &market.id,
+                asset_id,
+                TransferDirection::In,
+                shares,
+                now,
+            );
+        }
+    }
+
+    /// Expect `pairs` shares of both outcomes to leave when a pair is merged into collateral
+    pub fn expect_merge(
+        &mut self,
+        market: &MarketData,
+        group_id: &str,
+        pairs: f64,
+        now: DateTime<Utc>,
+    ) {
+        for asset_id in &market.asset_ids {
+            self.expect(
+                group_id,
+                &market.id,
+                asset_id,
+                TransferDirection::Out,
+                pairs,
+                now,
+            );
+        }
+    }
+
+    fn expect(
+        &mut self,
+        trade_id: &str,
+        market_id: &str,
+        asset_id: &str,
+        direction: TransferDirection,
+        shares: f64,
+        now: DateTime<Utc>,
+    ) {
+        if shares <= 0.0 {
+            return;
+        }
+        let mut expected = ExpectedSettlement {
+            trade_id: trade_id.to_string(),
+            market_id: market_id.to_string(),
+            asset_id: asset_id.to_string(),
+            direction,
+            shares,
+            settled_shares: 0.0,
+            expected_at: now,
+        };
+        // Claim transfers that beat the fill check here, oldest first
+        for (transfer, _) in self.unclaimed.iter_mut() {
+            if transfer.asset_id == expected.asset_id && transfer.direction == direction {
+                let taken = transfer.shares.min(expected.remaining());
+                transfer.shares -= taken;
+                expected.settled_shares += taken;
+            }
+        }
+        self.unclaimed.retain(|(transfer, _)| transfer.shares > 0.0);
+        if is_settled(&expected, self.tolerance_pct) {
+            self.confirmed += 1;
+        } else {
+            self.pending.push(expected);
+        }
+    }
+
+    /// Apply a transfer to the oldest matching expectations; what none of them needs is held
+    /// for fills not recorded yet. Returns the trade ids it completed
+    pub fn observe(&mut self, transfer: TokenTransfer, now: DateTime<Utc>) -> Vec<String> {
+        let mut left = transfer.shares;
+        for expected in self.pending.iter_mut() {
+            if left <= 0.0 {
+                break;
+            }
+            if expected.asset_id == transfer.asset_id && expected.direction == transfer.direction {
+                let taken = left.min(expected.remaining());
+                expected.settled_shares += taken;
+                left -= taken;
+            }
+        }
+
+        let mut completed = Vec::new();
+        let tolerance = self.tolerance_pct;
+        self.pending.retain(|expected| {
+            let settled = is_settled(expected, tolerance);
+            if settled {
+                completed.push(expected.trade_id.clone());
+            }
+            !settled
+        });
+        self.confirmed += completed.len() as u64;
+
+        if left > 0.0 {
+            let rest = TokenTransfer {
+                shares: left,
+                ..transfer
+            };
+            self.unclaimed.push_back((rest, now));
+        }
+        completed
+    }
+
+    /// Drop what has waited longer than the timeout: expectations become mismatches (returned
+    /// for alerting), unclaimed transfers are counted
+    pub fn sweep(&mut self, now: DateTime<Utc>) -> Vec<SettlementMismatch> {
+        let cutoff = now - self.timeout;
+        let (expired, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|expected| expected.expected_at < cutoff);
+        self.pending = pending;
+
+        let before = self.unclaimed.len();
+        self.unclaimed.retain(|(_, at)| *at >= cutoff);
+        self.unmatched_transfers += (before - self.unclaimed.len()) as u64;
+
+        let mismatches: Vec<SettlementMismatch> = expired
+            .into_iter()
+            .map(|expected| SettlementMismatch {
+                kind: if expected.settled_shares > 0.0 {
+                    MismatchKind::Short
+                } else {
+                    MismatchKind::Missing
+                },
+                expected,
+                detected_at: now,
+            })
+            .collect();
+        for mismatch in &mismatches {
+            self.mismatches += 1;
+            self.recent_mismatches.push_back(mismatch.clone());
+            if self.recent_mismatches.len() > MAX_MISMATCHES {
+                self.recent_mismatches.pop_front();
+            }
+        }
+        mismatches
+    }
+
+    pub fn status(&self) -> SettlementStatus {
+        SettlementStatus {
+            wallet: self.wallet.clone(),
+            pending: self.pending.clone(),
+            confirmed: self.confirmed,
+            mismatches: self.mismatches,
+            unmatched_transfers: self.unmatched_transfers,
+            recent_mismatches: self.recent_mismatches.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+/// Whether all but `tolerance_pct` of the expected shares have moved
+fn is_settled(expected: &ExpectedSettlement, tolerance_pct: f64) -> bool {
+    expected.settled_shares >= expected.shares * (1.0 - tolerance_pct)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(asset_id: &str, shares: f64, direction: TransferDirection) -> TokenTransfer {
+        TokenTransfer {
+            asset_id: asset_id.to_string(),
+            shares,
+            direction,
+            tx_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_settlement_reconciliation() {
+        let config = SettlementConfig {
+            enabled: true,
+            timeout_secs: 60,
+            tolerance_pct: 0.02,
+        };
+        let mut reconciler = SettlementReconciler::new(&config, "0xfunder".to_string());
+        let market = MarketData {
+            id: "0xabc".into(),
+            question: "Will it happen?".to_string(),
+            yes_price: 0.5,
+            no_price: 0.5,
+            asset_ids: vec!["111".to_string(), "222".to_string()],
+            ..Default::default()
+        };
+        let now = Utc::now();
+
+        // YES fill of $50 @ 0.50 = 100 shares, settled in two transfers
+        reconciler.expect_fill(&market, "t1", "YES", 50.0, 0.50, now);
+        assert!(reconciler
+            .observe(transfer("222", 60.0, TransferDirection::In), now)
+            .is_empty());
+        let done = reconciler.observe(transfer("222", 39.5, TransferDirection::In), now);
+        assert_eq!(done, vec!["t1".to_string()]);
+
+        // Tokens that land before the fill check are claimed when it records the fill
+        reconciler.observe(transfer("111", 20.0, TransferDirection::In), now);
+        reconciler.expect_fill(&market, "t2", "NO", 10.0, 0.50, now);
+        assert_eq!(reconciler.status().confirmed, 2);
+
+        // A merge nobody settled, and a fill half settled, are reported after the timeout
+        reconciler.expect_merge(&market, "g1", 5.0, now);
+        reconciler.expect_fill(&market, "t3", "YES", 10.0, 0.50, now);
+        reconciler.observe(transfer("222", 5.0, TransferDirection::In), now);
+        assert!(reconciler.sweep(now + Duration::seconds(30)).is_empty());
+        let mismatches = reconciler.sweep(now + Duration::seconds(61));
+        assert_eq!(mismatches.len(), 3);
+        let short = mismatches
+            .iter()
+            .find(|m| m.expected.trade_id == "t3")
+            .unwrap();
+        assert_eq!(short.kind, MismatchKind::Short);
+        assert!(mismatches
+            .iter()
+            .filter(|m| m.expected.trade_id == "g1")
+            .all(|m| m.kind == MismatchKind::Missing));
+
+        let status = reconciler.status();
+        assert_eq!(status.mismatches, 3);
+        assert!(status.pending.is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! Order submission queue
+//!
+//! A burst of signals must not turn into a burst of concurrent order calls: the CLOB rate-limits
+//! them and the executor health tracker would then pause everything. At most `max_in_flight`
+//! submissions run at once; the rest wait by priority (exits and hedges, then time-sensitive
+//! snipes, then routine entries), oldest first: a position already held outranks a new one. When `max_queued` are already waiting, the least urgent
+//! (newest) waiter is shed for a more urgent newcomer, otherwise the newcomer is refused; a
+//! waiter that doesn't get a slot within `max_wait` gives up, its signal being stale by then.
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::config::ExecutionQueueConfig;
+use crate::scheduling::Priority;
+
+/// Counters of the submission queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    pub in_flight: usize,
+    pub queued: usize,
+    pub peak_in_flight: usize,
+    pub peak_queued: usize,
+    /// Submissions let through
+    pub submitted: u64,
+    /// ... of which had to wait for a slot
+    pub waited: u64,
+    /// Refused on a full queue or bumped by a more urgent submission
+    pub shed: u64,
+    pub timed_out: u64,
+}
+
+/// Queue rank of a submission, highest first. Unlike the decision loop, which runs snipes
+/// before protection checks, an order protecting a held position goes out before new entries
+fn urgency(priority: Priority) -> u8 {
+    match priority {
+        Priority::Protection => 2,
+        Priority::NewMarket => 1,
+        Priority::Routine => 0,
+    }
+}
+
+struct Waiter {
+    priority: Priority,
+    seq: u64,
+    grant: oneshot::Sender<SubmissionPermit>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_seq: u64,
+    waiting: Vec<Waiter>,
+    stats: QueueStats,
+}
+
+impl QueueState {
+    /// Most urgent, then oldest
+    fn pop_next(&mut self) -> Option<Waiter> {
+        let idx = self
+            .waiting
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, w)| (urgency(w.priority), Reverse(w.seq)))
+            .map(|(idx, _)| idx)?;
+        let waiter = self.waiting.swap_remove(idx);
+        self.stats.queued = self.waiting.len();
+        Some(waiter)
+    }
+}
+
+pub struct SubmissionQueue {
+    /// 0 = unlimited
+    max_in_flight: usize,
+    max_queued: usize,
+    max_wait: Duration,
+    state: Mutex<QueueState>,
+}
+
+/// A submission slot, given back when dropped
+pub struct SubmissionPermit {
+    queue: Option<Arc<SubmissionQueue>>,
+}
+
+impl Drop for SubmissionPermit {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue.take() {
+            queue.release();
+        }
+    }
+}
+
+impl SubmissionQueue {
+    pub fn new(config: &ExecutionQueueConfig) -> Self {
+        Self {
+            max_in_flight: config.max_in_flight,
+            max_queued: config.max_queued,
+            max_wait: Duration::from_millis(config.max_wait_ms),
+            state: Mutex::new(QueueState::default()),
+        }
+    }
+
+    /// No limit: every submission goes straight through
+    pub fn unlimited() -> Self {
+        Self::new(&ExecutionQueueConfig {
+            max_in_flight: 0,
+            max_queued: 0,
+            max_wait_ms: 0,
+        })
+    }
+
+    /// Wait for a submission slot
+    pub async fn acquire(self: &Arc<Self>, priority: Priority) -> Result<SubmissionPermit> {
+        let (seq, mut granted) = {
+            let mut state = self.lock();
+            if self.max_in_flight == 0
+                || (state.stats.in_flight < self.max_in_flight && state.waiting.is_empty())
+            {
+                state.stats.in_flight += 1;
+                state.stats.peak_in_flight = state.stats.peak_in_flight.max(state.stats.in_flight);
+                state.stats.submitted += 1;
+                return Ok(SubmissionPermit {
+                    queue: Some(self.clone()),
+                });
+            }
+
+            if state.waiting.len() >= self.max_queued {
+                let victim = state
+                    .waiting
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, w)| (urgency(w.priority), Reverse(w.seq)))
+                    .map(|(idx, w)| (idx, urgency(w.priority)));
+                state.stats.shed += 1;
+                match victim {
+                    // Dropping its sender fails the victim's wait
+                    Some((idx, victim)) if victim < urgency(priority) => {
+                        state.waiting.swap_remove(idx);
+                    }
+                    _ => anyhow::bail!(
+                        "Execution queue full ({} in flight, {} waiting)",
+                        state.stats.in_flight,
+                        state.waiting.len()
+                    ),
+                }
+            }
+
+            let (grant, granted) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter {
+                priority,
+                seq,
+                grant,
+            });
+            state.stats.queued = state.waiting.len();
+            state.stats.peak_queued = state.stats.peak_queued.max(state.stats.queued);
+            (seq, granted)
+        };
+
+        let permit = match tokio::time::timeout(self.max_wait, &mut granted).await {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_)) => anyhow::bail!("Bumped from the execution queue by a more urgent order"),
+            Err(_) => {
+                let mut state = self.lock();
+                if let Some(idx) = state.waiting.iter().position(|w| w.seq == seq) {
+                    state.waiting.swap_remove(idx);
+                    state.stats.queued = state.waiting.len();
+                    state.stats.timed_out += 1;
+                    anyhow::bail!(
+                        "No execution slot within {}ms ({} in flight)",
+                        self.max_wait.as_millis(),
+                        state.stats.in_flight
+                    );
+                }
+                drop(state);
+                // Granted (or bumped) just as the wait ran out
+                granted
+                    .try_recv()
+                    .map_err(|_| anyhow::anyhow!("Bumped from the execution queue"))?
+            }
+        };
+        let mut state = self.lock();
+        state.stats.submitted += 1;
+        state.stats.waited += 1;
+        Ok(permit)
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.lock().stats
+    }
+
+    /// Hand the slot of a dropped permit to the next waiter, or free it
+    fn release(self: Arc<Self>) {
+        let mut permit = SubmissionPermit {
+            queue: Some(self.clone()),
+        };
+        loop {
+            let waiter = {
+                let mut state = self.lock();
+                match state.pop_next() {
+                    Some(waiter) => waiter,
+                    None => {
+                        state.stats.in_flight = state.stats.in_flight.saturating_sub(1);
+                        permit.queue = None;
+                        return;
+                    }
+                }
+            };
+            // A waiter that gave up hands the permit back
+            match waiter.grant.send(permit) {
+                Ok(()) => return,
+                Err(unclaimed) => permit = unclaimed,
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(max_in_flight: usize, max_queued: usize, max_wait_ms: u64) -> Arc<SubmissionQueue> {
+        Arc::new(SubmissionQueue::new(&ExecutionQueueConfig {
+            max_in_flight,
+            max_queued,
+            max_wait_ms,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_urgent_submissions_first_and_overflow_shed() {
+        let queue = queue(1, 2, 5_000);
+        let running = queue.acquire(Priority::Routine).await.unwrap();
+
+        let (done_tx, mut done_rx) = tokio::sync::mpsc::unbounded_channel();
+        let spawn = |name: &'static str, priority: Priority| {
+            let queue = queue.clone();
+            let done_tx = done_tx.clone();
+            tokio::spawn(async move {
+                let result = queue.acquire(priority).await;
+                let _ = done_tx.send((name, result.is_ok()));
+                // Hold the slot briefly so grants are observed in order
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                drop(result);
+            })
+        };
+        let entry = spawn("entry", Priority::Routine);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let snipe = spawn("snipe", Priority::NewMarket);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(queue.stats().queued, 2);
+
+        // Full: the routine entry is shed for the exit
+        let exit = spawn("exit", Priority::Protection);
+        assert_eq!(done_rx.recv().await, Some(("entry", false)));
+        // Nothing below a snipe to shed for another routine entry
+        assert!(queue.acquire(Priority::Routine).await.is_err());
+
+        // The exit goes before the snipe queued ahead of it
+        drop(running);
+        assert_eq!(done_rx.recv().await, Some(("exit", true)));
+        assert_eq!(done_rx.recv().await, Some(("snipe", true)));
+        for task in [entry, snipe, exit] {
+            task.await.unwrap();
+        }
+
+        let stats = queue.stats();
+        assert_eq!(stats.in_flight, 0);
+        assert_eq!(stats.peak_in_flight, 1);
+        assert_eq!(stats.submitted, 3);
+        assert_eq!(stats.waited, 2);
+        assert_eq!(stats.shed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_wait_times_out() {
+        let queue = queue(1, 4, 20);
+        let running = queue.acquire(Priority::NewMarket).await.unwrap();
+        assert!(queue.acquire(Priority::NewMarket).await.is_err());
+        assert_eq!(queue.stats().timed_out, 1);
+        drop(running);
+        assert!(queue.acquire(Priority::Routine).await.is_ok());
+        assert_eq!(queue.stats().in_flight, 0);
+    }
+}
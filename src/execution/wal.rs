@@ -0,0 +1,782 @@
+//! Write-ahead log of live orders
+//!
+//! Every live order is appended to `order_wal.jsonl` (and synced) before it is posted, and
+//! marked done or failed once the exchange answers. An intent left without an answer means the
+//! process died with the order in flight: at the next start it is looked up on the exchange -
+//! resting among our open orders, filled in the wallet's trades, or never placed - instead of
+//! leaving "did the crash double-buy?" to guesswork.
+//!
+//! Writes are synced off the async workers, and the file is compacted periodically down to the
+//! intents still open.
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::clob::types::OrderType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{error, warn};
+
+use crate::analytics::cost_basis::{CostBasisBackfill, Fill};
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::markets::normalize_market_id;
+use crate::polymarket::{
+    CollateralBalances, EventSeries, MarketData, MarketInterface, OpenOrder, OrderBook,
+    OrderRequest, OrderStatus,
+};
+use crate::venues::Venue;
+
+pub const ORDER_WAL_FILE: &str = "order_wal.jsonl";
+
+/// Slack on the intent time when matching exchange orders and fills (clock skew)
+const MATCH_SLACK_SECS: u64 = 60;
+
+/// Slack on the intent price when matching exchange orders and fills
+const PRICE_SLACK: f64 = 0.005;
+
+/// An order about to be posted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub market_id: String,
+    /// As passed to the exchange client ("YES" / "NO" / "BUY" / "SELL")
+    pub side: String,
+    pub size_usd: f64,
+    pub price: f64,
+    pub order_type: String,
+}
+
+impl OrderIntent {
+    /// Book side the client posts it on
+    fn book_side(&self) -> &'static str {
+        match self.side.to_uppercase().as_str() {
+            "BUY" | "YES" => "BUY",
+            _ => "SELL",
+        }
+    }
+
+    /// Token the client posts it for: YES for buys, NO otherwise (as `PolymarketClient` does)
+    fn token<'a>(&self, asset_ids: &'a [String]) -> Option<&'a String> {
+        match self.book_side() {
+            "BUY" => asset_ids.get(1),
+            _ => asset_ids.first(),
+        }
+    }
+
+    /// Outcome of the token it is for
+    pub fn outcome(&self) -> &'static str {
+        match self.book_side() {
+            "BUY" => "YES",
+            _ => "NO",
+        }
+    }
+
+    /// Shares it asked for
+    fn shares(&self) -> f64 {
+        if self.price > 0.0 {
+            self.size_usd / self.price
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether a fill at `price` is within its limit
+    fn within_limit(&self, price: f64) -> bool {
+        match self.book_side() {
+            "BUY" => price <= self.price + PRICE_SLACK,
+            _ => price >= self.price - PRICE_SLACK,
+        }
+    }
+
+    /// Unix seconds from which exchange records can belong to it
+    fn since(&self) -> u64 {
+        (self.at.timestamp().max(0) as u64).saturating_sub(MATCH_SLACK_SECS)
+    }
+
+    /// Claim the unclaimed `fills` of `token` that can be this order's: its book side, within
+    /// its limit and no larger than what is left of its size. Returns the shares and their cost
+    fn claim(&self, token: &str, fills: &mut [(Fill, bool)]) -> (f64, f64) {
+        let wanted = self.shares();
+        let (mut shares, mut cost) = (0.0, 0.0);
+        for (fill, claimed) in fills.iter_mut() {
+            if *claimed
+                || fill.timestamp < self.since()
+                || fill.asset != token
+                || !fill.side.eq_ignore_ascii_case(self.book_side())
+                || !self.within_limit(fill.price)
+                || fill.size > wanted - shares + 1e-6
+            {
+                continue;
+            }
+            *claimed = true;
+            shares += fill.size;
+            cost += fill.size * fill.price;
+        }
+        (shares, cost)
+    }
+}
+
+/// The wallet's fills of a market, each flagged once an intent has claimed it
+type MarketFills = HashMap<String, Vec<(Fill, bool)>>;
+
+/// What became of an order the process died posting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum WalOutcome {
+    /// Resting on the book
+    Resting { order_id: String },
+    /// Filled (partly or fully) before the crash
+    Filled { shares: f64, price: f64 },
+    /// Neither resting nor filled: it never reached the book
+    NotPlaced,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum WalRecord {
+    Intent(OrderIntent),
+    Done {
+        seq: u64,
+        at: DateTime<Utc>,
+        order_id: String,
+    },
+    Failed {
+        seq: u64,
+        at: DateTime<Utc>,
+        error: String,
+    },
+    Reconciled {
+        seq: u64,
+        at: DateTime<Utc>,
+        #[serde(flatten)]
+        outcome: WalOutcome,
+    },
+}
+
+/// A crashed order and what the exchange says became of it
+#[derive(Debug, Clone)]
+pub struct Reconciliation {
+    pub intent: OrderIntent,
+    pub outcome: WalOutcome,
+}
+
+/// Intents without an answer after replaying `records`, by sequence number
+fn unanswered(records: &[WalRecord]) -> BTreeMap<u64, OrderIntent> {
+    let mut pending = BTreeMap::new();
+    for record in records {
+        match record {
+            WalRecord::Intent(intent) => {
+                pending.insert(intent.seq, intent.clone());
+            }
+            WalRecord::Done { seq, .. }
+            | WalRecord::Failed { seq, .. }
+            | WalRecord::Reconciled { seq, .. } => {
+                pending.remove(seq);
+            }
+        }
+    }
+    pending
+}
+
+pub struct OrderWal {
+    path: PathBuf,
+    file: Mutex<File>,
+    next_seq: AtomicU64,
+    /// Intents left unanswered by a previous run
+    pending: Mutex<BTreeMap<u64, OrderIntent>>,
+    /// Intents of this run still waiting for the exchange's answer
+    in_flight: Mutex<BTreeMap<u64, OrderIntent>>,
+}
+
+impl OrderWal {
+    /// Open `data_dir/order_wal.jsonl`, keeping only the intents a previous run left unanswered
+    pub fn open(data_dir: &str) -> Result<Self> {
+        fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create data dir {}", data_dir))?;
+        let path = Path::new(data_dir).join(ORDER_WAL_FILE);
+        let records = Self::read(&path)?;
+        let next_seq = records
+            .iter()
+            .map(|r| match r {
+                WalRecord::Intent(intent) => intent.seq,
+                WalRecord::Done { seq, .. }
+                | WalRecord::Failed { seq, .. }
+                | WalRecord::Reconciled { seq, .. } => *seq,
+            })
+            .max()
+            .map_or(1, |seq| seq + 1);
+        let pending = unanswered(&records);
+        let file = Self::rewrite(&path, pending.values())?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            next_seq: AtomicU64::new(next_seq),
+            pending: Mutex::new(pending),
+            in_flight: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Replace the file with `intents` alone; answered entries are dropped, so the file only
+    /// ever holds what a crash would leave open. Returns the new file, opened for appending
+    fn rewrite<'a>(path: &Path, intents: impl Iterator<Item = &'a OrderIntent>) -> Result<File> {
+        let tmp = path.with_extension("jsonl.tmp");
+        {
+            let mut file =
+                File::create(&tmp).with_context(|| format!("Failed to create {:?}", tmp))?;
+            for intent in intents {
+                serde_json::to_writer(&mut file, &WalRecord::Intent(intent.clone()))?;
+                file.write_all(b"\n")?;
+            }
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))?;
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {:?}", path))
+    }
+
+    /// Drop the answered entries of this run from the file. Appends wait while it is rewritten
+    pub fn compact(&self) -> Result<()> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow!("Order WAL lock poisoned"))?;
+        let mut open: BTreeMap<u64, OrderIntent> =
+            self.pending.lock().map(|p| p.clone()).unwrap_or_default();
+        if let Ok(in_flight) = self.in_flight.lock() {
+            open.extend(in_flight.iter().map(|(seq, intent)| (*seq, intent.clone())));
+        }
+        *file = Self::rewrite(&self.path, open.values())?;
+        Ok(())
+    }
+
+    fn read(path: &Path) -> Result<Vec<WalRecord>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut records = Vec::new();
+        for (n, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                // A crash mid-append tears the last line: a torn intent was never posted, a
+                // torn answer leaves its intent to reconciliation
+                Err(e) => warn!("⚠️ Skipping unreadable order WAL line {}: {}", n + 1, e),
+            }
+        }
+        Ok(records)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Intents a previous run left without an answer, oldest first
+    pub fn pending(&self) -> Vec<OrderIntent> {
+        self.pending
+            .lock()
+            .map(|p| p.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Append and sync one record. Only the write holds the lock; the sync runs on a handle
+    /// of its own
+    fn append(&self, record: &WalRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let file = {
+            let mut file = self
+                .file
+                .lock()
+                .map_err(|_| anyhow!("Order WAL lock poisoned"))?;
+            file.write_all(&line)?;
+            file.try_clone()?
+        };
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Record an order about to be posted; it must not be posted if this fails. Blocks on the
+    /// sync: async callers go through `WalInterface`
+    pub fn begin(
+        &self,
+        market_id: &MarketId,
+        side: &str,
+        size_usd: UsdAmount,
+        price: Price,
+        order_type: &OrderType,
+    ) -> Result<u64> {
+        let seq = self.next_seq.fetch_add(1, Ordering::SeqCst);
+        let intent = OrderIntent {
+            seq,
+            at: Utc::now(),
+            market_id: market_id.to_string(),
+            side: side.to_string(),
+            size_usd: size_usd.get(),
+            price: price.get(),
+            order_type: format!("{:?}", order_type),
+        };
+        // Tracked first, so a compaction in between keeps it
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            in_flight.insert(seq, intent.clone());
+        }
+        if let Err(e) = self.append(&WalRecord::Intent(intent)) {
+            if let Ok(mut in_flight) = self.in_flight.lock() {
+                in_flight.remove(&seq);
+            }
+            return Err(e.context("Failed to write order WAL"));
+        }
+        Ok(seq)
+    }
+
+    /// Record the exchange's answer to an order
+    pub fn complete(&self, seq: u64, result: &Result<String>) {
+        // Untracked first: a compaction in between drops the intent rather than keeping it
+        // without its answer
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            in_flight.remove(&seq);
+        }
+        let record = match result {
+            Ok(order_id) => WalRecord::Done {
+                seq,
+                at: Utc::now(),
+                order_id: order_id.clone(),
+            },
+            Err(e) => WalRecord::Failed {
+                seq,
+                at: Utc::now(),
+                error: format!("{:#}", e),
+            },
+        };
+        if let Err(e) = self.append(&record) {
+            error!("❌ Failed to mark order {} in the WAL: {:#}", seq, e);
+        }
+    }
+
+    /// Look every unanswered intent up on the exchange and close the ones it accounts for.
+    /// Fills are searched in `wallet`'s trades; without a wallet only resting orders can be
+    /// found, and intents not found stay open for the next start
+    pub async fn reconcile(
+        &self,
+        market: &dyn MarketInterface,
+        wallet: Option<(&CostBasisBackfill, &str)>,
+    ) -> Vec<Reconciliation> {
+        let mut resolved = Vec::new();
+        let mut fills = MarketFills::new();
+        for intent in self.pending() {
+            let outcome = match Self::lookup(&intent, market, wallet, &mut fills).await {
+                Ok(Some(outcome)) => outcome,
+                Ok(None) => {
+                    warn!(
+                        "⚠️ Order WAL entry {} ({} ${:.2} @ {:.4} on {}) not on the book; no wallet to search fills, left open",
+                        intent.seq, intent.side, intent.size_usd, intent.price, intent.market_id
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    warn!(
+                        "⚠️ Could not reconcile order WAL entry {} on {}: {:#}",
+                        intent.seq, intent.market_id, e
+                    );
+                    continue;
+                }
+            };
+            let record = WalRecord::Reconciled {
+                seq: intent.seq,
+                at: Utc::now(),
+                outcome: outcome.clone(),
+            };
+            if let Err(e) = self.append(&record) {
+                error!("❌ Failed to mark order {} reconciled: {:#}", intent.seq, e);
+                continue;
+            }
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.remove(&intent.seq);
+            }
+            resolved.push(Reconciliation { intent, outcome });
+        }
+        resolved
+    }
+
+    /// What the exchange knows of an intent; None when it can't tell without the wallet's fills.
+    /// Fills carry no order id (and a crashed intent has none to match), so an intent claims
+    /// the fills of its token and book side within its limit price, each no larger than what
+    /// is left of its size. A claimed fill belongs to no other intent
+    async fn lookup(
+        intent: &OrderIntent,
+        market: &dyn MarketInterface,
+        wallet: Option<(&CostBasisBackfill, &str)>,
+        fills: &mut MarketFills,
+    ) -> Result<Option<WalOutcome>> {
+        let market_id = normalize_market_id(&intent.market_id);
+        let open = market.get_open_orders(Some(&intent.market_id)).await?;
+        if let Some(order) = open.iter().find(|o| {
+            normalize_market_id(&o.market_id) == market_id
+                && o.side.eq_ignore_ascii_case(intent.book_side())
+                && (o.price - intent.price).abs() < 0.005
+                && o.created_at >= intent.since()
+        }) {
+            return Ok(Some(WalOutcome::Resting {
+                order_id: order.order_id.clone(),
+            }));
+        }
+
+        let Some((backfill, user)) = wallet else {
+            return Ok(None);
+        };
+        let details = market.get_market_metadata(&intent.market_id).await?;
+        let token = intent
+            .token(&details.asset_ids)
+            .ok_or_else(|| anyhow!("Market {} has no token ids", intent.market_id))?;
+        // Intents are looked up oldest first: the first one of a market fetches for all
+        let market_fills = match fills.entry(market_id.into_owned()) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let fetched = backfill
+                    .fills_since(user, &intent.market_id, intent.since())
+                    .await?;
+                entry.insert(fetched.into_iter().map(|fill| (fill, false)).collect())
+            }
+        };
+        let (shares, cost) = intent.claim(token, market_fills);
+        Ok(Some(if shares > 0.0 {
+            WalOutcome::Filled {
+                shares,
+                price: cost / shares,
+            }
+        } else {
+            WalOutcome::NotPlaced
+        }))
+    }
+}
+
+/// A market interface whose orders go through the WAL: each is recorded before it is posted
+/// and marked with the answer. Wraps the live order entry only
+pub struct WalInterface {
+    inner: Arc<dyn MarketInterface + Send + Sync>,
+    wal: Arc<OrderWal>,
+}
+
+impl WalInterface {
+    pub fn new(inner: Arc<dyn MarketInterface + Send + Sync>, wal: Arc<OrderWal>) -> Self {
+        Self { inner, wal }
+    }
+
+    /// Run a WAL write, and its sync, off the async workers
+    async fn on_wal<T: Send + 'static>(
+        &self,
+        write: impl FnOnce(&OrderWal) -> T + Send + 'static,
+    ) -> Result<T> {
+        let wal = self.wal.clone();
+        tokio::task::spawn_blocking(move || write(&wal))
+            .await
+            .map_err(|e| anyhow!("Order WAL task failed: {}", e))
+    }
+
+    /// Record the answer to `seq`
+    async fn complete(&self, seq: u64, result: &Result<String>) {
+        let answer = match result {
+            Ok(order_id) => Ok(order_id.clone()),
+            Err(e) => Err(anyhow!("{:#}", e)),
+        };
+        if let Err(e) = self.on_wal(move |wal| wal.complete(seq, &answer)).await {
+            error!("❌ Failed to mark order {} in the WAL: {:#}", seq, e);
+        }
+    }
+}
+
+#[async_trait]
+impl MarketInterface for WalInterface {
+    fn venue(&self) -> Venue {
+        self.inner.venue()
+    }
+
+    async fn get_active_markets(&self) -> Result<Vec<MarketData>> {
+        self.inner.get_active_markets().await
+    }
+
+    async fn get_market_details(&self, market_id: &str) -> Result<MarketData> {
+        self.inner.get_market_details(market_id).await
+    }
+
+    async fn get_market_metadata(&self, market_id: &str) -> Result<MarketData> {
+        self.inner.get_market_metadata(market_id).await
+    }
+
+    async fn get_market_by_slug(&self, slug: &str) -> Result<MarketData> {
+        self.inner.get_market_by_slug(slug).await
+    }
+
+    async fn get_fee_rate_bps(&self, asset_id: &str) -> Result<Option<u32>> {
+        self.inner.get_fee_rate_bps(asset_id).await
+    }
+
+    async fn get_event_series(&self) -> Result<Vec<EventSeries>> {
+        self.inner.get_event_series().await
+    }
+
+    async fn get_series_markets(&self, series_id: &str) -> Result<Vec<MarketData>> {
+        self.inner.get_series_markets(series_id).await
+    }
+
+    async fn get_balance(&self) -> Result<f64> {
+        self.inner.get_balance().await
+    }
+
+    async fn get_collateral_balances(&self) -> Result<CollateralBalances> {
+        self.inner.get_collateral_balances().await
+    }
+
+    async fn place_order(
+        &self,
+        market_id: &MarketId,
+        side: &str,
+        size: UsdAmount,
+        price: Price,
+        order_type: OrderType,
+    ) -> Result<String> {
+        let (market, intent_side, intent_type) =
+            (market_id.clone(), side.to_string(), order_type.clone());
+        let seq = self
+            .on_wal(move |wal| wal.begin(&market, &intent_side, size, price, &intent_type))
+            .await??;
+        let result = self
+            .inner
+            .place_order(market_id, side, size, price, order_type)
+            .await;
+        self.complete(seq, &result).await;
+        result
+    }
+
+    async fn place_orders(&self, orders: &[OrderRequest]) -> Vec<Result<String>> {
+        let intents = orders.to_vec();
+        let seqs = self
+            .on_wal(move |wal| {
+                let mut seqs = Vec::with_capacity(intents.len());
+                for order in &intents {
+                    match wal.begin(
+                        &order.market_id,
+                        &order.side,
+                        order.size_usd,
+                        order.price,
+                        &order.order_type,
+                    ) {
+                        Ok(seq) => seqs.push(seq),
+                        Err(e) => {
+                            // Nothing is posted: the intents already written get the failure
+                            for seq in &seqs {
+                                wal.complete(*seq, &Err(anyhow!("{:#}", e)));
+                            }
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(seqs)
+            })
+            .await
+            .and_then(|seqs| seqs);
+        let seqs = match seqs {
+            Ok(seqs) => seqs,
+            Err(e) => return orders.iter().map(|_| Err(anyhow!("{:#}", e))).collect(),
+        };
+        let results = self.inner.place_orders(orders).await;
+        for (seq, result) in seqs.iter().zip(&results) {
+            self.complete(*seq, result).await;
+        }
+        results
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        self.inner.cancel_order(order_id).await
+    }
+
+    async fn get_open_orders(&self, market_id: Option<&str>) -> Result<Vec<OpenOrder>> {
+        self.inner.get_open_orders(market_id).await
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        self.inner.get_order_status(order_id).await
+    }
+
+    async fn get_orderbook(&self, asset_id: &str) -> Result<OrderBook> {
+        self.inner.get_orderbook(asset_id).await
+    }
+
+    fn downgrade_to_paper(&self) {
+        self.inner.downgrade_to_paper()
+    }
+
+    async fn poll_new_markets(&self) -> Result<Vec<MarketData>> {
+        self.inner.poll_new_markets().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::MarketSimulator;
+
+    fn temp_dir() -> String {
+        std::env::temp_dir()
+            .join(format!("order-wal-{}", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_reopen_keeps_only_unanswered_intents() {
+        let dir = temp_dir();
+        let wal = OrderWal::open(&dir).unwrap();
+        let done = wal
+            .begin(
+                &MarketId::from("m1"),
+                "YES",
+                UsdAmount::new(10.0),
+                Price::new(0.5),
+                &OrderType::GTC,
+            )
+            .unwrap();
+        let failed = wal
+            .begin(
+                &MarketId::from("m2"),
+                "YES",
+                UsdAmount::new(10.0),
+                Price::new(0.5),
+                &OrderType::GTC,
+            )
+            .unwrap();
+        let crashed = wal
+            .begin(
+                &MarketId::from("m3"),
+                "NO",
+                UsdAmount::new(5.0),
+                Price::new(0.4),
+                &OrderType::FOK,
+            )
+            .unwrap();
+        wal.complete(done, &Ok("0xabc".to_string()));
+        wal.complete(failed, &Err(anyhow!("rejected")));
+        drop(wal);
+
+        let wal = OrderWal::open(&dir).unwrap();
+        let pending = wal.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].seq, crashed);
+        assert_eq!(pending[0].market_id, "m3");
+        // Sequence numbers keep growing across runs; answered entries were compacted away
+        assert!(
+            wal.begin(
+                &MarketId::from("m4"),
+                "YES",
+                UsdAmount::new(1.0),
+                Price::new(0.5),
+                &OrderType::GTC
+            )
+            .unwrap()
+                > crashed
+        );
+        let lines = fs::read_to_string(wal.path()).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_compaction_keeps_only_orders_in_flight() {
+        let dir = temp_dir();
+        let wal = OrderWal::open(&dir).unwrap();
+        let done = wal
+            .begin(
+                &MarketId::from("m1"),
+                "YES",
+                UsdAmount::new(10.0),
+                Price::new(0.5),
+                &OrderType::GTC,
+            )
+            .unwrap();
+        let open = wal
+            .begin(
+                &MarketId::from("m2"),
+                "NO",
+                UsdAmount::new(5.0),
+                Price::new(0.4),
+                &OrderType::FOK,
+            )
+            .unwrap();
+        wal.complete(done, &Ok("0xabc".to_string()));
+        wal.compact().unwrap();
+        assert_eq!(fs::read_to_string(wal.path()).unwrap().lines().count(), 1);
+
+        // Appends go to the compacted file
+        wal.complete(open, &Ok("0xdef".to_string()));
+        drop(wal);
+        assert!(OrderWal::open(&dir).unwrap().pending().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_fills_are_claimed_by_side_and_size_once() {
+        let fill = |side: &str, size: f64, price: f64| Fill {
+            timestamp: Utc::now().timestamp() as u64,
+            side: side.to_string(),
+            asset: "yes-token".to_string(),
+            size,
+            price,
+        };
+        let intent = |seq, size_usd, price| OrderIntent {
+            seq,
+            at: Utc::now(),
+            market_id: "m1".to_string(),
+            side: "YES".to_string(),
+            size_usd,
+            price,
+            order_type: "GTC".to_string(),
+        };
+        let mut fills = vec![
+            (fill("BUY", 20.0, 0.50), false),
+            (fill("SELL", 10.0, 0.50), false),
+            (fill("BUY", 8.0, 0.60), false),
+            (fill("BUY", 10.0, 0.49), false),
+        ];
+        // $10 @ 0.50 is 20 shares: the first buy, not the sale, the one above its limit or
+        // the one it has no room left for
+        let (shares, cost) = intent(1, 10.0, 0.50).claim("yes-token", &mut fills);
+        assert_eq!((shares, cost), (20.0, 10.0));
+        // A second order on the book side only gets what the first left
+        let (shares, _) = intent(2, 10.0, 0.50).claim("yes-token", &mut fills);
+        assert_eq!(shares, 10.0);
+        assert_eq!(intent(3, 10.0, 0.50).outcome(), "YES");
+    }
+
+    #[tokio::test]
+    async fn test_wal_interface_records_answers() {
+        let dir = temp_dir();
+        let wal = Arc::new(OrderWal::open(&dir).unwrap());
+        let interface = WalInterface::new(Arc::new(MarketSimulator::new()), wal.clone());
+        let _ = interface
+            .place_order(
+                &MarketId::from("unknown-market"),
+                "YES",
+                UsdAmount::new(10.0),
+                Price::new(0.5),
+                OrderType::GTC,
+            )
+            .await;
+        drop(interface);
+        drop(wal);
+
+        // Answered (order id or error alike): nothing left to reconcile
+        assert!(OrderWal::open(&dir).unwrap().pending().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,242 @@
+//! Exit report for process supervisors
+//!
+//! systemd, Kubernetes and alerting only see an exit status. On the way out the agent writes
+//! `data_dir/exit_report.json` (or `EXIT_REPORT_PATH`) saying why it stopped and what it left
+//! behind: open positions, orders still resting, work queued for a retry and the last errors
+//! logged. The exit status tells the kind of stop apart:
+//!
+//! - `0` clean stop: SIGINT (Ctrl+C), SIGTERM or a `ShutdownHandle`, state persisted for
+//!   `--restore`
+//! - `1` crash: the run returned an error
+//! - `3` risk halt: the anomaly guard tripped with `ANOMALY_HALT=true`
+//! - `101` panic (no report beyond the one written at start)
+//!
+//! A report of kind `running` is written at start. Found once the process is gone, it means the
+//! process was killed (SIGKILL, OOM) or panicked before it could write anything else.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+use crate::polymarket::OpenOrder;
+use crate::report::LoggedError;
+use crate::strategies::risk;
+
+pub const EXIT_REPORT_FILE: &str = "exit_report.json";
+
+/// Why the process stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitKind {
+    /// Written at start, replaced on the way out
+    Running,
+    Clean,
+    Crash,
+    RiskHalt,
+}
+
+impl ExitKind {
+    /// Exit status of the process
+    pub fn code(self) -> u8 {
+        match self {
+            ExitKind::Running | ExitKind::Clean => 0,
+            ExitKind::Crash => 1,
+            ExitKind::RiskHalt => 3,
+        }
+    }
+}
+
+/// Kind of stop and what caused it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitReason {
+    pub kind: ExitKind,
+    pub reason: String,
+}
+
+impl ExitReason {
+    pub fn clean(reason: impl Into<String>) -> Self {
+        Self {
+            kind: ExitKind::Clean,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn crash(reason: impl Into<String>) -> Self {
+        Self {
+            kind: ExitKind::Crash,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn risk_halt(reason: impl Into<String>) -> Self {
+        Self {
+            kind: ExitKind::RiskHalt,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Work waiting for another attempt when the process stopped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PendingRetries {
+    /// Conditions still waiting for their Gamma metadata
+    pub metadata: Vec<String>,
+    /// Signals held until capital frees up
+    pub capital_wait: usize,
+}
+
+/// What the process left behind, for whatever supervises it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitReport {
+    pub kind: ExitKind,
+    pub exit_code: u8,
+    pub reason: String,
+    pub at: DateTime<Utc>,
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: i64,
+    pub paper_trading: bool,
+    pub open_positions: Vec<risk::Position>,
+    /// None when they couldn't be fetched
+    pub open_orders: Option<Vec<OpenOrder>>,
+    pub pending_retries: PendingRetries,
+    /// Most recent last
+    pub last_errors: Vec<LoggedError>,
+}
+
+impl ExitReport {
+    /// Report of a run that just started
+    pub fn running(session_id: &str, started_at: DateTime<Utc>, paper_trading: bool) -> Self {
+        Self {
+            kind: ExitKind::Running,
+            exit_code: ExitKind::Running.code(),
+            reason: "running".to_string(),
+            at: started_at,
+            session_id: session_id.to_string(),
+            started_at,
+            uptime_secs: 0,
+            paper_trading,
+            open_positions: Vec::new(),
+            open_orders: Some(Vec::new()),
+            pending_retries: PendingRetries::default(),
+            last_errors: Vec::new(),
+        }
+    }
+
+    /// The same run, stopped at `at` for `reason`
+    pub fn stopped(self, reason: &ExitReason, at: DateTime<Utc>) -> Self {
+        Self {
+            kind: reason.kind,
+            exit_code: reason.kind.code(),
+            reason: reason.reason.clone(),
+            at,
+            uptime_secs: (at - self.started_at).num_seconds().max(0),
+            ..self
+        }
+    }
+
+    /// Write to `path`: written and synced to a temp file, then renamed over the previous
+    /// report, so a supervisor never reads half of one
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp).with_context(|| format!("Failed to create {:?}", tmp))?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.flush()?;
+        file.sync_all()?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&raw).context("Invalid exit report")
+    }
+}
+
+/// `EXIT_REPORT_PATH`, or the report inside the data dir
+pub fn exit_report_path(data_dir: &str, configured: Option<&str>) -> PathBuf {
+    configured.map_or_else(|| Path::new(data_dir).join(EXIT_REPORT_FILE), PathBuf::from)
+}
+
+/// Stops a running agent the way SIGTERM does, from another task of an embedding host.
+/// Clones stop the same agent
+#[derive(Clone, Default)]
+pub struct ShutdownHandle {
+    requested: Arc<Notify>,
+}
+
+impl ShutdownHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop at the next turn of the run loop (asked before `run`, it stops as soon as it starts)
+    pub fn shutdown(&self) {
+        self.requested.notify_one();
+    }
+
+    /// Resolves once a stop is asked for
+    pub async fn requested(&self) {
+        self.requested.notified().await;
+    }
+}
+
+/// SIGINT or SIGTERM, whichever arrives first; the signal's name
+pub async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "SIGINT"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_exit_report_replaces_the_running_one() {
+        let dir = std::env::temp_dir().join(format!("exit-report-{}", uuid::Uuid::new_v4()));
+        let path = exit_report_path(dir.to_str().unwrap(), None);
+        let started_at = "2026-10-15T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let running = ExitReport::running("s1", started_at, false);
+        running.save(&path).unwrap();
+        assert_eq!(ExitReport::load(&path).unwrap().kind, ExitKind::Running);
+
+        let halt = ExitReason::risk_halt("2 single-leg arbitrage plans");
+        running
+            .stopped(&halt, started_at + Duration::hours(2))
+            .save(&path)
+            .unwrap();
+        let report = ExitReport::load(&path).unwrap();
+        assert_eq!((report.kind, report.exit_code), (ExitKind::RiskHalt, 3));
+        assert_eq!(report.reason, "2 single-leg arbitrage plans");
+        assert_eq!(report.uptime_secs, 7200);
+        assert_eq!(ExitReason::clean("SIGTERM").kind.code(), 0);
+        assert_eq!(ExitReason::crash("boom").kind.code(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,500 @@
+//! Hot-standby failover
+//!
+//! Two instances, usually in different regions, point `FAILOVER_PEER_URL` at each other's API
+//! and poll the peer's `GET /api/failover` heartbeat every `FAILOVER_HEARTBEAT_MS`. The standby
+//! runs the full pipeline with order entry fenced off. Once the primary has been silent
+//! (unreachable, or its trading loop stalled) for `FAILOVER_PROMOTE_AFTER_SECS`, the standby
+//! promotes itself under a new epoch and fences the old primary with `POST /api/failover/fence`.
+//!
+//! Split brain is prevented by epochs and a lease. An instance that sees a primary with a
+//! higher epoch steps down. A primary whose peer is a standby stops opening positions when it
+//! hasn't reached the peer for `FAILOVER_LEASE_SECS` (shorter than the promotion delay), so a
+//! partitioned primary is fenced before the standby takes over, and it only trades again once
+//! it has seen the new epoch. A fence may only move the epoch `MAX_EPOCH_JUMP` ahead, so a bogus
+//! one can't push it out of reach. The epoch and the last view of the peer are persisted in
+//! `data_dir/failover.json`.
+//!
+//! While fenced, the executor, the redeemer and the funding helper send nothing. A promoted
+//! standby holds order entry until the run loop has resumed the primary's state snapshot
+//! (`FAILOVER_SNAPSHOT_DIR`).
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, error, info, warn};
+use utoipa::ToSchema;
+
+use crate::config::FailoverConfig;
+
+pub const FAILOVER_FILE: &str = "failover.json";
+/// Furthest a fence may move the epoch ahead of ours
+const MAX_EPOCH_JUMP: u64 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverRole {
+    Primary,
+    Standby,
+}
+
+impl FailoverRole {
+    /// "primary" or "standby"; anything else turns failover off
+    pub fn parse(role: &str) -> Option<Self> {
+        match role.trim().to_lowercase().as_str() {
+            "primary" => Some(Self::Primary),
+            "standby" => Some(Self::Standby),
+            _ => None,
+        }
+    }
+}
+
+/// What an instance tells its peer
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Heartbeat {
+    /// Session id of the instance
+    pub instance_id: String,
+    pub role: FailoverRole,
+    pub epoch: u64,
+    /// Opening positions is allowed (a primary holding its lease)
+    pub trading: bool,
+    /// Last pass of the trading loop
+    pub alive_at: DateTime<Utc>,
+    pub at: DateTime<Utc>,
+}
+
+/// Body of `POST /api/failover/fence`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FenceRequest {
+    /// Epoch of the instance that took over
+    pub epoch: u64,
+}
+
+/// Peer as last seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct PeerView {
+    role: FailoverRole,
+    epoch: u64,
+}
+
+/// Persisted across restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FailoverFile {
+    epoch: u64,
+    peer: Option<PeerView>,
+}
+
+#[derive(Debug)]
+struct FailoverState {
+    role: FailoverRole,
+    epoch: u64,
+    peer: Option<PeerView>,
+    trading: bool,
+    alive_at: DateTime<Utc>,
+    /// Last successful heartbeat poll of the peer
+    peer_reached_at: Option<DateTime<Utc>>,
+    /// Last time the peer was a live primary (standbys promote when this gets old)
+    primary_seen_at: DateTime<Utc>,
+}
+
+impl FailoverState {
+    /// A primary needs the lease unless its peer is a primary of an older epoch, which can't
+    /// trade without reaching us first (never seen: assume it may take over)
+    fn needs_lease(&self) -> bool {
+        !self
+            .peer
+            .is_some_and(|p| p.role == FailoverRole::Primary && p.epoch < self.epoch)
+    }
+}
+
+/// This instance's side of the failover protocol
+pub struct Failover {
+    config: FailoverConfig,
+    instance_id: String,
+    path: PathBuf,
+    state: Mutex<FailoverState>,
+    /// Serializes writes of `failover.json` (done outside the state lock)
+    write: Mutex<()>,
+    /// Promoted, and the run loop hasn't resumed the peer's snapshot yet
+    handover: AtomicBool,
+    client: reqwest::Client,
+}
+
+impl Failover {
+    /// None unless `FAILOVER_ROLE` is primary or standby
+    pub fn new(config: &FailoverConfig, instance_id: &str, data_dir: &str) -> Option<Self> {
+        let role = FailoverRole::parse(&config.role)?;
+        let path = Path::new(data_dir).join(FAILOVER_FILE);
+        let saved: FailoverFile = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        let now = Utc::now();
+        let timeout = std::time::Duration::from_millis(
+            config
+                .peer_timeout_ms
+                .clamp(50, config.heartbeat_ms.max(100)),
+        );
+        Some(Self {
+            config: config.clone(),
+            instance_id: instance_id.to_string(),
+            path,
+            state: Mutex::new(FailoverState {
+                role,
+                epoch: saved.epoch,
+                peer: saved.peer,
+                // Nothing until the first heartbeat round
+                trading: false,
+                alive_at: now,
+                peer_reached_at: None,
+                primary_seen_at: now,
+            }),
+            write: Mutex::new(()),
+            handover: AtomicBool::new(false),
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Whether this instance may send orders or transactions
+    pub fn trading_allowed(&self) -> bool {
+        !self.handover.load(Ordering::SeqCst)
+            && self.state.lock().map(|s| s.trading).unwrap_or(false)
+    }
+
+    /// Err while fenced: for the paths that send orders or transactions
+    pub fn ensure_trading(&self) -> Result<()> {
+        if self.trading_allowed() {
+            Ok(())
+        } else {
+            anyhow::bail!("Fenced by failover (standby, lease lost or handover in progress)")
+        }
+    }
+
+    /// Promoted and waiting for the run loop to resume the peer's state
+    pub fn handover_pending(&self) -> bool {
+        self.handover.load(Ordering::SeqCst)
+    }
+
+    /// The peer's state is resumed (or there was none): order entry may open
+    pub fn complete_handover(&self) {
+        self.handover.store(false, Ordering::SeqCst);
+    }
+
+    /// The trading loop made a pass
+    pub fn mark_alive(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.alive_at = Utc::now();
+        }
+    }
+
+    pub fn heartbeat(&self) -> Heartbeat {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Heartbeat {
+            instance_id: self.instance_id.clone(),
+            role: state.role,
+            epoch: state.epoch,
+            trading: state.trading,
+            alive_at: state.alive_at,
+            at: Utc::now(),
+        }
+    }
+
+    /// The peer took over at `epoch`: step down unless ours is newer. An epoch more than
+    /// `MAX_EPOCH_JUMP` ahead is refused: no promotion gets there in one step
+    pub fn fence(&self, epoch: u64) -> Heartbeat {
+        let changed = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let jump = epoch.checked_sub(state.epoch).unwrap_or(0);
+            if jump > MAX_EPOCH_JUMP {
+                warn!(
+                    "⚠️ Fence to epoch {} refused: more than {} ahead of ours ({})",
+                    epoch, MAX_EPOCH_JUMP, state.epoch
+                );
+                false
+            } else if jump > 0 {
+                error!(
+                    "🚨 ALERT: Fenced by the peer at epoch {} (was {:?} at epoch {}) - order entry stopped",
+                    epoch, state.role, state.epoch
+                );
+                state.role = FailoverRole::Standby;
+                state.epoch = epoch;
+                state.trading = false;
+                state.peer = Some(PeerView {
+                    role: FailoverRole::Primary,
+                    epoch,
+                });
+                state.primary_seen_at = Utc::now();
+                self.handover.store(false, Ordering::SeqCst);
+                true
+            } else {
+                false
+            }
+        };
+        if changed {
+            self.persist();
+        }
+        self.heartbeat()
+    }
+
+    /// Apply one heartbeat round (`peer` None = unreachable). Returns the new epoch when this
+    /// instance promoted itself, so the old primary can be fenced
+    pub fn observe(&self, peer: Option<&Heartbeat>, now: DateTime<Utc>) -> Option<u64> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let promote_after = Duration::seconds(self.config.promote_after_secs as i64);
+        let before = (state.role, state.epoch, state.peer);
+        let mut promoted = None;
+
+        if let Some(peer) = peer {
+            state.peer_reached_at = Some(now);
+            state.peer = Some(PeerView {
+                role: peer.role,
+                epoch: peer.epoch,
+            });
+            let outranked = peer.epoch > state.epoch
+                || (peer.epoch == state.epoch && peer.instance_id < self.instance_id);
+            if peer.role == FailoverRole::Primary {
+                if state.role == FailoverRole::Primary && outranked {
+                    warn!(
+                        "🔀 Peer {} is primary at epoch {} - stepping down to standby",
+                        peer.instance_id, peer.epoch
+                    );
+                    state.role = FailoverRole::Standby;
+                }
+                // A primary whose loop stalled is as good as gone
+                if now - peer.alive_at < promote_after {
+                    state.primary_seen_at = now;
+                }
+                if state.role == FailoverRole::Standby {
+                    state.epoch = state.epoch.max(peer.epoch);
+                }
+            } else if state.role == FailoverRole::Standby && !outranked {
+                // Both standby: the lower instance id takes over, the other keeps waiting
+                state.primary_seen_at = state.primary_seen_at.min(now - promote_after);
+            } else {
+                state.primary_seen_at = now;
+            }
+        }
+
+        match state.role {
+            FailoverRole::Standby => {
+                let next = state
+                    .epoch
+                    .max(state.peer.map_or(0, |p| p.epoch))
+                    .checked_add(1);
+                match next {
+                    Some(epoch) if now - state.primary_seen_at >= promote_after => {
+                        error!(
+                            "🚨 ALERT: Primary silent for {}s - promoting to primary at epoch {}",
+                            (now - state.primary_seen_at).num_seconds(),
+                            epoch
+                        );
+                        state.role = FailoverRole::Primary;
+                        state.epoch = epoch;
+                        self.handover.store(true, Ordering::SeqCst);
+                        promoted = Some(epoch);
+                    }
+                    Some(_) => {}
+                    None => error!("🚨 ALERT: Failover epoch exhausted - can't promote"),
+                }
+            }
+            FailoverRole::Primary => {}
+        }
+
+        let trading = state.role == FailoverRole::Primary
+            && (self.config.lease_secs == 0
+                || !state.needs_lease()
+                || state
+                    .peer_reached_at
+                    .is_some_and(|t| now - t < Duration::seconds(self.config.lease_secs as i64)));
+        if trading != state.trading {
+            if trading {
+                info!("✅ Failover: trading as primary (epoch {})", state.epoch);
+            } else if state.role == FailoverRole::Primary {
+                error!(
+                    "🚨 ALERT: Peer unreachable for {}s - primary lease lost, order entry fenced",
+                    self.config.lease_secs
+                );
+            }
+            state.trading = trading;
+        }
+
+        let changed = (state.role, state.epoch, state.peer) != before;
+        drop(state);
+        if changed {
+            self.persist();
+        }
+        promoted
+    }
+
+    /// Poll the peer and apply the protocol until the process exits
+    pub async fn run(self: Arc<Self>) {
+        let Some(peer_url) = self.config.peer_url.clone() else {
+            warn!("⚠️ FAILOVER_PEER_URL not set - failover can't see its peer");
+            return;
+        };
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(
+            self.config.heartbeat_ms.max(100),
+        ));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let peer = match self.fetch_heartbeat(&peer_url).await {
+                Ok(peer) => Some(peer),
+                Err(e) => {
+                    debug!("Failover peer heartbeat failed: {:#}", e);
+                    None
+                }
+            };
+            if let Some(epoch) = self.observe(peer.as_ref(), Utc::now()) {
+                if let Err(e) = self.fence_peer(&peer_url, epoch).await {
+                    warn!(
+                        "⚠️ Could not fence the old primary (it fences itself on its lease): {:#}",
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    async fn fetch_heartbeat(&self, peer_url: &str) -> Result<Heartbeat> {
+        let request = self
+            .client
+            .get(format!("{}/api/failover", peer_url.trim_end_matches('/')));
+        self.authorized(request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Invalid peer heartbeat")
+    }
+
+    async fn fence_peer(&self, peer_url: &str, epoch: u64) -> Result<()> {
+        let request = self
+            .client
+            .post(format!(
+                "{}/api/failover/fence",
+                peer_url.trim_end_matches('/')
+            ))
+            .json(&FenceRequest { epoch });
+        self.authorized(request).send().await?.error_for_status()?;
+        info!("🔒 Fenced the old primary at epoch {}", epoch);
+        Ok(())
+    }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.config.peer_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Write the current epoch and peer view; the state lock is only held to copy them
+    fn persist(&self) {
+        let _write = self.write.lock().unwrap_or_else(|e| e.into_inner());
+        let file = {
+            let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            FailoverFile {
+                epoch: state.epoch,
+                peer: state.peer,
+            }
+        };
+        let written = (|| -> Result<()> {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let tmp = self.path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_string_pretty(&file)?)?;
+            fs::rename(&tmp, &self.path)?;
+            Ok(())
+        })();
+        if let Err(e) = written {
+            warn!("⚠️ Failed to persist failover state: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failover(role: &str, id: &str, dir: &Path) -> Failover {
+        let config = FailoverConfig {
+            role: role.to_string(),
+            peer_url: None,
+            peer_token: None,
+            heartbeat_ms: 1000,
+            peer_timeout_ms: 500,
+            snapshot_dir: None,
+            lease_secs: 5,
+            promote_after_secs: 15,
+        };
+        Failover::new(&config, id, dir.join(id).to_str().unwrap()).unwrap()
+    }
+
+    fn beat(failover: &Failover, now: DateTime<Utc>) -> Heartbeat {
+        Heartbeat {
+            alive_at: now,
+            at: now,
+            ..failover.heartbeat()
+        }
+    }
+
+    #[test]
+    fn test_promotion_and_fencing() {
+        let dir = std::env::temp_dir().join(format!("failover-{}", uuid::Uuid::new_v4()));
+        let primary = failover("primary", "a", &dir);
+        let standby = failover("standby", "b", &dir);
+        let t0 = Utc::now();
+
+        // Healthy pair: only the primary trades
+        primary.observe(Some(&beat(&standby, t0)), t0);
+        standby.observe(Some(&beat(&primary, t0)), t0);
+        assert!(primary.trading_allowed());
+        assert!(!standby.trading_allowed());
+
+        // Partition: the primary loses its lease before the standby promotes
+        let t6 = t0 + Duration::seconds(6);
+        assert_eq!(primary.observe(None, t6), None);
+        assert_eq!(standby.observe(None, t6), None);
+        assert!(!primary.trading_allowed());
+        assert!(!standby.trading_allowed());
+
+        let t16 = t0 + Duration::seconds(16);
+        assert_eq!(standby.observe(None, t16), Some(1));
+        // Order entry waits for the run loop to resume the old primary's state
+        assert!(!standby.trading_allowed());
+        assert!(standby.handover_pending());
+        standby.complete_handover();
+        assert!(standby.trading_allowed());
+        // The new primary keeps trading without its peer: the old one can't trade unseen
+        standby.observe(None, t16 + Duration::seconds(30));
+        assert!(standby.trading_allowed());
+
+        // Partition heals: the old primary sees the newer epoch and stays down
+        let t50 = t0 + Duration::seconds(50);
+        primary.observe(Some(&beat(&standby, t50)), t50);
+        assert_eq!(primary.heartbeat().role, FailoverRole::Standby);
+        assert!(!primary.trading_allowed());
+        standby.observe(Some(&beat(&primary, t50)), t50);
+        assert!(standby.trading_allowed());
+
+        // An explicit fence from a newer epoch demotes at once; older ones are ignored, and so
+        // are ones too far ahead
+        assert_eq!(standby.fence(1).role, FailoverRole::Primary);
+        assert_eq!(standby.fence(u64::MAX).role, FailoverRole::Primary);
+        assert_eq!(
+            standby.fence(1 + MAX_EPOCH_JUMP + 1).role,
+            FailoverRole::Primary
+        );
+        assert_eq!(standby.fence(2).role, FailoverRole::Standby);
+        assert!(!standby.trading_allowed());
+        assert!(standby.ensure_trading().is_err());
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,215 @@
+//! Identifier and amount newtypes
+//!
+//! Market ids, token ids and order ids are all strings, and prices and USD sizes are all
+//! `f64`: nothing stopped a call from passing one for the other, and the argument orders don't
+//! agree (`place_order` takes the size first, a plan leg the price first). These wrap them so
+//! the compiler catches the mix-up. They serialize as the plain value (`serde(transparent)`),
+//! so the API, the WAL and every file on disk read and write the same JSON as before.
+//!
+//! The order path (order requests, `MarketInterface::place_order`, plan legs, strategy
+//! signals, the WAL, the executor's entry points, and the risk manager's and PnL tracker's
+//! position updates) takes them, and `MarketData::id` is a `MarketId`; the ids deref to `str`,
+//! borrow as one and compare with plain strings, so the maps keyed by `String` and the code
+//! still passing plain strings keep working.
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+
+macro_rules! string_id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn new(id: impl Into<String>) -> Self {
+                Self(id.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.pad(&self.0)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> Self {
+                Self(id.to_string())
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(id: &String) -> Self {
+                Self(id.clone())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> String {
+                id.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<$name> for str {
+            fn eq(&self, other: &$name) -> bool {
+                self == other.0
+            }
+        }
+
+        impl PartialEq<$name> for &str {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0
+            }
+        }
+
+        impl PartialEq<$name> for String {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0
+            }
+        }
+    };
+}
+
+macro_rules! amount {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(f64);
+
+        impl $name {
+            pub const fn new(value: f64) -> Self {
+                Self(value)
+            }
+
+            pub const fn get(self) -> f64 {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(value: $name) -> f64 {
+                value.0
+            }
+        }
+    };
+}
+
+string_id!(
+    /// Condition id of a Polymarket market (a Kalshi ticker on the Kalshi adapter)
+    MarketId
+);
+string_id!(
+    /// CLOB token id of one outcome of a market
+    AssetId
+);
+string_id!(
+    /// Order id the venue assigned
+    OrderId
+);
+amount!(
+    /// Price of an outcome share, 0 to 1
+    Price
+);
+amount!(
+    /// Notional in USD(C)
+    UsdAmount
+);
+
+impl UsdAmount {
+    /// Shares this notional buys at `price`
+    pub fn shares_at(self, price: Price) -> f64 {
+        if price.0 > 0.0 {
+            self.0 / price.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_newtypes_serialize_as_the_plain_value() {
+        let id = MarketId::from("0xabc");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"0xabc\"");
+        assert_eq!(serde_json::from_str::<MarketId>("\"0xabc\"").unwrap(), id);
+        assert_eq!(serde_json::to_string(&Price::new(0.45)).unwrap(), "0.45");
+        assert_eq!(
+            serde_json::from_str::<UsdAmount>("12.5").unwrap(),
+            UsdAmount::new(12.5)
+        );
+
+        // Maps keyed by the id are looked up with a plain string
+        let mut orders: HashMap<OrderId, f64> = HashMap::new();
+        orders.insert(OrderId::new("o1"), 1.0);
+        assert_eq!(orders.get("o1"), Some(&1.0));
+        assert!(id.starts_with("0x"));
+        assert!(id == "0xabc".to_string() && "0xabc" == id);
+        assert_eq!(UsdAmount::new(10.0).shares_at(Price::new(0.5)), 20.0);
+    }
+}
@@ -1,8 +1,105 @@
+//! Polymarket HFT agent as a library
+//!
+//! The types re-exported at the crate root are the embedding surface, kept stable across
+//! releases: build an [`Agent`] from a [`Config`], add [`Strategy`] implementations, optionally
+//! route market data and orders through your own [`MarketInterface`], and follow what the
+//! engine does through [`AgentEvent`]s. The modules below are public for the binary, the
+//! examples and the benches; their items may change between releases.
+//!
+//! The `trading` feature (on by default) holds everything that signs: the engine, order entry
+//! and the wallet's on-chain operations. Without it (`--no-default-features`) the crate builds
+//! a read-only analytics and monitoring binary that never handles a private key.
+//!
+//! ```no_run
+//! use async_trait::async_trait;
+//! use chrono::{DateTime, Utc};
+//! use polymarket_hft_agent::ids::{Price, UsdAmount};
+//! use polymarket_hft_agent::{Agent, Config, MarketData, Strategy, TradeAction};
+//!
+//! struct CheapYes;
+//!
+//! #[async_trait]
+//! impl Strategy for CheapYes {
+//!     fn name(&self) -> &str {
+//!         "cheapyes"
+//!     }
+//!
+//!     async fn evaluate(&self, market: &MarketData, _now: DateTime<Utc>) -> TradeAction {
+//!         if market.yes_price < 0.05 {
+//!             TradeAction::Snipe {
+//!                 market_id: market.id.clone(),
+//!                 side: "YES".to_string(),
+//!                 price: Price::new(market.yes_price),
+//!                 size_usd: UsdAmount::new(10.0),
+//!             }
+//!         } else {
+//!             TradeAction::None
+//!         }
+//!     }
+//! }
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let agent = Agent::builder(Config::from_env()?)
+//!     .strategy(CheapYes)
+//!     .build()
+//!     .await?;
+//! let mut events = agent.events();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         println!("{:?}", event);
+//!     }
+//! });
+//! // Stop from elsewhere in the host as on SIGTERM (state saved, exit report written)
+//! let shutdown = agent.shutdown_handle();
+//! tokio::spawn(async move {
+//!     tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+//!     shutdown.shutdown();
+//! });
+//! agent.run().await?;
+//! # Ok(())
+//! # }
+//! ```
+#[cfg(feature = "trading")]
+pub mod agent;
+pub mod alloc_profile;
 pub mod analytics;
+pub mod api;
+pub mod cli;
+pub mod clock;
 pub mod config;
+pub mod events;
 pub mod execution;
+pub mod exit_report;
+pub mod failover;
+pub mod ids;
+pub mod llm_budget;
+pub mod logging;
+pub mod markets;
+pub mod ml;
+pub mod monitor;
+pub mod observer;
+#[cfg(feature = "trading")]
+pub mod onboarding;
+pub mod policy;
 pub mod polymarket;
 pub mod pricefeed;
+pub mod profiles;
+pub mod report;
+pub mod scheduling;
 pub mod simulation;
+pub mod snapshot;
+#[cfg(feature = "trading")]
 pub mod sniper;
+pub mod storage;
 pub mod strategies;
+pub mod telemetry;
+pub mod venues;
+
+#[cfg(feature = "trading")]
+pub use agent::{Agent, AgentBuilder};
+pub use config::Config;
+pub use events::AgentEvent;
+pub use exit_report::{ExitKind, ExitReason, ShutdownHandle};
+pub use ids::{AssetId, MarketId, OrderId, Price, UsdAmount};
+pub use polymarket::{MarketData, MarketInterface};
+pub use strategies::{Strategy, TradeAction};
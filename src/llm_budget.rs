@@ -0,0 +1,472 @@
+//! Spending limits of LLM calls
+//!
+//! Model calls are billed per token, and a burst of new markets or a restart loop must not turn
+//! into a bill. Every call takes a token from an hourly bucket (`LLM_REQUESTS_PER_HOUR`) and is
+//! refused once the day's spend reaches `LLM_MAX_USD_PER_DAY`. A prompt answered within
+//! `LLM_CACHE_TTL_SECS` is answered from cache, and one already in flight isn't sent again. What
+//! the calls cost is kept in total, per day and per market in `DATA_DIR/llm_ledger.json` (so the
+//! daily cap holds across restarts, written with the registry save rather than on each call) and
+//! served at `/api/llm/costs`. The cache and the per-market ledger are bounded: expired and
+//! oldest answers go first, and the markets that cost least.
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::config::LlmBudgetConfig;
+
+pub const LLM_LEDGER_FILE: &str = "llm_ledger.json";
+
+/// Answers kept in the prompt cache
+const MAX_CACHED_PROMPTS: usize = 10_000;
+
+/// Markets kept in the ledger's per-market costs
+const MAX_LEDGER_MARKETS: usize = 5_000;
+
+/// Requests per hour, refilled continuously
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last: DateTime<Utc>,
+}
+
+impl TokenBucket {
+    /// Starts full
+    pub fn per_hour(requests: u32, now: DateTime<Utc>) -> Self {
+        Self {
+            capacity: f64::from(requests),
+            tokens: f64::from(requests),
+            refill_per_sec: f64::from(requests) / 3600.0,
+            last: now,
+        }
+    }
+
+    fn refill(&mut self, now: DateTime<Utc>) {
+        let elapsed = (now - self.last).num_milliseconds().max(0) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last = now;
+    }
+
+    pub fn try_take(&mut self, now: DateTime<Utc>) -> bool {
+        self.refill(now);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    /// Whole requests that could be made right now
+    pub fn available(&self, now: DateTime<Utc>) -> u32 {
+        let mut bucket = self.clone();
+        bucket.refill(now);
+        bucket.tokens.floor() as u32
+    }
+}
+
+/// Tokens a call used, as the provider reported them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LlmUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Cost the provider reported (OpenRouter's `usage.cost`), else estimated from token prices
+    pub cost_usd: Option<f64>,
+}
+
+/// What calls cost
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct LlmCost {
+    /// Calls sent, failed ones included
+    pub requests: u64,
+    /// Answered from cache
+    pub cache_hits: u64,
+    /// Refused by the bucket, the daily cap or a call already in flight
+    pub refused: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl LlmCost {
+    fn add(&mut self, usage: &LlmUsage, cost_usd: f64) {
+        self.requests += 1;
+        self.prompt_tokens += usage.prompt_tokens;
+        self.completion_tokens += usage.completion_tokens;
+        self.cost_usd += cost_usd;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Ledger {
+    total: LlmCost,
+    days: BTreeMap<NaiveDate, LlmCost>,
+    markets: BTreeMap<String, LlmCost>,
+}
+
+/// Cost of the calls made about one market
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MarketLlmCost {
+    pub market_id: String,
+    #[serde(flatten)]
+    pub cost: LlmCost,
+}
+
+/// `/api/llm/costs`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LlmCostReport {
+    pub total: LlmCost,
+    /// UTC day so far
+    pub today: LlmCost,
+    pub max_usd_per_day: f64,
+    pub requests_per_hour: u32,
+    /// Calls the bucket allows right now
+    pub requests_available: u32,
+    pub cached_prompts: usize,
+    /// Most expensive first
+    pub markets: Vec<MarketLlmCost>,
+}
+
+/// What to do with a prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum Admission {
+    /// Answered within the cache TTL
+    Cached(String),
+    /// Within budget: send it, then `record` the outcome under this key
+    Send(u64),
+    /// Not sent, and why
+    Refused(String),
+}
+
+pub struct LlmBudget {
+    config: LlmBudgetConfig,
+    bucket: TokenBucket,
+    cache: HashMap<u64, (String, DateTime<Utc>)>,
+    in_flight: HashSet<u64>,
+    ledger: Ledger,
+    path: Option<PathBuf>,
+    /// Ledger changed since the last save
+    dirty: bool,
+}
+
+fn prompt_key(prompt: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    prompt.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl LlmBudget {
+    /// In-memory ledger
+    pub fn new(config: LlmBudgetConfig, now: DateTime<Utc>) -> Self {
+        Self {
+            bucket: TokenBucket::per_hour(config.requests_per_hour, now),
+            config,
+            cache: HashMap::new(),
+            in_flight: HashSet::new(),
+            ledger: Ledger::default(),
+            path: None,
+            dirty: false,
+        }
+    }
+
+    /// Ledger kept in `data_dir`
+    pub fn load(config: LlmBudgetConfig, data_dir: &str, now: DateTime<Utc>) -> Self {
+        let path = Path::new(data_dir).join(LLM_LEDGER_FILE);
+        let ledger = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                Ledger::default()
+            }),
+            Err(_) => Ledger::default(),
+        };
+        Self {
+            ledger,
+            path: Some(path),
+            ..Self::new(config, now)
+        }
+    }
+
+    /// No-op for in-memory ledgers
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&self.ledger)?)?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    /// Save if calls were booked since the last save; true when written
+    pub fn save_if_dirty(&mut self) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+        self.save()?;
+        self.dirty = false;
+        Ok(true)
+    }
+
+    /// Ledger entry of `market_id`, making room by dropping the market that cost least
+    fn market(&mut self, market_id: &str) -> &mut LlmCost {
+        let markets = &mut self.ledger.markets;
+        if !markets.contains_key(market_id) && markets.len() >= MAX_LEDGER_MARKETS {
+            let cheapest = markets
+                .iter()
+                .min_by(|a, b| a.1.cost_usd.total_cmp(&b.1.cost_usd))
+                .map(|(id, _)| id.clone());
+            if let Some(cheapest) = cheapest {
+                markets.remove(&cheapest);
+            }
+        }
+        self.dirty = true;
+        markets.entry(market_id.to_string()).or_default()
+    }
+
+    /// Cache `answer`, making room by dropping expired answers, then the oldest
+    fn cache_answer(&mut self, key: u64, answer: &str, now: DateTime<Utc>) {
+        if !self.cache.contains_key(&key) && self.cache.len() >= MAX_CACHED_PROMPTS {
+            let ttl = chrono::Duration::seconds(self.config.cache_ttl_secs as i64);
+            self.cache.retain(|_, (_, at)| now - *at <= ttl);
+            if self.cache.len() >= MAX_CACHED_PROMPTS {
+                let oldest = self
+                    .cache
+                    .iter()
+                    .min_by_key(|(_, (_, at))| *at)
+                    .map(|(key, _)| *key);
+                if let Some(oldest) = oldest {
+                    self.cache.remove(&oldest);
+                }
+            }
+        }
+        self.cache.insert(key, (answer.to_string(), now));
+    }
+
+    fn spent_on(&self, day: NaiveDate) -> f64 {
+        self.ledger.days.get(&day).map_or(0.0, |d| d.cost_usd)
+    }
+
+    /// Cache, dedup and budget checks for `prompt` (everything that shapes the answer: model,
+    /// instructions and input), asked about `market_id`
+    pub fn admit(&mut self, market_id: &str, prompt: &str, now: DateTime<Utc>) -> Admission {
+        let key = prompt_key(prompt);
+        let ttl = chrono::Duration::seconds(self.config.cache_ttl_secs as i64);
+        if let Some((answer, at)) = self.cache.get(&key) {
+            if now - *at <= ttl {
+                let answer = answer.clone();
+                self.ledger.total.cache_hits += 1;
+                self.market(market_id).cache_hits += 1;
+                return Admission::Cached(answer);
+            }
+            self.cache.remove(&key);
+        }
+
+        let refusal = if self.in_flight.contains(&key) {
+            Some("the same prompt is already in flight".to_string())
+        } else if self.spent_on(now.date_naive()) >= self.config.max_usd_per_day {
+            Some(format!(
+                "daily LLM budget of ${:.2} spent",
+                self.config.max_usd_per_day
+            ))
+        } else if !self.bucket.try_take(now) {
+            Some(format!(
+                "{} LLM requests per hour used up",
+                self.config.requests_per_hour
+            ))
+        } else {
+            None
+        };
+        if let Some(reason) = refusal {
+            self.ledger.total.refused += 1;
+            self.market(market_id).refused += 1;
+            return Admission::Refused(reason);
+        }
+        self.in_flight.insert(key);
+        Admission::Send(key)
+    }
+
+    /// Book a sent call; its answer (None when it failed) is cached under `key`
+    pub fn record(
+        &mut self,
+        market_id: &str,
+        key: u64,
+        answer: Option<&str>,
+        usage: LlmUsage,
+        now: DateTime<Utc>,
+    ) {
+        self.in_flight.remove(&key);
+        let cost_usd = usage.cost_usd.unwrap_or_else(|| {
+            usage.prompt_tokens as f64 / 1000.0 * self.config.usd_per_1k_prompt_tokens
+                + usage.completion_tokens as f64 / 1000.0 * self.config.usd_per_1k_completion_tokens
+        });
+        let day = now.date_naive();
+        let before = self.spent_on(day);
+        self.ledger.total.add(&usage, cost_usd);
+        self.ledger
+            .days
+            .entry(day)
+            .or_default()
+            .add(&usage, cost_usd);
+        self.market(market_id).add(&usage, cost_usd);
+        if let Some(answer) = answer {
+            self.cache_answer(key, answer, now);
+        }
+        if before < self.config.max_usd_per_day && self.spent_on(day) >= self.config.max_usd_per_day
+        {
+            info!(
+                "💸 Daily LLM budget of ${:.2} spent: no more calls until tomorrow (UTC)",
+                self.config.max_usd_per_day
+            );
+        }
+    }
+
+    pub fn report(&self, now: DateTime<Utc>) -> LlmCostReport {
+        let mut markets: Vec<MarketLlmCost> = self
+            .ledger
+            .markets
+            .iter()
+            .map(|(market_id, cost)| MarketLlmCost {
+                market_id: market_id.clone(),
+                cost: cost.clone(),
+            })
+            .collect();
+        markets.sort_by(|a, b| b.cost.cost_usd.total_cmp(&a.cost.cost_usd));
+        LlmCostReport {
+            total: self.ledger.total.clone(),
+            today: self
+                .ledger
+                .days
+                .get(&now.date_naive())
+                .cloned()
+                .unwrap_or_default(),
+            max_usd_per_day: self.config.max_usd_per_day,
+            requests_per_hour: self.config.requests_per_hour,
+            requests_available: self.bucket.available(now),
+            cached_prompts: self.cache.len(),
+            markets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn config() -> LlmBudgetConfig {
+        LlmBudgetConfig {
+            requests_per_hour: 2,
+            max_usd_per_day: 0.01,
+            cache_ttl_secs: 3600,
+            usd_per_1k_prompt_tokens: 1.0,
+            usd_per_1k_completion_tokens: 2.0,
+        }
+    }
+
+    fn usage(prompt_tokens: u64) -> LlmUsage {
+        LlmUsage {
+            prompt_tokens,
+            completion_tokens: 1,
+            cost_usd: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_dedup_and_bucket() {
+        let now = Utc::now();
+        let mut budget = LlmBudget::new(config(), now);
+
+        let Admission::Send(key) = budget.admit("m1", "q1", now) else {
+            panic!("first call refused");
+        };
+        // Same prompt in flight, from another market
+        assert!(matches!(
+            budget.admit("m2", "q1", now),
+            Admission::Refused(_)
+        ));
+        budget.record("m1", key, Some("crypto"), usage(1), now);
+        assert_eq!(
+            budget.admit("m2", "q1", now),
+            Admission::Cached("crypto".to_string())
+        );
+
+        assert!(matches!(budget.admit("m3", "q2", now), Admission::Send(_)));
+        // Bucket of 2 per hour is empty; one refills in 30 minutes
+        assert!(matches!(
+            budget.admit("m4", "q3", now),
+            Admission::Refused(_)
+        ));
+        assert!(matches!(
+            budget.admit("m4", "q3", now + Duration::minutes(31)),
+            Admission::Send(_)
+        ));
+
+        let report = budget.report(now);
+        assert_eq!(report.total.requests, 1);
+        assert_eq!(report.total.cache_hits, 1);
+        assert_eq!(report.total.refused, 2);
+        // 1 prompt token at $1 / 1k + 1 completion token at $2 / 1k
+        assert!((report.markets[0].cost.cost_usd - 0.003).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cache_and_ledger_are_bounded() {
+        let now = Utc::now();
+        let mut budget = LlmBudget::new(config(), now);
+        for i in 0..MAX_CACHED_PROMPTS as u64 {
+            budget.cache_answer(i, "crypto", now + Duration::milliseconds(i as i64));
+        }
+        let later = now + Duration::seconds(60);
+        budget.cache_answer(u64::MAX, "sports", later);
+        // Still fresh, so the oldest answer made room
+        assert_eq!(budget.cache.len(), MAX_CACHED_PROMPTS);
+        assert!(!budget.cache.contains_key(&0));
+        // Expired ones all go at once
+        budget.cache_answer(u64::MAX - 1, "macro", later + Duration::hours(2));
+        assert_eq!(budget.cache.len(), 1);
+
+        for i in 0..MAX_LEDGER_MARKETS {
+            budget.market(&format!("m{}", i)).cost_usd = 1.0;
+        }
+        budget.market("m0").cost_usd = 0.5;
+        budget.market("new");
+        assert_eq!(budget.ledger.markets.len(), MAX_LEDGER_MARKETS);
+        assert!(!budget.ledger.markets.contains_key("m0"));
+        assert!(budget.save_if_dirty().unwrap());
+    }
+
+    #[test]
+    fn test_daily_cap() {
+        let now = Utc::now();
+        let mut budget = LlmBudget::new(
+            LlmBudgetConfig {
+                requests_per_hour: 100,
+                ..config()
+            },
+            now,
+        );
+        let Admission::Send(key) = budget.admit("m1", "q1", now) else {
+            panic!("first call refused");
+        };
+        budget.record("m1", key, None, usage(20), now);
+        assert!(matches!(
+            budget.admit("m1", "q1", now),
+            Admission::Refused(_)
+        ));
+        // Failed calls aren't cached, and the cap resets with the day
+        assert!(matches!(
+            budget.admit("m1", "q1", now + Duration::days(1)),
+            Admission::Send(_)
+        ));
+    }
+}
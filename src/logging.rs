@@ -0,0 +1,243 @@
+//! Log filter that can be changed while the agent runs
+//!
+//! The `RUST_LOG` filter is installed behind a reload layer. Directives added through the API
+//! (`/api/log-levels`) or the `log-level` command override it per target, optionally for a
+//! limited time - e.g. `polymarket_hft_agent::polymarket::ws=trace` for five minutes - so
+//! debugging a live instance doesn't take a restart that loses its in-memory state.
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::info;
+use tracing_subscriber::filter::{Directive, EnvFilter};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, Registry};
+use utoipa::ToSchema;
+
+use crate::report;
+
+/// Filter used when `RUST_LOG` is unset
+pub const DEFAULT_FILTER: &str = "polymarket_hft_agent=debug,info";
+
+/// Longest an override can be set for (longer ttls are cut to it)
+pub const MAX_TTL_SECS: u64 = 30 * 86_400;
+
+static LEVELS: OnceLock<LogLevels> = OnceLock::new();
+
+/// A directive layered over the startup filter
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LogOverride {
+    /// `target=level`, or a bare level for everything
+    pub directive: String,
+    /// None = until reset
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Filter in effect
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LogLevelsView {
+    /// Filter the process started with (`RUST_LOG`)
+    pub base: String,
+    pub overrides: Vec<LogOverride>,
+    /// Directives currently applied
+    pub effective: String,
+}
+
+/// Target a directive sets the level of ("" for the bare default level): what comes before
+/// the first `=` outside its span filter (`target[span{field=value}]=level`)
+fn target(directive: &str) -> &str {
+    let mut depth = 0usize;
+    for (i, c) in directive.char_indices() {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.saturating_sub(1),
+            '=' if depth == 0 => return directive[..i].trim(),
+            _ => {}
+        }
+    }
+    // A bare target (`target[span]`) enables everything of it
+    if directive.parse::<tracing::Level>().is_ok() || directive.eq_ignore_ascii_case("off") {
+        ""
+    } else {
+        directive.trim()
+    }
+}
+
+/// `base` with its directives for overridden targets replaced by the overrides
+fn combine(base: &str, overrides: &[LogOverride]) -> String {
+    base.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .filter(|d| overrides.iter().all(|o| target(&o.directive) != target(d)))
+        .chain(overrides.iter().map(|o| o.directive.as_str()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+struct State {
+    overrides: Vec<LogOverride>,
+}
+
+/// Handle on the process-wide filter
+#[derive(Clone)]
+pub struct LogLevels {
+    base: String,
+    handle: reload::Handle<EnvFilter, Registry>,
+    state: Arc<Mutex<State>>,
+}
+
+/// Install the global subscriber: reloadable filter, formatter and the shutdown log counter
+pub fn init() {
+    let base = std::env::var("RUST_LOG")
+        .ok()
+        .filter(|filter| EnvFilter::try_new(filter).is_ok())
+        .unwrap_or_else(|| DEFAULT_FILTER.to_string());
+    let (filter, handle) = reload::Layer::new(EnvFilter::new(&base));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(report::LogCounter)
+        .init();
+    let _ = LEVELS.set(LogLevels {
+        base,
+        handle,
+        state: Arc::new(Mutex::new(State {
+            overrides: Vec::new(),
+        })),
+    });
+}
+
+/// The global filter handle, None when `init` wasn't called (tests, other binaries)
+pub fn levels() -> Option<LogLevels> {
+    LEVELS.get().cloned()
+}
+
+impl LogLevels {
+    /// Layer `directive` over the filter, replacing any override of the same target; it lapses
+    /// after `ttl` (at most MAX_TTL_SECS) when given
+    pub fn set(&self, directive: &str, ttl: Option<Duration>) -> Result<LogLevelsView> {
+        let directive = directive.trim();
+        let parsed: Directive = directive
+            .parse()
+            .map_err(|e| anyhow!("Invalid log directive {:?}: {}", directive, e))?;
+        let directive = parsed.to_string();
+        let ttl =
+            ttl.map(|ttl| ttl.clamp(Duration::zero(), Duration::seconds(MAX_TTL_SECS as i64)));
+        let expires_at = match ttl {
+            Some(ttl) => Some(
+                Utc::now()
+                    .checked_add_signed(ttl)
+                    .ok_or_else(|| anyhow!("Log override ttl out of range"))?,
+            ),
+            None => None,
+        };
+        {
+            let mut state = self.lock()?;
+            state
+                .overrides
+                .retain(|o| target(&o.directive) != target(&directive));
+            state.overrides.push(LogOverride {
+                directive: directive.clone(),
+                expires_at,
+            });
+            self.apply(&state)?;
+        }
+        info!(
+            "🔧 Log filter override {}{}",
+            directive,
+            expires_at.map_or(String::new(), |at| format!(" until {}", at))
+        );
+
+        if let Some(ttl) = ttl {
+            let levels = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(ttl.to_std().unwrap_or_default()).await;
+                levels.expire();
+            });
+        }
+        self.view()
+    }
+
+    /// Drop every override
+    pub fn reset(&self) -> Result<LogLevelsView> {
+        {
+            let mut state = self.lock()?;
+            state.overrides.clear();
+            self.apply(&state)?;
+        }
+        info!("🔧 Log filter back to {}", self.base);
+        self.view()
+    }
+
+    /// Drop the overrides whose time is up
+    fn expire(&self) {
+        let Ok(mut state) = self.lock() else {
+            return;
+        };
+        let now = Utc::now();
+        let before = state.overrides.len();
+        state
+            .overrides
+            .retain(|o| o.expires_at.map_or(true, |at| at > now));
+        if state.overrides.len() != before && self.apply(&state).is_ok() {
+            info!("🔧 Log filter override lapsed");
+        }
+    }
+
+    pub fn view(&self) -> Result<LogLevelsView> {
+        let state = self.lock()?;
+        Ok(LogLevelsView {
+            base: self.base.clone(),
+            overrides: state.overrides.clone(),
+            effective: combine(&self.base, &state.overrides),
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, State>> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Log levels lock poisoned"))
+    }
+
+    fn apply(&self, state: &State) -> Result<()> {
+        let filter = EnvFilter::try_new(combine(&self.base, &state.overrides))?;
+        self.handle
+            .reload(filter)
+            .map_err(|e| anyhow!("Failed to reload the log filter: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn over(directive: &str) -> LogOverride {
+        LogOverride {
+            directive: directive.to_string(),
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_overrides_replace_base_directives_per_target() {
+        let base = "polymarket_hft_agent=debug,info";
+        assert_eq!(combine(base, &[]), base);
+        assert_eq!(
+            combine(base, &[over("polymarket_hft_agent::polymarket::ws=trace")]),
+            "polymarket_hft_agent=debug,info,polymarket_hft_agent::polymarket::ws=trace"
+        );
+        assert_eq!(
+            combine(base, &[over("polymarket_hft_agent=warn"), over("error")]),
+            "polymarket_hft_agent=warn,error"
+        );
+        // Span field filters carry their own `=`
+        assert_eq!(
+            target("polymarket_hft_agent[order{side=YES}]=trace"),
+            "polymarket_hft_agent[order{side=YES}]"
+        );
+        assert_eq!(
+            target("polymarket_hft_agent[order{side=YES}]"),
+            "polymarket_hft_agent[order{side=YES}]"
+        );
+        assert_eq!(target("debug"), "");
+    }
+}
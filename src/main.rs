@@ -1,30 +1,69 @@
 use anyhow::Result;
+use std::process::ExitCode;
+#[cfg(feature = "trading")]
 use std::sync::{Arc, Mutex};
-use tracing::{info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::info;
+#[cfg(feature = "trading")]
+use tracing::{error, warn};
 
+#[cfg(any(feature = "trading", feature = "alloc-profiling"))]
+use polymarket_hft_agent::alloc_profile;
+#[cfg(feature = "trading")]
 use polymarket_hft_agent::analytics::PnLTracker;
+#[cfg(feature = "trading")]
+use polymarket_hft_agent::api;
+use polymarket_hft_agent::cli::{self, Command};
 use polymarket_hft_agent::config::Config;
+#[cfg(feature = "trading")]
+use polymarket_hft_agent::exit_report::ExitReason;
+use polymarket_hft_agent::logging;
+#[cfg(not(feature = "trading"))]
+use polymarket_hft_agent::observer;
+#[cfg(feature = "trading")]
 use polymarket_hft_agent::sniper::Sniper;
 
 // Unused imports removed
 
+#[cfg(not(feature = "alloc-profiling"))]
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
+// Same allocator, with per-region accounting
+#[cfg(feature = "alloc-profiling")]
+#[global_allocator]
+static GLOBAL: alloc_profile::CountingAlloc<mimalloc::MiMalloc> =
+    alloc_profile::CountingAlloc(mimalloc::MiMalloc);
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "polymarket_hft_agent=debug,info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+async fn main() -> Result<ExitCode> {
+    // Initialize logging (filter adjustable at runtime, see /api/log-levels)
+    logging::init();
+
+    // One-shot subcommands (e.g. `replay-decision <id>`)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = Command::parse(&args)? {
+        cli::run(command).await?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    run(&args).await
+}
+
+/// Read-only build: no engine to run, serve the API from the DATA_DIR of the trading host
+#[cfg(not(feature = "trading"))]
+async fn run(_args: &[String]) -> Result<ExitCode> {
+    info!("🔭 Read-only build (no trading feature): running the observer");
+    observer::run(Config::from_env()?).await?;
+    Ok(ExitCode::SUCCESS)
+}
 
+#[cfg(feature = "trading")]
+async fn run(args: &[String]) -> Result<ExitCode> {
     // Load configuration
-    let config = Config::from_env()?;
+    let mut config = Config::from_env()?;
+    if args.iter().any(|arg| arg == "--restore") {
+        config.agent.restore_snapshot = true;
+    }
 
     // Phase 2 Optimization: CPU Pinning
     // Pin main thread to dedicated core for consistent latency
@@ -43,22 +82,43 @@ async fn main() -> Result<()> {
     print_banner(&config);
 
     // Initialize PnL tracker
-    let pnl_tracker = Arc::new(Mutex::new(PnLTracker::new(1000.0))); // $1000 initial capital
+    let pnl_tracker = Arc::new(Mutex::new(PnLTracker::new(config.risk.capital_usd)));
 
     // Small delay to ensure tokio runtime is fully initialized
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
     // Create and run sniper
+    let api_port = config.agent.api_port;
+    let api_bind = config.agent.api_bind.clone();
     let mut sniper = Sniper::new(config, pnl_tracker).await?;
 
-    // Run sniper (this blocks until Ctrl+C)
+    // HTTP API (metrics / dashboard)
+    if api_port > 0 {
+        let state = sniper.api_state();
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(state, &api_bind, api_port).await {
+                error!("❌ API server error: {:#}", e);
+            }
+        });
+    }
+
+    // Run sniper (this blocks until SIGINT / SIGTERM or a risk halt)
     let sniper_result = sniper.run().await;
 
+    // Exit report for the supervisor: 0 clean stop, 1 crash, 3 risk halt
+    let reason = match &sniper_result {
+        Ok(reason) => reason.clone(),
+        Err(e) => ExitReason::crash(format!("{:#}", e)),
+    };
+    sniper.write_exit_report(&reason).await;
+
     // Cleanup
+    alloc_profile::print_report(20);
 
-    sniper_result
+    sniper_result.map(|reason| ExitCode::from(reason.kind.code()))
 }
 
+#[cfg(feature = "trading")]
 fn print_banner(config: &Config) {
     println!("\n╔═══════════════════════════════════════════════════════════╗");
     println!("║          Polymarket HFT Agent with OpenRouter            ║");
@@ -116,6 +176,12 @@ fn print_banner(config: &Config) {
         "⏱️  Poll Interval: {} seconds",
         config.agent.market_poll_interval_secs
     );
+    if config.agent.new_market_poll_interval_ms > 0 {
+        println!(
+            "⏱️  New-Market Poll: {} ms",
+            config.agent.new_market_poll_interval_ms
+        );
+    }
     println!();
     println!("Press Ctrl+C to stop");
     println!("═══════════════════════════════════════════════════════════");
@@ -0,0 +1,385 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::config::ClassifierConfig;
+use crate::llm_budget::{Admission, LlmBudget, LlmUsage};
+use crate::polymarket::MarketData;
+
+/// Coarse market buckets that share liquidity characteristics and risk
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+pub enum MarketCategory {
+    Crypto,
+    Politics,
+    Sports,
+    PopCulture,
+    Macro,
+    Other,
+}
+
+// Whole words / phrases, matched on the lowercased question with punctuation stripped
+const CRYPTO: &[&str] = &[
+    "bitcoin",
+    "btc",
+    "ethereum",
+    "eth",
+    "solana",
+    "sol",
+    "xrp",
+    "ripple",
+    "dogecoin",
+    "doge",
+    "cardano",
+    "bnb",
+    "shiba inu",
+    "crypto",
+    "cryptocurrency",
+    "stablecoin",
+    "usdt",
+    "usdc",
+    "memecoin",
+    "altcoin",
+    "halving",
+    "etf flows",
+    "coinbase",
+    "binance",
+    "microstrategy",
+];
+const MACRO: &[&str] = &[
+    "fed",
+    "federal reserve",
+    "fomc",
+    "interest rate",
+    "interest rates",
+    "rate cut",
+    "rate cuts",
+    "rate hike",
+    "bps",
+    "basis points",
+    "cpi",
+    "inflation",
+    "gdp",
+    "recession",
+    "unemployment",
+    "jobs report",
+    "nonfarm payrolls",
+    "treasury",
+    "yield",
+    "yields",
+    "s p 500",
+    "nasdaq",
+    "dow jones",
+    "ecb",
+    "bank of england",
+    "boj",
+    "oil price",
+    "crude oil",
+    "tariff",
+    "tariffs",
+];
+const SPORTS: &[&str] = &[
+    "vs",
+    "nba",
+    "nfl",
+    "nhl",
+    "mlb",
+    "mls",
+    "wnba",
+    "ncaa",
+    "premier league",
+    "champions league",
+    "la liga",
+    "serie a",
+    "bundesliga",
+    "world cup",
+    "super bowl",
+    "stanley cup",
+    "world series",
+    "ufc",
+    "boxing",
+    "f1",
+    "formula 1",
+    "grand prix",
+    "wimbledon",
+    "us open",
+    "french open",
+    "olympics",
+    "match",
+    "game",
+    "tournament",
+    "playoffs",
+    "finals",
+    "mvp",
+    "touchdown",
+];
+const POLITICS: &[&str] = &[
+    "election",
+    "elections",
+    "president",
+    "presidential",
+    "senate",
+    "congress",
+    "governor",
+    "mayor",
+    "parliament",
+    "prime minister",
+    "trump",
+    "biden",
+    "harris",
+    "vote",
+    "primary",
+    "nominee",
+    "republican",
+    "democrat",
+    "democrats",
+    "republicans",
+    "impeach",
+    "impeached",
+    "cabinet",
+    "supreme court",
+    "minister",
+    "referendum",
+    "poll",
+];
+const POP_CULTURE: &[&str] = &[
+    "oscar",
+    "oscars",
+    "grammy",
+    "grammys",
+    "emmy",
+    "emmys",
+    "golden globe",
+    "album",
+    "song",
+    "movie",
+    "film",
+    "box office",
+    "netflix",
+    "spotify",
+    "billboard",
+    "youtube",
+    "tiktok",
+    "twitter followers",
+    "celebrity",
+    "taylor swift",
+    "kanye",
+    "drake",
+    "beyonce",
+    "kardashian",
+    "mrbeast",
+    "eurovision",
+    "tv show",
+    "season finale",
+    "gta",
+    "time person of the year",
+];
+
+impl MarketCategory {
+    pub const ALL: [MarketCategory; 6] = [
+        MarketCategory::Crypto,
+        MarketCategory::Politics,
+        MarketCategory::Sports,
+        MarketCategory::PopCulture,
+        MarketCategory::Macro,
+        MarketCategory::Other,
+    ];
+
+    /// Keyword rules on the question text. Order matters: a Fed decision tweeted by a president
+    /// is macro, a crypto ETF vote is crypto
+    pub fn classify(question: &str) -> Self {
+        let text = normalize(question);
+        let rules: [(&[&str], MarketCategory); 5] = [
+            (CRYPTO, MarketCategory::Crypto),
+            (MACRO, MarketCategory::Macro),
+            (SPORTS, MarketCategory::Sports),
+            (POLITICS, MarketCategory::Politics),
+            (POP_CULTURE, MarketCategory::PopCulture),
+        ];
+        rules
+            .iter()
+            .find(|(keywords, _)| keywords.iter().any(|k| text.contains(&format!(" {} ", k))))
+            .map(|(_, category)| *category)
+            .unwrap_or(MarketCategory::Other)
+    }
+
+    /// Stored category of a market, or the rule-based one while it isn't classified yet
+    pub fn of(market: &MarketData) -> Self {
+        market
+            .category
+            .unwrap_or_else(|| Self::classify(&market.question))
+    }
+
+    /// Config / API spelling: crypto, politics, sports, pop-culture, macro, other
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "crypto" => Some(MarketCategory::Crypto),
+            "politics" => Some(MarketCategory::Politics),
+            "sports" => Some(MarketCategory::Sports),
+            "pop-culture" | "popculture" => Some(MarketCategory::PopCulture),
+            "macro" => Some(MarketCategory::Macro),
+            "other" => Some(MarketCategory::Other),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MarketCategory::Crypto => "crypto",
+            MarketCategory::Politics => "politics",
+            MarketCategory::Sports => "sports",
+            MarketCategory::PopCulture => "pop-culture",
+            MarketCategory::Macro => "macro",
+            MarketCategory::Other => "other",
+        }
+    }
+}
+
+/// Lowercase, punctuation to spaces, padded so every word is surrounded by spaces
+fn normalize(question: &str) -> String {
+    let words: Vec<String> = question
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+const CLASSIFIER_PROMPT: &str = "Classify the prediction market question. Answer with exactly \
+    one of: crypto, politics, sports, pop-culture, macro, other";
+
+/// Fallback for questions the rules leave as Other: asks an OpenAI-compatible chat completions
+/// endpoint for one of the category names
+pub struct LlmClassifier {
+    http: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+    budget: Option<Arc<Mutex<LlmBudget>>>,
+}
+
+impl LlmClassifier {
+    /// None unless `CATEGORY_LLM_URL` is set
+    pub fn from_config(config: &ClassifierConfig) -> Option<Self> {
+        let url = config.llm_url.clone()?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .ok()?;
+        Some(Self {
+            http,
+            url,
+            api_key: config.llm_api_key.clone(),
+            model: config.llm_model.clone(),
+            budget: None,
+        })
+    }
+
+    /// Rate-limit, cache and cost every call through `budget`
+    pub fn with_budget(mut self, budget: Arc<Mutex<LlmBudget>>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Category of `market_id`'s question. Refused without a call when over budget
+    pub async fn classify(&self, market_id: &str, question: &str) -> Result<MarketCategory> {
+        let prompt = format!("{}\n{}\n{}", self.model, CLASSIFIER_PROMPT, question);
+        let key = match self.budget.as_ref().map(|b| b.lock()) {
+            Some(Ok(mut budget)) => match budget.admit(market_id, &prompt, Utc::now()) {
+                Admission::Cached(answer) => return parse_answer(&answer),
+                Admission::Refused(reason) => anyhow::bail!("LLM call refused: {}", reason),
+                Admission::Send(key) => Some(key),
+            },
+            _ => None,
+        };
+
+        let result = self.complete(question).await;
+        if let (Some(key), Some(Ok(mut budget))) = (key, self.budget.as_ref().map(|b| b.lock())) {
+            let (answer, usage) = match &result {
+                Ok((answer, usage)) => (Some(answer.as_str()), *usage),
+                Err(_) => (None, LlmUsage::default()),
+            };
+            budget.record(market_id, key, answer, usage, Utc::now());
+        }
+        parse_answer(&result?.0)
+    }
+
+    /// One chat completion: the answer and the tokens it took
+    async fn complete(&self, question: &str) -> Result<(String, LlmUsage)> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "temperature": 0,
+            "max_tokens": 5,
+            "messages": [
+                { "role": "system", "content": CLASSIFIER_PROMPT },
+                { "role": "user", "content": question },
+            ],
+        });
+        let mut request = self.http.post(&self.url).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response: serde_json::Value = request.send().await?.error_for_status()?.json().await?;
+        let answer = response["choices"][0]["message"]["content"]
+            .as_str()
+            .context("No completion in classifier response")?;
+        let usage = LlmUsage {
+            prompt_tokens: response["usage"]["prompt_tokens"].as_u64().unwrap_or(0),
+            completion_tokens: response["usage"]["completion_tokens"].as_u64().unwrap_or(0),
+            cost_usd: response["usage"]["cost"].as_f64(),
+        };
+        Ok((answer.to_string(), usage))
+    }
+}
+
+fn parse_answer(answer: &str) -> Result<MarketCategory> {
+    MarketCategory::parse(answer.trim_matches(|c: char| !c.is_alphanumeric() && c != '-'))
+        .with_context(|| format!("Classifier answered {:?}", answer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules() {
+        let cases = [
+            (
+                "Will Bitcoin reach $150k by December?",
+                MarketCategory::Crypto,
+            ),
+            (
+                "Will the Fed cut interest rates in March?",
+                MarketCategory::Macro,
+            ),
+            ("Lakers vs. Celtics", MarketCategory::Sports),
+            (
+                "Will Trump win the 2028 presidential election?",
+                MarketCategory::Politics,
+            ),
+            (
+                "Will Oppenheimer win Best Picture at the Oscars?",
+                MarketCategory::PopCulture,
+            ),
+            // Word boundaries: "resolution" isn't SOL, "Canada" isn't ADA, "fedex" isn't the Fed
+            (
+                "Will the UN pass a resolution on Canada?",
+                MarketCategory::Other,
+            ),
+            ("Will FedEx stock close higher?", MarketCategory::Other),
+        ];
+        for (question, expected) in cases {
+            assert_eq!(MarketCategory::classify(question), expected, "{}", question);
+        }
+        assert_eq!(
+            MarketCategory::parse("Pop_Culture"),
+            Some(MarketCategory::PopCulture)
+        );
+        assert_eq!(MarketCategory::parse("weather"), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Condition ids in one canonical form
+//!
+//! The same condition reaches the agent spelled in different ways: `{:?}` of an H256 from the
+//! CTF event listener, Gamma's `conditionId`, ids typed by the operator and ids in state files
+//! written by older runs. The registry, the lifecycle (seen markets) and the metadata retry
+//! queue only match them when they're spelled the same, so ids are brought to lowercase,
+//! 0x-prefixed hex where they enter. Condition ids are hashes, not addresses: there is no
+//! EIP-55 checksum to apply.
+use anyhow::{bail, Result};
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+/// Hex digits of a 32-byte id
+const HEX_LEN: usize = 64;
+
+/// A condition id, always lowercase and 0x-prefixed
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ConditionId(String);
+
+impl ConditionId {
+    /// Accepts any case, with or without the 0x prefix, surrounding whitespace trimmed
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        let hex = raw
+            .strip_prefix("0x")
+            .or_else(|| raw.strip_prefix("0X"))
+            .unwrap_or(raw);
+        if hex.len() != HEX_LEN || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Invalid condition id '{}': expected 32 bytes of hex", raw);
+        }
+        Ok(Self(format!("0x{}", hex.to_ascii_lowercase())))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    pub fn to_h256(&self) -> H256 {
+        H256::from_str(&self.0).expect("canonical condition id is valid hex")
+    }
+}
+
+impl From<H256> for ConditionId {
+    fn from(hash: H256) -> Self {
+        // Debug is the full 0x-prefixed lowercase hex (Display abbreviates)
+        Self(format!("{:?}", hash))
+    }
+}
+
+impl FromStr for ConditionId {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        Self::parse(raw)
+    }
+}
+
+impl TryFrom<String> for ConditionId {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: String) -> Result<Self> {
+        Self::parse(&raw)
+    }
+}
+
+impl From<ConditionId> for String {
+    fn from(id: ConditionId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<str> for ConditionId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ConditionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Key a market id the way the registry and lifecycle do: condition ids in canonical form,
+/// anything else (simulator and test markets) as given. Borrows when nothing changes, which
+/// is every lookup of an id that already came through here
+pub fn normalize_market_id(market_id: &str) -> Cow<'_, str> {
+    if is_canonical(market_id) {
+        return Cow::Borrowed(market_id);
+    }
+    match ConditionId::parse(market_id) {
+        Ok(id) => Cow::Owned(id.into_string()),
+        Err(_) => Cow::Borrowed(market_id),
+    }
+}
+
+fn is_canonical(id: &str) -> bool {
+    id.len() == HEX_LEN + 2
+        && id.starts_with("0x")
+        && id[2..]
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1";
+
+    #[test]
+    fn test_spellings_of_one_condition_agree() {
+        let from_event = ConditionId::from(H256::from_str(ID).unwrap());
+        let from_gamma = ConditionId::parse(&ID.to_uppercase().replacen("0X", "0x", 1)).unwrap();
+        let unprefixed = ConditionId::parse(&format!(" {} ", &ID[2..])).unwrap();
+        assert_eq!(from_event.as_str(), ID);
+        assert_eq!(from_event, from_gamma);
+        assert_eq!(from_event, unprefixed);
+        assert_eq!(from_event.to_h256(), H256::from_str(ID).unwrap());
+
+        assert!(ConditionId::parse("0x1234").is_err());
+        assert!(ConditionId::parse(&ID.replace('f', "g")).is_err());
+
+        let json = serde_json::to_string(&from_event).unwrap();
+        assert_eq!(json, format!("\"{}\"", ID));
+        let back: ConditionId = serde_json::from_str(&json.to_uppercase()).unwrap();
+        assert_eq!(back, from_event);
+    }
+
+    #[test]
+    fn test_normalize_market_id() {
+        assert!(matches!(normalize_market_id(ID), Cow::Borrowed(_)));
+        assert_eq!(normalize_market_id(&ID.to_uppercase()), ID);
+        // Not a condition id: left alone
+        assert_eq!(normalize_market_id("sim-market-7"), "sim-market-7");
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use crate::polymarket::ws::{OrderbookUpdate, PriceLevel};
+
+/// Ask levels kept per asset, best first
+pub const MAX_LEVELS: usize = 20;
+
+/// Ask side of each asset's CLOB WS book, as (price, size) best first: replaced by each book
+/// snapshot and patched by the price changes in between. The registry only keeps best asks;
+/// the arbitrage dust guard needs to know how much sits behind them
+#[derive(Debug, Default)]
+pub struct AskLadders {
+    ladders: HashMap<String, Vec<(f64, f64)>>,
+}
+
+impl AskLadders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the asset's ladder with the asks of a book snapshot, or patch it with a price
+    /// change (until the first snapshot there is nothing to patch)
+    pub fn observe(&mut self, update: &OrderbookUpdate) {
+        match &update.change {
+            Some(change) => self.apply_change(update, change.side == "SELL", &change.level),
+            None => self.replace(&update.asset_id, &update.asks),
+        }
+    }
+
+    fn replace(&mut self, asset_id: &str, asks: &[PriceLevel]) {
+        let mut levels: Vec<(f64, f64)> = asks.iter().filter_map(parse_level).collect();
+        levels.retain(|(price, size)| *price > 0.0 && *size > 0.0);
+        if levels.is_empty() {
+            return;
+        }
+        levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+        levels.truncate(MAX_LEVELS);
+        self.ladders.insert(asset_id.to_string(), levels);
+    }
+
+    /// Set the size of an ask level (`is_ask`), then drop the levels under the new best ask:
+    /// they were taken
+    fn apply_change(&mut self, update: &OrderbookUpdate, is_ask: bool, level: &PriceLevel) {
+        let Some(ladder) = self.ladders.get_mut(&update.asset_id) else {
+            return;
+        };
+        if let Some((price, size)) = parse_level(level).filter(|_| is_ask) {
+            let at = ladder.partition_point(|(p, _)| *p < price);
+            match ladder.get_mut(at) {
+                Some(existing) if existing.0 == price && size > 0.0 => existing.1 = size,
+                Some(existing) if existing.0 == price => {
+                    ladder.remove(at);
+                }
+                _ if size > 0.0 && price > 0.0 => ladder.insert(at, (price, size)),
+                _ => {}
+            }
+            ladder.truncate(MAX_LEVELS);
+        }
+        let best_ask = update
+            .asks
+            .first()
+            .and_then(|l| l.price.parse::<f64>().ok());
+        if let Some(best_ask) = best_ask.filter(|p| *p > 0.0) {
+            ladder.retain(|(price, _)| *price >= best_ask);
+        }
+        if ladder.is_empty() {
+            self.ladders.remove(&update.asset_id);
+        }
+    }
+
+    pub fn get(&self, asset_id: &str) -> Option<&[(f64, f64)]> {
+        self.ladders.get(asset_id).map(Vec::as_slice)
+    }
+
+    /// Stop tracking an asset (unsubscribed)
+    pub fn forget(&mut self, asset_id: &str) {
+        self.ladders.remove(asset_id);
+    }
+}
+
+fn parse_level(level: &PriceLevel) -> Option<(f64, f64)> {
+    Some((level.price.parse().ok()?, level.size.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::ws::LevelChange;
+
+    fn level(price: &str, size: &str) -> PriceLevel {
+        PriceLevel {
+            price: price.to_string(),
+            size: size.to_string(),
+        }
+    }
+
+    fn update(asks: Vec<PriceLevel>, change: Option<(&str, PriceLevel)>) -> OrderbookUpdate {
+        OrderbookUpdate {
+            asset_id: "a1".to_string(),
+            bids: vec![],
+            asks,
+            timestamp: "1".to_string(),
+            hash: "h".to_string(),
+            seq: 0,
+            received_ns: 0,
+            change: change.map(|(side, level)| LevelChange {
+                side: side.to_string(),
+                level,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_price_changes_patch_the_ladder() {
+        let mut ladders = AskLadders::new();
+        // Nothing to patch before the first snapshot
+        ladders.observe(&update(
+            vec![level("0.50", "0")],
+            Some(("SELL", level("0.50", "10"))),
+        ));
+        assert!(ladders.get("a1").is_none());
+
+        ladders.observe(&update(
+            vec![level("0.55", "20"), level("0.50", "10")],
+            None,
+        ));
+        assert_eq!(ladders.get("a1").unwrap(), &[(0.50, 10.0), (0.55, 20.0)]);
+
+        // A new level, a resized one, and a bid change that leaves the asks alone
+        ladders.observe(&update(
+            vec![level("0.50", "0")],
+            Some(("SELL", level("0.52", "5"))),
+        ));
+        ladders.observe(&update(
+            vec![level("0.50", "0")],
+            Some(("SELL", level("0.55", "8"))),
+        ));
+        ladders.observe(&update(
+            vec![level("0.50", "0")],
+            Some(("BUY", level("0.45", "100"))),
+        ));
+        assert_eq!(
+            ladders.get("a1").unwrap(),
+            &[(0.50, 10.0), (0.52, 5.0), (0.55, 8.0)]
+        );
+
+        // The best level is taken: size 0, and the best ask moves up
+        ladders.observe(&update(
+            vec![level("0.52", "0")],
+            Some(("SELL", level("0.50", "0"))),
+        ));
+        assert_eq!(ladders.get("a1").unwrap(), &[(0.52, 5.0), (0.55, 8.0)]);
+        ladders.observe(&update(
+            vec![level("0.55", "0")],
+            Some(("BUY", level("0.45", "90"))),
+        ));
+        assert_eq!(ladders.get("a1").unwrap(), &[(0.55, 8.0)]);
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::polymarket::MarketData;
+
+/// A tracked market on the expirations calendar
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExpirationEntry {
+    pub market_id: String,
+    pub question: String,
+    pub end_date: DateTime<Utc>,
+    pub seconds_remaining: i64,
+    /// On the pre-expiry watchlist
+    pub watched: bool,
+}
+
+/// Markets inside the expiration watch window. Their books stay subscribed and they are
+/// re-evaluated on every watchlist tick, since time running out changes the expiration
+/// strategy's answer even when the book doesn't move
+#[derive(Debug)]
+pub struct ExpirationWatchlist {
+    window: Duration,
+    /// market_id -> end time
+    watched: HashMap<String, DateTime<Utc>>,
+}
+
+impl ExpirationWatchlist {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window: Duration::seconds(window_secs.min(i64::MAX as u64) as i64),
+            watched: HashMap::new(),
+        }
+    }
+
+    /// Promote markets entering the window and drop those past their end
+    /// Returns the newly promoted markets
+    pub fn refresh<'a>(
+        &mut self,
+        markets: &'a [MarketData],
+        now: DateTime<Utc>,
+    ) -> Vec<&'a MarketData> {
+        self.watched.retain(|_, end| *end > now);
+        let mut promoted = Vec::new();
+        for market in markets {
+            let Some(end) = market.end_time else {
+                continue;
+            };
+            if end > now
+                && end - now <= self.window
+                && !self.watched.contains_key(market.id.as_str())
+            {
+                self.watched.insert(market.id.to_string(), end);
+                promoted.push(market);
+            }
+        }
+        promoted
+    }
+
+    pub fn contains(&self, market_id: &str) -> bool {
+        self.watched.contains_key(market_id)
+    }
+
+    pub fn market_ids(&self) -> Vec<String> {
+        self.watched.keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.watched.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.watched.is_empty()
+    }
+
+    /// Markets that haven't ended yet, soonest first
+    pub fn calendar(
+        &self,
+        markets: &[MarketData],
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<ExpirationEntry> {
+        let mut entries: Vec<ExpirationEntry> = markets
+            .iter()
+            .filter_map(|market| {
+                let end = market.end_time.filter(|end| *end > now)?;
+                Some(ExpirationEntry {
+                    market_id: market.id.to_string(),
+                    question: market.question.clone(),
+                    end_date: end,
+                    seconds_remaining: (end - now).num_seconds(),
+                    watched: self.contains(&market.id),
+                })
+            })
+            .collect();
+        entries.sort_by_key(|e| e.end_date);
+        entries.truncate(limit);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(id: &str, end: Option<DateTime<Utc>>) -> MarketData {
+        MarketData {
+            id: id.into(),
+            question: format!("{}?", id),
+            end_date: end.map(|end| end.to_rfc3339()),
+            yes_price: 0.5,
+            no_price: 0.5,
+            ..Default::default()
+        }
+        .with_end_time()
+    }
+
+    #[test]
+    fn test_promotes_markets_entering_the_window() {
+        let now = Utc::now();
+        let markets = vec![
+            market("later", Some(now + Duration::hours(2))),
+            market("soon", Some(now + Duration::minutes(5))),
+            market("ended", Some(now - Duration::minutes(1))),
+            market("undated", None),
+        ];
+        let mut watchlist = ExpirationWatchlist::new(600);
+
+        let promoted = watchlist.refresh(&markets, now);
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(promoted[0].id, "soon");
+        // Already watched: not promoted twice
+        assert!(watchlist.refresh(&markets, now).is_empty());
+
+        let calendar = watchlist.calendar(&markets, now, 10);
+        let ids: Vec<&str> = calendar.iter().map(|e| e.market_id.as_str()).collect();
+        assert_eq!(ids, vec!["soon", "later"]);
+        assert!(calendar[0].watched && !calendar[1].watched);
+
+        // Past its end it leaves the watchlist; "later" enters once within the window
+        let promoted = watchlist.refresh(&markets, now + Duration::minutes(111));
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(promoted[0].id, "later");
+        assert!(!watchlist.contains("soon"));
+        assert_eq!(watchlist.len(), 1);
+    }
+}
@@ -0,0 +1,356 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use utoipa::ToSchema;
+
+use crate::polymarket::ws::PriceLevel;
+
+/// Levels per side summed into the depth imbalance
+pub const DEPTH_LEVELS: usize = 5;
+
+/// Best bid / ask with the size resting there
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BookTop {
+    bid: f64,
+    bid_size: f64,
+    ask: f64,
+    ask_size: f64,
+    /// (bid depth - ask depth) / (bid depth + ask depth) over the top `DEPTH_LEVELS`
+    depth_imbalance: f64,
+}
+
+impl BookTop {
+    /// None unless both sides carry a sized level (price-change events only carry prices)
+    fn from_levels(bids: &[PriceLevel], asks: &[PriceLevel]) -> Option<Self> {
+        let parse = |levels: &[PriceLevel]| -> Vec<(f64, f64)> {
+            levels
+                .iter()
+                .filter_map(|l| Some((l.price.parse::<f64>().ok()?, l.size.parse::<f64>().ok()?)))
+                .filter(|(price, size)| *price > 0.0 && *size > 0.0)
+                .collect()
+        };
+        let mut bids = parse(bids);
+        let mut asks = parse(asks);
+        // Best first, whatever order the feed lists levels in
+        bids.sort_by(|a, b| b.0.total_cmp(&a.0));
+        asks.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (&(bid, bid_size), &(ask, ask_size)) = (bids.first()?, asks.first()?);
+
+        let depth = |levels: &[(f64, f64)]| -> f64 {
+            levels.iter().take(DEPTH_LEVELS).map(|(_, size)| size).sum()
+        };
+        let (bid_depth, ask_depth) = (depth(&bids), depth(&asks));
+        Some(Self {
+            bid,
+            bid_size,
+            ask,
+            ask_size,
+            depth_imbalance: (bid_depth - ask_depth) / (bid_depth + ask_depth),
+        })
+    }
+
+    fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+}
+
+/// Change of the best levels between two books
+#[derive(Debug, Clone, Copy)]
+struct FlowEvent {
+    at_ms: u64,
+    /// Order flow imbalance (Cont, Kukanov & Stoikov): bid-side inflow minus ask-side inflow
+    ofi: f64,
+    /// Size taken off the best bid without it improving (sells hitting the queue or cancels)
+    bid_depletion: f64,
+    /// Size taken off the best ask without it improving (buys lifting the queue or cancels)
+    ask_depletion: f64,
+    /// Mean of the two best queues, the scale OFI is normalized by
+    top_depth: f64,
+    mid_before: f64,
+}
+
+impl FlowEvent {
+    fn between(prev: &BookTop, cur: &BookTop, at_ms: u64) -> Self {
+        let bid_flow = if cur.bid > prev.bid {
+            cur.bid_size
+        } else if cur.bid == prev.bid {
+            cur.bid_size - prev.bid_size
+        } else {
+            -prev.bid_size
+        };
+        let ask_flow = if cur.ask < prev.ask {
+            cur.ask_size
+        } else if cur.ask == prev.ask {
+            cur.ask_size - prev.ask_size
+        } else {
+            -prev.ask_size
+        };
+        let bid_depletion = if cur.bid == prev.bid {
+            (prev.bid_size - cur.bid_size).max(0.0)
+        } else if cur.bid < prev.bid {
+            prev.bid_size
+        } else {
+            0.0
+        };
+        let ask_depletion = if cur.ask == prev.ask {
+            (prev.ask_size - cur.ask_size).max(0.0)
+        } else if cur.ask > prev.ask {
+            prev.ask_size
+        } else {
+            0.0
+        };
+        Self {
+            at_ms,
+            ofi: bid_flow - ask_flow,
+            bid_depletion,
+            ask_depletion,
+            top_depth: (prev.bid_size + prev.ask_size) / 2.0,
+            mid_before: prev.mid(),
+        }
+    }
+}
+
+/// Flow features of one asset over one rolling window
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WindowFeatures {
+    pub window_ms: u64,
+    /// Book changes inside the window
+    pub events: usize,
+    /// Summed OFI in shares: positive = net buying pressure at the touch
+    pub ofi: f64,
+    /// OFI over the mean best-queue size, comparable across assets
+    pub normalized_ofi: f64,
+    pub bid_depletion: f64,
+    pub ask_depletion: f64,
+    /// Mid now minus mid at the start of the window
+    pub mid_change: f64,
+}
+
+/// Latest book features of one asset, as consumed by strategies
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeatureFrame {
+    pub asset_id: String,
+    pub market_id: String,
+    /// YES or NO
+    pub side: String,
+    /// Feed time of the last book, unix ms
+    pub updated_ms: u64,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub bid_size: f64,
+    pub ask_size: f64,
+    pub mid: f64,
+    pub spread: f64,
+    /// Top-of-book depth imbalance in [-1, 1]: positive = more resting bids
+    pub depth_imbalance: f64,
+    /// Shortest window first
+    pub windows: Vec<WindowFeatures>,
+}
+
+impl FeatureFrame {
+    /// Features over the shortest window at least `window_ms` long
+    pub fn window(&self, window_ms: u64) -> Option<&WindowFeatures> {
+        self.windows.iter().find(|w| w.window_ms >= window_ms)
+    }
+}
+
+struct AssetFlow {
+    market_id: String,
+    side: String,
+    top: BookTop,
+    updated_ms: u64,
+    events: VecDeque<FlowEvent>,
+}
+
+/// Order flow imbalance and queue depletion per asset, from the L2 snapshots of the CLOB WS
+/// over rolling windows
+pub struct FeatureTracker {
+    /// Ascending
+    windows_ms: Vec<u64>,
+    assets: HashMap<String, AssetFlow>,
+}
+
+impl FeatureTracker {
+    pub fn new(windows_ms: &[u64]) -> Self {
+        let mut windows_ms: Vec<u64> = windows_ms.iter().copied().filter(|w| *w > 0).collect();
+        windows_ms.sort_unstable();
+        windows_ms.dedup();
+        Self {
+            windows_ms,
+            assets: HashMap::new(),
+        }
+    }
+
+    /// Fold one book of an asset in. Books without a sized level on both sides are skipped
+    pub fn observe(
+        &mut self,
+        asset_id: &str,
+        market_id: &str,
+        side: &str,
+        bids: &[PriceLevel],
+        asks: &[PriceLevel],
+        at_ms: u64,
+    ) {
+        let Some(top) = BookTop::from_levels(bids, asks) else {
+            return;
+        };
+        let horizon = self.windows_ms.last().copied().unwrap_or(0);
+        match self.assets.get_mut(asset_id) {
+            Some(flow) => {
+                if at_ms < flow.updated_ms {
+                    return;
+                }
+                if top != flow.top {
+                    flow.events
+                        .push_back(FlowEvent::between(&flow.top, &top, at_ms));
+                }
+                flow.top = top;
+                flow.updated_ms = at_ms;
+                let cutoff = at_ms.saturating_sub(horizon);
+                while flow.events.front().is_some_and(|e| e.at_ms < cutoff) {
+                    flow.events.pop_front();
+                }
+            }
+            None => {
+                self.assets.insert(
+                    asset_id.to_string(),
+                    AssetFlow {
+                        market_id: market_id.to_string(),
+                        side: side.to_string(),
+                        top,
+                        updated_ms: at_ms,
+                        events: VecDeque::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Features of an asset with windows ending at `now_ms`
+    pub fn frame(&self, asset_id: &str, now_ms: u64) -> Option<FeatureFrame> {
+        let flow = self.assets.get(asset_id)?;
+        let top = &flow.top;
+        let windows = self
+            .windows_ms
+            .iter()
+            .map(|&window_ms| {
+                let cutoff = now_ms.saturating_sub(window_ms);
+                let events: Vec<&FlowEvent> =
+                    flow.events.iter().filter(|e| e.at_ms >= cutoff).collect();
+                let ofi: f64 = events.iter().map(|e| e.ofi).sum();
+                let mean_depth = if events.is_empty() {
+                    0.0
+                } else {
+                    events.iter().map(|e| e.top_depth).sum::<f64>() / events.len() as f64
+                };
+                WindowFeatures {
+                    window_ms,
+                    events: events.len(),
+                    ofi,
+                    normalized_ofi: if mean_depth > 0.0 {
+                        ofi / mean_depth
+                    } else {
+                        0.0
+                    },
+                    bid_depletion: events.iter().map(|e| e.bid_depletion).sum(),
+                    ask_depletion: events.iter().map(|e| e.ask_depletion).sum(),
+                    mid_change: events
+                        .first()
+                        .map(|e| top.mid() - e.mid_before)
+                        .unwrap_or(0.0),
+                }
+            })
+            .collect();
+        Some(FeatureFrame {
+            asset_id: asset_id.to_string(),
+            market_id: flow.market_id.clone(),
+            side: flow.side.clone(),
+            updated_ms: flow.updated_ms,
+            best_bid: top.bid,
+            best_ask: top.ask,
+            bid_size: top.bid_size,
+            ask_size: top.ask_size,
+            mid: top.mid(),
+            spread: top.ask - top.bid,
+            depth_imbalance: top.depth_imbalance,
+            windows,
+        })
+    }
+
+    /// Frame of one outcome of a market
+    pub fn market_frame(&self, market_id: &str, side: &str, now_ms: u64) -> Option<FeatureFrame> {
+        let asset_id = self
+            .assets
+            .iter()
+            .find(|(_, f)| f.market_id == market_id && f.side == side)
+            .map(|(id, _)| id.clone())?;
+        self.frame(&asset_id, now_ms)
+    }
+
+    /// Drop an unsubscribed asset: its next book would otherwise read as one huge flow event
+    pub fn forget(&mut self, asset_id: &str) {
+        self.assets.remove(asset_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(levels: &[(&str, &str)]) -> Vec<PriceLevel> {
+        levels
+            .iter()
+            .map(|(price, size)| PriceLevel {
+                price: price.to_string(),
+                size: size.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ofi_and_queue_depletion() {
+        let mut tracker = FeatureTracker::new(&[60_000, 1_000]);
+        let observe =
+            |tracker: &mut FeatureTracker, bids: &[(&str, &str)], asks: &[(&str, &str)], at_ms| {
+                tracker.observe("yes", "m1", "YES", &levels(bids), &levels(asks), at_ms)
+            };
+
+        // Listed worst-first, as the CLOB sends them
+        observe(
+            &mut tracker,
+            &[("0.48", "300"), ("0.50", "100")],
+            &[("0.54", "80"), ("0.52", "200")],
+            0,
+        );
+        // 150 lifted off the best ask, bid queue grows by 20
+        observe(
+            &mut tracker,
+            &[("0.48", "300"), ("0.50", "120")],
+            &[("0.54", "80"), ("0.52", "50")],
+            500,
+        );
+        // Ask level cleared (50 more depleted), next ask at 0.54; bid improves to 0.51 x 40
+        observe(
+            &mut tracker,
+            &[("0.50", "120"), ("0.51", "40")],
+            &[("0.54", "80")],
+            2_000,
+        );
+        // Prices only (price-change event): ignored
+        observe(&mut tracker, &[("0.51", "0")], &[("0.53", "0")], 2_500);
+
+        let frame = tracker.frame("yes", 2_000).unwrap();
+        assert_eq!(frame.windows[0].window_ms, 1_000);
+        let (recent, all) = (&frame.windows[0], &frame.windows[1]);
+        assert_eq!((recent.events, all.events), (1, 2));
+        // First event: bid +20, ask -150 -> 170; second: bid improved +40, ask moved up -(-50) -> 90
+        assert!((all.ofi - 260.0).abs() < 1e-9);
+        assert!((recent.ofi - 90.0).abs() < 1e-9);
+        assert!((all.ask_depletion - 200.0).abs() < 1e-9);
+        assert_eq!(all.bid_depletion, 0.0);
+        assert!((all.mid_change - (0.525 - 0.51)).abs() < 1e-9);
+        assert_eq!((frame.best_bid, frame.best_ask), (0.51, 0.54));
+        assert!(frame.depth_imbalance > 0.0);
+        assert!(tracker.market_frame("m1", "YES", 2_000).is_some());
+        assert!(tracker.market_frame("m1", "NO", 2_000).is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! Per-market fee rates
+//!
+//! Arbitrage edges are costed at `FEE_PER_TRADE_BPS` a leg, but Polymarket runs promotional
+//! markets without fees, where that assumption throws away every edge under 80 bps. The CLOB
+//! reports each token's base fee (`/fee-rate`); with `ZERO_FEE_ENABLED` markets are checked
+//! within a minute of being registered and again every `FEE_RATE_REFRESH_SECS`. A market
+//! known to be fee-free is costed at 0 and gets its own minimum edge and a larger size.
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use utoipa::ToSchema;
+
+/// A market's fee as last reported by the CLOB
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarketFee {
+    pub market_id: String,
+    /// Base fee of the market's tokens, per trade
+    pub fee_bps: u32,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl MarketFee {
+    pub fn is_zero(&self) -> bool {
+        self.fee_bps == 0
+    }
+}
+
+/// Fee rates of the markets checked so far. Cheap to clone, all clones share the rates
+#[derive(Clone, Default)]
+pub struct FeeRates {
+    inner: Arc<RwLock<HashMap<String, MarketFee>>>,
+}
+
+impl FeeRates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, market_id: &str, fee_bps: u32, now: DateTime<Utc>) {
+        if let Ok(mut rates) = self.inner.write() {
+            rates.insert(
+                market_id.to_string(),
+                MarketFee {
+                    market_id: market_id.to_string(),
+                    fee_bps,
+                    checked_at: now,
+                },
+            );
+        }
+    }
+
+    /// The fee the CLOB reported for the market, None until it's checked
+    pub fn fee_bps(&self, market_id: &str) -> Option<u32> {
+        self.inner
+            .read()
+            .ok()
+            .and_then(|rates| rates.get(market_id).map(|fee| fee.fee_bps))
+    }
+
+    pub fn is_zero_fee(&self, market_id: &str) -> bool {
+        self.fee_bps(market_id) == Some(0)
+    }
+
+    /// Never checked, or checked more than `refresh` ago
+    pub fn is_due(&self, market_id: &str, refresh: Duration, now: DateTime<Utc>) -> bool {
+        self.inner.read().map_or(true, |rates| {
+            rates
+                .get(market_id)
+                .map_or(true, |fee| now - fee.checked_at >= refresh)
+        })
+    }
+
+    /// Drop a market that left the registry
+    pub fn forget(&self, market_id: &str) {
+        if let Ok(mut rates) = self.inner.write() {
+            rates.remove(market_id);
+        }
+    }
+
+    /// Every market checked, fee-free ones first
+    pub fn all(&self) -> Vec<MarketFee> {
+        let mut all: Vec<MarketFee> = self
+            .inner
+            .read()
+            .map(|rates| rates.values().cloned().collect())
+            .unwrap_or_default();
+        all.sort_by(|a, b| (a.fee_bps, &a.market_id).cmp(&(b.fee_bps, &b.market_id)));
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rates_are_rechecked_after_the_refresh() {
+        let rates = FeeRates::new();
+        let now = Utc::now();
+        let refresh = Duration::hours(1);
+        assert!(rates.is_due("m1", refresh, now));
+        assert_eq!(rates.fee_bps("m1"), None);
+        assert!(!rates.is_zero_fee("m1"));
+
+        rates.record("m1", 0, now);
+        rates.record("m2", 40, now);
+        assert!(rates.is_zero_fee("m1"));
+        assert!(!rates.is_zero_fee("m2"));
+        assert!(!rates.is_due("m1", refresh, now + Duration::minutes(59)));
+        assert!(rates.is_due("m1", refresh, now + Duration::minutes(60)));
+
+        let all = rates.all();
+        assert_eq!(all[0].market_id, "m1");
+        assert!(all[0].is_zero());
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::polymarket::FeedHealth;
+
+/// Why an asset's WS quote can't be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Staleness {
+    /// The feed is down
+    FeedDown,
+    /// The feed reconnected and the asset's book hasn't come back on the new connection yet
+    NotResynced,
+    /// The connection is up but hasn't said anything (keep-alives included) for too long
+    FeedSilent,
+    /// The asset's updates skipped a sequence number and no full book has arrived since
+    SequenceGap,
+}
+
+impl fmt::Display for Staleness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Staleness::FeedDown => "feed down",
+            Staleness::NotResynced => "not resynced since reconnect",
+            Staleness::FeedSilent => "feed silent",
+            Staleness::SequenceGap => "sequence gap",
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct AssetQuote {
+    /// Last refresh (WS book update or REST re-read), monotonic ns (see `clock`)
+    updated_ns: u64,
+    /// Highest feed sequence number seen (0 = none yet)
+    seq: u64,
+    gap: bool,
+}
+
+/// Whether each asset's quote can be trusted. A book nobody trades gets no updates, so a quiet
+/// quote isn't a stale one: a WS hiccup shows up as the feed dropping, going silent, or an
+/// asset's sequence numbers skipping. Until then the registry's yes_price / no_price are
+/// current however old; after, entries check the quotes they price off first
+pub struct QuoteFreshness {
+    /// 0 = guard off
+    max_silence_ns: u64,
+    assets: HashMap<String, AssetQuote>,
+}
+
+impl QuoteFreshness {
+    pub fn new(max_feed_silence_ms: u64) -> Self {
+        Self {
+            max_silence_ns: max_feed_silence_ms.saturating_mul(1_000_000),
+            assets: HashMap::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_silence_ns > 0
+    }
+
+    /// A WS update for the asset, read at `at_ns` with feed sequence number `seq` (0 = not
+    /// sequenced). A `snapshot` (full book) closes any gap; a change after a skipped number
+    /// opens one. Updates arriving out of order keep the latest time
+    pub fn observe(&mut self, asset_id: &str, seq: u64, snapshot: bool, at_ns: u64) {
+        if !self.assets.contains_key(asset_id) {
+            self.assets
+                .insert(asset_id.to_string(), AssetQuote::default());
+        }
+        let Some(quote) = self.assets.get_mut(asset_id) else {
+            return;
+        };
+        quote.updated_ns = quote.updated_ns.max(at_ns);
+        if seq > 0 {
+            if quote.seq > 0 && seq > quote.seq + 1 {
+                quote.gap = true;
+            }
+            quote.seq = seq;
+        }
+        if snapshot {
+            quote.gap = false;
+        }
+    }
+
+    /// The asset's book was re-read over REST at `at_ns`: its quote is current again
+    pub fn refreshed(&mut self, asset_id: &str, at_ns: u64) {
+        let quote = self.assets.entry(asset_id.to_string()).or_default();
+        quote.updated_ns = quote.updated_ns.max(at_ns);
+        quote.gap = false;
+    }
+
+    /// Age of the asset's quote in ms (None if it was never refreshed)
+    pub fn age_ms(&self, asset_id: &str, now_ns: u64) -> Option<u64> {
+        self.assets
+            .get(asset_id)
+            .map(|quote| now_ns.saturating_sub(quote.updated_ns) / 1_000_000)
+    }
+
+    /// Why the asset's quote is stale, None if it isn't. An asset never refreshed has no book
+    /// on the WS yet: its price came from the market fetch that triggered the evaluation
+    pub fn staleness(&self, asset_id: &str, feed: FeedHealth, now_ns: u64) -> Option<Staleness> {
+        if !self.enabled() {
+            return None;
+        }
+        let quote = self.assets.get(asset_id)?;
+        let Some(connected_ns) = feed.connected_ns else {
+            return Some(Staleness::FeedDown);
+        };
+        if quote.updated_ns < connected_ns {
+            return Some(Staleness::NotResynced);
+        }
+        if quote.gap {
+            return Some(Staleness::SequenceGap);
+        }
+        let heard_ns = feed
+            .last_message_ns
+            .unwrap_or(connected_ns)
+            .max(quote.updated_ns);
+        if now_ns.saturating_sub(heard_ns) > self.max_silence_ns {
+            return Some(Staleness::FeedSilent);
+        }
+        None
+    }
+
+    /// Stop tracking an asset (unsubscribed: its quote is dropped)
+    pub fn forget(&mut self, asset_id: &str) {
+        self.assets.remove(asset_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    fn feed(connected_ms: u64, last_message_ms: u64) -> FeedHealth {
+        FeedHealth {
+            connected_ns: Some(connected_ms * MS),
+            last_message_ns: Some(last_message_ms * MS),
+        }
+    }
+
+    #[test]
+    fn test_quiet_book_on_a_live_feed_is_fresh() {
+        let mut freshness = QuoteFreshness::new(500);
+        freshness.observe("yes", 1, true, 1_000 * MS);
+        // Out-of-order update doesn't move the quote back in time
+        freshness.observe("yes", 0, false, 900 * MS);
+        assert_eq!(freshness.age_ms("yes", 61_000 * MS), Some(60_000));
+
+        // A minute without changes, but the feed kept talking
+        assert_eq!(
+            freshness.staleness("yes", feed(0, 60_900), 61_000 * MS),
+            None
+        );
+        // Never seen on the WS: not judged
+        assert_eq!(freshness.staleness("no", feed(0, 0), 61_000 * MS), None);
+        assert!(!QuoteFreshness::new(0).enabled());
+    }
+
+    #[test]
+    fn test_feed_trouble_makes_quotes_stale() {
+        let mut freshness = QuoteFreshness::new(500);
+        freshness.observe("yes", 1, true, 1_000 * MS);
+
+        assert_eq!(
+            freshness.staleness("yes", FeedHealth::default(), 1_100 * MS),
+            Some(Staleness::FeedDown)
+        );
+        assert_eq!(
+            freshness.staleness("yes", feed(1_050, 1_050), 1_100 * MS),
+            Some(Staleness::NotResynced)
+        );
+        assert_eq!(
+            freshness.staleness("yes", feed(0, 1_000), 1_501 * MS),
+            Some(Staleness::FeedSilent)
+        );
+
+        // The book coming back on the new connection, or a REST re-read, clears it
+        freshness.observe("yes", 2, true, 1_060 * MS);
+        assert_eq!(
+            freshness.staleness("yes", feed(1_050, 1_060), 1_100 * MS),
+            None
+        );
+        freshness.forget("yes");
+        freshness.refreshed("yes", 1_200 * MS);
+        assert_eq!(
+            freshness.staleness("yes", feed(1_050, 1_200), 1_300 * MS),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sequence_gap_holds_until_full_book() {
+        let mut freshness = QuoteFreshness::new(500);
+        let live = feed(0, 1_000);
+        freshness.observe("yes", 1, true, 900 * MS);
+        freshness.observe("yes", 2, false, 950 * MS);
+        assert_eq!(freshness.staleness("yes", live, 1_000 * MS), None);
+
+        // Update 3 never arrived
+        freshness.observe("yes", 4, false, 960 * MS);
+        assert_eq!(
+            freshness.staleness("yes", live, 1_000 * MS),
+            Some(Staleness::SequenceGap)
+        );
+        freshness.observe("yes", 5, false, 970 * MS);
+        assert_eq!(
+            freshness.staleness("yes", live, 1_000 * MS),
+            Some(Staleness::SequenceGap)
+        );
+        freshness.observe("yes", 6, true, 980 * MS);
+        assert_eq!(freshness.staleness("yes", live, 1_000 * MS), None);
+    }
+}
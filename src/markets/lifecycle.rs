@@ -0,0 +1,317 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tracing::debug;
+use utoipa::ToSchema;
+
+use crate::markets::condition::normalize_market_id;
+
+/// Where a market is on its way from on-chain creation to redemption
+/// Ordered: a market only moves forward, except Positioned -> Tradable when a position is closed
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+pub enum MarketState {
+    /// ConditionPreparation seen on-chain
+    DetectedOnChain,
+    /// Token ids computed locally from the condition id
+    DerivedAssets,
+    /// CLOB book subscription sent for its assets
+    Subscribed,
+    /// Gamma returned question / end date / volume
+    MetadataLoaded,
+    /// Metadata plus a live price: strategies can act on it
+    Tradable,
+    /// We hold a position
+    Positioned,
+    /// Condition resolved on-chain
+    Resolved,
+    /// Winnings redeemed
+    Redeemed,
+}
+
+impl MarketState {
+    pub fn has_metadata(self) -> bool {
+        self >= MarketState::MetadataLoaded
+    }
+
+    /// No more trading once the outcome is known
+    pub fn is_closed(self) -> bool {
+        self >= MarketState::Resolved
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StateTransition {
+    pub state: MarketState,
+    pub at: DateTime<Utc>,
+}
+
+/// One market's state plus its transition history
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarketLifecycle {
+    pub market_id: String,
+    pub state: MarketState,
+    pub transitions: Vec<StateTransition>,
+    /// Gamma metadata fetches attempted so far
+    pub metadata_attempts: u8,
+    /// Book suspended or one-sided since (see SuspensionTracker)
+    #[serde(default)]
+    pub suspended_since: Option<DateTime<Utc>>,
+}
+
+impl MarketLifecycle {
+    fn new(market_id: &str, state: MarketState) -> Self {
+        Self {
+            market_id: market_id.to_string(),
+            state,
+            transitions: vec![StateTransition {
+                state,
+                at: Utc::now(),
+            }],
+            metadata_attempts: 0,
+            suspended_since: None,
+        }
+    }
+
+    fn set(&mut self, state: MarketState) {
+        self.state = state;
+        self.transitions.push(StateTransition {
+            state,
+            at: Utc::now(),
+        });
+    }
+
+    /// When the market entered a state (latest entry)
+    pub fn entered_at(&self, state: MarketState) -> Option<DateTime<Utc>> {
+        self.transitions
+            .iter()
+            .rev()
+            .find(|t| t.state == state)
+            .map(|t| t.at)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct LifecycleSummary {
+    pub markets: usize,
+    pub by_state: BTreeMap<MarketState, usize>,
+    /// Markets queued for another Gamma metadata fetch
+    pub pending_metadata_retries: usize,
+    /// Markets whose book is currently suspended or one-sided
+    #[serde(default)]
+    pub suspended: usize,
+}
+
+/// Per-market state machine driving what the Sniper does next with each market
+#[derive(Default)]
+pub struct LifecycleTracker {
+    markets: HashMap<String, MarketLifecycle>,
+    metadata_retries: VecDeque<String>,
+}
+
+impl LifecycleTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self, market_id: &str) -> Option<MarketState> {
+        self.get(market_id).map(|m| m.state)
+    }
+
+    pub fn get(&self, market_id: &str) -> Option<&MarketLifecycle> {
+        self.markets.get(normalize_market_id(market_id).as_ref())
+    }
+
+    pub fn has_metadata(&self, market_id: &str) -> bool {
+        self.state(market_id).is_some_and(|s| s.has_metadata())
+    }
+
+    /// Markets that made it to MetadataLoaded or beyond
+    pub fn with_metadata(&self) -> usize {
+        self.markets
+            .values()
+            .filter(|m| m.state.has_metadata())
+            .count()
+    }
+
+    /// Move a market forward (unknown markets enter directly at `to`)
+    /// Returns false when the market is already at or past `to`
+    pub fn advance(&mut self, market_id: &str, to: MarketState) -> bool {
+        let key = normalize_market_id(market_id);
+        let market_id = key.as_ref();
+        match self.markets.get_mut(market_id) {
+            None => {
+                self.markets
+                    .insert(market_id.to_string(), MarketLifecycle::new(market_id, to));
+                debug!("🧭 {} entered {:?}", market_id, to);
+                true
+            }
+            Some(m) if to > m.state => {
+                debug!("🧭 {} {:?} -> {:?}", market_id, m.state, to);
+                m.set(to);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Our last position in the market was closed: back to Tradable
+    pub fn position_closed(&mut self, market_id: &str) -> bool {
+        let key = normalize_market_id(market_id);
+        let market_id = key.as_ref();
+        match self.markets.get_mut(market_id) {
+            Some(m) if m.state == MarketState::Positioned => {
+                debug!("🧭 {} Positioned -> Tradable", market_id);
+                m.set(MarketState::Tradable);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Flag or clear a book suspension. Orthogonal to the state: a market stays Tradable
+    /// or Positioned while suspended
+    pub fn set_suspended(&mut self, market_id: &str, since: Option<DateTime<Utc>>) {
+        let key = normalize_market_id(market_id);
+        let market_id = key.as_ref();
+        if let Some(m) = self.markets.get_mut(market_id) {
+            m.suspended_since = since;
+        }
+    }
+
+    /// Queue another Gamma metadata fetch for a market still missing metadata
+    pub fn queue_metadata_retry(&mut self, market_id: &str) {
+        let key = normalize_market_id(market_id);
+        let market_id = key.as_ref();
+        if let Some(m) = self.markets.get_mut(market_id) {
+            if !m.state.has_metadata() {
+                m.metadata_attempts = m.metadata_attempts.saturating_add(1);
+                if !self.metadata_retries.iter().any(|id| id == market_id) {
+                    self.metadata_retries.push_back(market_id.to_string());
+                }
+            }
+        }
+    }
+
+    /// Drop a market from the metadata retry queue (fetches already in flight still report)
+    pub fn cancel_metadata_retry(&mut self, market_id: &str) {
+        let key = normalize_market_id(market_id);
+        self.metadata_retries.retain(|id| id != key.as_ref());
+    }
+
+    /// Next queued metadata fetches as (market_id, attempt), skipping markets loaded meanwhile
+    pub fn next_metadata_retries(&mut self, max: usize) -> Vec<(String, u8)> {
+        let mut batch = Vec::new();
+        while batch.len() < max {
+            let Some(market_id) = self.metadata_retries.pop_front() else {
+                break;
+            };
+            if let Some(m) = self.markets.get(&market_id) {
+                if !m.state.has_metadata() {
+                    batch.push((market_id, m.metadata_attempts));
+                }
+            }
+        }
+        batch
+    }
+
+    pub fn summary(&self) -> LifecycleSummary {
+        let mut by_state = BTreeMap::new();
+        for m in self.markets.values() {
+            *by_state.entry(m.state).or_insert(0) += 1;
+        }
+        LifecycleSummary {
+            markets: self.markets.len(),
+            by_state,
+            pending_metadata_retries: self.metadata_retries.len(),
+            suspended: self
+                .markets
+                .values()
+                .filter(|m| m.suspended_since.is_some())
+                .count(),
+        }
+    }
+
+    /// Every tracked market (state snapshots)
+    pub fn markets(&self) -> Vec<MarketLifecycle> {
+        self.markets.values().cloned().collect()
+    }
+
+    /// Queued metadata fetches, next first (state snapshots)
+    pub fn metadata_retry_queue(&self) -> Vec<String> {
+        self.metadata_retries.iter().cloned().collect()
+    }
+
+    /// Resume from a state snapshot: its markets replace ours, redeemed ones are dropped
+    pub fn restore(&mut self, markets: Vec<MarketLifecycle>, metadata_retries: Vec<String>) {
+        for mut market in markets {
+            market.market_id = normalize_market_id(&market.market_id).into_owned();
+            if market.state == MarketState::Redeemed {
+                self.markets.remove(&market.market_id);
+            } else {
+                self.markets.insert(market.market_id.clone(), market);
+            }
+        }
+        self.metadata_retries = metadata_retries
+            .iter()
+            .map(|id| normalize_market_id(id).into_owned())
+            .collect();
+    }
+
+    /// Markets currently in a given state
+    pub fn in_state(&self, state: MarketState) -> Vec<MarketLifecycle> {
+        self.markets
+            .values()
+            .filter(|m| m.state == state)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_only_transitions() {
+        let mut tracker = LifecycleTracker::new();
+        assert!(tracker.advance("m", MarketState::DetectedOnChain));
+        assert!(tracker.advance("m", MarketState::Subscribed));
+        assert!(!tracker.advance("m", MarketState::DerivedAssets));
+        assert!(tracker.advance("m", MarketState::Positioned));
+        assert!(!tracker.advance("m", MarketState::Tradable));
+        assert!(tracker.position_closed("m"));
+
+        let m = tracker.get("m").unwrap();
+        assert_eq!(m.state, MarketState::Tradable);
+        assert_eq!(m.transitions.len(), 4);
+        assert!(m.entered_at(MarketState::Positioned).is_some());
+    }
+
+    #[test]
+    fn test_metadata_retries_skip_loaded_markets() {
+        let mut tracker = LifecycleTracker::new();
+        tracker.advance("a", MarketState::Subscribed);
+        tracker.advance("b", MarketState::Subscribed);
+        tracker.queue_metadata_retry("a");
+        tracker.queue_metadata_retry("b");
+        tracker.queue_metadata_retry("b");
+        tracker.advance("a", MarketState::MetadataLoaded);
+
+        let batch = tracker.next_metadata_retries(10);
+        assert_eq!(batch, vec![("b".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_condition_id_spellings_share_a_lifecycle() {
+        let id = "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1";
+        let mut tracker = LifecycleTracker::new();
+        // Seen on-chain, then again through Gamma spelled in upper case
+        assert!(tracker.advance(id, MarketState::DetectedOnChain));
+        assert!(!tracker.advance(&id.to_uppercase(), MarketState::DetectedOnChain));
+        tracker.queue_metadata_retry(&id[2..]);
+        assert_eq!(tracker.next_metadata_retries(10), vec![(id.to_string(), 1)]);
+        assert_eq!(tracker.summary().markets, 1);
+    }
+}
@@ -0,0 +1,37 @@
+pub mod category;
+pub mod condition;
+pub mod depth;
+pub mod expirations;
+pub mod features;
+pub mod fees;
+pub mod freshness;
+pub mod lifecycle;
+pub mod pins;
+pub mod question;
+pub mod registry;
+pub mod series;
+pub mod subscriptions;
+pub mod suspension;
+pub mod synthetic;
+pub mod toxicity;
+pub mod whales;
+pub mod zombies;
+
+pub use category::{LlmClassifier, MarketCategory};
+pub use condition::{normalize_market_id, ConditionId};
+pub use depth::AskLadders;
+pub use expirations::{ExpirationEntry, ExpirationWatchlist};
+pub use features::{FeatureFrame, FeatureTracker, WindowFeatures};
+pub use fees::{FeeRates, MarketFee};
+pub use freshness::{QuoteFreshness, Staleness};
+pub use lifecycle::{LifecycleSummary, LifecycleTracker, MarketLifecycle, MarketState};
+pub use pins::{lookup_market, Pin, PinKey, PinRequest, PinnedMarkets};
+pub use question::{Comparator, QuestionSpec, QuestionTimezone, Underlying};
+pub use registry::{AssetRef, MarketRegistry};
+pub use series::{NewSeries, SeriesWatcher};
+pub use subscriptions::{SubscriptionSet, SubscriptionStats};
+pub use suspension::{BookShape, QuoteStatus, SuspensionEvent, SuspensionTracker};
+pub use synthetic::SyntheticMarkets;
+pub use toxicity::{MarketToxicity, PostContext, ToxicityTracker};
+pub use whales::{LargeTrade, WhaleStats, WhaleTracker};
+pub use zombies::{DormantMarket, ResolutionProposals, ZombieEvent, ZombieTracker};
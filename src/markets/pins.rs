@@ -0,0 +1,372 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+use crate::markets::ConditionId;
+use crate::polymarket::{MarketData, MarketInterface};
+
+pub const PINNED_MARKETS_FILE: &str = "pinned_markets.json";
+
+/// Body of `POST /api/watchlist`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PinRequest {
+    /// Condition id (0x...), market slug or polymarket.com market URL
+    pub market: String,
+    /// Log price moves of the market as alerts
+    #[serde(default)]
+    pub notify: bool,
+    /// Why it is pinned, e.g. "FOMC 2026-10-28"
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Trade it whatever the market filters say (volume, liquidity, spread, ...)
+    #[serde(default)]
+    pub bypass_filters: bool,
+}
+
+/// A market an operator pinned for aggressive tracking
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Pin {
+    /// Condition id or slug, as pinned
+    pub key: String,
+    /// Condition id, once the pin has been resolved to a market
+    pub market_id: Option<String>,
+    pub question: Option<String>,
+    pub notify: bool,
+    pub note: Option<String>,
+    pub pinned_at: DateTime<Utc>,
+    /// YES price of the last notification
+    #[serde(default)]
+    pub notified_price: Option<f64>,
+    /// Pinned by a watcher rule (a new event series), not by the operator: never bypasses the
+    /// market filters
+    #[serde(default)]
+    pub automatic: bool,
+    /// Traded whatever the market filters say; pins are traded within them otherwise
+    #[serde(default)]
+    pub bypass_filters: bool,
+}
+
+/// How a pin is looked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinKey {
+    ConditionId(ConditionId),
+    Slug(String),
+}
+
+impl PinKey {
+    /// A 0x-prefixed 32-byte hex string is a condition id; anything else a slug (the last path
+    /// segment of a URL)
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim().trim_end_matches('/');
+        let input = input.split(['?', '#']).next().unwrap_or_default();
+        let last = input.rsplit('/').next().unwrap_or_default();
+        if last.is_empty() {
+            return None;
+        }
+        Some(match ConditionId::parse(last) {
+            Ok(id) if last.starts_with("0x") => PinKey::ConditionId(id),
+            _ => PinKey::Slug(last.to_lowercase()),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            PinKey::ConditionId(id) => id.as_str(),
+            PinKey::Slug(slug) => slug,
+        }
+    }
+
+    /// Fetch the market: by condition id from the CLOB, by slug from Gamma
+    pub async fn fetch(&self, market: &dyn MarketInterface) -> Result<MarketData> {
+        match self {
+            PinKey::ConditionId(id) => market.get_market_details(id.as_str()).await,
+            PinKey::Slug(slug) => market.get_market_by_slug(slug).await,
+        }
+    }
+}
+
+/// Resolve what a user pasted (condition id, market slug or polymarket.com URL) to the market,
+/// with its condition id and token ids
+pub async fn lookup_market(market: &dyn MarketInterface, input: &str) -> Result<MarketData> {
+    let key = PinKey::parse(input)
+        .with_context(|| format!("Not a condition id, slug or URL: {}", input))?;
+    key.fetch(market)
+        .await
+        .with_context(|| format!("Market {} not found", key.as_str()))
+}
+
+/// Markets pinned through the watchlist API. They keep their WS subscriptions and are re-fetched and re-evaluated every `PINNED_POLL_INTERVAL_MS`.
+/// Persisted to `data_dir/pinned_markets.json` so pins survive restarts
+#[derive(Debug, Default)]
+pub struct PinnedMarkets {
+    /// key -> pin
+    pins: BTreeMap<String, Pin>,
+    path: Option<PathBuf>,
+}
+
+impl PinnedMarkets {
+    /// Pins of the previous run are restored when a data dir is given
+    pub fn new(data_dir: Option<&str>) -> Self {
+        let mut pinned = data_dir.map(Self::load).unwrap_or_default();
+        pinned.path = data_dir.map(pinned_markets_path);
+        pinned
+    }
+
+    /// In-memory copy of the pins in `data_dir` (observer mode; nothing is written back)
+    pub fn load(data_dir: &str) -> Self {
+        let path = pinned_markets_path(data_dir);
+        let pins = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str::<Vec<Pin>>(&raw).unwrap_or_else(|e| {
+                warn!("⚠️ Invalid pinned markets file {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(e) => {
+                debug!("No pinned markets loaded ({})", e);
+                Vec::new()
+            }
+        };
+        Self {
+            pins: pins.into_iter().map(|pin| (pin.key.clone(), pin)).collect(),
+            path: None,
+        }
+    }
+
+    /// Pin a market, or update the notify flag / note of an existing pin
+    pub fn pin(&mut self, request: PinRequest) -> Result<Pin> {
+        self.insert(request, false)
+    }
+
+    /// Pin a market for a watcher rule. An operator's pin of the same market is left as it is
+    pub fn pin_automatic(&mut self, request: PinRequest) -> Result<Pin> {
+        if let Some(pin) = PinKey::parse(&request.market)
+            .and_then(|key| self.pins.get(key.as_str()))
+            .filter(|pin| !pin.automatic)
+        {
+            return Ok(pin.clone());
+        }
+        self.insert(request, true)
+    }
+
+    fn insert(&mut self, request: PinRequest, automatic: bool) -> Result<Pin> {
+        let key = PinKey::parse(&request.market)
+            .with_context(|| format!("Not a condition id, slug or URL: {}", request.market))?;
+        let pin = self
+            .pins
+            .entry(key.as_str().to_string())
+            .and_modify(|pin| {
+                pin.notify = request.notify;
+                pin.note = request.note.clone();
+                pin.automatic = automatic;
+                pin.bypass_filters = request.bypass_filters && !automatic;
+            })
+            .or_insert_with(|| Pin {
+                key: key.as_str().to_string(),
+                market_id: match &key {
+                    PinKey::ConditionId(id) => Some(id.as_str().to_string()),
+                    PinKey::Slug(_) => None,
+                },
+                question: None,
+                notify: request.notify,
+                note: request.note,
+                pinned_at: Utc::now(),
+                notified_price: None,
+                automatic,
+                bypass_filters: request.bypass_filters && !automatic,
+            })
+            .clone();
+        self.save()?;
+        Ok(pin)
+    }
+
+    /// Remove a pin by its key or market id
+    pub fn unpin(&mut self, key: &str) -> Result<Option<Pin>> {
+        let key = PinKey::parse(key).map(|k| k.as_str().to_string());
+        let found = self
+            .pins
+            .values()
+            .find(|pin| Some(&pin.key) == key.as_ref() || pin.market_id == key)
+            .map(|pin| pin.key.clone());
+        let removed = found.and_then(|key| self.pins.remove(&key));
+        if removed.is_some() {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub fn pins(&self) -> Vec<Pin> {
+        self.pins.values().cloned().collect()
+    }
+
+    /// Condition ids of the pins resolved to a market
+    pub fn market_ids(&self) -> HashSet<String> {
+        self.pins
+            .values()
+            .filter_map(|pin| pin.market_id.clone())
+            .collect()
+    }
+
+    pub fn contains(&self, market_id: &str) -> bool {
+        self.pins
+            .values()
+            .any(|pin| pin.market_id.as_deref() == Some(market_id))
+    }
+
+    /// Pinned by the operator with `bypass_filters`: the market filters don't apply
+    pub fn bypasses_filters(&self, market_id: &str) -> bool {
+        self.pins
+            .values()
+            .any(|pin| pin.bypass_filters && pin.market_id.as_deref() == Some(market_id))
+    }
+
+    /// Record the market a pin resolved to; true the first time
+    pub fn resolve(&mut self, key: &str, market: &MarketData) -> bool {
+        let Some(pin) = self.pins.get_mut(key) else {
+            return false;
+        };
+        let first = pin.question.is_none();
+        pin.market_id = Some(market.id.to_string());
+        pin.question = Some(market.question.clone());
+        if first {
+            if let Err(e) = self.save() {
+                warn!("⚠️ Failed to persist pinned markets: {}", e);
+            }
+        }
+        first
+    }
+
+    /// For notifying pins: the previously notified YES price when the price moved at least
+    /// `threshold` since (the first price seen is only recorded)
+    pub fn price_move(&mut self, market_id: &str, yes_price: f64, threshold: f64) -> Option<f64> {
+        if yes_price <= 0.0 {
+            return None;
+        }
+        let pin = self
+            .pins
+            .values_mut()
+            .find(|pin| pin.notify && pin.market_id.as_deref() == Some(market_id))?;
+        match pin.notified_price {
+            Some(previous) if (yes_price - previous).abs() < threshold => None,
+            previous => {
+                pin.notified_price = Some(yes_price);
+                previous
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.pins())?)
+            .with_context(|| format!("Failed to write {:?}", tmp))?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+}
+
+/// Path of the pinned markets inside a data dir
+pub fn pinned_markets_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(PINNED_MARKETS_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONDITION: &str = "0xAB00000000000000000000000000000000000000000000000000000000000001";
+
+    fn request(market: &str, notify: bool) -> PinRequest {
+        PinRequest {
+            market: market.to_string(),
+            notify,
+            note: None,
+            bypass_filters: false,
+        }
+    }
+
+    #[test]
+    fn test_pins_resolve_notify_and_persist() {
+        assert_eq!(
+            PinKey::parse("https://polymarket.com/event/fed-october/fed-cuts-rates?tid=1"),
+            Some(PinKey::Slug("fed-cuts-rates".to_string()))
+        );
+        assert_eq!(
+            PinKey::parse(CONDITION),
+            Some(PinKey::ConditionId(ConditionId::parse(CONDITION).unwrap()))
+        );
+        assert_eq!(PinKey::parse(" / "), None);
+
+        let dir = std::env::temp_dir().join(format!("pins-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let mut pins = PinnedMarkets::new(Some(data_dir));
+        let by_id = pins.pin(request(CONDITION, false)).unwrap();
+        assert_eq!(
+            by_id.market_id.as_deref(),
+            Some(CONDITION.to_lowercase().as_str())
+        );
+        let by_slug = pins.pin(request("fed-cuts-rates", true)).unwrap();
+        assert_eq!(by_slug.market_id, None);
+        assert!(pins.pin(request("", true)).is_err());
+
+        let market = MarketData {
+            id: "0xfed".into(),
+            question: "Fed cuts rates?".to_string(),
+            ..Default::default()
+        };
+        assert!(pins.resolve("fed-cuts-rates", &market));
+        assert!(!pins.resolve("fed-cuts-rates", &market));
+        assert!(pins.contains("0xfed"));
+
+        // Notifying pins report moves of at least the threshold
+        assert_eq!(pins.price_move("0xfed", 0.40, 0.05), None);
+        assert_eq!(pins.price_move("0xfed", 0.43, 0.05), None);
+        assert_eq!(pins.price_move("0xfed", 0.46, 0.05), Some(0.40));
+        assert_eq!(pins.price_move(&CONDITION.to_lowercase(), 0.90, 0.05), None);
+
+        // Restored on the next run
+        let restored = PinnedMarkets::new(Some(data_dir));
+        assert_eq!(restored.pins().len(), 2);
+        assert!(restored.contains("0xfed"));
+
+        assert!(pins.unpin("0xfed").unwrap().is_some());
+        assert!(pins.unpin("fed-cuts-rates").unwrap().is_none());
+        assert_eq!(PinnedMarkets::load(data_dir).pins().len(), 1);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_only_operator_pins_asking_for_it_bypass_filters() {
+        let mut pins = PinnedMarkets::new(None);
+        let id = CONDITION.to_lowercase();
+        let bypassing = PinRequest {
+            bypass_filters: true,
+            ..request(CONDITION, false)
+        };
+        pins.pin_automatic(bypassing.clone()).unwrap();
+        assert!(pins.contains(&id));
+        assert!(!pins.bypasses_filters(&id));
+
+        // An operator's pin takes over, within the filters unless it asks otherwise
+        pins.pin(request(CONDITION, true)).unwrap();
+        assert!(!pins.bypasses_filters(&id));
+        pins.pin(bypassing).unwrap();
+        assert!(pins.bypasses_filters(&id));
+
+        // A later rule match leaves it alone
+        assert!(
+            !pins
+                .pin_automatic(request(CONDITION, false))
+                .unwrap()
+                .automatic
+        );
+        assert!(pins.bypasses_filters(&id));
+    }
+}
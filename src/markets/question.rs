@@ -0,0 +1,521 @@
+use chrono::NaiveTime;
+
+/// Asset a price question is about, with the Binance pair it is priced off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Underlying {
+    pub ticker: &'static str,
+    pub binance_symbol: &'static str,
+}
+
+const fn underlying(ticker: &'static str, binance_symbol: &'static str) -> Underlying {
+    Underlying {
+        ticker,
+        binance_symbol,
+    }
+}
+
+// Whole words / phrases. Order matters: "Bitcoin Cash" before Bitcoin, ETH before BTC
+// (for "ETH flip BTC")
+const UNDERLYINGS: [(&[&str], Underlying); 17] = [
+    (&["bitcoin cash", "bch"], underlying("BCH", "BCHUSDT")),
+    (&["dogecoin", "doge"], underlying("DOGE", "DOGEUSDT")),
+    (&["shiba inu", "shib"], underlying("SHIB", "SHIBUSDT")),
+    (&["cardano", "ada"], underlying("ADA", "ADAUSDT")),
+    (&["xrp", "ripple"], underlying("XRP", "XRPUSDT")),
+    (&["solana", "sol"], underlying("SOL", "SOLUSDT")),
+    (&["bnb", "binance coin"], underlying("BNB", "BNBUSDT")),
+    (&["ethereum", "eth", "ether"], underlying("ETH", "ETHUSDT")),
+    (&["bitcoin", "btc"], underlying("BTC", "BTCUSDT")),
+    (&["avalanche", "avax"], underlying("AVAX", "AVAXUSDT")),
+    (&["polygon", "matic"], underlying("MATIC", "MATICUSDT")),
+    (&["polkadot", "dot"], underlying("DOT", "DOTUSDT")),
+    (&["tron", "trx"], underlying("TRX", "TRXUSDT")),
+    (&["litecoin", "ltc"], underlying("LTC", "LTCUSDT")),
+    (&["chainlink", "link"], underlying("LINK", "LINKUSDT")),
+    (&["near protocol", "near"], underlying("NEAR", "NEARUSDT")),
+    (&["uniswap", "uni"], underlying("UNI", "UNIUSDT")),
+];
+
+/// How the price is compared to the strike(s) for YES
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparator {
+    /// Above the strike at the deadline
+    Above,
+    /// Below the strike at the deadline
+    Below,
+    /// Inside the range at the deadline
+    Between,
+    /// Trades at or above the strike at any point before the deadline
+    Reach,
+    /// Trades at or below the strike at any point before the deadline
+    Dip,
+    /// Closes above where it opened ("Bitcoin Up or Down"), no strike
+    UpOrDown,
+}
+
+// Phrases, longest first within a comparator
+const COMPARATORS: [(&str, Comparator); 18] = [
+    ("up or down", Comparator::UpOrDown),
+    ("greater than", Comparator::Above),
+    ("higher than", Comparator::Above),
+    ("more than", Comparator::Above),
+    ("less than", Comparator::Below),
+    ("lower than", Comparator::Below),
+    ("between", Comparator::Between),
+    ("above", Comparator::Above),
+    ("over", Comparator::Above),
+    ("exceed", Comparator::Above),
+    ("exceeds", Comparator::Above),
+    ("below", Comparator::Below),
+    ("under", Comparator::Below),
+    ("reach", Comparator::Reach),
+    ("reaches", Comparator::Reach),
+    ("hit", Comparator::Reach),
+    ("hits", Comparator::Reach),
+    ("dip", Comparator::Dip),
+];
+
+/// Time zone a question's deadline is given in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuestionTimezone {
+    /// New York time (ET / EST / EDT)
+    Eastern,
+    Utc,
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// What a market question asks, parsed once instead of by per-strategy string matching:
+/// "Bitcoin above $65,500 on June 13 at 5 PM ET?" is BTC, Above, [65500], June 13, 17:00, ET
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuestionSpec {
+    pub underlying: Option<Underlying>,
+    pub comparator: Option<Comparator>,
+    /// One strike, or the bounds of a `Between` range (ascending)
+    pub strikes: Vec<f64>,
+    /// Month (1-12) and day named in the question ("on June 13"); the year comes from the
+    /// market's end date
+    pub deadline_date: Option<(u32, u32)>,
+    /// Wall-clock time named in the question ("at 5 PM ET", "by 11:59 PM ET")
+    pub deadline_time: Option<NaiveTime>,
+    /// Zone of `deadline_time`
+    pub timezone: Option<QuestionTimezone>,
+}
+
+impl QuestionSpec {
+    pub fn parse(question: &str) -> Self {
+        let tokens = tokenize(question);
+        let text = format!(" {} ", tokens.join(" "));
+        let has = |phrase: &str| text.contains(&format!(" {} ", phrase));
+
+        let underlying = UNDERLYINGS
+            .iter()
+            .find(|(aliases, _)| aliases.iter().any(|alias| has(alias)))
+            .map(|(_, underlying)| *underlying);
+
+        // The first comparator in the question, and the token after it
+        let comparator = COMPARATORS
+            .iter()
+            .filter_map(|(phrase, comparator)| {
+                let words: Vec<&str> = phrase.split(' ').collect();
+                let at = tokens
+                    .windows(words.len())
+                    .position(|window| window == words.as_slice())?;
+                Some((at, at + words.len(), *comparator))
+            })
+            .min_by_key(|(at, _, _)| *at);
+
+        let mut spec = QuestionSpec {
+            underlying,
+            comparator: comparator.map(|(_, _, c)| c),
+            ..Default::default()
+        };
+        if let Some((_, after, comparator)) = comparator {
+            spec.strikes = strikes(&tokens[after..], comparator);
+        }
+        spec.parse_deadline(&tokens);
+        spec
+    }
+
+    /// The strike of an Above / Below / Reach / Dip question
+    pub fn strike(&self) -> Option<f64> {
+        match (self.comparator?, self.strikes.as_slice()) {
+            (Comparator::Between | Comparator::UpOrDown, _) => None,
+            (_, [strike]) => Some(*strike),
+            _ => None,
+        }
+    }
+
+    /// A price of `underlying` above or below one strike at the deadline: the questions priced
+    /// off an external spot price
+    pub fn is_terminal_strike(&self) -> bool {
+        self.underlying.is_some()
+            && matches!(self.comparator, Some(Comparator::Above | Comparator::Below))
+            && self.strike().is_some()
+    }
+
+    fn parse_deadline(&mut self, tokens: &[String]) {
+        for (i, token) in tokens.iter().enumerate() {
+            let next = tokens.get(i + 1).map(String::as_str);
+            if self.deadline_date.is_none() {
+                if let Some(month) = month_number(token) {
+                    if let Some(day) = next.and_then(day_of_month) {
+                        self.deadline_date = Some((month, day));
+                    }
+                }
+            }
+            if self.deadline_time.is_none() {
+                if let Some((time, zone_at)) = clock_time(tokens, i) {
+                    self.deadline_time = Some(time);
+                    self.timezone = tokens.get(zone_at).map(String::as_str).and_then(timezone);
+                }
+            }
+        }
+    }
+}
+
+/// Lowercased words, keeping the characters of amounts and clock times ("$65,500.00", "5:30pm")
+fn tokenize(question: &str) -> Vec<String> {
+    question
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || matches!(c, '?' | '!' | '(' | ')' | '"' | '/'))
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && c != '$')
+                .to_string()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// "$65,500.00" / "100k" / "$1.5m" -> (amount, had a $)
+fn amount(token: &str) -> Option<(f64, bool)> {
+    let (token, dollar) = match token.strip_prefix('$') {
+        Some(rest) => (rest, true),
+        None => (token, false),
+    };
+    if token.contains(':') || !token.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let token = token.replace(',', "");
+    let (digits, multiplier) = match token.chars().last()? {
+        'k' => (&token[..token.len() - 1], 1e3),
+        'm' => (&token[..token.len() - 1], 1e6),
+        'b' => (&token[..token.len() - 1], 1e9),
+        _ => (token.as_str(), 1.0),
+    };
+    let value = digits.parse::<f64>().ok()? * multiplier;
+    (value > 0.0).then_some((value, dollar))
+}
+
+/// Strikes named after the comparator. Dollar amounts win over bare numbers, which skip dates
+/// ("June 13") and times ("5 PM")
+fn strikes(tokens: &[String], comparator: Comparator) -> Vec<f64> {
+    if comparator == Comparator::UpOrDown {
+        return Vec::new();
+    }
+    let amounts: Vec<(f64, bool)> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            let after_month = i
+                .checked_sub(1)
+                .is_some_and(|prev| month_number(&tokens[prev]).is_some());
+            let before_meridiem = tokens
+                .get(i + 1)
+                .is_some_and(|next| matches!(next.as_str(), "am" | "pm" | "et" | "utc"));
+            !after_month && !before_meridiem
+        })
+        .filter_map(|(_, token)| amount(token))
+        .collect();
+    let dollars = amounts.iter().any(|(_, dollar)| *dollar);
+    let mut strikes: Vec<f64> = amounts
+        .into_iter()
+        .filter(|(_, dollar)| *dollar || !dollars)
+        .map(|(value, _)| value)
+        .take(if comparator == Comparator::Between {
+            2
+        } else {
+            1
+        })
+        .collect();
+    if comparator == Comparator::Between && strikes.len() < 2 {
+        return Vec::new();
+    }
+    strikes.sort_by(|a, b| a.total_cmp(b));
+    strikes
+}
+
+/// "june" / "jun" / "sept" -> 6 / 6 / 9
+fn month_number(token: &str) -> Option<u32> {
+    MONTHS
+        .iter()
+        .position(|month| {
+            *month == token || (token.len() >= 3 && token.len() <= 4 && month.starts_with(token))
+        })
+        .map(|i| i as u32 + 1)
+}
+
+/// "13" / "13th" -> 13
+fn day_of_month(token: &str) -> Option<u32> {
+    let digits = token.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let day: u32 = digits.parse().ok()?;
+    (1..=31).contains(&day).then_some(day)
+}
+
+/// A time starting at token `i`, with the index of the token that may carry its zone.
+/// "5pm", "5 pm", "5:30 PM", "noon", "17:00 UTC"
+fn clock_time(tokens: &[String], i: usize) -> Option<(NaiveTime, usize)> {
+    let token = tokens[i].as_str();
+    if token == "noon" {
+        return Some((NaiveTime::from_hms_opt(12, 0, 0)?, i + 1));
+    }
+    if token == "midnight" {
+        return Some((NaiveTime::from_hms_opt(0, 0, 0)?, i + 1));
+    }
+    let (clock, meridiem, zone_at) = if let Some(clock) = token.strip_suffix("am") {
+        (clock, Some("am"), i + 1)
+    } else if let Some(clock) = token.strip_suffix("pm") {
+        (clock, Some("pm"), i + 1)
+    } else {
+        match tokens.get(i + 1).map(String::as_str) {
+            Some(m @ ("am" | "pm")) => (token, Some(m), i + 2),
+            // 24-hour clock with a zone: "17:00 UTC"
+            Some(zone) if token.contains(':') && timezone(zone).is_some() => (token, None, i + 1),
+            _ => return None,
+        }
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match (hour, meridiem) {
+        (1..=11, Some("pm")) => hour + 12,
+        (12, Some("am")) => 0,
+        (1..=12, Some(_)) | (0..=23, None) => hour,
+        _ => return None,
+    };
+    Some((NaiveTime::from_hms_opt(hour, minute, 0)?, zone_at))
+}
+
+fn timezone(token: &str) -> Option<QuestionTimezone> {
+    match token {
+        "et" | "est" | "edt" => Some(QuestionTimezone::Eastern),
+        "utc" | "gmt" => Some(QuestionTimezone::Utc),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (question, ticker, comparator, strikes)
+    type Case = (
+        &'static str,
+        Option<&'static str>,
+        Option<Comparator>,
+        &'static [f64],
+    );
+
+    fn time(hour: u32, minute: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(hour, minute, 0)
+    }
+
+    #[test]
+    fn test_question_corpus() {
+        use Comparator::*;
+        let cases: [Case; 24] = [
+            (
+                "Bitcoin above $65,500.00 at 5:00 PM ET?",
+                Some("BTC"),
+                Some(Above),
+                &[65_500.0],
+            ),
+            (
+                "Will the price of Bitcoin be above $100,000 on June 13?",
+                Some("BTC"),
+                Some(Above),
+                &[100_000.0],
+            ),
+            (
+                "Bitcoin greater than 65500.00?",
+                Some("BTC"),
+                Some(Above),
+                &[65_500.0],
+            ),
+            (
+                "Ethereum above 3,800 on June 13?",
+                Some("ETH"),
+                Some(Above),
+                &[3_800.0],
+            ),
+            (
+                "Will ETH be below $2,500 on December 31?",
+                Some("ETH"),
+                Some(Below),
+                &[2_500.0],
+            ),
+            (
+                "Will the price of Solana be less than $140 on March 9?",
+                Some("SOL"),
+                Some(Below),
+                &[140.0],
+            ),
+            (
+                "Will Bitcoin reach $150k by December 31?",
+                Some("BTC"),
+                Some(Reach),
+                &[150_000.0],
+            ),
+            (
+                "Will Bitcoin hit $1m in 2025?",
+                Some("BTC"),
+                Some(Reach),
+                &[1_000_000.0],
+            ),
+            (
+                "Will Ethereum dip to $2,000 in June?",
+                Some("ETH"),
+                Some(Dip),
+                &[2_000.0],
+            ),
+            (
+                "Will the price of Bitcoin be between $104,000 and $106,000 on June 13?",
+                Some("BTC"),
+                Some(Between),
+                &[104_000.0, 106_000.0],
+            ),
+            (
+                "Will the price of XRP be between $3.00 and $2.80 on July 20?",
+                Some("XRP"),
+                Some(Between),
+                &[2.8, 3.0],
+            ),
+            (
+                "Bitcoin Up or Down - June 13, 5PM ET",
+                Some("BTC"),
+                Some(UpOrDown),
+                &[],
+            ),
+            (
+                "Ethereum Up or Down on June 13?",
+                Some("ETH"),
+                Some(UpOrDown),
+                &[],
+            ),
+            (
+                "Will Dogecoin be above $0.25 on June 13?",
+                Some("DOGE"),
+                Some(Above),
+                &[0.25],
+            ),
+            (
+                "Will Bitcoin Cash be above $500 on June 30?",
+                Some("BCH"),
+                Some(Above),
+                &[500.0],
+            ),
+            (
+                "Will ETH flip BTC by market cap in 2025?",
+                Some("ETH"),
+                None,
+                &[],
+            ),
+            (
+                "Will Chainlink (LINK) be above $20 at 12 PM ET?",
+                Some("LINK"),
+                Some(Above),
+                &[20.0],
+            ),
+            (
+                "Will MicroStrategy hold more than 500k BTC by December 31?",
+                Some("BTC"),
+                Some(Above),
+                &[500_000.0],
+            ),
+            // Word boundaries: "resolution" isn't SOL, "Canada" isn't ADA, "whether" isn't ETH
+            ("Will the UN pass a resolution on Canada?", None, None, &[]),
+            (
+                "Will the Fed decide whether to cut rates in March?",
+                None,
+                None,
+                &[],
+            ),
+            (
+                "Will Trump win the 2028 presidential election?",
+                None,
+                None,
+                &[],
+            ),
+            (
+                "Will the Lakers score over 110.5 points?",
+                None,
+                Some(Above),
+                &[110.5],
+            ),
+            (
+                "Will inflation be under 3% in May?",
+                None,
+                Some(Below),
+                &[3.0],
+            ),
+            (
+                "Will Tesla stock close higher than $300 on Friday?",
+                None,
+                Some(Above),
+                &[300.0],
+            ),
+        ];
+        for (question, ticker, comparator, strikes) in cases {
+            let spec = QuestionSpec::parse(question);
+            assert_eq!(spec.underlying.map(|u| u.ticker), ticker, "{}", question);
+            assert_eq!(spec.comparator, comparator, "{}", question);
+            assert_eq!(spec.strikes, strikes, "{}", question);
+        }
+    }
+
+    #[test]
+    fn test_deadlines() {
+        let spec = QuestionSpec::parse("Bitcoin above $105,000 on June 13 at 5 PM ET?");
+        assert_eq!(spec.deadline_date, Some((6, 13)));
+        assert_eq!(spec.deadline_time, time(17, 0));
+        assert_eq!(spec.timezone, Some(QuestionTimezone::Eastern));
+        assert!(spec.is_terminal_strike());
+        assert_eq!(spec.underlying.unwrap().binance_symbol, "BTCUSDT");
+
+        let spec = QuestionSpec::parse("Solana above $140 at 11:30am EDT on Sept 3rd?");
+        assert_eq!(spec.deadline_date, Some((9, 3)));
+        assert_eq!(spec.deadline_time, time(11, 30));
+        assert_eq!(spec.timezone, Some(QuestionTimezone::Eastern));
+
+        let spec = QuestionSpec::parse("Will Trump say 'crypto' by 12 AM EST on Jan 20?");
+        assert_eq!(spec.deadline_time, time(0, 0));
+        assert_eq!(spec.deadline_date, Some((1, 20)));
+
+        let spec = QuestionSpec::parse("ETH above $4,000 at 16:00 UTC on Dec 12?");
+        assert_eq!(spec.deadline_time, time(16, 0));
+        assert_eq!(spec.timezone, Some(QuestionTimezone::Utc));
+        assert_eq!(spec.strikes, vec![4_000.0]);
+
+        let spec = QuestionSpec::parse("Will Bitcoin reach $150k in 2025?");
+        assert_eq!(
+            (spec.deadline_date, spec.deadline_time, spec.timezone),
+            (None, None, None)
+        );
+        assert!(!spec.is_terminal_strike());
+        assert_eq!(spec.strike(), Some(150_000.0));
+    }
+}
@@ -0,0 +1,334 @@
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info};
+use utoipa::ToSchema;
+
+use crate::markets::condition::normalize_market_id;
+use crate::polymarket::MarketData;
+
+pub const MARKET_REGISTRY_FILE: &str = "market_registry.json";
+
+/// Which market and outcome a CLOB token belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AssetRef {
+    pub market_id: String,
+    pub side: String, // "YES" or "NO"
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistrySnapshot {
+    markets: Vec<MarketData>,
+    assets: Vec<(String, AssetRef)>,
+}
+
+/// Shared market / asset-id registry (cheap to clone, all clones see the same maps)
+/// Persisted to `data_dir/market_registry.json` so open positions keep their mapping across restarts
+/// Saves are blocking; callers on the async path run them on the blocking pool
+#[derive(Clone)]
+pub struct MarketRegistry {
+    markets: Arc<DashMap<String, MarketData>>,
+    assets: Arc<DashMap<String, AssetRef>>,
+    path: Option<PathBuf>,
+    dirty: Arc<AtomicBool>,
+    /// Held from taking the snapshot to renaming the file, so concurrent saves land in order
+    save_lock: Arc<Mutex<()>>,
+}
+
+impl MarketRegistry {
+    /// In-memory registry (nothing persisted)
+    pub fn new() -> Self {
+        Self {
+            markets: Arc::new(DashMap::new()),
+            assets: Arc::new(DashMap::new()),
+            path: None,
+            dirty: Arc::new(AtomicBool::new(false)),
+            save_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Registry backed by the data dir, restoring the previous run's state if present
+    pub fn load(data_dir: &str) -> Self {
+        let path = market_registry_path(data_dir);
+        let registry = Self {
+            path: Some(path.clone()),
+            ..Self::new()
+        };
+
+        match read_snapshot(&path) {
+            Ok(snapshot) => {
+                for market in snapshot.markets {
+                    registry.markets.insert(market.id.to_string(), market);
+                }
+                for (asset_id, asset) in snapshot.assets {
+                    registry.assets.insert(asset_id, asset);
+                }
+                info!(
+                    "🗂️ Restored {} markets / {} assets from {:?}",
+                    registry.markets.len(),
+                    registry.assets.len(),
+                    path
+                );
+            }
+            Err(e) => debug!("No market registry loaded ({}), starting fresh", e),
+        }
+
+        registry
+    }
+
+    pub fn get(&self, market_id: &str) -> Option<MarketData> {
+        let key = normalize_market_id(market_id);
+        self.markets.get(key.as_ref()).map(|m| m.clone())
+    }
+
+    pub fn contains(&self, market_id: &str) -> bool {
+        self.markets
+            .contains_key(normalize_market_id(market_id).as_ref())
+    }
+
+    pub fn insert(&self, mut market: MarketData) {
+        normalize_market(&mut market);
+        self.markets.insert(market.id.to_string(), market);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Mutate a market in place. Returns false if it isn't registered
+    pub fn update<F: FnOnce(&mut MarketData)>(&self, market_id: &str, f: F) -> bool {
+        let key = normalize_market_id(market_id);
+        match self.markets.get_mut(key.as_ref()) {
+            Some(mut market) => {
+                f(&mut market);
+                self.dirty.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Map both outcome tokens of a market ([NO, YES] convention)
+    pub fn map_assets(&self, market_id: &str, no_asset_id: &str, yes_asset_id: &str) {
+        let market_id = normalize_market_id(market_id);
+        self.assets.insert(
+            no_asset_id.to_string(),
+            AssetRef {
+                market_id: market_id.to_string(),
+                side: "NO".to_string(),
+            },
+        );
+        self.assets.insert(
+            yes_asset_id.to_string(),
+            AssetRef {
+                market_id: market_id.to_string(),
+                side: "YES".to_string(),
+            },
+        );
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    pub fn asset(&self, asset_id: &str) -> Option<AssetRef> {
+        self.assets.get(asset_id).map(|a| a.clone())
+    }
+
+    pub fn asset_ids(&self) -> Vec<String> {
+        self.assets.iter().map(|a| a.key().clone()).collect()
+    }
+
+    /// Snapshot of all asset mappings
+    pub fn assets(&self) -> Vec<(String, AssetRef)> {
+        self.assets
+            .iter()
+            .map(|a| (a.key().clone(), a.value().clone()))
+            .collect()
+    }
+
+    /// Fill in markets and asset mappings from another source (the storage backend),
+    /// keeping entries already present
+    pub fn restore(&self, markets: Vec<MarketData>, assets: Vec<(String, AssetRef)>) -> usize {
+        let before = self.markets.len();
+        for mut market in markets {
+            normalize_market(&mut market);
+            self.markets.entry(market.id.to_string()).or_insert(market);
+        }
+        for (asset_id, mut asset) in assets {
+            normalize_asset(&mut asset);
+            self.assets.entry(asset_id).or_insert(asset);
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+        self.markets.len() - before
+    }
+
+    /// Snapshot of all registered markets
+    pub fn markets(&self) -> Vec<MarketData> {
+        self.markets.iter().map(|m| m.value().clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.markets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.markets.is_empty()
+    }
+
+    /// Asset id -> market mappings
+    pub fn asset_count(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Replace the contents with the snapshot on disk, written by another process (observer mode)
+    /// Returns the number of markets loaded
+    pub fn reload(&self) -> Result<usize> {
+        let path = self
+            .path
+            .as_ref()
+            .context("In-memory registry has nothing to reload")?;
+        let snapshot = read_snapshot(path)?;
+
+        let market_ids: HashSet<String> =
+            snapshot.markets.iter().map(|m| m.id.to_string()).collect();
+        let asset_ids: HashSet<String> = snapshot.assets.iter().map(|(id, _)| id.clone()).collect();
+        for market in snapshot.markets {
+            self.markets.insert(market.id.to_string(), market);
+        }
+        for (asset_id, asset) in snapshot.assets {
+            self.assets.insert(asset_id, asset);
+        }
+        self.markets.retain(|id, _| market_ids.contains(id));
+        self.assets.retain(|id, _| asset_ids.contains(id));
+        Ok(market_ids.len())
+    }
+
+    /// Write to disk if anything changed since the last save. True if it was written
+    pub fn save_if_dirty(&self) -> Result<bool> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(false);
+        }
+        if let Err(e) = self.save() {
+            self.dirty.store(true, Ordering::Relaxed);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Write to disk now (no-op for in-memory registries)
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let _saving = self.save_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let snapshot = RegistrySnapshot {
+            markets: self.markets(),
+            assets: self.assets(),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write-then-rename so a crash mid-write never leaves a truncated registry
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&snapshot)?)
+            .with_context(|| format!("Failed to write {:?}", tmp))?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+}
+
+impl Default for MarketRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Condition ids in canonical form: whichever path a market came in by, it has one key.
+/// Markets persisted before the end time was cached get it parsed here
+fn normalize_market(market: &mut MarketData) {
+    if let Cow::Owned(id) = normalize_market_id(&market.id) {
+        market.id = id.into();
+    }
+    if market.end_time.is_none() {
+        market.end_time = market.parse_end_time();
+    }
+}
+
+fn normalize_asset(asset: &mut AssetRef) {
+    if let Cow::Owned(id) = normalize_market_id(&asset.market_id) {
+        asset.market_id = id;
+    }
+}
+
+fn read_snapshot(path: &Path) -> Result<RegistrySnapshot> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let mut snapshot: RegistrySnapshot =
+        serde_json::from_str(&raw).context("Invalid market registry file")?;
+    // Files written before ids were normalized
+    snapshot.markets.iter_mut().for_each(normalize_market);
+    snapshot
+        .assets
+        .iter_mut()
+        .for_each(|(_, asset)| normalize_asset(asset));
+    Ok(snapshot)
+}
+
+/// Path of the persisted registry inside a data dir
+pub fn market_registry_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(MARKET_REGISTRY_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(id: &str) -> MarketData {
+        MarketData {
+            asset_ids: vec!["no".to_string(), "yes".to_string()],
+            ..MarketData::quoted(id, 0.4, 0.6)
+        }
+    }
+
+    #[test]
+    fn test_persist_and_restore() {
+        let dir = std::env::temp_dir().join(format!("registry-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+
+        let registry = MarketRegistry::load(data_dir);
+        registry.insert(market("m1"));
+        registry.map_assets("m1", "no", "yes");
+        registry.update("m1", |m| m.yes_price = 0.45);
+        registry.save_if_dirty().unwrap();
+
+        let restored = MarketRegistry::load(data_dir);
+        assert_eq!(restored.get("m1").unwrap().yes_price, 0.45);
+        assert_eq!(restored.asset("yes").unwrap().side, "YES");
+
+        // An observer picks up the writer's changes, removals included
+        registry.insert(market("m2"));
+        registry.save().unwrap();
+        assert_eq!(restored.reload().unwrap(), 2);
+        assert!(restored.contains("m2"));
+        registry.markets.remove("m1");
+        registry.save().unwrap();
+        restored.reload().unwrap();
+        assert!(!restored.contains("m1"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_condition_id_spellings_key_one_market() {
+        let id = "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1";
+        let registry = MarketRegistry::new();
+        // Derived from the on-chain event, then loaded from Gamma spelled differently
+        registry.insert(market(id));
+        registry.map_assets(&id[2..], "no", "yes");
+        registry.insert(market(&id.to_uppercase().replacen("0X", "0x", 1)));
+
+        assert_eq!(registry.len(), 1);
+        assert!(registry.contains(&id.to_uppercase()));
+        assert_eq!(registry.get(&id[2..]).unwrap().id, id);
+        assert_eq!(registry.asset("yes").unwrap().market_id, id);
+    }
+}
@@ -0,0 +1,285 @@
+//! New event series
+//!
+//! Polymarket runs recurring market families (a weekly "Bitcoin above ___ on Friday?", a daily
+//! temperature question) as event series on Gamma, and the systematic edges are in the first
+//! instances of a new one, not a few days later when someone happens to notice it. With
+//! `SERIES_WATCH_ENABLED` the newest series are polled every `SERIES_WATCH_INTERVAL_SECS`; one
+//! not seen before is raised as an alert (log and `AgentEvent::NewSeries`) as soon as it's
+//! detected, and when its title or slug contains one of `SERIES_AUTO_PIN_KEYWORDS` up to
+//! `SERIES_AUTO_PIN_MAX_MARKETS` of its open markets are pinned on the watchlist (automatic
+//! pins, which unlike the operator's don't override the market filters). A series only counts
+//! as seen once that's done: one whose markets couldn't be fetched (or had none open yet) is
+//! retried on the next polls, for up to `PENDING_MAX_AGE_HOURS`.
+//! The series listed on the very first poll only prime the watcher. Seen ids are kept in
+//! `data_dir/event_series.json`.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+use crate::config::SeriesWatchConfig;
+use crate::polymarket::EventSeries;
+
+pub const EVENT_SERIES_FILE: &str = "event_series.json";
+
+/// New series kept for the API
+const RECENT_SERIES: usize = 50;
+
+/// A new series whose markets still can't be pinned this long after detection is given up on
+const PENDING_MAX_AGE_HOURS: i64 = 48;
+
+/// A series first seen by the watcher
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NewSeries {
+    pub series: EventSeries,
+    pub detected_at: DateTime<Utc>,
+    /// Auto-pin keyword the series matched, if any
+    pub matched_keyword: Option<String>,
+    /// Condition ids pinned for it
+    pub pinned: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SeenSeries {
+    ids: BTreeSet<String>,
+}
+
+/// Event series seen so far, and the new ones of this run
+#[derive(Debug)]
+pub struct SeriesWatcher {
+    seen: BTreeSet<String>,
+    /// False until the first poll (with nothing persisted) has recorded what's listed
+    primed: bool,
+    keywords: Vec<String>,
+    /// Reported series not handled yet (markets still to pin), by id. Not persisted: a restart
+    /// reports them again
+    pending: BTreeMap<String, NewSeries>,
+    /// Most recent first
+    recent: VecDeque<NewSeries>,
+    path: Option<PathBuf>,
+}
+
+impl SeriesWatcher {
+    /// Series seen by the previous run are restored when a data dir is given
+    pub fn new(config: &SeriesWatchConfig, data_dir: Option<&str>) -> Self {
+        let path = data_dir.map(event_series_path);
+        let seen = match path.as_deref().map(read_seen) {
+            Some(Ok(seen)) => Some(seen.ids),
+            Some(Err(e)) => {
+                debug!("No event series loaded ({}), priming on the first poll", e);
+                None
+            }
+            None => None,
+        };
+        Self {
+            primed: seen.is_some(),
+            seen: seen.unwrap_or_default(),
+            keywords: config.auto_pin_keywords.clone(),
+            pending: BTreeMap::new(),
+            recent: VecDeque::new(),
+            path,
+        }
+    }
+
+    /// The series of `listed` neither seen nor reported before; they stay pending until
+    /// `mark_seen`. Empty on the first poll, which only records what's already listed
+    pub fn observe(&mut self, listed: Vec<EventSeries>, now: DateTime<Utc>) -> Vec<NewSeries> {
+        // An empty page (Gamma hiccup) mustn't prime the watcher with nothing
+        if listed.is_empty() {
+            return Vec::new();
+        }
+        if !self.primed {
+            self.seen.extend(listed.into_iter().map(|series| series.id));
+            self.primed = true;
+            self.persist();
+            return Vec::new();
+        }
+        let new: Vec<NewSeries> = listed
+            .into_iter()
+            .filter(|series| {
+                !self.seen.contains(&series.id) && !self.pending.contains_key(&series.id)
+            })
+            .map(|series| NewSeries {
+                matched_keyword: self.matched_keyword(&series),
+                series,
+                detected_at: now,
+                pinned: Vec::new(),
+            })
+            .collect();
+        for new_series in &new {
+            self.pending
+                .insert(new_series.series.id.clone(), new_series.clone());
+        }
+        new
+    }
+
+    /// Reported series still to handle: the ones just observed and those whose markets
+    /// couldn't be pinned on an earlier poll. Those pending for over `PENDING_MAX_AGE_HOURS`
+    /// are marked seen with nothing pinned instead
+    pub fn pending(&mut self, now: DateTime<Utc>) -> Vec<NewSeries> {
+        let cutoff = now - chrono::Duration::hours(PENDING_MAX_AGE_HOURS);
+        let expired: Vec<String> = self
+            .pending
+            .values()
+            .filter(|new_series| new_series.detected_at < cutoff)
+            .map(|new_series| new_series.series.id.clone())
+            .collect();
+        for series_id in expired {
+            warn!(
+                "⚠️ No markets of series {} pinned in {}h, giving up",
+                series_id, PENDING_MAX_AGE_HOURS
+            );
+            self.mark_seen(&series_id, Vec::new());
+        }
+        self.pending.values().cloned().collect()
+    }
+
+    /// A reported series is handled (its markets pinned, or nothing to pin): seen from now on,
+    /// and its recent entry gets the pinned condition ids
+    pub fn mark_seen(&mut self, series_id: &str, pinned: Vec<String>) {
+        self.pending.remove(series_id);
+        self.seen.insert(series_id.to_string());
+        self.persist();
+        if let Some(entry) = self.recent.iter_mut().find(|r| r.series.id == series_id) {
+            entry.pinned = pinned;
+        }
+    }
+
+    /// First auto-pin keyword in the series' title or slug
+    pub fn matched_keyword(&self, series: &EventSeries) -> Option<String> {
+        let title = series.title.to_lowercase();
+        let slug = series.slug.to_lowercase();
+        self.keywords
+            .iter()
+            .find(|keyword| title.contains(keyword.as_str()) || slug.contains(keyword.as_str()))
+            .cloned()
+    }
+
+    /// Keep a new series (with what was pinned for it) for the API
+    pub fn record(&mut self, new_series: NewSeries) {
+        if self.recent.len() == RECENT_SERIES {
+            self.recent.pop_back();
+        }
+        self.recent.push_front(new_series);
+    }
+
+    /// New series of this run, most recent first
+    pub fn recent(&self) -> Vec<NewSeries> {
+        self.recent.iter().cloned().collect()
+    }
+
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to persist event series: {}", e);
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let seen = SeenSeries {
+            ids: self.seen.clone(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&seen)?)
+            .with_context(|| format!("Failed to write {:?}", tmp))?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+}
+
+fn read_seen(path: &Path) -> Result<SeenSeries> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&raw).context("Invalid event series file")
+}
+
+/// Path of the seen event series inside a data dir
+pub fn event_series_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(EVENT_SERIES_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(id: &str, title: &str) -> EventSeries {
+        EventSeries {
+            id: id.to_string(),
+            slug: title.to_lowercase().replace(' ', "-"),
+            title: title.to_string(),
+            recurrence: Some("weekly".to_string()),
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_new_series_are_reported_once_after_priming() {
+        let dir = std::env::temp_dir().join(format!("series-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let config = SeriesWatchConfig {
+            enabled: true,
+            poll_interval_secs: 300,
+            auto_pin_keywords: vec!["bitcoin".to_string()],
+            auto_pin_max_markets: 5,
+            auto_pin_notify: false,
+        };
+        let now = Utc::now();
+
+        let mut watcher = SeriesWatcher::new(&config, Some(data_dir));
+        // First poll: what's already listed isn't new
+        assert!(watcher
+            .observe(vec![series("1", "Fed Decision")], now)
+            .is_empty());
+        let new = watcher.observe(
+            vec![series("2", "Bitcoin Weekly"), series("1", "Fed Decision")],
+            now,
+        );
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].series.id, "2");
+        assert_eq!(new[0].matched_keyword.as_deref(), Some("bitcoin"));
+        watcher.record(new[0].clone());
+
+        // Its markets weren't pinned yet: not reported again, but still pending
+        assert!(watcher
+            .observe(vec![series("2", "Bitcoin Weekly")], now)
+            .is_empty());
+        assert_eq!(watcher.pending(now).len(), 1);
+        watcher.mark_seen("2", vec!["0xabc".to_string()]);
+        assert!(watcher.pending(now).is_empty());
+        assert_eq!(watcher.recent()[0].pinned, vec!["0xabc".to_string()]);
+
+        // A restart remembers both, so a third series is new straight away
+        let mut restored = SeriesWatcher::new(&config, Some(data_dir));
+        let new = restored.observe(
+            vec![
+                series("3", "NYC Temperature"),
+                series("2", "Bitcoin Weekly"),
+            ],
+            now,
+        );
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].matched_keyword, None);
+        restored.record(new[0].clone());
+        assert_eq!(restored.recent()[0].series.id, "3");
+
+        // Reported but never marked seen: a restart reports it again
+        let mut restored = SeriesWatcher::new(&config, Some(data_dir));
+        let new = restored.observe(vec![series("3", "NYC Temperature")], now);
+        assert_eq!(new.len(), 1);
+
+        // Still pending past the cutoff: given up on and seen from then on
+        let later = now + chrono::Duration::hours(PENDING_MAX_AGE_HOURS + 1);
+        assert!(restored.pending(later).is_empty());
+        assert!(restored
+            .observe(vec![series("3", "NYC Temperature")], later)
+            .is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// Subscription counters for logs and the API
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct SubscriptionStats {
+    pub subscribed: usize,
+    /// 0 = unlimited
+    pub cap: usize,
+    pub peak: usize,
+    pub evicted: u64,
+    /// Times the cap was exceeded because every candidate was pinned (open positions)
+    pub over_cap: u64,
+}
+
+/// CLOB WS asset subscriptions, grouped by market and ordered by last book activity
+/// Past `cap` assets, the least recently active markets are evicted whole (YES and NO
+/// together), except pinned ones
+pub struct SubscriptionSet {
+    cap: usize,
+    /// market_id -> activity sequence and its subscribed assets
+    markets: HashMap<String, (u64, Vec<String>)>,
+    /// asset_id -> market_id
+    asset_market: HashMap<String, String>,
+    /// activity sequence -> market_id, oldest first
+    order: BTreeMap<u64, String>,
+    next_seq: u64,
+    stats: SubscriptionStats,
+}
+
+impl SubscriptionSet {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            cap,
+            markets: HashMap::new(),
+            asset_market: HashMap::new(),
+            order: BTreeMap::new(),
+            next_seq: 0,
+            stats: SubscriptionStats {
+                cap,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Track a new subscription of one of `market_id`'s assets; false if the asset is already
+    /// subscribed
+    pub fn insert(&mut self, market_id: &str, asset_id: &str) -> bool {
+        if self.asset_market.contains_key(asset_id) {
+            return false;
+        }
+        self.asset_market
+            .insert(asset_id.to_string(), market_id.to_string());
+        self.markets
+            .entry(market_id.to_string())
+            .or_insert_with(|| (self.next_seq, Vec::new()))
+            .1
+            .push(asset_id.to_string());
+        self.bump(market_id);
+        self.stats.peak = self.stats.peak.max(self.asset_market.len());
+        true
+    }
+
+    /// A book update arrived for the asset: its market is active (ignored if not subscribed)
+    pub fn touch(&mut self, asset_id: &str) {
+        if let Some(market_id) = self.asset_market.get(asset_id).cloned() {
+            self.bump(&market_id);
+        }
+    }
+
+    /// Stop tracking an asset; false if it wasn't subscribed
+    pub fn remove(&mut self, asset_id: &str) -> bool {
+        let Some(market_id) = self.asset_market.remove(asset_id) else {
+            return false;
+        };
+        if let Some((seq, assets)) = self.markets.get_mut(&market_id) {
+            assets.retain(|a| a != asset_id);
+            if assets.is_empty() {
+                self.order.remove(seq);
+                self.markets.remove(&market_id);
+            }
+        }
+        true
+    }
+
+    fn bump(&mut self, market_id: &str) {
+        let Some((seq, _)) = self.markets.get_mut(market_id) else {
+            return;
+        };
+        self.order.remove(seq);
+        *seq = self.next_seq;
+        self.order.insert(self.next_seq, market_id.to_string());
+        self.next_seq += 1;
+    }
+
+    pub fn contains(&self, asset_id: &str) -> bool {
+        self.asset_market.contains_key(asset_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.asset_market.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.asset_market.is_empty()
+    }
+
+    /// Drop least recently active markets until back under the cap, skipping `pinned` ones
+    /// (by market id). Returns the evicted asset ids (to unsubscribe)
+    pub fn evict(&mut self, pinned: impl Fn(&str) -> bool) -> Vec<String> {
+        if self.cap == 0 || self.asset_market.len() <= self.cap {
+            return Vec::new();
+        }
+
+        let mut excess = self.asset_market.len() - self.cap;
+        let mut victims: Vec<String> = Vec::new();
+        for market_id in self.order.values() {
+            if excess == 0 {
+                break;
+            }
+            if pinned(market_id) {
+                continue;
+            }
+            excess = excess.saturating_sub(self.markets[market_id].1.len());
+            victims.push(market_id.clone());
+        }
+        if excess > 0 {
+            self.stats.over_cap += 1;
+        }
+
+        let mut evicted = Vec::new();
+        for market_id in victims {
+            if let Some((seq, assets)) = self.markets.remove(&market_id) {
+                self.order.remove(&seq);
+                for asset_id in &assets {
+                    self.asset_market.remove(asset_id);
+                }
+                self.stats.evicted += assets.len() as u64;
+                evicted.extend(assets);
+            }
+        }
+        evicted
+    }
+
+    pub fn stats(&self) -> SubscriptionStats {
+        SubscriptionStats {
+            subscribed: self.asset_market.len(),
+            ..self.stats.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_active_unpinned() {
+        let mut subs = SubscriptionSet::new(4);
+        for market in ["a", "b", "c", "d"] {
+            assert!(subs.insert(market, &format!("{}-yes", market)));
+            assert!(subs.insert(market, &format!("{}-no", market)));
+        }
+        assert!(!subs.insert("a", "a-yes"));
+
+        // "a" just ticked (on one side), "b" backs an open position
+        subs.touch("a-no");
+        let mut evicted = subs.evict(|market| market == "b");
+        evicted.sort();
+        // Whole markets go: no YES kept without its NO
+        assert_eq!(evicted, vec!["c-no", "c-yes", "d-no", "d-yes"]);
+        assert!(subs.contains("a-yes") && subs.contains("a-no") && subs.contains("b-yes"));
+
+        let stats = subs.stats();
+        assert_eq!(stats.subscribed, 4);
+        assert_eq!(stats.peak, 8);
+        assert_eq!(stats.evicted, 4);
+
+        // An evicted market can be subscribed again
+        assert!(subs.insert("c", "c-yes"));
+        assert!(subs.remove("c-yes"));
+        assert!(!subs.remove("c-yes"));
+
+        // Everything pinned: the cap gives way
+        subs.insert("e", "e-yes");
+        assert!(subs.evict(|_| true).is_empty());
+        assert_eq!(subs.stats().over_cap, 1);
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::config::SuspensionConfig;
+use crate::markets::MarketCategory;
+
+/// Which sides of one outcome's book have resting orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BookShape {
+    TwoSided,
+    NoBids,
+    NoAsks,
+    Empty,
+}
+
+impl BookShape {
+    pub fn new(has_bids: bool, has_asks: bool) -> Self {
+        match (has_bids, has_asks) {
+            (true, true) => BookShape::TwoSided,
+            (false, true) => BookShape::NoBids,
+            (true, false) => BookShape::NoAsks,
+            (false, false) => BookShape::Empty,
+        }
+    }
+
+    pub fn is_two_sided(self) -> bool {
+        self == BookShape::TwoSided
+    }
+}
+
+/// A guarded market went in or out of suspension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspensionEvent {
+    Suspended,
+    Resumed,
+}
+
+/// Latest book shape of each outcome, plus the suspension window
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuoteStatus {
+    pub yes: Option<BookShape>,
+    pub no: Option<BookShape>,
+    pub suspended_since: Option<DateTime<Utc>>,
+    pub resumed_at: Option<DateTime<Utc>>,
+}
+
+impl QuoteStatus {
+    /// Either outcome is missing a side (a fresh market with only one book seen counts too)
+    fn one_sided(&self) -> bool {
+        [self.yes, self.no]
+            .iter()
+            .any(|shape| shape.is_some_and(|s| !s.is_two_sided()))
+    }
+}
+
+/// Detects suspended or one-sided books on live markets (in-play sports by default)
+/// While a market is suspended, and for a grace period after it reopens, its quotes are stale
+/// or mid-reprice and strategies must not act on them
+pub struct SuspensionTracker {
+    config: SuspensionConfig,
+    markets: HashMap<String, QuoteStatus>,
+    /// Whether the guard applies to a market (sports_only: cached category check)
+    guarded: HashMap<String, bool>,
+}
+
+impl SuspensionTracker {
+    pub fn new(config: SuspensionConfig) -> Self {
+        Self {
+            config,
+            markets: HashMap::new(),
+            guarded: HashMap::new(),
+        }
+    }
+
+    /// Record a book update for one outcome. `question` is only looked up the first time a
+    /// market is seen, to decide whether the guard applies to it
+    pub fn observe(
+        &mut self,
+        market_id: &str,
+        side: &str,
+        shape: BookShape,
+        now: DateTime<Utc>,
+        question: impl FnOnce() -> Option<String>,
+    ) -> Option<SuspensionEvent> {
+        if !self.config.enabled {
+            return None;
+        }
+        let guarded = match self.guarded.get(market_id) {
+            Some(guarded) => *guarded,
+            None if !self.config.sports_only => true,
+            // Not cached until the question is known (metadata may still be loading)
+            None => match question() {
+                Some(q) => {
+                    let sports = MarketCategory::classify(&q) == MarketCategory::Sports;
+                    self.guarded.insert(market_id.to_string(), sports);
+                    sports
+                }
+                None => false,
+            },
+        };
+        if !guarded {
+            return None;
+        }
+
+        let status = self.markets.entry(market_id.to_string()).or_default();
+        if side == "YES" {
+            status.yes = Some(shape);
+        } else {
+            status.no = Some(shape);
+        }
+
+        match (status.one_sided(), status.suspended_since) {
+            (true, None) => {
+                status.suspended_since = Some(now);
+                warn!("⛔ {} suspended: {} book {:?}", market_id, side, shape);
+                Some(SuspensionEvent::Suspended)
+            }
+            (false, Some(since)) => {
+                status.suspended_since = None;
+                status.resumed_at = Some(now);
+                info!(
+                    "▶️ {} resumed after {}ms",
+                    market_id,
+                    (now - since).num_milliseconds()
+                );
+                Some(SuspensionEvent::Resumed)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_suspended(&self, market_id: &str) -> bool {
+        self.markets
+            .get(market_id)
+            .is_some_and(|s| s.suspended_since.is_some())
+    }
+
+    /// Quotes can be acted on: not suspended and past the grace period after reopening
+    pub fn can_trade(&self, market_id: &str, now: DateTime<Utc>) -> bool {
+        let Some(status) = self.markets.get(market_id) else {
+            return true;
+        };
+        if status.suspended_since.is_some() {
+            return false;
+        }
+        status.resumed_at.is_none_or(|resumed| {
+            now - resumed >= Duration::milliseconds(self.config.resume_grace_ms as i64)
+        })
+    }
+
+    pub fn status(&self, market_id: &str) -> Option<&QuoteStatus> {
+        self.markets.get(market_id)
+    }
+
+    /// Markets suspended right now, as (market_id, since)
+    pub fn suspended(&self) -> Vec<(String, DateTime<Utc>)> {
+        self.markets
+            .iter()
+            .filter_map(|(id, s)| s.suspended_since.map(|since| (id.clone(), since)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(sports_only: bool) -> SuspensionConfig {
+        SuspensionConfig {
+            enabled: true,
+            sports_only,
+            resume_grace_ms: 2000,
+        }
+    }
+
+    #[test]
+    fn test_one_sided_book_suspends_until_grace_passes() {
+        let mut tracker = SuspensionTracker::new(config(true));
+        let now = Utc::now();
+        let question = || Some("Lakers vs. Celtics".to_string());
+
+        assert_eq!(
+            tracker.observe("m1", "YES", BookShape::TwoSided, now, question),
+            None
+        );
+        assert_eq!(
+            tracker.observe("m1", "NO", BookShape::NoAsks, now, question),
+            Some(SuspensionEvent::Suspended)
+        );
+        assert!(tracker.is_suspended("m1"));
+        assert!(!tracker.can_trade("m1", now));
+
+        let reopen = now + Duration::seconds(30);
+        assert_eq!(
+            tracker.observe("m1", "NO", BookShape::TwoSided, reopen, question),
+            Some(SuspensionEvent::Resumed)
+        );
+        assert!(!tracker.can_trade("m1", reopen + Duration::milliseconds(500)));
+        assert!(tracker.can_trade("m1", reopen + Duration::seconds(2)));
+    }
+
+    #[test]
+    fn test_non_sports_markets_are_not_guarded() {
+        let mut tracker = SuspensionTracker::new(config(true));
+        let now = Utc::now();
+        let question = || Some("Will the Fed cut rates in March?".to_string());
+        assert_eq!(
+            tracker.observe("m2", "YES", BookShape::Empty, now, question),
+            None
+        );
+        assert!(tracker.can_trade("m2", now));
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use tracing::debug;
+
+use crate::config::SyntheticConfig;
+use crate::ids::UsdAmount;
+use crate::strategies::TradeAction;
+
+/// One synthetic market: when it was seen on-chain and when its first book came in
+#[derive(Debug, Clone)]
+struct SyntheticEntry {
+    detected_at: DateTime<Utc>,
+    first_book_at: Option<DateTime<Utc>>,
+    abandoned: bool,
+}
+
+/// Markets traded on derived token ids while Gamma has no metadata for them, and the policy
+/// they're held to instead of the market filters (see `SyntheticConfig`). A market leaves once
+/// its metadata loads and the regular filters take over
+pub struct SyntheticMarkets {
+    config: SyntheticConfig,
+    markets: HashMap<String, SyntheticEntry>,
+}
+
+impl SyntheticMarkets {
+    pub fn new(config: SyntheticConfig) -> Self {
+        Self {
+            config,
+            markets: HashMap::new(),
+        }
+    }
+
+    /// Start tracking a market created from an on-chain event
+    pub fn detected(&mut self, market_id: &str, now: DateTime<Utc>) {
+        self.markets
+            .entry(market_id.to_string())
+            .or_insert(SyntheticEntry {
+                detected_at: now,
+                first_book_at: None,
+                abandoned: false,
+            });
+    }
+
+    /// A book update for the market (only the first one is kept)
+    pub fn book_seen(&mut self, market_id: &str, now: DateTime<Utc>) {
+        if let Some(entry) = self.markets.get_mut(market_id) {
+            entry.first_book_at.get_or_insert(now);
+        }
+    }
+
+    /// Metadata arrived: the market is a regular one from now on
+    pub fn metadata_loaded(&mut self, market_id: &str) {
+        self.markets.remove(market_id);
+    }
+
+    /// The market resolved or left the book: nothing to track any more
+    pub fn forget(&mut self, market_id: &str) {
+        self.markets.remove(market_id);
+    }
+
+    pub fn is_abandoned(&self, market_id: &str) -> bool {
+        self.markets.get(market_id).is_some_and(|m| m.abandoned)
+    }
+
+    /// Whether strategies may look at the market at all: tracked, not abandoned, and its
+    /// first book in time
+    pub fn can_evaluate(&self, market_id: &str) -> bool {
+        let Some(entry) = self.markets.get(market_id) else {
+            return false;
+        };
+        if entry.abandoned {
+            return false;
+        }
+        let in_time = entry.first_book_at.is_some_and(|at| {
+            at - entry.detected_at <= Duration::milliseconds(self.config.first_book_ms as i64)
+        });
+        if !in_time {
+            debug!("⏭️ Synthetic market {} had no book in time", market_id);
+        }
+        in_time
+    }
+
+    /// `action` of `strategy` as the policy lets it through: dropped for strategies not
+    /// allowed on synthetic markets, otherwise capped in size
+    pub fn gate(&self, strategy: &str, action: TradeAction) -> TradeAction {
+        if matches!(action, TradeAction::None) {
+            return action;
+        }
+        let strategy = strategy.to_lowercase();
+        if !self.config.strategies.contains(&strategy) {
+            debug!("⏭️ {} not allowed on synthetic markets", strategy);
+            return TradeAction::None;
+        }
+        let cap =
+            |size_usd: UsdAmount| UsdAmount::new(size_usd.get().min(self.config.max_size_usd));
+        match action {
+            TradeAction::Snipe {
+                market_id,
+                side,
+                price,
+                size_usd,
+            } => TradeAction::Snipe {
+                market_id,
+                side,
+                price,
+                size_usd: cap(size_usd),
+            },
+            TradeAction::BuyBoth {
+                market_id,
+                yes_price,
+                no_price,
+                size_usd,
+                expected_profit_bps,
+            } => TradeAction::BuyBoth {
+                market_id,
+                yes_price,
+                no_price,
+                size_usd: cap(size_usd),
+                expected_profit_bps,
+            },
+            TradeAction::None => TradeAction::None,
+        }
+    }
+
+    /// Markets still without metadata past the timeout, marked abandoned. Each is returned
+    /// once. Abandoned markets are dropped after a second timeout, by when no metadata retry
+    /// of theirs is still in flight
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        let timeout = Duration::seconds(self.config.metadata_timeout_secs as i64);
+        self.markets
+            .retain(|_, entry| !entry.abandoned || now - entry.detected_at < timeout * 2);
+        let mut expired = Vec::new();
+        for (market_id, entry) in self.markets.iter_mut() {
+            if !entry.abandoned && now - entry.detected_at >= timeout {
+                entry.abandoned = true;
+                expired.push(market_id.clone());
+            }
+        }
+        expired
+    }
+
+    /// Markets currently synthetic (abandoned ones included)
+    pub fn len(&self) -> usize {
+        self.markets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.markets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::Price;
+
+    fn config() -> SyntheticConfig {
+        SyntheticConfig {
+            strategies: vec!["arbitrage".to_string()],
+            max_size_usd: 25.0,
+            first_book_ms: 5000,
+            metadata_timeout_secs: 600,
+        }
+    }
+
+    fn buy_both(size_usd: f64) -> TradeAction {
+        TradeAction::BuyBoth {
+            market_id: "m1".into(),
+            yes_price: Price::new(0.45),
+            no_price: Price::new(0.50),
+            size_usd: UsdAmount::new(size_usd),
+            expected_profit_bps: 500,
+        }
+    }
+
+    #[test]
+    fn test_policy_replaces_the_filter_bypass() {
+        let mut synthetic = SyntheticMarkets::new(config());
+        let t0 = Utc::now();
+        synthetic.detected("fast", t0);
+        synthetic.detected("slow", t0);
+        assert!(!synthetic.can_evaluate("fast"));
+
+        synthetic.book_seen("fast", t0 + Duration::seconds(2));
+        synthetic.book_seen("slow", t0 + Duration::seconds(8));
+        assert!(synthetic.can_evaluate("fast"));
+        assert!(!synthetic.can_evaluate("slow"));
+        assert!(!synthetic.can_evaluate("unknown"));
+
+        // Allowed strategies are capped, the rest dropped
+        match synthetic.gate("arbitrage", buy_both(100.0)) {
+            TradeAction::BuyBoth { size_usd, .. } => assert_eq!(size_usd.get(), 25.0),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            synthetic.gate("predictive", buy_both(10.0)),
+            TradeAction::None
+        ));
+
+        // Metadata makes a market regular; the rest are abandoned after the timeout
+        synthetic.metadata_loaded("fast");
+        assert!(synthetic.expire(t0 + Duration::seconds(599)).is_empty());
+        assert_eq!(synthetic.expire(t0 + Duration::seconds(600)), vec!["slow"]);
+        assert!(synthetic.expire(t0 + Duration::seconds(900)).is_empty());
+        assert!(synthetic.is_abandoned("slow"));
+        assert_eq!(synthetic.len(), 1);
+
+        // Abandoned markets are dropped a timeout later, resolved ones right away
+        synthetic.expire(t0 + Duration::seconds(1199));
+        assert!(synthetic.is_abandoned("slow"));
+        synthetic.expire(t0 + Duration::seconds(1200));
+        assert!(synthetic.is_empty());
+        synthetic.detected("resolved", t0);
+        synthetic.forget("resolved");
+        assert!(synthetic.is_empty());
+    }
+}
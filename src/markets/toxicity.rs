@@ -0,0 +1,307 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::config::ToxicityConfig;
+
+/// Weight of each judged order in a market's score
+const SCORE_ALPHA: f64 = 0.3;
+
+/// Share of the depth at our limit that must vanish in the window for a pulled quote
+const PULL_FRACTION: f64 = 0.5;
+
+/// Orders never judged (their fill check didn't run) are dropped after this long (seconds)
+const MAX_WATCH_SECS: i64 = 60;
+
+/// Price tolerance when comparing levels and prints to our limit
+const PRICE_EPSILON: f64 = 1e-9;
+
+/// The book an order went out into, captured before it is sent
+#[derive(Debug, Clone)]
+pub struct PostContext {
+    pub asset_id: String,
+    /// Ask ladder of the asset when the order was sent, best first
+    pub asks: Vec<(f64, f64)>,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// An order we posted, watched for the window after it
+#[derive(Debug, Clone)]
+struct WatchedOrder {
+    market_id: String,
+    asset_id: String,
+    limit_price: f64,
+    /// Shares the order was for
+    shares: f64,
+    /// Shares asked at or below our limit when we sent it
+    depth: f64,
+    posted_at: DateTime<Utc>,
+    /// The depth at our limit was pulled within the window
+    pulled: bool,
+    /// Shares others bought at or below our limit within the window: the queue ahead of us
+    jumped: f64,
+    /// Someone else's exchange settlement of the asset reached the mempool within the window
+    raced: bool,
+}
+
+impl WatchedOrder {
+    /// We were last in queue: others bought at our price until what was left at our limit
+    /// couldn't fill us
+    fn last_in_queue(&self) -> bool {
+        self.jumped > 0.0 && self.depth - self.jumped < self.shares - PRICE_EPSILON
+    }
+}
+
+/// How often our orders on a market are picked off: quotes pulled as soon as we post, the price
+/// we wanted printing to others first, or their settlements racing ours in the mempool
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct MarketToxicity {
+    pub market_id: String,
+    /// EWMA of judged orders that missed with their quote pulled, last in queue or raced, 0-1
+    pub score: f64,
+    pub samples: u32,
+    pub pulled: u32,
+    pub last_in_queue: u32,
+    pub raced: u32,
+    pub toxic: bool,
+}
+
+/// Per-market toxicity from the CLOB book right after our posts, the public trade tape and the
+/// pending exchange settlements, settled by each order's fill check
+#[derive(Debug)]
+pub struct ToxicityTracker {
+    window: Duration,
+    threshold: f64,
+    min_samples: u32,
+    extra_edge_bps: i32,
+    /// Orders waiting for their fill check, by trade id
+    orders: HashMap<String, WatchedOrder>,
+    markets: HashMap<String, MarketToxicity>,
+}
+
+impl ToxicityTracker {
+    pub fn new(config: &ToxicityConfig) -> Self {
+        Self {
+            window: Duration::milliseconds(config.window_ms.min(i64::MAX as u64) as i64),
+            threshold: config.threshold,
+            min_samples: config.min_samples,
+            extra_edge_bps: config.extra_edge_bps,
+            orders: HashMap::new(),
+            markets: HashMap::new(),
+        }
+    }
+
+    /// Watch an order for `shares` at `limit_price`, sent into the book of `post`. The window
+    /// runs from the send, so what happened while the post was in flight counts
+    pub fn record_post(
+        &mut self,
+        trade_id: &str,
+        market_id: &str,
+        limit_price: f64,
+        shares: f64,
+        post: PostContext,
+    ) {
+        let now = post.sent_at;
+        self.orders
+            .retain(|_, order| now - order.posted_at < Duration::seconds(MAX_WATCH_SECS));
+        self.orders.insert(
+            trade_id.to_string(),
+            WatchedOrder {
+                market_id: market_id.to_string(),
+                depth: depth_within(&post.asks, limit_price),
+                asset_id: post.asset_id,
+                limit_price,
+                shares,
+                posted_at: now,
+                pulled: false,
+                jumped: 0.0,
+                raced: false,
+            },
+        );
+    }
+
+    /// A new ask ladder of `asset_id`: orders posted within the window whose depth vanished
+    /// are marked pulled (our own fill looks the same, which the fill check tells apart)
+    pub fn on_book(&mut self, asset_id: &str, asks: &[(f64, f64)], now: DateTime<Utc>) {
+        for order in self.orders.values_mut() {
+            if order.asset_id != asset_id || now - order.posted_at > self.window {
+                continue;
+            }
+            if depth_within(asks, order.limit_price) < order.depth * (1.0 - PULL_FRACTION) {
+                order.pulled = true;
+            }
+        }
+    }
+
+    /// A print on the trade tape: buys at or below our limit within the window went to
+    /// someone else if our order then misses
+    pub fn on_trade(
+        &mut self,
+        asset_id: &str,
+        buy: bool,
+        price: f64,
+        size: f64,
+        at: DateTime<Utc>,
+    ) {
+        if !buy {
+            return;
+        }
+        for order in self.orders.values_mut() {
+            let in_window = at >= order.posted_at && at - order.posted_at <= self.window;
+            if order.asset_id == asset_id && in_window && price <= order.limit_price + PRICE_EPSILON
+            {
+                order.jumped += size;
+            }
+        }
+    }
+
+    /// A pending exchange settlement of `asset_id` (not ours) seen in the mempool: orders
+    /// sent within the window before it were raced
+    pub fn on_pending_fill(&mut self, asset_id: &str, at: DateTime<Utc>) {
+        for order in self.orders.values_mut() {
+            let in_window = at >= order.posted_at && at - order.posted_at <= self.window;
+            if order.asset_id == asset_id && in_window {
+                order.raced = true;
+            }
+        }
+    }
+
+    /// Judge an order once its fill is known. Returns whether it counted as picked off
+    pub fn record_outcome(&mut self, trade_id: &str, filled: bool) -> bool {
+        let Some(order) = self.orders.remove(trade_id) else {
+            return false;
+        };
+        let last_in_queue = order.last_in_queue();
+        let picked_off = !filled && (order.pulled || last_in_queue || order.raced);
+        let market = self
+            .markets
+            .entry(order.market_id.clone())
+            .or_insert_with(|| MarketToxicity {
+                market_id: order.market_id.clone(),
+                ..Default::default()
+            });
+        market.samples += 1;
+        let x = if picked_off { 1.0 } else { 0.0 };
+        market.score += SCORE_ALPHA * (x - market.score);
+        if picked_off && order.pulled {
+            market.pulled += 1;
+        }
+        if picked_off && last_in_queue {
+            market.last_in_queue += 1;
+        }
+        if picked_off && order.raced {
+            market.raced += 1;
+        }
+        picked_off
+    }
+
+    pub fn is_toxic(&self, market_id: &str) -> bool {
+        self.markets
+            .get(market_id)
+            .is_some_and(|m| m.samples >= self.min_samples && m.score >= self.threshold)
+    }
+
+    /// Edge to add to the market's arbitrage minimum (0 unless toxic)
+    pub fn extra_edge_bps(&self, market_id: &str) -> i32 {
+        if self.is_toxic(market_id) {
+            self.extra_edge_bps
+        } else {
+            0
+        }
+    }
+
+    /// Judged markets, most toxic first
+    pub fn snapshot(&self) -> Vec<MarketToxicity> {
+        let mut all: Vec<MarketToxicity> = self
+            .markets
+            .values()
+            .map(|m| MarketToxicity {
+                toxic: self.is_toxic(&m.market_id),
+                ..m.clone()
+            })
+            .collect();
+        all.sort_by(|a, b| b.score.total_cmp(&a.score));
+        all
+    }
+}
+
+/// Shares asked at or below `limit_price`
+fn depth_within(asks: &[(f64, f64)], limit_price: f64) -> f64 {
+    asks.iter()
+        .filter(|(price, _)| *price <= limit_price + PRICE_EPSILON)
+        .map(|(_, size)| size)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pulled_and_jumped_orders_make_a_market_toxic() {
+        let mut tracker = ToxicityTracker::new(&ToxicityConfig {
+            enabled: true,
+            window_ms: 1000,
+            threshold: 0.5,
+            min_samples: 3,
+            extra_edge_bps: 50,
+            mempool: false,
+        });
+        let now = Utc::now();
+        let post = |sent_at| PostContext {
+            asset_id: "a1".to_string(),
+            asks: vec![(0.40, 100.0), (0.45, 200.0)],
+            sent_at,
+        };
+
+        // Depth at our limit pulled right after the post, and we missed
+        tracker.record_post("t1", "m1", 0.40, 60.0, post(now));
+        tracker.on_book("a1", &[(0.45, 200.0)], now + Duration::milliseconds(200));
+        assert!(tracker.record_outcome("t1", false));
+
+        // Others bought at our price until too little was left for us, and we missed
+        tracker.record_post("t2", "m1", 0.40, 60.0, post(now));
+        tracker.on_trade("a1", true, 0.40, 50.0, now + Duration::milliseconds(100));
+        assert!(tracker.record_outcome("t2", false));
+        // Too few samples to trust yet
+        assert!(!tracker.is_toxic("m1"));
+
+        // Enough was left for us after their prints: not last in queue
+        tracker.record_post("q1", "m1", 0.40, 20.0, post(now));
+        tracker.on_trade("a1", true, 0.40, 50.0, now + Duration::milliseconds(100));
+        assert!(!tracker.record_outcome("q1", false));
+
+        // A pull after the window doesn't count; a fill never does
+        tracker.record_post("t3", "m1", 0.40, 60.0, post(now));
+        tracker.on_book("a1", &[(0.45, 200.0)], now + Duration::seconds(5));
+        assert!(!tracker.record_outcome("t3", false));
+        tracker.record_post("t4", "m1", 0.40, 60.0, post(now));
+        tracker.on_book("a1", &[(0.45, 200.0)], now);
+        assert!(!tracker.record_outcome("t4", true));
+        assert!(!tracker.is_toxic("m1"));
+
+        // Another settlement of the asset pending right after ours was sent
+        tracker.record_post("r1", "m1", 0.40, 60.0, post(now));
+        tracker.on_pending_fill("a1", now + Duration::milliseconds(300));
+        assert!(tracker.record_outcome("r1", false));
+
+        for i in 0..3 {
+            let trade_id = format!("p{}", i);
+            tracker.record_post(&trade_id, "m1", 0.40, 60.0, post(now));
+            tracker.on_book("a1", &[(0.45, 200.0)], now);
+            tracker.record_outcome(&trade_id, false);
+        }
+        assert!(tracker.is_toxic("m1"));
+        assert_eq!(tracker.extra_edge_bps("m1"), 50);
+        assert_eq!(tracker.extra_edge_bps("m2"), 0);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].samples, 9);
+        assert_eq!(snapshot[0].pulled, 4);
+        assert_eq!(snapshot[0].last_in_queue, 1);
+        assert_eq!(snapshot[0].raced, 1);
+        assert!(snapshot[0].toxic);
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use utoipa::ToSchema;
+
+use crate::config::WhaleConfig;
+
+/// A trade from the activity feed above the whale threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct LargeTrade {
+    /// Outcome traded, "YES" or "NO"
+    pub side: String,
+    pub buy: bool,
+    pub price: f64,
+    pub notional: f64,
+    pub at: DateTime<Utc>,
+}
+
+impl LargeTrade {
+    /// Buying YES or selling NO pushes the market towards YES
+    pub fn toward_yes(&self) -> bool {
+        (self.side == "YES") == self.buy
+    }
+}
+
+/// Large-trade flow of one market over the whale window
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct WhaleStats {
+    pub market_id: String,
+    pub count: usize,
+    pub notional: f64,
+    /// Notional pushing towards YES / towards NO
+    pub yes_notional: f64,
+    pub no_notional: f64,
+}
+
+impl WhaleStats {
+    /// Net direction of the flow, from -1 (all towards NO) to 1 (all towards YES)
+    pub fn bias(&self) -> f64 {
+        if self.notional <= 0.0 {
+            return 0.0;
+        }
+        (self.yes_notional - self.no_notional) / self.notional
+    }
+
+    /// Bias seen from a position on `side` (positive = whales agree)
+    pub fn bias_for(&self, side: &str) -> f64 {
+        if side == "YES" {
+            self.bias()
+        } else {
+            -self.bias()
+        }
+    }
+}
+
+/// Recent large trades per market, fed by the public activity stream
+#[derive(Debug)]
+pub struct WhaleTracker {
+    min_notional_usd: f64,
+    window: Duration,
+    trades: HashMap<String, VecDeque<LargeTrade>>,
+}
+
+impl WhaleTracker {
+    pub fn new(config: &WhaleConfig) -> Self {
+        Self {
+            min_notional_usd: config.min_notional_usd,
+            window: Duration::seconds(config.window_secs.min(i64::MAX as u64) as i64),
+            trades: HashMap::new(),
+        }
+    }
+
+    /// Keep the trade if it's large enough and inside the window. Returns whether it was kept
+    pub fn record(
+        &mut self,
+        market_id: &str,
+        side: &str,
+        buy: bool,
+        price: f64,
+        size: f64,
+        at: DateTime<Utc>,
+    ) -> bool {
+        let notional = price * size;
+        let since = Utc::now() - self.window;
+        if !notional.is_finite() || notional < self.min_notional_usd || at < since {
+            return false;
+        }
+        let trades = self.trades.entry(market_id.to_string()).or_default();
+        trades.push_back(LargeTrade {
+            side: side.to_string(),
+            buy,
+            price,
+            notional,
+            at,
+        });
+        while trades.front().is_some_and(|t| t.at < since) {
+            trades.pop_front();
+        }
+        self.trades
+            .retain(|_, trades| trades.back().is_some_and(|t| t.at >= since));
+        true
+    }
+
+    pub fn stats(&self, market_id: &str, now: DateTime<Utc>) -> Option<WhaleStats> {
+        let since = now - self.window;
+        let mut stats = WhaleStats {
+            market_id: market_id.to_string(),
+            ..Default::default()
+        };
+        for trade in self.trades.get(market_id)?.iter().filter(|t| t.at >= since) {
+            stats.count += 1;
+            stats.notional += trade.notional;
+            if trade.toward_yes() {
+                stats.yes_notional += trade.notional;
+            } else {
+                stats.no_notional += trade.notional;
+            }
+        }
+        (stats.count > 0).then_some(stats)
+    }
+
+    /// Markets with whale activity in the window, largest notional first
+    pub fn all(&self, now: DateTime<Utc>) -> Vec<WhaleStats> {
+        let mut all: Vec<WhaleStats> = self
+            .trades
+            .keys()
+            .filter_map(|market_id| self.stats(market_id, now))
+            .collect();
+        all.sort_by(|a, b| b.notional.total_cmp(&a.notional));
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_direction_and_window() {
+        let mut tracker = WhaleTracker::new(&WhaleConfig {
+            enabled: true,
+            min_notional_usd: 1000.0,
+            window_secs: 600,
+            confidence_weight: 0.2,
+        });
+        let now = Utc::now();
+
+        // Below the threshold
+        assert!(!tracker.record("m1", "YES", true, 0.5, 100.0, now));
+        assert!(tracker.stats("m1", now).is_none());
+
+        assert!(tracker.record("m1", "YES", true, 0.5, 6000.0, now));
+        // Selling NO also pushes towards YES
+        assert!(tracker.record("m1", "NO", false, 0.5, 2000.0, now));
+        assert!(tracker.record("m1", "NO", true, 0.5, 4000.0, now));
+        // Outside the window
+        assert!(!tracker.record("m1", "NO", true, 0.5, 9000.0, now - Duration::hours(1)));
+
+        let stats = tracker.stats("m1", now).unwrap();
+        assert_eq!(stats.count, 3);
+        assert!((stats.notional - 6000.0).abs() < 1e-9);
+        assert!((stats.yes_notional - 4000.0).abs() < 1e-9);
+        assert!((stats.bias() - 1.0 / 3.0).abs() < 1e-9);
+        assert!(stats.bias_for("NO") < 0.0);
+
+        assert!(tracker.stats("m1", now + Duration::hours(1)).is_none());
+        assert_eq!(tracker.all(now).len(), 1);
+    }
+}
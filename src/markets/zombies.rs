@@ -0,0 +1,255 @@
+//! Markets that keep quoting after they're effectively resolved
+//!
+//! A market whose outcome is known often stays open on the CLOB for hours or days, quoting
+//! 0.999 / 0.001 until the resolution lands on-chain. Its books still stream and its quotes are
+//! still evaluated, for nothing. A market is moved to the dormant list once its quotes have sat at
+//! an extreme for a while and either its end date is past or UMA has a resolution proposed for
+//! it. Dormant markets are skipped by the strategies and unsubscribed from the WS, but stay in
+//! the registry so their positions are redeemed as usual. A quote back inside the band (a
+//! disputed proposal, a mispriced end date) wakes the market up.
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use utoipa::ToSchema;
+
+use crate::config::ZombieConfig;
+use crate::polymarket::{MarketData, QuoteQuality};
+
+/// Conditions Gamma reports a UMA resolution proposed (or settled) for, from
+/// `umaResolutionStatus`. Cheap to clone, all clones share the set
+#[derive(Clone, Default)]
+pub struct ResolutionProposals {
+    inner: Arc<RwLock<HashSet<String>>>,
+}
+
+impl ResolutionProposals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the latest status Gamma returned for a condition. A dispute clears it
+    pub fn record(&self, condition_id: &str, status: Option<&str>) {
+        let proposed = status.is_some_and(|status| {
+            matches!(
+                status.trim().to_lowercase().as_str(),
+                "proposed" | "resolved"
+            )
+        });
+        let Ok(mut proposals) = self.inner.write() else {
+            return;
+        };
+        if proposed {
+            proposals.insert(condition_id.to_string());
+        } else {
+            proposals.remove(condition_id);
+        }
+    }
+
+    pub fn contains(&self, condition_id: &str) -> bool {
+        self.inner
+            .read()
+            .map(|proposals| proposals.contains(condition_id))
+            .unwrap_or(false)
+    }
+}
+
+/// A market on the dormant list
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DormantMarket {
+    pub market_id: String,
+    pub question: String,
+    pub yes_price: f64,
+    pub no_price: f64,
+    /// What, besides the stable extreme quotes, marked it resolved
+    pub reason: String,
+    pub since: DateTime<Utc>,
+}
+
+/// A market went on or off the dormant list
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZombieEvent {
+    Dormant,
+    Woke,
+}
+
+/// Classifies markets as resolved-but-open and keeps the dormant list
+pub struct ZombieTracker {
+    config: ZombieConfig,
+    /// When each market's quotes reached the extreme band (dropped when they leave it)
+    extreme_since: HashMap<String, DateTime<Utc>>,
+    dormant: HashMap<String, DormantMarket>,
+}
+
+impl ZombieTracker {
+    pub fn new(config: ZombieConfig) -> Self {
+        Self {
+            config,
+            extreme_since: HashMap::new(),
+            dormant: HashMap::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn is_dormant(&self, market_id: &str) -> bool {
+        self.dormant.contains_key(market_id)
+    }
+
+    /// Either outcome's ask sits at the extreme of the price range
+    fn is_extreme(&self, market: &MarketData) -> bool {
+        let extreme = self.config.extreme_price;
+        [market.yes_price, market.no_price]
+            .into_iter()
+            .filter(|price| QuoteQuality::is_usable(*price))
+            .any(|price| price >= extreme || price <= 1.0 - extreme)
+    }
+
+    /// Classify a market from its latest quotes. `proposed`: a UMA resolution is proposed
+    pub fn observe(
+        &mut self,
+        market: &MarketData,
+        proposed: bool,
+        now: DateTime<Utc>,
+    ) -> Option<ZombieEvent> {
+        if !self.config.enabled {
+            return None;
+        }
+        if !self.is_extreme(market) {
+            self.extreme_since.remove(market.id.as_str());
+            return self
+                .dormant
+                .remove(market.id.as_str())
+                .map(|_| ZombieEvent::Woke);
+        }
+        if self.dormant.contains_key(market.id.as_str()) {
+            return None;
+        }
+
+        let since = *self
+            .extreme_since
+            .entry(market.id.to_string())
+            .or_insert(now);
+        if now - since < Duration::seconds(self.config.stable_secs as i64) {
+            return None;
+        }
+        let past_end = market
+            .end_time
+            .filter(|end| now - *end >= Duration::seconds(self.config.end_grace_secs as i64));
+        let reason = match (proposed, past_end) {
+            (true, _) => "resolution proposed".to_string(),
+            (false, Some(end)) => format!("ended {}", end.format("%Y-%m-%d %H:%M UTC")),
+            (false, None) => return None,
+        };
+        self.dormant.insert(
+            market.id.to_string(),
+            DormantMarket {
+                market_id: market.id.to_string(),
+                question: market.question.clone(),
+                yes_price: market.yes_price,
+                no_price: market.no_price,
+                reason,
+                since: now,
+            },
+        );
+        Some(ZombieEvent::Dormant)
+    }
+
+    pub fn get(&self, market_id: &str) -> Option<&DormantMarket> {
+        self.dormant.get(market_id)
+    }
+
+    /// Drop a market once it resolved (closed markets are skipped anyway)
+    pub fn forget(&mut self, market_id: &str) {
+        self.extreme_since.remove(market_id);
+        self.dormant.remove(market_id);
+    }
+
+    /// The dormant list, longest dormant first
+    pub fn dormant(&self) -> Vec<DormantMarket> {
+        let mut dormant: Vec<DormantMarket> = self.dormant.values().cloned().collect();
+        dormant.sort_by_key(|market| market.since);
+        dormant
+    }
+
+    pub fn len(&self) -> usize {
+        self.dormant.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dormant.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ZombieConfig {
+        ZombieConfig {
+            enabled: true,
+            extreme_price: 0.995,
+            stable_secs: 600,
+            end_grace_secs: 3600,
+            check_interval_secs: 60,
+        }
+    }
+
+    fn market(yes_price: f64, no_price: f64, end_date: &str) -> MarketData {
+        MarketData {
+            id: "0xabc".into(),
+            question: "Will it happen?".to_string(),
+            end_date: Some(end_date.to_string()),
+            yes_price,
+            no_price,
+            ..Default::default()
+        }
+        .with_end_time()
+    }
+
+    #[test]
+    fn test_stable_extreme_quotes_past_the_end_go_dormant() {
+        let mut zombies = ZombieTracker::new(config());
+        let start = "2026-03-02T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let ended = market(0.999, 0.001, "2026-03-01T00:00:00Z");
+
+        assert_eq!(zombies.observe(&ended, false, start), None);
+        let later = start + Duration::minutes(11);
+        assert_eq!(
+            zombies.observe(&ended, false, later),
+            Some(ZombieEvent::Dormant)
+        );
+        assert!(zombies.is_dormant("0xabc"));
+        assert!(zombies.get("0xabc").unwrap().reason.starts_with("ended"));
+
+        // Repriced inside the band: back to the strategies
+        let repriced = market(0.70, 0.32, "2026-03-01T00:00:00Z");
+        assert_eq!(
+            zombies.observe(&repriced, false, later),
+            Some(ZombieEvent::Woke)
+        );
+        assert!(zombies.is_empty());
+    }
+
+    #[test]
+    fn test_needs_a_sign_of_resolution_besides_the_price() {
+        let mut zombies = ZombieTracker::new(config());
+        let start = "2026-03-02T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let later = start + Duration::minutes(30);
+        // A long-dated market at 0.999 is just a favourite
+        let open = market(0.999, 0.001, "2026-12-31T00:00:00Z");
+        zombies.observe(&open, false, start);
+        assert_eq!(zombies.observe(&open, false, later), None);
+
+        let proposals = ResolutionProposals::new();
+        proposals.record("0xabc", Some("proposed"));
+        assert_eq!(
+            zombies.observe(&open, proposals.contains("0xabc"), later),
+            Some(ZombieEvent::Dormant)
+        );
+        proposals.record("0xabc", Some("disputed"));
+        assert!(!proposals.contains("0xabc"));
+    }
+}
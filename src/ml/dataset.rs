@@ -0,0 +1,334 @@
+use anyhow::{Context, Result};
+use arrow_array::builder::{
+    BooleanBuilder, Float32Builder, Float64Builder, StringBuilder, TimestampMillisecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, Duration, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::{ModelInput, FEATURE_NAMES};
+use crate::analytics::resolutions::MarketResolution;
+
+/// Directory of the daily feature files inside the data dir
+pub const FEATURES_DIR: &str = "features";
+
+/// Feature vector of a market at one point in time, as the model would have scored it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureRecord {
+    pub at: DateTime<Utc>,
+    pub market_id: String,
+    /// `FEATURE_NAMES` order
+    pub features: Vec<f32>,
+    /// YES mid (YES ask without a book), the price forward returns are measured from
+    pub yes_mid: f64,
+}
+
+/// Samples the feature vectors of markets predictive entries are evaluated on into
+/// `data_dir/features/YYYY-MM-DD.jsonl`, at most once per market every `sample_secs`
+pub struct FeatureRecorder {
+    dir: PathBuf,
+    sample_secs: i64,
+    last: HashMap<String, DateTime<Utc>>,
+}
+
+impl FeatureRecorder {
+    pub fn new(data_dir: &str, sample_secs: u64) -> Result<Self> {
+        let dir = Path::new(data_dir).join(FEATURES_DIR);
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            sample_secs: sample_secs as i64,
+            last: HashMap::new(),
+        })
+    }
+
+    /// Append a sample unless the market was sampled less than `sample_secs` ago
+    pub fn record(
+        &mut self,
+        market_id: &str,
+        input: &ModelInput,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        if self
+            .last
+            .get(market_id)
+            .is_some_and(|last| (now - *last).num_seconds() < self.sample_secs)
+        {
+            return Ok(());
+        }
+        self.last.insert(market_id.to_string(), now);
+
+        let record = FeatureRecord {
+            at: now,
+            market_id: market_id.to_string(),
+            features: input.features(),
+            yes_mid: input
+                .yes_frame
+                .as_ref()
+                .map(|frame| frame.mid)
+                .unwrap_or(input.yes_ask),
+        };
+        let path = self.dir.join(format!("{}.jsonl", now.format("%Y-%m-%d")));
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        Ok(())
+    }
+}
+
+/// Feature records sampled since `since`, oldest first. Unreadable lines are skipped
+pub fn load_records(data_dir: &str, since: DateTime<Utc>) -> Result<Vec<FeatureRecord>> {
+    let dir = Path::new(data_dir).join(FEATURES_DIR);
+    let mut records = Vec::new();
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(records);
+    };
+    let since_day = since.format("%Y-%m-%d").to_string();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(day) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl")
+            || day < since_day.as_str()
+        {
+            continue;
+        }
+        let file = File::open(&path)?;
+        records.extend(
+            BufReader::new(file)
+                .lines()
+                .map_while(|line| line.ok())
+                .filter_map(|line| serde_json::from_str::<FeatureRecord>(&line).ok())
+                .filter(|record| record.at >= since),
+        );
+    }
+    records.sort_by_key(|record| record.at);
+    Ok(records)
+}
+
+/// One market's feature records between `from` and `to`, oldest first, read a line at a time
+/// from the day files the span covers. Unreadable lines are skipped
+pub fn market_records(
+    data_dir: &str,
+    market_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<FeatureRecord>> {
+    let dir = Path::new(data_dir).join(FEATURES_DIR);
+    let mut records = Vec::new();
+    let mut day = from.date_naive();
+    while day <= to.date_naive() {
+        let path = dir.join(format!("{}.jsonl", day.format("%Y-%m-%d")));
+        if let Ok(file) = File::open(&path) {
+            for line in BufReader::new(file).lines().map_while(|line| line.ok()) {
+                // Cheap pre-filter: most lines are other markets
+                if !line.contains(market_id) {
+                    continue;
+                }
+                let Ok(record) = serde_json::from_str::<FeatureRecord>(&line) else {
+                    continue;
+                };
+                if record.market_id == market_id && record.at >= from && record.at <= to {
+                    records.push(record);
+                }
+            }
+        }
+        let Some(next) = day.succ_opt() else {
+            break;
+        };
+        day = next;
+    }
+    records.sort_by_key(|record| record.at);
+    Ok(records)
+}
+
+/// A feature record with what happened next
+#[derive(Debug, Clone)]
+pub struct TrainingRow {
+    pub record: FeatureRecord,
+    /// YES mid change over each horizon (None when nothing was observed that far ahead)
+    pub forward_returns: Vec<Option<f64>>,
+    /// None while the market is unresolved (or outside the resolution dataset)
+    pub resolved_yes: Option<bool>,
+}
+
+/// Join feature records with the YES mid `horizons` later and with the markets' outcomes.
+/// The mid at a horizon is the first later sample of the market at or after it; past the end
+/// date of a resolved market it is the payout (1 or 0)
+pub fn label(
+    records: Vec<FeatureRecord>,
+    resolutions: &[MarketResolution],
+    horizons: &[Duration],
+) -> Vec<TrainingRow> {
+    let outcomes: HashMap<&str, &MarketResolution> = resolutions
+        .iter()
+        .map(|r| (r.market_id.as_str(), r))
+        .collect();
+    let mut by_market: HashMap<&str, Vec<&FeatureRecord>> = HashMap::new();
+    for record in &records {
+        by_market.entry(&record.market_id).or_default().push(record);
+    }
+
+    records
+        .iter()
+        .map(|record| {
+            let resolution = outcomes.get(record.market_id.as_str());
+            let later = &by_market[record.market_id.as_str()];
+            let forward_returns = horizons
+                .iter()
+                .map(|horizon| {
+                    let target = record.at + *horizon;
+                    let mid = match resolution {
+                        Some(r) if r.end_date <= target => Some(payout(r)),
+                        _ => later.iter().find(|r| r.at >= target).map(|r| r.yes_mid),
+                    };
+                    mid.map(|mid| mid - record.yes_mid)
+                })
+                .collect();
+            TrainingRow {
+                record: record.clone(),
+                forward_returns,
+                resolved_yes: resolution.map(|r| r.winner == "YES"),
+            }
+        })
+        .collect()
+}
+
+fn payout(resolution: &MarketResolution) -> f64 {
+    if resolution.winner == "YES" {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Write the training set as Parquet: `at`, `market_id`, one column per feature, `yes_mid`,
+/// `fwd_return_<secs>s` per horizon and `resolved_yes`
+pub fn write_parquet(path: &str, rows: &[TrainingRow], horizons: &[Duration]) -> Result<()> {
+    let mut fields = vec![
+        Field::new(
+            "at",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("market_id", DataType::Utf8, false),
+    ];
+    fields.extend(
+        FEATURE_NAMES
+            .iter()
+            .map(|name| Field::new(*name, DataType::Float32, false)),
+    );
+    fields.push(Field::new("yes_mid", DataType::Float64, false));
+    fields.extend(horizons.iter().map(|h| {
+        Field::new(
+            format!("fwd_return_{}s", h.num_seconds()),
+            DataType::Float64,
+            true,
+        )
+    }));
+    fields.push(Field::new("resolved_yes", DataType::Boolean, true));
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut at = TimestampMillisecondBuilder::new().with_timezone("UTC");
+    let mut market_id = StringBuilder::new();
+    let mut features: Vec<Float32Builder> = FEATURE_NAMES
+        .iter()
+        .map(|_| Float32Builder::new())
+        .collect();
+    let mut yes_mid = Float64Builder::new();
+    let mut forward: Vec<Float64Builder> = horizons.iter().map(|_| Float64Builder::new()).collect();
+    let mut resolved_yes = BooleanBuilder::new();
+    for row in rows {
+        at.append_value(row.record.at.timestamp_millis());
+        market_id.append_value(&row.record.market_id);
+        for (i, column) in features.iter_mut().enumerate() {
+            column.append_value(row.record.features.get(i).copied().unwrap_or(0.0));
+        }
+        yes_mid.append_value(row.record.yes_mid);
+        for (column, value) in forward.iter_mut().zip(&row.forward_returns) {
+            column.append_option(*value);
+        }
+        resolved_yes.append_option(row.resolved_yes);
+    }
+
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(at.finish()), Arc::new(market_id.finish())];
+    columns.extend(
+        features
+            .iter_mut()
+            .map(|column| Arc::new(column.finish()) as ArrayRef),
+    );
+    columns.push(Arc::new(yes_mid.finish()));
+    columns.extend(
+        forward
+            .iter_mut()
+            .map(|column| Arc::new(column.finish()) as ArrayRef),
+    );
+    columns.push(Arc::new(resolved_yes.finish()));
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let file = File::create(path).with_context(|| format!("Failed to create {}", path))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(market_id: &str, secs: i64, yes_mid: f64) -> FeatureRecord {
+        FeatureRecord {
+            at: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+            market_id: market_id.to_string(),
+            features: vec![0.0; FEATURE_NAMES.len()],
+            yes_mid,
+        }
+    }
+
+    #[test]
+    fn test_forward_returns_and_resolution_labels() {
+        let records = vec![
+            record("m1", 0, 0.60),
+            record("m2", 5, 0.30),
+            record("m1", 12, 0.66),
+            record("m1", 70, 0.70),
+        ];
+        let resolution = MarketResolution {
+            market_id: "m1".to_string(),
+            question: "Q?".to_string(),
+            end_date: DateTime::from_timestamp(1_700_000_100, 0).unwrap(),
+            winner: "YES".to_string(),
+            resolved_at: DateTime::from_timestamp(1_700_000_200, 0).unwrap(),
+            trajectory: Vec::new(),
+        };
+        let horizons = [Duration::seconds(10), Duration::seconds(60)];
+        let rows = label(records, &[resolution], &horizons);
+
+        assert_eq!(rows.len(), 4);
+        // First m1 sample: mid 0.66 after 12s, 0.70 after 70s
+        assert!((rows[0].forward_returns[0].unwrap() - 0.06).abs() < 1e-9);
+        assert!((rows[0].forward_returns[1].unwrap() - 0.10).abs() < 1e-9);
+        assert_eq!(rows[0].resolved_yes, Some(true));
+        // m2 is never seen again nor resolved
+        assert_eq!(rows[1].forward_returns, vec![None, None]);
+        assert_eq!(rows[1].resolved_yes, None);
+        // Last m1 sample: the market ends within 60s and pays 1
+        assert_eq!(rows[3].forward_returns[0], None);
+        assert!((rows[3].forward_returns[1].unwrap() - 0.30).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,156 @@
+//! Signal scoring with models trained offline
+//!
+//! A model maps the feature vector of a market (`ModelInput::features`, laid out as
+//! `FEATURE_NAMES`) to the probability that it resolves YES. Strategies only see the
+//! `SignalScorer` trait; the ONNX backend is behind the `onnx` feature, so a new model is
+//! deployed by pointing `PREDICTIVE_MODEL_PATH` at it.
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::markets::FeatureFrame;
+
+pub mod dataset;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+
+pub use dataset::{FeatureRecord, FeatureRecorder, TrainingRow};
+#[cfg(feature = "onnx")]
+pub use onnx::OnnxScorer;
+
+/// Order flow windows in the feature vector (the first ones of `FEATURE_WINDOWS_MS`)
+pub const MODEL_WINDOWS: usize = 3;
+
+/// Column names of the feature vector, in order. Models have to be trained on this layout and
+/// with the `FEATURE_WINDOWS_MS` they are deployed with
+pub const FEATURE_NAMES: [&str; 14] = [
+    "has_book",
+    "yes_mid",
+    "yes_spread",
+    "yes_depth_imbalance",
+    "yes_ofi_w0",
+    "yes_mid_change_w0",
+    "yes_ofi_w1",
+    "yes_mid_change_w1",
+    "yes_ofi_w2",
+    "yes_mid_change_w2",
+    "secs_to_expiry",
+    "yes_log_moneyness",
+    "yes_ask",
+    "no_ask",
+];
+
+/// What a model sees of one market at decision time
+#[derive(Debug, Clone, Default)]
+pub struct ModelInput {
+    /// Order flow features of the YES token (None before its first book)
+    pub yes_frame: Option<FeatureFrame>,
+    pub secs_to_expiry: f64,
+    /// ln(spot / strike) oriented so that positive favours YES (None without an external price)
+    pub yes_log_moneyness: Option<f64>,
+    pub yes_ask: f64,
+    pub no_ask: f64,
+}
+
+impl ModelInput {
+    /// Feature vector in `FEATURE_NAMES` order; missing values are 0
+    pub fn features(&self) -> Vec<f32> {
+        let mut features = Vec::with_capacity(FEATURE_NAMES.len());
+        match &self.yes_frame {
+            Some(frame) => {
+                features.extend([1.0, frame.mid, frame.spread, frame.depth_imbalance]);
+                for i in 0..MODEL_WINDOWS {
+                    let window = frame.windows.get(i);
+                    features.push(window.map_or(0.0, |w| w.normalized_ofi));
+                    features.push(window.map_or(0.0, |w| w.mid_change));
+                }
+            }
+            None => features.extend([0.0; 4 + 2 * MODEL_WINDOWS]),
+        }
+        features.extend([
+            self.secs_to_expiry,
+            self.yes_log_moneyness.unwrap_or(0.0),
+            self.yes_ask,
+            self.no_ask,
+        ]);
+        features.into_iter().map(|f| f as f32).collect()
+    }
+}
+
+/// A trained model scoring feature vectors
+pub trait SignalScorer: Send + Sync {
+    /// Probability in [0, 1] that the market resolves YES
+    fn score(&self, features: &[f32]) -> Result<f64>;
+    /// Model file, for logs
+    fn name(&self) -> &str;
+}
+
+/// Load the model at `path`; the format is picked by extension
+pub fn load_scorer(path: &str) -> Result<Arc<dyn SignalScorer>> {
+    if !path.ends_with(".onnx") {
+        anyhow::bail!("Unsupported model {} (expected an .onnx file)", path);
+    }
+    #[cfg(feature = "onnx")]
+    {
+        Ok(Arc::new(OnnxScorer::load(path)?))
+    }
+    #[cfg(not(feature = "onnx"))]
+    {
+        anyhow::bail!("PREDICTIVE_MODEL_PATH needs a build with `--features onnx`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markets::WindowFeatures;
+
+    #[test]
+    fn test_feature_vector_layout() {
+        let empty = ModelInput {
+            secs_to_expiry: 90.0,
+            yes_ask: 0.6,
+            no_ask: 0.42,
+            ..Default::default()
+        };
+        let features = empty.features();
+        assert_eq!(features.len(), FEATURE_NAMES.len());
+        assert_eq!(features[0], 0.0);
+        assert_eq!(features[10], 90.0);
+
+        let window = |window_ms, normalized_ofi| WindowFeatures {
+            window_ms,
+            events: 1,
+            ofi: 0.0,
+            normalized_ofi,
+            bid_depletion: 0.0,
+            ask_depletion: 0.0,
+            mid_change: 0.01,
+        };
+        let input = ModelInput {
+            yes_frame: Some(FeatureFrame {
+                asset_id: "yes".to_string(),
+                market_id: "m1".to_string(),
+                side: "YES".to_string(),
+                updated_ms: 0,
+                best_bid: 0.58,
+                best_ask: 0.6,
+                bid_size: 100.0,
+                ask_size: 50.0,
+                mid: 0.59,
+                spread: 0.02,
+                depth_imbalance: 0.3,
+                // Only two windows configured: the third is padded
+                windows: vec![window(1_000, 1.5), window(10_000, 0.5)],
+            }),
+            yes_log_moneyness: Some(0.002),
+            ..empty
+        };
+        let features = input.features();
+        assert_eq!(features.len(), FEATURE_NAMES.len());
+        let column = |name| features[FEATURE_NAMES.iter().position(|n| *n == name).unwrap()];
+        assert_eq!(column("has_book"), 1.0);
+        assert_eq!(column("yes_ofi_w0"), 1.5);
+        assert_eq!(column("yes_ofi_w2"), 0.0);
+        assert_eq!(column("yes_log_moneyness"), 0.002);
+    }
+}
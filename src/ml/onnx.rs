@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use ort::session::Session;
+use ort::value::Tensor;
+use std::sync::Mutex;
+use tracing::info;
+
+use super::{SignalScorer, FEATURE_NAMES};
+
+/// ONNX model run in-process with ONNX Runtime. Takes one float tensor of shape
+/// `[1, FEATURE_NAMES.len()]`; the first output is read as P(YES), or, when it holds two values
+/// per row (a classifier's `[P(NO), P(YES)]`), its second value
+pub struct OnnxScorer {
+    path: String,
+    // Runs need exclusive access to the session
+    session: Mutex<Session>,
+}
+
+impl OnnxScorer {
+    pub fn load(path: &str) -> Result<Self> {
+        let session = Session::builder()
+            .and_then(|builder| builder.commit_from_file(path))
+            .with_context(|| format!("Failed to load ONNX model {}", path))?;
+        info!(
+            "🧠 Loaded ONNX model {} ({} inputs, {} outputs)",
+            path,
+            session.inputs.len(),
+            session.outputs.len()
+        );
+        Ok(Self {
+            path: path.to_string(),
+            session: Mutex::new(session),
+        })
+    }
+}
+
+impl SignalScorer for OnnxScorer {
+    fn score(&self, features: &[f32]) -> Result<f64> {
+        if features.len() != FEATURE_NAMES.len() {
+            anyhow::bail!(
+                "Expected {} features, got {}",
+                FEATURE_NAMES.len(),
+                features.len()
+            );
+        }
+        let input = Tensor::from_array(([1usize, features.len()], features.to_vec()))?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|_| anyhow::anyhow!("ONNX session lock poisoned"))?;
+        let outputs = session.run(ort::inputs![input])?;
+        let (_, values) = outputs[0].try_extract_tensor::<f32>()?;
+        let probability = match values {
+            [p_yes] => *p_yes,
+            [_, p_yes] => *p_yes,
+            _ => anyhow::bail!("Unexpected model output of {} values", values.len()),
+        };
+        Ok((probability as f64).clamp(0.0, 1.0))
+    }
+
+    fn name(&self) -> &str {
+        &self.path
+    }
+}
@@ -0,0 +1,193 @@
+//! Self-monitoring of long runs
+//!
+//! Spawned retries and per-market maps grow with every market seen, so leaks only show after
+//! hours. Every `SELF_MONITOR_INTERVAL_MINS` the agent logs its resident memory, live tokio
+//! tasks, map sizes and channel depths with their growth since the previous report, and raises
+//! an alert for each configured limit it is over.
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::config::SelfMonitorConfig;
+
+/// Resource usage of the agent at one point in time
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelfReport {
+    /// None off Linux
+    pub rss_mb: Option<f64>,
+    /// Live tokio tasks (spawned retries, fill checks, feeds...)
+    pub tasks: usize,
+    /// Registry markets / asset id -> market entries
+    pub markets: usize,
+    pub assets: usize,
+    /// Markets in the lifecycle tracker (everything seen this run and restored ones)
+    pub seen_markets: usize,
+    pub subscriptions: usize,
+    pub pending_jobs: usize,
+    pub metadata_retries: usize,
+    /// Queued items per channel
+    pub ws_queue_depth: usize,
+    pub retry_channel_depth: usize,
+    pub new_market_channel_depth: usize,
+}
+
+impl SelfReport {
+    fn channel_depth(&self) -> usize {
+        self.ws_queue_depth
+            .max(self.retry_channel_depth)
+            .max(self.new_market_channel_depth)
+    }
+}
+
+/// Logs self reports and checks them against the configured limits
+pub struct SelfMonitor {
+    config: SelfMonitorConfig,
+    previous: Option<SelfReport>,
+}
+
+impl SelfMonitor {
+    pub fn new(config: SelfMonitorConfig) -> Self {
+        Self {
+            config,
+            previous: None,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.interval_mins > 0
+    }
+
+    /// Limits `report` is over, as alert messages (0 limits are off)
+    pub fn breaches(&self, report: &SelfReport) -> Vec<String> {
+        let mut breaches = Vec::new();
+        let mut check = |name: &str, value: f64, limit: f64| {
+            if limit > 0.0 && value > limit {
+                breaches.push(format!("{} {:.0} over limit {:.0}", name, value, limit));
+            }
+        };
+        if let Some(rss_mb) = report.rss_mb {
+            check("RSS (MB)", rss_mb, self.config.max_rss_mb);
+        }
+        check(
+            "tokio tasks",
+            report.tasks as f64,
+            self.config.max_tasks as f64,
+        );
+        check(
+            "registry markets",
+            report.markets as f64,
+            self.config.max_markets as f64,
+        );
+        check(
+            "seen markets",
+            report.seen_markets as f64,
+            self.config.max_markets as f64,
+        );
+        check(
+            "registry assets",
+            report.assets as f64,
+            self.config.max_assets as f64,
+        );
+        check(
+            "channel depth",
+            report.channel_depth() as f64,
+            self.config.max_channel_depth as f64,
+        );
+        breaches
+    }
+
+    /// Log `report` with its growth since the last one and alert on breached limits
+    pub fn report(&mut self, report: SelfReport) -> Vec<String> {
+        let previous = self.previous.take().unwrap_or_else(|| report.clone());
+        let delta = |now: usize, before: usize| now as i64 - before as i64;
+        info!(
+            "🩻 Self: RSS {} | tasks {} ({:+}) | markets {} ({:+}) | assets {} ({:+}) | seen {} ({:+}) | subscriptions {} | jobs {} | retries {} | queues ws {} / retry {} / new-market {}",
+            report
+                .rss_mb
+                .map(|mb| format!(
+                    "{:.0}MB ({:+.0})",
+                    mb,
+                    mb - previous.rss_mb.unwrap_or(mb)
+                ))
+                .unwrap_or_else(|| "n/a".to_string()),
+            report.tasks,
+            delta(report.tasks, previous.tasks),
+            report.markets,
+            delta(report.markets, previous.markets),
+            report.assets,
+            delta(report.assets, previous.assets),
+            report.seen_markets,
+            delta(report.seen_markets, previous.seen_markets),
+            report.subscriptions,
+            report.pending_jobs,
+            report.metadata_retries,
+            report.ws_queue_depth,
+            report.retry_channel_depth,
+            report.new_market_channel_depth
+        );
+        let breaches = self.breaches(&report);
+        for breach in &breaches {
+            error!("🚨 ALERT: {}", breach);
+        }
+        self.previous = Some(report);
+        breaches
+    }
+}
+
+/// Resident set size in MB (Linux only)
+pub fn rss_mb() -> Option<f64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kb: f64 = status
+        .lines()
+        .find(|line| line.starts_with("VmRSS:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()?;
+    Some(kb / 1024.0)
+}
+
+/// Live tasks of the current tokio runtime (0 outside one)
+pub fn alive_tasks() -> usize {
+    tokio::runtime::Handle::try_current()
+        .map(|handle| handle.metrics().num_alive_tasks())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_raise_alerts() {
+        let mut monitor = SelfMonitor::new(SelfMonitorConfig {
+            interval_mins: 5,
+            max_rss_mb: 1_024.0,
+            max_tasks: 500,
+            max_markets: 0,
+            max_assets: 20_000,
+            max_channel_depth: 1_000,
+        });
+        let report = SelfReport {
+            rss_mb: Some(512.0),
+            tasks: 120,
+            markets: 50_000,
+            assets: 100_000,
+            retry_channel_depth: 1_000,
+            ..Default::default()
+        };
+        // Unlimited markets; the channel is at, not over, its limit
+        let breaches = monitor.report(report.clone());
+        assert_eq!(breaches, vec!["registry assets 100000 over limit 20000"]);
+
+        let leaking = SelfReport {
+            rss_mb: Some(2_048.0),
+            tasks: 900,
+            assets: 10,
+            ..report
+        };
+        let breaches = monitor.report(leaking);
+        assert_eq!(breaches.len(), 2);
+        assert!(breaches[0].starts_with("RSS (MB) 2048"));
+        assert!(breaches[1].starts_with("tokio tasks 900"));
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::analytics::session::load_sessions;
+use crate::analytics::{
+    DriftTracker, EdgeRealizationTracker, ExecutionDriftTracker, IndexingLatencyTracker,
+    PnLTracker, PostmortemLog,
+};
+use crate::api::{self, ApiAuth, ApiState, SummaryCache};
+use crate::config::Config;
+use crate::logging;
+use crate::markets::{
+    ExpirationWatchlist, LifecycleTracker, MarketRegistry, PinnedMarkets, SubscriptionSet,
+};
+use crate::polymarket::CircuitBreakers;
+
+/// Read-only observer: serve the API from the data dir of a trading instance running elsewhere
+/// (shared volume, rsync, ...), so the dashboard doesn't compete with it for CPU or network
+///
+/// Served from the persisted files: markets, session, edge realization, paper/live drift, PnL
+/// buckets, the trade journal and completed indexing latencies. Live-only state (lifecycle,
+/// breakers, subscriptions, WS bandwidth, open positions, opportunities) is empty or 404.
+/// Nothing is written to the data dir.
+pub async fn run(config: Config) -> Result<()> {
+    let data_dir = config.agent.data_dir.clone();
+    let port = config.agent.api_port;
+    if port == 0 {
+        anyhow::bail!("Observer mode serves the API: set API_PORT");
+    }
+
+    // The live instance's latest session (config hash, start time)
+    let session = load_sessions(&data_dir)?.pop().with_context(|| {
+        format!(
+            "No session recorded in {} - point DATA_DIR at a trading instance's data dir",
+            data_dir
+        )
+    })?;
+    info!(
+        "🔭 Observing session {} (config {}) in {}",
+        session.session_id, session.config_hash, data_dir
+    );
+
+    let registry = MarketRegistry::load(&data_dir);
+    let mut pnl_tracker = PnLTracker::new(0.0);
+    pnl_tracker.set_performance_config(config.performance.clone());
+    pnl_tracker.load_calendar(&data_dir);
+    pnl_tracker.load_journal(&data_dir);
+
+    let state = ApiState {
+        indexing_latency: Arc::new(Mutex::new(IndexingLatencyTracker::load_completed(
+            &data_dir,
+        ))),
+        lifecycle: Arc::new(Mutex::new(LifecycleTracker::new())),
+        registry,
+        session: Arc::new(session),
+        opportunities: None,
+        edge_realization: Arc::new(Mutex::new(EdgeRealizationTracker::load(&data_dir))),
+        execution_drift: Arc::new(Mutex::new(ExecutionDriftTracker::load(&data_dir))),
+        postmortems: Arc::new(Mutex::new(PostmortemLog::load(&data_dir))),
+        settlement: None,
+        margin: None,
+        breakers: Arc::new(CircuitBreakers::new(
+            config.polymarket.circuit_breaker.clone(),
+        )),
+        subscriptions: Arc::new(Mutex::new(SubscriptionSet::new(0))),
+        pnl_tracker: Arc::new(Mutex::new(pnl_tracker)),
+        paper_pnl: None,
+        watchlist: Arc::new(Mutex::new(ExpirationWatchlist::new(0))),
+        pins: Arc::new(Mutex::new(PinnedMarkets::load(&data_dir))),
+        market_lookup: None,
+        anomaly_guard: None,
+        whales: None,
+        zombies: None,
+        series: None,
+        fee_rates: None,
+        missed_windows: None,
+        hedger: None,
+        toxicity: None,
+        profiles: None,
+        drift: Some(Arc::new(Mutex::new(DriftTracker::load(&data_dir)))),
+        ws_bandwidth: None,
+        ws_sequencing: None,
+        ws_control: None,
+        log_levels: logging::levels(),
+        price_feed: None,
+        llm_costs: None,
+        scheduler: None,
+        features: None,
+        failover: None,
+        data_dir: data_dir.clone(),
+        read_only: true,
+        // Control calls are refused here anyway; audit them to the log only
+        auth: Arc::new(ApiAuth::new(&config.agent, None)),
+        summary: SummaryCache::new(config.agent.api_summary_cache_secs),
+    };
+
+    let refresh = Duration::from_secs(config.agent.observer_refresh_secs.max(1));
+    let refreshed = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(refresh);
+        interval.tick().await; // Just loaded
+        loop {
+            interval.tick().await;
+            reload(&refreshed, &data_dir);
+        }
+    });
+
+    api::serve(state, &config.agent.api_bind, port).await
+}
+
+/// Re-read everything the trading instance persists
+fn reload(state: &ApiState, data_dir: &str) {
+    if let Err(e) = state.registry.reload() {
+        warn!("⚠️ Market registry not reloaded: {}", e);
+    }
+    if let Ok(mut tracker) = state.edge_realization.lock() {
+        *tracker = EdgeRealizationTracker::load(data_dir);
+    }
+    if let Ok(mut tracker) = state.execution_drift.lock() {
+        *tracker = ExecutionDriftTracker::load(data_dir);
+    }
+    if let Ok(mut log) = state.postmortems.lock() {
+        *log = PostmortemLog::load(data_dir);
+    }
+    if let Ok(mut pins) = state.pins.lock() {
+        *pins = PinnedMarkets::load(data_dir);
+    }
+    if let Some(Ok(mut tracker)) = state.drift.as_ref().map(|d| d.lock()) {
+        *tracker = DriftTracker::load(data_dir);
+    }
+    if let Ok(mut tracker) = state.pnl_tracker.lock() {
+        tracker.load_calendar(data_dir);
+        tracker.load_journal(data_dir);
+    }
+    if let Ok(mut tracker) = state.indexing_latency.lock() {
+        *tracker = IndexingLatencyTracker::load_completed(data_dir);
+    }
+}
@@ -0,0 +1,381 @@
+use alloy::signers::local::PrivateKeySigner;
+use anyhow::{Context, Result};
+use polymarket_client_sdk::POLYGON;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::config::{CircuitBreakerConfig, PolymarketConfig};
+use crate::polymarket::{MarketInterface, PolymarketClient};
+
+/// Risk appetite presets offered by the `init` wizard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskPreset {
+    Conservative,
+    Standard,
+    Aggressive,
+}
+
+/// Settings a preset controls
+#[derive(Debug, Clone, Copy)]
+pub struct PresetValues {
+    pub min_edge_bps: i32,
+    pub max_position_size_usd: f64,
+    pub kelly_fraction: f64,
+    pub max_position_pct: f64,
+    pub max_position_size_pct: f64,
+    pub max_portfolio_exposure_pct: f64,
+    pub stop_loss_pct: f64,
+}
+
+impl RiskPreset {
+    pub fn parse(preset: &str) -> Option<Self> {
+        match preset.trim().to_lowercase().as_str() {
+            "conservative" | "c" => Some(RiskPreset::Conservative),
+            "standard" | "s" | "" => Some(RiskPreset::Standard),
+            "aggressive" | "a" => Some(RiskPreset::Aggressive),
+            _ => None,
+        }
+    }
+
+    pub fn values(&self) -> PresetValues {
+        match self {
+            RiskPreset::Conservative => PresetValues {
+                min_edge_bps: 50,
+                max_position_size_usd: 5.0,
+                kelly_fraction: 0.1,
+                max_position_pct: 0.05,
+                max_position_size_pct: 2.0,
+                max_portfolio_exposure_pct: 25.0,
+                stop_loss_pct: 5.0,
+            },
+            RiskPreset::Standard => PresetValues {
+                min_edge_bps: 20,
+                max_position_size_usd: 10.0,
+                kelly_fraction: 0.25,
+                max_position_pct: 0.10,
+                max_position_size_pct: 5.0,
+                max_portfolio_exposure_pct: 50.0,
+                stop_loss_pct: 10.0,
+            },
+            RiskPreset::Aggressive => PresetValues {
+                min_edge_bps: 10,
+                max_position_size_usd: 50.0,
+                kelly_fraction: 0.5,
+                max_position_pct: 0.20,
+                max_position_size_pct: 10.0,
+                max_portfolio_exposure_pct: 80.0,
+                stop_loss_pct: 20.0,
+            },
+        }
+    }
+}
+
+/// Answers collected by the wizard
+#[derive(Debug, Clone)]
+pub struct InitAnswers {
+    pub private_key: String,
+    pub ws_rpc: String,
+    pub paper_trading: bool,
+    pub preset: RiskPreset,
+}
+
+/// Interactive `init`: ask for key, RPC, mode and risk preset, check the wallet, write `output`
+pub async fn run_wizard(output: &str) -> Result<()> {
+    println!("🧭 Polymarket sniper setup");
+    println!("   Press Enter to accept the [default].");
+    println!();
+
+    if Path::new(output).exists()
+        && !confirm(&format!("{} already exists. Overwrite?", output), false)?
+    {
+        println!("⏭️  Keeping existing {}", output);
+        return Ok(());
+    }
+
+    // 1. Wallet
+    let key = ask_secret("Polygon private key (not shown; leave empty to generate a new wallet)")?;
+    let signer = if key.is_empty() {
+        let signer = PrivateKeySigner::random();
+        println!("🆕 Generated a new wallet - back up the key written to {}", output);
+        signer
+    } else {
+        PrivateKeySigner::from_str(&key)
+            .map_err(|e| anyhow::anyhow!("Invalid private key: {}", e))?
+    };
+    let private_key = format!("0x{}", alloy::primitives::hex::encode(signer.to_bytes()));
+
+    let safe_wallet = polymarket_client_sdk::derive_safe_wallet(signer.address(), POLYGON);
+    println!("🔑 Signer address: {}", signer.address());
+    match safe_wallet {
+        Some(addr) => println!("🔐 Polymarket Safe wallet (fund this one): {}", addr),
+        None => println!("⚠️ Could not derive the Polymarket Safe wallet"),
+    }
+
+    // 2. RPC
+    let ws_rpc = ask(
+        "Polygon WebSocket RPC (wss://...)",
+        "wss://polygon-mainnet.g.alchemy.com/v2/your-key",
+    )?;
+
+    // 3. Mode
+    let paper_trading = loop {
+        match ask("Mode: paper or live", "paper")?
+            .to_lowercase()
+            .as_str()
+        {
+            "paper" => break true,
+            "live" => break false,
+            other => println!("   Unknown mode '{}', expected paper or live", other),
+        }
+    };
+
+    // 4. Risk appetite
+    let preset = loop {
+        let answer = ask("Risk preset: conservative, standard or aggressive", "standard")?;
+        match RiskPreset::parse(&answer) {
+            Some(preset) => break preset,
+            None => println!("   Unknown preset '{}'", answer),
+        }
+    };
+
+    // 5. Balance check (best effort, public RPCs)
+    println!();
+    println!("💰 Checking wallet balance...");
+    let polymarket = PolymarketConfig {
+        api_key: String::new(),
+        secret: String::new(),
+        passphrase: String::new(),
+        host: "https://clob.polymarket.com".to_string(),
+        proxy_address: safe_wallet.map(|a| a.to_string()),
+        auth_max_retries: 3,
+        circuit_breaker: CircuitBreakerConfig {
+            enabled: false,
+            failure_threshold: 5,
+            cooldown_secs: 30,
+        },
+        collateral_token: None,
+    };
+    match PolymarketClient::new(&polymarket, true, Some(private_key.clone())) {
+        Ok(client) => match client.get_balance().await {
+            Ok(balance) if balance > 0.0 => println!("✅ USDC balance: ${:.2}", balance),
+            Ok(_) if !paper_trading => {
+                println!("⚠️ USDC balance is $0.00 - fund the Safe wallet before trading live")
+            }
+            Ok(_) => println!("ℹ️ USDC balance is $0.00 (fine for paper trading)"),
+            Err(e) => println!("⚠️ Balance check failed: {}", e),
+        },
+        Err(e) => println!("⚠️ Balance check skipped: {}", e),
+    }
+
+    let answers = InitAnswers {
+        private_key,
+        ws_rpc,
+        paper_trading,
+        preset,
+    };
+    write_private(output, &render_env(&answers))
+        .with_context(|| format!("Failed to write {}", output))?;
+
+    println!();
+    println!(
+        "📝 Wrote {} ({:?} preset, {} mode)",
+        output,
+        preset,
+        mode_name(paper_trading)
+    );
+    println!("   Review it, then start the bot with `cargo run --release`.");
+    if !paper_trading {
+        println!("🚨 LIVE mode places real orders. Keep {} private.", output);
+    }
+
+    Ok(())
+}
+
+fn mode_name(paper_trading: bool) -> &'static str {
+    if paper_trading {
+        "paper"
+    } else {
+        "live"
+    }
+}
+
+/// Render a commented .env for the given answers
+pub fn render_env(answers: &InitAnswers) -> String {
+    let p = answers.preset.values();
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# Generated by `init` ({:?} preset)", answers.preset);
+    let _ = writeln!(out, "# Every setting is documented in .env.example");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Wallet - KEEP THIS FILE PRIVATE");
+    let _ = writeln!(out, "# Fund the Polymarket Safe wallet derived from this key");
+    let _ = writeln!(out, "POLYGON_PRIVATE_KEY={}", answers.private_key);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Polymarket CLOB (API credentials are derived from the key)");
+    let _ = writeln!(out, "POLYMARKET_HOST=https://clob.polymarket.com");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Polygon WebSocket RPC (real-time market detection)");
+    let _ = writeln!(out, "POLYGON_WS_RPC={}", answers.ws_rpc);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Mode: paper trading logs orders without sending them");
+    let _ = writeln!(out, "PAPER_TRADING={}", answers.paper_trading);
+    let _ = writeln!(out, "SIMULATION_MODE=false");
+    let _ = writeln!(out, "DATA_DIR=data");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Strategy");
+    let _ = writeln!(out, "MIN_EDGE_BPS={}", p.min_edge_bps);
+    let _ = writeln!(out, "MAX_POSITION_SIZE_USD={:.1}", p.max_position_size_usd);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Position sizing (fraction of full Kelly, % of capital per trade)");
+    let _ = writeln!(out, "SIZING_MODE=kelly");
+    let _ = writeln!(out, "KELLY_FRACTION={}", p.kelly_fraction);
+    let _ = writeln!(out, "MIN_POSITION_PCT=0.01");
+    let _ = writeln!(out, "MAX_POSITION_PCT={}", p.max_position_pct);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Risk management");
+    let _ = writeln!(out, "MAX_POSITION_SIZE_PCT={:.1}", p.max_position_size_pct);
+    let _ = writeln!(
+        out,
+        "MAX_PORTFOLIO_EXPOSURE_PCT={:.1}",
+        p.max_portfolio_exposure_pct
+    );
+    let _ = writeln!(out, "STOP_LOSS_PCT={:.1}", p.stop_loss_pct);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# Logging");
+    let _ = writeln!(out, "RUST_LOG=info,polymarket_hft_agent=debug");
+
+    out
+}
+
+fn ask(question: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("❓ {}: ", question);
+    } else {
+        print!("❓ {} [{}]: ", question, default);
+    }
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        anyhow::bail!("Setup aborted (end of input)");
+    }
+    let answer = line.trim();
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer.to_string()
+    })
+}
+
+/// `ask` for a secret: what's typed isn't echoed to the terminal
+fn ask_secret(question: &str) -> Result<String> {
+    print!("❓ {}: ", question);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    let read = {
+        let _echo_off = EchoOff::new();
+        io::stdin().read_line(&mut line)
+    };
+    if read? == 0 {
+        anyhow::bail!("Setup aborted (end of input)");
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Stdin's terminal echo, turned off until dropped (nothing to do when stdin isn't a terminal)
+struct EchoOff(Option<libc::termios>);
+
+impl EchoOff {
+    fn new() -> Self {
+        let mut saved = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr only writes to the termios it's given, and fully on success
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, saved.as_mut_ptr()) } != 0 {
+            return Self(None);
+        }
+        // SAFETY: initialized by the successful tcgetattr
+        let saved = unsafe { saved.assume_init() };
+        let mut quiet = saved;
+        // Still echo the Enter, so the next prompt starts on its own line
+        quiet.c_lflag &= !libc::ECHO;
+        quiet.c_lflag |= libc::ECHONL;
+        // SAFETY: a termios read back from the same terminal, with only local flags changed
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &quiet) } != 0 {
+            return Self(None);
+        }
+        Self(Some(saved))
+    }
+}
+
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.0 {
+            // SAFETY: restores the settings tcgetattr returned
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved) };
+        }
+    }
+}
+
+/// Write `contents` to `path` readable by the owner only, an existing file included: it holds
+/// the private key
+fn write_private(path: &str, contents: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // `mode` only applies to a file it creates
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+fn confirm(question: &str, default: bool) -> Result<bool> {
+    let answer = ask(question, if default { "y" } else { "n" })?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_env_uses_preset() {
+        let answers = InitAnswers {
+            private_key: "0xabc".to_string(),
+            ws_rpc: "wss://rpc".to_string(),
+            paper_trading: true,
+            preset: RiskPreset::parse("conservative").unwrap(),
+        };
+        let env = render_env(&answers);
+
+        assert!(env.contains("POLYGON_PRIVATE_KEY=0xabc\n"));
+        assert!(env.contains("PAPER_TRADING=true\n"));
+        assert!(env.contains("MIN_EDGE_BPS=50\n"));
+        assert!(env.contains("KELLY_FRACTION=0.1\n"));
+    }
+
+    #[test]
+    fn test_env_file_is_owner_only() {
+        let dir = std::env::temp_dir().join(format!("init-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".env");
+        // Overwriting a world-readable file tightens it too
+        fs::write(&path, "OLD=1\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(path.to_str().unwrap(), "POLYGON_PRIVATE_KEY=0xabc\n").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "POLYGON_PRIVATE_KEY=0xabc\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
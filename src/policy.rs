@@ -0,0 +1,240 @@
+//! Startup policy: configuration combinations that are almost always a mistake
+//!
+//! Each setting on its own is valid, but some combinations do something other than what the
+//! operator most likely meant (a "live" run that only trades the simulator, take-profit exits
+//! that fire on the entry fill). The agent refuses to start on them unless the rule is named
+//! in `POLICY_OVERRIDES`, which turns the refusal into a warning.
+use anyhow::{bail, Result};
+use tracing::warn;
+
+use crate::config::Config;
+
+/// A blocked combination, named as in `POLICY_OVERRIDES`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolicyRule {
+    /// PAPER_TRADING=false with SIMULATION_MODE=true
+    LiveSimulation,
+    /// Live Flashbots bundles without FLASHBOTS_SIGNING_KEY
+    FlashbotsSigningKey,
+    /// AUTO_SELL_THRESHOLD below what an enabled strategy pays on entry
+    AutoSellBelowEntry,
+    /// SCAN_EXISTING_ON_STARTUP with no volume / liquidity / category filter
+    UnfilteredScan,
+}
+
+impl PolicyRule {
+    pub const ALL: [PolicyRule; 4] = [
+        PolicyRule::LiveSimulation,
+        PolicyRule::FlashbotsSigningKey,
+        PolicyRule::AutoSellBelowEntry,
+        PolicyRule::UnfilteredScan,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.into_iter().find(|rule| rule.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PolicyRule::LiveSimulation => "live_simulation",
+            PolicyRule::FlashbotsSigningKey => "flashbots_signing_key",
+            PolicyRule::AutoSellBelowEntry => "auto_sell_below_entry",
+            PolicyRule::UnfilteredScan => "unfiltered_scan",
+        }
+    }
+}
+
+/// A rule the config breaks, with what would actually happen
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    pub rule: PolicyRule,
+    pub detail: String,
+}
+
+/// Every rule `config` breaks, overridden or not
+pub fn check(config: &Config) -> Vec<PolicyViolation> {
+    let mut violations = Vec::new();
+    let mut violation = |rule, detail: String| violations.push(PolicyViolation { rule, detail });
+    let agent = &config.agent;
+    let live = !agent.paper_trading && !agent.simulation_mode;
+
+    if !agent.paper_trading && agent.simulation_mode {
+        violation(
+            PolicyRule::LiveSimulation,
+            "PAPER_TRADING=false but SIMULATION_MODE=true: every order goes to the simulator, \
+             none to the exchange"
+                .to_string(),
+        );
+    }
+
+    if live && config.flashbots.enabled && config.flashbots.signing_key.is_none() {
+        violation(
+            PolicyRule::FlashbotsSigningKey,
+            "FLASHBOTS_ENABLED without FLASHBOTS_SIGNING_KEY: bundles would be signed with the \
+             trading wallet's key (or skipped without one)"
+                .to_string(),
+        );
+    }
+
+    let threshold = config.risk.auto_sell_threshold;
+    let entry_ceilings = [
+        (
+            "expiration",
+            config.expiration.enabled,
+            config.expiration.target_price,
+        ),
+        (
+            "predictive",
+            config.predictive.enabled,
+            config.predictive.max_entry_price,
+        ),
+        (
+            "dislocation",
+            config.dislocation.enabled,
+            config.dislocation.max_entry_price,
+        ),
+    ];
+    for (strategy, enabled, ceiling) in entry_ceilings {
+        if enabled && threshold < ceiling {
+            violation(
+                PolicyRule::AutoSellBelowEntry,
+                format!(
+                    "AUTO_SELL_THRESHOLD {:.2} is below the {} strategy's entry prices (up to \
+                     {:.2}): its positions would be sold as soon as they are filled",
+                    threshold, strategy, ceiling
+                ),
+            );
+        }
+    }
+
+    let filters = &config.market_filters;
+    if agent.scan_existing_on_startup
+        && filters.min_market_volume <= 0.0
+        && filters.min_liquidity <= 0.0
+        && filters.min_24h_volume <= 0.0
+        && filters.categories.is_empty()
+    {
+        violation(
+            PolicyRule::UnfilteredScan,
+            "SCAN_EXISTING_ON_STARTUP with every market filter at 0: the startup scan \
+             evaluates (and may trade) every listed market"
+                .to_string(),
+        );
+    }
+
+    violations
+}
+
+/// Refuse to start on a violation that isn't overridden; warn about those that are
+pub fn enforce(config: &Config) -> Result<()> {
+    let mut overrides = Vec::new();
+    for name in &config.agent.policy_overrides {
+        match PolicyRule::parse(name) {
+            Some(rule) => overrides.push(rule),
+            None => bail!(
+                "Unknown rule '{}' in POLICY_OVERRIDES (expected one of: {})",
+                name,
+                rule_names()
+            ),
+        }
+    }
+
+    let mut blocking = Vec::new();
+    for violation in check(config) {
+        if overrides.contains(&violation.rule) {
+            warn!(
+                "⚠️ Policy {} overridden: {}",
+                violation.rule.as_str(),
+                violation.detail
+            );
+        } else {
+            blocking.push(violation);
+        }
+    }
+    if blocking.is_empty() {
+        return Ok(());
+    }
+
+    let reasons: Vec<String> = blocking
+        .iter()
+        .map(|v| format!("  - {}: {}", v.rule.as_str(), v.detail))
+        .collect();
+    let mut rules: Vec<&str> = blocking.iter().map(|v| v.rule.as_str()).collect();
+    rules.dedup();
+    bail!(
+        "Refusing to start on an unsafe configuration:\n{}\nFix the settings, or set \
+         POLICY_OVERRIDES={} to run anyway",
+        reasons.join("\n"),
+        rules.join(",")
+    )
+}
+
+fn rule_names() -> String {
+    PolicyRule::ALL
+        .iter()
+        .map(|rule| rule.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn safe_config() -> Config {
+        let mut config = Config::default();
+        config.agent.paper_trading = true;
+        config.agent.simulation_mode = false;
+        config.agent.scan_existing_on_startup = false;
+        config.agent.policy_overrides = Vec::new();
+        config.flashbots.enabled = false;
+        config.risk.auto_sell_threshold = 0.99;
+        config.expiration.enabled = true;
+        config.expiration.target_price = 0.99;
+        config.predictive.enabled = false;
+        config.dislocation.enabled = false;
+        config
+    }
+
+    #[test]
+    fn test_unsafe_combinations_need_an_override() {
+        let config = safe_config();
+        assert!(check(&config).is_empty());
+        assert!(enforce(&config).is_ok());
+
+        let mut config = safe_config();
+        config.agent.paper_trading = false;
+        config.agent.simulation_mode = true;
+        config.risk.auto_sell_threshold = 0.95;
+        let rules: Vec<PolicyRule> = check(&config).iter().map(|v| v.rule).collect();
+        assert_eq!(
+            rules,
+            vec![PolicyRule::LiveSimulation, PolicyRule::AutoSellBelowEntry]
+        );
+        let err = enforce(&config).unwrap_err().to_string();
+        assert!(err.contains("POLICY_OVERRIDES=live_simulation,auto_sell_below_entry"));
+
+        config.agent.policy_overrides = vec![
+            "live_simulation".to_string(),
+            "AUTO_SELL_BELOW_ENTRY".to_string(),
+        ];
+        assert!(enforce(&config).is_ok());
+
+        config.agent.policy_overrides = vec!["everything".to_string()];
+        assert!(enforce(&config).is_err());
+    }
+
+    #[test]
+    fn test_live_flashbots_needs_its_own_key() {
+        let mut config = safe_config();
+        config.agent.paper_trading = false;
+        config.flashbots.enabled = true;
+        config.flashbots.signing_key = None;
+        assert_eq!(check(&config)[0].rule, PolicyRule::FlashbotsSigningKey);
+
+        // Paper runs use a paper relay: no key involved
+        config.agent.paper_trading = true;
+        assert!(check(&config).is_empty());
+    }
+}
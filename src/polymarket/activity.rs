@@ -0,0 +1,160 @@
+use chrono::{DateTime, TimeZone, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tracing::{debug, error, info, trace, warn};
+
+use crate::markets::{MarketRegistry, ToxicityTracker, WhaleTracker};
+
+const ACTIVITY_WS_URL: &str = "wss://ws-live-data.polymarket.com";
+
+/// The live-data server drops connections that stay silent
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct ActivityMessage {
+    #[serde(default)]
+    topic: String,
+    #[serde(rename = "type", default)]
+    msg_type: String,
+    payload: Option<TradePayload>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradePayload {
+    /// Outcome token traded
+    asset: String,
+    /// "BUY" or "SELL"
+    side: String,
+    size: f64,
+    price: f64,
+    /// Unix seconds
+    #[serde(default)]
+    timestamp: Option<i64>,
+}
+
+/// Public trades stream (the "activity" topic of the live-data socket), kept apart from the
+/// CLOB market channel. Trades on assets known to the registry feed the whale tracker and the
+/// toxicity tracker (either may be off); everything else is ignored
+pub struct ActivityFeed;
+
+impl ActivityFeed {
+    pub fn spawn(
+        registry: MarketRegistry,
+        whales: Option<Arc<Mutex<WhaleTracker>>>,
+        toxicity: Option<Arc<Mutex<ToxicityTracker>>>,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                info!("🐋 Connecting to activity feed: {}", ACTIVITY_WS_URL);
+                match connect_async(ACTIVITY_WS_URL).await {
+                    Ok((ws_stream, _)) => {
+                        info!("✅ Activity feed connected");
+                        let (mut write, mut read) = ws_stream.split();
+                        let subscribe = json!({
+                            "action": "subscribe",
+                            "subscriptions": [{ "topic": "activity", "type": "trades" }],
+                        });
+                        if let Err(e) = write.send(Message::Text(subscribe.to_string())).await {
+                            error!("❌ Failed to subscribe to activity feed: {}", e);
+                        }
+
+                        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+                        loop {
+                            tokio::select! {
+                                msg = read.next() => {
+                                    match msg {
+                                        Some(Ok(Message::Text(text))) => {
+                                            Self::handle_message(
+                                                &text,
+                                                &registry,
+                                                whales.as_deref(),
+                                                toxicity.as_deref(),
+                                            );
+                                        }
+                                        Some(Ok(Message::Ping(data))) => {
+                                            let _ = write.send(Message::Pong(data)).await;
+                                        }
+                                        Some(Ok(Message::Close(_))) | None => {
+                                            warn!("⚠️ Activity feed closed by server");
+                                            break;
+                                        }
+                                        Some(Ok(_)) => {}
+                                        Some(Err(e)) => {
+                                            error!("❌ Activity feed error: {}", e);
+                                            break;
+                                        }
+                                    }
+                                }
+                                _ = ping_interval.tick() => {
+                                    if let Err(e) = write.send(Message::Text("PING".to_string())).await {
+                                        error!("❌ Failed to ping activity feed: {}", e);
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("❌ Activity feed connection failed: {}", e);
+                    }
+                }
+                warn!("🔄 Reconnecting activity feed in 5s...");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    fn handle_message(
+        text: &str,
+        registry: &MarketRegistry,
+        whales: Option<&Mutex<WhaleTracker>>,
+        toxicity: Option<&Mutex<ToxicityTracker>>,
+    ) {
+        let message = match serde_json::from_str::<ActivityMessage>(text) {
+            Ok(message) => message,
+            Err(e) => {
+                if !text.trim().eq_ignore_ascii_case("PONG") {
+                    trace!("ℹ️ Ignored activity msg: {} | {}", e, text);
+                }
+                return;
+            }
+        };
+        if message.topic != "activity" || message.msg_type != "trades" {
+            return;
+        }
+        let Some(trade) = message.payload else {
+            return;
+        };
+        let Some(asset) = registry.asset(&trade.asset) else {
+            return;
+        };
+        let at: DateTime<Utc> = trade
+            .timestamp
+            .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+            .unwrap_or_else(Utc::now);
+        let buy = trade.side.eq_ignore_ascii_case("BUY");
+        if let Some(Ok(mut toxicity)) = toxicity.map(|t| t.lock()) {
+            toxicity.on_trade(&trade.asset, buy, trade.price, trade.size, at);
+        }
+        let Some(Ok(mut whales)) = whales.map(|w| w.lock()) else {
+            return;
+        };
+        if whales.record(
+            &asset.market_id,
+            &asset.side,
+            buy,
+            trade.price,
+            trade.size,
+            at,
+        ) {
+            debug!(
+                "🐋 Whale {} {} {:.0} @ {:.3} on {}",
+                trade.side, asset.side, trade.size, trade.price, asset.market_id
+            );
+        }
+    }
+}
@@ -1,26 +1,128 @@
-use super::types::MarketData;
+use super::collateral::CollateralBalances;
+use super::types::{EventSeries, MarketData, OpenOrder, OrderBook, OrderStatus};
 use anyhow::Result;
 use async_trait::async_trait;
 use polymarket_client_sdk::clob::types::OrderType;
 
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::venues::Venue;
+
+/// One order in a multi-order submission
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    pub market_id: MarketId,
+    pub side: String,
+    pub size_usd: UsdAmount,
+    pub price: Price,
+    pub order_type: OrderType,
+}
+
+/// A venue's markets, books and order entry. Polymarket's client and the simulator implement it;
+/// other venues plug in as adapters (see `crate::venues`)
 #[async_trait]
 pub trait MarketInterface: Send + Sync {
+    /// Venue behind the interface
+    fn venue(&self) -> Venue {
+        Venue::Polymarket
+    }
+
     /// Fetch all active markets
     async fn get_active_markets(&self) -> Result<Vec<MarketData>>;
 
     /// Get details for a specific market
     async fn get_market_details(&self, market_id: &str) -> Result<MarketData>;
 
-    /// Get account balance (USDC)
+    /// Question, end date and token ids of a market, for callers that don't need its prices
+    /// (which may be missing: served from the metadata cache while Gamma is down). Defaults to
+    /// `get_market_details` for interfaces without a metadata cache
+    async fn get_market_metadata(&self, market_id: &str) -> Result<MarketData> {
+        self.get_market_details(market_id).await
+    }
+
+    /// Look a market up by its URL slug
+    /// Defaults to an error for interfaces without slugs
+    async fn get_market_by_slug(&self, slug: &str) -> Result<MarketData> {
+        anyhow::bail!("Market slugs are not supported here ({})", slug)
+    }
+
+    /// Get account balance in the collateral the exchange settles in
     async fn get_balance(&self) -> Result<f64>;
 
-    /// Place an order
+    /// Balance of each USDC variant
+    /// Defaults to reporting the trading balance as USDC.e for interfaces with a single balance
+    async fn get_collateral_balances(&self) -> Result<CollateralBalances> {
+        Ok(CollateralBalances {
+            usdc: 0.0,
+            usdc_e: self.get_balance().await?,
+        })
+    }
+
+    /// Place an order for `size` USD at `price`
     async fn place_order(
         &self,
-        market_id: &str,
+        market_id: &MarketId,
         side: &str,
-        size: f64,
-        price: f64,
+        size: UsdAmount,
+        price: Price,
         order_type: OrderType,
     ) -> Result<String>;
+
+    /// Submit several orders together, one result per request (in order)
+    /// Defaults to sequential place_order calls for interfaces without a batch endpoint
+    async fn place_orders(&self, orders: &[OrderRequest]) -> Vec<Result<String>> {
+        let mut results = Vec::with_capacity(orders.len());
+        for order in orders {
+            results.push(
+                self.place_order(
+                    &order.market_id,
+                    &order.side,
+                    order.size_usd,
+                    order.price,
+                    order.order_type.clone(),
+                )
+                .await,
+            );
+        }
+        results
+    }
+
+    /// Cancel a resting order
+    async fn cancel_order(&self, order_id: &str) -> Result<()>;
+
+    /// List our resting orders, optionally restricted to one market
+    async fn get_open_orders(&self, market_id: Option<&str>) -> Result<Vec<OpenOrder>>;
+
+    /// Get the current status of an order
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus>;
+
+    /// Get an L2 book snapshot for a token (asset) id
+    async fn get_orderbook(&self, asset_id: &str) -> Result<OrderBook>;
+
+    /// Stop sending real orders for the rest of the run (anomaly guard)
+    /// Defaults to a no-op for interfaces that never trade live
+    fn downgrade_to_paper(&self) {}
+
+    /// Cheap incremental check for newly listed markets (callers dedupe by id)
+    /// Defaults to the full active list for interfaces without a cheaper source
+    async fn poll_new_markets(&self) -> Result<Vec<MarketData>> {
+        self.get_active_markets().await
+    }
+
+    /// Base fee (bps per trade) the venue charges on a token, to spot fee-free markets
+    /// Defaults to None (unknown: the usual fee is assumed) for interfaces without fee rates
+    async fn get_fee_rate_bps(&self, _asset_id: &str) -> Result<Option<u32>> {
+        Ok(None)
+    }
+
+    /// Most recently created event series, newest first
+    /// Defaults to none for interfaces without series
+    async fn get_event_series(&self) -> Result<Vec<EventSeries>> {
+        Ok(Vec::new())
+    }
+
+    /// Open markets of a series' events
+    /// Defaults to none for interfaces without series
+    async fn get_series_markets(&self, _series_id: &str) -> Result<Vec<MarketData>> {
+        Ok(Vec::new())
+    }
 }
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+use utoipa::ToSchema;
+
+use crate::clock;
+
+/// Closed connections kept for the API
+const MAX_CLOSED_CONNECTIONS: usize = 10;
+
+/// Inbound traffic of one WS connection
+/// Bytes are message payloads as handed to us (decompressed when a compression extension is on)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ConnectionBandwidth {
+    pub id: u64,
+    pub connected_at: DateTime<Utc>,
+    pub disconnected_at: Option<DateTime<Utc>>,
+    /// `Sec-WebSocket-Extensions` accepted by the server (e.g. permessage-deflate), if any
+    pub extensions: Option<String>,
+    pub bytes_in: u64,
+    pub messages_in: u64,
+    /// Rates over the last completed one-second window
+    pub bytes_per_sec: f64,
+    pub messages_per_sec: f64,
+    pub peak_bytes_per_sec: f64,
+    pub avg_bytes_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct BandwidthStats {
+    pub total_bytes_in: u64,
+    pub total_messages_in: u64,
+    pub current: Option<ConnectionBandwidth>,
+    /// Most recent first
+    pub closed: Vec<ConnectionBandwidth>,
+}
+
+/// Whether the connection quotes arrive on is up and talking, monotonic ns (see `clock`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeedHealth {
+    /// When the current connection opened; None while the feed is down
+    pub connected_ns: Option<u64>,
+    /// Last message of any kind read on it (keep-alives included)
+    pub last_message_ns: Option<u64>,
+}
+
+struct Live {
+    stats: ConnectionBandwidth,
+    connected_ns: u64,
+    last_message_ns: Option<u64>,
+    started: Instant,
+    window_start: Instant,
+    window_bytes: u64,
+    window_messages: u64,
+}
+
+#[derive(Default)]
+struct MeterInner {
+    next_id: u64,
+    total_bytes_in: u64,
+    total_messages_in: u64,
+    live: Option<Live>,
+    closed: VecDeque<ConnectionBandwidth>,
+}
+
+/// Inbound bytes / messages per connection of a WS client
+#[derive(Default)]
+pub struct BandwidthMeter {
+    inner: Mutex<MeterInner>,
+}
+
+impl BandwidthMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start metering a new connection (closing the previous one if still open)
+    pub fn connected(&self, extensions: Option<String>) {
+        let mut inner = self.inner.lock().unwrap();
+        Self::close(&mut inner);
+        inner.next_id += 1;
+        let now = Instant::now();
+        inner.live = Some(Live {
+            stats: ConnectionBandwidth {
+                id: inner.next_id,
+                connected_at: Utc::now(),
+                disconnected_at: None,
+                extensions,
+                bytes_in: 0,
+                messages_in: 0,
+                bytes_per_sec: 0.0,
+                messages_per_sec: 0.0,
+                peak_bytes_per_sec: 0.0,
+                avg_bytes_per_sec: 0.0,
+            },
+            connected_ns: clock::now_ns(),
+            last_message_ns: None,
+            started: now,
+            window_start: now,
+            window_bytes: 0,
+            window_messages: 0,
+        });
+    }
+
+    pub fn disconnected(&self) {
+        Self::close(&mut self.inner.lock().unwrap());
+    }
+
+    fn close(inner: &mut MeterInner) {
+        if let Some(mut live) = inner.live.take() {
+            live.stats.disconnected_at = Some(Utc::now());
+            Self::finish_avg(&mut live);
+            inner.closed.push_front(live.stats);
+            inner.closed.truncate(MAX_CLOSED_CONNECTIONS);
+        }
+    }
+
+    fn finish_avg(live: &mut Live) {
+        let secs = live.started.elapsed().as_secs_f64();
+        if secs > 0.0 {
+            live.stats.avg_bytes_per_sec = live.stats.bytes_in as f64 / secs;
+        }
+    }
+
+    /// Count one inbound message of `bytes` payload bytes
+    pub fn record(&self, bytes: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.total_bytes_in += bytes as u64;
+        inner.total_messages_in += 1;
+        let Some(live) = inner.live.as_mut() else {
+            return;
+        };
+        live.stats.bytes_in += bytes as u64;
+        live.stats.messages_in += 1;
+        live.last_message_ns = Some(clock::now_ns());
+        live.window_bytes += bytes as u64;
+        live.window_messages += 1;
+
+        let elapsed = live.window_start.elapsed().as_secs_f64();
+        if elapsed >= 1.0 {
+            live.stats.bytes_per_sec = live.window_bytes as f64 / elapsed;
+            live.stats.messages_per_sec = live.window_messages as f64 / elapsed;
+            live.stats.peak_bytes_per_sec =
+                live.stats.peak_bytes_per_sec.max(live.stats.bytes_per_sec);
+            live.window_start = Instant::now();
+            live.window_bytes = 0;
+            live.window_messages = 0;
+        }
+    }
+
+    /// Is the feed up, and when did it last say anything
+    pub fn health(&self) -> FeedHealth {
+        let inner = self.inner.lock().unwrap();
+        FeedHealth {
+            connected_ns: inner.live.as_ref().map(|live| live.connected_ns),
+            last_message_ns: inner.live.as_ref().and_then(|live| live.last_message_ns),
+        }
+    }
+
+    pub fn stats(&self) -> BandwidthStats {
+        let mut inner = self.inner.lock().unwrap();
+        let current = inner.live.as_mut().map(|live| {
+            Self::finish_avg(live);
+            live.stats.clone()
+        });
+        BandwidthStats {
+            total_bytes_in: inner.total_bytes_in,
+            total_messages_in: inner.total_messages_in,
+            current,
+            closed: inner.closed.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_per_connection() {
+        let meter = BandwidthMeter::new();
+        meter.record(100); // before any connection: totals only
+
+        meter.connected(None);
+        meter.record(300);
+        meter.record(200);
+        meter.connected(Some("permessage-deflate".to_string()));
+        meter.record(50);
+
+        let stats = meter.stats();
+        assert_eq!(stats.total_bytes_in, 650);
+        assert_eq!(stats.total_messages_in, 4);
+        let current = stats.current.unwrap();
+        assert_eq!(current.id, 2);
+        assert_eq!(current.bytes_in, 50);
+        assert_eq!(current.extensions.as_deref(), Some("permessage-deflate"));
+        assert_eq!(stats.closed.len(), 1);
+        assert_eq!(stats.closed[0].bytes_in, 500);
+        assert!(stats.closed[0].disconnected_at.is_some());
+
+        let health = meter.health();
+        assert!(health.connected_ns.is_some());
+        assert!(health.last_message_ns >= health.connected_ns);
+
+        meter.disconnected();
+        assert!(meter.stats().current.is_none());
+        assert!(meter.health().connected_ns.is_none());
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::config::CircuitBreakerConfig;
+
+/// Breaker position, as in the classic pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum BreakerState {
+    /// Calls go through
+    Closed,
+    /// Calls are rejected until the cooldown ends
+    Open,
+    /// Cooldown over: a single probe call decides whether to close or re-open
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BreakerSnapshot {
+    pub name: String,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    /// Times the breaker opened since startup
+    pub trips: u64,
+    /// Calls rejected without reaching the upstream
+    pub rejected: u64,
+    /// Seconds until a probe is allowed (0 unless open)
+    pub probe_in_secs: u64,
+}
+
+/// Returned instead of calling an upstream whose breaker is open
+#[derive(Debug, Clone)]
+pub struct CircuitOpen {
+    pub name: String,
+}
+
+impl std::fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "circuit breaker open for {}", self.name)
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+/// Was this error produced by an open breaker (no request was made)?
+pub fn is_circuit_open(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<CircuitOpen>().is_some()
+}
+
+/// Non-success HTTP status from an upstream, kept typed so callers can tell 4xx from 5xx
+#[derive(Debug, Clone)]
+pub struct UpstreamStatus {
+    pub what: String,
+    pub status: u16,
+}
+
+impl UpstreamStatus {
+    pub fn new(what: impl Into<String>, status: reqwest::StatusCode) -> Self {
+        Self {
+            what: what.into(),
+            status: status.as_u16(),
+        }
+    }
+}
+
+impl std::fmt::Display for UpstreamStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: HTTP {}", self.what, self.status)
+    }
+}
+
+impl std::error::Error for UpstreamStatus {}
+
+/// HTTP status the upstream answered with, if the error carries one
+pub fn upstream_status(e: &anyhow::Error) -> Option<u16> {
+    e.chain().find_map(|cause| {
+        if let Some(status) = cause.downcast_ref::<UpstreamStatus>() {
+            return Some(status.status);
+        }
+        if let Some(status) = cause.downcast_ref::<polymarket_client_sdk::error::Status>() {
+            return Some(status.status_code.as_u16());
+        }
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .and_then(|e| e.status())
+            .map(|status| status.as_u16())
+    })
+}
+
+/// Does this error say the upstream is unhealthy: a 5xx, a timeout or a failed connection?
+/// A 4xx is the upstream answering about our request, and errors that never reached it
+/// (bad input, parse failures) say nothing about its health
+pub fn is_upstream_failure(e: &anyhow::Error) -> bool {
+    if let Some(status) = upstream_status(e) {
+        return status >= 500;
+    }
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout() || e.is_connect())
+            || cause.is::<tokio::time::error::Elapsed>()
+            || cause.is::<std::io::Error>()
+    })
+}
+
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the half-open probe was let through (a dropped probe is replaced after a cooldown)
+    probe_started: Option<Instant>,
+    trips: u64,
+    rejected: u64,
+}
+
+/// Stops calling an upstream after `failure_threshold` consecutive failures, then lets one
+/// probe through every `cooldown_secs` until it succeeds
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &str, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            config,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_started: None,
+                trips: 0,
+                rejected: 0,
+            }),
+        }
+    }
+
+    fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.config.cooldown_secs)
+    }
+
+    /// May a call go out now? In half-open only the probe is admitted
+    pub fn allow(&self) -> bool {
+        if !self.config.enabled {
+            return true;
+        }
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.state == BreakerState::Open
+            && inner
+                .opened_at
+                .is_some_and(|at| now.duration_since(at) >= self.cooldown())
+        {
+            inner.state = BreakerState::HalfOpen;
+            inner.probe_started = None;
+        }
+
+        let admitted = match inner.state {
+            BreakerState::Closed => true,
+            BreakerState::Open => false,
+            BreakerState::HalfOpen => {
+                let probe_free = inner
+                    .probe_started
+                    .is_none_or(|at| now.duration_since(at) >= self.cooldown());
+                if probe_free {
+                    info!("🔌 Probing {} after cooldown", self.name);
+                    inner.probe_started = Some(now);
+                }
+                probe_free
+            }
+        };
+        if !admitted {
+            inner.rejected += 1;
+        }
+        admitted
+    }
+
+    /// Would a call be rejected right now (cooling down, or a probe already in flight)?
+    pub fn is_open(&self) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let inner = self.inner.lock().unwrap();
+        let cooling =
+            |since: Option<Instant>| since.is_some_and(|at| at.elapsed() < self.cooldown());
+        match inner.state {
+            BreakerState::Closed => false,
+            BreakerState::Open => cooling(inner.opened_at),
+            BreakerState::HalfOpen => cooling(inner.probe_started),
+        }
+    }
+
+    pub fn record(&self, ok: bool) {
+        if !self.config.enabled {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+
+        if ok {
+            if inner.state != BreakerState::Closed {
+                info!(
+                    "💚 {} recovered - circuit closed after {} failures",
+                    self.name, inner.consecutive_failures
+                );
+            }
+            inner.state = BreakerState::Closed;
+            inner.consecutive_failures = 0;
+            inner.opened_at = None;
+            inner.probe_started = None;
+            return;
+        }
+
+        inner.consecutive_failures += 1;
+        let reopen = inner.state == BreakerState::HalfOpen;
+        if reopen
+            || (inner.state == BreakerState::Closed
+                && inner.consecutive_failures >= self.config.failure_threshold.max(1))
+        {
+            if !reopen {
+                inner.trips += 1;
+                warn!(
+                    "🔌 {} failed {} times in a row - circuit open, next probe in {}s",
+                    self.name, inner.consecutive_failures, self.config.cooldown_secs
+                );
+            }
+            inner.state = BreakerState::Open;
+            inner.opened_at = Some(Instant::now());
+            inner.probe_started = None;
+        }
+    }
+
+    /// Run `call` through the breaker: rejected with `CircuitOpen` while open. Only upstream
+    /// failures (see `is_upstream_failure`) count toward tripping it
+    pub async fn call<T, Fut>(&self, call: Fut) -> Result<T>
+    where
+        Fut: Future<Output = Result<T>>,
+    {
+        if !self.allow() {
+            return Err(CircuitOpen {
+                name: self.name.clone(),
+            }
+            .into());
+        }
+        let result = call.await;
+        self.record(
+            result
+                .as_ref()
+                .map_or_else(|e| !is_upstream_failure(e), |_| true),
+        );
+        result
+    }
+
+    pub fn snapshot(&self) -> BreakerSnapshot {
+        let inner = self.inner.lock().unwrap();
+        BreakerSnapshot {
+            name: self.name.clone(),
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            trips: inner.trips,
+            rejected: inner.rejected,
+            probe_in_secs: match (inner.state, inner.opened_at) {
+                (BreakerState::Open, Some(at)) => {
+                    self.cooldown().saturating_sub(at.elapsed()).as_secs()
+                }
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// One breaker per upstream: Gamma, CLOB REST, and each RPC endpoint
+pub struct CircuitBreakers {
+    config: CircuitBreakerConfig,
+    pub gamma: CircuitBreaker,
+    pub clob: CircuitBreaker,
+    rpc: Mutex<BTreeMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            gamma: CircuitBreaker::new("Gamma", config.clone()),
+            clob: CircuitBreaker::new("CLOB REST", config.clone()),
+            rpc: Mutex::new(BTreeMap::new()),
+            config,
+        }
+    }
+
+    /// Breaker of one RPC endpoint (created on first use)
+    /// Named by host only: RPC URLs often carry an API key in the path
+    pub fn rpc(&self, endpoint: &str) -> Arc<CircuitBreaker> {
+        self.rpc
+            .lock()
+            .unwrap()
+            .entry(endpoint.to_string())
+            .or_insert_with(|| {
+                let host = reqwest::Url::parse(endpoint)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string))
+                    .unwrap_or_else(|| "unknown".to_string());
+                Arc::new(CircuitBreaker::new(
+                    &format!("RPC {}", host),
+                    self.config.clone(),
+                ))
+            })
+            .clone()
+    }
+
+    pub fn snapshot(&self) -> Vec<BreakerSnapshot> {
+        let mut snapshots = vec![self.gamma.snapshot(), self.clob.snapshot()];
+        snapshots.extend(self.rpc.lock().unwrap().values().map(|b| b.snapshot()));
+        snapshots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(cooldown_secs: u64) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "test",
+            CircuitBreakerConfig {
+                enabled: true,
+                failure_threshold: 3,
+                cooldown_secs,
+            },
+        )
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = breaker(60);
+        breaker.record(false);
+        breaker.record(false);
+        breaker.record(true);
+        breaker.record(false);
+        breaker.record(false);
+        assert!(breaker.allow());
+
+        breaker.record(false);
+        assert!(breaker.is_open());
+        assert!(!breaker.allow());
+        let snapshot = breaker.snapshot();
+        assert_eq!(snapshot.state, BreakerState::Open);
+        assert_eq!(snapshot.trips, 1);
+        assert_eq!(snapshot.rejected, 1);
+    }
+
+    #[test]
+    fn test_probe_closes_or_reopens() {
+        let breaker = breaker(0);
+        for _ in 0..3 {
+            breaker.record(false);
+        }
+
+        // Cooldown elapsed: one probe, then a failed probe re-opens
+        assert!(breaker.allow());
+        assert_eq!(breaker.snapshot().state, BreakerState::HalfOpen);
+        breaker.record(false);
+        assert_eq!(breaker.snapshot().state, BreakerState::Open);
+        assert_eq!(breaker.snapshot().trips, 1);
+
+        assert!(breaker.allow());
+        breaker.record(true);
+        assert_eq!(breaker.snapshot().state, BreakerState::Closed);
+        assert!(!breaker.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_only_upstream_failures_trip() {
+        let breaker = breaker(60);
+        let status = |code: u16| -> Result<()> {
+            Err(UpstreamStatus::new("test", reqwest::StatusCode::from_u16(code).unwrap()).into())
+        };
+
+        for _ in 0..5 {
+            assert!(breaker.call(async { status(404) }).await.is_err());
+            assert!(breaker.call(async { status(429) }).await.is_err());
+            assert!(breaker
+                .call(async { anyhow::bail!("bad input") })
+                .await
+                .is_err());
+        }
+        assert_eq!(breaker.snapshot().state, BreakerState::Closed);
+
+        let refused = || -> Result<()> {
+            Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into())
+        };
+        assert!(breaker.call(async { status(502) }).await.is_err());
+        assert!(breaker.call(async { refused() }).await.is_err());
+        assert!(breaker.call(async { status(503) }).await.is_err());
+        assert!(breaker.is_open());
+    }
+}
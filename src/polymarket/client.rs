@@ -1,27 +1,52 @@
 use super::api::MarketInterface;
 use alloy::primitives::Address;
 use alloy::signers::local::PrivateKeySigner;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use rust_decimal::{Decimal, RoundingStrategy};
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 // SDK Imports
 use polymarket_client_sdk::auth::state::Authenticated;
 use polymarket_client_sdk::auth::Normal;
+#[cfg(feature = "trading")]
 use polymarket_client_sdk::auth::{LocalSigner, Signer};
+use polymarket_client_sdk::clob::types::request::OrdersRequest;
 use polymarket_client_sdk::clob::types::response::MarketResponse;
+use polymarket_client_sdk::clob::types::response::OpenOrderResponse;
 use polymarket_client_sdk::clob::types::response::PostOrderResponse;
-use polymarket_client_sdk::clob::types::{OrderType, Side, SignableOrder, SignedOrder};
+use polymarket_client_sdk::clob::types::{
+    OrderStatusType, OrderType, Side, SignableOrder, SignedOrder,
+};
 use polymarket_client_sdk::clob::Client as ClobClient;
 use polymarket_client_sdk::error::Error as SdkError;
 use polymarket_client_sdk::types::U256;
 use polymarket_client_sdk::POLYGON;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
 use std::str::FromStr; // Only Deserialize is used for GammaMarket
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::config::PolymarketConfig;
-use crate::polymarket::types::MarketData;
+use crate::execution::obfuscation::random_salt;
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::markets::{normalize_market_id, ConditionId, MarketCategory, ResolutionProposals};
+use crate::polymarket::breaker::{
+    is_circuit_open, is_upstream_failure, upstream_status, CircuitBreakers, UpstreamStatus,
+};
+use crate::polymarket::collateral::{
+    required_collateral, Collateral, CollateralBalances, PositionCollateral,
+};
+use crate::polymarket::derived_assets::DerivedAssetCache;
+use crate::polymarket::metadata_cache::{MarketMetadata, MetadataCache};
+use crate::polymarket::neg_risk::NegRiskGroups;
+use crate::polymarket::types::{
+    EventSeries, MarketData, OpenOrder, OrderBook, OrderLevel, OrderStatus,
+};
 
 // We need reqwest for Gamma API fallback (http_client)
 // But warning said unused `reqwest::Client`.
@@ -35,8 +60,10 @@ use crate::polymarket::types::MarketData;
 pub struct PolymarketClient {
     pub client: ClobClient, // Now from new SDK
     pub http_client: reqwest::Client,
+    pub host: String,
     pub gamma_url: String,
-    pub paper_trading: bool,
+    /// Flipped on by the anomaly guard, never back off within a run
+    pub paper_trading: AtomicBool,
     pub proxy_address: Option<String>,
     // order_builder removed if integrated into ClobClient or handled differently
     // API Credentials for manual requests (Proxy Balance)
@@ -45,12 +72,76 @@ pub struct PolymarketClient {
     pub passphrase: String,
     pub signer_address: Address,
     pub private_key: Option<String>,
+    // Conditional-request state for fast new-market polling
+    new_market_cursor: std::sync::Mutex<NewMarketCursor>,
+    // Cached L2 session, dropped and rebuilt when the CLOB rejects it
+    auth_session: tokio::sync::Mutex<Option<Arc<AuthSession>>>,
+    auth_max_retries: u32,
+    // Upstream breakers, shared between clients hitting the same endpoints
+    breakers: Arc<CircuitBreakers>,
+    // Last good results, served while a breaker is open
+    markets_cache: std::sync::Mutex<Vec<MarketData>>,
+    balance_cache: std::sync::Mutex<HashMap<(Collateral, String), f64>>,
+    // Collateral of neg-risk markets seen on Gamma is recorded here
+    derived_assets: Option<DerivedAssetCache>,
+    // Adapter market and question index of neg-risk markets seen on Gamma
+    neg_risk_groups: Option<NegRiskGroups>,
+    // UMA resolution status of markets seen on Gamma (zombie market detection)
+    resolution_proposals: Option<ResolutionProposals>,
+    // Metadata of markets seen on Gamma, kept on disk
+    metadata_cache: Option<MetadataCache>,
+    // Random order salts (OBFUSCATION_RANDOM_ORDER_IDS)
+    random_salts: bool,
 }
 
+/// Authenticated CLOB client plus the signer it was derived from
+struct AuthSession {
+    client: ClobClient<Authenticated<Normal>>,
+    signer: PrivateKeySigner,
+}
+
+/// Base delay between re-authentication attempts (doubles per attempt)
+const AUTH_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Does this error mean the CLOB rejected our credentials (expired / revoked API key)?
+/// Judged by the HTTP status only: "401" can just as well appear in an order id or a price
+fn is_auth_error(e: &anyhow::Error) -> bool {
+    upstream_status(e) == Some(401)
+}
+
+/// Validators and cursor from the last new-market poll
+#[derive(Debug, Default, Clone)]
+struct NewMarketCursor {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Highest Gamma market id seen (ids are assigned in creation order)
+    last_id: Option<u64>,
+}
+
+/// Page size for the newest-first Gamma query used by fast polling
+const NEW_MARKET_PAGE_SIZE: usize = 50;
+
+/// Series (and events of a series) fetched per Gamma request
+const SERIES_PAGE: usize = 50;
+
+/// `next_cursor` of the last page of a CLOB listing
+const END_CURSOR: &str = "LTE=";
+
+/// Pages of open orders read per listing at most (a cursor that never ends stops here)
+const MAX_ORDER_PAGES: usize = 100;
+
 #[async_trait]
 impl MarketInterface for PolymarketClient {
     async fn get_active_markets(&self) -> Result<Vec<MarketData>> {
-        let markets = self.fetch_markets().await?;
+        let markets = match self.breakers.clob.call(self.fetch_markets()).await {
+            Ok(markets) => markets,
+            Err(e) if is_circuit_open(&e) => {
+                let cached = self.markets_cache.lock().unwrap().clone();
+                debug!("{} - serving {} cached markets", e, cached.len());
+                return Ok(cached);
+            }
+            Err(e) => return Err(e),
+        };
         let mut market_data_list = Vec::new();
 
         for market in markets {
@@ -60,6 +151,7 @@ impl MarketInterface for PolymarketClient {
             }
         }
 
+        *self.markets_cache.lock().unwrap() = market_data_list.clone();
         Ok(market_data_list)
     }
 
@@ -69,25 +161,39 @@ impl MarketInterface for PolymarketClient {
         // Optimized: Fetch specific market from Gamma API directly
         let url = format!("{}/markets?condition_ids={}", self.gamma_url, market_id);
 
-        let response = self.http_client.get(&url).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            warn!(
-                "Gamma API error for {}: {} - {}",
-                market_id, status, error_text
-            );
-            anyhow::bail!("Gamma API error: {} - {}", status, error_text);
-        }
+        let fetched: Result<Vec<GammaMarket>> = self
+            .breakers
+            .gamma
+            .call(async {
+                let response = self.http_client.get(&url).send().await?;
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unknown error".to_string());
+                    warn!(
+                        "Gamma API error for {}: {} - {}",
+                        market_id, status, error_text
+                    );
+                    anyhow::bail!(UpstreamStatus::new(
+                        format!("Gamma API error ({})", error_text),
+                        status
+                    ));
+                }
 
-        let markets: Vec<GammaMarket> = response.json().await?;
+                Ok(response.json().await?)
+            })
+            .await;
+        // Callers read prices off this: a Gamma failure is an error, never cached metadata
+        let markets = fetched?;
 
         // Check Gamma findings
-        let market_gamma = markets.into_iter().find(|m| m.condition_id == market_id);
+        let wanted = normalize_market_id(market_id);
+        let market_gamma = markets
+            .into_iter()
+            .find(|m| normalize_market_id(&m.condition_id) == wanted);
 
         if let Some(m) = market_gamma {
             return self.convert_gamma_market(&m);
@@ -99,16 +205,23 @@ impl MarketInterface for PolymarketClient {
             market_id
         );
 
-        match crate::polymarket::contracts::derive_asset_ids(market_id) {
+        let derived = match &self.derived_assets {
+            Some(derived_assets) => {
+                ConditionId::parse(market_id).map(|id| derived_assets.get_or_derive(&id))
+            }
+            None => crate::polymarket::contracts::derive_asset_ids(market_id),
+        };
+        match derived {
             Ok((yes_id, no_id)) => {
                 info!(
                     "✅ Derived IDs for {}: YES={}, NO={}",
                     market_id, yes_id, no_id
                 );
                 Ok(MarketData {
-                    id: market_id.to_string(),
+                    id: market_id.into(),
                     question: format!("Market {}", market_id),
                     end_date: None,
+                    end_time: None,
                     volume: 0.0,
                     liquidity: 0.0,
                     yes_price: 0.0,
@@ -119,6 +232,7 @@ impl MarketInterface for PolymarketClient {
                     best_bid: 0.0,
                     best_ask: 0.0,
                     asset_ids: vec![no_id, yes_id],
+                    category: None,
                 })
             }
             Err(e) => {
@@ -131,7 +245,43 @@ impl MarketInterface for PolymarketClient {
         }
     }
 
+    async fn get_market_metadata(&self, market_id: &str) -> Result<MarketData> {
+        if let Some(metadata) = self
+            .metadata_cache
+            .as_ref()
+            .and_then(|c| c.fresh(market_id, Utc::now()))
+        {
+            return Ok(metadata.to_market());
+        }
+        match self.get_market_details(market_id).await {
+            Ok(market) => Ok(market),
+            // Gamma unreachable: what it last said, whatever its age
+            Err(e) => match self.metadata_cache.as_ref().and_then(|c| c.get(market_id)) {
+                Some(metadata) => {
+                    debug!("{} - serving cached metadata of {}", e, market_id);
+                    Ok(metadata.to_market())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
     async fn get_balance(&self) -> Result<f64> {
+        // Only the collateral the exchange settles in can be traded with
+        let required = required_collateral();
+        let balances = self.get_collateral_balances().await?;
+        if balances.of(required) <= 0.0 && balances.of(required.other()) > 0.0 {
+            warn!(
+                "⚠️ No {} to trade with, but ${:.2} {} in the wallet (swap needed)",
+                required.symbol(),
+                balances.of(required.other()),
+                required.other().symbol()
+            );
+        }
+        Ok(balances.of(required))
+    }
+
+    async fn get_collateral_balances(&self) -> Result<CollateralBalances> {
         // Balance for trading should be checked on proxy/safe wallet if available.
         // Fallback to signer only if proxy is unavailable.
         let target_addr = if let Some(proxy) = &self.proxy_address {
@@ -142,17 +292,420 @@ impl MarketInterface for PolymarketClient {
 
         if target_addr.is_empty() || target_addr == "0x0000000000000000000000000000000000000000" {
             warn!("⚠️ Unable to determine target wallet for balance check");
-            return Ok(0.0);
+            return Ok(CollateralBalances::default());
+        }
+
+        let balances = CollateralBalances {
+            usdc: self.erc20_balance(Collateral::Usdc, &target_addr).await?,
+            usdc_e: self.erc20_balance(Collateral::UsdcE, &target_addr).await?,
+        };
+        info!(
+            "💰 Collateral on {}: ${:.2} USDC, ${:.2} USDC.e",
+            target_addr, balances.usdc, balances.usdc_e
+        );
+        Ok(balances)
+    }
+
+    async fn place_order(
+        &self,
+        market_id: &MarketId,
+        side_str: &str,
+        size_usd: UsdAmount,
+        price: Price,
+        order_type: OrderType,
+    ) -> Result<String> {
+        let (size_usd, price_f64) = (size_usd.get(), price.get());
+        if self.is_paper() {
+            info!(
+                "📝 [PAPER] Order: {} ${:.2} @ ${:.4} on market {}",
+                side_str, size_usd, price_f64, market_id
+            );
+            return Ok(format!("paper-order-{}", uuid::Uuid::new_v4()));
+        }
+
+        info!(
+            "🚨 LIVE ORDER: {} ${:.2} @ ${:.4} on market {}",
+            side_str, size_usd, price_f64, market_id
+        );
+
+        let side = match side_str.to_uppercase().as_str() {
+            "BUY" | "YES" => Side::Buy,
+            "SELL" | "NO" => Side::Sell,
+            _ => anyhow::bail!("Invalid side: {}", side_str),
+        };
+
+        // Token ids only: cached metadata saves a Gamma round trip
+        let market_details = self.get_market_metadata(market_id).await?;
+
+        let token_id = if side_str.to_uppercase() == "YES" || side_str.to_uppercase() == "BUY" {
+            if market_details.asset_ids.len() >= 2 {
+                market_details.asset_ids[1].clone()
+            } else {
+                anyhow::bail!("Market {} missing YES token ID", market_id);
+            }
+        } else {
+            if !market_details.asset_ids.is_empty() {
+                market_details.asset_ids[0].clone()
+            } else {
+                anyhow::bail!("Market {} missing NO token ID", market_id);
+            }
+        };
+
+        let price = Self::normalize_order_price(price_f64)?;
+        let size = Self::normalize_order_size(size_usd, price)?;
+
+        let token_id_u256 =
+            U256::from_str(&token_id).map_err(|e| anyhow::anyhow!("Invalid token ID: {}", e))?;
+
+        let randomize_salt = self.random_salts;
+        let response = self
+            .with_auth(|session| {
+                let side = side.clone();
+                let order_type = order_type.clone();
+                async move {
+                    // 1-2. Build and Sign Order
+                    let signed_order = Self::build_signed_order(
+                        &session,
+                        token_id_u256,
+                        price,
+                        size,
+                        side,
+                        order_type,
+                        randomize_salt,
+                    )
+                    .await?;
+
+                    // 3. Post Order
+                    let post_res: Result<PostOrderResponse, SdkError> =
+                        session.client.post_order(signed_order).await;
+                    post_res.context("Failed to post order")
+                }
+            })
+            .await?;
+
+        let order_id = response.order_id;
+        info!("✅ LIVE ORDER SUCCESS: ID {}", order_id);
+
+        Ok(order_id)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        if self.is_paper() {
+            info!("📝 [PAPER] Cancel order {}", order_id);
+            return Ok(());
         }
 
-        // Native USDC: 0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359
-        // Bridged USDC: 0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174
-        // We check Native first, then Bridged.
+        let response = self
+            .with_auth(|session| async move {
+                session
+                    .client
+                    .cancel_order(order_id)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to cancel order {}: {}", order_id, e))
+            })
+            .await?;
+
+        if let Some(reason) = response.not_canceled.get(order_id) {
+            anyhow::bail!("Order {} not cancelled: {}", order_id, reason);
+        }
+
+        info!("🗑️ Cancelled order {}", order_id);
+        Ok(())
+    }
+
+    async fn get_open_orders(&self, market_id: Option<&str>) -> Result<Vec<OpenOrder>> {
+        if self.is_paper() {
+            // Paper orders are treated as immediately filled, nothing rests on the book
+            return Ok(Vec::new());
+        }
+
+        // Every page, following `next_cursor` to the end
+        let mut orders = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_ORDER_PAGES {
+            let page = self
+                .breakers
+                .clob
+                .call(self.with_auth(|session| {
+                    let cursor = cursor.clone();
+                    async move {
+                        session
+                            .client
+                            .orders(&OrdersRequest::default(), cursor)
+                            .await
+                            .context("Failed to fetch open orders")
+                    }
+                }))
+                .await?;
+
+            orders.extend(
+                page.data
+                    .iter()
+                    .map(Self::convert_open_order)
+                    .filter(|o| market_id.map_or(true, |m| o.market_id == m)),
+            );
+            if page.data.is_empty() || page.next_cursor.is_empty() || page.next_cursor == END_CURSOR
+            {
+                return Ok(orders);
+            }
+            cursor = Some(page.next_cursor);
+        }
+        // A partial list would leave orders out of what's locked
+        anyhow::bail!(
+            "Open orders listing didn't end within {} pages",
+            MAX_ORDER_PAGES
+        )
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        if self.is_paper() || order_id.starts_with("paper-order-") {
+            return Ok(OrderStatus::Matched);
+        }
+
+        let order = self
+            .breakers
+            .clob
+            .call(self.with_auth(|session| async move {
+                session
+                    .client
+                    .order(order_id)
+                    .await
+                    .with_context(|| format!("Failed to fetch order {}", order_id))
+            }))
+            .await?;
+
+        Ok(Self::convert_open_order(&order).status)
+    }
+
+    async fn get_orderbook(&self, asset_id: &str) -> Result<OrderBook> {
+        // Public endpoint, no auth required
+        let url = format!("{}/book?token_id={}", self.host, asset_id);
+        let book: ClobBookResponse = self
+            .breakers
+            .clob
+            .call(async {
+                let response = self.http_client.get(&url).send().await?;
+
+                if !response.status().is_success() {
+                    anyhow::bail!(UpstreamStatus::new(
+                        format!("CLOB book request failed for {}", asset_id),
+                        response.status()
+                    ));
+                }
+
+                Ok(response.json().await?)
+            })
+            .await?;
+
+        let parse_levels = |levels: &[ClobBookLevel]| -> Vec<OrderLevel> {
+            levels
+                .iter()
+                .filter_map(|l| {
+                    Some(OrderLevel {
+                        price: l.price.parse().ok()?,
+                        size: l.size.parse().ok()?,
+                    })
+                })
+                .collect()
+        };
+
+        // The REST book is not guaranteed best-first, so sort explicitly
+        let mut bids = parse_levels(&book.bids);
+        let mut asks = parse_levels(&book.asks);
+        bids.sort_by(|a, b| {
+            b.price
+                .partial_cmp(&a.price)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        asks.sort_by(|a, b| {
+            a.price
+                .partial_cmp(&b.price)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(OrderBook::from_levels(&bids, &asks))
+    }
+
+    async fn get_fee_rate_bps(&self, asset_id: &str) -> Result<Option<u32>> {
+        // Public endpoint, no auth required
+        let url = format!("{}/fee-rate?token_id={}", self.host, asset_id);
+        let rate: ClobFeeRateResponse = self
+            .breakers
+            .clob
+            .call(async {
+                let response = self.http_client.get(&url).send().await?;
+
+                if !response.status().is_success() {
+                    anyhow::bail!(UpstreamStatus::new(
+                        format!("CLOB fee rate request failed for {}", asset_id),
+                        response.status()
+                    ));
+                }
+
+                Ok(response.json().await?)
+            })
+            .await?;
+
+        Ok(Some(rate.base_fee))
+    }
+
+    async fn get_event_series(&self) -> Result<Vec<EventSeries>> {
+        let url = format!(
+            "{}/series?limit={}&order=createdAt&ascending=false",
+            self.gamma_url, SERIES_PAGE
+        );
+        let series: Vec<GammaSeries> = self
+            .breakers
+            .gamma
+            .call(async {
+                let response = self.http_client.get(&url).send().await?;
+                if !response.status().is_success() {
+                    anyhow::bail!(UpstreamStatus::new(
+                        "Gamma series request failed",
+                        response.status()
+                    ));
+                }
+                Ok(response.json().await?)
+            })
+            .await?;
+
+        let mut series: Vec<EventSeries> = series
+            .into_iter()
+            .filter_map(|series| {
+                Some(EventSeries {
+                    id: match series.id {
+                        serde_json::Value::String(id) => id,
+                        serde_json::Value::Number(id) => id.to_string(),
+                        _ => return None,
+                    },
+                    title: series.title.unwrap_or_else(|| series.slug.clone()),
+                    slug: series.slug,
+                    recurrence: series.recurrence,
+                    created_at: series
+                        .created_at
+                        .as_deref()
+                        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                        .map(|at| at.with_timezone(&Utc)),
+                })
+            })
+            .collect();
+        series.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(series)
+    }
+
+    async fn get_series_markets(&self, series_id: &str) -> Result<Vec<MarketData>> {
+        let url = format!(
+            "{}/events?series_id={}&closed=false&limit={}",
+            self.gamma_url, series_id, SERIES_PAGE
+        );
+        let events: Vec<GammaEvent> = self
+            .breakers
+            .gamma
+            .call(async {
+                let response = self.http_client.get(&url).send().await?;
+                if !response.status().is_success() {
+                    anyhow::bail!(UpstreamStatus::new(
+                        format!("Gamma events request failed for series {}", series_id),
+                        response.status()
+                    ));
+                }
+                Ok(response.json().await?)
+            })
+            .await?;
 
+        // Markets not tradable yet (no token ids) are skipped
+        Ok(events
+            .iter()
+            .flat_map(|event| &event.markets)
+            .filter_map(|market| self.convert_gamma_market(market).ok())
+            .collect())
+    }
+
+    fn downgrade_to_paper(&self) {
+        if !self.paper_trading.swap(true, Ordering::Relaxed) {
+            warn!("📝 Live order submission disabled, continuing in PAPER mode");
+        }
+    }
+
+    async fn get_market_by_slug(&self, slug: &str) -> Result<MarketData> {
+        let url = format!("{}/markets?slug={}", self.gamma_url, slug);
+        let markets: Vec<GammaMarket> = self
+            .breakers
+            .gamma
+            .call(async {
+                let response = self.http_client.get(&url).send().await?;
+                if !response.status().is_success() {
+                    anyhow::bail!(UpstreamStatus::new(
+                        format!("Gamma API error for slug {}", slug),
+                        response.status()
+                    ));
+                }
+                Ok(response.json().await?)
+            })
+            .await?;
+        if let Some(market) = markets.first() {
+            return self.convert_gamma_market(market);
+        }
+
+        // Event URLs carry the event slug; fine as long as the event has a single market
+        let url = format!("{}/events?slug={}", self.gamma_url, slug);
+        let events: Vec<GammaEvent> = self
+            .breakers
+            .gamma
+            .call(async {
+                let response = self.http_client.get(&url).send().await?;
+                if !response.status().is_success() {
+                    anyhow::bail!(UpstreamStatus::new(
+                        format!("Gamma API error for event {}", slug),
+                        response.status()
+                    ));
+                }
+                Ok(response.json().await?)
+            })
+            .await?;
+        let event = events
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No market or event with slug {}", slug))?;
+        match event.markets.as_slice() {
+            [market] => self.convert_gamma_market(market),
+            [] => anyhow::bail!("Event {} has no markets", slug),
+            markets => anyhow::bail!(
+                "Event {} has {} markets, use a market slug: {}",
+                slug,
+                markets.len(),
+                markets
+                    .iter()
+                    .filter_map(|m| m.slug.as_deref())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    async fn poll_new_markets(&self) -> Result<Vec<MarketData>> {
+        // Skipped while Gamma is down; the full scan catches up once it recovers
+        match self
+            .breakers
+            .gamma
+            .call(self.fetch_new_gamma_markets())
+            .await
+        {
+            Err(e) if is_circuit_open(&e) => Ok(Vec::new()),
+            result => result,
+        }
+    }
+}
+
+// Keep inherent impl for helper methods and new
+impl PolymarketClient {
+    /// ERC-20 balanceOf(owner) for a USDC variant, trying each RPC until one answers. An error
+    /// if none does: an unknown balance must not read as an empty wallet
+    /// RPCs with an open breaker are skipped; if all were skipped the last known balance is used
+    async fn erc20_balance(&self, collateral: Collateral, owner: &str) -> Result<f64> {
         // Selector for balanceOf(address): 70a08231
         // Pad address to 32 bytes (64 hex chars)
         // proxy string is 0x... (42 chars). strip 0x, pad left with 0s to 64 chars.
-        let addr_clean = target_addr.trim_start_matches("0x");
+        let addr_clean = owner.trim_start_matches("0x");
         let data = format!("0x70a08231000000000000000000000000{}", addr_clean);
 
         let mut rpc_candidates = vec![
@@ -170,167 +723,240 @@ impl MarketInterface for PolymarketClient {
         // Dedup and keep order.
         rpc_candidates.dedup();
 
+        let req = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_call",
+            "params": [{
+                "to": collateral.address(),
+                "data": data
+            }, "latest"],
+            "id": 1
+        });
+
+        let cache_key = (collateral, owner.to_string());
+        let mut attempted = false;
         for rpc_url in rpc_candidates {
-            let native_req = serde_json::json!({
-                "jsonrpc": "2.0",
-                "method": "eth_call",
-                "params": [{
-                    "to": "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359",
-                    "data": data
-                }, "latest"],
-                "id": 1
-            });
-
-            match self
-                .http_client
-                .post(&rpc_url)
-                .json(&native_req)
-                .send()
-                .await
-            {
-                Ok(resp) => {
-                    let json: serde_json::Value = match resp.json().await {
-                        Ok(v) => v,
-                        Err(e) => {
-                            warn!(
-                                "⚠️ Failed to parse balance response from {}: {}",
-                                rpc_url, e
-                            );
-                            continue;
-                        }
-                    };
-
-                    if let Some(err) = json.get("error") {
-                        warn!("⚠️ RPC error from {}: {}", rpc_url, err);
-                        continue;
-                    }
-
-                    if let Some(result) = json.get("result").and_then(|v| v.as_str()) {
-                        if let Ok(amount) =
-                            u128::from_str_radix(result.trim_start_matches("0x"), 16)
-                        {
-                            let balance = amount as f64 / 1_000_000.0;
-                            if balance > 0.0 {
-                                info!(
-                                    "💰 Native USDC balance on {} via {}: ${:.2}",
-                                    target_addr, rpc_url, balance
+            let breaker = self.breakers.rpc(&rpc_url);
+            if !breaker.allow() {
+                continue;
+            }
+            attempted = true;
+
+            let json: serde_json::Value =
+                match self.http_client.post(&rpc_url).json(&req).send().await {
+                    Ok(resp) => {
+                        let status = resp.status();
+                        match resp.json().await {
+                            Ok(v) => v,
+                            Err(e) => {
+                                warn!(
+                                    "⚠️ Failed to parse {} balance response from {} ({}): {}",
+                                    collateral.symbol(),
+                                    rpc_url,
+                                    status,
+                                    e
                                 );
-                                return Ok(balance);
+                                breaker.record(!status.is_server_error());
+                                continue;
                             }
                         }
                     }
-                }
-                Err(e) => {
-                    warn!("⚠️ Balance RPC request failed via {}: {}", rpc_url, e);
-                    continue;
-                }
-            }
+                    Err(e) => {
+                        warn!(
+                            "⚠️ {} balance RPC request failed via {}: {}",
+                            collateral.symbol(),
+                            rpc_url,
+                            e
+                        );
+                        breaker.record(!is_upstream_failure(&anyhow::Error::from(e)));
+                        continue;
+                    }
+                };
 
-            let bridged_req = serde_json::json!({
-                "jsonrpc": "2.0",
-                "method": "eth_call",
-                "params": [{
-                    "to": "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174",
-                    "data": data
-                }, "latest"],
-                "id": 2
-            });
+            // A JSON-RPC error is the node answering: move on without counting it
+            if let Some(err) = json.get("error") {
+                warn!("⚠️ RPC error from {}: {}", rpc_url, err);
+                breaker.record(true);
+                continue;
+            }
 
-            match self
-                .http_client
-                .post(&rpc_url)
-                .json(&bridged_req)
-                .send()
-                .await
+            if let Some(amount) = json
+                .get("result")
+                .and_then(|v| v.as_str())
+                .and_then(|r| u128::from_str_radix(r.trim_start_matches("0x"), 16).ok())
             {
-                Ok(resp) => {
-                    let json: serde_json::Value = match resp.json().await {
-                        Ok(v) => v,
-                        Err(e) => {
-                            warn!(
-                                "⚠️ Failed to parse bridged balance response from {}: {}",
-                                rpc_url, e
-                            );
-                            continue;
-                        }
-                    };
-
-                    if let Some(err) = json.get("error") {
-                        warn!("⚠️ RPC bridged error from {}: {}", rpc_url, err);
-                        continue;
-                    }
+                breaker.record(true);
+                let balance = amount as f64 / 1_000_000.0;
+                self.balance_cache
+                    .lock()
+                    .unwrap()
+                    .insert(cache_key, balance);
+                return Ok(balance);
+            }
+        }
 
-                    if let Some(result) = json.get("result").and_then(|v| v.as_str()) {
-                        if let Ok(amount) =
-                            u128::from_str_radix(result.trim_start_matches("0x"), 16)
-                        {
-                            let balance = amount as f64 / 1_000_000.0;
-                            if balance > 0.0 {
-                                info!(
-                                    "💰 Bridged USDC balance on {} via {}: ${:.2}",
-                                    target_addr, rpc_url, balance
-                                );
-                                return Ok(balance);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!(
-                        "⚠️ Bridged balance RPC request failed via {}: {}",
-                        rpc_url, e
-                    );
-                    continue;
-                }
+        if !attempted {
+            if let Some(balance) = self.balance_cache.lock().unwrap().get(&cache_key) {
+                debug!(
+                    "All RPC breakers open - using last known {} balance ${:.2}",
+                    collateral.symbol(),
+                    balance
+                );
+                return Ok(*balance);
             }
         }
+        anyhow::bail!(
+            "No RPC endpoint returned the {} balance of {}",
+            collateral.symbol(),
+            owner
+        )
+    }
 
-        Ok(0.0)
+    /// Run the CLOB authentication flow (or reuse the session), e.g. to check credentials
+    pub async fn authenticate(&self) -> Result<()> {
+        self.auth_session().await.map(|_| ())
     }
 
-    async fn place_order(
+    /// Build and sign a limit order for `token_id` as `place_order` would, without posting it
+    pub async fn sign_order(
         &self,
-        market_id: &str,
-        side_str: &str,
+        token_id: &str,
+        side: Side,
         size_usd: f64,
         price_f64: f64,
         order_type: OrderType,
-    ) -> Result<String> {
-        if self.paper_trading {
-            info!(
-                "📝 [PAPER] Order: {} ${:.2} @ ${:.4} on market {}",
-                side_str, size_usd, price_f64, market_id
-            );
-            return Ok(format!("paper-order-{}", uuid::Uuid::new_v4()));
-        }
+    ) -> Result<SignedOrder> {
+        let price = Self::normalize_order_price(price_f64)?;
+        let size = Self::normalize_order_size(size_usd, price)?;
+        let token_id =
+            U256::from_str(token_id).map_err(|e| anyhow::anyhow!("Invalid token ID: {}", e))?;
+
+        let randomize_salt = self.random_salts;
+        self.with_auth(|session| {
+            let side = side.clone();
+            let order_type = order_type.clone();
+            async move {
+                Self::build_signed_order(
+                    &session,
+                    token_id,
+                    price,
+                    size,
+                    side,
+                    order_type,
+                    randomize_salt,
+                )
+                .await
+            }
+        })
+        .await
+    }
 
-        info!(
-            "🚨 LIVE ORDER: {} ${:.2} @ ${:.4} on market {}",
-            side_str, size_usd, price_f64, market_id
-        );
+    async fn build_signed_order(
+        session: &AuthSession,
+        token_id: U256,
+        price: Decimal,
+        size: Decimal,
+        side: Side,
+        order_type: OrderType,
+        randomize_salt: bool,
+    ) -> Result<SignedOrder> {
+        let builder = session
+            .client
+            .limit_order()
+            .token_id(token_id)
+            .price(price)
+            .size(size)
+            .side(side)
+            .order_type(order_type);
 
-        let side = match side_str.to_uppercase().as_str() {
-            "BUY" | "YES" => Side::Buy,
-            "SELL" | "NO" => Side::Sell,
-            _ => anyhow::bail!("Invalid side: {}", side_str),
-        };
+        let build_res: Result<SignableOrder, SdkError> = builder.build().await;
+        let mut order = build_res.map_err(|e| anyhow::anyhow!("Failed to build order: {}", e))?;
+        if randomize_salt {
+            order.order.salt = U256::from(random_salt());
+        }
 
-        let market_details = self.get_market_details(market_id).await?;
+        let sign_res: Result<SignedOrder, SdkError> =
+            session.client.sign(&session.signer, order).await;
+        sign_res.map_err(|e| anyhow::anyhow!("Failed to sign order: {}", e))
+    }
 
-        let token_id = if side_str.to_uppercase() == "YES" || side_str.to_uppercase() == "BUY" {
-            if market_details.asset_ids.len() >= 2 {
-                market_details.asset_ids[1].clone()
-            } else {
-                anyhow::bail!("Market {} missing YES token ID", market_id);
+    /// Run an authenticated CLOB call, re-authenticating once if the session was rejected
+    async fn with_auth<T, F, Fut>(&self, op: F) -> Result<T>
+    where
+        F: Fn(Arc<AuthSession>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let session = self.auth_session().await?;
+        match op(session).await {
+            Err(e) if is_auth_error(&e) => {
+                warn!("🔑 CLOB rejected credentials ({}), re-authenticating", e);
+                self.invalidate_auth_session().await;
+                let session = self.auth_session().await?;
+                op(session).await
             }
-        } else {
-            if !market_details.asset_ids.is_empty() {
-                market_details.asset_ids[0].clone()
-            } else {
-                anyhow::bail!("Market {} missing NO token ID", market_id);
+            result => result,
+        }
+    }
+
+    /// Cached authenticated session, (re)built on demand with bounded retries
+    async fn auth_session(&self) -> Result<Arc<AuthSession>> {
+        let mut cached = self.auth_session.lock().await;
+        if let Some(session) = cached.as_ref() {
+            return Ok(session.clone());
+        }
+
+        let attempts = self.auth_max_retries.max(1);
+        let mut last_err = None;
+        for attempt in 1..=attempts {
+            match self.authenticated_client().await {
+                Ok((client, signer)) => {
+                    if attempt > 1 {
+                        info!("🔑 CLOB re-authenticated after {} attempts", attempt);
+                    }
+                    let session = Arc::new(AuthSession { client, signer });
+                    *cached = Some(session.clone());
+                    return Ok(session);
+                }
+                Err(e) => {
+                    warn!(
+                        "⚠️ CLOB authentication attempt {}/{} failed: {}",
+                        attempt, attempts, e
+                    );
+                    last_err = Some(e);
+                    if attempt < attempts {
+                        tokio::time::sleep(AUTH_RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+                    }
+                }
             }
-        };
+        }
+
+        let e = last_err.unwrap_or_else(|| anyhow::anyhow!("no authentication attempts made"));
+        error!(
+            "🚨 ALERT: CLOB authentication failed after {} attempts - orders cannot be placed until credentials are fixed: {}",
+            attempts, e
+        );
+        Err(e.context("CLOB authentication failed"))
+    }
+
+    /// Drop the cached session so the next call re-runs the authentication flow
+    async fn invalidate_auth_session(&self) {
+        *self.auth_session.lock().await = None;
+    }
 
+    /// The read-only build never turns a private key into a signer: every authenticated call
+    /// (order entry, cancels, open orders) fails here
+    #[cfg(not(feature = "trading"))]
+    async fn authenticated_client(
+        &self,
+    ) -> Result<(ClobClient<Authenticated<Normal>>, PrivateKeySigner)> {
+        anyhow::bail!("Read-only build: CLOB authentication and order signing are compiled out")
+    }
+
+    /// Run the authentication flow for the configured private key
+    #[cfg(feature = "trading")]
+    async fn authenticated_client(
+        &self,
+    ) -> Result<(ClobClient<Authenticated<Normal>>, PrivateKeySigner)> {
         // Authenticated Client Setup
         let signer = if let Some(pk) = &self.private_key {
             LocalSigner::from_str(pk)
@@ -342,10 +968,8 @@ impl MarketInterface for PolymarketClient {
 
         // Create a NEW unauthenticated client (not clone) to avoid Arc ref count issues
         // SDK's authenticate() consumes the client and requires Arc::into_inner() to succeed
-        let fresh_client = ClobClient::new(
-            "https://clob.polymarket.com",
-            polymarket_client_sdk::clob::Config::default(),
-        )?;
+        let fresh_client =
+            ClobClient::new(&self.host, polymarket_client_sdk::clob::Config::default())?;
 
         // Use SDK-derived Safe wallet as funder
         // This is the signup address shown in Polymarket UI
@@ -361,42 +985,43 @@ impl MarketInterface for PolymarketClient {
             .authenticate()
             .await?;
 
-        let price = Self::normalize_order_price(price_f64)?;
-        let size = Self::normalize_order_size(size_usd, price)?;
-
-        let token_id_u256 =
-            U256::from_str(&token_id).map_err(|e| anyhow::anyhow!("Invalid token ID: {}", e))?;
-
-        // 1. Build Order
-        let builder = auth_client
-            .limit_order()
-            .token_id(token_id_u256)
-            .price(price)
-            .size(size)
-            .side(side)
-            .order_type(order_type);
-
-        let build_res: Result<SignableOrder, SdkError> = builder.build().await;
-        let order = build_res.map_err(|e| anyhow::anyhow!("Failed to build order: {}", e))?;
-
-        // 2. Sign Order
-        let sign_res: Result<SignedOrder, SdkError> = auth_client.sign(&signer, order).await;
-        let signed_order = sign_res.map_err(|e| anyhow::anyhow!("Failed to sign order: {}", e))?;
-
-        // 3. Post Order
-        let post_res: Result<PostOrderResponse, SdkError> =
-            auth_client.post_order(signed_order).await;
-        let response = post_res.map_err(|e| anyhow::anyhow!("Failed to post order: {}", e))?;
+        Ok((auth_client, signer))
+    }
 
-        let order_id = response.order_id;
-        info!("✅ LIVE ORDER SUCCESS: ID {}", order_id);
+    /// Convert an SDK open-order response to our OpenOrder
+    fn convert_open_order(order: &OpenOrderResponse) -> OpenOrder {
+        let size_matched = order.size_matched.to_string().parse().unwrap_or(0.0);
+        OpenOrder {
+            order_id: order.id.clone(),
+            market_id: order.market.to_string(),
+            asset_id: order.asset_id.to_string(),
+            side: match order.side {
+                Side::Buy => "BUY",
+                Side::Sell => "SELL",
+                _ => "UNKNOWN",
+            }
+            .to_string(),
+            price: order.price.to_string().parse().unwrap_or(0.0),
+            original_size: order.original_size.to_string().parse().unwrap_or(0.0),
+            size_matched,
+            status: Self::convert_order_status(&order.status, size_matched),
+            created_at: order.created_at.timestamp().max(0) as u64,
+        }
+    }
 
-        Ok(order_id)
+    /// The CLOB keeps a partly filled order LIVE; `size_matched` tells it apart
+    fn convert_order_status(status: &OrderStatusType, size_matched: f64) -> OrderStatus {
+        match status {
+            OrderStatusType::Live | OrderStatusType::Delayed if size_matched > 0.0 => {
+                OrderStatus::PartiallyFilled
+            }
+            OrderStatusType::Live | OrderStatusType::Delayed => OrderStatus::Live,
+            OrderStatusType::Matched => OrderStatus::Matched,
+            OrderStatusType::Canceled | OrderStatusType::Unmatched => OrderStatus::Cancelled,
+            _ => OrderStatus::Unknown,
+        }
     }
-}
 
-// Keep inherent impl for helper methods and new
-impl PolymarketClient {
     fn normalize_order_price(price_f64: f64) -> Result<Decimal> {
         if !price_f64.is_finite() || price_f64 <= 0.0 {
             anyhow::bail!("Invalid price: {}", price_f64);
@@ -440,6 +1065,10 @@ impl PolymarketClient {
         Ok(normalized)
     }
 
+    fn is_paper(&self) -> bool {
+        self.paper_trading.load(Ordering::Relaxed)
+    }
+
     pub fn new(
         config: &PolymarketConfig,
         paper_trading: bool,
@@ -483,17 +1112,147 @@ impl PolymarketClient {
         Ok(Self {
             client,
             http_client,
+            host: config.host.clone(),
             gamma_url: "https://gamma-api.polymarket.com".to_string(),
-            paper_trading,
+            paper_trading: AtomicBool::new(paper_trading),
             proxy_address,
             api_key: config.api_key.clone(),
             secret: config.secret.clone(),
             passphrase: config.passphrase.clone(),
             signer_address,
             private_key,
+            new_market_cursor: std::sync::Mutex::new(NewMarketCursor::default()),
+            auth_session: tokio::sync::Mutex::new(None),
+            auth_max_retries: config.auth_max_retries,
+            breakers: Arc::new(CircuitBreakers::new(config.circuit_breaker.clone())),
+            markets_cache: std::sync::Mutex::new(Vec::new()),
+            balance_cache: std::sync::Mutex::new(HashMap::new()),
+            derived_assets: None,
+            neg_risk_groups: None,
+            resolution_proposals: None,
+            metadata_cache: None,
+            random_salts: false,
         })
     }
 
+    /// Share breakers with other clients of the same upstreams
+    pub fn with_breakers(mut self, breakers: Arc<CircuitBreakers>) -> Self {
+        self.breakers = breakers;
+        self
+    }
+
+    /// Record the collateral of markets Gamma marks as neg-risk, so their positions are
+    /// redeemed and merged on the right collateral
+    pub fn with_derived_assets(mut self, derived_assets: DerivedAssetCache) -> Self {
+        self.derived_assets = Some(derived_assets);
+        self
+    }
+
+    /// Record which adapter market each neg-risk market belongs to, for the convert path
+    pub fn with_neg_risk_groups(mut self, neg_risk_groups: NegRiskGroups) -> Self {
+        self.neg_risk_groups = Some(neg_risk_groups);
+        self
+    }
+
+    /// Record which markets Gamma has a UMA resolution proposed for
+    pub fn with_resolution_proposals(mut self, resolution_proposals: ResolutionProposals) -> Self {
+        self.resolution_proposals = Some(resolution_proposals);
+        self
+    }
+
+    /// Keep the metadata of markets seen on Gamma, served for token ids within its TTL and
+    /// in place of Gamma while it's down (None: always ask Gamma)
+    pub fn with_metadata_cache(mut self, metadata_cache: Option<MetadataCache>) -> Self {
+        self.metadata_cache = metadata_cache;
+        self
+    }
+
+    /// Sign orders with a random salt instead of the SDK's time-based one
+    pub fn with_random_salts(mut self, random_salts: bool) -> Self {
+        self.random_salts = random_salts;
+        self
+    }
+
+    /// Fetch only markets created since the last call
+    /// Sends If-None-Match / If-Modified-Since so an unchanged newest-first page costs a 304,
+    /// and filters by a Gamma id cursor. The first call only primes the cursor
+    pub async fn fetch_new_gamma_markets(&self) -> Result<Vec<MarketData>> {
+        let cursor = self.new_market_cursor.lock().unwrap().clone();
+
+        let url = format!(
+            "{}/markets?active=true&closed=false&order=id&ascending=false&limit={}",
+            self.gamma_url, NEW_MARKET_PAGE_SIZE
+        );
+        let mut request = self.http_client.get(&url);
+        if let Some(etag) = &cursor.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cursor.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            anyhow::bail!(UpstreamStatus::new(
+                "Gamma new-market poll failed",
+                response.status()
+            ));
+        }
+
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+        let markets: Vec<GammaMarket> = response.json().await?;
+        let newest_id = markets
+            .iter()
+            .filter_map(|m| m.id.parse::<u64>().ok())
+            .max();
+
+        {
+            let mut state = self.new_market_cursor.lock().unwrap();
+            state.etag = etag;
+            state.last_modified = last_modified;
+            if newest_id > state.last_id {
+                state.last_id = newest_id;
+            }
+        }
+
+        let Some(last_id) = cursor.last_id else {
+            debug!("Primed new-market cursor at Gamma id {:?}", newest_id);
+            return Ok(Vec::new());
+        };
+
+        let new_markets: Vec<MarketData> = markets
+            .iter()
+            .filter(|m| m.id.parse::<u64>().map(|id| id > last_id).unwrap_or(false))
+            .filter_map(|m| match self.convert_gamma_market(m) {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    warn!("Failed to convert market {}: {}", m.question, e);
+                    None
+                }
+            })
+            .collect();
+
+        if new_markets.len() >= NEW_MARKET_PAGE_SIZE {
+            warn!(
+                "⚠️ New-market poll page is full ({}), some markets may only be seen by the full scan",
+                NEW_MARKET_PAGE_SIZE
+            );
+        }
+        Ok(new_markets)
+    }
+
     /// Fetch all active markets
     pub async fn fetch_markets(&self) -> Result<Vec<MarketResponse>> {
         debug!("Fetching markets from Polymarket");
@@ -538,10 +1297,11 @@ impl PolymarketClient {
             id: market
                 .condition_id
                 .as_ref()
-                .map(|b| b.to_string())
+                .map(|b| normalize_market_id(&b.to_string()).into_owned().into())
                 .unwrap_or_default(),
             question: market.question.clone(),
             end_date: market.end_date_iso.map(|dt| dt.to_string()),
+            end_time: None,
             volume,
             liquidity,
             yes_price,
@@ -552,7 +1312,9 @@ impl PolymarketClient {
             best_bid: 0.0,
             best_ask: 0.0,
             asset_ids,
-        })
+            category: Some(MarketCategory::classify(&market.question)),
+        }
+        .with_end_time())
     }
 
     /// Convert GammaMarket to MarketData
@@ -617,10 +1379,59 @@ impl PolymarketClient {
             serde_json::from_str(&market.clob_token_ids).unwrap_or_default()
         };
 
+        if market.neg_risk {
+            if let (Some(derived_assets), Ok(condition)) = (
+                &self.derived_assets,
+                ConditionId::parse(&market.condition_id),
+            ) {
+                if let Err(e) =
+                    derived_assets.record_collateral(&condition, PositionCollateral::NegRiskWrapped)
+                {
+                    warn!("Failed to record collateral of {}: {}", condition, e);
+                }
+            }
+            if let (Some(groups), Some(neg_risk_market_id), Some(question_id)) = (
+                &self.neg_risk_groups,
+                &market.neg_risk_market_id,
+                &market.question_id,
+            ) {
+                groups.record(
+                    &normalize_market_id(&market.condition_id),
+                    neg_risk_market_id,
+                    question_id,
+                );
+            }
+        }
+
+        if let Some(proposals) = &self.resolution_proposals {
+            proposals.record(
+                &normalize_market_id(&market.condition_id),
+                market.uma_resolution_status.as_deref(),
+            );
+        }
+
+        if let Some(cache) = &self.metadata_cache {
+            cache.record(MarketMetadata {
+                condition_id: market.condition_id.clone(),
+                question: market.question.clone(),
+                description: market.description.clone(),
+                outcomes: outcomes.clone(),
+                asset_ids: asset_ids.clone(),
+                end_date: market.end_date_iso.clone(),
+                collateral: market
+                    .neg_risk
+                    .then_some(PositionCollateral::NegRiskWrapped),
+                fetched_at: Utc::now(),
+            });
+        }
+
         Ok(MarketData {
-            id: market.condition_id.clone(),
+            id: normalize_market_id(&market.condition_id)
+                .into_owned()
+                .into(),
             question: market.question.clone(),
             end_date: market.end_date_iso.clone(),
+            end_time: None,
             volume,
             liquidity,
             yes_price,
@@ -635,16 +1446,66 @@ impl PolymarketClient {
             best_bid: 0.0,
             best_ask: 0.0,
             asset_ids,
-        })
+            category: Some(MarketCategory::classify(&market.question)),
+        }
+        .with_end_time())
     }
 }
 
+/// CLOB REST `/book` response
+#[derive(Debug, Clone, Deserialize)]
+struct ClobBookResponse {
+    #[serde(default)]
+    bids: Vec<ClobBookLevel>,
+    #[serde(default)]
+    asks: Vec<ClobBookLevel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ClobBookLevel {
+    price: String,
+    size: String,
+}
+
+/// CLOB REST `/fee-rate` response
+#[derive(Debug, Clone, Deserialize)]
+struct ClobFeeRateResponse {
+    /// Per trade, in bps. No default: a response without it mustn't read as fee-free
+    base_fee: u32,
+}
+
+/// Gamma event series (a recurring family of events)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GammaSeries {
+    /// A string on some responses, a number on others
+    id: serde_json::Value,
+    slug: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    recurrence: Option<String>,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+/// Gamma event (a group of markets under one page / URL)
+#[derive(Debug, Clone, Deserialize)]
+struct GammaEvent {
+    #[serde(default)]
+    markets: Vec<GammaMarket>,
+}
+
 /// Struct matching Gamma API response format
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GammaMarket {
+    #[serde(default)]
+    pub id: String,
     pub condition_id: String,
     pub question: String,
+    #[serde(default)]
+    pub slug: Option<String>,
     // end_date_iso is optional
     pub end_date_iso: Option<String>,
     pub description: Option<String>,
@@ -659,4 +1520,40 @@ struct GammaMarket {
     #[serde(default)]
     pub volume_24hr: serde_json::Value, // Added for popularity filter
     pub liquidity: serde_json::Value, // Can be String or Number
+    /// Multi-outcome market: its conditions are backed by the NegRiskAdapter's wrapped USDC.e
+    #[serde(default)]
+    pub neg_risk: bool,
+    /// The NegRiskAdapter market the question belongs to (neg-risk markets only)
+    #[serde(default, rename = "negRiskMarketID")]
+    pub neg_risk_market_id: Option<String>,
+    #[serde(default, rename = "questionID")]
+    pub question_id: Option<String>,
+    /// UMA oracle progress: "proposed", "disputed", "resolved" (absent before a proposal)
+    #[serde(default)]
+    pub uma_resolution_status: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_error_needs_a_401_status() {
+        let rejected = anyhow::Error::new(UpstreamStatus::new(
+            "CLOB order",
+            reqwest::StatusCode::UNAUTHORIZED,
+        ))
+        .context("Failed to post order");
+        assert!(is_auth_error(&rejected));
+
+        let throttled = anyhow::Error::new(UpstreamStatus::new(
+            "CLOB order",
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+        ));
+        assert!(!is_auth_error(&throttled));
+
+        // A 401 inside an id or message is not a credentials rejection
+        let mentions = anyhow::anyhow!("Failed to fetch order 0x401f: unauthorized-looking text");
+        assert!(!is_auth_error(&mentions));
+    }
 }
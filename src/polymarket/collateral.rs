@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+
+/// Native USDC issued by Circle on Polygon
+pub const USDC_ADDRESS: &str = "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359";
+/// Bridged USDC (PoS bridge), the collateral the Polymarket exchanges settle in
+pub const USDC_E_ADDRESS: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+
+/// The two USDC variants on Polygon (both 6 decimals)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Collateral {
+    Usdc,
+    UsdcE,
+}
+
+impl Collateral {
+    pub fn address(&self) -> &'static str {
+        match self {
+            Collateral::Usdc => USDC_ADDRESS,
+            Collateral::UsdcE => USDC_E_ADDRESS,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Collateral::Usdc => "USDC",
+            Collateral::UsdcE => "USDC.e",
+        }
+    }
+
+    /// The other variant (swap counterpart)
+    pub fn other(&self) -> Collateral {
+        match self {
+            Collateral::Usdc => Collateral::UsdcE,
+            Collateral::UsdcE => Collateral::Usdc,
+        }
+    }
+}
+
+/// Polymarket's NegRiskAdapter: oracle of multi-outcome conditions, and the contract their
+/// positions are redeemed and merged through
+pub const NEG_RISK_ADAPTER_ADDRESS: &str = "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296";
+/// USDC.e wrapped by the NegRiskAdapter, the CTF collateral of multi-outcome conditions
+pub const NEG_RISK_WRAPPED_COLLATERAL_ADDRESS: &str = "0x3A3BD7bb9528E159577F7C2e685CC81A765002E2";
+
+/// Collateral a condition's outcome tokens are backed by on the CTF. It goes into the token
+/// ids and has to be named again to redeem or merge them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PositionCollateral {
+    /// Binary markets
+    #[default]
+    UsdcE,
+    /// Multi-outcome (negative risk) markets: wrapped USDC.e, redeemed through the adapter
+    NegRiskWrapped,
+    /// Any other ERC-20 (other deployments, e.g. native USDC)
+    Token(String),
+}
+
+impl PositionCollateral {
+    pub fn address(&self) -> &str {
+        match self {
+            PositionCollateral::UsdcE => USDC_E_ADDRESS,
+            PositionCollateral::NegRiskWrapped => NEG_RISK_WRAPPED_COLLATERAL_ADDRESS,
+            PositionCollateral::Token(address) => address,
+        }
+    }
+
+    /// From a configured token address; the known ones map to their variants
+    pub fn from_address(address: &str) -> Self {
+        let address = address.trim();
+        if address.eq_ignore_ascii_case(USDC_E_ADDRESS) {
+            PositionCollateral::UsdcE
+        } else if address.eq_ignore_ascii_case(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS) {
+            PositionCollateral::NegRiskWrapped
+        } else {
+            PositionCollateral::Token(address.to_string())
+        }
+    }
+
+    /// From the oracle of a ConditionPreparation event: the NegRiskAdapter prepares its
+    /// conditions on wrapped collateral, every other oracle on `default`
+    pub fn for_oracle(oracle: &str, default: &PositionCollateral) -> Self {
+        if oracle.eq_ignore_ascii_case(NEG_RISK_ADAPTER_ADDRESS) {
+            PositionCollateral::NegRiskWrapped
+        } else {
+            default.clone()
+        }
+    }
+
+    /// Redeemed and merged through the NegRiskAdapter rather than the CTF directly
+    pub fn is_neg_risk(&self) -> bool {
+        matches!(self, PositionCollateral::NegRiskWrapped)
+    }
+}
+
+/// Polymarket exchange contracts an order can settle on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExchangeContract {
+    /// Binary markets
+    CtfExchange,
+    /// Multi-outcome (negative risk) markets
+    NegRiskCtfExchange,
+}
+
+impl ExchangeContract {
+    pub fn address(&self) -> &'static str {
+        match self {
+            ExchangeContract::CtfExchange => "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E",
+            ExchangeContract::NegRiskCtfExchange => "0xC5d563A36AE78145C45a50134d48A1215220f80a",
+        }
+    }
+
+    /// Collateral the exchange pulls when an order is matched
+    pub fn collateral(&self) -> Collateral {
+        match self {
+            ExchangeContract::CtfExchange | ExchangeContract::NegRiskCtfExchange => {
+                Collateral::UsdcE
+            }
+        }
+    }
+}
+
+/// Collateral the trading path needs (markets we trade settle on the CTF exchange)
+pub fn required_collateral() -> Collateral {
+    ExchangeContract::CtfExchange.collateral()
+}
+
+/// Wallet balance of each USDC variant, in dollars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CollateralBalances {
+    pub usdc: f64,
+    pub usdc_e: f64,
+}
+
+impl CollateralBalances {
+    pub fn of(&self, collateral: Collateral) -> f64 {
+        match collateral {
+            Collateral::Usdc => self.usdc,
+            Collateral::UsdcE => self.usdc_e,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.usdc + self.usdc_e
+    }
+
+    /// True when the collateral the exchange needs is below `floor_usd`
+    pub fn is_short(&self, required: Collateral, floor_usd: f64) -> bool {
+        self.of(required) < floor_usd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balances_are_tracked_per_variant() {
+        let balances = CollateralBalances {
+            usdc: 50.0,
+            usdc_e: 2.0,
+        };
+        assert_eq!(required_collateral(), Collateral::UsdcE);
+        assert_eq!(balances.of(Collateral::UsdcE), 2.0);
+        assert_eq!(balances.total(), 52.0);
+        assert!(balances.is_short(Collateral::UsdcE, 10.0));
+        assert!(!balances.is_short(Collateral::Usdc, 10.0));
+        assert_eq!(Collateral::UsdcE.other(), Collateral::Usdc);
+    }
+
+    #[test]
+    fn test_position_collateral_from_oracle_and_config() {
+        let default = PositionCollateral::from_address(&USDC_E_ADDRESS.to_lowercase());
+        assert_eq!(default, PositionCollateral::UsdcE);
+        assert_eq!(
+            PositionCollateral::for_oracle(&NEG_RISK_ADAPTER_ADDRESS.to_lowercase(), &default),
+            PositionCollateral::NegRiskWrapped
+        );
+        let uma = "0x6A9D222616C90FcA5754cd1333cFD9b7fb6a4F74";
+        assert_eq!(PositionCollateral::for_oracle(uma, &default), default);
+
+        let native = PositionCollateral::from_address(USDC_ADDRESS);
+        assert_eq!(native.address(), USDC_ADDRESS);
+        assert!(!native.is_neg_risk());
+    }
+}
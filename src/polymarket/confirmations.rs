@@ -0,0 +1,210 @@
+//! Confirmation depth and dedup window for chain events
+//!
+//! A reorg can deliver a ConditionPreparation for a block that then leaves the canonical chain
+//! (the subscription repeats the log with `removed` set, when the provider bothers), and a
+//! resubscription or the log of the new canonical block delivers the same condition again.
+//! Events wait here until `CHAIN_CONFIRMATIONS` blocks sit on top of theirs; the listener then
+//! checks the block hash is still canonical before acting. A condition acted on is remembered
+//! for `CHAIN_DEDUP_WINDOW_SECS` so a repeat of it is dropped.
+use chrono::{DateTime, Duration, Utc};
+use ethers::types::H256;
+use std::collections::HashMap;
+
+use crate::config::ChainEventsConfig;
+
+/// A chain event keyed by what it's about (a condition id), with the block that carried it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainEvent<T> {
+    pub key: String,
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub payload: T,
+}
+
+/// What to do with a delivered event
+#[derive(Debug, PartialEq)]
+pub enum Admission<T> {
+    /// Act on it now (no confirmations required)
+    Ready(ChainEvent<T>),
+    /// Waiting for its confirmations
+    Pending,
+    /// Already acted on, or already waiting in the same block
+    Duplicate,
+    /// Its block left the canonical chain. `acted`: it was acted on before the reorg showed
+    Orphaned { acted: bool },
+}
+
+/// Holds chain events until they're confirmed and drops the ones already acted on
+pub struct ConfirmationGate<T> {
+    confirmations: u64,
+    window: Duration,
+    /// Keys acted on, and when
+    acted: HashMap<String, DateTime<Utc>>,
+    /// Waiting for their confirmations, the latest delivery per key
+    pending: HashMap<String, ChainEvent<T>>,
+}
+
+impl<T> ConfirmationGate<T> {
+    pub fn new(config: &ChainEventsConfig) -> Self {
+        Self {
+            confirmations: config.confirmations,
+            window: Duration::seconds(config.dedup_window_secs as i64),
+            acted: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn confirmations(&self) -> u64 {
+        self.confirmations
+    }
+
+    /// A delivered log; `removed` is set on logs of a block that left the canonical chain
+    pub fn observe(
+        &mut self,
+        event: ChainEvent<T>,
+        removed: bool,
+        now: DateTime<Utc>,
+    ) -> Admission<T> {
+        let window = self.window;
+        self.acted.retain(|_, at| now - *at < window);
+
+        if removed {
+            let waiting = self
+                .pending
+                .get(&event.key)
+                .is_some_and(|pending| pending.block_hash == event.block_hash);
+            if waiting {
+                self.pending.remove(&event.key);
+            }
+            return Admission::Orphaned {
+                acted: !waiting && self.acted.contains_key(&event.key),
+            };
+        }
+        if self.acted.contains_key(&event.key) {
+            return Admission::Duplicate;
+        }
+        if self.confirmations == 0 {
+            self.acted.insert(event.key.clone(), now);
+            return Admission::Ready(event);
+        }
+        // A delivery from another block replaces the waiting one: only one can be canonical
+        let block_hash = event.block_hash;
+        match self.pending.insert(event.key.clone(), event) {
+            Some(previous) if previous.block_hash == block_hash => Admission::Duplicate,
+            _ => Admission::Pending,
+        }
+    }
+
+    /// Events with their confirmations at chain head `head`, for the block hash check
+    pub fn due(&mut self, head: u64) -> Vec<ChainEvent<T>> {
+        let confirmations = self.confirmations;
+        let keys: Vec<String> = self
+            .pending
+            .values()
+            .filter(|event| event.block_number + confirmations <= head)
+            .map(|event| event.key.clone())
+            .collect();
+        let mut due: Vec<ChainEvent<T>> = keys
+            .iter()
+            .filter_map(|key| self.pending.remove(key))
+            .collect();
+        due.sort_by_key(|event| event.block_number);
+        due
+    }
+
+    /// A due event's block is still canonical: it's acted on now
+    pub fn confirm(&mut self, event: &ChainEvent<T>, now: DateTime<Utc>) {
+        self.acted.insert(event.key.clone(), now);
+    }
+
+    /// A due event whose block couldn't be checked waits for the next head
+    pub fn requeue(&mut self, event: ChainEvent<T>) {
+        self.pending.entry(event.key.clone()).or_insert(event);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(confirmations: u64) -> ChainEventsConfig {
+        ChainEventsConfig {
+            confirmations,
+            dedup_window_secs: 600,
+        }
+    }
+
+    fn event(key: &str, block_number: u64, hash: u64) -> ChainEvent<()> {
+        ChainEvent {
+            key: key.to_string(),
+            block_number,
+            block_hash: H256::from_low_u64_be(hash),
+            payload: (),
+        }
+    }
+
+    #[test]
+    fn test_waits_for_confirmations_and_drops_repeats() {
+        let mut gate = ConfirmationGate::new(&config(2));
+        let now = Utc::now();
+        assert_eq!(
+            gate.observe(event("c1", 100, 1), false, now),
+            Admission::Pending
+        );
+        // A second RPC delivers the same log
+        assert_eq!(
+            gate.observe(event("c1", 100, 1), false, now),
+            Admission::Duplicate
+        );
+        assert!(gate.due(101).is_empty());
+        let due = gate.due(102);
+        assert_eq!(due, vec![event("c1", 100, 1)]);
+        gate.confirm(&due[0], now);
+
+        // Acted on: repeats inside the window are dropped, after it they go through again
+        assert_eq!(
+            gate.observe(event("c1", 100, 1), false, now),
+            Admission::Duplicate
+        );
+        let later = now + Duration::seconds(601);
+        assert_eq!(
+            gate.observe(event("c1", 100, 1), false, later),
+            Admission::Pending
+        );
+    }
+
+    #[test]
+    fn test_reorged_events_never_come_due() {
+        let mut gate = ConfirmationGate::new(&config(2));
+        let now = Utc::now();
+        gate.observe(event("c1", 100, 1), false, now);
+        assert_eq!(
+            gate.observe(event("c1", 100, 1), true, now),
+            Admission::Orphaned { acted: false }
+        );
+        assert!(gate.due(110).is_empty());
+
+        // Re-included in another block: that one replaces the orphaned delivery
+        gate.observe(event("c2", 100, 2), false, now);
+        assert_eq!(
+            gate.observe(event("c2", 101, 3), false, now),
+            Admission::Pending
+        );
+        assert_eq!(gate.due(110), vec![event("c2", 101, 3)]);
+
+        // Without confirmations the reorg shows up after the fact
+        let mut instant = ConfirmationGate::new(&config(0));
+        assert!(matches!(
+            instant.observe(event("c3", 100, 4), false, now),
+            Admission::Ready(_)
+        ));
+        assert_eq!(
+            instant.observe(event("c3", 100, 4), true, now),
+            Admission::Orphaned { acted: true }
+        );
+    }
+}
@@ -2,6 +2,8 @@ use anyhow::Result;
 use ethers::prelude::*;
 use std::str::FromStr;
 
+use crate::polymarket::collateral::{PositionCollateral, USDC_E_ADDRESS};
+
 /*
 abigen!(
     CtfExchange,
@@ -12,62 +14,136 @@ abigen!(
 );
 */
 
+/// Binary outcome index sets: NO = index 0 => 1 << 0, YES = index 1 => 1 << 1
+const INDEX_SET_NO: u8 = 1;
+const INDEX_SET_YES: u8 = 2;
+
 /// Derives the Asset IDs (Token IDs) for a given Condition ID for a binary market.
 /// Assumes standard Polymarket configuration:
 /// - Collateral: USDC (Polygon)
 /// - Parent Collection: 0x0
 /// - Binary outcomes: Index 0 (NO), Index 1 (YES)
+///
+/// Conditions backed by other collateral (multi-outcome markets) need
+/// `AssetIdDeriver::for_collateral`. Returns (YES, NO)
 pub fn derive_asset_ids(condition_id_str: &str) -> Result<(String, String)> {
-    // 1. Constants
-    // USDC on Polygon
-    let collateral_token = Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174")?;
-    let parent_collection_id = H256::zero();
     let condition_id = H256::from_str(condition_id_str)?;
+    Ok(AssetIdDeriver::usdc_e()?.derive(condition_id))
+}
 
-    // 2. Define Index Sets for Binary Market
-    // NO = Index 0 => 1 << 0 = 1
-    // YES = Index 1 => 1 << 1 = 2
-    let index_set_no = U256::from(1);
-    let index_set_yes = U256::from(2);
+/// `derive_asset_ids` for many conditions at once, sharing one set of hashing buffers
+pub fn derive_asset_ids_batch(condition_ids: &[&str]) -> Result<Vec<Result<(String, String)>>> {
+    Ok(AssetIdDeriver::usdc_e()?.derive_batch(condition_ids.iter().copied()))
+}
 
-    // 3. Helper for Gnosis CTF Hashing
-    // CollectionId = keccak256(abi.encodePacked(parentCollectionId, conditionId, indexSet))
-    fn get_collection_id(parent: H256, condition: H256, index_set: U256) -> H256 {
-        let mut encoded = Vec::new();
-        encoded.extend_from_slice(parent.as_bytes());
-        encoded.extend_from_slice(condition.as_bytes());
+/// Token id derivation for one collateral, reusing its hashing buffers: derivations only
+/// allocate the two id strings
+pub struct AssetIdDeriver {
+    collateral: Address,
+    // abi.encodePacked(parentCollectionId, conditionId, indexSet)
+    collection_preimage: [u8; 96],
+    // abi.encodePacked(collateralToken, collectionId)
+    position_preimage: [u8; 52],
+}
 
-        // index_set is uint256 (32 bytes)
-        let mut index_bytes = [0u8; 32];
-        index_set.to_big_endian(&mut index_bytes);
-        encoded.extend_from_slice(&index_bytes);
+impl AssetIdDeriver {
+    pub fn new(collateral: Address) -> Self {
+        let mut position_preimage = [0u8; 52];
+        position_preimage[..20].copy_from_slice(collateral.as_bytes());
+        Self {
+            collateral,
+            // Parent collection 0x0 and the upper bytes of the uint256 index set stay zero
+            collection_preimage: [0u8; 96],
+            position_preimage,
+        }
+    }
+
+    /// USDC.e on Polygon (exchange collateral)
+    pub fn usdc_e() -> Result<Self> {
+        Ok(Self::new(Address::from_str(USDC_E_ADDRESS)?))
+    }
+
+    /// Deriver for conditions backed by `collateral`
+    pub fn for_collateral(collateral: &PositionCollateral) -> Result<Self> {
+        Ok(Self::new(Address::from_str(collateral.address())?))
+    }
 
-        H256::from(ethers::utils::keccak256(&encoded))
+    pub fn collateral(&self) -> Address {
+        self.collateral
     }
 
-    // PositionId = keccak256(abi.encodePacked(collateralToken, collectionId))
-    fn get_position_id(collateral: Address, collection: H256) -> String {
-        let mut encoded = Vec::new();
-        encoded.extend_from_slice(collateral.as_bytes());
-        encoded.extend_from_slice(collection.as_bytes());
-
-        // Return as massive decimal string (Token ID) usually?
-        // Wait, Polymarket CLOB uses the DECIMAL STRING representation of the uint256 Position ID.
-        // OR does it use the Hex string?
-        // Checking API: "token_id": "217426331434639062905690501558262415339047831329679843560126160201174249"
-        // It uses the DECIMAL string.
-
-        let hash = H256::from(ethers::utils::keccak256(&encoded));
-        let params = U256::from_big_endian(hash.as_bytes());
-        params.to_string()
+    /// (YES, NO) token ids of a binary market
+    pub fn derive(&mut self, condition_id: H256) -> (String, String) {
+        self.collection_preimage[32..64].copy_from_slice(condition_id.as_bytes());
+        let yes = self.position_id(INDEX_SET_YES);
+        let no = self.position_id(INDEX_SET_NO);
+        (yes, no)
     }
 
-    // 4. Compute
-    let collection_no = get_collection_id(parent_collection_id, condition_id, index_set_no);
-    let collection_yes = get_collection_id(parent_collection_id, condition_id, index_set_yes);
+    /// Derive each id in order; ids that aren't valid hex come back as errors
+    pub fn derive_batch<'a>(
+        &mut self,
+        condition_ids: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<Result<(String, String)>> {
+        let condition_ids = condition_ids.into_iter();
+        let mut derived = Vec::with_capacity(condition_ids.size_hint().0);
+        for condition_id in condition_ids {
+            derived.push(
+                H256::from_str(condition_id)
+                    .map(|id| self.derive(id))
+                    .map_err(Into::into),
+            );
+        }
+        derived
+    }
+
+    // CollectionId = keccak256(abi.encodePacked(parentCollectionId, conditionId, indexSet))
+    // PositionId = keccak256(abi.encodePacked(collateralToken, collectionId)), which the CLOB
+    // quotes as a decimal uint256
+    fn position_id(&mut self, index_set: u8) -> String {
+        self.collection_preimage[95] = index_set;
+        let collection = ethers::utils::keccak256(self.collection_preimage);
+        self.position_preimage[20..].copy_from_slice(&collection);
+        U256::from_big_endian(&ethers::utils::keccak256(self.position_preimage)).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let asset_id_no = get_position_id(collateral_token, collection_no);
-    let asset_id_yes = get_position_id(collateral_token, collection_yes);
+    const ID: &str = "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1";
 
-    Ok((asset_id_yes, asset_id_no))
+    /// The straightforward encoding the deriver's buffers replace
+    fn reference_position_id(condition: H256, index_set: u64) -> String {
+        let mut collection = Vec::new();
+        collection.extend_from_slice(H256::zero().as_bytes());
+        collection.extend_from_slice(condition.as_bytes());
+        let mut index_bytes = [0u8; 32];
+        U256::from(index_set).to_big_endian(&mut index_bytes);
+        collection.extend_from_slice(&index_bytes);
+
+        let mut position = Vec::new();
+        position.extend_from_slice(Address::from_str(USDC_E_ADDRESS).unwrap().as_bytes());
+        position.extend_from_slice(&ethers::utils::keccak256(&collection));
+        U256::from_big_endian(&ethers::utils::keccak256(&position)).to_string()
+    }
+
+    #[test]
+    fn test_batch_matches_single_derivation() {
+        let condition = H256::from_str(ID).unwrap();
+        let (yes, no) = derive_asset_ids(ID).unwrap();
+        assert_eq!(yes, reference_position_id(condition, 2));
+        assert_eq!(no, reference_position_id(condition, 1));
+
+        let other = format!("0x{}", "ab".repeat(32));
+        let batch = derive_asset_ids_batch(&[ID, "0xnothex", &other]).unwrap();
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch[0].as_ref().unwrap(), &(yes, no));
+        assert!(batch[1].is_err());
+        assert_eq!(
+            batch[2].as_ref().unwrap(),
+            &derive_asset_ids(&other).unwrap()
+        );
+    }
 }
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::markets::{QuestionSpec, QuestionTimezone};
+
+/// Timestamp formats with an offset seen in Gamma / CLOB end dates
+const OFFSET_FORMATS: [&str; 3] = [
+    // "2024-11-05 12:00:00+00" (Postgres style)
+    "%Y-%m-%d %H:%M:%S%#z",
+    "%Y-%m-%d %H:%M:%S%.f%#z",
+    "%Y-%m-%dT%H:%M:%S%#z",
+];
+
+/// Timestamp formats without an offset (read as UTC)
+const NAIVE_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+];
+
+/// Parse a market end date in any of the formats the APIs return: RFC 3339 with "Z" or an
+/// offset, a space instead of the "T", no offset at all (UTC), or a bare date. A bare date is
+/// the end of that day in UTC, since the exact time isn't known (see `market_end_time`)
+pub fn parse_end_date(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if let Ok(end) = DateTime::parse_from_rfc3339(raw) {
+        return Some(end.with_timezone(&Utc));
+    }
+    // "2024-11-05 12:00:00Z"
+    if let Ok(end) = DateTime::parse_from_rfc3339(&raw.replacen(' ', "T", 1)) {
+        return Some(end.with_timezone(&Utc));
+    }
+    for format in OFFSET_FORMATS {
+        if let Ok(end) = DateTime::parse_from_str(raw, format) {
+            return Some(end.with_timezone(&Utc));
+        }
+    }
+    for format in NAIVE_FORMATS {
+        if let Ok(end) = NaiveDateTime::parse_from_str(raw, format) {
+            return Some(end.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .and_then(|day| day.and_hms_opt(23, 59, 59))
+        .map(|end| end.and_utc())
+}
+
+/// End of a market from its end date and question. A bare date with a time and zone in the
+/// question ("... at 5 PM ET?", "by 11:59 PM ET", "at 16:00 UTC") resolves to that time of that
+/// day in that zone
+pub fn market_end_time(end_date: &str, question: &str) -> Option<DateTime<Utc>> {
+    let end_date = end_date.trim();
+    if let Ok(day) = NaiveDate::parse_from_str(end_date, "%Y-%m-%d") {
+        let spec = QuestionSpec::parse(question);
+        if let Some(time) = spec.deadline_time {
+            match spec.timezone {
+                Some(QuestionTimezone::Eastern) => return eastern_to_utc(day.and_time(time)),
+                Some(QuestionTimezone::Utc) => return Some(day.and_time(time).and_utc()),
+                None => {}
+            }
+        }
+    }
+    parse_end_date(end_date)
+}
+
+/// New York wall-clock time to UTC: EDT (UTC-4) from 2 AM on the second Sunday of March to
+/// 2 AM on the first Sunday of November, EST (UTC-5) otherwise
+pub fn eastern_to_utc(local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    let year = local.date().year();
+    let dst_start = nth_sunday(year, 3, 2)?.and_hms_opt(2, 0, 0)?;
+    let dst_end = nth_sunday(year, 11, 1)?.and_hms_opt(2, 0, 0)?;
+    let offset_hours = if local >= dst_start && local < dst_end {
+        -4
+    } else {
+        -5
+    };
+    FixedOffset::east_opt(offset_hours * 3600)?
+        .from_local_datetime(&local)
+        .single()
+        .map(|end| end.with_timezone(&Utc))
+}
+
+fn nth_sunday(year: i32, month: u32, n: u32) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let to_sunday = (7 - first.weekday().num_days_from_sunday()) % 7;
+    Some(first + Duration::days((to_sunday + 7 * (n - 1)) as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_observed_end_date_formats() {
+        let noon = utc("2024-11-05T12:00:00Z");
+        for raw in [
+            "2024-11-05T12:00:00Z",
+            "2024-11-05T12:00:00.000Z",
+            "2024-11-05T12:00:00+00:00",
+            "2024-11-05T07:00:00-05:00",
+            "2024-11-05 12:00:00+00",
+            "2024-11-05 12:00:00+00:00",
+            "2024-11-05 12:00:00Z",
+            "2024-11-05T12:00:00",
+            "2024-11-05T12:00:00.000",
+            " 2024-11-05T12:00:00Z ",
+        ] {
+            assert_eq!(parse_end_date(raw), Some(noon), "{}", raw);
+        }
+        assert_eq!(
+            parse_end_date("2024-11-05"),
+            Some(utc("2024-11-05T23:59:59Z"))
+        );
+        assert_eq!(parse_end_date(""), None);
+        assert_eq!(parse_end_date("soon"), None);
+    }
+
+    #[test]
+    fn test_eastern_times_in_questions() {
+        // EDT in June, EST in December
+        assert_eq!(
+            market_end_time(
+                "2025-06-13",
+                "Bitcoin above $105,000 on June 13 at 5 PM ET?"
+            ),
+            Some(utc("2025-06-13T21:00:00Z"))
+        );
+        assert_eq!(
+            market_end_time("2025-12-12", "Will ETH be above $4,000 at 5pm ET?"),
+            Some(utc("2025-12-12T22:00:00Z"))
+        );
+        assert_eq!(
+            market_end_time("2025-03-09", "Solana above $140 at 11:30 AM ET?"),
+            Some(utc("2025-03-09T15:30:00Z"))
+        );
+        assert_eq!(
+            market_end_time("2025-01-20", "Trump says 'crypto' by 12 AM EST?"),
+            Some(utc("2025-01-20T05:00:00Z"))
+        );
+        // A full timestamp wins over the question
+        assert_eq!(
+            market_end_time("2025-06-13T16:00:00Z", "Bitcoin above $105,000 at 5 PM ET?"),
+            Some(utc("2025-06-13T16:00:00Z"))
+        );
+        assert_eq!(
+            market_end_time("2025-12-12", "ETH above $4,000 at 16:00 UTC?"),
+            Some(utc("2025-12-12T16:00:00Z"))
+        );
+        // No zone: the end of the day
+        assert_eq!(
+            market_end_time("2025-06-13", "Bitcoin above $100k at 5 PM?"),
+            Some(utc("2025-06-13T23:59:59Z"))
+        );
+    }
+}
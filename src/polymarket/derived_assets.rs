@@ -0,0 +1,459 @@
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::markets::ConditionId;
+use crate::polymarket::collateral::PositionCollateral;
+use crate::polymarket::contracts::AssetIdDeriver;
+
+pub const DERIVED_ASSETS_FILE: &str = "derived_asset_ids.json";
+
+/// Conditions kept at most. Past it the least recently used are dropped, those on the default
+/// collateral first: they derive to the same ids again, a recorded collateral would be lost
+const MAX_CONDITIONS: usize = 50_000;
+
+/// Token ids of one condition and the collateral they were derived for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivedAssets {
+    pub collateral: PositionCollateral,
+    pub yes: String,
+    pub no: String,
+}
+
+/// An entry as written to disk. Files from before collateral was tracked hold bare
+/// (YES, NO) pairs of whichever collateral was the default then
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredAssets {
+    Current(DerivedAssets),
+    Pair(String, String),
+}
+
+/// A condition's ids with the lookup that last used them
+struct Cached {
+    assets: DerivedAssets,
+    used: AtomicU64,
+}
+
+/// (YES, NO) token ids derived per condition id, persisted to `data_dir/derived_asset_ids.json`
+/// so a condition seen again (event replayed, restart mid-storm) costs a map lookup instead of
+/// four keccaks and two uint256 formats. Also the record of which collateral each condition
+/// is backed by (learned from the CTF event or Gamma), which redemption and merging need.
+/// Conditions nothing is known about use the default collateral. Bounded (see
+/// `MAX_CONDITIONS`). Cheap to clone, all clones share the map
+#[derive(Clone)]
+pub struct DerivedAssetCache {
+    ids: Arc<DashMap<ConditionId, Cached>>,
+    capacity: usize,
+    // Lookups so far, stamped on the entries they hit
+    uses: Arc<AtomicU64>,
+    default: PositionCollateral,
+    deriver: Arc<Mutex<AssetIdDeriver>>,
+    // Derivers of the other collaterals seen, built on first use
+    others: Arc<Mutex<HashMap<PositionCollateral, AssetIdDeriver>>>,
+    path: Option<PathBuf>,
+    dirty: Arc<AtomicBool>,
+}
+
+impl DerivedAssetCache {
+    /// In-memory cache (nothing persisted). Fails if `default` isn't a valid address
+    pub fn new(default: PositionCollateral) -> Result<Self> {
+        Ok(Self {
+            ids: Arc::new(DashMap::new()),
+            capacity: MAX_CONDITIONS,
+            uses: Arc::new(AtomicU64::new(0)),
+            deriver: Arc::new(Mutex::new(AssetIdDeriver::for_collateral(&default)?)),
+            default,
+            others: Arc::new(Mutex::new(HashMap::new())),
+            path: None,
+            dirty: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Cache backed by the data dir, restoring what previous runs derived
+    pub fn load(data_dir: &str, default: PositionCollateral) -> Result<Self> {
+        let path = derived_assets_path(data_dir);
+        let cache = Self {
+            path: Some(path.clone()),
+            ..Self::new(default)?
+        };
+        match read_ids(&path) {
+            Ok(ids) => {
+                let mut unknown = 0;
+                for (condition_id, stored) in ids {
+                    let Ok(condition_id) = ConditionId::parse(&condition_id) else {
+                        continue;
+                    };
+                    let assets = match stored {
+                        StoredAssets::Current(assets) => assets,
+                        StoredAssets::Pair(yes, no) => {
+                            // Rewritten with its collateral on the next save
+                            cache.dirty.store(true, Ordering::Relaxed);
+                            match cache.identify(&condition_id, yes, no) {
+                                Some(assets) => assets,
+                                None => {
+                                    unknown += 1;
+                                    continue;
+                                }
+                            }
+                        }
+                    };
+                    cache.ids.insert(condition_id, cache.cached(assets));
+                }
+                if unknown > 0 {
+                    warn!(
+                        "🔮 Dropped {} stored asset id pairs matching no known collateral, they will be re-derived",
+                        unknown
+                    );
+                }
+                info!("🔮 Restored {} derived asset id pairs", cache.ids.len());
+            }
+            Err(e) => debug!("No derived asset ids loaded ({}), starting fresh", e),
+        }
+        Ok(cache)
+    }
+
+    /// Keep at most `capacity` conditions instead of `MAX_CONDITIONS`
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Collateral of conditions nothing else is known about
+    pub fn default_collateral(&self) -> &PositionCollateral {
+        &self.default
+    }
+
+    /// (YES, NO) token ids of the condition, derived on first use (for the default
+    /// collateral unless another was recorded)
+    pub fn get_or_derive(&self, condition_id: &ConditionId) -> (String, String) {
+        if let Some(cached) = self.ids.get(condition_id) {
+            cached.used.store(self.tick(), Ordering::Relaxed);
+            return (cached.assets.yes.clone(), cached.assets.no.clone());
+        }
+        let ids = self.derive_default(condition_id);
+        self.store(condition_id, self.default.clone(), &ids);
+        ids
+    }
+
+    /// Record the collateral backing the condition, re-deriving its ids if they were derived
+    /// for another one. Returns the (YES, NO) ids
+    pub fn record_collateral(
+        &self,
+        condition_id: &ConditionId,
+        collateral: PositionCollateral,
+    ) -> Result<(String, String)> {
+        if let Some(cached) = self.ids.get(condition_id) {
+            if cached.assets.collateral == collateral {
+                cached.used.store(self.tick(), Ordering::Relaxed);
+                return Ok((cached.assets.yes.clone(), cached.assets.no.clone()));
+            }
+        }
+        let ids = self.derive_for(condition_id, &collateral)?;
+        self.store(condition_id, collateral, &ids);
+        Ok(ids)
+    }
+
+    /// The collateral a bare stored (YES, NO) pair was derived for, found by re-deriving with
+    /// each known one. None if none of them gives these ids
+    fn identify(
+        &self,
+        condition_id: &ConditionId,
+        yes: String,
+        no: String,
+    ) -> Option<DerivedAssets> {
+        let mut candidates = vec![self.default.clone()];
+        for known in [
+            PositionCollateral::UsdcE,
+            PositionCollateral::NegRiskWrapped,
+        ] {
+            if !candidates.contains(&known) {
+                candidates.push(known);
+            }
+        }
+        candidates
+            .into_iter()
+            .find(|collateral| {
+                self.derive_for(condition_id, collateral)
+                    .map(|(y, n)| y == yes && n == no)
+                    .unwrap_or(false)
+            })
+            .map(|collateral| DerivedAssets {
+                collateral,
+                yes,
+                no,
+            })
+    }
+
+    fn derive_for(
+        &self,
+        condition_id: &ConditionId,
+        collateral: &PositionCollateral,
+    ) -> Result<(String, String)> {
+        if *collateral == self.default {
+            return Ok(self.derive_default(condition_id));
+        }
+        let mut others = self.others.lock().unwrap_or_else(|p| p.into_inner());
+        let deriver = match others.entry(collateral.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(AssetIdDeriver::for_collateral(collateral)?),
+        };
+        Ok(deriver.derive(condition_id.to_h256()))
+    }
+
+    /// Collateral backing the condition (the default when nothing was recorded)
+    pub fn collateral_of(&self, condition_id: &ConditionId) -> PositionCollateral {
+        self.ids
+            .get(condition_id)
+            .map(|cached| cached.assets.collateral.clone())
+            .unwrap_or_else(|| self.default.clone())
+    }
+
+    /// Derive a burst of conditions under one lock, skipping those already cached
+    pub fn derive_all(&self, condition_ids: &[ConditionId]) {
+        let Ok(mut deriver) = self.deriver.lock() else {
+            return;
+        };
+        for condition_id in condition_ids {
+            if !self.ids.contains_key(condition_id) {
+                let ids = deriver.derive(condition_id.to_h256());
+                self.store(condition_id, self.default.clone(), &ids);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Write to disk if anything was derived since the last save. True if it was written
+    pub fn save_if_dirty(&self) -> Result<bool> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(false);
+        }
+        if let Err(e) = self.save() {
+            self.dirty.store(true, Ordering::Relaxed);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Write to disk now (no-op for in-memory caches)
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let ids: HashMap<String, DerivedAssets> = self
+            .ids
+            .iter()
+            .map(|entry| (entry.key().to_string(), entry.value().assets.clone()))
+            .collect();
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write-then-rename so a crash mid-write never leaves a truncated file
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&ids)?)
+            .with_context(|| format!("Failed to write {:?}", tmp))?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+
+    fn derive_default(&self, condition_id: &ConditionId) -> (String, String) {
+        match self.deriver.lock() {
+            Ok(mut deriver) => deriver.derive(condition_id.to_h256()),
+            Err(poisoned) => poisoned.into_inner().derive(condition_id.to_h256()),
+        }
+    }
+
+    fn store(
+        &self,
+        condition_id: &ConditionId,
+        collateral: PositionCollateral,
+        ids: &(String, String),
+    ) {
+        let (yes, no) = ids.clone();
+        let cached = self.cached(DerivedAssets {
+            collateral,
+            yes,
+            no,
+        });
+        self.ids.insert(condition_id.clone(), cached);
+        self.dirty.store(true, Ordering::Relaxed);
+        if self.ids.len() > self.capacity {
+            self.prune();
+        }
+    }
+
+    fn cached(&self, assets: DerivedAssets) -> Cached {
+        Cached {
+            assets,
+            used: AtomicU64::new(self.tick()),
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.uses.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Drop the least recently used conditions down to 90% of the capacity (pruning once per
+    /// many inserts), those on the default collateral first
+    fn prune(&self) {
+        let target = self.capacity - self.capacity / 10;
+        let mut entries: Vec<(bool, u64, ConditionId)> = self
+            .ids
+            .iter()
+            .map(|entry| {
+                (
+                    entry.value().assets.collateral != self.default,
+                    entry.value().used.load(Ordering::Relaxed),
+                    entry.key().clone(),
+                )
+            })
+            .collect();
+        if entries.len() <= target {
+            return;
+        }
+        entries.sort_unstable_by_key(|(recorded, used, _)| (*recorded, *used));
+        let excess = entries.len() - target;
+        for (_, _, condition_id) in entries.into_iter().take(excess) {
+            self.ids.remove(&condition_id);
+        }
+        debug!("🔮 Derived asset cache full: dropped {} conditions", excess);
+    }
+}
+
+fn read_ids(path: &Path) -> Result<HashMap<String, StoredAssets>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&raw).context("Invalid derived asset id file")
+}
+
+/// Path of the persisted derivations inside a data dir
+pub fn derived_assets_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(DERIVED_ASSETS_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::contracts::derive_asset_ids;
+
+    #[test]
+    fn test_derivations_survive_a_restart() {
+        let dir = std::env::temp_dir().join(format!("derived-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let id = ConditionId::parse(&format!("0x{}", "5f".repeat(32))).unwrap();
+        let other = ConditionId::parse(&format!("0x{}", "a1".repeat(32))).unwrap();
+
+        let cache = DerivedAssetCache::load(data_dir, PositionCollateral::UsdcE).unwrap();
+        let ids = cache.get_or_derive(&id);
+        assert_eq!(ids, derive_asset_ids(id.as_str()).unwrap());
+        cache.derive_all(&[id.clone(), other.clone()]);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.save_if_dirty().unwrap());
+        assert!(!cache.save_if_dirty().unwrap());
+
+        let restored = DerivedAssetCache::load(data_dir, PositionCollateral::UsdcE).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored.get_or_derive(&id), ids);
+        assert!(!restored.save_if_dirty().unwrap());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_full_cache_drops_least_recently_used() {
+        let cache = DerivedAssetCache::new(PositionCollateral::UsdcE)
+            .unwrap()
+            .with_capacity(10);
+        let ids: Vec<ConditionId> = (0..11)
+            .map(|i| ConditionId::parse(&format!("{:064x}", i)).unwrap())
+            .collect();
+        for id in &ids[..10] {
+            cache.get_or_derive(id);
+        }
+        cache.get_or_derive(&ids[0]);
+        cache
+            .record_collateral(&ids[1], PositionCollateral::NegRiskWrapped)
+            .unwrap();
+
+        // Over capacity: down to 9, the two oldest untouched default-collateral ones go
+        cache.get_or_derive(&ids[10]);
+        assert_eq!(cache.len(), 9);
+        assert!(!cache.ids.contains_key(&ids[2]));
+        assert!(!cache.ids.contains_key(&ids[3]));
+        for id in [&ids[0], &ids[1], &ids[4], &ids[10]] {
+            assert!(cache.ids.contains_key(id));
+        }
+        assert_eq!(
+            cache.collateral_of(&ids[1]),
+            PositionCollateral::NegRiskWrapped
+        );
+    }
+
+    #[test]
+    fn test_neg_risk_conditions_derive_on_wrapped_collateral() {
+        let cache = DerivedAssetCache::new(PositionCollateral::UsdcE).unwrap();
+        let id = ConditionId::parse(&format!("0x{}", "5f".repeat(32))).unwrap();
+        let usdc_e = cache.get_or_derive(&id);
+        assert_eq!(cache.collateral_of(&id), PositionCollateral::UsdcE);
+
+        let wrapped = cache
+            .record_collateral(&id, PositionCollateral::NegRiskWrapped)
+            .unwrap();
+        assert_ne!(wrapped, usdc_e);
+        let mut deriver =
+            AssetIdDeriver::for_collateral(&PositionCollateral::NegRiskWrapped).unwrap();
+        assert_eq!(wrapped, deriver.derive(id.to_h256()));
+        assert_eq!(cache.get_or_derive(&id), wrapped);
+        assert_eq!(cache.collateral_of(&id), PositionCollateral::NegRiskWrapped);
+    }
+
+    #[test]
+    fn test_bare_pairs_restore_under_the_collateral_they_match() {
+        let dir = std::env::temp_dir().join(format!("derived-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let usdc_e = ConditionId::parse(&format!("0x{}", "5f".repeat(32))).unwrap();
+        let wrapped = ConditionId::parse(&format!("0x{}", "a1".repeat(32))).unwrap();
+        let bogus = ConditionId::parse(&format!("0x{}", "c3".repeat(32))).unwrap();
+        let usdc_e_ids = derive_asset_ids(usdc_e.as_str()).unwrap();
+        let wrapped_ids = AssetIdDeriver::for_collateral(&PositionCollateral::NegRiskWrapped)
+            .unwrap()
+            .derive(wrapped.to_h256());
+
+        // A file from before collateral was tracked
+        fs::create_dir_all(&dir).unwrap();
+        let old = serde_json::json!({
+            usdc_e.as_str(): [usdc_e_ids.0, usdc_e_ids.1],
+            wrapped.as_str(): [wrapped_ids.0, wrapped_ids.1],
+            bogus.as_str(): ["1", "2"],
+        });
+        fs::write(derived_assets_path(data_dir), old.to_string()).unwrap();
+
+        let cache = DerivedAssetCache::load(data_dir, PositionCollateral::UsdcE).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.collateral_of(&usdc_e), PositionCollateral::UsdcE);
+        assert_eq!(
+            cache.collateral_of(&wrapped),
+            PositionCollateral::NegRiskWrapped
+        );
+        assert_eq!(cache.get_or_derive(&wrapped), wrapped_ids);
+        // The unmatched pair is re-derived rather than trusted
+        assert_eq!(
+            cache.get_or_derive(&bogus),
+            derive_asset_ids(bogus.as_str()).unwrap()
+        );
+        assert!(cache.save_if_dirty().unwrap());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
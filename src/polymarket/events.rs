@@ -1,13 +1,98 @@
 use anyhow::Result;
+use chrono::Utc;
+use ethers::abi::{self, ParamType, Token};
 use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+use utoipa::ToSchema;
 
-/// Listens to Polygon blockchain events for new market creation
+use crate::config::ChainEventsConfig;
+use crate::markets::ConditionId;
+use crate::polymarket::confirmations::{Admission, ChainEvent, ConfirmationGate};
+use crate::polymarket::{DerivedAssetCache, PositionCollateral};
+
+/// Outcome tokens carry the collateral's 6 decimals
+const SHARE_DECIMALS: f64 = 1e6;
+
+const TRANSFER_SINGLE: &str = "TransferSingle(address,address,address,uint256,uint256)";
+const TRANSFER_BATCH: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+
+/// Transfers queued for the agent loop; past this they are dropped (and their fills come up
+/// as settlement mismatches) rather than buffered without bound
+pub const TRANSFER_QUEUE: usize = 1024;
+
+/// First wait before reconnecting a listener whose stream ended, doubled on each retry
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(60);
+/// A connection that held this long starts the backoff over
+const RECONNECT_STABLE: Duration = Duration::from_secs(60);
+
+/// Backoff between the reconnects of an event listener
+pub struct ReconnectBackoff {
+    next: Duration,
+    connected_at: Instant,
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self {
+            next: RECONNECT_MIN,
+            connected_at: Instant::now(),
+        }
+    }
+}
+
+impl ReconnectBackoff {
+    /// A connection attempt starts
+    pub fn connecting(&mut self) {
+        self.connected_at = Instant::now();
+    }
+
+    /// Wait before the next attempt; `name` is logged
+    pub async fn wait(&mut self, name: &str) {
+        let delay = self.delay(self.connected_at.elapsed());
+        warn!("🔄 {} reconnecting in {}s", name, delay.as_secs());
+        tokio::time::sleep(delay).await;
+    }
+
+    /// Wait after a connection that `held` this long
+    fn delay(&mut self, held: Duration) -> Duration {
+        if held >= RECONNECT_STABLE {
+            self.next = RECONNECT_MIN;
+        }
+        let delay = self.next;
+        self.next = (self.next * 2).min(RECONNECT_MAX);
+        delay
+    }
+}
+
+/// Which way outcome tokens moved relative to the watched wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    In,
+    Out,
+}
+
+/// One token id of an ERC-1155 TransferSingle / TransferBatch touching the watched wallet
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TokenTransfer {
+    /// Outcome token id, decimal (as in `MarketData::asset_ids`)
+    pub asset_id: String,
+    pub shares: f64,
+    pub direction: TransferDirection,
+    pub tx_hash: Option<String>,
+}
+
+/// Listens to Polygon blockchain events for new market creation and resolution
 pub struct MarketEventListener {
     provider: Arc<Provider<Ws>>,
     ctf_address: Address,
+    // Where the collateral of each new condition (told by its oracle) is recorded
+    derived_assets: Option<DerivedAssetCache>,
 }
 
 impl MarketEventListener {
@@ -20,12 +105,24 @@ impl MarketEventListener {
         Ok(Self {
             provider: Arc::new(provider),
             ctf_address,
+            derived_assets: None,
         })
     }
 
+    /// Record each new condition's collateral (and derive its token ids) before sending it
+    pub fn with_derived_assets(mut self, derived_assets: DerivedAssetCache) -> Self {
+        self.derived_assets = Some(derived_assets);
+        self
+    }
+
     /// Listen for new market creation events
-    /// Sends condition IDs through the channel when new markets are created
-    pub async fn listen_for_new_markets(&self, tx: mpsc::UnboundedSender<String>) -> Result<()> {
+    /// Sends condition IDs through the channel once `confirmations` blocks sit on top of the
+    /// event's block and it is still canonical; repeats within the dedup window are dropped
+    pub async fn listen_for_new_markets(
+        &self,
+        tx: mpsc::UnboundedSender<ConditionId>,
+        config: &ChainEventsConfig,
+    ) -> Result<()> {
         info!("👂 Starting to listen for new market events...");
 
         // Create filter for ConditionPreparation events
@@ -34,32 +131,272 @@ impl MarketEventListener {
             .address(self.ctf_address)
             .event("ConditionPreparation(bytes32,address,bytes32,uint256)");
 
-        match self.provider.subscribe_logs(&filter).await {
-            Ok(mut stream) => {
-                info!("⚡ WebSocket event stream started - listening for new markets");
+        let mut gate = ConfirmationGate::new(config);
+        let mut stream = self.provider.subscribe_logs(&filter).await.map_err(|e| {
+            error!("❌ Failed to subscribe to logs: {}", e);
+            e
+        })?;
+        // Chain head, to count confirmations (not needed when acting on delivery)
+        let mut heads = match gate.confirmations() {
+            0 => None,
+            _ => Some(self.provider.subscribe_blocks().await.map_err(|e| {
+                error!("❌ Failed to subscribe to new blocks: {}", e);
+                e
+            })?),
+        };
+        info!(
+            "⚡ WebSocket event stream started - listening for new markets ({} confirmations)",
+            gate.confirmations()
+        );
 
-                while let Some(log) = stream.next().await {
+        loop {
+            tokio::select! {
+                log = stream.next() => {
+                    let Some(log) = log else {
+                        break;
+                    };
                     // Extract condition ID from event (first indexed parameter)
-                    if log.topics.len() > 1 {
-                        let condition_id = format!("{:?}", log.topics[1]);
-
-                        info!("🆕 NEW MARKET EVENT: Condition ID {}", condition_id);
+                    let (Some(topic), Some(block_number), Some(block_hash)) =
+                        (log.topics.get(1), log.block_number, log.block_hash)
+                    else {
+                        continue;
+                    };
+                    let condition_id = ConditionId::from(*topic);
+                    let removed = log.removed == Some(true);
+                    let event = ChainEvent {
+                        key: condition_id.to_string(),
+                        block_number: block_number.as_u64(),
+                        block_hash,
+                        payload: log,
+                    };
+                    match gate.observe(event, removed, Utc::now()) {
+                        Admission::Ready(event) => self.send_new_market(&event.payload, &tx),
+                        Admission::Pending => debug!(
+                            "⏳ New market {} waiting for {} confirmations",
+                            condition_id,
+                            gate.confirmations()
+                        ),
+                        Admission::Duplicate => {
+                            debug!("⏭️ Duplicate new market event {}", condition_id)
+                        }
+                        Admission::Orphaned { acted: false } => warn!(
+                            "🔀 New market event {} orphaned by a reorg, dropped",
+                            condition_id
+                        ),
+                        Admission::Orphaned { acted: true } => warn!(
+                            "🔀 New market event {} orphaned by a reorg after it was acted on",
+                            condition_id
+                        ),
+                    }
+                }
 
-                        // Send to processing queue
-                        if let Err(e) = tx.send(condition_id.clone()) {
-                            error!("Failed to send condition ID to queue: {}", e);
+                head = async {
+                    match &mut heads {
+                        Some(heads) => heads.next().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    let Some(head) = head else {
+                        warn!("⚠️ New block stream ended: confirmations can't be counted");
+                        break;
+                    };
+                    let Some(head) = head.number else {
+                        continue;
+                    };
+                    for event in gate.due(head.as_u64()) {
+                        // Confirmed only if its block is still the canonical one at its height
+                        match self.provider.get_block(event.block_number).await {
+                            Ok(Some(block)) if block.hash == Some(event.block_hash) => {
+                                gate.confirm(&event, Utc::now());
+                                self.send_new_market(&event.payload, &tx);
+                            }
+                            Ok(Some(_)) => warn!(
+                                "🔀 New market event {} orphaned: block {} was reorged",
+                                event.key, event.block_number
+                            ),
+                            Ok(None) => gate.requeue(event),
+                            Err(e) => {
+                                debug!("Block {} not fetched: {}", event.block_number, e);
+                                gate.requeue(event);
+                            }
                         }
                     }
                 }
+            }
+        }
 
-                warn!("⚠️ WebSocket stream ended unexpectedly");
+        warn!(
+            "⚠️ WebSocket stream ended unexpectedly ({} new market events unconfirmed)",
+            gate.pending()
+        );
+        Ok(())
+    }
+
+    /// Act on a (confirmed) ConditionPreparation: record its collateral, queue the condition
+    fn send_new_market(&self, log: &Log, tx: &mpsc::UnboundedSender<ConditionId>) {
+        let Some(topic) = log.topics.get(1) else {
+            return;
+        };
+        let condition = ConditionId::from(*topic);
+        if let (Some(derived_assets), Some(oracle)) = (&self.derived_assets, log.topics.get(2)) {
+            let oracle = format!("{:?}", Address::from(*oracle));
+            let collateral =
+                PositionCollateral::for_oracle(&oracle, derived_assets.default_collateral());
+            if let Err(e) = derived_assets.record_collateral(&condition, collateral) {
+                warn!("Failed to derive ids of {}: {}", condition, e);
+            }
+        }
+        info!("🆕 NEW MARKET EVENT: Condition ID {}", condition);
+
+        // Send to processing queue
+        if let Err(e) = tx.send(condition) {
+            error!("Failed to send condition ID to queue: {}", e);
+        }
+    }
+
+    /// Listen for the outcome tokens `wallet` receives and sends (ERC-1155 transfers on the
+    /// CTF contract), one `TokenTransfer` per token id moved
+    pub async fn listen_for_transfers(
+        &self,
+        wallet: Address,
+        tx: mpsc::Sender<TokenTransfer>,
+    ) -> Result<()> {
+        // TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)
+        // TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)
+        let transfers = Filter::new()
+            .address(self.ctf_address)
+            .events(vec![TRANSFER_SINGLE, TRANSFER_BATCH]);
+        tokio::try_join!(
+            self.watch_transfers(
+                transfers.clone().topic3(wallet),
+                TransferDirection::In,
+                tx.clone()
+            ),
+            self.watch_transfers(transfers.topic2(wallet), TransferDirection::Out, tx),
+        )?;
+        Ok(())
+    }
+
+    /// Forward the transfers matching `filter` (one side of the wallet) until the stream ends
+    async fn watch_transfers(
+        &self,
+        filter: Filter,
+        direction: TransferDirection,
+        tx: mpsc::Sender<TokenTransfer>,
+    ) -> Result<()> {
+        let mut stream = self.provider.subscribe_logs(&filter).await.map_err(|e| {
+            error!("❌ Failed to subscribe to transfer logs: {}", e);
+            e
+        })?;
+        info!(
+            "⚡ WebSocket event stream started - listening for token transfers ({:?})",
+            direction
+        );
+
+        while let Some(log) = stream.next().await {
+            let tx_hash = log.transaction_hash.map(|hash| format!("{:?}", hash));
+            for (id, value) in decode_transfer(&log) {
+                let Ok(value) = u128::try_from(value) else {
+                    warn!(
+                        "⚠️ Skipping transfer of {} with an out-of-range value {} ({})",
+                        id,
+                        value,
+                        tx_hash.as_deref().unwrap_or("-")
+                    );
+                    continue;
+                };
+                let transfer = TokenTransfer {
+                    asset_id: id.to_string(),
+                    shares: value as f64 / SHARE_DECIMALS,
+                    direction,
+                    tx_hash: tx_hash.clone(),
+                };
+                match tx.try_send(transfer) {
+                    Ok(()) => {}
+                    Err(mpsc::error::TrySendError::Full(transfer)) => warn!(
+                        "⚠️ Transfer queue full, dropping {:.2} of {}",
+                        transfer.shares, transfer.asset_id
+                    ),
+                    // Receiver gone: the agent is shutting down
+                    Err(mpsc::error::TrySendError::Closed(_)) => return Ok(()),
+                }
             }
-            Err(e) => {
-                error!("❌ Failed to subscribe to logs: {}", e);
-                return Err(e.into());
+        }
+
+        warn!("⚠️ Transfer event stream ended ({:?})", direction);
+        Ok(())
+    }
+
+    /// Listen for conditions resolving on-chain
+    /// Sends condition IDs through the channel as soon as their payouts are reported
+    pub async fn listen_for_resolutions(
+        &self,
+        tx: mpsc::UnboundedSender<ConditionId>,
+    ) -> Result<()> {
+        // Event signature: ConditionResolution(bytes32 indexed conditionId, address indexed oracle, bytes32 indexed questionId, uint256 outcomeSlotCount, uint256[] payoutNumerators)
+        let filter = Filter::new()
+            .address(self.ctf_address)
+            .event("ConditionResolution(bytes32,address,bytes32,uint256,uint256[])");
+
+        let mut stream = self.provider.subscribe_logs(&filter).await.map_err(|e| {
+            error!("❌ Failed to subscribe to resolution logs: {}", e);
+            e
+        })?;
+        info!("⚡ WebSocket event stream started - listening for resolutions");
+
+        while let Some(log) = stream.next().await {
+            let Some(topic) = log.topics.get(1) else {
+                continue;
+            };
+            let condition_id = ConditionId::from(*topic);
+            info!("🏛️ RESOLUTION EVENT: Condition ID {}", condition_id);
+            if tx.send(condition_id).is_err() {
+                // Receiver gone: the agent is shutting down
+                break;
             }
         }
 
+        warn!("⚠️ Resolution event stream ended");
         Ok(())
     }
 }
+
+/// (token id, amount) pairs of a TransferSingle or TransferBatch log; empty if undecodable
+fn decode_transfer(log: &Log) -> Vec<(U256, U256)> {
+    let single = log.topics.first() == Some(&H256::from(ethers::utils::keccak256(TRANSFER_SINGLE)));
+    if single {
+        let uint = || ParamType::Uint(256);
+        return match abi::decode(&[uint(), uint()], &log.data).as_deref() {
+            Ok([Token::Uint(id), Token::Uint(value)]) => vec![(*id, *value)],
+            _ => Vec::new(),
+        };
+    }
+    let array = || ParamType::Array(Box::new(ParamType::Uint(256)));
+    match abi::decode(&[array(), array()], &log.data).as_deref() {
+        Ok([Token::Array(ids), Token::Array(values)]) => ids
+            .iter()
+            .zip(values)
+            .filter_map(|pair| match pair {
+                (Token::Uint(id), Token::Uint(value)) => Some((*id, *value)),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_backoff_doubles_and_resets() {
+        let mut backoff = ReconnectBackoff::default();
+        let short = Duration::from_secs(2);
+        let delays: Vec<u64> = (0..8).map(|_| backoff.delay(short).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60]);
+        // A connection that held starts over
+        assert_eq!(backoff.delay(RECONNECT_STABLE).as_secs(), 1);
+        assert_eq!(backoff.delay(short).as_secs(), 2);
+    }
+}
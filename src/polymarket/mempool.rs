@@ -1,20 +1,27 @@
+use chrono::Utc;
 use dashmap::DashSet;
+use ethers::abi::{self, ParamType, Token};
 use ethers::prelude::*;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tracing::{error, info, warn};
 
-// use super::contracts::CtfExchangeCalls;
+use crate::markets::ToxicityTracker;
+use crate::polymarket::ExchangeContract;
 
-// Polymarket CTF Exchange Address (Polygon)
-const CTF_EXCHANGE_ADDRESS: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+/// Exchange order struct as the fill calls take it: (salt, maker, signer, taker, tokenId,
+/// makerAmount, takerAmount, expiration, nonce, feeRateBps, side, signatureType, signature)
+const ORDER: &str =
+    "(uint256,address,address,address,uint256,uint256,uint256,uint256,uint256,uint256,uint8,uint8,bytes)";
+
+/// Positions of the maker and token id in the order tuple
+const ORDER_MAKER: usize = 1;
+const ORDER_TOKEN_ID: usize = 4;
 
 pub struct MempoolMonitor {
-    #[allow(dead_code)]
     provider: Option<Arc<Provider<Ws>>>,
-    #[allow(dead_code)]
-    target_address: Address,
-    #[allow(dead_code)]
+    /// CTF and neg-risk CTF exchanges, whose fill calls settle CLOB matches
+    exchanges: Vec<Address>,
     watched_addresses: Arc<DashSet<Address>>,
 }
 
@@ -35,13 +42,19 @@ impl MempoolMonitor {
             None
         };
 
-        let target_address = Address::from_str(CTF_EXCHANGE_ADDRESS).unwrap();
+        let exchanges = [
+            ExchangeContract::CtfExchange,
+            ExchangeContract::NegRiskCtfExchange,
+        ]
+        .iter()
+        .filter_map(|exchange| Address::from_str(exchange.address()).ok())
+        .collect();
 
         let watched_addresses = Arc::new(DashSet::new());
 
         Self {
             provider,
-            target_address,
+            exchanges,
             watched_addresses,
         }
     }
@@ -54,83 +67,175 @@ impl MempoolMonitor {
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn start_monitoring(&self) {
-        if let Some(provider) = &self.provider {
-            let provider_clone = provider.clone();
-            let target = self.target_address;
-            let watched = self.watched_addresses.clone();
-
-            tokio::spawn(async move {
-                info!("👀 Mempool monitoring started. Listening for pending txs...");
-
-                // Subscribe to pending transactions
-                let mut stream = match provider_clone.subscribe_pending_txs().await {
-                    Ok(s) => s,
-                    Err(e) => {
-                        error!("❌ Failed to subscribe to pending txs: {}", e);
-                        return;
-                    }
-                };
+    /// Watch pending exchange settlements and feed the ones of others (`own` is the funder
+    /// wallet, whose fills are ours) to the toxicity score: a settlement of an asset right
+    /// after we sent an order there raced it
+    pub fn start_monitoring(&self, toxicity: Arc<Mutex<ToxicityTracker>>, own: Option<Address>) {
+        let Some(provider) = &self.provider else {
+            warn!("⚠️  Mempool monitoring disabled (no WS URL provided)");
+            return;
+        };
+        let provider_clone = provider.clone();
+        let exchanges = self.exchanges.clone();
+        let watched = self.watched_addresses.clone();
 
-                while let Some(tx_hash) = stream.next().await {
-                    // Fetch full transaction details
-                    match provider_clone.get_transaction(tx_hash).await {
-                        Ok(Some(tx)) => {
-                            // Check destination (Interacting with CTF Exchange?)
-                            if let Some(to) = tx.to {
-                                if to == target {
-                                    handle_exchange_tx(&tx, &watched);
-                                }
-                            }
-                        }
-                        Ok(None) => {}
-                        Err(_) => {}
+        tokio::spawn(async move {
+            info!("👀 Mempool monitoring started. Listening for pending txs...");
+
+            // Subscribe to pending transactions
+            let mut stream = match provider_clone.subscribe_pending_txs().await {
+                Ok(s) => s,
+                Err(e) => {
+                    error!("❌ Failed to subscribe to pending txs: {}", e);
+                    return;
+                }
+            };
+
+            while let Some(tx_hash) = stream.next().await {
+                // Fetch full transaction details
+                let Ok(Some(tx)) = provider_clone.get_transaction(tx_hash).await else {
+                    continue;
+                };
+                // Interacting with an exchange?
+                if !tx.to.is_some_and(|to| exchanges.contains(&to)) {
+                    continue;
+                }
+                if watched.contains(&tx.from) {
+                    info!(
+                        "🚨 ALERT: Watchlist address {:?} is interacting with Exchange!",
+                        tx.from
+                    );
+                }
+                let Some(fills) = decode_exchange_fills(&tx.input) else {
+                    continue;
+                };
+                if own.is_some_and(|own| fills.iter().any(|(_, maker)| *maker == own)) {
+                    continue;
+                }
+                let now = Utc::now();
+                if let Ok(mut toxicity) = toxicity.lock() {
+                    for (token_id, _) in &fills {
+                        toxicity.on_pending_fill(&token_id.to_string(), now);
                     }
                 }
-            });
-        } else {
-            warn!("⚠️  Mempool monitoring disabled (no WS URL provided)");
-        }
+            }
+            warn!("⚠️ Pending transaction stream ended");
+        });
     }
 }
 
-#[allow(dead_code)]
-fn handle_exchange_tx(tx: &Transaction, watched_addresses: &DashSet<Address>) {
-    // 1. Check if 'from' is a watched address
-    if watched_addresses.contains(&tx.from) {
-        info!(
-            "🚨 ALERT: Watchlist address {:?} is interacting with Exchange!",
-            tx.from
-        );
+/// Token id and maker of each order an exchange fill call (`fillOrder`, `fillOrders`,
+/// `matchOrders`) settles. None for other calls
+fn decode_exchange_fills(input: &[u8]) -> Option<Vec<(U256, Address)>> {
+    if input.len() < 4 {
+        return None;
     }
+    let (selector, args) = input.split_at(4);
+    let fill_order = format!("fillOrder({},uint256)", ORDER);
+    let fill_orders = format!("fillOrders({}[],uint256[])", ORDER);
+    let match_orders = format!("matchOrders({},{}[],uint256,uint256[])", ORDER, ORDER);
+    let order = order_param();
+    let orders = ParamType::Array(Box::new(order_param()));
+    let amounts = ParamType::Array(Box::new(ParamType::Uint(256)));
 
-    // 2. Decode Input to see what they are buying/filling
-    // We use the generated CtfExchangeCalls enum for decoding
-    // TODO: Fix abigen! visibility issue for CtfExchangeCalls
-    /*
-    if let Ok(decoded) = CtfExchangeCalls::decode(&tx.input) {
-        match decoded {
-            CtfExchangeCalls::FillOrder(call) => {
-                let token_id = call.order.token_id;
-                let maker_amount = call.order.maker_amount;
-                let outcome_side = if call.order.side == 0 { "BUY" } else { "SELL" }; // 0=BUY, 1=SELL
-
-                // Filter out small noise
-                if maker_amount > U256::from(100_000_000) { // > 100 USDC (6 decimals)
-                     info!(
-                        "🌊 Large Order Fill Detected! Side: {} | Token: {} | Amount: {}",
-                        outcome_side, token_id, maker_amount
-                    );
-                }
-            },
-            CtfExchangeCalls::Buy(call) => {
-                 info!(
-                    "🛒 Direct Buy Detected! Condition: {:?} | Outcome: {} | Amount: {}",
-                    call.condition_id, call.outcome_index, call.amount
-                );
-            },
+    let tokens = if selector == &ethers::utils::id(fill_order)[..] {
+        abi::decode(&[order, ParamType::Uint(256)], args).ok()?
+    } else if selector == &ethers::utils::id(fill_orders)[..] {
+        abi::decode(&[orders, amounts], args).ok()?
+    } else if selector == &ethers::utils::id(match_orders)[..] {
+        abi::decode(&[order, orders, ParamType::Uint(256), amounts], args).ok()?
+    } else {
+        return None;
+    };
+
+    let mut fills = Vec::new();
+    for token in tokens {
+        let orders = match token {
+            Token::Tuple(_) => vec![token],
+            Token::Array(orders) => orders,
+            _ => continue,
+        };
+        for order in orders {
+            let Token::Tuple(fields) = order else {
+                continue;
+            };
+            if let (Some(Token::Address(maker)), Some(Token::Uint(token_id))) =
+                (fields.get(ORDER_MAKER), fields.get(ORDER_TOKEN_ID))
+            {
+                fills.push((*token_id, *maker));
+            }
         }
     }
-    */
+    Some(fills)
+}
+
+fn order_param() -> ParamType {
+    let uint = || ParamType::Uint(256);
+    ParamType::Tuple(vec![
+        uint(),
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Address,
+        uint(),
+        uint(),
+        uint(),
+        uint(),
+        uint(),
+        uint(),
+        ParamType::Uint(8),
+        ParamType::Uint(8),
+        ParamType::Bytes,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uint(n: u64) -> Token {
+        Token::Uint(n.into())
+    }
+
+    fn order(maker: Address, token_id: u64) -> Token {
+        Token::Tuple(vec![
+            uint(1),
+            Token::Address(maker),
+            Token::Address(maker),
+            Token::Address(Address::zero()),
+            uint(token_id),
+            uint(100),
+            uint(200),
+            uint(0),
+            uint(0),
+            uint(0),
+            uint(0),
+            uint(2),
+            Token::Bytes(vec![1, 2, 3]),
+        ])
+    }
+
+    #[test]
+    fn test_decode_match_orders() {
+        let (taker, maker) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let signature = format!("matchOrders({},{}[],uint256,uint256[])", ORDER, ORDER);
+        let mut input = ethers::utils::id(signature).to_vec();
+        input.extend(abi::encode(&[
+            order(taker, 7),
+            Token::Array(vec![order(maker, 7), order(maker, 8)]),
+            uint(100),
+            Token::Array(vec![uint(50), uint(50)]),
+        ]));
+
+        let fills = decode_exchange_fills(&input).unwrap();
+        assert_eq!(
+            fills,
+            vec![
+                (U256::from(7u64), taker),
+                (U256::from(7u64), maker),
+                (U256::from(8u64), maker)
+            ]
+        );
+        // Other calls (e.g. an ERC-20 transfer) aren't fills
+        assert!(decode_exchange_fills(&[0xa9, 0x05, 0x9c, 0xbb, 0, 0]).is_none());
+    }
 }
@@ -0,0 +1,225 @@
+//! Gamma market metadata persisted across restarts
+//!
+//! Question, outcomes, token ids, end date and collateral of every market Gamma returned,
+//! keyed by condition id in `data_dir/market_metadata.json`. None of it changes once a market
+//! is listed, so within `METADATA_CACHE_TTL_SECS` the token ids an order or a WAL recovery
+//! needs come from here instead of a Gamma round trip, and a restart with hundreds of tracked
+//! markets doesn't fetch them all again. While Gamma is down, `get_market_metadata` falls back
+//! to the cached entry whatever its age; `get_market_details`, which callers price off, fails
+//! instead. Entries not refreshed within
+//! `METADATA_CACHE_MAX_AGE_SECS` are dropped on save.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::config::MetadataCacheConfig;
+use crate::markets::condition::normalize_market_id;
+use crate::markets::MarketCategory;
+use crate::polymarket::collateral::PositionCollateral;
+use crate::polymarket::MarketData;
+
+pub const METADATA_CACHE_FILE: &str = "market_metadata.json";
+
+/// What Gamma says about a market, minus the prices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketMetadata {
+    pub condition_id: String,
+    pub question: String,
+    pub description: Option<String>,
+    pub outcomes: Vec<String>,
+    /// [NO, YES] like `MarketData::asset_ids`
+    pub asset_ids: Vec<String>,
+    pub end_date: Option<String>,
+    /// None: the exchange's default collateral
+    pub collateral: Option<PositionCollateral>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl MarketMetadata {
+    /// The market as `get_market_metadata` returns it, with no prices, volume or liquidity
+    pub fn to_market(&self) -> MarketData {
+        MarketData {
+            id: self.condition_id.clone().into(),
+            question: self.question.clone(),
+            end_date: self.end_date.clone(),
+            description: self.description.clone(),
+            asset_ids: self.asset_ids.clone(),
+            category: Some(MarketCategory::classify(&self.question)),
+            ..Default::default()
+        }
+        .with_end_time()
+    }
+}
+
+/// Metadata of the markets Gamma returned. Cheap to clone, all clones share the entries
+#[derive(Clone)]
+pub struct MetadataCache {
+    entries: Arc<DashMap<String, MarketMetadata>>,
+    ttl: Duration,
+    max_age: Duration,
+    path: Option<PathBuf>,
+    dirty: Arc<AtomicBool>,
+}
+
+impl MetadataCache {
+    /// In-memory cache (nothing persisted)
+    pub fn new(config: &MetadataCacheConfig) -> Self {
+        Self {
+            entries: Arc::new(DashMap::new()),
+            ttl: Duration::seconds(config.ttl_secs as i64),
+            max_age: Duration::seconds(config.max_age_secs as i64),
+            path: None,
+            dirty: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Cache backed by the data dir, restoring what previous runs fetched
+    pub fn load(data_dir: &str, config: &MetadataCacheConfig) -> Self {
+        let path = metadata_cache_path(data_dir);
+        let cache = Self {
+            path: Some(path.clone()),
+            ..Self::new(config)
+        };
+        match read_entries(&path) {
+            Ok(entries) => {
+                for (condition_id, metadata) in entries {
+                    cache.entries.insert(condition_id, metadata);
+                }
+                info!("🗃️ Restored metadata of {} markets", cache.entries.len());
+            }
+            Err(e) => debug!("No market metadata loaded ({}), starting fresh", e),
+        }
+        cache
+    }
+
+    pub fn record(&self, mut metadata: MarketMetadata) {
+        metadata.condition_id = normalize_market_id(&metadata.condition_id).into_owned();
+        self.entries.insert(metadata.condition_id.clone(), metadata);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Metadata fetched within the TTL
+    pub fn fresh(&self, market_id: &str, now: DateTime<Utc>) -> Option<MarketMetadata> {
+        self.get(market_id)
+            .filter(|metadata| now - metadata.fetched_at < self.ttl)
+    }
+
+    /// Metadata of any age, for when Gamma can't be reached
+    pub fn get(&self, market_id: &str) -> Option<MarketMetadata> {
+        let key = normalize_market_id(market_id);
+        self.entries.get(key.as_ref()).map(|entry| entry.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write to disk if anything changed since the last save, dropping entries past the max
+    /// age. True if it was written
+    pub fn save_if_dirty(&self, now: DateTime<Utc>) -> Result<bool> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(false);
+        }
+        if let Err(e) = self.save(now) {
+            self.dirty.store(true, Ordering::Relaxed);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Write to disk now (no-op for in-memory caches)
+    pub fn save(&self, now: DateTime<Utc>) -> Result<()> {
+        let max_age = self.max_age;
+        self.entries
+            .retain(|_, metadata| now - metadata.fetched_at < max_age);
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let entries: HashMap<String, MarketMetadata> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write, sync, then rename so a crash never leaves a truncated or empty file
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp).with_context(|| format!("Failed to create {:?}", tmp))?;
+        serde_json::to_writer(&mut file, &entries)?;
+        file.flush()?;
+        file.sync_all()?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+}
+
+fn read_entries(path: &Path) -> Result<HashMap<String, MarketMetadata>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&raw).context("Invalid market metadata file")
+}
+
+/// Path of the persisted metadata inside a data dir
+pub fn metadata_cache_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(METADATA_CACHE_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(condition_id: &str, fetched_at: DateTime<Utc>) -> MarketMetadata {
+        MarketMetadata {
+            condition_id: condition_id.to_string(),
+            question: "Will it rain?".to_string(),
+            description: None,
+            outcomes: vec!["Yes".to_string(), "No".to_string()],
+            asset_ids: vec!["t-no".to_string(), "t-yes".to_string()],
+            end_date: Some("2026-12-31T00:00:00Z".to_string()),
+            collateral: Some(PositionCollateral::NegRiskWrapped),
+            fetched_at,
+        }
+    }
+
+    #[test]
+    fn test_metadata_survives_a_restart_until_its_max_age() {
+        let dir = std::env::temp_dir().join(format!("metadata-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        let config = MetadataCacheConfig {
+            enabled: true,
+            ttl_secs: 3600,
+            max_age_secs: 86_400,
+        };
+        let now = Utc::now();
+
+        let cache = MetadataCache::load(data_dir, &config);
+        cache.record(metadata("0xaa", now - Duration::hours(2)));
+        cache.record(metadata("0xbb", now - Duration::days(2)));
+        // Fetched 2h ago: past the TTL, still served as a fallback
+        assert!(cache.fresh("0xaa", now).is_none());
+        assert!(cache.fresh("0xaa", now - Duration::minutes(61)).is_some());
+        assert_eq!(cache.get("0xaa").unwrap().to_market().asset_ids[1], "t-yes");
+        assert!(cache.save_if_dirty(now).unwrap());
+        assert!(!cache.save_if_dirty(now).unwrap());
+
+        let restored = MetadataCache::load(data_dir, &config);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            restored.get("0xaa"),
+            Some(metadata("0xaa", now - Duration::hours(2)))
+        );
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,17 +1,43 @@
+pub mod activity;
 pub mod api;
+pub mod bandwidth;
+pub mod breaker;
 pub mod client;
+pub mod collateral;
+pub mod confirmations;
 pub mod contracts;
+pub mod dates;
+pub mod derived_assets;
 pub mod events;
 pub mod lockfree_queue;
 pub mod mempool;
+pub mod metadata_cache;
+pub mod neg_risk;
 pub mod orderbook;
+pub mod sequencing;
+pub mod throttle;
 pub mod types;
+pub mod update_queue;
 
-pub use api::MarketInterface;
+pub use activity::ActivityFeed;
+pub use api::{MarketInterface, OrderRequest};
+pub use bandwidth::{BandwidthMeter, BandwidthStats, FeedHealth};
+pub use breaker::{is_circuit_open, CircuitBreakers};
 pub use client::PolymarketClient;
-pub use events::MarketEventListener;
+pub use collateral::{Collateral, CollateralBalances, ExchangeContract, PositionCollateral};
+pub use derived_assets::DerivedAssetCache;
+pub use events::{
+    MarketEventListener, ReconnectBackoff, TokenTransfer, TransferDirection, TRANSFER_QUEUE,
+};
 pub use lockfree_queue::OrderBookQueue;
 pub use mempool::MempoolMonitor;
-pub use types::{MarketData, OrderBook, OrderLevel};
+pub use metadata_cache::{MarketMetadata, MetadataCache};
+pub use neg_risk::NegRiskGroups;
+pub use sequencing::{BookSequencer, SequenceStats};
+pub use throttle::{ThrottleStats, TickThrottle};
+pub use types::{
+    EventSeries, MarketData, OpenOrder, OrderBook, OrderLevel, OrderStatus, QuoteQuality,
+};
+pub use update_queue::{update_queue, DropPolicy, QueueStats, UpdateReceiver, UpdateSender};
 pub mod ws;
-pub use ws::ClobWebSocket;
+pub use ws::{ClobWebSocket, WsControl};
@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+/// A condition's place in its multi-outcome (negative risk) market
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegRiskMember {
+    /// The NegRiskAdapter's market id, shared by every question of the market
+    pub neg_risk_market_id: String,
+    /// Question index within it (the last byte of the question id)
+    pub question_index: u8,
+}
+
+#[derive(Default)]
+struct Groups {
+    members: HashMap<String, NegRiskMember>,
+    /// Adapter market id -> question index -> condition id
+    markets: HashMap<String, BTreeMap<u8, String>>,
+}
+
+/// Which conditions share a NegRiskAdapter market, learned from Gamma's `negRiskMarketID` and
+/// `questionID`. Only the questions Gamma listed are known: a group can be missing some of
+/// its market's questions. Cheap to clone, all clones share the map
+#[derive(Clone, Default)]
+pub struct NegRiskGroups {
+    inner: Arc<RwLock<Groups>>,
+}
+
+impl NegRiskGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a condition of an adapter market. Ignored unless the question id parses
+    pub fn record(&self, condition_id: &str, neg_risk_market_id: &str, question_id: &str) {
+        let Some(question_index) = question_index(question_id) else {
+            return;
+        };
+        let member = NegRiskMember {
+            neg_risk_market_id: neg_risk_market_id.to_lowercase(),
+            question_index,
+        };
+        let Ok(mut groups) = self.inner.write() else {
+            return;
+        };
+        groups
+            .markets
+            .entry(member.neg_risk_market_id.clone())
+            .or_default()
+            .insert(question_index, condition_id.to_string());
+        groups.members.insert(condition_id.to_string(), member);
+    }
+
+    pub fn member(&self, condition_id: &str) -> Option<NegRiskMember> {
+        self.inner.read().ok()?.members.get(condition_id).cloned()
+    }
+
+    /// (question index, condition id) of every known question of an adapter market
+    pub fn questions(&self, neg_risk_market_id: &str) -> Vec<(u8, String)> {
+        self.inner
+            .read()
+            .ok()
+            .and_then(|groups| {
+                groups.markets.get(neg_risk_market_id).map(|questions| {
+                    questions
+                        .iter()
+                        .map(|(index, condition)| (*index, condition.clone()))
+                        .collect()
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().map(|g| g.markets.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The adapter derives question ids from the market id, the index in the last byte
+fn question_index(question_id: &str) -> Option<u8> {
+    let hex = question_id.trim().trim_start_matches("0x");
+    if hex.len() != 64 {
+        return None;
+    }
+    u8::from_str_radix(&hex[62..], 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_questions_by_adapter_market() {
+        let market = format!("0x{}00", "ab".repeat(31));
+        let question = |i: u8| format!("0x{}{:02x}", "ab".repeat(31), i);
+        let groups = NegRiskGroups::new();
+        groups.record("0xc2", &market, &question(2));
+        groups.record("0xc0", &market, &question(0));
+        groups.record("0xbad", &market, "0x12");
+
+        assert_eq!(groups.member("0xc2").unwrap().question_index, 2);
+        assert!(groups.member("0xbad").is_none());
+        assert_eq!(
+            groups.questions(&market),
+            vec![(0, "0xc0".to_string()), (2, "0xc2".to_string())]
+        );
+        assert_eq!(groups.len(), 1);
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::polymarket::ws::OrderbookUpdate;
+
+/// Hashes remembered for the latest timestamp of an asset (several changes can share a ms)
+const MAX_HASHES_PER_TICK: usize = 16;
+
+/// Counters of the per-asset ordering checks on feed updates
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct SequenceStats {
+    /// Assets with at least one accepted update
+    pub assets: usize,
+    pub accepted: u64,
+    /// Dropped: older event time than an update already applied for the asset
+    pub reordered: u64,
+    /// Dropped: same event time and hash as an update already applied
+    pub duplicates: u64,
+    /// Accepted without ordering checks because the timestamp didn't parse
+    pub unordered: u64,
+}
+
+#[derive(Default)]
+struct AssetSequence {
+    seq: u64,
+    last_timestamp: u64,
+    hashes: Vec<String>,
+}
+
+#[derive(Default)]
+struct SequencerInner {
+    assets: HashMap<String, AssetSequence>,
+    stats: SequenceStats,
+}
+
+/// Orders book updates by the feed's event time: each asset only moves forward, stale and
+/// duplicated frames are dropped, and accepted updates get a per-asset sequence number
+#[derive(Default)]
+pub struct BookSequencer {
+    inner: Mutex<SequencerInner>,
+}
+
+impl BookSequencer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `update` should be applied. Accepted updates have `seq` set
+    /// (a poisoned lock lets every update through unchecked)
+    pub fn admit(&self, update: &mut OrderbookUpdate) -> bool {
+        let Ok(mut inner) = self.inner.lock() else {
+            return true;
+        };
+        let SequencerInner { assets, stats } = &mut *inner;
+        let asset = assets.entry(update.asset_id.clone()).or_default();
+
+        match update.timestamp.trim().parse::<u64>() {
+            Ok(ts) if ts < asset.last_timestamp => {
+                stats.reordered += 1;
+                return false;
+            }
+            Ok(ts) if ts == asset.last_timestamp => {
+                if asset.hashes.contains(&update.hash) {
+                    stats.duplicates += 1;
+                    return false;
+                }
+                if asset.hashes.len() >= MAX_HASHES_PER_TICK {
+                    asset.hashes.remove(0);
+                }
+                asset.hashes.push(update.hash.clone());
+            }
+            Ok(ts) => {
+                asset.last_timestamp = ts;
+                asset.hashes.clear();
+                asset.hashes.push(update.hash.clone());
+            }
+            Err(_) => stats.unordered += 1,
+        }
+
+        asset.seq += 1;
+        update.seq = asset.seq;
+        stats.accepted += 1;
+        true
+    }
+
+    /// Drop the ordering state of an unsubscribed asset
+    pub fn forget(&self, asset_id: &str) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.assets.remove(asset_id);
+        }
+    }
+
+    pub fn stats(&self) -> SequenceStats {
+        match self.inner.lock() {
+            Ok(inner) => SequenceStats {
+                assets: inner.assets.len(),
+                ..inner.stats.clone()
+            },
+            Err(_) => SequenceStats::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(asset_id: &str, timestamp: &str, hash: &str) -> OrderbookUpdate {
+        OrderbookUpdate {
+            asset_id: asset_id.to_string(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+            timestamp: timestamp.to_string(),
+            hash: hash.to_string(),
+            seq: 0,
+            received_ns: 0,
+            change: None,
+        }
+    }
+
+    #[test]
+    fn test_drops_stale_and_duplicate_updates() {
+        let sequencer = BookSequencer::new();
+        let admit = |asset: &str, ts: &str, hash: &str| {
+            let mut u = update(asset, ts, hash);
+            sequencer.admit(&mut u).then_some(u.seq)
+        };
+
+        assert_eq!(admit("a", "100", "h1"), Some(1));
+        assert_eq!(admit("a", "100", "h2"), Some(2));
+        // Same frame delivered twice
+        assert_eq!(admit("a", "100", "h1"), None);
+        assert_eq!(admit("a", "102", "h3"), Some(3));
+        // Arrived after a newer update for the same asset
+        assert_eq!(admit("a", "101", "h4"), None);
+        // Ordering is per asset
+        assert_eq!(admit("b", "50", "h1"), Some(1));
+        assert_eq!(admit("b", "bad", "h2"), Some(2));
+
+        let stats = sequencer.stats();
+        assert_eq!((stats.assets, stats.accepted), (2, 5));
+        assert_eq!(
+            (stats.reordered, stats.duplicates, stats.unordered),
+            (1, 1, 1)
+        );
+
+        // An unsubscribed asset starts over when it comes back
+        sequencer.forget("a");
+        assert_eq!(sequencer.stats().assets, 1);
+        assert_eq!(admit("a", "90", "h5"), Some(1));
+    }
+}
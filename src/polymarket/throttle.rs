@@ -0,0 +1,201 @@
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::polymarket::ws::OrderbookUpdate;
+
+/// Counters of the per-asset tick throttle
+#[derive(Debug, Clone, Default)]
+pub struct ThrottleStats {
+    /// Assets currently throttled as marking-only
+    pub marking_assets: usize,
+    pub forwarded: u64,
+    /// Updates superseded by a newer book of the same asset before their interval was up
+    pub sampled_out: u64,
+}
+
+/// Per-asset update rate of the WS dispatch layer. Snipe targets get every book update; assets
+/// only needed to mark open positions get at most one per interval (always the latest book),
+/// back to full rate in the last stretch before their market ends. The rules are set by the
+/// agent, the sampling itself runs in the reader task (see `ThrottleGate`)
+pub struct TickThrottle {
+    interval_ns: u64,
+    full_rate_before_expiry: Duration,
+    /// Marking-only assets with their market's end, when known
+    marking: DashMap<String, Option<DateTime<Utc>>>,
+    forwarded: AtomicU64,
+    sampled_out: AtomicU64,
+}
+
+impl TickThrottle {
+    /// `interval_ms` = 0 turns throttling off
+    pub fn new(interval_ms: u64, full_rate_before_expiry_secs: u64) -> Self {
+        Self {
+            interval_ns: interval_ms * 1_000_000,
+            full_rate_before_expiry: Duration::seconds(full_rate_before_expiry_secs as i64),
+            marking: DashMap::new(),
+            forwarded: AtomicU64::new(0),
+            sampled_out: AtomicU64::new(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.interval_ns > 0
+    }
+
+    /// Replace the marking-only assets (asset id -> market end); every other asset is full rate
+    pub fn set_marking(&self, assets: HashMap<String, Option<DateTime<Utc>>>) {
+        self.marking
+            .retain(|asset_id, _| assets.contains_key(asset_id));
+        for (asset_id, end) in assets {
+            self.marking.insert(asset_id, end);
+        }
+    }
+
+    /// Minimum time between forwarded updates of an asset, ns (0 = every update)
+    pub fn interval_ns(&self, asset_id: &str) -> u64 {
+        if self.interval_ns == 0 {
+            return 0;
+        }
+        match self.marking.get(asset_id).map(|end| *end) {
+            None => 0,
+            Some(Some(end)) if end - Utc::now() <= self.full_rate_before_expiry => 0,
+            Some(_) => self.interval_ns,
+        }
+    }
+
+    pub fn stats(&self) -> ThrottleStats {
+        ThrottleStats {
+            marking_assets: self.marking.len(),
+            forwarded: self.forwarded.load(Ordering::Relaxed),
+            sampled_out: self.sampled_out.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Sampling state of one WS connection: when each asset was last forwarded and the newest book
+/// held back for it
+#[derive(Default)]
+pub struct ThrottleGate {
+    last_sent: HashMap<String, u64>,
+    held: HashMap<String, OrderbookUpdate>,
+}
+
+impl ThrottleGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The update to forward now, if any; a throttled one is held until `due` releases it
+    pub fn offer(
+        &mut self,
+        throttle: &TickThrottle,
+        update: OrderbookUpdate,
+        now_ns: u64,
+    ) -> Option<OrderbookUpdate> {
+        let interval = throttle.interval_ns(&update.asset_id);
+        let last = self.last_sent.get(&update.asset_id).copied();
+        if interval == 0 || last.is_none_or(|last| now_ns.saturating_sub(last) >= interval) {
+            if self.held.remove(&update.asset_id).is_some() {
+                throttle.sampled_out.fetch_add(1, Ordering::Relaxed);
+            }
+            if interval > 0 {
+                self.last_sent.insert(update.asset_id.clone(), now_ns);
+            }
+            throttle.forwarded.fetch_add(1, Ordering::Relaxed);
+            return Some(update);
+        }
+        if self.held.insert(update.asset_id.clone(), update).is_some() {
+            throttle.sampled_out.fetch_add(1, Ordering::Relaxed);
+        }
+        None
+    }
+
+    /// Held updates whose interval is up (or whose asset went back to full rate)
+    pub fn due(&mut self, throttle: &TickThrottle, now_ns: u64) -> Vec<OrderbookUpdate> {
+        let ready: Vec<String> = self
+            .held
+            .keys()
+            .filter(|asset_id| {
+                let last = self.last_sent.get(*asset_id).copied().unwrap_or(0);
+                now_ns.saturating_sub(last) >= throttle.interval_ns(asset_id)
+            })
+            .cloned()
+            .collect();
+        ready
+            .into_iter()
+            .filter_map(|asset_id| {
+                self.last_sent.insert(asset_id.clone(), now_ns);
+                throttle.forwarded.fetch_add(1, Ordering::Relaxed);
+                self.held.remove(&asset_id)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    fn update(asset_id: &str, hash: &str) -> OrderbookUpdate {
+        OrderbookUpdate {
+            asset_id: asset_id.to_string(),
+            bids: vec![],
+            asks: vec![],
+            timestamp: "1".to_string(),
+            hash: hash.to_string(),
+            seq: 0,
+            received_ns: 0,
+            change: None,
+        }
+    }
+
+    #[test]
+    fn test_marking_assets_sampled_to_latest_book() {
+        let throttle = TickThrottle::new(250, 60);
+        throttle.set_marking(HashMap::from([
+            ("held".to_string(), Some(Utc::now() + Duration::hours(1))),
+            (
+                "expiring".to_string(),
+                Some(Utc::now() + Duration::seconds(30)),
+            ),
+        ]));
+        let mut gate = ThrottleGate::new();
+
+        // Snipe targets and assets about to expire go straight through
+        assert!(gate.offer(&throttle, update("target", "t1"), 0).is_some());
+        assert!(gate.offer(&throttle, update("target", "t2"), MS).is_some());
+        assert!(gate.offer(&throttle, update("expiring", "e1"), 0).is_some());
+        assert!(gate
+            .offer(&throttle, update("expiring", "e2"), MS)
+            .is_some());
+
+        // Marking asset: the first update passes, the next two within 250ms are held (the
+        // later superseding the earlier) and the latest is released once the interval is up
+        assert!(gate.offer(&throttle, update("held", "h1"), 0).is_some());
+        assert!(gate
+            .offer(&throttle, update("held", "h2"), 100 * MS)
+            .is_none());
+        assert!(gate
+            .offer(&throttle, update("held", "h3"), 200 * MS)
+            .is_none());
+        assert!(gate.due(&throttle, 240 * MS).is_empty());
+        let released = gate.due(&throttle, 250 * MS);
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].hash, "h3");
+
+        let stats = throttle.stats();
+        assert_eq!(stats.marking_assets, 2);
+        assert_eq!(stats.forwarded, 6);
+        assert_eq!(stats.sampled_out, 1);
+
+        // No longer marking-only: full rate again
+        throttle.set_marking(HashMap::new());
+        assert!(gate
+            .offer(&throttle, update("held", "h4"), 260 * MS)
+            .is_some());
+    }
+}
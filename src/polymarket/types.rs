@@ -1,10 +1,22 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::clock;
+use crate::ids::MarketId;
+use crate::markets::MarketCategory;
+use crate::polymarket::dates::market_end_time;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct MarketData {
-    pub id: String,
+    #[schema(value_type = String)]
+    pub id: MarketId,
     pub question: String,
     pub end_date: Option<String>,
+    /// `end_date` parsed once where the market is built (see `MarketData::with_end_time`),
+    /// None when missing or unparseable
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
     pub volume: f64,
     pub liquidity: f64,
     pub yes_price: f64,
@@ -20,6 +32,94 @@ pub struct MarketData {
     pub best_ask: f64,
     #[serde(default)]
     pub asset_ids: Vec<String>, // Token IDs for YES/NO
+    /// Set from the question once metadata is known (None for synthetic markets)
+    #[serde(default)]
+    pub category: Option<MarketCategory>,
+}
+
+impl MarketData {
+    /// Which outcome asks can be acted on. A side with no asks is stored as 0, which must not
+    /// be read as a price (it would look like a 10,000 bps edge)
+    pub fn quote_quality(&self) -> QuoteQuality {
+        QuoteQuality::from_prices(self.yes_price, self.no_price)
+    }
+
+    /// Ask of one outcome ("YES" / "NO"), None when that side is missing
+    pub fn ask(&self, side: &str) -> Option<f64> {
+        let price = if side == "YES" {
+            self.yes_price
+        } else {
+            self.no_price
+        };
+        QuoteQuality::is_usable(price).then_some(price)
+    }
+
+    /// Parse `end_date` (any format Gamma returns, see `dates::market_end_time`). Scans the
+    /// question, so read the cached `end_time` on hot paths
+    pub fn parse_end_time(&self) -> Option<DateTime<Utc>> {
+        market_end_time(self.end_date.as_deref()?, &self.question)
+    }
+
+    /// The market with `end_time` set from its end date and question
+    pub fn with_end_time(self) -> Self {
+        Self {
+            end_time: self.parse_end_time(),
+            ..self
+        }
+    }
+
+    /// Test fixture: market `id` asking "`id`?" at these prices, the rest defaulted
+    #[cfg(test)]
+    pub fn quoted(id: &str, yes_price: f64, no_price: f64) -> Self {
+        Self {
+            id: id.into(),
+            question: format!("{}?", id),
+            yes_price,
+            no_price,
+            ..Default::default()
+        }
+    }
+}
+
+/// Completeness of a market's YES/NO quotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuoteQuality {
+    /// Both outcomes have an ask
+    Complete,
+    MissingYes,
+    MissingNo,
+    /// No usable quote on either side
+    Empty,
+}
+
+impl QuoteQuality {
+    pub fn from_prices(yes_price: f64, no_price: f64) -> Self {
+        match (Self::is_usable(yes_price), Self::is_usable(no_price)) {
+            (true, true) => QuoteQuality::Complete,
+            (false, true) => QuoteQuality::MissingYes,
+            (true, false) => QuoteQuality::MissingNo,
+            (false, false) => QuoteQuality::Empty,
+        }
+    }
+
+    /// A real probability price (0 and NaN mean "no quote")
+    pub fn is_usable(price: f64) -> bool {
+        price.is_finite() && price > 0.0 && price <= 1.0
+    }
+
+    pub fn is_complete(self) -> bool {
+        self == QuoteQuality::Complete
+    }
+
+    /// The given outcome ("YES" / "NO") has a usable ask
+    pub fn has(self, side: &str) -> bool {
+        match self {
+            QuoteQuality::Complete => true,
+            QuoteQuality::MissingYes => side != "YES",
+            QuoteQuality::MissingNo => side == "YES",
+            QuoteQuality::Empty => false,
+        }
+    }
 }
 
 /// Represents a single price level in the orderbook
@@ -46,7 +146,8 @@ pub struct OrderBook {
     pub asks: [OrderLevel; 50],
     pub bid_count: usize,
     pub ask_count: usize,
-    pub timestamp: u64,
+    /// When the book was built, monotonic ns (see `clock`)
+    pub timestamp_ns: u64,
 }
 
 impl OrderBook {
@@ -56,13 +157,23 @@ impl OrderBook {
             asks: [OrderLevel::default(); 50],
             bid_count: 0,
             ask_count: 0,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp_ns: clock::now_ns(),
         }
     }
 
+    /// Build a book from level lists (bids best-first, asks best-first)
+    /// Levels beyond the fixed depth of 50 are dropped
+    pub fn from_levels(bids: &[OrderLevel], asks: &[OrderLevel]) -> Self {
+        let mut book = Self::new();
+        let bid_count = bids.len().min(book.bids.len());
+        let ask_count = asks.len().min(book.asks.len());
+        book.bids[..bid_count].copy_from_slice(&bids[..bid_count]);
+        book.asks[..ask_count].copy_from_slice(&asks[..ask_count]);
+        book.bid_count = bid_count;
+        book.ask_count = ask_count;
+        book
+    }
+
     #[inline(always)]
     pub fn best_bid(&self) -> Option<f64> {
         if self.bid_count > 0 {
@@ -109,3 +220,57 @@ impl OrderBook {
         &self.asks[..self.ask_count]
     }
 }
+
+/// Lifecycle state of an order on the CLOB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// Resting on the book, nothing filled yet
+    Live,
+    /// Resting on the book with part of the size filled
+    PartiallyFilled,
+    /// Fully filled
+    Matched,
+    /// Cancelled (by us or by the exchange) before fully filling
+    Cancelled,
+    /// Status could not be determined
+    Unknown,
+}
+
+impl OrderStatus {
+    /// True if the order can no longer change state
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Matched | OrderStatus::Cancelled)
+    }
+}
+
+/// An order resting (or recently resting) on the book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub order_id: String,
+    pub market_id: String,
+    pub asset_id: String,
+    pub side: String, // "BUY" or "SELL"
+    pub price: f64,
+    pub original_size: f64,
+    pub size_matched: f64,
+    pub status: OrderStatus,
+    pub created_at: u64,
+}
+
+impl OpenOrder {
+    /// Size still resting on the book
+    pub fn remaining_size(&self) -> f64 {
+        (self.original_size - self.size_matched).max(0.0)
+    }
+}
+
+/// A recurring family of events on Gamma (e.g. a weekly "Bitcoin above ___ on Friday?")
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct EventSeries {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    /// "daily", "weekly", ... as Gamma reports it
+    pub recurrence: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,356 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use crate::alloc_profile;
+use crate::polymarket::ws::OrderbookUpdate;
+
+/// What the WS reader does when the agent falls behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DropPolicy {
+    /// Wait for space (backpressures the WS reader)
+    Block,
+    /// Evict the oldest queued update
+    DropOldest,
+    /// Replace a queued update for the same asset in place (only the latest book matters),
+    /// falling back to drop-oldest when the queue is full of distinct assets
+    Coalesce,
+}
+
+impl DropPolicy {
+    pub fn parse(policy: &str) -> Self {
+        match policy.to_lowercase().as_str() {
+            "block" => DropPolicy::Block,
+            "drop_oldest" | "drop-oldest" => DropPolicy::DropOldest,
+            _ => DropPolicy::Coalesce,
+        }
+    }
+}
+
+/// Point-in-time queue counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub capacity: usize,
+    pub depth: usize,
+    pub high_water_mark: usize,
+    /// Times the queue filled up to capacity (each counted once, until it drains below)
+    #[serde(default)]
+    pub saturated: u64,
+    pub enqueued: u64,
+    pub dropped: u64,
+    pub coalesced: u64,
+    /// Times the sender had to wait for space (Block policy)
+    pub blocked: u64,
+    /// Updates dequeued but skipped because a newer book for the same asset was in the batch
+    pub superseded: u64,
+}
+
+#[derive(Default)]
+struct QueueMetrics {
+    depth: AtomicUsize,
+    high_water_mark: AtomicUsize,
+    saturated: AtomicU64,
+    enqueued: AtomicU64,
+    dropped: AtomicU64,
+    coalesced: AtomicU64,
+    blocked: AtomicU64,
+    superseded: AtomicU64,
+}
+
+struct Shared {
+    queue: Mutex<VecDeque<OrderbookUpdate>>,
+    capacity: usize,
+    policy: DropPolicy,
+    metrics: QueueMetrics,
+    items: Notify,
+    space: Notify,
+    senders: AtomicUsize,
+    closed: AtomicBool,
+}
+
+impl Shared {
+    fn stats(&self) -> QueueStats {
+        QueueStats {
+            capacity: self.capacity,
+            depth: self.metrics.depth.load(Ordering::Relaxed),
+            high_water_mark: self.metrics.high_water_mark.load(Ordering::Relaxed),
+            saturated: self.metrics.saturated.load(Ordering::Relaxed),
+            enqueued: self.metrics.enqueued.load(Ordering::Relaxed),
+            dropped: self.metrics.dropped.load(Ordering::Relaxed),
+            coalesced: self.metrics.coalesced.load(Ordering::Relaxed),
+            blocked: self.metrics.blocked.load(Ordering::Relaxed),
+            superseded: self.metrics.superseded.load(Ordering::Relaxed),
+        }
+    }
+
+    fn set_depth(&self, depth: usize) {
+        let previous = self.metrics.depth.swap(depth, Ordering::Relaxed);
+        if depth >= self.capacity && previous < self.capacity {
+            self.metrics.saturated.fetch_add(1, Ordering::Relaxed);
+        }
+        self.metrics
+            .high_water_mark
+            .fetch_max(depth, Ordering::Relaxed);
+    }
+}
+
+/// Create a bounded orderbook update queue with the given overflow policy
+pub fn update_queue(capacity: usize, policy: DropPolicy) -> (UpdateSender, UpdateReceiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity: capacity.max(1),
+        policy,
+        metrics: QueueMetrics::default(),
+        items: Notify::new(),
+        space: Notify::new(),
+        senders: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+    });
+    (
+        UpdateSender {
+            shared: shared.clone(),
+        },
+        UpdateReceiver { shared },
+    )
+}
+
+/// Producer side (WS reader)
+pub struct UpdateSender {
+    shared: Arc<Shared>,
+}
+
+impl UpdateSender {
+    /// Enqueue an update according to the drop policy
+    /// Only fails once the receiver is gone
+    pub async fn send(&self, update: OrderbookUpdate) -> anyhow::Result<()> {
+        let shared = &self.shared;
+        let mut update = Some(update);
+
+        loop {
+            if shared.closed.load(Ordering::Acquire) {
+                anyhow::bail!("update queue closed");
+            }
+
+            // Register interest before checking, so a pop between check and await isn't missed
+            let space = shared.space.notified();
+            {
+                let mut queue = shared.queue.lock().unwrap();
+
+                if shared.policy == DropPolicy::Coalesce {
+                    // Scan from the back: a burst for one asset is usually at the tail
+                    let asset_id = &update.as_ref().unwrap().asset_id;
+                    if let Some(slot) = queue.iter_mut().rev().find(|u| &u.asset_id == asset_id) {
+                        *slot = update.take().unwrap();
+                        shared.metrics.coalesced.fetch_add(1, Ordering::Relaxed);
+                        shared.metrics.enqueued.fetch_add(1, Ordering::Relaxed);
+                        return Ok(());
+                    }
+                }
+
+                if queue.len() >= shared.capacity {
+                    match shared.policy {
+                        DropPolicy::Block => {
+                            shared.metrics.blocked.fetch_add(1, Ordering::Relaxed);
+                            drop(queue);
+                            space.await;
+                            continue;
+                        }
+                        DropPolicy::DropOldest | DropPolicy::Coalesce => {
+                            queue.pop_front();
+                            shared.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+
+                queue.push_back(update.take().unwrap());
+                shared.set_depth(queue.len());
+            }
+
+            shared.metrics.enqueued.fetch_add(1, Ordering::Relaxed);
+            shared.items.notify_one();
+            return Ok(());
+        }
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.shared.stats()
+    }
+}
+
+impl Clone for UpdateSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl Drop for UpdateSender {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.closed.store(true, Ordering::Release);
+            self.shared.items.notify_one();
+        }
+    }
+}
+
+/// Consumer side (Sniper)
+pub struct UpdateReceiver {
+    shared: Arc<Shared>,
+}
+
+impl UpdateReceiver {
+    /// Next update, or None once all senders are gone and the queue is drained
+    pub async fn recv(&mut self) -> Option<OrderbookUpdate> {
+        loop {
+            let items = self.shared.items.notified();
+            if let Some(update) = self.try_recv() {
+                return Some(update);
+            }
+            if self.shared.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            items.await;
+        }
+    }
+
+    /// Non-blocking pop
+    pub fn try_recv(&mut self) -> Option<OrderbookUpdate> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let update = queue.pop_front()?;
+        self.shared.set_depth(queue.len());
+        drop(queue);
+        self.shared.space.notify_one();
+        Some(update)
+    }
+
+    /// Coalescing stage: take `first` plus everything already queued (up to `max_batch`)
+    /// and keep only the newest update per asset, in first-seen order
+    pub fn drain_latest(
+        &mut self,
+        first: OrderbookUpdate,
+        max_batch: usize,
+    ) -> Vec<OrderbookUpdate> {
+        let _alloc = alloc_profile::region("update_queue::drain_latest");
+        let mut latest: Vec<OrderbookUpdate> = vec![first];
+        let mut index: HashMap<String, usize> = HashMap::new();
+        index.insert(latest[0].asset_id.clone(), 0);
+        let mut superseded = 0u64;
+
+        for _ in 1..max_batch.max(1) {
+            let Some(update) = self.try_recv() else {
+                break;
+            };
+            match index.get(&update.asset_id) {
+                Some(&i) => {
+                    latest[i] = update;
+                    superseded += 1;
+                }
+                None => {
+                    index.insert(update.asset_id.clone(), latest.len());
+                    latest.push(update);
+                }
+            }
+        }
+
+        if superseded > 0 {
+            self.shared
+                .metrics
+                .superseded
+                .fetch_add(superseded, Ordering::Relaxed);
+        }
+        latest
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.shared.stats()
+    }
+}
+
+impl Drop for UpdateReceiver {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.space.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(asset_id: &str, hash: &str) -> OrderbookUpdate {
+        OrderbookUpdate {
+            asset_id: asset_id.to_string(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+            timestamp: "0".to_string(),
+            hash: hash.to_string(),
+            seq: 0,
+            received_ns: 0,
+            change: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_keeps_latest_per_asset() {
+        let (tx, mut rx) = update_queue(10, DropPolicy::Coalesce);
+        tx.send(update("a", "1")).await.unwrap();
+        tx.send(update("b", "1")).await.unwrap();
+        tx.send(update("a", "2")).await.unwrap();
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!((first.asset_id.as_str(), first.hash.as_str()), ("a", "2"));
+        assert_eq!(rx.recv().await.unwrap().asset_id, "b");
+        assert_eq!(rx.stats().coalesced, 1);
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_when_full() {
+        let (tx, mut rx) = update_queue(2, DropPolicy::DropOldest);
+        for i in 0..3 {
+            tx.send(update(&i.to_string(), "h")).await.unwrap();
+        }
+
+        assert_eq!(rx.recv().await.unwrap().asset_id, "1");
+        assert_eq!(rx.recv().await.unwrap().asset_id, "2");
+        let stats = rx.stats();
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.high_water_mark, 2);
+        // Staying full was one breach; filling up again after draining is another
+        assert_eq!(stats.saturated, 1);
+        tx.send(update("3", "h")).await.unwrap();
+        tx.send(update("4", "h")).await.unwrap();
+        assert_eq!(rx.stats().saturated, 2);
+    }
+
+    #[tokio::test]
+    async fn test_drain_latest_skips_superseded() {
+        let (tx, mut rx) = update_queue(10, DropPolicy::DropOldest);
+        tx.send(update("a", "1")).await.unwrap();
+        tx.send(update("b", "1")).await.unwrap();
+        tx.send(update("a", "2")).await.unwrap();
+        tx.send(update("a", "3")).await.unwrap();
+
+        let first = rx.recv().await.unwrap();
+        let batch = rx.drain_latest(first, 100);
+        let seen: Vec<(&str, &str)> = batch
+            .iter()
+            .map(|u| (u.asset_id.as_str(), u.hash.as_str()))
+            .collect();
+        assert_eq!(seen, vec![("a", "3"), ("b", "1")]);
+        assert_eq!(rx.stats().superseded, 2);
+    }
+
+    #[tokio::test]
+    async fn test_recv_none_after_sender_dropped() {
+        let (tx, mut rx) = update_queue(2, DropPolicy::Block);
+        tx.send(update("a", "1")).await.unwrap();
+        drop(tx);
+
+        assert!(rx.recv().await.is_some());
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -1,11 +1,23 @@
 use anyhow::Result;
+use dashmap::DashMap;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::de::IgnoredAny;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 use tracing::{debug, error, info, trace, warn};
 
+use crate::alloc_profile;
+use crate::clock;
+use crate::polymarket::bandwidth::{BandwidthMeter, BandwidthStats, FeedHealth};
+use crate::polymarket::sequencing::{BookSequencer, SequenceStats};
+use crate::polymarket::throttle::{ThrottleGate, ThrottleStats, TickThrottle};
+use crate::polymarket::update_queue::UpdateSender;
+use crate::simulation::{ChaosFault, ChaosMonkey};
+
 const CLOB_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
 
 #[derive(Debug, Clone, Serialize)]
@@ -13,26 +25,47 @@ pub struct Subscription {
     pub assets_ids: Vec<String>,
     #[serde(rename = "type")]
     pub msg_type: String,
+    /// "unsubscribe" to drop assets (omitted for subscriptions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
 }
 
-// NEW STRUCTS DEFINITION
-#[derive(Debug, Deserialize)]
-pub struct WsMessage {
-    pub market: String,
-    pub price_changes: Vec<WsPriceChange>,
-    pub timestamp: String,
-    pub event_type: String,
+/// Change to the asset set of the market channel
+#[derive(Debug)]
+enum SubscriptionCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+    /// Unsubscribe and subscribe again, for a fresh book snapshot
+    Resubscribe(Vec<String>),
+    /// Drop the connection and reconnect right away
+    Reconnect,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct WsBookSnapshot {
-    pub asset_id: String,
-    pub bids: Vec<PriceLevel>,
-    pub asks: Vec<PriceLevel>,
-    pub timestamp: String,
-    pub hash: String,
+/// Cloneable handle on the market channel for operators (control API): forces a resync when
+/// prices look stale, without restarting the agent
+#[derive(Clone)]
+pub struct WsControl {
+    subscribe_tx: mpsc::UnboundedSender<SubscriptionCommand>,
 }
 
+impl WsControl {
+    /// Drop the connection and rebuild it at once; every subscription is replayed, so each
+    /// asset gets a fresh book snapshot. False if the socket task is gone
+    pub fn resync(&self) -> bool {
+        self.subscribe_tx
+            .send(SubscriptionCommand::Reconnect)
+            .is_ok()
+    }
+
+    /// Re-request book snapshots of subscribed assets (others are ignored)
+    pub fn resubscribe(&self, asset_ids: Vec<String>) -> bool {
+        self.subscribe_tx
+            .send(SubscriptionCommand::Resubscribe(asset_ids))
+            .is_ok()
+    }
+}
+
+// NEW STRUCTS DEFINITION
 #[derive(Debug, Deserialize)]
 pub struct WsPriceChange {
     pub asset_id: String,
@@ -51,6 +84,16 @@ pub struct OrderbookUpdate {
     pub asks: Vec<PriceLevel>,
     pub timestamp: String,
     pub hash: String,
+    /// Per-asset sequence number, set once the update passes the ordering checks
+    #[serde(default)]
+    pub seq: u64,
+    /// When the message was read off the socket, monotonic ns (see `clock`); 0 = not stamped
+    #[serde(skip)]
+    pub received_ns: u64,
+    /// Level a `price_change` event set (`bids` / `asks` then only carry the best prices, with
+    /// size 0). None on full book snapshots
+    #[serde(skip)]
+    pub change: Option<LevelChange>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -59,127 +102,334 @@ pub struct PriceLevel {
     pub size: String,
 }
 
+/// New total size of one price level ("0" = level gone) on one side of the book
+#[derive(Debug)]
+pub struct LevelChange {
+    /// "BUY" for bids, "SELL" for asks
+    pub side: String,
+    pub level: PriceLevel,
+}
+
+/// Level as it sits in the frame, copied out only if it is kept
+#[derive(Debug, Deserialize)]
+struct RawLevel<'a> {
+    #[serde(borrow)]
+    price: Cow<'a, str>,
+    #[serde(borrow)]
+    size: Cow<'a, str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawBookSnapshot<'a> {
+    asset_id: String,
+    #[serde(borrow)]
+    bids: Vec<RawLevel<'a>>,
+    #[serde(borrow)]
+    asks: Vec<RawLevel<'a>>,
+    timestamp: String,
+    hash: String,
+}
+
+/// Any object event: the fields routing needs, book levels borrowed from the frame
+#[derive(Debug, Deserialize)]
+struct RawEvent<'a> {
+    #[serde(default, borrow)]
+    event_type: Option<Cow<'a, str>>,
+    #[serde(default)]
+    asset_id: Option<String>,
+    #[serde(default, borrow)]
+    bids: Option<Vec<RawLevel<'a>>>,
+    #[serde(default, borrow)]
+    asks: Option<Vec<RawLevel<'a>>>,
+    #[serde(default)]
+    price_changes: Option<Vec<WsPriceChange>>,
+    #[serde(default)]
+    timestamp: Option<String>,
+    #[serde(default)]
+    hash: Option<String>,
+}
+
+/// What a text frame turned out to be
+#[derive(Debug)]
+enum WsEvent {
+    Updates(Vec<OrderbookUpdate>),
+    /// Known event we have no use for (last trade ticks)
+    Ignored,
+    /// Valid JSON of a shape we don't handle
+    Unknown {
+        array: bool,
+    },
+    NotJson(serde_json::Error),
+}
+
+/// The `depth` best levels of one side, best first (highest bid / lowest ask); 0 keeps them all.
+/// The feed's level order isn't relied on, and only the kept levels are allocated
+fn top_levels(levels: Vec<RawLevel<'_>>, depth: usize, bids: bool) -> Vec<PriceLevel> {
+    let mut priced: Vec<(f64, RawLevel<'_>)> = levels
+        .into_iter()
+        .filter_map(|level| Some((level.price.parse::<f64>().ok()?, level)))
+        .collect();
+    let better = |a: &(f64, RawLevel<'_>), b: &(f64, RawLevel<'_>)| {
+        if bids {
+            b.0.total_cmp(&a.0)
+        } else {
+            a.0.total_cmp(&b.0)
+        }
+    };
+    if depth > 0 && priced.len() > depth {
+        priced.select_nth_unstable_by(depth - 1, better);
+        priced.truncate(depth);
+    }
+    priced.sort_unstable_by(better);
+    priced
+        .into_iter()
+        .map(|(_, level)| PriceLevel {
+            price: level.price.into_owned(),
+            size: level.size.into_owned(),
+        })
+        .collect()
+}
+
+fn book_update(
+    asset_id: String,
+    bids: Vec<RawLevel<'_>>,
+    asks: Vec<RawLevel<'_>>,
+    timestamp: String,
+    hash: String,
+    depth: usize,
+) -> OrderbookUpdate {
+    OrderbookUpdate {
+        asset_id,
+        bids: top_levels(bids, depth, true),
+        asks: top_levels(asks, depth, false),
+        timestamp,
+        hash,
+        seq: 0,
+        received_ns: 0,
+        change: None,
+    }
+}
+
+/// Decode a text frame straight into book updates, keeping `depth` levels per side (0 = all)
+fn decode_message(text: &str, depth: usize) -> WsEvent {
+    // Tells malformed frames from valid JSON of another shape, only on the failure path
+    let unknown = |array: bool, e: serde_json::Error| match serde_json::from_str::<IgnoredAny>(text)
+    {
+        Ok(_) => WsEvent::Unknown { array },
+        Err(_) => WsEvent::NotJson(e),
+    };
+
+    // 1) Array snapshots
+    if text.trim_start().starts_with('[') {
+        return match serde_json::from_str::<Vec<RawBookSnapshot>>(text) {
+            Ok(snapshots) => WsEvent::Updates(
+                snapshots
+                    .into_iter()
+                    .map(|snap| {
+                        book_update(
+                            snap.asset_id,
+                            snap.bids,
+                            snap.asks,
+                            snap.timestamp,
+                            snap.hash,
+                            depth,
+                        )
+                    })
+                    .collect(),
+            ),
+            Err(e) => unknown(true, e),
+        };
+    }
+
+    // 2) Object events (book / last_trade_price / price_changes)
+    let event = match serde_json::from_str::<RawEvent>(text) {
+        Ok(event) => event,
+        Err(e) => return unknown(false, e),
+    };
+    let event_type = event.event_type.as_deref().unwrap_or_default();
+    if event_type == "last_trade_price" {
+        return WsEvent::Ignored;
+    }
+
+    let has_book = event.bids.is_some() && event.asks.is_some();
+    if event_type == "book" || has_book {
+        if let (Some(asset_id), Some(bids), Some(asks), Some(timestamp), Some(hash)) = (
+            event.asset_id,
+            event.bids,
+            event.asks,
+            event.timestamp.clone(),
+            event.hash,
+        ) {
+            return WsEvent::Updates(vec![book_update(
+                asset_id, bids, asks, timestamp, hash, depth,
+            )]);
+        }
+    }
+
+    if let (Some(changes), Some(timestamp)) = (event.price_changes, event.timestamp) {
+        return WsEvent::Updates(
+            changes
+                .into_iter()
+                .map(|change| OrderbookUpdate {
+                    asset_id: change.asset_id,
+                    bids: vec![PriceLevel {
+                        price: change.best_bid,
+                        size: "0".to_string(),
+                    }],
+                    asks: vec![PriceLevel {
+                        price: change.best_ask,
+                        size: "0".to_string(),
+                    }],
+                    timestamp: timestamp.clone(),
+                    hash: change.hash,
+                    seq: 0,
+                    received_ns: 0,
+                    change: Some(LevelChange {
+                        side: change.side,
+                        level: PriceLevel {
+                            price: change.price,
+                            size: change.size,
+                        },
+                    }),
+                })
+                .collect(),
+        );
+    }
+    WsEvent::Unknown { array: false }
+}
+
 pub struct ClobWebSocket {
     // We might need to send subscriptions dynamically
-    subscribe_tx: mpsc::UnboundedSender<Vec<String>>,
+    subscribe_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+    bandwidth: Arc<BandwidthMeter>,
+    sequencer: Arc<BookSequencer>,
+    /// Latest best ask per asset, kept by the reader task so it is current even while the
+    /// agent is busy (e.g. posting an order)
+    best_asks: Arc<DashMap<String, f64>>,
+    throttle: Arc<TickThrottle>,
 }
 
 impl ClobWebSocket {
-    pub async fn new(update_tx: mpsc::Sender<OrderbookUpdate>) -> Result<Self> {
-        let (subscribe_tx, mut subscribe_rx) = mpsc::unbounded_channel::<Vec<String>>();
+    /// `book_depth` levels per side are kept from each book (0 = full book). `throttle` samples
+    /// the updates of marking-only assets. `chaos` drops the connection at random (dry runs
+    /// with CHAOS_ENABLED)
+    pub async fn new(
+        update_tx: UpdateSender,
+        book_depth: usize,
+        throttle: TickThrottle,
+        chaos: Option<Arc<ChaosMonkey>>,
+    ) -> Result<Self> {
+        let (subscribe_tx, mut subscribe_rx) = mpsc::unbounded_channel::<SubscriptionCommand>();
+        let bandwidth = Arc::new(BandwidthMeter::new());
+        let meter = bandwidth.clone();
+        let sequencer = Arc::new(BookSequencer::new());
+        let ordering = sequencer.clone();
+        let best_asks = Arc::new(DashMap::new());
+        let asks = best_asks.clone();
+        let throttle = Arc::new(throttle);
+        let rates = throttle.clone();
 
         tokio::spawn(async move {
+            // Every asset subscribed, replayed on each (re)connection
+            let mut subscribed: HashSet<String> = HashSet::new();
+            let mut reconnect_now = false;
             loop {
                 info!("🔌 Connecting to CLOB WebSocket: {}", CLOB_WS_URL);
+                // permessage-deflate is not offered: tungstenite 0.20 can't inflate frames,
+                // so a server accepting it would break the stream. Whatever the server did
+                // negotiate is recorded with the bandwidth stats.
                 match connect_async(CLOB_WS_URL).await {
-                    Ok((ws_stream, _)) => {
-                        info!("✅ CLOB WebSocket Connected!");
+                    Ok((ws_stream, response)) => {
+                        let extensions = response
+                            .headers()
+                            .get("sec-websocket-extensions")
+                            .and_then(|v| v.to_str().ok())
+                            .map(str::to_string);
+                        info!(
+                            "✅ CLOB WebSocket Connected! (extensions: {})",
+                            extensions.as_deref().unwrap_or("none")
+                        );
+                        meter.connected(extensions);
                         let (mut write, mut read) = ws_stream.split();
 
-                        // Keep registration of new subs
-                        // In a real robust implem, we should re-subscribe to everything on reconnect.
-                        // For now, we listen for new requests.
-
-                        let mut pending_subs: Vec<String> = Vec::new();
+                        // A new connection starts with no subscriptions: replay them all
+                        let mut pending_subs: Vec<String> = subscribed.iter().cloned().collect();
+                        if !pending_subs.is_empty() {
+                            info!("🔌 Replaying {} CLOB WS subscriptions", pending_subs.len());
+                        }
+                        let mut pending_unsubs: Vec<String> = Vec::new();
+                        let mut pending_resubs: Vec<String> = Vec::new();
                         let mut invalid_operation_logged = false;
                         let mut unknown_object_logged = false;
                         let mut flush_interval =
                             tokio::time::interval(std::time::Duration::from_millis(200));
+                        let mut gate = ThrottleGate::new();
+                        let mut throttle_interval =
+                            tokio::time::interval(std::time::Duration::from_millis(50));
 
                         loop {
                             tokio::select! {
                                                             Some(msg) = read.next() => {
+                                                                let received_ns = clock::now_ns();
+                                                                if chaos.as_ref().is_some_and(|c| c.roll(ChaosFault::WsDisconnect)) {
+                                                                    warn!("🐒 Dropping CLOB WebSocket (chaos)");
+                                                                    break;
+                                                                }
+                                                                if let Ok(msg) = &msg {
+                                                                    meter.record(msg.len());
+                                                                }
                                                                 match msg {
                                                                     Ok(Message::Text(text)) => {
-                                                                        let json: Value = match serde_json::from_str(&text) {
-                                                                            Ok(v) => v,
-                                                                            Err(e) => {
-                                                                                if !text.contains("check_ka") {
-                                                                                    if text.trim().eq_ignore_ascii_case("INVALID OPERATION") {
-                                                                                        if !invalid_operation_logged {
-                                                                                            debug!("ℹ️ Ignoring repeated WS non-JSON control message: {}", text.trim());
-                                                                                            invalid_operation_logged = true;
+                                                                        let event = {
+                                                                            let _alloc = alloc_profile::region("ws::parse_json");
+                                                                            decode_message(&text, book_depth)
+                                                                        };
+                                                                        match event {
+                                                                            WsEvent::Updates(updates) => {
+                                                                                for mut update in updates {
+                                                                                    update.received_ns = received_ns;
+                                                                                    if !ordering.admit(&mut update) {
+                                                                                        continue;
+                                                                                    }
+                                                                                    match update.asks.first().and_then(|l| l.price.parse::<f64>().ok()).filter(|p| *p > 0.0) {
+                                                                                        Some(ask) => {
+                                                                                            asks.insert(update.asset_id.clone(), ask);
+                                                                                        }
+                                                                                        None => {
+                                                                                            asks.remove(&update.asset_id);
                                                                                         }
-                                                                                    } else {
-                                                                                        trace!("ℹ️ Ignored non-JSON WS msg: {} | {}", e, text);
                                                                                     }
-                                                                                }
-                                                                                continue;
-                                                                            }
-                                                                        };
-
-                                                                        // 1) Array snapshots
-                                                                        if json.is_array() {
-                                                                            if let Ok(snapshots) = serde_json::from_value::<Vec<WsBookSnapshot>>(json) {
-                                                                                for snap in snapshots {
-                                                                                    let update = OrderbookUpdate {
-                                                                                        asset_id: snap.asset_id,
-                                                                                        bids: snap.bids,
-                                                                                        asks: snap.asks,
-                                                                                        timestamp: snap.timestamp,
-                                                                                        hash: snap.hash,
+                                                                                    // Best ask stays current; marking-only assets are sampled
+                                                                                    let Some(update) = gate.offer(&rates, update, received_ns) else {
+                                                                                        continue;
                                                                                     };
                                                                                     if let Err(e) = update_tx.send(update).await {
-                                                                                        error!("❌ Failed to send snapshot update to agent: {}", e);
+                                                                                        error!("❌ Failed to send book update to agent: {}", e);
                                                                                     }
                                                                                 }
-                                                                                continue;
-                                                                            }
-                                                                            debug!("ℹ️ Ignored WS array msg (unknown shape)");
-                                                                            continue;
-                                                                        }
-
-                                                                        // 2) Object events (book / last_trade_price / price_changes)
-                                                                        if let Some(obj) = json.as_object() {
-                                                                            let event_type = obj
-                                                                                .get("event_type")
-                                                                                .and_then(|v| v.as_str())
-                                                                                .unwrap_or_default();
-
-                                                                            if event_type == "last_trade_price" {
-                                                                                // We do not use last trade ticks for orderbook-based strategy.
-                                                                                continue;
                                                                             }
-
-                                                                            if event_type == "book" || (obj.contains_key("bids") && obj.contains_key("asks")) {
-                                                                                if let Ok(snap) = serde_json::from_value::<WsBookSnapshot>(Value::Object(obj.clone())) {
-                                                                                    let update = OrderbookUpdate {
-                                                                                        asset_id: snap.asset_id,
-                                                                                        bids: snap.bids,
-                                                                                        asks: snap.asks,
-                                                                                        timestamp: snap.timestamp,
-                                                                                        hash: snap.hash,
-                                                                                    };
-                                                                                    if let Err(e) = update_tx.send(update).await {
-                                                                                        error!("❌ Failed to send book update to agent: {}", e);
-                                                                                    }
-                                                                                    continue;
+                                                                            // We do not use last trade ticks for orderbook-based strategy.
+                                                                            WsEvent::Ignored => {}
+                                                                            WsEvent::Unknown { array: true } => debug!("ℹ️ Ignored WS array msg (unknown shape)"),
+                                                                            WsEvent::Unknown { array: false } => {
+                                                                                if !text.contains("check_ka") && !unknown_object_logged {
+                                                                                    debug!("ℹ️ Ignoring unsupported WS object payload shape (logging once per connection)");
+                                                                                    unknown_object_logged = true;
                                                                                 }
                                                                             }
-
-                                                                            if obj.contains_key("price_changes") {
-                                                                                if let Ok(msg) = serde_json::from_value::<WsMessage>(Value::Object(obj.clone())) {
-                                                                                    for change in msg.price_changes {
-                                                                                        let update = OrderbookUpdate {
-                                                                                            asset_id: change.asset_id,
-                                                                                            bids: vec![PriceLevel { price: change.best_bid, size: "0".to_string() }],
-                                                                                            asks: vec![PriceLevel { price: change.best_ask, size: "0".to_string() }],
-                                                                                            timestamp: msg.timestamp.clone(),
-                                                                                            hash: change.hash,
-                                                                                        };
-                                                                                        if let Err(e) = update_tx.send(update).await {
-                                                                                            error!("❌ Failed to send update to agent: {}", e);
+                                                                            WsEvent::NotJson(e) => {
+                                                                                if !text.contains("check_ka") {
+                                                                                    if text.trim().eq_ignore_ascii_case("INVALID OPERATION") {
+                                                                                        if !invalid_operation_logged {
+                                                                                            debug!("ℹ️ Ignoring repeated WS non-JSON control message: {}", text.trim());
+                                                                                            invalid_operation_logged = true;
                                                                                         }
+                                                                                    } else {
+                                                                                        trace!("ℹ️ Ignored non-JSON WS msg: {} | {}", e, text);
                                                                                     }
-                                                                                    continue;
                                                                                 }
                                                                             }
                                                                         }
-
-                                                                        if !text.contains("check_ka") && !unknown_object_logged {
-                                                                            debug!("ℹ️ Ignoring unsupported WS object payload shape (logging once per connection)");
-                                                                            unknown_object_logged = true;
-                                                                        }
                                                                     }
 
                             // ... (Ping/Close handlers remain same) ...
@@ -201,10 +451,76 @@ impl ClobWebSocket {
                                                                     _ => {}
                                                                 }
                                                             }
-                                                            Some(mut assets) = subscribe_rx.recv() => {
-                                                                pending_subs.append(&mut assets);
+                                                            Some(command) = subscribe_rx.recv() => {
+                                                                match command {
+                                                                    SubscriptionCommand::Subscribe(mut assets) => {
+                                                                        subscribed.extend(assets.iter().cloned());
+                                                                        pending_unsubs.retain(|a| !assets.contains(a));
+                                                                        pending_subs.append(&mut assets);
+                                                                    }
+                                                                    SubscriptionCommand::Unsubscribe(mut assets) => {
+                                                                        for asset in &assets {
+                                                                            subscribed.remove(asset);
+                                                                        }
+                                                                        pending_subs.retain(|a| !assets.contains(a));
+                                                                        pending_unsubs.append(&mut assets);
+                                                                    }
+                                                                    SubscriptionCommand::Resubscribe(assets) => {
+                                                                        pending_resubs.extend(assets.into_iter().filter(|a| subscribed.contains(a)));
+                                                                    }
+                                                                    SubscriptionCommand::Reconnect => {
+                                                                        info!("🔄 Resync requested: rebuilding the CLOB WebSocket");
+                                                                        reconnect_now = true;
+                                                                        break;
+                                                                    }
+                                                                }
+                                                            }
+                                                            _ = throttle_interval.tick(), if rates.enabled() => {
+                                                                for update in gate.due(&rates, clock::now_ns()) {
+                                                                    if let Err(e) = update_tx.send(update).await {
+                                                                        error!("❌ Failed to send book update to agent: {}", e);
+                                                                    }
+                                                                }
                                                             }
                                                             _ = flush_interval.tick() => {
+                                                                if !pending_unsubs.is_empty() {
+                                                                    let mut batch: Vec<String> = pending_unsubs.drain(..).collect();
+                                                                    batch.sort();
+                                                                    batch.dedup();
+                                                                    for chunk in batch.chunks(50) {
+                                                                        let unsub = Subscription {
+                                                                            assets_ids: chunk.to_vec(),
+                                                                            msg_type: "market".to_string(),
+                                                                            operation: Some("unsubscribe".to_string()),
+                                                                        };
+                                                                        let json = serde_json::to_string(&unsub).unwrap_or_default();
+                                                                        debug!("📤 Sending Unsub: {}", json);
+                                                                        if let Err(e) = write.send(Message::Text(json)).await {
+                                                                            error!("❌ Failed to send batch unsubscription: {}", e);
+                                                                        }
+                                                                    }
+                                                                }
+                                                                if !pending_resubs.is_empty() {
+                                                                    let mut batch: Vec<String> = pending_resubs.drain(..).collect();
+                                                                    batch.sort();
+                                                                    batch.dedup();
+                                                                    info!("🔄 Re-requesting books of {} assets", batch.len());
+                                                                    // Out and back in: the subscribe answers with a book snapshot
+                                                                    for chunk in batch.chunks(50) {
+                                                                        for operation in [Some("unsubscribe".to_string()), None] {
+                                                                            let msg = Subscription {
+                                                                                assets_ids: chunk.to_vec(),
+                                                                                msg_type: "market".to_string(),
+                                                                                operation,
+                                                                            };
+                                                                            let json = serde_json::to_string(&msg).unwrap_or_default();
+                                                                            debug!("📤 Sending Resub: {}", json);
+                                                                            if let Err(e) = write.send(Message::Text(json)).await {
+                                                                                error!("❌ Failed to send resubscription: {}", e);
+                                                                            }
+                                                                        }
+                                                                    }
+                                                                }
                                                                 if !pending_subs.is_empty() {
                                                                     let mut batch: Vec<String> = pending_subs.drain(..).collect();
                                                                     batch.sort();
@@ -215,6 +531,7 @@ impl ClobWebSocket {
                                                                         let sub = Subscription {
                                                                             assets_ids: chunk.to_vec(),
                                                                             msg_type: "market".to_string(),
+                                                                            operation: None,
                                                                         };
                                                                         let json = serde_json::to_string(&sub).unwrap_or_default();
                                                                         debug!("📤 Sending Sub: {}", json); // Downgrade to debug
@@ -232,18 +549,153 @@ impl ClobWebSocket {
                         error!("❌ Connection failed: {}", e);
                     }
                 }
+                meter.disconnected();
 
-                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                // A requested resync reconnects at once; failures back off
+                if !std::mem::take(&mut reconnect_now) {
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                }
                 info!("🔄 Reconnecting CLOB WebSocket...");
             }
         });
 
-        Ok(Self { subscribe_tx })
+        Ok(Self {
+            subscribe_tx,
+            bandwidth,
+            sequencer,
+            best_asks,
+            throttle,
+        })
+    }
+
+    /// Handle for forced resyncs / resubscriptions (control API)
+    pub fn control(&self) -> WsControl {
+        WsControl {
+            subscribe_tx: self.subscribe_tx.clone(),
+        }
+    }
+
+    /// Best ask of an asset as last seen on the socket
+    pub fn best_ask(&self, asset_id: &str) -> Option<f64> {
+        self.best_asks.get(asset_id).map(|ask| *ask)
+    }
+
+    /// Inbound bytes / messages per connection
+    pub fn bandwidth(&self) -> BandwidthStats {
+        self.bandwidth.stats()
+    }
+
+    /// Whether the connection is up and talking (see `QuoteFreshness`)
+    pub fn feed_health(&self) -> FeedHealth {
+        self.bandwidth.health()
+    }
+
+    pub fn bandwidth_meter(&self) -> Arc<BandwidthMeter> {
+        self.bandwidth.clone()
+    }
+
+    /// Stale / duplicated book updates dropped by the ordering checks
+    pub fn sequencing(&self) -> SequenceStats {
+        self.sequencer.stats()
+    }
+
+    pub fn sequencer(&self) -> Arc<BookSequencer> {
+        self.sequencer.clone()
+    }
+
+    /// Per-asset rate rules of the dispatch layer
+    pub fn throttle(&self) -> Arc<TickThrottle> {
+        self.throttle.clone()
+    }
+
+    /// Updates forwarded / sampled out by the tick throttle
+    pub fn throttling(&self) -> ThrottleStats {
+        self.throttle.stats()
     }
 
     pub fn subscribe(&self, asset_ids: Vec<String>) {
-        if let Err(e) = self.subscribe_tx.send(asset_ids) {
+        if let Err(e) = self
+            .subscribe_tx
+            .send(SubscriptionCommand::Subscribe(asset_ids))
+        {
             error!("❌ Failed to queue subscription: {}", e);
         }
     }
+
+    /// Unsubscribed assets also leave the sequencer
+    pub fn unsubscribe(&self, asset_ids: Vec<String>) {
+        for asset_id in &asset_ids {
+            self.sequencer.forget(asset_id);
+        }
+        if let Err(e) = self
+            .subscribe_tx
+            .send(SubscriptionCommand::Unsubscribe(asset_ids))
+        {
+            error!("❌ Failed to queue unsubscription: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOK: &str = r#"{"event_type":"book","asset_id":"a1","market":"m1",
+        "bids":[{"price":"0.40","size":"5"},{"price":"0.48","size":"30"},{"price":"0.45","size":"10"}],
+        "asks":[{"price":"0.60","size":"1"},{"price":"0.52","size":"25"},{"price":"0.55","size":"7"}],
+        "timestamp":"1","hash":"h1"}"#;
+
+    fn prices(levels: &[PriceLevel]) -> Vec<&str> {
+        levels.iter().map(|l| l.price.as_str()).collect()
+    }
+
+    #[test]
+    fn test_book_truncated_to_best_levels() {
+        let WsEvent::Updates(updates) = decode_message(BOOK, 2) else {
+            panic!("book not decoded");
+        };
+        assert_eq!(updates.len(), 1);
+        assert_eq!(prices(&updates[0].bids), vec!["0.48", "0.45"]);
+        assert_eq!(prices(&updates[0].asks), vec!["0.52", "0.55"]);
+        assert_eq!(updates[0].bids[0].size, "30");
+
+        // 0 = full book, still best first
+        let WsEvent::Updates(updates) = decode_message(BOOK, 0) else {
+            panic!("book not decoded");
+        };
+        assert_eq!(prices(&updates[0].bids), vec!["0.48", "0.45", "0.40"]);
+        assert_eq!(prices(&updates[0].asks), vec!["0.52", "0.55", "0.60"]);
+    }
+
+    #[test]
+    fn test_decode_message_shapes() {
+        let array = format!("[{}]", BOOK);
+        assert!(matches!(decode_message(&array, 10), WsEvent::Updates(u) if u.len() == 1));
+
+        let changes = r#"{"market":"m1","event_type":"price_change","timestamp":"2",
+            "price_changes":[{"asset_id":"a1","price":"0.5","size":"3","side":"BUY","hash":"h2",
+            "best_bid":"0.49","best_ask":"0.51"}]}"#;
+        let WsEvent::Updates(updates) = decode_message(changes, 10) else {
+            panic!("price changes not decoded");
+        };
+        assert_eq!(prices(&updates[0].asks), vec!["0.51"]);
+        assert_eq!(updates[0].timestamp, "2");
+        let change = updates[0].change.as_ref().unwrap();
+        assert_eq!((change.side.as_str(), change.level.size.as_str()), ("BUY", "3"));
+
+        let trade = r#"{"event_type":"last_trade_price","asset_id":"a1","price":"0.5"}"#;
+        assert!(matches!(decode_message(trade, 10), WsEvent::Ignored));
+        assert!(matches!(
+            decode_message(r#"{"foo":1}"#, 10),
+            WsEvent::Unknown { array: false }
+        ));
+        assert!(matches!(
+            decode_message("[1,2]", 10),
+            WsEvent::Unknown { array: true }
+        ));
+        assert!(matches!(
+            decode_message("INVALID OPERATION", 10),
+            WsEvent::NotJson(_)
+        ));
+    }
 }
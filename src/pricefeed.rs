@@ -1,12 +1,21 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use reqwest::Client;
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::config::PriceFeedConfig;
+
 const BINANCE_API_URL: &str = "https://api.binance.com/api/v3/ticker/price";
+const COINBASE_API_URL: &str = "https://api.coinbase.com/v2/prices";
 const CACHE_DURATION: Duration = Duration::from_millis(500);
+/// Consecutive agreeing prints away from the median taken as a real move rather than outliers
+const SUSPECTS_TO_CONFIRM: usize = 3;
 
 #[derive(Debug, Deserialize)]
 struct BinancePriceResponse {
@@ -15,6 +24,258 @@ struct BinancePriceResponse {
     price: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct CoinbasePriceResponse {
+    data: CoinbaseAmount,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinbaseAmount {
+    amount: String,
+}
+
+/// Why a print wasn't used
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedFault {
+    /// Too far from the rolling median
+    Outlier {
+        price: f64,
+        median: f64,
+        deviation_bps: f64,
+    },
+    /// The second source disagrees
+    Mismatch {
+        price: f64,
+        confirm: f64,
+        deviation_bps: f64,
+    },
+    /// Too many faults lately: strategies on this feed hold
+    Held { remaining_secs: u64 },
+}
+
+impl std::fmt::Display for FeedFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeedFault::Outlier {
+                price,
+                median,
+                deviation_bps,
+            } => write!(
+                f,
+                "outlier print {} ({:.0} bps off the median {})",
+                price, deviation_bps, median
+            ),
+            FeedFault::Mismatch {
+                price,
+                confirm,
+                deviation_bps,
+            } => write!(
+                f,
+                "print {} not confirmed by the second source ({}, {:.0} bps apart)",
+                price, confirm, deviation_bps
+            ),
+            FeedFault::Held { remaining_secs } => {
+                write!(f, "feed degraded, on hold for {}s more", remaining_secs)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FeedFault {}
+
+/// Health of one symbol's feed
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FeedStatus {
+    pub symbol: String,
+    pub last_price: Option<f64>,
+    /// Median of the window
+    pub median: Option<f64>,
+    pub samples: usize,
+    /// Faults within the window
+    pub recent_faults: usize,
+    /// Prints rejected since startup
+    pub rejected: u64,
+    /// Set while strategies on the feed hold
+    pub held_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+struct SymbolFeed {
+    samples: VecDeque<(Instant, f64)>,
+    /// Prints rejected as outliers in a row, kept to tell a real jump from bad prints
+    suspects: Vec<f64>,
+    faults: VecDeque<Instant>,
+    held_until: Option<Instant>,
+    rejected: u64,
+}
+
+fn median(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let mut values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+fn deviation_bps(price: f64, reference: f64) -> f64 {
+    if reference > 0.0 {
+        (price - reference).abs() / reference * 10_000.0
+    } else {
+        0.0
+    }
+}
+
+/// Rejects prints far from the rolling median or from a second source, and holds a symbol
+/// after repeated faults. A jump that several prints in a row agree on is a real move: the
+/// window restarts from it
+pub struct FeedFilter {
+    config: PriceFeedConfig,
+    feeds: HashMap<String, SymbolFeed>,
+}
+
+impl FeedFilter {
+    pub fn new(config: PriceFeedConfig) -> Self {
+        Self {
+            config,
+            feeds: HashMap::new(),
+        }
+    }
+
+    /// Check a print (and the second source's price, when there is one)
+    pub fn check(
+        &mut self,
+        symbol: &str,
+        price: f64,
+        confirm: Option<f64>,
+        now: Instant,
+    ) -> std::result::Result<f64, FeedFault> {
+        let window = Duration::from_secs(self.config.window_secs);
+        let feed = self.feeds.entry(symbol.to_string()).or_default();
+        while feed
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > window)
+        {
+            feed.samples.pop_front();
+        }
+        while feed
+            .faults
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > window)
+        {
+            feed.faults.pop_front();
+        }
+
+        let fault = match confirm {
+            Some(confirm)
+                if deviation_bps(price, confirm) > self.config.max_cross_deviation_bps =>
+            {
+                Some(FeedFault::Mismatch {
+                    price,
+                    confirm,
+                    deviation_bps: deviation_bps(price, confirm),
+                })
+            }
+            _ => Self::outlier(&self.config, feed, price),
+        };
+
+        if let Some(fault) = fault {
+            feed.rejected += 1;
+            feed.faults.push_back(now);
+            if self.config.hold_after_faults > 0
+                && feed.faults.len() >= self.config.hold_after_faults
+            {
+                if feed.held_until.map_or(true, |until| until <= now) {
+                    warn!(
+                        "🚧 {} price feed degraded ({} faults in {}s): strategies on it hold",
+                        symbol,
+                        feed.faults.len(),
+                        self.config.window_secs
+                    );
+                }
+                feed.held_until = Some(now + Duration::from_secs(self.config.hold_secs));
+            }
+            return Err(fault);
+        }
+
+        feed.samples.push_back((now, price));
+        match feed.held_until {
+            Some(until) if until > now => Err(FeedFault::Held {
+                remaining_secs: until.duration_since(now).as_secs(),
+            }),
+            Some(_) => {
+                feed.held_until = None;
+                info!("✅ {} price feed healthy again: hold lifted", symbol);
+                Ok(price)
+            }
+            None => Ok(price),
+        }
+    }
+
+    /// Outlier against the window's median, unless the window restarts at a confirmed jump
+    fn outlier(config: &PriceFeedConfig, feed: &mut SymbolFeed, price: f64) -> Option<FeedFault> {
+        if config.max_deviation_bps <= 0.0 || feed.samples.len() < config.min_samples.max(1) {
+            feed.suspects.clear();
+            return None;
+        }
+        let median = median(feed.samples.iter().map(|(_, p)| *p))?;
+        let deviation = deviation_bps(price, median);
+        if deviation <= config.max_deviation_bps {
+            feed.suspects.clear();
+            return None;
+        }
+
+        feed.suspects.push(price);
+        let agreed = median_of_suspects(&feed.suspects).is_some_and(|m| {
+            feed.suspects
+                .iter()
+                .all(|p| deviation_bps(*p, m) <= config.max_deviation_bps)
+        });
+        if feed.suspects.len() >= SUSPECTS_TO_CONFIRM && agreed {
+            debug!("Price feed moved to {} (median was {})", price, median);
+            feed.samples.clear();
+            feed.suspects.clear();
+            return None;
+        }
+        Some(FeedFault::Outlier {
+            price,
+            median,
+            deviation_bps: deviation,
+        })
+    }
+
+    pub fn status(&self, now: Instant) -> Vec<FeedStatus> {
+        let mut status: Vec<FeedStatus> = self
+            .feeds
+            .iter()
+            .map(|(symbol, feed)| FeedStatus {
+                symbol: symbol.clone(),
+                last_price: feed.samples.back().map(|(_, p)| *p),
+                median: median(feed.samples.iter().map(|(_, p)| *p)),
+                samples: feed.samples.len(),
+                recent_faults: feed.faults.len(),
+                rejected: feed.rejected,
+                held_until: feed.held_until.filter(|until| *until > now).map(|until| {
+                    Utc::now()
+                        + chrono::Duration::from_std(until.duration_since(now)).unwrap_or_default()
+                }),
+            })
+            .collect();
+        status.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        status
+    }
+}
+
+fn median_of_suspects(suspects: &[f64]) -> Option<f64> {
+    median(suspects.iter().copied())
+}
+
 #[derive(Clone)]
 struct CachedPrice {
     price: f64,
@@ -24,6 +285,9 @@ struct CachedPrice {
 pub struct BinanceClient {
     http_client: Client,
     cache: Arc<RwLock<HashMap<String, CachedPrice>>>,
+    // Outlier filter (None = prints used as they come, e.g. replays)
+    filter: Option<Mutex<FeedFilter>>,
+    confirm_source: Option<String>,
 }
 
 impl BinanceClient {
@@ -35,6 +299,30 @@ impl BinanceClient {
                 .build()
                 .unwrap_or_default(),
             cache: Arc::new(RwLock::new(HashMap::new())),
+            filter: None,
+            confirm_source: None,
+        }
+    }
+
+    /// Check every print with `FeedFilter` (and against a second source when configured)
+    pub fn with_filter(mut self, config: PriceFeedConfig) -> Self {
+        self.confirm_source = match config.confirm_source.as_deref() {
+            Some("coinbase") => Some("coinbase".to_string()),
+            Some(other) => {
+                warn!("⚠️ Unknown PRICE_FEED_CONFIRM_SOURCE {:?} ignored", other);
+                None
+            }
+            None => None,
+        };
+        self.filter = Some(Mutex::new(FeedFilter::new(config)));
+        self
+    }
+
+    /// Health of each symbol's feed (empty without a filter)
+    pub fn feed_status(&self) -> Vec<FeedStatus> {
+        match self.filter.as_ref().map(|f| f.lock()) {
+            Some(Ok(filter)) => filter.status(Instant::now()),
+            _ => Vec::new(),
         }
     }
 
@@ -51,18 +339,29 @@ impl BinanceClient {
             }
         }
 
-        // 2. Fetch from Binance
-        let url = format!("{}?symbol={}", BINANCE_API_URL, symbol);
-        let resp = self.http_client.get(url).send().await?;
+        // 2. Fetch from Binance (and the second source alongside)
+        let (price, confirm) = match &self.confirm_source {
+            Some(_) => {
+                let (price, confirm) =
+                    tokio::join!(self.fetch_binance(&symbol), self.fetch_coinbase(&symbol));
+                // An unreachable second source leaves Binance unconfirmed, not refused
+                let confirm = confirm
+                    .map_err(|e| debug!("Confirmation price for {} not fetched: {}", symbol, e))
+                    .ok();
+                (price?, confirm)
+            }
+            None => (self.fetch_binance(&symbol).await?, None),
+        };
 
-        if !resp.status().is_success() {
-            anyhow::bail!("Binance API returned status {}", resp.status());
+        // 3. Sanity checks
+        if let Some(Ok(mut filter)) = self.filter.as_ref().map(|f| f.lock()) {
+            if let Err(fault) = filter.check(&symbol, price, confirm, Instant::now()) {
+                debug!("{} print rejected: {}", symbol, fault);
+                return Err(fault.into());
+            }
         }
 
-        let data: BinancePriceResponse = resp.json().await?;
-        let price: f64 = data.price.parse()?;
-
-        // 3. Update cache
+        // 4. Update cache
         {
             let mut cache = self.cache.write().await;
             cache.insert(
@@ -77,68 +376,103 @@ impl BinanceClient {
         Ok(price)
     }
 
-    pub fn symbol_from_question(question: &str) -> Option<&'static str> {
-        let q = question.to_lowercase();
+    async fn fetch_binance(&self, symbol: &str) -> Result<f64> {
+        let url = format!("{}?symbol={}", BINANCE_API_URL, symbol);
+        let resp = self.http_client.get(url).send().await?;
 
-        // Priority 1: Exact matches or strong indicators
-        if q.contains("dogecoin") || q.contains("doge") {
-            return Some("DOGEUSDT");
-        }
-        if q.contains("shiba inu") || q.contains("shib") {
-            return Some("SHIBUSDT");
-        }
-        if q.contains("cardano") || q.contains("ada") {
-            return Some("ADAUSDT");
-        }
-        if q.contains("xrp") || q.contains("ripple") {
-            return Some("XRPUSDT");
-        }
-        if q.contains("solana") || q.contains("sol ") || q.contains("sol?") {
-            return Some("SOLUSDT");
-        } // Avoid "resolution" matching "sol"
-        if q.contains("bnb") || q.contains("binance coin") {
-            return Some("BNBUSDT");
+        if !resp.status().is_success() {
+            anyhow::bail!("Binance API returned status {}", resp.status());
         }
 
-        // Priority 2: Majors (ETH before BTC to catch "ETH flip BTC")
-        if q.contains("ethereum") || q.contains("eth") {
-            return Some("ETHUSDT");
-        }
-        if q.contains("bitcoin") || q.contains("btc") {
-            return Some("BTCUSDT");
-        }
+        let data: BinancePriceResponse = resp.json().await?;
+        Ok(data.price.parse()?)
+    }
 
-        // Priority 3: L1s / L2s
-        if q.contains("avalanche") || q.contains("avax") {
-            return Some("AVAXUSDT");
-        }
-        if q.contains("polygon") || q.contains("matic") {
-            return Some("MATICUSDT");
-        }
-        if q.contains("polkadot") || q.contains("dot") {
-            return Some("DOTUSDT");
-        }
-        if q.contains("tron") || q.contains("trx") {
-            return Some("TRXUSDT");
-        }
-        if q.contains("litecoin") || q.contains("ltc") {
-            return Some("LTCUSDT");
-        }
-        if q.contains("chainlink") || q.contains("link") {
-            return Some("LINKUSDT");
-        }
-        if q.contains("near protocol") || q.contains("near") {
-            return Some("NEARUSDT");
+    /// Coinbase spot of the same base asset in USD (`BTCUSDT` -> `BTC-USD`)
+    async fn fetch_coinbase(&self, symbol: &str) -> Result<f64> {
+        let base = ["USDT", "USDC", "BUSD", "USD"]
+            .iter()
+            .find_map(|quote| symbol.strip_suffix(quote))
+            .unwrap_or(symbol);
+        let url = format!("{}/{}-USD/spot", COINBASE_API_URL, base);
+        let resp = self.http_client.get(url).send().await?;
+
+        if !resp.status().is_success() {
+            anyhow::bail!("Coinbase API returned status {}", resp.status());
         }
 
-        // Priority 4: DeFi / Others
-        if q.contains("uniswap") || q.contains("uni") {
-            return Some("UNIUSDT");
+        let data: CoinbasePriceResponse = resp.json().await?;
+        Ok(data.data.amount.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PriceFeedConfig {
+        PriceFeedConfig {
+            max_deviation_bps: 200.0,
+            window_secs: 60,
+            min_samples: 3,
+            confirm_source: None,
+            max_cross_deviation_bps: 50.0,
+            hold_after_faults: 2,
+            hold_secs: 30,
         }
-        if q.contains("bitcoin cash") || q.contains("bch") {
-            return Some("BCHUSDT");
+    }
+
+    #[test]
+    fn test_outlier_rejected_and_real_move_accepted() {
+        let mut filter = FeedFilter::new(PriceFeedConfig {
+            hold_after_faults: 0,
+            ..config()
+        });
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        for (i, price) in [100.0, 100.1, 99.9].into_iter().enumerate() {
+            assert_eq!(
+                filter.check("BTCUSDT", price, None, at(i as u64)),
+                Ok(price)
+            );
         }
+        // One bad print
+        assert!(matches!(
+            filter.check("BTCUSDT", 90.0, None, at(3)),
+            Err(FeedFault::Outlier { .. })
+        ));
+        assert_eq!(filter.check("BTCUSDT", 100.0, None, at(4)), Ok(100.0));
+        // Three prints agreeing on a new level
+        assert!(filter.check("BTCUSDT", 95.0, None, at(5)).is_err());
+        assert!(filter.check("BTCUSDT", 95.1, None, at(6)).is_err());
+        assert_eq!(filter.check("BTCUSDT", 95.0, None, at(7)), Ok(95.0));
+    }
 
-        None
+    #[test]
+    fn test_mismatches_put_the_feed_on_hold() {
+        let mut filter = FeedFilter::new(config());
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        assert_eq!(
+            filter.check("ETHUSDT", 2000.0, Some(2001.0), at(0)),
+            Ok(2000.0)
+        );
+        assert!(matches!(
+            filter.check("ETHUSDT", 2000.0, Some(2100.0), at(1)),
+            Err(FeedFault::Mismatch { .. })
+        ));
+        assert!(filter
+            .check("ETHUSDT", 2000.0, Some(2100.0), at(2))
+            .is_err());
+        // Good prints again, but held until 30s after the last fault
+        assert!(matches!(
+            filter.check("ETHUSDT", 2000.0, Some(2000.0), at(10)),
+            Err(FeedFault::Held { .. })
+        ));
+        assert_eq!(
+            filter.check("ETHUSDT", 2000.0, Some(2000.0), at(40)),
+            Ok(2000.0)
+        );
+        assert_eq!(filter.status(at(40))[0].rejected, 2);
     }
 }
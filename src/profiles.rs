@@ -0,0 +1,430 @@
+//! Scheduled parameter profiles
+//!
+//! Liquidity and competition change with the time of day and the day of the week, so one set
+//! of limits rarely fits around the clock. Profiles listed in `PARAMETER_PROFILES_FILE` switch
+//! risk limits, entry sizes and strategies on a cron-like schedule (e.g. half size and no
+//! predictive entries overnight). A profile is active during every minute (UTC) its schedule
+//! matches; when several match, the first one in the file wins, and outside all of them the
+//! environment's settings apply.
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tracing::debug;
+use utoipa::ToSchema;
+
+use crate::config::RiskConfig;
+use crate::ids::UsdAmount;
+use crate::strategies::TradeAction;
+
+/// One profile of the profiles file:
+/// `[{"name": "overnight", "schedule": "* 0-6 * * *", "disabled_strategies": ["predictive"],
+/// "size_multiplier": 0.5, "risk": {"max_portfolio_exposure_pct": 0.2}}]`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ParameterProfile {
+    pub name: String,
+    /// Cron expression (minute hour day-of-month month day-of-week, UTC) of the minutes the
+    /// profile is active. Fields take `*`, numbers, ranges, lists and `/` steps
+    pub schedule: String,
+    /// Strategies that don't enter while the profile is active (`arbitrage`, `expiration`,
+    /// `predictive`, `dislocation` or a custom strategy's name)
+    #[serde(default)]
+    pub disabled_strategies: Vec<String>,
+    /// Entry sizes are scaled by this
+    #[serde(default)]
+    pub size_multiplier: Option<f64>,
+    /// Then capped at this (USD)
+    #[serde(default)]
+    pub max_size_usd: Option<f64>,
+    /// `RiskConfig` fields replaced while the profile is active, e.g. `max_position_size_pct`
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub risk: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A parsed cron expression. Each field is a bit set of the values it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day-of-month and day-of-week were both restricted: either one matching is enough
+    either_day: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!(
+                "'{}' has {} fields, expected 5 (minute hour day month weekday)",
+                expression,
+                fields.len()
+            );
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // Sunday is 0 or 7
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            either_day: day != "*" && weekday != "*",
+        })
+    }
+
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let has = |set: u64, value: u32| set & (1 << value) != 0;
+        let day = has(self.days, at.day());
+        let weekday = has(self.weekdays, at.weekday().num_days_from_sunday());
+        let day_matches = if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        };
+        has(self.minutes, at.minute())
+            && has(self.hours, at.hour())
+            && has(self.months, at.month())
+            && day_matches
+    }
+}
+
+/// Values one comma-separated cron field matches, as a bit set
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| anyhow!("invalid step in '{}'", part))?,
+            ),
+            None => (part, 1),
+        };
+        let (from, to) = if range == "*" {
+            (min, max)
+        } else if let Some((from, to)) = range.split_once('-') {
+            (parse_value(from, part)?, parse_value(to, part)?)
+        } else {
+            let value = parse_value(range, part)?;
+            // `5/15` runs from 5 to the end of the range
+            (value, if part.contains('/') { max } else { value })
+        };
+        if from < min || to > max || from > to {
+            bail!("'{}' is outside {}-{}", part, min, max);
+        }
+        for value in (from..=to).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn parse_value(value: &str, part: &str) -> Result<u32> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("invalid value in '{}'", part))
+}
+
+/// `base` with the fields of `overrides` replaced. Map fields (per-category caps, strategy
+/// capital) are merged entry by entry, so the base entries a profile doesn't name carry over
+fn overlay(
+    base: &RiskConfig,
+    overrides: &serde_json::Map<String, serde_json::Value>,
+) -> Result<RiskConfig> {
+    let mut value = serde_json::to_value(base)?;
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Risk config is not an object"))?;
+    for (key, field) in overrides {
+        let Some(current) = obj.get_mut(key) else {
+            bail!("unknown risk field '{}'", key);
+        };
+        match (current, field) {
+            (serde_json::Value::Object(entries), serde_json::Value::Object(changes)) => {
+                entries.extend(changes.clone());
+            }
+            (current, field) => *current = field.clone(),
+        }
+    }
+    serde_json::from_value(value).context("risk overrides produced an invalid config")
+}
+
+/// What `GET /api/profiles` shows
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProfileStatus {
+    /// None while the environment's settings apply
+    pub active: Option<String>,
+    pub active_since: Option<DateTime<Utc>>,
+    /// Risk limits in force
+    #[schema(value_type = Object)]
+    pub risk: serde_json::Value,
+    pub profiles: Vec<ParameterProfile>,
+}
+
+/// The configured profiles and the one in force
+pub struct ProfileSchedule {
+    base_risk: RiskConfig,
+    profiles: Vec<ParameterProfile>,
+    schedules: Vec<CronSchedule>,
+    /// Risk config of each profile (its overrides over the base)
+    risk: Vec<RiskConfig>,
+    active: Option<usize>,
+    active_since: Option<DateTime<Utc>>,
+}
+
+impl ProfileSchedule {
+    /// Validate every profile up front: a bad schedule, size or risk field fails here, not at
+    /// night
+    pub fn new(profiles: Vec<ParameterProfile>, base_risk: RiskConfig) -> Result<Self> {
+        let mut schedules = Vec::new();
+        let mut risk = Vec::new();
+        for profile in &profiles {
+            for (field, value) in [
+                ("size_multiplier", profile.size_multiplier),
+                ("max_size_usd", profile.max_size_usd),
+            ] {
+                if let Some(value) = value.filter(|v| !v.is_finite() || *v <= 0.0) {
+                    bail!(
+                        "Profile '{}' {} must be a positive number, got {}",
+                        profile.name,
+                        field,
+                        value
+                    );
+                }
+            }
+            schedules.push(
+                CronSchedule::parse(&profile.schedule)
+                    .with_context(|| format!("Profile '{}' schedule", profile.name))?,
+            );
+            risk.push(
+                overlay(&base_risk, &profile.risk)
+                    .with_context(|| format!("Profile '{}' risk", profile.name))?,
+            );
+        }
+        Ok(Self {
+            base_risk,
+            profiles,
+            schedules,
+            risk,
+            active: None,
+            active_since: None,
+        })
+    }
+
+    pub fn load(path: &str, base_risk: RiskConfig) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read parameter profiles {}", path))?;
+        let profiles = serde_json::from_str(&raw)
+            .with_context(|| format!("Invalid parameter profiles file {}", path))?;
+        Self::new(profiles, base_risk)
+    }
+
+    /// Switch to the profile scheduled at `now`. Returns true when that changed the one in
+    /// force
+    pub fn update(&mut self, now: DateTime<Utc>) -> bool {
+        let due = self.schedules.iter().position(|s| s.matches(now));
+        if due == self.active {
+            return false;
+        }
+        self.active = due;
+        self.active_since = Some(now);
+        true
+    }
+
+    pub fn active(&self) -> Option<&ParameterProfile> {
+        self.active.map(|i| &self.profiles[i])
+    }
+
+    /// Risk limits in force
+    pub fn risk_config(&self) -> &RiskConfig {
+        self.active.map_or(&self.base_risk, |i| &self.risk[i])
+    }
+
+    /// `action` of `strategy` as the active profile lets it through: dropped for disabled
+    /// strategies, otherwise scaled and capped in size
+    pub fn gate(&self, strategy: &str, action: TradeAction) -> TradeAction {
+        let Some(profile) = self.active() else {
+            return action;
+        };
+        if matches!(action, TradeAction::None) {
+            return action;
+        }
+        let strategy = strategy.to_lowercase();
+        if profile
+            .disabled_strategies
+            .iter()
+            .any(|s| s.to_lowercase() == strategy)
+        {
+            debug!("⏭️ {} disabled by profile {}", strategy, profile.name);
+            return TradeAction::None;
+        }
+        let size = |size_usd: UsdAmount| {
+            let scaled = size_usd.get() * profile.size_multiplier.unwrap_or(1.0);
+            UsdAmount::new(profile.max_size_usd.map_or(scaled, |max| scaled.min(max)))
+        };
+        match action {
+            TradeAction::Snipe {
+                market_id,
+                side,
+                price,
+                size_usd,
+            } => TradeAction::Snipe {
+                market_id,
+                side,
+                price,
+                size_usd: size(size_usd),
+            },
+            TradeAction::BuyBoth {
+                market_id,
+                yes_price,
+                no_price,
+                size_usd,
+                expected_profit_bps,
+            } => TradeAction::BuyBoth {
+                market_id,
+                yes_price,
+                no_price,
+                size_usd: size(size_usd),
+                expected_profit_bps,
+            },
+            TradeAction::None => TradeAction::None,
+        }
+    }
+
+    pub fn status(&self) -> ProfileStatus {
+        ProfileStatus {
+            active: self.active().map(|p| p.name.clone()),
+            active_since: self.active_since,
+            risk: serde_json::to_value(self.risk_config()).unwrap_or_default(),
+            profiles: self.profiles.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::Price;
+    use crate::markets::MarketCategory;
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
+
+    fn risk() -> RiskConfig {
+        RiskConfig {
+            max_position_size_pct: 5.0,
+            max_portfolio_exposure_pct: 0.5,
+            stop_loss_pct: 0.1,
+            use_dynamic_sl: false,
+            min_hold_time_secs: 60,
+            auto_sell_threshold: 0.99,
+            capital_usd: 1000.0,
+            category_exposure_limits: BTreeMap::new(),
+            strategy_capital_pct: BTreeMap::new(),
+        }
+    }
+
+    fn snipe(size_usd: f64) -> TradeAction {
+        TradeAction::Snipe {
+            market_id: "m1".into(),
+            side: "YES".to_string(),
+            price: Price::new(0.95),
+            size_usd: UsdAmount::new(size_usd),
+        }
+    }
+
+    #[test]
+    fn test_profile_keeps_the_caps_it_does_not_name() {
+        let mut base = risk();
+        base.category_exposure_limits
+            .insert(MarketCategory::Sports, 200.0);
+        base.category_exposure_limits
+            .insert(MarketCategory::Politics, 500.0);
+        let profiles: Vec<ParameterProfile> = serde_json::from_str(
+            r#"[{"name": "overnight", "schedule": "* * * * *",
+                 "risk": {"category_exposure_limits": {"Sports": 50}}}]"#,
+        )
+        .unwrap();
+        let mut schedule = ProfileSchedule::new(profiles, base).unwrap();
+        assert!(schedule.update(Utc::now()));
+
+        let caps = &schedule.risk_config().category_exposure_limits;
+        assert_eq!(caps.get(&MarketCategory::Sports), Some(&50.0));
+        assert_eq!(caps.get(&MarketCategory::Politics), Some(&500.0));
+    }
+
+    #[test]
+    fn test_profiles_follow_their_schedule() {
+        let weekdays = CronSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        // Friday 2026-03-20
+        let friday = Utc.with_ymd_and_hms(2026, 3, 20, 9, 30, 0).unwrap();
+        assert!(weekdays.matches(friday));
+        assert!(!weekdays.matches(friday + chrono::Duration::minutes(1)));
+        assert!(!weekdays.matches(friday + chrono::Duration::days(1)));
+        assert!(CronSchedule::parse("* * * * 7")
+            .unwrap()
+            .matches(friday + chrono::Duration::days(2)));
+        assert!(CronSchedule::parse("* 24 * * *").is_err());
+        assert!(CronSchedule::parse("* * *").is_err());
+
+        let profiles: Vec<ParameterProfile> = serde_json::from_str(
+            r#"[
+                {"name": "overnight", "schedule": "* 0-6 * * *",
+                 "disabled_strategies": ["predictive"], "size_multiplier": 0.5,
+                 "max_size_usd": 20, "risk": {"max_portfolio_exposure_pct": 0.2}},
+                {"name": "weekend", "schedule": "* * * * 0,6"}
+            ]"#,
+        )
+        .unwrap();
+        let mut schedule = ProfileSchedule::new(profiles.clone(), risk()).unwrap();
+        assert!(!schedule.update(friday));
+        assert!(schedule.active().is_none());
+
+        // Saturday 03:00: both match, the first listed wins
+        let night = Utc.with_ymd_and_hms(2026, 3, 21, 3, 0, 0).unwrap();
+        assert!(schedule.update(night));
+        assert_eq!(schedule.active().unwrap().name, "overnight");
+        assert_eq!(schedule.risk_config().max_portfolio_exposure_pct, 0.2);
+        assert_eq!(schedule.risk_config().max_position_size_pct, 5.0);
+        assert!(matches!(
+            schedule.gate("predictive", snipe(30.0)),
+            TradeAction::None
+        ));
+        match schedule.gate("expiration", snipe(30.0)) {
+            TradeAction::Snipe { size_usd, .. } => assert_eq!(size_usd.get(), 15.0),
+            other => panic!("unexpected {:?}", other),
+        }
+        match schedule.gate("expiration", snipe(100.0)) {
+            TradeAction::Snipe { size_usd, .. } => assert_eq!(size_usd.get(), 20.0),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(schedule.update(night + chrono::Duration::hours(5)));
+        assert_eq!(schedule.status().active.as_deref(), Some("weekend"));
+        assert!(matches!(
+            schedule.gate("predictive", snipe(30.0)),
+            TradeAction::Snipe { .. }
+        ));
+
+        // Bad risk fields and sizes are refused up front
+        let mut bad = profiles.clone();
+        bad[1].risk.insert("max_leverage".to_string(), 3.into());
+        assert!(ProfileSchedule::new(bad, risk()).is_err());
+        for multiplier in [0.0, -0.5, f64::NAN, f64::INFINITY] {
+            let mut bad = profiles.clone();
+            bad[1].size_multiplier = Some(multiplier);
+            assert!(ProfileSchedule::new(bad, risk()).is_err());
+        }
+        let mut bad = profiles;
+        bad[0].max_size_usd = Some(-20.0);
+        assert!(ProfileSchedule::new(bad, risk()).is_err());
+    }
+}
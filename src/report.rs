@@ -0,0 +1,567 @@
+//! Daily report
+//!
+//! Once a day at `DAILY_REPORT_HOUR_UTC` the agent sums up the period since its previous report
+//! (at most 24h): realized / unrealized PnL, closed trades with the biggest win and loss, errors
+//! and warnings logged, uptime and the balance change. The report goes to
+//! `DAILY_REPORT_WEBHOOK_URL` (Slack / Discord compatible JSON) and / or by email over SMTP, so
+//! operators get a pulse without opening the dashboard. Delivered reports are appended to
+//! `data_dir/daily_reports.jsonl`; a report missed while the agent was down goes out on start,
+//! and one that failed to go out is retried (where it failed) every `DELIVERY_RETRY_SECS`.
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Duration, Utc};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::{error, info, warn, Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::analytics::PnLTracker;
+use crate::config::DailyReportConfig;
+
+pub const DAILY_REPORTS_FILE: &str = "daily_reports.jsonl";
+
+/// Wait before sending a report again where it failed to go out
+const DELIVERY_RETRY_SECS: i64 = 900;
+
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+static WARNINGS: AtomicU64 = AtomicU64::new(0);
+
+/// ERROR events kept for the exit report
+const RECENT_ERRORS: usize = 20;
+static RECENT: Mutex<VecDeque<LoggedError>> = Mutex::new(VecDeque::new());
+
+/// An ERROR event as logged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedError {
+    pub at: DateTime<Utc>,
+    pub target: String,
+    pub message: String,
+}
+
+/// The `message` field of an event
+struct MessageField(String);
+
+impl Visit for MessageField {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+/// Counts ERROR and WARN events for the daily report and keeps the last errors for the exit
+/// report (installed next to the fmt layer)
+pub struct LogCounter;
+
+impl<S: Subscriber> Layer<S> for LogCounter {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        match *event.metadata().level() {
+            Level::ERROR => {
+                ERRORS.fetch_add(1, Ordering::Relaxed);
+                let mut message = MessageField(String::new());
+                event.record(&mut message);
+                if let Ok(mut recent) = RECENT.lock() {
+                    if recent.len() == RECENT_ERRORS {
+                        recent.pop_front();
+                    }
+                    recent.push_back(LoggedError {
+                        at: Utc::now(),
+                        target: event.metadata().target().to_string(),
+                        message: message.0,
+                    });
+                }
+            }
+            Level::WARN => {
+                WARNINGS.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The last ERROR events logged, oldest first
+pub fn recent_errors() -> Vec<LoggedError> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// (errors, warnings) logged since start
+pub fn log_counts() -> (u64, u64) {
+    (
+        ERRORS.load(Ordering::Relaxed),
+        WARNINGS.load(Ordering::Relaxed),
+    )
+}
+
+/// A closed trade as shown in the report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeLine {
+    pub market_question: String,
+    pub side: String,
+    pub realized_pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyReport {
+    pub generated_at: DateTime<Utc>,
+    /// Start of the reported period (the previous report, at most 24h back)
+    pub since: DateTime<Utc>,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub biggest_win: Option<TradeLine>,
+    pub biggest_loss: Option<TradeLine>,
+    pub open_positions: usize,
+    /// Logged over the period (since start when the agent restarted in between)
+    pub errors: u64,
+    pub warnings: u64,
+    pub uptime_secs: i64,
+    /// Wallet balance, None when it couldn't be fetched
+    pub balance: Option<f64>,
+    /// Since the previous report
+    pub balance_change: Option<f64>,
+}
+
+impl DailyReport {
+    /// Trades of `tracker` closed since `since`; `log_counts` are the errors / warnings of the
+    /// period
+    pub fn compile(
+        tracker: &PnLTracker,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+        started_at: DateTime<Utc>,
+        log_counts: (u64, u64),
+        balance: Option<f64>,
+        previous_balance: Option<f64>,
+    ) -> Self {
+        let closed: Vec<TradeLine> = tracker
+            .trades
+            .iter()
+            .filter(|t| t.exit_time.is_some_and(|at| at > since && at <= now))
+            .filter_map(|t| {
+                Some(TradeLine {
+                    market_question: t.market_question.clone(),
+                    side: t.side.clone(),
+                    realized_pnl: t.realized_pnl?,
+                })
+            })
+            .collect();
+        let best = closed
+            .iter()
+            .max_by(|a, b| a.realized_pnl.total_cmp(&b.realized_pnl));
+        let worst = closed
+            .iter()
+            .min_by(|a, b| a.realized_pnl.total_cmp(&b.realized_pnl));
+
+        Self {
+            generated_at: now,
+            since,
+            realized_pnl: closed.iter().map(|t| t.realized_pnl).sum(),
+            unrealized_pnl: tracker.calculate_unrealized_pnl(),
+            trades: closed.len(),
+            wins: closed.iter().filter(|t| t.realized_pnl > 0.0).count(),
+            losses: closed.iter().filter(|t| t.realized_pnl < 0.0).count(),
+            biggest_win: best.filter(|t| t.realized_pnl > 0.0).cloned(),
+            biggest_loss: worst.filter(|t| t.realized_pnl < 0.0).cloned(),
+            open_positions: tracker.positions.len(),
+            errors: log_counts.0,
+            warnings: log_counts.1,
+            uptime_secs: (now - started_at).num_seconds().max(0),
+            balance,
+            balance_change: balance
+                .zip(previous_balance)
+                .map(|(now, before)| now - before),
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        format!(
+            "Polymarket agent daily report {}: {:+.2} USD",
+            self.generated_at.format("%Y-%m-%d"),
+            self.realized_pnl
+        )
+    }
+
+    /// Plain text body of the webhook message and email
+    pub fn render(&self) -> String {
+        let trade = |t: &TradeLine| {
+            format!(
+                "{:+.2} USD {} {}",
+                t.realized_pnl, t.side, t.market_question
+            )
+        };
+        let mut lines = vec![
+            format!(
+                "📊 Daily report {} ({:.1}h)",
+                self.generated_at.format("%Y-%m-%d %H:%M UTC"),
+                (self.generated_at - self.since).num_minutes() as f64 / 60.0
+            ),
+            format!(
+                "PnL: realized {:+.2} USD | unrealized {:+.2} USD",
+                self.realized_pnl, self.unrealized_pnl
+            ),
+            format!(
+                "Trades: {} ({} won / {} lost) | open positions: {}",
+                self.trades, self.wins, self.losses, self.open_positions
+            ),
+        ];
+        if let Some(t) = &self.biggest_win {
+            lines.push(format!("Biggest win: {}", trade(t)));
+        }
+        if let Some(t) = &self.biggest_loss {
+            lines.push(format!("Biggest loss: {}", trade(t)));
+        }
+        lines.push(match (self.balance, self.balance_change) {
+            (Some(balance), Some(change)) => {
+                format!("Balance: {:.2} USD ({:+.2})", balance, change)
+            }
+            (Some(balance), None) => format!("Balance: {:.2} USD", balance),
+            (None, _) => "Balance: unavailable".to_string(),
+        });
+        lines.push(format!(
+            "Errors: {} | warnings: {}",
+            self.errors, self.warnings
+        ));
+        lines.push(format!(
+            "Uptime: {}d {}h {}m",
+            self.uptime_secs / 86_400,
+            self.uptime_secs % 86_400 / 3_600,
+            self.uptime_secs % 3_600 / 60
+        ));
+        lines.join("\n")
+    }
+}
+
+/// A compiled report and where it still has to go
+struct Delivery {
+    report: DailyReport,
+    /// Log counts it was compiled at
+    counts: (u64, u64),
+    webhook: bool,
+    email: bool,
+}
+
+/// Schedules, compiles and sends the daily report
+pub struct DailyReporter {
+    config: DailyReportConfig,
+    http: reqwest::Client,
+    path: PathBuf,
+    /// Last report delivered
+    previous: Option<DailyReport>,
+    next_due: DateTime<Utc>,
+    /// Log counts at the previous report of this run
+    counted: (u64, u64),
+    /// Compiled, but not delivered everywhere yet
+    pending: Option<Delivery>,
+    delivered_tx: mpsc::UnboundedSender<Delivery>,
+    delivered_rx: mpsc::UnboundedReceiver<Delivery>,
+}
+
+impl DailyReporter {
+    pub fn new(config: DailyReportConfig, data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(DAILY_REPORTS_FILE);
+        let previous = fs::read_to_string(&path).ok().and_then(|raw| {
+            raw.lines()
+                .rev()
+                .find_map(|line| serde_json::from_str::<DailyReport>(line).ok())
+        });
+        // Overdue right away when the last scheduled report was missed
+        let next_due = next_occurrence(
+            previous.as_ref().map_or_else(Utc::now, |r| r.generated_at),
+            config.hour_utc,
+        );
+        let (delivered_tx, delivered_rx) = mpsc::unbounded_channel();
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            config,
+            path,
+            previous,
+            next_due,
+            counted: (0, 0),
+            pending: None,
+            delivered_tx,
+            delivered_rx,
+        }
+    }
+
+    /// A webhook or an SMTP server with recipients is configured
+    pub fn enabled(&self) -> bool {
+        self.config.webhook_url.is_some()
+            || (self.config.smtp_host.is_some() && !self.config.email_to.is_empty())
+    }
+
+    pub fn due(&self, now: DateTime<Utc>) -> bool {
+        now >= self.next_due
+    }
+
+    /// A report failed to go out somewhere and is waiting to be sent again
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Report on the period since the previous report (at most 24h), to `send`
+    pub fn compile(
+        &mut self,
+        tracker: &PnLTracker,
+        started_at: DateTime<Utc>,
+        balance: Option<f64>,
+        now: DateTime<Utc>,
+    ) -> DailyReport {
+        let day_ago = now - Duration::hours(24);
+        let since = self
+            .previous
+            .as_ref()
+            .map_or(day_ago, |r| r.generated_at.max(day_ago));
+        let counts = log_counts();
+        let report = DailyReport::compile(
+            tracker,
+            since,
+            now,
+            started_at,
+            (counts.0 - self.counted.0, counts.1 - self.counted.1),
+            balance,
+            self.previous.as_ref().and_then(|r| r.balance),
+        );
+        self.pending = Some(Delivery {
+            report: report.clone(),
+            counts,
+            webhook: self.config.webhook_url.is_some(),
+            email: self.config.smtp_host.is_some() && !self.config.email_to.is_empty(),
+        });
+        report
+    }
+
+    /// Deliver the pending report in the background (webhook and / or email, where it hasn't
+    /// gone yet). Until `collect_deliveries` sees it delivered, it is sent again every
+    /// `DELIVERY_RETRY_SECS`
+    pub fn send(&mut self, now: DateTime<Utc>) {
+        let Some(mut delivery) = self.pending.take() else {
+            return;
+        };
+        self.next_due = now + Duration::seconds(DELIVERY_RETRY_SECS);
+        let config = self.config.clone();
+        let http = self.http.clone();
+        let delivered = self.delivered_tx.clone();
+        tokio::spawn(async move {
+            if let (true, Some(url)) = (delivery.webhook, &config.webhook_url) {
+                match post_webhook(&http, url, &delivery.report).await {
+                    Ok(()) => {
+                        info!("📨 Daily report posted to the webhook");
+                        delivery.webhook = false;
+                    }
+                    Err(e) => error!("❌ Daily report webhook failed: {:#}", e),
+                }
+            }
+            if delivery.email {
+                match send_email(&config, &delivery.report).await {
+                    Ok(()) => {
+                        info!("📨 Daily report emailed to {}", config.email_to.join(", "));
+                        delivery.email = false;
+                    }
+                    Err(e) => error!("❌ Daily report email failed: {:#}", e),
+                }
+            }
+            let _ = delivered.send(delivery);
+        });
+    }
+
+    /// Take in the outcome of the sends: a report delivered everywhere becomes the previous
+    /// one and the next is scheduled; one that failed somewhere waits for its retry
+    pub fn collect_deliveries(&mut self) {
+        while let Ok(delivery) = self.delivered_rx.try_recv() {
+            if delivery.webhook || delivery.email {
+                self.pending = Some(delivery);
+                continue;
+            }
+            if let Err(e) = self.append(&delivery.report) {
+                warn!("⚠️ Daily report not recorded: {:#}", e);
+            }
+            self.counted = delivery.counts;
+            self.next_due = next_occurrence(delivery.report.generated_at, self.config.hour_utc);
+            self.previous = Some(delivery.report);
+        }
+    }
+
+    fn append(&self, report: &DailyReport) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {:?}", self.path))?;
+        serde_json::to_writer(&mut file, report)?;
+        file.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// First `hour`:00 UTC strictly after `after`
+fn next_occurrence(after: DateTime<Utc>, hour: u32) -> DateTime<Utc> {
+    let today = after
+        .date_naive()
+        .and_hms_opt(hour.min(23), 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    if today > after {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+/// `text` for Slack, `content` for Discord, the report itself for everything else
+async fn post_webhook(http: &reqwest::Client, url: &str, report: &DailyReport) -> Result<()> {
+    let body = report.render();
+    let response = http
+        .post(url)
+        .json(&serde_json::json!({
+            "text": body,
+            "content": body,
+            "report": report,
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!("Webhook returned {}", response.status());
+    }
+    Ok(())
+}
+
+async fn send_email(config: &DailyReportConfig, report: &DailyReport) -> Result<()> {
+    let host = config.smtp_host.as_deref().unwrap_or_default();
+    let from: Mailbox = config
+        .email_from
+        .as_deref()
+        .or(config.smtp_username.as_deref())
+        .context("Set DAILY_REPORT_EMAIL_FROM (or SMTP_USERNAME)")?
+        .parse()?;
+    let mut message = Message::builder().from(from).subject(report.subject());
+    for to in &config.email_to {
+        message = message.to(to.parse()?);
+    }
+    let email = message.body(report.render())?;
+
+    let mut transport =
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?.port(config.smtp_port);
+    if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    transport.build().send(email).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::pnl::Trade;
+
+    fn trade(id: &str, pnl: f64, exit_time: DateTime<Utc>) -> Trade {
+        Trade {
+            id: id.to_string(),
+            market_id: format!("m-{}", id),
+            market_question: format!("Market {}?", id),
+            side: "YES".to_string(),
+            size: 10.0,
+            entry_price: 0.5,
+            exit_price: Some(0.6),
+            entry_time: exit_time - Duration::hours(1),
+            exit_time: Some(exit_time),
+            realized_pnl: Some(pnl),
+            config_hash: None,
+            note: None,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_daily_report_covers_the_period() {
+        let now = "2026-10-15T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let since = now - Duration::hours(24);
+        let mut tracker = PnLTracker::new(1000.0);
+        tracker.trades = vec![
+            trade("old", 50.0, since - Duration::minutes(1)),
+            trade("a", 12.0, now - Duration::hours(3)),
+            trade("b", -4.5, now - Duration::hours(2)),
+            trade("c", 1.5, now - Duration::hours(1)),
+        ];
+
+        let report = DailyReport::compile(
+            &tracker,
+            since,
+            now,
+            now - Duration::hours(50),
+            (2, 7),
+            Some(1009.0),
+            Some(1000.0),
+        );
+        assert_eq!(report.trades, 3);
+        assert_eq!((report.wins, report.losses), (2, 1));
+        assert!((report.realized_pnl - 9.0).abs() < 1e-9);
+        assert_eq!(report.biggest_win.as_ref().unwrap().realized_pnl, 12.0);
+        assert_eq!(report.biggest_loss.as_ref().unwrap().realized_pnl, -4.5);
+        assert_eq!(report.balance_change, Some(9.0));
+        assert_eq!(report.uptime_secs, 50 * 3600);
+
+        let body = report.render();
+        assert!(body.contains("Biggest loss: -4.50 USD YES Market b?"));
+        assert!(body.contains("Errors: 2 | warnings: 7"));
+        assert!(body.contains("Uptime: 2d 2h 0m"));
+
+        // Scheduled at the configured hour, the next day once it passed
+        assert_eq!(next_occurrence(now, 9), now + Duration::hours(1));
+        assert_eq!(next_occurrence(now, 8), now + Duration::days(1));
+    }
+
+    #[test]
+    fn test_report_counts_as_sent_once_delivered_everywhere() {
+        let dir = std::env::temp_dir().join(format!("report-{}", uuid::Uuid::new_v4()));
+        let config = DailyReportConfig {
+            webhook_url: Some("http://127.0.0.1:9/hook".to_string()),
+            ..DailyReportConfig::default()
+        };
+        let mut reporter = DailyReporter::new(config, dir.to_str().unwrap());
+        let now = Utc::now();
+        let report = reporter.compile(&PnLTracker::new(1000.0), now, None, now);
+        assert!(reporter.has_pending());
+
+        // The webhook failed: nothing recorded, sent again later
+        let failed = reporter.pending.take().unwrap();
+        reporter.delivered_tx.send(failed).unwrap();
+        reporter.collect_deliveries();
+        assert!(reporter.has_pending());
+        assert!(reporter.previous.is_none());
+        assert!(!dir.join(DAILY_REPORTS_FILE).exists());
+
+        let mut delivered = reporter.pending.take().unwrap();
+        delivered.webhook = false;
+        reporter.delivered_tx.send(delivered).unwrap();
+        reporter.collect_deliveries();
+        assert!(!reporter.has_pending());
+        assert_eq!(
+            reporter.previous.as_ref().map(|r| r.generated_at),
+            Some(report.generated_at)
+        );
+        assert!(!reporter.due(now));
+        assert!(dir.join(DAILY_REPORTS_FILE).exists());
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,438 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::markets::ConditionId;
+use crate::polymarket::MarketData;
+
+/// Work priority, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Priority {
+    /// Re-evaluating strategies on a fresh book
+    Routine = 0,
+    /// Stop-loss / take-profit checks on markets we hold
+    Protection = 1,
+    /// Time-critical new-market snipes
+    NewMarket = 2,
+}
+
+/// A unit of work for the Sniper's decision loop
+#[derive(Debug, Clone)]
+pub enum Job {
+    /// On-chain condition detected, metadata may not be indexed yet
+    NewCondition(ConditionId),
+    /// New market with metadata available
+    NewMarket(MarketData),
+    /// Run position protection for a market on its latest state
+    ProtectPosition(String),
+    /// Run strategies for a market on its latest state. Carries the socket read time (monotonic
+    /// ns, see `clock`) of the book update that triggered it, None for timer re-evaluations; a
+    /// deduped job keeps the oldest, so latencies count the full wait
+    Reevaluate(String, Option<u64>),
+}
+
+impl Job {
+    pub fn priority(&self) -> Priority {
+        match self {
+            Job::NewCondition(_) | Job::NewMarket(_) => Priority::NewMarket,
+            Job::ProtectPosition(_) => Priority::Protection,
+            Job::Reevaluate(..) => Priority::Routine,
+        }
+    }
+
+    /// Market-state jobs read the latest state when they run, so one pending job per market is enough
+    fn dedupe_key(&self) -> Option<(Priority, String)> {
+        match self {
+            Job::ProtectPosition(id) | Job::Reevaluate(id, _) => {
+                Some((self.priority(), id.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+struct Entry {
+    priority: Priority,
+    seq: u64,
+    job: Job,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    // Max-heap: higher priority first, then FIFO (lower seq first) within a priority
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobQueueStats {
+    pub pending: usize,
+    pub enqueued: u64,
+    pub deduped: u64,
+    pub dispatched_new_market: u64,
+    pub dispatched_protection: u64,
+    pub dispatched_routine: u64,
+}
+
+/// Priority queue of pending Sniper work
+pub struct JobQueue {
+    heap: BinaryHeap<Entry>,
+    pending_keys: HashSet<(Priority, String)>,
+    next_seq: u64,
+    stats: JobQueueStats,
+}
+
+impl JobQueue {
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            pending_keys: HashSet::new(),
+            next_seq: 0,
+            stats: JobQueueStats::default(),
+        }
+    }
+
+    /// Queue a job. Returns false if an equivalent job was already pending
+    pub fn push(&mut self, job: Job) -> bool {
+        if let Some(key) = job.dedupe_key() {
+            if !self.pending_keys.insert(key) {
+                self.stats.deduped += 1;
+                return false;
+            }
+        }
+
+        self.stats.enqueued += 1;
+        self.heap.push(Entry {
+            priority: job.priority(),
+            seq: self.next_seq,
+            job,
+        });
+        self.next_seq += 1;
+        true
+    }
+
+    /// Highest-priority job
+    pub fn pop(&mut self) -> Option<Job> {
+        let entry = self.heap.pop()?;
+        if let Some(key) = entry.job.dedupe_key() {
+            self.pending_keys.remove(&key);
+        }
+        match entry.priority {
+            Priority::NewMarket => self.stats.dispatched_new_market += 1,
+            Priority::Protection => self.stats.dispatched_protection += 1,
+            Priority::Routine => self.stats.dispatched_routine += 1,
+        }
+        Some(entry.job)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn stats(&self) -> JobQueueStats {
+        JobQueueStats {
+            pending: self.heap.len(),
+            ..self.stats.clone()
+        }
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs and timing of one recurring task
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TaskStats {
+    pub name: String,
+    pub interval_ms: u64,
+    /// Up to this much is added to every interval
+    pub jitter_ms: u64,
+    pub runs: u64,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: f64,
+    pub avg_duration_ms: f64,
+    pub max_duration_ms: f64,
+    /// Runs that ended after the next one was due (the ticks missed are skipped, not caught up)
+    pub overruns: u64,
+    /// The last run overran
+    pub overrunning: bool,
+}
+
+/// A task starting or ceasing to overrun its interval, as `Scheduler::finish` reports it
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverrunChange {
+    Started { name: String, duration_ms: f64 },
+    Cleared { name: String },
+}
+
+/// Shared view of a scheduler's task stats
+#[derive(Clone, Default)]
+pub struct SchedulerStats(Arc<Mutex<Vec<TaskStats>>>);
+
+impl SchedulerStats {
+    pub fn snapshot(&self) -> Vec<TaskStats> {
+        self.0.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+/// A registered recurring task
+pub struct Scheduled<T> {
+    id: T,
+    period: Duration,
+    jitter: Duration,
+    due: Instant,
+}
+
+impl<T> Scheduled<T> {
+    /// Add a random delay of up to `jitter` to every interval, so tasks started together (or
+    /// instances started together) drift apart
+    pub fn jitter(&mut self, jitter: Duration) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// First run after one interval instead of right away
+    pub fn delayed(&mut self) -> &mut Self {
+        self.due += self.period;
+        self
+    }
+
+    fn next_delay(&self) -> Duration {
+        self.period + self.jitter.mul_f64(rand::random::<f64>())
+    }
+}
+
+/// Recurring maintenance work of a run loop: one `select!` branch for every timer. `next` waits
+/// for the earliest due task and is cancel-safe; the caller runs it and reports back with `finish`
+pub struct Scheduler<T> {
+    tasks: Vec<Scheduled<T>>,
+    stats: SchedulerStats,
+}
+
+impl<T: Copy + PartialEq> Scheduler<T> {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            stats: SchedulerStats::default(),
+        }
+    }
+
+    /// Run `id` every `period`, first right away
+    pub fn every(&mut self, id: T, name: &str, period: Duration) -> &mut Scheduled<T> {
+        self.tasks.push(Scheduled {
+            id,
+            period,
+            jitter: Duration::ZERO,
+            due: Instant::now(),
+        });
+        if let Ok(mut stats) = self.stats.0.lock() {
+            stats.push(TaskStats {
+                name: name.to_string(),
+                interval_ms: period.as_millis() as u64,
+                jitter_ms: 0,
+                runs: 0,
+                last_run_at: None,
+                last_duration_ms: 0.0,
+                avg_duration_ms: 0.0,
+                max_duration_ms: 0.0,
+                overruns: 0,
+                overrunning: false,
+            });
+        }
+        let index = self.tasks.len() - 1;
+        &mut self.tasks[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub fn stats(&self) -> SchedulerStats {
+        self.stats.clone()
+    }
+
+    /// The task that has been due the longest at `now` (registration order breaks ties)
+    pub fn due(&self, now: Instant) -> Option<T> {
+        self.tasks
+            .iter()
+            .filter(|t| t.due <= now)
+            .min_by_key(|t| t.due)
+            .map(|t| t.id)
+    }
+
+    /// Wait for the next due task. Never resolves without tasks
+    pub async fn next(&self) -> T {
+        loop {
+            let Some(due) = self.tasks.iter().map(|t| t.due).min() else {
+                return std::future::pending().await;
+            };
+            tokio::time::sleep_until(due).await;
+            if let Some(id) = self.due(Instant::now()) {
+                return id;
+            }
+        }
+    }
+
+    /// Record a run of `id` that started at `started`, and schedule the next one. Some when the
+    /// task started overrunning with this run, or stopped
+    pub fn finish(&mut self, id: T, started: Instant, now: Instant) -> Option<OverrunChange> {
+        let index = self.tasks.iter().position(|t| t.id == id)?;
+        let task = &mut self.tasks[index];
+        let mut next = task.due + task.next_delay();
+        let overrun = next <= now;
+        if overrun {
+            next = now + task.next_delay();
+        }
+        task.due = next;
+        let jitter_ms = task.jitter.as_millis() as u64;
+
+        let mut stats = self.stats.0.lock().ok()?;
+        let stats = &mut stats[index];
+        let duration_ms = now.saturating_duration_since(started).as_secs_f64() * 1000.0;
+        stats.jitter_ms = jitter_ms;
+        stats.runs += 1;
+        stats.last_run_at = Some(Utc::now());
+        stats.last_duration_ms = duration_ms;
+        stats.avg_duration_ms += (duration_ms - stats.avg_duration_ms) / stats.runs as f64;
+        stats.max_duration_ms = stats.max_duration_ms.max(duration_ms);
+        if overrun {
+            stats.overruns += 1;
+        }
+        let was_overrunning = std::mem::replace(&mut stats.overrunning, overrun);
+        match (was_overrunning, overrun) {
+            (false, true) => Some(OverrunChange::Started {
+                name: stats.name.clone(),
+                duration_ms,
+            }),
+            (true, false) => Some(OverrunChange::Cleared {
+                name: stats.name.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> Default for Scheduler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    #[test]
+    fn test_priority_order_and_fifo() {
+        let mut queue = JobQueue::new();
+        queue.push(Job::Reevaluate("a".into(), None));
+        queue.push(Job::ProtectPosition("b".into()));
+        queue.push(Job::Reevaluate("c".into(), None));
+        queue.push(Job::NewCondition(ConditionId::from(H256::zero())));
+
+        let order: Vec<Priority> = std::iter::from_fn(|| queue.pop())
+            .map(|j| j.priority())
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                Priority::NewMarket,
+                Priority::Protection,
+                Priority::Routine,
+                Priority::Routine
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedupe_market_jobs() {
+        let mut queue = JobQueue::new();
+        assert!(queue.push(Job::Reevaluate("a".into(), Some(1))));
+        assert!(!queue.push(Job::Reevaluate("a".into(), Some(2))));
+        assert!(queue.push(Job::ProtectPosition("a".into())));
+        assert_eq!(queue.len(), 2);
+
+        queue.pop();
+        // The pending job keeps the oldest trigger
+        assert!(matches!(queue.pop(), Some(Job::Reevaluate(_, Some(1)))));
+        assert!(queue.push(Job::Reevaluate("a".into(), None)));
+        assert_eq!(queue.stats().deduped, 1);
+    }
+
+    #[test]
+    fn test_scheduler_runs_the_longest_due_task_and_counts_overruns() {
+        let mut scheduler = Scheduler::new();
+        scheduler.every(1, "fast", Duration::from_secs(1));
+        scheduler
+            .every(2, "slow", Duration::from_secs(10))
+            .delayed();
+        let start = Instant::now();
+        assert_eq!(scheduler.due(start), Some(1));
+
+        scheduler.finish(1, start, start + Duration::from_millis(200));
+        assert_eq!(scheduler.due(start + Duration::from_millis(500)), None);
+        assert_eq!(scheduler.due(start + Duration::from_secs(1)), Some(1));
+
+        // Ran past its next tick: counted, and the missed tick is skipped
+        let late = start + Duration::from_millis(3500);
+        assert_eq!(
+            scheduler.finish(1, start + Duration::from_secs(1), late),
+            Some(OverrunChange::Started {
+                name: "fast".to_string(),
+                duration_ms: 2500.0
+            })
+        );
+        assert_eq!(scheduler.due(late), None);
+        assert_eq!(scheduler.due(late + Duration::from_secs(1)), Some(1));
+
+        let stats = scheduler.stats().snapshot();
+        assert_eq!((stats[0].runs, stats[0].overruns), (2, 1));
+        assert_eq!(stats[0].max_duration_ms, 2500.0);
+        assert_eq!(stats[1].runs, 0);
+
+        // Reported once while it keeps overrunning, and again once it stops
+        let again = late + Duration::from_secs(1);
+        let later = again + Duration::from_secs(3);
+        assert_eq!(scheduler.finish(1, again, later), None);
+        let on_time = later + Duration::from_secs(1);
+        assert_eq!(
+            scheduler.finish(1, on_time, on_time + Duration::from_millis(100)),
+            Some(OverrunChange::Cleared {
+                name: "fast".to_string()
+            })
+        );
+        assert_eq!(scheduler.stats().snapshot()[0].overruns, 2);
+    }
+}
@@ -0,0 +1,328 @@
+//! Fault injection for dry runs
+//!
+//! Randomly fails the network layers the bot depends on - CLOB WS disconnects, HTTP timeouts,
+//! 429s and RPC errors - so reconnection, retries, breakers and state consistency can be watched
+//! before trusting a build live. Only available in builds with `--features chaos`, and refused on
+//! live runs.
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use polymarket_client_sdk::clob::types::OrderType;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::ChaosConfig;
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::polymarket::{
+    CollateralBalances, EventSeries, MarketData, MarketInterface, OpenOrder, OrderBook,
+    OrderRequest, OrderStatus,
+};
+use crate::venues::Venue;
+
+/// A failure the chaos layer can inject
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosFault {
+    WsDisconnect,
+    HttpTimeout,
+    RateLimited,
+    RpcError,
+}
+
+impl ChaosFault {
+    const ALL: [ChaosFault; 4] = [
+        ChaosFault::WsDisconnect,
+        ChaosFault::HttpTimeout,
+        ChaosFault::RateLimited,
+        ChaosFault::RpcError,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChaosFault::WsDisconnect => "ws_disconnect",
+            ChaosFault::HttpTimeout => "http_timeout",
+            ChaosFault::RateLimited => "http_429",
+            ChaosFault::RpcError => "rpc_error",
+        }
+    }
+}
+
+/// Rolls the dice for every network call of a dry run and counts what it injected
+pub struct ChaosMonkey {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+    injected: [AtomicU64; 4],
+}
+
+impl ChaosMonkey {
+    /// The chaos layer for this run: None when off. Fails on live runs and on builds without
+    /// the `chaos` feature
+    pub fn from_config(config: &ChaosConfig, live: bool) -> Result<Option<Arc<Self>>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        if live {
+            anyhow::bail!("CHAOS_ENABLED is only allowed in simulation or paper trading runs");
+        }
+        #[cfg(feature = "chaos")]
+        {
+            tracing::info!(
+                "🐒 Chaos mode: ws disconnect {:.4}/msg, http timeout {:.3}, 429 {:.3}, rpc error {:.3} (seed {})",
+                config.ws_disconnect_prob,
+                config.http_timeout_prob,
+                config.http_429_prob,
+                config.rpc_error_prob,
+                config.seed
+            );
+            Ok(Some(Arc::new(Self::new(config.clone()))))
+        }
+        #[cfg(not(feature = "chaos"))]
+        {
+            anyhow::bail!("CHAOS_ENABLED needs a build with `--features chaos`")
+        }
+    }
+
+    /// Seeded from `config.seed` so a failing run can be replayed (0 = random)
+    pub fn new(config: ChaosConfig) -> Self {
+        let rng = if config.seed == 0 {
+            StdRng::from_os_rng()
+        } else {
+            StdRng::seed_from_u64(config.seed)
+        };
+        Self {
+            config,
+            rng: Mutex::new(rng),
+            injected: Default::default(),
+        }
+    }
+
+    /// Whether to inject `fault` now
+    pub fn roll(&self, fault: ChaosFault) -> bool {
+        let probability = match fault {
+            ChaosFault::WsDisconnect => self.config.ws_disconnect_prob,
+            ChaosFault::HttpTimeout => self.config.http_timeout_prob,
+            ChaosFault::RateLimited => self.config.http_429_prob,
+            ChaosFault::RpcError => self.config.rpc_error_prob,
+        };
+        if probability <= 0.0 {
+            return false;
+        }
+        let hit = self
+            .rng
+            .lock()
+            .map(|mut rng| rng.random_bool(probability.min(1.0)))
+            .unwrap_or(false);
+        if hit {
+            let count = self.injected[fault as usize].fetch_add(1, Ordering::Relaxed) + 1;
+            warn!("🐒 Chaos: injecting {} (#{})", fault.as_str(), count);
+        }
+        hit
+    }
+
+    /// Fail an HTTP call to `operation` with a timeout (after `timeout_ms`, like the real
+    /// client) or a 429
+    pub async fn http(&self, operation: &str) -> Result<()> {
+        if self.roll(ChaosFault::HttpTimeout) {
+            tokio::time::sleep(Duration::from_millis(self.config.timeout_ms)).await;
+            return Err(anyhow!("{}: operation timed out (chaos)", operation));
+        }
+        if self.roll(ChaosFault::RateLimited) {
+            return Err(anyhow!("{}: HTTP 429 Too Many Requests (chaos)", operation));
+        }
+        Ok(())
+    }
+
+    /// Fail a JSON-RPC call to `method`
+    pub fn rpc(&self, method: &str) -> Result<()> {
+        if self.roll(ChaosFault::RpcError) {
+            return Err(anyhow!(
+                "{}: RPC error -32005 limit exceeded (chaos)",
+                method
+            ));
+        }
+        Ok(())
+    }
+
+    /// Faults injected so far, by kind
+    pub fn injected(&self) -> Vec<(&'static str, u64)> {
+        ChaosFault::ALL
+            .iter()
+            .map(|fault| {
+                (
+                    fault.as_str(),
+                    self.injected[*fault as usize].load(Ordering::Relaxed),
+                )
+            })
+            .collect()
+    }
+}
+
+/// A market interface whose calls fail at random: balances as RPC errors, everything else as
+/// HTTP timeouts / 429s. Wraps the simulator or the real client alike
+pub struct ChaosInterface {
+    inner: Arc<dyn MarketInterface + Send + Sync>,
+    chaos: Arc<ChaosMonkey>,
+}
+
+impl ChaosInterface {
+    pub fn new(inner: Arc<dyn MarketInterface + Send + Sync>, chaos: Arc<ChaosMonkey>) -> Self {
+        Self { inner, chaos }
+    }
+}
+
+#[async_trait]
+impl MarketInterface for ChaosInterface {
+    fn venue(&self) -> Venue {
+        self.inner.venue()
+    }
+
+    async fn get_active_markets(&self) -> Result<Vec<MarketData>> {
+        self.chaos.http("get_active_markets").await?;
+        self.inner.get_active_markets().await
+    }
+
+    async fn get_market_details(&self, market_id: &str) -> Result<MarketData> {
+        self.chaos.http("get_market_details").await?;
+        self.inner.get_market_details(market_id).await
+    }
+
+    async fn get_balance(&self) -> Result<f64> {
+        self.chaos.rpc("eth_call")?;
+        self.inner.get_balance().await
+    }
+
+    async fn get_collateral_balances(&self) -> Result<CollateralBalances> {
+        self.chaos.rpc("eth_call")?;
+        self.inner.get_collateral_balances().await
+    }
+
+    async fn place_order(
+        &self,
+        market_id: &MarketId,
+        side: &str,
+        size: UsdAmount,
+        price: Price,
+        order_type: OrderType,
+    ) -> Result<String> {
+        self.chaos.http("place_order").await?;
+        self.inner
+            .place_order(market_id, side, size, price, order_type)
+            .await
+    }
+
+    async fn place_orders(&self, orders: &[OrderRequest]) -> Vec<Result<String>> {
+        if let Err(e) = self.chaos.http("place_orders").await {
+            return orders.iter().map(|_| Err(anyhow!("{}", e))).collect();
+        }
+        self.inner.place_orders(orders).await
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        self.chaos.http("cancel_order").await?;
+        self.inner.cancel_order(order_id).await
+    }
+
+    async fn get_open_orders(&self, market_id: Option<&str>) -> Result<Vec<OpenOrder>> {
+        self.chaos.http("get_open_orders").await?;
+        self.inner.get_open_orders(market_id).await
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        self.chaos.http("get_order_status").await?;
+        self.inner.get_order_status(order_id).await
+    }
+
+    async fn get_orderbook(&self, asset_id: &str) -> Result<OrderBook> {
+        self.chaos.http("get_orderbook").await?;
+        self.inner.get_orderbook(asset_id).await
+    }
+
+    fn downgrade_to_paper(&self) {
+        self.inner.downgrade_to_paper()
+    }
+
+    async fn get_market_metadata(&self, market_id: &str) -> Result<MarketData> {
+        self.chaos.http("get_market_metadata").await?;
+        self.inner.get_market_metadata(market_id).await
+    }
+
+    async fn get_market_by_slug(&self, slug: &str) -> Result<MarketData> {
+        self.chaos.http("get_market_by_slug").await?;
+        self.inner.get_market_by_slug(slug).await
+    }
+
+    async fn get_fee_rate_bps(&self, asset_id: &str) -> Result<Option<u32>> {
+        self.chaos.http("get_fee_rate_bps").await?;
+        self.inner.get_fee_rate_bps(asset_id).await
+    }
+
+    async fn get_event_series(&self) -> Result<Vec<EventSeries>> {
+        self.chaos.http("get_event_series").await?;
+        self.inner.get_event_series().await
+    }
+
+    async fn get_series_markets(&self, series_id: &str) -> Result<Vec<MarketData>> {
+        self.chaos.http("get_series_markets").await?;
+        self.inner.get_series_markets(series_id).await
+    }
+
+    async fn poll_new_markets(&self) -> Result<Vec<MarketData>> {
+        self.chaos.http("poll_new_markets").await?;
+        self.inner.poll_new_markets().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::MarketSimulator;
+
+    fn config(probability: f64) -> ChaosConfig {
+        ChaosConfig {
+            enabled: true,
+            seed: 7,
+            ws_disconnect_prob: probability,
+            http_timeout_prob: 0.0,
+            http_429_prob: probability,
+            rpc_error_prob: probability,
+            timeout_ms: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_injects_faults_only_when_rolled() {
+        assert!(ChaosMonkey::from_config(&config(1.0), true).is_err());
+        assert!(ChaosMonkey::from_config(
+            &ChaosConfig {
+                enabled: false,
+                ..config(1.0)
+            },
+            true
+        )
+        .unwrap()
+        .is_none());
+
+        let simulator: Arc<dyn MarketInterface + Send + Sync> = Arc::new(MarketSimulator::new());
+        let always =
+            ChaosInterface::new(simulator.clone(), Arc::new(ChaosMonkey::new(config(1.0))));
+        let err = always.get_active_markets().await.unwrap_err();
+        assert!(err.to_string().contains("429"));
+        assert!(always.get_balance().await.is_err());
+        assert!(always.chaos.roll(ChaosFault::WsDisconnect));
+        assert_eq!(
+            always.chaos.injected(),
+            vec![
+                ("ws_disconnect", 1),
+                ("http_timeout", 0),
+                ("http_429", 1),
+                ("rpc_error", 1)
+            ]
+        );
+
+        let never = ChaosInterface::new(simulator, Arc::new(ChaosMonkey::new(config(0.0))));
+        assert!(never.get_active_markets().await.is_ok());
+        assert!(never.get_balance().await.is_ok());
+    }
+}
@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::Tick;
+use crate::markets::normalize_market_id;
+
+const GAMMA_URL: &str = "https://gamma-api.polymarket.com";
+const DATA_API_URL: &str = "https://data-api.polymarket.com";
+
+/// Trades requested per data-api page
+const TRADE_PAGE_SIZE: usize = 500;
+
+/// Pages of trades fetched per market before giving up on older volume
+const MAX_TRADE_PAGES: usize = 20;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GammaMarket {
+    condition_id: String,
+    question: String,
+    #[serde(default)]
+    outcomes: String,
+    #[serde(default)]
+    clob_token_ids: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceHistory {
+    history: Vec<PricePoint>,
+}
+
+/// One point of `/prices-history`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PricePoint {
+    /// Unix seconds
+    pub t: u64,
+    pub p: f64,
+}
+
+/// One trade of the data-api `/trades` listing
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoricalTrade {
+    /// Unix seconds
+    pub timestamp: u64,
+    /// Shares
+    pub size: f64,
+    pub price: f64,
+}
+
+/// Builds replay tapes from Polymarket's public history: YES prices from the CLOB
+/// `/prices-history` endpoint, volume from the data-api trade listing. No API key needed
+pub struct HistoryDownloader {
+    http: reqwest::Client,
+    clob_url: String,
+    /// Minutes between price points
+    fidelity_mins: u64,
+}
+
+impl HistoryDownloader {
+    pub fn new(clob_url: &str, fidelity_mins: u64) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            http,
+            clob_url: clob_url.trim_end_matches('/').to_string(),
+            fidelity_mins: fidelity_mins.max(1),
+        })
+    }
+
+    /// Ticks of every market between `start` and `end` (unix seconds; None = all history),
+    /// merged oldest first
+    pub async fn download(
+        &self,
+        market_ids: &[String],
+        start: Option<u64>,
+        end: Option<u64>,
+    ) -> Result<Vec<Tick>> {
+        let mut ticks = Vec::new();
+        for market_id in market_ids {
+            let market = self.market(market_id).await?;
+            let token_id = yes_token_id(&market)
+                .with_context(|| format!("No YES token for market {}", market_id))?;
+            let prices = self.prices(&token_id, start, end).await?;
+            let trades = self
+                .trades(&market.condition_id, start)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("⚠️ No trade volume for {}: {:#}", market_id, e);
+                    Vec::new()
+                });
+            info!(
+                "📥 {}: {} price points, {} trades ({})",
+                market_id,
+                prices.len(),
+                trades.len(),
+                market.question
+            );
+            ticks.extend(to_ticks(
+                &market.condition_id,
+                &prices,
+                &trades,
+                self.fidelity_mins * 60,
+            ));
+        }
+        ticks.sort_by_key(|t| t.timestamp);
+        Ok(ticks)
+    }
+
+    async fn market(&self, market_id: &str) -> Result<GammaMarket> {
+        let url = format!("{}/markets?condition_ids={}", GAMMA_URL, market_id);
+        let markets: Vec<GammaMarket> = self
+            .http
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        markets
+            .into_iter()
+            .find(|m| normalize_market_id(&m.condition_id) == normalize_market_id(market_id))
+            .with_context(|| format!("Market {} not found on Gamma", market_id))
+    }
+
+    async fn prices(
+        &self,
+        token_id: &str,
+        start: Option<u64>,
+        end: Option<u64>,
+    ) -> Result<Vec<PricePoint>> {
+        let mut query = vec![
+            ("market", token_id.to_string()),
+            ("fidelity", self.fidelity_mins.to_string()),
+        ];
+        match (start, end) {
+            (None, None) => query.push(("interval", "max".to_string())),
+            _ => {
+                query.push(("startTs", start.unwrap_or(0).to_string()));
+                if let Some(end) = end {
+                    query.push(("endTs", end.to_string()));
+                }
+            }
+        }
+        let history: PriceHistory = self
+            .http
+            .get(format!("{}/prices-history", self.clob_url))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(history.history)
+    }
+
+    /// Newest first, paging back until `start` (or `MAX_TRADE_PAGES`)
+    async fn trades(&self, condition_id: &str, start: Option<u64>) -> Result<Vec<HistoricalTrade>> {
+        let mut trades = Vec::new();
+        for page in 0..MAX_TRADE_PAGES {
+            let batch: Vec<HistoricalTrade> = self
+                .http
+                .get(format!("{}/trades", DATA_API_URL))
+                .query(&[
+                    ("market", condition_id.to_string()),
+                    ("limit", TRADE_PAGE_SIZE.to_string()),
+                    ("offset", (page * TRADE_PAGE_SIZE).to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let done = batch.len() < TRADE_PAGE_SIZE
+                || batch
+                    .last()
+                    .is_some_and(|t| start.is_some_and(|s| t.timestamp < s));
+            trades.extend(batch);
+            if done {
+                break;
+            }
+        }
+        Ok(trades)
+    }
+}
+
+/// YES token of a Gamma market (`clobTokenIds` follows `outcomes`, YES second by default)
+fn yes_token_id(market: &GammaMarket) -> Option<String> {
+    let outcomes: Vec<String> = serde_json::from_str(&market.outcomes).unwrap_or_default();
+    let token_ids: Vec<String> = serde_json::from_str(&market.clob_token_ids).ok()?;
+    let yes_idx = outcomes
+        .iter()
+        .position(|o| o.eq_ignore_ascii_case("Yes"))
+        .unwrap_or(1);
+    token_ids.get(yes_idx).cloned()
+}
+
+/// One tick per price point, carrying the USD traded from that point until the next
+/// (`bucket_secs` for the last one)
+pub fn to_ticks(
+    market_id: &str,
+    prices: &[PricePoint],
+    trades: &[HistoricalTrade],
+    bucket_secs: u64,
+) -> Vec<Tick> {
+    let mut prices = prices.to_vec();
+    prices.sort_by_key(|p| p.t);
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let until = prices
+                .get(i + 1)
+                .map(|next| next.t)
+                .unwrap_or(point.t + bucket_secs);
+            let volume = trades
+                .iter()
+                .filter(|t| t.timestamp >= point.t && t.timestamp < until)
+                .map(|t| t.size * t.price)
+                .sum();
+            Tick {
+                timestamp: point.t * 1000,
+                market_id: market_id.to_string(),
+                price: point.p,
+                volume,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{read_tape, write_tape};
+
+    #[test]
+    fn test_ticks_round_trip_through_tape() {
+        let prices = [PricePoint { t: 120, p: 0.55 }, PricePoint { t: 60, p: 0.5 }];
+        let trades = [
+            HistoricalTrade {
+                timestamp: 60,
+                size: 100.0,
+                price: 0.5,
+            },
+            HistoricalTrade {
+                timestamp: 119,
+                size: 10.0,
+                price: 0.5,
+            },
+            HistoricalTrade {
+                timestamp: 150,
+                size: 22.0,
+                price: 0.5,
+            },
+            // After the last bucket
+            HistoricalTrade {
+                timestamp: 400,
+                size: 1000.0,
+                price: 0.6,
+            },
+        ];
+        let ticks = to_ticks("0xabc", &prices, &trades, 60);
+        let summary: Vec<(u64, f64, f64)> = ticks
+            .iter()
+            .map(|t| (t.timestamp, t.price, t.volume))
+            .collect();
+        assert_eq!(summary, vec![(60_000, 0.5, 55.0), (120_000, 0.55, 11.0)]);
+
+        let path = std::env::temp_dir().join(format!("tape-{}.csv", std::process::id()));
+        write_tape(&path, &ticks).unwrap();
+        let replayed = read_tape(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed[1].market_id, "0xabc");
+        assert_eq!(replayed[1].volume, 11.0);
+    }
+}
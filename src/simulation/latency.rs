@@ -0,0 +1,432 @@
+//! Network latency injection for simulation runs and backtests
+//!
+//! Orders reach the simulated book after a decision -> order delay (signing, submission) and
+//! match after a further order -> fill delay, each drawn from its own distribution. Replays
+//! move the order to the tick at which it would have arrived; real-time simulation runs sleep.
+//! `sweep` replays one tape at a range of latencies to show how much each strategy's PnL
+//! depends on speed.
+use anyhow::{anyhow, Context, Result};
+use chrono::{TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{MarketSimulator, Tick};
+use crate::analytics::ExecutionDriftTracker;
+use crate::config::SimLatencyConfig;
+use crate::ids::UsdAmount;
+use crate::polymarket::{MarketData, MarketInterface};
+use crate::strategies::{Strategy, TradeAction};
+use polymarket_client_sdk::clob::types::OrderType;
+
+/// Delay distribution of one hop, in milliseconds
+#[derive(Debug, Clone, PartialEq)]
+pub enum LatencyDistribution {
+    Fixed(f64),
+    Uniform {
+        min_ms: f64,
+        max_ms: f64,
+    },
+    /// Clamped at 0
+    Normal {
+        mean_ms: f64,
+        std_ms: f64,
+    },
+    /// Resampled from latencies measured on a real deployment
+    Empirical(Vec<f64>),
+}
+
+impl LatencyDistribution {
+    /// `50` or `fixed:50`, `uniform:20-80`, `normal:50,15`, or `measured`: the signal -> post
+    /// latencies the live agent recorded in `data_dir/execution_drift.jsonl`. Empty = 0
+    pub fn parse(spec: &str, data_dir: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let number = |s: &str| -> Result<f64> {
+            let value: f64 = s
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid latency {:?}", s))?;
+            if !value.is_finite() || value < 0.0 {
+                anyhow::bail!("Latency must be a non-negative number of ms, got {}", value);
+            }
+            Ok(value)
+        };
+        if spec.is_empty() {
+            return Ok(Self::Fixed(0.0));
+        }
+        if spec == "measured" {
+            let samples = ExecutionDriftTracker::load(data_dir).latencies_ms();
+            if samples.is_empty() {
+                anyhow::bail!(
+                    "No measured latencies in {}/execution_drift.jsonl",
+                    data_dir
+                );
+            }
+            return Ok(Self::Empirical(samples));
+        }
+        let (kind, args) = spec.split_once(':').unwrap_or(("fixed", spec));
+        match kind {
+            "fixed" => Ok(Self::Fixed(number(args)?)),
+            "uniform" => {
+                let (min, max) = args
+                    .split_once('-')
+                    .ok_or_else(|| anyhow!("Expected uniform:MIN-MAX, got {:?}", spec))?;
+                let (min_ms, max_ms) = (number(min)?, number(max)?);
+                if min_ms > max_ms {
+                    anyhow::bail!("Empty latency range {:?}", spec);
+                }
+                Ok(Self::Uniform { min_ms, max_ms })
+            }
+            "normal" => {
+                let (mean, std) = args
+                    .split_once(',')
+                    .ok_or_else(|| anyhow!("Expected normal:MEAN,STD, got {:?}", spec))?;
+                Ok(Self::Normal {
+                    mean_ms: number(mean)?,
+                    std_ms: number(std)?,
+                })
+            }
+            _ => anyhow::bail!("Unknown latency distribution {:?}", spec),
+        }
+    }
+
+    /// One delay, in whole milliseconds
+    pub fn sample(&self, rng: &mut impl Rng) -> u64 {
+        let ms = match self {
+            Self::Fixed(ms) => *ms,
+            Self::Uniform { min_ms, max_ms } if min_ms < max_ms => {
+                rng.random_range(*min_ms..*max_ms)
+            }
+            Self::Uniform { min_ms, .. } => *min_ms,
+            Self::Normal { mean_ms, std_ms } => {
+                // Box-Muller
+                let u1: f64 = rng.random::<f64>().max(f64::MIN_POSITIVE);
+                let u2: f64 = rng.random();
+                let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                mean_ms + std_ms * z
+            }
+            Self::Empirical(samples) if !samples.is_empty() => {
+                samples[rng.random_range(0..samples.len())]
+            }
+            Self::Empirical(_) => 0.0,
+        };
+        ms.max(0.0).round() as u64
+    }
+}
+
+impl fmt::Display for LatencyDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(ms) => write!(f, "{}ms", ms),
+            Self::Uniform { min_ms, max_ms } => write!(f, "uniform {}-{}ms", min_ms, max_ms),
+            Self::Normal { mean_ms, std_ms } => write!(f, "normal {}±{}ms", mean_ms, std_ms),
+            Self::Empirical(samples) => write!(f, "measured ({} samples)", samples.len()),
+        }
+    }
+}
+
+/// Decision -> order and order -> fill delays of simulated orders
+pub struct LatencyModel {
+    pub decision_to_order: LatencyDistribution,
+    pub order_to_fill: LatencyDistribution,
+    rng: Mutex<StdRng>,
+}
+
+impl LatencyModel {
+    /// Seeded so a backtest can be replayed (0 = random)
+    pub fn new(
+        decision_to_order: LatencyDistribution,
+        order_to_fill: LatencyDistribution,
+        seed: u64,
+    ) -> Self {
+        let rng = if seed == 0 {
+            StdRng::from_os_rng()
+        } else {
+            StdRng::seed_from_u64(seed)
+        };
+        Self {
+            decision_to_order,
+            order_to_fill,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    pub fn from_config(config: &SimLatencyConfig, data_dir: &str) -> Result<Self> {
+        Ok(Self::new(
+            LatencyDistribution::parse(&config.decision_to_order, data_dir)
+                .context("SIM_DECISION_LATENCY")?,
+            LatencyDistribution::parse(&config.order_to_fill, data_dir)
+                .context("SIM_FILL_LATENCY")?,
+            config.seed,
+        ))
+    }
+
+    /// No delay at all
+    pub fn zero() -> Self {
+        Self::new(
+            LatencyDistribution::Fixed(0.0),
+            LatencyDistribution::Fixed(0.0),
+            0,
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        let zero = LatencyDistribution::Fixed(0.0);
+        self.decision_to_order == zero && self.order_to_fill == zero
+    }
+
+    /// (decision -> order, order -> fill) delays of the next order, in ms
+    pub fn sample(&self) -> (u64, u64) {
+        let Ok(mut rng) = self.rng.lock() else {
+            return (0, 0);
+        };
+        (
+            self.decision_to_order.sample(&mut *rng),
+            self.order_to_fill.sample(&mut *rng),
+        )
+    }
+}
+
+impl Default for LatencyModel {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+/// One strategy's replay result at one latency
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepPoint {
+    /// Decision -> order latency of the run (order -> fill as configured)
+    pub latency_ms: u64,
+    pub strategy: String,
+    pub orders: usize,
+    pub fills: usize,
+    /// Fills marked at the last price of the tape
+    pub pnl_usd: f64,
+}
+
+/// Replay `ticks` over `markets` once per decision -> order latency, running `strategies` on
+/// every tick (one entry per strategy and market), and mark each strategy's fills at the end
+pub async fn sweep(
+    markets: &[MarketData],
+    ticks: &[Tick],
+    strategies: &[Arc<dyn Strategy>],
+    latencies_ms: &[u64],
+    order_to_fill: &LatencyDistribution,
+    seed: u64,
+) -> Result<Vec<SweepPoint>> {
+    let mut points = Vec::new();
+    for &latency_ms in latencies_ms {
+        let latency = LatencyModel::new(
+            LatencyDistribution::Fixed(latency_ms as f64),
+            order_to_fill.clone(),
+            seed,
+        );
+        let simulator = MarketSimulator::new().with_latency(latency);
+        simulator.load_markets(markets.to_vec());
+        simulator.load_ticks(ticks.to_vec());
+        for mut point in replay(&simulator, strategies).await? {
+            point.latency_ms = latency_ms;
+            points.push(point);
+        }
+    }
+    Ok(points)
+}
+
+/// Run one replay to the end of the tape. Points carry latency 0 (the caller's to fill in)
+async fn replay(
+    simulator: &MarketSimulator,
+    strategies: &[Arc<dyn Strategy>],
+) -> Result<Vec<SweepPoint>> {
+    let mut entered: HashSet<(String, String)> = HashSet::new();
+    let mut strategy_of: HashMap<String, String> = HashMap::new();
+    let mut orders: HashMap<String, usize> = HashMap::new();
+
+    while let Some(tick) = simulator.next_tick() {
+        let market = simulator.get_market_details(&tick.market_id).await?;
+        let now = Utc
+            .timestamp_millis_opt(tick.timestamp as i64)
+            .single()
+            .unwrap_or_else(Utc::now);
+        for strategy in strategies {
+            let key = (strategy.name().to_string(), market.id.to_string());
+            if entered.contains(&key) {
+                continue;
+            }
+            let legs = match strategy.evaluate(&market, now).await {
+                TradeAction::Snipe {
+                    side,
+                    price,
+                    size_usd,
+                    ..
+                } => vec![(side, price, size_usd, OrderType::FOK)],
+                TradeAction::BuyBoth {
+                    yes_price,
+                    no_price,
+                    size_usd,
+                    ..
+                } => vec![
+                    (
+                        "YES".to_string(),
+                        yes_price,
+                        UsdAmount::new(size_usd.get() / 2.0),
+                        OrderType::GTC,
+                    ),
+                    (
+                        "NO".to_string(),
+                        no_price,
+                        UsdAmount::new(size_usd.get() / 2.0),
+                        OrderType::GTC,
+                    ),
+                ],
+                TradeAction::None => continue,
+            };
+            entered.insert(key);
+            for (side, price, size_usd, order_type) in legs {
+                // A rejected order (e.g. out of balance) still counts as tried
+                *orders.entry(strategy.name().to_string()).or_default() += 1;
+                if let Ok(order_id) = simulator
+                    .place_order(&market.id, &side, size_usd, price, order_type)
+                    .await
+                {
+                    strategy_of.insert(order_id, strategy.name().to_string());
+                }
+            }
+        }
+    }
+
+    let mut points: HashMap<String, SweepPoint> = strategies
+        .iter()
+        .map(|s| {
+            let point = SweepPoint {
+                latency_ms: 0,
+                strategy: s.name().to_string(),
+                orders: orders.get(s.name()).copied().unwrap_or(0),
+                fills: 0,
+                pnl_usd: 0.0,
+            };
+            (s.name().to_string(), point)
+        })
+        .collect();
+    for fill in simulator.fills() {
+        let Some(point) = strategy_of
+            .get(&fill.order_id)
+            .and_then(|name| points.get_mut(name))
+        else {
+            continue;
+        };
+        let last = simulator.get_market_details(&fill.market_id).await?;
+        let mark = if fill.side == "YES" {
+            last.yes_price
+        } else {
+            last.no_price
+        };
+        point.fills += 1;
+        if fill.price > 0.0 {
+            point.pnl_usd += fill.size_usd / fill.price * mark - fill.size_usd;
+        }
+    }
+    let mut points: Vec<SweepPoint> = points.into_values().collect();
+    points.sort_by(|a, b| a.strategy.cmp(&b.strategy));
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::Price;
+    use async_trait::async_trait;
+    use chrono::DateTime;
+
+    /// Buys YES once it trades at or below 0.40
+    struct BuyTheDip;
+
+    #[async_trait]
+    impl Strategy for BuyTheDip {
+        fn name(&self) -> &str {
+            "dip"
+        }
+
+        async fn evaluate(&self, market: &MarketData, _now: DateTime<Utc>) -> TradeAction {
+            if market.yes_price > 0.40 {
+                return TradeAction::None;
+            }
+            TradeAction::Snipe {
+                market_id: market.id.clone(),
+                side: "YES".to_string(),
+                price: Price::new(market.yes_price),
+                size_usd: UsdAmount::new(100.0),
+            }
+        }
+    }
+
+    fn tick(timestamp: u64, price: f64) -> Tick {
+        Tick {
+            timestamp,
+            market_id: "m1".to_string(),
+            price,
+            volume: 1_000.0,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_sample_distributions() {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(
+            LatencyDistribution::parse("50", "/nonexistent").unwrap(),
+            LatencyDistribution::Fixed(50.0)
+        );
+        let uniform = LatencyDistribution::parse("uniform:20-80", "/nonexistent").unwrap();
+        assert!((0..100).all(|_| (20..=80).contains(&uniform.sample(&mut rng))));
+        let normal = LatencyDistribution::parse("normal:50,15", "/nonexistent").unwrap();
+        let mean = (0..2000)
+            .map(|_| normal.sample(&mut rng) as f64)
+            .sum::<f64>()
+            / 2000.0;
+        assert!((mean - 50.0).abs() < 3.0);
+        assert!(LatencyDistribution::parse("uniform:80-20", "/nonexistent").is_err());
+        assert!(LatencyDistribution::parse("measured", "/nonexistent").is_err());
+        assert!(LatencyDistribution::parse("bogus:1", "/nonexistent").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_shows_the_cost_of_latency() {
+        let market = MarketData {
+            id: "m1".into(),
+            question: "Will it happen?".to_string(),
+            yes_price: 0.5,
+            no_price: 0.5,
+            ..Default::default()
+        };
+        // A one-tick dip: only an order arriving within 100ms buys it
+        let ticks = vec![
+            tick(0, 0.50),
+            tick(1_000, 0.40),
+            tick(1_100, 0.60),
+            tick(5_000, 0.60),
+        ];
+        let strategies: Vec<Arc<dyn Strategy>> = vec![Arc::new(BuyTheDip)];
+        let points = sweep(
+            &[market],
+            &ticks,
+            &strategies,
+            &[0, 500],
+            &LatencyDistribution::Fixed(0.0),
+            1,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(points.len(), 2);
+        let fast = &points[0];
+        assert_eq!((fast.latency_ms, fast.orders, fast.fills), (0, 1, 1));
+        assert!((fast.pnl_usd - 50.0).abs() < 1e-6);
+        // Arriving at 0.60 with a 0.40 limit: the FOK is killed
+        let slow = &points[1];
+        assert_eq!((slow.latency_ms, slow.orders, slow.fills), (500, 1, 0));
+        assert_eq!(slow.pnl_usd, 0.0);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::polymarket::{OpenOrder, OrderStatus};
+use std::collections::{HashMap, VecDeque};
+
+/// Filled and cancelled orders kept for status lookups; older ones are dropped so long
+/// simulations and soak runs don't grow without bound
+pub(crate) const TERMINAL_RETAINED: usize = 1024;
+
+/// An order held by the simulated matching engine
+/// All simulated orders buy an outcome token ("YES" or "NO"), sized in USD notional
+#[derive(Debug, Clone)]
+pub struct SimOrder {
+    pub order_id: String,
+    pub market_id: String,
+    pub side: String, // "YES" or "NO"
+    pub price: f64,
+    pub size_usd: f64,
+    pub filled_usd: f64,
+    pub status: OrderStatus,
+    pub seq: u64, // Arrival sequence for time priority
+    pub created_at: u64,
+}
+
+impl SimOrder {
+    pub fn remaining(&self) -> f64 {
+        (self.size_usd - self.filled_usd).max(0.0)
+    }
+
+    pub fn to_open_order(&self) -> OpenOrder {
+        OpenOrder {
+            order_id: self.order_id.clone(),
+            market_id: self.market_id.clone(),
+            asset_id: format!("{}:{}", self.market_id, self.side),
+            side: "BUY".to_string(),
+            price: self.price,
+            original_size: self.size_usd,
+            size_matched: self.filled_usd,
+            status: self.status,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// A fill produced by the engine
+#[derive(Debug, Clone)]
+pub struct SimFill {
+    pub order_id: String,
+    pub market_id: String,
+    pub side: String,
+    pub price: f64,
+    pub size_usd: f64,
+}
+
+/// Price-time priority matching engine for resting simulated orders
+/// Liquidity comes from replayed ticks: each tick offers `volume` USD at the tick price
+#[derive(Debug, Default)]
+pub struct MatchingEngine {
+    orders: HashMap<String, SimOrder>,
+    /// Ids of the orders that reached a terminal state, oldest first
+    terminal: VecDeque<String>,
+    next_seq: u64,
+}
+
+impl MatchingEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Submit a new order
+    /// `available_usd` is the liquidity at `market_price` that can fill immediately
+    /// Returns the fill (if any) produced on arrival
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        order_id: String,
+        market_id: &str,
+        side: &str,
+        price: f64,
+        size_usd: f64,
+        market_price: f64,
+        available_usd: f64,
+        fill_or_kill: bool,
+        now: u64,
+    ) -> Result<Option<SimFill>, String> {
+        let crosses = market_price > 0.0 && price >= market_price;
+
+        if fill_or_kill && (!crosses || available_usd < size_usd) {
+            return Err(format!(
+                "FOK order not filled (limit {:.4}, market {:.4}, liquidity ${:.2})",
+                price, market_price, available_usd
+            ));
+        }
+
+        self.next_seq += 1;
+        let mut order = SimOrder {
+            order_id: order_id.clone(),
+            market_id: market_id.to_string(),
+            side: side.to_string(),
+            price,
+            size_usd,
+            filled_usd: 0.0,
+            status: OrderStatus::Live,
+            seq: self.next_seq,
+            created_at: now,
+        };
+
+        let mut fill = None;
+        if crosses {
+            let fill_size = order.remaining().min(available_usd);
+            if fill_size > 0.0 {
+                order.filled_usd += fill_size;
+                fill = Some(SimFill {
+                    order_id: order_id.clone(),
+                    market_id: market_id.to_string(),
+                    side: side.to_string(),
+                    price: market_price,
+                    size_usd: fill_size,
+                });
+            }
+        }
+        order.status = Self::status_for(&order);
+
+        let terminal = order.status.is_terminal();
+        self.orders.insert(order_id.clone(), order);
+        if terminal {
+            self.retire(order_id);
+        }
+        Ok(fill)
+    }
+
+    /// Cancel a resting order. Returns false if unknown or already terminal
+    pub fn cancel(&mut self, order_id: &str) -> bool {
+        match self.orders.get_mut(order_id) {
+            Some(order) if !order.status.is_terminal() => {
+                order.status = OrderStatus::Cancelled;
+                self.retire(order_id.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn status(&self, order_id: &str) -> Option<OrderStatus> {
+        self.orders.get(order_id).map(|o| o.status)
+    }
+
+    /// Resting (non-terminal) orders, optionally for one market
+    pub fn open_orders(&self, market_id: Option<&str>) -> Vec<SimOrder> {
+        let mut orders: Vec<SimOrder> = self
+            .orders
+            .values()
+            .filter(|o| !o.status.is_terminal())
+            .filter(|o| market_id.map_or(true, |m| o.market_id == m))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|o| o.seq);
+        orders
+    }
+
+    /// Match resting orders on one outcome against a replayed tick
+    /// Best (highest) price first, then earliest arrival
+    pub fn on_tick(
+        &mut self,
+        market_id: &str,
+        side: &str,
+        tick_price: f64,
+        tick_volume_usd: f64,
+    ) -> Vec<SimFill> {
+        let mut candidates: Vec<(f64, u64, String)> = self
+            .orders
+            .values()
+            .filter(|o| {
+                o.market_id == market_id
+                    && o.side == side
+                    && !o.status.is_terminal()
+                    && o.price >= tick_price
+            })
+            .map(|o| (o.price, o.seq, o.order_id.clone()))
+            .collect();
+
+        candidates.sort_by(|a, b| {
+            b.0.partial_cmp(&a.0)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.1.cmp(&b.1))
+        });
+
+        let mut remaining_liquidity = tick_volume_usd;
+        let mut fills = Vec::new();
+
+        for (_, _, order_id) in candidates {
+            if remaining_liquidity <= 0.0 {
+                break;
+            }
+            if let Some(order) = self.orders.get_mut(&order_id) {
+                let fill_size = order.remaining().min(remaining_liquidity);
+                order.filled_usd += fill_size;
+                remaining_liquidity -= fill_size;
+                order.status = Self::status_for(order);
+
+                fills.push(SimFill {
+                    order_id: order.order_id.clone(),
+                    market_id: order.market_id.clone(),
+                    side: order.side.clone(),
+                    price: tick_price,
+                    size_usd: fill_size,
+                });
+                if order.status.is_terminal() {
+                    self.retire(order_id);
+                }
+            }
+        }
+
+        fills
+    }
+
+    /// Queue a terminal order for eviction, dropping the oldest past `TERMINAL_RETAINED`
+    fn retire(&mut self, order_id: String) {
+        self.terminal.push_back(order_id);
+        while self.terminal.len() > TERMINAL_RETAINED {
+            if let Some(oldest) = self.terminal.pop_front() {
+                self.orders.remove(&oldest);
+            }
+        }
+    }
+
+    fn status_for(order: &SimOrder) -> OrderStatus {
+        if order.remaining() <= f64::EPSILON {
+            OrderStatus::Matched
+        } else if order.filled_usd > 0.0 {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Live
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossing_order_fills_on_arrival() {
+        let mut engine = MatchingEngine::new();
+        let fill = engine
+            .submit("o1".into(), "m", "YES", 0.55, 10.0, 0.50, 100.0, false, 0)
+            .unwrap();
+
+        assert_eq!(fill.unwrap().size_usd, 10.0);
+        assert_eq!(engine.status("o1"), Some(OrderStatus::Matched));
+    }
+
+    #[test]
+    fn test_fok_rejected_when_not_crossing() {
+        let mut engine = MatchingEngine::new();
+        let res = engine.submit("o1".into(), "m", "YES", 0.40, 10.0, 0.50, 100.0, true, 0);
+        assert!(res.is_err());
+        assert!(engine.status("o1").is_none());
+    }
+
+    #[test]
+    fn test_price_time_priority_and_partial_fills() {
+        let mut engine = MatchingEngine::new();
+        engine
+            .submit("early".into(), "m", "YES", 0.45, 10.0, 0.50, 0.0, false, 0)
+            .unwrap();
+        engine
+            .submit("late".into(), "m", "YES", 0.45, 10.0, 0.50, 0.0, false, 1)
+            .unwrap();
+        engine
+            .submit("better".into(), "m", "YES", 0.48, 10.0, 0.50, 0.0, false, 2)
+            .unwrap();
+
+        // 15 USD at 0.45: "better" fills fully, "early" gets the rest, "late" nothing
+        let fills = engine.on_tick("m", "YES", 0.45, 15.0);
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].order_id, "better");
+        assert_eq!(fills[1].order_id, "early");
+        assert_eq!(engine.status("better"), Some(OrderStatus::Matched));
+        assert_eq!(engine.status("early"), Some(OrderStatus::PartiallyFilled));
+        assert_eq!(engine.status("late"), Some(OrderStatus::Live));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut engine = MatchingEngine::new();
+        engine
+            .submit("o1".into(), "m", "NO", 0.30, 10.0, 0.50, 0.0, false, 0)
+            .unwrap();
+
+        assert!(engine.cancel("o1"));
+        assert!(!engine.cancel("o1"));
+        assert!(engine.open_orders(None).is_empty());
+        assert!(engine.on_tick("m", "NO", 0.20, 100.0).is_empty());
+    }
+
+    #[test]
+    fn test_terminal_orders_are_evicted_past_the_retention() {
+        let mut engine = MatchingEngine::new();
+        for i in 0..=TERMINAL_RETAINED {
+            engine
+                .submit(
+                    format!("o{}", i),
+                    "m",
+                    "YES",
+                    0.55,
+                    1.0,
+                    0.50,
+                    100.0,
+                    false,
+                    0,
+                )
+                .unwrap();
+        }
+        engine
+            .submit("resting".into(), "m", "YES", 0.40, 1.0, 0.50, 0.0, false, 0)
+            .unwrap();
+
+        assert!(engine.status("o0").is_none());
+        assert_eq!(engine.status("o1"), Some(OrderStatus::Matched));
+        assert_eq!(engine.orders.len(), TERMINAL_RETAINED + 1);
+        // Resting orders are never evicted
+        assert_eq!(engine.status("resting"), Some(OrderStatus::Live));
+    }
+}
@@ -1,88 +1,229 @@
-use crate::polymarket::{MarketData, MarketInterface};
-use anyhow::{anyhow, Result};
+pub mod chaos;
+pub mod history;
+pub mod latency;
+pub mod matching;
+
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::polymarket::{
+    MarketData, MarketInterface, OpenOrder, OrderBook, OrderLevel, OrderStatus,
+};
+use crate::venues::Venue;
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
 use tracing::info;
 
+pub use chaos::{ChaosFault, ChaosInterface, ChaosMonkey};
+pub use history::HistoryDownloader;
+pub use latency::{LatencyDistribution, LatencyModel, SweepPoint};
+pub use matching::{MatchingEngine, SimFill, SimOrder};
+
+use matching::TERMINAL_RETAINED;
+
+/// Liquidity (USD) assumed available at the current simulated price for marketable orders
+const SIM_TOP_OF_BOOK_USD: f64 = 1_000.0;
+
+/// Header line of replay tapes
+pub const TAPE_HEADER: &str = "timestamp,market_id,price,volume";
+
 /// Simulates market interactions for backtesting
+/// State sits behind a Mutex so one instance can be shared (Arc) by the Sniper and Executor
 pub struct MarketSimulator {
+    state: Mutex<SimState>,
+    latency: LatencyModel,
+}
+
+struct SimState {
     // Current simulated time or tick index could be stored here
     active_markets: Vec<MarketData>,
     balance: f64,
-    _positions: HashMap<String, f64>, // (MarketID, SizeUSD)
+    /// USD held by the in-flight and resting orders, by order id: released as they fill,
+    /// and on cancel or kill
+    reserved: HashMap<String, f64>,
+    positions: HashMap<(String, String), f64>, // (MarketID, Side) -> SizeUSD
+    engine: MatchingEngine,
+    fills: Vec<SimFill>,
+    /// Orders still travelling to the book (simulated latency), in placement order
+    in_flight: Vec<InFlightOrder>,
+    /// In-flight orders cancelled, or killed on arrival, before reaching the engine (the
+    /// latest `TERMINAL_RETAINED`, oldest first in `dropped_order`)
+    dropped: HashSet<String>,
+    dropped_order: VecDeque<String>,
 
     // Backtesting Fields
     historical_ticks: Vec<Tick>,
     current_tick_index: usize,
+    current_timestamp: u64,
+}
+
+/// An order placed during a replay that reaches the book at `arrives_at` (unix ms)
+#[derive(Debug, Clone)]
+struct InFlightOrder {
+    order_id: String,
+    market_id: String,
+    side: String,
+    price: f64,
+    size_usd: f64,
+    fill_or_kill: bool,
+    placed_at: u64,
+    arrives_at: u64,
+}
+
+impl InFlightOrder {
+    fn to_open_order(&self) -> OpenOrder {
+        OpenOrder {
+            order_id: self.order_id.clone(),
+            market_id: self.market_id.clone(),
+            asset_id: format!("{}:{}", self.market_id, self.side),
+            side: "BUY".to_string(),
+            price: self.price,
+            original_size: self.size_usd,
+            size_matched: 0.0,
+            status: OrderStatus::Live,
+            created_at: self.placed_at,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Tick {
+    /// Unix ms
     pub timestamp: u64,
     pub market_id: String,
+    /// YES price
     pub price: f64,
+    /// USD traded since the previous tick of the market
     pub volume: f64,
     // We could add L2 updates here too
 }
 
+impl Tick {
+    fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            self.timestamp, self.market_id, self.price, self.volume
+        )
+    }
+
+    fn from_csv_row(row: &str) -> Result<Self> {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let [timestamp, market_id, price, volume] = fields[..] else {
+            anyhow::bail!("expected 4 fields, got {}", fields.len());
+        };
+        Ok(Self {
+            timestamp: timestamp.parse()?,
+            market_id: market_id.to_string(),
+            price: price.parse()?,
+            volume: volume.parse()?,
+        })
+    }
+}
+
+/// Replay tape: `TAPE_HEADER` then one tick per line, oldest first
+pub fn read_tape(path: impl AsRef<Path>) -> Result<Vec<Tick>> {
+    let path = path.as_ref();
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let mut ticks = Vec::new();
+    for (i, line) in raw.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with("timestamp") {
+            continue;
+        }
+        let tick = Tick::from_csv_row(line)
+            .with_context(|| format!("{:?} line {}: {:?}", path, i + 1, line))?;
+        ticks.push(tick);
+    }
+    ticks.sort_by_key(|t| t.timestamp);
+    Ok(ticks)
+}
+
+pub fn write_tape(path: impl AsRef<Path>, ticks: &[Tick]) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let mut out = String::with_capacity(ticks.len() * 64);
+    out.push_str(TAPE_HEADER);
+    out.push('\n');
+    for tick in ticks {
+        out.push_str(&tick.to_csv_row());
+        out.push('\n');
+    }
+    fs::write(path, out).with_context(|| format!("Failed to write {:?}", path))
+}
+
 impl MarketSimulator {
     pub fn new() -> Self {
         Self {
-            active_markets: Vec::new(),
-            balance: 10_000.0, // Start with $10k paper money
-            _positions: HashMap::new(),
-            historical_ticks: Vec::new(),
-            current_tick_index: 0,
+            state: Mutex::new(SimState {
+                active_markets: Vec::new(),
+                balance: 10_000.0, // Start with $10k paper money
+                reserved: HashMap::new(),
+                positions: HashMap::new(),
+                engine: MatchingEngine::new(),
+                fills: Vec::new(),
+                in_flight: Vec::new(),
+                dropped: HashSet::new(),
+                dropped_order: VecDeque::new(),
+                historical_ticks: Vec::new(),
+                current_tick_index: 0,
+                current_timestamp: 0,
+            }),
+            latency: LatencyModel::zero(),
         }
     }
 
+    /// Delay orders by `latency`: during a replay an order reaches the book at the first tick
+    /// after its delay (orders still in flight when the tape ends never arrive), otherwise
+    /// `place_order` sleeps for it
+    pub fn with_latency(mut self, latency: LatencyModel) -> Self {
+        self.latency = latency;
+        self
+    }
+
     /// Load mock data for testing
-    pub fn load_markets(&mut self, markets: Vec<MarketData>) {
-        self.active_markets = markets;
-        info!("🎞️  Simulator loaded {} markets", self.active_markets.len());
-    }
-
-    /// Load historical ticks (Mock CSV loader for now)
-    pub fn load_from_csv(&mut self, _path: &str) {
-        // TODO: Implement actual CSV parsing
-        // Creating mock ticks for demonstration
-        self.historical_ticks = vec![
-            Tick {
-                timestamp: 1000,
-                market_id: "mkt1".into(),
-                price: 0.50,
-                volume: 100.0,
-            },
-            Tick {
-                timestamp: 2000,
-                market_id: "mkt1".into(),
-                price: 0.55,
-                volume: 200.0,
-            },
-            Tick {
-                timestamp: 3000,
-                market_id: "mkt1".into(),
-                price: 0.45,
-                volume: 150.0,
-            },
-        ];
+    pub fn load_markets(&self, markets: Vec<MarketData>) {
+        let mut state = self.state.lock().unwrap();
+        state.active_markets = markets;
+        info!("🎞️  Simulator loaded {} markets", state.active_markets.len());
+    }
+
+    /// Load historical ticks from a replay tape (see `read_tape`, `download-history`)
+    pub fn load_from_csv(&self, path: &str) -> Result<()> {
+        self.load_ticks(read_tape(path)?);
+        Ok(())
+    }
+
+    /// Replace the replay tape with the given ticks
+    pub fn load_ticks(&self, ticks: Vec<Tick>) {
+        let mut state = self.state.lock().unwrap();
+        state.historical_ticks = ticks;
+        state.current_tick_index = 0;
         info!(
             "🎞️  Simulator loaded {} historical ticks",
-            self.historical_ticks.len()
+            state.historical_ticks.len()
         );
     }
 
     /// Advance simulation by one tick
-    pub fn next_tick(&mut self) -> Option<&Tick> {
-        if self.current_tick_index >= self.historical_ticks.len() {
+    /// Updates market prices and matches resting orders against the tick's volume
+    pub fn next_tick(&self) -> Option<Tick> {
+        let mut state = self.state.lock().unwrap();
+        if state.current_tick_index >= state.historical_ticks.len() {
             return None;
         }
 
-        let tick = &self.historical_ticks[self.current_tick_index];
-        self.current_tick_index += 1;
+        let tick = state.historical_ticks[state.current_tick_index].clone();
+        state.current_tick_index += 1;
+        // Orders arriving before the tick see the previous prices
+        state.release_in_flight(tick.timestamp.saturating_sub(1));
+        state.current_timestamp = tick.timestamp;
 
         // Update valid market state based on this tick
-        if let Some(market) = self
+        if let Some(market) = state
             .active_markets
             .iter_mut()
             .find(|m| m.id == tick.market_id)
@@ -93,52 +234,384 @@ impl MarketSimulator {
             market.volume += tick.volume;
         }
 
+        // Tick volume is split evenly between the two outcome books
+        let half_volume = tick.volume / 2.0;
+        let mut fills = state
+            .engine
+            .on_tick(&tick.market_id, "YES", tick.price, half_volume);
+        fills.extend(
+            state
+                .engine
+                .on_tick(&tick.market_id, "NO", 1.0 - tick.price, half_volume),
+        );
+        for fill in fills {
+            state.apply_fill(fill);
+        }
+        state.release_in_flight(tick.timestamp);
+
         Some(tick)
     }
+
+    /// All fills produced so far (for backtest reporting)
+    pub fn fills(&self) -> Vec<SimFill> {
+        self.state.lock().unwrap().fills.clone()
+    }
+
+    /// Current simulated position (USD notional) for a market side
+    pub fn position(&self, market_id: &str, side: &str) -> f64 {
+        self.state
+            .lock()
+            .unwrap()
+            .positions
+            .get(&(market_id.to_string(), side.to_string()))
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
+
+impl Default for MarketSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimState {
+    /// Cash not held by open orders
+    fn available(&self) -> f64 {
+        self.balance - self.reserved.values().sum::<f64>()
+    }
+
+    /// Release what `order_id` holds once it can't fill any more
+    fn release_if_done(&mut self, order_id: &str) {
+        let open = self
+            .engine
+            .status(order_id)
+            .map_or(false, |status| !status.is_terminal());
+        if !open && !self.in_flight.iter().any(|o| o.order_id == order_id) {
+            self.reserved.remove(order_id);
+        }
+    }
+
+    fn outcome_price(&self, market_id: &str, side: &str) -> Option<f64> {
+        self.active_markets
+            .iter()
+            .find(|m| m.id == market_id)
+            .map(|m| if side == "YES" { m.yes_price } else { m.no_price })
+    }
+
+    /// Match an order that reached the book against the current price
+    fn submit(&mut self, order: InFlightOrder) -> Result<()> {
+        let market_price = self
+            .outcome_price(&order.market_id, &order.side)
+            .ok_or_else(|| anyhow!("Market {} not found in simulation", order.market_id))?;
+        let now = self.current_timestamp;
+        let fill = self
+            .engine
+            .submit(
+                order.order_id,
+                &order.market_id,
+                &order.side,
+                order.price,
+                order.size_usd,
+                market_price,
+                SIM_TOP_OF_BOOK_USD,
+                order.fill_or_kill,
+                now,
+            )
+            .map_err(|e| anyhow!("[SIM] {}", e))?;
+        if let Some(fill) = fill {
+            self.apply_fill(fill);
+        }
+        Ok(())
+    }
+
+    /// Submit the in-flight orders that have arrived by `now`
+    fn release_in_flight(&mut self, now: u64) {
+        let (arrived, in_flight): (Vec<_>, Vec<_>) = std::mem::take(&mut self.in_flight)
+            .into_iter()
+            .partition(|order| order.arrives_at <= now);
+        self.in_flight = in_flight;
+        for order in arrived {
+            let order_id = order.order_id.clone();
+            if let Err(e) = self.submit(order) {
+                info!("⚡ [SIM] Order {} killed on arrival: {}", order_id, e);
+                self.reserved.remove(&order_id);
+                self.drop_order(order_id);
+            }
+        }
+    }
+
+    /// Record an order that never reached the engine, forgetting the oldest past
+    /// `TERMINAL_RETAINED` like the engine does its terminal orders
+    fn drop_order(&mut self, order_id: String) {
+        if !self.dropped.insert(order_id.clone()) {
+            return;
+        }
+        self.dropped_order.push_back(order_id);
+        while self.dropped_order.len() > TERMINAL_RETAINED {
+            if let Some(oldest) = self.dropped_order.pop_front() {
+                self.dropped.remove(&oldest);
+            }
+        }
+    }
+
+    fn apply_fill(&mut self, fill: SimFill) {
+        info!(
+            "⚡ [SIM] Fill: {} {} ${:.2} @ {:.4} (order {})",
+            fill.market_id, fill.side, fill.size_usd, fill.price, fill.order_id
+        );
+        self.balance -= fill.size_usd;
+        if let Some(held) = self.reserved.get_mut(&fill.order_id) {
+            *held = (*held - fill.size_usd).max(0.0);
+        }
+        self.release_if_done(&fill.order_id);
+        *self
+            .positions
+            .entry((fill.market_id.clone(), fill.side.clone()))
+            .or_insert(0.0) += fill.size_usd;
+        self.fills.push(fill);
+    }
 }
 
 use polymarket_client_sdk::clob::types::OrderType;
 
 #[async_trait]
 impl MarketInterface for MarketSimulator {
+    fn venue(&self) -> Venue {
+        Venue::Simulation
+    }
+
     async fn get_active_markets(&self) -> Result<Vec<MarketData>> {
         // In a real backtester, this would return the slice of markets valid at `current_time`
-        // Should we auto-advance tick here? Or external control?
-        // Ideally Agent calls this in loop.
-        // For simplicity, let's just return current state. The 'runner' drives the ticks.
-        Ok(self.active_markets.clone())
+        // The 'runner' drives the ticks via next_tick().
+        Ok(self.state.lock().unwrap().active_markets.clone())
     }
 
     async fn get_market_details(&self, market_id: &str) -> Result<MarketData> {
-        self.active_markets
+        self.state
+            .lock()
+            .unwrap()
+            .active_markets
             .iter()
             .find(|m| m.id == market_id)
             .cloned()
             .ok_or_else(|| anyhow!("Market {} not found in simulation", market_id))
     }
 
+    /// Cash not held by open orders, as the exchange would let it be spent
     async fn get_balance(&self) -> Result<f64> {
-        Ok(self.balance)
+        Ok(self.state.lock().unwrap().available())
     }
 
     async fn place_order(
         &self,
-        market_id: &str,
+        market_id: &MarketId,
         side: &str,
-        size: f64,
-        price: f64,
-        _order_type: OrderType,
+        size: UsdAmount,
+        price: Price,
+        order_type: OrderType,
     ) -> Result<String> {
-        // Simulate immediate fill at the requested price
+        let (size, price) = (size.get(), price.get());
+        let (decision_ms, fill_ms) = self.latency.sample();
+        let delay_ms = decision_ms + fill_ms;
+        let replaying = !self.state.lock().unwrap().historical_ticks.is_empty();
+        if delay_ms > 0 && !replaying {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        if size > state.available() {
+            anyhow::bail!(
+                "[SIM] Insufficient balance: ${:.2} < ${:.2}",
+                state.available(),
+                size
+            );
+        }
+
+        if state.outcome_price(market_id, side).is_none() {
+            anyhow::bail!("Market {} not found in simulation", market_id);
+        }
+
+        let order_id = format!("sim-order-{}", uuid::Uuid::new_v4());
+        let now = state.current_timestamp;
+        let order = InFlightOrder {
+            order_id: order_id.clone(),
+            market_id: market_id.to_string(),
+            side: side.to_string(),
+            price,
+            size_usd: size,
+            fill_or_kill: matches!(order_type, OrderType::FOK),
+            placed_at: now,
+            arrives_at: now + delay_ms,
+        };
+
         info!(
-            "⚡ [SIM] Order Placed: {} {} @ ${:.2} on {}",
-            side, size, price, market_id
+            "⚡ [SIM] Order Placed: {} ${:.2} @ ${:.2} on {} ({:?})",
+            side, size, price, market_id, order_type
         );
 
-        // In a real simulator, we'd deduct balance, update positions, check liquidity etc.
-        // For now, simple logging success.
+        state.reserved.insert(order_id.clone(), size);
+        if delay_ms > 0 && replaying {
+            // A killed FOK can't fail the call any more: its status turns Cancelled instead
+            state.in_flight.push(order);
+        } else if let Err(e) = state.submit(order) {
+            state.reserved.remove(&order_id);
+            return Err(e);
+        }
+
+        Ok(order_id)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(i) = state.in_flight.iter().position(|o| o.order_id == order_id) {
+            state.in_flight.remove(i);
+            state.reserved.remove(order_id);
+            state.drop_order(order_id.to_string());
+            info!("⚡ [SIM] Cancelled {} before it reached the book", order_id);
+            Ok(())
+        } else if state.engine.cancel(order_id) {
+            state.reserved.remove(order_id);
+            info!("⚡ [SIM] Cancelled {}", order_id);
+            Ok(())
+        } else {
+            Err(anyhow!("[SIM] Order {} not open", order_id))
+        }
+    }
+
+    async fn get_open_orders(&self, market_id: Option<&str>) -> Result<Vec<OpenOrder>> {
+        let state = self.state.lock().unwrap();
+        let in_flight = state
+            .in_flight
+            .iter()
+            .filter(|o| market_id.map_or(true, |m| o.market_id == m))
+            .map(InFlightOrder::to_open_order);
+        Ok(state
+            .engine
+            .open_orders(market_id)
+            .iter()
+            .map(|o| o.to_open_order())
+            .chain(in_flight)
+            .collect())
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        let state = self.state.lock().unwrap();
+        if state.in_flight.iter().any(|o| o.order_id == order_id) {
+            return Ok(OrderStatus::Live);
+        }
+        if state.dropped.contains(order_id) {
+            return Ok(OrderStatus::Cancelled);
+        }
+        Ok(state
+            .engine
+            .status(order_id)
+            .unwrap_or(OrderStatus::Unknown))
+    }
+
+    async fn get_orderbook(&self, asset_id: &str) -> Result<OrderBook> {
+        let state = self.state.lock().unwrap();
+
+        // Synthesize a one-level book around the simulated price of the matching outcome
+        let market = state
+            .active_markets
+            .iter()
+            .find(|m| m.asset_ids.iter().any(|a| a == asset_id) || m.id == asset_id)
+            .ok_or_else(|| anyhow!("Asset {} not found in simulation", asset_id))?;
+
+        // asset_ids follow the [NO, YES] convention
+        let side = if market.asset_ids.first().map(|a| a.as_str()) == Some(asset_id) {
+            "NO"
+        } else {
+            "YES"
+        };
+        let price = if side == "YES" {
+            market.yes_price
+        } else {
+            market.no_price
+        };
+
+        // Our own resting bids are part of the simulated book
+        let mut bids: Vec<OrderLevel> = state
+            .engine
+            .open_orders(Some(market.id.as_str()))
+            .iter()
+            .filter(|o| o.side == side)
+            .map(|o| OrderLevel {
+                price: o.price,
+                size: o.remaining(),
+            })
+            .collect();
+        bids.push(OrderLevel {
+            price: (price - 0.01).max(0.01),
+            size: SIM_TOP_OF_BOOK_USD,
+        });
+        bids.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap_or(std::cmp::Ordering::Equal));
+
+        let ask = OrderLevel {
+            price,
+            size: SIM_TOP_OF_BOOK_USD,
+        };
+        Ok(OrderBook::from_levels(&bids, &[ask]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_orders_are_evicted_past_the_retention() {
+        let simulator = MarketSimulator::new();
+        let mut state = simulator.state.lock().unwrap();
+        for i in 0..=TERMINAL_RETAINED {
+            state.drop_order(format!("o{}", i));
+        }
+        assert_eq!(state.dropped.len(), TERMINAL_RETAINED);
+        assert!(!state.dropped.contains("o0"));
+        assert!(state.dropped.contains("o1"));
+    }
+
+    #[tokio::test]
+    async fn test_resting_orders_hold_their_balance() {
+        let simulator = MarketSimulator::new();
+        simulator.load_markets(vec![MarketData {
+            id: "m1".into(),
+            yes_price: 0.5,
+            no_price: 0.5,
+            ..Default::default()
+        }]);
+        let market_id = MarketId::from("m1");
+        let place = |size: f64| {
+            simulator.place_order(
+                &market_id,
+                "YES",
+                UsdAmount::new(size),
+                Price::new(0.40),
+                OrderType::GTC,
+            )
+        };
+
+        // A bid under the market rests and holds its $6000
+        let resting = place(6_000.0).await.unwrap();
+        assert!((simulator.get_balance().await.unwrap() - 4_000.0).abs() < 1e-9);
+        assert!(place(6_000.0).await.is_err());
+
+        // Half of it fills: the cash is spent, the rest stays held
+        let fills = {
+            let mut state = simulator.state.lock().unwrap();
+            state.engine.on_tick("m1", "YES", 0.40, 3_000.0)
+        };
+        for fill in fills {
+            simulator.state.lock().unwrap().apply_fill(fill);
+        }
+        assert!((simulator.get_balance().await.unwrap() - 4_000.0).abs() < 1e-9);
 
-        Ok(format!("sim-order-{}", uuid::Uuid::new_v4()))
+        // Cancelling releases what's left
+        simulator.cancel_order(&resting).await.unwrap();
+        assert!((simulator.get_balance().await.unwrap() - 7_000.0).abs() < 1e-9);
+        assert!(simulator.state.lock().unwrap().reserved.is_empty());
     }
 }
 
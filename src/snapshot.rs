@@ -0,0 +1,220 @@
+//! Full-state snapshots for fast restarts
+//!
+//! A cold start re-detects everything: the first poll scans every active market and new ones
+//! wait for Gamma again. Every `STATE_SNAPSHOT_INTERVAL_SECS` and on Ctrl+C the agent writes its
+//! registry, positions, stop-loss ladders (with their resting orders), market lifecycles,
+//! metadata retry queue and PnL to `data_dir/state_snapshot.json`; started with `--restore`
+//! it resumes from there instead.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::analytics::pnl::{PortfolioSnapshot, Position, Trade};
+use crate::analytics::PnLTracker;
+use crate::execution::LiquidationLadder;
+use crate::markets::{AssetRef, MarketLifecycle};
+use crate::polymarket::MarketData;
+use crate::strategies::risk;
+
+pub const STATE_SNAPSHOT_FILE: &str = "state_snapshot.json";
+pub const PAPER_BOOK_FILE: &str = "paper_book.json";
+
+/// PnL tracker state of the run (calendar and journal are persisted on their own)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PnlState {
+    pub initial_capital: f64,
+    pub cash: f64,
+    pub positions: Vec<Position>,
+    pub trades: Vec<Trade>,
+    pub snapshots: Vec<PortfolioSnapshot>,
+}
+
+impl PnlState {
+    pub fn capture(tracker: &PnLTracker) -> Self {
+        Self {
+            initial_capital: tracker.initial_capital,
+            cash: tracker.cash,
+            positions: tracker.positions.values().cloned().collect(),
+            trades: tracker.trades.clone(),
+            snapshots: tracker.snapshots.clone(),
+        }
+    }
+
+    pub fn apply(self, tracker: &mut PnLTracker) {
+        tracker.initial_capital = self.initial_capital;
+        tracker.cash = self.cash;
+        tracker.positions = self
+            .positions
+            .into_iter()
+            .map(|position| (position.id.clone(), position))
+            .collect();
+        tracker.trades = self.trades;
+        tracker.snapshots = self.snapshots;
+    }
+}
+
+/// Everything a restart needs to pick up where the previous run stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub taken_at: DateTime<Utc>,
+    /// Session that wrote it
+    pub session_id: String,
+    pub markets: Vec<MarketData>,
+    pub assets: Vec<(String, AssetRef)>,
+    /// Risk manager positions
+    pub positions: Vec<risk::Position>,
+    /// Stop-loss exits in progress, with the order resting on the book
+    pub liquidations: Vec<LiquidationLadder>,
+    /// Every market seen, with its state and metadata attempts
+    pub lifecycle: Vec<MarketLifecycle>,
+    /// Markets awaiting another Gamma metadata fetch, in queue order
+    pub metadata_retries: Vec<String>,
+    pub pnl: PnlState,
+}
+
+impl StateSnapshot {
+    /// Write to `data_dir/state_snapshot.json`: written and synced to a temp file, then renamed
+    /// over the previous one, so a crash leaves either snapshot whole
+    pub fn save(&self, data_dir: &str) -> Result<()> {
+        fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create data dir {}", data_dir))?;
+        let path = state_snapshot_path(data_dir);
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp).with_context(|| format!("Failed to create {:?}", tmp))?;
+        serde_json::to_writer(&mut file, self)?;
+        file.flush()?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+
+    /// The last snapshot written to `data_dir`, None if there is none
+    pub fn load(data_dir: &str) -> Result<Option<Self>> {
+        let path = state_snapshot_path(data_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&raw)
+            .map(Some)
+            .context("Invalid state snapshot")
+    }
+
+    /// Seconds since it was taken
+    pub fn age_secs(&self) -> i64 {
+        (Utc::now() - self.taken_at).num_seconds()
+    }
+}
+
+/// Positions and PnL of the paper-only strategies of a live run, kept across runs (restored
+/// without `--restore`, like the wallet the live book stands for)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaperBook {
+    pub positions: Vec<risk::Position>,
+    pub pnl: PnlState,
+}
+
+impl PaperBook {
+    /// Write to `data_dir/paper_book.json` through a synced temp file
+    pub fn save(&self, data_dir: &str) -> Result<()> {
+        fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create data dir {}", data_dir))?;
+        let path = Path::new(data_dir).join(PAPER_BOOK_FILE);
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp).with_context(|| format!("Failed to create {:?}", tmp))?;
+        serde_json::to_writer(&mut file, self)?;
+        file.flush()?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+
+    /// The book the previous run left in `data_dir`, None if there is none
+    pub fn load(data_dir: &str) -> Result<Option<Self>> {
+        let path = Path::new(data_dir).join(PAPER_BOOK_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&raw)
+            .map(Some)
+            .context("Invalid paper book")
+    }
+}
+
+/// Path of the state snapshot inside a data dir
+pub fn state_snapshot_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(STATE_SNAPSHOT_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markets::{LifecycleTracker, MarketState};
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir = std::env::temp_dir().join(format!("snapshot-{}", uuid::Uuid::new_v4()));
+        let data_dir = dir.to_str().unwrap();
+        assert!(StateSnapshot::load(data_dir).unwrap().is_none());
+
+        let mut lifecycle = LifecycleTracker::new();
+        lifecycle.advance("m1", MarketState::Tradable);
+        lifecycle.advance("m2", MarketState::DerivedAssets);
+        lifecycle.queue_metadata_retry("m2");
+        let mut tracker = PnLTracker::new(1_000.0);
+        tracker.cash = 750.0;
+
+        let snapshot = StateSnapshot {
+            taken_at: Utc::now(),
+            session_id: "s1".to_string(),
+            markets: Vec::new(),
+            assets: vec![(
+                "yes-token".to_string(),
+                AssetRef {
+                    market_id: "m1".to_string(),
+                    side: "YES".to_string(),
+                },
+            )],
+            positions: Vec::new(),
+            liquidations: Vec::new(),
+            lifecycle: lifecycle.markets(),
+            metadata_retries: lifecycle.metadata_retry_queue(),
+            pnl: PnlState::capture(&tracker),
+        };
+        snapshot.save(data_dir).unwrap();
+        snapshot.save(data_dir).unwrap(); // Replaces the previous one
+        assert!(!state_snapshot_path(data_dir)
+            .with_extension("json.tmp")
+            .exists());
+
+        let restored = StateSnapshot::load(data_dir).unwrap().unwrap();
+        assert_eq!(restored.session_id, "s1");
+        assert_eq!(restored.assets, snapshot.assets);
+
+        let mut resumed = LifecycleTracker::new();
+        resumed.restore(restored.lifecycle, restored.metadata_retries);
+        assert_eq!(resumed.state("m1"), Some(MarketState::Tradable));
+        assert_eq!(
+            resumed.next_metadata_retries(10),
+            vec![("m2".to_string(), 1)]
+        );
+
+        let mut fresh = PnLTracker::new(1_000.0);
+        restored.pnl.apply(&mut fresh);
+        assert_eq!(fresh.cash, 750.0);
+
+        assert!(PaperBook::load(data_dir).unwrap().is_none());
+        PaperBook {
+            positions: Vec::new(),
+            pnl: PnlState::capture(&tracker),
+        }
+        .save(data_dir)
+        .unwrap();
+        assert_eq!(PaperBook::load(data_dir).unwrap().unwrap().pnl.cash, 750.0);
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -1,821 +1,6960 @@
 use anyhow::Result;
 use ethers::types::Address;
+use futures_util::stream::{self, StreamExt};
+use polymarket_client_sdk::clob::types::OrderType;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc;
-use tokio::time::{interval, Duration};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use crate::analytics::{pnl::Position, PnLTracker};
-use crate::config::Config;
-use crate::execution::{Executor, RedemptionManager};
-use crate::polymarket::contracts::derive_asset_ids;
-use crate::polymarket::ws::{ClobWebSocket, OrderbookUpdate};
+use crate::alloc_profile;
+use crate::analytics::cost_basis::CostBasisBackfill;
+use crate::analytics::decisions::StrategyConfigSnapshot;
+use crate::analytics::resolutions::winner_from_prices;
+use crate::analytics::{
+    pnl::Position, DecisionLog, DecisionLogMode, DriftSample, DriftTracker, EdgeRealization,
+    EdgeRealizationTracker, ExecutionDriftTracker, IndexingLatencyTracker, OpportunityBook,
+    OpportunityRecord, PnLTracker, PostmortemLog, ResolutionRecorder, SessionRecord,
+};
+use crate::api::{ApiAuth, ApiState, SummaryCache};
+use crate::clock;
+use crate::config::{Config, SeriesWatchConfig};
+use crate::events::{AgentEvent, EventBus};
+use crate::execution::{
+    choose_role, gas, missed_window, role, AnomalyGuard, CapitalRelease, CapitalWait,
+    CollateralSwapper, CrossVenueExecutor, DeferredAction, DeferredSignal, ExecutionPlan, Executor,
+    FlashbotsClient, FundingManager, GasCost, LatencyBudget, LegBook, LiquidationLadder,
+    LiquidityRole, MarginBook, MissedWindow, MissedWindowEntry, MissedWindows, Obfuscator,
+    OrderWal, PaperRelay, RedemptionCandidate, RedemptionManager, RedemptionResult, RemoteExecutor,
+    RestingMakerLeg, SettlementReconciler, SubmissionQueue, TakenLeg, TxOutcome, WalInterface,
+    WalOutcome,
+};
+use crate::exit_report::{self, ExitReason, ExitReport, PendingRetries, ShutdownHandle};
+use crate::failover::Failover;
+use crate::ids::{Price, UsdAmount};
+use crate::llm_budget::LlmBudget;
+use crate::logging;
+use crate::markets::{
+    depth, normalize_market_id, AskLadders, AssetRef, BookShape, ConditionId, ExpirationWatchlist,
+    FeatureFrame, FeatureTracker, FeeRates, LifecycleTracker, LlmClassifier, MarketCategory,
+    MarketRegistry, MarketState, PinKey, PinRequest, PinnedMarkets, QuoteFreshness,
+    ResolutionProposals, SeriesWatcher, Staleness, SubscriptionSet, SuspensionTracker,
+    SyntheticMarkets, ToxicityTracker, WhaleTracker, ZombieEvent, ZombieTracker,
+};
+use crate::ml::{self, FeatureRecorder};
+use crate::monitor::{self, SelfMonitor, SelfReport};
+use crate::policy;
+use crate::polymarket::breaker::BreakerState;
+use crate::polymarket::collateral::required_collateral;
+use crate::polymarket::throttle::TickThrottle;
+use crate::polymarket::ws::{ClobWebSocket, OrderbookUpdate, PriceLevel};
 use crate::polymarket::{
-    MarketData, MarketEventListener, MarketInterface, MempoolMonitor, PolymarketClient,
+    is_circuit_open, update_queue, ActivityFeed, CircuitBreakers, CollateralBalances,
+    DerivedAssetCache, DropPolicy, MarketData, MarketEventListener, MarketInterface,
+    MempoolMonitor, MetadataCache, NegRiskGroups, OpenOrder, OrderBook, OrderStatus,
+    PolymarketClient, PositionCollateral, QuoteQuality, ReconnectBackoff, TokenTransfer,
+    UpdateReceiver, TRANSFER_QUEUE,
 };
 use crate::pricefeed::BinanceClient;
-use crate::simulation::MarketSimulator;
-use crate::strategies::arbitrage::{ArbitrageStrategy, TradeAction};
+use crate::profiles::ProfileSchedule;
+use crate::report::{self, DailyReporter};
+use crate::scheduling::{Job, JobQueue, OverrunChange, Scheduler};
+use crate::simulation::{ChaosInterface, ChaosMonkey, LatencyModel, MarketSimulator};
+use crate::snapshot::{PaperBook, PnlState, StateSnapshot};
+use crate::storage::{self, MarketStore, PositionStore, SeenMarketStore, StorageOp, StorageWriter};
+use crate::strategies::arb_exit::{self, ArbExit, ArbPairQuote};
+use crate::strategies::arbitrage::{
+    ArbitrageInputs, ArbitrageStrategy, TradeAction, FEE_PER_TRADE_BPS,
+};
+use crate::strategies::cross_venue::{
+    self, kalshi_position_key, CrossVenuePosition, CrossVenueStrategy,
+};
+use crate::strategies::dislocation::DislocationStrategy;
+use crate::strategies::edge_tuning::{observe_fill, EdgeTuner};
+use crate::strategies::experiment::Experiment;
 use crate::strategies::expiration::ExpirationStrategy;
+use crate::strategies::hedging::{hedge_trade_id, HedgeBook, HedgeOrder, Hedger, PendingSnipe};
+use crate::strategies::neg_risk::{
+    self, NegRiskBasket, NegRiskBaskets, NegRiskQuote, NegRiskRoute, MAX_CONVERT_ATTEMPTS,
+};
 use crate::strategies::predictive::PredictiveStrategy;
-use crate::strategies::risk::RiskManager;
-use chrono::Utc;
-use std::collections::VecDeque;
+use crate::strategies::risk::{self, RiskManager};
+use crate::strategies::Strategy;
+use crate::telemetry::{Percentiles, TelemetryPusher, TelemetrySnapshot};
+use crate::venues::{load_pairs, KalshiClient, Venue};
+use chrono::{DateTime, Utc};
 use std::collections::{HashMap, HashSet};
 
+/// Max book updates merged into one decision cycle
+const WS_COALESCE_MAX_BATCH: usize = 256;
+
+/// How long after placement an order's fill quality is checked
+const FILL_CHECK_DELAY: Duration = Duration::from_secs(2);
+
+/// Ended markets of the resolution dataset checked for their outcome per redemption tick
+const RESOLUTION_CHECKS_PER_TICK: usize = 50;
+/// Of those, checks in flight at once
+const RESOLUTION_CHECKS_IN_FLIGHT: usize = 10;
+
+/// Recurring work of the run loop, registered with its `Scheduler`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Maintenance {
+    MetadataRetry,
+    NewMarketPoll,
+    MarketPoll,
+    Watchlist,
+    ResolutionSample,
+    CrossVenue,
+    PinnedMarkets,
+    Redemption,
+    PnlUpdate,
+    QueueStats,
+    SelfMonitor,
+    ArbExit,
+    DailyReport,
+    Telemetry,
+    CapitalWait,
+    Profile,
+    StateSnapshot,
+    Funding,
+    RegistrySave,
+    ExperimentReport,
+    Zombies,
+    FeeRates,
+    SeriesWatch,
+    GasPrice,
+    MakerLegs,
+    CompactWal,
+}
+
+/// An entry whose obfuscation delay runs off the loop
+struct JitteredSignal {
+    signal: DeferredSignal,
+    /// `clock` time its latency budget runs from
+    signal_ns: u64,
+}
+
+/// A redemption batch sent off the loop, with the winners read for its conditions
+struct RedemptionRound {
+    results: Vec<RedemptionResult>,
+    winners: HashMap<String, Option<&'static str>>,
+}
+
+/// Balances and open orders read off the loop, to be applied here
+struct BalanceRefresh {
+    balances: CollateralBalances,
+    /// None when the order list couldn't be read
+    open_orders: Option<Vec<OpenOrder>>,
+}
+
+/// How a resolved position closes
+#[derive(Clone, Copy)]
+enum Closing {
+    /// Nothing to collect, no transaction
+    Lost,
+    /// Redeemed for this much
+    Redeemed(f64),
+    /// Not worth its gas after `REDEEM_MAX_WAIT_SECS`: booked at nothing
+    WrittenOff,
+}
+
+/// An arbitrage group's merge sent on-chain, booked once its receipt is in
+struct PendingMerge {
+    market_id: String,
+    tx_hash: String,
+    pairs: f64,
+}
+
+/// The listed questions of a neg-risk market with their NO asks
+struct BasketQuote {
+    neg_risk_market_id: String,
+    /// (question index, market, NO ask)
+    questions: Vec<(u8, MarketData, f64)>,
+}
+
 pub struct Sniper {
     config: Config,
     market_interface: Arc<dyn MarketInterface + Send + Sync>,
+    breakers: Arc<CircuitBreakers>, // Gamma / CLOB REST / RPC upstream breakers
     risk_manager: RiskManager,
     _start_time: chrono::DateTime<chrono::Utc>, // Keep track of uptime
     strategy: ArbitrageStrategy,
     expiration_strategy: ExpirationStrategy,
     predictive_strategy: PredictiveStrategy,
+    dislocation_strategy: DislocationStrategy,
     executor: Executor,
     _mempool_monitor: MempoolMonitor,
-    redemption_manager: Option<RedemptionManager>,
-    seen_markets: HashSet<String>,
+    redemption_manager: Option<Arc<RedemptionManager>>,
+    // Redemption batches sent off the loop, to be booked here (one at a time)
+    redeemed_tx: mpsc::UnboundedSender<RedemptionRound>,
+    redeemed_rx: mpsc::UnboundedReceiver<RedemptionRound>,
+    redeeming: Arc<AtomicBool>,
+    // Balance refreshes read off the loop (for the jobs that can use the cached one meanwhile)
+    balance_tx: mpsc::UnboundedSender<BalanceRefresh>,
+    balance_rx: mpsc::UnboundedReceiver<BalanceRefresh>,
+    refreshing_balance: Arc<AtomicBool>,
+    collateral_swapper: Option<CollateralSwapper>, // USDC -> USDC.e top-ups (opt-in)
+    /// The `executor` process, when it holds the key: swaps and funding are its to run
+    remote_executor: Option<Arc<RemoteExecutor>>,
+    funding: Option<Arc<FundingManager>>, // Mainnet / CEX top-ups (opt-in)
+    order_wal: Option<Arc<OrderWal>>,     // Live orders in flight (compacted periodically)
+    lifecycle: Arc<Mutex<LifecycleTracker>>, // Per-market state machine (detection -> redemption)
     pnl_tracker: Arc<Mutex<PnLTracker>>,
-    new_market_rx: Option<mpsc::UnboundedReceiver<String>>, // From WebSocket events
-    pending_retries: VecDeque<(String, u8)>,                // (MarketID, RetryCount)
+    // Book of the paper-only strategies of a live run (None when every strategy trades alike)
+    paper_pnl: Option<Arc<Mutex<PnLTracker>>>,
+    // Their positions, kept apart from the live ones the redeemer and margin view work on
+    paper_risk: RiskManager,
+    new_market_rx: Option<mpsc::UnboundedReceiver<ConditionId>>, // From WebSocket events
+    // Conditions resolving on-chain, redeemed right away when held (None without redemption)
+    resolution_rx: Option<mpsc::UnboundedReceiver<ConditionId>>,
+    // ERC-1155 transfers of the funder wallet (None unless settlement is watched)
+    transfer_rx: Option<mpsc::Receiver<TokenTransfer>>,
+    jobs: JobQueue,                                         // Prioritized decision work
+    // Timers of the run loop's maintenance tasks
+    scheduler: Scheduler<Maintenance>,
     // WebSocket CLOB
     ws_client: Option<ClobWebSocket>,
-    ws_update_rx: Option<mpsc::Receiver<OrderbookUpdate>>,
-    active_markets: HashMap<String, MarketData>,
-    asset_map: HashMap<String, (String, String)>, // AssetID -> (MarketID, Side)
-    subscribed_assets: HashSet<String>,
+    ws_update_rx: Option<UpdateReceiver>,
+    // WS queue saturations already warned about
+    ws_queue_saturations: u64,
+    registry: MarketRegistry, // Market state + AssetID -> (MarketID, Side), shared and persisted
+    subscriptions: Arc<Mutex<SubscriptionSet>>, // CLOB WS assets, LRU-capped
     // Caching
     cached_balance: f64,
     last_balance_update: std::time::Instant,
+    // Polygon gas price, refreshed by the scheduler so signals don't wait on the RPC
+    cached_gas_gwei: f64,
+    // Collateral locked in resting orders / positions / pending redemptions vs free
+    margin: Arc<Mutex<MarginBook>>,
+    // Postmortem recording
+    decision_log: Option<DecisionLog>,
+    // A/B strategy experiment (treatment arm)
+    experiment: Option<Experiment>,
+    // Per-category min_edge learned from fill quality
+    edge_tuner: Option<Arc<Mutex<EdgeTuner>>>,
+    // On-chain -> Gamma -> tradeable quote timing for new markets
+    indexing_latency: Arc<Mutex<IndexingLatencyTracker>>,
+    // Stop-loss exits being worked out (market_id -> ladder)
+    liquidations: HashMap<String, LiquidationLadder>,
+    // Config snapshot + hash for this run
+    session: Arc<SessionRecord>,
+    // Every evaluated arbitrage edge, near misses included
+    opportunities: Option<Arc<Mutex<OpportunityBook>>>,
+    // Expected vs realized edge per executed arbitrage
+    edge_realization: Arc<Mutex<EdgeRealizationTracker>>,
+    // Best ask drift between a signal and its order post, by latency
+    execution_drift: Arc<Mutex<ExecutionDriftTracker>>,
+    // Cause of each losing or failed trade
+    postmortems: Arc<Mutex<PostmortemLog>>,
+    // Fills reconciled against the tokens that moved on-chain (live trading only)
+    settlement: Option<Arc<Mutex<SettlementReconciler>>>,
+    // Suspended / one-sided books on live markets
+    suspension: SuspensionTracker,
+    /// Policy for markets traded on derived ids before their metadata loads
+    synthetic: SyntheticMarkets,
+    /// Risk limits, sizes and strategies switched on a schedule (None without a profiles file)
+    profiles: Option<Arc<Mutex<ProfileSchedule>>>,
+    /// Size and timing jitter of entries
+    obfuscator: Obfuscator,
+    // How often our orders get picked off per market (live trading only)
+    toxicity: Option<Arc<Mutex<ToxicityTracker>>>,
+    // Age of each asset's last book update, checked before entries
+    quote_freshness: QuoteFreshness,
+    /// Ask depth of each WS book, for the arbitrage dust guard
+    ask_ladders: AskLadders,
+    // Token ids derived from condition ids, persisted
+    derived_assets: DerivedAssetCache,
+    // Gamma metadata shared with the Polymarket clients, persisted (None when off)
+    metadata_cache: Option<MetadataCache>,
+    // Adapter market of each neg-risk question Gamma listed
+    neg_risk_groups: NegRiskGroups,
+    // NO baskets bought to convert, by plan id, until converted (persisted)
+    neg_risk_baskets: NegRiskBaskets,
+    // Posted arbitrage legs waiting for their fill (ORDER_ROLE_SELECTION)
+    maker_legs: Vec<RestingMakerLeg>,
+    // Early merges of arbitrage groups waiting for their receipt, by group id
+    pending_merges: HashMap<String, PendingMerge>,
+    // Order flow imbalance / queue depletion per asset from the L2 stream (opt-out)
+    features: Option<Arc<Mutex<FeatureTracker>>>,
+    // Feature vectors of predictive candidates, for training models (opt-in)
+    feature_recorder: Option<Mutex<FeatureRecorder>>,
+    // Trades, positions, snapshots, registry and seen markets mirrored to the database
+    storage: Option<StorageWriter>,
+    // Categorizes markets the keyword rules leave as Other (opt-in)
+    llm_classifier: Option<Arc<LlmClassifier>>,
+    // Rate limit, cache and cost ledger of the classifier's calls
+    llm_budget: Option<Arc<Mutex<LlmBudget>>>,
+    classify_requested: HashSet<String>,
+    // Markets close to expiry: kept subscribed and re-evaluated at a high frequency
+    watchlist: Arc<Mutex<ExpirationWatchlist>>,
+    // Markets pinned by the operator through the watchlist API
+    pins: Arc<Mutex<PinnedMarkets>>,
+    // Pinned markets fetched off the loop (pin key, market), to be evaluated here
+    pinned_tx: mpsc::UnboundedSender<Vec<(String, MarketData)>>,
+    pinned_rx: mpsc::UnboundedReceiver<Vec<(String, MarketData)>>,
+    pins_polling: Arc<AtomicBool>,
+    // A state snapshot is being written off the loop
+    snapshot_saving: Arc<AtomicBool>,
+    // Execution anomalies that switch a live run to paper trading (live runs only)
+    anomaly_guard: Option<Arc<AnomalyGuard>>,
+    // Set when the anomaly guard trips with ANOMALY_HALT: the run loop stops on it
+    risk_halt: Option<String>,
+    // Recent large trades from the public activity feed
+    whales: Arc<Mutex<WhaleTracker>>,
+    // Signals dropped for running over their latency budget
+    missed_windows: Arc<Mutex<MissedWindows>>,
+    // Resolved-but-open markets, skipped by the strategies
+    zombies: Arc<Mutex<ZombieTracker>>,
+    // Per-market fee rates from the CLOB (fee-free promotional markets)
+    fee_rates: FeeRates,
+    // Event series Gamma listed for the first time (SERIES_WATCH_ENABLED)
+    series_watcher: Option<Arc<Mutex<SeriesWatcher>>>,
+    series_polling: Arc<AtomicBool>,
+    // Markets Gamma has a UMA resolution proposed for
+    resolution_proposals: ResolutionProposals,
+    // Complementary-outcome hedges of predictive / expiration positions (opt-in)
+    hedger: Option<Arc<Mutex<Hedger>>>,
+    // Order status (and share matched) of snipes to hedge, read off the loop
+    snipe_fill_tx: mpsc::UnboundedSender<(PendingSnipe, OrderStatus, f64)>,
+    snipe_fill_rx: mpsc::UnboundedReceiver<(PendingSnipe, OrderStatus, f64)>,
+    // Live orders vs their paper twins (live runs only)
+    drift: Option<Arc<Mutex<DriftTracker>>>,
+    // Paper trading engine each live order is mirrored to, in parallel, for the drift report
+    paper_twin: Option<Arc<dyn MarketInterface + Send + Sync>>,
+    // Last-hours prices of markets awaiting their outcome (resolution dataset, needs storage)
+    resolutions: Option<ResolutionRecorder>,
+    // Complete sets bought across Polymarket and Kalshi (opt-in)
+    cross_venue: Option<CrossVenueStrategy>,
+    cross_venue_executor: Option<Arc<CrossVenueExecutor>>,
+    // Sets a scan running off the loop executed, to be opened and booked here
+    cross_venue_tx: mpsc::UnboundedSender<CrossVenuePosition>,
+    cross_venue_rx: mpsc::UnboundedReceiver<CrossVenuePosition>,
+    cross_venue_scanning: Arc<AtomicBool>,
+    // Entries back from their obfuscation delay (OBFUSCATION_TIMING_JITTER_MS)
+    jittered_tx: mpsc::UnboundedSender<JitteredSignal>,
+    jittered_rx: mpsc::UnboundedReceiver<JitteredSignal>,
+    // Set while a jittered entry is placed, so it isn't delayed again
+    jitter_released: bool,
+    // Periodic memory / task / map / channel report with alert limits
+    self_monitor: SelfMonitor,
+    // Daily PnL / health summary by webhook or email (opt-in)
+    daily_report: DailyReporter,
+    // Anonymized aggregate metrics pushed to the user's collector (opt-in)
+    telemetry: TelemetryPusher,
+    // High-edge signals held until redemption proceeds fund them (opt-in)
+    capital_wait: CapitalWait,
+    /// When the waiting signal being placed first waited, so deferring it again keeps that
+    deferred_since: Option<DateTime<Utc>>,
+    // Binance prices behind the predictive / dislocation strategies, outlier-filtered
+    price_feed: Arc<BinanceClient>,
+    // Hot-standby protocol with the peer instance; fences order entry (opt-in)
+    failover: Option<Arc<Failover>>,
+    // Socket read time (monotonic ns) of the book update behind the evaluation in progress
+    evaluation_received_ns: Option<u64>,
+    // Strategies added by an embedding project, run after the built-in ones
+    custom_strategies: Vec<Arc<dyn Strategy>>,
+    // Signals, entries and closes for embedders
+    events: EventBus,
+    // Stops `run` for an embedding host, as SIGTERM does
+    shutdown: ShutdownHandle,
 }
 
-impl Sniper {
-    pub async fn new(config: Config, pnl_tracker: Arc<Mutex<PnLTracker>>) -> Result<Self> {
+/// How `SniperBuilder` sources an optional component
+enum Component<T> {
+    /// Set up from the config, as `Sniper::new` does
+    Default,
+    /// Supplied by the caller
+    Given(T),
+    /// Run without it
+    Omitted,
+}
+
+/// Assembles a `Sniper`, with any of its optional components supplied by the caller or left
+/// out. Whatever isn't set is set up from the config exactly as `Sniper::new` does
+pub struct SniperBuilder {
+    config: Config,
+    pnl_tracker: Option<Arc<Mutex<PnLTracker>>>,
+    market_interface: Option<Arc<dyn MarketInterface + Send + Sync>>,
+    ws: Component<(Option<ClobWebSocket>, UpdateReceiver)>,
+    new_markets: Component<mpsc::UnboundedReceiver<ConditionId>>,
+    redemption: Component<RedemptionManager>,
+    flashbots: Component<FlashbotsClient>,
+}
+
+impl SniperBuilder {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            pnl_tracker: None,
+            market_interface: None,
+            ws: Component::Default,
+            new_markets: Component::Default,
+            redemption: Component::Default,
+            flashbots: Component::Default,
+        }
+    }
+
+    /// Share a PnL tracker with the caller (default: a fresh one on $1000)
+    pub fn pnl_tracker(mut self, pnl_tracker: Arc<Mutex<PnLTracker>>) -> Self {
+        self.pnl_tracker = Some(pnl_tracker);
+        self
+    }
+
+    /// Read markets and place orders through `market_interface` (another venue, a test
+    /// double) instead of the Polymarket client or simulator the config picks
+    pub fn market_interface(
+        mut self,
+        market_interface: Arc<dyn MarketInterface + Send + Sync>,
+    ) -> Self {
+        self.market_interface = Some(market_interface);
+        self
+    }
+
+    /// Use an already connected CLOB WebSocket and the queue its updates arrive on
+    pub fn ws_client(mut self, ws: ClobWebSocket, updates: UpdateReceiver) -> Self {
+        self.ws = Component::Given((Some(ws), updates));
+        self
+    }
+
+    /// Take book updates from `updates` with no CLOB WebSocket (synthetic or replayed feeds):
+    /// nothing is subscribed or unsubscribed
+    pub fn book_feed(mut self, updates: UpdateReceiver) -> Self {
+        self.ws = Component::Given((None, updates));
+        self
+    }
+
+    /// No CLOB WebSocket: prices come from polling only
+    pub fn without_ws(mut self) -> Self {
+        self.ws = Component::Omitted;
+        self
+    }
+
+    /// Take new condition ids from `new_markets` instead of the CTF event listener
+    pub fn new_market_feed(mut self, new_markets: mpsc::UnboundedReceiver<ConditionId>) -> Self {
+        self.new_markets = Component::Given(new_markets);
+        self
+    }
+
+    /// No CTF event listener: new markets are found by polling only
+    pub fn without_event_listener(mut self) -> Self {
+        self.new_markets = Component::Omitted;
+        self
+    }
+
+    pub fn redemption_manager(mut self, manager: RedemptionManager) -> Self {
+        self.redemption = Component::Given(manager);
+        self
+    }
+
+    /// No on-chain redemptions or merges
+    pub fn without_redemption(mut self) -> Self {
+        self.redemption = Component::Omitted;
+        self
+    }
+
+    pub fn flashbots_client(mut self, client: FlashbotsClient) -> Self {
+        self.flashbots = Component::Given(client);
+        self
+    }
+
+    /// Regular submission only, no Flashbots bundles (real or paper)
+    pub fn without_flashbots(mut self) -> Self {
+        self.flashbots = Component::Omitted;
+        self
+    }
+
+    pub async fn build(self) -> Result<Sniper> {
+        let SniperBuilder {
+            config,
+            pnl_tracker,
+            market_interface,
+            ws,
+            new_markets,
+            redemption,
+            flashbots,
+        } = self;
+        policy::enforce(&config)?;
+        // Token ids and collateral per condition, shared with the client and event listener
+        // that learn which conditions are neg-risk
+        let derived_assets = DerivedAssetCache::load(
+            &config.agent.data_dir,
+            config.polymarket.default_collateral(),
+        )?;
+        let metadata_cache = config
+            .metadata_cache
+            .enabled
+            .then(|| MetadataCache::load(&config.agent.data_dir, &config.metadata_cache));
+        let neg_risk_groups = NegRiskGroups::new();
+        let resolution_proposals = ResolutionProposals::new();
+        let pnl_tracker = pnl_tracker
+            .unwrap_or_else(|| Arc::new(Mutex::new(PnLTracker::new(config.risk.capital_usd))));
+
         // Initialize Market Interface (Real or Sim)
-        let market_interface: Arc<dyn MarketInterface + Send + Sync> =
-            if config.agent.simulation_mode {
-                info!("🎞️  Initializing Market Simulator");
-                Arc::new(MarketSimulator::new())
-            } else {
-                info!("🌐 Initializing Real Polymarket Client");
-                Arc::new(PolymarketClient::new(
-                    &config.polymarket,
-                    config.agent.paper_trading,
-                    config.polygon_private_key.clone(),
-                )?)
-            };
+        // In simulation the Executor shares the same simulator instance so resting orders,
+        // fills and balance are consistent between detection and execution.
+        // Both clients hit the same upstreams, so they share breakers
+        let breakers = Arc::new(CircuitBreakers::new(
+            config.polymarket.circuit_breaker.clone(),
+        ));
+        let (market_interface, executor_interface): (
+            Arc<dyn MarketInterface + Send + Sync>,
+            Arc<dyn MarketInterface + Send + Sync>,
+        ) = if let Some(market_interface) = market_interface {
+            info!("🧩 Using the caller's market interface");
+            (market_interface.clone(), market_interface)
+        } else if config.agent.simulation_mode {
+            info!("🎞️  Initializing Market Simulator");
+            let latency = LatencyModel::from_config(&config.sim_latency, &config.agent.data_dir)?;
+            if !latency.is_zero() {
+                info!(
+                    "🐢 Simulated latency: decision -> order {}, order -> fill {}",
+                    latency.decision_to_order, latency.order_to_fill
+                );
+            }
+            let simulator = Arc::new(MarketSimulator::new().with_latency(latency));
+            (simulator.clone(), simulator)
+        } else {
+            info!("🌐 Initializing Real Polymarket Client");
+            (
+                Arc::new(
+                    PolymarketClient::new(
+                        &config.polymarket,
+                        config.agent.paper_trading,
+                        config.polygon_private_key.clone(),
+                    )?
+                    .with_breakers(breakers.clone())
+                    .with_derived_assets(derived_assets.clone())
+                    .with_neg_risk_groups(neg_risk_groups.clone())
+                    .with_resolution_proposals(resolution_proposals.clone())
+                    .with_metadata_cache(metadata_cache.clone())
+                    .with_random_salts(config.obfuscation.randomize_order_ids),
+                ),
+                Arc::new(
+                    PolymarketClient::new(
+                        &config.polymarket,
+                        config.agent.paper_trading,
+                        config.polygon_private_key.clone(),
+                    )?
+                    .with_breakers(breakers.clone())
+                    .with_derived_assets(derived_assets.clone())
+                    .with_neg_risk_groups(neg_risk_groups.clone())
+                    .with_resolution_proposals(resolution_proposals.clone())
+                    .with_metadata_cache(metadata_cache.clone())
+                    .with_random_salts(config.obfuscation.randomize_order_ids),
+                ),
+            )
+        };
+
+        // Two-process deployment: orders are signed by the `executor` process, which alone holds
+        // the key; this process only detects
+        let executor_socket = config
+            .agent
+            .executor_socket
+            .as_deref()
+            .filter(|_| !config.agent.simulation_mode);
+        let remote_executor = executor_socket.map(|socket| {
+            if config.polygon_private_key.is_some() {
+                warn!("⚠️  EXECUTOR_SOCKET is set but this process also has POLYGON_PRIVATE_KEY; remove it from the detector's environment");
+            }
+            Arc::new(RemoteExecutor::new(socket, market_interface.clone()))
+        });
+        let executor_interface: Arc<dyn MarketInterface + Send + Sync> = match &remote_executor {
+            Some(remote) => {
+                match remote.ping().await {
+                    Ok(()) => info!("🔌 Order entry via executor at {}", remote.socket_path()),
+                    Err(e) => warn!(
+                        "⚠️  Executor at {} not answering yet: {}",
+                        remote.socket_path(),
+                        e
+                    ),
+                }
+                remote.clone()
+            }
+            None => executor_interface,
+        };
+
+        // Random network faults for dry runs (CHAOS_ENABLED, `--features chaos` builds only)
+        let chaos = ChaosMonkey::from_config(
+            &config.chaos,
+            !config.agent.paper_trading && !config.agent.simulation_mode,
+        )?;
+        let (market_interface, executor_interface): (
+            Arc<dyn MarketInterface + Send + Sync>,
+            Arc<dyn MarketInterface + Send + Sync>,
+        ) = match &chaos {
+            Some(chaos) => (
+                Arc::new(ChaosInterface::new(market_interface, chaos.clone())),
+                Arc::new(ChaosInterface::new(executor_interface, chaos.clone())),
+            ),
+            None => (market_interface, executor_interface),
+        };
+
+        // Live orders are logged before they are posted, so one in flight at a crash can be
+        // looked up at the next start
+        let order_wal = if config.order_wal.enabled
+            && !config.agent.paper_trading
+            && !config.agent.simulation_mode
+        {
+            Some(Arc::new(OrderWal::open(&config.agent.data_dir)?))
+        } else {
+            None
+        };
+        let executor_interface: Arc<dyn MarketInterface + Send + Sync> = match &order_wal {
+            Some(wal) => Arc::new(WalInterface::new(executor_interface, wal.clone())),
+            None => executor_interface,
+        };
+
+        // Config snapshot for this run; its hash is stamped on every position and trade
+        let session = Arc::new(SessionRecord::new(&config)?);
+        if let Err(e) = session.save(&config.agent.data_dir) {
+            error!("❌ Failed to record session config: {}", e);
+        }
+        info!(
+            "🧾 Session {} (config {})",
+            session.session_id, session.config_hash
+        );
+
+        let mut risk_manager = RiskManager::new(config.risk.clone());
+        risk_manager.set_config_hash(session.config_hash.clone());
+        let profiles = match &config.agent.profiles_file {
+            Some(path) => {
+                let profiles = ProfileSchedule::load(path, config.risk.clone())?;
+                info!("⏰ Parameter profiles from {}", path);
+                Some(Arc::new(Mutex::new(profiles)))
+            }
+            None => None,
+        };
+        if !config.risk.strategy_capital_pct.is_empty() {
+            info!(
+                "💼 Capital buckets (% per strategy): {:?}",
+                config.risk.strategy_capital_pct
+            );
+        }
+        if let Ok(mut tracker) = pnl_tracker.lock() {
+            tracker.set_config_hash(session.config_hash.clone());
+            tracker.set_performance_config(config.performance.clone());
+            tracker.load_calendar(&config.agent.data_dir);
+            tracker.load_journal(&config.agent.data_dir);
+        }
 
-        let risk_manager = RiskManager::new(config.risk.clone());
-        let strategy = ArbitrageStrategy::new(config.arbitrage.clone());
+        // Durable storage: restore what the previous run left open, then mirror changes
+        let storage = match storage::open(&config.storage, &config.agent.data_dir).await {
+            Ok(storage) => storage,
+            Err(e) => {
+                error!("❌ Storage unavailable, running without it: {:#}", e);
+                None
+            }
+        };
+        let mut seen_markets = Vec::new();
+        let mut stored_registry = None;
+        if let Some(storage) = &storage {
+            match storage.positions().await {
+                Ok(positions) if !positions.is_empty() => {
+                    info!("🗄️ Restored {} open positions", positions.len());
+                    risk_manager.restore_positions(positions);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️ Failed to restore positions: {:#}", e),
+            }
+            match storage.seen_markets().await {
+                Ok(seen) => seen_markets = seen,
+                Err(e) => warn!("⚠️ Failed to restore seen markets: {:#}", e),
+            }
+            match (storage.markets().await, storage.assets().await) {
+                (Ok(markets), Ok(assets)) => stored_registry = Some((markets, assets)),
+                (Err(e), _) | (_, Err(e)) => warn!("⚠️ Failed to restore markets: {:#}", e),
+            }
+        }
+        let storage =
+            storage.map(|storage| StorageWriter::spawn(storage, config.storage.queue_capacity));
+        if let Some(writer) = &storage {
+            risk_manager.set_storage(writer.clone());
+            if let Ok(mut tracker) = pnl_tracker.lock() {
+                tracker.set_storage(writer.clone());
+            }
+        }
+        let strategy = ArbitrageStrategy::new(config.arbitrage.clone(), config.risk.capital_usd);
         let expiration_strategy = ExpirationStrategy::new(config.expiration.clone());
-        let binance_client = Arc::new(BinanceClient::new());
-        let predictive_strategy =
+        let binance_client = Arc::new(BinanceClient::new().with_filter(config.price_feed.clone()));
+        let mut predictive_strategy =
             PredictiveStrategy::new(config.predictive.clone(), binance_client.clone());
+        if let Some(path) = &config.predictive.model_path {
+            predictive_strategy = predictive_strategy.with_scorer(ml::load_scorer(path)?);
+            info!("🧠 Predictive entries gated by model {}", path);
+        }
+        let dislocation_strategy = DislocationStrategy::new(config.dislocation.clone());
 
-        // Executor needs a separate instance or clone.
-        // For Sim: new simulator instance (Note: State sharing logic needed if we want positions to sync)
-        // Ideally we pass Arc<Mutex<Simulator>>.
-        // For now, creating a fresh simulator means the executor has its own empty state.
-        // This is a known limitation of current refactor (Step 7 limitation).
-        // TODO: Use Arc<dyn MarketInterface> for shared state in future.
-        let executor_interface: Box<dyn MarketInterface> = if config.agent.simulation_mode {
-            Box::new(MarketSimulator::new())
-        } else {
-            Box::new(PolymarketClient::new(
-                &config.polymarket,
-                config.agent.paper_trading,
-                config.polygon_private_key.clone(),
-            )?)
-        };
-
-        // Initialize Flashbots client if enabled
-        let flashbots_client = if config.flashbots.enabled {
-            if let (Some(rpc), Some(pk)) = (&config.polygon_ws_rpc, &config.polygon_private_key) {
-                let signing_key = config.flashbots.signing_key.as_ref().unwrap_or(pk);
-
-                match crate::execution::flashbots::FlashbotsClient::new(
-                    rpc,
-                    signing_key,
-                    Some(&config.flashbots.relay_url),
-                    config.flashbots.max_retries,
-                )
-                .await
-                {
-                    Ok(client) => {
-                        info!("✅ Flashbots client initialized - MEV protection ENABLED");
-                        Some(client)
-                    }
-                    Err(e) => {
-                        error!("❌ Failed to initialize Flashbots client: {}", e);
-                        warn!("⚠️ Continuing without Flashbots protection");
-                        None
-                    }
+        // Flashbots client if enabled; dry runs get a paper relay instead
+        let (flashbots_client, paper_bundles) = match flashbots {
+            Component::Default => {
+                let paper_bundles = config.flashbots.enabled
+                    && (config.agent.paper_trading || config.agent.simulation_mode);
+                if paper_bundles {
+                    info!("📦 Flashbots bundles simulated by a paper relay - nothing is broadcast");
+                    (None, true)
+                } else {
+                    (connect_flashbots(&config).await, false)
                 }
-            } else {
-                warn!("⚠️ Flashbots enabled but missing RPC or private key");
-                None
             }
+            Component::Given(client) => (Some(client), false),
+            Component::Omitted => (None, false),
+        };
+
+        // Restores the previous run's markets / asset mapping (the database fills in gaps)
+        let registry = MarketRegistry::load(&config.agent.data_dir);
+        if let Some((markets, assets)) = stored_registry {
+            let restored = registry.restore(markets, assets);
+            if restored > 0 {
+                info!("🗄️ Restored {} markets from storage", restored);
+            }
+        }
+
+        // `--restore`: resume the last run's state snapshot instead of cold-starting detection
+        let snapshot = if config.agent.restore_snapshot {
+            match StateSnapshot::load(&config.agent.data_dir) {
+                Ok(Some(snapshot)) => {
+                    info!(
+                        "♻️  Resuming session {} from a state snapshot taken {}s ago: {} markets, {} positions, {} liquidations",
+                        snapshot.session_id,
+                        snapshot.age_secs(),
+                        snapshot.lifecycle.len(),
+                        snapshot.positions.len(),
+                        snapshot.liquidations.len()
+                    );
+                    Some(snapshot)
+                }
+                Ok(None) => {
+                    warn!(
+                        "⚠️ --restore: no state snapshot in {}, cold start",
+                        config.agent.data_dir
+                    );
+                    None
+                }
+                Err(e) => {
+                    warn!("⚠️ --restore: cold start, {:#}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(snapshot) = &snapshot {
+            registry.restore(snapshot.markets.clone(), snapshot.assets.clone());
+            risk_manager.restore_positions(snapshot.positions.clone());
+            if let Ok(mut tracker) = pnl_tracker.lock() {
+                snapshot.pnl.clone().apply(&mut tracker);
+            }
+        }
+
+        let failover = Failover::new(
+            &config.failover,
+            &session.session_id,
+            &config.agent.data_dir,
+        )
+        .map(Arc::new);
+
+        let mut executor = Executor::new(
+            executor_interface.clone(),
+            flashbots_client,
+            config.execution_health.clone(),
+            registry.clone(),
+        );
+        if let Some(failover) = &failover {
+            executor.set_failover(failover.clone());
+        }
+        if let (Some(remote), Some(failover)) = (&remote_executor, &failover) {
+            remote.set_failover(failover.clone());
+        }
+        if paper_bundles {
+            executor.set_paper_relay(Arc::new(PaperRelay::new(config.flashbots.paper_miss_rate)));
+        }
+        // Strategies kept in paper while the others trade live, booked apart
+        let paper_pnl = if !config.agent.paper_trading
+            && !config.agent.simulation_mode
+            && !config.agent.paper_strategies.is_empty()
+        {
+            let paper = PolymarketClient::new(&config.polymarket, true, None)?
+                .with_breakers(breakers.clone())
+                .with_derived_assets(derived_assets.clone())
+                .with_metadata_cache(metadata_cache.clone());
+            executor.set_paper_strategies(Arc::new(paper), config.agent.paper_strategies.clone());
+            info!(
+                "📝 Paper-only strategies: {}",
+                config.agent.paper_strategies.join(", ")
+            );
+            Some(Arc::new(Mutex::new(PnLTracker::new(
+                config.risk.capital_usd,
+            ))))
         } else {
-            info!("📊 Flashbots disabled - using regular transaction submission");
             None
         };
+        let mut paper_risk = RiskManager::new(config.risk.clone());
+        paper_risk.set_config_hash(session.config_hash.clone());
+        // The paper book carries over runs like the live wallet does
+        if let Some(Ok(mut tracker)) = paper_pnl.as_ref().map(|p| p.lock()) {
+            match PaperBook::load(&config.agent.data_dir) {
+                Ok(Some(book)) => {
+                    info!("📝 Paper book restored: {} positions", book.positions.len());
+                    paper_risk.restore_positions(book.positions);
+                    book.pnl.apply(&mut tracker);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("⚠️ Paper book not restored: {}", e),
+            }
+        }
+        executor.set_submission_queue(Arc::new(SubmissionQueue::new(&config.execution_queue)));
+        executor.set_market_order_slippage(config.market_orders.slippage_bps);
+        // Dead man's switch: only meaningful while real orders go out
+        let anomaly_guard = (config.anomaly.enabled
+            && !config.agent.paper_trading
+            && !config.agent.simulation_mode)
+            .then(|| Arc::new(AnomalyGuard::new(config.anomaly.clone())));
+        if let Some(guard) = &anomaly_guard {
+            executor.set_anomaly_guard(guard.clone());
+        }
+
+        // Only connected for the toxicity score's mempool watch
+        let mempool_ws = (config.toxicity.enabled && config.toxicity.mempool)
+            .then(|| config.polygon_ws_rpc.clone())
+            .flatten();
+        let mempool_monitor = MempoolMonitor::new(mempool_ws).await;
 
-        let executor = Executor::new(executor_interface, flashbots_client);
+        let mut redemption_manager = match redemption {
+            Component::Default => connect_redemption(&config, remote_executor.as_ref()).await,
+            Component::Given(manager) => Some(manager),
+            Component::Omitted => None,
+        };
+        if let (Some(manager), Some(failover)) = (&mut redemption_manager, &failover) {
+            manager.set_failover(failover.clone());
+        }
+        // Only worth listening for when there's something to redeem with
+        let resolution_rx = redemption_manager
+            .as_ref()
+            .and_then(|_| spawn_resolution_listener(&config));
 
-        let mempool_monitor = MempoolMonitor::new(config.polygon_ws_rpc.clone()).await;
+        let live = !config.agent.paper_trading && !config.agent.simulation_mode;
+        let collateral_swapper = CollateralSwapper::connect(&config).await;
 
-        let redemption_manager =
-            if let (Some(rpc), Some(pk)) = (&config.polygon_ws_rpc, &config.polygon_private_key) {
-                match RedemptionManager::new(rpc, pk).await {
-                    Ok(rm) => {
-                        info!("✅ RedemptionManager initialized");
-                        Some(rm)
+        // Confirm on-chain that each fill's tokens arrived
+        let (settlement, transfer_rx) = match settlement_wallet(&config) {
+            Some(wallet) if config.settlement.enabled && live => {
+                match spawn_transfer_listener(&config, wallet) {
+                    Some(rx) => {
+                        let reconciler =
+                            SettlementReconciler::new(&config.settlement, format!("{:?}", wallet));
+                        (Some(Arc::new(Mutex::new(reconciler))), Some(rx))
                     }
+                    None => (None, None),
+                }
+            }
+            _ => (None, None),
+        };
+
+        // Orders a crash left without an answer: resting, filled or never placed
+        if let Some(wal) = &order_wal {
+            reconcile_order_wal(
+                &config,
+                wal,
+                market_interface.as_ref(),
+                &mut risk_manager,
+                &pnl_tracker,
+            )
+            .await;
+        }
+
+        // Positions the wallet holds that no run of ours opened, at a rebuilt cost basis
+        if config.position_import.enabled && live {
+            import_wallet_positions(&config, &mut risk_manager, &pnl_tracker).await;
+        }
+
+        // The executor process runs the funding helper when it holds the key
+        let funding = remote_executor
+            .is_none()
+            .then(|| FundingManager::from_config(&config))
+            .flatten()
+            .map(Arc::new);
+
+        // New markets as they are created on-chain
+        let new_market_rx = match new_markets {
+            Component::Default => spawn_event_listener(&config, derived_assets.clone()),
+            Component::Given(rx) => Some(rx),
+            Component::Omitted => None,
+        };
+
+        // CLOB WebSocket book updates
+        let (ws_client, ws_update_rx) = match ws {
+            Component::Default if !config.agent.simulation_mode => {
+                match connect_clob_ws(&config, chaos.clone()).await {
+                    Some((ws, rx)) => (Some(ws), Some(rx)),
+                    None => (None, None),
+                }
+            }
+            Component::Default | Component::Omitted => (None, None),
+            Component::Given((ws, rx)) => (ws, Some(rx)),
+        };
+
+        // Resume book updates for restored markets (open positions need live prices)
+        let mut subscriptions = SubscriptionSet::new(config.agent.max_subscribed_assets);
+        if let Some(ws) = &ws_client {
+            let restored = registry.asset_ids();
+            if !restored.is_empty() {
+                info!("🔌 Re-subscribing {} restored assets", restored.len());
+                for asset_id in &restored {
+                    let market_id = registry.asset(asset_id).map(|a| a.market_id);
+                    subscriptions.insert(market_id.as_deref().unwrap_or(asset_id), asset_id);
+                }
+                ws.subscribe(restored);
+            }
+        }
+
+        let decision_log = match DecisionLogMode::parse(&config.agent.decision_log_mode) {
+            DecisionLogMode::Off => None,
+            mode => {
+                let snapshot = StrategyConfigSnapshot {
+                    arbitrage: config.arbitrage.clone(),
+                    expiration: config.expiration.clone(),
+                    predictive: config.predictive.clone(),
+                    dislocation: Some(config.dislocation.clone()),
+                };
+                match DecisionLog::new(
+                    &config.agent.data_dir,
+                    mode,
+                    snapshot,
+                    Some(session.config_hash.clone()),
+                ) {
+                    Ok(log) => Some(log),
                     Err(e) => {
-                        error!("❌ Failed to init RedemptionManager: {}", e);
+                        error!("❌ Failed to open decision log: {}", e);
                         None
                     }
                 }
-            } else {
-                None
-            };
-
-        // Setup WebSocket event listener if configured
-        let new_market_rx = if let (Some(ws_url), Some(ctf_addr_str)) =
-            (&config.polygon_ws_rpc, &config.ctf_contract_address)
-        {
-            // Parse CTF contract address
-            if let Ok(ctf_address) = ctf_addr_str.parse::<Address>() {
-                let (tx, rx) = mpsc::unbounded_channel();
+            }
+        };
 
-                // Spawn WebSocket listener task
-                let ws_url_clone = ws_url.clone();
-                tokio::spawn(async move {
-                    match MarketEventListener::new(&ws_url_clone, ctf_address).await {
-                        Ok(listener) => {
-                            if let Err(e) = listener.listen_for_new_markets(tx).await {
-                                error!("❌ WebSocket listener error: {}", e);
-                            }
-                        }
-                        Err(e) => {
-                            error!("❌ Failed to create WebSocket listener: {}", e);
-                        }
-                    }
-                });
+        let feature_recorder = if config.features.record_secs > 0 {
+            match FeatureRecorder::new(&config.agent.data_dir, config.features.record_secs) {
+                Ok(recorder) => Some(Mutex::new(recorder)),
+                Err(e) => {
+                    error!("❌ Failed to set up feature recording: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
-                info!("⚡ WebSocket real-time detection enabled");
-                Some(rx)
-            } else {
-                warn!("⚠️ Invalid CTF contract address, WebSocket disabled");
-                None
+        let experiment = if config.experiment.enabled {
+            match Experiment::new(&config, binance_client.clone()) {
+                Ok(exp) => Some(exp),
+                Err(e) => {
+                    error!("❌ Failed to set up experiment: {}", e);
+                    None
+                }
             }
         } else {
-            info!("📊 WebSocket disabled, using polling only");
             None
         };
 
-        // Initialize CLOB WebSocket
-        let (ws_client, ws_update_rx) = if !config.agent.simulation_mode {
-            let (tx, rx) = mpsc::channel(1000);
-            match ClobWebSocket::new(tx).await {
-                Ok(ws) => (Some(ws), Some(rx)),
+        let edge_tuner = if config.edge_tuning.enabled {
+            Some(Arc::new(Mutex::new(EdgeTuner::load(
+                config.edge_tuning.clone(),
+                &config.agent.data_dir,
+            ))))
+        } else {
+            None
+        };
+
+        let indexing_latency = Arc::new(Mutex::new(IndexingLatencyTracker::new(Some(
+            &config.agent.data_dir,
+        ))));
+
+        let opportunities = (config.agent.opportunity_book_capacity > 0).then(|| {
+            Arc::new(Mutex::new(OpportunityBook::new(
+                config.agent.opportunity_book_capacity,
+                Some(&config.agent.data_dir),
+            )))
+        });
+
+        let edge_realization = Arc::new(Mutex::new(EdgeRealizationTracker::load(
+            &config.agent.data_dir,
+        )));
+        let execution_drift = Arc::new(Mutex::new(ExecutionDriftTracker::new(Some(
+            &config.agent.data_dir,
+        ))));
+        let postmortems = Arc::new(Mutex::new(PostmortemLog::load(&config.agent.data_dir)));
+        let drift = live.then(|| Arc::new(Mutex::new(DriftTracker::load(&config.agent.data_dir))));
+        let paper_twin: Option<Arc<dyn MarketInterface + Send + Sync>> = match &drift {
+            Some(_) => Some(Arc::new(
+                PolymarketClient::new(&config.polymarket, true, None)?
+                    .with_breakers(breakers.clone())
+                    .with_derived_assets(derived_assets.clone())
+                    .with_metadata_cache(metadata_cache.clone()),
+            )),
+            None => None,
+        };
+        let resolutions =
+            (storage.is_some() && config.agent.resolution_window_hours > 0).then(|| {
+                ResolutionRecorder::new(
+                    config.agent.resolution_window_hours,
+                    config.agent.resolution_sample_secs,
+                )
+            });
+
+        // Lifecycle of markets seen by previous runs (redeemed ones are done with)
+        let mut lifecycle = LifecycleTracker::new();
+        for (market_id, state) in seen_markets {
+            if state != MarketState::Redeemed {
+                lifecycle.advance(&market_id, state);
+            }
+        }
+        let mut liquidations = HashMap::new();
+        if let Some(snapshot) = snapshot {
+            lifecycle.restore(snapshot.lifecycle, snapshot.metadata_retries);
+            liquidations = snapshot
+                .liquidations
+                .into_iter()
+                .filter(|ladder| {
+                    // Saved before ladders counted shares: the stop loss starts a new one
+                    let counts_shares = ladder.entry_price > 0.0;
+                    if !counts_shares {
+                        warn!(
+                            "⚠️ Dropping outdated liquidation ladder of {} (resting order {:?})",
+                            ladder.market_id,
+                            ladder.resting.as_ref().map(|order| &order.order_id)
+                        );
+                    }
+                    counts_shares
+                })
+                .map(|ladder| (ladder.market_id.clone(), ladder))
+                .collect();
+        }
+
+        let suspension = SuspensionTracker::new(config.suspension.clone());
+        let synthetic = SyntheticMarkets::new(config.synthetic.clone());
+        let obfuscator = Obfuscator::new(config.obfuscation.clone());
+        let quote_freshness = QuoteFreshness::new(config.agent.quote_max_feed_silence_ms);
+        let features = config
+            .features
+            .enabled
+            .then(|| Arc::new(Mutex::new(FeatureTracker::new(&config.features.windows_ms))));
+        let whales = Arc::new(Mutex::new(WhaleTracker::new(&config.whales)));
+        let zombies = Arc::new(Mutex::new(ZombieTracker::new(config.zombies.clone())));
+        let toxicity = (config.toxicity.enabled && live)
+            .then(|| Arc::new(Mutex::new(ToxicityTracker::new(&config.toxicity))));
+        if (config.whales.enabled || toxicity.is_some()) && !config.agent.simulation_mode {
+            ActivityFeed::spawn(
+                registry.clone(),
+                config.whales.enabled.then(|| whales.clone()),
+                toxicity.clone(),
+            );
+        }
+        if let Some(toxicity) = toxicity.as_ref().filter(|_| config.toxicity.mempool) {
+            mempool_monitor.start_monitoring(toxicity.clone(), settlement_wallet(&config));
+        }
+        // Expiration hedges are tracked (and unwound) like the exposure hedges
+        let hedger = (config.hedging.enabled || config.expiration.hedge_enabled).then(|| {
+            let mut hedger = Hedger::new(config.hedging.clone());
+            // Hedges still held from a previous run count toward the limits again
+            match HedgeBook::load(&config.agent.data_dir) {
+                Ok(Some(book)) => {
+                    info!("🛡️ Hedge book restored: {} hedges", book.hedges.len());
+                    for hedge in &book.hedges {
+                        if executor.is_paper(&hedge.trade_id()) {
+                            paper_risk.add_hedge(hedge.risk_position());
+                        } else {
+                            risk_manager.add_hedge(hedge.risk_position());
+                        }
+                    }
+                    hedger.restore(book);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("⚠️ Hedge book not restored: {}", e),
+            }
+            Arc::new(Mutex::new(hedger))
+        });
+        let watchlist = ExpirationWatchlist::new(config.expiration.watch_window_sec);
+        let llm_classifier = LlmClassifier::from_config(&config.classifier);
+        let llm_budget = llm_classifier.is_some().then(|| {
+            Arc::new(Mutex::new(LlmBudget::load(
+                config.llm_budget.clone(),
+                &config.agent.data_dir,
+                Utc::now(),
+            )))
+        });
+        let llm_classifier = llm_classifier
+            .zip(llm_budget.clone())
+            .map(|(classifier, budget)| Arc::new(classifier.with_budget(budget)));
+        if llm_classifier.is_some() {
+            info!(
+                "🏷️ LLM fallback for market categories enabled ({} requests/h, ${:.2}/day)",
+                config.llm_budget.requests_per_hour, config.llm_budget.max_usd_per_day
+            );
+        }
+
+        // Polymarket legs go through the executor's client, Kalshi legs through its adapter
+        let cross_venue_setup = if config.cross_venue.enabled && !config.agent.simulation_mode {
+            let setup = load_pairs(&config.venues).and_then(|pairs| {
+                let kalshi = KalshiClient::from_config(&config.venues, config.agent.paper_trading)?;
+                Ok((pairs, kalshi))
+            });
+            match setup {
+                Ok((_, kalshi)) if !kalshi.can_trade() => {
+                    error!("❌ Cross-venue arbitrage needs KALSHI_API_KEY_ID and KALSHI_PRIVATE_KEY_PATH to trade live");
+                    None
+                }
+                Ok((pairs, kalshi)) => {
+                    info!(
+                        "🔀 Cross-venue arbitrage on {} Polymarket / Kalshi pairs",
+                        pairs.len()
+                    );
+                    Some((
+                        CrossVenueStrategy::load(
+                            config.cross_venue.clone(),
+                            pairs,
+                            &config.agent.data_dir,
+                        ),
+                        Arc::new(CrossVenueExecutor::new(
+                            executor_interface,
+                            Arc::new(kalshi),
+                        )),
+                    ))
+                }
                 Err(e) => {
-                    error!("❌ Failed to init CLOB WS: {}", e);
-                    (None, None)
+                    error!("❌ Cross-venue arbitrage disabled: {:#}", e);
+                    None
                 }
             }
         } else {
-            (None, None)
+            None
         };
+        let (cross_venue, cross_venue_executor) = cross_venue_setup.unzip();
+        let (cross_venue_tx, cross_venue_rx) = mpsc::unbounded_channel();
+        let (jittered_tx, jittered_rx) = mpsc::unbounded_channel();
+        let (redeemed_tx, redeemed_rx) = mpsc::unbounded_channel();
+        let (balance_tx, balance_rx) = mpsc::unbounded_channel();
+        let (snipe_fill_tx, snipe_fill_rx) = mpsc::unbounded_channel();
+        let (pinned_tx, pinned_rx) = mpsc::unbounded_channel();
+        let self_monitor = SelfMonitor::new(config.self_monitor.clone());
+        let daily_report = DailyReporter::new(config.daily_report.clone(), &config.agent.data_dir);
+        let telemetry = TelemetryPusher::new(config.telemetry.clone(), &config.agent.data_dir);
+        if telemetry.enabled() {
+            info!("📡 Telemetry on as instance {}", telemetry.instance());
+        }
+        let cached_gas_gwei = config.gas.fallback_gas_price_gwei;
+        let neg_risk_baskets = NegRiskBaskets::load(&config.agent.data_dir);
+        if !neg_risk_baskets.is_empty() {
+            info!(
+                "🔄 {} NO baskets left to convert by the previous run",
+                neg_risk_baskets.len()
+            );
+        }
 
-        Ok(Self {
+        Ok(Sniper {
             config,
             market_interface,
+            breakers,
             risk_manager,
             _start_time: Utc::now(),
             strategy,
             expiration_strategy,
             predictive_strategy,
+            dislocation_strategy,
             executor,
             _mempool_monitor: mempool_monitor,
-            redemption_manager,
-            seen_markets: HashSet::new(),
+            redemption_manager: redemption_manager.map(Arc::new),
+            redeemed_tx,
+            redeemed_rx,
+            redeeming: Arc::new(AtomicBool::new(false)),
+            balance_tx,
+            balance_rx,
+            refreshing_balance: Arc::new(AtomicBool::new(false)),
+            collateral_swapper,
+            remote_executor,
+            funding,
+            order_wal,
+            lifecycle: Arc::new(Mutex::new(lifecycle)),
             pnl_tracker,
+            paper_pnl,
+            paper_risk,
             new_market_rx,
-            pending_retries: VecDeque::new(),
+            resolution_rx,
+            transfer_rx,
+            jobs: JobQueue::new(),
+            scheduler: Scheduler::new(),
             ws_client,
             ws_update_rx,
-            active_markets: HashMap::new(),
-            asset_map: HashMap::new(),
-            subscribed_assets: HashSet::new(),
+            ws_queue_saturations: 0,
+            registry,
+            subscriptions: Arc::new(Mutex::new(subscriptions)),
             cached_balance: 0.0,
+            cached_gas_gwei,
             last_balance_update: std::time::Instant::now() - Duration::from_secs(600), // Force initial update
+            margin: Arc::new(Mutex::new(MarginBook::new())),
+            decision_log,
+            experiment,
+            edge_tuner,
+            indexing_latency,
+            liquidations,
+            session,
+            opportunities,
+            edge_realization,
+            execution_drift,
+            postmortems,
+            settlement,
+            suspension,
+            synthetic,
+            profiles,
+            obfuscator,
+            toxicity,
+            quote_freshness,
+            ask_ladders: AskLadders::new(),
+            derived_assets,
+            metadata_cache,
+            neg_risk_groups,
+            neg_risk_baskets,
+            maker_legs: Vec::new(),
+            pending_merges: HashMap::new(),
+            features,
+            feature_recorder,
+            storage,
+            llm_classifier,
+            llm_budget,
+            classify_requested: HashSet::new(),
+            watchlist: Arc::new(Mutex::new(watchlist)),
+            pins: Arc::new(Mutex::new(PinnedMarkets::new(Some(&config.agent.data_dir)))),
+            pinned_tx,
+            pinned_rx,
+            pins_polling: Arc::new(AtomicBool::new(false)),
+            snapshot_saving: Arc::new(AtomicBool::new(false)),
+            anomaly_guard,
+            risk_halt: None,
+            whales,
+            missed_windows: Arc::new(Mutex::new(MissedWindows::new())),
+            zombies,
+            fee_rates: FeeRates::new(),
+            series_watcher: config.series_watch.enabled.then(|| {
+                Arc::new(Mutex::new(SeriesWatcher::new(
+                    &config.series_watch,
+                    Some(&config.agent.data_dir),
+                )))
+            }),
+            series_polling: Arc::new(AtomicBool::new(false)),
+            resolution_proposals,
+            hedger,
+            snipe_fill_tx,
+            snipe_fill_rx,
+            drift,
+            paper_twin,
+            resolutions,
+            cross_venue,
+            cross_venue_executor,
+            cross_venue_tx,
+            cross_venue_rx,
+            cross_venue_scanning: Arc::new(AtomicBool::new(false)),
+            jittered_tx,
+            jittered_rx,
+            jitter_released: false,
+            self_monitor,
+            daily_report,
+            telemetry,
+            capital_wait: CapitalWait::new(config.capital_wait.clone()),
+            deferred_since: None,
+            price_feed: binance_client,
+            failover,
+            evaluation_received_ns: None,
+            custom_strategies: Vec::new(),
+            events: EventBus::new(),
+            shutdown: ShutdownHandle::new(),
         })
     }
+}
 
-    /// Main agent loop
-    pub async fn run(&mut self) -> Result<()> {
-        info!("🚀 Starting Polymarket HFT Agent");
-
-        // Start background tasks
-        // self.mempool_monitor.start_monitoring().await;
-        info!("⚠️ Mempool monitoring disabled to save API credits");
+/// Flashbots client for live bundles, None if disabled or it can't be set up
+async fn connect_flashbots(config: &Config) -> Option<FlashbotsClient> {
+    if config.flashbots.enabled {
+        if let (Some(rpc), Some(pk)) = (&config.polygon_ws_rpc, &config.polygon_private_key) {
+            let signing_key = config.flashbots.signing_key.as_ref().unwrap_or(pk);
 
-        info!(
-            "📊 Mode: {}",
-            if self.config.agent.paper_trading {
-                "PAPER TRADING"
-            } else {
-                "LIVE TRADING"
+            match FlashbotsClient::new(
+                rpc,
+                signing_key,
+                Some(&config.flashbots.relay_url),
+                config.flashbots.max_retries,
+            )
+            .await
+            {
+                Ok(client) => {
+                    info!("✅ Flashbots client initialized - MEV protection ENABLED");
+                    Some(client)
+                }
+                Err(e) => {
+                    error!("❌ Failed to initialize Flashbots client: {}", e);
+                    warn!("⚠️ Continuing without Flashbots protection");
+                    None
+                }
             }
-        );
-        info!(
-            "⏱️  Poll interval: {} seconds",
-            self.config.agent.market_poll_interval_secs
-        );
-
-        let mut tick_interval = interval(Duration::from_secs(
-            self.config.agent.market_poll_interval_secs,
-        ));
-
-        // Redemption check interval (every 5 minutes)
-        let mut redemption_interval = interval(Duration::from_secs(300));
-
-        // PnL update interval (every 10 seconds)
-        let mut pnl_update_interval = interval(Duration::from_secs(10));
-
-        // Retry interval (every 1 second)
-        let mut retry_interval = interval(Duration::from_secs(1));
-        retry_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        } else {
+            warn!("⚠️ Flashbots enabled but missing RPC or private key");
+            None
+        }
+    } else {
+        info!("📊 Flashbots disabled - using regular transaction submission");
+        None
+    }
+}
 
-        // Async Retry Results Channel
-        let (retry_tx, mut retry_rx) = mpsc::channel::<(String, u8, Result<MarketData>)>(1000);
-
-        loop {
-            tokio::select! {
-                // 0. Async Retry Results handling
-                Some((condition_id, attempts, result)) = retry_rx.recv() => {
-                     match result {
-                        Ok(market) => {
-                            info!("✅ Sync success for {} after {} attempts", market.question, attempts);
-                            if !self.seen_markets.contains(&market.id) {
-                                self.seen_markets.insert(market.id.clone());
-                                if let Err(e) = self.process_single_market(&market).await {
-                                    error!("❌ Error processing market {}: {}", market.question, e);
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            let max_attempts = 60;
-                            if attempts < max_attempts {
-                                // Silent retry for most attempts to avoid log spam
-                                if attempts == 1 || attempts % 20 == 0 {
-                                    info!("⏳ Still waiting for Gamma to sync market {} (Attempt {})", condition_id, attempts);
-                                }
-                                self.pending_retries.push_back((condition_id, attempts + 1));
-                            } else {
-                                error!("❌ Gave up fetching {} after {} attempts. Error: {}", condition_id, max_attempts, e);
-                            }
-                        }
-                     }
-                }
-                // 1. CLOB Orderbook Updates (HIGHEST PRIORITY)
-                Some(update) = async {
-                    match &mut self.ws_update_rx {
-                        Some(rx) => rx.recv().await,
-                        None => std::future::pending().await,
-                    }
-                } => {
-                     // 1. Identify Market
-                     if let Some((market_id, side)) = self.asset_map.get(&update.asset_id).cloned() {
-                         // Sampled heartbeat to avoid log spam under high WS throughput
-                         if rand::random::<f64>() < 0.002 {
-                             info!("⚡ WS Tick Alive: {} [{}] ({} bids, {} asks)", market_id, side, update.bids.len(), update.asks.len());
-                         }
-
-                         // 2. Update State
-                         if let Some(market) = self.active_markets.get_mut(&market_id) {
-                             // Update Prices based on Bids/Asks
-                             // NOTE: We are SNIPING, so we want to BUY.
-                             // Buying YES means taking the Lowest ASK.
-                             // Buying NO means taking the Lowest ASK.
-                             // So we care about ASKS.
-
-                             if let Some(best_ask) = update.asks.first() {
-                                 let price = best_ask.price.parse::<f64>().unwrap_or(0.0);
-                                 if side == "YES" {
-                                     market.yes_price = price;
-                                     if rand::random::<f64>() < 0.002 {
-                                         debug!("📊 WS Update: {} YES -> {:.4}", market.question, price);
-                                     }
-                                 } else {
-                                     market.no_price = price;
-                                     if rand::random::<f64>() < 0.002 {
-                                         debug!("📊 WS Update: {} NO -> {:.4}", market.question, price);
-                                     }
-                                 }
-
-                                 // Trigger re-eval
-                                 // Clone to avoid borrow issues while calling async func
-                                 let market_clone = market.clone();
-
-                                 // We need to spawn this or call it?
-                                 // Calling await here might block other updates slightly, but it's the core logic.
-                                 // For HFT, we should check strategy synchronously if possible, but execute async.
-                                 // Our current process_single_market is async.
-                                 // Let's call it.
-                                 if let Err(e) = self.process_single_market(&market_clone).await {
-                                     error!("❌ Processing error: {}", e);
-                                 }
-                             }
-                         }
-                     }
-                }
+/// On-chain redemptions / merges, None without an RPC and a key: held here, or by the
+/// `executor` process, which sends the transactions then
+async fn connect_redemption(
+    config: &Config,
+    remote: Option<&Arc<RemoteExecutor>>,
+) -> Option<RedemptionManager> {
+    let (Some(remote), Some(rpc), None) =
+        (remote, &config.polygon_ws_rpc, &config.polygon_private_key)
+    else {
+        return RedemptionManager::connect(config).await;
+    };
+    match RedemptionManager::remote(rpc, remote.clone()).await {
+        Ok(rm) => {
+            info!("✅ RedemptionManager initialized (transactions via the executor)");
+            Some(rm)
+        }
+        Err(e) => {
+            error!("❌ Failed to init RedemptionManager: {}", e);
+            None
+        }
+    }
+}
 
-                // WebSocket events (New Markets)
-                Some(condition_id) = async {
-                    match &mut self.new_market_rx {
-                        Some(rx) => rx.recv().await,
-                        None => std::future::pending().await, // Never resolves if no WS
-                    }
-                } => {
-                    info!("⚡ WebSocket event: New market condition {}", condition_id);
+/// Blocking; `mirror` is the standby's copy (failover primary only)
+fn write_state_snapshot(snapshot: &StateSnapshot, data_dir: &str, mirror: Option<&str>) {
+    match snapshot.save(data_dir) {
+        Ok(()) => debug!(
+            "♻️  State snapshot: {} markets, {} positions",
+            snapshot.lifecycle.len(),
+            snapshot.positions.len()
+        ),
+        Err(e) => warn!("⚠️ Failed to write state snapshot: {:#}", e),
+    }
+    if let Some(dir) = mirror {
+        if let Err(e) = snapshot.save(dir) {
+            warn!("⚠️ Failed to mirror state snapshot to {}: {:#}", dir, e);
+        }
+    }
+}
 
-                    // OPTIMIZATION: Check if we've already seen this market (e.g. via polling or previous event)
-                    if self.seen_markets.contains(&condition_id) {
-                        continue;
-                    }
+/// Spawn the CTF event listener if configured: the receiver of new condition ids
+fn spawn_event_listener(
+    config: &Config,
+    derived_assets: DerivedAssetCache,
+) -> Option<mpsc::UnboundedReceiver<ConditionId>> {
+    if let (Some(ws_url), Some(ctf_addr_str)) =
+        (&config.polygon_ws_rpc, &config.ctf_contract_address)
+    {
+        // Parse CTF contract address
+        if let Ok(ctf_address) = ctf_addr_str.parse::<Address>() {
+            let (tx, rx) = mpsc::unbounded_channel();
 
-                    // GOD MODE: Perform local calculation of Token IDs immediately
-                    // This creates a "Synthetic" market to start tracking prices while Gamma indexes
-                    match derive_asset_ids(&condition_id) {
-                        Ok((yes_id, no_id)) => {
-                            info!("🔮 Derived Token IDs locally! YES: ...{}, NO: ...{}",
-                                &yes_id[yes_id.len()-6..], &no_id[no_id.len()-6..]);
-
-                            // Initialize "Synthetic" Market Entry
-                            // We don't have the question yet, but we have the IDs to trade!
-                            let synthetic_market = MarketData {
-                                id: condition_id.clone(),
-                                question: format!("⌛ Loading Metadata ({})", condition_id),
-                                end_date: Some("Unknown".to_string()),
-                                description: None,
-                                volume: 0.0,
-                                liquidity: 0.0,
-                                yes_price: 0.0,
-                                no_price: 0.0,
-                                volume_24h: 0.0,
-                                best_bid: 0.0,
-                                best_ask: 0.0,
-                                order_book_imbalance: 0.0,
-                                asset_ids: vec![no_id.clone(), yes_id.clone()], // Standard: [No, Yes] usually, but verify?
-                                // Important: derive_asset_ids returns (YES, NO) tuple order I defined?
-                                // My function returns (yes_id, no_id).
-                                // MarketData.asset_ids usually matches the order in CLOB.
-                                // Let's store them and map them.
-                            };
-
-                            // Map Assets for Price Updates
-                            self.asset_map.insert(yes_id.clone(), (condition_id.clone(), "YES".to_string()));
-                            self.asset_map.insert(no_id.clone(), (condition_id.clone(), "NO".to_string()));
-                            self.active_markets.insert(condition_id.clone(), synthetic_market.clone());
-
-                            // Subscribe to CLOB WebSocket IMMEDIATELY
-                            if let Some(ws) = &mut self.ws_client {
-                                let mut to_subscribe = Vec::new();
-                                if self.subscribed_assets.insert(yes_id.clone()) {
-                                    to_subscribe.push(yes_id);
-                                }
-                                if self.subscribed_assets.insert(no_id.clone()) {
-                                    to_subscribe.push(no_id);
-                                }
-                                if !to_subscribe.is_empty() {
-                                    ws.subscribe(to_subscribe);
-                                    info!("🔌 Subscribed to CLOB for derived IDs (YES/NO)");
-                                }
+            // Spawn WebSocket listener task
+            let ws_url_clone = ws_url.clone();
+            let chain_events = config.chain_events.clone();
+            // Reconnects whenever the stream ends, until the agent stops listening
+            tokio::spawn(async move {
+                let mut backoff = ReconnectBackoff::default();
+                while !tx.is_closed() {
+                    backoff.connecting();
+                    match MarketEventListener::new(&ws_url_clone, ctf_address).await {
+                        Ok(listener) => {
+                            let listener = listener.with_derived_assets(derived_assets.clone());
+                            if let Err(e) = listener
+                                .listen_for_new_markets(tx.clone(), &chain_events)
+                                .await
+                            {
+                                error!("❌ WebSocket listener error: {}", e);
                             }
                         }
                         Err(e) => {
-                            error!("❌ Failed to derive asset ids: {}", e);
+                            error!("❌ Failed to create WebSocket listener: {}", e);
                         }
                     }
-
-                    // Also try normal fetch (will likely fail initially but needed for metadata)
-                    // If it fails, add to retry queue to avoid blocking
-                    debug!("⚡ Triggering immediate fast market fetch...");
-                    match self.market_interface.get_market_details(&condition_id).await {
-                        Ok(market) => {
-                            debug!("✅ Fast sync success: {}", market.question);
-                            if !self.seen_markets.contains(&market.id) {
-                                self.seen_markets.insert(market.id.clone());
-                                // Update the synthetic market with real metadata
-                                self.active_markets.insert(market.id.clone(), market.clone());
-                                info!("🚀 Processing new market immediately: {}", market.question);
-                                if let Err(e) = self.process_single_market(&market).await {
-                                    error!("❌ Error processing market {}: {}", market.question, e);
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            // Non-blocking retry: Queue it
-                            debug!("⚠️ Initial fetch failed ({}), queuing for retry...", e);
-                            self.pending_retries.push_back((condition_id, 1));
-                        }
+                    if tx.is_closed() {
+                        break;
                     }
+                    backoff.wait("WebSocket listener").await;
                 }
+            });
 
-                // Generic Retry Processing (Non-blocking spawning)
-                _ = retry_interval.tick() => {
-                    // Process a batch of retries to avoid spawning too many tasks at once
-                    let batch_size = 20;
-                    for _ in 0..batch_size {
-                        if let Some((condition_id, attempts)) = self.pending_retries.pop_front() {
-                            if self.seen_markets.contains(&condition_id) {
-                                continue;
-                            }
-
-                            // Spawn async fetch
-                            let client = self.market_interface.clone();
-                            let tx = retry_tx.clone();
-                            let cid = condition_id.clone();
+            info!("⚡ WebSocket real-time detection enabled");
+            Some(rx)
+        } else {
+            warn!("⚠️ Invalid CTF contract address, WebSocket disabled");
+            None
+        }
+    } else {
+        info!("📊 WebSocket disabled, using polling only");
+        None
+    }
+}
 
-                            tokio::spawn(async move {
-                                let res = client.get_market_details(&cid).await;
-                                let _ = tx.send((cid, attempts, res)).await;
-                            });
-                        } else {
-                            break;
-                        }
+/// Spawn the CTF resolution listener if configured: the receiver of resolved condition ids
+fn spawn_resolution_listener(config: &Config) -> Option<mpsc::UnboundedReceiver<ConditionId>> {
+    let (Some(ws_url), Some(ctf_addr_str)) = (&config.polygon_ws_rpc, &config.ctf_contract_address)
+    else {
+        return None;
+    };
+    let Ok(ctf_address) = ctf_addr_str.parse::<Address>() else {
+        warn!("⚠️ Invalid CTF contract address, resolution events disabled");
+        return None;
+    };
+    let (tx, rx) = mpsc::unbounded_channel();
+    let ws_url = ws_url.clone();
+    // Reconnects whenever the stream ends, until the agent stops listening
+    tokio::spawn(async move {
+        let mut backoff = ReconnectBackoff::default();
+        while !tx.is_closed() {
+            backoff.connecting();
+            match MarketEventListener::new(&ws_url, ctf_address).await {
+                Ok(listener) => {
+                    if let Err(e) = listener.listen_for_resolutions(tx.clone()).await {
+                        error!("❌ Resolution listener error: {}", e);
                     }
                 }
+                Err(e) => error!("❌ Failed to create resolution listener: {}", e),
+            }
+            if tx.is_closed() {
+                break;
+            }
+            backoff.wait("Resolution listener").await;
+        }
+    });
+    info!("⚡ On-chain resolution events enabled: held conditions redeem as they resolve");
+    Some(rx)
+}
 
-                // Polling (BACKUP - catches anything WS might miss)
-                _ = tick_interval.tick() => {
-                    if let Err(e) = self.process_markets().await {
-                        error!("❌ Error processing markets: {}", e);
-                    }
-                    // Saturation spread log is only useful for arbitrage mode.
-                    if self.config.arbitrage.enabled {
-                        self.log_top_opportunities().await;
-                    }
-                }
-
-                _ = redemption_interval.tick() => {
-                    if let Some(rm) = &self.redemption_manager {
-                         // Iterate all positions and check if resolved
-                         // Optimization: in real app, maintain a list of 'potential to redeem'
-                         let positions = self.risk_manager.get_positions();
-                         for pos in positions {
-                             match rm.is_condition_resolved(&pos.market_id).await {
-                                 Ok(resolved) => {
-                                     if resolved {
-                                         info!("🎉 Market {} resolved! Redeeming...", pos.market_id);
-                                         if let Err(e) = rm.redeem_positions(&pos.market_id).await {
-                                            error!("❌ Redemption failed for {}: {}", pos.market_id, e);
-                                         } else {
-                                             // Remove position from risk manager upon successful redemption request
-                                             // (Or wait for confirmation, but for now remove to free up exposure)
-                                             self.risk_manager.remove_position(&pos.market_id);
-                                         }
-                                     }
-                                 }
-                                 Err(e) => {
-                                     warn!("⚠️ Failed to check resolution for {}: {}", pos.market_id, e);
-                                 }
-                             }
-                         }
-                    }
-                }
-                _ = pnl_update_interval.tick() => {
-                    // Update prices with LIVE data
-                    let mut market_ids: Vec<String> = Vec::new();
-                    if let Ok(tracker) = self.pnl_tracker.lock() {
-                        // Get unique market IDs from active positions
-                        for pos in tracker.positions.values() {
-                            if !market_ids.contains(&pos.market_id) {
-                                market_ids.push(pos.market_id.clone());
-                            }
-                        }
-                    }
+/// Read the collateral balances and open orders for `Sniper::apply_balance`. None when the
+/// balances can't be read; an unreadable order list keeps the last one (better stale than
+/// assumed free)
+async fn read_balance(
+    market_interface: &(dyn MarketInterface + Send + Sync),
+) -> Option<BalanceRefresh> {
+    let balances = match market_interface.get_collateral_balances().await {
+        Ok(balances) => balances,
+        Err(e) => {
+            debug!("Balance refresh failed: {}", e);
+            return None;
+        }
+    };
+    let open_orders = match market_interface.get_open_orders(None).await {
+        Ok(orders) => Some(orders),
+        Err(e) => {
+            debug!("Open orders refresh failed: {}", e);
+            None
+        }
+    };
+    Some(BalanceRefresh {
+        balances,
+        open_orders,
+    })
+}
 
-                    // Fetch updates for these markets
-                    if !market_ids.is_empty() {
-                        // info!("Updating PnL for {} markets...", market_ids.len());
-                        for market_id in market_ids {
-                            match self.market_interface.get_market_details(&market_id).await {
-                                Ok(market) => {
-                                    if let Ok(mut tracker) = self.pnl_tracker.lock() {
-                                        tracker.update_market_price(&market_id, market.yes_price, market.no_price);
-                                    }
-                                }
-                                Err(e) => {
-                                    warn!("Failed to fetch price for PnL update {}: {}", market_id, e);
+/// The wallet CLOB fills settle to: the configured proxy, else the Safe derived from the key
+fn settlement_wallet(config: &Config) -> Option<Address> {
+    if let Some(proxy) = &config.polymarket.proxy_address {
+        return proxy.parse().ok();
+    }
+    let signer: alloy::signers::local::PrivateKeySigner =
+        config.polygon_private_key.as_deref()?.parse().ok()?;
+    let signer_address = polymarket_client_sdk::auth::Signer::address(&signer);
+    polymarket_client_sdk::derive_safe_wallet(signer_address, polymarket_client_sdk::POLYGON)?
+        .to_string()
+        .parse()
+        .ok()
+}
+
+/// Import the funder wallet's positions the risk manager doesn't know of (bought by hand or by
+/// another instance), entered at the cost basis rebuilt from the wallet's fills. Each token held
+/// is its own PnL position; a market held on both outcomes counts their summed cost at risk
+async fn import_wallet_positions(
+    config: &Config,
+    risk_manager: &mut RiskManager,
+    pnl_tracker: &Mutex<PnLTracker>,
+) {
+    let Some(wallet) = settlement_wallet(config) else {
+        warn!("⚠️ Position import needs POLYMARKET_PROXY_ADDRESS or POLYGON_PRIVATE_KEY");
+        return;
+    };
+    let user = format!("{:?}", wallet);
+    let backfill = match CostBasisBackfill::new(
+        &config.polymarket.host,
+        config.position_import.history_days,
+    ) {
+        Ok(backfill) => backfill,
+        Err(e) => {
+            error!("❌ Failed to init position import: {}", e);
+            return;
+        }
+    };
+    let held = match backfill.positions(&user).await {
+        Ok(held) => held,
+        Err(e) => {
+            warn!("⚠️ Failed to list wallet positions for import: {:#}", e);
+            return;
+        }
+    };
+
+    let known: HashSet<String> = risk_manager
+        .get_positions()
+        .iter()
+        .map(|p| normalize_market_id(&p.market_id).into_owned())
+        .collect();
+    let now = Utc::now();
+    for position in held {
+        let market_id = normalize_market_id(&position.condition_id).into_owned();
+        let Some(side) = position.side() else {
+            continue;
+        };
+        if position.size < config.position_import.min_shares || known.contains(&market_id) {
+            continue;
+        }
+        let basis = backfill.cost_basis(&user, &position).await;
+        if basis.entry_price <= 0.0 {
+            warn!(
+                "⚠️ No cost basis for {} {} ({}), not imported",
+                side, market_id, position.title
+            );
+            continue;
+        }
+        let trade_id = format!(
+            "import_{}_{}_{}",
+            market_id,
+            side.to_lowercase(),
+            now.timestamp_millis()
+        );
+        let size_usd = position.size * basis.entry_price;
+        // The other outcome of the market was imported already: the exposure adds up
+        let added = risk_manager.increase_position(
+            &market_id,
+            UsdAmount::new(size_usd),
+            Price::new(basis.entry_price),
+        );
+        if !added {
+            risk_manager.add_position(
+                market_id.clone(),
+                trade_id.clone(),
+                side.to_string(),
+                UsdAmount::new(size_usd),
+                Price::new(basis.entry_price),
+            );
+        }
+        if let Ok(mut tracker) = pnl_tracker.lock() {
+            tracker.add_position(Position {
+                id: trade_id,
+                market_id: market_id.clone(),
+                market_question: position.title.clone(),
+                side: side.to_string(),
+                size: size_usd,
+                entry_price: basis.entry_price,
+                current_price: if position.cur_price > 0.0 {
+                    position.cur_price
+                } else {
+                    basis.entry_price
+                },
+                entry_time: now,
+                config_hash: None,
+                group_id: None,
+            });
+        }
+        info!(
+            "📥 Imported {:.2} {} shares of {} @ {:.4} (cost basis from {:?})",
+            position.size, side, position.title, basis.entry_price, basis.source
+        );
+    }
+}
+
+/// Look up the orders the WAL holds without an answer. Fills are booked against the risk
+/// manager's positions, so they are neither bought twice nor left unmanaged: a new market opens
+/// a position, more of a held outcome adds to it and the other outcome (an exit) closes what
+/// it covered. Resting orders are left to the open order refresh
+async fn reconcile_order_wal(
+    config: &Config,
+    wal: &OrderWal,
+    market_interface: &(dyn MarketInterface + Send + Sync),
+    risk_manager: &mut RiskManager,
+    pnl_tracker: &Mutex<PnLTracker>,
+) {
+    let pending = wal.pending();
+    if pending.is_empty() {
+        return;
+    }
+    warn!(
+        "⚠️ {} order(s) were in flight when the last run stopped; reconciling",
+        pending.len()
+    );
+    let user = settlement_wallet(config).map(|wallet| format!("{:?}", wallet));
+    let backfill =
+        CostBasisBackfill::new(&config.polymarket.host, config.position_import.history_days).ok();
+    let wallet = match (&backfill, &user) {
+        (Some(backfill), Some(user)) => Some((backfill, user.as_str())),
+        _ => None,
+    };
+
+    let now = Utc::now();
+    for reconciliation in wal.reconcile(market_interface, wallet).await {
+        let intent = &reconciliation.intent;
+        match &reconciliation.outcome {
+            WalOutcome::NotPlaced => info!(
+                "🧾 WAL order {} ({} ${:.2} on {}) never reached the book",
+                intent.seq, intent.side, intent.size_usd, intent.market_id
+            ),
+            WalOutcome::Resting { order_id } => info!(
+                "🧾 WAL order {} on {} is resting as {}",
+                intent.seq, intent.market_id, order_id
+            ),
+            WalOutcome::Filled { shares, price } => {
+                let market_id = normalize_market_id(&intent.market_id).into_owned();
+                warn!(
+                    "🧾 WAL order {} on {} filled {:.2} shares @ {:.4} before the crash",
+                    intent.seq, market_id, shares, price
+                );
+                // The intent's side may be a book side: the position is in its token's outcome
+                let outcome = intent.outcome();
+                let held = risk_manager
+                    .get_positions()
+                    .into_iter()
+                    .find(|p| normalize_market_id(&p.market_id) == market_id);
+                let size_usd = shares * price;
+                match held {
+                    // The other outcome bought against the position: an exit, closing as many
+                    // shares as it bought at the complement of its price
+                    Some(position) if position.side != outcome => {
+                        let exit_price = 1.0 - price;
+                        risk_manager.reduce_position(
+                            &position.market_id,
+                            UsdAmount::new(shares * position.entry_price),
+                            Price::new(exit_price),
+                        );
+                        if let Ok(mut tracker) = pnl_tracker.lock() {
+                            let mut left = *shares;
+                            let legs: Vec<(String, f64)> = tracker
+                                .positions
+                                .values()
+                                .filter(|p| {
+                                    normalize_market_id(&p.market_id) == market_id
+                                        && p.side == position.side
+                                })
+                                .map(|p| (p.id.clone(), p.shares()))
+                                .collect();
+                            for (id, held_shares) in legs {
+                                let closed = left.min(held_shares);
+                                if closed <= 0.0 {
+                                    break;
                                 }
+                                let entry = tracker.positions[&id].entry_price;
+                                tracker.reduce_position(
+                                    &id,
+                                    UsdAmount::new(closed * entry),
+                                    Price::new(exit_price),
+                                );
+                                left -= closed;
                             }
                         }
+                        continue;
                     }
-
-                    // Take snapshot after updates
-                    if let Ok(mut tracker) = self.pnl_tracker.lock() {
-                        tracker.take_snapshot();
+                    Some(position) => {
+                        risk_manager.increase_position(
+                            &position.market_id,
+                            UsdAmount::new(size_usd),
+                            Price::new(*price),
+                        );
+                    }
+                    None => {
+                        risk_manager.add_position(
+                            market_id.clone(),
+                            format!("wal_{}_{}", market_id, now.timestamp_millis()),
+                            outcome.to_string(),
+                            UsdAmount::new(size_usd),
+                            Price::new(*price),
+                        );
                     }
                 }
+                let trade_id = format!(
+                    "wal_{}_{}_{}",
+                    market_id,
+                    intent.seq,
+                    now.timestamp_millis()
+                );
+                if let Ok(mut tracker) = pnl_tracker.lock() {
+                    tracker.add_position(Position {
+                        id: trade_id,
+                        market_id: market_id.clone(),
+                        market_question: String::new(),
+                        side: outcome.to_string(),
+                        size: size_usd,
+                        entry_price: *price,
+                        current_price: *price,
+                        entry_time: now,
+                        config_hash: None,
+                        group_id: None,
+                    });
+                }
             }
         }
     }
+}
 
-    /// Process new markets and manage positions
-    async fn process_markets(&mut self) -> Result<()> {
-        // Fetch current state of all markets
-        let all_markets = self.market_interface.get_active_markets().await?;
-
-        // FIRST TIME ONLY: Mark all existing markets as seen without analyzing
-        // This prevents analyzing 1000+ old markets on startup
-        if self.seen_markets.is_empty() {
-            if !self.config.agent.scan_existing_on_startup {
-                for market in &all_markets {
-                    self.seen_markets.insert(market.id.clone());
+/// One poll of the event series: alert on (and publish) the new ones as they are detected, then
+/// pin the markets of the pending ones matching an auto-pin keyword
+async fn poll_series(
+    client: &Arc<dyn MarketInterface + Send + Sync>,
+    watcher: &Mutex<SeriesWatcher>,
+    pins: &Mutex<PinnedMarkets>,
+    events: &EventBus,
+    config: &SeriesWatchConfig,
+) {
+    let listed = match client.get_event_series().await {
+        Ok(listed) => listed,
+        Err(e) => {
+            debug!("Event series poll failed: {}", e);
+            return;
+        }
+    };
+    let now = Utc::now();
+    let Ok((new, pending)) = watcher.lock().map(|mut watcher| {
+        let new = watcher.observe(listed, now);
+        for new_series in &new {
+            watcher.record(new_series.clone());
+        }
+        (new, watcher.pending(now))
+    }) else {
+        return;
+    };
+    for new_series in &new {
+        let series = &new_series.series;
+        warn!(
+            "🚨 ALERT: new event series \"{}\" ({}, {})",
+            series.title,
+            series.slug,
+            series.recurrence.as_deref().unwrap_or("no recurrence")
+        );
+        events.publish(|| AgentEvent::NewSeries {
+            series_id: series.id.clone(),
+            slug: series.slug.clone(),
+            title: series.title.clone(),
+            recurrence: series.recurrence.clone(),
+            matched_keyword: new_series.matched_keyword.clone(),
+        });
+    }
+    // A series is seen once its markets are pinned; until then it's retried every poll (up to
+    // the watcher's age cutoff)
+    for new_series in pending {
+        let series = new_series.series;
+        let pinned = match new_series.matched_keyword {
+            None => Vec::new(),
+            Some(keyword) => {
+                let markets = match client.get_series_markets(&series.id).await {
+                    Ok(markets) => markets,
+                    Err(e) => {
+                        warn!("⚠️ Markets of series {} not fetched: {}", series.slug, e);
+                        continue;
+                    }
+                };
+                let note = format!("series {} ({})", series.slug, keyword);
+                let pinned = pin_series_markets(pins, &markets, config, &note);
+                if pinned.is_empty() {
+                    debug!("No markets of series {} pinned yet", series.slug);
+                    continue;
                 }
-                info!("🚀 Startup: Skipped {} existing markets (only trading NEW markets from now on)", all_markets.len());
-
-                // Still manage positions even on first run
-                self.manage_positions(&all_markets).await?;
-                return Ok(());
-            } else {
                 info!(
-                    "🚀 Startup: Analyzing {} existing markets for immediate opportunities...",
-                    all_markets.len()
+                    "📌 Pinned {} markets of series {} (keyword \"{}\")",
+                    pinned.len(),
+                    series.slug,
+                    keyword
                 );
-                // Don't insert into seen_markets yet, let the loop below do it
+                pinned
             }
+        };
+        if let Ok(mut watcher) = watcher.lock() {
+            watcher.mark_seen(&series.id, pinned);
         }
+    }
+}
 
-        // SUBSEQUENT RUNS: Detect and process NEW markets only
-        let mut new_markets = Vec::new();
-        for market in &all_markets {
-            if !self.seen_markets.contains(&market.id) {
-                info!("🆕 NEW market detected: {}", market.question);
-                self.seen_markets.insert(market.id.clone());
-                new_markets.push(market.clone());
+/// Pin up to `SERIES_AUTO_PIN_MAX_MARKETS` of a new series' markets; the condition ids pinned
+fn pin_series_markets(
+    pins: &Mutex<PinnedMarkets>,
+    markets: &[MarketData],
+    config: &SeriesWatchConfig,
+    note: &str,
+) -> Vec<String> {
+    let Ok(mut pins) = pins.lock() else {
+        return Vec::new();
+    };
+    let mut pinned = Vec::new();
+    for market in markets.iter().take(config.auto_pin_max_markets) {
+        let request = PinRequest {
+            market: market.id.to_string(),
+            notify: config.auto_pin_notify,
+            note: Some(note.to_string()),
+            bypass_filters: false,
+        };
+        match pins.pin_automatic(request) {
+            Ok(pin) => {
+                pins.resolve(&pin.key, market);
+                pinned.push(market.id.to_string());
             }
+            Err(e) => warn!("⚠️ Failed to pin {}: {}", market.id, e),
         }
+    }
+    pinned
+}
 
-        if !new_markets.is_empty() {
-            info!("⚡ Processing {} brand new markets", new_markets.len());
-            for market in new_markets {
-                if let Err(e) = self.process_single_market(&market).await {
-                    error!("❌ Error processing market {}: {}", market.question, e);
-                    continue;
+/// Spawn the ERC-1155 transfer listener of `wallet` if configured: the receiver of its
+/// outcome token transfers
+fn spawn_transfer_listener(
+    config: &Config,
+    wallet: Address,
+) -> Option<mpsc::Receiver<TokenTransfer>> {
+    let (Some(ws_url), Some(ctf_addr_str)) = (&config.polygon_ws_rpc, &config.ctf_contract_address)
+    else {
+        return None;
+    };
+    let Ok(ctf_address) = ctf_addr_str.parse::<Address>() else {
+        warn!("⚠️ Invalid CTF contract address, settlement watch disabled");
+        return None;
+    };
+    let (tx, rx) = mpsc::channel(TRANSFER_QUEUE);
+    let ws_url = ws_url.clone();
+    tokio::spawn(async move {
+        match MarketEventListener::new(&ws_url, ctf_address).await {
+            Ok(listener) => {
+                if let Err(e) = listener.listen_for_transfers(wallet, tx).await {
+                    error!("❌ Transfer listener error: {}", e);
                 }
             }
+            Err(e) => error!("❌ Failed to create transfer listener: {}", e),
         }
+    });
+    info!(
+        "⚡ Settlement watch enabled: fills confirmed by transfers to {:?}",
+        wallet
+    );
+    Some(rx)
+}
 
-        // Manage existing positions
-        self.manage_positions(&all_markets).await?;
+/// Book levels kept per side from WS snapshots (0 = all): `WS_BOOK_DEPTH`, never below what
+/// the feature tracker sums into its depth imbalance
+fn ws_book_depth(config: &Config) -> usize {
+    match config.agent.ws_book_depth {
+        0 => 0,
+        depth => depth.max(crate::markets::features::DEPTH_LEVELS),
+    }
+}
 
-        Ok(())
+/// Connect the CLOB WebSocket and the queue its book updates arrive on
+async fn connect_clob_ws(
+    config: &Config,
+    chaos: Option<Arc<ChaosMonkey>>,
+) -> Option<(ClobWebSocket, UpdateReceiver)> {
+    let (tx, rx) = update_queue(
+        config.agent.ws_queue_capacity,
+        DropPolicy::parse(&config.agent.ws_queue_policy),
+    );
+    let book_depth = ws_book_depth(config);
+    let throttle = TickThrottle::new(
+        config.agent.ws_marking_interval_ms,
+        config.agent.ws_full_rate_before_expiry_secs,
+    );
+    match ClobWebSocket::new(tx, book_depth, throttle, chaos).await {
+        Ok(ws) => Some((ws, rx)),
+        Err(e) => {
+            error!("❌ Failed to init CLOB WS: {}", e);
+            None
+        }
     }
+}
 
-    /// Process a single market through the entire pipeline
-    async fn process_single_market(&mut self, market: &MarketData) -> Result<()> {
-        // Filter out garbage markets
-        if !self.passes_filters(market) {
-            return Ok(());
+impl Sniper {
+    pub async fn new(config: Config, pnl_tracker: Arc<Mutex<PnLTracker>>) -> Result<Self> {
+        Self::builder(config).pnl_tracker(pnl_tracker).build().await
+    }
+
+    pub fn builder(config: Config) -> SniperBuilder {
+        SniperBuilder::new(config)
+    }
+
+    pub fn pnl_tracker(&self) -> Arc<Mutex<PnLTracker>> {
+        self.pnl_tracker.clone()
+    }
+
+    /// Book `trade_id` is kept in: the paper one for paper-only strategies
+    fn pnl_book(&self, trade_id: &str) -> &Arc<Mutex<PnLTracker>> {
+        match &self.paper_pnl {
+            Some(paper) if self.executor.is_paper(trade_id) => paper,
+            _ => &self.pnl_tracker,
         }
+    }
 
-        // Register for WS Updates
-        if let Some(ws) = &self.ws_client {
-            if !market.asset_ids.is_empty() {
-                let mut to_subscribe = Vec::new();
-                for asset_id in &market.asset_ids {
-                    if self.subscribed_assets.insert(asset_id.clone()) {
-                        to_subscribe.push(asset_id.clone());
-                    }
-                }
-                if !to_subscribe.is_empty() {
-                    info!("🔌 Subscribing to Orderbook for {}", market.question);
-                    ws.subscribe(to_subscribe);
-                }
+    /// Live book, then the paper one if any
+    fn pnl_books(&self) -> impl Iterator<Item = &Arc<Mutex<PnLTracker>>> {
+        std::iter::once(&self.pnl_tracker).chain(self.paper_pnl.as_ref())
+    }
 
-                // Cache state - BUT PRESERVE WS PRICES if they exist and are newer
-                if let Some(existing) = self.active_markets.get_mut(&market.id) {
-                    // Metadata update only
-                    existing.question = market.question.clone();
-                    existing.volume = market.volume;
-                    existing.liquidity = market.liquidity;
-                    existing.volume_24h = market.volume_24h;
+    /// Risk positions `trade_id` is kept with: the paper ones for paper-only strategies
+    fn risk_book(&mut self, trade_id: &str) -> &mut RiskManager {
+        if self.executor.is_paper(trade_id) {
+            &mut self.paper_risk
+        } else {
+            &mut self.risk_manager
+        }
+    }
 
-                    // Only update prices if Gamma has values AND WS hasn't provided better ones yet
-                    if existing.yes_price <= 0.0 && market.yes_price > 0.0 {
-                        existing.yes_price = market.yes_price;
-                    }
-                    if existing.no_price <= 0.0 && market.no_price > 0.0 {
-                        existing.no_price = market.no_price;
-                    }
-                } else {
-                    self.active_markets
-                        .insert(market.id.clone(), market.clone());
-                }
-
-                // Map Asset Maps
-                // Assuming asset_ids[0] = NO, asset_ids[1] = YES (This is standard for Polymarket CTF)
-                // But wait, let's just Map them both. We need to know which is which.
-                // In `client.rs` we didn't strictly order them, but `outcome_prices` logic assumed order.
-                // IMPORTANT: We need to know which asset_id is YES and which is NO.
-                // For now, let's simplistically Map:
-                // If we have 2 assets, assign them based on index (Hack for now, need robust mapping later)
-                if market.asset_ids.len() >= 2 {
-                    self.asset_map.insert(
-                        market.asset_ids[0].clone(),
-                        (market.id.clone(), "NO".to_string()),
-                    );
-                    self.asset_map.insert(
-                        market.asset_ids[1].clone(),
-                        (market.id.clone(), "YES".to_string()),
-                    );
+    /// Live positions, then the paper ones
+    fn all_positions(&self) -> Vec<risk::Position> {
+        let mut positions = self.risk_manager.get_positions();
+        positions.extend(self.paper_risk.get_positions());
+        positions
+    }
+
+    /// The paper book as of now, None unless some strategies are paper-only
+    fn paper_book(&self) -> Option<PaperBook> {
+        let tracker = self.paper_pnl.as_ref()?.lock().ok()?;
+        Some(PaperBook {
+            positions: self.paper_risk.get_positions(),
+            pnl: PnlState::capture(&tracker),
+        })
+    }
+
+    /// Write the paper book off the loop
+    fn save_paper_book(&self) {
+        let Some(book) = self.paper_book() else {
+            return;
+        };
+        let data_dir = self.config.agent.data_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = book.save(&data_dir) {
+                warn!("⚠️ Failed to persist the paper book: {}", e);
+            }
+        });
+    }
+
+    /// Write the market registry off the loop if it changed, mirroring it to storage
+    fn save_registry(&self) {
+        let registry = self.registry.clone();
+        let storage = self.storage.clone();
+        tokio::task::spawn_blocking(move || match registry.save_if_dirty() {
+            Ok(true) => {
+                if let Some(storage) = storage {
+                    storage.send(StorageOp::Registry {
+                        markets: registry.markets(),
+                        assets: registry.assets(),
+                    });
                 }
             }
-        }
+            Ok(false) => {}
+            Err(e) => warn!("⚠️ Failed to persist market registry: {}", e),
+        });
+    }
 
-        // 1) Primary: Last-minute predictive (Binance)
-        match self.predictive_strategy.check_opportunity(market).await {
-            TradeAction::Snipe {
-                market_id: _,
-                side,
-                price,
-                size_usd,
-            } => {
-                info!(
-                    "🎯 PREDICTIVE LAST-MINUTE Signal: {} (Side: {})",
-                    market.question, side
-                );
-                self.execute_snipe_signal(market, &side, price, size_usd, "pred", 0.8)
-                    .await?;
-                return Ok(());
+    /// Write the trade journal's changes off the loop
+    fn save_trade_journal(&self) {
+        let Some(unsaved) = self
+            .pnl_tracker
+            .lock()
+            .ok()
+            .and_then(|mut t| t.journal.take_unsaved())
+        else {
+            return;
+        };
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = unsaved.save() {
+                warn!("⚠️ Failed to persist trade journal: {}", e);
             }
-            _ => {}
-        }
+        });
+    }
 
-        // 2) Secondary: Expiration snipe
-        match self.expiration_strategy.check_opportunity(market) {
-            TradeAction::Snipe {
-                market_id: _,
-                side,
-                price,
-                size_usd,
-            } => {
+    /// Write the hedge book off the loop
+    fn save_hedge_book(&self) {
+        let Some(book) = self
+            .hedger
+            .as_ref()
+            .and_then(|h| h.lock().ok())
+            .map(|h| h.book())
+        else {
+            return;
+        };
+        let data_dir = self.config.agent.data_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = book.save(&data_dir) {
+                warn!("⚠️ Failed to persist the hedge book: {}", e);
+            }
+        });
+    }
+
+    /// Settle the paper positions whose market has resolved; the redeemer only sees live ones
+    async fn settle_paper_positions(&mut self) {
+        if self.paper_pnl.is_none() {
+            return;
+        }
+        let mut settled = false;
+        for position in self.paper_risk.get_positions() {
+            let Some(winner) = self.resolved_winner(&position.market_id).await else {
+                continue;
+            };
+            if let Some(Ok(mut tracker)) = self.paper_pnl.as_ref().map(|p| p.lock()) {
+                let pnl = tracker.resolve_market(&position.market_id, winner);
                 info!(
-                    "🎯 EXPIRATION SNIPE Signal: {} (Side: {})",
-                    market.question, side
+                    "📝 Paper {} settled {}: PnL ${:.2}",
+                    position.market_id, winner, pnl
                 );
-                self.execute_snipe_signal(market, &side, price, size_usd, "snipe", 0.9)
-                    .await?;
-                return Ok(());
             }
-            _ => {}
+            self.paper_risk.remove_position(&position.market_id);
+            settled = true;
         }
+        if settled {
+            self.save_paper_book();
+        }
+    }
+
+    /// Handles served by the HTTP API
+    pub fn api_state(&self) -> ApiState {
+        ApiState {
+            indexing_latency: self.indexing_latency.clone(),
+            lifecycle: self.lifecycle.clone(),
+            registry: self.registry.clone(),
+            session: self.session.clone(),
+            opportunities: self.opportunities.clone(),
+            edge_realization: self.edge_realization.clone(),
+            execution_drift: self.execution_drift.clone(),
+            postmortems: self.postmortems.clone(),
+            settlement: self.settlement.clone(),
+            margin: Some(self.margin.clone()),
+            breakers: self.breakers.clone(),
+            subscriptions: self.subscriptions.clone(),
+            pnl_tracker: self.pnl_tracker.clone(),
+            paper_pnl: self.paper_pnl.clone(),
+            watchlist: self.watchlist.clone(),
+            pins: self.pins.clone(),
+            market_lookup: Some(self.market_interface.clone()),
+            anomaly_guard: self.anomaly_guard.clone(),
+            whales: self.config.whales.enabled.then(|| self.whales.clone()),
+            zombies: self.config.zombies.enabled.then(|| self.zombies.clone()),
+            series: self.series_watcher.clone(),
+            fee_rates: Some(self.fee_rates.clone()),
+            missed_windows: Some(self.missed_windows.clone()),
+            hedger: self.hedger.clone(),
+            toxicity: self.toxicity.clone(),
+            profiles: self.profiles.clone(),
+            drift: self.drift.clone(),
+            ws_bandwidth: self.ws_client.as_ref().map(|ws| ws.bandwidth_meter()),
+            ws_sequencing: self.ws_client.as_ref().map(|ws| ws.sequencer()),
+            ws_control: self.ws_client.as_ref().map(|ws| ws.control()),
+            log_levels: logging::levels(),
+            price_feed: Some(self.price_feed.clone()),
+            llm_costs: self.llm_budget.clone(),
+            scheduler: Some(self.scheduler.stats()),
+            features: self.features.clone(),
+            failover: self.failover.clone(),
+            data_dir: self.config.agent.data_dir.clone(),
+            read_only: false,
+            auth: Arc::new(ApiAuth::new(
+                &self.config.agent,
+                Some(&self.config.agent.data_dir),
+            )),
+            summary: SummaryCache::new(self.config.agent.api_summary_cache_secs),
+        }
+    }
+
+    fn market_state(&self, market_id: &str) -> Option<MarketState> {
+        self.lifecycle.lock().ok()?.state(market_id)
+    }
+
+    fn has_metadata(&self, market_id: &str) -> bool {
+        self.market_state(market_id)
+            .is_some_and(|s| s.has_metadata())
+    }
+
+    /// Move a market forward in its lifecycle. False if it was already at or past `to`
+    fn advance(&self, market_id: &str, to: MarketState) -> bool {
+        let advanced = self
+            .lifecycle
+            .lock()
+            .map(|mut l| l.advance(market_id, to))
+            .unwrap_or(false);
+        if advanced {
+            self.store(StorageOp::Seen(market_id.to_string(), to));
+        }
+        advanced
+    }
+
+    fn store(&self, op: StorageOp) {
+        if let Some(storage) = &self.storage {
+            storage.send(op);
+        }
+    }
+
+    /// Store ended markets of the resolution dataset whose outcome is now known
+    async fn record_resolutions(&mut self) {
+        let Some(recorder) = &self.resolutions else {
+            return;
+        };
+        let pending = recorder.pending(Utc::now());
+        let this = &*self;
+        let resolved: Vec<(String, &'static str)> =
+            stream::iter(pending.into_iter().take(RESOLUTION_CHECKS_PER_TICK))
+                .map(|market_id| async move {
+                    let winner = this.resolution_winner(&market_id).await;
+                    winner.map(|winner| (market_id, winner))
+                })
+                .buffer_unordered(RESOLUTION_CHECKS_IN_FLIGHT)
+                .filter_map(|resolved| async move { resolved })
+                .collect()
+                .await;
+        for (market_id, winner) in resolved {
+            let Some(resolution) = self
+                .resolutions
+                .as_mut()
+                .and_then(|r| r.resolve(&market_id, winner, Utc::now()))
+            else {
+                continue;
+            };
+            info!(
+                "📚 Resolution dataset: {} -> {} ({} price samples)",
+                resolution.question,
+                winner,
+                resolution.trajectory.len()
+            );
+            self.store(StorageOp::Resolution(resolution));
+        }
+    }
+
+    /// Collateral backing the market's condition (redemption and merging name it)
+    fn collateral_of(&self, market_id: &str) -> PositionCollateral {
+        match ConditionId::parse(market_id) {
+            Ok(condition) => self.derived_assets.collateral_of(&condition),
+            Err(_) => self.derived_assets.default_collateral().clone(),
+        }
+    }
+
+    /// On-chain payouts when the redemption manager is up, else Gamma's outcome prices once
+    /// they have settled at 1 / 0. None while unresolved
+    async fn resolution_winner(&self, market_id: &str) -> Option<&'static str> {
+        if let Some(rm) = &self.redemption_manager {
+            let rpc = self
+                .breakers
+                .rpc(self.config.polygon_ws_rpc.as_deref().unwrap_or_default());
+            match rpc.call(rm.winning_side(market_id)).await {
+                Ok(winner) => return winner,
+                Err(e) => debug!("Resolution check via RPC failed for {}: {}", market_id, e),
+            }
+        }
+        match self.market_interface.get_market_details(market_id).await {
+            Ok(market) => winner_from_prices(market.yes_price, market.no_price),
+            Err(e) => {
+                debug!("Resolution check via Gamma failed for {}: {}", market_id, e);
+                None
+            }
+        }
+    }
+
+    /// Price every Polymarket / Kalshi pair and buy the sets that still pay after fees. The
+    /// scan runs off the loop, one at a time, against a snapshot of the open positions; the
+    /// sets it executes come back through `cross_venue_rx`
+    fn scan_cross_venue(&mut self) {
+        if !self.failover_allows_trading() {
+            return;
+        }
+        let (Some(strategy), Some(executor)) = (&self.cross_venue, &self.cross_venue_executor)
+        else {
+            return;
+        };
+        if self.cross_venue_scanning.swap(true, Ordering::SeqCst) {
+            debug!("Cross-venue scan still running, skipping");
+            return;
+        }
+        let mut strategy = strategy.snapshot();
+        let executor = executor.clone();
+        let market_interface = self.market_interface.clone();
+        let opened = self.cross_venue_tx.clone();
+        let scanning = self.cross_venue_scanning.clone();
+        tokio::spawn(async move {
+            for pair in strategy.pairs().to_vec() {
+                if strategy.has_position(&pair) {
+                    continue;
+                }
+                let quotes = tokio::try_join!(
+                    market_interface.get_market_details(&pair.polymarket_id),
+                    executor.kalshi().get_market_details(&pair.kalshi_ticker)
+                );
+                let (polymarket, kalshi) = match quotes {
+                    Ok((polymarket, kalshi)) => (polymarket, pair.align(kalshi)),
+                    Err(e) => {
+                        debug!(
+                            "Cross-venue quotes for {} / {} unavailable: {:#}",
+                            pair.polymarket_id, pair.kalshi_ticker, e
+                        );
+                        continue;
+                    }
+                };
+                let Some(signal) = strategy.evaluate(&pair, &polymarket, &kalshi) else {
+                    continue;
+                };
+
+                info!(
+                    "🔀 CROSS-VENUE Signal: {} | YES on {} @ {:.3} + NO on {} @ {:.3} | {} sets | net {} bps (${:.2} after ${:.2} fees, ${:.2} friction)",
+                    signal.question,
+                    signal.buy_yes_on.name(),
+                    signal.yes_price,
+                    signal.buy_no_on().name(),
+                    signal.no_price,
+                    signal.contracts,
+                    signal.net_edge_bps,
+                    signal.net_profit_usd,
+                    signal.fees_usd,
+                    signal.friction_usd
+                );
+                let id = format!(
+                    "xv_{}_{}",
+                    pair.polymarket_id,
+                    Utc::now().timestamp_millis()
+                );
+                match executor.execute(&signal).await {
+                    Ok(fill) => {
+                        let position = CrossVenuePosition {
+                            id,
+                            signal,
+                            polymarket_order: fill.polymarket_order,
+                            kalshi_order: fill.kalshi_order,
+                            kalshi_unwind: fill.kalshi_unwind,
+                            end_date: polymarket.end_time,
+                            opened_at: Utc::now(),
+                        };
+                        // The rest of the scan counts it against the venue limits
+                        strategy.open(position.clone());
+                        let _ = opened.send(position);
+                    }
+                    Err(e) => error!("❌ Cross-venue {} not executed: {:#}", id, e),
+                }
+            }
+            scanning.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Open a set the scan executed: each leg held goes to the risk manager (the Kalshi ones
+    /// under `kalshi:<ticker>`) and to the PnL tracker, in Polymarket's orientation so the
+    /// question's resolution settles them all
+    fn open_cross_venue(&mut self, position: CrossVenuePosition) {
+        if position.is_one_sided() {
+            warn!(
+                "⚠️ Cross-venue {} is one-sided until resolution",
+                position.id
+            );
+        }
+        let pair = &position.signal.pair;
+        let market_question = position.signal.question.clone();
+        let mut legs = Vec::new();
+        for (leg_id, side, price, size_usd) in position.legs() {
+            let market_id = if leg_id == position.leg_id(Venue::Polymarket) {
+                pair.polymarket_id.clone()
+            } else {
+                kalshi_position_key(&pair.kalshi_ticker)
+            };
+            let added = self.risk_manager.increase_position(
+                &market_id,
+                UsdAmount::new(size_usd),
+                Price::new(price),
+            );
+            if !added {
+                self.risk_manager.add_position(
+                    market_id,
+                    leg_id.clone(),
+                    side.to_string(),
+                    UsdAmount::new(size_usd),
+                    Price::new(price),
+                );
+            }
+            legs.push(Position {
+                id: leg_id,
+                market_id: pair.polymarket_id.clone(),
+                market_question: market_question.clone(),
+                side: side.to_string(),
+                size: size_usd,
+                entry_price: price,
+                current_price: price,
+                entry_time: Utc::now(),
+                config_hash: None,
+                group_id: None,
+            });
+        }
+        if let Ok(mut tracker) = self.pnl_book(&position.id).lock() {
+            tracker.add_group(&position.id, legs);
+        }
+        if let Some(strategy) = self.cross_venue.as_mut() {
+            strategy.open(position);
+        }
+    }
+
+    /// Book the result of the cross-venue sets whose question has resolved, then close them.
+    /// Ended ones still waiting for their resolution stay open
+    async fn settle_cross_venue(&mut self) {
+        let ended = match &self.cross_venue {
+            Some(strategy) => strategy.ended(Utc::now()),
+            None => return,
+        };
+        for position in ended {
+            let market_id = position.signal.pair.polymarket_id.clone();
+            let Some(winner) = self.resolved_winner(&market_id).await else {
+                continue;
+            };
+            let mut pnl = 0.0;
+            if let Ok(mut tracker) = self.pnl_book(&position.id).lock() {
+                for (leg_id, _, _, _) in position.legs() {
+                    pnl += tracker.settle_position(&leg_id, winner).unwrap_or_default();
+                }
+            }
+            // Kalshi pays out on its own; the Polymarket leg is left to the redeemer
+            self.risk_manager
+                .remove_position(&kalshi_position_key(&position.signal.pair.kalshi_ticker));
+            info!(
+                "🏁 Cross-venue {} settled {}: PnL ${:.2} (expected ${:.2})",
+                position.id, winner, pnl, position.signal.net_profit_usd
+            );
+            if let Some(strategy) = self.cross_venue.as_mut() {
+                strategy.close(&position.id);
+            }
+        }
+    }
+
+    /// Winning outcome of a resolved market: on-chain with a wallet, else from its last prices
+    async fn resolved_winner(&self, market_id: &str) -> Option<&'static str> {
+        if let Some(rm) = &self.redemption_manager {
+            return match rm.is_condition_resolved(market_id).await {
+                Ok(true) => rm.winning_side(market_id).await.ok().flatten(),
+                _ => None,
+            };
+        }
+        let market = self.registry.get(market_id)?;
+        // Resolved outcomes trade at (or within a tick of) 1 and 0
+        if market.yes_price >= 0.99 {
+            Some("YES")
+        } else if market.no_price >= 0.99 {
+            Some("NO")
+        } else {
+            None
+        }
+    }
+
+    fn queue_metadata_retry(&self, market_id: &str) {
+        if let Ok(mut lifecycle) = self.lifecycle.lock() {
+            lifecycle.queue_metadata_retry(market_id);
+        }
+    }
+
+    /// Strategy signal as the active parameter profile allows it and, on a market still
+    /// without metadata, the synthetic policy
+    fn gate_action(
+        &self,
+        strategy: &str,
+        market: &MarketData,
+        action: TradeAction,
+    ) -> TradeAction {
+        let action = match self.profiles.as_ref().map(|p| p.lock()) {
+            Some(Ok(profiles)) => profiles.gate(strategy, action),
+            _ => action,
+        };
+        if self.has_metadata(&market.id) {
+            return action;
+        }
+        self.synthetic.gate(strategy, action)
+    }
+
+    /// Put the risk limits of the parameter profile scheduled at `now` in force
+    fn apply_profile(&mut self, now: DateTime<Utc>) {
+        let Some(Ok(mut profiles)) = self.profiles.as_ref().map(|p| p.lock()) else {
+            return;
+        };
+        if !profiles.update(now) {
+            return;
+        }
+        match profiles.active() {
+            Some(profile) => info!("⏰ Parameter profile {} in force", profile.name),
+            None => info!("⏰ Parameter profiles over, back to the configured limits"),
+        }
+        let risk = profiles.risk_config().clone();
+        self.risk_manager.set_config(risk.clone());
+        self.paper_risk.set_config(risk.clone());
+        self.config.risk = risk;
+    }
+
+    /// Give up on synthetic markets whose metadata never came: no more fetches, and their
+    /// books are unsubscribed unless a position still needs marking
+    fn abandon_synthetic_markets(&mut self) {
+        let abandoned = self.synthetic.expire(Utc::now());
+        if abandoned.is_empty() {
+            return;
+        }
+        let held: HashSet<String> = self
+            .risk_manager
+            .get_positions()
+            .into_iter()
+            .map(|p| p.market_id)
+            .collect();
+        for market_id in abandoned {
+            warn!(
+                "⌛ No metadata for synthetic market {} after {}s, abandoning it",
+                market_id, self.config.synthetic.metadata_timeout_secs
+            );
+            if let Ok(mut lifecycle) = self.lifecycle.lock() {
+                lifecycle.cancel_metadata_retry(&market_id);
+            }
+            if held.contains(&market_id) {
+                continue;
+            }
+            let Some(market) = self.registry.get(&market_id) else {
+                continue;
+            };
+            self.unsubscribe_assets(&market.asset_ids);
+        }
+    }
+
+    /// Main agent loop: trade until SIGINT / SIGTERM or a risk halt, and say which one stopped the run
+    pub async fn run(&mut self) -> Result<ExitReason> {
+        info!("🚀 Starting Polymarket HFT Agent");
+        let report = ExitReport::running(
+            &self.session.session_id,
+            self.session.started_at,
+            self.config.agent.paper_trading,
+        );
+        if let Err(e) = report.save(&self.exit_report_path()) {
+            warn!("⚠️ Failed to write exit report: {:#}", e);
+        }
+
+        info!(
+            "📊 Mode: {}",
+            if self.config.agent.paper_trading {
+                "PAPER TRADING"
+            } else {
+                "LIVE TRADING"
+            }
+        );
+        info!(
+            "⏱️  Poll interval: {} seconds",
+            self.config.agent.market_poll_interval_secs
+        );
+
+        // Recurring maintenance, run by the loop between events
+        self.scheduler.every(
+            Maintenance::MetadataRetry,
+            "metadata-retry",
+            Duration::from_secs(1),
+        );
+        self.scheduler.every(
+            Maintenance::MarketPoll,
+            "market-poll",
+            Duration::from_secs(self.config.agent.market_poll_interval_secs.max(1)),
+        );
+
+        // Fast new-market discovery (sub-second, conditional requests); 0 disables it
+        if self.config.agent.new_market_poll_interval_ms > 0 {
+            info!(
+                "⏱️  New-market poll interval: {} ms",
+                self.config.agent.new_market_poll_interval_ms
+            );
+            self.scheduler.every(
+                Maintenance::NewMarketPoll,
+                "new-market-poll",
+                Duration::from_millis(self.config.agent.new_market_poll_interval_ms),
+            );
+        }
+
+        // Pre-expiry watchlist re-evaluation (only with the expiration strategy on)
+        if self.config.expiration.enabled {
+            self.scheduler.every(
+                Maintenance::Watchlist,
+                "watchlist",
+                Duration::from_millis(self.config.expiration.watch_poll_interval_ms.max(1)),
+            );
+        }
+
+        // Gas price for the edge checks, kept off the signal path
+        if self.redemption_manager.is_some() {
+            self.scheduler
+                .every(Maintenance::GasPrice, "gas-price", Duration::from_secs(15));
+        }
+
+        // Posted arbitrage legs: booked as they fill, cancelled at their deadline
+        if self.config.order_role.enabled {
+            self.scheduler
+                .every(Maintenance::MakerLegs, "maker-legs", Duration::from_secs(5));
+        }
+
+        // Answered orders out of the WAL, so it doesn't grow for the whole run
+        if self.order_wal.is_some() {
+            self.scheduler.every(
+                Maintenance::CompactWal,
+                "order-wal",
+                Duration::from_secs(600),
+            );
+        }
+
+        // Redemptions, resolution outcomes and edge fills (every 5 minutes)
+        self.scheduler
+            .every(
+                Maintenance::Redemption,
+                "redemption",
+                Duration::from_secs(300),
+            )
+            .jitter(Duration::from_secs(15));
+
+        // Resolution dataset price sampling
+        if self.resolutions.is_some() {
+            self.scheduler.every(
+                Maintenance::ResolutionSample,
+                "resolution-sample",
+                Duration::from_secs(self.config.agent.resolution_sample_secs.max(1)),
+            );
+        }
+
+        // Cross-venue pair scans
+        if self.cross_venue.as_ref().is_some_and(|c| c.enabled()) {
+            self.scheduler.every(
+                Maintenance::CrossVenue,
+                "cross-venue",
+                Duration::from_secs(self.config.cross_venue.poll_interval_secs.max(1)),
+            );
+        }
+
+        // Operator-pinned markets
+        self.scheduler.every(
+            Maintenance::PinnedMarkets,
+            "pinned-markets",
+            Duration::from_millis(self.config.agent.pinned_poll_interval_ms.max(100)),
+        );
+
+        self.scheduler.every(
+            Maintenance::PnlUpdate,
+            "pnl-update",
+            Duration::from_secs(10),
+        );
+        self.scheduler.every(
+            Maintenance::QueueStats,
+            "queue-stats",
+            Duration::from_secs(60),
+        );
+
+        // Registry persistence (only when changed)
+        self.scheduler.every(
+            Maintenance::RegistrySave,
+            "registry-save",
+            Duration::from_secs(30),
+        );
+
+        self.scheduler.every(
+            Maintenance::Funding,
+            "funding",
+            Duration::from_secs(self.config.funding.check_interval_secs.max(1)),
+        );
+        self.scheduler.every(
+            Maintenance::ExperimentReport,
+            "experiment-report",
+            Duration::from_secs(900),
+        );
+
+        // Resolved-but-open markets off the strategies
+        if self.config.zombies.enabled {
+            self.scheduler.every(
+                Maintenance::Zombies,
+                "zombies",
+                Duration::from_secs(self.config.zombies.check_interval_secs.max(1)),
+            );
+        }
+
+        // Fee rates of markets never checked or due a recheck (fee-free promotional markets)
+        if self.config.zero_fee.enabled {
+            self.scheduler
+                .every(Maintenance::FeeRates, "fee-rates", Duration::from_secs(60));
+        }
+
+        // New event series on Gamma (new recurring market families)
+        if self.series_watcher.is_some() {
+            self.scheduler.every(
+                Maintenance::SeriesWatch,
+                "series-watch",
+                Duration::from_secs(self.config.series_watch.poll_interval_secs.max(1)),
+            );
+        }
+
+        // Self report of memory, tasks, maps and channels
+        if self.self_monitor.enabled() {
+            self.scheduler.every(
+                Maintenance::SelfMonitor,
+                "self-monitor",
+                Duration::from_secs(self.config.self_monitor.interval_mins.max(1) * 60),
+            );
+        }
+
+        // Re-values open arbitrage pairs: merge early, sell a leg, or hold
+        if self.config.arb_exit.enabled {
+            self.scheduler.every(
+                Maintenance::ArbExit,
+                "arb-exit",
+                Duration::from_secs(self.config.arb_exit.check_interval_secs.max(1)),
+            );
+        }
+
+        // Checks whether the daily report is due
+        if self.daily_report.enabled() {
+            self.scheduler.every(
+                Maintenance::DailyReport,
+                "daily-report",
+                Duration::from_secs(60),
+            );
+        }
+
+        // Anonymized metrics push, jittered so a fleet started together doesn't push in lockstep
+        if self.telemetry.enabled() {
+            let every = self.telemetry.interval();
+            self.scheduler
+                .every(Maintenance::Telemetry, "telemetry", every)
+                .jitter(every / 10);
+        }
+
+        // Places the signals waiting for capital once it lands
+        if self.capital_wait.enabled() {
+            self.scheduler.every(
+                Maintenance::CapitalWait,
+                "capital-wait",
+                Duration::from_secs(5),
+            );
+        }
+
+        // Switches to the parameter profile scheduled now
+        if self.profiles.is_some() {
+            self.scheduler
+                .every(Maintenance::Profile, "profile", Duration::from_secs(10));
+        }
+
+        // Full state snapshot for `--restore` (also written on Ctrl+C); nothing worth saving yet
+        if self.config.agent.state_snapshot_interval_secs > 0 {
+            self.scheduler
+                .every(
+                    Maintenance::StateSnapshot,
+                    "state-snapshot",
+                    Duration::from_secs(self.config.agent.state_snapshot_interval_secs),
+                )
+                .delayed();
+        }
+
+        // Heartbeats with the peer instance; order entry waits for the first round
+        if let Some(failover) = &self.failover {
+            info!(
+                "🔀 Failover: {} with peer {}",
+                self.config.failover.role,
+                self.config.failover.peer_url.as_deref().unwrap_or("-")
+            );
+            if self.config.agent.api_port == 0 {
+                warn!("⚠️ API_PORT=0: the peer can't read this instance's heartbeat");
+            } else if self
+                .config
+                .agent
+                .api_bind
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+            {
+                warn!(
+                    "⚠️ API_BIND={}: a peer on another host can't read this instance's heartbeat",
+                    self.config.agent.api_bind
+                );
+            }
+            if self.config.agent.api_admin_tokens.is_empty() {
+                warn!("⚠️ No API_ADMIN_TOKENS: the peer can't fence this instance over the API");
+            }
+            tokio::spawn(failover.clone().run());
+        }
+
+        // Async Retry Results Channel
+        let (retry_tx, mut retry_rx) = mpsc::channel::<(String, u8, Result<MarketData>)>(1000);
+
+        // Registered once: a fresh handler per iteration could miss a signal between two
+        let shutdown = self.shutdown.clone();
+        let signal = async move {
+            tokio::select! {
+                signal = exit_report::shutdown_signal() => signal,
+                _ = shutdown.requested() => "shutdown requested",
+            }
+        };
+        tokio::pin!(signal);
+
+        loop {
+            if let Some(failover) = self.failover.clone() {
+                failover.mark_alive();
+                if failover.handover_pending() {
+                    self.resume_peer_snapshot();
+                    failover.complete_handover();
+                }
+            }
+            // Branches only ingest events; priority is enforced by the job queue drained below
+            tokio::select! {
+                // Shutdown: persist what a `--restore` start needs, then stop
+                signal = &mut signal => {
+                    info!("🛑 Shutting down ({})", signal);
+                    self.persist_for_restart();
+                    return Ok(ExitReason::clean(signal));
+                }
+
+                // WebSocket events (New Markets)
+                Some(condition_id) = async {
+                    match &mut self.new_market_rx {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await, // Never resolves if no WS
+                    }
+                } => {
+                    self.jobs.push(Job::NewCondition(condition_id));
+                }
+
+                // On-chain resolutions: redeem and true up PnL now instead of at the next round
+                Some(condition_id) = async {
+                    match &mut self.resolution_rx {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    self.on_condition_resolved(&condition_id).await;
+                }
+
+                // Cross-venue sets executed off the loop
+                Some(position) = self.cross_venue_rx.recv() => {
+                    self.open_cross_venue(position);
+                }
+
+                // Pinned markets fetched off the loop
+                Some(fetched) = self.pinned_rx.recv() => {
+                    self.on_pinned_markets(fetched).await;
+                }
+
+                // Redemption batches sent off the loop
+                Some(round) = self.redeemed_rx.recv() => {
+                    self.on_redemptions(round).await;
+                }
+
+                // Balance refreshes read off the loop
+                Some(refresh) = self.balance_rx.recv() => {
+                    self.apply_balance(refresh).await;
+                    self.refreshing_balance.store(false, Ordering::SeqCst);
+                }
+
+                // Snipe order statuses read off the loop
+                Some((snipe, status, filled)) = self.snipe_fill_rx.recv() => {
+                    self.on_snipe_fill(snipe, status, filled).await;
+                }
+
+                // Entries whose obfuscation delay ran out
+                Some(jittered) = self.jittered_rx.recv() => {
+                    self.release_jittered(jittered).await;
+                }
+
+                // Outcome tokens moving through the funder wallet: settle the fills they belong to
+                Some(transfer) = async {
+                    match &mut self.transfer_rx {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    self.on_token_transfer(transfer);
+                }
+
+                // 0. Async Retry Results handling
+                Some((condition_id, attempts, result)) = retry_rx.recv() => {
+                     match result {
+                        Ok(market) => {
+                            info!("✅ Sync success for {} after {} attempts", market.question, attempts);
+                            if let Ok(mut tracker) = self.indexing_latency.lock() {
+                                tracker.mark_gamma_available(&condition_id);
+                            }
+                            self.jobs.push(Job::NewMarket(market));
+                        },
+                        Err(e) => {
+                            let max_attempts = 60;
+                            if self.synthetic.is_abandoned(&condition_id) {
+                                debug!("⏭️ Not retrying abandoned synthetic market {}", condition_id);
+                            } else if attempts < max_attempts {
+                                // Silent retry for most attempts to avoid log spam
+                                if attempts == 1 || attempts % 20 == 0 {
+                                    info!("⏳ Still waiting for Gamma to sync market {} (Attempt {})", condition_id, attempts);
+                                }
+                                self.queue_metadata_retry(&condition_id);
+                            } else {
+                                error!("❌ Gave up fetching {} after {} attempts. Error: {}", condition_id, max_attempts, e);
+                            }
+                        }
+                     }
+                }
+                // 1. CLOB Orderbook Updates (state is applied here, re-evaluation is queued)
+                Some(update) = async {
+                    match &mut self.ws_update_rx {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                     // Coalesce the burst: only the newest book per asset is worth evaluating
+                     let updates = match &mut self.ws_update_rx {
+                         Some(rx) => rx.drain_latest(update, WS_COALESCE_MAX_BATCH),
+                         None => vec![update],
+                     };
+
+                     let dirty_markets = self.apply_book_updates(updates);
+
+                     // Queue re-evaluation on the merged state (held markets get protection first)
+                     let positions = self.all_positions();
+                     for (market_id, received_ns) in dirty_markets {
+                         if positions.iter().any(|p| p.market_id == market_id) {
+                             self.jobs.push(Job::ProtectPosition(market_id.clone()));
+                         }
+                         self.jobs.push(Job::Reevaluate(market_id, received_ns));
+                     }
+                }
+
+                // Recurring maintenance (registered with the scheduler above)
+                task = self.scheduler.next() => {
+                    let started = Instant::now();
+                    self.run_maintenance(task, &retry_tx).await;
+                    match self.scheduler.finish(task, started, Instant::now()) {
+                        Some(OverrunChange::Started { name, duration_ms }) => warn!(
+                            "⏲️ Maintenance task {} overran its interval ({:.0}ms)",
+                            name, duration_ms
+                        ),
+                        Some(OverrunChange::Cleared { name }) => {
+                            info!("⏲️ Maintenance task {} is back on schedule", name)
+                        }
+                        None => {}
+                    }
+                }
+            }
+
+            // Drain queued work: new markets > position protection > routine re-evaluation
+            self.run_jobs().await;
+
+            if let Some(reason) = self.risk_halt.take() {
+                error!("🛑 Risk halt: {}", reason);
+                self.persist_for_restart();
+                return Ok(ExitReason::risk_halt(reason));
+            }
+        }
+    }
+
+    /// Write the Gamma metadata if any was fetched since the last save
+    fn save_metadata_cache(&self) {
+        let Some(cache) = &self.metadata_cache else {
+            return;
+        };
+        if let Err(e) = cache.save_if_dirty(Utc::now()) {
+            warn!("⚠️ Failed to persist market metadata: {}", e);
+        }
+    }
+
+    /// Write the LLM cost ledger if calls were booked since the last save
+    fn save_llm_ledger(&self) {
+        let Some(Ok(mut budget)) = self.llm_budget.as_ref().map(|b| b.lock()) else {
+            return;
+        };
+        if let Err(e) = budget.save_if_dirty() {
+            warn!("⚠️ Failed to persist the LLM ledger: {}", e);
+        }
+    }
+
+    /// Persist what a `--restore` start needs
+    fn persist_for_restart(&self) {
+        if let Err(e) = self.registry.save() {
+            warn!("⚠️ Failed to persist market registry: {}", e);
+        }
+        if let Err(e) = self.derived_assets.save_if_dirty() {
+            warn!("⚠️ Failed to persist derived asset ids: {}", e);
+        }
+        self.save_metadata_cache();
+        self.save_llm_ledger();
+        self.save_state_snapshot_now();
+        let unsaved = self
+            .pnl_tracker
+            .lock()
+            .ok()
+            .and_then(|mut t| t.journal.take_unsaved());
+        if let Some(Err(e)) = unsaved.map(|journal| journal.save()) {
+            warn!("⚠️ Failed to persist trade journal: {}", e);
+        }
+        if let Some(book) = self.paper_book() {
+            if let Err(e) = book.save(&self.config.agent.data_dir) {
+                warn!("⚠️ Failed to persist the paper book: {}", e);
+            }
+        }
+        if let Some(Ok(hedger)) = self.hedger.as_ref().map(|h| h.lock()) {
+            if let Err(e) = hedger.book().save(&self.config.agent.data_dir) {
+                warn!("⚠️ Failed to persist the hedge book: {}", e);
+            }
+        }
+    }
+
+    fn exit_report_path(&self) -> std::path::PathBuf {
+        exit_report::exit_report_path(
+            &self.config.agent.data_dir,
+            self.config.agent.exit_report_path.as_deref(),
+        )
+    }
+
+    /// Write the exit report of the run stopped for `reason`: what's left open and the last
+    /// errors, for systemd / Kubernetes wrappers and alerting
+    pub async fn write_exit_report(&self, reason: &ExitReason) {
+        let open_orders = match tokio::time::timeout(
+            Duration::from_secs(5),
+            self.market_interface.get_open_orders(None),
+        )
+        .await
+        {
+            Ok(Ok(orders)) => Some(orders),
+            Ok(Err(e)) => {
+                warn!("⚠️ Open orders not fetched for the exit report: {}", e);
+                None
+            }
+            Err(_) => {
+                warn!("⚠️ Open orders not fetched for the exit report: timed out");
+                None
+            }
+        };
+        let report = ExitReport {
+            open_positions: self.risk_manager.get_positions(),
+            open_orders,
+            pending_retries: PendingRetries {
+                metadata: self
+                    .lifecycle
+                    .lock()
+                    .map(|l| l.metadata_retry_queue())
+                    .unwrap_or_default(),
+                capital_wait: self.capital_wait.len(),
+            },
+            last_errors: report::recent_errors(),
+            ..ExitReport::running(
+                &self.session.session_id,
+                self.session.started_at,
+                self.config.agent.paper_trading,
+            )
+        }
+        .stopped(reason, Utc::now());
+        let path = self.exit_report_path();
+        match report.save(&path) {
+            Ok(()) => info!(
+                "📝 Exit report ({:?}, exit code {}) written to {:?}",
+                report.kind, report.exit_code, path
+            ),
+            Err(e) => warn!("⚠️ Failed to write exit report: {:#}", e),
+        }
+    }
+
+    /// One run of a recurring maintenance task
+    async fn run_maintenance(
+        &mut self,
+        task: Maintenance,
+        retry_tx: &mpsc::Sender<(String, u8, Result<MarketData>)>,
+    ) {
+        match task {
+            // Metadata of conditions Gamma hadn't indexed yet (non-blocking spawning)
+            Maintenance::MetadataRetry => {
+                self.abandon_synthetic_markets();
+                // Process a batch of retries to avoid spawning too many tasks at once
+                // While Gamma's breaker is open the queue is kept instead of spawning fetches
+                // that would be rejected; once a probe is due a single fetch goes out
+                let batch_size = if self.breakers.gamma.is_open() {
+                    0
+                } else if self.breakers.gamma.snapshot().state != BreakerState::Closed {
+                    1
+                } else {
+                    20
+                };
+                let batch = self
+                    .lifecycle
+                    .lock()
+                    .map(|mut l| l.next_metadata_retries(batch_size))
+                    .unwrap_or_default();
+                for (condition_id, attempts) in batch {
+                    // Spawn async fetch
+                    let client = self.market_interface.clone();
+                    let tx = retry_tx.clone();
+
+                    tokio::spawn(async move {
+                        let res = client.get_market_details(&condition_id).await;
+                        let _ = tx.send((condition_id, attempts, res)).await;
+                    });
+                }
+            }
+            // Fast new-market poll (only fetches what changed since last time)
+            Maintenance::NewMarketPoll => match self.market_interface.poll_new_markets().await {
+                Ok(markets) => {
+                    for market in markets {
+                        if let Ok(mut tracker) = self.indexing_latency.lock() {
+                            tracker.mark_gamma_available(&market.id);
+                        }
+                        if !self.has_metadata(&market.id) {
+                            info!("🆕 NEW market detected (fast poll): {}", market.question);
+                            self.jobs.push(Job::NewMarket(market));
+                        }
+                    }
+                }
+                Err(e) => debug!("Fast new-market poll failed: {}", e),
+            },
+            // Polling (BACKUP - catches anything WS might miss)
+            Maintenance::MarketPoll => {
+                if let Err(e) = self.process_markets().await {
+                    error!("❌ Error processing markets: {}", e);
+                }
+                if self.config.expiration.enabled {
+                    self.promote_expiring_markets();
+                }
+                // Saturation spread log is only useful for arbitrage mode.
+                if self.config.arbitrage.enabled {
+                    self.log_top_opportunities().await;
+                }
+            }
+            // Time to expiry changes the expiration strategy's answer without a book update
+            Maintenance::Watchlist => {
+                let watched = self
+                    .watchlist
+                    .lock()
+                    .map(|w| w.market_ids())
+                    .unwrap_or_default();
+                for market_id in watched {
+                    self.jobs.push(Job::Reevaluate(market_id, None));
+                }
+            }
+            Maintenance::ResolutionSample => {
+                if let Some(recorder) = self.resolutions.as_mut() {
+                    recorder.observe(&self.registry.markets(), Utc::now());
+                }
+            }
+            Maintenance::CrossVenue => self.scan_cross_venue(),
+            Maintenance::PinnedMarkets => self.poll_pinned_markets(),
+            Maintenance::Redemption => {
+                self.refresh_edge_fills().await;
+                self.record_resolutions().await;
+                self.settle_cross_venue().await;
+                self.settle_paper_positions().await;
+                self.redeem_resolved(None).await;
+                self.convert_neg_risk_baskets().await;
+            }
+            Maintenance::PnlUpdate => {
+                self.resubscribe_pinned();
+                self.update_pnl_prices().await;
+                self.hedge_filled_snipes().await;
+                self.save_trade_journal();
+            }
+            Maintenance::QueueStats => self.log_queue_stats(),
+            Maintenance::SelfMonitor => {
+                let retry_depth = retry_tx.max_capacity() - retry_tx.capacity();
+                let report = self.self_report(retry_depth);
+                self.self_monitor.report(report);
+            }
+            Maintenance::ArbExit => self.manage_arb_exits().await,
+            Maintenance::DailyReport => {
+                self.daily_report.collect_deliveries();
+                if self.daily_report.due(Utc::now()) {
+                    self.send_daily_report().await;
+                }
+            }
+            Maintenance::Telemetry => {
+                let snapshot = self.telemetry_snapshot();
+                self.telemetry.push(snapshot);
+            }
+            Maintenance::CapitalWait => self.run_waiting_signals().await,
+            Maintenance::Profile => self.apply_profile(Utc::now()),
+            Maintenance::StateSnapshot => self.save_state_snapshot(),
+            Maintenance::Funding => {
+                let remote = self.remote_executor.is_some() && self.config.funding.enabled;
+                if self.funding.is_some() || remote {
+                    self.check_funding().await;
+                }
+            }
+            Maintenance::RegistrySave => {
+                self.save_registry();
+                if let Err(e) = self.derived_assets.save_if_dirty() {
+                    warn!("⚠️ Failed to persist derived asset ids: {}", e);
+                }
+                self.save_metadata_cache();
+                self.save_llm_ledger();
+                if let Some(Ok(mut book)) = self.opportunities.as_ref().map(|b| b.lock()) {
+                    if let Err(e) = book.flush() {
+                        warn!("⚠️ Failed to persist opportunity book: {}", e);
+                    }
+                }
+            }
+            Maintenance::ExperimentReport => {
+                if let Some(exp) = &self.experiment {
+                    match exp.write_report(&self.config.agent.data_dir) {
+                        Ok(report) => info!(
+                            "🧪 Experiment [{}]: control {} pos, mean {:+.4} | treatment {} pos, mean {:+.4} | t={:.2} p={:.3}",
+                            report.strategy,
+                            report.control.positions,
+                            report.control.mean_return,
+                            report.treatment.positions,
+                            report.treatment.mean_return,
+                            report.t_stat,
+                            report.p_value
+                        ),
+                        Err(e) => warn!("⚠️ Failed to write experiment report: {}", e),
+                    }
+                }
+            }
+            Maintenance::Zombies => self.scan_zombies(),
+            Maintenance::FeeRates => self.refresh_fee_rates(),
+            Maintenance::SeriesWatch => self.watch_series(),
+            Maintenance::GasPrice => self.cached_gas_gwei = self.gas_price_gwei().await,
+            Maintenance::MakerLegs => self.check_maker_legs().await,
+            Maintenance::CompactWal => {
+                if let Some(wal) = self.order_wal.clone() {
+                    match tokio::task::spawn_blocking(move || wal.compact()).await {
+                        Ok(Err(e)) => warn!("⚠️ Failed to compact the order WAL: {}", e),
+                        Err(e) => warn!("⚠️ Order WAL compaction failed: {}", e),
+                        Ok(Ok(())) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fetch the fee rate of a batch of markets due a check, from their YES token (both
+    /// tokens of a market share the rate). Spawned: a slow CLOB doesn't hold the run loop
+    fn refresh_fee_rates(&self) {
+        if self.breakers.clob.is_open() {
+            return;
+        }
+        let now = Utc::now();
+        let refresh = chrono::Duration::seconds(self.config.zero_fee.refresh_secs as i64);
+        let due = self
+            .registry
+            .markets()
+            .into_iter()
+            .filter(|market| self.fee_rates.is_due(&market.id, refresh, now))
+            .filter_map(|market| {
+                // asset_ids follow the [NO, YES] convention
+                let asset_id = market.asset_ids.get(1).or(market.asset_ids.first())?;
+                Some((market.id.clone(), asset_id.clone()))
+            })
+            .take(20);
+        for (market_id, asset_id) in due {
+            let client = self.market_interface.clone();
+            let fee_rates = self.fee_rates.clone();
+            tokio::spawn(async move {
+                match client.get_fee_rate_bps(&asset_id).await {
+                    Ok(Some(fee_bps)) => {
+                        if fee_bps == 0 && !fee_rates.is_zero_fee(&market_id) {
+                            info!("🆓 Fee-free market: {}", market_id);
+                        }
+                        fee_rates.record(&market_id, fee_bps, Utc::now());
+                    }
+                    Ok(None) => {}
+                    Err(e) => debug!("Fee rate check failed for {}: {}", market_id, e),
+                }
+            });
+        }
+    }
+
+    /// Fee a leg pays on `market_id`, in bps: the CLOB's rate once checked, the usual fee
+    /// until then
+    fn fee_per_leg_bps(&self, market_id: &str) -> i32 {
+        self.fee_rates
+            .fee_bps(market_id)
+            .map_or(FEE_PER_TRADE_BPS, |fee_bps| fee_bps as i32)
+    }
+
+    /// A market checked fee-free, with ZERO_FEE_ENABLED
+    fn is_zero_fee(&self, market_id: &str) -> bool {
+        self.config.zero_fee.enabled && self.fee_rates.is_zero_fee(market_id)
+    }
+
+    /// Alert on the event series Gamma lists for the first time, and pin the open markets of
+    /// those matching an auto-pin keyword. One poll at a time
+    fn watch_series(&self) {
+        let Some(watcher) = self.series_watcher.clone() else {
+            return;
+        };
+        if self.breakers.gamma.is_open() {
+            return;
+        }
+        if self.series_polling.swap(true, Ordering::SeqCst) {
+            debug!("Event series poll still running, skipping");
+            return;
+        }
+        let client = self.market_interface.clone();
+        let pins = self.pins.clone();
+        let events = self.events.clone();
+        let config = self.config.series_watch.clone();
+        let polling = self.series_polling.clone();
+        tokio::spawn(async move {
+            poll_series(&client, &watcher, &pins, &events, &config).await;
+            polling.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Move resolved-but-open markets to the dormant list, and back when they reprice. Dormant
+    /// markets nobody holds, watches or pinned are dropped from the WS; held ones keep their
+    /// books for marking and exits
+    fn scan_zombies(&mut self) {
+        let now = Utc::now();
+        let mut kept: HashSet<String> = self
+            .risk_manager
+            .get_positions()
+            .into_iter()
+            .map(|p| p.market_id)
+            .collect();
+        if let Ok(watchlist) = self.watchlist.lock() {
+            kept.extend(watchlist.market_ids());
+        }
+        if let Ok(pins) = self.pins.lock() {
+            kept.extend(pins.market_ids());
+        }
+
+        let mut unsubscribe = Vec::new();
+        let zombies = self.zombies.clone();
+        let Ok(mut zombies) = zombies.lock() else {
+            return;
+        };
+        for market in self.registry.markets() {
+            if self.market_state(&market.id).is_some_and(|s| s.is_closed()) {
+                zombies.forget(&market.id);
+                continue;
+            }
+            let proposed = self.resolution_proposals.contains(&market.id);
+            match zombies.observe(&market, proposed, now) {
+                Some(ZombieEvent::Dormant) => {
+                    info!(
+                        "🧟 Dormant: {} ({}, YES {:.3} / NO {:.3})",
+                        market.question,
+                        zombies.get(&market.id).map_or("", |d| d.reason.as_str()),
+                        market.yes_price,
+                        market.no_price
+                    );
+                    if !kept.contains(market.id.as_str()) {
+                        unsubscribe.extend(market.asset_ids);
+                    }
+                }
+                Some(ZombieEvent::Woke) => info!(
+                    "🧟 Repriced, back to the strategies: {} (YES {:.3} / NO {:.3})",
+                    market.question, market.yes_price, market.no_price
+                ),
+                None => {}
+            }
+        }
+        drop(zombies);
+        if !unsubscribe.is_empty() {
+            self.unsubscribe_assets(&unsubscribe);
+        }
+    }
+
+    fn is_dormant(&self, market_id: &str) -> bool {
+        self.zombies
+            .lock()
+            .map(|zombies| zombies.is_dormant(market_id))
+            .unwrap_or(false)
+    }
+
+    /// Mark open positions (and experiment markets) to fresh prices, then snapshot the PnL
+    async fn update_pnl_prices(&mut self) {
+        self.refresh_tick_throttle();
+        // Update prices with LIVE data
+        let mut market_ids: Vec<String> = Vec::new();
+        for book in self.pnl_books() {
+            if let Ok(tracker) = book.lock() {
+                // Get unique market IDs from active positions
+                for pos in tracker.positions.values() {
+                    if !market_ids.contains(&pos.market_id) {
+                        market_ids.push(pos.market_id.clone());
+                    }
+                }
+            }
+        }
+        if let Some(exp) = &self.experiment {
+            for market_id in exp.tracked_markets() {
+                if !market_ids.contains(&market_id) {
+                    market_ids.push(market_id);
+                }
+            }
+        }
+
+        // Fetch updates for these markets
+        if !market_ids.is_empty() {
+            // info!("Updating PnL for {} markets...", market_ids.len());
+            for market_id in market_ids {
+                match self.market_interface.get_market_details(&market_id).await {
+                    Ok(market) => {
+                        for book in self.pnl_books() {
+                            if let Ok(mut tracker) = book.lock() {
+                                tracker.update_market_price(
+                                    &market_id,
+                                    market.yes_price,
+                                    market.no_price,
+                                );
+                            }
+                        }
+                        if let Some(exp) = &mut self.experiment {
+                            exp.update_market_price(&market_id, market.yes_price, market.no_price);
+                        }
+                    }
+                    Err(e) => {
+                        // Fall back to the registry's WS-fed prices
+                        match self.registry.get(&market_id) {
+                            Some(market) if market.quote_quality() != QuoteQuality::Empty => {
+                                debug!("PnL update for {} from registry prices ({})", market_id, e);
+                                for book in self.pnl_books() {
+                                    if let Ok(mut tracker) = book.lock() {
+                                        tracker.update_market_price(
+                                            &market_id,
+                                            market.yes_price,
+                                            market.no_price,
+                                        );
+                                    }
+                                }
+                            }
+                            _ => warn!("Failed to fetch price for PnL update {}: {}", market_id, e),
+                        }
+                    }
+                }
+            }
+        }
+
+        self.review_losses();
+        self.sweep_settlements();
+
+        // Take snapshot after updates
+        if let Ok(mut tracker) = self.pnl_tracker.lock() {
+            tracker.take_snapshot();
+            self.strategy.set_portfolio_value(tracker.portfolio_value());
+            if let Err(e) = tracker.calendar.save() {
+                warn!("⚠️ Failed to save PnL buckets: {}", e);
+            }
+        }
+        if let Some(Ok(mut tracker)) = self.paper_pnl.as_ref().map(|p| p.lock()) {
+            tracker.take_snapshot();
+        }
+        self.save_paper_book();
+    }
+
+    /// Log the health of the queues, the WS connection, subscriptions and the executor
+    fn log_queue_stats(&mut self) {
+        if let Some(rx) = &self.ws_update_rx {
+            let stats = rx.stats();
+            info!(
+                "📬 WS queue: depth {}/{} (peak {}) | enqueued {} | coalesced {} | superseded {} | dropped {} | blocked {}",
+                stats.depth,
+                stats.capacity,
+                stats.high_water_mark,
+                stats.enqueued,
+                stats.coalesced,
+                stats.superseded,
+                stats.dropped,
+                stats.blocked
+            );
+            // Warn once per breach, not on every tick after the first one
+            if stats.saturated > self.ws_queue_saturations {
+                warn!(
+                    "⚠️ WS update queue hit capacity - agent is falling behind the feed ({} times so far)",
+                    stats.saturated
+                );
+                self.ws_queue_saturations = stats.saturated;
+            }
+        }
+        if let Some(ws) = &self.ws_client {
+            let bandwidth = ws.bandwidth();
+            if let Some(conn) = &bandwidth.current {
+                info!(
+                    "📶 WS bandwidth: {:.1} KB/s ({:.0} msg/s) | avg {:.1} KB/s | peak {:.1} KB/s | {:.1} MB this connection | compression: {}",
+                    conn.bytes_per_sec / 1024.0,
+                    conn.messages_per_sec,
+                    conn.avg_bytes_per_sec / 1024.0,
+                    conn.peak_bytes_per_sec / 1024.0,
+                    conn.bytes_in as f64 / (1024.0 * 1024.0),
+                    conn.extensions.as_deref().unwrap_or("none")
+                );
+            }
+            let ordering = ws.sequencing();
+            if ordering.reordered + ordering.duplicates > 0 {
+                warn!(
+                    "🔀 WS book updates dropped: {} out of order, {} duplicated ({} accepted)",
+                    ordering.reordered, ordering.duplicates, ordering.accepted
+                );
+            }
+            let throttling = ws.throttling();
+            if throttling.sampled_out > 0 {
+                info!(
+                    "🐢 WS tick throttle: {} marking-only assets | {} updates sampled out ({} forwarded)",
+                    throttling.marking_assets, throttling.sampled_out, throttling.forwarded
+                );
+            }
+        }
+        if let Ok(subscriptions) = self.subscriptions.lock() {
+            let stats = subscriptions.stats();
+            if stats.subscribed > 0 {
+                info!(
+                    "🔌 WS subscriptions: {}/{} (peak {}) | evicted {} | over cap {}",
+                    stats.subscribed,
+                    if stats.cap == 0 {
+                        "∞".to_string()
+                    } else {
+                        stats.cap.to_string()
+                    },
+                    stats.peak,
+                    stats.evicted,
+                    stats.over_cap
+                );
+            }
+        }
+        let jobs = self.jobs.stats();
+        info!(
+            "🗂️ Jobs: pending {} | new-market {} | protection {} | routine {} | deduped {}",
+            jobs.pending,
+            jobs.dispatched_new_market,
+            jobs.dispatched_protection,
+            jobs.dispatched_routine,
+            jobs.deduped
+        );
+        if let Ok(lifecycle) = self.lifecycle.lock() {
+            let summary = lifecycle.summary();
+            info!(
+                "🧭 Markets: {} tracked | {:?} | {} awaiting metadata retry",
+                summary.markets, summary.by_state, summary.pending_metadata_retries
+            );
+        }
+        let health = self.executor.health().snapshot();
+        info!(
+            "🩺 Executor: {:?} | error rate {:.0}% | avg latency {:.0}ms | samples {}",
+            health.status,
+            health.error_rate * 100.0,
+            health.avg_latency_ms,
+            health.samples
+        );
+        let queue = self.executor.submission_queue().stats();
+        if queue.shed > 0 || queue.timed_out > 0 {
+            warn!(
+                "🚦 Order queue: {} submitted ({} waited) | {} shed | {} timed out | peak {} in flight, {} queued",
+                queue.submitted,
+                queue.waited,
+                queue.shed,
+                queue.timed_out,
+                queue.peak_in_flight,
+                queue.peak_queued
+            );
+        }
+    }
+
+    /// Everything a `--restore` start resumes from
+    fn state_snapshot(&self) -> StateSnapshot {
+        let (lifecycle, metadata_retries) = self
+            .lifecycle
+            .lock()
+            .map(|l| (l.markets(), l.metadata_retry_queue()))
+            .unwrap_or_default();
+        StateSnapshot {
+            taken_at: Utc::now(),
+            session_id: self.session.session_id.clone(),
+            markets: self.registry.markets(),
+            assets: self.registry.assets(),
+            positions: self.risk_manager.get_positions(),
+            liquidations: self.liquidations.values().cloned().collect(),
+            lifecycle,
+            metadata_retries,
+            pnl: self
+                .pnl_tracker
+                .lock()
+                .map(|tracker| PnlState::capture(&tracker))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Dirs the state snapshot goes to: the data dir, plus the standby's mirror when this is
+    /// the trading primary
+    fn state_snapshot_dirs(&self) -> (String, Option<String>) {
+        let mirror = self
+            .config
+            .failover
+            .snapshot_dir
+            .clone()
+            .filter(|_| self.failover.is_some() && self.failover_allows_trading());
+        (self.config.agent.data_dir.clone(), mirror)
+    }
+
+    /// Take a state snapshot and write it off the loop (skipped while the previous one is
+    /// still being written)
+    fn save_state_snapshot(&self) {
+        if self.snapshot_saving.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let snapshot = self.state_snapshot();
+        let (data_dir, mirror) = self.state_snapshot_dirs();
+        let saving = self.snapshot_saving.clone();
+        tokio::task::spawn_blocking(move || {
+            write_state_snapshot(&snapshot, &data_dir, mirror.as_deref());
+            saving.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Write the final state snapshot in place, after any write still in flight
+    fn save_state_snapshot_now(&self) {
+        while self.snapshot_saving.swap(true, Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let (data_dir, mirror) = self.state_snapshot_dirs();
+        write_state_snapshot(&self.state_snapshot(), &data_dir, mirror.as_deref());
+        self.snapshot_saving.store(false, Ordering::SeqCst);
+    }
+
+    /// Promoted to primary: pick up the positions, PnL and markets the old primary mirrored
+    /// before any order goes out
+    fn resume_peer_snapshot(&mut self) {
+        let Some(dir) = self.config.failover.snapshot_dir.clone() else {
+            warn!("⚠️ Promoted without FAILOVER_SNAPSHOT_DIR: trading from this instance's state");
+            return;
+        };
+        let snapshot = match StateSnapshot::load(&dir) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => {
+                warn!("⚠️ Promoted, but no state snapshot in {}", dir);
+                return;
+            }
+            Err(e) => {
+                error!(
+                    "❌ Promoted, but the peer's state snapshot is unreadable: {:#}",
+                    e
+                );
+                return;
+            }
+        };
+        info!(
+            "♻️  Resuming the old primary's state (session {}, {}s old): {} positions, {} markets",
+            snapshot.session_id,
+            snapshot.age_secs(),
+            snapshot.positions.len(),
+            snapshot.markets.len()
+        );
+        self.registry
+            .restore(snapshot.markets.clone(), snapshot.assets.clone());
+        self.risk_manager
+            .restore_positions(snapshot.positions.clone());
+        if let Ok(mut tracker) = self.pnl_tracker.lock() {
+            snapshot.pnl.clone().apply(&mut tracker);
+        }
+        if let Ok(mut lifecycle) = self.lifecycle.lock() {
+            lifecycle.restore(
+                snapshot.lifecycle.clone(),
+                snapshot.metadata_retries.clone(),
+            );
+        }
+        for ladder in snapshot.liquidations {
+            if ladder.entry_price > 0.0 {
+                self.liquidations.insert(ladder.market_id.clone(), ladder);
+            }
+        }
+    }
+
+    /// Compile the daily report from the PnL tracker and wallet balance and send it; one that
+    /// failed to go out is sent again as it was compiled
+    async fn send_daily_report(&mut self) {
+        if self.daily_report.has_pending() {
+            self.daily_report.send(Utc::now());
+            return;
+        }
+        let balance = match self.market_interface.get_balance().await {
+            Ok(balance) => Some(balance),
+            Err(e) => {
+                warn!("⚠️ Balance not fetched for the daily report: {}", e);
+                None
+            }
+        };
+        let report = match self.pnl_tracker.lock() {
+            Ok(tracker) => {
+                self.daily_report
+                    .compile(&tracker, self.session.started_at, balance, Utc::now())
+            }
+            Err(_) => return,
+        };
+        info!("📊 Daily report: {}", report.subject());
+        self.daily_report.send(Utc::now());
+    }
+
+    /// Aggregates for the telemetry collector: rates and percentiles, nothing identifying
+    fn telemetry_snapshot(&self) -> TelemetrySnapshot {
+        let now = Utc::now();
+        let signal_to_post_ms = self
+            .execution_drift
+            .lock()
+            .map(|d| Percentiles::from_samples(d.latencies_ms()))
+            .unwrap_or_default();
+        let (gamma_lag_ms, book_to_quote_ms) = self
+            .indexing_latency
+            .lock()
+            .map(|l| {
+                let report = l.report();
+                (report.gamma_lag, report.book_to_quote)
+            })
+            .unwrap_or_default();
+        let edge = self.edge_realization.lock().map(|e| e.report()).ok();
+        let (trades, win_rate) = self
+            .pnl_tracker
+            .lock()
+            .map(|t| {
+                let stats = t.get_stats();
+                (stats.num_trades, stats.win_rate)
+            })
+            .unwrap_or_default();
+        let sequencing = self.ws_client.as_ref().map(|ws| ws.sequencer().stats());
+        let (errors, warnings) = report::log_counts();
+        TelemetrySnapshot {
+            instance: self.telemetry.instance().to_string(),
+            version: env!("CARGO_PKG_VERSION"),
+            at: now,
+            uptime_secs: (now - self.session.started_at).num_seconds(),
+            mode: if self.config.agent.simulation_mode {
+                "simulation"
+            } else if self.config.agent.paper_trading {
+                "paper"
+            } else {
+                "live"
+            },
+            signal_to_post_ms,
+            gamma_lag_ms,
+            book_to_quote_ms,
+            trades,
+            win_rate,
+            fill_rate: edge
+                .as_ref()
+                .filter(|e| e.settled > 0)
+                .map(|e| e.fully_filled_rate),
+            edge_capture_ratio: edge
+                .as_ref()
+                .filter(|e| e.settled > 0)
+                .map(|e| e.capture_ratio),
+            ws_reordered: sequencing.as_ref().map_or(0, |s| s.reordered),
+            ws_duplicates: sequencing.as_ref().map_or(0, |s| s.duplicates),
+            errors,
+            warnings,
+            rss_mb: monitor::rss_mb(),
+        }
+    }
+
+    /// Current resource usage; `retry_depth` is the metadata retry channel's (local to `run`)
+    fn self_report(&self, retry_depth: usize) -> SelfReport {
+        let (seen_markets, metadata_retries) = self
+            .lifecycle
+            .lock()
+            .map(|l| {
+                let summary = l.summary();
+                (summary.markets, summary.pending_metadata_retries)
+            })
+            .unwrap_or_default();
+        SelfReport {
+            rss_mb: monitor::rss_mb(),
+            tasks: monitor::alive_tasks(),
+            markets: self.registry.len(),
+            assets: self.registry.asset_count(),
+            seen_markets,
+            subscriptions: self
+                .subscriptions
+                .lock()
+                .map(|s| s.stats().subscribed)
+                .unwrap_or(0),
+            pending_jobs: self.jobs.stats().pending,
+            metadata_retries,
+            ws_queue_depth: self.ws_update_rx.as_ref().map_or(0, |rx| rx.stats().depth),
+            retry_channel_depth: retry_depth,
+            new_market_channel_depth: self.new_market_rx.as_ref().map_or(0, |rx| rx.len()),
+        }
+    }
+
+    /// Handle an on-chain new-market event: derive token IDs, subscribe, and try a fast metadata fetch
+    async fn handle_new_condition(&mut self, condition: ConditionId) {
+        let condition_id = condition.as_str().to_string();
+        info!("⚡ WebSocket event: New market condition {}", condition_id);
+
+        // OPTIMIZATION: Check if we already know this market (e.g. via polling or previous event)
+        if self.market_state(&condition_id).is_some() {
+            return;
+        }
+        self.advance(&condition_id, MarketState::DetectedOnChain);
+        if let Ok(mut tracker) = self.indexing_latency.lock() {
+            tracker.mark_detected(&condition_id);
+        }
+
+        // GOD MODE: Perform local calculation of Token IDs immediately
+        // This creates a "Synthetic" market to start tracking prices while Gamma indexes
+        // (cached and persisted: ids derived with a burst or by an earlier run are a lookup)
+        let (yes_id, no_id) = self.derived_assets.get_or_derive(&condition);
+        info!(
+            "🔮 Derived Token IDs locally! YES: ...{}, NO: ...{}",
+            &yes_id[yes_id.len() - 6..],
+            &no_id[no_id.len() - 6..]
+        );
+
+        // Initialize "Synthetic" Market Entry
+        // We don't have the question yet, but we have the IDs to trade!
+        let synthetic_market = MarketData {
+            id: condition_id.clone().into(),
+            question: format!("⌛ Loading Metadata ({})", condition_id),
+            end_date: Some("Unknown".to_string()),
+            end_time: None,
+            description: None,
+            volume: 0.0,
+            liquidity: 0.0,
+            yes_price: 0.0,
+            no_price: 0.0,
+            volume_24h: 0.0,
+            best_bid: 0.0,
+            best_ask: 0.0,
+            order_book_imbalance: 0.0,
+            asset_ids: vec![no_id.clone(), yes_id.clone()], // Standard: [No, Yes] usually, but verify?
+            // Important: derive_asset_ids returns (YES, NO) tuple order I defined?
+            // My function returns (yes_id, no_id).
+            // MarketData.asset_ids usually matches the order in CLOB.
+            // Let's store them and map them.
+            category: None, // Classified once the question is known
+        };
+
+        self.advance(&condition_id, MarketState::DerivedAssets);
+        self.synthetic.detected(&condition_id, Utc::now());
+
+        // Map Assets for Price Updates
+        self.registry.map_assets(&condition_id, &no_id, &yes_id);
+        self.registry.insert(synthetic_market);
+
+        // Subscribe to CLOB WebSocket IMMEDIATELY
+        if self.ws_client.is_some() {
+            if self.subscribe_assets(&condition_id, &[yes_id, no_id]) > 0 {
+                info!("🔌 Subscribed to CLOB for derived IDs (YES/NO)");
+            }
+            self.advance(&condition_id, MarketState::Subscribed);
+        }
+
+        // Also try normal fetch (will likely fail initially but needed for metadata)
+        // If it fails, add to retry queue to avoid blocking
+        debug!("⚡ Triggering immediate fast market fetch...");
+        match self.market_interface.get_market_details(&condition_id).await {
+            Ok(market) => {
+                debug!("✅ Fast sync success: {}", market.question);
+                if let Ok(mut tracker) = self.indexing_latency.lock() {
+                    tracker.mark_gamma_available(&condition_id);
+                }
+                if self.advance(&market.id, MarketState::MetadataLoaded) {
+                    // Update the synthetic market with real metadata
+                    self.registry.insert(market.clone());
+                    info!("🚀 Processing new market immediately: {}", market.question);
+                    if let Err(e) = self.process_single_market(&market).await {
+                        error!("❌ Error processing market {}: {}", market.question, e);
+                    }
+                }
+            },
+            Err(e) => {
+                // Non-blocking retry: Queue it
+                debug!("⚠️ Initial fetch failed ({}), queuing for retry...", e);
+                self.queue_metadata_retry(&condition_id);
+            }
+        }
+    }
+
+    /// Run queued work in priority order
+    /// New-market events that arrive while we're busy are pulled in between jobs so they preempt routine work
+    async fn run_jobs(&mut self) {
+        while let Some(job) = self.jobs.pop() {
+            self.enforce_anomaly_guard();
+            match job {
+                Job::NewCondition(condition_id) => self.handle_new_condition(condition_id).await,
+                Job::NewMarket(market) => {
+                    if self.advance(&market.id, MarketState::MetadataLoaded) {
+                        if let Err(e) = self.process_single_market(&market).await {
+                            error!("❌ Error processing market {}: {}", market.question, e);
+                        }
+                    }
+                }
+                Job::ProtectPosition(market_id) => {
+                    if let Some(market) = self.registry.get(&market_id) {
+                        if let Err(e) = self.manage_positions(&[market]).await {
+                            error!("❌ Position protection failed for {}: {}", market_id, e);
+                        }
+                    }
+                }
+                Job::Reevaluate(market_id, received_ns) => {
+                    // Clone to avoid borrow issues while calling async func
+                    if let Some(market) = self.registry.get(&market_id) {
+                        self.evaluation_received_ns = received_ns;
+                        if let Err(e) = self.process_single_market(&market).await {
+                            error!("❌ Processing error: {}", e);
+                        }
+                        self.evaluation_received_ns = None;
+                    }
+                }
+            }
+
+            self.poll_new_market_events();
+        }
+    }
+
+    /// Non-blocking pull of pending new-market events into the job queue
+    fn poll_new_market_events(&mut self) {
+        let Some(rx) = &mut self.new_market_rx else {
+            return;
+        };
+        let mut pending = Vec::new();
+        while let Ok(condition_id) = rx.try_recv() {
+            pending.push(condition_id);
+        }
+        if pending.len() > 1 {
+            // A storm: derive the token ids of the whole burst in one go
+            self.derived_assets.derive_all(&pending);
+        }
+        for condition_id in pending {
+            self.jobs.push(Job::NewCondition(condition_id));
+        }
+    }
+
+    /// Process new markets and manage positions
+    async fn process_markets(&mut self) -> Result<()> {
+        // Fetch current state of all markets
+        let all_markets = self.market_interface.get_active_markets().await?;
+
+        // FIRST TIME ONLY: Mark all existing markets as seen without analyzing
+        // This prevents analyzing 1000+ old markets on startup
+        let first_scan = self
+            .lifecycle
+            .lock()
+            .map(|l| l.with_metadata() == 0)
+            .unwrap_or(false);
+        if first_scan {
+            if !self.config.agent.scan_existing_on_startup {
+                for market in &all_markets {
+                    self.advance(&market.id, MarketState::MetadataLoaded);
+                }
+                info!("🚀 Startup: Skipped {} existing markets (only trading NEW markets from now on)", all_markets.len());
+
+                // Still manage positions even on first run
+                self.manage_positions(&all_markets).await?;
+                return Ok(());
+            } else {
+                info!(
+                    "🚀 Startup: Analyzing {} existing markets for immediate opportunities...",
+                    all_markets.len()
+                );
+                // Don't mark them loaded yet, let the loop below do it
+            }
+        }
+
+        // SUBSEQUENT RUNS: Detect and process NEW markets only
+        let mut new_markets = Vec::new();
+        for market in &all_markets {
+            if !self.has_metadata(&market.id) {
+                info!("🆕 NEW market detected: {}", market.question);
+                self.advance(&market.id, MarketState::MetadataLoaded);
+                new_markets.push(market.clone());
+            }
+        }
+
+        if !new_markets.is_empty() {
+            info!("⚡ Processing {} brand new markets", new_markets.len());
+            for market in new_markets {
+                if let Err(e) = self.process_single_market(&market).await {
+                    error!("❌ Error processing market {}: {}", market.question, e);
+                    continue;
+                }
+            }
+        }
+
+        // Manage existing positions
+        self.manage_positions(&all_markets).await?;
+
+        Ok(())
+    }
+
+    /// Process a single market through the entire pipeline
+    async fn process_single_market(&mut self, market: &MarketData) -> Result<()> {
+        // Nothing left to trade once the outcome is known
+        if self.market_state(&market.id).is_some_and(|s| s.is_closed()) {
+            return Ok(());
+        }
+
+        // Filter out garbage markets
+        if !self.passes_filters(market) {
+            return Ok(());
+        }
+
+        // Register for WS Updates
+        if self.ws_client.is_some() && !market.asset_ids.is_empty() {
+            // Dormant markets were dropped from the WS on purpose (the held ones never were)
+            if !self.is_dormant(&market.id)
+                && self.subscribe_assets(&market.id, &market.asset_ids) > 0
+            {
+                info!("🔌 Subscribing to Orderbook for {}", market.question);
+            }
+
+            // Cache state - BUT PRESERVE WS PRICES if they exist and are newer
+            let known = self.registry.update(&market.id, |existing| {
+                // Metadata update only; the cached end time follows the end date and question
+                // it's parsed from (a synthetic entry has neither yet)
+                let new_end = market.end_date.is_some() && existing.end_date != market.end_date;
+                if existing.question != market.question || new_end {
+                    existing.question = market.question.clone();
+                    existing.end_time = if market.end_date.is_some() {
+                        existing.end_date = market.end_date.clone();
+                        market.end_time.or_else(|| existing.parse_end_time())
+                    } else {
+                        existing.parse_end_time()
+                    };
+                }
+                existing.volume = market.volume;
+                existing.liquidity = market.liquidity;
+                existing.volume_24h = market.volume_24h;
+                // Keep an LLM answer over the rules' Other
+                if existing.category.is_none() || market.category != Some(MarketCategory::Other) {
+                    existing.category = market.category;
+                }
+
+                // Only update prices if Gamma has values AND WS hasn't provided better ones yet
+                if existing.yes_price <= 0.0 && market.yes_price > 0.0 {
+                    existing.yes_price = market.yes_price;
+                }
+                if existing.no_price <= 0.0 && market.no_price > 0.0 {
+                    existing.no_price = market.no_price;
+                }
+            });
+            if !known {
+                self.registry.insert(market.clone());
+            }
+
+            // Map Asset Maps
+            // Assuming asset_ids[0] = NO, asset_ids[1] = YES (This is standard for Polymarket CTF)
+            // But wait, let's just Map them both. We need to know which is which.
+            // In `client.rs` we didn't strictly order them, but `outcome_prices` logic assumed order.
+            // IMPORTANT: We need to know which asset_id is YES and which is NO.
+            // For now, let's simplistically Map:
+            // If we have 2 assets, assign them based on index (Hack for now, need robust mapping later)
+            if market.asset_ids.len() >= 2 {
+                self.registry
+                    .map_assets(&market.id, &market.asset_ids[0], &market.asset_ids[1]);
+            }
+
+            if market.category == Some(MarketCategory::Other) {
+                self.classify_with_llm(market);
+            }
+        }
+
+        // Metadata plus a live price: strategies can act on it
+        if self.has_metadata(&market.id) {
+            self.synthetic.metadata_loaded(&market.id);
+            if market.quote_quality() != QuoteQuality::Empty {
+                self.advance(&market.id, MarketState::Tradable);
+            }
+        }
+
+        if !self.quotes_actionable(market) {
+            return Ok(());
+        }
+
+        self.enforce_anomaly_guard();
+
+        // Back off while the executor is erroring or slow: signals would only add to the failures
+        if !self.executor.health().admit() {
+            debug!("⏸️ Executor backpressure - skipping signals for {}", market.question);
+            return Ok(());
+        }
+
+        // Standby, or a primary that lost its lease: the peer may be trading
+        if !self.failover_allows_trading() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+
+        // 1) Primary: Last-minute predictive (Binance)
+        let (predictive_action, binance_price) = self
+            .predictive_strategy
+            .check_opportunity_with_inputs(market, now)
+            .await;
+        self.record_decision(
+            "predictive",
+            market,
+            now,
+            binance_price,
+            None,
+            &predictive_action,
+        );
+        if let Some(binance_price) = binance_price {
+            self.record_features(market, now, binance_price);
+        }
+        let predictive_action = self
+            .route_experiment("predictive", market, now, binance_price, predictive_action)
+            .await;
+        let predictive_action = self.gate_action("predictive", market, predictive_action);
+        match predictive_action {
+            TradeAction::Snipe {
+                market_id: _,
+                side,
+                price,
+                size_usd,
+            } if self.predictive_confirms(market, &side, price.get(), binance_price) => {
+                info!(
+                    "🎯 PREDICTIVE LAST-MINUTE Signal: {} (Side: {})",
+                    market.question, side
+                );
+                self.execute_snipe_signal(market, &side, price, size_usd, "pred", 0.8)
+                    .await?;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // 2) Secondary: Expiration snipe
+        let expiration_action = self.expiration_strategy.check_opportunity_at(market, now);
+        self.record_decision("expiration", market, now, None, None, &expiration_action);
+        let expiration_action = self
+            .route_experiment("expiration", market, now, None, expiration_action)
+            .await;
+        let expiration_action = self.gate_action("expiration", market, expiration_action);
+        match expiration_action {
+            TradeAction::Snipe {
+                market_id: _,
+                side,
+                price,
+                size_usd,
+            } => {
+                info!(
+                    "🎯 EXPIRATION SNIPE Signal: {} (Side: {})",
+                    market.question, side
+                );
+                self.execute_snipe_signal(market, &side, price, size_usd, "snipe", 0.9)
+                    .await?;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // 3) Optional fallback: arbitrage (disabled by default)
+        let base_edge_bps = self.strategy.min_edge_bps();
+        let min_edge_bps = self.arbitrage_min_edge(market);
+        let mut arbitrage_action = self.arbitrage_opportunity(market, min_edge_bps);
+        if matches!(arbitrage_action, TradeAction::None)
+            && self.arbitrage_depth_cut(market, min_edge_bps)
+        {
+            arbitrage_action = self.arbitrage_on_full_books(market, min_edge_bps).await;
+        }
+        self.record_opportunity(market, now, min_edge_bps);
+        self.record_decision(
+            "arbitrage",
+            market,
+            now,
+            None,
+            (min_edge_bps != base_edge_bps).then_some(min_edge_bps),
+            &arbitrage_action,
+        );
+        let arbitrage_action = self
+            .route_experiment("arbitrage", market, now, None, arbitrage_action)
+            .await;
+        let arbitrage_action = self.gate_action("arbitrage", market, arbitrage_action);
+        match arbitrage_action {
+            TradeAction::BuyBoth {
+                market_id: _,
+                yes_price,
+                no_price,
+                size_usd,
+                expected_profit_bps,
+            } => {
+                info!(
+                    "🎯 ARBITRAGE Signal: {} (Profit: {} bps)",
+                    market.question, expected_profit_bps
+                );
+                self.execute_arbitrage_signal(
+                    market,
+                    now,
+                    "arb",
+                    yes_price,
+                    no_price,
+                    size_usd,
+                    expected_profit_bps,
+                )
+                .await?;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // 4) Directional fallback: buy only the side trading below fair value
+        if self.dislocation_strategy.enabled() {
+            let (fair_yes, binance_price) = self
+                .predictive_strategy
+                .fair_yes_with_inputs(market, now, self.dislocation_strategy.annual_volatility())
+                .await;
+            // The books' bids let the complement fire with YES + NO >= 1 (FEATURES_ENABLED)
+            let bid = |side: &str| {
+                self.feature_frame(&market.id, side)
+                    .map(|frame| frame.best_bid)
+                    .filter(|bid| *bid > 0.0)
+            };
+            let (yes_bid, no_bid) = (bid("YES"), bid("NO"));
+            let dislocation_action = self
+                .dislocation_strategy
+                .check_opportunity_with_bids(market, fair_yes, yes_bid, no_bid);
+            self.record_decision(
+                "dislocation",
+                market,
+                now,
+                binance_price,
+                None,
+                &dislocation_action,
+            );
+            let dislocation_action = self
+                .route_experiment(
+                    "dislocation",
+                    market,
+                    now,
+                    binance_price,
+                    dislocation_action,
+                )
+                .await;
+            let dislocation_action = self.gate_action("dislocation", market, dislocation_action);
+            if let TradeAction::Snipe {
+                market_id: _,
+                side,
+                price,
+                size_usd,
+            } = dislocation_action
+            {
+                info!(
+                    "🎯 DISLOCATION Signal: {} (Side: {})",
+                    market.question, side
+                );
+                self.execute_snipe_signal(market, &side, price, size_usd, "disloc", 0.7)
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        // 5) Strategies added by the embedding project
+        for strategy in self.custom_strategies.clone() {
+            let name = strategy.name().to_string();
+            let action = strategy.evaluate(market, now).await;
+            self.record_decision(&name, market, now, None, None, &action);
+            match self.gate_action(&name, market, action) {
+                TradeAction::Snipe {
+                    market_id: _,
+                    side,
+                    price,
+                    size_usd,
+                } => {
+                    info!("🎯 {} Signal: {} (Side: {})", name, market.question, side);
+                    self.execute_snipe_signal(market, &side, price, size_usd, &name, 0.7)
+                        .await?;
+                    return Ok(());
+                }
+                TradeAction::BuyBoth {
+                    market_id: _,
+                    yes_price,
+                    no_price,
+                    size_usd,
+                    expected_profit_bps,
+                } => {
+                    info!(
+                        "🎯 {} Signal: {} (Profit: {} bps)",
+                        name, market.question, expected_profit_bps
+                    );
+                    self.execute_arbitrage_signal(
+                        market,
+                        now,
+                        &name,
+                        yes_price,
+                        no_price,
+                        size_usd,
+                        expected_profit_bps,
+                    )
+                    .await?;
+                    return Ok(());
+                }
+                TradeAction::None => {}
+            }
+        }
+
+        if rand::random::<f64>() < 0.001 {
+            debug!("🔍 Checked {} - No signal", market.question);
+        }
+
+        Ok(())
+    }
+
+    /// Can strategies act on this market's quotes right now?
+    fn quotes_actionable(&self, market: &MarketData) -> bool {
+        // Resolved but still quoting: nothing left to win
+        if self.is_dormant(&market.id) {
+            debug!(
+                "🧟 Dormant market - skipping signals for {}",
+                market.question
+            );
+            return false;
+        }
+
+        // Suspended or just-reopened books: the quotes are stale or mid-reprice
+        if !self.suspension.can_trade(&market.id, Utc::now()) {
+            debug!(
+                "⛔ Book suspended - skipping signals for {}",
+                market.question
+            );
+            return false;
+        }
+
+        // Neither outcome has asks: nothing to price a signal against
+        if market.quote_quality() == QuoteQuality::Empty {
+            debug!("📭 No quotes on either side - skipping {}", market.question);
+            return false;
+        }
+        true
+    }
+
+    /// Arbitrage threshold for a market: thin categories with poor fill history, and markets
+    /// where our orders get picked off, need more edge
+    fn arbitrage_min_edge(&self, market: &MarketData) -> i32 {
+        let base_edge_bps = if self.is_zero_fee(&market.id) {
+            self.config.zero_fee.min_edge_bps
+        } else {
+            self.strategy.min_edge_bps()
+        };
+        let min_edge_bps = match &self.edge_tuner {
+            Some(tuner) => tuner
+                .lock()
+                .map(|t| t.effective_min_edge(base_edge_bps, market))
+                .unwrap_or(base_edge_bps),
+            None => base_edge_bps,
+        };
+        let toxic_edge_bps = self
+            .toxicity
+            .as_ref()
+            .and_then(|t| t.lock().ok().map(|t| t.extra_edge_bps(&market.id)))
+            .unwrap_or(0);
+        min_edge_bps + toxic_edge_bps
+    }
+
+    /// Whether our orders on the market keep getting picked off
+    fn is_toxic(&self, market_id: &str) -> bool {
+        self.toxicity
+            .as_ref()
+            .is_some_and(|t| t.lock().is_ok_and(|t| t.is_toxic(market_id)))
+    }
+
+    /// The book a `side` order is about to be sent into, for the toxicity score. None when
+    /// the asset's WS book is stale: a pull couldn't be told from a missed update
+    fn toxicity_context(&self, market: &MarketData, side: &str) -> Option<PostContext> {
+        self.toxicity.as_ref()?;
+        // asset_ids follow the [NO, YES] convention
+        let asset_id = match side {
+            "YES" => market.asset_ids.get(1),
+            _ => market.asset_ids.first(),
+        }?;
+        if self.quote_staleness(asset_id).is_some() {
+            return None;
+        }
+        Some(PostContext {
+            asset_id: asset_id.clone(),
+            asks: self.ask_ladders.get(asset_id).unwrap_or_default().to_vec(),
+            sent_at: Utc::now(),
+        })
+    }
+
+    /// Watch the book, tape and mempool from the send of an order that posted, for the
+    /// toxicity score
+    fn watch_toxicity(
+        &self,
+        market: &MarketData,
+        trade_id: &str,
+        limit_price: f64,
+        size_usd: f64,
+        post: Option<PostContext>,
+    ) {
+        let (Some(post), Some(Ok(mut toxicity))) = (post, self.toxicity.as_ref().map(|t| t.lock()))
+        else {
+            return;
+        };
+        if limit_price > 0.0 {
+            let shares = size_usd / limit_price;
+            toxicity.record_post(trade_id, &market.id, limit_price, shares, post);
+        }
+    }
+
+    /// Arbitrage decision on a market's current registry state, without recording or executing
+    /// it (the tick -> decision path measured by the benches)
+    pub fn arbitrage_signal(&self, market_id: &str) -> TradeAction {
+        match self.registry.get(market_id) {
+            Some(market) if self.quotes_actionable(&market) => {
+                self.arbitrage_opportunity(&market, self.arbitrage_min_edge(&market))
+            }
+            _ => TradeAction::None,
+        }
+    }
+
+    /// Arbitrage on the best asks, dropped when the WS books hold too little within the edge
+    /// (without the WS there are no books to check). Costed at the market's fee rate; fee-free
+    /// markets trade ZERO_FEE_SIZE_MULTIPLIER times the size
+    fn arbitrage_opportunity(&self, market: &MarketData, min_edge_bps: i32) -> TradeAction {
+        self.strategy.check_opportunity_on_book(
+            market,
+            min_edge_bps,
+            self.fee_per_leg_bps(&market.id),
+            self.arbitrage_size_multiplier(&market.id),
+            self.arbitrage_book(market),
+        )
+    }
+
+    /// Fee-free markets trade ZERO_FEE_SIZE_MULTIPLIER times the arbitrage size
+    fn arbitrage_size_multiplier(&self, market_id: &str) -> f64 {
+        if self.is_zero_fee(market_id) {
+            self.config.zero_fee.size_multiplier
+        } else {
+            1.0
+        }
+    }
+
+    /// YES and NO ask ladders of the market's WS books, None without the WS
+    fn arbitrage_book<'a>(
+        &'a self,
+        market: &MarketData,
+    ) -> Option<(&'a [(f64, f64)], &'a [(f64, f64)])> {
+        self.ws_client.as_ref()?;
+        let ladder = |index: usize| -> &'a [(f64, f64)] {
+            market
+                .asset_ids
+                .get(index)
+                .and_then(|asset_id| self.ask_ladders.get(asset_id))
+                .unwrap_or_default()
+        };
+        // asset_ids are [NO, YES]
+        Some((ladder(1), ladder(0)))
+    }
+
+    /// Whether the depth guard may have rejected an edge only for want of levels the WS books
+    /// don't keep: there is an edge on the best asks, and a ladder is as deep as they are cut
+    fn arbitrage_depth_cut(&self, market: &MarketData, min_edge_bps: i32) -> bool {
+        let kept = match ws_book_depth(&self.config) {
+            0 => depth::MAX_LEVELS,
+            levels => levels.min(depth::MAX_LEVELS),
+        };
+        let Some((yes_asks, no_asks)) = self.arbitrage_book(market) else {
+            return false;
+        };
+        (yes_asks.len() >= kept || no_asks.len() >= kept)
+            && matches!(
+                self.strategy.check_opportunity_at_fee(
+                    market,
+                    min_edge_bps,
+                    self.fee_per_leg_bps(&market.id)
+                ),
+                TradeAction::BuyBoth { .. }
+            )
+    }
+
+    /// `arbitrage_opportunity` with the depth guard run on both full books, fetched over REST
+    /// (no trade when either can't be read)
+    async fn arbitrage_on_full_books(&self, market: &MarketData, min_edge_bps: i32) -> TradeAction {
+        // asset_ids are [NO, YES]
+        let (Some(no_asset), Some(yes_asset)) = (market.asset_ids.first(), market.asset_ids.get(1))
+        else {
+            return TradeAction::None;
+        };
+        let (yes_book, no_book) = tokio::join!(
+            self.market_interface.get_orderbook(yes_asset),
+            self.market_interface.get_orderbook(no_asset)
+        );
+        let (yes_book, no_book) = match (yes_book, no_book) {
+            (Ok(yes_book), Ok(no_book)) => (yes_book, no_book),
+            (Err(e), _) | (_, Err(e)) => {
+                debug!("Full books of {} unavailable: {:#}", market.question, e);
+                return TradeAction::None;
+            }
+        };
+        let ladder = |book: &OrderBook| -> Vec<(f64, f64)> {
+            book.ask_levels()
+                .iter()
+                .map(|level| (level.price, level.size))
+                .collect()
+        };
+        let (yes_asks, no_asks) = (ladder(&yes_book), ladder(&no_book));
+        debug!(
+            "📚 Re-checking {} on full books ({} YES / {} NO asks)",
+            market.question,
+            yes_asks.len(),
+            no_asks.len()
+        );
+        self.strategy.check_opportunity_on_book(
+            market,
+            min_edge_bps,
+            self.fee_per_leg_bps(&market.id),
+            self.arbitrage_size_multiplier(&market.id),
+            Some((&yes_asks, &no_asks)),
+        )
+    }
+
+    /// What `arbitrage_opportunity` used on the market, for the decision log
+    fn arbitrage_inputs(&self, market: &MarketData) -> ArbitrageInputs {
+        let book = self.arbitrage_book(market);
+        ArbitrageInputs {
+            fee_per_leg_bps: self.fee_per_leg_bps(&market.id),
+            size_multiplier: self.arbitrage_size_multiplier(&market.id),
+            yes_asks: book.map(|(yes_asks, _)| yes_asks.to_vec()),
+            no_asks: book.map(|(_, no_asks)| no_asks.to_vec()),
+            sizing_capital: Some(self.strategy.sizing_capital()),
+        }
+    }
+
+    pub fn registry(&self) -> &MarketRegistry {
+        &self.registry
+    }
+
+    /// Run `strategy` on every evaluated market, after the built-in strategies
+    pub fn add_strategy(&mut self, strategy: Arc<dyn Strategy>) {
+        info!("🧩 Strategy {} added", strategy.name());
+        self.custom_strategies.push(strategy);
+    }
+
+    /// Signals, entries and closes from now on
+    pub fn subscribe_events(&self) -> broadcast::Receiver<AgentEvent> {
+        self.events.subscribe()
+    }
+
+    /// Stops `run` as SIGTERM does
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Apply a batch of CLOB book updates to the registry (prices, book shape, latency marks)
+    /// Returns the markets whose quotes changed, once each, for re-evaluation, with the socket
+    /// read time of their oldest update in the batch (None if unstamped)
+    pub fn apply_book_updates(
+        &mut self,
+        updates: Vec<OrderbookUpdate>,
+    ) -> Vec<(String, Option<u64>)> {
+        let _alloc = alloc_profile::region("sniper::apply_book_updates");
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            for update in &updates {
+                subscriptions.touch(&update.asset_id);
+            }
+        }
+
+        let mut dirty_markets: Vec<(String, Option<u64>)> = Vec::new();
+        for update in updates {
+            // 1. Identify Market
+            let Some(AssetRef { market_id, side }) = self.registry.asset(&update.asset_id) else {
+                continue;
+            };
+            if let Ok(mut tracker) = self.indexing_latency.lock() {
+                tracker.mark_book(&market_id, !update.asks.is_empty());
+            }
+            self.synthetic.book_seen(&market_id, Utc::now());
+            self.observe_book(&market_id, &side, &update);
+            let received_ns = (update.received_ns > 0).then_some(update.received_ns);
+            self.quote_freshness.observe(
+                &update.asset_id,
+                update.seq,
+                update.change.is_none(),
+                received_ns.unwrap_or_else(clock::now_ns),
+            );
+            self.ask_ladders.observe(&update);
+            if let (Some(Ok(mut toxicity)), Some(asks)) = (
+                self.toxicity.as_ref().map(|t| t.lock()),
+                self.ask_ladders.get(&update.asset_id),
+            ) {
+                toxicity.on_book(&update.asset_id, asks, Utc::now());
+            }
+            if let Some(features) = &self.features {
+                let at_ms = update
+                    .timestamp
+                    .parse()
+                    .unwrap_or_else(|_| Utc::now().timestamp_millis() as u64);
+                if let Ok(mut features) = features.lock() {
+                    features.observe(
+                        &update.asset_id,
+                        &market_id,
+                        &side,
+                        &update.bids,
+                        &update.asks,
+                        at_ms,
+                    );
+                }
+            }
+            // Sampled heartbeat to avoid log spam under high WS throughput
+            if rand::random::<f64>() < 0.002 {
+                info!(
+                    "⚡ WS Tick Alive: {} [{}] ({} bids, {} asks)",
+                    market_id,
+                    side,
+                    update.bids.len(),
+                    update.asks.len()
+                );
+            }
+
+            // 2. Update State
+            // Update Prices based on Bids/Asks
+            // NOTE: We are SNIPING, so we want to BUY.
+            // Buying YES means taking the Lowest ASK.
+            // Buying NO means taking the Lowest ASK.
+            // So we care about ASKS.
+            if let Some(best_ask) = update.asks.first() {
+                let price = best_ask.price.parse::<f64>().unwrap_or(0.0);
+                let updated = self.registry.update(&market_id, |market| {
+                    if side == "YES" {
+                        market.yes_price = price;
+                        if rand::random::<f64>() < 0.002 {
+                            debug!("📊 WS Update: {} YES -> {:.4}", market.question, price);
+                        }
+                    } else {
+                        market.no_price = price;
+                        if rand::random::<f64>() < 0.002 {
+                            debug!("📊 WS Update: {} NO -> {:.4}", market.question, price);
+                        }
+                    }
+                });
+
+                // YES and NO books of one market in the same batch -> one re-eval
+                if updated {
+                    match dirty_markets.iter_mut().find(|(id, _)| *id == market_id) {
+                        Some((_, oldest)) => {
+                            if let Some(ns) = received_ns {
+                                *oldest = Some(oldest.map_or(ns, |o| o.min(ns)));
+                            }
+                        }
+                        None => dirty_markets.push((market_id, received_ns)),
+                    }
+                }
+            }
+        }
+        dirty_markets
+    }
+
+    /// Markets whose books must stay on the WS: open positions, watchlisted and pinned ones
+    fn subscription_pinned_markets(&self) -> HashSet<String> {
+        let mut pinned_markets: Vec<String> = self
+            .risk_manager
+            .get_positions()
+            .into_iter()
+            .map(|p| p.market_id)
+            .collect();
+        if let Ok(watchlist) = self.watchlist.lock() {
+            pinned_markets.extend(watchlist.market_ids());
+        }
+        if let Ok(pins) = self.pins.lock() {
+            pinned_markets.extend(pins.market_ids());
+        }
+        // As the registry keys them
+        pinned_markets
+            .iter()
+            .filter_map(|market_id| self.registry.get(market_id))
+            .map(|m| m.id.to_string())
+            .collect()
+    }
+
+    /// Subscribe to a market's assets not yet on the WS; past MAX_SUBSCRIBED_ASSETS the least
+    /// recently active markets are unsubscribed whole, except those of open positions and
+    /// watchlisted or pinned markets. Returns the number added
+    fn subscribe_assets(&mut self, market_id: &str, asset_ids: &[String]) -> usize {
+        let Some(ws) = &self.ws_client else {
+            return 0;
+        };
+        let pinned = self.subscription_pinned_markets();
+        let market_id = self
+            .registry
+            .get(market_id)
+            .map_or_else(|| market_id.to_string(), |m| m.id.to_string());
+        let Ok(mut subscriptions) = self.subscriptions.lock() else {
+            return 0;
+        };
+
+        let added: Vec<String> = asset_ids
+            .iter()
+            .filter(|asset_id| subscriptions.insert(&market_id, asset_id))
+            .cloned()
+            .collect();
+        if added.is_empty() {
+            return 0;
+        }
+        ws.subscribe(added.clone());
+
+        let evicted = subscriptions.evict(|market_id| pinned.contains(market_id));
+        drop(subscriptions);
+
+        if !evicted.is_empty() {
+            info!(
+                "♻️ Unsubscribing {} inactive assets (cap {})",
+                evicted.len(),
+                self.config.agent.max_subscribed_assets
+            );
+            // No more book updates: drop the last ask so strategies don't act on a stale price
+            for asset_id in &evicted {
+                if let Some(AssetRef { market_id, side }) = self.registry.asset(asset_id) {
+                    self.registry.update(&market_id, |market| {
+                        if side == "YES" {
+                            market.yes_price = 0.0;
+                        } else {
+                            market.no_price = 0.0;
+                        }
+                    });
+                }
+            }
+            if let Some(Ok(mut features)) = self.features.as_ref().map(|f| f.lock()) {
+                for asset_id in &evicted {
+                    features.forget(asset_id);
+                }
+            }
+            for asset_id in &evicted {
+                self.quote_freshness.forget(asset_id);
+                self.ask_ladders.forget(asset_id);
+            }
+            ws.unsubscribe(evicted);
+        }
+        added.len()
+    }
+
+    /// Put back on the WS the books of pinned markets (open positions, watchlist, operator
+    /// pins) that were evicted before they became pinned
+    fn resubscribe_pinned(&mut self) {
+        if self.ws_client.is_none() {
+            return;
+        }
+        let missing: Vec<MarketData> = self
+            .subscription_pinned_markets()
+            .iter()
+            .filter(|market_id| !self.is_dormant(market_id))
+            .filter_map(|market_id| self.registry.get(market_id))
+            .filter(|market| {
+                self.subscriptions.lock().is_ok_and(|subscriptions| {
+                    market.asset_ids.iter().any(|a| !subscriptions.contains(a))
+                })
+            })
+            .collect();
+        for market in missing {
+            let added = self.subscribe_assets(&market.id, &market.asset_ids);
+            if added > 0 {
+                info!(
+                    "🔌 Re-subscribed {} assets of {} (needed again)",
+                    added, market.question
+                );
+            }
+        }
+    }
+
+    /// Drop assets from the WS and forget their quotes
+    fn unsubscribe_assets(&mut self, asset_ids: &[String]) {
+        let Some(ws) = &self.ws_client else {
+            return;
+        };
+        let removed: Vec<String> = match self.subscriptions.lock() {
+            Ok(mut subscriptions) => asset_ids
+                .iter()
+                .filter(|asset_id| subscriptions.remove(asset_id))
+                .cloned()
+                .collect(),
+            Err(_) => return,
+        };
+        if removed.is_empty() {
+            return;
+        }
+        if let Some(Ok(mut features)) = self.features.as_ref().map(|f| f.lock()) {
+            for asset_id in &removed {
+                features.forget(asset_id);
+            }
+        }
+        for asset_id in &removed {
+            self.quote_freshness.forget(asset_id);
+            self.ask_ladders.forget(asset_id);
+        }
+        ws.unsubscribe(removed);
+    }
+
+    /// Assets held only to mark open positions get throttled book updates; snipe targets
+    /// (everything else, watchlisted and pinned markets included) stay at full rate
+    fn refresh_tick_throttle(&self) {
+        let Some(ws) = &self.ws_client else {
+            return;
+        };
+        let throttle = ws.throttle();
+        if !throttle.enabled() {
+            return;
+        }
+        let mut watched: HashSet<String> = self
+            .watchlist
+            .lock()
+            .map(|watchlist| watchlist.market_ids().into_iter().collect())
+            .unwrap_or_default();
+        if let Ok(pins) = self.pins.lock() {
+            watched.extend(pins.market_ids());
+        }
+        let mut marking = HashMap::new();
+        for position in self.all_positions() {
+            if watched.contains(&position.market_id) {
+                continue;
+            }
+            if let Some(market) = self.registry.get(&position.market_id) {
+                let end = market.end_time;
+                for asset_id in market.asset_ids {
+                    marking.insert(asset_id, end);
+                }
+            }
+        }
+        throttle.set_marking(marking);
+    }
+
+    /// Track one outcome's book shape; flags the market while its book is suspended or one-sided
+    fn observe_book(&mut self, market_id: &str, side: &str, update: &OrderbookUpdate) {
+        let _alloc = alloc_profile::region("sniper::observe_book");
+        // Price-change events carry best bid/ask, which come through empty or zero when a side is gone
+        let has_level = |levels: &[PriceLevel]| {
+            levels
+                .iter()
+                .any(|l| l.price.parse::<f64>().is_ok_and(|p| p > 0.0))
+        };
+        let shape = BookShape::new(has_level(&update.bids), has_level(&update.asks));
+        let registry = &self.registry;
+        let event = self
+            .suspension
+            .observe(market_id, side, shape, Utc::now(), || {
+                registry.get(market_id).map(|m| m.question)
+            });
+        if event.is_some() {
+            let since = self
+                .suspension
+                .status(market_id)
+                .and_then(|s| s.suspended_since);
+            if let Ok(mut lifecycle) = self.lifecycle.lock() {
+                lifecycle.set_suspended(market_id, since);
+            }
+        }
+    }
+
+    /// Order flow of the outcome a predictive entry buys (PREDICTIVE_MIN_OFI) and the model
+    /// (PREDICTIVE_MODEL_PATH) have to back it
+    fn predictive_confirms(
+        &self,
+        market: &MarketData,
+        side: &str,
+        price: f64,
+        binance_price: Option<f64>,
+    ) -> bool {
+        let now = Utc::now();
+        if !self
+            .predictive_strategy
+            .flow_confirms(self.feature_frame(&market.id, side).as_ref())
+        {
+            return false;
+        }
+        let input = self.predictive_strategy.model_input(
+            market,
+            now,
+            binance_price,
+            self.feature_frame(&market.id, "YES"),
+        );
+        self.predictive_strategy.model_confirms(&input, side, price)
+    }
+
+    fn feature_frame(&self, market_id: &str, side: &str) -> Option<FeatureFrame> {
+        let now_ms = Utc::now().timestamp_millis() as u64;
+        self.features
+            .as_ref()?
+            .lock()
+            .ok()?
+            .market_frame(market_id, side, now_ms)
+    }
+
+    /// Sample what a model would have seen of a predictive candidate (FEATURE_RECORD_SECS)
+    fn record_features(&self, market: &MarketData, now: chrono::DateTime<Utc>, binance_price: f64) {
+        let Some(Ok(mut recorder)) = self.feature_recorder.as_ref().map(|r| r.lock()) else {
+            return;
+        };
+        let input = self.predictive_strategy.model_input(
+            market,
+            now,
+            Some(binance_price),
+            self.feature_frame(&market.id, "YES"),
+        );
+        if let Err(e) = recorder.record(&market.id, &input, now) {
+            warn!("⚠️ Failed to record features of {}: {}", market.id, e);
+        }
+    }
+
+    /// A ConditionResolution event: redeem the condition right away if we hold it
+    async fn on_condition_resolved(&mut self, condition_id: &ConditionId) {
+        if let Ok(mut zombies) = self.zombies.lock() {
+            zombies.forget(condition_id.as_str());
+        }
+        self.fee_rates.forget(condition_id.as_str());
+        self.synthetic.forget(condition_id.as_str());
+        let held = self
+            .risk_manager
+            .get_positions()
+            .iter()
+            .any(|p| normalize_market_id(&p.market_id) == condition_id.as_str());
+        if !held {
+            debug!("Resolution of {} (not held)", condition_id);
+            return;
+        }
+        info!(
+            "🏛️ Held condition {} resolved on-chain, redeeming",
+            condition_id
+        );
+        self.redeem_resolved(Some(condition_id.as_str())).await;
+    }
+
+    /// Redeem resolved positions (or only those in market `only`) and book their PnL: the
+    /// winners through an on-chain redemption worth its gas, sent off the loop (one batch at a
+    /// time, booked by `on_redemptions`), the losers without one
+    async fn redeem_resolved(&mut self, only: Option<&str>) {
+        let Some(rm) = self.redemption_manager.clone() else {
+            return;
+        };
+        // Resolved positions with their winner and what redeeming them pays
+        let positions = self.risk_manager.get_positions();
+        let now = Utc::now();
+        let rpc = self
+            .breakers
+            .rpc(self.config.polygon_ws_rpc.as_deref().unwrap_or_default());
+        let mut resolved: Vec<(String, Option<&'static str>)> = Vec::new();
+        let mut candidates: Vec<RedemptionCandidate> = Vec::new();
+        for pos in positions {
+            if only.is_some_and(|id| !id.eq_ignore_ascii_case(&pos.market_id))
+                || cross_venue::is_kalshi_position(&pos.market_id)
+                || resolved.iter().any(|(id, _)| *id == pos.market_id)
+            {
+                continue;
+            }
+            match rpc.call(rm.is_condition_resolved(&pos.market_id)).await {
+                Ok(true) => {
+                    if self.advance(&pos.market_id, MarketState::Resolved) {
+                        info!("🎉 Market {} resolved!", pos.market_id);
+                    }
+                    let payouts = match rpc.call(rm.payout_per_share(&pos.market_id)).await {
+                        Ok(payouts) => Some(payouts),
+                        Err(e) => {
+                            warn!("⚠️ Failed to read payout of {}: {}", pos.market_id, e);
+                            None
+                        }
+                    };
+                    // None for a split resolution: booked from what the redemption pays
+                    let winner = payouts.and_then(|(yes, no)| winner_from_prices(yes, no));
+                    let resolved_at = self
+                        .lifecycle
+                        .lock()
+                        .ok()
+                        .and_then(|l| l.get(&pos.market_id)?.entered_at(MarketState::Resolved))
+                        .unwrap_or(now);
+                    candidates.push(RedemptionCandidate::new(&pos, payouts, resolved_at));
+                    resolved.push((pos.market_id.clone(), winner));
+                }
+                Ok(false) => {}
+                // RPC down: the remaining checks would be rejected too
+                Err(e) if is_circuit_open(&e) => {
+                    debug!("Skipping resolution checks: {}", e);
+                    break;
+                }
+                Err(e) => {
+                    warn!("⚠️ Failed to check resolution for {}: {}", pos.market_id, e);
+                }
+            }
+        }
+
+        // Only redemptions worth their gas go on-chain; losers need none
+        let price_gwei = if candidates.is_empty() {
+            self.config.gas.fallback_gas_price_gwei
+        } else {
+            match rpc.call(rm.gas_price_gwei()).await {
+                Ok(price) => price,
+                Err(e) => {
+                    debug!("Gas price unavailable, using GAS_PRICE_GWEI: {}", e);
+                    self.config.gas.fallback_gas_price_gwei
+                }
+            }
+        };
+        let gas = GasCost {
+            units: self.config.gas.redeem_gas_units,
+            price_gwei,
+            native_token_usd: self.config.gas.native_token_usd,
+        };
+        let plan = gas::plan_redemptions(&candidates, gas, &self.config.gas, now);
+        if !plan.waiting.is_empty() {
+            info!(
+                "⛽ {} resolved positions wait for redemption (gas ${:.4} per tx, batch at ${:.2})",
+                plan.waiting.len(),
+                gas.usd(),
+                self.config.gas.batch_min_value_usd
+            );
+        }
+        // Resolved but not redeemed yet: on its way back, not free to spend. Those redeemed now
+        // are due by the receipt timeout
+        let due_at =
+            now + chrono::Duration::seconds(self.config.gas.redeem_receipt_timeout_secs as i64);
+        if let Ok(mut margin) = self.margin.lock() {
+            for candidate in &candidates {
+                let due = plan.redeem.contains(&candidate.market_id).then_some(due_at);
+                margin.set_pending_redemption(&candidate.market_id, candidate.expected_usd(), due);
+            }
+        }
+
+        // One transaction per condition on consecutive nonces, or one for all through the Safe
+        let mut to_redeem: Vec<(String, PositionCollateral)> = Vec::new();
+        for market_id in &plan.redeem {
+            if !to_redeem.iter().any(|(id, _)| id == market_id) {
+                to_redeem.push((market_id.clone(), self.collateral_of(market_id)));
+            }
+        }
+        if !to_redeem.is_empty() {
+            if self.redeeming.swap(true, Ordering::SeqCst) {
+                debug!("Redemption batch still running, skipping");
+            } else {
+                info!("💰 Redeeming {} resolved conditions...", to_redeem.len());
+                let winners: HashMap<String, Option<&'static str>> = resolved
+                    .iter()
+                    .filter(|(id, _)| to_redeem.iter().any(|(redeem, _)| redeem == id))
+                    .cloned()
+                    .collect();
+                let max_in_flight = self.config.gas.redeem_max_in_flight;
+                let receipt_timeout =
+                    Duration::from_secs(self.config.gas.redeem_receipt_timeout_secs);
+                let redeemed = self.redeemed_tx.clone();
+                tokio::spawn(async move {
+                    let results = rm
+                        .redeem_batch(&to_redeem, max_in_flight, receipt_timeout)
+                        .await;
+                    let _ = redeemed.send(RedemptionRound { results, winners });
+                });
+            }
+        }
+
+        for (market_id, winner) in resolved {
+            if plan.release.contains(&market_id) {
+                info!(
+                    "🧾 {} lost: nothing to redeem, closed without a transaction",
+                    market_id
+                );
+                self.close_resolved(&market_id, winner, Closing::Lost).await;
+            } else if plan.written_off.contains(&market_id) {
+                warn!(
+                    "🧾 {} not worth its redemption gas after {}s: written off, tokens left in the wallet",
+                    market_id, self.config.gas.redeem_max_wait_secs
+                );
+                self.close_resolved(&market_id, winner, Closing::WrittenOff)
+                    .await;
+            }
+        }
+    }
+
+    /// Book a redemption batch sent by `redeem_resolved`. The next batch may only go once
+    /// these positions are closed: it would redeem them again
+    async fn on_redemptions(&mut self, round: RedemptionRound) {
+        for result in round.results {
+            match (result.payout_usd, &result.error) {
+                (Some(payout), _) => {
+                    info!("✅ Redeemed {} for ${:.2}", result.condition_id, payout);
+                    let winner = round.winners.get(&result.condition_id).copied().flatten();
+                    self.close_resolved(&result.condition_id, winner, Closing::Redeemed(payout))
+                        .await;
+                }
+                // Retried next round
+                (None, error) => {
+                    error!(
+                        "❌ Redemption failed for {} ({}): {}",
+                        result.condition_id,
+                        result.tx_hash.as_deref().unwrap_or("not sent"),
+                        error.as_deref().unwrap_or("unknown")
+                    );
+                }
+            }
+        }
+        self.redeeming.store(false, Ordering::SeqCst);
+    }
+
+    /// Close a resolved position and book its PnL (from the winner when known)
+    async fn close_resolved(
+        &mut self,
+        market_id: &str,
+        winner: Option<&'static str>,
+        closing: Closing,
+    ) {
+        self.advance(market_id, MarketState::Redeemed);
+        self.settle_edge(market_id, winner, closing);
+        // Redemption confirmed on-chain: free up the exposure
+        self.risk_manager.remove_position(market_id);
+        if let Ok(mut margin) = self.margin.lock() {
+            margin.clear_pending_redemption(market_id);
+        }
+        self.close_hedge(market_id, Some(winner)).await;
+        if let Ok(mut tracker) = self.pnl_tracker.lock() {
+            match (winner, closing) {
+                (_, Closing::WrittenOff) => {
+                    let pnl = tracker.redeem_market(market_id, 0.0);
+                    info!("🏁 {} written off: PnL ${:.2}", market_id, pnl);
+                }
+                (Some(winner), _) => {
+                    let pnl = tracker.resolve_market(market_id, winner);
+                    info!("🏁 {} settled {}: PnL ${:.2}", market_id, winner, pnl);
+                }
+                // Split or unreadable winner: book what the redemption paid
+                (None, Closing::Redeemed(payout)) => {
+                    let pnl = tracker.redeem_market(market_id, payout);
+                    info!(
+                        "🏁 {} redeemed for ${:.2}: PnL ${:.2}",
+                        market_id, payout, pnl
+                    );
+                }
+                (None, Closing::Lost) => {}
+            }
+        }
+    }
+
+    /// Re-check legs of unsettled arbitrage trades that hadn't fully filled yet
+    async fn refresh_edge_fills(&self) {
+        let checks = match self.edge_realization.lock() {
+            Ok(tracker) => tracker.pending_fill_checks(),
+            Err(_) => return,
+        };
+        for check in checks {
+            let filled = self
+                .executor
+                .filled_fraction(&check.order_id, &check.market_id)
+                .await;
+            if let Ok(mut tracker) = self.edge_realization.lock() {
+                tracker.record_fill(&check.trade_id, &check.side, filled);
+            }
+        }
+    }
+
+    /// Settle a resolved market's arbitrage trades at what one share of each outcome paid
+    fn settle_edge(&self, market_id: &str, winner: Option<&str>, closing: Closing) {
+        let (yes_value, no_value) = match (winner, closing) {
+            (_, Closing::WrittenOff) | (None, Closing::Lost) => (0.0, 0.0),
+            (Some("YES"), _) => (1.0, 0.0),
+            (Some(_), _) => (0.0, 1.0),
+            // Split or unreadable winner: the payout over the shares actually held of each
+            // outcome, none to an outcome we don't hold
+            (None, Closing::Redeemed(payout)) => {
+                let (yes, no) = self
+                    .pnl_tracker
+                    .lock()
+                    .map(|t| t.market_shares(market_id))
+                    .unwrap_or_default();
+                let value = if yes + no > 0.0 {
+                    (payout / (yes + no)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (
+                    if yes > 0.0 { value } else { 0.0 },
+                    if no > 0.0 { value } else { 0.0 },
+                )
+            }
+        };
+        if let Ok(mut tracker) = self.edge_realization.lock() {
+            let settled = tracker.settle_market(market_id, yes_value, no_value, Utc::now());
+            if settled > 0 {
+                if let Some(guard) = &self.anomaly_guard {
+                    guard.record_pnl_shortfall(market_id, tracker.market_shortfall_usd(market_id));
+                }
+            }
+        }
+    }
+
+    /// Add the arbitrage edge just evaluated to the opportunity book (if enabled)
+    fn record_opportunity(
+        &self,
+        market: &MarketData,
+        now: chrono::DateTime<Utc>,
+        min_edge_bps: i32,
+    ) {
+        if !self.strategy.enabled() || !market.quote_quality().is_complete() {
+            return;
+        }
+        if let Some(Ok(mut book)) = self.opportunities.as_ref().map(|b| b.lock()) {
+            book.record(OpportunityRecord {
+                timestamp: now,
+                market_id: market.id.to_string(),
+                net_spread_bps: ArbitrageStrategy::net_spread_bps_at_fee(
+                    market,
+                    self.fee_per_leg_bps(&market.id),
+                ),
+                min_edge_bps,
+                liquidity: market.liquidity,
+            });
+        }
+    }
+
+    /// Write a strategy evaluation to the decision log (if enabled)
+    fn record_decision(
+        &self,
+        strategy: &str,
+        market: &MarketData,
+        now: chrono::DateTime<Utc>,
+        binance_price: Option<f64>,
+        min_edge_bps: Option<i32>,
+        action: &TradeAction,
+    ) {
+        if !matches!(action, TradeAction::None) {
+            self.events.publish(|| AgentEvent::Signal {
+                strategy: strategy.to_string(),
+                market_id: market.id.to_string(),
+                action: action.clone(),
+            });
+        }
+        if let Some(log) = &self.decision_log {
+            let receive_to_decision_ns = self.evaluation_received_ns.map(clock::elapsed_ns);
+            // Same tick, same state: the fee, multiplier and books the decision just used
+            let arbitrage = (strategy == "arbitrage").then(|| self.arbitrage_inputs(market));
+            if let Some(id) = log.record(
+                strategy,
+                market,
+                now,
+                binance_price,
+                min_edge_bps,
+                arbitrage,
+                receive_to_decision_ns,
+                action,
+            ) {
+                if matches!(action, TradeAction::None) {
+                    debug!("📼 Recorded {} decision {}", strategy, id);
+                } else {
+                    info!(
+                        "📼 Recorded {} signal as decision {} (receive→decision {})",
+                        strategy,
+                        id,
+                        receive_to_decision_ns
+                            .map(|ns| format!("{}µs", ns / 1_000))
+                            .unwrap_or_else(|| "n/a".to_string())
+                    );
+                }
+            }
+        }
+    }
+
+    /// Let the experiment (if it targets this strategy) attribute both arms and pick the live action
+    async fn route_experiment(
+        &mut self,
+        strategy: &str,
+        market: &MarketData,
+        now: chrono::DateTime<Utc>,
+        binance_price: Option<f64>,
+        action: TradeAction,
+    ) -> TradeAction {
+        match &mut self.experiment {
+            Some(exp) if exp.strategy() == strategy => {
+                exp.route(market, now, binance_price, action).await
+            }
+            _ => action,
+        }
+    }
+
+    /// Refresh the cached trading balance (at most every 10s), counting only the collateral
+    /// the exchange settles in, and top it up from the other USDC variant if auto-swap is on.
+    /// The margin view and the risk manager's free collateral are refreshed with it
+    async fn refresh_balance(&mut self) {
+        if self.last_balance_update.elapsed() <= Duration::from_secs(10) {
+            return;
+        }
+
+        if let Some(refresh) = read_balance(self.market_interface.as_ref()).await {
+            self.apply_balance(refresh).await;
+        }
+    }
+
+    /// Before sizing an entry: the first balance is read inline, later ones off the loop (the
+    /// orders placed meanwhile are deducted from the cached one)
+    async fn refresh_balance_for_entry(&mut self) {
+        if self.margin.lock().is_ok_and(|m| m.is_refreshed()) {
+            self.refresh_balance_in_background();
+        } else {
+            self.refresh_balance().await;
+        }
+    }
+
+    /// `refresh_balance` off the loop, for jobs fine with the cached balance until it lands
+    /// (through `balance_rx`)
+    fn refresh_balance_in_background(&self) {
+        if self.last_balance_update.elapsed() <= Duration::from_secs(10)
+            || self.refreshing_balance.swap(true, Ordering::SeqCst)
+        {
+            return;
+        }
+        let market_interface = self.market_interface.clone();
+        let (tx, refreshing) = (self.balance_tx.clone(), self.refreshing_balance.clone());
+        tokio::spawn(async move {
+            match read_balance(market_interface.as_ref()).await {
+                Some(refresh) => {
+                    let _ = tx.send(refresh);
+                }
+                None => refreshing.store(false, Ordering::SeqCst),
+            }
+        });
+    }
+
+    /// Take a balance refresh into the cache, the margin view and the free collateral, then
+    /// top up the exchange collateral if it runs short
+    async fn apply_balance(&mut self, refresh: BalanceRefresh) {
+        let BalanceRefresh {
+            balances,
+            open_orders,
+        } = refresh;
+        let required = required_collateral();
+        self.cached_balance = balances.of(required);
+        self.last_balance_update = std::time::Instant::now();
+        if let Ok(mut margin) = self.margin.lock() {
+            margin.set_wallet(self.cached_balance, Utc::now());
+            if let Some(orders) = &open_orders {
+                margin.set_resting_orders(orders);
+            }
+            // Kalshi legs hold no Polymarket collateral
+            let positions: Vec<_> = self
+                .risk_manager
+                .get_positions()
+                .into_iter()
+                .filter(|p| !cross_venue::is_kalshi_position(&p.market_id))
+                .collect();
+            margin.set_positions(&positions);
+        }
+        self.sync_free_collateral();
+
+        if !self.failover_allows_trading() {
+            return;
+        }
+        if let Some(swapper) = &mut self.collateral_swapper {
+            match swapper.top_up(required, &balances).await {
+                Ok(Some(tx)) => info!(
+                    "🔁 Topping up {} (${:.2} left), swap tx {}",
+                    required.symbol(),
+                    self.cached_balance,
+                    tx
+                ),
+                Ok(None) => {}
+                Err(e) => error!("❌ Collateral swap failed: {}", e),
+            }
+        } else if let Some(remote) = self.remote_executor.clone().filter(|_| {
+            self.config.collateral.auto_swap
+                && balances.is_short(required, self.config.collateral.swap_floor_usd)
+        }) {
+            // The executor swaps (one at a time, with its cooldown), off the loop
+            let left = self.cached_balance;
+            tokio::spawn(async move {
+                match remote.top_up_collateral(required, balances).await {
+                    Ok(Some(tx)) => info!(
+                        "🔁 Topping up {} (${:.2} left), swap tx {}",
+                        required.symbol(),
+                        left,
+                        tx
+                    ),
+                    Ok(None) => {}
+                    Err(e) => error!("❌ Collateral swap failed: {}", e),
+                }
+            });
+        }
+    }
+
+    /// Collateral new entries can use: the wallet less what resting orders lock
+    fn free_collateral(&self) -> f64 {
+        self.margin
+            .lock()
+            .map(|m| m.free_usd())
+            .unwrap_or(self.cached_balance)
+    }
+
+    /// Hand the margin view's free collateral to the risk manager's entry checks
+    fn sync_free_collateral(&mut self) {
+        let free_usd = self.free_collateral();
+        self.risk_manager.set_free_collateral(free_usd);
+    }
+
+    /// Collateral an order just took, kept out of sizing until the next refresh sees it
+    fn spend_collateral(&mut self, size_usd: f64) {
+        if let Ok(mut margin) = self.margin.lock() {
+            margin.spend(size_usd);
+        }
+        self.sync_free_collateral();
+    }
+
+    /// Collateral expected back: the redemptions sent, when due to land. Those still pending a
+    /// horizon after first due are stuck and not counted on
+    fn capital_releases(&self, now: DateTime<Utc>) -> Vec<CapitalRelease> {
+        let Ok(margin) = self.margin.lock() else {
+            return Vec::new();
+        };
+        margin
+            .expected_redemptions(now, self.capital_wait.horizon())
+            .map(|(market_id, payout_usd, due_at)| CapitalRelease {
+                market_id: market_id.to_string(),
+                payout_usd,
+                expected_at: due_at.max(now),
+            })
+            .collect()
+    }
+
+    /// Hold a signal short `shortfall_usd` of collateral until releases expected soon fund it
+    fn defer_for_capital(
+        &mut self,
+        market: &MarketData,
+        trade_prefix: &str,
+        action: DeferredAction,
+        shortfall_usd: f64,
+    ) -> bool {
+        if !self.capital_wait.enabled() || shortfall_usd <= 0.0 {
+            return false;
+        }
+        let now = Utc::now();
+        let releases = self.capital_releases(now);
+        let signal = DeferredSignal {
+            market_id: market.id.to_string(),
+            trade_prefix: trade_prefix.to_string(),
+            action,
+            // A waiting signal deferred again keeps its wait
+            queued_at: self.deferred_since.unwrap_or(now),
+        };
+        let edge_bps = signal.action.edge_bps();
+        if !self
+            .capital_wait
+            .defer(signal, shortfall_usd, &releases, now)
+        {
+            return false;
+        }
+        info!(
+            "⏳ {} signal on {} ({:.0} bps) waits for ${:.2} of collateral due back within {}s",
+            trade_prefix,
+            market.question,
+            edge_bps,
+            self.capital_wait.expected_release_usd(&releases, now),
+            self.config.capital_wait.horizon_secs
+        );
+        true
+    }
+
+    /// Place the waiting signals the free collateral now covers, at their prices or better;
+    /// those that waited too long are dropped
+    async fn run_waiting_signals(&mut self) {
+        if self.capital_wait.is_empty() {
+            return;
+        }
+        let now = Utc::now();
+        for signal in self.capital_wait.expire(now) {
+            info!(
+                "⌛ {} signal on {} dropped: no collateral freed in time",
+                signal.trade_prefix, signal.market_id
+            );
+        }
+        if self.capital_wait.is_empty() || !self.failover_allows_trading() {
+            return;
+        }
+
+        // Releases on the cached balance; a fresh one lands for the next run
+        self.refresh_balance_in_background();
+        let ready = self.capital_wait.release(self.free_collateral());
+        for signal in ready {
+            let Some(market) = self.registry.get(&signal.market_id) else {
+                continue;
+            };
+            if self.market_state(&market.id).is_some_and(|s| s.is_closed()) {
+                continue;
+            }
+            info!(
+                "▶️ Collateral back: placing the waiting {} signal on {}",
+                signal.trade_prefix, market.question
+            );
+            self.deferred_since = Some(signal.queued_at);
+            let result = self.execute_deferred(&market, signal).await;
+            self.deferred_since = None;
+            if let Err(e) = result {
+                error!("❌ Waiting signal on {} failed: {}", market.question, e);
+            }
+        }
+    }
+
+    /// Place a signal that waited (for collateral, or its obfuscation delay) on the market's
+    /// book as of now: dropped if the asks moved past its prices
+    async fn execute_deferred(
+        &mut self,
+        market: &MarketData,
+        signal: DeferredSignal,
+    ) -> Result<()> {
+        match signal.action {
+            DeferredAction::Snipe {
+                side,
+                price,
+                size_usd,
+                confidence,
+            } => {
+                if !market.ask(&side).is_some_and(|ask| ask <= price) {
+                    info!(
+                        "⌛ Waiting {} signal on {} dropped: price moved",
+                        signal.trade_prefix, market.question
+                    );
+                    return Ok(());
+                }
+                self.execute_snipe_signal(
+                    market,
+                    &side,
+                    Price::new(price),
+                    UsdAmount::new(size_usd),
+                    &signal.trade_prefix,
+                    confidence,
+                )
+                .await
+            }
+            DeferredAction::Arbitrage {
+                yes_price,
+                no_price,
+                size_usd,
+                expected_profit_bps,
+            } => {
+                let fills =
+                    |side: &str, price: f64| market.ask(side).is_some_and(|ask| ask <= price);
+                if !(fills("YES", yes_price) && fills("NO", no_price)) {
+                    info!(
+                        "⌛ Waiting {} signal on {} dropped: price moved",
+                        signal.trade_prefix, market.question
+                    );
+                    return Ok(());
+                }
+                self.execute_arbitrage_signal(
+                    market,
+                    Utc::now(),
+                    &signal.trade_prefix,
+                    Price::new(yes_price),
+                    Price::new(no_price),
+                    UsdAmount::new(size_usd),
+                    expected_profit_bps,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Hold an entry for the obfuscation delay, if timing jitter is on: the delay runs in a task
+    /// off the loop and the signal comes back through `jittered_rx`. False when it goes now
+    fn jitter(&self, market: &MarketData, trade_prefix: &str, action: DeferredAction) -> bool {
+        if self.jitter_released {
+            return false;
+        }
+        let Some(delay) = self.obfuscator.delay() else {
+            return false;
+        };
+        let jittered = JitteredSignal {
+            signal: DeferredSignal {
+                market_id: market.id.to_string(),
+                trade_prefix: trade_prefix.to_string(),
+                action,
+                queued_at: Utc::now(),
+            },
+            signal_ns: self.signal_ns(),
+        };
+        let tx = self.jittered_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = tx.send(jittered);
+        });
+        true
+    }
+
+    /// Place an entry whose obfuscation delay is over, re-read off the market's current book.
+    /// Its latency budget still runs from the original signal
+    async fn release_jittered(&mut self, jittered: JitteredSignal) {
+        let Some(market) = self.registry.get(&jittered.signal.market_id) else {
+            return;
+        };
+        if self.market_state(&market.id).is_some_and(|s| s.is_closed())
+            || !self.failover_allows_trading()
+        {
+            return;
+        }
+        self.evaluation_received_ns = Some(jittered.signal_ns);
+        self.jitter_released = true;
+        if let Err(e) = self.execute_deferred(&market, jittered.signal).await {
+            error!("❌ Jittered signal on {} failed: {}", market.question, e);
+        }
+        self.jitter_released = false;
+        self.evaluation_received_ns = None;
+    }
+
+    /// Raise / execute / settle funding requests against the current trading balance
+    async fn check_funding(&mut self) {
+        // The peer may be funding the same wallet
+        if !self.failover_allows_trading() {
+            return;
+        }
+        // Always use a fresh balance: a stale one would re-raise a request that already landed
+        self.last_balance_update = std::time::Instant::now() - Duration::from_secs(600);
+        self.refresh_balance().await;
+        let balance = self.cached_balance;
+
+        let Some(funding) = self.funding.clone() else {
+            // The executor holds the key and runs the helper: it gets the balance
+            if let Some(remote) = &self.remote_executor {
+                if let Err(e) = remote.fund(balance).await {
+                    warn!("⚠️ Funding check on the executor failed: {}", e);
+                }
+            }
+            return;
+        };
+        if let Err(e) = funding.settle(balance) {
+            warn!("⚠️ Funding settle failed: {}", e);
+        }
+        if let Err(e) = funding.check(balance) {
+            warn!("⚠️ Funding check failed: {}", e);
+        }
+        // Approvals and deposits wait for mainnet: off the run loop (one round at a time)
+        tokio::spawn(async move {
+            if let Err(e) = funding.process().await {
+                warn!("⚠️ Funding processing failed: {}", e);
+            }
+        });
+    }
+
+    async fn execute_snipe_signal(
+        &mut self,
+        market: &MarketData,
+        side: &str,
+        price: Price,
+        size_usd: UsdAmount,
+        trade_prefix: &str,
+        confidence: f64,
+    ) -> Result<()> {
+        let (price, size_usd) = (price.get(), size_usd.get());
+        // Jitter first, off the loop: the freshness check and sizing below see the market as of
+        // submission
+        let action = DeferredAction::Snipe {
+            side: side.to_string(),
+            price,
+            size_usd,
+            confidence,
+        };
+        if self.jitter(market, trade_prefix, action) {
+            return Ok(());
+        }
+        // The budget runs from the book update behind the signal: the queue wait, jitter,
+        // freshness check and sizing all count against it
+        let latency_budget =
+            LatencyBudget::for_snipe(&self.config.latency_budget, trade_prefix, self.signal_ns());
+        if !self.quotes_fresh(market, &[(side, price)]).await {
+            return Ok(());
+        }
+        let signal_ns = clock::now_ns();
+        let signal_ask = self.live_ask(market, side);
+        let confidence = self.whale_adjusted_confidence(&market.id, side, confidence);
+        self.refresh_balance_for_entry().await;
+
+        let free_usd = self.free_collateral();
+        let final_size = self.obfuscator.size(size_usd.min(free_usd));
+        if final_size < 1.0 {
+            let action = DeferredAction::Snipe {
+                side: side.to_string(),
+                price,
+                size_usd,
+                confidence,
+            };
+            if self.defer_for_capital(market, trade_prefix, action, size_usd - free_usd) {
+                return Ok(());
+            }
+            warn!(
+                "❌ Insufficient free collateral for snipe (${:.2})",
+                free_usd
+            );
+            return Ok(());
+        }
+
+        if self
+            .risk_book(trade_prefix)
+            .validate_entry(&market.id, final_size, confidence)
+            && self.validate_category_exposure(trade_prefix, market, final_size)
+            && self.validate_capital_bucket(trade_prefix, final_size)
+        {
+            let post = self.toxicity_context(market, side);
+            // Read before the order goes out, so its own fill can't pass for drift
+            let post_quote = (self.live_ask(market, side), clock::now_ns());
+            // Paper-only strategies book their positions apart
+            let risk_book = if self.executor.is_paper(trade_prefix) {
+                &mut self.paper_risk
+            } else {
+                &mut self.risk_manager
+            };
+            let trade_id = format!(
+                "{}_{}_{}",
+                trade_prefix,
+                market.id,
+                Utc::now().timestamp_millis()
+            );
+            // Market orders take the book now, up to the signal's price plus the slippage cap
+            let placed = if self.config.market_orders.snipes {
+                let max_price =
+                    price * (1.0 + self.config.market_orders.max_slippage_bps / 10_000.0);
+                self.executor
+                    .execute_market_order(
+                        market,
+                        side,
+                        Price::new(max_price),
+                        UsdAmount::new(final_size),
+                        &trade_id,
+                        risk_book,
+                        latency_budget,
+                    )
+                    .await
+            } else {
+                self.executor
+                    .execute_snipe(
+                        market,
+                        side,
+                        Price::new(price),
+                        UsdAmount::new(final_size),
+                        &trade_id,
+                        risk_book,
+                        latency_budget,
+                    )
+                    .await
+                    .map(|order_id| (order_id, price))
+            };
+            match placed {
+                Ok((order_id, price)) => {
+                    if !self.executor.is_paper(&trade_id) {
+                        self.spend_collateral(final_size);
+                    }
+                    self.events.publish(|| AgentEvent::OrderPlaced {
+                        trade_id: trade_id.clone(),
+                        market_id: market.id.to_string(),
+                        side: side.to_string(),
+                        price,
+                        size_usd: final_size,
+                    });
+                    self.record_execution_drift(
+                        market,
+                        &trade_id,
+                        side,
+                        (signal_ask, signal_ns),
+                        post_quote,
+                    );
+                    self.advance(&market.id, MarketState::Positioned);
+                    self.record_shadow_order(market, &trade_id, side, price, final_size);
+                    self.watch_toxicity(market, &trade_id, price, final_size, post);
+                    let snipe = (trade_prefix == "snipe").then(|| PendingSnipe {
+                        trade_id: trade_id.clone(),
+                        order_id: order_id.clone(),
+                        market_id: market.id.to_string(),
+                        side: side.to_string(),
+                        price,
+                        size_usd: final_size,
+                        ends_at: market.end_time,
+                        placed_at: Utc::now(),
+                    });
+                    self.track_fill(market, &trade_id, side, order_id, price, final_size);
+                    if let Some(snipe) = snipe {
+                        self.hedge_when_filled(snipe, self.config.market_orders.snipes)
+                            .await;
+                    }
+                }
+                Err(e) => match missed_window(&e) {
+                    Some(missed) => {
+                        self.record_missed_window(market, &trade_id, trade_prefix, missed)
+                    }
+                    None => {
+                        error!("❌ Snipe execution failed: {}", e);
+                        self.record_failure(market, &trade_id, &e);
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Size, risk-check and place a YES + NO arbitrage entry, trade ids prefixed `trade_prefix`
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_arbitrage_signal(
+        &mut self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        trade_prefix: &str,
+        yes_price: Price,
+        no_price: Price,
+        size_usd: UsdAmount,
+        expected_profit_bps: i32,
+    ) -> Result<()> {
+        let (yes_price, no_price, size_usd) = (yes_price.get(), no_price.get(), size_usd.get());
+        let action = DeferredAction::Arbitrage {
+            yes_price,
+            no_price,
+            size_usd,
+            expected_profit_bps,
+        };
+        if self.jitter(market, trade_prefix, action) {
+            return Ok(());
+        }
+        let latency_budget =
+            LatencyBudget::for_arbitrage(&self.config.latency_budget, self.signal_ns());
+        if !self
+            .quotes_fresh(market, &[("YES", yes_price), ("NO", no_price)])
+            .await
+        {
+            return Ok(());
+        }
+        // Under the NegRiskAdapter the pair merges through it, and a NO basket converted by it
+        // may pay more: the edge has to survive both costings
+        if self.config.neg_risk.enabled && self.collateral_of(&market.id).is_neg_risk() {
+            let basket = self.neg_risk_basket(market, no_price);
+            let price_gwei = self.cached_gas_gwei;
+            let questions = basket.as_ref().map_or(0, |b| b.questions.len()) as u64;
+            let quote = NegRiskQuote {
+                yes_ask: yes_price,
+                no_ask: no_price,
+                basket_no_asks: basket
+                    .as_ref()
+                    .map(|b| b.questions.iter().map(|(_, _, ask)| *ask).collect())
+                    .unwrap_or_default(),
+                size_usd,
+                merge_gas_usd: self.gas_usd(self.config.arb_exit.merge_gas_units, price_gwei),
+                convert_gas_usd: self.gas_usd(
+                    self.config.neg_risk.convert_gas_units
+                        + self.config.neg_risk.convert_gas_units_per_question * questions,
+                    price_gwei,
+                ),
+            };
+            let check = neg_risk::check_arbitrage(&self.config.neg_risk, &quote);
+            match (check.route, basket) {
+                (NegRiskRoute::Pair, _) => debug!(
+                    "➕ Neg-risk pair of {} keeps {:.0} bps after fees and merge gas",
+                    market.question,
+                    check.pair.edge_bps()
+                ),
+                (NegRiskRoute::Convert, Some(basket)) => {
+                    let convert = check.convert.unwrap_or(check.pair);
+                    info!(
+                        "🔄 Converting a NO basket of {} questions beats the pair on {} ({:.0} vs {:.0} bps)",
+                        basket.questions.len(),
+                        market.question,
+                        convert.edge_bps(),
+                        check.pair.edge_bps()
+                    );
+                    return self
+                        .execute_neg_risk_basket(
+                            market,
+                            trade_prefix,
+                            basket,
+                            convert.payout,
+                            size_usd,
+                        )
+                        .await;
+                }
+                _ => {
+                    info!(
+                        "🚫 Arbitrage on {} doesn't survive the adapter's costs (pair {:.0} bps, basket {:?} bps, need {:.0})",
+                        market.question,
+                        check.pair.edge_bps(),
+                        check.convert.map(|c| c.edge_bps().round()),
+                        self.config.neg_risk.min_edge_bps
+                    );
+                    return Ok(());
+                }
+            }
+        }
+        let signal_ns = clock::now_ns();
+        let signal_asks = [
+            ("YES", self.live_ask(market, "YES")),
+            ("NO", self.live_ask(market, "NO")),
+        ];
+
+        self.refresh_balance_for_entry().await;
+        let free_usd = self.free_collateral();
+        let mut final_size = size_usd;
+        if free_usd < final_size {
+            warn!(
+                "⚠️ Free collateral (${:.2}) < Target Size (${:.2}). Capping to it.",
+                free_usd, final_size
+            );
+            final_size = free_usd;
+        }
+        // Both legs share the factor, so the pair stays balanced
+        let final_size = self.obfuscator.size(final_size);
+
+        const MIN_ARB_NOTIONAL_USD: f64 = 1.0;
+        if final_size < MIN_ARB_NOTIONAL_USD {
+            let action = DeferredAction::Arbitrage {
+                yes_price,
+                no_price,
+                size_usd,
+                expected_profit_bps,
+            };
+            self.defer_for_capital(market, trade_prefix, action, size_usd - free_usd);
+            return Ok(());
+        }
+
+        if self
+            .risk_book(trade_prefix)
+            .validate_entry(&market.id, final_size, 1.0)
+            && self.validate_category_exposure(trade_prefix, market, final_size)
+            && self.validate_capital_bucket(trade_prefix, final_size)
+        {
+            let trade_id = format!(
+                "{}_{}_{}",
+                trade_prefix,
+                market.id,
+                Utc::now().timestamp_millis()
+            );
+            let mut plan = ExecutionPlan::arbitrage(
+                &trade_id,
+                market,
+                Price::new(yes_price),
+                Price::new(no_price),
+                UsdAmount::new(final_size),
+            )
+            .with_latency_budget(latency_budget);
+            if self.is_toxic(&market.id) {
+                // Resting legs are what gets picked off
+                info!(
+                    "☣️ Toxic market {}: arbitrage legs fill-or-kill",
+                    market.question
+                );
+                plan = plan.fill_or_kill();
+            }
+            // A posted leg may never fill: the expected edge stays the one of taking both
+            let fee_per_leg_bps = self.fee_per_leg_bps(&market.id);
+            let maker_leg = if self.config.order_role.enabled && !self.is_toxic(&market.id) {
+                self.assign_roles(market, &mut plan)
+            } else {
+                None
+            };
+            let posts: Vec<(String, PostContext)> = plan
+                .legs
+                .iter()
+                .filter_map(|leg| {
+                    Some((leg.side.clone(), self.toxicity_context(market, &leg.side)?))
+                })
+                .collect();
+            // Read before the legs go out, so their own fills can't pass for drift
+            let post_ns = clock::now_ns();
+            let post_asks = [
+                ("YES", self.live_ask(market, "YES")),
+                ("NO", self.live_ask(market, "NO")),
+            ];
+            let risk_book = if self.executor.is_paper(&trade_id) {
+                &mut self.paper_risk
+            } else {
+                &mut self.risk_manager
+            };
+            match self.executor.execute_plan(&plan, risk_book).await {
+                Ok(report) => {
+                    info!("✅ Arbitrage Executed! Orders: {}", report.summary());
+                    if let Ok(mut tracker) = self.edge_realization.lock() {
+                        tracker.open(EdgeRealization::from_report(
+                            &market.id,
+                            now,
+                            expected_profit_bps,
+                            fee_per_leg_bps,
+                            &report,
+                        ));
+                    }
+                    self.advance(&market.id, MarketState::Positioned);
+                    // The posted leg is booked as it fills, against the leg that took
+                    let resting = maker_leg.and_then(|index| {
+                        let (leg, result) = (plan.legs.get(index)?, report.legs.get(index)?);
+                        let order_id = result.order_id.clone().filter(|_| result.is_placed())?;
+                        let leg_id = plan.leg_trade_id(leg);
+                        if self.executor.is_paper(&leg_id) {
+                            return None;
+                        }
+                        let taker = plan
+                            .legs
+                            .iter()
+                            .zip(&report.legs)
+                            .find(|(other, result)| other.side != leg.side && result.is_placed())
+                            .map(|(other, result)| TakenLeg {
+                                leg_id: plan.leg_trade_id(other),
+                                side: other.side.clone(),
+                                price: result.price,
+                                size_usd: other.size_usd.get(),
+                            });
+                        Some(RestingMakerLeg {
+                            group_id: trade_id.clone(),
+                            leg_id,
+                            market_id: market.id.to_string(),
+                            side: leg.side.clone(),
+                            order_id,
+                            price: result.price,
+                            size_usd: leg.size_usd.get(),
+                            cancel_at: Utc::now()
+                                + chrono::Duration::seconds(
+                                    self.config.order_role.maker_cancel_secs.min(86_400) as i64,
+                                ),
+                            taker,
+                        })
+                    });
+                    for (side, order_id, limit_price) in report.placed() {
+                        let Some(leg) = plan.legs.iter().find(|l| l.side == side) else {
+                            continue;
+                        };
+                        let leg_id = plan.leg_trade_id(leg);
+                        let size_usd = leg.size_usd.get();
+                        if !self.executor.is_paper(&leg_id) {
+                            self.spend_collateral(size_usd);
+                        }
+                        self.events.publish(|| AgentEvent::OrderPlaced {
+                            trade_id: leg_id.clone(),
+                            market_id: market.id.to_string(),
+                            side: side.to_string(),
+                            price: limit_price,
+                            size_usd,
+                        });
+                        let ask_of = |asks: &[(&str, Option<f64>)]| {
+                            asks.iter()
+                                .find(|(s, _)| *s == side)
+                                .and_then(|(_, ask)| *ask)
+                        };
+                        self.record_execution_drift(
+                            market,
+                            &leg_id,
+                            side,
+                            (ask_of(&signal_asks), signal_ns),
+                            (ask_of(&post_asks), post_ns),
+                        );
+                        self.record_shadow_order(market, &leg_id, side, limit_price, size_usd);
+                        let post = posts
+                            .iter()
+                            .find(|(s, _)| s == side)
+                            .map(|(_, post)| post.clone());
+                        self.watch_toxicity(market, &leg_id, limit_price, size_usd, post);
+                        self.track_fill(
+                            market,
+                            &leg_id,
+                            side,
+                            order_id.to_string(),
+                            limit_price,
+                            size_usd,
+                        );
+                    }
 
-        // 3) Optional fallback: arbitrage (disabled by default)
-        match self.strategy.check_opportunity(market) {
-            TradeAction::BuyBoth {
-                market_id: _,
-                yes_price,
-                no_price,
-                size_usd,
-                expected_profit_bps,
-            } => {
+                    // One position per placed leg, linked by the plan id, so each side
+                    // is marked, exited and settled at its own price
+                    let legs: Vec<Position> = plan
+                        .legs
+                        .iter()
+                        .zip(&report.legs)
+                        .filter(|(leg, result)| {
+                            result.is_placed()
+                                && resting.as_ref().map_or(true, |r| r.side != leg.side)
+                        })
+                        .map(|(leg, _)| Position {
+                            id: plan.leg_trade_id(leg),
+                            market_id: market.id.to_string(),
+                            market_question: market.question.clone(),
+                            side: leg.side.clone(),
+                            size: leg.size_usd.get(),
+                            entry_price: leg.price.get(),
+                            current_price: leg.price.get(),
+                            entry_time: Utc::now(),
+                            config_hash: None,
+                            group_id: None,
+                        })
+                        .collect();
+                    if let Ok(mut tracker) = self.pnl_book(&trade_id).lock() {
+                        tracker.add_group(&trade_id, legs);
+                    }
+                    if let Some(resting) = resting {
+                        info!(
+                            "⏳ {} leg of {} rests @ {:.2} until {}",
+                            resting.side,
+                            market.question,
+                            resting.price,
+                            resting.cancel_at.format("%H:%M:%S")
+                        );
+                        self.maker_legs.push(resting);
+                    }
+                }
+                Err(e) => match missed_window(&e) {
+                    Some(missed) => {
+                        self.record_missed_window(market, &trade_id, trade_prefix, missed)
+                    }
+                    None => {
+                        error!("❌ Execution failed: {}", e);
+                        self.record_failure(market, &trade_id, &e);
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Give each leg of an arbitrage plan its role (ORDER_ROLE_SELECTION), re-pricing the one
+    /// leg allowed to post a tick above its bid. Returns the index of that leg, if any
+    fn assign_roles(&self, market: &MarketData, plan: &mut ExecutionPlan) -> Option<usize> {
+        let secs_to_expiry = market.end_time.map(|end| (end - Utc::now()).num_seconds());
+        let edge_bps = (1.0 - plan.legs.iter().map(|l| l.price.get()).sum::<f64>()) * 10_000.0;
+        let mut maker = None;
+        for (index, leg) in plan.legs.iter_mut().enumerate() {
+            let frame = self.feature_frame(&market.id, &leg.side);
+            let book = LegBook {
+                best_bid: frame.as_ref().map(|f| f.best_bid).filter(|bid| *bid > 0.0),
+                best_ask: leg.price.get(),
+                recent_changes: frame
+                    .as_ref()
+                    .and_then(|f| f.windows.first())
+                    .map(|w| w.events),
+            };
+            // Two resting legs could leave either side naked: the second one takes
+            let choice = if maker.is_some() {
+                role::taker(&self.config.order_role, &book, "other leg posts")
+            } else {
+                choose_role(&self.config.order_role, &book, edge_bps, secs_to_expiry)
+            };
+            debug!(
+                "🎚️ {} leg of {}: {:?} @ {:.2} ({})",
+                leg.side, market.question, choice.role, choice.price, choice.reason
+            );
+            if choice.role == LiquidityRole::Maker {
+                maker = Some(index);
+            }
+            leg.price = Price::new(choice.price);
+        }
+        maker
+    }
+
+    /// Book what the posted arbitrage legs filled. One past its deadline is cancelled, and the
+    /// part of its taker leg left uncovered is sold back
+    async fn check_maker_legs(&mut self) {
+        let now = Utc::now();
+        for leg in std::mem::take(&mut self.maker_legs) {
+            let mut filled = self
+                .executor
+                .filled_fraction(&leg.order_id, &leg.market_id)
+                .await;
+            if filled < 1.0 && now < leg.cancel_at {
+                self.maker_legs.push(leg);
+                continue;
+            }
+            if filled < 1.0 {
+                if let Err(e) = self.executor.cancel_order(&leg.order_id).await {
+                    // Most likely matched meanwhile: read it again next round
+                    warn!("⚠️ Failed to cancel maker leg {}: {}", leg.leg_id, e);
+                    self.maker_legs.push(leg);
+                    continue;
+                }
+                // What matched before the cancel
+                filled = self
+                    .executor
+                    .filled_fraction(&leg.order_id, &leg.market_id)
+                    .await;
+            }
+            self.settle_maker_leg(&leg, filled).await;
+        }
+    }
+
+    /// Book `filled` (0-1) of a posted leg, release the rest and sell back the part of the
+    /// taker leg it leaves uncovered
+    async fn settle_maker_leg(&mut self, leg: &RestingMakerLeg, filled: f64) {
+        let filled_usd = leg.size_usd * filled.clamp(0.0, 1.0);
+        let market = self.registry.get(&leg.market_id);
+        info!(
+            "⏳ Maker leg {} filled {:.0}% of ${:.2}",
+            leg.leg_id,
+            filled * 100.0,
+            leg.size_usd
+        );
+        if filled_usd > 0.0 {
+            if let Ok(mut tracker) = self.pnl_book(&leg.group_id).lock() {
+                tracker.add_group(
+                    &leg.group_id,
+                    vec![Position {
+                        id: leg.leg_id.clone(),
+                        market_id: leg.market_id.clone(),
+                        market_question: market
+                            .as_ref()
+                            .map(|m| m.question.clone())
+                            .unwrap_or_default(),
+                        side: leg.side.clone(),
+                        size: filled_usd,
+                        entry_price: leg.price,
+                        current_price: leg.price,
+                        entry_time: Utc::now(),
+                        config_hash: None,
+                        group_id: None,
+                    }],
+                );
+            }
+        }
+        if filled_usd < leg.size_usd {
+            self.risk_book(&leg.leg_id).reduce_position(
+                &leg.market_id,
+                UsdAmount::new(leg.size_usd - filled_usd),
+                Price::new(leg.price),
+            );
+        }
+
+        let uncovered_usd = leg.uncovered_usd(filled);
+        let Some(taker) = leg.taker.as_ref().filter(|_| uncovered_usd > 0.0) else {
+            return;
+        };
+        let Some(market) = market else {
+            warn!(
+                "⚠️ Can't unwind taker leg {}: market unknown, held to resolution",
+                taker.leg_id
+            );
+            return;
+        };
+        let uncovered_shares = uncovered_usd / taker.price.max(0.01);
+        // The taker side's bid is the complement of the other outcome's ask
+        let opposite = if taker.side == "YES" { "NO" } else { "YES" };
+        let Some(price) = market.ask(opposite).map(|ask| 1.0 - ask) else {
+            warn!(
+                "⚠️ Can't unwind taker leg {}: no {} quote, held to resolution",
+                taker.leg_id, opposite
+            );
+            return;
+        };
+        match self
+            .executor
+            .sell_leg(
+                &market,
+                &taker.side,
+                Price::new(price),
+                uncovered_shares,
+                &taker.leg_id,
+            )
+            .await
+        {
+            Ok(order_id) => {
+                let pnl = self
+                    .pnl_book(&taker.leg_id)
+                    .lock()
+                    .ok()
+                    .and_then(|mut tracker| {
+                        tracker.reduce_position(
+                            &taker.leg_id,
+                            UsdAmount::new(uncovered_usd),
+                            Price::new(price),
+                        )
+                    });
+                self.risk_book(&taker.leg_id).reduce_position(
+                    &leg.market_id,
+                    UsdAmount::new(uncovered_usd),
+                    Price::new(price),
+                );
+                self.expect_exit_settlement(&market, &taker.leg_id, &taker.side, uncovered_shares);
                 info!(
-                    "🎯 ARBITRAGE Signal: {} (Profit: {} bps)",
-                    market.question, expected_profit_bps
+                    "↩️ Sold back ${:.2} of taker leg {} @ {:.4} (order {}, PnL ${:.2})",
+                    uncovered_usd,
+                    taker.leg_id,
+                    price,
+                    order_id,
+                    pnl.unwrap_or_default()
+                );
+                self.release_arb_group(&leg.market_id, &leg.group_id);
+            }
+            Err(e) => error!("❌ Failed to unwind taker leg {}: {}", taker.leg_id, e),
+        }
+    }
+
+    /// The listed questions of `market`'s adapter market with their NO asks (`no_price` for
+    /// `market` itself). None unless every one has an ask
+    fn neg_risk_basket(&self, market: &MarketData, no_price: f64) -> Option<BasketQuote> {
+        let member = self.neg_risk_groups.member(&market.id)?;
+        let mut questions = Vec::new();
+        for (index, condition_id) in self.neg_risk_groups.questions(&member.neg_risk_market_id) {
+            if condition_id == market.id {
+                questions.push((index, market.clone(), no_price));
+                continue;
+            }
+            let question = self.registry.get(&condition_id)?;
+            let ask = self
+                .live_ask(&question, "NO")
+                .or_else(|| question.ask("NO"))?;
+            questions.push((index, question, ask));
+        }
+        Some(BasketQuote {
+            neg_risk_market_id: member.neg_risk_market_id,
+            questions,
+        })
+    }
+
+    /// Buy the same number of NOs of every question of a basket, all or nothing. The sets every
+    /// leg filled are converted on a later redemption round, once their tokens have settled
+    async fn execute_neg_risk_basket(
+        &mut self,
+        market: &MarketData,
+        trade_prefix: &str,
+        basket: BasketQuote,
+        payout_per_set: f64,
+        size_usd: f64,
+    ) -> Result<()> {
+        // Every question is bought: each one's quote has to be fresh
+        for (_, question, ask) in &basket.questions {
+            if !self.quotes_fresh(question, &[("NO", *ask)]).await {
+                return Ok(());
+            }
+        }
+        self.refresh_balance_for_entry().await;
+        let final_size = self.obfuscator.size(size_usd.min(self.free_collateral()));
+        let cost: f64 = basket.questions.iter().map(|(_, _, ask)| ask).sum();
+        // Every leg has to clear the exchange's minimum
+        const MIN_LEG_NOTIONAL_USD: f64 = 1.0;
+        let sets = final_size / cost;
+        if basket
+            .questions
+            .iter()
+            .any(|(_, _, ask)| sets * ask < MIN_LEG_NOTIONAL_USD)
+        {
+            debug!(
+                "Basket of {} too small for its legs (${:.2} over {} questions)",
+                market.question,
+                final_size,
+                basket.questions.len()
+            );
+            return Ok(());
+        }
+        // ... and pass the entry checks for its own leg
+        let risk_book = if self.executor.is_paper(trade_prefix) {
+            &self.paper_risk
+        } else {
+            &self.risk_manager
+        };
+        let legs_pass = basket.questions.iter().all(|(_, question, ask)| {
+            risk_book.validate_entry(&question.id, sets * ask, 1.0)
+                && self.validate_category_exposure(trade_prefix, question, sets * ask)
+        });
+        if !(legs_pass && self.validate_capital_bucket(trade_prefix, final_size)) {
+            return Ok(());
+        }
+
+        let trade_id = format!(
+            "{}_{}_{}",
+            trade_prefix,
+            market.id,
+            Utc::now().timestamp_millis()
+        );
+        let questions: Vec<(MarketData, f64)> = basket
+            .questions
+            .iter()
+            .map(|(_, question, ask)| (question.clone(), *ask))
+            .collect();
+        let plan = ExecutionPlan::neg_risk_basket(&trade_id, &questions, sets);
+        let risk_book = if self.executor.is_paper(&trade_id) {
+            &mut self.paper_risk
+        } else {
+            &mut self.risk_manager
+        };
+        let report = match self.executor.execute_plan(&plan, risk_book).await {
+            Ok(report) => report,
+            Err(e) => {
+                error!("❌ Basket execution failed: {}", e);
+                self.record_failure(market, &trade_id, &e);
+                return Ok(());
+            }
+        };
+        info!("✅ NO basket bought! Orders: {}", report.summary());
+        self.advance(&market.id, MarketState::Positioned);
+
+        // FOK legs that were placed may still have been killed, or matched in part: only
+        // what filled is held, and only the sets every leg filled convert
+        let simulated = self.config.agent.paper_trading || self.config.agent.simulation_mode;
+        let mut legs = Vec::new();
+        let mut filled_sets = if report.is_complete() { sets } else { 0.0 };
+        for ((leg, result), (_, question, _)) in
+            plan.legs.iter().zip(&report.legs).zip(&basket.questions)
+        {
+            let Some(order_id) = result.order_id.as_ref().filter(|_| result.is_placed()) else {
+                continue;
+            };
+            let leg_id = plan.leg_trade_id(leg);
+            let filled = if simulated || self.executor.is_paper(&leg_id) {
+                1.0
+            } else {
+                self.executor.filled_fraction(order_id, &question.id).await
+            };
+            let filled_usd = leg.size_usd.get() * filled;
+            filled_sets = filled_sets.min(sets * filled);
+            if filled < 1.0 {
+                warn!(
+                    "⚠️ Basket leg {} filled {:.0}% of ${:.2}",
+                    leg_id,
+                    filled * 100.0,
+                    leg.size_usd
+                );
+                self.risk_book(&leg_id).reduce_position(
+                    &question.id,
+                    UsdAmount::new(leg.size_usd.get() - filled_usd),
+                    Price::new(result.price),
                 );
+            }
+            if filled_usd <= 0.0 {
+                continue;
+            }
+            if !self.executor.is_paper(&leg_id) {
+                self.spend_collateral(filled_usd);
+            }
+            self.events.publish(|| AgentEvent::OrderPlaced {
+                trade_id: leg_id.clone(),
+                market_id: question.id.to_string(),
+                side: leg.side.clone(),
+                price: result.price,
+                size_usd: filled_usd,
+            });
+            legs.push(Position {
+                id: leg_id,
+                market_id: question.id.to_string(),
+                market_question: question.question.clone(),
+                side: leg.side.clone(),
+                size: filled_usd,
+                entry_price: result.price,
+                current_price: result.price,
+                entry_time: Utc::now(),
+                config_hash: None,
+                group_id: None,
+            });
+        }
+        if let Ok(mut tracker) = self.pnl_book(&trade_id).lock() {
+            tracker.add_group(&trade_id, legs);
+        }
+        // Sets some leg is missing don't convert: their NOs are held to resolution
+        if filled_sets * payout_per_set < MIN_LEG_NOTIONAL_USD {
+            return Ok(());
+        }
+        let (question_indices, market_ids) = basket
+            .questions
+            .iter()
+            .map(|(index, question, _)| (*index, question.id.to_string()))
+            .unzip();
+        self.neg_risk_baskets.insert(
+            trade_id,
+            NegRiskBasket {
+                neg_risk_market_id: basket.neg_risk_market_id,
+                question_indices,
+                market_ids,
+                sets: filled_sets,
+                payout_per_set,
+                bought_at: Utc::now(),
+                attempts: 0,
+                tx_hash: None,
+            },
+        );
+        if let Err(e) = self.neg_risk_baskets.save() {
+            warn!("⚠️ Failed to persist neg-risk baskets: {}", e);
+        }
+        Ok(())
+    }
 
-                if self.last_balance_update.elapsed() > Duration::from_secs(10) {
-                    if let Ok(bal) = self.market_interface.get_balance().await {
-                        self.cached_balance = bal;
-                        self.last_balance_update = std::time::Instant::now();
+    /// Convert the NO baskets whose tokens have had time to settle, and book each conversion
+    /// once its receipt is in. Paper baskets are only booked; a failed or dropped conversion
+    /// is sent again, up to `MAX_CONVERT_ATTEMPTS`
+    async fn convert_neg_risk_baskets(&mut self) {
+        // Fills settle on-chain some blocks after the match
+        const SETTLE_SECS: i64 = 60;
+        let due = self.neg_risk_baskets.due(Utc::now(), SETTLE_SECS);
+        if due.is_empty() {
+            return;
+        }
+        let simulated = self.config.agent.paper_trading || self.config.agent.simulation_mode;
+        for (group_id, basket) in due {
+            if !simulated && !self.executor.is_paper(&group_id) {
+                let Some(rm) = &self.redemption_manager else {
+                    warn!(
+                        "⚠️ No wallet to convert basket {}: its NOs are held to resolution",
+                        group_id
+                    );
+                    self.neg_risk_baskets.remove(&group_id);
+                    continue;
+                };
+                // A conversion already sent: book it on its receipt, never send it twice
+                if let Some(tx_hash) = &basket.tx_hash {
+                    match rm.transaction_outcome(tx_hash).await {
+                        Ok(TxOutcome::Succeeded) => {}
+                        Ok(TxOutcome::Pending) => continue,
+                        Ok(outcome) => {
+                            warn!(
+                                "⚠️ Conversion of basket {} {:?}: {}",
+                                group_id, outcome, tx_hash
+                            );
+                            if let Some(basket) = self.neg_risk_baskets.get_mut(&group_id) {
+                                basket.tx_hash = None;
+                            }
+                            continue;
+                        }
+                        Err(e) => {
+                            debug!("Conversion receipt of {} not read: {}", group_id, e);
+                            continue;
+                        }
+                    }
+                } else {
+                    if basket.attempts >= MAX_CONVERT_ATTEMPTS {
+                        warn!(
+                            "⚠️ Basket {} not converted after {} attempts: its NOs are held to resolution",
+                            group_id, basket.attempts
+                        );
+                        self.neg_risk_baskets.remove(&group_id);
+                        continue;
+                    }
+                    let Some(index_set) = neg_risk::index_set(&basket.question_indices) else {
+                        warn!(
+                            "⚠️ Basket {} has a question index past 127: its NOs are held to resolution",
+                            group_id
+                        );
+                        self.neg_risk_baskets.remove(&group_id);
+                        continue;
+                    };
+                    let sent = rm
+                        .convert_positions(&basket.neg_risk_market_id, index_set, basket.sets)
+                        .await;
+                    if let Some(pending) = self.neg_risk_baskets.get_mut(&group_id) {
+                        pending.attempts += 1;
+                        match sent {
+                            Ok(tx_hash) => pending.tx_hash = Some(tx_hash),
+                            Err(e) => warn!(
+                                "⚠️ Conversion of basket {} failed (attempt {}): {}",
+                                group_id, pending.attempts, e
+                            ),
+                        }
                     }
+                    continue;
                 }
-                let balance = self.cached_balance;
-                let mut final_size = size_usd;
-                if balance < final_size {
+            }
+            self.neg_risk_baskets.remove(&group_id);
+            let pnl = self
+                .pnl_book(&group_id)
+                .lock()
+                .ok()
+                .and_then(|mut tracker| {
+                    tracker.convert_group(&group_id, basket.sets, basket.payout_per_set)
+                });
+            info!(
+                "🔄 Converted {:.2} NO sets of {} questions (PnL ${:.2})",
+                basket.sets,
+                basket.market_ids.len(),
+                pnl.unwrap_or_default()
+            );
+            for market_id in &basket.market_ids {
+                self.release_arb_group(market_id, &group_id);
+            }
+        }
+        if let Err(e) = self.neg_risk_baskets.save() {
+            warn!("⚠️ Failed to persist neg-risk baskets: {}", e);
+        }
+    }
+
+    /// Put an entry whose order call failed down to a cause
+    fn record_failure(&self, market: &MarketData, trade_id: &str, error: &anyhow::Error) {
+        if let Ok(mut log) = self.postmortems.lock() {
+            log.record_failure(trade_id, &market.id, &format!("{:#}", error), Utc::now());
+        }
+    }
+
+    /// A signal that ran over its latency budget: nothing was submitted
+    fn record_missed_window(
+        &self,
+        market: &MarketData,
+        trade_id: &str,
+        strategy: &str,
+        missed: &MissedWindow,
+    ) {
+        warn!(
+            "⏱️ Missed window on {}: {} signal took {:.1} ms (budget {} ms), not submitted",
+            market.question, strategy, missed.elapsed_ms, missed.budget_ms
+        );
+        if let Ok(mut log) = self.missed_windows.lock() {
+            log.record(MissedWindowEntry {
+                at: Utc::now(),
+                trade_id: trade_id.to_string(),
+                market_id: market.id.to_string(),
+                strategy: strategy.to_string(),
+                budget_ms: missed.budget_ms,
+                elapsed_ms: missed.elapsed_ms,
+            });
+        }
+        self.events.publish(|| AgentEvent::MissedWindow {
+            trade_id: trade_id.to_string(),
+            market_id: market.id.to_string(),
+            strategy: strategy.to_string(),
+            budget_ms: missed.budget_ms,
+            elapsed_ms: missed.elapsed_ms,
+        });
+    }
+
+    /// Classify the trades that closed at a loss since the last sweep, gathered one lock at a
+    /// time
+    fn review_losses(&self) {
+        let Some(mut sweep) = self.postmortems.lock().ok().map(|log| log.sweep()) else {
+            return;
+        };
+        match self.pnl_tracker.lock() {
+            Ok(tracker) => sweep.collect_trades(&tracker.trades),
+            Err(_) => return,
+        }
+        match self.edge_realization.lock() {
+            Ok(edges) => sweep.collect_arbitrages(&edges),
+            Err(_) => return,
+        }
+        if let Ok(drift) = self.execution_drift.lock() {
+            sweep.attach_drift(&drift);
+        }
+        if let Ok(mut log) = self.postmortems.lock() {
+            log.review(sweep, Utc::now());
+        }
+    }
+
+    /// Settle the fills an outcome token transfer of the funder wallet belongs to
+    fn on_token_transfer(&self, transfer: TokenTransfer) {
+        let Some(Ok(mut settlement)) = self.settlement.as_ref().map(|s| s.lock()) else {
+            return;
+        };
+        debug!(
+            "🔁 Token transfer {:?}: {:.2} of {} ({})",
+            transfer.direction,
+            transfer.shares,
+            transfer.asset_id,
+            transfer.tx_hash.as_deref().unwrap_or("-")
+        );
+        for trade_id in settlement.observe(transfer, Utc::now()) {
+            debug!("✅ Settlement confirmed on-chain for {}", trade_id);
+        }
+    }
+
+    /// Alert on the fills whose tokens still haven't moved after SETTLEMENT_TIMEOUT_SECS
+    fn sweep_settlements(&self) {
+        let Some(Ok(mut settlement)) = self.settlement.as_ref().map(|s| s.lock()) else {
+            return;
+        };
+        for mismatch in settlement.sweep(Utc::now()) {
+            let expected = &mismatch.expected;
+            error!(
+                "🚨 ALERT: Settlement mismatch ({:?}) for {} in {}: {:.2}/{:.2} shares of {} moved {:?}",
+                mismatch.kind,
+                expected.trade_id,
+                expected.market_id,
+                expected.settled_shares,
+                expected.shares,
+                expected.asset_id,
+                expected.direction
+            );
+        }
+    }
+
+    /// Close `position` in its PnL book: the shares it held (the recorded fills, falling back
+    /// to the risk book's entry)
+    fn close_tracked(&self, position: &risk::Position) -> f64 {
+        let recorded = self
+            .pnl_book(&position.trade_id)
+            .lock()
+            .ok()
+            .and_then(|mut tracker| {
+                let shares = tracker
+                    .positions
+                    .get(&position.trade_id)
+                    .map(|p| p.shares());
+                tracker.close_position(&position.trade_id);
+                shares
+            });
+        recorded.unwrap_or_else(|| {
+            if position.entry_price > 0.0 {
+                position.size_usd / position.entry_price
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Expect the tokens of a filled exit order (`side` is the position's; the exit buys
+    /// `shares` of the opposite side) to arrive
+    fn expect_exit_settlement(&self, market: &MarketData, trade_id: &str, side: &str, shares: f64) {
+        if self.executor.is_paper(trade_id) {
+            return;
+        }
+        let opposite_side = if side == "YES" { "NO" } else { "YES" };
+        if let Some(Ok(mut settlement)) = self.settlement.as_ref().map(|s| s.lock()) {
+            settlement.expect_shares(market, trade_id, opposite_side, shares, Utc::now());
+        }
+    }
+
+    /// Why the asset's WS quote can't be trusted right now, None if it can (see `QuoteFreshness`)
+    fn quote_staleness(&self, asset_id: &str) -> Option<Staleness> {
+        let feed = self
+            .ws_client
+            .as_ref()
+            .map(|ws| ws.feed_health())
+            .unwrap_or_default();
+        self.quote_freshness
+            .staleness(asset_id, feed, clock::now_ns())
+    }
+
+    /// Hold an entry priced off a quote the feed may have missed changes to: the WS dropped or
+    /// went quiet past QUOTE_MAX_FEED_SILENCE_MS, or the asset's updates skipped a sequence
+    /// number. With QUOTE_REVERIFY the stale books are re-read over REST instead, and the entry
+    /// goes ahead if their best asks are still at or below the prices of the signal (`legs`:
+    /// side, price)
+    async fn quotes_fresh(&mut self, market: &MarketData, legs: &[(&str, f64)]) -> bool {
+        let now_ns = clock::now_ns();
+        for &(side, price) in legs {
+            let asset_id = match side {
+                "YES" => market.asset_ids.get(1),
+                _ => market.asset_ids.first(),
+            };
+            let Some(asset_id) = asset_id else {
+                continue;
+            };
+            let Some(staleness) = self.quote_staleness(asset_id) else {
+                continue;
+            };
+            let age_ms = self
+                .quote_freshness
+                .age_ms(asset_id, now_ns)
+                .unwrap_or_default();
+            if !self.config.agent.quote_reverify {
+                warn!(
+                    "🕰️ Stale {} quote on {} ({}, {}ms old) - entry refused",
+                    side, market.question, staleness, age_ms
+                );
+                return false;
+            }
+
+            let ask = match self.market_interface.get_orderbook(asset_id).await {
+                Ok(book) => book.best_ask(),
+                Err(e) => {
                     warn!(
-                        "⚠️ Balance (${:.2}) < Target Size (${:.2}). Capping to balance.",
-                        balance, final_size
+                        "🕰️ Stale {} quote on {} ({}, {}ms old), re-read failed: {} - entry refused",
+                        side, market.question, staleness, age_ms, e
                     );
-                    final_size = balance;
+                    return false;
+                }
+            };
+            self.quote_freshness.refreshed(asset_id, clock::now_ns());
+            let Some(ask) = ask else {
+                warn!(
+                    "🕰️ Stale {} quote on {}: no asks left - entry refused",
+                    side, market.question
+                );
+                return false;
+            };
+            self.registry.update(&market.id, |market| {
+                if side == "YES" {
+                    market.yes_price = ask;
+                } else {
+                    market.no_price = ask;
                 }
+            });
+            if ask > price + f64::EPSILON {
+                warn!(
+                    "🕰️ Stale {} quote on {} ({}, {}ms old): ask now {:.4} vs {:.4} signalled - entry refused",
+                    side, market.question, staleness, age_ms, ask, price
+                );
+                return false;
+            }
+            debug!(
+                "🕰️ Re-verified {} quote on {} ({}, {}ms old): ask {:.4}",
+                side, market.question, staleness, age_ms, ask
+            );
+        }
+        true
+    }
 
-                const MIN_ARB_NOTIONAL_USD: f64 = 1.0;
-                if final_size < MIN_ARB_NOTIONAL_USD {
-                    return Ok(());
+    /// False while failover fences order entry (always true without failover)
+    fn failover_allows_trading(&self) -> bool {
+        self.failover.as_ref().is_none_or(|f| f.trading_allowed())
+    }
+
+    /// Best ask of a market side as the CLOB WS last saw it (None without the WS)
+    fn live_ask(&self, market: &MarketData, side: &str) -> Option<f64> {
+        let asset_id = match side {
+            "YES" => market.asset_ids.get(1),
+            _ => market.asset_ids.first(),
+        }?;
+        self.ws_client.as_ref()?.best_ask(asset_id)
+    }
+
+    /// When the signal being executed started: the socket read of the book update it came from,
+    /// now for timer re-evaluations and new markets
+    fn signal_ns(&self) -> u64 {
+        self.evaluation_received_ns.unwrap_or_else(clock::now_ns)
+    }
+
+    /// Record how far a side's best ask moved between the signal and the order post, each
+    /// given as (best ask, `clock::now_ns` when read). The post ask is read before the order
+    /// goes out: read after, it would count the levels our own order took
+    fn record_execution_drift(
+        &self,
+        market: &MarketData,
+        trade_id: &str,
+        side: &str,
+        (signal_ask, signal_ns): (Option<f64>, u64),
+        (post_ask, post_ns): (Option<f64>, u64),
+    ) {
+        let (Some(signal_ask), Some(post_ask)) = (signal_ask, post_ask) else {
+            return;
+        };
+        let latency_us = post_ns.saturating_sub(signal_ns) / 1000;
+        let sample = DriftSample {
+            trade_id: trade_id.to_string(),
+            market_id: market.id.to_string(),
+            side: side.to_string(),
+            signal_at: Utc::now()
+                - chrono::Duration::microseconds((clock::elapsed_ns(signal_ns) / 1000) as i64),
+            signal_ask,
+            post_ask,
+            latency_us,
+        };
+        if let Ok(mut tracker) = self.execution_drift.lock() {
+            tracker.record(sample);
+        }
+    }
+
+    /// Shadow a live order with its paper twin for the drift report: the same order goes to
+    /// the paper engine now, and its fill is checked as the live one's is (see `track_fill`)
+    fn record_shadow_order(
+        &self,
+        market: &MarketData,
+        trade_id: &str,
+        side: &str,
+        limit_price: f64,
+        size_usd: f64,
+    ) {
+        // Orders after a downgrade to paper trading aren't live
+        if self.config.agent.paper_trading {
+            return;
+        }
+        let (Some(drift), Some(paper)) = (self.drift.clone(), self.paper_twin.clone()) else {
+            return;
+        };
+        if let Ok(mut drift) = drift.lock() {
+            drift.record_order(
+                trade_id,
+                &market.id,
+                side,
+                limit_price,
+                size_usd,
+                Utc::now(),
+            );
+        }
+        let market = market.clone();
+        let trade_id = trade_id.to_string();
+        let side = side.to_string();
+
+        tokio::spawn(async move {
+            let order_id = match paper
+                .place_order(
+                    &market.id,
+                    &side,
+                    UsdAmount::new(size_usd),
+                    Price::new(limit_price),
+                    OrderType::GTC,
+                )
+                .await
+            {
+                Ok(order_id) => order_id,
+                Err(e) => {
+                    debug!("Paper twin of {} not placed: {:#}", trade_id, e);
+                    return;
                 }
+            };
+            tokio::time::sleep(FILL_CHECK_DELAY).await;
+            let observation =
+                observe_fill(paper.as_ref(), &market, &side, &order_id, limit_price).await;
+            if let Ok(mut drift) = drift.lock() {
+                let filled = if observation.filled { 1.0 } else { 0.0 };
+                drift.record_paper_fill(&trade_id, filled, observation.slippage_bps);
+            }
+        });
+    }
 
-                if self
-                    .risk_manager
-                    .validate_entry(&market.id, final_size, 1.0)
-                {
-                    let trade_id = format!("arb_{}_{}", market.id, Utc::now().timestamp_millis());
-                    match self
-                        .executor
-                        .execute_arbitrage_bundle(
-                            market,
-                            yes_price,
-                            no_price,
-                            final_size,
-                            &trade_id,
-                            &mut self.risk_manager,
-                        )
-                        .await
-                    {
-                        Ok(tx_hash) => info!("✅ Arbitrage Executed! TX/Order: {}", tx_hash),
-                        Err(e) => error!("❌ Execution failed: {}", e),
-                    }
-
-                    let position = Position {
-                        id: trade_id.clone(),
-                        market_id: market.id.clone(),
-                        market_question: market.question.clone(),
-                        side: "BOTH".to_string(),
-                        size: final_size,
-                        entry_price: yes_price + no_price,
-                        current_price: yes_price + no_price,
-                        entry_time: Utc::now(),
-                    };
-                    if let Ok(mut tracker) = self.pnl_tracker.lock() {
-                        tracker.add_position(position);
-                    }
+    /// Check an order's fill quality in the background and feed it to the edge tuner, the
+    /// anomaly guard, the drift tracker, the settlement reconciler and the toxicity score
+    fn track_fill(
+        &self,
+        market: &MarketData,
+        trade_id: &str,
+        side: &str,
+        order_id: String,
+        limit_price: f64,
+        size_usd: f64,
+    ) {
+        // Paper fills say nothing about live execution
+        if self.executor.is_paper(trade_id) {
+            return;
+        }
+        let tuner = self.edge_tuner.clone();
+        let guard = self.anomaly_guard.clone();
+        let drift = self.drift.clone();
+        let settlement = self.settlement.clone();
+        let toxicity = self.toxicity.clone();
+        if tuner.is_none()
+            && guard.is_none()
+            && drift.is_none()
+            && settlement.is_none()
+            && toxicity.is_none()
+        {
+            return;
+        }
+        let market_interface = self.market_interface.clone();
+        let market = market.clone();
+        let trade_id = trade_id.to_string();
+        let side = side.to_string();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(FILL_CHECK_DELAY).await;
+            let observation =
+                observe_fill(market_interface.as_ref(), &market, &side, &order_id, limit_price)
+                    .await;
+            if let Some(Ok(mut tuner)) = tuner.as_ref().map(|t| t.lock()) {
+                tuner.record(&market, observation);
+            }
+            if let Some(guard) = guard {
+                guard.record_slippage(&market.id, observation.slippage_bps);
+            }
+            if let Some(Ok(mut drift)) = drift.as_ref().map(|d| d.lock()) {
+                let filled = if observation.filled { 1.0 } else { 0.0 };
+                drift.record_fill(&trade_id, filled, observation.slippage_bps);
+            }
+            if let Some(Ok(mut settlement)) = settlement.as_ref().map(|s| s.lock()) {
+                if observation.filled {
+                    let now = Utc::now();
+                    settlement.expect_fill(&market, &trade_id, &side, size_usd, limit_price, now);
                 }
             }
-            _ => {
-                if rand::random::<f64>() < 0.001 {
-                    debug!(
-                        "🔍 Checked {} - No predictive/expiration signal",
-                        market.question
+            if let Some(Ok(mut toxicity)) = toxicity.as_ref().map(|t| t.lock()) {
+                if toxicity.record_outcome(&trade_id, observation.filled) {
+                    info!(
+                        "☣️ {} on {} missed with its quote pulled, last in queue or raced",
+                        trade_id, market.question
                     );
                 }
             }
+        });
+    }
+
+    /// Re-fetch every pinned market (resolving slugs the first time) off the loop, one poll at
+    /// a time; they come back through `pinned_rx`
+    fn poll_pinned_markets(&self) {
+        let pins = match self.pins.lock() {
+            Ok(pins) => pins.pins(),
+            Err(_) => return,
+        };
+        if pins.is_empty() {
+            return;
+        }
+        if self.pins_polling.swap(true, Ordering::SeqCst) {
+            debug!("Pinned market fetch still running, skipping");
+            return;
+        }
+        let market_interface = self.market_interface.clone();
+        let (fetched_tx, polling) = (self.pinned_tx.clone(), self.pins_polling.clone());
+        tokio::spawn(async move {
+            let mut fetched = Vec::new();
+            for pin in pins {
+                let market = match (&pin.market_id, PinKey::parse(&pin.key)) {
+                    (Some(market_id), _) => market_interface.get_market_details(market_id).await,
+                    (None, Some(key)) => key.fetch(market_interface.as_ref()).await,
+                    (None, None) => continue,
+                };
+                match market {
+                    Ok(market) => fetched.push((pin.key, market)),
+                    Err(e) => debug!("Pinned market {} not fetched: {}", pin.key, e),
+                }
+            }
+            let _ = fetched_tx.send(fetched);
+            polling.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Run the pinned markets a poll fetched through the pipeline; notifying pins raise an
+    /// alert when the YES price moves `PIN_NOTIFY_MOVE`
+    async fn on_pinned_markets(&mut self, fetched: Vec<(String, MarketData)>) {
+        for (key, market) in fetched {
+            let first = self
+                .pins
+                .lock()
+                .is_ok_and(|mut pins| pins.resolve(&key, &market));
+            if first {
+                info!("📌 Pinned {} ({})", market.question, market.id);
+            }
+            self.advance(&market.id, MarketState::MetadataLoaded);
+            if let Err(e) = self.process_single_market(&market).await {
+                error!(
+                    "❌ Error processing pinned market {}: {}",
+                    market.question, e
+                );
+            }
+
+            // The live (WS) price when there is one
+            let yes_price = self
+                .registry
+                .get(&market.id)
+                .map_or(market.yes_price, |m| m.yes_price);
+            let moved = self.pins.lock().ok().and_then(|mut pins| {
+                pins.price_move(&market.id, yes_price, self.config.agent.pin_notify_move)
+            });
+            if let Some(previous) = moved {
+                error!(
+                    "🚨 ALERT: Pinned {} moved: YES {:.3} -> {:.3}",
+                    market.question, previous, yes_price
+                );
+            }
         }
+    }
 
-        Ok(())
+    /// Move markets entering the expiration window onto the watchlist and make sure their
+    /// books are subscribed
+    fn promote_expiring_markets(&mut self) {
+        let mut markets = self.registry.markets();
+        markets.retain(|m| !self.market_state(&m.id).is_some_and(|s| s.is_closed()));
+        let now = Utc::now();
+        let promoted: Vec<MarketData> = match self.watchlist.lock() {
+            Ok(mut watchlist) => watchlist
+                .refresh(&markets, now)
+                .into_iter()
+                .cloned()
+                .collect(),
+            Err(_) => return,
+        };
+        for market in promoted {
+            let remaining = market.end_time.map_or(0, |end| (end - now).num_seconds());
+            info!(
+                "⏰ Watchlisted {} ({}s to expiry)",
+                market.question, remaining
+            );
+            self.subscribe_assets(&market.id, &market.asset_ids);
+            self.jobs
+                .push(Job::Reevaluate(market.id.into_inner(), None));
+        }
     }
 
-    async fn execute_snipe_signal(
-        &mut self,
-        market: &MarketData,
-        side: &str,
-        price: f64,
-        size_usd: f64,
-        trade_prefix: &str,
-        confidence: f64,
-    ) -> Result<()> {
-        if self.last_balance_update.elapsed() > Duration::from_secs(10) {
-            if let Ok(bal) = self.market_interface.get_balance().await {
-                self.cached_balance = bal;
-                self.last_balance_update = std::time::Instant::now();
-            }
+    /// Once the anomaly guard trips, stop sending real orders for the rest of the run
+    fn enforce_anomaly_guard(&mut self) {
+        let Some(reason) = self.anomaly_guard.as_ref().and_then(|g| g.take_trip()) else {
+            return;
+        };
+        error!(
+            "🚨 LIVE TRADING DISABLED ({}) - paper trading until restart",
+            reason
+        );
+        self.market_interface.downgrade_to_paper();
+        self.executor.downgrade_to_paper();
+        if let Some(executor) = &self.cross_venue_executor {
+            executor.kalshi().downgrade_to_paper();
+        }
+        self.config.agent.paper_trading = true;
+        if self.config.anomaly.halt {
+            self.risk_halt = Some(reason);
         }
+    }
 
-        let balance = self.cached_balance;
-        let final_size = size_usd.min(balance);
-        if final_size < 1.0 {
-            warn!("❌ Insufficient balance for snipe (${:.2})", balance);
-            return Ok(());
+    /// Ask the LLM classifier about a market the rules couldn't place (once per market per run)
+    fn classify_with_llm(&mut self, market: &MarketData) {
+        let Some(classifier) = self.llm_classifier.clone() else {
+            return;
+        };
+        if !self.classify_requested.insert(market.id.to_string()) {
+            return;
         }
+        let registry = self.registry.clone();
+        let market_id = market.id.clone();
+        let question = market.question.clone();
 
-        if self
-            .risk_manager
-            .validate_entry(&market.id, final_size, confidence)
-        {
-            let trade_id = format!(
-                "{}_{}_{}",
-                trade_prefix,
-                market.id,
-                Utc::now().timestamp_millis()
-            );
-            if let Err(e) = self
-                .executor
-                .execute_snipe(
-                    market,
-                    side,
-                    price,
-                    final_size,
-                    &trade_id,
-                    &mut self.risk_manager,
-                )
-                .await
-            {
-                error!("❌ Snipe execution failed: {}", e);
+        tokio::spawn(async move {
+            match classifier.classify(&market_id, &question).await {
+                Ok(category) => {
+                    debug!("🏷️ {} -> {}", question, category.name());
+                    registry.update(&market_id, |m| m.category = Some(category));
+                }
+                Err(e) => debug!("⚠️ LLM classification failed for {}: {:#}", market_id, e),
             }
-        }
-        Ok(())
+        });
     }
 
     /// Monitor and log the top 5 raw opportunities (ignoring fees/filters) to gauge market saturation
     async fn log_top_opportunities(&self) {
         let mut spreads: Vec<(String, f64, f64, f64)> = Vec::new(); // (Question, Yes, No, Cost)
 
-        for market in self.active_markets.values() {
-            if market.yes_price > 0.0 && market.no_price > 0.0 {
+        for market in self.registry.markets() {
+            if market.quote_quality().is_complete() {
                 let cost = market.yes_price + market.no_price;
                 if cost < 1.02 {
                     // Only care about close calls
@@ -854,7 +6993,7 @@ impl Sniper {
 
     /// Manage active positions (Stop Loss, Take Profit)
     async fn manage_positions(&mut self, current_markets: &[MarketData]) -> Result<()> {
-        let positions = self.risk_manager.get_positions();
+        let positions = self.all_positions();
 
         if positions.is_empty() {
             return Ok(());
@@ -865,26 +7004,66 @@ impl Sniper {
         for position in positions {
             // Find current market data
             if let Some(market) = current_markets.iter().find(|m| m.id == position.market_id) {
-                let current_price = if position.side == "YES" {
-                    market.yes_price
-                } else {
-                    market.no_price
+                // Stop loss already being worked out through the ladder
+                if self.liquidations.contains_key(market.id.as_str()) {
+                    self.step_liquidation(market, &position.trade_id).await;
+                    continue;
+                }
+
+                // A missing side reads as 0 and would fire the stop loss: wait for a quote
+                let Some(current_price) = market.ask(&position.side) else {
+                    debug!(
+                        "📭 No {} quote for {} - holding position checks",
+                        position.side, market.question
+                    );
+                    continue;
                 };
 
                 // Check Stop Loss via RiskManager
-                if self.risk_manager.check_stop_loss(&position, current_price) {
+                let stop_hit = self
+                    .risk_book(&position.trade_id)
+                    .check_stop_loss(&position, current_price);
+                if stop_hit && self.config.liquidation.enabled {
+                    info!(
+                        "🪜 Starting liquidation ladder for {} (${:.2} {})",
+                        market.question, position.size_usd, position.side
+                    );
+                    let ladder = LiquidationLadder::start(
+                        &self.config.liquidation,
+                        market,
+                        &position.trade_id,
+                        &position.side,
+                        position.size_usd,
+                        position.entry_price,
+                        Utc::now(),
+                    );
+                    self.liquidations.insert(market.id.to_string(), ladder);
+                    self.step_liquidation(market, &position.trade_id).await;
+                    continue;
+                } else if stop_hit {
                     info!("🛑 Executing STOP LOSS for {}", market.question);
+                    let risk_book = if self.executor.is_paper(&position.trade_id) {
+                        &mut self.paper_risk
+                    } else {
+                        &mut self.risk_manager
+                    };
                     if let Err(e) = self
                         .executor
-                        .close_position(market, &position.side, &mut self.risk_manager)
+                        .close_position(market, &position.side, risk_book)
                         .await
                     {
                         error!("❌ Failed to close position for {}: {}", market.question, e);
                     } else {
                         // Success: Update PnL Tracker
-                        if let Ok(mut tracker) = self.pnl_tracker.lock() {
-                            tracker.close_position(&position.trade_id);
-                        }
+                        let shares = self.close_tracked(&position);
+                        self.expect_exit_settlement(
+                            market,
+                            &position.trade_id,
+                            &position.side,
+                            shares,
+                        );
+                        self.mark_position_closed(&market.id);
+                        self.close_hedge(&market.id, None).await;
                     }
                 }
 
@@ -894,9 +7073,14 @@ impl Sniper {
                         "💰 Executing AUTO-SELL (Take Profit) for {} at {:.4}",
                         market.question, current_price
                     );
+                    let risk_book = if self.executor.is_paper(&position.trade_id) {
+                        &mut self.paper_risk
+                    } else {
+                        &mut self.risk_manager
+                    };
                     if let Err(e) = self
                         .executor
-                        .close_position(market, &position.side, &mut self.risk_manager)
+                        .close_position(market, &position.side, risk_book)
                         .await
                     {
                         error!(
@@ -905,22 +7089,663 @@ impl Sniper {
                         );
                     } else {
                         // Success: Update PnL Tracker
-                        if let Ok(mut tracker) = self.pnl_tracker.lock() {
-                            tracker.close_position(&position.trade_id);
-                        }
+                        let shares = self.close_tracked(&position);
+                        self.expect_exit_settlement(
+                            market,
+                            &position.trade_id,
+                            &position.side,
+                            shares,
+                        );
+                        self.mark_position_closed(&market.id);
+                        self.close_hedge(&market.id, None).await;
                     }
                 }
             }
         }
 
+        self.rebalance_hedges(current_markets).await;
         Ok(())
     }
 
+    /// Gas price now: the wallet's RPC, else (or when it fails) GAS_PRICE_GWEI
+    async fn gas_price_gwei(&self) -> f64 {
+        match &self.redemption_manager {
+            Some(rm) => {
+                let rpc = self
+                    .breakers
+                    .rpc(self.config.polygon_ws_rpc.as_deref().unwrap_or_default());
+                rpc.call(rm.gas_price_gwei())
+                    .await
+                    .unwrap_or(self.config.gas.fallback_gas_price_gwei)
+            }
+            None => self.config.gas.fallback_gas_price_gwei,
+        }
+    }
+
+    fn gas_usd(&self, units: u64, price_gwei: f64) -> f64 {
+        GasCost {
+            units,
+            price_gwei,
+            native_token_usd: self.config.gas.native_token_usd,
+        }
+        .usd()
+    }
+
+    /// Take each open arbitrage pair's best exit: merge it back into collateral now, sell one
+    /// leg into a bid richer than the predictive model's fair value, or hold it to resolution.
+    /// A group whose merge is on its way waits for the receipt
+    async fn manage_arb_exits(&mut self) {
+        self.settle_pending_merges().await;
+        // Group id -> (YES leg, NO leg) of the entries still holding both
+        let mut legs: HashMap<String, Vec<Position>> = HashMap::new();
+        for book in self.pnl_books() {
+            let Ok(tracker) = book.lock() else {
+                return;
+            };
+            for position in tracker.positions.values() {
+                if let Some(group_id) = &position.group_id {
+                    legs.entry(group_id.clone())
+                        .or_default()
+                        .push(position.clone());
+                }
+            }
+        }
+        let groups: Vec<(String, Position, Position)> = legs
+            .into_iter()
+            .filter(|(group_id, _)| !self.pending_merges.contains_key(group_id))
+            .filter_map(|(group_id, legs)| {
+                let yes = legs.iter().find(|p| p.side == "YES")?.clone();
+                let no = legs.iter().find(|p| p.side == "NO")?.clone();
+                Some((group_id, yes, no))
+            })
+            .collect();
+        if groups.is_empty() {
+            return;
+        }
+
+        // Paper merges are only booked; live ones need the wallet's CTF client
+        let simulated = self.config.agent.paper_trading || self.config.agent.simulation_mode;
+        let can_merge = simulated || self.redemption_manager.is_some();
+        let price_gwei = self.gas_price_gwei().await;
+        let gas_usd = |units: u64| self.gas_usd(units, price_gwei);
+        let merge_gas_usd = gas_usd(self.config.arb_exit.merge_gas_units);
+        let redeem_gas_usd = gas_usd(self.config.gas.redeem_gas_units);
+
+        let now = Utc::now();
+        for (group_id, yes, no) in groups {
+            let Some(market) = self.registry.get(&yes.market_id) else {
+                continue;
+            };
+            if self.market_state(&market.id).is_some_and(|s| s.is_closed()) {
+                continue;
+            }
+            let (Some(yes_ask), Some(no_ask)) = (market.ask("YES"), market.ask("NO")) else {
+                continue;
+            };
+            // Leg sizes are notional: pairs are the shares both legs hold
+            let shares = |leg: &Position| {
+                if leg.entry_price > 0.0 {
+                    leg.size / leg.entry_price
+                } else {
+                    0.0
+                }
+            };
+            let pairs = shares(&yes).min(shares(&no));
+            let mut quote = ArbPairQuote::from_asks(pairs, yes_ask, no_ask);
+            let (fair_yes, _) = self
+                .predictive_strategy
+                .fair_yes_with_inputs(&market, now, self.dislocation_strategy.annual_volatility())
+                .await;
+            if let Some(fair_yes) = fair_yes {
+                quote.fair_yes = fair_yes;
+            }
+            // A paper-only group's merge is only booked, like a paper run's
+            let paper = self.executor.is_paper(&group_id);
+            quote.merge_gas_usd = (can_merge || paper).then_some(merge_gas_usd);
+            quote.redeem_gas_usd = redeem_gas_usd;
+
+            let (exit, values) = arb_exit::choose_exit(&self.config.arb_exit, &quote);
+            debug!(
+                "🔀 Arb exit for {}: {:?} (hold ${:.2}, merge {:?}, sell YES ${:.2}, sell NO ${:.2})",
+                group_id, exit, values.hold, values.merge, values.sell_yes, values.sell_no
+            );
+            match exit {
+                ArbExit::Hold => {}
+                ArbExit::Merge => {
+                    if !simulated && !paper {
+                        let Some(rm) = &self.redemption_manager else {
+                            continue;
+                        };
+                        // The condition's own collateral comes back (the adapter's for neg-risk)
+                        let collateral = self.collateral_of(&market.id);
+                        match rm.merge_positions(&market.id, pairs, &collateral).await {
+                            Ok(tx_hash) => {
+                                info!(
+                                    "🔗 Merging {:.2} pairs of {} early into {:?}: {}",
+                                    pairs, market.question, collateral, tx_hash
+                                );
+                                self.pending_merges.insert(
+                                    group_id,
+                                    PendingMerge {
+                                        market_id: market.id.to_string(),
+                                        tx_hash,
+                                        pairs,
+                                    },
+                                );
+                            }
+                            Err(e) => error!("❌ Merge failed for {}: {}", market.question, e),
+                        }
+                        continue;
+                    }
+                    self.book_merge(&market, &group_id, pairs);
+                }
+                ArbExit::SellLeg { side, price } => {
+                    let leg = if side == "YES" { &yes } else { &no };
+                    // Only the matched pairs were valued: that many shares of the leg are sold
+                    let sold_usd = pairs * leg.entry_price;
+                    match self
+                        .executor
+                        .sell_leg(&market, &side, Price::new(price), pairs, &leg.id)
+                        .await
+                    {
+                        Ok(order_id) => {
+                            let pnl = self.pnl_book(&leg.id).lock().ok().and_then(|mut tracker| {
+                                tracker.reduce_position(
+                                    &leg.id,
+                                    UsdAmount::new(sold_usd),
+                                    Price::new(price),
+                                )
+                            });
+                            self.risk_book(&leg.id).reduce_position(
+                                &market.id,
+                                UsdAmount::new(sold_usd),
+                                Price::new(price),
+                            );
+                            self.expect_exit_settlement(&market, &leg.id, &side, pairs);
+                            info!(
+                                "💰 Sold {:.2} {} shares of {} into the move @ {:.4} (order {}, PnL ${:.2})",
+                                pairs,
+                                side,
+                                market.question,
+                                price,
+                                order_id,
+                                pnl.unwrap_or_default()
+                            );
+                        }
+                        Err(e) => error!("❌ Leg sale failed for {}: {}", market.question, e),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Book the early merges whose receipt is in. A reverted or dropped one is forgotten, so the
+    /// group's exit is chosen again
+    async fn settle_pending_merges(&mut self) {
+        let Some(rm) = &self.redemption_manager else {
+            return;
+        };
+        let mut settled = Vec::new();
+        for (group_id, merge) in &self.pending_merges {
+            match rm.transaction_outcome(&merge.tx_hash).await {
+                Ok(TxOutcome::Pending) => {}
+                Ok(outcome) => settled.push((group_id.clone(), outcome)),
+                Err(e) => debug!("Merge receipt of {} not read: {}", group_id, e),
+            }
+        }
+        for (group_id, outcome) in settled {
+            let Some(merge) = self.pending_merges.remove(&group_id) else {
+                continue;
+            };
+            if outcome != TxOutcome::Succeeded {
+                warn!("⚠️ Merge of {} {:?}: {}", group_id, outcome, merge.tx_hash);
+                continue;
+            }
+            match self.registry.get(&merge.market_id) {
+                Some(market) => self.book_merge(&market, &group_id, merge.pairs),
+                None => warn!(
+                    "⚠️ Merge of {} confirmed for unknown market {}",
+                    group_id, merge.market_id
+                ),
+            }
+        }
+    }
+
+    /// Book a group's merge of `pairs`: collateral back at 1 per pair
+    fn book_merge(&mut self, market: &MarketData, group_id: &str, pairs: f64) {
+        let now = Utc::now();
+        let simulated = self.config.agent.paper_trading || self.config.agent.simulation_mode;
+        if !simulated && !self.executor.is_paper(group_id) {
+            if let Some(Ok(mut settlement)) = self.settlement.as_ref().map(|s| s.lock()) {
+                settlement.expect_merge(market, group_id, pairs, now);
+            }
+        }
+        let pnl = self
+            .pnl_book(group_id)
+            .lock()
+            .ok()
+            .and_then(|mut tracker| tracker.merge_group(group_id));
+        info!(
+            "🔗 Merged {:.2} pairs of {} early (PnL ${:.2})",
+            pairs,
+            market.question,
+            pnl.unwrap_or_default()
+        );
+        if let Ok(mut tracker) = self.edge_realization.lock() {
+            tracker.settle_market(&market.id, 0.5, 0.5, now);
+        }
+        self.release_arb_group(&market.id, group_id);
+    }
+
+    /// Drop the market's risk position once none of an arbitrage group's legs is left open
+    fn release_arb_group(&mut self, market_id: &str, group_id: &str) {
+        let open = self
+            .pnl_book(group_id)
+            .lock()
+            .map(|tracker| !tracker.group_legs(group_id).is_empty())
+            .unwrap_or(true);
+        if !open {
+            self.risk_book(group_id).remove_position(market_id);
+            self.mark_position_closed(market_id);
+        }
+    }
+
+    /// Hedge the largest predictive / expiration positions while their unhedged notional is
+    /// over the limit. The hedge is journaled under the origin's trade id
+    async fn rebalance_hedges(&mut self, current_markets: &[MarketData]) {
+        let Some(hedger) = self.hedger.clone().filter(|_| self.config.hedging.enabled) else {
+            return;
+        };
+        let positions = self.risk_manager.get_positions();
+        let orders = match hedger.lock() {
+            Ok(mut hedger) => hedger.plan(&positions, |market_id| {
+                current_markets
+                    .iter()
+                    .find(|m| m.id == market_id)
+                    .cloned()
+                    .or_else(|| self.registry.get(market_id))
+            }),
+            Err(_) => return,
+        };
+        if orders.is_empty() {
+            return;
+        }
+
+        self.refresh_balance().await;
+        for order in orders {
+            if order.size_usd > self.free_collateral() {
+                warn!(
+                    "❌ Insufficient balance to hedge {} (${:.2} needed)",
+                    order.origin.market_id, order.size_usd
+                );
+                break;
+            }
+            info!(
+                "🛡️ Hedging {} {} (${:.2}) with {} in {} @ {:.3}",
+                order.origin.side,
+                order.origin.market_id,
+                order.origin.size_usd,
+                order.side,
+                order.market.id,
+                order.price
+            );
+            let trade_id = hedge_trade_id(&order.origin.trade_id);
+            if let Err(e) = self
+                .executor
+                .execute_hedge(
+                    &order.market,
+                    &order.side,
+                    Price::new(order.price),
+                    UsdAmount::new(order.size_usd),
+                    &trade_id,
+                )
+                .await
+            {
+                error!("❌ Hedge failed for {}: {}", order.origin.market_id, e);
+                continue;
+            }
+            if !self.executor.is_paper(&trade_id) {
+                self.cached_balance -= order.size_usd;
+                self.spend_collateral(order.size_usd);
+            }
+            if let Ok(mut hedger) = hedger.lock() {
+                let hedge = hedger.record(&order, Utc::now());
+                self.risk_book(&trade_id).add_hedge(hedge.risk_position());
+            }
+            self.save_hedge_book();
+            if let Ok(mut tracker) = self.pnl_book(&trade_id).lock() {
+                tracker.add_position(Position {
+                    id: trade_id,
+                    market_id: order.market.id.to_string(),
+                    market_question: order.market.question.clone(),
+                    side: order.side.clone(),
+                    size: order.size_usd,
+                    entry_price: order.price,
+                    current_price: order.price,
+                    entry_time: Utc::now(),
+                    config_hash: None,
+                    group_id: None,
+                });
+            }
+        }
+    }
+
+    /// Hedge an expiration snipe once its order has filled. `filled` when it is known to have
+    /// (a market order: FOK, placed is filled) and for paper orders, which fill when placed;
+    /// otherwise its status is read off the loop and comes back through `snipe_fill_rx`
+    async fn hedge_when_filled(&mut self, snipe: PendingSnipe, filled: bool) {
+        if self.hedger.is_none() {
+            return;
+        }
+        if filled || self.executor.is_paper(&snipe.trade_id) {
+            self.on_snipe_fill(snipe, OrderStatus::Matched, 1.0).await;
+            return;
+        }
+        let client = self.executor.client_for_order(&snipe.order_id);
+        let tx = self.snipe_fill_tx.clone();
+        tokio::spawn(async move {
+            let (status, filled) =
+                order_fill(client.as_ref(), &snipe.order_id, &snipe.market_id).await;
+            let _ = tx.send((snipe, status, filled));
+        });
+    }
+
+    /// Act on the status of a snipe's order: hedged when matched, a partly filled one at its
+    /// filled size. A killed or cancelled snipe is never hedged; one still resting waits in the
+    /// Hedger and is checked again (with the PnL update) until its market ends, or for an hour
+    /// when its end isn't known
+    async fn on_snipe_fill(&mut self, snipe: PendingSnipe, status: OrderStatus, filled: f64) {
+        let Some(hedger) = self.hedger.clone() else {
+            return;
+        };
+        match status {
+            OrderStatus::Matched => self.hedge_expiration_snipe(&snipe).await,
+            OrderStatus::Cancelled => {
+                debug!("Snipe {} cancelled unfilled, not hedged", snipe.trade_id)
+            }
+            // Hedged at the size filled so far: the Hedger holds one hedge per position
+            OrderStatus::PartiallyFilled => {
+                if filled > 0.0 {
+                    let partial = PendingSnipe {
+                        size_usd: snipe.size_usd * filled,
+                        ..snipe
+                    };
+                    self.hedge_expiration_snipe(&partial).await;
+                } else {
+                    if let Ok(mut hedger) = hedger.lock() {
+                        hedger.await_fill(snipe);
+                    }
+                    self.save_hedge_book();
+                }
+            }
+            // Resting or unreadable
+            _ => {
+                if let Ok(mut hedger) = hedger.lock() {
+                    hedger.await_fill(snipe);
+                }
+                self.save_hedge_book();
+            }
+        }
+    }
+
+    /// Re-check the snipes still waiting for their fill
+    async fn hedge_filled_snipes(&mut self) {
+        let pending = match self.hedger.as_ref().map(|h| h.lock()) {
+            Some(Ok(mut hedger)) => hedger.take_pending(Utc::now()),
+            _ => return,
+        };
+        if pending.is_empty() {
+            return;
+        }
+        for snipe in pending {
+            self.hedge_when_filled(snipe, false).await;
+        }
+        // Snipes dropped with their market
+        self.save_hedge_book();
+    }
+
+    /// Buy the opposite outcome of a filled expiration snipe, when the strategy finds it cheap
+    /// enough to keep the pair's expected value. Tracked by the Hedger: unwound or settled
+    /// with the snipe, its PnL charged to the expiration strategy
+    async fn hedge_expiration_snipe(&mut self, snipe: &PendingSnipe) {
+        let Some(hedger) = self.hedger.clone() else {
+            return;
+        };
+        let Some(market) = self.registry.get(&snipe.market_id) else {
+            return;
+        };
+        let Some(hedge) =
+            self.expiration_strategy
+                .hedge_for(&market, &snipe.side, snipe.price, snipe.size_usd)
+        else {
+            return;
+        };
+        // The snipe's own position (closed already: nothing left to hedge)
+        let Some(origin) = self
+            .risk_book(&snipe.trade_id)
+            .get_positions()
+            .into_iter()
+            .find(|p| p.trade_id == snipe.trade_id)
+        else {
+            return;
+        };
+        if hedge.size_usd > self.free_collateral() {
+            warn!(
+                "❌ Insufficient balance to hedge snipe {} (${:.2} needed)",
+                snipe.trade_id, hedge.size_usd
+            );
+            return;
+        }
+
+        let hedge_id = hedge_trade_id(&snipe.trade_id);
+        if let Err(e) = self
+            .executor
+            .execute_hedge(
+                &market,
+                &hedge.side,
+                Price::new(hedge.price),
+                UsdAmount::new(hedge.size_usd),
+                &hedge_id,
+            )
+            .await
+        {
+            error!("❌ Hedge failed for snipe {}: {}", snipe.trade_id, e);
+            return;
+        }
+        info!(
+            "🛡️ Snipe {} hedged with {} @ {:.3} (${:.2}): worst case -${:.2}, EV {:.0} bps",
+            snipe.trade_id,
+            hedge.side,
+            hedge.price,
+            hedge.size_usd,
+            hedge.worst_case_loss_usd,
+            hedge.expected_value_bps
+        );
+        if !self.executor.is_paper(&hedge_id) {
+            self.cached_balance -= hedge.size_usd;
+            self.spend_collateral(hedge.size_usd);
+        }
+        let order = HedgeOrder {
+            origin,
+            market: market.clone(),
+            side: hedge.side.clone(),
+            price: hedge.price,
+            shares: hedge.shares,
+            size_usd: hedge.size_usd,
+            covered_usd: hedge.covered_usd,
+        };
+        if let Ok(mut hedger) = hedger.lock() {
+            let recorded = hedger.record(&order, Utc::now());
+            self.risk_book(&hedge_id)
+                .add_hedge(recorded.risk_position());
+        }
+        self.save_hedge_book();
+        if let Ok(mut tracker) = self.pnl_book(&hedge_id).lock() {
+            tracker.add_position(Position {
+                id: hedge_id,
+                market_id: market.id.to_string(),
+                market_question: market.question.clone(),
+                side: hedge.side,
+                size: hedge.size_usd,
+                entry_price: hedge.price,
+                current_price: hedge.price,
+                entry_time: Utc::now(),
+                config_hash: None,
+                group_id: None,
+            });
+        }
+    }
+
+    /// Unwind the hedge of a closed position and charge its PnL to the originating strategy.
+    /// `redeemed` carries the winning side (when known) of a redeemed origin market: a hedge
+    /// held in that market settles with it, anything else is sold
+    async fn close_hedge(&mut self, market_id: &str, redeemed: Option<Option<&str>>) {
+        let Some(hedger) = self.hedger.clone() else {
+            return;
+        };
+        let Some(hedge) = hedger.lock().ok().and_then(|h| h.get(market_id).cloned()) else {
+            return;
+        };
+
+        let settled = redeemed.filter(|_| hedge.market_id == hedge.origin_market_id);
+        if settled.is_none() {
+            let Some(market) = self.registry.get(&hedge.market_id) else {
+                warn!("⚠️ Can't unwind hedge {}: market unknown", hedge.trade_id());
+                return;
+            };
+            // Closing buys as many shares of the opposite outcome, like any other position
+            let opposite = if hedge.side == "YES" { "NO" } else { "YES" };
+            let Some(price) = market.ask(opposite) else {
+                warn!(
+                    "⚠️ Can't unwind hedge {}: no {} quote",
+                    hedge.trade_id(),
+                    opposite
+                );
+                return;
+            };
+            if let Err(e) = self
+                .executor
+                .execute_hedge(
+                    &market,
+                    opposite,
+                    Price::new(price),
+                    UsdAmount::new(hedge.shares * price),
+                    &format!("close_{}", hedge.trade_id()),
+                )
+                .await
+            {
+                error!("❌ Failed to unwind hedge {}: {}", hedge.trade_id(), e);
+                return;
+            }
+        }
+
+        let realized = self
+            .pnl_book(&hedge.trade_id())
+            .lock()
+            .ok()
+            .and_then(|mut t| match settled.flatten() {
+                Some(winner) => t.settle_position(&hedge.trade_id(), winner),
+                None => t.close_position(&hedge.trade_id()),
+            })
+            .unwrap_or(0.0);
+        if let Ok(mut hedger) = hedger.lock() {
+            hedger.close(market_id, realized);
+        }
+        self.risk_book(&hedge.trade_id())
+            .remove_hedge(&hedge.trade_id());
+        self.save_hedge_book();
+        info!(
+            "🛡️ Hedge {} closed: PnL ${:.2} charged to {}",
+            hedge.trade_id(),
+            realized,
+            hedge.strategy
+        );
+    }
+
+    /// Work a ladder one step (if due) and book the exit once it's complete
+    async fn step_liquidation(&mut self, market: &MarketData, trade_id: &str) {
+        let now = Utc::now();
+        let Some(ladder) = self.liquidations.get_mut(market.id.as_str()) else {
+            return;
+        };
+        if !ladder.is_due(now, ladder.exit_ask(market)) {
+            return;
+        }
+
+        let result = self.executor.step_liquidation(ladder, market, now).await;
+
+        // Book partial exits as they happen so exposure and realized PnL stay current
+        for fill in ladder.take_fills() {
+            self.risk_book(trade_id).reduce_position(
+                &market.id,
+                UsdAmount::new(fill.size_usd),
+                Price::new(fill.exit_price),
+            );
+            if let Ok(mut tracker) = self.pnl_book(trade_id).lock() {
+                tracker.reduce_position(
+                    trade_id,
+                    UsdAmount::new(fill.size_usd),
+                    Price::new(fill.exit_price),
+                );
+            }
+        }
+
+        match result {
+            Ok(true) => {
+                info!("✅ Liquidation ladder finished for {}", market.question);
+                self.liquidations.remove(market.id.as_str());
+                // Rounding dust is written off with the position
+                self.risk_book(trade_id).remove_position(&market.id);
+                if let Ok(mut tracker) = self.pnl_book(trade_id).lock() {
+                    tracker.close_position(trade_id);
+                }
+                self.mark_position_closed(&market.id);
+                self.close_hedge(&market.id, None).await;
+            }
+            Ok(false) => {}
+            Err(e) => error!("❌ Liquidation step failed for {}: {}", market.question, e),
+        }
+    }
+
+    /// Back to Tradable once no position is left in the market
+    fn mark_position_closed(&self, market_id: &str) {
+        let still_held = self
+            .risk_manager
+            .get_positions()
+            .iter()
+            .any(|p| p.market_id == market_id);
+        if !still_held {
+            self.events.publish(|| AgentEvent::PositionClosed {
+                market_id: market_id.to_string(),
+            });
+            let closed = self
+                .lifecycle
+                .lock()
+                .map(|mut l| l.position_closed(market_id))
+                .unwrap_or(false);
+            if closed {
+                self.store(StorageOp::Seen(
+                    market_id.to_string(),
+                    MarketState::Tradable,
+                ));
+            }
+        }
+    }
+
     /// Check if market passes filters
     fn passes_filters(&self, market: &MarketData) -> bool {
-        // GOD MODE: Skip filters for fresh derived markets
-        if market.question.contains("Loading Metadata") {
-            debug!("⚡ Skipping filters for Synthetic Market (God Mode)");
+        // GOD MODE: fresh derived markets have no metadata to filter on yet; the synthetic
+        // policy decides instead
+        if !self.has_metadata(&market.id) {
+            return self.synthetic.can_evaluate(&market.id);
+        }
+
+        // Pinned by the operator with `bypass_filters`: tracked whatever the filters say
+        if self
+            .pins
+            .lock()
+            .is_ok_and(|pins| pins.bypasses_filters(&market.id))
+        {
             return true;
         }
 
@@ -948,6 +7773,66 @@ impl Sniper {
             return false;
         }
 
+        let categories = &self.config.market_filters.categories;
+        if let Some(category) = market.category {
+            if !categories.is_empty() && !categories.contains(&category) {
+                debug!(
+                    "⏭️  Category {} not in MARKET_CATEGORIES for {}",
+                    category.name(),
+                    market.question
+                );
+                return false;
+            }
+        }
+
         true
     }
+
+    /// Large recent trades in the signal's direction raise its confidence, trades against it
+    /// lower it (possibly below what the risk manager accepts)
+    fn whale_adjusted_confidence(&self, market_id: &str, side: &str, confidence: f64) -> f64 {
+        let Some(stats) = self
+            .whales
+            .lock()
+            .ok()
+            .and_then(|w| w.stats(market_id, Utc::now()))
+        else {
+            return confidence;
+        };
+        let adjusted = (confidence + self.config.whales.confidence_weight * stats.bias_for(side))
+            .clamp(0.0, 1.0);
+        debug!(
+            "🐋 {} whale trades (${:.0}) on {}: {} confidence {:.2} -> {:.2}",
+            stats.count, stats.notional, market_id, side, confidence, adjusted
+        );
+        adjusted
+    }
+
+    /// Per-category exposure limit of the book `trade_id` goes in, with open positions
+    /// categorized through the registry
+    fn validate_category_exposure(
+        &self,
+        trade_id: &str,
+        market: &MarketData,
+        size_usd: f64,
+    ) -> bool {
+        let risk_book = if self.executor.is_paper(trade_id) {
+            &self.paper_risk
+        } else {
+            &self.risk_manager
+        };
+        risk_book.validate_category_exposure(MarketCategory::of(market), size_usd, |market_id| {
+            self.registry.get(market_id).map(|m| MarketCategory::of(&m))
+        })
+    }
+
+    /// Whether the strategy's capital bucket (grown or shrunk by its realized PnL) has room
+    fn validate_capital_bucket(&self, strategy: &str, size_usd: f64) -> bool {
+        let realized = match self.pnl_tracker.lock() {
+            Ok(tracker) => tracker.realized_pnl_by_strategy(),
+            Err(_) => return false,
+        };
+        self.risk_manager
+            .validate_capital_bucket(strategy, size_usd, &realized)
+    }
 }
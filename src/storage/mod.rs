@@ -0,0 +1,360 @@
+//! Durable storage for trades, positions, portfolio snapshots, the market registry, seen markets
+//! and the market resolution dataset
+//!
+//! The trading loop never waits on the database: changes are sent to a `StorageWriter`, which
+//! applies them from a background task. Reads happen at startup (restoring open positions and
+//! lifecycle states) and from analytics tooling querying the database directly.
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::analytics::equity::SnapshotRetention;
+use crate::analytics::pnl::{PortfolioSnapshot, Trade};
+use crate::analytics::resolutions::MarketResolution;
+use crate::config::StorageConfig;
+use crate::markets::{AssetRef, MarketState};
+use crate::polymarket::MarketData;
+use crate::strategies::risk::Position;
+
+#[cfg(feature = "postgres")]
+pub mod postgres;
+mod sql;
+pub mod sqlite;
+
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+/// SQLite database file inside the data dir
+pub const STORAGE_FILE: &str = "agent.db";
+
+#[async_trait]
+pub trait TradeStore: Send + Sync {
+    /// Insert or replace (annotated trades are saved again)
+    async fn save_trade(&self, trade: &Trade) -> Result<()>;
+    /// Trades closed since `since`, newest first
+    async fn trades(&self, since: Option<DateTime<Utc>>, limit: usize) -> Result<Vec<Trade>>;
+}
+
+/// Open risk-manager positions, keyed by market id
+#[async_trait]
+pub trait PositionStore: Send + Sync {
+    async fn save_position(&self, position: &Position) -> Result<()>;
+    async fn remove_position(&self, market_id: &str) -> Result<()>;
+    async fn positions(&self) -> Result<Vec<Position>>;
+}
+
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    async fn save_snapshot(&self, snapshot: &PortfolioSnapshot) -> Result<()>;
+    /// Oldest first
+    async fn snapshots(&self, since: DateTime<Utc>) -> Result<Vec<PortfolioSnapshot>>;
+    /// Downsample stored snapshots as of `now` (see `analytics::equity`). Returns how many
+    /// were deleted
+    async fn compact_snapshots(
+        &self,
+        now: DateTime<Utc>,
+        retention: &SnapshotRetention,
+    ) -> Result<usize>;
+}
+
+#[async_trait]
+pub trait MarketStore: Send + Sync {
+    /// Upsert a batch of markets and asset mappings in one transaction
+    async fn save_registry(
+        &self,
+        markets: &[MarketData],
+        assets: &[(String, AssetRef)],
+    ) -> Result<()>;
+    async fn markets(&self) -> Result<Vec<MarketData>>;
+    async fn assets(&self) -> Result<Vec<(String, AssetRef)>>;
+}
+
+/// Markets the agent has seen, with their latest lifecycle state
+#[async_trait]
+pub trait SeenMarketStore: Send + Sync {
+    async fn mark_seen(&self, market_id: &str, state: MarketState) -> Result<()>;
+    async fn seen_markets(&self) -> Result<Vec<(String, MarketState)>>;
+}
+
+/// Outcomes and last-hours price trajectories of resolved markets (strategy research)
+#[async_trait]
+pub trait ResolutionStore: Send + Sync {
+    async fn save_resolution(&self, resolution: &MarketResolution) -> Result<()>;
+    /// Markets that ended since `since`, oldest first
+    async fn resolutions(&self, since: DateTime<Utc>) -> Result<Vec<MarketResolution>>;
+}
+
+pub trait Storage:
+    TradeStore + PositionStore + SnapshotStore + MarketStore + SeenMarketStore + ResolutionStore
+{
+    fn backend(&self) -> &'static str;
+}
+
+/// Open the configured backend. None when storage is disabled
+pub async fn open(config: &StorageConfig, data_dir: &str) -> Result<Option<Arc<dyn Storage>>> {
+    let storage: Arc<dyn Storage> = match config.backend.to_lowercase().as_str() {
+        "" | "none" | "off" => return Ok(None),
+        "sqlite" => {
+            let url = match &config.url {
+                Some(url) => url.clone(),
+                None => {
+                    fs::create_dir_all(data_dir)
+                        .with_context(|| format!("Failed to create {}", data_dir))?;
+                    format!("sqlite://{}/{}", data_dir, STORAGE_FILE)
+                }
+            };
+            Arc::new(SqliteStorage::connect(&url).await?)
+        }
+        #[cfg(feature = "postgres")]
+        "postgres" => {
+            let url = config
+                .url
+                .as_deref()
+                .context("STORAGE_BACKEND=postgres needs STORAGE_URL")?;
+            Arc::new(PostgresStorage::connect(url).await?)
+        }
+        #[cfg(not(feature = "postgres"))]
+        "postgres" => bail!("STORAGE_BACKEND=postgres needs a build with `--features postgres`"),
+        other => bail!(
+            "Unknown STORAGE_BACKEND {:?} (sqlite, postgres or none)",
+            other
+        ),
+    };
+    info!("🗄️ Storage: {}", storage.backend());
+    Ok(Some(storage))
+}
+
+/// A change to persist
+#[derive(Debug, Clone)]
+pub enum StorageOp {
+    Trade(Trade),
+    Position(Position),
+    PositionClosed(String),
+    Snapshot(PortfolioSnapshot),
+    CompactSnapshots {
+        now: DateTime<Utc>,
+        retention: SnapshotRetention,
+    },
+    Registry {
+        markets: Vec<MarketData>,
+        assets: Vec<(String, AssetRef)>,
+    },
+    Seen(String, MarketState),
+    Resolution(MarketResolution),
+}
+
+impl StorageOp {
+    /// Equity samples and their compaction: the next snapshot supersedes a lost one, so these
+    /// are shed first when the writer falls behind
+    fn sheddable(&self) -> bool {
+        matches!(
+            self,
+            StorageOp::Snapshot(_) | StorageOp::CompactSnapshots { .. }
+        )
+    }
+}
+
+/// Pending ops of a `StorageWriter`, bounded by `capacity`
+///
+/// When full: a registry save replaces a queued one in place (each carries the whole
+/// registry), otherwise the oldest queued snapshot is evicted, or the incoming one dropped.
+/// Trades, positions, seen states and resolutions are never dropped; with nothing left to
+/// shed they are queued past the bound and counted as overflow
+struct WriteQueue {
+    ops: Mutex<VecDeque<StorageOp>>,
+    capacity: usize,
+    items: Notify,
+    senders: AtomicUsize,
+    closed: AtomicBool,
+    dropped: AtomicU64,
+    coalesced: AtomicU64,
+    overflowed: AtomicU64,
+}
+
+impl WriteQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            ops: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            items: Notify::new(),
+            senders: AtomicUsize::new(1),
+            closed: AtomicBool::new(false),
+            dropped: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+            overflowed: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&self, op: StorageOp) {
+        let mut ops = self.ops.lock().unwrap();
+        if let StorageOp::Registry { .. } = op {
+            if let Some(slot) = ops
+                .iter_mut()
+                .rev()
+                .find(|queued| matches!(queued, StorageOp::Registry { .. }))
+            {
+                *slot = op;
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+
+        if ops.len() >= self.capacity {
+            if let Some(oldest) = ops.iter().position(StorageOp::sheddable) {
+                ops.remove(oldest);
+                self.record_drop();
+            } else if op.sheddable() {
+                self.record_drop();
+                return;
+            } else {
+                let overflowed = self.overflowed.fetch_add(1, Ordering::Relaxed) + 1;
+                if overflowed == 1 || overflowed % 100 == 0 {
+                    warn!(
+                        "⚠️ Storage writer {} ops behind; queued past the bound ({} so far)",
+                        ops.len(),
+                        overflowed
+                    );
+                }
+            }
+        }
+        ops.push_back(op);
+        drop(ops);
+        self.items.notify_one();
+    }
+
+    fn record_drop(&self) {
+        let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        if dropped == 1 || dropped % 100 == 0 {
+            warn!(
+                "⚠️ Storage writer behind; dropped {} portfolio snapshot writes",
+                dropped
+            );
+        }
+    }
+
+    /// Next op, or None once every writer handle is gone and the queue is drained
+    async fn pop(&self) -> Option<StorageOp> {
+        loop {
+            let items = self.items.notified();
+            if let Some(op) = self.ops.lock().unwrap().pop_front() {
+                return Some(op);
+            }
+            if self.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            items.await;
+        }
+    }
+}
+
+/// Fire-and-forget handle applying `StorageOp`s in order from a background task
+/// (cheap to clone; failures are logged, never surfaced to the trading loop). The queue is
+/// bounded by `STORAGE_QUEUE_CAPACITY` (see `WriteQueue` for what gets dropped)
+pub struct StorageWriter {
+    queue: Arc<WriteQueue>,
+}
+
+impl StorageWriter {
+    /// Must be called from within a tokio runtime
+    pub fn spawn(storage: Arc<dyn Storage>, capacity: usize) -> Self {
+        let queue = Arc::new(WriteQueue::new(capacity));
+        let pending = queue.clone();
+        tokio::spawn(async move {
+            while let Some(op) = pending.pop().await {
+                if let Err(e) = apply(storage.as_ref(), op).await {
+                    warn!("⚠️ Storage write failed: {:#}", e);
+                }
+            }
+        });
+        Self { queue }
+    }
+
+    pub fn send(&self, op: StorageOp) {
+        self.queue.push(op);
+    }
+}
+
+impl Clone for StorageWriter {
+    fn clone(&self) -> Self {
+        self.queue.senders.fetch_add(1, Ordering::AcqRel);
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl Drop for StorageWriter {
+    fn drop(&mut self) {
+        if self.queue.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.queue.closed.store(true, Ordering::Release);
+            self.queue.items.notify_one();
+        }
+    }
+}
+
+async fn apply(storage: &dyn Storage, op: StorageOp) -> Result<()> {
+    match op {
+        StorageOp::Trade(trade) => storage.save_trade(&trade).await,
+        StorageOp::Position(position) => storage.save_position(&position).await,
+        StorageOp::PositionClosed(market_id) => storage.remove_position(&market_id).await,
+        StorageOp::Snapshot(snapshot) => storage.save_snapshot(&snapshot).await,
+        StorageOp::CompactSnapshots { now, retention } => {
+            storage.compact_snapshots(now, &retention).await.map(|_| ())
+        }
+        StorageOp::Registry { markets, assets } => storage.save_registry(&markets, &assets).await,
+        StorageOp::Seen(market_id, state) => storage.mark_seen(&market_id, state).await,
+        StorageOp::Resolution(resolution) => storage.save_resolution(&resolution).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(total_value: f64) -> StorageOp {
+        StorageOp::Snapshot(PortfolioSnapshot {
+            timestamp: Utc::now(),
+            total_value,
+            cash: total_value,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        })
+    }
+
+    fn registry() -> StorageOp {
+        StorageOp::Registry {
+            markets: Vec::new(),
+            assets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_write_queue_sheds_snapshots_only() {
+        let queue = WriteQueue::new(2);
+        queue.push(snapshot(1.0));
+        queue.push(registry());
+        queue.push(registry());
+        assert_eq!(queue.coalesced.load(Ordering::Relaxed), 1);
+
+        // Full: the queued snapshot makes room, then an incoming one is dropped
+        queue.push(StorageOp::PositionClosed("m1".to_string()));
+        queue.push(snapshot(2.0));
+        assert_eq!(queue.dropped.load(Ordering::Relaxed), 2);
+
+        // Nothing left to shed: a position change still gets queued
+        queue.push(StorageOp::PositionClosed("m2".to_string()));
+        assert_eq!(queue.overflowed.load(Ordering::Relaxed), 1);
+
+        let ops = queue.ops.lock().unwrap();
+        assert_eq!(ops.len(), 3);
+        assert!(matches!(ops[0], StorageOp::Registry { .. }));
+        assert!(matches!(&ops[2], StorageOp::PositionClosed(id) if id == "m2"));
+    }
+}
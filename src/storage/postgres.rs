@@ -0,0 +1,26 @@
+use anyhow::{Context, Result};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+use super::sql::{self, impl_sql_storage};
+
+/// Shared database for several agents or heavier analytics (`postgres` feature)
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect(url)
+            .await
+            // The URL carries the password: don't echo it
+            .context("Failed to connect to Postgres")?;
+        for statement in sql::SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        Ok(Self { pool })
+    }
+}
+
+impl_sql_storage!(PostgresStorage, "postgres");
@@ -0,0 +1,378 @@
+//! Schema and queries shared by the SQL backends
+//!
+//! Written to run unchanged on SQLite and Postgres: `$n` placeholders, `ON CONFLICT` upserts,
+//! timestamps as fixed-width RFC 3339 text (sorts chronologically) and full records in a JSON
+//! `data` column next to the indexed ones.
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::markets::MarketState;
+
+pub(super) const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS trades (
+        id TEXT PRIMARY KEY,
+        market_id TEXT NOT NULL,
+        exit_time TEXT NOT NULL,
+        realized_pnl DOUBLE PRECISION,
+        config_hash TEXT,
+        data TEXT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS trades_exit_time ON trades (exit_time)",
+    "CREATE INDEX IF NOT EXISTS trades_market_id ON trades (market_id)",
+    "CREATE TABLE IF NOT EXISTS positions (
+        market_id TEXT PRIMARY KEY,
+        trade_id TEXT NOT NULL,
+        data TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS snapshots (
+        timestamp TEXT PRIMARY KEY,
+        total_value DOUBLE PRECISION NOT NULL,
+        unrealized_pnl DOUBLE PRECISION NOT NULL,
+        realized_pnl DOUBLE PRECISION NOT NULL,
+        data TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS markets (
+        id TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS assets (
+        asset_id TEXT PRIMARY KEY,
+        market_id TEXT NOT NULL,
+        side TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS seen_markets (
+        market_id TEXT PRIMARY KEY,
+        state TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS resolutions (
+        market_id TEXT PRIMARY KEY,
+        end_date TEXT NOT NULL,
+        winner TEXT NOT NULL,
+        data TEXT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS resolutions_end_date ON resolutions (end_date)",
+];
+
+pub(super) const UPSERT_TRADE: &str = "INSERT INTO trades (id, market_id, exit_time, realized_pnl, config_hash, data)
+    VALUES ($1, $2, $3, $4, $5, $6)
+    ON CONFLICT (id) DO UPDATE SET exit_time = excluded.exit_time, realized_pnl = excluded.realized_pnl,
+        config_hash = excluded.config_hash, data = excluded.data";
+pub(super) const SELECT_TRADES: &str =
+    "SELECT data FROM trades WHERE exit_time >= $1 ORDER BY exit_time DESC LIMIT $2";
+
+pub(super) const UPSERT_POSITION: &str = "INSERT INTO positions (market_id, trade_id, data)
+    VALUES ($1, $2, $3)
+    ON CONFLICT (market_id) DO UPDATE SET trade_id = excluded.trade_id, data = excluded.data";
+pub(super) const DELETE_POSITION: &str = "DELETE FROM positions WHERE market_id = $1";
+pub(super) const SELECT_POSITIONS: &str = "SELECT data FROM positions";
+
+pub(super) const INSERT_SNAPSHOT: &str =
+    "INSERT INTO snapshots (timestamp, total_value, unrealized_pnl, realized_pnl, data)
+    VALUES ($1, $2, $3, $4, $5)
+    ON CONFLICT (timestamp) DO NOTHING";
+pub(super) const SELECT_SNAPSHOTS: &str =
+    "SELECT data FROM snapshots WHERE timestamp >= $1 ORDER BY timestamp";
+pub(super) const SELECT_SNAPSHOT_TIMES: &str =
+    "SELECT timestamp FROM snapshots WHERE timestamp < $1 ORDER BY timestamp";
+pub(super) const DELETE_SNAPSHOT: &str = "DELETE FROM snapshots WHERE timestamp = $1";
+
+pub(super) const UPSERT_MARKET: &str = "INSERT INTO markets (id, data) VALUES ($1, $2)
+    ON CONFLICT (id) DO UPDATE SET data = excluded.data";
+pub(super) const SELECT_MARKETS: &str = "SELECT data FROM markets";
+pub(super) const UPSERT_ASSET: &str =
+    "INSERT INTO assets (asset_id, market_id, side) VALUES ($1, $2, $3)
+    ON CONFLICT (asset_id) DO UPDATE SET market_id = excluded.market_id, side = excluded.side";
+pub(super) const SELECT_ASSETS: &str = "SELECT asset_id, market_id, side FROM assets";
+
+pub(super) const UPSERT_SEEN: &str = "INSERT INTO seen_markets (market_id, state, updated_at)
+    VALUES ($1, $2, $3)
+    ON CONFLICT (market_id) DO UPDATE SET state = excluded.state, updated_at = excluded.updated_at";
+pub(super) const SELECT_SEEN: &str = "SELECT market_id, state FROM seen_markets";
+
+pub(super) const UPSERT_RESOLUTION: &str =
+    "INSERT INTO resolutions (market_id, end_date, winner, data) VALUES ($1, $2, $3, $4)
+    ON CONFLICT (market_id) DO UPDATE SET end_date = excluded.end_date, winner = excluded.winner,
+        data = excluded.data";
+pub(super) const SELECT_RESOLUTIONS: &str =
+    "SELECT data FROM resolutions WHERE end_date >= $1 ORDER BY end_date";
+
+pub(super) fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+pub(super) fn parse_timestamp(raw: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(raw)
+        .with_context(|| format!("Unreadable stored timestamp {:?}", raw))?
+        .with_timezone(&Utc))
+}
+
+pub(super) fn state_name(state: MarketState) -> Result<String> {
+    serde_json::to_value(state)?
+        .as_str()
+        .map(str::to_string)
+        .context("MarketState doesn't serialize to a string")
+}
+
+pub(super) fn parse_state(name: &str) -> Result<MarketState> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .with_context(|| format!("Unknown market state {:?}", name))
+}
+
+pub(super) fn decode<T: serde::de::DeserializeOwned>(rows: Vec<(String,)>) -> Result<Vec<T>> {
+    rows.iter()
+        .map(|(data,)| serde_json::from_str(data).context("Unreadable stored record"))
+        .collect()
+}
+
+/// Implement the storage traits for a backend struct holding a sqlx `pool`
+macro_rules! impl_sql_storage {
+    ($storage:ty, $backend:literal) => {
+        #[async_trait::async_trait]
+        impl $crate::storage::TradeStore for $storage {
+            async fn save_trade(
+                &self,
+                trade: &$crate::analytics::pnl::Trade,
+            ) -> anyhow::Result<()> {
+                use $crate::storage::sql;
+                let exit_time = trade.exit_time.unwrap_or(trade.entry_time);
+                sqlx::query(sql::UPSERT_TRADE)
+                    .bind(&trade.id)
+                    .bind(&trade.market_id)
+                    .bind(sql::timestamp(exit_time))
+                    .bind(trade.realized_pnl)
+                    .bind(&trade.config_hash)
+                    .bind(serde_json::to_string(trade)?)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            async fn trades(
+                &self,
+                since: Option<chrono::DateTime<chrono::Utc>>,
+                limit: usize,
+            ) -> anyhow::Result<Vec<$crate::analytics::pnl::Trade>> {
+                use $crate::storage::sql;
+                let rows: Vec<(String,)> = sqlx::query_as(sql::SELECT_TRADES)
+                    .bind(since.map(sql::timestamp).unwrap_or_default())
+                    .bind(limit.min(i64::MAX as usize) as i64)
+                    .fetch_all(&self.pool)
+                    .await?;
+                sql::decode(rows)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::storage::PositionStore for $storage {
+            async fn save_position(
+                &self,
+                position: &$crate::strategies::risk::Position,
+            ) -> anyhow::Result<()> {
+                sqlx::query($crate::storage::sql::UPSERT_POSITION)
+                    .bind(&position.market_id)
+                    .bind(&position.trade_id)
+                    .bind(serde_json::to_string(position)?)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            async fn remove_position(&self, market_id: &str) -> anyhow::Result<()> {
+                sqlx::query($crate::storage::sql::DELETE_POSITION)
+                    .bind(market_id)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            async fn positions(&self) -> anyhow::Result<Vec<$crate::strategies::risk::Position>> {
+                let rows: Vec<(String,)> = sqlx::query_as($crate::storage::sql::SELECT_POSITIONS)
+                    .fetch_all(&self.pool)
+                    .await?;
+                $crate::storage::sql::decode(rows)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::storage::SnapshotStore for $storage {
+            async fn save_snapshot(
+                &self,
+                snapshot: &$crate::analytics::pnl::PortfolioSnapshot,
+            ) -> anyhow::Result<()> {
+                use $crate::storage::sql;
+                sqlx::query(sql::INSERT_SNAPSHOT)
+                    .bind(sql::timestamp(snapshot.timestamp))
+                    .bind(snapshot.total_value)
+                    .bind(snapshot.unrealized_pnl)
+                    .bind(snapshot.realized_pnl)
+                    .bind(serde_json::to_string(snapshot)?)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            async fn snapshots(
+                &self,
+                since: chrono::DateTime<chrono::Utc>,
+            ) -> anyhow::Result<Vec<$crate::analytics::pnl::PortfolioSnapshot>> {
+                use $crate::storage::sql;
+                let rows: Vec<(String,)> = sqlx::query_as(sql::SELECT_SNAPSHOTS)
+                    .bind(sql::timestamp(since))
+                    .fetch_all(&self.pool)
+                    .await?;
+                sql::decode(rows)
+            }
+
+            async fn compact_snapshots(
+                &self,
+                now: chrono::DateTime<chrono::Utc>,
+                retention: &$crate::analytics::equity::SnapshotRetention,
+            ) -> anyhow::Result<usize> {
+                use $crate::storage::sql;
+                // Only timestamps are read: rows at full resolution are never candidates
+                let rows: Vec<(String,)> = sqlx::query_as(sql::SELECT_SNAPSHOT_TIMES)
+                    .bind(sql::timestamp(now - retention.full))
+                    .fetch_all(&self.pool)
+                    .await?;
+                let timestamps = rows
+                    .iter()
+                    .map(|(raw,)| sql::parse_timestamp(raw))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let keep = $crate::analytics::equity::retained(&timestamps, now, retention);
+
+                let mut tx = self.pool.begin().await?;
+                let mut deleted = 0;
+                for ((raw,), keep) in rows.iter().zip(keep) {
+                    if !keep {
+                        sqlx::query(sql::DELETE_SNAPSHOT)
+                            .bind(raw)
+                            .execute(&mut *tx)
+                            .await?;
+                        deleted += 1;
+                    }
+                }
+                tx.commit().await?;
+                Ok(deleted)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::storage::MarketStore for $storage {
+            async fn save_registry(
+                &self,
+                markets: &[$crate::polymarket::MarketData],
+                assets: &[(String, $crate::markets::AssetRef)],
+            ) -> anyhow::Result<()> {
+                use $crate::storage::sql;
+                let mut tx = self.pool.begin().await?;
+                for market in markets {
+                    sqlx::query(sql::UPSERT_MARKET)
+                        .bind(market.id.as_str())
+                        .bind(serde_json::to_string(market)?)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                for (asset_id, asset) in assets {
+                    sqlx::query(sql::UPSERT_ASSET)
+                        .bind(asset_id)
+                        .bind(&asset.market_id)
+                        .bind(&asset.side)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                Ok(())
+            }
+
+            async fn markets(&self) -> anyhow::Result<Vec<$crate::polymarket::MarketData>> {
+                let rows: Vec<(String,)> = sqlx::query_as($crate::storage::sql::SELECT_MARKETS)
+                    .fetch_all(&self.pool)
+                    .await?;
+                $crate::storage::sql::decode(rows)
+            }
+
+            async fn assets(&self) -> anyhow::Result<Vec<(String, $crate::markets::AssetRef)>> {
+                let rows: Vec<(String, String, String)> =
+                    sqlx::query_as($crate::storage::sql::SELECT_ASSETS)
+                        .fetch_all(&self.pool)
+                        .await?;
+                Ok(rows
+                    .into_iter()
+                    .map(|(asset_id, market_id, side)| {
+                        (asset_id, $crate::markets::AssetRef { market_id, side })
+                    })
+                    .collect())
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::storage::SeenMarketStore for $storage {
+            async fn mark_seen(
+                &self,
+                market_id: &str,
+                state: $crate::markets::MarketState,
+            ) -> anyhow::Result<()> {
+                use $crate::storage::sql;
+                sqlx::query(sql::UPSERT_SEEN)
+                    .bind(market_id)
+                    .bind(sql::state_name(state)?)
+                    .bind(sql::timestamp(chrono::Utc::now()))
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            async fn seen_markets(
+                &self,
+            ) -> anyhow::Result<Vec<(String, $crate::markets::MarketState)>> {
+                let rows: Vec<(String, String)> = sqlx::query_as($crate::storage::sql::SELECT_SEEN)
+                    .fetch_all(&self.pool)
+                    .await?;
+                rows.into_iter()
+                    .map(|(market_id, state)| {
+                        Ok((market_id, $crate::storage::sql::parse_state(&state)?))
+                    })
+                    .collect()
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::storage::ResolutionStore for $storage {
+            async fn save_resolution(
+                &self,
+                resolution: &$crate::analytics::resolutions::MarketResolution,
+            ) -> anyhow::Result<()> {
+                use $crate::storage::sql;
+                sqlx::query(sql::UPSERT_RESOLUTION)
+                    .bind(&resolution.market_id)
+                    .bind(sql::timestamp(resolution.end_date))
+                    .bind(&resolution.winner)
+                    .bind(serde_json::to_string(resolution)?)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            async fn resolutions(
+                &self,
+                since: chrono::DateTime<chrono::Utc>,
+            ) -> anyhow::Result<Vec<$crate::analytics::resolutions::MarketResolution>> {
+                use $crate::storage::sql;
+                let rows: Vec<(String,)> = sqlx::query_as(sql::SELECT_RESOLUTIONS)
+                    .bind(sql::timestamp(since))
+                    .fetch_all(&self.pool)
+                    .await?;
+                sql::decode(rows)
+            }
+        }
+
+        impl $crate::storage::Storage for $storage {
+            fn backend(&self) -> &'static str {
+                $backend
+            }
+        }
+    };
+}
+
+pub(super) use impl_sql_storage;
@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use std::str::FromStr;
+
+use super::sql::{self, impl_sql_storage};
+
+/// Default backend: a single file in the data dir (`sqlite://<path>`)
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(url)
+            .with_context(|| format!("Invalid SQLite URL {:?}", url))?
+            .create_if_missing(true)
+            // Readers (analytics queries) don't block the agent's writes
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await
+            .with_context(|| format!("Failed to open {}", url))?;
+        for statement in sql::SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        Ok(Self { pool })
+    }
+}
+
+impl_sql_storage!(SqliteStorage, "sqlite");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::equity::SnapshotRetention;
+    use crate::analytics::pnl::{PortfolioSnapshot, Trade};
+    use crate::analytics::resolutions::MarketResolution;
+    use crate::markets::{AssetRef, MarketState};
+    use crate::storage::{ResolutionStore, Storage};
+    use crate::strategies::risk::Position;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let dir = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let url = format!("sqlite://{}/agent.db", dir.display());
+        let storage = SqliteStorage::connect(&url).await.unwrap();
+        assert_eq!(storage.backend(), "sqlite");
+
+        let now = Utc::now();
+        let mut trade = Trade {
+            id: "p1".to_string(),
+            market_id: "m1".to_string(),
+            market_question: "Q?".to_string(),
+            side: "YES".to_string(),
+            size: 10.0,
+            entry_price: 0.5,
+            exit_price: Some(0.6),
+            entry_time: now - Duration::minutes(5),
+            exit_time: Some(now),
+            realized_pnl: Some(2.0),
+            config_hash: None,
+            note: None,
+            tags: Vec::new(),
+        };
+        storage.save_trade(&trade).await.unwrap();
+        trade.note = Some("late fill".to_string());
+        storage.save_trade(&trade).await.unwrap(); // Annotation replaces the row
+        let trades = storage.trades(None, 10).await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].note.as_deref(), Some("late fill"));
+        assert!(storage
+            .trades(Some(now + Duration::seconds(1)), 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let position = Position {
+            market_id: "m1".to_string(),
+            trade_id: "p1".to_string(),
+            side: "YES".to_string(),
+            size_usd: 10.0,
+            entry_price: 0.5,
+            timestamp: 0,
+            realized_pnl: 0.0,
+            config_hash: None,
+        };
+        storage.save_position(&position).await.unwrap();
+        assert_eq!(storage.positions().await.unwrap().len(), 1);
+        storage.remove_position("m1").await.unwrap();
+        assert!(storage.positions().await.unwrap().is_empty());
+
+        storage
+            .save_snapshot(&PortfolioSnapshot {
+                timestamp: now,
+                total_value: 100.0,
+                cash: 90.0,
+                unrealized_pnl: 1.0,
+                realized_pnl: 2.0,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            storage
+                .snapshots(now - Duration::hours(1))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        // Two days old: compacted to the last snapshot of its minute
+        let old = now - Duration::days(2);
+        let old = old - Duration::seconds(old.timestamp().rem_euclid(60));
+        for seconds in [0, 10] {
+            storage
+                .save_snapshot(&PortfolioSnapshot {
+                    timestamp: old + Duration::seconds(seconds),
+                    total_value: 100.0 + seconds as f64,
+                    cash: 90.0,
+                    unrealized_pnl: 1.0,
+                    realized_pnl: 2.0,
+                })
+                .await
+                .unwrap();
+        }
+        let retention = SnapshotRetention {
+            full: Duration::hours(24),
+            minute_bars: Duration::days(7),
+        };
+        assert_eq!(storage.compact_snapshots(now, &retention).await.unwrap(), 1);
+        let kept = storage.snapshots(old - Duration::hours(1)).await.unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].total_value, 110.0);
+        assert_eq!(storage.compact_snapshots(now, &retention).await.unwrap(), 0);
+
+        let asset = AssetRef {
+            market_id: "m1".to_string(),
+            side: "NO".to_string(),
+        };
+        storage
+            .save_registry(&[], &[("a1".to_string(), asset.clone())])
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.assets().await.unwrap(),
+            vec![("a1".to_string(), asset)]
+        );
+
+        storage
+            .mark_seen("m1", MarketState::Tradable)
+            .await
+            .unwrap();
+        storage
+            .mark_seen("m1", MarketState::Positioned)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.seen_markets().await.unwrap(),
+            vec![("m1".to_string(), MarketState::Positioned)]
+        );
+
+        let resolution = MarketResolution {
+            market_id: "m1".to_string(),
+            question: "Q?".to_string(),
+            end_date: now,
+            winner: "YES".to_string(),
+            resolved_at: now,
+            trajectory: Vec::new(),
+        };
+        storage.save_resolution(&resolution).await.unwrap();
+        storage.save_resolution(&resolution).await.unwrap();
+        let resolutions = storage.resolutions(now - Duration::hours(1)).await.unwrap();
+        assert_eq!(resolutions.len(), 1);
+        assert_eq!(resolutions[0].winner, "YES");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
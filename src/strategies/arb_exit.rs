@@ -0,0 +1,153 @@
+//! Exits of YES + NO arbitrage pairs
+//!
+//! A matched pair pays exactly 1 whichever way the market resolves, so holding it to resolution
+//! only ties up capital until the redemption. Merging the pair back into collateral pays the
+//! same 1 right away for one transaction's gas. When one outcome rallies past what the model
+//! thinks it is worth, selling that leg into the move and keeping the other is worth more than
+//! either. Each check values the three exits for the matched quantity and picks the best.
+use crate::config::ArbExitConfig;
+use crate::strategies::arbitrage::FEE_PER_TRADE_BPS;
+
+/// What to do with the matched pairs of an arbitrage entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArbExit {
+    /// Keep the pairs and redeem them at resolution
+    Hold,
+    /// Merge the pairs back into collateral now
+    Merge,
+    /// Sell this side's leg into its bid, keep the other to resolution
+    SellLeg { side: String, price: f64 },
+}
+
+/// An arbitrage entry's matched quantity as it trades now
+#[derive(Debug, Clone, Copy)]
+pub struct ArbPairQuote {
+    /// YES + NO shares held in matched pairs
+    pub pairs: f64,
+    pub yes_bid: f64,
+    pub no_bid: f64,
+    /// Probability of YES: the model's where it prices the market, else the book mid
+    pub fair_yes: f64,
+    /// Gas of a merge now, None where merging isn't possible (no wallet to send it from)
+    pub merge_gas_usd: Option<f64>,
+    /// Gas of the redemption at resolution
+    pub redeem_gas_usd: f64,
+}
+
+impl ArbPairQuote {
+    /// Bids from the asks of the complementary books: the CLOB matches a YES bid against a NO
+    /// ask (minting a pair), so the best price a leg sells at is 1 minus the other side's ask
+    pub fn from_asks(pairs: f64, yes_ask: f64, no_ask: f64) -> Self {
+        let yes_bid = (1.0 - no_ask).clamp(0.0, 1.0);
+        let no_bid = (1.0 - yes_ask).clamp(0.0, 1.0);
+        Self {
+            pairs,
+            yes_bid,
+            no_bid,
+            fair_yes: (yes_bid + yes_ask) / 2.0,
+            merge_gas_usd: None,
+            redeem_gas_usd: 0.0,
+        }
+    }
+}
+
+/// Expected USD value of each exit for the matched pairs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArbExitValues {
+    pub hold: f64,
+    pub merge: Option<f64>,
+    pub sell_yes: f64,
+    pub sell_no: f64,
+}
+
+/// Value the exits of `quote` and pick one. Merging wins ties with holding (the capital comes
+/// back sooner); selling a leg must beat the other exits by `min_leg_edge` per pair, the
+/// margin for the fair value being wrong
+pub fn choose_exit(config: &ArbExitConfig, quote: &ArbPairQuote) -> (ArbExit, ArbExitValues) {
+    let fee = FEE_PER_TRADE_BPS as f64 / 10_000.0;
+    let pairs = quote.pairs;
+    let fair_yes = quote.fair_yes.clamp(0.0, 1.0);
+    let values = ArbExitValues {
+        hold: pairs - quote.redeem_gas_usd,
+        merge: quote.merge_gas_usd.map(|gas| pairs - gas),
+        // The kept leg still pays out at resolution, through a redemption
+        sell_yes: pairs * quote.yes_bid * (1.0 - fee) + pairs * (1.0 - fair_yes)
+            - quote.redeem_gas_usd,
+        sell_no: pairs * quote.no_bid * (1.0 - fee) + pairs * fair_yes - quote.redeem_gas_usd,
+    };
+    if pairs <= 0.0 {
+        return (ArbExit::Hold, values);
+    }
+
+    let (mut exit, mut best) = (ArbExit::Hold, values.hold);
+    if let Some(merge) = values.merge {
+        if merge >= best {
+            (exit, best) = (ArbExit::Merge, merge);
+        }
+    }
+    let hurdle = best + pairs * config.min_leg_edge;
+    let sells = [
+        ("YES", quote.yes_bid, values.sell_yes),
+        ("NO", quote.no_bid, values.sell_no),
+    ];
+    if let Some((side, price, _)) = sells
+        .into_iter()
+        .filter(|(_, price, value)| *price > 0.0 && *value > hurdle)
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+    {
+        exit = ArbExit::SellLeg {
+            side: side.to_string(),
+            price,
+        };
+    }
+    (exit, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ArbExitConfig {
+        ArbExitConfig {
+            enabled: true,
+            check_interval_secs: 10,
+            merge_gas_units: 150_000,
+            min_leg_edge: 0.02,
+        }
+    }
+
+    #[test]
+    fn test_merge_unless_a_leg_trades_rich() {
+        // Asks of 0.55 / 0.47: bids 0.53 / 0.45, mid 0.54
+        let mut quote = ArbPairQuote::from_asks(100.0, 0.55, 0.47);
+        assert!((quote.yes_bid - 0.53).abs() < 1e-9);
+        assert!((quote.fair_yes - 0.54).abs() < 1e-9);
+        quote.redeem_gas_usd = 0.01;
+        quote.merge_gas_usd = Some(0.01);
+
+        // Priced off the book a leg sale never beats the merge
+        let (exit, values) = choose_exit(&config(), &quote);
+        assert_eq!(exit, ArbExit::Merge);
+        assert!((values.merge.unwrap() - 99.99).abs() < 1e-9);
+
+        // The model has YES at 0.40: sell YES into the 0.53 bid, keep NO
+        quote.fair_yes = 0.40;
+        let (exit, values) = choose_exit(&config(), &quote);
+        match exit {
+            ArbExit::SellLeg { side, price } => {
+                assert_eq!(side, "YES");
+                assert!((price - 0.53).abs() < 1e-9);
+            }
+            other => panic!("expected a YES sale, got {:?}", other),
+        }
+        assert!(values.sell_yes > values.merge.unwrap() + 2.0);
+
+        // Within the margin: merge
+        quote.fair_yes = 0.525;
+        assert_eq!(choose_exit(&config(), &quote).0, ArbExit::Merge);
+
+        // No merge possible and nothing rich: hold to resolution
+        quote.merge_gas_usd = None;
+        assert_eq!(choose_exit(&config(), &quote).0, ArbExit::Hold);
+    }
+}
@@ -1,28 +1,60 @@
-use crate::config::ArbitrageConfig;
+use crate::alloc_profile;
+use crate::config::{ArbitrageConfig, SizingMode};
+use crate::ids::{MarketId, Price, UsdAmount};
 use crate::polymarket::{MarketData, OrderBook, OrderLevel};
 use crate::strategies::position_sizing::{
     estimate_volatility, estimate_win_probability, PositionSizer,
 };
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
-#[derive(Debug)]
+// Polymarket fees: ~0.2% maker + ~0.2% taker = 0.4% per trade
+// For arbitrage (buy YES + buy NO), we pay fees twice = 0.8% total
+// (fee-free promotional markets are costed at their CLOB fee rate instead, see markets::fees)
+pub const FEE_PER_TRADE_BPS: i32 = 40; // 0.4% = 40 bps
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TradeAction {
     BuyBoth {
-        market_id: String,
-        yes_price: f64,
-        no_price: f64,
-        size_usd: f64,
+        market_id: MarketId,
+        yes_price: Price,
+        no_price: Price,
+        size_usd: UsdAmount,
         expected_profit_bps: i32,
     },
     Snipe {
-        market_id: String,
+        market_id: MarketId,
         side: String, // "YES" or "NO"
-        price: f64,
-        size_usd: f64,
+        price: Price,
+        size_usd: UsdAmount,
     },
     None,
 }
 
+/// What an arbitrage decision used beyond the market's best asks, kept in the decision log so
+/// a replay takes the same path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArbitrageInputs {
+    /// Fee a leg was costed at: the CLOB's rate once checked, the usual fee until then
+    pub fee_per_leg_bps: i32,
+    /// Size multiplier of fee-free markets (1 elsewhere)
+    pub size_multiplier: f64,
+    /// YES and NO ask ladders (price, size) the depth guard checked; None without WS books
+    pub yes_asks: Option<Vec<(f64, f64)>>,
+    pub no_asks: Option<Vec<(f64, f64)>>,
+    /// Capital entries were sized from; None in records written before it was kept (replayed
+    /// at the default bankroll)
+    #[serde(default)]
+    pub sizing_capital: Option<f64>,
+}
+
+impl ArbitrageInputs {
+    /// The ladders as `check_opportunity_on_book` takes them
+    pub fn book(&self) -> Option<(&[(f64, f64)], &[(f64, f64)])> {
+        Some((self.yes_asks.as_deref()?, self.no_asks.as_deref()?))
+    }
+}
+
 /// Orderbook depth analysis result
 #[derive(Debug, Clone)]
 pub struct OrderbookDepth {
@@ -37,12 +69,16 @@ pub struct OrderbookDepth {
 pub struct ArbitrageStrategy {
     config: ArbitrageConfig,
     position_sizer: Option<PositionSizer>,
+    /// Capital the sizer takes fractions of
+    capital: f64,
 }
 
 impl ArbitrageStrategy {
-    pub fn new(config: ArbitrageConfig) -> Self {
+    /// `capital_usd` is the bankroll Kelly sizing works from until (or unless) portfolio value
+    /// is fed in
+    pub fn new(config: ArbitrageConfig, capital_usd: f64) -> Self {
         // Initialize position sizer if dynamic sizing is enabled
-        let position_sizer = if config.use_dynamic_sizing {
+        let position_sizer = if config.sizing_mode != SizingMode::Fixed {
             Some(PositionSizer::new(
                 config.kelly_fraction,
                 config.min_position_pct,
@@ -55,75 +91,208 @@ impl ArbitrageStrategy {
         Self {
             config,
             position_sizer,
+            capital: capital_usd,
         }
     }
 
+    /// Latest portfolio value: the sizing capital in compounding mode, less the reserve
+    pub fn set_portfolio_value(&mut self, portfolio_value: f64) {
+        if self.config.sizing_mode != SizingMode::Compounding || !portfolio_value.is_finite() {
+            return;
+        }
+        let reserve = self.config.capital_reserve_fraction.clamp(0.0, 1.0);
+        self.capital = (portfolio_value * (1.0 - reserve)).max(0.0);
+    }
+
+    /// Capital entries are currently sized from
+    pub fn sizing_capital(&self) -> f64 {
+        self.capital
+    }
+
+    /// Configured (base) minimum net edge
+    pub fn min_edge_bps(&self) -> i32 {
+        self.config.min_edge_bps
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Edge of buying both sides at the current asks, after fees
+    /// Only meaningful when `market.quote_quality()` is complete
+    #[inline(always)]
+    pub fn net_spread_bps(market: &MarketData) -> i32 {
+        Self::net_spread_bps_at_fee(market, FEE_PER_TRADE_BPS)
+    }
+
+    /// `net_spread_bps` for a market charging `fee_per_leg_bps` a trade (0 on fee-free markets)
+    #[inline(always)]
+    pub fn net_spread_bps_at_fee(market: &MarketData, fee_per_leg_bps: i32) -> i32 {
+        let spread = 1.0 - (market.yes_price + market.no_price);
+        (spread * 10000.0) as i32 - fee_per_leg_bps * 2
+    }
+
     /// Check for arbitrage opportunity using simple best bid/ask
     /// Now with dynamic position sizing based on Kelly Criterion
     /// Optimized with branchless code and early returns
     #[inline(always)]
     pub fn check_opportunity(&self, market: &MarketData) -> TradeAction {
+        self.check_opportunity_with_edge(market, self.config.min_edge_bps)
+    }
+
+    /// Same as `check_opportunity` but with an explicit (e.g. per-market adjusted) edge threshold
+    #[inline(always)]
+    pub fn check_opportunity_with_edge(
+        &self,
+        market: &MarketData,
+        min_edge_bps: i32,
+    ) -> TradeAction {
+        self.check_opportunity_at_fee(market, min_edge_bps, FEE_PER_TRADE_BPS)
+    }
+
+    /// `check_opportunity_with_edge` on a market charging `fee_per_leg_bps` a trade
+    #[inline(always)]
+    pub fn check_opportunity_at_fee(
+        &self,
+        market: &MarketData,
+        min_edge_bps: i32,
+        fee_per_leg_bps: i32,
+    ) -> TradeAction {
+        let _alloc = alloc_profile::region("arbitrage::check_opportunity");
         if !self.config.enabled {
             return TradeAction::None;
         }
 
+        // A missing side is stored as 0 and would read as a huge edge: both asks are required
+        let quality = market.quote_quality();
+        if !quality.is_complete() {
+            debug!("⚠️ Incomplete quotes for {}: {:?}", market.id, quality);
+            return TradeAction::None;
+        }
+
         let yes_ask = market.yes_price;
         let no_ask = market.no_price;
-
-        // Branchless validation: both prices must be positive
-        // If either is <= 0, total_cost will be invalid
         let total_cost = yes_ask + no_ask;
-
-        // Polymarket fees: ~0.2% maker + ~0.2% taker = 0.4% per trade
-        // For arbitrage (buy YES + buy NO), we pay fees twice = 0.8% total
-        const FEE_PER_TRADE_BPS: i32 = 40; // 0.4% = 40 bps
-        const TOTAL_FEE_BPS: i32 = FEE_PER_TRADE_BPS * 2; // 80 bps for both trades
-
-        // Calculate spread AFTER fees
-        let spread = 1.0 - total_cost;
-        let spread_bps = (spread * 10000.0) as i32;
-        let net_spread_bps = spread_bps - TOTAL_FEE_BPS;
+        let total_fee_bps = fee_per_leg_bps * 2;
+        let net_spread_bps = Self::net_spread_bps_at_fee(market, fee_per_leg_bps);
+        let spread_bps = net_spread_bps + total_fee_bps;
 
         // DEBUG: Sample 0.1% of checks to ensure we are seeing correct prices
         if rand::random::<f64>() < 0.001 {
             info!("🔍 SAMPLE CHECK [{}]: Yes={:.3} No={:.3} Cost={:.3} Spread={}bps Fees={}bps Net={}bps", 
-                market.question, yes_ask, no_ask, total_cost, spread_bps, TOTAL_FEE_BPS, net_spread_bps);
+                market.question, yes_ask, no_ask, total_cost, spread_bps, total_fee_bps, net_spread_bps);
         }
 
         // Early return if no opportunity after fees (most common case)
-        if net_spread_bps <= self.config.min_edge_bps {
+        if net_spread_bps <= min_edge_bps {
             // Log "Close calls" (e.g. within 50bps of target) to show it's working
-            if net_spread_bps > (self.config.min_edge_bps - 50) {
+            if net_spread_bps > (min_edge_bps - 50) {
                 debug!(
                     "👀 CLOSE CALL [{}]: Net Edge {} bps (Target {})",
-                    market.question, net_spread_bps, self.config.min_edge_bps
+                    market.question, net_spread_bps, min_edge_bps
                 );
             }
             return TradeAction::None;
         }
 
-        if yes_ask <= 0.0 || no_ask <= 0.0 {
-            debug!(
-                "⚠️ Missing prices for {}: YES={:.4}, NO={:.4}",
-                market.id, yes_ask, no_ask
-            );
-            return TradeAction::None;
-        }
-
         // Hot path: calculate position size using NET spread (after fees)
         let size_usd = self.calculate_position_size(net_spread_bps, &market.id, 0, true);
 
         TradeAction::BuyBoth {
             market_id: market.id.clone(),
-            yes_price: yes_ask,
-            no_price: no_ask,
-            size_usd,
+            yes_price: Price::new(yes_ask),
+            no_price: Price::new(no_ask),
+            size_usd: UsdAmount::new(size_usd),
             expected_profit_bps: net_spread_bps, // Net profit after fees
         }
     }
 
+    /// Dust guard: whether `yes_asks` and `no_asks` (price, size) each hold
+    /// `ARBITRAGE_MIN_DEPTH_USD` of notional within the edge, i.e. priced so that against the
+    /// other side's best ask the pair still clears `min_edge_bps` after fees. An edge only
+    /// there for a few dollars is usually bait. Always true with the guard off
+    pub fn edge_has_depth(
+        &self,
+        yes_asks: &[(f64, f64)],
+        no_asks: &[(f64, f64)],
+        min_edge_bps: i32,
+    ) -> bool {
+        self.edge_has_depth_at_fee(yes_asks, no_asks, min_edge_bps, FEE_PER_TRADE_BPS)
+    }
+
+    /// `edge_has_depth` on a market charging `fee_per_leg_bps` a trade
+    pub fn edge_has_depth_at_fee(
+        &self,
+        yes_asks: &[(f64, f64)],
+        no_asks: &[(f64, f64)],
+        min_edge_bps: i32,
+        fee_per_leg_bps: i32,
+    ) -> bool {
+        self.depth_within_edge(yes_asks, no_asks, min_edge_bps + fee_per_leg_bps * 2)
+    }
+
+    /// The full arbitrage decision: `check_opportunity_at_fee` with the size scaled by
+    /// `size_multiplier`, dropped when the YES / NO ask ladders of `book` are too thin within
+    /// the edge (no depth check without books)
+    pub fn check_opportunity_on_book(
+        &self,
+        market: &MarketData,
+        min_edge_bps: i32,
+        fee_per_leg_bps: i32,
+        size_multiplier: f64,
+        book: Option<(&[(f64, f64)], &[(f64, f64)])>,
+    ) -> TradeAction {
+        let mut action = self.check_opportunity_at_fee(market, min_edge_bps, fee_per_leg_bps);
+        let TradeAction::BuyBoth { size_usd, .. } = &mut action else {
+            return action;
+        };
+        *size_usd = UsdAmount::new(size_usd.get() * size_multiplier);
+        let Some((yes_asks, no_asks)) = book else {
+            return action;
+        };
+        if !self.edge_has_depth_at_fee(yes_asks, no_asks, min_edge_bps, fee_per_leg_bps) {
+            debug!("🪤 Skipping thin arbitrage on {}", market.question);
+            return TradeAction::None;
+        }
+        action
+    }
+
+    /// `edge_has_depth` for a pair that has to clear `required_bps` below 1
+    fn depth_within_edge(
+        &self,
+        yes_asks: &[(f64, f64)],
+        no_asks: &[(f64, f64)],
+        required_bps: i32,
+    ) -> bool {
+        let min_depth = self.config.min_depth_usd;
+        if min_depth <= 0.0 {
+            return true;
+        }
+        let best = |asks: &[(f64, f64)]| asks.iter().map(|(price, _)| *price).reduce(f64::min);
+        let (Some(best_yes), Some(best_no)) = (best(yes_asks), best(no_asks)) else {
+            return false;
+        };
+        let required = required_bps as f64 / 10000.0;
+        let notional_below = |asks: &[(f64, f64)], limit: f64| -> f64 {
+            asks.iter()
+                .filter(|(price, _)| *price <= limit + 1e-9)
+                .map(|(price, size)| price * size)
+                .sum()
+        };
+        let yes_depth = notional_below(yes_asks, 1.0 - best_no - required);
+        let no_depth = notional_below(no_asks, 1.0 - best_yes - required);
+        if yes_depth < min_depth || no_depth < min_depth {
+            debug!(
+                "🪤 Edge too thin: ${:.2} YES / ${:.2} NO within it (min ${:.2})",
+                yes_depth, no_depth, min_depth
+            );
+            return false;
+        }
+        true
+    }
+
     /// Calculate optimal position size based on edge and risk parameters
-    /// Optimized with const capital and inline hint
+    /// Sized off `sizing_capital` (the portfolio value in compounding mode)
     #[inline(always)]
     fn calculate_position_size(
         &self,
@@ -139,11 +308,10 @@ impl ArbitrageStrategy {
         };
 
         // Dynamic sizing using Kelly Criterion
-        const CAPITAL: f64 = 1000.0; // Const for compiler optimization
         let win_prob = estimate_win_probability(is_atomic, slippage_bps);
         let volatility = estimate_volatility(market_id);
 
-        sizer.calculate_optimal_size(edge_bps, win_prob, CAPITAL, volatility)
+        sizer.calculate_optimal_size(edge_bps, win_prob, self.capital, volatility)
     }
 
     /// Analyze full orderbook depth for a given order size
@@ -253,12 +421,24 @@ impl ArbitrageStrategy {
         let total_slippage = yes_depth.slippage_bps + no_depth.slippage_bps;
         let net_edge_bps = spread_bps - total_slippage;
 
-        if net_edge_bps > self.config.min_edge_bps {
+        let levels = |book: &OrderBook| -> Vec<(f64, f64)> {
+            book.ask_levels()
+                .iter()
+                .map(|l| (l.price, l.size))
+                .collect()
+        };
+        if net_edge_bps > self.config.min_edge_bps
+            && self.depth_within_edge(
+                &levels(yes_orderbook),
+                &levels(no_orderbook),
+                self.config.min_edge_bps,
+            )
+        {
             return TradeAction::BuyBoth {
-                market_id: market_id.to_string(),
-                yes_price: yes_ask,
-                no_price: no_ask,
-                size_usd: order_size_usd,
+                market_id: MarketId::from(market_id),
+                yes_price: Price::new(yes_ask),
+                no_price: Price::new(no_ask),
+                size_usd: UsdAmount::new(order_size_usd),
                 expected_profit_bps: net_edge_bps,
             };
         }
@@ -272,3 +452,90 @@ impl ArbitrageStrategy {
         depth.slippage_bps
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(sizing_mode: SizingMode) -> ArbitrageStrategy {
+        ArbitrageStrategy::new(
+            ArbitrageConfig {
+                enabled: true,
+                min_edge_bps: 20,
+                max_position_size_usd: 10.0,
+                sizing_mode,
+                kelly_fraction: 0.25,
+                min_position_pct: 0.01,
+                max_position_pct: 0.10,
+                capital_reserve_fraction: 0.5,
+                min_depth_usd: 50.0,
+            },
+            1000.0,
+        )
+    }
+
+    #[test]
+    fn test_compounding_sizes_from_portfolio_value() {
+        let mut fixed = strategy(SizingMode::Fixed);
+        fixed.set_portfolio_value(5000.0);
+        assert_eq!(fixed.calculate_position_size(300, "m1", 0, true), 10.0);
+
+        let mut kelly = strategy(SizingMode::Kelly);
+        let base = kelly.calculate_position_size(300, "m1", 0, true);
+        kelly.set_portfolio_value(5000.0);
+        assert_eq!(kelly.calculate_position_size(300, "m1", 0, true), base);
+
+        // Half of $4000 kept in reserve: twice the base capital
+        let mut compounding = strategy(SizingMode::Compounding);
+        compounding.set_portfolio_value(4000.0);
+        assert_eq!(compounding.sizing_capital(), 2000.0);
+        let grown = compounding.calculate_position_size(300, "m1", 0, true);
+        assert!(grown > base);
+        assert!((grown - 2.0 * base).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fee_free_markets_keep_edges_under_the_fees() {
+        let strategy = strategy(SizingMode::Fixed);
+        let market = MarketData {
+            id: "m1".into(),
+            question: "Will it happen?".to_string(),
+            yes_price: 0.49,
+            no_price: 0.50,
+            ..Default::default()
+        };
+        // 100 bps under 1: 20 left after two 40 bps fees, not above the 20 bps minimum
+        assert!(matches!(
+            strategy.check_opportunity_with_edge(&market, 20),
+            TradeAction::None
+        ));
+        match strategy.check_opportunity_at_fee(&market, 20, 0) {
+            TradeAction::BuyBoth {
+                expected_profit_bps,
+                ..
+            } => assert_eq!(expected_profit_bps, 100),
+            other => panic!("expected an arbitrage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_edge_needs_depth_on_both_sides() {
+        let strategy = strategy(SizingMode::Fixed);
+        // Against NO at 0.45, YES asks up to 0.54 clear 20 bps + fees (and the other way round)
+        let deep_no = [(0.45, 200.0)];
+        // $4.50 at the edge, the rest above it
+        let bait_yes = [(0.45, 10.0), (0.60, 1000.0)];
+        assert!(!strategy.edge_has_depth(&bait_yes, &deep_no, 20));
+
+        // Size priced worse than the best ask still counts while inside the edge
+        let layered_yes = [(0.45, 10.0), (0.50, 100.0), (0.56, 1000.0)];
+        assert!(strategy.edge_has_depth(&layered_yes, &deep_no, 20));
+        // A higher required edge pulls the limit below the second level
+        assert!(!strategy.edge_has_depth(&layered_yes, &deep_no, 700));
+
+        assert!(!strategy.edge_has_depth(&[], &deep_no, 20));
+        let mut off = strategy;
+        off.config.min_depth_usd = 0.0;
+        assert!(off.edge_has_depth(&bait_yes, &deep_no, 20));
+    }
+}
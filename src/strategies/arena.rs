@@ -54,16 +54,17 @@ impl Default for StrategyArena {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ids::{Price, UsdAmount};
 
     #[test]
     fn test_arena_allocation() {
         let arena = StrategyArena::new();
 
         let action = TradeAction::BuyBoth {
-            market_id: "test".to_string(),
-            yes_price: 0.4,
-            no_price: 0.4,
-            size_usd: 10.0,
+            market_id: "test".into(),
+            yes_price: Price::new(0.4),
+            no_price: Price::new(0.4),
+            size_usd: UsdAmount::new(10.0),
             expected_profit_bps: 2000,
         };
 
@@ -0,0 +1,477 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+use crate::config::CrossVenueConfig;
+use crate::ids::{Price, UsdAmount};
+use crate::polymarket::MarketData;
+use crate::strategies::arbitrage::FEE_PER_TRADE_BPS;
+use crate::venues::{compare, Venue, VenuePair};
+
+pub const CROSS_VENUE_FILE: &str = "cross_venue_positions.json";
+
+/// Risk manager key of a Kalshi leg: its ticker behind this prefix, never a condition id
+pub const KALSHI_POSITION_PREFIX: &str = "kalshi:";
+
+/// Risk manager key of the Kalshi leg on `ticker`
+pub fn kalshi_position_key(ticker: &str) -> String {
+    format!("{}{}", KALSHI_POSITION_PREFIX, ticker)
+}
+
+/// Whether a risk manager key is a Kalshi leg's: nothing to redeem or hold collateral for
+pub fn is_kalshi_position(market_id: &str) -> bool {
+    market_id.starts_with(KALSHI_POSITION_PREFIX)
+}
+
+/// Kalshi's taker fee: 0.07 x contracts x P x (1 - P), rounded up to the cent
+const KALSHI_FEE_RATE: f64 = 0.07;
+
+/// Kalshi's fee on buying `contracts` at `price`
+pub fn kalshi_fee(contracts: f64, price: f64) -> f64 {
+    // Float error must not round an exact cent up to the next one
+    let cents = KALSHI_FEE_RATE * contracts * price * (1.0 - price) * 100.0;
+    (cents - 1e-9).ceil().max(0.0) / 100.0
+}
+
+/// Polymarket's fee on buying `contracts` at `price` (the flat rate arbitrage assumes)
+pub fn polymarket_fee(contracts: f64, price: f64) -> f64 {
+    contracts * price * FEE_PER_TRADE_BPS as f64 / 10_000.0
+}
+
+fn venue_fee(venue: Venue, contracts: f64, price: f64) -> f64 {
+    match venue {
+        Venue::Kalshi => kalshi_fee(contracts, price),
+        Venue::Polymarket | Venue::Simulation => polymarket_fee(contracts, price),
+    }
+}
+
+/// A complete set priced below 1 after fees: YES on one venue, NO on the other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossVenueSignal {
+    pub pair: VenuePair,
+    pub question: String,
+    pub buy_yes_on: Venue,
+    /// Outcome asks in Polymarket's orientation
+    pub yes_price: f64,
+    pub no_price: f64,
+    /// Whole contracts (Kalshi doesn't trade fractions), the same on both legs
+    pub contracts: f64,
+    pub cost_usd: f64,
+    pub fees_usd: f64,
+    /// Withdrawal / transfer cost of bringing the capital back together
+    pub friction_usd: f64,
+    /// Payout (1 per set) minus cost, fees and friction
+    pub net_profit_usd: f64,
+    pub net_edge_bps: i32,
+}
+
+impl CrossVenueSignal {
+    pub fn buy_no_on(&self) -> Venue {
+        match self.buy_yes_on {
+            Venue::Kalshi => Venue::Polymarket,
+            _ => Venue::Kalshi,
+        }
+    }
+
+    /// (side, price, notional) of the leg bought on `venue`
+    pub fn leg(&self, venue: Venue) -> (&'static str, Price, UsdAmount) {
+        let (side, price) = if venue == self.buy_yes_on {
+            ("YES", self.yes_price)
+        } else {
+            ("NO", self.no_price)
+        };
+        (
+            side,
+            Price::new(price),
+            UsdAmount::new(self.contracts * price),
+        )
+    }
+}
+
+/// An executed set, held to resolution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossVenuePosition {
+    pub id: String,
+    pub signal: CrossVenueSignal,
+    /// Order id per leg, None when the leg wasn't placed (one-sided position)
+    pub polymarket_order: Option<String>,
+    pub kalshi_order: Option<String>,
+    /// The opposite Kalshi outcome bought when the Polymarket leg failed, and its price
+    #[serde(default)]
+    pub kalshi_unwind: Option<(String, f64)>,
+    pub end_date: Option<DateTime<Utc>>,
+    pub opened_at: DateTime<Utc>,
+}
+
+impl CrossVenuePosition {
+    pub fn is_one_sided(&self) -> bool {
+        (self.polymarket_order.is_none() && self.kalshi_unwind.is_none())
+            || self.kalshi_order.is_none()
+    }
+
+    /// Open notional on `venue`
+    pub fn exposure(&self, venue: Venue) -> f64 {
+        let placed = match venue {
+            Venue::Kalshi => self.kalshi_order.is_some(),
+            _ => self.polymarket_order.is_some(),
+        };
+        let unwind = match venue {
+            Venue::Kalshi => self
+                .kalshi_unwind
+                .as_ref()
+                .map_or(0.0, |(_, price)| self.signal.contracts * price),
+            _ => 0.0,
+        };
+        if placed {
+            self.signal.leg(venue).2.get() + unwind
+        } else {
+            unwind
+        }
+    }
+
+    /// PnL tracker id of the leg bought on `venue`
+    pub fn leg_id(&self, venue: Venue) -> String {
+        format!("{}_{}", self.id, venue.name())
+    }
+
+    /// PnL tracker id of the Kalshi unwind
+    pub fn unwind_id(&self) -> String {
+        format!("{}_kalshi_unwind", self.id)
+    }
+
+    /// (tracker id, side in Polymarket's orientation, price, notional) of each leg held
+    pub fn legs(&self) -> Vec<(String, &'static str, f64, f64)> {
+        let mut legs = Vec::new();
+        for (venue, placed) in [
+            (Venue::Polymarket, self.polymarket_order.is_some()),
+            (Venue::Kalshi, self.kalshi_order.is_some()),
+        ] {
+            if placed {
+                let (side, price, size_usd) = self.signal.leg(venue);
+                legs.push((self.leg_id(venue), side, price.get(), size_usd.get()));
+            }
+        }
+        if let Some((_, price)) = &self.kalshi_unwind {
+            let (side, _, _) = self.signal.leg(Venue::Kalshi);
+            let opposite = if side == "YES" { "NO" } else { "YES" };
+            legs.push((
+                self.unwind_id(),
+                opposite,
+                *price,
+                self.signal.contracts * price,
+            ));
+        }
+        legs
+    }
+}
+
+/// Arbitrage of one question listed on Polymarket and Kalshi: buys the cheaper complete set
+/// across venues when it still pays after both venues' fees and the cost of moving capital,
+/// within per-venue exposure limits. One open position per pair, held to resolution
+#[derive(Clone)]
+pub struct CrossVenueStrategy {
+    config: CrossVenueConfig,
+    pairs: Vec<VenuePair>,
+    positions: Vec<CrossVenuePosition>,
+    path: Option<PathBuf>,
+}
+
+impl CrossVenueStrategy {
+    pub fn new(config: CrossVenueConfig, pairs: Vec<VenuePair>) -> Self {
+        Self {
+            config,
+            pairs,
+            positions: Vec::new(),
+            path: None,
+        }
+    }
+
+    /// Strategy with the positions a previous run left open in `data_dir`
+    pub fn load(config: CrossVenueConfig, pairs: Vec<VenuePair>, data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(CROSS_VENUE_FILE);
+        let positions = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            path: Some(path),
+            positions,
+            ..Self::new(config, pairs)
+        }
+    }
+
+    /// Copy that prices against the current positions and never writes the file
+    pub fn snapshot(&self) -> Self {
+        Self {
+            path: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.positions)?)?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled && !self.pairs.is_empty()
+    }
+
+    pub fn pairs(&self) -> &[VenuePair] {
+        &self.pairs
+    }
+
+    pub fn positions(&self) -> &[CrossVenuePosition] {
+        &self.positions
+    }
+
+    pub fn has_position(&self, pair: &VenuePair) -> bool {
+        self.positions
+            .iter()
+            .any(|p| p.signal.pair.polymarket_id == pair.polymarket_id)
+    }
+
+    /// Open cross-venue notional on `venue`
+    pub fn exposure(&self, venue: Venue) -> f64 {
+        self.positions.iter().map(|p| p.exposure(venue)).sum()
+    }
+
+    fn limit(&self, venue: Venue) -> f64 {
+        match venue {
+            Venue::Kalshi => self.config.max_kalshi_usd,
+            Venue::Polymarket | Venue::Simulation => self.config.max_polymarket_usd,
+        }
+    }
+
+    /// Price the cheapest cross-venue set of a pair. `kalshi` must already be aligned to
+    /// Polymarket's orientation (`VenuePair::align`)
+    pub fn evaluate(
+        &self,
+        pair: &VenuePair,
+        polymarket: &MarketData,
+        kalshi: &MarketData,
+    ) -> Option<CrossVenueSignal> {
+        if !self.config.enabled || self.has_position(pair) {
+            return None;
+        }
+        let gap = compare(Venue::Polymarket, polymarket, Venue::Kalshi, kalshi)?;
+        let (yes_market, no_market) = if gap.buy_yes_on == Venue::Polymarket {
+            (polymarket, kalshi)
+        } else {
+            (kalshi, polymarket)
+        };
+        let yes_price = yes_market.ask("YES")?;
+        let no_price = no_market.ask("NO")?;
+        let buy_no_on = match gap.buy_yes_on {
+            Venue::Kalshi => Venue::Polymarket,
+            _ => Venue::Kalshi,
+        };
+
+        // Target size, capped by what each venue's limit has left for its leg
+        let headroom =
+            |venue: Venue, price: f64| (self.limit(venue) - self.exposure(venue)).max(0.0) / price;
+        let contracts = (self.config.size_usd / gap.cross_cost)
+            .min(headroom(gap.buy_yes_on, yes_price))
+            .min(headroom(buy_no_on, no_price))
+            .floor();
+        if contracts < 1.0 {
+            debug!(
+                "Cross-venue {} / {}: no room under the venue limits",
+                pair.polymarket_id, pair.kalshi_ticker
+            );
+            return None;
+        }
+
+        let cost_usd = contracts * gap.cross_cost;
+        let fees_usd = venue_fee(gap.buy_yes_on, contracts, yes_price)
+            + venue_fee(buy_no_on, contracts, no_price);
+        let friction_usd =
+            cost_usd * self.config.withdrawal_bps / 10_000.0 + self.config.withdrawal_usd;
+        let net_profit_usd = contracts - cost_usd - fees_usd - friction_usd;
+        let net_edge_bps = (net_profit_usd / cost_usd * 10_000.0) as i32;
+        if net_edge_bps < self.config.min_net_edge_bps {
+            debug!(
+                "Cross-venue {} / {}: gross {:.0} bps, net {} bps (< {} bps)",
+                pair.polymarket_id,
+                pair.kalshi_ticker,
+                gap.edge_bps,
+                net_edge_bps,
+                self.config.min_net_edge_bps
+            );
+            return None;
+        }
+
+        Some(CrossVenueSignal {
+            pair: pair.clone(),
+            question: polymarket.question.clone(),
+            buy_yes_on: gap.buy_yes_on,
+            yes_price,
+            no_price,
+            contracts,
+            cost_usd,
+            fees_usd,
+            friction_usd,
+            net_profit_usd,
+            net_edge_bps,
+        })
+    }
+
+    pub fn open(&mut self, position: CrossVenuePosition) {
+        self.positions.push(position);
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to persist cross-venue positions: {}", e);
+        }
+    }
+
+    /// Positions whose market has ended: they are closed once the resolution is booked
+    pub fn ended(&self, now: DateTime<Utc>) -> Vec<CrossVenuePosition> {
+        self.positions
+            .iter()
+            .filter(|p| p.end_date.is_some_and(|end| end <= now))
+            .cloned()
+            .collect()
+    }
+
+    /// Drop a position whose result is booked, which frees the venue limits again
+    pub fn close(&mut self, id: &str) -> Option<CrossVenuePosition> {
+        let index = self.positions.iter().position(|p| p.id == id)?;
+        let position = self.positions.remove(index);
+        info!(
+            "🏁 Cross-venue position {} closed ({} sets, expected ${:.2})",
+            position.id, position.signal.contracts, position.signal.net_profit_usd
+        );
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to persist cross-venue positions: {}", e);
+        }
+        Some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(id: &str, yes_price: f64, no_price: f64) -> MarketData {
+        MarketData {
+            id: id.into(),
+            question: "Fed cuts in December?".to_string(),
+            yes_price,
+            no_price,
+            ..Default::default()
+        }
+    }
+
+    fn config() -> CrossVenueConfig {
+        CrossVenueConfig {
+            enabled: true,
+            poll_interval_secs: 30,
+            min_net_edge_bps: 100,
+            size_usd: 90.0,
+            withdrawal_bps: 25.0,
+            withdrawal_usd: 0.0,
+            max_polymarket_usd: 1000.0,
+            max_kalshi_usd: 60.0,
+        }
+    }
+
+    #[test]
+    fn test_net_edge_and_venue_limits() {
+        let pair = VenuePair {
+            polymarket_id: "0xabc".to_string(),
+            kalshi_ticker: "KX-1".to_string(),
+            inverted: false,
+        };
+        let mut strategy = CrossVenueStrategy::new(config(), vec![pair.clone()]);
+
+        // YES 0.40 on Polymarket + NO 0.50 on Kalshi = 0.90 per set
+        let polymarket = market("0xabc", 0.40, 0.62);
+        let kalshi = market("KX-1", 0.52, 0.50);
+        let signal = strategy.evaluate(&pair, &polymarket, &kalshi).unwrap();
+        assert_eq!(signal.buy_yes_on, Venue::Polymarket);
+        // 90 / 0.90 = 100 sets, but the Kalshi leg only has $60 of room: 120 at 0.50 -> 100
+        assert_eq!(signal.contracts, 100.0);
+        // Polymarket 0.4% of $40, Kalshi ceil(0.07 x 100 x 0.25) = $1.75
+        assert!((signal.fees_usd - (0.16 + 1.75)).abs() < 1e-9);
+        assert!((signal.friction_usd - 0.225).abs() < 1e-9);
+        assert!((signal.net_profit_usd - (10.0 - 0.16 - 1.75 - 0.225)).abs() < 1e-9);
+        assert_eq!(
+            signal.leg(Venue::Kalshi),
+            ("NO", Price::new(0.50), UsdAmount::new(50.0))
+        );
+
+        strategy.open(CrossVenuePosition {
+            id: "xv_1".to_string(),
+            signal,
+            polymarket_order: Some("pm".to_string()),
+            kalshi_order: Some("k".to_string()),
+            kalshi_unwind: None,
+            end_date: Some(Utc::now()),
+            opened_at: Utc::now(),
+        });
+        assert!((strategy.exposure(Venue::Kalshi) - 50.0).abs() < 1e-9);
+        // One position per pair
+        assert!(strategy.evaluate(&pair, &polymarket, &kalshi).is_none());
+
+        // Fees eat a thin gap: 0.49 + 0.50 leaves 1 cent per set
+        let other = VenuePair {
+            polymarket_id: "0xdef".to_string(),
+            ..pair.clone()
+        };
+        let thin = market("0xdef", 0.49, 0.52);
+        assert!(strategy.evaluate(&other, &thin, &kalshi).is_none());
+
+        let ended = strategy.ended(Utc::now());
+        assert_eq!(ended.len(), 1);
+        let legs = ended[0].legs();
+        assert_eq!(legs.len(), 2);
+        assert_eq!((legs[1].0.as_str(), legs[1].1), ("xv_1_kalshi", "NO"));
+        assert!(strategy.close("xv_1").is_some());
+        assert_eq!(strategy.exposure(Venue::Kalshi), 0.0);
+    }
+
+    #[test]
+    fn test_unwound_kalshi_leg_holds_both_outcomes() {
+        let pair = VenuePair {
+            polymarket_id: "0xabc".to_string(),
+            kalshi_ticker: "KX-1".to_string(),
+            inverted: false,
+        };
+        let strategy = CrossVenueStrategy::new(config(), vec![pair.clone()]);
+        let signal = strategy
+            .evaluate(
+                &pair,
+                &market("0xabc", 0.40, 0.62),
+                &market("KX-1", 0.52, 0.50),
+            )
+            .unwrap();
+        let position = CrossVenuePosition {
+            id: "xv_2".to_string(),
+            signal,
+            polymarket_order: None,
+            kalshi_order: Some("k".to_string()),
+            kalshi_unwind: Some(("k2".to_string(), 0.55)),
+            end_date: None,
+            opened_at: Utc::now(),
+        };
+        assert!(!position.is_one_sided());
+        // 100 NO @ 0.50 and 100 YES @ 0.55 on Kalshi, nothing on Polymarket
+        assert!((position.exposure(Venue::Kalshi) - 105.0).abs() < 1e-9);
+        assert_eq!(position.exposure(Venue::Polymarket), 0.0);
+        let sides: Vec<&str> = position.legs().iter().map(|leg| leg.1).collect();
+        assert_eq!(sides, ["NO", "YES"]);
+    }
+}
@@ -0,0 +1,45 @@
+//! Strategies supplied by a project embedding the agent (`AgentBuilder::strategy`)
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::polymarket::MarketData;
+use crate::strategies::arbitrage::{ArbitrageStrategy, TradeAction};
+use crate::strategies::expiration::ExpirationStrategy;
+
+/// A signal source run on every market evaluation, after the built-in strategies and only
+/// when none of them signalled. `TradeAction::Snipe` and `TradeAction::BuyBoth` go through
+/// the same freshness, balance, risk and capital checks and the same execution as theirs
+#[async_trait]
+pub trait Strategy: Send + Sync {
+    /// Short name without underscores: the decision log label and the prefix of the trade
+    /// ids, which attribute PnL and capital (`STRATEGY_CAPITAL_PCT`) to the strategy
+    fn name(&self) -> &str;
+
+    /// Decide on a market's current quotes (`yes_price` / `no_price` are the best asks)
+    async fn evaluate(&self, market: &MarketData, now: DateTime<Utc>) -> TradeAction;
+}
+
+// The built-in strategies that decide on quotes alone, so replays (`simulation::latency`) can
+// run them next to custom ones
+
+#[async_trait]
+impl Strategy for ArbitrageStrategy {
+    fn name(&self) -> &str {
+        "arbitrage"
+    }
+
+    async fn evaluate(&self, market: &MarketData, _now: DateTime<Utc>) -> TradeAction {
+        self.check_opportunity(market)
+    }
+}
+
+#[async_trait]
+impl Strategy for ExpirationStrategy {
+    fn name(&self) -> &str {
+        "expiration"
+    }
+
+    async fn evaluate(&self, market: &MarketData, now: DateTime<Utc>) -> TradeAction {
+        self.check_opportunity_at(market, now)
+    }
+}
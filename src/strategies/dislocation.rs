@@ -0,0 +1,198 @@
+use crate::config::DislocationConfig;
+use crate::ids::{Price, UsdAmount};
+use crate::polymarket::MarketData;
+use crate::strategies::arbitrage::TradeAction;
+use tracing::{debug, info};
+
+/// Directional single-leg entries when one outcome trades well below its fair value
+/// Unlike arbitrage this still fires when YES + NO >= 1: only the cheap side is bought
+pub struct DislocationStrategy {
+    config: DislocationConfig,
+}
+
+impl DislocationStrategy {
+    pub fn new(config: DislocationConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn annual_volatility(&self) -> f64 {
+        self.config.annual_volatility
+    }
+
+    /// Compare each side's ask to a fair value and snipe the most underpriced one, without
+    /// the books' bids (see `check_opportunity_with_bids`)
+    pub fn check_opportunity(&self, market: &MarketData, fair_yes: Option<f64>) -> TradeAction {
+        self.check_opportunity_with_bids(market, fair_yes, None, None)
+    }
+
+    /// Compare each side's ask to a fair value and snipe the most underpriced one.
+    /// `fair_yes` is an external YES probability (e.g. from Binance for strike markets).
+    /// Without it the complementary side is used: fair YES = 1 - NO mid, fair NO = 1 - YES mid,
+    /// from the outcome's best bid when known. A side's ask alone is no mid: 1 - NO ask can only
+    /// beat the YES ask when YES + NO < 1, which is arbitrage
+    pub fn check_opportunity_with_bids(
+        &self,
+        market: &MarketData,
+        fair_yes: Option<f64>,
+        yes_bid: Option<f64>,
+        no_bid: Option<f64>,
+    ) -> TradeAction {
+        if !self.config.enabled {
+            return TradeAction::None;
+        }
+
+        let (fair_yes, fair_no) = match fair_yes {
+            Some(p) if (0.0..=1.0).contains(&p) => (p, 1.0 - p),
+            Some(_) => return TradeAction::None,
+            // The complement needs both sides quoted
+            None => {
+                if !market.quote_quality().is_complete() {
+                    return TradeAction::None;
+                }
+                (
+                    1.0 - mid(market.no_price, no_bid),
+                    1.0 - mid(market.yes_price, yes_bid),
+                )
+            }
+        };
+
+        let candidates = [("YES", fair_yes), ("NO", fair_no)];
+        let best = candidates
+            .iter()
+            .filter_map(|(side, fair)| market.ask(side).map(|price| (side, price, fair)))
+            .filter(|(_, price, _)| *price <= self.config.max_entry_price)
+            .map(|(side, price, fair)| (*side, *price, *fair, ((fair - price) * 10000.0) as i32))
+            .max_by_key(|(_, _, _, bps)| *bps);
+
+        let Some((side, price, fair, dislocation_bps)) = best else {
+            return TradeAction::None;
+        };
+        if dislocation_bps < self.config.min_dislocation_bps {
+            debug!(
+                "No dislocation on {}: best {} {} bps (< {} bps)",
+                market.question, side, dislocation_bps, self.config.min_dislocation_bps
+            );
+            return TradeAction::None;
+        }
+
+        info!(
+            "📐 DISLOCATION SIGNAL ({}): {} | Ask {:.4} vs Fair {:.4} | {} bps | Pair sum {:.4}",
+            side,
+            market.question,
+            price,
+            fair,
+            dislocation_bps,
+            market.yes_price + market.no_price
+        );
+        TradeAction::Snipe {
+            market_id: market.id.clone(),
+            side: side.to_string(),
+            price: Price::new(price),
+            size_usd: UsdAmount::new(1.0),
+        }
+    }
+}
+
+/// Mid of an outcome's book, its ask when the bid is unknown or crossed
+fn mid(ask: f64, bid: Option<f64>) -> f64 {
+    match bid {
+        Some(bid) if bid > 0.0 && bid < ask => (bid + ask) / 2.0,
+        _ => ask,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(yes_price: f64, no_price: f64) -> MarketData {
+        MarketData {
+            id: "m1".into(),
+            question: "Bitcoin above $100,000 at 5:00 PM ET?".to_string(),
+            yes_price,
+            no_price,
+            ..Default::default()
+        }
+    }
+
+    fn strategy() -> DislocationStrategy {
+        DislocationStrategy::new(DislocationConfig {
+            enabled: true,
+            min_dislocation_bps: 500,
+            max_entry_price: 0.90,
+            annual_volatility: 0.6,
+        })
+    }
+
+    #[test]
+    fn test_buys_cheap_side_even_when_pair_is_overpriced() {
+        // 0.30 + 0.75 = 1.05, no arbitrage, but YES is worth ~0.45
+        match strategy().check_opportunity(&market(0.30, 0.75), Some(0.45)) {
+            TradeAction::Snipe { side, price, .. } => {
+                assert_eq!(side, "YES");
+                assert_eq!(price, Price::new(0.30));
+            }
+            other => panic!("expected a YES snipe, got {:?}", other),
+        }
+
+        // Fair value in line with the book: nothing to do
+        assert!(matches!(
+            strategy().check_opportunity(&market(0.30, 0.75), Some(0.28)),
+            TradeAction::None
+        ));
+    }
+
+    #[test]
+    fn test_complementary_fair_value() {
+        // NO ask 0.60 implies YES is worth 0.40, YES ask 0.30 is 1000 bps cheap
+        assert!(matches!(
+            strategy().check_opportunity(&market(0.30, 0.60), None),
+            TradeAction::Snipe { ref side, .. } if side == "YES"
+        ));
+        assert!(matches!(
+            strategy().check_opportunity(&market(0.45, 0.58), None),
+            TradeAction::None
+        ));
+    }
+
+    #[test]
+    fn test_complement_fires_when_the_pair_is_overpriced() {
+        // 0.40 + 0.70 = 1.10: asks alone show nothing
+        let overpriced = market(0.40, 0.70);
+        assert!(matches!(
+            strategy().check_opportunity(&overpriced, None),
+            TradeAction::None
+        ));
+        // NO bid at 0.30: NO trades around 0.50, so YES is worth 0.50 and 0.40 is cheap
+        match strategy().check_opportunity_with_bids(&overpriced, None, Some(0.38), Some(0.30)) {
+            TradeAction::Snipe { side, price, .. } => {
+                assert_eq!(side, "YES");
+                assert_eq!(price, Price::new(0.40));
+            }
+            other => panic!("expected a YES snipe, got {:?}", other),
+        }
+        // A tight NO book agrees with the YES ask
+        assert!(matches!(
+            strategy().check_opportunity_with_bids(&overpriced, None, Some(0.38), Some(0.68)),
+            TradeAction::None
+        ));
+    }
+
+    #[test]
+    fn test_missing_side_is_not_a_price() {
+        // No NO asks: the complement is unknown, and a 0 ask is never a bargain
+        assert!(matches!(
+            strategy().check_opportunity(&market(0.30, 0.0), None),
+            TradeAction::None
+        ));
+        // With a fair value, only the quoted side is a candidate
+        assert!(matches!(
+            strategy().check_opportunity(&market(0.0, 0.40), Some(0.45)),
+            TradeAction::Snipe { ref side, .. } if side == "NO"
+        ));
+    }
+}
@@ -0,0 +1,261 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+use crate::config::EdgeTuningConfig;
+pub use crate::markets::MarketCategory;
+use crate::polymarket::{MarketData, MarketInterface, OrderStatus};
+
+pub const EDGE_ADJUSTMENTS_FILE: &str = "edge_adjustments.json";
+
+/// Weight of the newest observation in the slippage EWMA
+const SLIPPAGE_EWMA_ALPHA: f64 = 0.2;
+
+/// Outcome of one submitted order, checked shortly after placement
+#[derive(Debug, Clone, Copy)]
+pub struct FillObservation {
+    pub filled: bool,
+    /// Filled: how far the price paid was above the ask the signal saw. Missed: how far the
+    /// market moved past our limit (bps)
+    pub slippage_bps: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CategoryStats {
+    pub attempts: u64,
+    pub fills: u64,
+    pub ewma_slippage_bps: f64,
+    pub adjustment_bps: i32,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl CategoryStats {
+    pub fn fill_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            1.0
+        } else {
+            self.fills as f64 / self.attempts as f64
+        }
+    }
+}
+
+/// Learns per-category edge requirements from realized fill quality
+/// Thin markets that slip or miss fills get a higher effective min_edge_bps
+pub struct EdgeTuner {
+    config: EdgeTuningConfig,
+    path: PathBuf,
+    stats: HashMap<MarketCategory, CategoryStats>,
+}
+
+impl EdgeTuner {
+    /// Load previously learned adjustments from the data dir (fresh state if none)
+    pub fn load(config: EdgeTuningConfig, data_dir: &str) -> Self {
+        let path = edge_adjustments_path(data_dir);
+        let stats = match load_adjustments(data_dir) {
+            Ok(stats) => {
+                info!(
+                    "📐 Loaded edge adjustments for {} categories from {:?}",
+                    stats.len(),
+                    path
+                );
+                stats
+            }
+            Err(e) => {
+                debug!("No edge adjustments loaded ({}), starting fresh", e);
+                HashMap::new()
+            }
+        };
+
+        Self {
+            config,
+            path,
+            stats,
+        }
+    }
+
+    /// Effective min edge for a market: base threshold plus its category's learned adjustment
+    pub fn effective_min_edge(&self, base_bps: i32, market: &MarketData) -> i32 {
+        let category = MarketCategory::of(market);
+        base_bps
+            + self
+                .stats
+                .get(&category)
+                .map(|s| s.adjustment_bps)
+                .unwrap_or(0)
+    }
+
+    pub fn record(&mut self, market: &MarketData, observation: FillObservation) {
+        let category = MarketCategory::of(market);
+        let stats = self.stats.entry(category).or_default();
+
+        stats.attempts += 1;
+        if observation.filled {
+            stats.fills += 1;
+        }
+        stats.ewma_slippage_bps = if stats.attempts == 1 {
+            observation.slippage_bps
+        } else {
+            SLIPPAGE_EWMA_ALPHA * observation.slippage_bps
+                + (1.0 - SLIPPAGE_EWMA_ALPHA) * stats.ewma_slippage_bps
+        };
+        stats.updated_at = Some(Utc::now());
+
+        let previous = stats.adjustment_bps;
+        stats.adjustment_bps = if stats.attempts < self.config.min_samples {
+            0
+        } else {
+            let raw = stats.ewma_slippage_bps
+                + (1.0 - stats.fill_rate()) * self.config.fill_penalty_bps as f64;
+            (raw.round() as i32).clamp(0, self.config.max_adjust_bps)
+        };
+
+        if stats.adjustment_bps != previous {
+            info!(
+                "📐 {:?} min_edge adjustment {} -> {} bps (fill rate {:.0}%, slippage {:.1} bps, n={})",
+                category,
+                previous,
+                stats.adjustment_bps,
+                stats.fill_rate() * 100.0,
+                stats.ewma_slippage_bps,
+                stats.attempts
+            );
+        }
+
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to persist edge adjustments: {}", e);
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.stats)?)
+            .with_context(|| format!("Failed to write {:?}", self.path))
+    }
+}
+
+/// Check how an order did shortly after placement. `market` is as the signal saw it and
+/// `limit_price` what the order paid at most (market orders: the price booked)
+/// Filled orders are compared against the signal's ask, unfilled ones against the current best
+/// ask to measure how far price ran away
+pub async fn observe_fill(
+    market_interface: &(dyn MarketInterface + Send + Sync),
+    market: &MarketData,
+    side: &str,
+    order_id: &str,
+    limit_price: f64,
+) -> FillObservation {
+    let status = market_interface
+        .get_order_status(order_id)
+        .await
+        .unwrap_or(OrderStatus::Unknown);
+    if matches!(status, OrderStatus::Matched) {
+        return FillObservation {
+            filled: true,
+            slippage_bps: market
+                .ask(side)
+                .map_or(0.0, |ask| slippage_bps(limit_price, ask)),
+        };
+    }
+
+    // asset_ids follow the [NO, YES] convention
+    let asset_id = match side {
+        "YES" => market.asset_ids.get(1),
+        _ => market.asset_ids.first(),
+    };
+    let best_ask = match asset_id {
+        Some(asset_id) => market_interface
+            .get_orderbook(asset_id)
+            .await
+            .ok()
+            .and_then(|book| book.best_ask()),
+        None => None,
+    };
+    FillObservation {
+        filled: false,
+        slippage_bps: best_ask.map_or(0.0, |ask| slippage_bps(ask, limit_price)),
+    }
+}
+
+/// How far `price` is above `reference`, in bps of it (0 when not above)
+fn slippage_bps(price: f64, reference: f64) -> f64 {
+    if reference > 0.0 {
+        ((price - reference) / reference * 10_000.0).max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// Path of the learned adjustments inside a data dir
+pub fn edge_adjustments_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(EDGE_ADJUSTMENTS_FILE)
+}
+
+pub fn load_adjustments(data_dir: &str) -> Result<HashMap<MarketCategory, CategoryStats>> {
+    let path = edge_adjustments_path(data_dir);
+    let raw = fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&raw).context("Invalid edge adjustments file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuner() -> EdgeTuner {
+        let dir = std::env::temp_dir().join(format!("edge-tuning-{}", uuid::Uuid::new_v4()));
+        let config = EdgeTuningConfig {
+            enabled: true,
+            max_adjust_bps: 200,
+            fill_penalty_bps: 100,
+            min_samples: 4,
+        };
+        EdgeTuner::load(config, dir.to_str().unwrap())
+    }
+
+    #[test]
+    fn test_slippage_of_fills_and_misses() {
+        // A market order paid 0.51 for a 0.50 ask; a limit at the ask paid none
+        assert!((slippage_bps(0.51, 0.50) - 200.0).abs() < 1e-6);
+        assert_eq!(slippage_bps(0.50, 0.50), 0.0);
+        // Filled below the ask, or no ask to compare with
+        assert_eq!(slippage_bps(0.49, 0.50), 0.0);
+        assert_eq!(slippage_bps(0.51, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_adjustment_from_slippage_and_misses() {
+        let mut tuner = tuner();
+        let market = MarketData::quoted("Will the Lakers win the NBA Finals", 0.5, 0.5);
+        let fill = |slippage_bps| FillObservation {
+            filled: true,
+            slippage_bps,
+        };
+
+        for _ in 0..3 {
+            tuner.record(&market, fill(40.0));
+        }
+        // Under min_samples: no adjustment yet
+        assert_eq!(tuner.effective_min_edge(50, &market), 50);
+
+        tuner.record(
+            &market,
+            FillObservation {
+                filled: false,
+                slippage_bps: 40.0,
+            },
+        );
+        // 40 bps of slippage plus a quarter of the 100 bps miss penalty
+        assert_eq!(tuner.effective_min_edge(50, &market), 50 + 65);
+
+        // Other categories keep the base threshold
+        let other = MarketData::quoted("Bitcoin above $100,000 on Friday", 0.5, 0.5);
+        assert_ne!(MarketCategory::of(&other), MarketCategory::of(&market));
+        assert_eq!(tuner.effective_min_edge(50, &other), 50);
+        let _ = fs::remove_dir_all(tuner.path.parent().unwrap());
+    }
+}
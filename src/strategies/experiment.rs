@@ -0,0 +1,451 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::analytics::pnl::Position;
+use crate::analytics::PnLTracker;
+use crate::config::{Config, ExperimentConfig};
+use crate::polymarket::MarketData;
+use crate::pricefeed::BinanceClient;
+use crate::strategies::arbitrage::{ArbitrageStrategy, TradeAction};
+use crate::strategies::expiration::ExpirationStrategy;
+use crate::strategies::predictive::PredictiveStrategy;
+
+pub const EXPERIMENT_REPORT_FILE: &str = "experiment_report.json";
+
+/// Notional used for hypothetical arm positions so both arms are compared on the same scale
+const ARM_NOTIONAL_USD: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExperimentArm {
+    Control,
+    Treatment,
+}
+
+/// The treatment parameterization of the strategy under test
+enum TreatmentStrategy {
+    Arbitrage(ArbitrageStrategy),
+    Expiration(ExpirationStrategy),
+    Predictive(PredictiveStrategy),
+}
+
+/// Per-arm bookkeeping: hypothetical positions opened at signal prices
+struct ArmBook {
+    tracker: PnLTracker,
+    open_markets: HashSet<String>,
+    signals: usize,
+    live_signals: usize,
+}
+
+impl ArmBook {
+    fn new() -> Self {
+        Self {
+            tracker: PnLTracker::new(0.0),
+            open_markets: HashSet::new(),
+            signals: 0,
+            live_signals: 0,
+        }
+    }
+
+    fn record_signal(&mut self, market: &MarketData, action: &TradeAction, live: bool) {
+        self.signals += 1;
+        if live {
+            self.live_signals += 1;
+        }
+
+        // One hypothetical position per market, repeated ticks don't stack
+        if !self.open_markets.insert(market.id.to_string()) {
+            return;
+        }
+
+        let now = Utc::now();
+        let legs: Vec<(&str, f64, f64)> = match action {
+            TradeAction::BuyBoth {
+                yes_price,
+                no_price,
+                ..
+            } => vec![
+                ("YES", yes_price.get(), ARM_NOTIONAL_USD / 2.0),
+                ("NO", no_price.get(), ARM_NOTIONAL_USD / 2.0),
+            ],
+            TradeAction::Snipe { side, price, .. } => {
+                vec![(side.as_str(), price.get(), ARM_NOTIONAL_USD)]
+            }
+            TradeAction::None => return,
+        };
+
+        // Arbitrage legs are linked so they're marked and closed as one entry
+        let group_id =
+            (legs.len() > 1).then(|| format!("{}_{}", market.id, now.timestamp_millis()));
+        for (side, price, size) in legs {
+            self.tracker.add_position(Position {
+                id: format!("{}_{}_{}", market.id, side, now.timestamp_millis()),
+                market_id: market.id.to_string(),
+                market_question: market.question.clone(),
+                side: side.to_string(),
+                size,
+                entry_price: price,
+                current_price: price,
+                entry_time: now,
+                config_hash: None,
+                group_id: group_id.clone(),
+            });
+        }
+    }
+
+    /// Per-position returns (PnL / notional), used for the statistical comparison
+    fn returns(&self) -> Vec<f64> {
+        let open = self
+            .tracker
+            .positions
+            .values()
+            .filter(|p| p.size > 0.0)
+            .map(|p| p.unrealized_pnl() / p.size);
+        let closed = self
+            .tracker
+            .trades
+            .iter()
+            .filter(|t| t.size > 0.0)
+            .filter_map(|t| t.realized_pnl.map(|pnl| pnl / t.size));
+        open.chain(closed).collect()
+    }
+
+    fn summary(&self, arm: ExperimentArm) -> ArmSummary {
+        let returns = self.returns();
+        let (mean, std_dev) = mean_std(&returns);
+        ArmSummary {
+            arm,
+            signals: self.signals,
+            live_signals: self.live_signals,
+            positions: returns.len(),
+            total_pnl: self.tracker.calculate_total_pnl(),
+            mean_return: mean,
+            std_return: std_dev,
+            win_rate: if returns.is_empty() {
+                0.0
+            } else {
+                returns.iter().filter(|r| **r > 0.0).count() as f64 / returns.len() as f64
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArmSummary {
+    pub arm: ExperimentArm,
+    pub signals: usize,
+    pub live_signals: usize,
+    pub positions: usize,
+    pub total_pnl: f64,
+    pub mean_return: f64,
+    pub std_return: f64,
+    pub win_rate: f64,
+}
+
+/// Comparison of the two arms (Welch's t-test on per-position returns)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub generated_at: DateTime<Utc>,
+    pub strategy: String,
+    pub overrides: String,
+    pub control: ArmSummary,
+    pub treatment: ArmSummary,
+    pub mean_diff: f64, // treatment - control
+    pub t_stat: f64,
+    pub p_value: f64,
+}
+
+/// Runs a treatment parameterization of one strategy next to the production (control) one
+pub struct Experiment {
+    config: ExperimentConfig,
+    treatment: TreatmentStrategy,
+    control_book: ArmBook,
+    treatment_book: ArmBook,
+}
+
+impl Experiment {
+    /// Build the experiment from config. The treatment config is the control config of the
+    /// selected strategy with `overrides` ("key=value,key=value") applied on top
+    pub fn new(config: &Config, binance: Arc<BinanceClient>) -> Result<Self> {
+        let exp = config.experiment.clone();
+        let treatment = match exp.strategy.as_str() {
+            "arbitrage" => TreatmentStrategy::Arbitrage(ArbitrageStrategy::new(
+                apply_overrides(&config.arbitrage, &exp.overrides)?,
+                config.risk.capital_usd,
+            )),
+            "expiration" => TreatmentStrategy::Expiration(ExpirationStrategy::new(
+                apply_overrides(&config.expiration, &exp.overrides)?,
+            )),
+            "predictive" => TreatmentStrategy::Predictive(PredictiveStrategy::new(
+                apply_overrides(&config.predictive, &exp.overrides)?,
+                binance,
+            )),
+            other => anyhow::bail!("Unknown experiment strategy: {}", other),
+        };
+
+        info!(
+            "🧪 Experiment enabled on {} strategy (overrides: {}, treatment traffic {:.0}%)",
+            exp.strategy, exp.overrides, exp.treatment_traffic_pct
+        );
+
+        Ok(Self {
+            config: exp,
+            treatment,
+            control_book: ArmBook::new(),
+            treatment_book: ArmBook::new(),
+        })
+    }
+
+    /// Name of the strategy under test
+    pub fn strategy(&self) -> &str {
+        &self.config.strategy
+    }
+
+    /// Deterministic per-market traffic split: which arm trades this market for real
+    pub fn live_arm(&self, market_id: &str) -> ExperimentArm {
+        if traffic_bucket(market_id) < self.config.treatment_traffic_pct {
+            ExperimentArm::Treatment
+        } else {
+            ExperimentArm::Control
+        }
+    }
+
+    /// Evaluate the treatment arm on the same inputs the control arm saw
+    pub async fn evaluate_treatment(
+        &self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        binance_price: Option<f64>,
+    ) -> TradeAction {
+        match &self.treatment {
+            TreatmentStrategy::Arbitrage(s) => s.check_opportunity(market),
+            TreatmentStrategy::Expiration(s) => s.check_opportunity_at(market, now),
+            TreatmentStrategy::Predictive(s) => match binance_price {
+                Some(price) => s.evaluate(market, now, price),
+                // Control pre-filtered without fetching; treatment may have a wider window
+                None => s.check_opportunity_with_inputs(market, now).await.0,
+            },
+        }
+    }
+
+    /// Attribute both arms' decisions for one evaluation and return the action to execute live
+    pub async fn route(
+        &mut self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        binance_price: Option<f64>,
+        control_action: TradeAction,
+    ) -> TradeAction {
+        let treatment_action = self.evaluate_treatment(market, now, binance_price).await;
+        let live_arm = self.live_arm(&market.id);
+
+        if !matches!(control_action, TradeAction::None) {
+            self.control_book.record_signal(
+                market,
+                &control_action,
+                live_arm == ExperimentArm::Control,
+            );
+        }
+        if !matches!(treatment_action, TradeAction::None) {
+            self.treatment_book.record_signal(
+                market,
+                &treatment_action,
+                live_arm == ExperimentArm::Treatment,
+            );
+        }
+
+        match live_arm {
+            ExperimentArm::Control => control_action,
+            ExperimentArm::Treatment => treatment_action,
+        }
+    }
+
+    /// Market ids with open hypothetical positions in either arm
+    pub fn tracked_markets(&self) -> HashSet<String> {
+        self.control_book
+            .open_markets
+            .union(&self.treatment_book.open_markets)
+            .cloned()
+            .collect()
+    }
+
+    /// Mark both arms to market
+    pub fn update_market_price(&mut self, market_id: &str, yes_price: f64, no_price: f64) {
+        self.control_book
+            .tracker
+            .update_market_price(market_id, yes_price, no_price);
+        self.treatment_book
+            .tracker
+            .update_market_price(market_id, yes_price, no_price);
+    }
+
+    pub fn report(&self) -> ExperimentReport {
+        let control = self.control_book.summary(ExperimentArm::Control);
+        let treatment = self.treatment_book.summary(ExperimentArm::Treatment);
+        let (t_stat, p_value) = welch_t_test(
+            &self.treatment_book.returns(),
+            &self.control_book.returns(),
+        );
+
+        ExperimentReport {
+            generated_at: Utc::now(),
+            strategy: self.config.strategy.clone(),
+            overrides: self.config.overrides.clone(),
+            mean_diff: treatment.mean_return - control.mean_return,
+            control,
+            treatment,
+            t_stat,
+            p_value,
+        }
+    }
+
+    /// Build the report and persist it as JSON in the data dir
+    pub fn write_report(&self, data_dir: &str) -> Result<ExperimentReport> {
+        let report = self.report();
+        fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create data dir {}", data_dir))?;
+        let path = experiment_report_path(data_dir);
+        fs::write(&path, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(report)
+    }
+}
+
+/// Path of the latest experiment report inside a data dir
+pub fn experiment_report_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(EXPERIMENT_REPORT_FILE)
+}
+
+/// Load the latest persisted experiment report
+pub fn load_report(data_dir: &str) -> Result<ExperimentReport> {
+    let path = experiment_report_path(data_dir);
+    let raw = fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&raw).context("Invalid experiment report")
+}
+
+/// Apply "key=value,key=value" overrides to a serializable config
+pub fn apply_overrides<T>(base: &T, overrides: &str) -> Result<T>
+where
+    T: Serialize + for<'de> Deserialize<'de>,
+{
+    let mut value = serde_json::to_value(base)?;
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("Config is not an object"))?;
+
+    for pair in overrides.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, raw) = pair
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Invalid override '{}', expected key=value", pair))?;
+        let key = key.trim();
+        if !obj.contains_key(key) {
+            anyhow::bail!("Unknown config field in override: {}", key);
+        }
+        // Numbers and bools parse as JSON, anything else is taken as a string
+        let parsed = serde_json::from_str(raw.trim())
+            .unwrap_or_else(|_| serde_json::Value::String(raw.trim().to_string()));
+        obj.insert(key.to_string(), parsed);
+    }
+
+    serde_json::from_value(value).context("Override produced an invalid config")
+}
+
+/// Market's position in 0..100, from a SHA-256 of its id so the split survives restarts and
+/// toolchain upgrades (a market must not switch arms mid-experiment)
+fn traffic_bucket(market_id: &str) -> f64 {
+    let digest = Sha256::digest(market_id.as_bytes());
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % 10_000) as f64 / 100.0
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance.sqrt())
+}
+
+/// Welch's t-test for a difference in means. Returns (t, two-sided p-value)
+/// The p-value uses a normal approximation, fine for the sample sizes we accumulate
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> (f64, f64) {
+    if a.len() < 2 || b.len() < 2 {
+        return (0.0, 1.0);
+    }
+    let (mean_a, std_a) = mean_std(a);
+    let (mean_b, std_b) = mean_std(b);
+    let se = (std_a.powi(2) / a.len() as f64 + std_b.powi(2) / b.len() as f64).sqrt();
+    if se <= 0.0 {
+        return (0.0, 1.0);
+    }
+    let t = (mean_a - mean_b) / se;
+    let p = 2.0 * (1.0 - normal_cdf(t.abs()));
+    (t, p.clamp(0.0, 1.0))
+}
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26 erf approximation)
+pub(crate) fn normal_cdf(x: f64) -> f64 {
+    let z = x / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    let erf = if z >= 0.0 { erf } else { -erf };
+    0.5 * (1.0 + erf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ArbitrageConfig, SizingMode};
+
+    #[test]
+    fn test_apply_overrides() {
+        let base = ArbitrageConfig {
+            enabled: true,
+            min_edge_bps: 20,
+            max_position_size_usd: 10.0,
+            sizing_mode: SizingMode::Fixed,
+            kelly_fraction: 0.25,
+            min_position_pct: 0.01,
+            max_position_pct: 0.10,
+            capital_reserve_fraction: 0.1,
+            min_depth_usd: 0.0,
+        };
+        let variant = apply_overrides(&base, "min_edge_bps=40, kelly_fraction=0.5").unwrap();
+        assert_eq!(variant.min_edge_bps, 40);
+        assert_eq!(variant.kelly_fraction, 0.5);
+        assert!(apply_overrides(&base, "not_a_field=1").is_err());
+    }
+
+    #[test]
+    fn test_traffic_bucket_is_pinned() {
+        assert_eq!(traffic_bucket("0xabc"), 0.72);
+        assert_eq!(traffic_bucket("market-1"), 70.75);
+    }
+
+    #[test]
+    fn test_welch_t_test() {
+        let a = [0.05, 0.06, 0.04, 0.05, 0.07];
+        let b = [0.01, 0.00, 0.02, 0.01, -0.01];
+        let (t, p) = welch_t_test(&a, &b);
+        assert!(t > 0.0);
+        assert!(p < 0.05);
+
+        let (_, p_same) = welch_t_test(&a, &a);
+        assert!(p_same > 0.9);
+    }
+}
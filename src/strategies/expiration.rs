@@ -1,4 +1,5 @@
 use crate::config::ExpirationConfig;
+use crate::ids::{Price, UsdAmount};
 use crate::polymarket::MarketData;
 use crate::strategies::arbitrage::TradeAction;
 use chrono::{DateTime, Utc};
@@ -8,6 +9,22 @@ pub struct ExpirationStrategy {
     config: ExpirationConfig,
 }
 
+/// Protective buy of the opposite outcome placed with an expiration snipe
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpirationHedge {
+    pub side: String,
+    pub price: f64,
+    /// Opposite shares bought
+    pub shares: f64,
+    pub size_usd: f64,
+    /// Snipe notional the hedge covers
+    pub covered_usd: f64,
+    /// Loss of snipe and hedge together if the sniped side loses (0 when fully covered)
+    pub worst_case_loss_usd: f64,
+    /// Expected value of snipe and hedge at `EXPIRATION_WIN_PROB`, bps of their cost
+    pub expected_value_bps: f64,
+}
+
 impl ExpirationStrategy {
     pub fn new(config: ExpirationConfig) -> Self {
         Self { config }
@@ -19,25 +36,21 @@ impl ExpirationStrategy {
     /// 2. Winning probability > min_win_prob (e.g. 0.90) based on price
     /// 3. Price < target_price (e.g. 0.99)
     pub fn check_opportunity(&self, market: &MarketData) -> TradeAction {
+        self.check_opportunity_at(market, Utc::now())
+    }
+
+    /// Same as `check_opportunity` but evaluated at an explicit point in time (used for replay)
+    pub fn check_opportunity_at(&self, market: &MarketData, now: DateTime<Utc>) -> TradeAction {
         if !self.config.enabled {
             return TradeAction::None;
         }
 
-        // 1. Check Time Remaining
-        let end_date_str = match &market.end_date {
-            Some(d) => d,
+        // 1. Check Time Remaining (no or invalid end date: nothing to time)
+        let time_remaining = match market.end_time {
+            Some(end) => (end - now).num_seconds(),
             None => return TradeAction::None,
         };
 
-        let time_remaining = match DateTime::parse_from_rfc3339(end_date_str) {
-            Ok(dt) => {
-                let now = Utc::now();
-                let end = dt.with_timezone(&Utc);
-                (end - now).num_seconds()
-            }
-            Err(_) => return TradeAction::None, // Invalid date format
-        };
-
         if time_remaining <= 0 || time_remaining > self.config.max_time_remaining_sec as i64 {
             // debug!("⏳ Market {} time remaining: {}s (Target: < {}s)", market.id, time_remaining, self.config.max_time_remaining_sec);
             return TradeAction::None;
@@ -51,11 +64,10 @@ impl ExpirationStrategy {
         // 2. Identify Winning Side & Check Rules
         // Rule: Price must be > min_price (highly likely to win) AND < target_price (profitable)
 
-        let yes_price = market.yes_price;
-        let no_price = market.no_price;
+        let in_range = |p: &f64| *p >= self.config.min_price && *p < self.config.target_price;
 
-        // Check YES
-        if yes_price >= self.config.min_price && yes_price < self.config.target_price {
+        // Check YES (a side without asks has no price to check)
+        if let Some(yes_price) = market.ask("YES").filter(in_range) {
             let profit_bps = ((1.0 - yes_price) * 10000.0) as i32;
             info!(
                 "🎯 EXPIRATION SIGNAL (YES): {} | Price: {:.4} | Profit: {} bps | Time: {}s",
@@ -65,13 +77,13 @@ impl ExpirationStrategy {
             return TradeAction::Snipe {
                 market_id: market.id.clone(),
                 side: "YES".to_string(),
-                price: yes_price,
-                size_usd: 1.0, // Default size, will be capped by balance
+                price: Price::new(yes_price),
+                size_usd: UsdAmount::new(1.0), // Default size, will be capped by balance
             };
         }
 
         // Check NO
-        if no_price >= self.config.min_price && no_price < self.config.target_price {
+        if let Some(no_price) = market.ask("NO").filter(in_range) {
             let profit_bps = ((1.0 - no_price) * 10000.0) as i32;
             info!(
                 "🎯 EXPIRATION SIGNAL (NO): {} | Price: {:.4} | Profit: {} bps | Time: {}s",
@@ -81,11 +93,113 @@ impl ExpirationStrategy {
             return TradeAction::Snipe {
                 market_id: market.id.clone(),
                 side: "NO".to_string(),
-                price: no_price,
-                size_usd: 1.0,
+                price: Price::new(no_price),
+                size_usd: UsdAmount::new(1.0),
             };
         }
 
         TradeAction::None
     }
+
+    /// Hedge of a snipe of `size_usd` on `side` at `price`: `EXPIRATION_HEDGE_RATIO` opposite
+    /// shares per share sniped, if the opposite ask is at most `EXPIRATION_HEDGE_MAX_PRICE` and
+    /// the pair still keeps `EXPIRATION_HEDGE_MIN_EV_BPS` of expected value. None otherwise:
+    /// the snipe stands unhedged
+    pub fn hedge_for(
+        &self,
+        market: &MarketData,
+        side: &str,
+        price: f64,
+        size_usd: f64,
+    ) -> Option<ExpirationHedge> {
+        if !self.config.hedge_enabled || price <= 0.0 || self.config.hedge_ratio <= 0.0 {
+            return None;
+        }
+        let opposite = if side == "YES" { "NO" } else { "YES" };
+        let hedge_price = market
+            .ask(opposite)
+            .filter(|p| *p <= self.config.hedge_max_price)?;
+
+        let shares = size_usd / price;
+        let hedge_shares = shares * self.config.hedge_ratio;
+        let hedge_usd = hedge_shares * hedge_price;
+        let cost = size_usd + hedge_usd;
+        // Each side pays $1 a share if it wins
+        let won = shares - cost;
+        let lost = hedge_shares - cost;
+        let q = self.config.win_prob;
+        let expected_value_bps = (q * won + (1.0 - q) * lost) / cost * 10_000.0;
+        if expected_value_bps < self.config.hedge_min_ev_bps {
+            debug!(
+                "🛡️ No hedge for {}: {} @ {:.3} leaves {:.0} bps of expected value",
+                market.question, opposite, hedge_price, expected_value_bps
+            );
+            return None;
+        }
+
+        Some(ExpirationHedge {
+            side: opposite.to_string(),
+            price: hedge_price,
+            shares: hedge_shares,
+            size_usd: hedge_usd,
+            covered_usd: hedge_shares * price,
+            worst_case_loss_usd: (-won.min(lost)).max(0.0),
+            expected_value_bps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ExpirationConfig {
+        ExpirationConfig {
+            enabled: true,
+            max_time_remaining_sec: 60,
+            min_price: 0.92,
+            target_price: 0.99,
+            watch_window_sec: 600,
+            watch_poll_interval_ms: 500,
+            hedge_enabled: true,
+            hedge_max_price: 0.02,
+            hedge_ratio: 1.0,
+            win_prob: 0.98,
+            hedge_min_ev_bps: 100.0,
+        }
+    }
+
+    fn market(yes_price: f64, no_price: f64) -> MarketData {
+        MarketData::quoted("m1", yes_price, no_price)
+    }
+
+    #[test]
+    fn test_hedge_caps_the_loss_while_it_keeps_its_value() {
+        // 100 YES @ 0.95 and 100 NO @ 0.02: $3 either way
+        let strategy = ExpirationStrategy::new(config());
+        let hedge = strategy
+            .hedge_for(&market(0.95, 0.02), "YES", 0.95, 95.0)
+            .unwrap();
+        assert_eq!(hedge.side, "NO");
+        assert!((hedge.shares - 100.0).abs() < 1e-9);
+        assert!((hedge.size_usd - 2.0).abs() < 1e-9);
+        assert_eq!(hedge.worst_case_loss_usd, 0.0);
+        assert!((hedge.expected_value_bps - 3.0 / 97.0 * 10_000.0).abs() < 1e-6);
+
+        // Half covered: $46 at risk instead of $95
+        let half = ExpirationStrategy::new(config().hedge_ratio(0.5));
+        let hedge = half
+            .hedge_for(&market(0.95, 0.02), "YES", 0.95, 95.0)
+            .unwrap();
+        assert!((hedge.worst_case_loss_usd - 46.0).abs() < 1e-9);
+
+        // Too expensive, or too little value left at this win rate
+        assert!(strategy
+            .hedge_for(&market(0.95, 0.03), "YES", 0.95, 95.0)
+            .is_none());
+        let doubtful = ExpirationStrategy::new(config().hedge_ratio(0.5).win_prob(0.9));
+        assert!(doubtful
+            .hedge_for(&market(0.95, 0.02), "YES", 0.95, 95.0)
+            .is_none());
+    }
 }
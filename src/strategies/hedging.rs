@@ -0,0 +1,456 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::analytics::attribution::strategy_of;
+use crate::config::HedgeConfig;
+use crate::polymarket::MarketData;
+use crate::strategies::risk::Position;
+
+/// How long a snipe whose market has no known end waits for its fill
+const UNDATED_SNIPE_WAIT_SECS: i64 = 3600;
+
+/// Trade id prefixes of the strategies whose single-sided positions are hedged
+/// (predictive and expiration snipes)
+const DIRECTIONAL_STRATEGIES: [&str; 2] = ["pred", "snipe"];
+
+pub const HEDGE_BOOK_FILE: &str = "hedges.json";
+
+fn complement(side: &str) -> &'static str {
+    if side == "YES" {
+        "NO"
+    } else {
+        "YES"
+    }
+}
+
+/// Complementary outcome bought against a directional position
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Hedge {
+    pub origin_market_id: String,
+    pub origin_trade_id: String,
+    pub strategy: String,
+    /// Market the hedge is held in: the origin market, or the one correlated with it
+    pub market_id: String,
+    pub side: String,
+    pub price: f64,
+    pub shares: f64,
+    pub cost_usd: f64,
+    /// Origin notional the hedge covers
+    pub covered_usd: f64,
+    pub opened_at: DateTime<Utc>,
+}
+
+impl Hedge {
+    /// Journal id of the hedge: the origin's trade id, so its PnL lands on the same strategy
+    pub fn trade_id(&self) -> String {
+        hedge_trade_id(&self.origin_trade_id)
+    }
+
+    /// The hedge as the risk manager counts it
+    pub fn risk_position(&self) -> Position {
+        Position {
+            market_id: self.market_id.clone(),
+            trade_id: self.trade_id(),
+            side: self.side.clone(),
+            size_usd: self.cost_usd,
+            entry_price: self.price,
+            timestamp: self.opened_at.timestamp().max(0) as u64,
+            realized_pnl: 0.0,
+            config_hash: None,
+        }
+    }
+}
+
+pub fn hedge_trade_id(origin_trade_id: &str) -> String {
+    format!("{}-hedge", origin_trade_id)
+}
+
+/// Hedge the Hedger wants placed
+#[derive(Debug, Clone)]
+pub struct HedgeOrder {
+    pub origin: Position,
+    pub market: MarketData,
+    pub side: String,
+    pub price: f64,
+    /// Complementary shares bought, one per origin share covered
+    pub shares: f64,
+    pub size_usd: f64,
+    pub covered_usd: f64,
+}
+
+/// Expiration snipe placed but not confirmed filled yet: hedged once its order fills
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSnipe {
+    pub trade_id: String,
+    pub order_id: String,
+    pub market_id: String,
+    pub side: String,
+    pub price: f64,
+    pub size_usd: f64,
+    /// Given up (unhedged) once the market has ended
+    pub ends_at: Option<DateTime<Utc>>,
+    pub placed_at: DateTime<Utc>,
+}
+
+impl PendingSnipe {
+    /// Whether it's still worth waiting for: its market hasn't ended, or (end unknown) it
+    /// was placed less than `UNDATED_SNIPE_WAIT_SECS` ago
+    fn waiting(&self, now: DateTime<Utc>) -> bool {
+        match self.ends_at {
+            Some(end) => end > now,
+            None => now - self.placed_at < Duration::seconds(UNDATED_SNIPE_WAIT_SECS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct HedgeSnapshot {
+    pub directional_exposure_usd: f64,
+    pub max_directional_exposure_usd: f64,
+    pub hedges: Vec<Hedge>,
+    /// Realized hedge PnL charged to each originating strategy
+    pub realized_by_strategy: BTreeMap<String, f64>,
+}
+
+/// Hedges, realized PnL and waiting snipes, kept across runs in `data_dir/hedges.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HedgeBook {
+    pub hedges: Vec<Hedge>,
+    pub realized_by_strategy: BTreeMap<String, f64>,
+    pub pending: Vec<PendingSnipe>,
+}
+
+impl HedgeBook {
+    /// Write to `data_dir/hedges.json` through a temp file
+    pub fn save(&self, data_dir: &str) -> Result<()> {
+        fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create data dir {}", data_dir))?;
+        let path = Path::new(data_dir).join(HEDGE_BOOK_FILE);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {:?}", tmp))?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+
+    /// The book the previous run left in `data_dir`, None if there is none
+    pub fn load(data_dir: &str) -> Result<Option<Self>> {
+        let path = Path::new(data_dir).join(HEDGE_BOOK_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&raw)
+            .map(Some)
+            .context("Invalid hedge book")
+    }
+}
+
+/// Caps directional exposure by buying the complementary outcome of the largest unhedged
+/// predictive / expiration positions. Hedges are tracked here by origin market, and counted
+/// by the risk manager apart from its positions (one per market)
+pub struct Hedger {
+    config: HedgeConfig,
+    /// origin market_id -> hedge
+    hedges: HashMap<String, Hedge>,
+    realized: BTreeMap<String, f64>,
+    exposure: f64,
+    /// Snipes waiting for their fill before they're hedged
+    pending: Vec<PendingSnipe>,
+}
+
+impl Hedger {
+    pub fn new(config: HedgeConfig) -> Self {
+        Self {
+            config,
+            hedges: HashMap::new(),
+            realized: BTreeMap::new(),
+            exposure: 0.0,
+            pending: Vec::new(),
+        }
+    }
+
+    fn is_directional(position: &Position) -> bool {
+        DIRECTIONAL_STRATEGIES.contains(&strategy_of(&position.trade_id))
+    }
+
+    /// Put back the book a previous run left
+    pub fn restore(&mut self, book: HedgeBook) {
+        self.hedges = book
+            .hedges
+            .into_iter()
+            .map(|hedge| (hedge.origin_market_id.clone(), hedge))
+            .collect();
+        self.realized = book.realized_by_strategy;
+        self.pending = book.pending;
+    }
+
+    pub fn book(&self) -> HedgeBook {
+        HedgeBook {
+            hedges: self.snapshot().hedges,
+            realized_by_strategy: self.realized.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+
+    /// Directional notional not covered by a hedge: the origin's shares left uncovered, at
+    /// its entry price
+    pub fn directional_exposure(&self, positions: &[Position]) -> f64 {
+        positions
+            .iter()
+            .filter(|p| Self::is_directional(p) && p.entry_price > 0.0)
+            .map(|p| {
+                let covered = self.hedges.get(&p.market_id).map_or(0.0, |h| h.shares);
+                (p.size_usd / p.entry_price - covered).max(0.0) * p.entry_price
+            })
+            .sum()
+    }
+
+    /// Hedges needed to bring directional exposure back under the limit, largest positions
+    /// first. `market_of` looks up current quotes
+    pub fn plan(
+        &mut self,
+        positions: &[Position],
+        market_of: impl Fn(&str) -> Option<MarketData>,
+    ) -> Vec<HedgeOrder> {
+        self.exposure = self.directional_exposure(positions);
+        let mut exposure = self.exposure;
+        if exposure <= self.config.max_directional_exposure_usd {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<&Position> = positions
+            .iter()
+            .filter(|p| Self::is_directional(p) && !self.hedges.contains_key(&p.market_id))
+            .filter(|p| p.entry_price > 0.0)
+            .collect();
+        candidates.sort_by(|a, b| b.size_usd.total_cmp(&a.size_usd));
+
+        let mut orders = Vec::new();
+        for position in candidates {
+            if exposure <= self.config.max_directional_exposure_usd {
+                break;
+            }
+            let side = complement(&position.side);
+            let hedge_market = std::iter::once(position.market_id.as_str())
+                .chain(
+                    self.config
+                        .correlated_markets
+                        .get(&position.market_id)
+                        .map(String::as_str),
+                )
+                .filter_map(&market_of)
+                .find_map(|market| {
+                    let price = market.ask(side)?;
+                    (price <= self.config.max_hedge_price).then_some((market, price))
+                });
+            let Some((market, price)) = hedge_market else {
+                continue;
+            };
+
+            let shares = position.size_usd / position.entry_price * self.config.hedge_ratio;
+            let covered_usd = shares * position.entry_price;
+            exposure -= covered_usd;
+            orders.push(HedgeOrder {
+                origin: position.clone(),
+                market,
+                side: side.to_string(),
+                price,
+                shares,
+                size_usd: shares * price,
+                covered_usd,
+            });
+        }
+        orders
+    }
+
+    /// Register a placed hedge order
+    pub fn record(&mut self, order: &HedgeOrder, now: DateTime<Utc>) -> Hedge {
+        let hedge = Hedge {
+            origin_market_id: order.origin.market_id.clone(),
+            origin_trade_id: order.origin.trade_id.clone(),
+            strategy: strategy_of(&order.origin.trade_id).to_string(),
+            market_id: order.market.id.to_string(),
+            side: order.side.clone(),
+            price: order.price,
+            shares: order.shares,
+            cost_usd: order.size_usd,
+            covered_usd: order.covered_usd,
+            opened_at: now,
+        };
+        self.exposure -= hedge.covered_usd;
+        self.hedges
+            .insert(hedge.origin_market_id.clone(), hedge.clone());
+        hedge
+    }
+
+    /// Hold a snipe until its order is seen filled
+    pub fn await_fill(&mut self, snipe: PendingSnipe) {
+        self.pending.push(snipe);
+    }
+
+    /// Snipes waiting for a fill, to re-check. Those no longer worth waiting for (market
+    /// ended, or undated and waiting too long) are dropped unhedged
+    pub fn take_pending(&mut self, now: DateTime<Utc>) -> Vec<PendingSnipe> {
+        let (pending, expired): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|snipe| snipe.waiting(now));
+        for snipe in expired {
+            warn!(
+                "⚠️ Snipe {} never seen filled in time: left unhedged",
+                snipe.trade_id
+            );
+        }
+        pending
+    }
+
+    pub fn get(&self, origin_market_id: &str) -> Option<&Hedge> {
+        self.hedges.get(origin_market_id)
+    }
+
+    /// Drop a hedge once unwound, charging its realized PnL to the originating strategy
+    pub fn close(&mut self, origin_market_id: &str, realized_pnl: f64) -> Option<Hedge> {
+        let hedge = self.hedges.remove(origin_market_id)?;
+        *self.realized.entry(hedge.strategy.clone()).or_default() += realized_pnl;
+        Some(hedge)
+    }
+
+    pub fn snapshot(&self) -> HedgeSnapshot {
+        let mut hedges: Vec<Hedge> = self.hedges.values().cloned().collect();
+        hedges.sort_by_key(|h| h.opened_at);
+        HedgeSnapshot {
+            directional_exposure_usd: self.exposure,
+            max_directional_exposure_usd: self.config.max_directional_exposure_usd,
+            hedges,
+            realized_by_strategy: self.realized.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(trade_id: &str, market_id: &str, side: &str, size_usd: f64) -> Position {
+        Position {
+            market_id: market_id.to_string(),
+            trade_id: trade_id.to_string(),
+            side: side.to_string(),
+            size_usd,
+            entry_price: 0.8,
+            timestamp: 0,
+            realized_pnl: 0.0,
+            config_hash: None,
+        }
+    }
+
+    fn market(id: &str, yes_price: f64, no_price: f64) -> MarketData {
+        MarketData::quoted(id, yes_price, no_price)
+    }
+
+    #[test]
+    fn test_hedges_largest_positions_until_under_limit() {
+        let mut hedger = Hedger::new(HedgeConfig {
+            enabled: true,
+            max_directional_exposure_usd: 100.0,
+            hedge_ratio: 0.5,
+            max_hedge_price: 0.5,
+            correlated_markets: BTreeMap::from([("m3".to_string(), "m4".to_string())]),
+        });
+        let positions = vec![
+            position("pred_m1_1", "m1", "YES", 80.0),
+            position("snipe_m2_1", "m2", "NO", 40.0),
+            // Its own NO is too expensive: hedged through the correlated market
+            position("pred_m3_1", "m3", "YES", 100.0),
+            // Dislocation entries aren't hedged
+            position("disloc_m5_1", "m5", "YES", 500.0),
+        ];
+        let markets = [
+            market("m1", 0.85, 0.15),
+            market("m2", 0.6, 0.4),
+            market("m3", 0.3, 0.7),
+            market("m4", 0.6, 0.4),
+        ];
+        let market_of = |id: &str| markets.iter().find(|m| m.id == id).cloned();
+
+        assert!((hedger.directional_exposure(&positions) - 220.0).abs() < 1e-9);
+        // 220 -> 170 after m3, -> 130 after m1 (m2's complement is too expensive)
+        let orders = hedger.plan(&positions, market_of);
+        let hedged: Vec<(&str, &str)> = orders
+            .iter()
+            .map(|o| (o.origin.market_id.as_str(), o.market.id.as_str()))
+            .collect();
+        assert_eq!(hedged, vec![("m3", "m4"), ("m1", "m1")]);
+        assert_eq!(orders[1].side, "NO");
+        // Half of the 100 shares held, at the complement's ask
+        assert!((orders[1].shares - 50.0).abs() < 1e-9);
+        assert!((orders[1].size_usd - 50.0 * 0.15).abs() < 1e-9);
+
+        for order in &orders {
+            hedger.record(order, Utc::now());
+        }
+        assert!((hedger.directional_exposure(&positions) - 130.0).abs() < 1e-9);
+        // Nothing left to hedge with
+        assert!(hedger.plan(&positions, market_of).is_empty());
+        // A position cut below its hedged shares is fully covered
+        let reduced = [position("pred_m1_1", "m1", "YES", 20.0)];
+        assert_eq!(hedger.directional_exposure(&reduced), 0.0);
+
+        // A restart picks the hedges back up
+        let mut restored = Hedger::new(HedgeConfig::default());
+        restored.restore(hedger.book());
+        let counted = restored.get("m1").unwrap().risk_position();
+        assert_eq!(counted.trade_id, "pred_m1_1-hedge");
+        assert!((counted.size_usd - 7.5).abs() < 1e-9);
+
+        let hedge = hedger.close("m1", -2.5).unwrap();
+        assert_eq!(hedge.trade_id(), "pred_m1_1-hedge");
+        assert_eq!(
+            hedger.snapshot().realized_by_strategy.get("pred"),
+            Some(&-2.5)
+        );
+    }
+
+    #[test]
+    fn test_unfilled_snipes_wait_until_their_market_ends() {
+        let now = Utc::now();
+        let snipe = |trade_id: &str, ends_at: Option<DateTime<Utc>>| PendingSnipe {
+            trade_id: trade_id.to_string(),
+            order_id: format!("order-{}", trade_id),
+            market_id: "m1".to_string(),
+            side: "YES".to_string(),
+            price: 0.9,
+            size_usd: 10.0,
+            ends_at,
+            placed_at: now - chrono::Duration::minutes(5),
+        };
+        let mut hedger = Hedger::new(HedgeConfig::default());
+        hedger.await_fill(snipe(
+            "snipe_m1_1",
+            Some(now + chrono::Duration::minutes(2)),
+        ));
+        hedger.await_fill(snipe(
+            "snipe_m1_2",
+            Some(now - chrono::Duration::seconds(1)),
+        ));
+        // No known end: waits an hour from placing
+        hedger.await_fill(snipe("snipe_m1_3", None));
+
+        let pending = hedger.take_pending(now);
+        let waiting: Vec<&str> = pending.iter().map(|s| s.trade_id.as_str()).collect();
+        assert_eq!(waiting, vec!["snipe_m1_1", "snipe_m1_3"]);
+        // Taken out until put back
+        assert!(hedger.take_pending(now).is_empty());
+
+        hedger.await_fill(pending[1].clone());
+        assert!(hedger
+            .take_pending(now + chrono::Duration::minutes(56))
+            .is_empty());
+    }
+}
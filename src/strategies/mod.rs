@@ -1,7 +1,18 @@
+pub mod arb_exit;
 pub mod arbitrage;
 pub mod arena;
+pub mod cross_venue;
+pub mod custom;
+pub mod dislocation;
+pub mod edge_tuning;
+pub mod experiment;
 pub mod expiration;
+pub mod hedging;
+pub mod neg_risk;
 pub mod position_sizing;
 pub mod predictive;
 pub mod risk;
 pub mod types;
+
+pub use arbitrage::TradeAction;
+pub use custom::Strategy;
@@ -0,0 +1,286 @@
+//! Arbitrage on multi-outcome (negative risk) markets
+//!
+//! A question of a NegRiskAdapter market trades as an ordinary YES/NO pair, but its pairs merge
+//! through the adapter, and the adapter offers a second way to lock in a payout: converting NO
+//! tokens. Converting one NO of each of k questions pays k - 1 of collateral (less the market's
+//! fee) plus a YES of every question left out, so a basket of NOs bought for less than k - 1 is
+//! an arbitrage of its own. The YES tokens left over are valued at nothing, which makes the
+//! basket's edge a floor when Gamma didn't list every question.
+//!
+//! Before an arbitrage on such a question is executed, both paths are costed from the live asks
+//! with CLOB fees and each path's gas spread over the size: the pair has to keep the minimum edge,
+//! and the conversion replaces it when it earns more per dollar.
+//!
+//! A bought basket converts only the sets every leg filled. It is persisted in
+//! `data_dir/neg_risk_baskets.json` until its conversion's receipt is in, and booked then.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::config::NegRiskConfig;
+use crate::strategies::arbitrage::FEE_PER_TRADE_BPS;
+
+pub const NEG_RISK_BASKETS_FILE: &str = "neg_risk_baskets.json";
+/// Conversions sent for one basket before it is held to resolution instead
+pub const MAX_CONVERT_ATTEMPTS: u32 = 3;
+
+/// One unit of a path (a YES + NO pair, or a basket of NOs), per share of each leg
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PathEconomics {
+    /// Paid per unit, CLOB fees and the unit's share of the gas included
+    pub cost: f64,
+    /// Collateral the adapter pays back per unit
+    pub payout: f64,
+}
+
+impl PathEconomics {
+    pub fn profit(&self) -> f64 {
+        self.payout - self.cost
+    }
+
+    /// Profit per dollar put in
+    pub fn edge_bps(&self) -> f64 {
+        if self.cost > 0.0 {
+            self.profit() / self.cost * 10_000.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How an arbitrage on a neg-risk question is executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NegRiskRoute {
+    /// Buy YES + NO on the CLOB and merge the pairs through the adapter
+    Pair,
+    /// Buy a NO of every listed question and convert the basket through the adapter
+    Convert,
+    /// Neither path keeps the minimum edge
+    Skip,
+}
+
+/// A neg-risk question as it trades now
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegRiskQuote {
+    pub yes_ask: f64,
+    pub no_ask: f64,
+    /// NO asks of the listed questions of the market, this one included. Empty when one of
+    /// them has no ask (the basket can't be bought)
+    pub basket_no_asks: Vec<f64>,
+    /// Capital the arbitrage would put in
+    pub size_usd: f64,
+    /// Gas of the adapter merge, and of converting the whole basket
+    pub merge_gas_usd: f64,
+    pub convert_gas_usd: f64,
+}
+
+/// Both paths costed, and the one to take
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegRiskCheck {
+    pub route: NegRiskRoute,
+    pub pair: PathEconomics,
+    /// None when there is no basket to buy
+    pub convert: Option<PathEconomics>,
+}
+
+/// Cost both paths of `quote`. The pair is kept unless it loses the minimum edge, or the
+/// conversion (when enabled) clears it with a better return
+pub fn check_arbitrage(config: &NegRiskConfig, quote: &NegRiskQuote) -> NegRiskCheck {
+    let fee = FEE_PER_TRADE_BPS as f64 / 10_000.0;
+    let per_unit_gas = |gas_usd: f64, unit_cost: f64| {
+        let units = quote.size_usd / unit_cost;
+        if units > 0.0 {
+            gas_usd / units
+        } else {
+            f64::INFINITY
+        }
+    };
+
+    let pair_cost = quote.yes_ask + quote.no_ask;
+    let pair = PathEconomics {
+        cost: pair_cost * (1.0 + fee) + per_unit_gas(quote.merge_gas_usd, pair_cost),
+        payout: 1.0,
+    };
+
+    let basket = quote.basket_no_asks.len();
+    let basket_cost: f64 = quote.basket_no_asks.iter().sum();
+    let convert = (basket >= 2 && basket_cost > 0.0).then(|| {
+        let adapter_fee = config.adapter_fee_bps as f64 / 10_000.0;
+        PathEconomics {
+            cost: basket_cost * (1.0 + fee) + per_unit_gas(quote.convert_gas_usd, basket_cost),
+            payout: (basket - 1) as f64 * (1.0 - adapter_fee),
+        }
+    });
+
+    let clears = |path: &PathEconomics| path.edge_bps() >= config.min_edge_bps;
+    let route = match convert.filter(|_| config.convert) {
+        Some(basket) if clears(&basket) && basket.edge_bps() > pair.edge_bps() => {
+            NegRiskRoute::Convert
+        }
+        _ if clears(&pair) => NegRiskRoute::Pair,
+        _ => NegRiskRoute::Skip,
+    };
+    NegRiskCheck {
+        route,
+        pair,
+        convert,
+    }
+}
+
+/// A NO basket bought to convert, waiting for its tokens to settle and its conversion to land
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegRiskBasket {
+    pub neg_risk_market_id: String,
+    /// Questions the basket holds a NO of, and their conditions
+    pub question_indices: Vec<u8>,
+    pub market_ids: Vec<String>,
+    /// Sets every leg filled: the NO tokens converted of each question
+    pub sets: f64,
+    /// Collateral each set converts into, after the adapter's fee
+    pub payout_per_set: f64,
+    pub bought_at: DateTime<Utc>,
+    /// Conversions sent so far
+    #[serde(default)]
+    pub attempts: u32,
+    /// Conversion sent and not yet confirmed
+    #[serde(default)]
+    pub tx_hash: Option<String>,
+}
+
+/// Baskets waiting to convert, persisted in `data_dir/neg_risk_baskets.json`
+#[derive(Debug, Default)]
+pub struct NegRiskBaskets {
+    path: Option<PathBuf>,
+    baskets: HashMap<String, NegRiskBasket>,
+}
+
+impl NegRiskBaskets {
+    /// The baskets a previous run left unconverted
+    pub fn load(data_dir: &str) -> Self {
+        let path = Path::new(data_dir).join(NEG_RISK_BASKETS_FILE);
+        let baskets = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                warn!("⚠️ Ignoring unreadable {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            path: Some(path),
+            baskets,
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.baskets)?)?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    pub fn insert(&mut self, group_id: String, basket: NegRiskBasket) {
+        self.baskets.insert(group_id, basket);
+    }
+
+    pub fn remove(&mut self, group_id: &str) -> Option<NegRiskBasket> {
+        self.baskets.remove(group_id)
+    }
+
+    pub fn get_mut(&mut self, group_id: &str) -> Option<&mut NegRiskBasket> {
+        self.baskets.get_mut(group_id)
+    }
+
+    /// Baskets bought at least `settle_secs` ago
+    pub fn due(&self, now: DateTime<Utc>, settle_secs: i64) -> Vec<(String, NegRiskBasket)> {
+        self.baskets
+            .iter()
+            .filter(|(_, basket)| (now - basket.bought_at).num_seconds() >= settle_secs)
+            .map(|(id, basket)| (id.clone(), basket.clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.baskets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.baskets.is_empty()
+    }
+}
+
+/// Index set of the questions a basket converts (bit i for question i). None when a question
+/// index doesn't fit the 128 bits
+pub fn index_set(question_indices: &[u8]) -> Option<u128> {
+    question_indices.iter().try_fold(0u128, |set, index| {
+        1u128.checked_shl(*index as u32).map(|bit| set | bit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> NegRiskConfig {
+        NegRiskConfig {
+            enabled: true,
+            convert: true,
+            adapter_fee_bps: 0,
+            convert_gas_units: 150_000,
+            convert_gas_units_per_question: 60_000,
+            min_edge_bps: 10.0,
+        }
+    }
+
+    fn quote(yes_ask: f64, no_ask: f64, basket_no_asks: Vec<f64>) -> NegRiskQuote {
+        NegRiskQuote {
+            yes_ask,
+            no_ask,
+            basket_no_asks,
+            size_usd: 100.0,
+            merge_gas_usd: 0.05,
+            convert_gas_usd: 0.10,
+        }
+    }
+
+    #[test]
+    fn test_pair_edge_must_survive_fees_and_merge_gas() {
+        // 2 cents under 1 clears the 0.4% fee and the gas
+        let check = check_arbitrage(&config(), &quote(0.48, 0.50, Vec::new()));
+        assert_eq!(check.route, NegRiskRoute::Pair);
+        assert!(check.convert.is_none());
+
+        // The fee eats most of half a cent
+        let check = check_arbitrage(&config(), &quote(0.495, 0.50, Vec::new()));
+        assert_eq!(check.route, NegRiskRoute::Skip);
+        assert!(check.pair.edge_bps() < config().min_edge_bps);
+    }
+
+    #[test]
+    fn test_prefers_the_conversion_when_it_earns_more() {
+        // Three NOs for 1.90 convert into 2: beats the pair's 2 cents on 0.98
+        let check = check_arbitrage(&config(), &quote(0.48, 0.50, vec![0.50, 0.70, 0.70]));
+        assert_eq!(check.route, NegRiskRoute::Convert);
+        assert!(check.convert.unwrap().edge_bps() > check.pair.edge_bps());
+
+        // The adapter's fee takes the conversion's edge away
+        let config = NegRiskConfig {
+            adapter_fee_bps: 500,
+            ..config()
+        };
+        let check = check_arbitrage(&config, &quote(0.48, 0.50, vec![0.50, 0.70, 0.70]));
+        assert_eq!(check.route, NegRiskRoute::Pair);
+
+        assert_eq!(index_set(&[0, 2, 3]), Some(0b1101));
+        assert_eq!(index_set(&[127]), Some(1 << 127));
+        assert_eq!(index_set(&[1, 128]), None);
+    }
+}
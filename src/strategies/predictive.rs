@@ -1,19 +1,81 @@
 use crate::config::PredictiveConfig;
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::markets::{Comparator, FeatureFrame, QuestionSpec};
+use crate::ml::{ModelInput, SignalScorer};
 use crate::polymarket::MarketData;
 use crate::pricefeed::BinanceClient;
 use crate::strategies::arbitrage::TradeAction;
+use crate::strategies::experiment::normal_cdf;
 use chrono::{DateTime, Utc};
-use std::sync::Arc;
-use tracing::{debug, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+/// Parsed questions kept at most; once full, those of ended markets are dropped (all of them
+/// if none has ended)
+const SPEC_CACHE_CAPACITY: usize = 10_000;
+
+/// A market's question, parsed once
+struct CachedSpec {
+    question: String,
+    end_time: Option<DateTime<Utc>>,
+    spec: Arc<QuestionSpec>,
+}
 
 pub struct PredictiveStrategy {
     config: PredictiveConfig,
     binance: Arc<BinanceClient>,
+    // Model gating entries (PREDICTIVE_MODEL_PATH)
+    scorer: Option<Arc<dyn SignalScorer>>,
+    // Parsed questions by market id: one evaluation reads the spec several times
+    specs: Mutex<HashMap<MarketId, CachedSpec>>,
 }
 
 impl PredictiveStrategy {
     pub fn new(config: PredictiveConfig, binance: Arc<BinanceClient>) -> Self {
-        Self { config, binance }
+        Self {
+            config,
+            binance,
+            scorer: None,
+            specs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The market's parsed question, from the cache unless the market or its question is new
+    fn spec(&self, market: &MarketData) -> Arc<QuestionSpec> {
+        let Ok(mut specs) = self.specs.lock() else {
+            return Arc::new(QuestionSpec::parse(&market.question));
+        };
+        if let Some(cached) = specs
+            .get(market.id.as_str())
+            .filter(|cached| cached.question == market.question)
+        {
+            return cached.spec.clone();
+        }
+
+        if specs.len() >= SPEC_CACHE_CAPACITY {
+            let now = Utc::now();
+            specs.retain(|_, cached| cached.end_time.map_or(true, |end| end > now));
+            if specs.len() >= SPEC_CACHE_CAPACITY {
+                specs.clear();
+            }
+        }
+        let spec = Arc::new(QuestionSpec::parse(&market.question));
+        specs.insert(
+            market.id.clone(),
+            CachedSpec {
+                question: market.question.clone(),
+                end_time: market.end_time,
+                spec: spec.clone(),
+            },
+        );
+        spec
+    }
+
+    /// Only take entries the model backs (see `model_confirms`)
+    pub fn with_scorer(mut self, scorer: Arc<dyn SignalScorer>) -> Self {
+        self.scorer = Some(scorer);
+        self
     }
 
     /// Check for opportunities using external price signals (Binance)
@@ -22,77 +84,122 @@ impl PredictiveStrategy {
     /// - Time to expiry must be within configured final window
     /// - Binance must show directional edge beyond threshold
     pub async fn check_opportunity(&self, market: &MarketData) -> TradeAction {
+        self.check_opportunity_with_inputs(market, Utc::now())
+            .await
+            .0
+    }
+
+    /// Check for an opportunity and also return the Binance price that was used (if fetched),
+    /// so the decision can be recorded and replayed later
+    pub async fn check_opportunity_with_inputs(
+        &self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+    ) -> (TradeAction, Option<f64>) {
         if !self.config.enabled {
-            return TradeAction::None;
+            return (TradeAction::None, None);
         }
 
-        // 0. Last-minute filter
-        let Some(end_date) = &market.end_date else {
-            return TradeAction::None;
+        // Cheap filters first so we only hit Binance for candidate markets
+        let Some(symbol) = self.candidate_symbol(market, now) else {
+            return (TradeAction::None, None);
         };
-        let Ok(end_dt) = DateTime::parse_from_rfc3339(end_date) else {
-            return TradeAction::None;
+
+        // 3. Get current Binance price
+        let binance_price = match self.binance.get_price(symbol).await {
+            Ok(p) => p,
+            Err(e) => {
+                debug!("Failed to fetch Binance price for {}: {}", symbol, e);
+                return (TradeAction::None, None);
+            }
         };
-        let time_remaining = (end_dt.with_timezone(&Utc) - Utc::now()).num_seconds();
+
+        (
+            self.evaluate(market, now, binance_price),
+            Some(binance_price),
+        )
+    }
+
+    /// Returns the Binance symbol if the market is a last-minute strike candidate
+    fn candidate_symbol(&self, market: &MarketData, now: DateTime<Utc>) -> Option<&'static str> {
+        // 0. Last-minute filter
+        let time_remaining = (market.end_time? - now).num_seconds();
         if time_remaining <= 0 || time_remaining > self.config.final_window_sec as i64 {
+            return None;
+        }
+
+        // 1. Must be an above / below question on a supported asset with a parseable strike,
+        // otherwise there's nothing to compare against
+        let spec = self.spec(market);
+        if !spec.is_terminal_strike() {
+            return None;
+        }
+        Some(spec.underlying?.binance_symbol)
+    }
+
+    /// Pure decision given a Binance price (no I/O), used both live and for replay
+    pub fn evaluate(
+        &self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        binance_price: f64,
+    ) -> TradeAction {
+        if !self.config.enabled || self.candidate_symbol(market, now).is_none() {
             return TradeAction::None;
         }
 
-        // 1. Identify if this is a supported Up/Down market
-        let symbol = match BinanceClient::symbol_from_question(&market.question) {
-            Some(s) => s,
-            None => return TradeAction::None,
-        };
+        let time_remaining = market
+            .end_time
+            .map(|end| (end - now).num_seconds())
+            .unwrap_or(0);
 
-        // 2. Extract strike price from question
+        // 2. Strike and direction from the question
         // Example: "Bitcoin above $65,500.00 at 5:00 PM ET?"
-        let strike_price = match self.extract_strike_price(&market.question) {
+        let spec = self.spec(market);
+        let strike_price = match spec.strike() {
             Some(p) => p,
             None => {
                 debug!("Failed to extract strike price from: {}", market.question);
                 return TradeAction::None;
             }
         };
-
-        // 3. Get current Binance price
-        let binance_price = match self.binance.get_price(symbol).await {
-            Ok(p) => p,
-            Err(e) => {
-                debug!("Failed to fetch Binance price for {}: {}", symbol, e);
-                return TradeAction::None;
-            }
+        let is_above_bet = match spec.comparator {
+            Some(Comparator::Above) => true,
+            Some(Comparator::Below) => false,
+            _ => return TradeAction::None,
         };
 
-        // 4. Compare and determine signal
-        let q = market.question.to_lowercase();
-        let is_above_bet = q.contains("above") || q.contains("over") || q.contains("greater than");
-        let is_below_bet = q.contains("below") || q.contains("under") || q.contains("less than");
-        if !is_above_bet && !is_below_bet {
-            return TradeAction::None;
-        }
-
         let price_diff_pct = ((binance_price - strike_price) / strike_price).abs() * 100.0;
         let threshold = self.config.binance_signal_threshold_pct;
         let binance_edge_up = binance_price > strike_price * (1.0 + threshold / 100.0);
         let binance_edge_down = binance_price < strike_price * (1.0 - threshold / 100.0);
 
-        let (side, entry_price) = if is_above_bet {
+        let side = if is_above_bet {
             if binance_edge_up {
-                ("YES", market.yes_price)
+                "YES"
             } else if binance_edge_down {
-                ("NO", market.no_price)
+                "NO"
             } else {
                 return TradeAction::None;
             }
         } else if binance_edge_down {
-            ("YES", market.yes_price)
+            "YES"
         } else if binance_edge_up {
-            ("NO", market.no_price)
+            "NO"
         } else {
             return TradeAction::None;
         };
 
-        if entry_price <= 0.0 || entry_price > self.config.max_entry_price {
+        let Some(entry_price) = market.ask(side) else {
+            debug!(
+                "Skip predictive entry ({}): no {} ask ({:?})",
+                market.question,
+                side,
+                market.quote_quality()
+            );
+            return TradeAction::None;
+        };
+        if entry_price > self.config.max_entry_price {
             debug!(
                 "Skip predictive entry ({}): entry price {:.4} outside allowed range (<= {:.4})",
                 market.question, entry_price, self.config.max_entry_price
@@ -112,33 +219,168 @@ impl PredictiveStrategy {
         TradeAction::Snipe {
             market_id: market.id.clone(),
             side: side.to_string(),
-            price: entry_price,
-            size_usd: 1.0,
+            price: Price::new(entry_price),
+            size_usd: UsdAmount::new(1.0),
         }
     }
 
-    fn extract_strike_price(&self, question: &str) -> Option<f64> {
-        // Regex-free simple extraction
-        // Look for '$' and then the number
-        let parts: Vec<&str> = question.split('$').collect();
-        if parts.len() < 2 {
-            // Some questions might not use '$'
-            // Example: "Bitcoin greater than 65500.00?"
-            // We'll try to find the first numeric part
-            for word in question.split_whitespace() {
-                let cleaned = word.trim_matches(|c: char| !c.is_digit(10) && c != '.' && c != ',');
-                if let Ok(p) = cleaned.replace(',', "").parse::<f64>() {
-                    // Sanity check: price must be positive
-                    if p > 0.0 {
-                        return Some(p);
-                    }
+    /// Whether the order flow of the side an entry buys backs the signal: its normalized OFI
+    /// over the shortest window must reach `min_ofi`. Always true with the check off
+    pub fn flow_confirms(&self, frame: Option<&FeatureFrame>) -> bool {
+        if self.config.min_ofi <= 0.0 {
+            return true;
+        }
+        let Some(window) = frame.and_then(|f| f.windows.first()) else {
+            debug!("Skip predictive entry: no order flow features yet");
+            return false;
+        };
+        if window.normalized_ofi < self.config.min_ofi {
+            debug!(
+                "Skip predictive entry: OFI {:.2} over {}ms below {:.2}",
+                window.normalized_ofi, window.window_ms, self.config.min_ofi
+            );
+            return false;
+        }
+        true
+    }
+
+    /// What the model sees of a market: the YES token's order flow, time left, and where the
+    /// external price sits against the strike
+    pub fn model_input(
+        &self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        binance_price: Option<f64>,
+        yes_frame: Option<FeatureFrame>,
+    ) -> ModelInput {
+        let secs_to_expiry = market
+            .end_time
+            .map(|end| (end - now).num_seconds().max(0) as f64)
+            .unwrap_or(0.0);
+        let spec = self.spec(market);
+        let is_below_bet = spec.comparator == Some(Comparator::Below);
+        let yes_log_moneyness = binance_price
+            .zip(spec.strike())
+            .filter(|(spot, strike)| *spot > 0.0 && *strike > 0.0)
+            .map(|(spot, strike)| {
+                let moneyness = (spot / strike).ln();
+                if is_below_bet {
+                    -moneyness
+                } else {
+                    moneyness
                 }
+            });
+        ModelInput {
+            yes_frame,
+            secs_to_expiry,
+            yes_log_moneyness,
+            yes_ask: market.ask("YES").unwrap_or(0.0),
+            no_ask: market.ask("NO").unwrap_or(0.0),
+        }
+    }
+
+    /// Whether the model backs buying `side` at `price`: its probability of that outcome must
+    /// reach `min_confidence` and beat the price. Always true without a model; false when
+    /// scoring fails
+    pub fn model_confirms(&self, input: &ModelInput, side: &str, price: f64) -> bool {
+        let Some(scorer) = &self.scorer else {
+            return true;
+        };
+        let p_yes = match scorer.score(&input.features()) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("⚠️ Model {} failed to score: {}", scorer.name(), e);
+                return false;
             }
+        };
+        let probability = if side == "YES" { p_yes } else { 1.0 - p_yes };
+        if probability < self.config.min_confidence || probability <= price {
+            debug!(
+                "Skip predictive entry: model gives {} {:.3} at price {:.3} (min {:.2})",
+                side, probability, price, self.config.min_confidence
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Fetch Binance and estimate the YES probability of a crypto strike market
+    /// Returns (fair YES, Binance price used); both None for non-strike markets
+    pub async fn fair_yes_with_inputs(
+        &self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        annual_volatility: f64,
+    ) -> (Option<f64>, Option<f64>) {
+        let spec = self.spec(market);
+        let Some(underlying) = spec.underlying.filter(|_| spec.is_terminal_strike()) else {
+            return (None, None);
+        };
+        let symbol = underlying.binance_symbol;
+
+        match self.binance.get_price(symbol).await {
+            Ok(price) => (
+                self.fair_yes_probability(market, now, price, annual_volatility),
+                Some(price),
+            ),
+            Err(e) => {
+                debug!("Failed to fetch Binance price for {}: {}", symbol, e);
+                (None, None)
+            }
+        }
+    }
+
+    /// Probability that a strike market resolves YES given the current spot price
+    /// Priced as a digital option under lognormal dynamics: P(S_T > K) = N(d2)
+    pub fn fair_yes_probability(
+        &self,
+        market: &MarketData,
+        now: DateTime<Utc>,
+        spot: f64,
+        annual_volatility: f64,
+    ) -> Option<f64> {
+        let spec = self.spec(market);
+        let strike = spec.strike()?;
+        let secs_remaining = (market.end_time? - now).num_seconds();
+        if secs_remaining <= 0 || spot <= 0.0 || strike <= 0.0 || annual_volatility <= 0.0 {
             return None;
         }
 
-        let price_part = parts[1].split_whitespace().next()?;
-        let cleaned = price_part.trim_matches(|c: char| !c.is_digit(10) && c != '.' && c != ',');
-        cleaned.replace(',', "").parse::<f64>().ok()
+        let is_above_bet = match spec.comparator? {
+            Comparator::Above => true,
+            Comparator::Below => false,
+            _ => return None,
+        };
+
+        let years = secs_remaining as f64 / (365.0 * 24.0 * 3600.0);
+        let sigma_t = annual_volatility * years.sqrt();
+        let d2 = ((spot / strike).ln() - 0.5 * sigma_t * sigma_t) / sigma_t;
+        let p_above = normal_cdf(d2);
+
+        Some(if is_above_bet { p_above } else { 1.0 - p_above })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_parsed_once_per_market() {
+        let strategy =
+            PredictiveStrategy::new(PredictiveConfig::default(), Arc::new(BinanceClient::new()));
+        let mut market = MarketData {
+            id: "0xbtc".into(),
+            question: "Bitcoin above $105,000 on June 13 at 5 PM ET?".to_string(),
+            ..Default::default()
+        };
+
+        let first = strategy.spec(&market);
+        assert_eq!(first.strike(), Some(105_000.0));
+        assert!(Arc::ptr_eq(&first, &strategy.spec(&market)));
+
+        // A changed question is parsed again
+        market.question = "Bitcoin above $110,000 on June 13 at 5 PM ET?".to_string();
+        assert_eq!(strategy.spec(&market).strike(), Some(110_000.0));
     }
 }
@@ -1,8 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, info, warn};
 
+use crate::analytics::attribution::strategy_of;
 use crate::config::RiskConfig;
+use crate::ids::{Price, UsdAmount};
+use crate::markets::MarketCategory;
+use crate::storage::{StorageOp, StorageWriter};
 use crate::strategies::types::TradingDecision;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,11 +17,37 @@ pub struct Position {
     pub size_usd: f64,
     pub entry_price: f64,
     pub timestamp: u64,
+    /// PnL booked by partial closes so far
+    #[serde(default)]
+    pub realized_pnl: f64,
+    /// Hash of the session config the position was opened under
+    #[serde(default)]
+    pub config_hash: Option<String>,
+}
+
+/// Bucket shared by strategies without an allocation of their own
+pub const UNALLOCATED_BUCKET: &str = "other";
+
+/// Capital and open notional of one strategy bucket
+#[derive(Debug, Clone)]
+pub struct CapitalBucket {
+    pub bucket: String,
+    pub allocation_pct: f64,
+    /// Allocation plus the bucket's realized PnL (gains compound within the bucket)
+    pub capital: f64,
+    pub exposure: f64,
 }
 
 pub struct RiskManager {
     config: RiskConfig,
     positions: HashMap<String, Position>, // market_id -> Position
+    /// Hedges, by hedge trade id: they may share a market with the position they cover, so
+    /// they count toward exposure without taking the market's slot
+    hedges: HashMap<String, Position>,
+    config_hash: Option<String>,
+    storage: Option<StorageWriter>,
+    /// Collateral not locked in resting orders (None until the first balance refresh)
+    free_collateral: Option<f64>,
 }
 
 impl RiskManager {
@@ -25,6 +55,47 @@ impl RiskManager {
         Self {
             config,
             positions: HashMap::new(),
+            hedges: HashMap::new(),
+            config_hash: None,
+            storage: None,
+            free_collateral: None,
+        }
+    }
+
+    /// Swap the limits checked from now on (scheduled parameter profiles)
+    pub fn set_config(&mut self, config: RiskConfig) {
+        self.config = config;
+    }
+
+    /// Session config hash stamped on positions added from now on
+    pub fn set_config_hash(&mut self, config_hash: String) {
+        self.config_hash = Some(config_hash);
+    }
+
+    /// Mirror every position change to the storage backend
+    pub fn set_storage(&mut self, storage: StorageWriter) {
+        self.storage = Some(storage);
+    }
+
+    /// Collateral new entries may use, from the margin view
+    pub fn set_free_collateral(&mut self, free_usd: f64) {
+        self.free_collateral = Some(free_usd);
+    }
+
+    /// Reinstate positions left open by a previous run
+    pub fn restore_positions(&mut self, positions: Vec<Position>) {
+        for position in positions {
+            self.positions.insert(position.market_id.clone(), position);
+        }
+    }
+
+    fn store(&self, market_id: &str) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        match self.positions.get(market_id) {
+            Some(position) => storage.send(StorageOp::Position(position.clone())),
+            None => storage.send(StorageOp::PositionClosed(market_id.to_string())),
         }
     }
 
@@ -47,8 +118,8 @@ impl RiskManager {
         }
 
         // 3. Check portfolio exposure
-        let current_exposure: f64 = self.positions.values().map(|p| p.size_usd).sum();
-        let max_exposure = self.config.max_portfolio_exposure_pct * 1000.0; // Assuming $1000 capital for now
+        let current_exposure: f64 = self.exposed().map(|p| p.size_usd).sum();
+        let max_exposure = self.config.max_portfolio_exposure_pct * self.config.capital_usd;
         if current_exposure + size_usd > max_exposure {
             warn!(
                 "⚠️ Risk: Exposure ${} would exceed limit ${}",
@@ -58,7 +129,18 @@ impl RiskManager {
             return false;
         }
 
-        // 4. Check confidence threshold
+        // 4. Check free collateral (wallet less resting orders)
+        if let Some(free_usd) = self.free_collateral {
+            if size_usd > free_usd {
+                warn!(
+                    "⚠️ Risk: Size ${:.2} exceeds free collateral ${:.2}",
+                    size_usd, free_usd
+                );
+                return false;
+            }
+        }
+
+        // 5. Check confidence threshold
         if confidence < 0.6 {
             warn!("⚠️ Risk: Confidence {:.2} too low (< 0.6)", confidence);
             return false;
@@ -135,9 +217,10 @@ impl RiskManager {
         market_id: String,
         trade_id: String,
         side: String,
-        size_usd: f64,
-        entry_price: f64,
+        size_usd: UsdAmount,
+        entry_price: Price,
     ) {
+        let (size_usd, entry_price) = (size_usd.get(), entry_price.get());
         let position = Position {
             market_id: market_id.clone(),
             trade_id,
@@ -148,20 +231,138 @@ impl RiskManager {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            realized_pnl: 0.0,
+            config_hash: self.config_hash.clone(),
         };
-        self.positions.insert(market_id, position);
+        self.positions.insert(market_id.clone(), position);
+        self.store(&market_id);
         info!(
             "📝 Position added: size=${:.2}, price={:.4}",
             size_usd, entry_price
         );
     }
 
+    /// Count a placed hedge toward the limits (`trade_id` is the hedge's own)
+    pub fn add_hedge(&mut self, hedge: Position) {
+        info!(
+            "📝 Hedge added: {} size=${:.2}, price={:.4}",
+            hedge.trade_id, hedge.size_usd, hedge.entry_price
+        );
+        self.hedges.insert(hedge.trade_id.clone(), hedge);
+    }
+
+    pub fn remove_hedge(&mut self, trade_id: &str) {
+        if self.hedges.remove(trade_id).is_some() {
+            info!("🗑️ Hedge removed: {}", trade_id);
+        }
+    }
+
+    pub fn get_hedges(&self) -> Vec<Position> {
+        self.hedges.values().cloned().collect()
+    }
+
+    /// Positions and hedges: everything the exposure limits count
+    fn exposed(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values().chain(self.hedges.values())
+    }
+
     pub fn remove_position(&mut self, market_id: &str) {
         if self.positions.remove(market_id).is_some() {
+            self.store(market_id);
             info!("🗑️ Position removed for market {}", market_id);
         }
     }
 
+    /// Add to an existing position at `price`; entry price becomes the weighted-average cost
+    pub fn increase_position(
+        &mut self,
+        market_id: &str,
+        size_usd: UsdAmount,
+        price: Price,
+    ) -> bool {
+        let (size_usd, price) = (size_usd.get(), price.get());
+        let Some(position) = self.positions.get_mut(market_id) else {
+            return false;
+        };
+        if size_usd <= 0.0 || price <= 0.0 {
+            return true;
+        }
+
+        // Sizes are notional: weight by shares held
+        let shares = position.size_usd / position.entry_price + size_usd / price;
+        position.size_usd += size_usd;
+        position.entry_price = position.size_usd / shares;
+        info!(
+            "📝 Position increased for {}: size=${:.2}, avg price={:.4}",
+            market_id, position.size_usd, position.entry_price
+        );
+        self.store(market_id);
+        true
+    }
+
+    /// Close `size_usd` of entry notional at `exit_price`, keeping the rest at its average cost
+    /// Returns the realized PnL of the closed part; the position is removed once nothing is left
+    pub fn reduce_position(
+        &mut self,
+        market_id: &str,
+        size_usd: UsdAmount,
+        exit_price: Price,
+    ) -> Option<f64> {
+        let (size_usd, exit_price) = (size_usd.get(), exit_price.get());
+        let position = self.positions.get_mut(market_id)?;
+        let closed_usd = size_usd.clamp(0.0, position.size_usd);
+        let realized = closed_usd * (exit_price / position.entry_price - 1.0);
+
+        position.size_usd -= closed_usd;
+        position.realized_pnl += realized;
+        info!(
+            "📝 Position reduced for {}: closed ${:.2} @ {:.4} (PnL ${:.2}), ${:.2} left",
+            market_id, closed_usd, exit_price, realized, position.size_usd
+        );
+
+        if position.size_usd < 0.01 {
+            self.remove_position(market_id);
+        } else {
+            self.store(market_id);
+        }
+        Some(realized)
+    }
+
+    /// Open notional in markets of `category` (`category_of` maps a market id to its category)
+    pub fn category_exposure(
+        &self,
+        category: MarketCategory,
+        category_of: impl Fn(&str) -> Option<MarketCategory>,
+    ) -> f64 {
+        self.exposed()
+            .filter(|p| category_of(&p.market_id) == Some(category))
+            .map(|p| p.size_usd)
+            .sum()
+    }
+
+    /// Whether `size_usd` more in a `category` market stays within its category limit
+    pub fn validate_category_exposure(
+        &self,
+        category: MarketCategory,
+        size_usd: f64,
+        category_of: impl Fn(&str) -> Option<MarketCategory>,
+    ) -> bool {
+        let Some(limit) = self.config.category_exposure_limits.get(&category) else {
+            return true;
+        };
+        let exposure = self.category_exposure(category, category_of);
+        if exposure + size_usd > *limit {
+            warn!(
+                "⚠️ Risk: {} exposure ${:.2} would exceed limit ${:.2}",
+                category.name(),
+                exposure + size_usd,
+                limit
+            );
+            return false;
+        }
+        true
+    }
+
     pub fn get_positions(&self) -> Vec<Position> {
         self.positions.values().cloned().collect()
     }
@@ -174,8 +375,7 @@ impl RiskManager {
         market_id: &str,
     ) -> Option<TradingDecision> {
         // Use validate_entry logic but map it back to decision
-        let capital = 1000.0;
-        let size_usd = capital * decision.position_size_pct;
+        let size_usd = self.config.capital_usd * decision.position_size_pct;
 
         if self.validate_entry(market_id, size_usd, decision.confidence) {
             Some(decision.clone())
@@ -184,8 +384,228 @@ impl RiskManager {
         }
     }
 
+    /// Bucket a strategy's entries are charged to: its own if it has an allocation, else the
+    /// unallocated remainder
+    pub fn capital_bucket<'a>(&self, strategy: &'a str) -> &'a str {
+        if self.config.strategy_capital_pct.contains_key(strategy) {
+            strategy
+        } else {
+            UNALLOCATED_BUCKET
+        }
+    }
+
+    fn bucket_allocation_pct(&self, bucket: &str) -> f64 {
+        match self.config.strategy_capital_pct.get(bucket) {
+            Some(pct) => *pct,
+            None => (100.0 - self.config.strategy_capital_pct.values().sum::<f64>()).max(0.0),
+        }
+    }
+
+    /// Capital buckets (empty when no strategy has an allocation). `realized_pnl` gives each
+    /// strategy's realized PnL so far, keyed by trade id prefix
+    pub fn capital_buckets(&self, realized_pnl: &BTreeMap<String, f64>) -> Vec<CapitalBucket> {
+        if self.config.strategy_capital_pct.is_empty() {
+            return Vec::new();
+        }
+        let mut buckets: BTreeMap<&str, CapitalBucket> = self
+            .config
+            .strategy_capital_pct
+            .keys()
+            .map(String::as_str)
+            .chain(std::iter::once(UNALLOCATED_BUCKET))
+            .map(|bucket| {
+                let allocation_pct = self.bucket_allocation_pct(bucket);
+                let capital = self.config.capital_usd * allocation_pct / 100.0;
+                (
+                    bucket,
+                    CapitalBucket {
+                        bucket: bucket.to_string(),
+                        allocation_pct,
+                        capital,
+                        exposure: 0.0,
+                    },
+                )
+            })
+            .collect();
+        for (strategy, pnl) in realized_pnl {
+            if let Some(bucket) = buckets.get_mut(self.capital_bucket(strategy)) {
+                bucket.capital += pnl;
+            }
+        }
+        for position in self.exposed() {
+            let strategy = strategy_of(&position.trade_id);
+            if let Some(bucket) = buckets.get_mut(self.capital_bucket(strategy)) {
+                bucket.exposure += position.size_usd;
+            }
+        }
+        buckets.into_values().collect()
+    }
+
+    /// Whether `size_usd` more for `strategy` stays within its bucket's capital
+    pub fn validate_capital_bucket(
+        &self,
+        strategy: &str,
+        size_usd: f64,
+        realized_pnl: &BTreeMap<String, f64>,
+    ) -> bool {
+        let bucket = self.capital_bucket(strategy);
+        let Some(bucket) = self
+            .capital_buckets(realized_pnl)
+            .into_iter()
+            .find(|b| b.bucket == bucket)
+        else {
+            return true;
+        };
+        if bucket.exposure + size_usd > bucket.capital {
+            warn!(
+                "⚠️ Risk: {} bucket exposure ${:.2} would exceed its capital ${:.2}",
+                bucket.bucket,
+                bucket.exposure + size_usd,
+                bucket.capital
+            );
+            return false;
+        }
+        true
+    }
+
     fn max_position_size(&self) -> f64 {
-        // Assume $1000 capital for now (TODO: Fetch real balance)
-        1000.0 * self.config.max_position_size_pct
+        self.config.capital_usd * self.config.max_position_size_pct
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RiskConfig {
+        RiskConfig {
+            max_position_size_pct: 5.0,
+            max_portfolio_exposure_pct: 50.0,
+            stop_loss_pct: 0.10,
+            use_dynamic_sl: false,
+            min_hold_time_secs: 0,
+            auto_sell_threshold: 0.99,
+            capital_usd: 1000.0,
+            category_exposure_limits: Default::default(),
+            strategy_capital_pct: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_partial_close_with_average_cost() {
+        let mut risk = RiskManager::new(config());
+        risk.add_position(
+            "m1".into(),
+            "t1".into(),
+            "YES".into(),
+            UsdAmount::new(4.0),
+            Price::new(0.40),
+        );
+        // $4 @ 0.40 (10 shares) + $6 @ 0.60 (10 shares) -> $10 for 20 shares = 0.50 avg
+        assert!(risk.increase_position("m1", UsdAmount::new(6.0), Price::new(0.60)));
+        assert!((risk.get_positions()[0].entry_price - 0.50).abs() < 1e-9);
+
+        // Half the notional (10 shares) sold at 0.55
+        let realized = risk
+            .reduce_position("m1", UsdAmount::new(5.0), Price::new(0.55))
+            .unwrap();
+        assert!((realized - 0.5).abs() < 1e-9);
+        assert!((risk.get_positions()[0].size_usd - 5.0).abs() < 1e-9);
+
+        risk.reduce_position("m1", UsdAmount::new(5.0), Price::new(0.50))
+            .unwrap();
+        assert!(risk.get_positions().is_empty());
+    }
+
+    #[test]
+    fn test_entries_need_free_collateral() {
+        let mut risk = RiskManager::new(config());
+        // No margin view yet: only the limits apply
+        assert!(risk.validate_entry("m1", 40.0, 0.9));
+        risk.set_free_collateral(30.0);
+        assert!(!risk.validate_entry("m1", 40.0, 0.9));
+        assert!(risk.validate_entry("m1", 30.0, 0.9));
+    }
+
+    #[test]
+    fn test_capital_buckets_compound_separately() {
+        let mut risk = RiskManager::new(RiskConfig {
+            strategy_capital_pct: BTreeMap::from([
+                ("arb".to_string(), 60.0),
+                ("snipe".to_string(), 30.0),
+            ]),
+            ..config()
+        });
+        risk.add_position(
+            "m1".into(),
+            "arb_m1_1".into(),
+            "YES".into(),
+            UsdAmount::new(550.0),
+            Price::new(0.5),
+        );
+        risk.add_position(
+            "m2".into(),
+            "disloc_m2_1".into(),
+            "NO".into(),
+            UsdAmount::new(90.0),
+            Price::new(0.5),
+        );
+        let mut realized = BTreeMap::new();
+
+        // $600 arbitrage bucket, $550 used
+        assert!(!risk.validate_capital_bucket("arb", 60.0, &realized));
+        // The snipe bucket is untouched by arbitrage
+        assert!(risk.validate_capital_bucket("snipe", 300.0, &realized));
+        // Unlisted strategies share the remaining 10%
+        assert_eq!(risk.capital_bucket("pred"), UNALLOCATED_BUCKET);
+        assert!(!risk.validate_capital_bucket("pred", 20.0, &realized));
+
+        // Arbitrage gains stay in the arbitrage bucket
+        realized.insert("arb".to_string(), 100.0);
+        assert!(risk.validate_capital_bucket("arb", 60.0, &realized));
+        assert!(!risk.validate_capital_bucket("pred", 20.0, &realized));
+
+        let buckets = risk.capital_buckets(&realized);
+        let other = buckets.iter().find(|b| b.bucket == "other").unwrap();
+        assert!((other.capital - 100.0).abs() < 1e-9);
+        assert!((other.exposure - 90.0).abs() < 1e-9);
+
+        // Buckets are shares of the configured bankroll
+        assert!(!risk.validate_capital_bucket("arb", 600.0, &realized));
+        risk.set_config(RiskConfig {
+            capital_usd: 2000.0,
+            ..risk.config.clone()
+        });
+        assert!(risk.validate_capital_bucket("arb", 600.0, &realized));
+    }
+
+    #[test]
+    fn test_hedges_count_toward_exposure() {
+        let mut risk = RiskManager::new(config());
+        risk.add_position(
+            "m1".into(),
+            "pred_m1_1".into(),
+            "YES".into(),
+            UsdAmount::new(80.0),
+            Price::new(0.8),
+        );
+        // Same market, other side: kept apart from the market's position
+        risk.add_hedge(Position {
+            market_id: "m1".into(),
+            trade_id: "pred_m1_1-hedge".into(),
+            side: "NO".into(),
+            size_usd: 15.0,
+            entry_price: 0.15,
+            timestamp: 0,
+            realized_pnl: 0.0,
+            config_hash: None,
+        });
+        assert_eq!(risk.get_positions().len(), 1);
+        let category_of = |_: &str| Some(MarketCategory::Crypto);
+        assert!((risk.category_exposure(MarketCategory::Crypto, category_of) - 95.0).abs() < 1e-9);
+
+        risk.remove_hedge("pred_m1_1-hedge");
+        assert!(risk.get_hedges().is_empty());
+        assert!((risk.category_exposure(MarketCategory::Crypto, category_of) - 80.0).abs() < 1e-9);
     }
 }
@@ -0,0 +1,176 @@
+//! Opt-in performance telemetry
+//!
+//! Users running a fleet of instances can point each at a collector of their own
+//! (`TELEMETRY_ENDPOINT`) and watch their health in one place. Every `TELEMETRY_INTERVAL_SECS`
+//! an instance POSTs aggregates only: latency percentiles, fill and win rates, WS ordering drops
+//! and log counts. Never keys, addresses, balances, PnL amounts or market ids - the instance is
+//! named by a random id kept in `DATA_DIR/telemetry_id`, not by anything tied to its wallet.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::analytics::indexing_latency::LatencyDistribution;
+use crate::config::TelemetryConfig;
+
+pub const TELEMETRY_ID_FILE: &str = "telemetry_id";
+
+/// Nearest-rank percentiles of a sample set
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub count: usize,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+impl Percentiles {
+    pub fn from_samples(mut samples: Vec<f64>) -> Self {
+        samples.retain(|s| s.is_finite());
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        // Nearest-rank percentile
+        let pct = |p: f64| -> f64 {
+            let rank = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+        Self {
+            count: samples.len(),
+            p50: pct(50.0),
+            p90: pct(90.0),
+            p99: pct(99.0),
+        }
+    }
+}
+
+/// What one push carries
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetrySnapshot {
+    /// Random per-install id
+    pub instance: String,
+    pub version: &'static str,
+    pub at: DateTime<Utc>,
+    pub uptime_secs: i64,
+    /// "live" / "paper" / "simulation"
+    pub mode: &'static str,
+    /// Signal -> order post, ms
+    pub signal_to_post_ms: Percentiles,
+    /// New market detected -> Gamma metadata
+    pub gamma_lag_ms: LatencyDistribution,
+    /// First book -> first tradeable quote
+    pub book_to_quote_ms: LatencyDistribution,
+    pub trades: usize,
+    pub win_rate: f64,
+    /// Settled arbitrages whose every leg filled in full
+    pub fill_rate: Option<f64>,
+    /// Share of the expected edge kept
+    pub edge_capture_ratio: Option<f64>,
+    /// Book updates dropped as out of order / duplicated
+    pub ws_reordered: u64,
+    pub ws_duplicates: u64,
+    pub errors: u64,
+    pub warnings: u64,
+    pub rss_mb: Option<f64>,
+}
+
+/// Posts snapshots to the configured collector
+pub struct TelemetryPusher {
+    config: TelemetryConfig,
+    http: reqwest::Client,
+    instance: String,
+}
+
+impl TelemetryPusher {
+    pub fn new(config: TelemetryConfig, data_dir: &str) -> Self {
+        let instance = if config.endpoint.is_some() {
+            instance_id(data_dir)
+        } else {
+            String::new()
+        };
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            config,
+            instance,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.endpoint.is_some()
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs.max(10))
+    }
+
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    /// Deliver in the background; failures are logged and the snapshot dropped
+    pub fn push(&self, snapshot: TelemetrySnapshot) {
+        let Some(endpoint) = self.config.endpoint.clone() else {
+            return;
+        };
+        let request = self.http.post(&endpoint).json(&snapshot);
+        let request = match &self.config.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        tokio::spawn(async move {
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(_) => debug!("📡 Telemetry pushed"),
+                Err(e) => warn!("⚠️ Telemetry push failed: {}", e),
+            }
+        });
+    }
+}
+
+/// Random id of this install, created on first use
+fn instance_id(data_dir: &str) -> String {
+    let path = Path::new(data_dir).join(TELEMETRY_ID_FILE);
+    if let Ok(id) = fs::read_to_string(&path) {
+        let id = id.trim();
+        if !id.is_empty() {
+            return id.to_string();
+        }
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    if let Err(e) = fs::create_dir_all(data_dir).and_then(|_| fs::write(&path, &id)) {
+        warn!(
+            "⚠️ Telemetry id not persisted ({}); a new one is used next run",
+            e
+        );
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_nearest_rank() {
+        assert_eq!(Percentiles::from_samples(vec![]), Percentiles::default());
+        let samples: Vec<f64> = (1..=100).map(f64::from).rev().collect();
+        let p = Percentiles::from_samples(samples);
+        assert_eq!(p.count, 100);
+        assert_eq!((p.p50, p.p90, p.p99), (50.0, 90.0, 99.0));
+        assert_eq!(Percentiles::from_samples(vec![f64::NAN, 3.0]).count, 1);
+    }
+
+    #[test]
+    fn test_instance_id_survives_restarts() {
+        let dir = std::env::temp_dir().join(format!("telemetry-{}", uuid::Uuid::new_v4()));
+        let dir = dir.to_string_lossy().into_owned();
+        let id = instance_id(&dir);
+        assert_eq!(instance_id(&dir), id);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
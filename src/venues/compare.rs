@@ -0,0 +1,71 @@
+use serde::Serialize;
+
+use super::Venue;
+use crate::polymarket::MarketData;
+
+/// The same question quoted on two venues
+#[derive(Debug, Clone, Serialize)]
+pub struct VenueGap {
+    pub left_venue: Venue,
+    pub left_market: String,
+    pub right_venue: Venue,
+    pub right_market: String,
+    /// YES ask on the right minus on the left
+    pub yes_gap: f64,
+    /// Cheapest complete set across venues: YES on one plus NO on the other
+    pub cross_cost: f64,
+    /// Venue whose YES goes into that set
+    pub buy_yes_on: Venue,
+    /// (1 - cross_cost) in bps, before either venue's fees. Positive = cross-venue arbitrage
+    pub edge_bps: f64,
+}
+
+/// Compare the asks of one question on two venues. None while either side is unquoted
+pub fn compare(
+    left_venue: Venue,
+    left: &MarketData,
+    right_venue: Venue,
+    right: &MarketData,
+) -> Option<VenueGap> {
+    let (left_yes, left_no) = (left.ask("YES")?, left.ask("NO")?);
+    let (right_yes, right_no) = (right.ask("YES")?, right.ask("NO")?);
+    let (cross_cost, buy_yes_on) = if left_yes + right_no <= right_yes + left_no {
+        (left_yes + right_no, left_venue)
+    } else {
+        (right_yes + left_no, right_venue)
+    };
+    Some(VenueGap {
+        left_venue,
+        left_market: left.id.to_string(),
+        right_venue,
+        right_market: right.id.to_string(),
+        yes_gap: right_yes - left_yes,
+        cross_cost,
+        buy_yes_on,
+        edge_bps: (1.0 - cross_cost) * 10_000.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(id: &str, yes_price: f64, no_price: f64) -> MarketData {
+        MarketData::quoted(id, yes_price, no_price)
+    }
+
+    #[test]
+    fn test_cheapest_cross_venue_set() {
+        let polymarket = market("0xabc", 0.40, 0.62);
+        let kalshi = market("KX-1", 0.45, 0.57);
+        let gap = compare(Venue::Polymarket, &polymarket, Venue::Kalshi, &kalshi).unwrap();
+        assert!((gap.yes_gap - 0.05).abs() < 1e-9);
+        // YES on Polymarket (0.40) + NO on Kalshi (0.57)
+        assert_eq!(gap.buy_yes_on, Venue::Polymarket);
+        assert!((gap.cross_cost - 0.97).abs() < 1e-9);
+        assert!((gap.edge_bps - 300.0).abs() < 1e-6);
+
+        let unquoted = market("KX-2", 0.0, 0.5);
+        assert!(compare(Venue::Polymarket, &polymarket, Venue::Kalshi, &unquoted).is_none());
+    }
+}
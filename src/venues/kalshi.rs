@@ -0,0 +1,529 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use polymarket_client_sdk::clob::types::OrderType;
+use reqwest::Method;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::pss::BlindedSigningKey;
+use rsa::rand_core::OsRng;
+use rsa::signature::{RandomizedSigner, SignatureEncoding};
+use rsa::RsaPrivateKey;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::info;
+
+use super::Venue;
+use crate::config::VenueConfig;
+use crate::ids::{MarketId, Price, UsdAmount};
+use crate::polymarket::{
+    MarketData, MarketInterface, OpenOrder, OrderBook, OrderLevel, OrderStatus,
+};
+
+/// Open markets fetched per page of `/markets`
+const MARKETS_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct MarketsPage {
+    markets: Vec<KalshiMarket>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketEnvelope {
+    market: KalshiMarket,
+}
+
+/// Prices are in cents, volume in contracts, liquidity in cents
+#[derive(Debug, Deserialize)]
+struct KalshiMarket {
+    ticker: String,
+    title: String,
+    #[serde(default)]
+    subtitle: Option<String>,
+    #[serde(default)]
+    close_time: Option<String>,
+    #[serde(default)]
+    yes_bid: f64,
+    #[serde(default)]
+    yes_ask: f64,
+    #[serde(default)]
+    no_ask: f64,
+    #[serde(default)]
+    volume: f64,
+    #[serde(default)]
+    volume_24h: f64,
+    #[serde(default)]
+    liquidity: f64,
+}
+
+impl KalshiMarket {
+    fn into_market_data(self) -> MarketData {
+        MarketData {
+            asset_ids: vec![asset_id(&self.ticker, "NO"), asset_id(&self.ticker, "YES")],
+            id: self.ticker.into(),
+            question: self.title,
+            end_date: self.close_time,
+            end_time: None,
+            volume: self.volume,
+            liquidity: self.liquidity / 100.0,
+            yes_price: self.yes_ask / 100.0,
+            no_price: self.no_ask / 100.0,
+            volume_24h: self.volume_24h,
+            description: self.subtitle,
+            order_book_imbalance: 0.0,
+            best_bid: self.yes_bid / 100.0,
+            best_ask: self.yes_ask / 100.0,
+            category: None,
+        }
+        .with_end_time()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderbookEnvelope {
+    orderbook: KalshiOrderbook,
+}
+
+/// Bids only, `[price cents, contracts]` ascending. A YES ask is a NO bid at 100 - price
+#[derive(Debug, Deserialize)]
+struct KalshiOrderbook {
+    #[serde(default)]
+    yes: Option<Vec<(f64, f64)>>,
+    #[serde(default)]
+    no: Option<Vec<(f64, f64)>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderEnvelope {
+    order: KalshiOrder,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrdersPage {
+    orders: Vec<KalshiOrder>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BalanceResponse {
+    /// Cents
+    balance: f64,
+}
+
+/// Prices in cents, counts in contracts
+#[derive(Debug, Deserialize)]
+struct KalshiOrder {
+    order_id: String,
+    ticker: String,
+    /// "yes" or "no"
+    side: String,
+    /// "resting", "executed", "canceled" or "pending"
+    status: String,
+    #[serde(default)]
+    yes_price: f64,
+    #[serde(default)]
+    no_price: f64,
+    #[serde(default)]
+    fill_count: f64,
+    #[serde(default)]
+    remaining_count: f64,
+    #[serde(default)]
+    created_time: Option<String>,
+}
+
+impl KalshiOrder {
+    fn order_status(&self) -> OrderStatus {
+        match self.status.as_str() {
+            "resting" if self.fill_count > 0.0 => OrderStatus::PartiallyFilled,
+            "resting" | "pending" => OrderStatus::Live,
+            "executed" => OrderStatus::Matched,
+            "canceled" => OrderStatus::Cancelled,
+            _ => OrderStatus::Unknown,
+        }
+    }
+
+    fn into_open_order(self) -> OpenOrder {
+        let side = self.side.to_uppercase();
+        let price = if side == "YES" {
+            self.yes_price
+        } else {
+            self.no_price
+        };
+        OpenOrder {
+            status: self.order_status(),
+            asset_id: asset_id(&self.ticker, &side),
+            side: "BUY".to_string(),
+            price: price / 100.0,
+            original_size: self.fill_count + self.remaining_count,
+            size_matched: self.fill_count,
+            created_at: self
+                .created_time
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.timestamp().max(0) as u64)
+                .unwrap_or_default(),
+            order_id: self.order_id,
+            market_id: self.ticker,
+        }
+    }
+}
+
+/// Asset ids follow the [NO, YES] convention as `<ticker>:<side>`
+fn asset_id(ticker: &str, side: &str) -> String {
+    format!("{}:{}", ticker, side)
+}
+
+/// Whole contracts `size_usd` buys at `price` (tolerating float error on exact multiples)
+fn contracts(size_usd: f64, price: f64) -> i64 {
+    if price <= 0.0 {
+        return 0;
+    }
+    (size_usd / price + 1e-6).floor() as i64
+}
+
+/// Book of one outcome from Kalshi's bid ladders (sizes in contracts)
+fn outcome_book(orderbook: &KalshiOrderbook, side: &str) -> OrderBook {
+    let (own, other) = if side == "YES" {
+        (&orderbook.yes, &orderbook.no)
+    } else {
+        (&orderbook.no, &orderbook.yes)
+    };
+    let bids: Vec<OrderLevel> = own
+        .iter()
+        .flatten()
+        .rev()
+        .map(|&(price, size)| OrderLevel {
+            price: price / 100.0,
+            size,
+        })
+        .collect();
+    let asks: Vec<OrderLevel> = other
+        .iter()
+        .flatten()
+        .rev()
+        .map(|&(price, size)| OrderLevel {
+            price: (100.0 - price) / 100.0,
+            size,
+        })
+        .collect();
+    OrderBook::from_levels(&bids, &asks)
+}
+
+/// Smallest and largest limit prices Kalshi accepts, in cents
+const MIN_PRICE_CENTS: i64 = 1;
+const MAX_PRICE_CENTS: i64 = 99;
+
+const READ_ONLY: &str =
+    "Kalshi adapter is read-only (set KALSHI_API_KEY_ID and KALSHI_PRIVATE_KEY_PATH to trade)";
+
+/// API key id plus the RSA key requests are signed with (RSA-PSS / SHA-256)
+struct KalshiCredentials {
+    key_id: String,
+    signing_key: BlindedSigningKey<Sha256>,
+}
+
+/// Kalshi adapter over the trade API. Markets and books are public; balance and order entry
+/// need an API key. Without one the client is read-only (paper orders still work)
+pub struct KalshiClient {
+    http: reqwest::Client,
+    base_url: String,
+    /// Path of `base_url` (e.g. /trade-api/v2), part of the signed message
+    path_prefix: String,
+    credentials: Option<KalshiCredentials>,
+    paper_trading: AtomicBool,
+}
+
+impl KalshiClient {
+    pub fn new(base_url: &str) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let path_prefix = url::Url::parse(&base_url)
+            .with_context(|| format!("Invalid Kalshi API URL: {}", base_url))?
+            .path()
+            .trim_end_matches('/')
+            .to_string();
+        Ok(Self {
+            http,
+            base_url,
+            path_prefix,
+            credentials: None,
+            paper_trading: AtomicBool::new(false),
+        })
+    }
+
+    /// Client for the configured venue, trading when a key is set (or in paper mode)
+    pub fn from_config(config: &VenueConfig, paper_trading: bool) -> Result<Self> {
+        let client = Self::new(&config.kalshi_url)?.with_paper_trading(paper_trading);
+        match (&config.kalshi_key_id, &config.kalshi_private_key_path) {
+            (Some(key_id), Some(path)) => {
+                let pem = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read Kalshi private key {}", path))?;
+                client.with_credentials(key_id, &pem)
+            }
+            _ => Ok(client),
+        }
+    }
+
+    /// Sign requests with an API key (PEM, PKCS#1 or PKCS#8)
+    pub fn with_credentials(mut self, key_id: &str, private_key_pem: &str) -> Result<Self> {
+        let key = RsaPrivateKey::from_pkcs1_pem(private_key_pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs8_pem(private_key_pem))
+            .context("Kalshi private key is not a PEM RSA key")?;
+        self.credentials = Some(KalshiCredentials {
+            key_id: key_id.to_string(),
+            signing_key: BlindedSigningKey::<Sha256>::new(key),
+        });
+        Ok(self)
+    }
+
+    /// Log orders instead of sending them
+    pub fn with_paper_trading(self, paper_trading: bool) -> Self {
+        self.paper_trading.store(paper_trading, Ordering::Relaxed);
+        self
+    }
+
+    fn is_paper(&self) -> bool {
+        self.paper_trading.load(Ordering::Relaxed)
+    }
+
+    pub fn can_trade(&self) -> bool {
+        self.is_paper() || self.credentials.is_some()
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        self.request(Method::GET, path, query, None).await
+    }
+
+    /// Signed when credentials are set: `KALSHI-ACCESS-SIGNATURE` covers timestamp (ms) +
+    /// method + full path without the query
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<serde_json::Value>,
+    ) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut request = self.http.request(method.clone(), &url).query(query);
+        if let Some(credentials) = &self.credentials {
+            let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+            let message = format!("{}{}{}{}", timestamp, method, self.path_prefix, path);
+            let signature = credentials
+                .signing_key
+                .sign_with_rng(&mut OsRng, message.as_bytes());
+            request = request
+                .header("KALSHI-ACCESS-KEY", &credentials.key_id)
+                .header("KALSHI-ACCESS-TIMESTAMP", timestamp)
+                .header(
+                    "KALSHI-ACCESS-SIGNATURE",
+                    BASE64.encode(signature.to_bytes()),
+                );
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        request
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Unexpected Kalshi response from {}", url))
+    }
+
+    fn require_credentials(&self) -> Result<()> {
+        if self.credentials.is_none() {
+            bail!(READ_ONLY);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MarketInterface for KalshiClient {
+    fn venue(&self) -> Venue {
+        Venue::Kalshi
+    }
+
+    async fn get_active_markets(&self) -> Result<Vec<MarketData>> {
+        let mut markets = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("status", "open".to_string()),
+                ("limit", MARKETS_PAGE_SIZE.to_string()),
+            ];
+            if let Some(cursor) = &cursor {
+                query.push(("cursor", cursor.clone()));
+            }
+            let page: MarketsPage = self.get("/markets", &query).await?;
+            let done = page.markets.len() < MARKETS_PAGE_SIZE;
+            markets.extend(page.markets.into_iter().map(KalshiMarket::into_market_data));
+            cursor = page.cursor.filter(|c| !c.is_empty());
+            if done || cursor.is_none() {
+                break;
+            }
+        }
+        Ok(markets)
+    }
+
+    async fn get_market_details(&self, market_id: &str) -> Result<MarketData> {
+        let envelope: MarketEnvelope = self.get(&format!("/markets/{}", market_id), &[]).await?;
+        Ok(envelope.market.into_market_data())
+    }
+
+    fn downgrade_to_paper(&self) {
+        self.paper_trading.store(true, Ordering::Relaxed);
+    }
+
+    async fn get_balance(&self) -> Result<f64> {
+        self.require_credentials()?;
+        let response: BalanceResponse = self.get("/portfolio/balance", &[]).await?;
+        Ok(response.balance / 100.0)
+    }
+
+    /// Buys `size_usd / price` contracts (whole contracts only) of a side of a ticker
+    async fn place_order(
+        &self,
+        market_id: &MarketId,
+        side: &str,
+        size_usd: UsdAmount,
+        price: Price,
+        order_type: OrderType,
+    ) -> Result<String> {
+        let (size_usd, price) = (size_usd.get(), price.get());
+        let count = contracts(size_usd, price);
+        if count < 1 {
+            bail!(
+                "Kalshi order below one contract: ${:.2} @ {:.2}",
+                size_usd,
+                price
+            );
+        }
+        if self.is_paper() {
+            info!(
+                "📝 [PAPER] Kalshi order: {} x{} @ ${:.2} on {}",
+                side, count, price, market_id
+            );
+            return Ok(format!("paper-order-{}", uuid::Uuid::new_v4()));
+        }
+        self.require_credentials()?;
+
+        let side = side.to_lowercase();
+        let cents = ((price * 100.0).round() as i64).clamp(MIN_PRICE_CENTS, MAX_PRICE_CENTS);
+        let mut body = serde_json::json!({
+            "ticker": market_id,
+            "client_order_id": uuid::Uuid::new_v4().to_string(),
+            "action": "buy",
+            "side": side,
+            "count": count,
+            "type": "limit",
+        });
+        body[format!("{}_price", side)] = cents.into();
+        if matches!(order_type, OrderType::FOK) {
+            body["time_in_force"] = "fill_or_kill".into();
+        }
+
+        info!(
+            "🚨 LIVE KALSHI ORDER: {} x{} @ {}c on {}",
+            side, count, cents, market_id
+        );
+        let response: OrderEnvelope = self
+            .request(Method::POST, "/portfolio/orders", &[], Some(body))
+            .await?;
+        if matches!(order_type, OrderType::FOK) && response.order.status == "canceled" {
+            bail!("Kalshi FOK order on {} not filled", market_id);
+        }
+        Ok(response.order.order_id)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        if self.is_paper() {
+            info!("📝 [PAPER] Cancel Kalshi order {}", order_id);
+            return Ok(());
+        }
+        self.require_credentials()?;
+        let _: serde_json::Value = self
+            .request(
+                Method::DELETE,
+                &format!("/portfolio/orders/{}", order_id),
+                &[],
+                None,
+            )
+            .await?;
+        info!("🗑️ Cancelled Kalshi order {}", order_id);
+        Ok(())
+    }
+
+    async fn get_open_orders(&self, market_id: Option<&str>) -> Result<Vec<OpenOrder>> {
+        if self.is_paper() || self.credentials.is_none() {
+            return Ok(Vec::new());
+        }
+        let mut query = vec![("status", "resting".to_string())];
+        if let Some(ticker) = market_id {
+            query.push(("ticker", ticker.to_string()));
+        }
+        let page: OrdersPage = self.get("/portfolio/orders", &query).await?;
+        Ok(page
+            .orders
+            .into_iter()
+            .map(KalshiOrder::into_open_order)
+            .collect())
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        if self.is_paper() || order_id.starts_with("paper-order-") {
+            return Ok(OrderStatus::Matched);
+        }
+        self.require_credentials()?;
+        let response: OrderEnvelope = self
+            .get(&format!("/portfolio/orders/{}", order_id), &[])
+            .await?;
+        Ok(response.order.order_status())
+    }
+
+    async fn get_orderbook(&self, asset_id: &str) -> Result<OrderBook> {
+        let (ticker, side) = asset_id
+            .rsplit_once(':')
+            .with_context(|| format!("Not a Kalshi asset id (<ticker>:<side>): {}", asset_id))?;
+        let envelope: OrderbookEnvelope = self
+            .get(&format!("/markets/{}/orderbook", ticker), &[])
+            .await?;
+        Ok(outcome_book(&envelope.orderbook, side))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_books_from_bid_ladders() {
+        let orderbook: OrderbookEnvelope = serde_json::from_str(
+            r#"{"orderbook": {"yes": [[40, 100], [42, 50]], "no": [[55, 30], [57, 10]]}}"#,
+        )
+        .unwrap();
+        let yes = outcome_book(&orderbook.orderbook, "YES");
+        assert_eq!(yes.best_bid(), Some(0.42));
+        // Best NO bid at 57 is a YES ask at 43
+        assert!((yes.best_ask().unwrap() - 0.43).abs() < 1e-9);
+
+        let no = outcome_book(&orderbook.orderbook, "NO");
+        assert_eq!(no.best_bid(), Some(0.57));
+        assert!((no.best_ask().unwrap() - 0.58).abs() < 1e-9);
+
+        let empty: OrderbookEnvelope =
+            serde_json::from_str(r#"{"orderbook": {"yes": null, "no": null}}"#).unwrap();
+        assert_eq!(outcome_book(&empty.orderbook, "YES").best_ask(), None);
+
+        // Notional of an exact number of contracts survives float error
+        assert_eq!(contracts(7.0 * 0.3, 0.3), 7);
+        assert_eq!(contracts(0.5, 0.6), 0);
+    }
+}
@@ -0,0 +1,42 @@
+//! Prediction-market venues behind `MarketInterface`
+//!
+//! Polymarket (and the simulator standing in for it) is the venue strategies trade on. Other
+//! venues plug in as adapters implementing the same trait, so their markets and books can be
+//! compared with Polymarket's (`compare`) and traded against them (cross-venue arbitrage).
+use serde::{Deserialize, Serialize};
+
+pub mod compare;
+pub mod kalshi;
+pub mod pairs;
+
+pub use compare::{compare, VenueGap};
+pub use kalshi::KalshiClient;
+pub use pairs::{load_pairs, VenuePair};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Venue {
+    Polymarket,
+    Kalshi,
+    /// Backtesting stand-in for Polymarket
+    Simulation,
+}
+
+impl Venue {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "polymarket" => Some(Venue::Polymarket),
+            "kalshi" => Some(Venue::Kalshi),
+            "simulation" | "sim" => Some(Venue::Simulation),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Venue::Polymarket => "polymarket",
+            Venue::Kalshi => "kalshi",
+            Venue::Simulation => "simulation",
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::config::VenueConfig;
+use crate::polymarket::MarketData;
+
+/// One question listed on Polymarket and on Kalshi. An entry of the mapping file:
+/// `[{"polymarket_id": "0xabc...", "kalshi_ticker": "KXFED-25DEC-H0", "inverted": false}]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VenuePair {
+    /// Polymarket condition id
+    pub polymarket_id: String,
+    pub kalshi_ticker: String,
+    /// Kalshi's YES is Polymarket's NO (the contract is worded the other way around)
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+impl VenuePair {
+    /// Kalshi market with its outcomes in Polymarket's orientation
+    pub fn align(&self, mut kalshi: MarketData) -> MarketData {
+        if self.inverted {
+            std::mem::swap(&mut kalshi.yes_price, &mut kalshi.no_price);
+            kalshi.asset_ids.reverse();
+        }
+        kalshi
+    }
+
+    /// Kalshi side to buy for an outcome in Polymarket's orientation
+    pub fn kalshi_side(&self, side: &str) -> &'static str {
+        match (side == "YES", self.inverted) {
+            (true, false) | (false, true) => "YES",
+            _ => "NO",
+        }
+    }
+}
+
+/// Pairs from the mapping file plus `KALSHI_PAIRS`; the file wins for a Polymarket market
+/// listed in both
+pub fn load_pairs(config: &VenueConfig) -> Result<Vec<VenuePair>> {
+    let mut pairs: Vec<VenuePair> = match &config.pairs_file {
+        Some(path) => {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read venue pairs {}", path))?;
+            serde_json::from_str(&raw)
+                .with_context(|| format!("Invalid venue pairs file {}", path))?
+        }
+        None => Vec::new(),
+    };
+    for (polymarket_id, kalshi_ticker) in &config.kalshi_pairs {
+        if pairs.iter().all(|p| &p.polymarket_id != polymarket_id) {
+            pairs.push(VenuePair {
+                polymarket_id: polymarket_id.clone(),
+                kalshi_ticker: kalshi_ticker.clone(),
+                inverted: false,
+            });
+        }
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_file_pairs_override_env_and_invert() {
+        let path = std::env::temp_dir().join(format!("venue-pairs-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"[{"polymarket_id": "0xabc", "kalshi_ticker": "KX-NOT", "inverted": true}]"#,
+        )
+        .unwrap();
+        let config = VenueConfig {
+            kalshi_url: String::new(),
+            kalshi_pairs: BTreeMap::from([
+                ("0xabc".to_string(), "KX-1".to_string()),
+                ("0xdef".to_string(), "KX-2".to_string()),
+            ]),
+            pairs_file: Some(path.to_string_lossy().into_owned()),
+            kalshi_key_id: None,
+            kalshi_private_key_path: None,
+        };
+        let pairs = load_pairs(&config).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].kalshi_ticker, "KX-NOT");
+        assert!(!pairs[1].inverted);
+
+        let kalshi = MarketData {
+            id: "KX-NOT".into(),
+            question: "Q?".to_string(),
+            yes_price: 0.30,
+            no_price: 0.72,
+            asset_ids: vec!["KX-NOT:NO".to_string(), "KX-NOT:YES".to_string()],
+            ..Default::default()
+        };
+        let aligned = pairs[0].align(kalshi);
+        assert_eq!((aligned.yes_price, aligned.no_price), (0.72, 0.30));
+        assert_eq!(aligned.asset_ids[1], "KX-NOT:NO");
+        assert_eq!(pairs[0].kalshi_side("YES"), "NO");
+        assert_eq!(pairs[1].kalshi_side("YES"), "YES");
+    }
+}
@@ -0,0 +1,175 @@
+//! End-to-end checks against the real Polymarket APIs: market fetch, asset id derivation, book
+//! subscription, authentication and order signing. They catch upstream API changes the unit
+//! tests can't, so they're slow, need the network and only build with the `live-tests`
+//! feature:
+//!
+//! ```text
+//! cargo test --features live-tests --test live -- --test-threads=1
+//! ```
+//!
+//! Settings come from the environment (and `.env`) as for the agent, so `POLYMARKET_HOST`
+//! points the CLOB calls at a testing endpoint. Authentication and signing need
+//! `POLYGON_PRIVATE_KEY` and are skipped without it. No order is ever posted.
+#![cfg(feature = "live-tests")]
+
+use std::future::Future;
+use std::time::Duration;
+
+use polymarket_client_sdk::clob::types::{OrderType, Side};
+use polymarket_hft_agent::markets::ConditionId;
+use polymarket_hft_agent::polymarket::{
+    update_queue, ClobWebSocket, DerivedAssetCache, DropPolicy, PolymarketClient, TickThrottle,
+};
+use polymarket_hft_agent::{Config, MarketData, MarketInterface};
+
+/// Upstreams are slow at times and rate limit bursts: only a call this late is a failure
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Markets sampled per test
+const SAMPLE: usize = 5;
+
+fn config() -> Config {
+    Config::from_env().expect("config from the environment")
+}
+
+/// Paper mode, whatever the environment says: nothing can be posted
+fn client(config: &Config) -> PolymarketClient {
+    PolymarketClient::new(&config.polymarket, true, config.polygon_private_key.clone())
+        .expect("Polymarket client")
+}
+
+async fn within<T>(what: &str, call: impl Future<Output = T>) -> T {
+    tokio::time::timeout(TIMEOUT, call)
+        .await
+        .unwrap_or_else(|_| panic!("{} took over {:?}", what, TIMEOUT))
+}
+
+/// A few active binary markets from the CLOB
+async fn sample_markets(client: &PolymarketClient) -> Vec<MarketData> {
+    let markets = within("market fetch", client.get_active_markets())
+        .await
+        .expect("active markets");
+    let sample: Vec<MarketData> = markets
+        .into_iter()
+        .filter(|m| !m.id.is_empty() && m.asset_ids.len() == 2)
+        .take(SAMPLE)
+        .collect();
+    assert!(!sample.is_empty(), "no binary market among the active ones");
+    sample
+}
+
+fn sorted(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort();
+    ids
+}
+
+/// Keys are only needed by the authenticated tests
+fn has_private_key(config: &Config) -> bool {
+    if config.polygon_private_key.is_none() {
+        eprintln!("POLYGON_PRIVATE_KEY not set, skipping");
+        return false;
+    }
+    true
+}
+
+#[tokio::test]
+async fn test_market_fetch() {
+    let config = config();
+    let client = client(&config);
+    for market in sample_markets(&client).await {
+        let details = within("Gamma market", client.get_market_details(&market.id))
+            .await
+            .expect("market details");
+        assert_eq!(details.id, market.id);
+        assert!(
+            !details.question.starts_with("Market "),
+            "{} missing from Gamma",
+            market.id
+        );
+        assert_eq!(
+            sorted(details.asset_ids),
+            sorted(market.asset_ids),
+            "Gamma and the CLOB disagree on the token ids of {}",
+            market.id
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_derived_ids_match_the_clob() {
+    let config = config();
+    let derived_assets =
+        DerivedAssetCache::new(config.polymarket.default_collateral()).expect("asset id deriver");
+    let client = client(&config).with_derived_assets(derived_assets.clone());
+    for market in sample_markets(&client).await {
+        // Gamma's neg-risk flag picks the collateral the ids derive from
+        within("Gamma market", client.get_market_details(&market.id))
+            .await
+            .expect("market details");
+        let condition = ConditionId::parse(&market.id).expect("condition id");
+        let (yes, no) = derived_assets.get_or_derive(&condition);
+        assert_eq!(
+            sorted(vec![yes, no]),
+            sorted(market.asset_ids),
+            "derived token ids of {} ({:?})",
+            market.id,
+            derived_assets.collateral_of(&condition)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_book_subscription() {
+    let config = config();
+    let client = client(&config);
+    let asset_ids: Vec<String> = sample_markets(&client)
+        .await
+        .into_iter()
+        .flat_map(|m| m.asset_ids)
+        .collect();
+
+    let (tx, mut rx) = update_queue(1024, DropPolicy::Coalesce);
+    let ws = within(
+        "CLOB WS connect",
+        ClobWebSocket::new(tx, 0, TickThrottle::new(0, 0), None),
+    )
+    .await
+    .expect("CLOB WebSocket");
+    ws.subscribe(asset_ids.clone());
+
+    // The subscription answers with a snapshot of each book
+    let update = within("first book", rx.recv()).await.expect("book update");
+    assert!(
+        asset_ids.contains(&update.asset_id),
+        "book for unsubscribed asset {}",
+        update.asset_id
+    );
+}
+
+#[tokio::test]
+async fn test_authentication() {
+    let config = config();
+    if !has_private_key(&config) {
+        return;
+    }
+    within("CLOB authentication", client(&config).authenticate())
+        .await
+        .expect("CLOB authentication");
+}
+
+#[tokio::test]
+async fn test_order_signing() {
+    let config = config();
+    if !has_private_key(&config) {
+        return;
+    }
+    let client = client(&config);
+    let market = sample_markets(&client).await.remove(0);
+    // Built and signed like a live order, then dropped
+    within(
+        "order signing",
+        client.sign_order(&market.asset_ids[1], Side::Buy, 5.0, 0.01, OrderType::GTC),
+    )
+    .await
+    .expect("signed order");
+}